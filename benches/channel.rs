@@ -0,0 +1,76 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+use futuresdr::futures::channel::mpsc;
+use futuresdr::futures::executor::block_on;
+use futuresdr::futures::Sink;
+use futuresdr::futures::SinkExt;
+use futuresdr::futures::Stream;
+use futuresdr::futures::StreamExt;
+use futuresdr::runtime::channel;
+use std::fmt::Debug;
+use std::thread;
+
+/// `n_senders` threads send `n_msg` messages in total, which are received on this thread.
+fn transfer<S, R>(tx: S, mut rx: R, n_senders: usize, n_msg: u64)
+where
+    S: Sink<u64> + Clone + Send + Unpin + 'static,
+    S::Error: Debug,
+    R: Stream<Item = u64> + Unpin,
+{
+    let per_sender = n_msg / n_senders as u64;
+    let handles: Vec<_> = (0..n_senders)
+        .map(|_| {
+            let mut tx = tx.clone();
+            thread::spawn(move || {
+                block_on(async {
+                    for i in 0..per_sender {
+                        tx.send(i).await.unwrap();
+                    }
+                })
+            })
+        })
+        .collect();
+    drop(tx);
+
+    let received = block_on(async {
+        let mut n = 0;
+        while rx.next().await.is_some() {
+            n += 1;
+        }
+        n
+    });
+    assert_eq!(received, per_sender * n_senders as u64);
+
+    for h in handles {
+        h.join().unwrap();
+    }
+}
+
+pub fn channel(c: &mut Criterion) {
+    let mut group = c.benchmark_group("channel");
+    group.sample_size(10);
+
+    let n_msg = 100_000;
+    group.throughput(Throughput::Elements(n_msg));
+    for capacity in [64, 8192] {
+        for n_senders in [1, 4] {
+            group.bench_function(format!("futuresdr-{capacity}-{n_senders}"), |b| {
+                b.iter(|| {
+                    let (tx, rx) = channel::channel::<u64>(capacity);
+                    transfer(tx, rx, n_senders, n_msg);
+                });
+            });
+            group.bench_function(format!("mpsc-{capacity}-{n_senders}"), |b| {
+                b.iter(|| {
+                    let (tx, rx) = mpsc::channel::<u64>(capacity);
+                    transfer(tx, rx, n_senders, n_msg);
+                });
+            });
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, channel);
+criterion_main!(benches);
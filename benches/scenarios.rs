@@ -0,0 +1,307 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+
+use futuresdr::anyhow::Result;
+use futuresdr::async_trait::async_trait;
+use futuresdr::blocks::Copy;
+use futuresdr::blocks::FirBuilder;
+use futuresdr::blocks::Head;
+use futuresdr::blocks::MessageBurst;
+use futuresdr::blocks::MessageCopy;
+use futuresdr::blocks::MessageSink;
+use futuresdr::blocks::NullSink;
+use futuresdr::blocks::NullSource;
+use futuresdr::macros::message_handler;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::BlockMeta;
+use futuresdr::runtime::BlockMetaBuilder;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Kernel;
+use futuresdr::runtime::MessageIo;
+use futuresdr::runtime::MessageIoBuilder;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+use futuresdr::runtime::StreamIo;
+use futuresdr::runtime::StreamIoBuilder;
+use futuresdr::runtime::WorkIo;
+
+/// Null source -> head -> copy chain -> null sink.
+fn copy_chain(n_samp: u64, n_copy: usize) -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(NullSource::<f32>::new());
+    let head = fg.add_block(Head::<f32>::new(n_samp));
+    fg.connect_stream(src, "out", head, "in")?;
+
+    let mut last = head;
+    for _ in 0..n_copy {
+        let copy = fg.add_block(Copy::<f32>::new());
+        fg.connect_stream(last, "out", copy, "in")?;
+        last = copy;
+    }
+
+    let snk = fg.add_block(NullSink::<f32>::new());
+    fg.connect_stream(last, "out", snk, "in")?;
+
+    Runtime::new().run(fg)?;
+    Ok(())
+}
+
+/// Null source -> head -> FIR chain -> null sink.
+fn fir_chain(n_samp: u64, n_fir: usize, n_taps: usize) -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(NullSource::<Complex32>::new());
+    let head = fg.add_block(Head::<Complex32>::new(n_samp));
+    fg.connect_stream(src, "out", head, "in")?;
+
+    let taps = vec![1.0 / n_taps as f32; n_taps];
+    let mut last = head;
+    for _ in 0..n_fir {
+        let fir = fg.add_block(FirBuilder::new::<Complex32, Complex32, f32, _>(
+            taps.clone(),
+        ));
+        fg.connect_stream(last, "out", fir, "in")?;
+        last = fir;
+    }
+
+    let snk = fg.add_block(NullSink::<Complex32>::new());
+    fg.connect_stream(last, "out", snk, "in")?;
+
+    Runtime::new().run(fg)?;
+    Ok(())
+}
+
+/// Message burst -> message copy chain -> message sink.
+fn message_chain(n_msg: u64, n_copy: usize) -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(MessageBurst::new(Pmt::U64(0), n_msg));
+    let mut last = src;
+    for _ in 0..n_copy {
+        let copy = fg.add_block(MessageCopy::new());
+        fg.connect_message(last, "out", copy, "in")?;
+        last = copy;
+    }
+    let snk = fg.add_block(MessageSink::new());
+    fg.connect_message(last, "out", snk, "in")?;
+
+    Runtime::new().run(fg)?;
+    Ok(())
+}
+
+/// Sends a message and waits for the reply, until `n` round trips completed.
+struct Ping {
+    n: u64,
+    received: u64,
+}
+
+impl Ping {
+    fn build(n: u64) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("Ping").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_input("in", Self::pong)
+                .add_output("out")
+                .build(),
+            Ping { n, received: 0 },
+        )
+    }
+
+    #[message_handler]
+    async fn pong(
+        &mut self,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        self.received += 1;
+        if self.received < self.n {
+            mio.post(0, p).await;
+        }
+        Ok(Pmt::Null)
+    }
+}
+
+#[async_trait]
+impl Kernel for Ping {
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        mio.post(0, Pmt::U64(0)).await;
+        Ok(())
+    }
+
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        if self.received >= self.n {
+            io.finished = true;
+        }
+        Ok(())
+    }
+}
+
+/// Returns each message to the sender.
+struct Pong;
+
+impl Pong {
+    fn build() -> Block {
+        Block::new(
+            BlockMetaBuilder::new("Pong").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_input("in", Self::ping)
+                .add_output("out")
+                .build(),
+            Pong,
+        )
+    }
+
+    #[message_handler]
+    async fn ping(
+        &mut self,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        mio.post(0, p).await;
+        Ok(Pmt::Null)
+    }
+}
+
+impl Kernel for Pong {}
+
+/// Message round trips between two blocks.
+fn ping_pong(n: u64) -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let ping = fg.add_block(Ping::build(n));
+    let pong = fg.add_block(Pong::build());
+    fg.connect_message(ping, "out", pong, "in")?;
+    fg.connect_message(pong, "out", ping, "in")?;
+
+    Runtime::new().run(fg)?;
+    Ok(())
+}
+
+/// Simulated multi-channel device, producing zeros on all channels in lockstep, like a
+/// multi-channel SDR source.
+struct Device {
+    n_channels: usize,
+    remaining: usize,
+}
+
+impl Device {
+    fn build(n_channels: usize, n_samp: usize) -> Block {
+        let mut sio = StreamIoBuilder::new();
+        for c in 0..n_channels {
+            sio = sio.add_output::<Complex32>(&format!("out{c}"));
+        }
+        Block::new(
+            BlockMetaBuilder::new("Device").build(),
+            sio.build(),
+            MessageIoBuilder::new().build(),
+            Device {
+                n_channels,
+                remaining: n_samp,
+            },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for Device {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let n = (0..self.n_channels)
+            .map(|c| sio.output(c).slice::<Complex32>().len())
+            .min()
+            .unwrap_or(0)
+            .min(self.remaining);
+
+        for c in 0..self.n_channels {
+            sio.output(c).slice::<Complex32>()[0..n].fill(Complex32::new(0.0, 0.0));
+            sio.output(c).produce(n);
+        }
+
+        self.remaining -= n;
+        if self.remaining == 0 {
+            io.finished = true;
+        }
+        Ok(())
+    }
+}
+
+/// Multi-channel device -> per-channel copy -> null sinks.
+fn fan_out(n_samp: usize, n_channels: usize) -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let dev = fg.add_block(Device::build(n_channels, n_samp));
+    for c in 0..n_channels {
+        let copy = fg.add_block(Copy::<Complex32>::new());
+        let snk = fg.add_block(NullSink::<Complex32>::new());
+        fg.connect_stream(dev, format!("out{c}").as_str(), copy, "in")?;
+        fg.connect_stream(copy, "out", snk, "in")?;
+    }
+
+    Runtime::new().run(fg)?;
+    Ok(())
+}
+
+pub fn scenarios(c: &mut Criterion) {
+    let mut group = c.benchmark_group("scenarios");
+    group.sample_size(10);
+
+    let n_samp = 10_000_000;
+    group.throughput(Throughput::Elements(n_samp));
+    for n_copy in [1, 10] {
+        group.bench_function(format!("copy-chain-{n_copy}"), |b| {
+            b.iter(|| copy_chain(black_box(n_samp), n_copy).unwrap());
+        });
+    }
+
+    let n_samp = 1_000_000;
+    group.throughput(Throughput::Elements(n_samp));
+    group.bench_function("fir-chain-4x64", |b| {
+        b.iter(|| fir_chain(black_box(n_samp), 4, 64).unwrap());
+    });
+
+    let n_msg = 100_000;
+    group.throughput(Throughput::Elements(n_msg));
+    group.bench_function("message-chain-4", |b| {
+        b.iter(|| message_chain(black_box(n_msg), 4).unwrap());
+    });
+
+    let n_msg = 10_000;
+    group.throughput(Throughput::Elements(n_msg));
+    group.bench_function("message-ping-pong", |b| {
+        b.iter(|| ping_pong(black_box(n_msg)).unwrap());
+    });
+
+    let n_samp = 1_000_000;
+    for n_channels in [2, 4] {
+        group.throughput(Throughput::Elements((n_samp * n_channels) as u64));
+        group.bench_function(format!("device-fan-out-{n_channels}"), |b| {
+            b.iter(|| fan_out(black_box(n_samp), n_channels).unwrap());
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, scenarios);
+criterion_main!(benches);
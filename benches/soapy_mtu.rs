@@ -0,0 +1,116 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::soapy::MockSoapyDevice;
+use futuresdr::blocks::soapy::SoapyDevSpec;
+use futuresdr::blocks::soapy::SoapyDirection;
+use futuresdr::blocks::Head;
+use futuresdr::blocks::MockSoapySinkBuilder;
+use futuresdr::blocks::MockSoapySourceBuilder;
+use futuresdr::blocks::NullSink;
+use futuresdr::blocks::NullSource;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::buffer::circular::Circular;
+use futuresdr::runtime::buffer::slab::Slab;
+use futuresdr::runtime::buffer::BufferBuilder;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+/// MTU of the mock device in samples, e.g., the MTU of a USRP B210 over USB 3.
+const MTU: usize = 2040;
+
+/// Buffer size of 64 MTUs, which is also a multiple of the page size.
+const ALIGNED: usize = 64 * MTU * 8;
+
+/// Buffer size of 1 MiB, i.e., 64.25 MTUs.
+const UNALIGNED: usize = 1 << 20;
+
+/// Mock source -> head -> null sink, returning the number of samples per driver call.
+fn rx<B: BufferBuilder + Debug + Eq + Hash>(n_samp: u64, buffer: B) -> Result<Vec<usize>> {
+    let mut fg = Flowgraph::new();
+    let dev = MockSoapyDevice::new(1).with_mtu(MTU);
+
+    let src = fg.add_block(
+        MockSoapySourceBuilder::new()
+            .device(SoapyDevSpec::Mock(dev.clone()))
+            .build(),
+    );
+    let head = fg.add_block(Head::<Complex32>::new(n_samp));
+    let snk = fg.add_block(NullSink::<Complex32>::new());
+    fg.connect_stream_with_type(src, "out", head, "in", buffer)?;
+    fg.connect_stream(head, "out", snk, "in")?;
+
+    Runtime::new().run(fg)?;
+    Ok(dev.stream_calls(&SoapyDirection::Rx))
+}
+
+/// Null source -> head -> mock sink, returning the number of samples per driver call.
+fn tx<B: BufferBuilder + Debug + Eq + Hash>(n_samp: u64, buffer: B) -> Result<Vec<usize>> {
+    let mut fg = Flowgraph::new();
+    let dev = MockSoapyDevice::new(1).with_mtu(MTU);
+
+    let src = fg.add_block(NullSource::<Complex32>::new());
+    let head = fg.add_block(Head::<Complex32>::new(n_samp));
+    let snk = fg.add_block(
+        MockSoapySinkBuilder::new()
+            .device(SoapyDevSpec::Mock(dev.clone()))
+            .build(),
+    );
+    fg.connect_stream(src, "out", head, "in")?;
+    fg.connect_stream_with_type(head, "out", snk, "in", buffer)?;
+
+    Runtime::new().run(fg)?;
+    Ok(dev.stream_calls(&SoapyDirection::Tx))
+}
+
+/// Print the number of driver calls and how many of them transferred partial MTUs.
+fn report(name: &str, calls: &[usize]) {
+    let partial = calls.iter().filter(|n| *n % MTU != 0).count();
+    println!(
+        "{name}: {} driver calls, {partial} with partial MTUs",
+        calls.len()
+    );
+}
+
+pub fn soapy(c: &mut Criterion) {
+    let mut group = c.benchmark_group("soapy");
+    group.sample_size(10);
+
+    let n_samp = 20_000_000;
+    group.throughput(Throughput::Elements(n_samp));
+    for (name, size) in [("aligned", ALIGNED), ("unaligned", UNALIGNED)] {
+        let circular = || Circular::with_size(size);
+        let slab = || Slab::with_config(size, 4, 0);
+
+        report(
+            &format!("rx-circular-{name}"),
+            &rx(n_samp, circular()).unwrap(),
+        );
+        group.bench_function(format!("rx-circular-{name}"), |b| {
+            b.iter(|| rx(black_box(n_samp), circular()).unwrap());
+        });
+        report(&format!("rx-slab-{name}"), &rx(n_samp, slab()).unwrap());
+        group.bench_function(format!("rx-slab-{name}"), |b| {
+            b.iter(|| rx(black_box(n_samp), slab()).unwrap());
+        });
+
+        report(
+            &format!("tx-circular-{name}"),
+            &tx(n_samp, circular()).unwrap(),
+        );
+        group.bench_function(format!("tx-circular-{name}"), |b| {
+            b.iter(|| tx(black_box(n_samp), circular()).unwrap());
+        });
+        report(&format!("tx-slab-{name}"), &tx(n_samp, slab()).unwrap());
+        group.bench_function(format!("tx-slab-{name}"), |b| {
+            b.iter(|| tx(black_box(n_samp), slab()).unwrap());
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, soapy);
+criterion_main!(benches);
@@ -28,6 +28,20 @@ fn gen_lttng_tracepoints() {
     std::fs::write(output_file_name, bindings).expect("writing back bindings failed");
 }
 
+/// Link a native library, statically if the given environment variable is set.
+#[allow(dead_code)]
+fn link_native(lib: &str, static_env: &str, static_deps: &[&str]) {
+    println!("cargo:rerun-if-env-changed={static_env}");
+    if std::env::var_os(static_env).is_some() {
+        println!("cargo:rustc-link-lib=static={lib}");
+        for dep in static_deps {
+            println!("cargo:rustc-link-lib={dep}");
+        }
+    } else {
+        println!("cargo:rustc-link-lib={lib}");
+    }
+}
+
 fn main() {
     match version_meta().unwrap().channel {
         Channel::Stable => {
@@ -46,4 +60,19 @@ fn main() {
 
     #[cfg(feature = "lttng")]
     gen_lttng_tracepoints();
+
+    #[cfg(feature = "digital_rf")]
+    link_native("hdf5", "HDF5_STATIC", &["z", "dl", "m"]);
+
+    #[cfg(feature = "hackrf")]
+    link_native("hackrf", "HACKRF_STATIC", &["usb-1.0"]);
+
+    #[cfg(feature = "pluto")]
+    link_native("iio", "IIO_STATIC", &["xml2", "usb-1.0"]);
+
+    // in the browser, the RTL-SDR is accessed through WebUSB
+    #[cfg(feature = "rtlsdr")]
+    if std::env::var("CARGO_CFG_TARGET_ARCH").as_deref() != Ok("wasm32") {
+        link_native("rtlsdr", "RTLSDR_STATIC", &["usb-1.0"]);
+    }
 }
@@ -0,0 +1,48 @@
+// Run a flowgraph from a YAML file, call a message handler, and print runtime events.
+//
+// cargo build --release -p futuresdr-capi
+// cc examples/embed.c -Iinclude -L../target/release -lfuturesdr_c -o embed
+// LD_LIBRARY_PATH=../target/release ./embed examples/flowgraph.yaml
+#include <stdio.h>
+#include <unistd.h>
+
+#include "futuresdr.h"
+
+static void on_event(const char *event, void *user_data) {
+    (void)user_data;
+    printf("event: %s\n", event);
+}
+
+int main(int argc, char **argv) {
+    if (argc != 2) {
+        fprintf(stderr, "usage: %s <flowgraph.yaml>\n", argv[0]);
+        return 1;
+    }
+
+    FsdrFlowgraph *fg = fsdr_flowgraph_from_yaml_file(argv[1]);
+    if (!fg) {
+        fprintf(stderr, "error: %s\n", fsdr_last_error());
+        return 1;
+    }
+
+    if (fsdr_flowgraph_start(fg) || fsdr_flowgraph_subscribe(fg, on_event, NULL)) {
+        fprintf(stderr, "error: %s\n", fsdr_last_error());
+        fsdr_flowgraph_free(fg);
+        return 1;
+    }
+
+    sleep(1);
+    char *ret = fsdr_flowgraph_callback(fg, "msg_snk", "in", "\"Null\"");
+    if (ret) {
+        printf("received messages: %s\n", ret);
+        fsdr_string_free(ret);
+    } else {
+        fprintf(stderr, "error: %s\n", fsdr_last_error());
+    }
+
+    if (fsdr_flowgraph_stop(fg)) {
+        fprintf(stderr, "error: %s\n", fsdr_last_error());
+    }
+    fsdr_flowgraph_free(fg);
+    return 0;
+}
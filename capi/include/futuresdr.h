@@ -0,0 +1,86 @@
+#ifndef FUTURESDR_H
+#define FUTURESDR_H
+
+/* Generated with cbindgen from futuresdr-capi. Do not edit manually. */
+
+#include <stdint.h>
+
+// Flowgraph, created from a YAML description.
+typedef struct FsdrFlowgraph FsdrFlowgraph;
+
+// Event callback, called with a JSON-encoded event and the user data.
+typedef void (*FsdrEventCallback)(const char *event, void *user_data);
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Create a flowgraph from a YAML description.
+//
+// Returns null on error.
+struct FsdrFlowgraph *fsdr_flowgraph_from_yaml(const char *yaml);
+
+// Create a flowgraph from a YAML file.
+//
+// Returns null on error.
+struct FsdrFlowgraph *fsdr_flowgraph_from_yaml_file(const char *path);
+
+// Start the flowgraph in the background.
+int32_t fsdr_flowgraph_start(struct FsdrFlowgraph *fg);
+
+// Wait for the flowgraph to terminate.
+//
+// Returns an error, if the flowgraph terminated with an error.
+int32_t fsdr_flowgraph_wait(struct FsdrFlowgraph *fg);
+
+// Stop the flowgraph and wait for it to terminate.
+int32_t fsdr_flowgraph_stop(struct FsdrFlowgraph *fg);
+
+// Get the id of a block, given its instance name.
+//
+// Returns -1, if there is no block with this name.
+int64_t fsdr_flowgraph_block_id(struct FsdrFlowgraph *fg, const char *block);
+
+// Call a message handler of a running flowgraph with a JSON-encoded Pmt, e.g., `{"F64": 1e6}`
+// or `"Null"`.
+int32_t fsdr_flowgraph_call(struct FsdrFlowgraph *fg,
+                            const char *block,
+                            const char *port,
+                            const char *pmt);
+
+// Call a message handler of a running flowgraph with a JSON-encoded Pmt and return its
+// JSON-encoded result.
+//
+// The returned string has to be freed with [fsdr_string_free]. Returns null on error.
+char *fsdr_flowgraph_callback(struct FsdrFlowgraph *fg,
+                              const char *block,
+                              const char *port,
+                              const char *pmt);
+
+// Receive runtime events of a running flowgraph, like block state changes and its termination.
+//
+// The callback is called from a background thread with the JSON-encoded event and the user
+// data, until the flowgraph terminates. The event string is only valid during the callback.
+int32_t fsdr_flowgraph_subscribe(struct FsdrFlowgraph *fg,
+                                 FsdrEventCallback callback,
+                                 void *user_data);
+
+// Free a flowgraph, stopping it, if it is running.
+void fsdr_flowgraph_free(struct FsdrFlowgraph *fg);
+
+// Free a string, returned by the library.
+void fsdr_string_free(char *s);
+
+// Message of the last error of the calling thread, or null.
+//
+// The string is owned by the library and valid until the next call that fails.
+const char *fsdr_last_error(void);
+
+// Version of the library.
+const char *fsdr_version(void);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* FUTURESDR_H */
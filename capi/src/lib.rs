@@ -0,0 +1,327 @@
+//! C API to embed FutureSDR flowgraphs into C/C++ applications.
+//!
+//! Flowgraphs are described in YAML (see [yaml]), started and stopped through an opaque
+//! `FsdrFlowgraph` handle, controlled by calling message handlers with JSON-encoded [Pmt]s, and
+//! observed with a callback that receives runtime events as JSON.
+//!
+//! Functions return `0` on success and `-1` on error, or a null pointer on error, if they return
+//! a pointer. The error message is available through [fsdr_last_error]. Strings returned by the
+//! library are owned by the caller and have to be freed with [fsdr_string_free].
+//!
+//! The header `include/futuresdr.h` is generated with `cbindgen`:
+//! ```sh
+//! cbindgen --config cbindgen.toml --output include/futuresdr.h
+//! ```
+#![allow(clippy::missing_safety_doc)]
+use futuresdr::anyhow::{anyhow, bail, Context, Result};
+use futuresdr::async_io::block_on;
+use futuresdr::futures::future::BoxFuture;
+use futuresdr::futures::FutureExt;
+use futuresdr::futures::StreamExt;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::FlowgraphHandle;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::{c_void, CStr, CString};
+use std::os::raw::c_char;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+use std::thread::JoinHandle;
+
+pub mod yaml;
+use yaml::Registry;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_error(e: String) {
+    let e = CString::new(e.replace('\0', " ")).unwrap();
+    LAST_ERROR.with(|l| *l.borrow_mut() = Some(e));
+}
+
+/// Run `f`, catching panics and storing errors for [fsdr_last_error].
+fn ffi<T>(default: T, f: impl FnOnce() -> Result<T>) -> T {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(v)) => v,
+        Ok(Err(e)) => {
+            set_error(format!("{:#}", e));
+            default
+        }
+        Err(_) => {
+            set_error("panic in FutureSDR".to_string());
+            default
+        }
+    }
+}
+
+unsafe fn str_arg<'a>(s: *const c_char, name: &str) -> Result<&'a str> {
+    if s.is_null() {
+        bail!("{} is null", name);
+    }
+    CStr::from_ptr(s)
+        .to_str()
+        .with_context(|| format!("{} is not valid UTF-8", name))
+}
+
+unsafe fn fg_arg<'a>(fg: *mut FsdrFlowgraph) -> Result<&'a mut FsdrFlowgraph> {
+    fg.as_mut().ok_or_else(|| anyhow!("flowgraph is null"))
+}
+
+enum State {
+    Built(Flowgraph),
+    Running {
+        task: BoxFuture<'static, Result<Flowgraph>>,
+        handle: FlowgraphHandle,
+    },
+    Terminated,
+}
+
+/// Event callback, called with a JSON-encoded event and the user data.
+pub type FsdrEventCallback = Option<extern "C" fn(event: *const c_char, user_data: *mut c_void)>;
+
+struct UserData(*mut c_void);
+// The caller guarantees that the user data can be used from the event thread.
+unsafe impl Send for UserData {}
+
+/// Flowgraph, created from a YAML description.
+pub struct FsdrFlowgraph {
+    runtime: Runtime<futuresdr::runtime::scheduler::SmolScheduler>,
+    state: State,
+    ids: HashMap<String, usize>,
+    subscribers: Vec<JoinHandle<()>>,
+}
+
+impl FsdrFlowgraph {
+    fn handle(&mut self) -> Result<&mut FlowgraphHandle> {
+        match &mut self.state {
+            State::Running { handle, .. } => Ok(handle),
+            _ => bail!("flowgraph is not running"),
+        }
+    }
+
+    fn block_id(&self, name: &str) -> Result<usize> {
+        self.ids
+            .get(name)
+            .copied()
+            .ok_or_else(|| anyhow!("no block named {}", name))
+    }
+
+    fn wait(&mut self) -> Result<()> {
+        let ret = match std::mem::replace(&mut self.state, State::Terminated) {
+            State::Running { task, .. } => block_on(task).map(|_| ()),
+            State::Built(_) => Ok(()),
+            State::Terminated => Ok(()),
+        };
+        for s in self.subscribers.drain(..) {
+            let _ = s.join();
+        }
+        ret
+    }
+}
+
+impl Drop for FsdrFlowgraph {
+    fn drop(&mut self) {
+        if let Ok(h) = self.handle() {
+            let _ = block_on(h.terminate());
+        }
+        let _ = self.wait();
+    }
+}
+
+fn flowgraph_from_yaml(yaml: &str) -> Result<*mut FsdrFlowgraph> {
+    let (fg, ids) = Registry::default().build(yaml)?;
+    Ok(Box::into_raw(Box::new(FsdrFlowgraph {
+        runtime: Runtime::new(),
+        state: State::Built(fg),
+        ids,
+        subscribers: Vec::new(),
+    })))
+}
+
+/// Create a flowgraph from a YAML description.
+///
+/// Returns null on error.
+#[no_mangle]
+pub unsafe extern "C" fn fsdr_flowgraph_from_yaml(yaml: *const c_char) -> *mut FsdrFlowgraph {
+    ffi(ptr::null_mut(), || {
+        flowgraph_from_yaml(str_arg(yaml, "yaml")?)
+    })
+}
+
+/// Create a flowgraph from a YAML file.
+///
+/// Returns null on error.
+#[no_mangle]
+pub unsafe extern "C" fn fsdr_flowgraph_from_yaml_file(path: *const c_char) -> *mut FsdrFlowgraph {
+    ffi(ptr::null_mut(), || {
+        let path = str_arg(path, "path")?;
+        let yaml =
+            std::fs::read_to_string(path).with_context(|| format!("cannot read {}", path))?;
+        flowgraph_from_yaml(&yaml)
+    })
+}
+
+/// Start the flowgraph in the background.
+#[no_mangle]
+pub unsafe extern "C" fn fsdr_flowgraph_start(fg: *mut FsdrFlowgraph) -> i32 {
+    ffi(-1, || {
+        let fg = fg_arg(fg)?;
+        match std::mem::replace(&mut fg.state, State::Terminated) {
+            State::Built(f) => {
+                let (task, handle) = block_on(fg.runtime.start(f));
+                fg.state = State::Running {
+                    task: task.boxed(),
+                    handle,
+                };
+                Ok(0)
+            }
+            s => {
+                fg.state = s;
+                bail!("flowgraph was already started")
+            }
+        }
+    })
+}
+
+/// Wait for the flowgraph to terminate.
+///
+/// Returns an error, if the flowgraph terminated with an error.
+#[no_mangle]
+pub unsafe extern "C" fn fsdr_flowgraph_wait(fg: *mut FsdrFlowgraph) -> i32 {
+    ffi(-1, || {
+        fg_arg(fg)?.wait()?;
+        Ok(0)
+    })
+}
+
+/// Stop the flowgraph and wait for it to terminate.
+#[no_mangle]
+pub unsafe extern "C" fn fsdr_flowgraph_stop(fg: *mut FsdrFlowgraph) -> i32 {
+    ffi(-1, || {
+        let fg = fg_arg(fg)?;
+        if let State::Built(_) = fg.state {
+            bail!("flowgraph is not running");
+        }
+        if let Ok(h) = fg.handle() {
+            // fails, if the flowgraph already terminated
+            let _ = block_on(h.terminate());
+        }
+        fg.wait()?;
+        Ok(0)
+    })
+}
+
+/// Get the id of a block, given its instance name.
+///
+/// Returns -1, if there is no block with this name.
+#[no_mangle]
+pub unsafe extern "C" fn fsdr_flowgraph_block_id(
+    fg: *mut FsdrFlowgraph,
+    block: *const c_char,
+) -> i64 {
+    ffi(-1, || {
+        let fg = fg_arg(fg)?;
+        Ok(fg.block_id(str_arg(block, "block")?)? as i64)
+    })
+}
+
+/// Call a message handler of a running flowgraph with a JSON-encoded Pmt, e.g., `{"F64": 1e6}`
+/// or `"Null"`.
+#[no_mangle]
+pub unsafe extern "C" fn fsdr_flowgraph_call(
+    fg: *mut FsdrFlowgraph,
+    block: *const c_char,
+    port: *const c_char,
+    pmt: *const c_char,
+) -> i32 {
+    ffi(-1, || {
+        let fg = fg_arg(fg)?;
+        let id = fg.block_id(str_arg(block, "block")?)?;
+        let port = str_arg(port, "port")?;
+        let pmt: Pmt = serde_json::from_str(str_arg(pmt, "pmt")?).context("invalid pmt")?;
+        block_on(fg.handle()?.call(id, port, pmt))?;
+        Ok(0)
+    })
+}
+
+/// Call a message handler of a running flowgraph with a JSON-encoded Pmt and return its
+/// JSON-encoded result.
+///
+/// The returned string has to be freed with [fsdr_string_free]. Returns null on error.
+#[no_mangle]
+pub unsafe extern "C" fn fsdr_flowgraph_callback(
+    fg: *mut FsdrFlowgraph,
+    block: *const c_char,
+    port: *const c_char,
+    pmt: *const c_char,
+) -> *mut c_char {
+    ffi(ptr::null_mut(), || {
+        let fg = fg_arg(fg)?;
+        let id = fg.block_id(str_arg(block, "block")?)?;
+        let port = str_arg(port, "port")?;
+        let pmt: Pmt = serde_json::from_str(str_arg(pmt, "pmt")?).context("invalid pmt")?;
+        let ret = block_on(fg.handle()?.callback(id, port, pmt))?;
+        Ok(CString::new(serde_json::to_string(&ret)?)?.into_raw())
+    })
+}
+
+/// Receive runtime events of a running flowgraph, like block state changes and its termination.
+///
+/// The callback is called from a background thread with the JSON-encoded event and the user
+/// data, until the flowgraph terminates. The event string is only valid during the callback.
+#[no_mangle]
+pub unsafe extern "C" fn fsdr_flowgraph_subscribe(
+    fg: *mut FsdrFlowgraph,
+    callback: FsdrEventCallback,
+    user_data: *mut c_void,
+) -> i32 {
+    ffi(-1, || {
+        let fg = fg_arg(fg)?;
+        let callback = callback.ok_or_else(|| anyhow!("callback is null"))?;
+        let mut events = block_on(fg.handle()?.subscribe())?;
+        let user_data = UserData(user_data);
+        fg.subscribers.push(std::thread::spawn(move || {
+            let user_data = user_data;
+            while let Some(e) = block_on(events.next()) {
+                if let Ok(s) = serde_json::to_string(&e) {
+                    let s = CString::new(s).unwrap();
+                    callback(s.as_ptr(), user_data.0);
+                }
+            }
+        }));
+        Ok(0)
+    })
+}
+
+/// Free a flowgraph, stopping it, if it is running.
+#[no_mangle]
+pub unsafe extern "C" fn fsdr_flowgraph_free(fg: *mut FsdrFlowgraph) {
+    if !fg.is_null() {
+        drop(Box::from_raw(fg));
+    }
+}
+
+/// Free a string, returned by the library.
+#[no_mangle]
+pub unsafe extern "C" fn fsdr_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Message of the last error of the calling thread, or null.
+///
+/// The string is owned by the library and valid until the next call that fails.
+#[no_mangle]
+pub extern "C" fn fsdr_last_error() -> *const c_char {
+    LAST_ERROR.with(|l| l.borrow().as_ref().map_or(ptr::null(), |e| e.as_ptr()))
+}
+
+/// Version of the library.
+#[no_mangle]
+pub extern "C" fn fsdr_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr() as *const c_char
+}
@@ -0,0 +1,337 @@
+//! Build flowgraphs from YAML descriptions.
+//!
+//! ```yaml
+//! blocks:
+//!   src:
+//!     type: MessageSource
+//!     message: { String: "hello" }
+//!     interval: 0.1
+//!     n_messages: 10
+//!   snk:
+//!     type: MessageSink
+//! connections:
+//!   - src.out | snk.in
+//! ```
+//!
+//! Blocks are given by their instance name, which is also used to address them through the C
+//! API, and their `type`. Further keys are parameters of the block. Stream blocks take the type
+//! of their samples as `item` parameter (`u8`, `i16`, `i32`, `u32`, `f32`, `f64`, `c32`, or
+//! `c64`; default: `f32`). Connections use the syntax of the `connect!` macro, i.e., `>` for
+//! stream and `|` for message connections.
+use futuresdr::anyhow::{anyhow, bail, Context, Result};
+use futuresdr::blocks;
+use futuresdr::num_complex::{Complex32, Complex64};
+use futuresdr::runtime::Block;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::PortId;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_yaml::Mapping;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Parameters of a block.
+pub struct Params<'a> {
+    name: &'a str,
+    map: &'a Mapping,
+}
+
+impl<'a> Params<'a> {
+    /// Instance name of the block.
+    pub fn name(&self) -> &str {
+        self.name
+    }
+
+    /// Optional parameter.
+    ///
+    /// Parameters are deserialized like JSON, i.e., enums like [Pmt]s are maps with the variant
+    /// as key (e.g., `{ F64: 1e6 }`).
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        self.map
+            .get(key)
+            .map(|v| {
+                serde_json::to_value(v)
+                    .and_then(serde_json::from_value)
+                    .with_context(|| format!("block {}: invalid parameter {}", self.name, key))
+            })
+            .transpose()
+    }
+
+    /// Required parameter.
+    pub fn required<T: DeserializeOwned>(&self, key: &str) -> Result<T> {
+        self.get(key)?
+            .ok_or_else(|| anyhow!("block {}: missing parameter {}", self.name, key))
+    }
+
+    /// Type of the samples (default: `f32`).
+    pub fn item(&self) -> Result<String> {
+        Ok(self.get("item")?.unwrap_or_else(|| "f32".to_string()))
+    }
+}
+
+type Constructor = Box<dyn Fn(&Params) -> Result<Block> + Send + Sync>;
+
+/// Constructors of blocks, by type name.
+pub struct Registry {
+    constructors: HashMap<String, Constructor>,
+}
+
+macro_rules! typed {
+    ($p:expr, $f:ident) => {
+        match $p.item()?.as_str() {
+            "u8" => $f::<u8>($p),
+            "i16" => $f::<i16>($p),
+            "i32" => $f::<i32>($p),
+            "u32" => $f::<u32>($p),
+            "f32" => $f::<f32>($p),
+            "f64" => $f::<f64>($p),
+            "c32" => $f::<Complex32>($p),
+            "c64" => $f::<Complex64>($p),
+            t => bail!("block {}: unsupported item type {}", $p.name(), t),
+        }
+    };
+}
+
+fn null_source<T: Send + 'static>(_p: &Params) -> Result<Block> {
+    Ok(blocks::NullSource::<T>::new())
+}
+
+fn null_sink<T: Send + 'static>(_p: &Params) -> Result<Block> {
+    Ok(blocks::NullSink::<T>::new())
+}
+
+fn head<T: Send + 'static>(p: &Params) -> Result<Block> {
+    Ok(blocks::Head::<T>::new(p.required("n_items")?))
+}
+
+fn copy<T: Send + 'static>(_p: &Params) -> Result<Block> {
+    Ok(blocks::Copy::<T>::new())
+}
+
+fn throttle<T: Send + 'static>(p: &Params) -> Result<Block> {
+    Ok(blocks::Throttle::<T>::new(p.required("rate")?))
+}
+
+fn file_source<T: Send + 'static>(p: &Params) -> Result<Block> {
+    Ok(blocks::FileSource::<T>::new(
+        p.required::<String>("path")?,
+        p.get("repeat")?.unwrap_or(false),
+    ))
+}
+
+fn file_sink<T: Send + 'static>(p: &Params) -> Result<Block> {
+    Ok(blocks::FileSink::<T>::new(p.required::<String>("path")?))
+}
+
+fn tag_debug<T: Send + 'static>(p: &Params) -> Result<Block> {
+    Ok(blocks::TagDebug::<T>::new(p.name()))
+}
+
+fn tcp_source<T: Send + 'static>(p: &Params) -> Result<Block> {
+    Ok(blocks::TcpSourceBuilder::<T>::new(tcp_mode(p)?).build())
+}
+
+fn tcp_sink<T: Send + 'static>(p: &Params) -> Result<Block> {
+    Ok(blocks::TcpSinkBuilder::<T>::new(tcp_mode(p)?).build())
+}
+
+fn tcp_mode(p: &Params) -> Result<blocks::TcpMode> {
+    match (p.get::<String>("listen")?, p.get::<String>("connect")?) {
+        (Some(a), None) => Ok(blocks::TcpMode::Server(a)),
+        (None, Some(a)) => Ok(blocks::TcpMode::Client(a)),
+        _ => bail!("block {}: set either listen or connect address", p.name()),
+    }
+}
+
+fn fir(p: &Params) -> Result<Block> {
+    let taps: Vec<f32> = p.required("taps")?;
+    match p.item()?.as_str() {
+        "f32" => Ok(blocks::FirBuilder::new::<f32, f32, f32, _>(taps)),
+        "c32" => Ok(blocks::FirBuilder::new::<Complex32, Complex32, f32, _>(
+            taps,
+        )),
+        t => bail!("block {}: unsupported item type {}", p.name(), t),
+    }
+}
+
+fn message_source(p: &Params) -> Result<Block> {
+    Ok(blocks::MessageSource::new(
+        p.required::<Pmt>("message")?,
+        Duration::from_secs_f64(p.required("interval")?),
+        p.get("n_messages")?,
+    ))
+}
+
+fn message_burst(p: &Params) -> Result<Block> {
+    Ok(blocks::MessageBurst::new(
+        p.required::<Pmt>("message")?,
+        p.required("n_messages")?,
+    ))
+}
+
+#[cfg(feature = "soapy")]
+fn soapy_source(p: &Params) -> Result<Block> {
+    let mut b = blocks::SoapySourceBuilder::new();
+    if let Some(f) = p.get::<String>("filter")? {
+        b = b.filter(f);
+    }
+    if let Some(f) = p.get("freq")? {
+        b = b.freq(f);
+    }
+    if let Some(r) = p.get("sample_rate")? {
+        b = b.sample_rate(r);
+    }
+    if let Some(g) = p.get("gain")? {
+        b = b.gain(g);
+    }
+    if let Some(a) = p.get::<String>("antenna")? {
+        b = b.antenna(a);
+    }
+    Ok(b.build())
+}
+
+#[cfg(feature = "soapy")]
+fn soapy_sink(p: &Params) -> Result<Block> {
+    let mut b = blocks::SoapySinkBuilder::new();
+    if let Some(f) = p.get::<String>("filter")? {
+        b = b.filter(f);
+    }
+    if let Some(f) = p.get("freq")? {
+        b = b.freq(f);
+    }
+    if let Some(r) = p.get("sample_rate")? {
+        b = b.sample_rate(r);
+    }
+    if let Some(g) = p.get("gain")? {
+        b = b.gain(g);
+    }
+    if let Some(a) = p.get::<String>("antenna")? {
+        b = b.antenna(a);
+    }
+    Ok(b.build())
+}
+
+impl Registry {
+    /// Registry without any blocks.
+    pub fn empty() -> Registry {
+        Registry {
+            constructors: HashMap::new(),
+        }
+    }
+
+    /// Add a block type, replacing a type with the same name.
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        f: impl Fn(&Params) -> Result<Block> + Send + Sync + 'static,
+    ) -> &mut Registry {
+        self.constructors.insert(name.into(), Box::new(f));
+        self
+    }
+
+    /// Names of the registered block types.
+    pub fn types(&self) -> Vec<&str> {
+        let mut v: Vec<&str> = self.constructors.keys().map(|k| k.as_str()).collect();
+        v.sort_unstable();
+        v
+    }
+
+    /// Build a flowgraph from its YAML description.
+    ///
+    /// Returns the flowgraph and the ids of the blocks by instance name.
+    pub fn build(&self, yaml: &str) -> Result<(Flowgraph, HashMap<String, usize>)> {
+        let desc: Description = serde_yaml::from_str(yaml).context("invalid flowgraph")?;
+
+        let mut fg = Flowgraph::new();
+        let mut ids = HashMap::new();
+
+        for (name, params) in desc.blocks.iter() {
+            let name = name
+                .as_str()
+                .ok_or_else(|| anyhow!("block names have to be strings"))?;
+            let map = params
+                .as_mapping()
+                .ok_or_else(|| anyhow!("block {}: parameters have to be a mapping", name))?;
+            let p = Params { name, map };
+            let t: String = p.required("type")?;
+            let f = self
+                .constructors
+                .get(&t)
+                .ok_or_else(|| anyhow!("block {}: unknown type {}", name, t))?;
+            let mut block = f(&p)?;
+            block.set_instance_name(name);
+            ids.insert(name.to_string(), fg.add_block(block));
+        }
+
+        for c in desc.connections.iter() {
+            let (src, dst, stream) = if let Some((s, d)) = c.split_once('>') {
+                (s, d, true)
+            } else if let Some((s, d)) = c.split_once('|') {
+                (s, d, false)
+            } else {
+                bail!("connection {}: expected > or |", c);
+            };
+            let (src, src_port) = endpoint(&ids, src)?;
+            let (dst, dst_port) = endpoint(&ids, dst)?;
+            if stream {
+                fg.connect_stream(src, src_port, dst, dst_port)
+            } else {
+                fg.connect_message(src, src_port, dst, dst_port)
+            }
+            .with_context(|| format!("connection {}", c))?;
+        }
+
+        Ok((fg, ids))
+    }
+}
+
+impl Default for Registry {
+    /// Registry with the built-in blocks.
+    fn default() -> Self {
+        let mut r = Registry::empty();
+        r.register("NullSource", |p| typed!(p, null_source))
+            .register("NullSink", |p| typed!(p, null_sink))
+            .register("Head", |p| typed!(p, head))
+            .register("Copy", |p| typed!(p, copy))
+            .register("Throttle", |p| typed!(p, throttle))
+            .register("FileSource", |p| typed!(p, file_source))
+            .register("FileSink", |p| typed!(p, file_sink))
+            .register("TagDebug", |p| typed!(p, tag_debug))
+            .register("TcpSource", |p| typed!(p, tcp_source))
+            .register("TcpSink", |p| typed!(p, tcp_sink))
+            .register("Fir", fir)
+            .register("MessageSource", message_source)
+            .register("MessageBurst", message_burst)
+            .register("MessageCopy", |_| Ok(blocks::MessageCopy::new()))
+            .register("MessageSink", |_| Ok(blocks::MessageSink::new()));
+        #[cfg(feature = "soapy")]
+        r.register("SoapySource", soapy_source)
+            .register("SoapySink", soapy_sink);
+        r
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Description {
+    blocks: Mapping,
+    #[serde(default)]
+    connections: Vec<String>,
+}
+
+/// Parse `block.port`, where the port is a name or an index.
+fn endpoint(ids: &HashMap<String, usize>, s: &str) -> Result<(usize, PortId)> {
+    let s = s.trim();
+    let (block, port) = s
+        .split_once('.')
+        .ok_or_else(|| anyhow!("endpoint {}: expected block.port", s))?;
+    let id = ids
+        .get(block)
+        .ok_or_else(|| anyhow!("endpoint {}: unknown block {}", s, block))?;
+    let port = match port.parse::<usize>() {
+        Ok(i) => PortId::Index(i),
+        Err(_) => PortId::Name(port.to_string()),
+    };
+    Ok((*id, port))
+}
@@ -0,0 +1,151 @@
+use futuresdr_c::*;
+use std::ffi::{c_void, CStr, CString};
+use std::os::raw::c_char;
+use std::sync::mpsc;
+
+fn c(s: &str) -> CString {
+    CString::new(s).unwrap()
+}
+
+fn last_error() -> String {
+    unsafe { CStr::from_ptr(fsdr_last_error()) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+const MESSAGES: &str = r#"
+blocks:
+  src:
+    type: MessageSource
+    message: { String: "foo" }
+    interval: 100.0
+  snk:
+    type: MessageSink
+connections:
+  - src.out | snk.in
+"#;
+
+#[test]
+fn invalid_yaml() {
+    unsafe {
+        let fg = fsdr_flowgraph_from_yaml(c("blocks:\n  a:\n    type: Foo\n").as_ptr());
+        assert!(fg.is_null());
+        assert!(last_error().contains("unknown type Foo"));
+
+        let yaml = "blocks:\n  a:\n    type: NullSource\nconnections:\n  - a.out > b.in\n";
+        let fg = fsdr_flowgraph_from_yaml(c(yaml).as_ptr());
+        assert!(fg.is_null());
+        assert!(last_error().contains("unknown block b"));
+
+        assert_eq!(fsdr_flowgraph_start(std::ptr::null_mut()), -1);
+        assert!(last_error().contains("null"));
+    }
+}
+
+#[test]
+fn run_to_completion() {
+    let yaml = r#"
+blocks:
+  src:
+    type: NullSource
+    item: c32
+  head:
+    type: Head
+    item: c32
+    n_items: 100000
+  snk:
+    type: NullSink
+    item: c32
+connections:
+  - src.out > head.in
+  - head.out > snk.in
+"#;
+    unsafe {
+        let fg = fsdr_flowgraph_from_yaml(c(yaml).as_ptr());
+        assert!(!fg.is_null(), "{}", last_error());
+        assert_eq!(fsdr_flowgraph_block_id(fg, c("head").as_ptr()), 1);
+        assert_eq!(fsdr_flowgraph_block_id(fg, c("foo").as_ptr()), -1);
+        assert_eq!(fsdr_flowgraph_start(fg), 0);
+        assert_eq!(fsdr_flowgraph_start(fg), -1);
+        assert_eq!(fsdr_flowgraph_wait(fg), 0, "{}", last_error());
+        fsdr_flowgraph_free(fg);
+    }
+}
+
+extern "C" fn on_event(event: *const c_char, user_data: *mut c_void) {
+    let tx = unsafe { &*(user_data as *const mpsc::Sender<String>) };
+    let e = unsafe { CStr::from_ptr(event) }
+        .to_string_lossy()
+        .into_owned();
+    tx.send(e).unwrap();
+}
+
+#[test]
+fn call_and_subscribe() {
+    let (tx, rx) = mpsc::channel::<String>();
+    unsafe {
+        let fg = fsdr_flowgraph_from_yaml(c(MESSAGES).as_ptr());
+        assert!(!fg.is_null(), "{}", last_error());
+
+        let ret = fsdr_flowgraph_callback(
+            fg,
+            c("snk").as_ptr(),
+            c("in").as_ptr(),
+            c("\"Null\"").as_ptr(),
+        );
+        assert!(ret.is_null());
+        assert!(last_error().contains("not running"));
+
+        assert_eq!(fsdr_flowgraph_start(fg), 0);
+        assert_eq!(
+            fsdr_flowgraph_subscribe(fg, Some(on_event), &tx as *const _ as *mut c_void),
+            0
+        );
+
+        assert_eq!(
+            fsdr_flowgraph_call(
+                fg,
+                c("snk").as_ptr(),
+                c("in").as_ptr(),
+                c("{\"U32\": 1}").as_ptr()
+            ),
+            0
+        );
+        assert_eq!(
+            fsdr_flowgraph_call(
+                fg,
+                c("snk").as_ptr(),
+                c("in").as_ptr(),
+                c("{\"Foo\"").as_ptr()
+            ),
+            -1
+        );
+        assert!(last_error().contains("invalid pmt"));
+
+        let ret = fsdr_flowgraph_callback(
+            fg,
+            c("snk").as_ptr(),
+            c("in").as_ptr(),
+            c("\"Null\"").as_ptr(),
+        );
+        assert!(!ret.is_null(), "{}", last_error());
+        // received the call and, depending on timing, the first message of the source
+        let ret_str = CStr::from_ptr(ret).to_str().unwrap();
+        assert!(
+            ret_str == "{\"U64\":2}" || ret_str == "{\"U64\":3}",
+            "{}",
+            ret_str
+        );
+        fsdr_string_free(ret);
+
+        assert_eq!(fsdr_flowgraph_stop(fg), 0, "{}", last_error());
+        fsdr_flowgraph_free(fg);
+    }
+
+    let events: Vec<String> = rx.try_iter().collect();
+    assert!(
+        events.iter().any(|e| e.contains("Terminated")),
+        "{:?}",
+        events
+    );
+}
@@ -2,7 +2,7 @@
 //!
 //! When you run the example, it will build a flowgraph consisting of the following blocks:
 //! * SoapySource: Gets data from your SDR using the SoapySDR driver
-//! * Demodulator: Demodulates the FM signal
+//! * WbfmRx: Filters, demodulates, and resamples the FM signal to the audio rate
 //! * AudioSink: Plays the demodulated signal on your device
 //!
 //! After giving it some time to start up the SDR, it enters a loop where you will
@@ -12,13 +12,12 @@
 
 use clap::Parser;
 
-use futuredsp::firdes;
 use futuresdr::anyhow::Result;
 use futuresdr::async_io;
 use futuresdr::blocks::audio::AudioSink;
 use futuresdr::blocks::Apply;
-use futuresdr::blocks::FirBuilder;
 use futuresdr::blocks::SoapySourceBuilder;
+use futuresdr::blocks::WbfmRx;
 use futuresdr::macros::connect;
 use futuresdr::num_complex::Complex32;
 use futuresdr::num_integer::gcd;
@@ -44,10 +43,6 @@ struct Args {
     #[clap(short, long, default_value = "")]
     soapy: String,
 
-    /// Multiplier for intermedia sample rate
-    #[clap(long)]
-    audio_mult: Option<u32>,
-
     /// Audio Rate
     #[clap(short, long)]
     audio_rate: Option<u32>,
@@ -72,17 +67,6 @@ fn main() -> Result<()> {
     };
     println!("Selected Audio Rate {audio_rate:?}");
 
-    let audio_mult = if let Some(m) = args.audio_mult {
-        m
-    } else {
-        let mut m = 5;
-        while (m * audio_rate) as f64 > freq_offset + 100e3 {
-            m -= 1;
-        }
-        m
-    };
-    println!("Audio Mult {audio_mult:?}");
-
     // Create the `Flowgraph` where the `Block`s will be added later on
     let mut fg = Flowgraph::new();
 
@@ -99,21 +83,6 @@ fn main() -> Result<()> {
         .message_input_name_to_id("freq")
         .expect("No freq port found!");
 
-    // Downsample before demodulation
-    let interp = (audio_rate * audio_mult) as usize;
-    let decim = sample_rate as usize;
-    println!("interp {interp}   decim {decim}");
-    let resamp1 = FirBuilder::new_resampling::<Complex32, Complex32>(interp, decim);
-
-    // Demodulation block using the conjugate delay method
-    // See https://en.wikipedia.org/wiki/Detector_(radio)#Quadrature_detector
-    let mut last = Complex32::new(0.0, 0.0); // store sample x[n-1]
-    let demod = Apply::new(move |v: &Complex32| -> f32 {
-        let arg = (v * last.conj()).arg(); // Obtain phase of x[n] * conj(x[n-1])
-        last = *v;
-        arg
-    });
-
     let mut last = Complex32::new(1.0, 0.0);
     let add = Complex32::from_polar(
         1.0,
@@ -124,41 +93,17 @@ fn main() -> Result<()> {
         last * v
     });
 
-    // Design filter for the audio and decimate by 5.
-    // Ideally, this should be a FM de-emphasis filter, but the following works.
-    let cutoff = 2_000.0 / (audio_rate * audio_mult) as f64;
-    let transition = 10_000.0 / (audio_rate * audio_mult) as f64;
-    println!("cutoff {cutoff}   transition {transition}");
-    let audio_filter_taps = firdes::kaiser::lowpass::<f32>(cutoff, transition, 0.1);
-    let resamp2 = FirBuilder::new_resampling_with_taps::<f32, f32, _, _>(
-        1,
-        audio_mult as usize,
-        audio_filter_taps,
-    );
+    // Filter, demodulate, and resample to the audio rate
+    let rx = WbfmRx::new(sample_rate, audio_rate);
 
-    // Single-channel `AudioSink` with the downsampled rate (sample_rate / (8*5) = 48_000)
+    // Single-channel `AudioSink` with the audio rate
     let snk = AudioSink::new(audio_rate, 1);
 
-    // Add all the blocks to the `Flowgraph`...
-    // let src = fg.add_block(src);
-    // let shift = fg.add_block(shift);
-    // let resamp1 = fg.add_block(resamp1);
-    // let demod = fg.add_block(demod);
-    // let resamp2 = fg.add_block(resamp2);
-    // let snk = fg.add_block(snk);
-
-    // ... and connect the ports appropriately
-    // fg.connect_stream(src, "out", shift, "in")?;
-    // fg.connect_stream(shift, "out", resamp1, "in")?;
-    // fg.connect_stream(resamp1, "out", demod, "in")?;
-    // fg.connect_stream(demod, "out", resamp2, "in")?;
-    // fg.connect_stream(resamp2, "out", snk, "in")?;
+    // Add all the blocks to the `Flowgraph` and connect the ports appropriately
     connect!(fg,
              src.out > shift;
-             shift > resamp1;
-             resamp1 > demod;
-             demod > resamp2;
-             resamp2 > snk.in;
+             shift > rx;
+             rx > snk.in;
     );
 
     // Start the flowgraph and save the handle
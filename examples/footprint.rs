@@ -0,0 +1,30 @@
+//! Footprint of a minimal flowgraph, measured by `perf/footprint.sh`.
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::Head;
+use futuresdr::blocks::NullSink;
+use futuresdr::blocks::NullSource;
+use futuresdr::runtime::scheduler::SmolScheduler;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+
+fn main() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(NullSource::<f32>::new());
+    let head = fg.add_block(Head::<f32>::new(10_000_000));
+    let snk = fg.add_block(NullSink::<f32>::new());
+
+    fg.connect_stream(src, "out", head, "in")?;
+    fg.connect_stream(head, "out", snk, "in")?;
+
+    Runtime::with_scheduler(SmolScheduler::new(1, false)).run(fg)?;
+
+    // peak resident set size, as reported by Linux
+    if let Ok(status) = std::fs::read_to_string("/proc/self/status") {
+        if let Some(l) = status.lines().find(|l| l.starts_with("VmHWM:")) {
+            println!("peak rss: {}", l["VmHWM:".len()..].trim());
+        }
+    }
+
+    Ok(())
+}
@@ -98,6 +98,11 @@ impl Slider {
                 let v = u64::try_from(value).ok()?;
                 Some(Pmt::U64(v))
             }
+            PmtKind::I32 => {
+                let v = i32::try_from(value).ok()?;
+                Some(Pmt::I32(v))
+            }
+            PmtKind::I64 => Some(Pmt::I64(value)),
             PmtKind::F64 => Some(Pmt::F64(value as f64)),
             _ => None,
         }
@@ -83,7 +83,7 @@ where
         })
         .collect();
     if truncate {
-        taps.remove(len);
+        taps.remove(len - 1);
     }
     taps
 }
@@ -513,4 +513,32 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn hann_periodic() {
+        let n_taps = 8;
+        let test_taps = [
+            0.0,
+            0.146446609,
+            0.5,
+            0.853553391,
+            1.0,
+            0.853553391,
+            0.5,
+            0.146446609,
+        ];
+        let window = hann(n_taps, true);
+        assert_eq!(window.len(), n_taps);
+        for (i, tap) in test_taps.iter().enumerate() {
+            let tol = 1e-5;
+            assert!(
+                (window[i] - tap).abs() < tol,
+                "abs({} - {}) < {} (tap {})",
+                window[i],
+                tap,
+                tol,
+                i
+            );
+        }
+    }
 }
@@ -397,6 +397,7 @@ pub fn message_handler(
     let mio = get_parameter_ident(&handler.sig.inputs[1]).unwrap();
     let meta = get_parameter_ident(&handler.sig.inputs[2]).unwrap();
     let pmt = get_parameter_ident(&handler.sig.inputs[3]).unwrap();
+    let pmt_type = get_parameter_type(&handler.sig.inputs[3]).unwrap();
     let body = handler.block.stmts;
 
     // println!("name {}", name);
@@ -409,7 +410,7 @@ pub fn message_handler(
             &'a mut self,
             #mio: &'a mut MessageIo<Self>,
             #meta: &'a mut BlockMeta,
-            #pmt: Pmt,
+            #pmt: #pmt_type,
         ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Pmt>> + Send + 'a>> {
             use crate::futures::FutureExt;
             async move {
@@ -434,6 +435,7 @@ pub fn message_handler_external(
     let mio = get_parameter_ident(&handler.sig.inputs[1]).unwrap();
     let meta = get_parameter_ident(&handler.sig.inputs[2]).unwrap();
     let pmt = get_parameter_ident(&handler.sig.inputs[3]).unwrap();
+    let pmt_type = get_parameter_type(&handler.sig.inputs[3]).unwrap();
     let body = handler.block.stmts;
 
     // println!("name {}", name);
@@ -446,7 +448,7 @@ pub fn message_handler_external(
             &'a mut self,
             #mio: &'a mut MessageIo<Self>,
             #meta: &'a mut BlockMeta,
-            #pmt: Pmt,
+            #pmt: #pmt_type,
         ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Pmt>> + Send + 'a>> {
             use futuresdr::futures::FutureExt;
             async move {
@@ -459,6 +461,13 @@ pub fn message_handler_external(
     out.into()
 }
 
+fn get_parameter_type(arg: &syn::FnArg) -> Option<syn::Type> {
+    if let syn::FnArg::Typed(syn::PatType { ty, .. }) = arg {
+        return Some((**ty).clone());
+    }
+    None
+}
+
 fn get_parameter_ident(arg: &syn::FnArg) -> Option<syn::Ident> {
     if let syn::FnArg::Typed(syn::PatType { pat, .. }) = arg {
         if let syn::Pat::Ident(ref i) = **pat {
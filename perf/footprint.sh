@@ -0,0 +1,25 @@
+#!/bin/bash
+
+# Footprint of the `footprint` example (NullSource > Head > NullSink, one worker thread),
+# with and without the default features: number of dependencies, size of the stripped
+# release binary, and peak RSS. Linux only.
+
+set -e
+
+cd "$(dirname "$0")/.."
+
+for FEATURES in "" "--no-default-features"
+do
+    echo "futuresdr ${FEATURES:-(default features)}"
+
+    DEPS=$(cargo tree --edges normal --prefix none ${FEATURES} | sed 's/ (\*)$//' | sort -u | wc -l)
+    echo "dependencies: ${DEPS} crates"
+
+    cargo build --release --example footprint ${FEATURES} 2> /dev/null
+    BIN=target/release/examples/footprint-stripped
+    strip -o ${BIN} target/release/examples/footprint
+    echo "binary size: $(du -h ${BIN} | cut -f1)"
+
+    ${BIN}
+    echo
+done
@@ -1,6 +1,11 @@
+use alloc::string::String;
+use alloc::vec::Vec;
 use serde::{Deserialize, Serialize};
 
+use crate::PmtKind;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct FlowgraphDescription {
     pub blocks: Vec<BlockDescription>,
     pub stream_edges: Vec<(usize, usize, usize, usize)>,
@@ -8,6 +13,7 @@ pub struct FlowgraphDescription {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct BlockDescription {
     pub id: usize,
     pub type_name: String,
@@ -15,6 +21,125 @@ pub struct BlockDescription {
     pub stream_inputs: Vec<String>,
     pub stream_outputs: Vec<String>,
     pub message_inputs: Vec<String>,
+    /// Descriptions of the message handlers, in the order of `message_inputs`.
+    #[serde(default)]
+    pub message_input_descriptions: Vec<HandlerDescription>,
     pub message_outputs: Vec<String>,
     pub blocking: bool,
 }
+
+/// Description of the values, a message handler accepts.
+///
+/// An empty description means that nothing is known about the handler, i.e., it might accept
+/// any [`Pmt`](crate::Pmt).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct HandlerDescription {
+    /// Kinds of PMTs, the handler accepts.
+    pub kinds: Vec<PmtKind>,
+    /// Entries of a [`Pmt::MapStrPmt`](crate::Pmt::MapStrPmt), the handler accepts.
+    pub parameters: Vec<ParameterDescription>,
+    /// Unit of scalar values.
+    pub unit: Option<String>,
+    pub description: Option<String>,
+}
+
+impl HandlerDescription {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn kinds(mut self, kinds: &[PmtKind]) -> Self {
+        self.kinds = kinds.to_vec();
+        self
+    }
+
+    /// Add a parameter. This implies that the handler accepts a
+    /// [`Pmt::MapStrPmt`](crate::Pmt::MapStrPmt).
+    #[must_use]
+    pub fn parameter(mut self, parameter: ParameterDescription) -> Self {
+        if !self.kinds.contains(&PmtKind::MapStrPmt) {
+            self.kinds.push(PmtKind::MapStrPmt);
+        }
+        self.parameters.push(parameter);
+        self
+    }
+
+    #[must_use]
+    pub fn unit(mut self, unit: &str) -> Self {
+        self.unit = Some(unit.into());
+        self
+    }
+
+    #[must_use]
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.kinds.is_empty()
+            && self.parameters.is_empty()
+            && self.unit.is_none()
+            && self.description.is_none()
+    }
+}
+
+/// Description of an entry of a [`Pmt::MapStrPmt`](crate::Pmt::MapStrPmt) parameter.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ParameterDescription {
+    pub name: String,
+    pub kind: PmtKind,
+    pub unit: Option<String>,
+    pub description: Option<String>,
+}
+
+impl ParameterDescription {
+    pub fn new(name: &str, kind: PmtKind) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+            unit: None,
+            description: None,
+        }
+    }
+
+    #[must_use]
+    pub fn unit(mut self, unit: &str) -> Self {
+        self.unit = Some(unit.into());
+        self
+    }
+
+    #[must_use]
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum BlockState {
+    Running,
+    Done,
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FlowgraphStats {
+    pub blocks: Vec<(usize, BlockState)>,
+    pub running: usize,
+    pub done: usize,
+    pub error: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum FlowgraphEvent {
+    BlockState { block_id: usize, state: BlockState },
+    Stats(FlowgraphStats),
+    Terminated { error: bool },
+}
@@ -1,12 +1,32 @@
+//! Polymorphic Types
+//!
+//! The crate is `no_std`-compatible, when the default `std` feature is disabled. It then only
+//! requires `alloc`, and [Pmt::MapStrPmt] uses a `BTreeMap` instead of a `HashMap`.
+#![cfg_attr(not(feature = "std"), no_std)]
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::any::Any;
+use core::fmt;
+use core::str::FromStr;
 use dyn_clone::DynClone;
 use serde::{Deserialize, Serialize};
-use std::any::Any;
+
+#[cfg(not(feature = "std"))]
+use alloc::collections::BTreeMap as HashMap;
+#[cfg(feature = "std")]
 use std::collections::HashMap;
-use std::fmt;
 
 mod description;
 pub use description::BlockDescription;
+pub use description::BlockState;
 pub use description::FlowgraphDescription;
+pub use description::FlowgraphEvent;
+pub use description::FlowgraphStats;
+pub use description::HandlerDescription;
+pub use description::ParameterDescription;
 
 pub trait PmtAny: Any + DynClone + Send + Sync + 'static {
     fn as_any(&self) -> &dyn Any;
@@ -40,11 +60,14 @@ impl dyn PmtAny {
 
 #[non_exhaustive]
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub enum Pmt {
     Null,
     String(String),
     U32(u32),
     U64(u64),
+    I32(i32),
+    I64(i64),
     F32(f32),
     F64(f64),
     VecF32(Vec<f32>),
@@ -53,6 +76,7 @@ pub enum Pmt {
     VecPmt(Vec<Pmt>),
     MapStrPmt(HashMap<String, Pmt>),
     #[serde(skip)]
+    #[cfg_attr(feature = "openapi", schema(skip))]
     Any(Box<dyn PmtAny>),
 }
 
@@ -63,6 +87,8 @@ impl PartialEq for Pmt {
             (Pmt::String(x), Pmt::String(y)) => x == y,
             (Pmt::U32(x), Pmt::U32(y)) => x == y,
             (Pmt::U64(x), Pmt::U64(y)) => x == y,
+            (Pmt::I32(x), Pmt::I32(y)) => x == y,
+            (Pmt::I64(x), Pmt::I64(y)) => x == y,
             (Pmt::F32(x), Pmt::F32(y)) => x == y,
             (Pmt::F64(x), Pmt::F64(y)) => x == y,
             (Pmt::VecF32(x), Pmt::VecF32(y)) => x == y,
@@ -101,6 +127,20 @@ impl Pmt {
                     None
                 }
             }
+            PmtKind::I32 => {
+                if let Ok(v) = s.parse::<i32>() {
+                    Some(Pmt::I32(v))
+                } else {
+                    None
+                }
+            }
+            PmtKind::I64 => {
+                if let Ok(v) = s.parse::<i64>() {
+                    Some(Pmt::I64(v))
+                } else {
+                    None
+                }
+            }
             PmtKind::F32 => {
                 if let Ok(v) = s.parse::<f32>() {
                     Some(Pmt::F32(v))
@@ -122,12 +162,15 @@ impl Pmt {
 }
 
 #[non_exhaustive]
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub enum PmtKind {
     Null,
     String,
     U32,
     U64,
+    I32,
+    I64,
     F32,
     F64,
     VecF32,
@@ -138,9 +181,69 @@ pub enum PmtKind {
     Any,
 }
 
+impl fmt::Display for PmtKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            PmtKind::Null => "Null",
+            PmtKind::String => "String",
+            PmtKind::U32 => "U32",
+            PmtKind::U64 => "U64",
+            PmtKind::I32 => "I32",
+            PmtKind::I64 => "I64",
+            PmtKind::F32 => "F32",
+            PmtKind::F64 => "F64",
+            PmtKind::VecF32 => "VecF32",
+            PmtKind::VecU64 => "VecU64",
+            PmtKind::Blob => "Blob",
+            PmtKind::VecPmt => "VecPmt",
+            PmtKind::MapStrPmt => "MapStrPmt",
+            PmtKind::Any => "Any",
+        };
+        write!(f, "{s}")
+    }
+}
+
+impl FromStr for PmtKind {
+    type Err = PmtConversionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "null" => Ok(PmtKind::Null),
+            "string" => Ok(PmtKind::String),
+            "u32" => Ok(PmtKind::U32),
+            "u64" => Ok(PmtKind::U64),
+            "i32" => Ok(PmtKind::I32),
+            "i64" => Ok(PmtKind::I64),
+            "f32" => Ok(PmtKind::F32),
+            "f64" => Ok(PmtKind::F64),
+            "vecf32" => Ok(PmtKind::VecF32),
+            "vecu64" => Ok(PmtKind::VecU64),
+            "blob" => Ok(PmtKind::Blob),
+            "vecpmt" => Ok(PmtKind::VecPmt),
+            "mapstrpmt" => Ok(PmtKind::MapStrPmt),
+            "any" => Ok(PmtKind::Any),
+            _ => Err(PmtConversionError),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PmtConversionError;
+
+impl fmt::Display for PmtConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PmtConversionError")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PmtConversionError {}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use alloc::borrow::ToOwned;
+    use alloc::vec;
 
     #[test]
     fn pmt() {
@@ -185,6 +288,35 @@ mod test {
         assert_ne!(f1, f3);
     }
 
+    #[test]
+    fn pmt_kind_from_str() {
+        assert_eq!(PmtKind::from_str("F64"), Ok(PmtKind::F64));
+        assert_eq!(PmtKind::from_str("u32"), Ok(PmtKind::U32));
+        assert!(PmtKind::from_str("foo").is_err());
+        assert_eq!(PmtKind::U64.to_string(), "U64");
+        assert_eq!(PmtKind::from_str("i64"), Ok(PmtKind::I64));
+        assert_eq!(PmtKind::I32.to_string(), "I32");
+    }
+
+    #[test]
+    fn signed_ints() {
+        assert_eq!(Pmt::I32(-5), Pmt::I32(-5));
+        assert_ne!(Pmt::I32(-5), Pmt::I32(5));
+        assert_ne!(Pmt::I32(5), Pmt::U32(5));
+        assert_ne!(Pmt::I64(5), Pmt::I32(5));
+
+        assert_eq!(
+            Pmt::from_string("-1200", &PmtKind::I32),
+            Some(Pmt::I32(-1200))
+        );
+        assert_eq!(
+            Pmt::from_string("-5000000000", &PmtKind::I64),
+            Some(Pmt::I64(-5_000_000_000))
+        );
+        assert_eq!(Pmt::from_string("-5000000000", &PmtKind::I32), None);
+        assert_eq!(Pmt::from_string("-1", &PmtKind::U32), None);
+    }
+
     #[test]
     fn vec_pmt() {
         let vpmt = Pmt::VecPmt(vec![Pmt::U32(1), Pmt::U32(2)]);
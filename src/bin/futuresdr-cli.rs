@@ -0,0 +1,94 @@
+//! Command line tool to control a FutureSDR runtime through its control port.
+use clap::{Parser, Subcommand};
+use futuresdr::anyhow::{anyhow, Context, Result};
+use futuresdr::async_io::block_on;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::RemoteFlowgraphHandle;
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    /// Control port address
+    #[clap(short, long, default_value = "127.0.0.1:1337")]
+    url: String,
+
+    /// Flowgraph id or name
+    #[clap(short, long, default_value = "0")]
+    fg: String,
+
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// List the blocks of the flowgraph
+    List,
+    /// Describe a block, given by id or instance name
+    Describe { block: String },
+    /// Call a message handler with a JSON-encoded Pmt, e.g., '{"F64": 100e6}' or '"Null"'
+    Call {
+        block: String,
+        port: String,
+        pmt: String,
+    },
+    /// Show the state of all blocks
+    Stats,
+    /// Terminate the flowgraph
+    Terminate,
+}
+
+async fn block_id(handle: &mut RemoteFlowgraphHandle, block: &str) -> Result<usize> {
+    if let Ok(id) = block.parse::<usize>() {
+        return Ok(id);
+    }
+
+    handle
+        .description()
+        .await?
+        .blocks
+        .iter()
+        .find(|b| b.instance_name == block)
+        .map(|b| b.id)
+        .with_context(|| format!("no block named {block}"))
+}
+
+async fn run(args: Args) -> Result<()> {
+    let mut handle = RemoteFlowgraphHandle::new(&args.url, args.fg);
+
+    match args.command {
+        Command::List => {
+            let d = handle.description().await?;
+            for b in d.blocks.iter() {
+                println!("{:>4}  {:<24} {}", b.id, b.instance_name, b.type_name);
+            }
+        }
+        Command::Describe { block } => {
+            let id = block_id(&mut handle, &block).await?;
+            let d = handle.block_description(id).await?;
+            println!("{}", serde_json::to_string_pretty(&d)?);
+        }
+        Command::Call { block, port, pmt } => {
+            let id = block_id(&mut handle, &block).await?;
+            let pmt: Pmt = serde_json::from_str(&pmt).context("invalid Pmt")?;
+            let ret = handle
+                .callback(id, port, pmt)
+                .await
+                .map_err(|e| anyhow!(e))?;
+            println!("{}", serde_json::to_string(&ret)?);
+        }
+        Command::Stats => {
+            let s = handle.stats().await?;
+            println!("{}", serde_json::to_string_pretty(&s)?);
+        }
+        Command::Terminate => {
+            handle.terminate().await?;
+        }
+    }
+
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    block_on(run(Args::parse()))
+}
@@ -0,0 +1,400 @@
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+use futures::FutureExt;
+
+use crate::anyhow::Result;
+use crate::blocks::Crc;
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Samples of the preamble at 2 Msps.
+const PREAMBLE_LEN: usize = 16;
+/// Samples of a preamble and a long message at 2 Msps.
+const MAX_LEN: usize = PREAMBLE_LEN + 2 * 112;
+
+/// Demodulate Mode S messages.
+///
+/// Searches the magnitude of the input, sampled at 2 Msps, for Mode S preambles and demodulates
+/// the following pulse position modulated message with 56 or 112 bits, depending on the downlink
+/// format. Messages with correct parity (CRC-24) are posted as [Pmt::Blob] (7 or 14 bytes).
+/// Messages where the parity is overlaid with the aircraft address, e.g., surveillance replies,
+/// cannot be verified and are dropped.
+///
+/// # Inputs
+///
+/// `in`: Samples at 2 Msps (Complex32), e.g., from a SoapySource tuned to 1090 MHz
+///
+/// # Outputs
+///
+/// **Message**: `out`: Mode S messages ([Pmt::Blob])
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::AdsbDecoder;
+/// use futuresdr::blocks::ModeSDemod;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let demod = fg.add_block(ModeSDemod::new());
+/// let decoder = fg.add_block(AdsbDecoder::new());
+/// fg.connect_message(demod, "out", decoder, "in").unwrap();
+/// ```
+pub struct ModeSDemod {
+    crc: Crc,
+    mag: Vec<f32>,
+}
+
+impl ModeSDemod {
+    pub fn new() -> Block {
+        Block::new(
+            BlockMetaBuilder::new("ModeSDemod").build(),
+            StreamIoBuilder::new().add_input::<Complex32>("in").build(),
+            MessageIoBuilder::<ModeSDemod>::new()
+                .add_output("out")
+                .build(),
+            ModeSDemod {
+                crc: Crc::new(24, 0xfff409, 0, false, false, 0),
+                mag: Vec::new(),
+            },
+        )
+    }
+
+    /// Check for a preamble, i.e., pulses at 0, 1, 3.5, and 4.5 µs, at the start of `m`.
+    fn preamble(m: &[f32]) -> bool {
+        if !(m[0] > m[1]
+            && m[1] < m[2]
+            && m[2] > m[3]
+            && m[3] < m[0]
+            && m[4] < m[0]
+            && m[5] < m[0]
+            && m[6] < m[0]
+            && m[7] > m[8]
+            && m[8] < m[9]
+            && m[9] > m[6])
+        {
+            return false;
+        }
+
+        // samples between the pulses and before the data have to be quiet
+        let high = (m[0] + m[2] + m[7] + m[9]) / 6.0;
+        m[4] < high && m[5] < high && m[11..PREAMBLE_LEN].iter().all(|x| *x < high)
+    }
+
+    /// Demodulate the message at the start of `m`, returning it if the parity is correct.
+    fn demod(&self, m: &[f32]) -> Option<Vec<u8>> {
+        if m.len() < PREAMBLE_LEN + 2 * 56 || !Self::preamble(m) {
+            return None;
+        }
+
+        // pulse position modulation with one bit per µs
+        let bit = |i: usize| {
+            let k = PREAMBLE_LEN + 2 * i;
+            (m[k] > m[k + 1]) as u8
+        };
+        let df = (0..5).fold(0, |acc, i| (acc << 1) | bit(i));
+        let bits = if df >= 16 { 112 } else { 56 };
+        if m.len() < PREAMBLE_LEN + 2 * bits {
+            return None;
+        }
+
+        let msg: Vec<u8> = (0..bits / 8)
+            .map(|b| (0..8).fold(0, |acc, i| (acc << 1) | bit(8 * b + i)))
+            .collect();
+        let n = msg.len() - 3;
+        if self.crc.encode(&msg[..n]) == msg[n..] {
+            Some(msg)
+        } else {
+            None
+        }
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for ModeSDemod {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex32>();
+        let finished = sio.input(0).finished();
+
+        // keep the samples of a complete message, unless the stream is finished
+        let n = if finished {
+            i.len()
+        } else {
+            i.len().saturating_sub(MAX_LEN - 1)
+        };
+
+        self.mag.clear();
+        self.mag.extend(i.iter().map(|x| x.norm()));
+
+        let mut msgs = Vec::new();
+        let mut k = 0;
+        while k < n {
+            let end = std::cmp::min(k + MAX_LEN, self.mag.len());
+            if let Some(msg) = self.demod(&self.mag[k..end]) {
+                k += PREAMBLE_LEN + 16 * msg.len();
+                msgs.push(Pmt::Blob(msg));
+            } else {
+                k += 1;
+            }
+        }
+
+        sio.input(0).consume(std::cmp::min(k, i.len()));
+
+        if finished {
+            io.finished = true;
+        }
+
+        for m in msgs {
+            mio.post(0, m).await;
+        }
+
+        Ok(())
+    }
+}
+
+/// Latest CPR-encoded position of an aircraft.
+#[derive(Clone, Copy, Default)]
+struct Cpr {
+    even: Option<(f64, f64)>,
+    odd: Option<(f64, f64)>,
+}
+
+/// Number of longitude zones for a latitude.
+fn cpr_nl(lat: f64) -> f64 {
+    let lat = lat.abs();
+    if lat < 1e-9 {
+        59.0
+    } else if (lat - 87.0).abs() < 1e-9 {
+        2.0
+    } else if lat > 87.0 {
+        1.0
+    } else {
+        let a = 1.0 - (PI / 30.0).cos();
+        let b = (PI / 180.0 * lat).cos().powi(2);
+        (2.0 * PI / (1.0 - a / b).acos()).floor()
+    }
+}
+
+fn modulo(x: f64, m: f64) -> f64 {
+    x - m * (x / m).floor()
+}
+
+/// Globally decode an airborne position from an even and an odd CPR position.
+fn cpr_decode(even: (f64, f64), odd: (f64, f64), odd_latest: bool) -> Option<(f64, f64)> {
+    let (lat0, lon0) = even;
+    let (lat1, lon1) = odd;
+
+    let j = (59.0 * lat0 - 60.0 * lat1 + 0.5).floor();
+    let mut rlat0 = 360.0 / 60.0 * (modulo(j, 60.0) + lat0);
+    let mut rlat1 = 360.0 / 59.0 * (modulo(j, 59.0) + lat1);
+    if rlat0 >= 270.0 {
+        rlat0 -= 360.0;
+    }
+    if rlat1 >= 270.0 {
+        rlat1 -= 360.0;
+    }
+
+    let nl = cpr_nl(rlat0);
+    if nl != cpr_nl(rlat1) {
+        // positions in different latitude zones
+        return None;
+    }
+
+    let m = (lon0 * (nl - 1.0) - lon1 * nl + 0.5).floor();
+    let (lat, lon) = if odd_latest {
+        let ni = (nl - 1.0).max(1.0);
+        (rlat1, 360.0 / ni * (modulo(m, ni) + lon1))
+    } else {
+        let ni = nl.max(1.0);
+        (rlat0, 360.0 / ni * (modulo(m, ni) + lon0))
+    };
+
+    Some((lat, if lon >= 180.0 { lon - 360.0 } else { lon }))
+}
+
+/// Read `len` bits of the message, starting with bit `start` (MSB first, zero-based).
+fn bits(msg: &[u8], start: usize, len: usize) -> u64 {
+    (start..start + len).fold(0, |acc, i| {
+        (acc << 1) | ((msg[i / 8] >> (7 - i % 8)) & 1) as u64
+    })
+}
+
+/// Decode ADS-B messages.
+///
+/// Decodes extended squitters (downlink format 17 and 18), as posted by the [ModeSDemod] block,
+/// and posts the contained information as [Pmt::MapStrPmt] with the entries
+/// - `icao`: aircraft address as hex string ([Pmt::String]),
+/// - `type`: `identification`, `position`, or `velocity` ([Pmt::String]).
+///
+/// Depending on the type, the following entries are added:
+/// - identification: `callsign` ([Pmt::String]),
+/// - position: `altitude` in ft and, once an even and an odd position were received from the
+///   aircraft, `latitude` and `longitude` in degrees ([Pmt::F64]),
+/// - velocity: `speed` in knots, `heading` in degrees, and `vertical_rate` in ft/min
+///   ([Pmt::F64]).
+///
+/// Positions are decoded globally from the latest even and odd airborne position of the
+/// aircraft. Other messages, surface positions, and airspeed-based velocities are dropped.
+///
+/// # Inputs
+///
+/// **Message**: `in`: Mode S messages ([Pmt::Blob])
+///
+/// # Outputs
+///
+/// **Message**: `out`: Aircraft information ([Pmt::MapStrPmt])
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::AdsbDecoder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let decoder = fg.add_block(AdsbDecoder::new());
+/// ```
+pub struct AdsbDecoder {
+    aircraft: HashMap<u32, Cpr>,
+}
+
+impl AdsbDecoder {
+    pub fn new() -> Block {
+        Block::new(
+            BlockMetaBuilder::new("AdsbDecoder").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_input(
+                    "in",
+                    |block: &mut AdsbDecoder,
+                     mio: &mut MessageIo<AdsbDecoder>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            if let Pmt::Blob(v) = p {
+                                if let Some(m) = block.decode(&v) {
+                                    mio.post(0, Pmt::MapStrPmt(m)).await;
+                                }
+                            } else {
+                                warn!("AdsbDecoder: received wrong PMT type. {:?}", p);
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .add_output("out")
+                .build(),
+            AdsbDecoder {
+                aircraft: HashMap::new(),
+            },
+        )
+    }
+
+    fn decode(&mut self, msg: &[u8]) -> Option<HashMap<String, Pmt>> {
+        if msg.len() != 14 || !matches!(bits(msg, 0, 5), 17 | 18) {
+            return None;
+        }
+
+        let icao = bits(msg, 8, 24) as u32;
+        let mut m = HashMap::new();
+        m.insert("icao".to_string(), Pmt::String(format!("{icao:06X}")));
+
+        // message extended squitter starts at bit 32
+        match bits(msg, 32, 5) {
+            1..=4 => {
+                const CHARS: &[u8] =
+                    b"#ABCDEFGHIJKLMNOPQRSTUVWXYZ##### ###############0123456789######";
+                let callsign: String = (0..8)
+                    .map(|i| CHARS[bits(msg, 40 + 6 * i, 6) as usize] as char)
+                    .collect();
+                m.insert(
+                    "type".to_string(),
+                    Pmt::String("identification".to_string()),
+                );
+                m.insert(
+                    "callsign".to_string(),
+                    Pmt::String(callsign.trim_end().to_string()),
+                );
+            }
+            9..=18 => {
+                m.insert("type".to_string(), Pmt::String("position".to_string()));
+
+                // altitude with 25 ft resolution, Gillham-coded altitudes are not supported
+                let alt = bits(msg, 40, 12);
+                if alt & 0x10 != 0 {
+                    let n = ((alt & 0xfe0) >> 1) | (alt & 0x0f);
+                    m.insert("altitude".to_string(), Pmt::F64(n as f64 * 25.0 - 1000.0));
+                }
+
+                let odd = bits(msg, 53, 1) == 1;
+                let cpr = (
+                    bits(msg, 54, 17) as f64 / 131072.0,
+                    bits(msg, 71, 17) as f64 / 131072.0,
+                );
+                let state = self.aircraft.entry(icao).or_default();
+                if odd {
+                    state.odd = Some(cpr);
+                } else {
+                    state.even = Some(cpr);
+                }
+                if let (Some(even), Some(odd_cpr)) = (state.even, state.odd) {
+                    if let Some((lat, lon)) = cpr_decode(even, odd_cpr, odd) {
+                        m.insert("latitude".to_string(), Pmt::F64(lat));
+                        m.insert("longitude".to_string(), Pmt::F64(lon));
+                    }
+                }
+            }
+            19 => {
+                let subtype = bits(msg, 37, 3);
+                if !(1..=2).contains(&subtype) {
+                    return None;
+                }
+                let scale = if subtype == 2 { 4.0 } else { 1.0 };
+                let v_ew = bits(msg, 46, 10);
+                let v_ns = bits(msg, 57, 10);
+                if v_ew == 0 || v_ns == 0 {
+                    return None;
+                }
+                let sign = |b: u64| if b == 1 { -1.0 } else { 1.0 };
+                let vx = sign(bits(msg, 45, 1)) * (v_ew - 1) as f64 * scale;
+                let vy = sign(bits(msg, 56, 1)) * (v_ns - 1) as f64 * scale;
+                let heading = modulo(vx.atan2(vy).to_degrees(), 360.0);
+
+                m.insert("type".to_string(), Pmt::String("velocity".to_string()));
+                m.insert("speed".to_string(), Pmt::F64(vx.hypot(vy)));
+                m.insert("heading".to_string(), Pmt::F64(heading));
+                let vr = bits(msg, 69, 9);
+                if vr != 0 {
+                    m.insert(
+                        "vertical_rate".to_string(),
+                        Pmt::F64(sign(bits(msg, 68, 1)) * (vr - 1) as f64 * 64.0),
+                    );
+                }
+            }
+            _ => return None,
+        }
+
+        Some(m)
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for AdsbDecoder {}
@@ -0,0 +1,376 @@
+use rustfft::num_complex::Complex32;
+use rustfft::{self, FftPlanner};
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+use crate::anyhow::Result;
+use crate::futuredsp::windows;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Number of estimates discarded after a retune, while buffered samples are still in flight.
+const SETTLE_WINDOWS: usize = 2;
+
+/// Frequency offset estimator of the [Afc] block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AfcEstimator {
+    /// Average phase increment of consecutive samples. Suited for signals with a strong carrier
+    /// or a symmetric spectrum (e.g., FM).
+    PhaseIncrement,
+    /// Peak of the averaged power spectrum, using FFTs of the given size.
+    Fft(usize),
+}
+
+enum Estimator {
+    PhaseIncrement {
+        acc: Complex32,
+        last: Complex32,
+    },
+    Fft {
+        plan: Arc<dyn rustfft::Fft<f32>>,
+        window: Vec<f32>,
+        buf: Vec<Complex32>,
+        scratch: Vec<Complex32>,
+        power: Vec<f32>,
+    },
+}
+
+impl Estimator {
+    fn new(estimator: AfcEstimator) -> Estimator {
+        match estimator {
+            AfcEstimator::PhaseIncrement => Estimator::PhaseIncrement {
+                acc: Complex32::new(0.0, 0.0),
+                last: Complex32::new(0.0, 0.0),
+            },
+            AfcEstimator::Fft(fft_size) => {
+                let mut planner = FftPlanner::<f32>::new();
+                let plan = planner.plan_fft_forward(fft_size);
+                let scratch = vec![Complex32::new(0.0, 0.0); plan.get_inplace_scratch_len()];
+                Estimator::Fft {
+                    plan,
+                    window: windows::hann(fft_size, true)
+                        .into_iter()
+                        .map(|w| w as f32)
+                        .collect(),
+                    buf: Vec::with_capacity(fft_size),
+                    scratch,
+                    power: vec![0.0; fft_size],
+                }
+            }
+        }
+    }
+
+    fn push(&mut self, x: Complex32) {
+        match self {
+            Estimator::PhaseIncrement { acc, last } => {
+                *acc += x * last.conj();
+                *last = x;
+            }
+            Estimator::Fft {
+                plan,
+                window,
+                buf,
+                scratch,
+                power,
+            } => {
+                buf.push(x * window[buf.len()]);
+                if buf.len() == window.len() {
+                    plan.process_with_scratch(buf, scratch);
+                    for (p, b) in power.iter_mut().zip(buf.iter()) {
+                        *p += b.norm_sqr();
+                    }
+                    buf.clear();
+                }
+            }
+        }
+    }
+
+    /// Offset in cycles per sample, resetting the estimator.
+    fn estimate(&mut self) -> f64 {
+        match self {
+            Estimator::PhaseIncrement { acc, .. } => {
+                let f = acc.arg() as f64 / (2.0 * PI);
+                *acc = Complex32::new(0.0, 0.0);
+                f
+            }
+            Estimator::Fft { buf, power, .. } => {
+                let n = power.len();
+                let (k, _) = power
+                    .iter()
+                    .enumerate()
+                    .fold(
+                        (0, f32::MIN),
+                        |(k, m), (i, p)| if *p > m { (i, *p) } else { (k, m) },
+                    );
+                // parabolic interpolation of the log-power peak
+                let (l, c, r) = (
+                    power[(k + n - 1) % n].max(f32::MIN_POSITIVE).ln(),
+                    power[k].max(f32::MIN_POSITIVE).ln(),
+                    power[(k + 1) % n].max(f32::MIN_POSITIVE).ln(),
+                );
+                let d = l - 2.0 * c + r;
+                let delta = if d < 0.0 { 0.5 * (l - r) / d } else { 0.0 };
+                let k = if k >= n / 2 {
+                    k as f64 - n as f64
+                } else {
+                    k as f64
+                };
+                power.iter_mut().for_each(|p| *p = 0.0);
+                buf.clear();
+                (k + delta as f64) / n as f64
+            }
+        }
+    }
+}
+
+/// Automatic frequency correction.
+pub struct Afc {
+    estimator: Estimator,
+    sample_rate: f64,
+    window: usize,
+    alpha: f64,
+    center_frequency: Option<f64>,
+    threshold: f64,
+    n: usize,
+    settle: usize,
+    offset: Option<f64>,
+    phase: f64,
+}
+
+impl Afc {
+    pub fn new(
+        sample_rate: f64,
+        estimator: AfcEstimator,
+        window: usize,
+        alpha: f64,
+        center_frequency: Option<f64>,
+        threshold: f64,
+    ) -> Block {
+        if let AfcEstimator::Fft(fft_size) = estimator {
+            assert!(fft_size > 1, "FFT size has to be larger than one");
+            assert!(
+                window >= fft_size,
+                "window has to be at least as long as the FFT"
+            );
+        }
+        assert!(window > 0, "window must be positive");
+        assert!(alpha > 0.0 && alpha <= 1.0, "alpha has to be in (0, 1]");
+
+        Block::new(
+            BlockMetaBuilder::new("Afc").build(),
+            StreamIoBuilder::new()
+                .add_input::<Complex32>("in")
+                .add_output::<Complex32>("out")
+                .build(),
+            MessageIoBuilder::<Afc>::new()
+                .add_output("freq")
+                .add_output("offset")
+                .build(),
+            Afc {
+                estimator: Estimator::new(estimator),
+                sample_rate,
+                window,
+                alpha,
+                center_frequency,
+                threshold,
+                n: 0,
+                settle: 0,
+                offset: None,
+                phase: 0.0,
+            },
+        )
+    }
+
+    /// Update the offset with a new estimate in Hz. Returns the new frequency, if the device has
+    /// to be retuned.
+    fn update(&mut self, estimate: f64) -> Option<f64> {
+        let offset = match self.offset {
+            Some(o) => o + self.alpha * (estimate - o),
+            None => estimate,
+        };
+        self.offset = Some(offset);
+
+        match self.center_frequency {
+            Some(ref mut center) if offset.abs() > self.threshold => {
+                *center += offset;
+                self.offset = None;
+                self.settle = SETTLE_WINDOWS;
+                Some(*center)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for Afc {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex32>();
+        let o = sio.output(0).slice::<Complex32>();
+
+        let m = std::cmp::min(i.len(), o.len());
+        let mut offsets = Vec::new();
+        let mut retunes = Vec::new();
+
+        for (x, y) in i[..m].iter().zip(o.iter_mut()) {
+            self.estimator.push(*x);
+            self.n += 1;
+            if self.n == self.window {
+                self.n = 0;
+                let estimate = self.estimator.estimate() * self.sample_rate;
+                if self.settle > 0 {
+                    self.settle -= 1;
+                } else {
+                    retunes.extend(self.update(estimate));
+                    offsets.extend(self.offset);
+                }
+            }
+
+            if self.center_frequency.is_some() {
+                *y = *x;
+            } else {
+                *y = x * Complex32::from_polar(1.0, -self.phase as f32);
+                let inc = 2.0 * PI * self.offset.unwrap_or(0.0) / self.sample_rate;
+                self.phase = (self.phase + inc) % (2.0 * PI);
+            }
+        }
+
+        sio.input(0).consume(m);
+        sio.output(0).produce(m);
+
+        for f in retunes {
+            mio.post(0, Pmt::F64(f)).await;
+        }
+        for o in offsets {
+            mio.post(1, Pmt::F64(o)).await;
+        }
+
+        if sio.input(0).finished() && m == i.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Automatic frequency correction.
+///
+/// Estimates the residual carrier offset over windows of `window` samples (default 8192) and
+/// smooths the estimates with a single-pole filter with gain `alpha` (default 0.2).
+///
+/// By default, the offset is corrected digitally, i.e., the input is mixed down by the current
+/// estimate. If a center frequency is set with [retune](AfcBuilder::retune), samples are passed
+/// through unchanged. Instead, once the offset exceeds the threshold (default 0.1% of the sample
+/// rate), the corrected center frequency is posted, which can be connected to the `freq` port of
+/// a `SoapySource`. The next two estimates are discarded, while
+/// samples of the old frequency are still in flight.
+///
+/// # Inputs
+///
+/// `in`: Input (Complex32)
+///
+/// # Outputs
+///
+/// `out`: Corrected (or unchanged) samples (Complex32)
+///
+/// **Message**: `freq`: Center frequency in Hz ([Pmt::F64]) for retuning the device
+///
+/// **Message**: `offset`: Smoothed frequency offset in Hz ([Pmt::F64]) after each window
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::AfcBuilder;
+/// use futuresdr::blocks::AfcEstimator;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let afc = fg.add_block(
+///     AfcBuilder::new(1e6)
+///         .estimator(AfcEstimator::Fft(1024))
+///         .retune(100e6)
+///         .build(),
+/// );
+/// ```
+pub struct AfcBuilder {
+    sample_rate: f64,
+    estimator: AfcEstimator,
+    window: usize,
+    alpha: f64,
+    center_frequency: Option<f64>,
+    threshold: Option<f64>,
+}
+
+impl AfcBuilder {
+    pub fn new(sample_rate: f64) -> AfcBuilder {
+        AfcBuilder {
+            sample_rate,
+            estimator: AfcEstimator::PhaseIncrement,
+            window: 8192,
+            alpha: 0.2,
+            center_frequency: None,
+            threshold: None,
+        }
+    }
+
+    #[must_use]
+    pub fn estimator(mut self, estimator: AfcEstimator) -> AfcBuilder {
+        self.estimator = estimator;
+        self
+    }
+
+    /// Number of samples per estimate.
+    #[must_use]
+    pub fn window(mut self, window: usize) -> AfcBuilder {
+        self.window = window;
+        self
+    }
+
+    /// Gain of the smoothing filter, one disables smoothing.
+    #[must_use]
+    pub fn alpha(mut self, alpha: f64) -> AfcBuilder {
+        self.alpha = alpha;
+        self
+    }
+
+    /// Retune the device, currently tuned to the given center frequency in Hz, instead of
+    /// correcting the offset digitally.
+    #[must_use]
+    pub fn retune(mut self, center_frequency: f64) -> AfcBuilder {
+        self.center_frequency = Some(center_frequency);
+        self
+    }
+
+    /// Minimum offset in Hz that triggers a retune.
+    #[must_use]
+    pub fn threshold(mut self, threshold: f64) -> AfcBuilder {
+        self.threshold = Some(threshold);
+        self
+    }
+
+    pub fn build(self) -> Block {
+        Afc::new(
+            self.sample_rate,
+            self.estimator,
+            self.window,
+            self.alpha,
+            self.center_frequency,
+            self.threshold.unwrap_or(self.sample_rate * 1e-3),
+        )
+    }
+}
@@ -0,0 +1,96 @@
+use crate::anyhow::Result;
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// AM envelope demodulator.
+///
+/// Outputs the magnitude of the input samples. By default, the DC component (i.e., the carrier)
+/// is removed with a single-pole DC blocker.
+///
+/// # Inputs
+///
+/// `in`: Baseband samples (Complex32), centered at the carrier
+///
+/// # Outputs
+///
+/// `out`: Demodulated signal (f32)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::AmDemod;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let demod = fg.add_block(AmDemod::new());
+/// ```
+pub struct AmDemod {
+    dc_block: bool,
+    avg: f32,
+}
+
+impl AmDemod {
+    /// Create an envelope demodulator that removes the carrier.
+    pub fn new() -> Block {
+        Self::with_dc_block(true)
+    }
+
+    /// Create an envelope demodulator, optionally keeping the DC component.
+    pub fn with_dc_block(dc_block: bool) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("AmDemod").build(),
+            StreamIoBuilder::new()
+                .add_input::<Complex32>("in")
+                .add_output::<f32>("out")
+                .build(),
+            MessageIoBuilder::<AmDemod>::new().build(),
+            AmDemod { dc_block, avg: 0.0 },
+        )
+    }
+}
+
+/// Pole of the DC blocker.
+const ALPHA: f32 = 0.999;
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for AmDemod {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex32>();
+        let o = sio.output(0).slice::<f32>();
+
+        let m = std::cmp::min(i.len(), o.len());
+        for (x, y) in i[..m].iter().zip(o.iter_mut()) {
+            let a = x.norm();
+            if self.dc_block {
+                self.avg = ALPHA * self.avg + (1.0 - ALPHA) * a;
+                *y = a - self.avg;
+            } else {
+                *y = a;
+            }
+        }
+
+        sio.input(0).consume(m);
+        sio.output(0).produce(m);
+
+        if sio.input(0).finished() && m == i.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
@@ -65,7 +65,7 @@ where
 {
     pub fn new(f: F) -> Block {
         Block::new(
-            BlockMetaBuilder::new("Apply").build(),
+            BlockMetaBuilder::new("Apply").in_place().build(),
             StreamIoBuilder::new()
                 .add_input::<A>("in")
                 .add_output::<B>("out")
@@ -95,20 +95,31 @@ where
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
-        let i = sio.input(0).slice::<A>();
-        let o = sio.output(0).slice::<B>();
-
-        let m = std::cmp::min(i.len(), o.len());
-        if m > 0 {
+        let (m, n) = if let Some(s) = sio.in_place_slice::<A>() {
+            // overwrite each item with the result, which has the same size
+            let p = s.as_mut_ptr();
+            for k in 0..s.len() {
+                unsafe {
+                    let r = (self.f)(&*p.add(k));
+                    p.add(k).cast::<B>().write(r);
+                }
+            }
+            (s.len(), s.len())
+        } else {
+            let i = sio.input(0).slice::<A>();
+            let o = sio.output(0).slice::<B>();
             for (v, r) in i.iter().zip(o.iter_mut()) {
                 *r = (self.f)(v);
             }
+            (std::cmp::min(i.len(), o.len()), i.len())
+        };
 
+        if m > 0 {
             sio.input(0).consume(m);
             sio.output(0).produce(m);
         }
 
-        if sio.input(0).finished() && m == i.len() {
+        if sio.input(0).finished() && m == n {
             io.finished = true;
         }
 
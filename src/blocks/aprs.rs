@@ -0,0 +1,195 @@
+use std::collections::HashMap;
+
+use futures::FutureExt;
+
+use crate::blocks::Ax25Frame;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIoBuilder;
+
+/// Position of an APRS report.
+#[derive(Debug, Clone, PartialEq)]
+struct Position {
+    latitude: f64,
+    longitude: f64,
+    symbol: String,
+    comment: String,
+}
+
+/// Parse an uncompressed latitude (`DDMM.mmN`) or longitude (`DDDMM.mmE`), where position
+/// ambiguity is given by spaces.
+fn parse_coordinate(s: &str, degrees: usize, positive: char, negative: char) -> Option<f64> {
+    let s = s.replace(' ', "0");
+    let (value, hemisphere) = s.split_at(s.len() - 1);
+    if value.len() != degrees + 5 || value.as_bytes()[degrees + 2] != b'.' {
+        return None;
+    }
+    let deg: f64 = value[..degrees].parse().ok()?;
+    let min: f64 = value[degrees..].parse().ok()?;
+    if min >= 60.0 {
+        return None;
+    }
+    let v = deg + min / 60.0;
+    match hemisphere.chars().next()? {
+        c if c == positive => Some(v),
+        c if c == negative => Some(-v),
+        _ => None,
+    }
+}
+
+/// Decode four base-91 digits.
+fn base91(s: &[u8]) -> Option<f64> {
+    s.iter().try_fold(0.0, |acc, c| {
+        (33..=123).contains(c).then(|| acc * 91.0 + (c - 33) as f64)
+    })
+}
+
+/// Parse a position without timestamp or message, i.e., the information field after the data
+/// type identifier and timestamp.
+fn parse_position(s: &[u8]) -> Option<Position> {
+    let first = *s.first()?;
+    if first.is_ascii_digit() || first == b' ' {
+        if s.len() < 19 {
+            return None;
+        }
+        let text = std::str::from_utf8(&s[..19]).ok()?;
+        let latitude = parse_coordinate(&text[0..8], 2, 'N', 'S')?;
+        let longitude = parse_coordinate(&text[9..18], 3, 'E', 'W')?;
+        if latitude.abs() > 90.0 || longitude.abs() > 180.0 {
+            return None;
+        }
+        Some(Position {
+            latitude,
+            longitude,
+            symbol: format!("{}{}", s[8] as char, s[18] as char),
+            comment: String::from_utf8_lossy(&s[19..]).to_string(),
+        })
+    } else {
+        // compressed position
+        if s.len() < 13 {
+            return None;
+        }
+        Some(Position {
+            latitude: 90.0 - base91(&s[1..5])? / 380926.0,
+            longitude: -180.0 + base91(&s[5..9])? / 190463.0,
+            symbol: format!("{}{}", s[0] as char, s[9] as char),
+            comment: String::from_utf8_lossy(&s[13..]).to_string(),
+        })
+    }
+}
+
+/// Parse the position of an APRS report.
+fn parse(frame: &Ax25Frame) -> Option<HashMap<String, Pmt>> {
+    let info = &frame.info;
+    let (position, timestamp) = match *info.first()? {
+        b'!' | b'=' => (parse_position(&info[1..])?, None),
+        b'/' | b'@' => {
+            if info.len() < 8 {
+                return None;
+            }
+            let timestamp = std::str::from_utf8(&info[1..8]).ok()?.to_string();
+            (parse_position(&info[8..])?, Some(timestamp))
+        }
+        _ => return None,
+    };
+
+    let mut map = HashMap::new();
+    map.insert("source".to_string(), Pmt::String(frame.source.clone()));
+    map.insert(
+        "destination".to_string(),
+        Pmt::String(frame.destination.clone()),
+    );
+    map.insert(
+        "path".to_string(),
+        Pmt::VecPmt(frame.path.iter().map(|p| Pmt::String(p.clone())).collect()),
+    );
+    map.insert("latitude".to_string(), Pmt::F64(position.latitude));
+    map.insert("longitude".to_string(), Pmt::F64(position.longitude));
+    map.insert("symbol".to_string(), Pmt::String(position.symbol));
+    map.insert("comment".to_string(), Pmt::String(position.comment));
+    if let Some(t) = timestamp {
+        map.insert("timestamp".to_string(), Pmt::String(t));
+    }
+    Some(map)
+}
+
+/// Parse APRS position reports.
+///
+/// Parses AX.25 frames, as posted by the [Ax25Decoder](crate::blocks::Ax25Decoder), and posts
+/// position reports with or without timestamp, in uncompressed or compressed format, as
+/// [Pmt::MapStrPmt] with the entries
+/// - `source`, `destination`: addresses ([Pmt::String]),
+/// - `path`: digipeater path ([Pmt::VecPmt] of [Pmt::String]),
+/// - `latitude`, `longitude`: position in degrees, north and east positive ([Pmt::F64]),
+/// - `symbol`: symbol table and code ([Pmt::String]),
+/// - `comment`: remaining information field ([Pmt::String]),
+/// - `timestamp`: timestamp as sent, e.g., `092345z`, if present ([Pmt::String]).
+///
+/// Other frames, including Mic-E reports, are dropped.
+///
+/// # Inputs
+///
+/// **Message**: `in`: AX.25 frames without FCS ([Pmt::Blob])
+///
+/// # Outputs
+///
+/// **Message**: `out`: Position reports ([Pmt::MapStrPmt])
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::AfskDemodBuilder;
+/// use futuresdr::blocks::AprsParser;
+/// use futuresdr::blocks::Ax25Decoder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let demod = fg.add_block(AfskDemodBuilder::new(48000.0).build());
+/// let decoder = fg.add_block(Ax25Decoder::new());
+/// let aprs = fg.add_block(AprsParser::new());
+/// fg.connect_stream(demod, "out", decoder, "in").unwrap();
+/// fg.connect_message(decoder, "out", aprs, "in").unwrap();
+/// ```
+pub struct AprsParser;
+
+impl AprsParser {
+    pub fn new() -> Block {
+        Block::new(
+            BlockMetaBuilder::new("AprsParser").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_input(
+                    "in",
+                    |_block: &mut AprsParser,
+                     mio: &mut MessageIo<AprsParser>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            if let Pmt::Blob(v) = p {
+                                let report = Ax25Frame::decode(&v).as_ref().and_then(parse);
+                                if let Some(report) = report {
+                                    mio.post(0, Pmt::MapStrPmt(report)).await;
+                                }
+                            } else {
+                                warn!("AprsParser: received wrong PMT type. {:?}", p);
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .add_output("out")
+                .build(),
+            AprsParser,
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for AprsParser {}
@@ -0,0 +1,323 @@
+use std::ops;
+
+use futures::FutureExt;
+
+use crate::anyhow::Result;
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Sample type supported by the arithmetic blocks.
+pub trait Arithmetic:
+    Copy + Send + ops::Add<Output = Self> + ops::Sub<Output = Self> + ops::Mul<Output = Self> + 'static
+{
+    /// Parse a constant from a message.
+    fn from_pmt(p: &Pmt) -> Option<Self>;
+}
+
+impl Arithmetic for f32 {
+    fn from_pmt(p: &Pmt) -> Option<Self> {
+        match p {
+            Pmt::F32(v) => Some(*v),
+            Pmt::F64(v) => Some(*v as f32),
+            Pmt::U32(v) => Some(*v as f32),
+            Pmt::U64(v) => Some(*v as f32),
+            _ => None,
+        }
+    }
+}
+
+/// Complex constants are given as real numbers or as [Pmt::VecF32] with real and imaginary part.
+impl Arithmetic for Complex32 {
+    fn from_pmt(p: &Pmt) -> Option<Self> {
+        match p {
+            Pmt::VecF32(v) if v.len() == 2 => Some(Complex32::new(v[0], v[1])),
+            p => f32::from_pmt(p).map(|v| Complex32::new(v, 0.0)),
+        }
+    }
+}
+
+/// Combine samples with a constant.
+struct ConstOp<T: Arithmetic> {
+    value: T,
+    f: fn(T, T) -> T,
+}
+
+impl<T: Arithmetic> ConstOp<T> {
+    fn block(name: &str, value: T, f: fn(T, T) -> T) -> Block {
+        Block::new(
+            BlockMetaBuilder::new(name).build(),
+            StreamIoBuilder::new()
+                .add_input::<T>("in")
+                .add_output::<T>("out")
+                .build(),
+            MessageIoBuilder::<Self>::new()
+                .add_input(
+                    "const",
+                    |block: &mut ConstOp<T>,
+                     _mio: &mut MessageIo<ConstOp<T>>,
+                     meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            if let Some(v) = T::from_pmt(&p) {
+                                block.value = v;
+                            } else {
+                                warn!("{}: received wrong PMT type. {:?}", meta.type_name(), p);
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .build(),
+            ConstOp { value, f },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: Arithmetic> Kernel for ConstOp<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<T>();
+        let o = sio.output(0).slice::<T>();
+
+        let m = std::cmp::min(i.len(), o.len());
+        for (x, y) in i[..m].iter().zip(o.iter_mut()) {
+            *y = (self.f)(*x, self.value);
+        }
+
+        sio.input(0).consume(m);
+        sio.output(0).produce(m);
+
+        if sio.input(0).finished() && m == i.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Combine samples of several streams.
+struct NaryOp<T: Arithmetic> {
+    f: fn(T, T) -> T,
+    n_inputs: usize,
+}
+
+impl<T: Arithmetic> NaryOp<T> {
+    fn block(name: &str, n_inputs: usize, f: fn(T, T) -> T) -> Block {
+        assert!(n_inputs > 0, "at least one input required");
+
+        let mut sio = StreamIoBuilder::new();
+        for i in 0..n_inputs {
+            sio = sio.add_input::<T>(format!("in{i}").as_str());
+        }
+
+        Block::new(
+            BlockMetaBuilder::new(name).build(),
+            sio.add_output::<T>("out").build(),
+            MessageIoBuilder::<Self>::new().build(),
+            NaryOp { f, n_inputs },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: Arithmetic> Kernel for NaryOp<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let inputs: Vec<&[T]> = (0..self.n_inputs)
+            .map(|i| sio.input(i).slice::<T>())
+            .collect();
+        let o = sio.output(0).slice::<T>();
+
+        let m = inputs.iter().map(|i| i.len()).fold(o.len(), std::cmp::min);
+        for (k, y) in o[..m].iter_mut().enumerate() {
+            *y = inputs[1..]
+                .iter()
+                .fold(inputs[0][k], |acc, i| (self.f)(acc, i[k]));
+        }
+
+        for i in 0..self.n_inputs {
+            sio.input(i).consume(m);
+        }
+        sio.output(0).produce(m);
+
+        if (0..self.n_inputs).any(|i| sio.input(i).finished() && m == inputs[i].len()) {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Add a constant to each sample.
+///
+/// # Inputs
+///
+/// `in`: Input (f32 or Complex32)
+///
+/// # Outputs
+///
+/// `out`: Output
+///
+/// **Message**: `const`: Set the constant (see [Arithmetic])
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::AddConst;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let dc = fg.add_block(AddConst::<f32>::new(0.5));
+/// ```
+pub struct AddConst<T: Arithmetic> {
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: Arithmetic> AddConst<T> {
+    pub fn new(value: T) -> Block {
+        ConstOp::<T>::block("AddConst", value, |a, b| a + b)
+    }
+}
+
+/// Multiply each sample with a constant.
+///
+/// # Inputs
+///
+/// `in`: Input (f32 or Complex32)
+///
+/// # Outputs
+///
+/// `out`: Output
+///
+/// **Message**: `const`: Set the constant (see [Arithmetic])
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::MultiplyConst;
+/// use futuresdr::num_complex::Complex32;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let gain = fg.add_block(MultiplyConst::<Complex32>::new(Complex32::new(0.0, 2.0)));
+/// ```
+pub struct MultiplyConst<T: Arithmetic> {
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: Arithmetic> MultiplyConst<T> {
+    pub fn new(value: T) -> Block {
+        ConstOp::<T>::block("MultiplyConst", value, |a, b| a * b)
+    }
+}
+
+/// Add samples of several streams.
+///
+/// # Inputs
+///
+/// `in0`, `in1`, ...: Inputs (f32 or Complex32)
+///
+/// # Outputs
+///
+/// `out`: Sum
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::Add;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let add = fg.add_block(Add::<f32>::new(3));
+/// ```
+pub struct Add<T: Arithmetic> {
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: Arithmetic> Add<T> {
+    pub fn new(n_inputs: usize) -> Block {
+        NaryOp::<T>::block("Add", n_inputs, |a, b| a + b)
+    }
+}
+
+/// Subtract samples of further streams from the first stream.
+///
+/// # Inputs
+///
+/// `in0`, `in1`, ...: Inputs (f32 or Complex32)
+///
+/// # Outputs
+///
+/// `out`: `in0 - in1 - ...`
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::Subtract;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let sub = fg.add_block(Subtract::<f32>::new(2));
+/// ```
+pub struct Subtract<T: Arithmetic> {
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: Arithmetic> Subtract<T> {
+    pub fn new(n_inputs: usize) -> Block {
+        NaryOp::<T>::block("Subtract", n_inputs, |a, b| a - b)
+    }
+}
+
+/// Multiply samples of several streams.
+///
+/// # Inputs
+///
+/// `in0`, `in1`, ...: Inputs (f32 or Complex32)
+///
+/// # Outputs
+///
+/// `out`: Product
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::Multiply;
+/// use futuresdr::num_complex::Complex32;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let mixer = fg.add_block(Multiply::<Complex32>::new(2));
+/// ```
+pub struct Multiply<T: Arithmetic> {
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: Arithmetic> Multiply<T> {
+    pub fn new(n_inputs: usize) -> Block {
+        NaryOp::<T>::block("Multiply", n_inputs, |a, b| a * b)
+    }
+}
@@ -0,0 +1,209 @@
+use crate::num_complex::Complex;
+use std::fmt::Debug;
+
+use crate::anyhow::{bail, Result};
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Compare samples, allowing for an absolute tolerance.
+pub trait ApproxEq: Debug {
+    /// Check if the distance between the samples is at most `tolerance`.
+    fn approx_eq(&self, other: &Self, tolerance: f64) -> bool;
+
+    /// Distance between the samples, used to report deviations.
+    ///
+    /// Defaults to `0` for equal and infinity for different samples.
+    fn distance(&self, other: &Self) -> f64 {
+        if self.approx_eq(other, 0.0) {
+            0.0
+        } else {
+            f64::INFINITY
+        }
+    }
+}
+
+macro_rules! impl_approx_eq_int {
+    ($($t:ty),*) => {
+        $(
+            impl ApproxEq for $t {
+                fn approx_eq(&self, other: &Self, tolerance: f64) -> bool {
+                    self == other || (*self as f64 - *other as f64).abs() <= tolerance
+                }
+
+                fn distance(&self, other: &Self) -> f64 {
+                    (*self as f64 - *other as f64).abs()
+                }
+            }
+        )*
+    };
+}
+impl_approx_eq_int!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+macro_rules! impl_approx_eq_float {
+    ($($t:ty),*) => {
+        $(
+            impl ApproxEq for $t {
+                fn approx_eq(&self, other: &Self, tolerance: f64) -> bool {
+                    self == other || (*self as f64 - *other as f64).abs() <= tolerance
+                }
+
+                fn distance(&self, other: &Self) -> f64 {
+                    (*self as f64 - *other as f64).abs()
+                }
+            }
+
+            impl ApproxEq for Complex<$t> {
+                fn approx_eq(&self, other: &Self, tolerance: f64) -> bool {
+                    self == other || (self - other).norm() as f64 <= tolerance
+                }
+
+                fn distance(&self, other: &Self) -> f64 {
+                    (self - other).norm() as f64
+                }
+            }
+        )*
+    };
+}
+impl_approx_eq_float!(f32, f64);
+
+impl ApproxEq for bool {
+    fn approx_eq(&self, other: &Self, _tolerance: f64) -> bool {
+        self == other
+    }
+}
+
+/// Assert that the received samples match the expected samples.
+pub struct AssertSink<T> {
+    expected: Vec<T>,
+    tolerance: f64,
+    n_received: usize,
+}
+
+impl<T: ApproxEq + Send + 'static> AssertSink<T> {
+    /// Assert that the received samples are equal to the expected samples.
+    pub fn new(expected: Vec<T>) -> Block {
+        AssertSinkBuilder::new(expected).build()
+    }
+
+    /// Number of samples that were received and checked.
+    pub fn received(&self) -> usize {
+        self.n_received
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: ApproxEq + Send + 'static> Kernel for AssertSink<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<T>();
+
+        for (n, v) in (self.n_received..).zip(i.iter()) {
+            match self.expected.get(n) {
+                Some(e) if v.approx_eq(e, self.tolerance) => {}
+                Some(e) => bail!(
+                    "AssertSink: sample {} is {:?}, expected {:?} (tolerance {})",
+                    n,
+                    v,
+                    e,
+                    self.tolerance
+                ),
+                None => bail!(
+                    "AssertSink: received more than the {} expected samples",
+                    self.expected.len()
+                ),
+            }
+        }
+
+        self.n_received += i.len();
+        sio.input(0).consume(i.len());
+
+        if sio.input(0).finished() {
+            if self.n_received < self.expected.len() {
+                bail!(
+                    "AssertSink: received {} samples, expected {}",
+                    self.n_received,
+                    self.expected.len()
+                );
+            }
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Build an [AssertSink].
+///
+/// Fails the flowgraph, if a received sample deviates from the expected sample by more than the
+/// tolerance (default: 0) or if the number of samples does not match. The error states the index
+/// of the first mismatch.
+///
+/// # Inputs
+///
+/// `in`: Samples
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::AssertSinkBuilder;
+/// use futuresdr::blocks::VectorSource;
+/// use futuresdr::runtime::Flowgraph;
+/// use futuresdr::runtime::Runtime;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let src = fg.add_block(VectorSource::<f32>::new(vec![1.0, 2.0, 3.0]));
+/// let snk = fg.add_block(
+///     AssertSinkBuilder::new(vec![1.0f32, 2.0, 3.001])
+///         .tolerance(0.01)
+///         .build(),
+/// );
+/// fg.connect_stream(src, "out", snk, "in").unwrap();
+///
+/// assert!(Runtime::new().run(fg).is_ok());
+/// ```
+pub struct AssertSinkBuilder<T> {
+    expected: Vec<T>,
+    tolerance: f64,
+}
+
+impl<T: ApproxEq + Send + 'static> AssertSinkBuilder<T> {
+    pub fn new(expected: Vec<T>) -> AssertSinkBuilder<T> {
+        AssertSinkBuilder {
+            expected,
+            tolerance: 0.0,
+        }
+    }
+
+    /// Maximum absolute difference between received and expected samples.
+    #[must_use]
+    pub fn tolerance(mut self, tolerance: f64) -> AssertSinkBuilder<T> {
+        self.tolerance = tolerance;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("AssertSink").build(),
+            StreamIoBuilder::new().add_input::<T>("in").build(),
+            MessageIoBuilder::new().build(),
+            AssertSink {
+                expected: self.expected,
+                tolerance: self.tolerance,
+                n_received: 0,
+            },
+        )
+    }
+}
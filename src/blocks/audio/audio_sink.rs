@@ -1,18 +1,21 @@
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use cpal::BufferSize;
-use cpal::SampleRate;
 use cpal::Stream;
-use cpal::StreamConfig;
 use futures::channel::mpsc;
+use futures::FutureExt;
 use futures::SinkExt;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
 
 use crate::anyhow::Result;
+use crate::blocks::audio::device;
 use crate::runtime::Block;
 use crate::runtime::BlockMeta;
 use crate::runtime::BlockMetaBuilder;
 use crate::runtime::Kernel;
 use crate::runtime::MessageIo;
 use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
 use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
 use crate::runtime::WorkIo;
@@ -20,34 +23,59 @@ use crate::runtime::WorkIo;
 /// Audio Sink.
 #[allow(clippy::type_complexity)]
 pub struct AudioSink {
-    sample_rate: u32,
+    device: Option<String>,
+    sample_rate: Option<u32>,
     channels: u16,
     stream: Option<Stream>,
     min_buffer_size: usize,
     vec: Vec<f32>,
     tx: Option<mpsc::Sender<Vec<f32>>>,
+    underruns: Arc<AtomicU64>,
+    reported: u64,
+    finished: Arc<AtomicBool>,
 }
 
 #[allow(clippy::non_send_fields_in_send_ty)]
 unsafe impl Send for AudioSink {}
 
 const QUEUE_SIZE: usize = 5;
-const STANDARD_RATES: [u32; 4] = [24000, 44100, 48000, 96000];
 
 impl AudioSink {
     #[allow(clippy::new_ret_no_self)]
     pub fn new(sample_rate: u32, channels: u16) -> Block {
+        AudioSinkBuilder::new()
+            .sample_rate(sample_rate)
+            .channels(channels)
+            .build()
+    }
+
+    fn create(device: Option<String>, sample_rate: Option<u32>, channels: u16) -> Block {
+        assert!(channels > 0, "number of channels must be positive");
+
         Block::new(
             BlockMetaBuilder::new("AudioSink").build(),
             StreamIoBuilder::new().add_input::<f32>("in").build(),
-            MessageIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_input(
+                    "status",
+                    |block: &mut AudioSink,
+                     _mio: &mut MessageIo<AudioSink>,
+                     _meta: &mut BlockMeta,
+                     _p: Pmt| { async move { Ok(block.status()) }.boxed() },
+                )
+                .add_output("underrun")
+                .build(),
             AudioSink {
+                device,
                 sample_rate,
                 channels,
                 stream: None,
                 min_buffer_size: 2048,
                 vec: Vec::new(),
                 tx: None,
+                underruns: Arc::new(AtomicU64::new(0)),
+                reported: 0,
+                finished: Arc::new(AtomicBool::new(false)),
             },
         )
     }
@@ -64,27 +92,38 @@ impl AudioSink {
     }
 
     pub fn supported_sample_rates() -> Vec<u32> {
-        if let Some(d) = cpal::default_host().default_output_device() {
-            if let Ok(configs) = d.supported_output_configs() {
-                let mut v = Vec::new();
-                for c in configs {
-                    let min = c.min_sample_rate().0;
-                    let max = c.max_sample_rate().0;
-                    if min >= 10000 {
-                        v.push(min);
-                    }
-                    if max >= 10000 {
-                        v.push(max);
-                    }
+        cpal::default_host()
+            .default_output_device()
+            .map(|d| device::supported_sample_rates(&d, false, None))
+            .unwrap_or_default()
+    }
 
-                    v.extend(STANDARD_RATES.iter().filter(|x| *x >= &min && *x <= &max));
-                }
-                v.sort();
-                v.dedup();
-                return v;
-            }
+    /// Names of the output devices.
+    pub fn devices() -> Vec<String> {
+        device::device_names(false)
+    }
+
+    /// Sample rate of the stream, negotiated with the device during initialization.
+    pub fn sample_rate(&self) -> Option<u32> {
+        self.stream.as_ref().and(self.sample_rate)
+    }
+
+    /// Number of times the device requested samples that were not available.
+    pub fn underruns(&self) -> u64 {
+        self.underruns.load(Ordering::Relaxed)
+    }
+
+    fn status(&self) -> Pmt {
+        let mut m = HashMap::new();
+        if let Some(d) = &self.device {
+            m.insert("device".to_string(), Pmt::String(d.clone()));
+        }
+        if let Some(r) = self.sample_rate() {
+            m.insert("sample_rate".to_string(), Pmt::U32(r));
         }
-        Vec::new()
+        m.insert("channels".to_string(), Pmt::U32(self.channels as u32));
+        m.insert("underruns".to_string(), Pmt::U64(self.underruns()));
+        Pmt::MapStrPmt(m)
     }
 }
 
@@ -97,46 +136,46 @@ impl Kernel for AudioSink {
         _m: &mut MessageIo<Self>,
         _b: &mut BlockMeta,
     ) -> Result<()> {
-        let host = cpal::default_host();
-        let device = host
-            .default_output_device()
-            .expect("no output device available");
-
-        let config = StreamConfig {
-            channels: self.channels,
-            sample_rate: SampleRate(self.sample_rate),
-            buffer_size: BufferSize::Default,
-        };
+        let device = device::find_device(self.device.as_deref(), false)?;
+        let config = device::negotiate(&device, false, self.sample_rate, self.channels)?;
+        self.device = device.name().ok();
+        self.sample_rate = Some(config.sample_rate.0);
 
         let (tx, mut rx) = mpsc::channel(QUEUE_SIZE);
         let mut iter: Option<Vec<f32>> = None;
+        let mut started = false;
+        let underruns = self.underruns.clone();
+        let finished = self.finished.clone();
+
+        let stream = device.build_output_stream(
+            &config,
+            move |data: &mut [f32], _: &cpal::OutputCallbackInfo| {
+                let mut i = 0;
 
-        let stream = device
-            .build_output_stream(
-                &config,
-                move |data: &mut [f32], _: &cpal::OutputCallbackInfo| {
-                    let mut i = 0;
-
-                    while let Some(mut v) =
-                        iter.take().or_else(|| rx.try_next().ok().and_then(|x| x))
-                    {
-                        let n = std::cmp::min(v.len(), data.len() - i);
-                        data[i..i + n].copy_from_slice(&v[..n]);
-                        i += n;
-
-                        if n < v.len() {
-                            iter = Some(v.split_off(n));
-                            return;
-                        } else if i == data.len() {
-                            return;
-                        }
+                while let Some(mut v) = iter.take().or_else(|| rx.try_next().ok().and_then(|x| x)) {
+                    started = true;
+                    let n = std::cmp::min(v.len(), data.len() - i);
+                    data[i..i + n].copy_from_slice(&v[..n]);
+                    i += n;
+
+                    if n < v.len() {
+                        iter = Some(v.split_off(n));
+                        return;
+                    } else if i == data.len() {
+                        return;
                     }
-                },
-                move |err| {
-                    panic!("cpal stream error {err:?}");
-                },
-            )
-            .expect("could not build output stream");
+                }
+
+                // play silence, reporting an underrun if the stream is running
+                data[i..].iter_mut().for_each(|x| *x = 0.0);
+                if started && !finished.load(Ordering::Relaxed) {
+                    underruns.fetch_add(1, Ordering::Relaxed);
+                }
+            },
+            move |err| {
+                panic!("cpal stream error {err:?}");
+            },
+        )?;
         // On Windows there is an issue in cpal with
         // shared devices, if the requested configuration
         // does not match the device configuration.
@@ -156,6 +195,7 @@ impl Kernel for AudioSink {
         _m: &mut MessageIo<Self>,
         _b: &mut BlockMeta,
     ) -> Result<()> {
+        self.finished.store(true, Ordering::Relaxed);
         for _ in 0..QUEUE_SIZE {
             let _ = self.tx.as_mut().unwrap().send(Vec::new()).await;
         }
@@ -166,9 +206,16 @@ impl Kernel for AudioSink {
         &mut self,
         io: &mut WorkIo,
         sio: &mut StreamIo,
-        _mio: &mut MessageIo<Self>,
+        mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
+        let underruns = self.underruns();
+        if underruns > self.reported {
+            warn!("AudioSink: {} underrun(s)", underruns - self.reported);
+            self.reported = underruns;
+            mio.post(0, Pmt::U64(underruns)).await;
+        }
+
         let i = sio.input(0).slice::<f32>();
         self.vec.extend_from_slice(i);
 
@@ -189,3 +236,71 @@ impl Kernel for AudioSink {
         Ok(())
     }
 }
+
+/// Play samples on a soundcard.
+///
+/// Plays interleaved samples with the given number of channels (default: 1) on the output device
+/// with the given name or the default device. The sample rate is negotiated with the device
+/// during initialization: if no rate is set, its default rate is used. If the device does not
+/// support the requested rate, the closest supported rate is used and a warning is logged.
+/// [AudioSink::sample_rate] returns the negotiated rate. Use [AudioSink::devices] and
+/// [AudioSink::supported_sample_rates] to find a suitable configuration.
+///
+/// If the flowgraph cannot keep up, the device plays silence. These underruns are logged and
+/// their total number is posted.
+///
+/// # Inputs
+///
+/// `in`: Interleaved samples (f32)
+///
+/// **Message**: `status`: Return the device, the sample rate, the number of channels, and the
+/// number of underruns ([Pmt::MapStrPmt])
+///
+/// # Outputs
+///
+/// **Message**: `underrun`: Total number of underruns ([Pmt::U64]), after new underruns occurred
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::audio::AudioSinkBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let snk = fg.add_block(AudioSinkBuilder::new().sample_rate(48_000).channels(2).build());
+/// ```
+#[derive(Default)]
+pub struct AudioSinkBuilder {
+    device: Option<String>,
+    sample_rate: Option<u32>,
+    channels: Option<u16>,
+}
+
+impl AudioSinkBuilder {
+    pub fn new() -> AudioSinkBuilder {
+        AudioSinkBuilder::default()
+    }
+
+    /// Name of the output device.
+    #[must_use]
+    pub fn device<S: Into<String>>(mut self, device: S) -> AudioSinkBuilder {
+        self.device = Some(device.into());
+        self
+    }
+
+    #[must_use]
+    pub fn sample_rate(mut self, sample_rate: u32) -> AudioSinkBuilder {
+        self.sample_rate = Some(sample_rate);
+        self
+    }
+
+    #[must_use]
+    pub fn channels(mut self, channels: u16) -> AudioSinkBuilder {
+        self.channels = Some(channels);
+        self
+    }
+
+    pub fn build(self) -> Block {
+        AudioSink::create(self.device, self.sample_rate, self.channels.unwrap_or(1))
+    }
+}
@@ -0,0 +1,349 @@
+use futures::channel::mpsc;
+use futures::channel::oneshot;
+use futures::FutureExt;
+use futures::SinkExt;
+use futures::StreamExt;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use wasm_bindgen::JsValue;
+use web_sys::AudioContext;
+use web_sys::AudioWorkletNode;
+use web_sys::AudioWorkletNodeOptions;
+use web_sys::MessagePort;
+
+use crate::anyhow::{anyhow, Result};
+use crate::blocks::audio::web;
+use crate::blocks::audio::web::js_err;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Number of buffers that are queued in the audio processor.
+const QUEUE_SIZE: usize = 5;
+
+async fn open(
+    sample_rate: Option<u32>,
+    channels: u16,
+) -> Result<(AudioContext, AudioWorkletNode, MessagePort)> {
+    let ctx = web::audio_context(sample_rate).await?;
+
+    let processor = js_sys::Object::new();
+    js_sys::Reflect::set(&processor, &"channels".into(), &channels.into()).map_err(js_err)?;
+    let options = AudioWorkletNodeOptions::new();
+    options.set_number_of_inputs(0);
+    options.set_number_of_outputs(1);
+    options.set_output_channel_count(&js_sys::Array::of1(&channels.into()));
+    options.set_processor_options(Some(&processor));
+
+    let node =
+        AudioWorkletNode::new_with_options(&ctx, "futuresdr-sink", &options).map_err(js_err)?;
+    node.connect_with_audio_node(&ctx.destination())
+        .map_err(js_err)?;
+    let port = node.port().map_err(js_err)?;
+    Ok((ctx, node, port))
+}
+
+/// Pass buffers to the audio processor, keeping at most [QUEUE_SIZE] buffers queued.
+///
+/// JavaScript objects cannot be shared between threads, so the audio context lives in a local
+/// task. After the block dropped its sender, the queued buffers are played and the context is
+/// closed.
+async fn play(
+    sample_rate: Option<u32>,
+    channels: u16,
+    mut rx: mpsc::Receiver<Vec<f32>>,
+    underruns: Arc<AtomicU64>,
+    ready: oneshot::Sender<Result<u32>>,
+) {
+    let (ctx, node, port) = match open(sample_rate, channels).await {
+        Ok(x) => {
+            let _ = ready.send(Ok(x.0.sample_rate() as u32));
+            x
+        }
+        Err(e) => {
+            let _ = ready.send(Err(e));
+            return;
+        }
+    };
+
+    let (events_tx, mut events) = mpsc::unbounded::<JsValue>();
+    let handler = web::on_message(&port, move |v| {
+        let _ = events_tx.unbounded_send(v);
+    });
+
+    let mut queued = 0;
+    let mut finished = false;
+    loop {
+        let event = if finished {
+            if queued == 0 {
+                break;
+            }
+            events.next().await
+        } else if queued < QUEUE_SIZE {
+            futures::select! {
+                v = rx.next() => {
+                    match v {
+                        Some(v) => {
+                            let a = js_sys::Float32Array::from(&v[..]);
+                            if let Err(e) =
+                                port.post_message_with_transferable(&a, &js_sys::Array::of1(&a.buffer()))
+                            {
+                                warn!("AudioSink: could not pass samples to the processor {:?}", e);
+                                break;
+                            }
+                            queued += 1;
+                        }
+                        None => finished = true,
+                    }
+                    continue;
+                },
+                e = events.next() => e,
+            }
+        } else {
+            events.next().await
+        };
+
+        match event.and_then(|e| e.as_string()).as_deref() {
+            Some("consumed") => queued -= 1,
+            Some("underrun") => {
+                if !finished {
+                    underruns.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+            _ => break,
+        }
+    }
+
+    port.set_onmessage(None);
+    drop(handler);
+    let _ = node.disconnect();
+    if let Ok(p) = ctx.close() {
+        let _ = web::wait::<JsValue>(p).await;
+    }
+}
+
+/// Audio Sink.
+pub struct AudioSink {
+    device: Option<String>,
+    sample_rate: Option<u32>,
+    channels: u16,
+    min_buffer_size: usize,
+    vec: Vec<f32>,
+    tx: Option<mpsc::Sender<Vec<f32>>>,
+    underruns: Arc<AtomicU64>,
+    reported: u64,
+}
+
+impl AudioSink {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(sample_rate: u32, channels: u16) -> Block {
+        AudioSinkBuilder::new()
+            .sample_rate(sample_rate)
+            .channels(channels)
+            .build()
+    }
+
+    fn create(device: Option<String>, sample_rate: Option<u32>, channels: u16) -> Block {
+        assert!(channels > 0, "number of channels must be positive");
+
+        Block::new(
+            BlockMetaBuilder::new("AudioSink").build(),
+            StreamIoBuilder::new().add_input::<f32>("in").build(),
+            MessageIoBuilder::new()
+                .add_input(
+                    "status",
+                    |block: &mut AudioSink,
+                     _mio: &mut MessageIo<AudioSink>,
+                     _meta: &mut BlockMeta,
+                     _p: Pmt| { async move { Ok(block.status()) }.boxed() },
+                )
+                .add_output("underrun")
+                .build(),
+            AudioSink {
+                device,
+                sample_rate,
+                channels,
+                min_buffer_size: 2048,
+                vec: Vec::new(),
+                tx: None,
+                underruns: Arc::new(AtomicU64::new(0)),
+                reported: 0,
+            },
+        )
+    }
+
+    /// Sample rate of the audio output of the browser.
+    pub fn default_sample_rate() -> Option<u32> {
+        let ctx = AudioContext::new().ok()?;
+        let rate = ctx.sample_rate() as u32;
+        let _ = ctx.close();
+        Some(rate)
+    }
+
+    /// Sample rate of the audio context, created during initialization.
+    pub fn sample_rate(&self) -> Option<u32> {
+        self.tx.as_ref().and(self.sample_rate)
+    }
+
+    /// Number of times the processor requested samples that were not available.
+    pub fn underruns(&self) -> u64 {
+        self.underruns.load(Ordering::Relaxed)
+    }
+
+    fn status(&self) -> Pmt {
+        let mut m = HashMap::new();
+        if let Some(r) = self.sample_rate() {
+            m.insert("sample_rate".to_string(), Pmt::U32(r));
+        }
+        m.insert("channels".to_string(), Pmt::U32(self.channels as u32));
+        m.insert("underruns".to_string(), Pmt::U64(self.underruns()));
+        Pmt::MapStrPmt(m)
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for AudioSink {
+    async fn init(
+        &mut self,
+        _s: &mut StreamIo,
+        _m: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        if let Some(d) = &self.device {
+            warn!(
+                "AudioSink: cannot select output device {} in the browser, using the default device",
+                d
+            );
+        }
+
+        let (tx, rx) = mpsc::channel(0);
+        let (ready_tx, ready_rx) = oneshot::channel();
+        wasm_bindgen_futures::spawn_local(play(
+            self.sample_rate,
+            self.channels,
+            rx,
+            self.underruns.clone(),
+            ready_tx,
+        ));
+        self.sample_rate = Some(
+            ready_rx
+                .await
+                .map_err(|_| anyhow!("AudioSink: audio task stopped"))??,
+        );
+        self.tx = Some(tx);
+
+        Ok(())
+    }
+
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let underruns = self.underruns();
+        if underruns > self.reported {
+            warn!("AudioSink: {} underrun(s)", underruns - self.reported);
+            self.reported = underruns;
+            mio.post(0, Pmt::U64(underruns)).await;
+        }
+
+        let i = sio.input(0).slice::<f32>();
+        self.vec.extend_from_slice(i);
+        sio.input(0).consume(i.len());
+
+        let finished = sio.input(0).finished();
+        if self.vec.len() >= self.min_buffer_size || (finished && !self.vec.is_empty()) {
+            self.tx
+                .as_mut()
+                .unwrap()
+                .send(std::mem::take(&mut self.vec))
+                .await?;
+        }
+
+        if finished {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Play samples in the browser, using the Web Audio API.
+///
+/// Plays interleaved samples with the given number of channels (default: 1). The samples are
+/// passed to an `AudioWorklet`, which runs on the audio thread of the browser. If a sample rate
+/// is set, the browser resamples to the rate of the output device; otherwise, the rate of the
+/// output device is used. The output device cannot be selected.
+///
+/// Browsers only play audio after the user interacted with the page. Start the flowgraph from a
+/// user gesture, like a button click.
+///
+/// If the flowgraph cannot keep up, the browser plays silence. These underruns are logged and
+/// their total number is posted.
+///
+/// # Inputs
+///
+/// `in`: Interleaved samples (f32)
+///
+/// **Message**: `status`: Return the sample rate, the number of channels, and the number of
+/// underruns ([Pmt::MapStrPmt])
+///
+/// # Outputs
+///
+/// **Message**: `underrun`: Total number of underruns ([Pmt::U64]), after new underruns occurred
+///
+/// # Usage
+/// ```ignore
+/// use futuresdr::blocks::audio::AudioSinkBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let snk = fg.add_block(AudioSinkBuilder::new().sample_rate(48_000).channels(2).build());
+/// ```
+#[derive(Default)]
+pub struct AudioSinkBuilder {
+    device: Option<String>,
+    sample_rate: Option<u32>,
+    channels: Option<u16>,
+}
+
+impl AudioSinkBuilder {
+    pub fn new() -> AudioSinkBuilder {
+        AudioSinkBuilder::default()
+    }
+
+    /// Name of the output device. Not supported in the browser, which uses the default device.
+    #[must_use]
+    pub fn device<S: Into<String>>(mut self, device: S) -> AudioSinkBuilder {
+        self.device = Some(device.into());
+        self
+    }
+
+    #[must_use]
+    pub fn sample_rate(mut self, sample_rate: u32) -> AudioSinkBuilder {
+        self.sample_rate = Some(sample_rate);
+        self
+    }
+
+    #[must_use]
+    pub fn channels(mut self, channels: u16) -> AudioSinkBuilder {
+        self.channels = Some(channels);
+        self
+    }
+
+    pub fn build(self) -> Block {
+        AudioSink::create(self.device, self.sample_rate, self.channels.unwrap_or(1))
+    }
+}
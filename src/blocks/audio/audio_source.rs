@@ -1,30 +1,39 @@
-use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use cpal::BufferSize;
-use cpal::SampleRate;
+use cpal::traits::{DeviceTrait, StreamTrait};
 use cpal::Stream;
-use cpal::StreamConfig;
+use futures::FutureExt;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 
 use crate::anyhow::Result;
+use crate::blocks::audio::device;
 use crate::runtime::Block;
 use crate::runtime::BlockMeta;
 use crate::runtime::BlockMetaBuilder;
 use crate::runtime::Kernel;
 use crate::runtime::MessageIo;
 use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
 use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
 use crate::runtime::WorkIo;
 use futures::channel::mpsc;
 use futures::StreamExt;
 
+/// Number of buffers that are queued, before the device overruns.
+const QUEUE_SIZE: usize = 32;
+
 /// Audio Source.
 #[allow(clippy::type_complexity)]
 pub struct AudioSource {
-    sample_rate: u32,
+    device: Option<String>,
+    sample_rate: Option<u32>,
     channels: u16,
     stream: Option<Stream>,
-    rx: Option<mpsc::UnboundedReceiver<Vec<f32>>>,
+    rx: Option<mpsc::Receiver<Vec<f32>>>,
     buff: Option<(Vec<f32>, usize)>,
+    overruns: Arc<AtomicU64>,
+    reported: u64,
 }
 
 #[allow(clippy::non_send_fields_in_send_ty)]
@@ -33,19 +42,75 @@ unsafe impl Send for AudioSource {}
 impl AudioSource {
     #[allow(clippy::new_ret_no_self)]
     pub fn new(sample_rate: u32, channels: u16) -> Block {
+        AudioSourceBuilder::new()
+            .sample_rate(sample_rate)
+            .channels(channels)
+            .build()
+    }
+
+    fn create(device: Option<String>, sample_rate: Option<u32>, channels: u16) -> Block {
+        assert!(channels > 0, "number of channels must be positive");
+
         Block::new(
             BlockMetaBuilder::new("AudioSource").build(),
             StreamIoBuilder::new().add_output::<f32>("out").build(),
-            MessageIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_input(
+                    "status",
+                    |block: &mut AudioSource,
+                     _mio: &mut MessageIo<AudioSource>,
+                     _meta: &mut BlockMeta,
+                     _p: Pmt| { async move { Ok(block.status()) }.boxed() },
+                )
+                .add_output("overrun")
+                .build(),
             AudioSource {
+                device,
                 sample_rate,
                 channels,
                 stream: None,
                 rx: None,
                 buff: None,
+                overruns: Arc::new(AtomicU64::new(0)),
+                reported: 0,
             },
         )
     }
+
+    /// Names of the input devices.
+    pub fn devices() -> Vec<String> {
+        device::device_names(true)
+    }
+
+    /// Sample rates supported by the default input device.
+    pub fn supported_sample_rates() -> Vec<u32> {
+        device::find_device(None, true)
+            .map(|d| device::supported_sample_rates(&d, true, None))
+            .unwrap_or_default()
+    }
+
+    /// Sample rate of the stream, negotiated with the device during initialization.
+    pub fn sample_rate(&self) -> Option<u32> {
+        self.stream.as_ref().and(self.sample_rate)
+    }
+
+    /// Number of buffers that were dropped, since the flowgraph did not keep up.
+    pub fn overruns(&self) -> u64 {
+        self.overruns.load(Ordering::Relaxed)
+    }
+
+    fn status(&self) -> Pmt {
+        let mut m = HashMap::new();
+        if let Some(d) = &self.device {
+            m.insert("device".to_string(), Pmt::String(d.clone()));
+        }
+        if let Some(r) = self.sample_rate() {
+            m.insert("sample_rate".to_string(), Pmt::U32(r));
+        }
+        m.insert("channels".to_string(), Pmt::U32(self.channels as u32));
+        m.insert("overruns".to_string(), Pmt::U64(self.overruns()));
+        Pmt::MapStrPmt(m)
+    }
 }
 
 #[doc(hidden)]
@@ -57,24 +122,22 @@ impl Kernel for AudioSource {
         _m: &mut MessageIo<Self>,
         _b: &mut BlockMeta,
     ) -> Result<()> {
-        let host = cpal::default_host();
-        let device = host
-            .default_input_device()
-            .expect("no output device available");
+        let device = device::find_device(self.device.as_deref(), true)?;
+        let config = device::negotiate(&device, true, self.sample_rate, self.channels)?;
+        self.device = device.name().ok();
+        self.sample_rate = Some(config.sample_rate.0);
 
-        let config = StreamConfig {
-            channels: self.channels,
-            sample_rate: SampleRate(self.sample_rate),
-            buffer_size: BufferSize::Default,
-        };
-
-        let (tx, rx) = mpsc::unbounded();
+        let (mut tx, rx) = mpsc::channel(QUEUE_SIZE);
+        let overruns = self.overruns.clone();
 
         let stream = device.build_input_stream(
             &config,
-            move |data, _| {
-                let data = data.to_owned();
-                tx.unbounded_send(data).unwrap();
+            move |data: &[f32], _: &cpal::InputCallbackInfo| {
+                if let Err(e) = tx.try_send(data.to_owned()) {
+                    if e.is_full() {
+                        overruns.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
             },
             move |err| {
                 panic!("cpal stream error {err:?}");
@@ -93,9 +156,16 @@ impl Kernel for AudioSource {
         &mut self,
         io: &mut WorkIo,
         sio: &mut StreamIo,
-        _mio: &mut MessageIo<Self>,
+        mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
+        let overruns = self.overruns();
+        if overruns > self.reported {
+            warn!("AudioSource: {} overrun(s)", overruns - self.reported);
+            self.reported = overruns;
+            mio.post(0, Pmt::U64(overruns)).await;
+        }
+
         if let Some((buff, mut full)) = self.buff.take() {
             let o = sio.output(0).slice::<f32>();
             let n = std::cmp::min(o.len(), buff.len() - full);
@@ -124,3 +194,71 @@ impl Kernel for AudioSource {
         Ok(())
     }
 }
+
+/// Record samples from a soundcard.
+///
+/// Records interleaved samples with the given number of channels (default: 1) from the input
+/// device with the given name or the default device. The sample rate is negotiated with the
+/// device during initialization: if no rate is set, its default rate is used. If the device
+/// does not support the requested rate, the closest supported rate is used and a warning is
+/// logged. [AudioSource::sample_rate] returns the negotiated rate. Use [AudioSource::devices]
+/// and [AudioSource::supported_sample_rates] to find a suitable configuration.
+///
+/// If the flowgraph cannot keep up, buffers from the device are dropped. These overruns are
+/// logged and their total number is posted.
+///
+/// # Inputs
+///
+/// **Message**: `status`: Return the device, the sample rate, the number of channels, and the
+/// number of overruns ([Pmt::MapStrPmt])
+///
+/// # Outputs
+///
+/// `out`: Interleaved samples (f32)
+///
+/// **Message**: `overrun`: Total number of overruns ([Pmt::U64]), after new overruns occurred
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::audio::AudioSourceBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let src = fg.add_block(AudioSourceBuilder::new().sample_rate(48_000).build());
+/// ```
+#[derive(Default)]
+pub struct AudioSourceBuilder {
+    device: Option<String>,
+    sample_rate: Option<u32>,
+    channels: Option<u16>,
+}
+
+impl AudioSourceBuilder {
+    pub fn new() -> AudioSourceBuilder {
+        AudioSourceBuilder::default()
+    }
+
+    /// Name of the input device.
+    #[must_use]
+    pub fn device<S: Into<String>>(mut self, device: S) -> AudioSourceBuilder {
+        self.device = Some(device.into());
+        self
+    }
+
+    #[must_use]
+    pub fn sample_rate(mut self, sample_rate: u32) -> AudioSourceBuilder {
+        self.sample_rate = Some(sample_rate);
+        self
+    }
+
+    #[must_use]
+    pub fn channels(mut self, channels: u16) -> AudioSourceBuilder {
+        self.channels = Some(channels);
+        self
+    }
+
+    pub fn build(self) -> Block {
+        AudioSource::create(self.device, self.sample_rate, self.channels.unwrap_or(1))
+    }
+}
@@ -0,0 +1,395 @@
+use futures::channel::mpsc;
+use futures::channel::oneshot;
+use futures::FutureExt;
+use futures::StreamExt;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use wasm_bindgen::JsCast;
+use wasm_bindgen::JsValue;
+use web_sys::AudioContext;
+use web_sys::AudioWorkletNode;
+use web_sys::AudioWorkletNodeOptions;
+use web_sys::ChannelCountMode;
+use web_sys::MediaDeviceInfo;
+use web_sys::MediaDeviceKind;
+use web_sys::MediaDevices;
+use web_sys::MediaStream;
+use web_sys::MediaStreamConstraints;
+use web_sys::MediaStreamTrack;
+use web_sys::MediaTrackConstraints;
+use web_sys::MessagePort;
+
+use crate::anyhow::{anyhow, Result};
+use crate::blocks::audio::web;
+use crate::blocks::audio::web::js_err;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Number of buffers that are queued, before the source overruns.
+const QUEUE_SIZE: usize = 32;
+
+fn media_devices() -> Result<MediaDevices> {
+    web_sys::window()
+        .ok_or_else(|| anyhow!("no window"))?
+        .navigator()
+        .media_devices()
+        .map_err(js_err)
+}
+
+async fn input_devices() -> Result<Vec<MediaDeviceInfo>> {
+    let devices: js_sys::Array =
+        web::wait(media_devices()?.enumerate_devices().map_err(js_err)?).await?;
+    Ok(devices
+        .iter()
+        .map(|d| d.unchecked_into::<MediaDeviceInfo>())
+        .filter(|d| d.kind() == MediaDeviceKind::Audioinput)
+        .collect())
+}
+
+async fn open(
+    device: Option<&str>,
+    sample_rate: Option<u32>,
+    channels: u16,
+) -> Result<(MediaStream, AudioContext, AudioWorkletNode, MessagePort)> {
+    let track = MediaTrackConstraints::new();
+    track.set_channel_count(&(channels as i32).into());
+    // disable the processing for voice calls
+    track.set_echo_cancellation(&false.into());
+    track.set_noise_suppression(&false.into());
+    track.set_auto_gain_control(&false.into());
+    if let Some(name) = device {
+        let id = input_devices()
+            .await?
+            .into_iter()
+            .find(|d| d.label() == name)
+            .ok_or_else(|| anyhow!("audio device {} not found", name))?
+            .device_id();
+        let c = web_sys::ConstrainDomStringParameters::new();
+        c.set_exact(&id.into());
+        track.set_device_id(&c);
+    }
+    let constraints = MediaStreamConstraints::new();
+    constraints.set_audio(&track);
+
+    let stream: MediaStream = web::wait(
+        media_devices()?
+            .get_user_media_with_constraints(&constraints)
+            .map_err(js_err)?,
+    )
+    .await?;
+
+    let ctx = web::audio_context(sample_rate).await?;
+    let src = ctx.create_media_stream_source(&stream).map_err(js_err)?;
+
+    let processor = js_sys::Object::new();
+    js_sys::Reflect::set(&processor, &"channels".into(), &channels.into()).map_err(js_err)?;
+    js_sys::Reflect::set(&processor, &"frames".into(), &web::SOURCE_FRAMES.into())
+        .map_err(js_err)?;
+    let options = AudioWorkletNodeOptions::new();
+    options.set_number_of_inputs(1);
+    options.set_number_of_outputs(1);
+    options.set_channel_count(channels as u32);
+    options.set_channel_count_mode(ChannelCountMode::Explicit);
+    options.set_processor_options(Some(&processor));
+
+    let node =
+        AudioWorkletNode::new_with_options(&ctx, "futuresdr-source", &options).map_err(js_err)?;
+    src.connect_with_audio_node(&node).map_err(js_err)?;
+    // the processor outputs silence, but has to be connected to be scheduled
+    node.connect_with_audio_node(&ctx.destination())
+        .map_err(js_err)?;
+    let port = node.port().map_err(js_err)?;
+
+    Ok((stream, ctx, node, port))
+}
+
+/// Record samples, until the block drops the stop sender.
+///
+/// JavaScript objects cannot be shared between threads, so the audio context lives in a local
+/// task.
+async fn record(
+    device: Option<String>,
+    sample_rate: Option<u32>,
+    channels: u16,
+    mut tx: mpsc::Sender<Vec<f32>>,
+    overruns: Arc<AtomicU64>,
+    ready: oneshot::Sender<Result<u32>>,
+    stop: oneshot::Receiver<()>,
+) {
+    let (stream, ctx, node, port) = match open(device.as_deref(), sample_rate, channels).await {
+        Ok(x) => {
+            let _ = ready.send(Ok(x.1.sample_rate() as u32));
+            x
+        }
+        Err(e) => {
+            let _ = ready.send(Err(e));
+            return;
+        }
+    };
+
+    let handler = web::on_message(&port, move |v| {
+        let data = js_sys::Float32Array::unchecked_from_js(v).to_vec();
+        if let Err(e) = tx.try_send(data) {
+            if e.is_full() {
+                overruns.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    });
+
+    let _ = stop.await;
+
+    port.set_onmessage(None);
+    drop(handler);
+    let _ = node.disconnect();
+    for t in stream.get_tracks().iter() {
+        t.unchecked_into::<MediaStreamTrack>().stop();
+    }
+    if let Ok(p) = ctx.close() {
+        let _ = web::wait::<JsValue>(p).await;
+    }
+}
+
+/// Audio Source.
+pub struct AudioSource {
+    device: Option<String>,
+    sample_rate: Option<u32>,
+    channels: u16,
+    stop: Option<oneshot::Sender<()>>,
+    rx: Option<mpsc::Receiver<Vec<f32>>>,
+    buff: Option<(Vec<f32>, usize)>,
+    overruns: Arc<AtomicU64>,
+    reported: u64,
+}
+
+impl AudioSource {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(sample_rate: u32, channels: u16) -> Block {
+        AudioSourceBuilder::new()
+            .sample_rate(sample_rate)
+            .channels(channels)
+            .build()
+    }
+
+    fn create(device: Option<String>, sample_rate: Option<u32>, channels: u16) -> Block {
+        assert!(channels > 0, "number of channels must be positive");
+
+        Block::new(
+            BlockMetaBuilder::new("AudioSource").build(),
+            StreamIoBuilder::new().add_output::<f32>("out").build(),
+            MessageIoBuilder::new()
+                .add_input(
+                    "status",
+                    |block: &mut AudioSource,
+                     _mio: &mut MessageIo<AudioSource>,
+                     _meta: &mut BlockMeta,
+                     _p: Pmt| { async move { Ok(block.status()) }.boxed() },
+                )
+                .add_output("overrun")
+                .build(),
+            AudioSource {
+                device,
+                sample_rate,
+                channels,
+                stop: None,
+                rx: None,
+                buff: None,
+                overruns: Arc::new(AtomicU64::new(0)),
+                reported: 0,
+            },
+        )
+    }
+
+    /// Names of the input devices.
+    ///
+    /// Browsers only reveal the names, after the page was granted access to a microphone.
+    pub async fn devices() -> Result<Vec<String>> {
+        Ok(input_devices()
+            .await?
+            .into_iter()
+            .map(|d| d.label())
+            .filter(|l| !l.is_empty())
+            .collect())
+    }
+
+    /// Sample rate of the audio context, created during initialization.
+    pub fn sample_rate(&self) -> Option<u32> {
+        self.stop.as_ref().and(self.sample_rate)
+    }
+
+    /// Number of buffers that were dropped, since the flowgraph did not keep up.
+    pub fn overruns(&self) -> u64 {
+        self.overruns.load(Ordering::Relaxed)
+    }
+
+    fn status(&self) -> Pmt {
+        let mut m = HashMap::new();
+        if let Some(d) = &self.device {
+            m.insert("device".to_string(), Pmt::String(d.clone()));
+        }
+        if let Some(r) = self.sample_rate() {
+            m.insert("sample_rate".to_string(), Pmt::U32(r));
+        }
+        m.insert("channels".to_string(), Pmt::U32(self.channels as u32));
+        m.insert("overruns".to_string(), Pmt::U64(self.overruns()));
+        Pmt::MapStrPmt(m)
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for AudioSource {
+    async fn init(
+        &mut self,
+        _s: &mut StreamIo,
+        _m: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        let (tx, rx) = mpsc::channel(QUEUE_SIZE);
+        let (ready_tx, ready_rx) = oneshot::channel();
+        let (stop_tx, stop_rx) = oneshot::channel();
+        wasm_bindgen_futures::spawn_local(record(
+            self.device.clone(),
+            self.sample_rate,
+            self.channels,
+            tx,
+            self.overruns.clone(),
+            ready_tx,
+            stop_rx,
+        ));
+        self.sample_rate = Some(
+            ready_rx
+                .await
+                .map_err(|_| anyhow!("AudioSource: audio task stopped"))??,
+        );
+
+        self.rx = Some(rx);
+        self.stop = Some(stop_tx);
+
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _s: &mut StreamIo,
+        _m: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        self.stop = None;
+        Ok(())
+    }
+
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let overruns = self.overruns();
+        if overruns > self.reported {
+            warn!("AudioSource: {} overrun(s)", overruns - self.reported);
+            self.reported = overruns;
+            mio.post(0, Pmt::U64(overruns)).await;
+        }
+
+        if let Some((buff, mut full)) = self.buff.take() {
+            let o = sio.output(0).slice::<f32>();
+            let n = std::cmp::min(o.len(), buff.len() - full);
+
+            o[..n].copy_from_slice(&buff[full..full + n]);
+            full += n;
+
+            if buff.len() == full {
+                io.call_again = true;
+            } else {
+                self.buff = Some((buff, full));
+            }
+
+            sio.output(0).produce(n);
+        } else if let Some(v) = self.rx.as_mut().unwrap().next().await {
+            io.call_again = true;
+            self.buff = Some((v, 0));
+        } else {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Record samples in the browser, using the Web Audio API.
+///
+/// Records interleaved samples with the given number of channels (default: 1) from the
+/// microphone with the given name or the default microphone. The browser asks the user for
+/// permission, when the block is initialized. Echo cancellation, noise suppression, and
+/// automatic gain control are disabled. If no sample rate is set, the rate of the audio output
+/// is used. Some browsers (e.g., Firefox) fail, if the rate differs from the rate of the
+/// microphone.
+///
+/// If the flowgraph cannot keep up, buffers are dropped. These overruns are logged and their
+/// total number is posted.
+///
+/// # Inputs
+///
+/// **Message**: `status`: Return the device, the sample rate, the number of channels, and the
+/// number of overruns ([Pmt::MapStrPmt])
+///
+/// # Outputs
+///
+/// `out`: Interleaved samples (f32)
+///
+/// **Message**: `overrun`: Total number of overruns ([Pmt::U64]), after new overruns occurred
+///
+/// # Usage
+/// ```ignore
+/// use futuresdr::blocks::audio::AudioSourceBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let src = fg.add_block(AudioSourceBuilder::new().sample_rate(48_000).build());
+/// ```
+#[derive(Default)]
+pub struct AudioSourceBuilder {
+    device: Option<String>,
+    sample_rate: Option<u32>,
+    channels: Option<u16>,
+}
+
+impl AudioSourceBuilder {
+    pub fn new() -> AudioSourceBuilder {
+        AudioSourceBuilder::default()
+    }
+
+    /// Name of the input device.
+    #[must_use]
+    pub fn device<S: Into<String>>(mut self, device: S) -> AudioSourceBuilder {
+        self.device = Some(device.into());
+        self
+    }
+
+    #[must_use]
+    pub fn sample_rate(mut self, sample_rate: u32) -> AudioSourceBuilder {
+        self.sample_rate = Some(sample_rate);
+        self
+    }
+
+    #[must_use]
+    pub fn channels(mut self, channels: u16) -> AudioSourceBuilder {
+        self.channels = Some(channels);
+        self
+    }
+
+    pub fn build(self) -> Block {
+        AudioSource::create(self.device, self.sample_rate, self.channels.unwrap_or(1))
+    }
+}
@@ -0,0 +1,217 @@
+use cpal::traits::{DeviceTrait, HostTrait};
+use cpal::BufferSize;
+use cpal::Device;
+use cpal::SampleRate;
+use cpal::StreamConfig;
+use cpal::SupportedStreamConfigRange;
+
+use crate::anyhow::{anyhow, bail, Result};
+
+/// Names of the input or output devices of the default host.
+pub fn device_names(input: bool) -> Vec<String> {
+    let host = cpal::default_host();
+    let devices = if input {
+        host.input_devices()
+    } else {
+        host.output_devices()
+    };
+    devices
+        .map(|d| d.filter_map(|d| d.name().ok()).collect())
+        .unwrap_or_default()
+}
+
+/// Input or output device with the given name or the default device.
+pub fn find_device(name: Option<&str>, input: bool) -> Result<Device> {
+    let host = cpal::default_host();
+    match name {
+        Some(name) => {
+            let mut devices: Vec<Device> = if input {
+                host.input_devices()?.collect()
+            } else {
+                host.output_devices()?.collect()
+            };
+            let names: Vec<String> = devices
+                .iter()
+                .map(|d| d.name().unwrap_or_default())
+                .collect();
+            Ok(devices.swap_remove(select_device(&names, name)?))
+        }
+        None => {
+            let device = if input {
+                host.default_input_device()
+            } else {
+                host.default_output_device()
+            };
+            device.ok_or_else(|| anyhow!("no default audio device available"))
+        }
+    }
+}
+
+/// Index of the device with the given name.
+fn select_device(names: &[String], name: &str) -> Result<usize> {
+    names
+        .iter()
+        .position(|n| n == name)
+        .ok_or_else(|| anyhow!("audio device {} not found (available: {:?})", name, names))
+}
+
+/// Sample rates that a device supports with the given number of channels.
+pub fn supported_sample_rates(device: &Device, input: bool, channels: Option<u16>) -> Vec<u32> {
+    let configs: Vec<SupportedStreamConfigRange> = if input {
+        device
+            .supported_input_configs()
+            .map(|c| c.collect())
+            .unwrap_or_default()
+    } else {
+        device
+            .supported_output_configs()
+            .map(|c| c.collect())
+            .unwrap_or_default()
+    };
+    sample_rates(&configs, channels)
+}
+
+/// Sample rates of the configurations with the given number of channels.
+///
+/// Devices often report continuous ranges, which are represented by their limits and the
+/// standard rates in between. Rates below 10 kHz are ignored.
+fn sample_rates(configs: &[SupportedStreamConfigRange], channels: Option<u16>) -> Vec<u32> {
+    const STANDARD_RATES: [u32; 4] = [24000, 44100, 48000, 96000];
+
+    let mut v = Vec::new();
+    for c in configs
+        .iter()
+        .filter(|c| channels.map_or(true, |n| c.channels() == n))
+    {
+        let min = c.min_sample_rate().0;
+        let max = c.max_sample_rate().0;
+        v.extend([min, max].iter().filter(|x| **x >= 10000));
+        v.extend(STANDARD_RATES.iter().filter(|x| **x >= min && **x <= max));
+    }
+    v.sort_unstable();
+    v.dedup();
+    v
+}
+
+/// Negotiate the stream configuration with the device.
+///
+/// Uses the default sample rate of the device, if no sample rate is requested. If the device
+/// does not support the requested sample rate, the closest supported rate is used and a
+/// warning is logged. Fails, if the device does not support the given number of channels.
+pub fn negotiate(
+    device: &Device,
+    input: bool,
+    sample_rate: Option<u32>,
+    channels: u16,
+) -> Result<StreamConfig> {
+    let requested = match sample_rate {
+        Some(r) => r,
+        None if input => device.default_input_config()?.sample_rate().0,
+        None => device.default_output_config()?.sample_rate().0,
+    };
+
+    let configs: Vec<SupportedStreamConfigRange> = if input {
+        device.supported_input_configs()?.collect()
+    } else {
+        device.supported_output_configs()?.collect()
+    };
+    let config = select_config(&configs, requested, channels)?;
+    if config.sample_rate.0 != requested {
+        warn!(
+            "audio device does not support {} Hz, using {} Hz (supported: {:?} Hz)",
+            requested,
+            config.sample_rate.0,
+            sample_rates(&configs, Some(channels))
+        );
+    }
+    Ok(config)
+}
+
+/// Stream configuration with the given number of channels and the supported sample rate that
+/// is closest to the requested one.
+fn select_config(
+    configs: &[SupportedStreamConfigRange],
+    sample_rate: u32,
+    channels: u16,
+) -> Result<StreamConfig> {
+    let rate = configs
+        .iter()
+        .filter(|c| c.channels() == channels)
+        .map(|c| sample_rate.clamp(c.min_sample_rate().0, c.max_sample_rate().0))
+        .min_by_key(|r| r.abs_diff(sample_rate));
+
+    match rate {
+        Some(r) => Ok(StreamConfig {
+            channels,
+            sample_rate: SampleRate(r),
+            buffer_size: BufferSize::Default,
+        }),
+        None => bail!(
+            "audio device does not support {} channel(s) (supported: {:?})",
+            channels,
+            configs.iter().map(|c| c.channels()).collect::<Vec<_>>()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cpal::SampleFormat;
+    use cpal::SupportedBufferSize;
+
+    fn range(channels: u16, min: u32, max: u32) -> SupportedStreamConfigRange {
+        SupportedStreamConfigRange::new(
+            channels,
+            SampleRate(min),
+            SampleRate(max),
+            SupportedBufferSize::Unknown,
+            SampleFormat::F32,
+        )
+    }
+
+    fn configs() -> Vec<SupportedStreamConfigRange> {
+        vec![
+            range(1, 44100, 44100),
+            range(2, 44100, 44100),
+            range(2, 8000, 48000),
+        ]
+    }
+
+    #[test]
+    fn exact_rate() {
+        let c = select_config(&configs(), 44100, 1).unwrap();
+        assert_eq!(c.channels, 1);
+        assert_eq!(c.sample_rate, SampleRate(44100));
+
+        let c = select_config(&configs(), 32000, 2).unwrap();
+        assert_eq!(c.sample_rate, SampleRate(32000));
+    }
+
+    #[test]
+    fn closest_rate() {
+        let c = select_config(&configs(), 48000, 1).unwrap();
+        assert_eq!(c.sample_rate, SampleRate(44100));
+
+        let c = select_config(&configs(), 96000, 2).unwrap();
+        assert_eq!(c.sample_rate, SampleRate(48000));
+
+        assert!(select_config(&configs(), 48000, 4).is_err());
+        assert!(select_config(&[], 48000, 1).is_err());
+    }
+
+    #[test]
+    fn no_device() {
+        let names = vec!["default".to_string(), "hw:1".to_string()];
+        assert_eq!(select_device(&names, "hw:1").unwrap(), 1);
+        let e = select_device(&names, "hw:2").unwrap_err();
+        assert!(e.to_string().contains("hw:1"));
+        assert!(select_device(&[], "default").is_err());
+    }
+
+    #[test]
+    fn rates() {
+        assert_eq!(sample_rates(&configs(), Some(1)), [44100]);
+        assert_eq!(sample_rates(&configs(), Some(2)), [24000, 44100, 48000]);
+    }
+}
@@ -1,12 +1,24 @@
 //! ## Audio Blocks
-#[cfg(feature = "audio")]
+//!
+//! In the browser, [AudioSink] and [AudioSource] use the Web Audio API.
+#[cfg(all(not(target_arch = "wasm32"), feature = "audio"))]
+mod audio_sink;
+#[cfg(all(target_arch = "wasm32", feature = "audio"))]
+#[path = "audio_sink_web.rs"]
 mod audio_sink;
 #[cfg(feature = "audio")]
-pub use audio_sink::AudioSink;
-#[cfg(feature = "audio")]
+pub use audio_sink::{AudioSink, AudioSinkBuilder};
+#[cfg(all(not(target_arch = "wasm32"), feature = "audio"))]
+mod audio_source;
+#[cfg(all(target_arch = "wasm32", feature = "audio"))]
+#[path = "audio_source_web.rs"]
 mod audio_source;
 #[cfg(feature = "audio")]
-pub use audio_source::AudioSource;
+pub use audio_source::{AudioSource, AudioSourceBuilder};
+#[cfg(all(not(target_arch = "wasm32"), feature = "audio"))]
+mod device;
+#[cfg(all(target_arch = "wasm32", feature = "audio"))]
+mod web;
 
 #[cfg(all(not(target_arch = "wasm32"), feature = "audio"))]
 mod file_source;
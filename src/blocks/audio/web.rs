@@ -0,0 +1,246 @@
+//! Web Audio helpers, shared by the sink and the source.
+//!
+//! Samples are exchanged with `AudioWorkletProcessor`s that run on the audio rendering thread of
+//! the browser. The processors are loaded from a Blob URL, so that no extra JavaScript file has
+//! to be served.
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::AudioContext;
+use web_sys::AudioContextOptions;
+use web_sys::MessageEvent;
+use web_sys::MessagePort;
+
+use crate::anyhow::{anyhow, Result};
+
+/// Frames per buffer, posted by the source processor.
+pub const SOURCE_FRAMES: u32 = 1024;
+
+const PROCESSORS: &str = r#"
+class FutureSdrSink extends AudioWorkletProcessor {
+  constructor(options) {
+    super();
+    this.channels = options.processorOptions.channels;
+    this.queue = [];
+    this.buf = null;
+    this.pos = 0;
+    this.started = false;
+    this.port.onmessage = (e) => this.queue.push(e.data);
+  }
+
+  process(inputs, outputs) {
+    const out = outputs[0];
+    const frames = out[0].length;
+    let i = 0;
+    while (i < frames) {
+      if (this.buf === null) {
+        if (this.queue.length === 0) {
+          break;
+        }
+        this.buf = this.queue.shift();
+        this.pos = 0;
+        this.started = true;
+      }
+      while (i < frames && this.pos < this.buf.length) {
+        for (let c = 0; c < out.length; c++) {
+          out[c][i] = this.buf[this.pos + Math.min(c, this.channels - 1)];
+        }
+        this.pos += this.channels;
+        i++;
+      }
+      if (this.pos >= this.buf.length) {
+        this.buf = null;
+        this.port.postMessage("consumed");
+      }
+    }
+    if (i < frames && this.started) {
+      this.port.postMessage("underrun");
+    }
+    return true;
+  }
+}
+
+class FutureSdrSource extends AudioWorkletProcessor {
+  constructor(options) {
+    super();
+    this.channels = options.processorOptions.channels;
+    this.frames = options.processorOptions.frames;
+    this.buf = new Float32Array(this.frames * this.channels);
+    this.pos = 0;
+  }
+
+  process(inputs) {
+    const input = inputs[0];
+    if (input.length === 0) {
+      return true;
+    }
+    for (let i = 0; i < input[0].length; i++) {
+      for (let c = 0; c < this.channels; c++) {
+        this.buf[this.pos++] = input[Math.min(c, input.length - 1)][i];
+      }
+      if (this.pos === this.buf.length) {
+        this.port.postMessage(this.buf, [this.buf.buffer]);
+        this.buf = new Float32Array(this.frames * this.channels);
+        this.pos = 0;
+      }
+    }
+    return true;
+  }
+}
+
+registerProcessor("futuresdr-sink", FutureSdrSink);
+registerProcessor("futuresdr-source", FutureSdrSource);
+"#;
+
+pub fn js_err(e: JsValue) -> crate::anyhow::Error {
+    anyhow!("{:?}", e)
+}
+
+/// Await a promise.
+pub async fn wait<T: JsCast>(promise: impl Into<JsValue>) -> Result<T> {
+    let v: JsValue = JsFuture::from(js_sys::Promise::unchecked_from_js(promise.into()))
+        .await
+        .map_err(js_err)?;
+    Ok(v.unchecked_into())
+}
+
+/// Create an audio context with the processors of the sink and the source.
+///
+/// Browsers only allow audio after the user interacted with the page, i.e., the flowgraph
+/// should be started from a user gesture, like a button click. Otherwise, the context stays
+/// suspended.
+pub async fn audio_context(sample_rate: Option<u32>) -> Result<AudioContext> {
+    let options = AudioContextOptions::new();
+    if let Some(r) = sample_rate {
+        options.set_sample_rate(r as f32);
+    }
+    let ctx = AudioContext::new_with_context_options(&options).map_err(js_err)?;
+
+    let parts = js_sys::Array::of1(&JsValue::from_str(PROCESSORS));
+    let props = web_sys::BlobPropertyBag::new();
+    props.set_type("application/javascript");
+    let blob = web_sys::Blob::new_with_str_sequence_and_options(&parts, &props).map_err(js_err)?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob).map_err(js_err)?;
+    let ret = wait::<JsValue>(
+        ctx.audio_worklet()
+            .map_err(js_err)?
+            .add_module(&url)
+            .map_err(js_err)?,
+    )
+    .await;
+    let _ = web_sys::Url::revoke_object_url(&url);
+    ret?;
+
+    let _ = ctx.resume();
+    Ok(ctx)
+}
+
+/// Handle the messages of a processor.
+///
+/// The returned closure has to be kept alive, as long as the port is used.
+pub fn on_message(
+    port: &MessagePort,
+    mut f: impl FnMut(JsValue) + 'static,
+) -> Closure<dyn FnMut(MessageEvent)> {
+    let closure = Closure::<dyn FnMut(MessageEvent)>::new(move |e: MessageEvent| f(e.data()));
+    port.set_onmessage(Some(closure.as_ref().unchecked_ref()));
+    closure
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    /// Minimal `AudioWorkletGlobalScope` to run the processors, with helpers that return plain
+    /// arrays.
+    const HARNESS: &str = r#"
+const processors = {};
+class AudioWorkletProcessor {
+  constructor() {
+    this.port = { posted: [], onmessage: null, postMessage(m) { this.posted.push(m); } };
+  }
+}
+const registerProcessor = (name, p) => { processors[name] = p; };
+PROCESSORS
+return {
+  sink(channels, buffers, outputs, frames, calls) {
+    const p = new processors["futuresdr-sink"]({ processorOptions: { channels } });
+    buffers.forEach((b) => p.port.onmessage({ data: Float32Array.from(b) }));
+    const r = [];
+    for (let n = 0; n < calls; n++) {
+      const out = Array.from({ length: outputs }, () => new Float32Array(frames));
+      p.process([], [out]);
+      r.push({ out: out.map((c) => Array.from(c)), posted: p.port.posted.splice(0) });
+    }
+    return r;
+  },
+  source(channels, frames, quanta) {
+    const p = new processors["futuresdr-source"]({ processorOptions: { channels, frames } });
+    return quanta.map((q) => {
+      p.process([q.map((c) => Float32Array.from(c))]);
+      return p.port.posted.splice(0).map((b) => Array.from(b));
+    });
+  },
+};
+"#;
+
+    /// Call a helper of the harness with JSON arguments, returning the JSON result.
+    fn run(helper: &str, args: &str) -> String {
+        let harness = js_sys::Function::new_no_args(&HARNESS.replace("PROCESSORS", PROCESSORS))
+            .call0(&JsValue::NULL)
+            .unwrap();
+        let f: js_sys::Function = js_sys::Reflect::get(&harness, &helper.into())
+            .unwrap()
+            .unchecked_into();
+        let args: js_sys::Array = js_sys::JSON::parse(args).unwrap().unchecked_into();
+        let r = f.apply(&harness, &args).unwrap();
+        js_sys::JSON::stringify(&r).unwrap().into()
+    }
+
+    #[wasm_bindgen_test]
+    fn sink_queue() {
+        // buffers are played back to back, across render quanta
+        assert_eq!(
+            run("sink", "[1, [[1, 2, 3], [4, 5]], 1, 4, 2]"),
+            r#"[{"out":[[1,2,3,4]],"posted":["consumed"]},{"out":[[5,0,0,0]],"posted":["consumed","underrun"]}]"#
+        );
+        // no underruns before the first buffer
+        assert_eq!(
+            run("sink", "[1, [], 1, 2, 1]"),
+            r#"[{"out":[[0,0]],"posted":[]}]"#
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn sink_channels() {
+        // interleaved samples
+        assert_eq!(
+            run("sink", "[2, [[1, -1, 2, -2]], 2, 2, 1]"),
+            r#"[{"out":[[1,2],[-1,-2]],"posted":["consumed"]}]"#
+        );
+        // mono on all outputs
+        assert_eq!(
+            run("sink", "[1, [[1, 2]], 2, 2, 1]"),
+            r#"[{"out":[[1,2],[1,2]],"posted":["consumed"]}]"#
+        );
+        // first channel on a mono output
+        assert_eq!(
+            run("sink", "[2, [[1, -1, 2, -2]], 1, 2, 1]"),
+            r#"[{"out":[[1,2]],"posted":["consumed"]}]"#
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn source_buffers() {
+        // buffers of `frames` interleaved frames, across render quanta
+        assert_eq!(
+            run("source", "[2, 2, [[[1, 2, 3], [4, 5, 6]], [[7], [8]]]]"),
+            "[[[1,4,2,5]],[[3,6,7,8]]]"
+        );
+        // mono input on all channels
+        assert_eq!(run("source", "[2, 1, [[[1, 2]]]]"), "[[[1,1],[2,2]]]");
+        // no input, e.g., before the microphone started
+        assert_eq!(run("source", "[1, 1, [[]]]"), "[[]]");
+    }
+}
@@ -0,0 +1,400 @@
+use futures::FutureExt;
+
+use crate::anyhow::Result;
+use crate::blocks::Crc;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// HDLC flag, which is the same with both bit orders.
+const FLAG: u8 = 0x7e;
+/// Minimum frame length, i.e., two addresses, control field, and FCS.
+const MIN_FRAME_LEN: usize = 2 * 7 + 1 + 2;
+/// Maximum frame length, i.e., ten addresses, control and PID field, 256 bytes of information,
+/// and FCS.
+const MAX_FRAME_LEN: usize = 10 * 7 + 2 + 256 + 2;
+
+/// AX.25 UI frame, as used by APRS.
+///
+/// Addresses are given as callsign with optional SSID, e.g., `N0CALL-9`. A `*` suffix on a path
+/// entry marks it as repeated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ax25Frame {
+    pub destination: String,
+    pub source: String,
+    pub path: Vec<String>,
+    pub info: Vec<u8>,
+}
+
+impl Ax25Frame {
+    fn encode_address(address: &str, command: bool, last: bool) -> Vec<u8> {
+        let (address, repeated) = match address.strip_suffix('*') {
+            Some(a) => (a, true),
+            None => (address, false),
+        };
+        let (call, ssid) = match address.split_once('-') {
+            Some((c, s)) => (c, s.parse::<u8>().unwrap_or(0) & 0x0f),
+            None => (address, 0),
+        };
+
+        let mut v: Vec<u8> = call
+            .bytes()
+            .chain(std::iter::repeat(b' '))
+            .take(6)
+            .map(|c| c.to_ascii_uppercase() << 1)
+            .collect();
+        v.push(((command || repeated) as u8) << 7 | 0x60 | ssid << 1 | last as u8);
+        v
+    }
+
+    fn decode_address(bytes: &[u8], path: bool) -> String {
+        let call: String = bytes[..6]
+            .iter()
+            .map(|b| (b >> 1) as char)
+            .collect::<String>()
+            .trim_end()
+            .to_string();
+        let ssid = (bytes[6] >> 1) & 0x0f;
+        let mut a = if ssid == 0 {
+            call
+        } else {
+            format!("{call}-{ssid}")
+        };
+        if path && bytes[6] & 0x80 != 0 {
+            a.push('*');
+        }
+        a
+    }
+
+    /// Encode the frame without FCS, using control field 0x03 (UI) and PID 0xF0 (no layer 3).
+    pub fn encode(&self) -> Vec<u8> {
+        let mut v = Self::encode_address(&self.destination, true, false);
+        v.extend(Self::encode_address(
+            &self.source,
+            false,
+            self.path.is_empty(),
+        ));
+        for (i, p) in self.path.iter().enumerate() {
+            v.extend(Self::encode_address(p, false, i == self.path.len() - 1));
+        }
+        v.push(0x03);
+        v.push(0xf0);
+        v.extend_from_slice(&self.info);
+        v
+    }
+
+    /// Decode a frame without FCS, returning [None] if it is malformed or not a UI frame.
+    pub fn decode(frame: &[u8]) -> Option<Ax25Frame> {
+        let n_addr = frame.iter().position(|b| b & 1 == 1)? / 7 + 1;
+        if !(2..=10).contains(&n_addr) || frame.len() < n_addr * 7 + 2 {
+            return None;
+        }
+        if frame[..n_addr * 7]
+            .iter()
+            .enumerate()
+            .any(|(i, b)| i % 7 != 6 && b & 1 == 1)
+        {
+            return None;
+        }
+        if frame[n_addr * 7] & 0xef != 0x03 {
+            return None;
+        }
+
+        let addresses: Vec<&[u8]> = frame[..n_addr * 7].chunks(7).collect();
+        Some(Ax25Frame {
+            destination: Self::decode_address(addresses[0], false),
+            source: Self::decode_address(addresses[1], false),
+            path: addresses[2..]
+                .iter()
+                .map(|a| Self::decode_address(a, true))
+                .collect(),
+            info: frame[n_addr * 7 + 2..].to_vec(),
+        })
+    }
+}
+
+/// HDLC-frame AX.25 frames.
+pub struct Ax25Encoder {
+    preamble: usize,
+    postamble: usize,
+    fcs: Crc,
+}
+
+impl Ax25Encoder {
+    fn new(preamble: usize, postamble: usize) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("Ax25Encoder").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_input(
+                    "in",
+                    |block: &mut Ax25Encoder,
+                     mio: &mut MessageIo<Ax25Encoder>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            if let Pmt::Blob(v) = p {
+                                let bits = block.encode(&v);
+                                mio.post(0, Pmt::Blob(bits)).await;
+                            } else {
+                                warn!("Ax25Encoder: received wrong PMT type. {:?}", p);
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .add_output("out")
+                .build(),
+            Ax25Encoder {
+                preamble,
+                postamble,
+                fcs: Crc::crc16_x25(),
+            },
+        )
+    }
+
+    fn encode(&self, frame: &[u8]) -> Vec<u8> {
+        let flag = (0..8).map(|i| (FLAG >> i) & 1);
+
+        let mut bits = Vec::new();
+        for _ in 0..self.preamble {
+            bits.extend(flag.clone());
+        }
+
+        let mut ones = 0;
+        let fcs = self.fcs.encode(frame);
+        for b in frame.iter().chain(fcs.iter()) {
+            for i in 0..8 {
+                let bit = (b >> i) & 1;
+                bits.push(bit);
+                if bit == 1 {
+                    ones += 1;
+                    if ones == 5 {
+                        bits.push(0);
+                        ones = 0;
+                    }
+                } else {
+                    ones = 0;
+                }
+            }
+        }
+
+        for _ in 0..self.postamble {
+            bits.extend(flag.clone());
+        }
+
+        // NRZI: a zero is sent as a change of the level
+        let mut level = 0;
+        for b in bits.iter_mut() {
+            if *b == 0 {
+                level ^= 1;
+            }
+            *b = level;
+        }
+        bits
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for Ax25Encoder {}
+
+/// HDLC-frame AX.25 frames.
+///
+/// Appends the FCS (CRC-16/X-25) to the frame, adds bit stuffing, encloses it in `preamble`
+/// (default 32) and `postamble` (default 2) HDLC flags, and NRZI-encodes the bits, which are sent
+/// least significant bit first. The frame is posted as [Pmt::Blob] with one bit per byte, ready to
+/// be streamed into an [AfskMod](crate::blocks::AfskModBuilder) for Bell 202 AFSK.
+///
+/// # Inputs
+///
+/// **Message**: `in`: AX.25 frames without FCS ([Pmt::Blob]), see [Ax25Frame::encode]
+///
+/// # Outputs
+///
+/// **Message**: `out`: NRZI-encoded bits ([Pmt::Blob])
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::Ax25EncoderBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let encoder = fg.add_block(Ax25EncoderBuilder::new().preamble(64).build());
+/// ```
+pub struct Ax25EncoderBuilder {
+    preamble: usize,
+    postamble: usize,
+}
+
+impl Ax25EncoderBuilder {
+    pub fn new() -> Ax25EncoderBuilder {
+        Ax25EncoderBuilder {
+            preamble: 32,
+            postamble: 2,
+        }
+    }
+
+    /// Number of flags before the frame.
+    #[must_use]
+    pub fn preamble(mut self, flags: usize) -> Ax25EncoderBuilder {
+        self.preamble = flags;
+        self
+    }
+
+    /// Number of flags after the frame.
+    #[must_use]
+    pub fn postamble(mut self, flags: usize) -> Ax25EncoderBuilder {
+        assert!(flags > 0, "at least one closing flag required");
+        self.postamble = flags;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        Ax25Encoder::new(self.preamble, self.postamble)
+    }
+}
+
+impl Default for Ax25EncoderBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Decode HDLC-framed AX.25 frames from a bit stream.
+///
+/// The input bits are NRZI-decoded, so the polarity of the demodulator does not matter. Frames
+/// between HDLC flags are unstuffed and posted without FCS if the FCS (CRC-16/X-25) is correct.
+/// Seven or more consecutive ones abort a frame.
+///
+/// # Inputs
+///
+/// `in`: NRZI-encoded bits (u8), e.g., from an [AfskDemod](crate::blocks::AfskDemodBuilder)
+///
+/// # Outputs
+///
+/// **Message**: `out`: AX.25 frames without FCS ([Pmt::Blob]), see [Ax25Frame::decode]
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::Ax25Decoder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let decoder = fg.add_block(Ax25Decoder::new());
+/// ```
+pub struct Ax25Decoder {
+    fcs: Crc,
+    level: u8,
+    ones: usize,
+    in_frame: bool,
+    bits: Vec<u8>,
+}
+
+impl Ax25Decoder {
+    pub fn new() -> Block {
+        Block::new(
+            BlockMetaBuilder::new("Ax25Decoder").build(),
+            StreamIoBuilder::new().add_input::<u8>("in").build(),
+            MessageIoBuilder::<Ax25Decoder>::new()
+                .add_output("out")
+                .build(),
+            Ax25Decoder {
+                fcs: Crc::crc16_x25(),
+                level: 0,
+                ones: 0,
+                in_frame: false,
+                bits: Vec::new(),
+            },
+        )
+    }
+
+    fn end_of_frame(&mut self, frames: &mut Vec<Pmt>) {
+        // drop the beginning of the flag, i.e., a zero and six ones
+        let n = self.bits.len().saturating_sub(7);
+        if self.in_frame && n % 8 == 0 && n >= 8 * MIN_FRAME_LEN {
+            let mut frame: Vec<u8> = self.bits[..n]
+                .chunks_exact(8)
+                .map(|c| c.iter().rev().fold(0, |acc, b| (acc << 1) | b))
+                .collect();
+            let len = frame.len() - 2;
+            if self.fcs.encode(&frame[..len]) == frame[len..] {
+                frame.truncate(len);
+                frames.push(Pmt::Blob(frame));
+            }
+        }
+        self.bits.clear();
+        self.in_frame = true;
+    }
+
+    fn push(&mut self, level: u8, frames: &mut Vec<Pmt>) {
+        let bit = (level == self.level) as u8;
+        self.level = level;
+
+        if bit == 1 {
+            self.ones += 1;
+            if self.ones > 6 {
+                self.in_frame = false;
+                self.bits.clear();
+                return;
+            }
+        } else {
+            let ones = std::mem::replace(&mut self.ones, 0);
+            if ones == 6 {
+                self.end_of_frame(frames);
+                return;
+            } else if ones == 5 {
+                // stuffed bit
+                return;
+            }
+        }
+
+        if self.in_frame {
+            self.bits.push(bit);
+            if self.bits.len() > 8 * MAX_FRAME_LEN + 7 {
+                self.in_frame = false;
+                self.bits.clear();
+            }
+        }
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for Ax25Decoder {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<u8>();
+
+        let mut frames = Vec::new();
+        for x in i.iter() {
+            self.push(x & 1, &mut frames);
+        }
+        sio.input(0).consume(i.len());
+
+        if sio.input(0).finished() {
+            io.finished = true;
+        }
+
+        for f in frames {
+            mio.post(0, f).await;
+        }
+
+        Ok(())
+    }
+}
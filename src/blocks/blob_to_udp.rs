@@ -5,6 +5,7 @@ use async_net::UdpSocket;
 use futures::FutureExt;
 
 use crate::anyhow::Result;
+use crate::runtime::pdu_pool;
 use crate::runtime::Block;
 use crate::runtime::BlockMeta;
 use crate::runtime::BlockMetaBuilder;
@@ -47,6 +48,7 @@ impl BlobToUdp {
                                 {
                                     Ok(s) => {
                                         assert_eq!(s, v.len());
+                                        pdu_pool::recycle(Pmt::Blob(v));
                                     }
                                     Err(e) => {
                                         println!("udp error: {e:?}");
@@ -0,0 +1,307 @@
+use std::collections::VecDeque;
+
+use crate::anyhow::Result;
+use crate::num_complex::Complex32;
+use crate::runtime::pdu_pool::PooledBlob;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::Tag;
+use crate::runtime::WorkIo;
+
+/// How the [BurstToPdu] block detects bursts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BurstDelimiter {
+    /// [Tag::String] tags at the first and the last sample of a burst.
+    Tags { start: String, end: String },
+    /// Samples with a positive value on the `gate` input belong to a burst.
+    Gate,
+}
+
+enum State {
+    Idle,
+    Burst,
+    Trailing(usize),
+}
+
+/// Cut bursts out of a stream and post them as PDUs.
+pub struct BurstToPdu {
+    delimiter: BurstDelimiter,
+    pre_padding: usize,
+    post_padding: usize,
+    max_len: usize,
+    history: VecDeque<Complex32>,
+    burst: Vec<Complex32>,
+    state: State,
+    gate: bool,
+}
+
+impl BurstToPdu {
+    pub fn new(
+        delimiter: BurstDelimiter,
+        pre_padding: usize,
+        post_padding: usize,
+        max_len: usize,
+    ) -> Block {
+        assert!(max_len > 0, "maximum burst length must be positive");
+
+        let sio = StreamIoBuilder::new().add_input::<Complex32>("in");
+        let sio = match delimiter {
+            BurstDelimiter::Gate => sio.add_input::<f32>("gate"),
+            BurstDelimiter::Tags { .. } => sio,
+        };
+
+        Block::new(
+            BlockMetaBuilder::new("BurstToPdu").build(),
+            sio.build(),
+            MessageIoBuilder::<BurstToPdu>::new()
+                .add_output("out")
+                .build(),
+            BurstToPdu {
+                delimiter,
+                pre_padding,
+                post_padding,
+                max_len,
+                history: VecDeque::with_capacity(pre_padding),
+                burst: Vec::new(),
+                state: State::Idle,
+                gate: false,
+            },
+        )
+    }
+
+    fn start(&mut self) {
+        self.burst.clear();
+        self.burst.extend(self.history.iter());
+        self.state = State::Burst;
+    }
+
+    fn end(&mut self, pdus: &mut Vec<Pmt>) {
+        if self.post_padding > 0 {
+            self.state = State::Trailing(self.post_padding);
+        } else {
+            self.emit(pdus);
+        }
+    }
+
+    fn emit(&mut self, pdus: &mut Vec<Pmt>) {
+        let mut blob = PooledBlob::with_capacity(self.burst.len() * 8);
+        for x in self.burst.drain(..) {
+            blob.extend_from_slice(&x.re.to_le_bytes());
+            blob.extend_from_slice(&x.im.to_le_bytes());
+        }
+        pdus.push(blob.into());
+        self.state = State::Idle;
+    }
+
+    fn push(&mut self, x: Complex32, pdus: &mut Vec<Pmt>) {
+        match self.state {
+            State::Idle => {}
+            State::Burst => {
+                self.burst.push(x);
+                if self.burst.len() >= self.max_len {
+                    // continue the burst in a new PDU
+                    self.emit(pdus);
+                    self.state = State::Burst;
+                }
+            }
+            State::Trailing(ref mut remaining) => {
+                self.burst.push(x);
+                *remaining -= 1;
+                if *remaining == 0 || self.burst.len() >= self.max_len {
+                    self.emit(pdus);
+                }
+            }
+        }
+
+        if self.pre_padding > 0 {
+            if self.history.len() == self.pre_padding {
+                self.history.pop_front();
+            }
+            self.history.push_back(x);
+        }
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for BurstToPdu {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex32>();
+        let mut pdus = Vec::new();
+
+        let (n, finished) = match self.delimiter.clone() {
+            BurstDelimiter::Tags { start, end } => {
+                let n = i.len();
+                let tags = sio.input(0).tags();
+                let is_tag = |k: usize, name: &str| {
+                    tags.iter()
+                        .any(|t| t.index == k && matches!(&t.tag, Tag::String(s) if s == name))
+                };
+                for (k, x) in i.iter().enumerate() {
+                    if is_tag(k, &start) {
+                        if !matches!(self.state, State::Idle) {
+                            self.emit(&mut pdus);
+                        }
+                        self.start();
+                    }
+                    self.push(*x, &mut pdus);
+                    if is_tag(k, &end) && matches!(self.state, State::Burst) {
+                        self.end(&mut pdus);
+                    }
+                }
+                (n, sio.input(0).finished())
+            }
+            BurstDelimiter::Gate => {
+                let g = sio.input(1).slice::<f32>();
+                let n = std::cmp::min(i.len(), g.len());
+                for (x, g) in i[..n].iter().zip(g.iter()) {
+                    let gate = *g > 0.0;
+                    if gate && !self.gate {
+                        if !matches!(self.state, State::Idle) {
+                            self.emit(&mut pdus);
+                        }
+                        self.start();
+                    } else if !gate && self.gate && matches!(self.state, State::Burst) {
+                        self.end(&mut pdus);
+                    }
+                    self.gate = gate;
+                    self.push(*x, &mut pdus);
+                }
+                let finished = (sio.input(0).finished() && n == i.len())
+                    || (sio.input(1).finished() && n == g.len());
+                sio.input(1).consume(n);
+                (n, finished)
+            }
+        };
+
+        sio.input(0).consume(n);
+
+        if finished {
+            if !matches!(self.state, State::Idle) {
+                self.emit(&mut pdus);
+            }
+            io.finished = true;
+        }
+
+        for pdu in pdus {
+            mio.post(0, pdu).await;
+        }
+
+        Ok(())
+    }
+}
+
+/// Cut bursts out of a stream and post them as PDUs.
+///
+/// Bursts are delimited either by [Tag::String] tags at their first and last sample (default:
+/// `squelch_sob` and `squelch_eob`, as added by the
+/// [PowerSquelch](crate::blocks::PowerSquelchBuilder)) or by a gate signal on a second input,
+/// where positive values mark samples of a burst. Optionally, bursts are extended by
+/// `pre_padding` samples before and `post_padding` samples after the burst. Bursts longer than
+/// `max_len` samples (default 65536) are split.
+///
+/// Each burst is posted as [Pmt::Blob] with interleaved real and imaginary parts as
+/// little-endian f32.
+///
+/// # Inputs
+///
+/// `in`: Input samples (Complex32)
+///
+/// `gate`: Gate signal (f32), only if gated
+///
+/// # Outputs
+///
+/// **Message**: `out`: Bursts
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::BurstToPduBuilder;
+/// use futuresdr::blocks::PowerSquelchBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let squelch = fg.add_block(PowerSquelchBuilder::new(-30.0).gate().tag().build());
+/// let bursts = fg.add_block(BurstToPduBuilder::new().padding(16, 16).build());
+/// fg.connect_stream(squelch, "out", bursts, "in").unwrap();
+/// ```
+pub struct BurstToPduBuilder {
+    delimiter: BurstDelimiter,
+    pre_padding: usize,
+    post_padding: usize,
+    max_len: usize,
+}
+
+impl BurstToPduBuilder {
+    pub fn new() -> BurstToPduBuilder {
+        BurstToPduBuilder {
+            delimiter: BurstDelimiter::Tags {
+                start: "squelch_sob".to_string(),
+                end: "squelch_eob".to_string(),
+            },
+            pre_padding: 0,
+            post_padding: 0,
+            max_len: 1 << 16,
+        }
+    }
+
+    /// Delimit bursts with the given start and end tags.
+    #[must_use]
+    pub fn tags(mut self, start: &str, end: &str) -> BurstToPduBuilder {
+        self.delimiter = BurstDelimiter::Tags {
+            start: start.to_string(),
+            end: end.to_string(),
+        };
+        self
+    }
+
+    /// Delimit bursts with a gate signal on a second input.
+    #[must_use]
+    pub fn gate(mut self) -> BurstToPduBuilder {
+        self.delimiter = BurstDelimiter::Gate;
+        self
+    }
+
+    /// Number of samples added before and after each burst.
+    #[must_use]
+    pub fn padding(mut self, pre: usize, post: usize) -> BurstToPduBuilder {
+        self.pre_padding = pre;
+        self.post_padding = post;
+        self
+    }
+
+    /// Maximum number of samples per PDU.
+    #[must_use]
+    pub fn max_len(mut self, max_len: usize) -> BurstToPduBuilder {
+        self.max_len = max_len;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        BurstToPdu::new(
+            self.delimiter,
+            self.pre_padding,
+            self.post_padding,
+            self.max_len,
+        )
+    }
+}
+
+impl Default for BurstToPduBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
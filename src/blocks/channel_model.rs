@@ -0,0 +1,291 @@
+use std::collections::VecDeque;
+use std::f64::consts::PI;
+
+use rand::rngs::StdRng;
+use rand::Rng;
+use rand::SeedableRng;
+
+use crate::anyhow::Result;
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Random number generator, seeded if reproducible results are required.
+pub(crate) fn rng(seed: Option<u64>) -> StdRng {
+    match seed {
+        Some(s) => StdRng::seed_from_u64(s),
+        None => StdRng::from_entropy(),
+    }
+}
+
+/// Circularly-symmetric complex Gaussian sample with unit variance (Box-Muller).
+pub(crate) fn complex_gaussian(rng: &mut StdRng) -> Complex32 {
+    let u: f32 = 1.0 - rng.gen::<f32>();
+    let v: f32 = rng.gen();
+    Complex32::from_polar((-u.ln()).sqrt(), 2.0 * std::f32::consts::PI * v)
+}
+
+/// Channel model with AWGN, frequency and phase offset, timing drift, and multipath.
+pub struct ChannelModel {
+    noise_std: Option<f32>,
+    phase: f64,
+    phase_inc: f64,
+    step: f64,
+    mu: f64,
+    history: [Complex32; 4],
+    taps: Vec<Complex32>,
+    delay_line: VecDeque<Complex32>,
+    rng: StdRng,
+}
+
+impl ChannelModel {
+    pub fn new(
+        snr_db: Option<f32>,
+        frequency_offset: f32,
+        phase_offset: f32,
+        timing_drift: f64,
+        taps: Vec<Complex32>,
+        seed: Option<u64>,
+    ) -> Block {
+        assert!(
+            timing_drift.abs() < 0.5,
+            "timing drift has to be in (-0.5, 0.5)"
+        );
+
+        Block::new(
+            BlockMetaBuilder::new("ChannelModel").build(),
+            StreamIoBuilder::new()
+                .add_input::<Complex32>("in")
+                .add_output::<Complex32>("out")
+                .build(),
+            MessageIoBuilder::<Self>::new().build(),
+            ChannelModel {
+                noise_std: snr_db.map(|s| 10.0f32.powf(-s / 20.0)),
+                phase: phase_offset as f64,
+                phase_inc: 2.0 * PI * frequency_offset as f64,
+                step: 1.0 + timing_drift,
+                mu: 0.0,
+                history: [Complex32::new(0.0, 0.0); 4],
+                delay_line: VecDeque::from(vec![Complex32::new(0.0, 0.0); taps.len()]),
+                taps,
+                rng: rng(seed),
+            },
+        )
+    }
+
+    /// Cubic Lagrange interpolation between `history[1]` and `history[2]`.
+    fn interpolate(&self, mu: f32) -> Complex32 {
+        let [xm1, x0, x1, x2] = self.history;
+        let c1 = -xm1 / 3.0 - x0 / 2.0 + x1 - x2 / 6.0;
+        let c2 = xm1 / 2.0 - x0 + x1 / 2.0;
+        let c3 = -xm1 / 6.0 + x0 / 2.0 - x1 / 2.0 + x2 / 6.0;
+        ((c3 * mu + c2) * mu + c1) * mu + x0
+    }
+
+    fn apply(&mut self, x: Complex32) -> Complex32 {
+        let mut y = x;
+
+        if !self.taps.is_empty() {
+            self.delay_line.pop_back();
+            self.delay_line.push_front(y);
+            y = self
+                .delay_line
+                .iter()
+                .zip(self.taps.iter())
+                .map(|(x, t)| x * t)
+                .sum();
+        }
+
+        if self.phase_inc != 0.0 || self.phase != 0.0 {
+            y *= Complex32::from_polar(1.0, self.phase as f32);
+            self.phase = (self.phase + self.phase_inc) % (2.0 * PI);
+        }
+
+        if let Some(std) = self.noise_std {
+            y += complex_gaussian(&mut self.rng) * std;
+        }
+
+        y
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for ChannelModel {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex32>();
+        let o = sio.output(0).slice::<Complex32>();
+
+        let resample = self.step != 1.0;
+        // maximum number of outputs per input
+        let max_out = if resample {
+            (1.0 / self.step).ceil() as usize + 1
+        } else {
+            1
+        };
+
+        let mut consumed = 0;
+        let mut produced = 0;
+        for x in i.iter() {
+            if o.len() - produced < max_out {
+                break;
+            }
+
+            if resample {
+                self.history.rotate_left(1);
+                self.history[3] = *x;
+                while self.mu < 1.0 {
+                    let y = self.interpolate(self.mu as f32);
+                    o[produced] = self.apply(y);
+                    produced += 1;
+                    self.mu += self.step;
+                }
+                self.mu -= 1.0;
+            } else {
+                o[produced] = self.apply(*x);
+                produced += 1;
+            }
+            consumed += 1;
+        }
+
+        sio.input(0).consume(consumed);
+        sio.output(0).produce(produced);
+
+        if sio.input(0).finished() && consumed == i.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Channel model for end-to-end simulations.
+///
+/// Applies, in this order:
+/// - timing drift: resampling with cubic interpolation, where a drift of `d` yields `1 / (1 + d)`
+///   output samples per input sample (e.g., `1e-4` for 100 ppm),
+/// - multipath: FIR filter with complex taps,
+/// - frequency offset (normalized to the sample rate) and phase offset (in radians),
+/// - AWGN with the given SNR in dB, relative to unit signal power.
+///
+/// By default, the channel is ideal. Optionally, the noise generator is seeded for reproducible
+/// results.
+///
+/// # Inputs
+///
+/// `in`: Input (Complex32)
+///
+/// # Outputs
+///
+/// `out`: Output (Complex32)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::ChannelModelBuilder;
+/// use futuresdr::num_complex::Complex32;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let channel = fg.add_block(
+///     ChannelModelBuilder::new()
+///         .snr(10.0)
+///         .frequency_offset(1e-3)
+///         .taps(vec![Complex32::new(1.0, 0.0), Complex32::new(0.0, 0.3)])
+///         .seed(42)
+///         .build(),
+/// );
+/// ```
+pub struct ChannelModelBuilder {
+    snr_db: Option<f32>,
+    frequency_offset: f32,
+    phase_offset: f32,
+    timing_drift: f64,
+    taps: Vec<Complex32>,
+    seed: Option<u64>,
+}
+
+impl ChannelModelBuilder {
+    pub fn new() -> ChannelModelBuilder {
+        ChannelModelBuilder {
+            snr_db: None,
+            frequency_offset: 0.0,
+            phase_offset: 0.0,
+            timing_drift: 0.0,
+            taps: Vec::new(),
+            seed: None,
+        }
+    }
+
+    /// Add AWGN with the given SNR in dB.
+    #[must_use]
+    pub fn snr(mut self, snr_db: f32) -> ChannelModelBuilder {
+        self.snr_db = Some(snr_db);
+        self
+    }
+
+    /// Frequency offset, normalized to the sample rate.
+    #[must_use]
+    pub fn frequency_offset(mut self, frequency_offset: f32) -> ChannelModelBuilder {
+        self.frequency_offset = frequency_offset;
+        self
+    }
+
+    /// Phase offset in radians.
+    #[must_use]
+    pub fn phase_offset(mut self, phase_offset: f32) -> ChannelModelBuilder {
+        self.phase_offset = phase_offset;
+        self
+    }
+
+    /// Relative sample rate offset between transmitter and receiver.
+    #[must_use]
+    pub fn timing_drift(mut self, timing_drift: f64) -> ChannelModelBuilder {
+        self.timing_drift = timing_drift;
+        self
+    }
+
+    /// Multipath FIR taps.
+    #[must_use]
+    pub fn taps(mut self, taps: Vec<Complex32>) -> ChannelModelBuilder {
+        self.taps = taps;
+        self
+    }
+
+    /// Seed of the noise generator.
+    #[must_use]
+    pub fn seed(mut self, seed: u64) -> ChannelModelBuilder {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn build(self) -> Block {
+        ChannelModel::new(
+            self.snr_db,
+            self.frequency_offset,
+            self.phase_offset,
+            self.timing_drift,
+            self.taps,
+            self.seed,
+        )
+    }
+}
+
+impl Default for ChannelModelBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,86 @@
+use crate::futures::channel::mpsc::Sender;
+use crate::futures::SinkExt;
+
+use crate::anyhow::Result;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Push samples from a stream connection into a channel.
+///
+/// Counterpart of the [ChannelSource](crate::blocks::ChannelSource). Samples are sent in chunks,
+/// as they become available. The channel is closed, when the input is finished, i.e., a connected
+/// [ChannelSource](crate::blocks::ChannelSource) finishes, once it received all samples.
+///
+/// # Inputs
+///
+/// `in`: Samples pushed into the channel
+///
+/// # Usage
+/// ```
+/// use futuresdr::futures::channel::mpsc;
+/// use futuresdr::blocks::ChannelSink;
+/// use futuresdr::blocks::ChannelSource;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+/// let (tx, rx) = mpsc::channel(10);
+///
+/// let snk = fg.add_block(ChannelSink::<u32>::new(tx));
+/// let src = fg.add_block(ChannelSource::<u32>::new(rx));
+/// ```
+pub struct ChannelSink<T: Clone + Send + 'static> {
+    sender: Option<Sender<Box<[T]>>>,
+}
+
+impl<T: Clone + Send + 'static> ChannelSink<T> {
+    pub fn new(sender: Sender<Box<[T]>>) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("ChannelSink").build(),
+            StreamIoBuilder::new().add_input::<T>("in").build(),
+            MessageIoBuilder::new().build(),
+            ChannelSink::<T> {
+                sender: Some(sender),
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: Clone + Send + 'static> Kernel for ChannelSink<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<T>();
+        let n = i.len();
+
+        if n > 0 {
+            if let Some(sender) = self.sender.as_mut() {
+                if sender.send(i.to_vec().into_boxed_slice()).await.is_err() {
+                    debug!("receiver-end of channel was closed");
+                    self.sender = None;
+                }
+            }
+            sio.input(0).consume(n);
+        }
+
+        if sio.input(0).finished() {
+            // closes the channel
+            self.sender = None;
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
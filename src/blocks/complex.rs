@@ -0,0 +1,336 @@
+use crate::anyhow::Result;
+use crate::kernels;
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Interleave real and imaginary parts of complex samples.
+///
+/// # Inputs
+///
+/// `in`: Input (Complex32)
+///
+/// # Outputs
+///
+/// `out`: Interleaved real and imaginary parts (f32)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::Interleave;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let interleave = fg.add_block(Interleave::new());
+/// ```
+pub struct Interleave;
+
+impl Interleave {
+    pub fn new() -> Block {
+        Block::new(
+            BlockMetaBuilder::new("Interleave").build(),
+            StreamIoBuilder::new()
+                .add_input::<Complex32>("in")
+                .add_output::<f32>("out")
+                .build(),
+            MessageIoBuilder::<Self>::new().build(),
+            Interleave,
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for Interleave {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex32>();
+        let o = sio.output(0).slice::<f32>();
+
+        let m = std::cmp::min(i.len(), o.len() / 2);
+        for (x, y) in i[..m].iter().zip(o.chunks_exact_mut(2)) {
+            y[0] = x.re;
+            y[1] = x.im;
+        }
+
+        sio.input(0).consume(m);
+        sio.output(0).produce(2 * m);
+
+        if sio.input(0).finished() && m == i.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Combine interleaved real and imaginary parts to complex samples.
+///
+/// An incomplete sample at the end of the stream is dropped.
+///
+/// # Inputs
+///
+/// `in`: Interleaved real and imaginary parts (f32)
+///
+/// # Outputs
+///
+/// `out`: Output (Complex32)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::Deinterleave;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let deinterleave = fg.add_block(Deinterleave::new());
+/// ```
+pub struct Deinterleave;
+
+impl Deinterleave {
+    pub fn new() -> Block {
+        Block::new(
+            BlockMetaBuilder::new("Deinterleave").build(),
+            StreamIoBuilder::new()
+                .add_input::<f32>("in")
+                .add_output::<Complex32>("out")
+                .build(),
+            MessageIoBuilder::<Self>::new().build(),
+            Deinterleave,
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for Deinterleave {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<f32>();
+        let o = sio.output(0).slice::<Complex32>();
+
+        let m = std::cmp::min(i.len() / 2, o.len());
+        for (x, y) in i.chunks_exact(2).zip(o[..m].iter_mut()) {
+            *y = Complex32::new(x[0], x[1]);
+        }
+
+        sio.input(0).consume(2 * m);
+        sio.output(0).produce(m);
+
+        if sio.input(0).finished() && i.len() - 2 * m < 2 {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Map complex samples to real values, a slice at a time.
+struct ComplexMap {
+    f: fn(&[Complex32], &mut [f32]),
+}
+
+impl ComplexMap {
+    fn block(name: &str, f: fn(&[Complex32], &mut [f32])) -> Block {
+        Block::new(
+            BlockMetaBuilder::new(name).build(),
+            StreamIoBuilder::new()
+                .add_input::<Complex32>("in")
+                .add_output::<f32>("out")
+                .build(),
+            MessageIoBuilder::<Self>::new().build(),
+            ComplexMap { f },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for ComplexMap {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex32>();
+        let o = sio.output(0).slice::<f32>();
+
+        let m = std::cmp::min(i.len(), o.len());
+        (self.f)(&i[..m], &mut o[..m]);
+
+        sio.input(0).consume(m);
+        sio.output(0).produce(m);
+
+        if sio.input(0).finished() && m == i.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Real part of complex samples.
+///
+/// # Inputs
+///
+/// `in`: Input (Complex32)
+///
+/// # Outputs
+///
+/// `out`: Real part (f32)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::ComplexToReal;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let re = fg.add_block(ComplexToReal::new());
+/// ```
+pub struct ComplexToReal;
+
+impl ComplexToReal {
+    pub fn new() -> Block {
+        ComplexMap::block("ComplexToReal", |i, o| {
+            for (x, y) in i.iter().zip(o.iter_mut()) {
+                *y = x.re;
+            }
+        })
+    }
+}
+
+/// Imaginary part of complex samples.
+///
+/// # Inputs
+///
+/// `in`: Input (Complex32)
+///
+/// # Outputs
+///
+/// `out`: Imaginary part (f32)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::ComplexToImag;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let im = fg.add_block(ComplexToImag::new());
+/// ```
+pub struct ComplexToImag;
+
+impl ComplexToImag {
+    pub fn new() -> Block {
+        ComplexMap::block("ComplexToImag", |i, o| {
+            for (x, y) in i.iter().zip(o.iter_mut()) {
+                *y = x.im;
+            }
+        })
+    }
+}
+
+/// Squared magnitude of complex samples.
+///
+/// # Inputs
+///
+/// `in`: Input (Complex32)
+///
+/// # Outputs
+///
+/// `out`: Squared magnitude (f32)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::ComplexToMagSquared;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let power = fg.add_block(ComplexToMagSquared::new());
+/// ```
+pub struct ComplexToMagSquared;
+
+impl ComplexToMagSquared {
+    pub fn new() -> Block {
+        ComplexMap::block("ComplexToMagSquared", kernels::magnitude_squared)
+    }
+}
+
+/// Magnitude of complex samples.
+///
+/// # Inputs
+///
+/// `in`: Input (Complex32)
+///
+/// # Outputs
+///
+/// `out`: Magnitude (f32)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::ComplexToMag;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let envelope = fg.add_block(ComplexToMag::new());
+/// ```
+pub struct ComplexToMag;
+
+impl ComplexToMag {
+    pub fn new() -> Block {
+        ComplexMap::block("ComplexToMag", kernels::magnitude)
+    }
+}
+
+/// Argument of complex samples.
+///
+/// # Inputs
+///
+/// `in`: Input (Complex32)
+///
+/// # Outputs
+///
+/// `out`: Argument in radians, in `[-pi, pi]` (f32)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::ComplexToArg;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let phase = fg.add_block(ComplexToArg::new());
+/// ```
+pub struct ComplexToArg;
+
+impl ComplexToArg {
+    pub fn new() -> Block {
+        ComplexMap::block("ComplexToArg", |i, o| {
+            for (x, y) in i.iter().zip(o.iter_mut()) {
+                *y = x.arg();
+            }
+        })
+    }
+}
@@ -0,0 +1,372 @@
+use std::f32::consts::PI;
+
+use crate::anyhow::Result;
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Constellation, mapping symbols to complex points.
+///
+/// Symbol `s` is mapped to `points()[s]`. The predefined constellations are Gray coded and have
+/// unit average energy.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Constellation {
+    points: Vec<Complex32>,
+    bits_per_symbol: usize,
+}
+
+impl Constellation {
+    /// Create a constellation from its points. The number of points has to be a power of two.
+    pub fn new(points: Vec<Complex32>) -> Constellation {
+        assert!(
+            points.len() >= 2 && points.len().is_power_of_two() && points.len() <= 256,
+            "number of points has to be a power of two between 2 and 256"
+        );
+        let bits_per_symbol = points.len().trailing_zeros() as usize;
+        Constellation {
+            points,
+            bits_per_symbol,
+        }
+    }
+
+    pub fn bpsk() -> Constellation {
+        Self::new(vec![Complex32::new(-1.0, 0.0), Complex32::new(1.0, 0.0)])
+    }
+
+    /// QPSK with bit 0 on the in-phase and bit 1 on the quadrature component.
+    pub fn qpsk() -> Constellation {
+        let a = 1.0 / 2.0f32.sqrt();
+        Self::new(
+            (0..4)
+                .map(|s| {
+                    Complex32::new(
+                        if s & 1 == 0 { -a } else { a },
+                        if s & 2 == 0 { -a } else { a },
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    pub fn psk8() -> Constellation {
+        let mut points = vec![Complex32::new(0.0, 0.0); 8];
+        for k in 0..8 {
+            points[gray(k)] = Complex32::from_polar(1.0, 2.0 * PI * k as f32 / 8.0);
+        }
+        Self::new(points)
+    }
+
+    /// 16QAM with bits 0-1 on the in-phase and bits 2-3 on the quadrature component.
+    pub fn qam16() -> Constellation {
+        let scale = 1.0 / 10.0f32.sqrt();
+        let mut levels = [0.0; 4];
+        for k in 0..4 {
+            levels[gray(k)] = (2 * k as i32 - 3) as f32 * scale;
+        }
+        Self::new(
+            (0..16)
+                .map(|s| Complex32::new(levels[s & 3], levels[s >> 2]))
+                .collect(),
+        )
+    }
+
+    pub fn points(&self) -> &[Complex32] {
+        &self.points
+    }
+
+    pub fn bits_per_symbol(&self) -> usize {
+        self.bits_per_symbol
+    }
+
+    /// Map a symbol to its point.
+    pub fn map(&self, symbol: u8) -> Complex32 {
+        self.points[symbol as usize]
+    }
+
+    /// Symbol of the closest point.
+    pub fn decide(&self, x: Complex32) -> u8 {
+        let mut best = 0;
+        let mut best_dist = f32::INFINITY;
+        for (s, p) in self.points.iter().enumerate() {
+            let d = (x - p).norm_sqr();
+            if d < best_dist {
+                best = s;
+                best_dist = d;
+            }
+        }
+        best as u8
+    }
+
+    /// Max-log approximation of the log-likelihood ratios `ln(P(1) / P(0))` of the symbol bits
+    /// (most significant bit first), assuming noise with variance one.
+    pub fn soft_decide(&self, x: Complex32, llrs: &mut [f32]) {
+        for (i, llr) in llrs.iter_mut().take(self.bits_per_symbol).enumerate() {
+            let bit = self.bits_per_symbol - 1 - i;
+            let mut d0 = f32::INFINITY;
+            let mut d1 = f32::INFINITY;
+            for (s, p) in self.points.iter().enumerate() {
+                let d = (x - p).norm_sqr();
+                if (s >> bit) & 1 == 0 {
+                    d0 = d0.min(d);
+                } else {
+                    d1 = d1.min(d);
+                }
+            }
+            *llr = d0 - d1;
+        }
+    }
+}
+
+/// Gray code of `k`.
+fn gray(k: usize) -> usize {
+    k ^ (k >> 1)
+}
+
+/// Map symbols to constellation points.
+///
+/// # Inputs
+///
+/// `in`: Symbols (u8)
+///
+/// # Outputs
+///
+/// `out`: Constellation points (Complex32)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::Constellation;
+/// use futuresdr::blocks::ConstellationMapper;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let mapper = fg.add_block(ConstellationMapper::new(Constellation::qpsk()));
+/// ```
+pub struct ConstellationMapper {
+    constellation: Constellation,
+}
+
+impl ConstellationMapper {
+    pub fn new(constellation: Constellation) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("ConstellationMapper").build(),
+            StreamIoBuilder::new()
+                .add_input::<u8>("in")
+                .add_output::<Complex32>("out")
+                .build(),
+            MessageIoBuilder::<ConstellationMapper>::new().build(),
+            ConstellationMapper { constellation },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for ConstellationMapper {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<u8>();
+        let o = sio.output(0).slice::<Complex32>();
+
+        let m = std::cmp::min(i.len(), o.len());
+        let mask = self.constellation.points.len() - 1;
+        for (x, y) in i[..m].iter().zip(o.iter_mut()) {
+            *y = self.constellation.points[*x as usize & mask];
+        }
+
+        sio.input(0).consume(m);
+        sio.output(0).produce(m);
+
+        if sio.input(0).finished() && m == i.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Demap constellation points.
+pub struct ConstellationDemapper {
+    constellation: Constellation,
+    soft: bool,
+    evm_interval: usize,
+    n: usize,
+    error: f32,
+    power: f32,
+}
+
+impl ConstellationDemapper {
+    pub fn new(constellation: Constellation, soft: bool, evm_interval: usize) -> Block {
+        let sio = StreamIoBuilder::new().add_input::<Complex32>("in");
+        let sio = if soft {
+            sio.add_output::<f32>("out")
+        } else {
+            sio.add_output::<u8>("out")
+        };
+
+        Block::new(
+            BlockMetaBuilder::new("ConstellationDemapper").build(),
+            sio.build(),
+            MessageIoBuilder::<ConstellationDemapper>::new()
+                .add_output("evm")
+                .build(),
+            ConstellationDemapper {
+                constellation,
+                soft,
+                evm_interval,
+                n: 0,
+                error: 0.0,
+                power: 0.0,
+            },
+        )
+    }
+
+    fn update_evm(&mut self, x: Complex32, s: u8) -> Option<f32> {
+        if self.evm_interval == 0 {
+            return None;
+        }
+        let p = self.constellation.map(s);
+        self.error += (x - p).norm_sqr();
+        self.power += p.norm_sqr();
+        self.n += 1;
+        if self.n == self.evm_interval {
+            let evm = (self.error / self.power).sqrt();
+            self.n = 0;
+            self.error = 0.0;
+            self.power = 0.0;
+            Some(evm)
+        } else {
+            None
+        }
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for ConstellationDemapper {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex32>();
+        let mut evms = Vec::new();
+
+        let m = if self.soft {
+            let bits = self.constellation.bits_per_symbol;
+            let o = sio.output(0).slice::<f32>();
+            let m = std::cmp::min(i.len(), o.len() / bits);
+            for (x, y) in i[..m].iter().zip(o.chunks_exact_mut(bits)) {
+                self.constellation.soft_decide(*x, y);
+                let s = self.constellation.decide(*x);
+                evms.extend(self.update_evm(*x, s));
+            }
+            sio.output(0).produce(m * bits);
+            m
+        } else {
+            let o = sio.output(0).slice::<u8>();
+            let m = std::cmp::min(i.len(), o.len());
+            for (x, y) in i[..m].iter().zip(o.iter_mut()) {
+                *y = self.constellation.decide(*x);
+                evms.extend(self.update_evm(*x, *y));
+            }
+            sio.output(0).produce(m);
+            m
+        };
+
+        sio.input(0).consume(m);
+
+        for evm in evms {
+            mio.post(0, Pmt::F32(evm)).await;
+        }
+
+        if sio.input(0).finished() && m == i.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Demap constellation points with hard or soft decisions.
+///
+/// Hard decisions output the symbol of the closest constellation point. Soft decisions output
+/// [log-likelihood ratios](Constellation::soft_decide) for each bit of the symbol (most
+/// significant bit first), i.e., positive values for ones.
+///
+/// Every `evm_interval` symbols (default 1000, 0 to disable), the RMS error vector magnitude,
+/// relative to the decided constellation points, is posted as [Pmt::F32].
+///
+/// # Inputs
+///
+/// `in`: Constellation points (Complex32)
+///
+/// # Outputs
+///
+/// `out`: Symbols (u8) or log-likelihood ratios (f32)
+///
+/// **Message**: `evm`: Error vector magnitude
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::Constellation;
+/// use futuresdr::blocks::ConstellationDemapperBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let demapper = fg.add_block(
+///     ConstellationDemapperBuilder::new(Constellation::qam16())
+///         .soft()
+///         .evm_interval(100)
+///         .build(),
+/// );
+/// ```
+pub struct ConstellationDemapperBuilder {
+    constellation: Constellation,
+    soft: bool,
+    evm_interval: usize,
+}
+
+impl ConstellationDemapperBuilder {
+    pub fn new(constellation: Constellation) -> ConstellationDemapperBuilder {
+        ConstellationDemapperBuilder {
+            constellation,
+            soft: false,
+            evm_interval: 1000,
+        }
+    }
+
+    /// Output soft decisions.
+    #[must_use]
+    pub fn soft(mut self) -> ConstellationDemapperBuilder {
+        self.soft = true;
+        self
+    }
+
+    /// Number of symbols per EVM report.
+    #[must_use]
+    pub fn evm_interval(mut self, evm_interval: usize) -> ConstellationDemapperBuilder {
+        self.evm_interval = evm_interval;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        ConstellationDemapper::new(self.constellation, self.soft, self.evm_interval)
+    }
+}
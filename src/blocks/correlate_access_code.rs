@@ -0,0 +1,187 @@
+use crate::anyhow::Result;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::Tag;
+use crate::runtime::WorkIo;
+
+/// Search a bit stream for an access code.
+pub struct CorrelateAccessCode {
+    code: u64,
+    mask: u64,
+    len: usize,
+    threshold: u32,
+    frame_len: usize,
+    reg: u64,
+    n_bits: usize,
+    frame: Option<Vec<u8>>,
+}
+
+impl CorrelateAccessCode {
+    pub fn new(access_code: &[u8], threshold: usize, frame_len: usize) -> Block {
+        assert!(
+            !access_code.is_empty() && access_code.len() <= 64,
+            "access code has to have between 1 and 64 bits"
+        );
+
+        let len = access_code.len();
+        let code = access_code
+            .iter()
+            .fold(0u64, |acc, b| (acc << 1) | (*b & 1) as u64);
+        let mask = if len == 64 { u64::MAX } else { (1 << len) - 1 };
+
+        Block::new(
+            BlockMetaBuilder::new("CorrelateAccessCode").build(),
+            StreamIoBuilder::new()
+                .add_input::<u8>("in")
+                .add_output::<u8>("out")
+                .build(),
+            MessageIoBuilder::<CorrelateAccessCode>::new()
+                .add_output("frames")
+                .build(),
+            CorrelateAccessCode {
+                code,
+                mask,
+                len,
+                threshold: threshold as u32,
+                frame_len,
+                reg: 0,
+                n_bits: 0,
+                frame: None,
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for CorrelateAccessCode {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<u8>();
+        let o = sio.output(0).slice::<u8>();
+
+        let m = std::cmp::min(i.len(), o.len());
+        let mut frames = Vec::new();
+
+        for (k, (x, y)) in i[..m].iter().zip(o.iter_mut()).enumerate() {
+            // the register holds the bits before the current one
+            if self.n_bits >= self.len {
+                let errors = ((self.reg ^ self.code) & self.mask).count_ones();
+                if errors <= self.threshold {
+                    sio.output(0).add_tag(
+                        k,
+                        Tag::NamedUsize("access_code".to_string(), errors as usize),
+                    );
+                    if self.frame_len > 0 && self.frame.is_none() {
+                        self.frame = Some(Vec::with_capacity(self.frame_len));
+                    }
+                }
+            }
+
+            let bit = x & 1;
+            if let Some(ref mut frame) = self.frame {
+                frame.push(bit);
+                if frame.len() == self.frame_len {
+                    frames.push(Pmt::Blob(self.frame.take().unwrap()));
+                }
+            }
+
+            self.reg = (self.reg << 1) | bit as u64;
+            self.n_bits += 1;
+            *y = *x;
+        }
+
+        sio.input(0).consume(m);
+        sio.output(0).produce(m);
+
+        for f in frames {
+            mio.post(0, f).await;
+        }
+
+        if sio.input(0).finished() && m == i.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Search a bit stream for an access code.
+///
+/// The input holds one bit per byte (the least significant bit). Whenever the last bits match the
+/// access code with at most `threshold` bit errors (default 0), the bit following the access code
+/// is tagged with [Tag::NamedUsize] `access_code` and the number of bit errors.
+///
+/// Optionally, the `frame_len` bits following the access code are posted as [Pmt::Blob] with one
+/// bit per byte. Access codes within a frame do not start a new frame.
+///
+/// # Inputs
+///
+/// `in`: Bits (u8)
+///
+/// # Outputs
+///
+/// `out`: Tagged bits (u8)
+///
+/// **Message**: `frames`: Frames following the access code, if enabled
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::CorrelateAccessCodeBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let code = [1, 0, 1, 1, 0, 1, 0, 0, 1, 1, 1, 0, 0, 1, 0, 0];
+/// let sync = fg.add_block(
+///     CorrelateAccessCodeBuilder::new(&code)
+///         .threshold(2)
+///         .frame_len(256)
+///         .build(),
+/// );
+/// ```
+pub struct CorrelateAccessCodeBuilder {
+    access_code: Vec<u8>,
+    threshold: usize,
+    frame_len: usize,
+}
+
+impl CorrelateAccessCodeBuilder {
+    pub fn new(access_code: &[u8]) -> CorrelateAccessCodeBuilder {
+        CorrelateAccessCodeBuilder {
+            access_code: access_code.to_vec(),
+            threshold: 0,
+            frame_len: 0,
+        }
+    }
+
+    /// Maximum number of bit errors.
+    #[must_use]
+    pub fn threshold(mut self, threshold: usize) -> CorrelateAccessCodeBuilder {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Post frames with the given number of bits.
+    #[must_use]
+    pub fn frame_len(mut self, frame_len: usize) -> CorrelateAccessCodeBuilder {
+        self.frame_len = frame_len;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        CorrelateAccessCode::new(&self.access_code, self.threshold, self.frame_len)
+    }
+}
@@ -0,0 +1,250 @@
+use crate::anyhow::Result;
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::Tag;
+use crate::runtime::WorkIo;
+
+/// Correlate with a known preamble and tag frame starts.
+pub struct CorrelateSync {
+    reference: Vec<Complex32>,
+    energy: f32,
+    threshold: f32,
+    frame_len: usize,
+    last: f32,
+    last_mag: f32,
+    holdoff: usize,
+    frame: Option<(Vec<Complex32>, Complex32)>,
+}
+
+impl CorrelateSync {
+    pub fn new(preamble: Vec<Complex32>, threshold: f32, frame_len: usize) -> Block {
+        assert!(!preamble.is_empty(), "preamble must not be empty");
+        assert!(
+            threshold > 0.0 && threshold <= 1.0,
+            "threshold has to be in (0, 1]"
+        );
+
+        let energy = preamble.iter().map(|x| x.norm_sqr()).sum();
+
+        Block::new(
+            BlockMetaBuilder::new("CorrelateSync").build(),
+            StreamIoBuilder::new()
+                .add_input::<Complex32>("in")
+                .add_output::<Complex32>("out")
+                .build(),
+            MessageIoBuilder::<CorrelateSync>::new()
+                .add_output("frames")
+                .build(),
+            CorrelateSync {
+                reference: preamble.iter().map(|x| x.conj()).collect(),
+                energy,
+                threshold,
+                frame_len,
+                last: 0.0,
+                last_mag: 0.0,
+                holdoff: 0,
+                frame: None,
+            },
+        )
+    }
+
+    /// Correlation and normalized correlation magnitude at the start of `x`.
+    fn correlate(&self, x: &[Complex32]) -> (Complex32, f32) {
+        let mut c = Complex32::new(0.0, 0.0);
+        let mut e = 0.0;
+        for (x, r) in x.iter().zip(self.reference.iter()) {
+            c += x * r;
+            e += x.norm_sqr();
+        }
+        let norm = if e > 0.0 {
+            c.norm() / (e * self.energy).sqrt()
+        } else {
+            0.0
+        };
+        (c, norm)
+    }
+
+    fn push_frame(&mut self, x: Complex32, frames: &mut Vec<Pmt>) {
+        if let Some((ref mut frame, correction)) = self.frame {
+            frame.push(x * correction);
+            if frame.len() == self.frame_len {
+                let mut blob = Vec::with_capacity(frame.len() * 8);
+                for x in frame.iter() {
+                    blob.extend_from_slice(&x.re.to_le_bytes());
+                    blob.extend_from_slice(&x.im.to_le_bytes());
+                }
+                frames.push(Pmt::Blob(blob));
+                self.frame = None;
+            }
+        }
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for CorrelateSync {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex32>();
+        let o = sio.output(0).slice::<Complex32>();
+
+        // the correlation at the next position is needed to detect a peak
+        let l = self.reference.len();
+        let m = std::cmp::min(i.len().saturating_sub(l), o.len());
+        let mut frames = Vec::new();
+
+        let mut next = if m > 0 {
+            self.correlate(&i[..l])
+        } else {
+            (Complex32::new(0.0, 0.0), 0.0)
+        };
+        for n in 0..m {
+            let (c, norm) = next;
+            next = self.correlate(&i[n + 1..n + 1 + l]);
+            let mag = c.norm();
+
+            if self.holdoff > 0 {
+                self.holdoff -= 1;
+            } else if norm > self.threshold && norm >= self.last && norm > next.1 {
+                // parabolic interpolation of the correlation peak
+                let (a, b, d) = (self.last_mag, mag, next.0.norm());
+                let den = a - 2.0 * b + d;
+                let time = if den < 0.0 { 0.5 * (a - d) / den } else { 0.0 };
+                let phase = c.arg();
+                let amp = mag / self.energy;
+
+                let mut tag = |name: &str, v: f32| {
+                    sio.output(0).add_tag(n, Tag::NamedF32(name.to_string(), v));
+                };
+                tag("corr_start", norm);
+                tag("phase_est", phase);
+                tag("time_est", time);
+                tag("amp_est", amp);
+
+                self.holdoff = l;
+                if self.frame_len > 0 && self.frame.is_none() {
+                    self.frame = Some((
+                        Vec::with_capacity(self.frame_len),
+                        Complex32::from_polar(1.0 / amp, -phase),
+                    ));
+                }
+            }
+            self.last = norm;
+            self.last_mag = mag;
+
+            self.push_frame(i[n], &mut frames);
+            o[n] = i[n];
+        }
+
+        let mut consumed = m;
+        if sio.input(0).finished() && m == i.len().saturating_sub(l) && o.len() >= i.len() {
+            // pass through the tail, which is too short for a preamble
+            for n in m..i.len() {
+                self.push_frame(i[n], &mut frames);
+                o[n] = i[n];
+            }
+            consumed = i.len();
+            io.finished = true;
+        }
+
+        sio.input(0).consume(consumed);
+        sio.output(0).produce(consumed);
+
+        for f in frames {
+            mio.post(0, f).await;
+        }
+
+        Ok(())
+    }
+}
+
+/// Correlate with a known preamble and tag frame starts.
+///
+/// Correlates the input with the preamble, given as samples at the input rate (e.g., symbols or
+/// pulse-shaped symbols). Local maxima of the normalized correlation magnitude (in `[0, 1]`) above
+/// the `threshold` (default 0.9) are tagged with [Tag::NamedF32] tags at the first sample of the
+/// preamble:
+/// - `corr_start`: normalized correlation magnitude,
+/// - `phase_est`: phase of the input relative to the preamble in radians,
+/// - `time_est`: fractional timing offset of the peak in samples, in `[-0.5, 0.5]`,
+/// - `amp_est`: amplitude of the input relative to the preamble.
+///
+/// Optionally, `frame_len` samples, starting with the preamble, are corrected in phase and
+/// amplitude and posted as [Pmt::Blob] with interleaved real and imaginary parts as little-endian
+/// f32.
+///
+/// # Inputs
+///
+/// `in`: Input (Complex32)
+///
+/// # Outputs
+///
+/// `out`: Tagged input (Complex32)
+///
+/// **Message**: `frames`: Aligned frames, if enabled
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::CorrelateSyncBuilder;
+/// use futuresdr::num_complex::Complex32;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let preamble: Vec<Complex32> = [1.0, 1.0, -1.0, 1.0, -1.0, -1.0, 1.0]
+///     .iter()
+///     .map(|x| Complex32::new(*x, 0.0))
+///     .collect();
+/// let sync = fg.add_block(
+///     CorrelateSyncBuilder::new(preamble)
+///         .threshold(0.8)
+///         .frame_len(128)
+///         .build(),
+/// );
+/// ```
+pub struct CorrelateSyncBuilder {
+    preamble: Vec<Complex32>,
+    threshold: f32,
+    frame_len: usize,
+}
+
+impl CorrelateSyncBuilder {
+    pub fn new(preamble: Vec<Complex32>) -> CorrelateSyncBuilder {
+        CorrelateSyncBuilder {
+            preamble,
+            threshold: 0.9,
+            frame_len: 0,
+        }
+    }
+
+    /// Minimum normalized correlation magnitude.
+    #[must_use]
+    pub fn threshold(mut self, threshold: f32) -> CorrelateSyncBuilder {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Post frames with the given number of samples, including the preamble.
+    #[must_use]
+    pub fn frame_len(mut self, frame_len: usize) -> CorrelateSyncBuilder {
+        self.frame_len = frame_len;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        CorrelateSync::new(self.preamble, self.threshold, self.frame_len)
+    }
+}
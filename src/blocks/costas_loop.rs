@@ -0,0 +1,140 @@
+use std::f32::consts::PI;
+
+use crate::anyhow::Result;
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Carrier recovery of the [CostasLoop] block.
+pub(crate) struct Costas {
+    order: usize,
+    /// Angle of the first constellation point.
+    offset: f32,
+    alpha: f32,
+    beta: f32,
+    max_freq: f32,
+    phase: f32,
+    freq: f32,
+}
+
+impl Costas {
+    pub(crate) fn new(order: usize, loop_bw: f32) -> Costas {
+        assert!(
+            matches!(order, 2 | 4 | 8),
+            "order has to be 2 (BPSK), 4 (QPSK), or 8 (8PSK)"
+        );
+
+        // critically damped second-order loop, like GNU Radio's control loop
+        let damping = 2.0f32.sqrt() / 2.0;
+        let denom = 1.0 + 2.0 * damping * loop_bw + loop_bw * loop_bw;
+
+        Costas {
+            order,
+            offset: if order == 4 { PI / 4.0 } else { 0.0 },
+            alpha: 4.0 * damping * loop_bw / denom,
+            beta: 4.0 * loop_bw * loop_bw / denom,
+            max_freq: 2.0 * PI / order as f32 / 2.0,
+            phase: 0.0,
+            freq: 0.0,
+        }
+    }
+
+    /// Derotate a sample and update the loop.
+    pub(crate) fn process(&mut self, x: Complex32) -> Complex32 {
+        let y = x * Complex32::from_polar(1.0, -self.phase);
+
+        // phase error w.r.t. the closest constellation point
+        let step = 2.0 * PI / self.order as f32;
+        let k = ((y.arg() - self.offset) / step).round();
+        let decision = Complex32::from_polar(1.0, k * step + self.offset);
+        let e = (y * decision.conj()).im.clamp(-1.0, 1.0);
+
+        self.freq = (self.freq + self.beta * e).clamp(-self.max_freq, self.max_freq);
+        self.phase = (self.phase + self.freq + self.alpha * e) % (2.0 * PI);
+        y
+    }
+}
+
+/// Costas loop.
+///
+/// Recovers the carrier phase and frequency of BPSK, QPSK, or 8PSK symbols (`order` 2, 4, or 8),
+/// locking to the points of [Constellation::bpsk](crate::blocks::Constellation::bpsk),
+/// [Constellation::qpsk](crate::blocks::Constellation::qpsk), or
+/// [Constellation::psk8](crate::blocks::Constellation::psk8), respectively. The phase error
+/// w.r.t. the closest point is tracked with a critically damped second-order loop with
+/// normalized bandwidth `loop_bw`. The input should be symbols with unit amplitude, i.e., the
+/// output of a symbol synchronizer; the phase is only recovered up to the symmetry of the
+/// constellation.
+///
+/// # Inputs
+///
+/// `in`: Symbols (Complex32)
+///
+/// # Outputs
+///
+/// `out`: Derotated symbols (Complex32)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::CostasLoop;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let costas = fg.add_block(CostasLoop::new(4, 0.02));
+/// ```
+pub struct CostasLoop {
+    costas: Costas,
+}
+
+impl CostasLoop {
+    pub fn new(order: usize, loop_bw: f32) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("CostasLoop").build(),
+            StreamIoBuilder::new()
+                .add_input::<Complex32>("in")
+                .add_output::<Complex32>("out")
+                .build(),
+            MessageIoBuilder::<CostasLoop>::new().build(),
+            CostasLoop {
+                costas: Costas::new(order, loop_bw),
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for CostasLoop {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex32>();
+        let o = sio.output(0).slice::<Complex32>();
+
+        let m = std::cmp::min(i.len(), o.len());
+        for (x, y) in i[..m].iter().zip(o.iter_mut()) {
+            *y = self.costas.process(*x);
+        }
+
+        sio.input(0).consume(m);
+        sio.output(0).produce(m);
+
+        if sio.input(0).finished() && m == i.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
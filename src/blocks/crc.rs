@@ -0,0 +1,241 @@
+use futures::FutureExt;
+
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIoBuilder;
+
+/// CRC parameters, following the Rocksoft model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crc {
+    width: usize,
+    poly: u64,
+    init: u64,
+    refin: bool,
+    refout: bool,
+    xorout: u64,
+}
+
+impl Crc {
+    /// Create a CRC with a width of 8 to 64 bits, multiple of 8.
+    pub fn new(width: usize, poly: u64, init: u64, refin: bool, refout: bool, xorout: u64) -> Crc {
+        assert!(
+            (8..=64).contains(&width) && width % 8 == 0,
+            "width has to be a multiple of 8 between 8 and 64"
+        );
+        Crc {
+            width,
+            poly,
+            init,
+            refin,
+            refout,
+            xorout,
+        }
+    }
+
+    /// CRC-8 (poly 0x07).
+    pub fn crc8() -> Crc {
+        Self::new(8, 0x07, 0x00, false, false, 0x00)
+    }
+
+    /// CRC-16/CCITT-FALSE (poly 0x1021, init 0xFFFF).
+    pub fn crc16_ccitt() -> Crc {
+        Self::new(16, 0x1021, 0xffff, false, false, 0x0000)
+    }
+
+    /// CRC-16/X-25 (poly 0x1021, reflected), the FCS of HDLC and AX.25.
+    pub fn crc16_x25() -> Crc {
+        Self::new(16, 0x1021, 0xffff, true, true, 0xffff)
+    }
+
+    /// CRC-16/KERMIT (poly 0x1021, reflected), the FCS of IEEE 802.15.4.
+    pub fn crc16_kermit() -> Crc {
+        Self::new(16, 0x1021, 0x0000, true, true, 0x0000)
+    }
+
+    /// CRC-32, as used by Ethernet and zip.
+    pub fn crc32() -> Crc {
+        Self::new(32, 0x04c11db7, 0xffffffff, true, true, 0xffffffff)
+    }
+
+    /// Number of CRC bytes.
+    pub fn bytes(&self) -> usize {
+        self.width / 8
+    }
+
+    /// Compute the CRC of `data`.
+    pub fn checksum(&self, data: &[u8]) -> u64 {
+        let mask = u64::MAX >> (64 - self.width);
+        let top = 1u64 << (self.width - 1);
+
+        let mut crc = self.init & mask;
+        for b in data {
+            let b = if self.refin { b.reverse_bits() } else { *b };
+            crc ^= (b as u64) << (self.width - 8);
+            for _ in 0..8 {
+                crc = if crc & top != 0 {
+                    (crc << 1) ^ self.poly
+                } else {
+                    crc << 1
+                };
+            }
+            crc &= mask;
+        }
+
+        if self.refout {
+            crc = crc.reverse_bits() >> (64 - self.width);
+        }
+        (crc ^ self.xorout) & mask
+    }
+
+    /// CRC bytes as appended to a frame, i.e., little endian for reflected CRCs and big endian
+    /// otherwise.
+    pub fn encode(&self, data: &[u8]) -> Vec<u8> {
+        let crc = self.checksum(data);
+        let n = self.bytes();
+        if self.refout {
+            crc.to_le_bytes()[..n].to_vec()
+        } else {
+            crc.to_be_bytes()[8 - n..].to_vec()
+        }
+    }
+}
+
+/// Append a CRC to PDUs.
+///
+/// The CRC bytes are appended little endian for reflected CRCs (e.g., CRC-32) and big endian
+/// otherwise.
+///
+/// # Inputs
+///
+/// **Message**: `in`: Frames ([Pmt::Blob])
+///
+/// # Outputs
+///
+/// **Message**: `out`: Frames with CRC ([Pmt::Blob])
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::Crc;
+/// use futuresdr::blocks::CrcAppend;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let crc = fg.add_block(CrcAppend::new(Crc::crc32()));
+/// ```
+pub struct CrcAppend {
+    crc: Crc,
+}
+
+impl CrcAppend {
+    pub fn new(crc: Crc) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("CrcAppend").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_input(
+                    "in",
+                    |block: &mut CrcAppend,
+                     mio: &mut MessageIo<CrcAppend>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            if let Pmt::Blob(mut v) = p {
+                                let crc = block.crc.encode(&v);
+                                v.extend_from_slice(&crc);
+                                mio.post(0, Pmt::Blob(v)).await;
+                            } else {
+                                warn!("CrcAppend: received wrong PMT type. {:?}", p);
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .add_output("out")
+                .build(),
+            CrcAppend { crc },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for CrcAppend {}
+
+/// Check and remove the CRC of PDUs.
+///
+/// Frames with a correct CRC are forwarded without the CRC. Frames with a wrong CRC are dropped
+/// or, if the `failed` port is connected, forwarded there unchanged.
+///
+/// # Inputs
+///
+/// **Message**: `in`: Frames with CRC ([Pmt::Blob])
+///
+/// # Outputs
+///
+/// **Message**: `out`: Frames with correct CRC, without the CRC ([Pmt::Blob])
+///
+/// **Message**: `failed`: Frames with wrong CRC ([Pmt::Blob])
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::Crc;
+/// use futuresdr::blocks::CrcCheck;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let crc = fg.add_block(CrcCheck::new(Crc::crc16_ccitt()));
+/// ```
+pub struct CrcCheck {
+    crc: Crc,
+}
+
+impl CrcCheck {
+    pub fn new(crc: Crc) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("CrcCheck").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_input(
+                    "in",
+                    |block: &mut CrcCheck,
+                     mio: &mut MessageIo<CrcCheck>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            if let Pmt::Blob(mut v) = p {
+                                let n = block.crc.bytes();
+                                if v.len() >= n
+                                    && block.crc.encode(&v[..v.len() - n]) == v[v.len() - n..]
+                                {
+                                    v.truncate(v.len() - n);
+                                    mio.post(0, Pmt::Blob(v)).await;
+                                } else {
+                                    mio.post(1, Pmt::Blob(v)).await;
+                                }
+                            } else {
+                                warn!("CrcCheck: received wrong PMT type. {:?}", p);
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .add_output("out")
+                .add_output("failed")
+                .build(),
+            CrcCheck { crc },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for CrcCheck {}
@@ -0,0 +1,98 @@
+use std::cmp;
+use std::ptr;
+
+use crate::anyhow::Result;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Delays the stream by a given number of samples.
+///
+/// Outputs `n_items` default values (i.e., zeros for numeric types) before copying the input.
+///
+/// # Inputs
+///
+/// `in`: Input
+///
+/// # Outputs
+///
+/// `out`: Output
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::Delay;
+/// use futuresdr::runtime::Flowgraph;
+/// use num_complex::Complex;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let delay = fg.add_block(Delay::<Complex<f32>>::new(128));
+/// ```
+pub struct Delay<T: Copy + Default + Send + 'static> {
+    n_items: u64,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: Copy + Default + Send + 'static> Delay<T> {
+    pub fn new(n_items: u64) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("Delay").build(),
+            StreamIoBuilder::new()
+                .add_input::<T>("in")
+                .add_output::<T>("out")
+                .build(),
+            MessageIoBuilder::new().build(),
+            Delay::<T> {
+                n_items,
+                _type: std::marker::PhantomData,
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: Copy + Default + Send + 'static> Kernel for Delay<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let o = sio.output(0).slice::<T>();
+
+        if self.n_items > 0 {
+            let m = cmp::min(self.n_items as usize, o.len());
+            o[..m].fill(T::default());
+            self.n_items -= m as u64;
+            sio.output(0).produce(m);
+            if self.n_items == 0 {
+                io.call_again = true;
+            }
+            return Ok(());
+        }
+
+        let i = sio.input(0).slice::<T>();
+        let m = cmp::min(i.len(), o.len());
+        if m > 0 {
+            unsafe {
+                ptr::copy_nonoverlapping(i.as_ptr(), o.as_mut_ptr(), m);
+            }
+            sio.input(0).consume(m);
+            sio.output(0).produce(m);
+        }
+
+        if sio.input(0).finished() && m == i.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
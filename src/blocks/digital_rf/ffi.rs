@@ -0,0 +1,100 @@
+//! Bindings of the used subset of libhdf5 (1.10 or later).
+//!
+//! The library is linked by the build script, statically if `HDF5_STATIC` is set.
+use std::os::raw::{c_char, c_int, c_uint, c_void};
+
+pub type hid_t = i64;
+pub type herr_t = c_int;
+pub type hsize_t = u64;
+
+pub const H5P_DEFAULT: hid_t = 0;
+pub const H5F_ACC_TRUNC: c_uint = 0x0002;
+pub const H5S_SCALAR: c_int = 0;
+pub const H5S_SELECT_SET: c_int = 0;
+pub const H5S_UNLIMITED: hsize_t = hsize_t::MAX;
+pub const H5T_COMPOUND: c_int = 6;
+pub const H5F_SCOPE_LOCAL: c_int = 0;
+
+extern "C" {
+    pub fn H5open() -> herr_t;
+
+    pub fn H5Fcreate(name: *const c_char, flags: c_uint, fcpl: hid_t, fapl: hid_t) -> hid_t;
+    pub fn H5Fflush(object_id: hid_t, scope: c_int) -> herr_t;
+    pub fn H5Fclose(file_id: hid_t) -> herr_t;
+
+    pub fn H5Gcreate2(
+        loc_id: hid_t,
+        name: *const c_char,
+        lcpl_id: hid_t,
+        gcpl_id: hid_t,
+        gapl_id: hid_t,
+    ) -> hid_t;
+    pub fn H5Gclose(group_id: hid_t) -> herr_t;
+
+    pub fn H5Screate(class: c_int) -> hid_t;
+    pub fn H5Screate_simple(rank: c_int, dims: *const hsize_t, maxdims: *const hsize_t) -> hid_t;
+    pub fn H5Sselect_hyperslab(
+        space_id: hid_t,
+        op: c_int,
+        start: *const hsize_t,
+        stride: *const hsize_t,
+        count: *const hsize_t,
+        block: *const hsize_t,
+    ) -> herr_t;
+    pub fn H5Sclose(space_id: hid_t) -> herr_t;
+
+    pub fn H5Pcreate(cls_id: hid_t) -> hid_t;
+    pub fn H5Pset_chunk(plist_id: hid_t, ndims: c_int, dim: *const hsize_t) -> herr_t;
+    pub fn H5Pclose(plist_id: hid_t) -> herr_t;
+
+    pub fn H5Tcreate(class: c_int, size: usize) -> hid_t;
+    pub fn H5Tcopy(type_id: hid_t) -> hid_t;
+    pub fn H5Tinsert(
+        parent_id: hid_t,
+        name: *const c_char,
+        offset: usize,
+        member_id: hid_t,
+    ) -> herr_t;
+    pub fn H5Tset_size(type_id: hid_t, size: usize) -> herr_t;
+    pub fn H5Tclose(type_id: hid_t) -> herr_t;
+
+    pub fn H5Dcreate2(
+        loc_id: hid_t,
+        name: *const c_char,
+        type_id: hid_t,
+        space_id: hid_t,
+        lcpl_id: hid_t,
+        dcpl_id: hid_t,
+        dapl_id: hid_t,
+    ) -> hid_t;
+    pub fn H5Dset_extent(dset_id: hid_t, size: *const hsize_t) -> herr_t;
+    pub fn H5Dget_space(dset_id: hid_t) -> hid_t;
+    pub fn H5Dwrite(
+        dset_id: hid_t,
+        mem_type_id: hid_t,
+        mem_space_id: hid_t,
+        file_space_id: hid_t,
+        plist_id: hid_t,
+        buf: *const c_void,
+    ) -> herr_t;
+    pub fn H5Dclose(dset_id: hid_t) -> herr_t;
+
+    pub fn H5Acreate2(
+        loc_id: hid_t,
+        attr_name: *const c_char,
+        type_id: hid_t,
+        space_id: hid_t,
+        acpl_id: hid_t,
+        aapl_id: hid_t,
+    ) -> hid_t;
+    pub fn H5Awrite(attr_id: hid_t, type_id: hid_t, buf: *const c_void) -> herr_t;
+    pub fn H5Aclose(attr_id: hid_t) -> herr_t;
+
+    pub static H5T_NATIVE_FLOAT_g: hid_t;
+    pub static H5T_NATIVE_DOUBLE_g: hid_t;
+    pub static H5T_NATIVE_INT_g: hid_t;
+    pub static H5T_NATIVE_LLONG_g: hid_t;
+    pub static H5T_NATIVE_ULLONG_g: hid_t;
+    pub static H5T_C_S1_g: hid_t;
+    pub static H5P_CLS_DATASET_CREATE_ID_g: hid_t;
+}
@@ -0,0 +1,300 @@
+//! Minimal wrappers around the HDF5 objects, written by the Digital RF sink.
+use std::ffi::CString;
+use std::os::raw::c_void;
+use std::path::Path;
+
+use super::ffi::*;
+use crate::anyhow::{bail, Result};
+
+/// Handle of an HDF5 object, closed on drop.
+pub struct Object {
+    id: hid_t,
+    close: unsafe extern "C" fn(hid_t) -> herr_t,
+}
+
+impl Object {
+    fn new(id: hid_t, close: unsafe extern "C" fn(hid_t) -> herr_t, what: &str) -> Result<Object> {
+        if id < 0 {
+            bail!("HDF5: {} failed", what);
+        }
+        Ok(Object { id, close })
+    }
+
+    pub fn id(&self) -> hid_t {
+        self.id
+    }
+}
+
+impl Drop for Object {
+    fn drop(&mut self) {
+        unsafe {
+            (self.close)(self.id);
+        }
+    }
+}
+
+fn check(ret: herr_t, what: &str) -> Result<()> {
+    if ret < 0 {
+        bail!("HDF5: {} failed", what);
+    }
+    Ok(())
+}
+
+fn cstr(s: &str) -> Result<CString> {
+    Ok(CString::new(s)?)
+}
+
+/// Initialize the library, which also sets up the predefined types.
+pub fn init() -> Result<()> {
+    check(unsafe { H5open() }, "initialization")
+}
+
+pub fn native_f32() -> hid_t {
+    unsafe { H5T_NATIVE_FLOAT_g }
+}
+
+pub fn native_i64() -> hid_t {
+    unsafe { H5T_NATIVE_LLONG_g }
+}
+
+/// Value of an attribute or scalar dataset.
+#[derive(Debug, Clone)]
+pub enum Value {
+    I32(i32),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    Str(String),
+}
+
+pub fn create_file(path: &Path) -> Result<Object> {
+    let name = cstr(&path.to_string_lossy())?;
+    let id = unsafe { H5Fcreate(name.as_ptr(), H5F_ACC_TRUNC, H5P_DEFAULT, H5P_DEFAULT) };
+    Object::new(id, H5Fclose, &format!("creating {}", path.display()))
+}
+
+pub fn flush(file: &Object) -> Result<()> {
+    check(unsafe { H5Fflush(file.id(), H5F_SCOPE_LOCAL) }, "flushing")
+}
+
+pub fn create_group(loc: &Object, name: &str) -> Result<Object> {
+    let n = cstr(name)?;
+    let id = unsafe { H5Gcreate2(loc.id(), n.as_ptr(), H5P_DEFAULT, H5P_DEFAULT, H5P_DEFAULT) };
+    Object::new(id, H5Gclose, &format!("creating group {name}"))
+}
+
+/// Compound type of complex samples with `r` and `i` fields, as used by Digital RF.
+pub fn complex_f32() -> Result<Object> {
+    let t = Object::new(
+        unsafe { H5Tcreate(H5T_COMPOUND, 8) },
+        H5Tclose,
+        "creating type",
+    )?;
+    for (name, offset) in [("r", 0), ("i", 4)] {
+        let n = cstr(name)?;
+        check(
+            unsafe { H5Tinsert(t.id(), n.as_ptr(), offset, native_f32()) },
+            "creating type",
+        )?;
+    }
+    Ok(t)
+}
+
+/// Write a scalar attribute or, if `dataset` is set, a scalar dataset.
+fn scalar(loc: &Object, name: &str, value: &Value, dataset: bool) -> Result<()> {
+    let n = cstr(name)?;
+    let space = Object::new(
+        unsafe { H5Screate(H5S_SCALAR) },
+        H5Sclose,
+        "creating dataspace",
+    )?;
+
+    let s;
+    let string_type;
+    let (dtype, buf): (hid_t, *const c_void) = unsafe {
+        match value {
+            Value::I32(v) => (H5T_NATIVE_INT_g, v as *const i32 as *const c_void),
+            Value::I64(v) => (H5T_NATIVE_LLONG_g, v as *const i64 as *const c_void),
+            Value::U64(v) => (H5T_NATIVE_ULLONG_g, v as *const u64 as *const c_void),
+            Value::F64(v) => (H5T_NATIVE_DOUBLE_g, v as *const f64 as *const c_void),
+            Value::Str(v) => {
+                s = cstr(v)?;
+                string_type = Object::new(H5Tcopy(H5T_C_S1_g), H5Tclose, "creating type")?;
+                check(
+                    H5Tset_size(string_type.id(), s.as_bytes_with_nul().len()),
+                    "creating type",
+                )?;
+                (string_type.id(), s.as_ptr() as *const c_void)
+            }
+        }
+    };
+
+    if dataset {
+        let d = Object::new(
+            unsafe {
+                H5Dcreate2(
+                    loc.id(),
+                    n.as_ptr(),
+                    dtype,
+                    space.id(),
+                    H5P_DEFAULT,
+                    H5P_DEFAULT,
+                    H5P_DEFAULT,
+                )
+            },
+            H5Dclose,
+            &format!("creating dataset {name}"),
+        )?;
+        check(
+            unsafe { H5Dwrite(d.id(), dtype, H5P_DEFAULT, H5P_DEFAULT, H5P_DEFAULT, buf) },
+            &format!("writing dataset {name}"),
+        )
+    } else {
+        let a = Object::new(
+            unsafe {
+                H5Acreate2(
+                    loc.id(),
+                    n.as_ptr(),
+                    dtype,
+                    space.id(),
+                    H5P_DEFAULT,
+                    H5P_DEFAULT,
+                )
+            },
+            H5Aclose,
+            &format!("creating attribute {name}"),
+        )?;
+        check(
+            unsafe { H5Awrite(a.id(), dtype, buf) },
+            &format!("writing attribute {name}"),
+        )
+    }
+}
+
+pub fn attribute(loc: &Object, name: &str, value: &Value) -> Result<()> {
+    scalar(loc, name, value, false)
+}
+
+pub fn scalar_dataset(loc: &Object, name: &str, value: &Value) -> Result<()> {
+    scalar(loc, name, value, true)
+}
+
+/// Two-dimensional dataset with a fixed number of columns, extended by appending rows.
+pub struct Table {
+    dataset: Object,
+    dtype: hid_t,
+    cols: hsize_t,
+    rows: hsize_t,
+}
+
+impl Table {
+    /// Create an empty table. The type has to outlive the table.
+    pub fn create(
+        loc: &Object,
+        name: &str,
+        dtype: hid_t,
+        cols: usize,
+        chunk: usize,
+    ) -> Result<Table> {
+        let n = cstr(name)?;
+        let cols = cols as hsize_t;
+        let dims = [0, cols];
+        let max = [H5S_UNLIMITED, cols];
+        let space = Object::new(
+            unsafe { H5Screate_simple(2, dims.as_ptr(), max.as_ptr()) },
+            H5Sclose,
+            "creating dataspace",
+        )?;
+        let plist = Object::new(
+            unsafe { H5Pcreate(H5P_CLS_DATASET_CREATE_ID_g) },
+            H5Pclose,
+            "creating property list",
+        )?;
+        let chunk = [chunk as hsize_t, cols];
+        check(
+            unsafe { H5Pset_chunk(plist.id(), 2, chunk.as_ptr()) },
+            "setting chunk size",
+        )?;
+        let dataset = Object::new(
+            unsafe {
+                H5Dcreate2(
+                    loc.id(),
+                    n.as_ptr(),
+                    dtype,
+                    space.id(),
+                    H5P_DEFAULT,
+                    plist.id(),
+                    H5P_DEFAULT,
+                )
+            },
+            H5Dclose,
+            &format!("creating dataset {name}"),
+        )?;
+        Ok(Table {
+            dataset,
+            dtype,
+            cols,
+            rows: 0,
+        })
+    }
+
+    pub fn object(&self) -> &Object {
+        &self.dataset
+    }
+
+    pub fn rows(&self) -> u64 {
+        self.rows
+    }
+
+    /// Append rows, i.e., `rows * cols` elements of the table type.
+    ///
+    /// # Safety
+    ///
+    /// `data` has to point to at least `rows * cols` elements of the table type.
+    pub unsafe fn append(&mut self, data: *const c_void, rows: u64) -> Result<()> {
+        if rows == 0 {
+            return Ok(());
+        }
+        let size = [self.rows + rows, self.cols];
+        check(
+            H5Dset_extent(self.dataset.id(), size.as_ptr()),
+            "extending dataset",
+        )?;
+        let file_space = Object::new(
+            H5Dget_space(self.dataset.id()),
+            H5Sclose,
+            "getting dataspace",
+        )?;
+        let start = [self.rows, 0];
+        let count = [rows, self.cols];
+        check(
+            H5Sselect_hyperslab(
+                file_space.id(),
+                H5S_SELECT_SET,
+                start.as_ptr(),
+                std::ptr::null(),
+                count.as_ptr(),
+                std::ptr::null(),
+            ),
+            "selecting rows",
+        )?;
+        let mem_space = Object::new(
+            H5Screate_simple(2, count.as_ptr(), std::ptr::null()),
+            H5Sclose,
+            "creating dataspace",
+        )?;
+        check(
+            H5Dwrite(
+                self.dataset.id(),
+                self.dtype,
+                mem_space.id(),
+                file_space.id(),
+                H5P_DEFAULT,
+                data,
+            ),
+            "writing rows",
+        )?;
+        self.rows += rows;
+        Ok(())
+    }
+}
@@ -0,0 +1,15 @@
+//! ## Digital RF Blocks (requires `digital_rf` feature)
+//!
+//! Recording in the HDF5-based [Digital RF](https://github.com/MITHaystack/digital_rf) format,
+//! using libhdf5 (1.10 or later) directly. Recordings can be read with the Digital RF tools,
+//! e.g., `digital_rf.DigitalRFReader` in Python. Set `HDF5_STATIC` at build time to link
+//! libhdf5 statically.
+#[allow(non_camel_case_types, non_upper_case_globals)]
+mod ffi;
+mod h5;
+
+mod sink;
+pub use self::sink::{DigitalRfSink, DigitalRfSinkBuilder};
+
+/// Version of the format, written to the properties.
+const DIGITAL_RF_VERSION: &str = "2.6.0";
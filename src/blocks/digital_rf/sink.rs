@@ -0,0 +1,543 @@
+use futures::FutureExt;
+use std::collections::HashMap;
+use std::os::raw::c_void;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use super::h5;
+use super::h5::{Object, Table, Value};
+use super::DIGITAL_RF_VERSION;
+use crate::anyhow::Result;
+use crate::blocks::rotating_file_sink::utc;
+use crate::blocks::FileSample;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::Tag;
+use crate::runtime::WorkIo;
+
+/// Name of a subdirectory, starting at the given second.
+fn subdir_name(secs: u64) -> String {
+    let (y, mo, d, h, mi, s, _) = utc(UNIX_EPOCH + Duration::from_secs(secs));
+    format!("{y:04}-{mo:02}-{d:02}T{h:02}-{mi:02}-{s:02}")
+}
+
+/// Open data file, covering one file cadence.
+struct RfFile {
+    file: Object,
+    data: Table,
+    index: Table,
+    /// First sample index after the file.
+    end: u64,
+    /// Index of the next sample, if it continues the current block.
+    expected: u64,
+}
+
+/// Write samples in the Digital RF format.
+pub struct DigitalRfSink<T: FileSample> {
+    channel_dir: PathBuf,
+    num: u64,
+    den: u64,
+    subdir_cadence: u64,
+    file_cadence: u64,
+    start: Option<SystemTime>,
+    uuid: String,
+    /// Global index of the next sample, i.e., samples since the epoch.
+    next: u64,
+    sequence: i32,
+    complex_type: Option<Object>,
+    file: Option<RfFile>,
+    /// Metadata, not yet written, and its sample index.
+    metadata: Option<(u64, HashMap<String, Pmt>)>,
+    /// Open metadata file and the first second it covers.
+    metadata_file: Option<(u64, Object)>,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: FileSample> DigitalRfSink<T> {
+    fn create(builder: DigitalRfSinkBuilder<T>) -> Block {
+        let metadata = if builder.metadata.is_empty() {
+            None
+        } else {
+            Some((0, builder.metadata))
+        };
+        Block::new(
+            BlockMetaBuilder::new("DigitalRfSink").blocking().build(),
+            StreamIoBuilder::new().add_input::<T>("in").build(),
+            MessageIoBuilder::new()
+                .add_input(
+                    "metadata",
+                    |block: &mut DigitalRfSink<T>,
+                     _mio: &mut MessageIo<DigitalRfSink<T>>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            match p {
+                                Pmt::MapStrPmt(m) => block.add_metadata(block.next, m)?,
+                                _ => warn!(
+                                    "DigitalRfSink/metadata: received wrong PMT type. {:?}",
+                                    p
+                                ),
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .build(),
+            DigitalRfSink::<T> {
+                channel_dir: builder.dir.join(builder.channel),
+                num: builder.num,
+                den: builder.den,
+                subdir_cadence: builder.subdir_cadence,
+                file_cadence: builder.file_cadence,
+                start: builder.start,
+                uuid: builder.uuid,
+                next: 0,
+                sequence: 0,
+                complex_type: None,
+                file: None,
+                metadata,
+                metadata_file: None,
+                _type: std::marker::PhantomData,
+            },
+        )
+    }
+
+    /// Milliseconds since the epoch of a sample (rounded down).
+    fn index_to_ms(&self, index: u64) -> u64 {
+        (index as u128 * 1000 * self.den as u128 / self.num as u128) as u64
+    }
+
+    /// First sample at or after the given milliseconds since the epoch.
+    fn ms_to_index(&self, ms: u64) -> u64 {
+        let d = 1000 * self.den as u128;
+        ((ms as u128 * self.num as u128 + d - 1) / d) as u64
+    }
+
+    /// Sample index of a time since the epoch.
+    fn time_to_index(&self, secs: u64, picos: u64) -> u64 {
+        let n = self.num as u128;
+        let d = self.den as u128;
+        (secs as u128 * n / d + picos as u128 * n / (d * 1_000_000_000_000)) as u64
+    }
+
+    /// Properties of the channel, written to `drf_properties.h5` and each data file.
+    fn properties(&self, loc: &Object) -> Result<()> {
+        let props = [
+            // class, size, and precision of the (real or imaginary) sample values
+            ("H5Tget_class", Value::I32(1)),
+            ("H5Tget_size", Value::I32(4)),
+            ("H5Tget_order", Value::I32(0)),
+            ("H5Tget_precision", Value::I32(32)),
+            ("H5Tget_offset", Value::I32(0)),
+            ("subdir_cadence_secs", Value::I64(self.subdir_cadence as i64)),
+            ("file_cadence_millisecs", Value::I64(self.file_cadence as i64)),
+            ("sample_rate_numerator", Value::U64(self.num)),
+            ("sample_rate_denominator", Value::U64(self.den)),
+            ("is_complex", Value::I32(T::COMPLEX as i32)),
+            ("num_subchannels", Value::I32(1)),
+            ("is_continuous", Value::I32(0)),
+            ("epoch", Value::Str("1970-01-01T00:00:00Z".to_string())),
+            (
+                "digital_rf_time_description",
+                Value::Str(
+                    "All times in this format are in number of samples since the epoch in the epoch attribute. The first sample time will be sample_rate * UTC time at first sample. Attribute init_utc_timestamp records this init UTC time so that a conversion to any other time is possible given the number of leapseconds difference at init_utc_timestamp. Leapseconds that occur during data recording are included in the data."
+                        .to_string(),
+                ),
+            ),
+            ("digital_rf_version", Value::Str(DIGITAL_RF_VERSION.to_string())),
+        ];
+        for (name, value) in props.iter() {
+            h5::attribute(loc, name, value)?;
+        }
+        Ok(())
+    }
+
+    /// Open the data file for the given sample.
+    fn open(&mut self, index: u64) -> Result<RfFile> {
+        let ms = self.index_to_ms(index);
+        let start = ms / self.file_cadence * self.file_cadence;
+        let secs = start / 1000;
+        let dir = self.channel_dir.join(subdir_name(
+            secs / self.subdir_cadence * self.subdir_cadence,
+        ));
+        std::fs::create_dir_all(&dir)?;
+
+        let file = h5::create_file(&dir.join(format!("rf@{}.{:03}.h5", secs, start % 1000)))?;
+        let per_file =
+            (self.num as u128 * self.file_cadence as u128 / (1000 * self.den as u128)) as usize;
+        let dtype = match &self.complex_type {
+            Some(t) => t.id(),
+            None => h5::native_f32(),
+        };
+        let data = Table::create(&file, "rf_data", dtype, 1, per_file.clamp(1, 1 << 16))?;
+        let index_table = Table::create(&file, "rf_data_index", h5::native_i64(), 2, 16)?;
+
+        self.properties(data.object())?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let init = self.start.map_or(now, |s| {
+            s.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+        });
+        h5::attribute(data.object(), "sequence_num", &Value::I32(self.sequence))?;
+        h5::attribute(data.object(), "init_utc_timestamp", &Value::U64(init))?;
+        h5::attribute(data.object(), "computer_time", &Value::U64(now))?;
+        h5::attribute(data.object(), "uuid_str", &Value::Str(self.uuid.clone()))?;
+        self.sequence += 1;
+
+        Ok(RfFile {
+            file,
+            data,
+            index: index_table,
+            end: self.ms_to_index(start + self.file_cadence),
+            expected: u64::MAX,
+        })
+    }
+
+    /// Write samples, starting at the next sample index.
+    fn write(&mut self, samples: &[T]) -> Result<()> {
+        let mut pos = 0;
+        while pos < samples.len() {
+            if self.file.as_ref().map_or(true, |f| self.next >= f.end) {
+                self.file = None;
+                self.file = Some(self.open(self.next)?);
+            }
+            let next = self.next;
+            let f = self.file.as_mut().unwrap();
+            let n = std::cmp::min((f.end - next) as usize, samples.len() - pos);
+
+            if f.expected != next {
+                // start of a continuous block, i.e., a new file or after a gap
+                let row = [next as i64, f.data.rows() as i64];
+                unsafe {
+                    f.index.append(row.as_ptr() as *const c_void, 1)?;
+                }
+            }
+            unsafe {
+                f.data
+                    .append(samples[pos..].as_ptr() as *const c_void, n as u64)?;
+            }
+            f.expected = next + n as u64;
+            self.next += n as u64;
+            pos += n;
+        }
+        Ok(())
+    }
+
+    /// Add metadata at the given sample, merged with other metadata at the same sample.
+    fn add_metadata(&mut self, index: u64, m: HashMap<String, Pmt>) -> Result<()> {
+        match self.metadata.as_mut() {
+            Some((i, pending)) if *i == index => pending.extend(m),
+            _ => {
+                self.flush_metadata()?;
+                self.metadata = Some((index, m));
+            }
+        }
+        Ok(())
+    }
+
+    fn flush_metadata(&mut self) -> Result<()> {
+        let (index, m) = match self.metadata.take() {
+            Some(m) => m,
+            None => return Ok(()),
+        };
+
+        let secs = index as u128 * self.den as u128 / self.num as u128;
+        let start = secs as u64 / self.subdir_cadence * self.subdir_cadence;
+        if self.metadata_file.as_ref().map(|(s, _)| *s) != Some(start) {
+            self.metadata_file = None;
+            let dir = self.channel_dir.join("metadata").join(subdir_name(start));
+            std::fs::create_dir_all(&dir)?;
+            let file = h5::create_file(&dir.join(format!("metadata@{start}.h5")))?;
+            self.metadata_file = Some((start, file));
+        }
+        let (_, file) = self.metadata_file.as_ref().unwrap();
+
+        let group = h5::create_group(file, &index.to_string())?;
+        for (k, v) in m.iter() {
+            let value = match v {
+                Pmt::F32(v) => Value::F64(*v as f64),
+                Pmt::F64(v) => Value::F64(*v),
+                Pmt::U32(v) => Value::U64(*v as u64),
+                Pmt::U64(v) => Value::U64(*v),
+                Pmt::String(s) => Value::Str(s.clone()),
+                _ => {
+                    warn!("DigitalRfSink: cannot store metadata {} ({:?})", k, v);
+                    continue;
+                }
+            };
+            h5::scalar_dataset(&group, k, &value)?;
+        }
+        h5::flush(file)
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: FileSample> Kernel for DigitalRfSink<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<T>();
+        let n = i.len();
+
+        let mut tags: Vec<(usize, HashMap<String, Pmt>)> = sio
+            .input(0)
+            .tags()
+            .iter()
+            .filter(|t| t.index < n)
+            .filter_map(|t| match &t.tag {
+                Tag::Data(Pmt::MapStrPmt(m)) => Some((t.index, m.clone())),
+                _ => None,
+            })
+            .collect();
+        tags.sort_by_key(|t| t.0);
+
+        let mut pos = 0;
+        for (index, mut m) in tags {
+            self.write(&i[pos..index])?;
+            pos = index;
+
+            if let Some(Pmt::U64(s)) = m.remove("seconds") {
+                let ps = match m.remove("picoseconds") {
+                    Some(Pmt::U64(ps)) => ps,
+                    _ => 0,
+                };
+                let next = self.time_to_index(s, ps);
+                if next < self.next {
+                    warn!("DigitalRfSink: time tag goes back in time, ignoring it");
+                } else {
+                    // samples in between are missing, i.e., a gap
+                    self.next = next;
+                }
+            }
+            if !m.is_empty() {
+                self.add_metadata(self.next, m)?;
+            }
+        }
+        self.write(&i[pos..n])?;
+
+        sio.input(0).consume(n);
+        if sio.input(0).finished() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        h5::init()?;
+        if T::COMPLEX {
+            self.complex_type = Some(h5::complex_f32()?);
+        }
+
+        let start = self
+            .start
+            .unwrap_or_else(SystemTime::now)
+            .duration_since(UNIX_EPOCH)?;
+        self.next = self.time_to_index(start.as_secs(), start.subsec_nanos() as u64 * 1000);
+        if let Some((i, _)) = self.metadata.as_mut() {
+            *i = self.next;
+        }
+
+        std::fs::create_dir_all(&self.channel_dir)?;
+        let props = h5::create_file(&self.channel_dir.join("drf_properties.h5"))?;
+        self.properties(&props)?;
+
+        let metadata_dir = self.channel_dir.join("metadata");
+        std::fs::create_dir_all(&metadata_dir)?;
+        let props = h5::create_file(&metadata_dir.join("dmd_properties.h5"))?;
+        let cadence = Value::I64(self.subdir_cadence as i64);
+        h5::attribute(&props, "subdir_cadence_secs", &cadence)?;
+        h5::attribute(&props, "file_cadence_secs", &cadence)?;
+        h5::attribute(&props, "sample_rate_numerator", &Value::U64(self.num))?;
+        h5::attribute(&props, "sample_rate_denominator", &Value::U64(self.den))?;
+        h5::attribute(&props, "file_name", &Value::Str("metadata".to_string()))?;
+        h5::attribute(
+            &props,
+            "digital_metadata_version",
+            &Value::Str(DIGITAL_RF_VERSION.to_string()),
+        )?;
+
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        self.flush_metadata()?;
+        if let Some(f) = self.file.take() {
+            h5::flush(&f.file)?;
+        }
+        self.metadata_file = None;
+        Ok(())
+    }
+}
+
+/// Write samples in the [Digital RF](https://github.com/MITHaystack/digital_rf) format.
+///
+/// Digital RF is an HDF5-based format for continuous, time-indexed recordings, as used in
+/// radio-science and ionospheric archives. The sink writes the channel directory
+/// `<dir>/<channel>` with
+/// - `drf_properties.h5`, holding the properties of the channel,
+/// - hourly (by default) subdirectories with one data file `rf@<seconds>.<ms>.h5` per second
+///   (by default), holding the samples in the `rf_data` dataset and the start of each
+///   continuous block in `rf_data_index`,
+/// - the Digital Metadata channel `metadata`, holding the per-channel metadata.
+///
+/// Samples are indexed by their time, i.e., samples since the epoch. The first sample is at the
+/// [start time](DigitalRfSinkBuilder::start_time) or the time at which the sink starts.
+///
+/// Gaps are written, when the input is tagged with a `Tag::Data(Pmt::MapStrPmt)` that has
+/// integer `seconds` and optional `picoseconds` entries (as produced by the
+/// [Vita49Source](crate::blocks::Vita49SourceBuilder)). Such a tag sets the time of the tagged
+/// sample; samples missing in between are skipped in the recording. Other entries of tags and
+/// maps, received on the `metadata` message input, are written as metadata at the current
+/// sample.
+///
+/// Complex samples are stored as `r`/`i` compound of 32-bit floats, real samples as 32-bit
+/// floats. The sample rate is given as a rational number, as in Digital RF.
+///
+/// # Inputs
+///
+/// `in`: Samples (`Complex32` or `f32`)
+///
+/// **Message**: `metadata`: Metadata for the current sample (`Pmt::MapStrPmt` with float,
+/// integer, or string values)
+///
+/// # Outputs
+///
+/// No outputs.
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::digital_rf::DigitalRfSinkBuilder;
+/// use futuresdr::num_complex::Complex32;
+/// use futuresdr::runtime::Flowgraph;
+/// use futuresdr::runtime::Pmt;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let snk = fg.add_block(
+///     DigitalRfSinkBuilder::<Complex32>::new("/data/drf", "ch0", 1_000_000)
+///         .metadata("center_frequencies", Pmt::F64(7.2e6))
+///         .build(),
+/// );
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "digital_rf")))]
+pub struct DigitalRfSinkBuilder<T: FileSample> {
+    dir: PathBuf,
+    channel: String,
+    num: u64,
+    den: u64,
+    subdir_cadence: u64,
+    file_cadence: u64,
+    start: Option<SystemTime>,
+    uuid: String,
+    metadata: HashMap<String, Pmt>,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: FileSample> DigitalRfSinkBuilder<T> {
+    /// Write channel `channel` to the directory `dir` with an integer sample rate.
+    pub fn new<P: Into<PathBuf>, S: Into<String>>(
+        dir: P,
+        channel: S,
+        sample_rate: u64,
+    ) -> DigitalRfSinkBuilder<T> {
+        DigitalRfSinkBuilder {
+            dir: dir.into(),
+            channel: channel.into(),
+            num: sample_rate,
+            den: 1,
+            subdir_cadence: 3600,
+            file_cadence: 1000,
+            start: None,
+            uuid: format!("{:032x}", rand::random::<u128>()),
+            metadata: HashMap::new(),
+            _type: std::marker::PhantomData,
+        }
+    }
+
+    /// Sample rate as fraction `numerator / denominator`.
+    #[must_use]
+    pub fn sample_rate_ratio(
+        mut self,
+        numerator: u64,
+        denominator: u64,
+    ) -> DigitalRfSinkBuilder<T> {
+        assert!(
+            numerator > 0 && denominator > 0,
+            "sample rate has to be positive"
+        );
+        self.num = numerator;
+        self.den = denominator;
+        self
+    }
+
+    /// Time of the first sample (default: the time at which the sink starts).
+    #[must_use]
+    pub fn start_time(mut self, start: SystemTime) -> DigitalRfSinkBuilder<T> {
+        self.start = Some(start);
+        self
+    }
+
+    /// Duration of a data file in milliseconds (default 1000).
+    #[must_use]
+    pub fn file_cadence_ms(mut self, ms: u64) -> DigitalRfSinkBuilder<T> {
+        self.file_cadence = ms;
+        self
+    }
+
+    /// Duration of a subdirectory in seconds (default 3600).
+    #[must_use]
+    pub fn subdir_cadence_secs(mut self, secs: u64) -> DigitalRfSinkBuilder<T> {
+        self.subdir_cadence = secs;
+        self
+    }
+
+    /// Unique id of the recording (default: random).
+    #[must_use]
+    pub fn uuid<S: Into<String>>(mut self, uuid: S) -> DigitalRfSinkBuilder<T> {
+        self.uuid = uuid.into();
+        self
+    }
+
+    /// Metadata at the first sample.
+    #[must_use]
+    pub fn metadata<S: Into<String>>(mut self, key: S, value: Pmt) -> DigitalRfSinkBuilder<T> {
+        self.metadata.insert(key.into(), value);
+        self
+    }
+
+    pub fn build(self) -> Block {
+        assert!(
+            self.file_cadence > 0 && self.subdir_cadence * 1000 % self.file_cadence == 0,
+            "subdirectory cadence has to be a multiple of the file cadence"
+        );
+        DigitalRfSink::<T>::create(self)
+    }
+}
@@ -0,0 +1,441 @@
+use futures::channel::mpsc;
+use futures::FutureExt;
+use futures::StreamExt;
+use std::alloc::Layout;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::os::unix::fs::FileExt;
+use std::os::unix::fs::OpenOptionsExt;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::thread::JoinHandle;
+
+use crate::anyhow::{anyhow, Context, Result};
+use crate::blocks::file_sink::Encoder;
+use crate::blocks::FileFormat;
+use crate::blocks::FileSample;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Alignment of buffers, lengths, and offsets for `O_DIRECT`.
+const ALIGN: usize = 4096;
+
+/// Buffer, aligned for `O_DIRECT`.
+struct AlignedBuf {
+    ptr: *mut u8,
+    cap: usize,
+    len: usize,
+}
+
+// the buffer is only accessed by its current owner
+unsafe impl Send for AlignedBuf {}
+
+impl AlignedBuf {
+    fn new(cap: usize) -> AlignedBuf {
+        let layout = Layout::from_size_align(cap, ALIGN).unwrap();
+        let ptr = unsafe { std::alloc::alloc_zeroed(layout) };
+        if ptr.is_null() {
+            std::alloc::handle_alloc_error(layout);
+        }
+        AlignedBuf { ptr, cap, len: 0 }
+    }
+
+    /// Unused part of the buffer.
+    fn spare(&mut self) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.add(self.len), self.cap - self.len) }
+    }
+
+    /// Filled part of the buffer, padded with zeros to the alignment.
+    fn padded(&mut self) -> &[u8] {
+        let len = (self.len + ALIGN - 1) / ALIGN * ALIGN;
+        let pad = len - self.len;
+        self.spare()[..pad].fill(0);
+        unsafe { std::slice::from_raw_parts(self.ptr, len) }
+    }
+}
+
+impl Drop for AlignedBuf {
+    fn drop(&mut self) {
+        unsafe {
+            std::alloc::dealloc(self.ptr, Layout::from_size_align(self.cap, ALIGN).unwrap());
+        }
+    }
+}
+
+/// Buffer, to be written at an offset of the file.
+struct Job {
+    buf: AlignedBuf,
+    offset: u64,
+}
+
+/// Buffer, returned by a writer, with the error of the write.
+type Done = (AlignedBuf, Option<std::io::Error>);
+
+/// Write samples to a file with `O_DIRECT`, bypassing the page cache.
+pub struct DirectFileSink<T: Send + 'static> {
+    file_name: String,
+    format: Option<Encoder<T>>,
+    remaining: Option<u64>,
+    buffer_size: usize,
+    queue_depth: usize,
+    writers: usize,
+    file: Option<Arc<File>>,
+    jobs: Option<std::sync::mpsc::Sender<Job>>,
+    done: Option<mpsc::UnboundedReceiver<Done>>,
+    threads: Vec<JoinHandle<()>>,
+    /// Buffers that are not queued for writing.
+    free: Vec<AlignedBuf>,
+    current: Option<AlignedBuf>,
+    /// Encoded samples, if the file is not in the native format.
+    buf: Vec<u8>,
+    /// Bytes that were queued for writing.
+    written: u64,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: Send + 'static> DirectFileSink<T> {
+    fn create(builder: DirectFileSinkBuilder<T>) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("DirectFileSink").build(),
+            StreamIoBuilder::new().add_input::<T>("in").build(),
+            MessageIoBuilder::new().build(),
+            DirectFileSink::<T> {
+                file_name: builder.file_name,
+                format: builder.format,
+                remaining: builder.length,
+                buffer_size: (builder.buffer_size + ALIGN - 1) / ALIGN * ALIGN,
+                queue_depth: builder.queue_depth,
+                writers: builder.writers,
+                file: None,
+                jobs: None,
+                done: None,
+                threads: Vec::new(),
+                free: Vec::new(),
+                current: None,
+                buf: Vec::new(),
+                written: 0,
+                _type: std::marker::PhantomData,
+            },
+        )
+    }
+
+    /// Take back a buffer from the writers.
+    fn recycle(&mut self, (mut buf, err): Done) -> Result<()> {
+        if let Some(e) = err {
+            return Err(anyhow!(e)).context(format!("DirectFileSink: writing {}", self.file_name));
+        }
+        buf.len = 0;
+        self.free.push(buf);
+        Ok(())
+    }
+
+    /// Get a buffer to fill, waiting for the writers, if all buffers are queued.
+    async fn buffer(&mut self) -> Result<AlignedBuf> {
+        while let Some(Some(d)) = self.done.as_mut().unwrap().next().now_or_never() {
+            self.recycle(d)?;
+        }
+        if self.free.is_empty() {
+            debug!("DirectFileSink: queue full, waiting for the disk");
+            let d = self.done.as_mut().unwrap().next().await.unwrap();
+            self.recycle(d)?;
+        }
+        Ok(self.free.pop().unwrap())
+    }
+
+    fn submit(&mut self, buf: AlignedBuf) {
+        let offset = self.written;
+        self.written += buf.len as u64;
+        self.jobs
+            .as_ref()
+            .unwrap()
+            .send(Job { buf, offset })
+            .unwrap();
+    }
+
+    /// Copy bytes to the buffers, queueing full buffers for writing.
+    async fn push(&mut self, mut data: &[u8]) -> Result<()> {
+        while !data.is_empty() {
+            let mut buf = match self.current.take() {
+                Some(b) => b,
+                None => self.buffer().await?,
+            };
+            let spare = buf.spare();
+            let n = std::cmp::min(spare.len(), data.len());
+            spare[..n].copy_from_slice(&data[..n]);
+            buf.len += n;
+            data = &data[n..];
+            if buf.len == buf.cap {
+                self.submit(buf);
+            } else {
+                self.current = Some(buf);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn writer(
+    file: Arc<File>,
+    jobs: Arc<Mutex<std::sync::mpsc::Receiver<Job>>>,
+    done: mpsc::UnboundedSender<Done>,
+) {
+    loop {
+        let job = jobs.lock().unwrap().recv();
+        let Job { mut buf, offset } = match job {
+            Ok(j) => j,
+            Err(_) => break,
+        };
+        let err = file.write_all_at(buf.padded(), offset).err();
+        if done.unbounded_send((buf, err)).is_err() {
+            break;
+        }
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: Send + 'static> Kernel for DirectFileSink<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice_unchecked::<u8>();
+
+        let t_size = std::mem::size_of::<T>();
+        let available = i.len() / t_size;
+        let items = match self.remaining {
+            Some(r) => std::cmp::min(available as u64, r) as usize,
+            None => available,
+        };
+
+        if items > 0 {
+            match self.format {
+                Some((format, convert)) => {
+                    let s = format.item_size();
+                    let mut buf = std::mem::take(&mut self.buf);
+                    buf.resize(items * s, 0);
+                    let i = sio.input(0).slice::<T>();
+                    for (x, b) in i[..items].iter().zip(buf.chunks_exact_mut(s)) {
+                        format.encode(convert(x), b);
+                    }
+                    let ret = self.push(&buf).await;
+                    self.buf = buf;
+                    ret?;
+                }
+                None => self.push(&i[..items * t_size]).await?,
+            }
+            if let Some(r) = self.remaining.as_mut() {
+                *r -= items as u64;
+            }
+        }
+
+        if sio.input(0).finished() || self.remaining == Some(0) {
+            io.finished = true;
+        }
+
+        sio.input(0).consume(items);
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        let file = match options
+            .clone()
+            .custom_flags(libc::O_DIRECT)
+            .open(&self.file_name)
+        {
+            Ok(f) => f,
+            Err(e) if e.raw_os_error() == Some(libc::EINVAL) => {
+                warn!(
+                    "DirectFileSink: {} does not support O_DIRECT, using buffered writes",
+                    self.file_name
+                );
+                options.open(&self.file_name)?
+            }
+            Err(e) => {
+                return Err(anyhow!(e))
+                    .context(format!("DirectFileSink: opening {}", self.file_name))
+            }
+        };
+        let file = Arc::new(file);
+
+        let (jobs_tx, jobs_rx) = std::sync::mpsc::channel();
+        let jobs_rx = Arc::new(Mutex::new(jobs_rx));
+        let (done_tx, done_rx) = mpsc::unbounded();
+        for _ in 0..self.writers {
+            let file = file.clone();
+            let jobs_rx = jobs_rx.clone();
+            let done_tx = done_tx.clone();
+            self.threads
+                .push(std::thread::spawn(move || writer(file, jobs_rx, done_tx)));
+        }
+
+        self.free = (0..self.queue_depth)
+            .map(|_| AlignedBuf::new(self.buffer_size))
+            .collect();
+        self.file = Some(file);
+        self.jobs = Some(jobs_tx);
+        self.done = Some(done_rx);
+        self.written = 0;
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        // the last buffer is written padded and the file truncated afterwards
+        if let Some(buf) = self.current.take() {
+            self.submit(buf);
+        }
+        self.jobs = None;
+        let mut result = Ok(());
+        while self.free.len() < self.queue_depth {
+            match self.done.as_mut().unwrap().next().await {
+                Some(d) => {
+                    if let Err(e) = self.recycle(d) {
+                        result = Err(e);
+                        self.queue_depth -= 1;
+                    }
+                }
+                None => break,
+            }
+        }
+        for t in self.threads.drain(..) {
+            let _ = t.join();
+        }
+        result?;
+
+        let file = self.file.take().unwrap();
+        file.set_len(self.written)?;
+        file.sync_all()?;
+        Ok(())
+    }
+}
+
+/// Write samples to a file with `O_DIRECT`, for sustained high rates to fast disks.
+///
+/// The sink copies samples into [queue_depth](DirectFileSinkBuilder::queue_depth) page-aligned
+/// buffers of [buffer_size](DirectFileSinkBuilder::buffer_size) bytes, which are written by
+/// [writers](DirectFileSinkBuilder::writers) threads, bypassing the page cache. This keeps many
+/// requests in flight on NVMe disks, absorbs stalls of the disk, and does not evict other data
+/// from the page cache. The sink only waits for the disk, if all buffers are queued.
+///
+/// If the file system does not support `O_DIRECT` (e.g., tmpfs), the sink falls back to buffered
+/// writes. Formats and the length are handled like in the
+/// [FileSinkBuilder](crate::blocks::FileSinkBuilder).
+///
+/// # Inputs
+///
+/// `in`: Input
+///
+/// # Outputs
+///
+/// No outputs.
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::DirectFileSinkBuilder;
+/// use futuresdr::blocks::FileFormat;
+/// use futuresdr::num_complex::Complex32;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // record 100 Msps as 16-bit integers with 256 MiB of buffers
+/// let sink = fg.add_block(
+///     DirectFileSinkBuilder::<Complex32>::new("/mnt/nvme/capture.ci16")
+///         .format(FileFormat::Ci16)
+///         .buffer_size(8 << 20)
+///         .queue_depth(32)
+///         .build(),
+/// );
+/// ```
+#[cfg_attr(docsrs, doc(cfg(target_os = "linux")))]
+pub struct DirectFileSinkBuilder<T: Send + 'static> {
+    file_name: String,
+    format: Option<Encoder<T>>,
+    length: Option<u64>,
+    buffer_size: usize,
+    queue_depth: usize,
+    writers: usize,
+}
+
+impl<T: Send + 'static> DirectFileSinkBuilder<T> {
+    pub fn new<S: Into<String>>(file_name: S) -> DirectFileSinkBuilder<T> {
+        DirectFileSinkBuilder {
+            file_name: file_name.into(),
+            format: None,
+            length: None,
+            buffer_size: 4 << 20,
+            queue_depth: 16,
+            writers: 4,
+        }
+    }
+
+    /// Maximum number of samples to write.
+    #[must_use]
+    pub fn length(mut self, length: u64) -> DirectFileSinkBuilder<T> {
+        self.length = Some(length);
+        self
+    }
+
+    /// Size of the buffers in bytes, rounded up to a multiple of 4096.
+    #[must_use]
+    pub fn buffer_size(mut self, bytes: usize) -> DirectFileSinkBuilder<T> {
+        assert!(bytes > 0, "buffer size has to be positive");
+        self.buffer_size = bytes;
+        self
+    }
+
+    /// Number of buffers.
+    #[must_use]
+    pub fn queue_depth(mut self, buffers: usize) -> DirectFileSinkBuilder<T> {
+        assert!(buffers > 0, "queue depth has to be positive");
+        self.queue_depth = buffers;
+        self
+    }
+
+    /// Number of threads, writing buffers concurrently.
+    #[must_use]
+    pub fn writers(mut self, threads: usize) -> DirectFileSinkBuilder<T> {
+        assert!(threads > 0, "at least one writer is required");
+        self.writers = threads;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        DirectFileSink::<T>::create(self)
+    }
+}
+
+impl<T: FileSample> DirectFileSinkBuilder<T> {
+    /// Sample format of the file.
+    #[must_use]
+    pub fn format(mut self, format: FileFormat) -> DirectFileSinkBuilder<T> {
+        assert!(
+            !T::COMPLEX || format.is_complex(),
+            "complex streams require a complex format"
+        );
+        self.format = Some((format, T::to_complex));
+        self
+    }
+}
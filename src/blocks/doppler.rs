@@ -0,0 +1,295 @@
+use futures::FutureExt;
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+use crate::anyhow::Result;
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Parse a `{"time": F64, "frequency": F64}` map.
+fn point(m: &HashMap<String, Pmt>) -> Option<(f64, f64)> {
+    let value = |k: &str| {
+        match m.get(k) {
+            Some(Pmt::F64(v)) => Some(*v),
+            Some(Pmt::F32(v)) => Some(*v as f64),
+            _ => None,
+        }
+        .filter(|v| v.is_finite())
+    };
+    Some((value("time")?, value("frequency")?))
+}
+
+/// Remove a time-varying Doppler shift.
+pub struct DopplerCorrection {
+    sample_rate: f64,
+    start_time: f64,
+    /// Doppler shift in Hz over time, sorted by time.
+    schedule: Vec<(f64, f64)>,
+    center_frequency: Option<f64>,
+    threshold: f64,
+    /// Part of the Doppler shift that is already removed by retuning the device.
+    tuned: f64,
+    n: u64,
+    phase: f64,
+}
+
+impl DopplerCorrection {
+    pub fn new(
+        sample_rate: f64,
+        start_time: f64,
+        schedule: Vec<(f64, f64)>,
+        center_frequency: Option<f64>,
+        threshold: f64,
+    ) -> Block {
+        assert!(sample_rate > 0.0, "sample rate must be positive");
+        assert!(threshold >= 0.0, "threshold must not be negative");
+        assert!(
+            schedule.iter().all(|(t, f)| t.is_finite() && f.is_finite()),
+            "schedule must be finite"
+        );
+
+        let mut s = DopplerCorrection {
+            sample_rate,
+            start_time,
+            schedule: Vec::new(),
+            center_frequency,
+            threshold,
+            tuned: 0.0,
+            n: 0,
+            phase: 0.0,
+        };
+        for p in schedule {
+            s.insert(p);
+        }
+
+        Block::new(
+            BlockMetaBuilder::new("DopplerCorrection").build(),
+            StreamIoBuilder::new()
+                .add_input::<Complex32>("in")
+                .add_output::<Complex32>("out")
+                .build(),
+            MessageIoBuilder::new()
+                .add_input(
+                    "doppler",
+                    |block: &mut DopplerCorrection,
+                     _mio: &mut MessageIo<DopplerCorrection>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            match p {
+                                Pmt::F64(f) => block.schedule = vec![(block.time(), f)],
+                                Pmt::F32(f) => block.schedule = vec![(block.time(), f as f64)],
+                                Pmt::MapStrPmt(ref m) if point(m).is_some() => {
+                                    block.insert(point(m).unwrap());
+                                }
+                                Pmt::VecPmt(ref v) => {
+                                    for p in v {
+                                        match p {
+                                            Pmt::MapStrPmt(m) if point(m).is_some() => {
+                                                block.insert(point(m).unwrap());
+                                            }
+                                            _ => warn!(
+                                                "DopplerCorrection: received wrong PMT type. {:?}",
+                                                p
+                                            ),
+                                        }
+                                    }
+                                }
+                                _ => warn!("DopplerCorrection: received wrong PMT type. {:?}", p),
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .add_output("cmd")
+                .build(),
+            s,
+        )
+    }
+
+    /// Time of the next sample.
+    fn time(&self) -> f64 {
+        self.start_time + self.n as f64 / self.sample_rate
+    }
+
+    /// Add a point to the schedule, replacing a point with the same time.
+    fn insert(&mut self, (time, freq): (f64, f64)) {
+        match self
+            .schedule
+            .binary_search_by(|(t, _)| t.partial_cmp(&time).unwrap())
+        {
+            Ok(i) => self.schedule[i].1 = freq,
+            Err(i) => self.schedule.insert(i, (time, freq)),
+        }
+    }
+
+    /// Doppler shift at the given time, interpolated linearly between the points of the schedule.
+    fn doppler(&mut self, time: f64) -> f64 {
+        // keep the last point before the current time
+        let past = self.schedule.iter().take_while(|(t, _)| *t <= time).count();
+        if past > 1 {
+            self.schedule.drain(..past - 1);
+        }
+
+        match self.schedule.as_slice() {
+            [] => 0.0,
+            [(t0, f0), (t1, f1), ..] if *t0 <= time => f0 + (f1 - f0) * (time - t0) / (t1 - t0),
+            [(_, f), ..] => *f,
+        }
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for DopplerCorrection {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex32>();
+        let o = sio.output(0).slice::<Complex32>();
+        let m = std::cmp::min(i.len(), o.len());
+
+        let mut retunes = Vec::new();
+        for (x, y) in i[..m].iter().zip(o.iter_mut()) {
+            let time = self.time();
+            let doppler = self.doppler(time);
+            if let Some(center) = self.center_frequency {
+                if (doppler - self.tuned).abs() > self.threshold {
+                    self.tuned = doppler;
+                    retunes.push(center + doppler);
+                }
+            }
+
+            *y = x * Complex32::from_polar(1.0, -self.phase as f32);
+            let inc = 2.0 * PI * (doppler - self.tuned) / self.sample_rate;
+            self.phase = (self.phase + inc) % (2.0 * PI);
+            self.n += 1;
+        }
+
+        sio.input(0).consume(m);
+        sio.output(0).produce(m);
+
+        for f in retunes {
+            let mut cmd = HashMap::new();
+            cmd.insert("freq".to_string(), Pmt::F64(f));
+            mio.post(0, Pmt::MapStrPmt(cmd)).await;
+        }
+
+        if sio.input(0).finished() && m == i.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Remove a time-varying Doppler shift.
+///
+/// Mixes the input down by the Doppler shift at the time of each sample, i.e., the time of the
+/// first sample (default 0, e.g., Unix time) plus the sample index divided by the sample rate.
+/// The Doppler shift is interpolated linearly between the points of a schedule, which can be
+/// set with [schedule](DopplerCorrectionBuilder::schedule) or updated at runtime, e.g., by an
+/// orbit propagator. Before the first and after the last point, the shift is held constant.
+/// Points in the past are dropped.
+///
+/// If a center frequency is set with [retune](DopplerCorrectionBuilder::retune), the device is
+/// retuned whenever the residual shift exceeds the threshold, and only the residual is corrected
+/// digitally. The retune commands can be connected to the `cmd` port of a `SoapySource` or
+/// `SoapySink`.
+///
+/// # Inputs
+///
+/// `in`: Input (Complex32)
+///
+/// **Message**: `doppler`: Doppler shift in Hz. Either a constant shift ([Pmt::F64]), which
+/// replaces the schedule, a point of the schedule as [Pmt::MapStrPmt] with the entries `time`
+/// and `frequency` ([Pmt::F64]), or a [Pmt::VecPmt] of such points.
+///
+/// # Outputs
+///
+/// `out`: Corrected samples (Complex32)
+///
+/// **Message**: `cmd`: Retune command as [Pmt::MapStrPmt] with the center frequency in Hz as
+/// `freq` entry ([Pmt::F64])
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::DopplerCorrectionBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // pass starting at the first sample, retuning in steps of 1 kHz
+/// let doppler = fg.add_block(
+///     DopplerCorrectionBuilder::new(1e6)
+///         .schedule(&[(0.0, 10e3), (300.0, 0.0), (600.0, -10e3)])
+///         .retune(437.5e6, 1e3)
+///         .build(),
+/// );
+/// ```
+pub struct DopplerCorrectionBuilder {
+    sample_rate: f64,
+    start_time: f64,
+    schedule: Vec<(f64, f64)>,
+    center_frequency: Option<f64>,
+    threshold: f64,
+}
+
+impl DopplerCorrectionBuilder {
+    pub fn new(sample_rate: f64) -> DopplerCorrectionBuilder {
+        DopplerCorrectionBuilder {
+            sample_rate,
+            start_time: 0.0,
+            schedule: Vec::new(),
+            center_frequency: None,
+            threshold: 0.0,
+        }
+    }
+
+    /// Time of the first sample in seconds.
+    #[must_use]
+    pub fn start_time(mut self, time: f64) -> DopplerCorrectionBuilder {
+        self.start_time = time;
+        self
+    }
+
+    /// Initial schedule of Doppler shifts as (time in seconds, shift in Hz).
+    #[must_use]
+    pub fn schedule(mut self, schedule: &[(f64, f64)]) -> DopplerCorrectionBuilder {
+        self.schedule = schedule.to_vec();
+        self
+    }
+
+    /// Retune the device, tuned to the given center frequency in Hz, once the residual shift
+    /// exceeds the threshold in Hz.
+    #[must_use]
+    pub fn retune(mut self, center_frequency: f64, threshold: f64) -> DopplerCorrectionBuilder {
+        self.center_frequency = Some(center_frequency);
+        self.threshold = threshold;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        DopplerCorrection::new(
+            self.sample_rate,
+            self.start_time,
+            self.schedule,
+            self.center_frequency,
+            self.threshold,
+        )
+    }
+}
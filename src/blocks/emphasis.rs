@@ -0,0 +1,187 @@
+use std::f64::consts::PI;
+
+use crate::anyhow::Result;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// First-order IIR filter `y[n] = b0 * x[n] + b1 * x[n-1] + a1 * y[n-1]`.
+struct FirstOrder {
+    b0: f32,
+    b1: f32,
+    a1: f32,
+    x1: f32,
+    y1: f32,
+}
+
+impl FirstOrder {
+    fn process(&mut self, sio: &mut StreamIo, io: &mut WorkIo) {
+        let i = sio.input(0).slice::<f32>();
+        let o = sio.output(0).slice::<f32>();
+
+        let m = std::cmp::min(i.len(), o.len());
+        for (x, y) in i[..m].iter().zip(o.iter_mut()) {
+            self.y1 = self.b0 * x + self.b1 * self.x1 + self.a1 * self.y1;
+            self.x1 = *x;
+            *y = self.y1;
+        }
+
+        sio.input(0).consume(m);
+        sio.output(0).produce(m);
+
+        if sio.input(0).finished() && m == i.len() {
+            io.finished = true;
+        }
+    }
+}
+
+/// FM de-emphasis filter.
+///
+/// First-order low-pass filter with time constant `tau` (75 us in the Americas and Korea, 50 us
+/// elsewhere), designed with the bilinear transform.
+///
+/// # Inputs
+///
+/// `in`: Input (f32)
+///
+/// # Outputs
+///
+/// `out`: Output (f32)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::Deemphasis;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let deemph = fg.add_block(Deemphasis::new(48000.0, 50e-6));
+/// ```
+pub struct Deemphasis {
+    filter: FirstOrder,
+}
+
+impl Deemphasis {
+    pub fn new(sample_rate: f32, tau: f32) -> Block {
+        let fs = sample_rate as f64;
+        let w_ca = 2.0 * fs * (1.0 / (tau as f64 * 2.0 * fs)).tan();
+        let k = -w_ca / (2.0 * fs);
+        let p1 = (1.0 + k) / (1.0 - k);
+        let b0 = -k / (1.0 - k);
+
+        Block::new(
+            BlockMetaBuilder::new("Deemphasis").build(),
+            StreamIoBuilder::new()
+                .add_input::<f32>("in")
+                .add_output::<f32>("out")
+                .build(),
+            MessageIoBuilder::<Deemphasis>::new().build(),
+            Deemphasis {
+                filter: FirstOrder {
+                    b0: b0 as f32,
+                    b1: b0 as f32,
+                    a1: p1 as f32,
+                    x1: 0.0,
+                    y1: 0.0,
+                },
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for Deemphasis {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        self.filter.process(sio, io);
+        Ok(())
+    }
+}
+
+/// FM pre-emphasis filter.
+///
+/// First-order high-frequency boost with time constant `tau` (75 us in the Americas and Korea,
+/// 50 us elsewhere), designed with the bilinear transform. The boost levels off just below the
+/// Nyquist frequency. The filter has unity gain at DC.
+///
+/// # Inputs
+///
+/// `in`: Input (f32)
+///
+/// # Outputs
+///
+/// `out`: Output (f32)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::Preemphasis;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let preemph = fg.add_block(Preemphasis::new(48000.0, 50e-6));
+/// ```
+pub struct Preemphasis {
+    filter: FirstOrder,
+}
+
+impl Preemphasis {
+    pub fn new(sample_rate: f32, tau: f32) -> Block {
+        let fs = sample_rate as f64;
+        let fh = 0.925 * fs / 2.0;
+        let w_cla = 2.0 * fs * (1.0 / (tau as f64 * 2.0 * fs)).tan();
+        let w_cha = 2.0 * fs * (2.0 * PI * fh / (2.0 * fs)).tan();
+        let kl = -w_cla / (2.0 * fs);
+        let kh = -w_cha / (2.0 * fs);
+        let z1 = (1.0 + kl) / (1.0 - kl);
+        let p1 = (1.0 + kh) / (1.0 - kh);
+        let b0 = (1.0 - kl) / (1.0 - kh);
+        // unity gain at DC
+        let g = (1.0 - p1).abs() / (b0 * (1.0 - z1).abs());
+
+        Block::new(
+            BlockMetaBuilder::new("Preemphasis").build(),
+            StreamIoBuilder::new()
+                .add_input::<f32>("in")
+                .add_output::<f32>("out")
+                .build(),
+            MessageIoBuilder::<Preemphasis>::new().build(),
+            Preemphasis {
+                filter: FirstOrder {
+                    b0: (g * b0) as f32,
+                    b1: (-g * b0 * z1) as f32,
+                    a1: p1 as f32,
+                    x1: 0.0,
+                    y1: 0.0,
+                },
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for Preemphasis {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        self.filter.process(sio, io);
+        Ok(())
+    }
+}
@@ -0,0 +1,190 @@
+use std::f64::consts::PI;
+
+use rand::Rng;
+
+use crate::anyhow::Result;
+use crate::blocks::channel_model::rng;
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Flat Rayleigh or Rician fading channel.
+pub struct FadingChannel {
+    // phase increment, phase, and complex weight of each sinusoid
+    sinusoids: Vec<(f64, f64, Complex32)>,
+    los: Option<(f64, f64, f32)>,
+    scale: f32,
+}
+
+impl FadingChannel {
+    pub fn new(doppler: f32, k_factor: f32, sinusoids: usize, seed: Option<u64>) -> Block {
+        assert!(
+            (0.0..0.5).contains(&doppler),
+            "normalized Doppler frequency has to be in [0, 0.5)"
+        );
+        assert!(k_factor >= 0.0, "K-factor must not be negative");
+        assert!(sinusoids > 0, "number of sinusoids must be positive");
+
+        let mut rng = rng(seed);
+        let w = 2.0 * PI * doppler as f64;
+        let m = sinusoids as f64;
+
+        // Zheng and Xiao: sinusoids with random arrival angles, sharing one phase
+        let theta: f64 = rng.gen_range(-PI..PI);
+        let phi: f64 = rng.gen_range(-PI..PI);
+        let amp = (2.0 / m).sqrt() as f32;
+        let sinusoids = (1..=sinusoids)
+            .map(|n| {
+                let alpha = (2.0 * PI * n as f64 - PI + theta) / (4.0 * m);
+                let psi: f64 = rng.gen_range(-PI..PI);
+                (
+                    w * alpha.cos(),
+                    phi,
+                    Complex32::new(psi.cos() as f32, psi.sin() as f32) * amp,
+                )
+            })
+            .collect();
+
+        let los = if k_factor > 0.0 {
+            let theta: f64 = rng.gen_range(-PI..PI);
+            let phi: f64 = rng.gen_range(-PI..PI);
+            Some((w * theta.cos(), phi, k_factor.sqrt()))
+        } else {
+            None
+        };
+
+        Block::new(
+            BlockMetaBuilder::new("FadingChannel").build(),
+            StreamIoBuilder::new()
+                .add_input::<Complex32>("in")
+                .add_output::<Complex32>("out")
+                .build(),
+            MessageIoBuilder::<Self>::new().build(),
+            FadingChannel {
+                sinusoids,
+                los,
+                scale: 1.0 / (1.0 + k_factor).sqrt(),
+            },
+        )
+    }
+
+    /// Next channel coefficient.
+    fn coefficient(&mut self) -> Complex32 {
+        let mut h = Complex32::new(0.0, 0.0);
+        for (inc, phase, weight) in self.sinusoids.iter_mut() {
+            h += *weight * phase.cos() as f32;
+            *phase = (*phase + *inc) % (2.0 * PI);
+        }
+        if let Some((inc, ref mut phase, amp)) = self.los {
+            h += Complex32::from_polar(amp, *phase as f32);
+            *phase = (*phase + inc) % (2.0 * PI);
+        }
+        h * self.scale
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for FadingChannel {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex32>();
+        let o = sio.output(0).slice::<Complex32>();
+
+        let m = std::cmp::min(i.len(), o.len());
+        for (x, y) in i[..m].iter().zip(o.iter_mut()) {
+            *y = x * self.coefficient();
+        }
+
+        sio.input(0).consume(m);
+        sio.output(0).produce(m);
+
+        if sio.input(0).finished() && m == i.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Flat Rayleigh or Rician fading channel.
+///
+/// Multiplies the input with a fading process of unit average power, generated with the
+/// sum-of-sinusoids model of Zheng and Xiao. The Doppler frequency is normalized to the sample
+/// rate, i.e., `v * f_c / (c * f_s)`. With a K-factor `K > 0` (default 0), a line-of-sight
+/// component with `K` times the power of the scattered components is added, yielding Rician
+/// fading. More sinusoids (default 8) approximate the Rayleigh statistics more closely.
+/// Optionally, the random generator is seeded for reproducible results.
+///
+/// # Inputs
+///
+/// `in`: Input (Complex32)
+///
+/// # Outputs
+///
+/// `out`: Output (Complex32)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::FadingChannelBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // 100 Hz Doppler at 1 MHz sample rate
+/// let fading = fg.add_block(FadingChannelBuilder::new(1e-4).rician(4.0).seed(42).build());
+/// ```
+pub struct FadingChannelBuilder {
+    doppler: f32,
+    k_factor: f32,
+    sinusoids: usize,
+    seed: Option<u64>,
+}
+
+impl FadingChannelBuilder {
+    pub fn new(doppler: f32) -> FadingChannelBuilder {
+        FadingChannelBuilder {
+            doppler,
+            k_factor: 0.0,
+            sinusoids: 8,
+            seed: None,
+        }
+    }
+
+    /// Rician fading with the given K-factor (linear).
+    #[must_use]
+    pub fn rician(mut self, k_factor: f32) -> FadingChannelBuilder {
+        self.k_factor = k_factor;
+        self
+    }
+
+    /// Number of sinusoids.
+    #[must_use]
+    pub fn sinusoids(mut self, sinusoids: usize) -> FadingChannelBuilder {
+        self.sinusoids = sinusoids;
+        self
+    }
+
+    /// Seed of the random generator.
+    #[must_use]
+    pub fn seed(mut self, seed: u64) -> FadingChannelBuilder {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn build(self) -> Block {
+        FadingChannel::new(self.doppler, self.k_factor, self.sinusoids, self.seed)
+    }
+}
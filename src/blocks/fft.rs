@@ -18,6 +18,9 @@ use crate::runtime::WorkIo;
 ///
 /// This block computes the FFT on `len` samples at a time, outputting `len` samples per FFT.
 ///
+/// With the `rayon` feature, large batches of FFTs (i.e., with large stream buffers) are split
+/// across the [compute pool](crate::runtime::parallel).
+///
 /// # Inputs
 ///
 /// `in`: Input samples (Complex32)
@@ -90,9 +93,83 @@ impl Fft {
     }
 }
 
+impl Fft {
+    fn process(&mut self, i: &mut [Complex32], o: &mut [Complex32]) {
+        process(
+            &*self.plan,
+            self.len,
+            matches!(self.direction, FftDirection::Inverse),
+            self.fft_shift,
+            self.normalize,
+            i,
+            o,
+            &mut self.scratch,
+        );
+    }
+}
+
+/// Samples per chunk, when the FFTs are computed on the compute pool.
+#[cfg(all(feature = "rayon", not(target_arch = "wasm32")))]
+const PARALLEL_CHUNK: usize = 1 << 14;
+
+/// Compute the FFTs of a batch of `len` samples each.
+#[allow(clippy::too_many_arguments)]
+fn process(
+    plan: &dyn rustfft::Fft<f32>,
+    len: usize,
+    inverse: bool,
+    fft_shift: bool,
+    normalize: Option<f32>,
+    i: &mut [Complex32],
+    o: &mut [Complex32],
+    scratch: &mut [Complex32],
+) {
+    let m = i.len();
+
+    if inverse && fft_shift {
+        for f in 0..(m / len) {
+            let mut sym = vec![Complex32::new(0.0, 0.0); len];
+            sym.copy_from_slice(&i[f * len..(f + 1) * len]);
+            for k in 0..len {
+                i[f * len + k] = sym[(k + len / 2) % len]
+            }
+        }
+    }
+
+    plan.process_outofplace_with_scratch(i, o, scratch);
+
+    if !inverse && fft_shift {
+        for f in 0..(m / len) {
+            let mut sym = vec![Complex32::new(0.0, 0.0); len];
+            sym.copy_from_slice(&o[f * len..(f + 1) * len]);
+            for k in 0..len {
+                o[f * len + k] = sym[(k + len / 2) % len]
+            }
+        }
+    }
+
+    if let Some(fac) = normalize {
+        for item in o.iter_mut() {
+            *item *= fac;
+        }
+    }
+}
+
 #[doc(hidden)]
 #[async_trait]
 impl Kernel for Fft {
+    async fn init(
+        &mut self,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        // only wake up for complete FFT frames and only ask for space for complete frames
+        sio.input(0).set_item_multiple(self.len);
+        sio.output(0).set_item_multiple(self.len);
+        Ok(())
+    }
+
     async fn work(
         &mut self,
         io: &mut WorkIo,
@@ -107,37 +184,38 @@ impl Kernel for Fft {
         let m = (m / self.len) * self.len;
 
         if m > 0 {
-            if matches!(self.direction, FftDirection::Inverse) && self.fft_shift {
-                for f in 0..(m / self.len) {
-                    let mut sym = vec![Complex32::new(0.0, 0.0); self.len];
-                    sym.copy_from_slice(&i[f * self.len..(f + 1) * self.len]);
-                    for k in 0..self.len {
-                        i[f * self.len + k] = sym[(k + self.len / 2) % self.len]
-                    }
-                }
-            }
-
-            self.plan.process_outofplace_with_scratch(
-                &mut i[0..m],
-                &mut o[0..m],
-                &mut self.scratch,
-            );
-
-            if matches!(self.direction, FftDirection::Forward) && self.fft_shift {
-                for f in 0..(m / self.len) {
-                    let mut sym = vec![Complex32::new(0.0, 0.0); self.len];
-                    sym.copy_from_slice(&o[f * self.len..(f + 1) * self.len]);
-                    for k in 0..self.len {
-                        o[f * self.len + k] = sym[(k + self.len / 2) % self.len]
-                    }
-                }
-            }
-
-            if let Some(fac) = self.normalize {
-                for item in o[0..m].iter_mut() {
-                    *item *= fac;
-                }
+            #[cfg(all(feature = "rayon", not(target_arch = "wasm32")))]
+            if m >= 2 * PARALLEL_CHUNK {
+                // split batches of FFTs across the compute pool
+                let chunk = cmp::max(PARALLEL_CHUNK / self.len, 1) * self.len;
+                let plan = self.plan.clone();
+                let (len, fft_shift, normalize) = (self.len, self.fft_shift, self.normalize);
+                let inverse = matches!(self.direction, FftDirection::Inverse);
+                crate::runtime::parallel::zip_chunks(
+                    &mut i[0..m],
+                    &mut o[0..m],
+                    chunk,
+                    move |i, o| {
+                        let mut scratch =
+                            vec![Complex32::new(0.0, 0.0); plan.get_outofplace_scratch_len()];
+                        process(
+                            &*plan,
+                            len,
+                            inverse,
+                            fft_shift,
+                            normalize,
+                            i,
+                            o,
+                            &mut scratch,
+                        );
+                    },
+                )
+                .await;
+            } else {
+                self.process(&mut i[0..m], &mut o[0..m]);
             }
+            #[cfg(not(all(feature = "rayon", not(target_arch = "wasm32"))))]
+            self.process(&mut i[0..m], &mut o[0..m]);
 
             sio.input(0).consume(m);
             sio.output(0).produce(m);
@@ -3,6 +3,9 @@ use futures::io::AsyncWriteExt;
 use std::fs::OpenOptions;
 
 use crate::anyhow::Result;
+use crate::blocks::FileFormat;
+use crate::blocks::FileSample;
+use crate::num_complex::Complex32;
 use crate::runtime::Block;
 use crate::runtime::BlockMeta;
 use crate::runtime::BlockMetaBuilder;
@@ -13,6 +16,9 @@ use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
 use crate::runtime::WorkIo;
 
+/// File format and conversion from the stream type.
+pub(super) type Encoder<T> = (FileFormat, fn(&T) -> Complex32);
+
 /// Write samples to a file.
 ///
 /// Samples are encoded using the in-memory format of the machine the runtime is
@@ -20,6 +26,8 @@ use crate::runtime::WorkIo;
 /// endian. Complex numbers are written with the real component coming before
 /// the complex component.
 ///
+/// Use the [FileSinkBuilder] to write other sample formats or to limit the number of samples.
+///
 /// # Inputs
 ///
 /// `in`: Input
@@ -41,18 +49,30 @@ use crate::runtime::WorkIo;
 pub struct FileSink<T: Send + 'static> {
     file_name: String,
     file: Option<File>,
+    /// Format and conversion from the stream type, if the file is not in the native format.
+    format: Option<Encoder<T>>,
+    /// Samples left to write.
+    remaining: Option<u64>,
+    buf: Vec<u8>,
     _type: std::marker::PhantomData<T>,
 }
 
 impl<T: Send + 'static> FileSink<T> {
     pub fn new<S: Into<String>>(file_name: S) -> Block {
+        Self::create(file_name.into(), None, None)
+    }
+
+    fn create(file_name: String, format: Option<Encoder<T>>, length: Option<u64>) -> Block {
         Block::new(
             BlockMetaBuilder::new("FileSink").build(),
             StreamIoBuilder::new().add_input::<T>("in").build(),
             MessageIoBuilder::new().build(),
             FileSink::<T> {
-                file_name: file_name.into(),
+                file_name,
                 file: None,
+                format,
+                remaining: length,
+                buf: Vec::new(),
                 _type: std::marker::PhantomData,
             },
         )
@@ -72,17 +92,35 @@ impl<T: Send + 'static> Kernel for FileSink<T> {
         let i = sio.input(0).slice_unchecked::<u8>();
 
         let item_size = std::mem::size_of::<T>();
-        let items = i.len() / item_size;
+        let available = i.len() / item_size;
+        let items = match self.remaining {
+            Some(r) => std::cmp::min(available as u64, r) as usize,
+            None => available,
+        };
 
         if items > 0 {
-            let i = &i[..items * item_size];
-            match self.file.as_mut().unwrap().write_all(i).await {
+            let data = match self.format {
+                Some((format, convert)) => {
+                    let s = format.item_size();
+                    self.buf.resize(items * s, 0);
+                    let i = sio.input(0).slice::<T>();
+                    for (x, b) in i[..items].iter().zip(self.buf.chunks_exact_mut(s)) {
+                        format.encode(convert(x), b);
+                    }
+                    &self.buf[..]
+                }
+                None => &i[..items * item_size],
+            };
+            match self.file.as_mut().unwrap().write_all(data).await {
                 Ok(()) => {}
                 Err(e) => panic!("FileSink: writing to {:?} failed: {e:?}", self.file_name),
             }
+            if let Some(r) = self.remaining.as_mut() {
+                *r -= items as u64;
+            }
         }
 
-        if sio.input(0).finished() {
+        if sio.input(0).finished() || self.remaining == Some(0) {
             io.finished = true;
         }
 
@@ -117,3 +155,70 @@ impl<T: Send + 'static> Kernel for FileSink<T> {
         Ok(())
     }
 }
+
+/// Write samples to a file, converting them to the given format.
+///
+/// Without a [format](FileSinkBuilder::format), samples are written in the native format, like
+/// with [FileSink::new]. With a format, samples of the stream type, i.e., `f32` or `Complex32`,
+/// are converted, saturating integer formats. Real samples can be written in complex formats with
+/// zero imaginary part, but complex samples can only be written in complex formats.
+///
+/// With a [length](FileSinkBuilder::length), the sink finishes after the given number of
+/// samples.
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::FileFormat;
+/// use futuresdr::blocks::FileSinkBuilder;
+/// use futuresdr::num_complex::Complex32;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // record one second at 1 Msps as 16-bit integers
+/// let sink = fg.add_block(
+///     FileSinkBuilder::<Complex32>::new("capture.ci16")
+///         .format(FileFormat::Ci16)
+///         .length(1_000_000)
+///         .build(),
+/// );
+/// ```
+pub struct FileSinkBuilder<T: Send + 'static> {
+    file_name: String,
+    format: Option<Encoder<T>>,
+    length: Option<u64>,
+}
+
+impl<T: Send + 'static> FileSinkBuilder<T> {
+    pub fn new<S: Into<String>>(file_name: S) -> FileSinkBuilder<T> {
+        FileSinkBuilder {
+            file_name: file_name.into(),
+            format: None,
+            length: None,
+        }
+    }
+
+    /// Maximum number of samples to write.
+    #[must_use]
+    pub fn length(mut self, length: u64) -> FileSinkBuilder<T> {
+        self.length = Some(length);
+        self
+    }
+
+    pub fn build(self) -> Block {
+        FileSink::<T>::create(self.file_name, self.format, self.length)
+    }
+}
+
+impl<T: FileSample> FileSinkBuilder<T> {
+    /// Sample format of the file.
+    #[must_use]
+    pub fn format(mut self, format: FileFormat) -> FileSinkBuilder<T> {
+        assert!(
+            !T::COMPLEX || format.is_complex(),
+            "complex streams require a complex format"
+        );
+        self.format = Some((format, T::to_complex));
+        self
+    }
+}
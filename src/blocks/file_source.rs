@@ -1,6 +1,9 @@
 use futures::AsyncReadExt;
+use futures::AsyncSeekExt;
+use std::io::SeekFrom;
 
 use crate::anyhow::Result;
+use crate::num_complex::Complex32;
 use crate::runtime::Block;
 use crate::runtime::BlockMeta;
 use crate::runtime::BlockMetaBuilder;
@@ -11,12 +14,125 @@ use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
 use crate::runtime::WorkIo;
 
+/// Sample format of a file.
+///
+/// All formats are little endian and interleave the real and imaginary parts of complex samples.
+/// Integer samples are scaled to [-1, 1).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
+    /// Complex 32-bit floats.
+    Cf32,
+    /// Complex 16-bit signed integers.
+    Ci16,
+    /// Complex 8-bit signed integers, e.g., from a HackRF.
+    Ci8,
+    /// Complex 8-bit unsigned integers with an offset of 127.5, e.g., from an RTL-SDR.
+    Cu8,
+    /// Real 32-bit floats.
+    F32,
+}
+
+impl FileFormat {
+    /// Size of one sample in bytes.
+    pub fn item_size(&self) -> usize {
+        match self {
+            FileFormat::Cf32 => 8,
+            FileFormat::Ci16 => 4,
+            FileFormat::Ci8 => 2,
+            FileFormat::Cu8 => 2,
+            FileFormat::F32 => 4,
+        }
+    }
+
+    pub fn is_complex(&self) -> bool {
+        *self != FileFormat::F32
+    }
+
+    /// Decode a sample of [item_size](Self::item_size) bytes.
+    pub fn decode(&self, b: &[u8]) -> Complex32 {
+        match self {
+            FileFormat::Cf32 => Complex32::new(
+                f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+                f32::from_le_bytes([b[4], b[5], b[6], b[7]]),
+            ),
+            FileFormat::Ci16 => Complex32::new(
+                i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0,
+                i16::from_le_bytes([b[2], b[3]]) as f32 / 32768.0,
+            ),
+            FileFormat::Ci8 => Complex32::new(b[0] as i8 as f32 / 128.0, b[1] as i8 as f32 / 128.0),
+            FileFormat::Cu8 => {
+                Complex32::new((b[0] as f32 - 127.5) / 128.0, (b[1] as f32 - 127.5) / 128.0)
+            }
+            FileFormat::F32 => Complex32::new(f32::from_le_bytes([b[0], b[1], b[2], b[3]]), 0.0),
+        }
+    }
+
+    /// Encode a sample into [item_size](Self::item_size) bytes, saturating integer formats.
+    pub fn encode(&self, x: Complex32, b: &mut [u8]) {
+        match self {
+            FileFormat::Cf32 => {
+                b[0..4].copy_from_slice(&x.re.to_le_bytes());
+                b[4..8].copy_from_slice(&x.im.to_le_bytes());
+            }
+            FileFormat::Ci16 => {
+                let q = |v: f32| (v * 32768.0).round().clamp(-32768.0, 32767.0) as i16;
+                b[0..2].copy_from_slice(&q(x.re).to_le_bytes());
+                b[2..4].copy_from_slice(&q(x.im).to_le_bytes());
+            }
+            FileFormat::Ci8 => {
+                let q = |v: f32| (v * 128.0).round().clamp(-128.0, 127.0) as i8 as u8;
+                b[0] = q(x.re);
+                b[1] = q(x.im);
+            }
+            FileFormat::Cu8 => {
+                let q = |v: f32| (v * 128.0 + 127.5).round().clamp(0.0, 255.0) as u8;
+                b[0] = q(x.re);
+                b[1] = q(x.im);
+            }
+            FileFormat::F32 => b[0..4].copy_from_slice(&x.re.to_le_bytes()),
+        }
+    }
+}
+
+/// Stream type that can be converted from and to a [FileFormat].
+pub trait FileSample: Copy + Send + 'static {
+    /// Whether the type can hold complex samples.
+    const COMPLEX: bool;
+    fn from_complex(x: Complex32) -> Self;
+    fn to_complex(&self) -> Complex32;
+}
+
+impl FileSample for f32 {
+    const COMPLEX: bool = false;
+    fn from_complex(x: Complex32) -> Self {
+        x.re
+    }
+    fn to_complex(&self) -> Complex32 {
+        Complex32::new(*self, 0.0)
+    }
+}
+
+impl FileSample for Complex32 {
+    const COMPLEX: bool = true;
+    fn from_complex(x: Complex32) -> Self {
+        x
+    }
+    fn to_complex(&self) -> Complex32 {
+        *self
+    }
+}
+
+/// File format and conversion to the stream type.
+pub(super) type Decoder<T> = (FileFormat, fn(Complex32) -> T);
+
 /// Read samples from a file.
 ///
 /// Samples are assumed to be encoded in the native format for the runtime. For
 /// example, on most machines, that means little endian. For complex samples,
 /// the real component must come before the complex component.
 ///
+/// Use the [FileSourceBuilder] to read other sample formats or only a part of the file.
+///
 /// # Inputs
 ///
 /// No inputs.
@@ -41,23 +157,85 @@ pub struct FileSource<T: Send + 'static> {
     file_name: String,
     file: Option<async_fs::File>,
     repeat: bool,
+    /// Format and conversion to the stream type, if the file is not in the native format.
+    format: Option<Decoder<T>>,
+    offset: u64,
+    length: Option<u64>,
+    /// Samples left in the current pass.
+    remaining: Option<u64>,
+    /// Whether the current pass produced samples.
+    produced: bool,
+    buf: Vec<u8>,
     _type: std::marker::PhantomData<T>,
 }
 
 impl<T: Send + 'static> FileSource<T> {
     pub fn new<S: Into<String>>(file_name: S, repeat: bool) -> Block {
+        Self::create(file_name.into(), repeat, None, 0, None)
+    }
+
+    fn create(
+        file_name: String,
+        repeat: bool,
+        format: Option<Decoder<T>>,
+        offset: u64,
+        length: Option<u64>,
+    ) -> Block {
         Block::new(
             BlockMetaBuilder::new("FileSource").build(),
             StreamIoBuilder::new().add_output::<T>("out").build(),
             MessageIoBuilder::new().build(),
             FileSource::<T> {
-                file_name: file_name.into(),
+                file_name,
                 file: None,
                 repeat,
+                format,
+                offset,
+                length,
+                remaining: length,
+                produced: false,
+                buf: Vec::new(),
                 _type: std::marker::PhantomData,
             },
         )
     }
+
+    fn item_size(&self) -> usize {
+        match self.format {
+            Some((f, _)) => f.item_size(),
+            None => std::mem::size_of::<T>(),
+        }
+    }
+
+    /// Seek to the start of the next pass. Returns false, if the source is done.
+    async fn rewind(&mut self) -> Result<bool> {
+        if !self.repeat || !self.produced {
+            return Ok(false);
+        }
+        let pos = self.offset * self.item_size() as u64;
+        self.file
+            .as_mut()
+            .unwrap()
+            .seek(SeekFrom::Start(pos))
+            .await?;
+        self.remaining = self.length;
+        self.produced = false;
+        Ok(true)
+    }
+
+    /// Read up to `out.len()` bytes, limited by the samples left in the pass, of which `partial`
+    /// bytes were already read.
+    async fn read(&mut self, out: &mut [u8], partial: usize) -> Result<usize> {
+        let item_size = self.item_size() as u64;
+        let n = match self.remaining {
+            Some(r) => std::cmp::min(out.len() as u64, r * item_size - partial as u64) as usize,
+            None => out.len(),
+        };
+        if n == 0 {
+            return Ok(0);
+        }
+        Ok(self.file.as_mut().unwrap().read(&mut out[..n]).await?)
+    }
 }
 
 #[doc(hidden)]
@@ -71,31 +249,61 @@ impl<T: Send + 'static> Kernel for FileSource<T> {
         _meta: &mut BlockMeta,
     ) -> Result<()> {
         let out = sio.output(0).slice_unchecked::<u8>();
-        let item_size = std::mem::size_of::<T>();
+        let t_size = std::mem::size_of::<T>();
+        let item_size = self.item_size();
 
-        debug_assert_eq!(out.len() % item_size, 0);
+        debug_assert_eq!(out.len() % t_size, 0);
 
-        let mut i = 0;
+        // read into the output buffer for native samples and convert afterwards otherwise
+        let mut buf = std::mem::take(&mut self.buf);
+        let n_bytes = match self.format {
+            Some(_) => {
+                buf.resize(out.len() / t_size * item_size, 0);
+                buf.len()
+            }
+            None => out.len(),
+        };
 
-        while i < out.len() {
-            match self.file.as_mut().unwrap().read(&mut out[i..]).await {
+        let mut i = 0;
+        let mut items = 0;
+        while items < n_bytes / item_size {
+            let target = match self.format {
+                Some(_) => &mut buf[i..],
+                None => &mut out[i..],
+            };
+            match self.read(target, i - items * item_size).await {
                 Ok(0) => {
-                    if self.repeat {
-                        self.file =
-                            Some(async_fs::File::open(self.file_name.clone()).await.unwrap());
-                    } else {
+                    // only complete samples are used, a trailing partial sample is dropped
+                    i = items * item_size;
+                    if !self.rewind().await? {
                         io.finished = true;
                         break;
                     }
                 }
-                Ok(written) => {
-                    i += written;
+                Ok(read) => {
+                    i += read;
+                    let complete = i / item_size;
+                    if complete > items {
+                        if let Some(r) = self.remaining.as_mut() {
+                            *r -= (complete - items) as u64;
+                        }
+                        self.produced = true;
+                        items = complete;
+                    }
                 }
                 Err(e) => panic!("FileSource: Error reading from file: {e:?}"),
             }
         }
 
-        sio.output(0).produce(i / item_size);
+        if let Some((format, convert)) = self.format {
+            let o = sio.output(0).slice::<T>();
+            for (b, y) in buf.chunks_exact(item_size).take(items).zip(o.iter_mut()) {
+                *y = convert(format.decode(b));
+            }
+        }
+        self.buf = buf;
+
+        sio.output(0).produce(items);
 
         Ok(())
     }
@@ -106,7 +314,104 @@ impl<T: Send + 'static> Kernel for FileSource<T> {
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
-        self.file = Some(async_fs::File::open(self.file_name.clone()).await.unwrap());
+        let mut file = async_fs::File::open(self.file_name.clone()).await?;
+        file.seek(SeekFrom::Start(self.offset * self.item_size() as u64))
+            .await?;
+        self.file = Some(file);
         Ok(())
     }
 }
+
+/// Read samples from a file, converting them from the given format.
+///
+/// Without a [format](FileSourceBuilder::format), samples are read in the native format, like
+/// with [FileSource::new]. With a format, samples are converted to the stream type, i.e., `f32`
+/// or `Complex32`. Complex formats can only be read into complex streams; real samples are read
+/// into complex streams with zero imaginary part.
+///
+/// The source can skip the first `offset` samples and read at most `length` samples. With
+/// [repeat](FileSourceBuilder::repeat), it loops over this part of the file.
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::FileFormat;
+/// use futuresdr::blocks::FileSourceBuilder;
+/// use futuresdr::num_complex::Complex32;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // loop over one second of an RTL-SDR recording at 2.4 Msps, skipping the first second
+/// let source = fg.add_block(
+///     FileSourceBuilder::<Complex32>::new("capture.cu8")
+///         .format(FileFormat::Cu8)
+///         .offset(2_400_000)
+///         .length(2_400_000)
+///         .repeat(true)
+///         .build(),
+/// );
+/// ```
+#[cfg_attr(docsrs, doc(cfg(not(target_arch = "wasm32"))))]
+pub struct FileSourceBuilder<T: Send + 'static> {
+    file_name: String,
+    repeat: bool,
+    format: Option<Decoder<T>>,
+    offset: u64,
+    length: Option<u64>,
+}
+
+impl<T: Send + 'static> FileSourceBuilder<T> {
+    pub fn new<S: Into<String>>(file_name: S) -> FileSourceBuilder<T> {
+        FileSourceBuilder {
+            file_name: file_name.into(),
+            repeat: false,
+            format: None,
+            offset: 0,
+            length: None,
+        }
+    }
+
+    /// Start over after the end of the file or the given length.
+    #[must_use]
+    pub fn repeat(mut self, repeat: bool) -> FileSourceBuilder<T> {
+        self.repeat = repeat;
+        self
+    }
+
+    /// Number of samples to skip at the start of the file.
+    #[must_use]
+    pub fn offset(mut self, offset: u64) -> FileSourceBuilder<T> {
+        self.offset = offset;
+        self
+    }
+
+    /// Maximum number of samples to read (per pass, if repeated).
+    #[must_use]
+    pub fn length(mut self, length: u64) -> FileSourceBuilder<T> {
+        self.length = Some(length);
+        self
+    }
+
+    pub fn build(self) -> Block {
+        FileSource::<T>::create(
+            self.file_name,
+            self.repeat,
+            self.format,
+            self.offset,
+            self.length,
+        )
+    }
+}
+
+impl<T: FileSample> FileSourceBuilder<T> {
+    /// Sample format of the file.
+    #[must_use]
+    pub fn format(mut self, format: FileFormat) -> FileSourceBuilder<T> {
+        assert!(
+            T::COMPLEX || !format.is_complex(),
+            "complex formats require a complex stream"
+        );
+        self.format = Some((format, T::from_complex));
+        self
+    }
+}
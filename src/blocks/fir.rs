@@ -1,4 +1,9 @@
+use std::any::Any;
+use std::any::TypeId;
+
 use crate::anyhow::Result;
+use crate::kernels::FirKernel;
+use crate::num_complex::Complex32;
 use crate::runtime::Block;
 use crate::runtime::BlockMeta;
 use crate::runtime::BlockMetaBuilder;
@@ -96,7 +101,8 @@ where
 /// Create a [Fir] filter.
 ///
 /// Uses `futuredsp` to pick the optimal FIR implementation for the given
-/// constraints.
+/// constraints. Non-resampling filters with `f32` taps for `f32` or `Complex32` samples use the
+/// vectorized [FirKernel](crate::kernels::FirKernel).
 ///
 /// Note that there must be an implementation of [futuredsp::TapsAccessor] for
 /// the taps object you pass in, see docs for details.
@@ -127,6 +133,9 @@ where
 /// let fir = fg.add_block(FirBuilder::new::<f32, f32, f32, Vec<f32>>(vec![1.0, 2.0, 3.0]));
 ///
 /// let fir = fg.add_block(FirBuilder::new_resampling_with_taps::<f32, f32, f32, _>(3, 2, vec![1.0f32, 2.0, 3.0]));
+///
+/// let taps = futuresdr::firdes::lowpass::<f32>(0.1, &futuresdr::futuredsp::windows::hamming(64, false));
+/// let fir = fg.add_block(FirBuilder::new_decimating::<Complex<f32>, Complex<f32>, f32, _>(4, taps));
 /// ```
 pub struct FirBuilder {
     //
@@ -143,6 +152,9 @@ impl FirBuilder {
         NonResamplingFirKernel<InputType, OutputType, Taps, TapType>:
             UnaryKernel<InputType, OutputType>,
     {
+        if let Some(block) = Self::vectorized::<InputType, OutputType, TapType, Taps>(&taps) {
+            return block;
+        }
         Fir::<
             InputType,
             OutputType,
@@ -151,6 +163,50 @@ impl FirBuilder {
         >::new(NonResamplingFirKernel::new(taps))
     }
 
+    /// Vectorized filter, if the sample and tap types are supported.
+    fn vectorized<InputType, OutputType, TapType, Taps>(taps: &Taps) -> Option<Block>
+    where
+        InputType: 'static,
+        OutputType: 'static,
+        TapType: 'static,
+        Taps: TapsAccessor<TapType = TapType>,
+    {
+        if TypeId::of::<TapType>() != TypeId::of::<f32>()
+            || TypeId::of::<InputType>() != TypeId::of::<OutputType>()
+            || taps.num_taps() == 0
+        {
+            return None;
+        }
+        let taps: Vec<f32> = (0..taps.num_taps())
+            .map(|i| unsafe { *(&taps.get(i) as &dyn Any).downcast_ref::<f32>().unwrap() })
+            .collect();
+
+        if TypeId::of::<InputType>() == TypeId::of::<f32>() {
+            Some(Fir::<f32, f32, f32, FirKernel<f32>>::new(FirKernel::new(
+                taps,
+            )))
+        } else if TypeId::of::<InputType>() == TypeId::of::<Complex32>() {
+            Some(Fir::<Complex32, Complex32, f32, FirKernel<Complex32>>::new(
+                FirKernel::new(taps),
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// Create a new FIR filter that only outputs every `decim`-th sample.
+    pub fn new_decimating<InputType, OutputType, TapType, Taps>(decim: usize, taps: Taps) -> Block
+    where
+        InputType: 'static + Send,
+        OutputType: 'static + Send,
+        TapType: 'static,
+        Taps: 'static + TapsAccessor<TapType = TapType>,
+        PolyphaseResamplingFirKernel<InputType, OutputType, Taps, TapType>:
+            UnaryKernel<InputType, OutputType>,
+    {
+        FirBuilder::new_resampling_with_taps::<InputType, OutputType, TapType, Taps>(1, decim, taps)
+    }
+
     /// Create a new rationally resampling FIR filter that changes the sampling
     /// rate by a factor `interp/decim`. The interpolation filter is constructed
     /// using default parameters.
@@ -194,3 +250,129 @@ impl FirBuilder {
         >::new(PolyphaseResamplingFirKernel::new(interp, decim, taps))
     }
 }
+
+/// Rate change of a [root-raised-cosine filter](RrcFilterBuilder).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RrcMode {
+    /// Filter without rate change, e.g., as matched filter in front of a symbol synchronizer.
+    Filter,
+    /// Upsample symbols by `sps` for pulse shaping.
+    Interpolate,
+    /// Matched filter that outputs one sample per symbol.
+    Decimate,
+}
+
+/// Create a root-raised-cosine [Fir] filter.
+///
+/// The taps are designed with [firdes::root_raised_cosine] and have unit energy, i.e., pulse
+/// shaping and matched filtering with the same parameters result in symbols with unit
+/// amplitude. `span * sps` has to be even.
+///
+/// # Inputs
+///
+/// `in`: Input (f32 or Complex32)
+///
+/// # Outputs
+///
+/// `out`: Output
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::RrcFilterBuilder;
+/// use futuresdr::num_complex::Complex32;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let pulse_shape = fg.add_block(
+///     RrcFilterBuilder::new(4)
+///         .rolloff(0.35)
+///         .span(10)
+///         .interpolate()
+///         .build::<Complex32>(),
+/// );
+/// let matched_filter = fg.add_block(RrcFilterBuilder::new(4).build::<Complex32>());
+/// ```
+pub struct RrcFilterBuilder {
+    sps: usize,
+    rolloff: f64,
+    span: usize,
+    mode: RrcMode,
+}
+
+impl RrcFilterBuilder {
+    /// Filter for `sps` samples per symbol (default: roll-off 0.35, span of 8 symbols, no rate
+    /// change).
+    pub fn new(sps: usize) -> RrcFilterBuilder {
+        assert!(sps > 0, "samples per symbol must be positive");
+        RrcFilterBuilder {
+            sps,
+            rolloff: 0.35,
+            span: 8,
+            mode: RrcMode::Filter,
+        }
+    }
+
+    /// Roll-off factor in `(0, 1]`.
+    #[must_use]
+    pub fn rolloff(mut self, rolloff: f64) -> RrcFilterBuilder {
+        self.rolloff = rolloff;
+        self
+    }
+
+    /// Length of the filter in symbols.
+    #[must_use]
+    pub fn span(mut self, span: usize) -> RrcFilterBuilder {
+        self.span = span;
+        self
+    }
+
+    /// Set the rate change.
+    #[must_use]
+    pub fn mode(mut self, mode: RrcMode) -> RrcFilterBuilder {
+        self.mode = mode;
+        self
+    }
+
+    /// Upsample symbols by `sps` for pulse shaping.
+    #[must_use]
+    pub fn interpolate(self) -> RrcFilterBuilder {
+        self.mode(RrcMode::Interpolate)
+    }
+
+    /// Output one sample per symbol.
+    #[must_use]
+    pub fn decimate(self) -> RrcFilterBuilder {
+        self.mode(RrcMode::Decimate)
+    }
+
+    /// Filter taps.
+    pub fn taps(&self) -> Vec<f32> {
+        firdes::root_raised_cosine::<f32>(self.span, self.sps, self.rolloff)
+    }
+
+    pub fn build<SampleType>(self) -> Block
+    where
+        SampleType: 'static + Send,
+        NonResamplingFirKernel<SampleType, SampleType, Vec<f32>, f32>:
+            UnaryKernel<SampleType, SampleType>,
+        PolyphaseResamplingFirKernel<SampleType, SampleType, Vec<f32>, f32>:
+            UnaryKernel<SampleType, SampleType>,
+    {
+        let mut taps = self.taps();
+        match self.mode {
+            RrcMode::Filter => FirBuilder::new::<SampleType, SampleType, f32, _>(taps),
+            RrcMode::Interpolate => {
+                // polyphase implementation requires a multiple of sps taps
+                let padding = (self.sps - taps.len() % self.sps) % self.sps;
+                taps.resize(taps.len() + padding, 0.0);
+                FirBuilder::new_resampling_with_taps::<SampleType, SampleType, f32, _>(
+                    self.sps, 1, taps,
+                )
+            }
+            RrcMode::Decimate => {
+                FirBuilder::new_decimating::<SampleType, SampleType, f32, _>(self.sps, taps)
+            }
+        }
+    }
+}
@@ -0,0 +1,228 @@
+use futures::FutureExt;
+
+use crate::blocks::Crc;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIoBuilder;
+
+/// Additive scrambler, based on a Fibonacci LFSR.
+///
+/// The LFSR shifts to the right, feeding back the parity of the `taps` to bit `degree - 1`. Each
+/// byte is XORed with the lowest eight bits of the register before it is advanced by eight bits.
+/// Whitening is its own inverse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Whitening {
+    degree: usize,
+    taps: u32,
+    seed: u32,
+}
+
+impl Whitening {
+    /// Create a whitening sequence with an LFSR of `degree` 8 to 32 bits.
+    pub fn new(degree: usize, taps: u32, seed: u32) -> Whitening {
+        assert!((8..=32).contains(&degree), "degree has to be in [8, 32]");
+        assert!(seed != 0, "seed must not be zero");
+        Whitening { degree, taps, seed }
+    }
+
+    /// PN9 sequence (x^9 + x^5 + 1, seed 0x1FF), as used by the TI CC1101 and many other
+    /// packet radios.
+    pub fn pn9() -> Whitening {
+        Self::new(9, 0x21, 0x1ff)
+    }
+
+    /// Whiten or dewhiten `data` in place.
+    pub fn apply(&self, data: &mut [u8]) {
+        let mut key = self.seed;
+        for b in data.iter_mut() {
+            *b ^= key as u8;
+            for _ in 0..8 {
+                let bit = (key & self.taps).count_ones() & 1;
+                key = (key >> 1) | (bit << (self.degree - 1));
+            }
+        }
+    }
+}
+
+/// Frame layout of the [Framer] block.
+#[derive(Debug, Clone)]
+struct Format {
+    preamble: Vec<u8>,
+    sync_word: Vec<u8>,
+    length_bytes: usize,
+    crc: Option<Crc>,
+    whitening: Option<Whitening>,
+}
+
+impl Format {
+    /// Build the frame for a payload, returning [None] if the payload is too long.
+    fn frame(&self, payload: &[u8]) -> Option<Vec<u8>> {
+        let len = payload.len() as u64;
+        if self.length_bytes < 8 && len >> (8 * self.length_bytes) != 0 {
+            return None;
+        }
+
+        let mut bytes = len.to_be_bytes()[8 - self.length_bytes..].to_vec();
+        bytes.extend_from_slice(payload);
+        if let Some(crc) = self.crc {
+            bytes.extend_from_slice(&crc.encode(payload));
+        }
+        if let Some(w) = self.whitening {
+            w.apply(&mut bytes);
+        }
+
+        let mut bits =
+            Vec::with_capacity(self.preamble.len() + self.sync_word.len() + 8 * bytes.len());
+        bits.extend_from_slice(&self.preamble);
+        bits.extend_from_slice(&self.sync_word);
+        for b in bytes {
+            bits.extend((0..8).rev().map(|i| (b >> i) & 1));
+        }
+        Some(bits)
+    }
+}
+
+/// Build frames from payload PDUs.
+pub struct Framer {
+    format: Format,
+}
+
+impl Framer {
+    fn new(format: Format) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("Framer").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_input(
+                    "in",
+                    |block: &mut Framer,
+                     mio: &mut MessageIo<Framer>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            if let Pmt::Blob(v) = p {
+                                if let Some(bits) = block.format.frame(&v) {
+                                    mio.post(0, Pmt::Blob(bits)).await;
+                                } else {
+                                    warn!(
+                                        "Framer: payload of {} bytes exceeds length field, dropping",
+                                        v.len()
+                                    );
+                                }
+                            } else {
+                                warn!("Framer: received wrong PMT type. {:?}", p);
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .add_output("out")
+                .build(),
+            Framer { format },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for Framer {}
+
+/// Build frames from payload PDUs.
+///
+/// A frame consists of
+/// - the preamble (default: 32 alternating bits, starting with 1),
+/// - the sync word,
+/// - the payload length in bytes as big-endian length field (default: 2 bytes, 0 to disable),
+/// - the payload,
+/// - optionally, the [Crc] of the payload, appended like by the
+///   [CrcAppend](crate::blocks::CrcAppend) block.
+///
+/// Optionally, the length field, payload, and CRC are whitened. Frames are posted as [Pmt::Blob]
+/// with one bit per byte, most significant bit first, ready to be streamed into a modulator, e.g.,
+/// with a [PduToStream](crate::blocks::PduToStream) block. On the receive side, the sync word can
+/// be found with a [CorrelateAccessCode](crate::blocks::CorrelateAccessCodeBuilder) block.
+///
+/// Preamble and sync word are given as bits, one bit per byte. Payloads that do not fit the
+/// length field are dropped.
+///
+/// # Inputs
+///
+/// **Message**: `in`: Payload ([Pmt::Blob])
+///
+/// # Outputs
+///
+/// **Message**: `out`: Frame bits ([Pmt::Blob])
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::Crc;
+/// use futuresdr::blocks::FramerBuilder;
+/// use futuresdr::blocks::Whitening;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let sync_word = [1, 0, 1, 1, 0, 1, 0, 0, 1, 1, 1, 0, 0, 1, 0, 0];
+/// let framer = fg.add_block(
+///     FramerBuilder::new(&sync_word)
+///         .crc(Crc::crc16_ccitt())
+///         .whitening(Whitening::pn9())
+///         .build(),
+/// );
+/// ```
+pub struct FramerBuilder {
+    format: Format,
+}
+
+impl FramerBuilder {
+    pub fn new(sync_word: &[u8]) -> FramerBuilder {
+        FramerBuilder {
+            format: Format {
+                preamble: (0..32).map(|i| (i + 1) % 2).collect(),
+                sync_word: sync_word.iter().map(|b| b & 1).collect(),
+                length_bytes: 2,
+                crc: None,
+                whitening: None,
+            },
+        }
+    }
+
+    /// Preamble bits.
+    #[must_use]
+    pub fn preamble(mut self, preamble: &[u8]) -> FramerBuilder {
+        self.format.preamble = preamble.iter().map(|b| b & 1).collect();
+        self
+    }
+
+    /// Number of bytes of the length field, 0 to disable.
+    #[must_use]
+    pub fn length_field(mut self, bytes: usize) -> FramerBuilder {
+        assert!(bytes <= 8, "length field can have at most 8 bytes");
+        self.format.length_bytes = bytes;
+        self
+    }
+
+    /// Append a CRC of the payload.
+    #[must_use]
+    pub fn crc(mut self, crc: Crc) -> FramerBuilder {
+        self.format.crc = Some(crc);
+        self
+    }
+
+    /// Whiten length field, payload, and CRC.
+    #[must_use]
+    pub fn whitening(mut self, whitening: Whitening) -> FramerBuilder {
+        self.format.whitening = Some(whitening);
+        self
+    }
+
+    pub fn build(self) -> Block {
+        Framer::new(self.format)
+    }
+}
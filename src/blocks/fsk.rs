@@ -0,0 +1,452 @@
+use std::f32::consts::PI;
+
+use crate::anyhow::Result;
+use crate::blocks::gfsk::ClockRecovery;
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Tones of M-FSK with the outermost tones at `-deviation` and `deviation`.
+fn fsk_tones(levels: usize, deviation: f32) -> Vec<f32> {
+    assert!(levels >= 2, "at least two levels required");
+    (0..levels)
+        .map(|k| deviation * (2 * k) as f32 / (levels - 1) as f32 - deviation)
+        .collect()
+}
+
+/// Continuous-phase modulator, sending one of several tones per symbol.
+struct ToneMod<T: Copy + Send + 'static> {
+    /// Phase increments of the tones.
+    tones: Vec<f32>,
+    sps: f32,
+    /// Samples left of the current symbol.
+    remaining: f32,
+    inc: f32,
+    phase: f32,
+    map: fn(f32) -> T,
+}
+
+impl<T: Copy + Send + 'static> ToneMod<T> {
+    /// Tone frequencies are normalized to the sample rate.
+    fn block(name: &str, tones: Vec<f32>, sps: f32, map: fn(f32) -> T) -> Block {
+        assert!(sps >= 1.0, "at least one sample per symbol required");
+
+        Block::new(
+            BlockMetaBuilder::new(name).build(),
+            StreamIoBuilder::new()
+                .add_input::<u8>("in")
+                .add_output::<T>("out")
+                .build(),
+            MessageIoBuilder::<Self>::new().build(),
+            ToneMod {
+                tones: tones.iter().map(|f| 2.0 * PI * f).collect(),
+                sps,
+                remaining: 0.0,
+                inc: 0.0,
+                phase: 0.0,
+                map,
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: Copy + Send + 'static> Kernel for ToneMod<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<u8>();
+        let o = sio.output(0).slice::<T>();
+
+        let mut consumed = 0;
+        let mut produced = 0;
+        while produced < o.len() {
+            if self.remaining <= 0.0 {
+                if consumed == i.len() {
+                    break;
+                }
+                self.inc = self.tones[i[consumed] as usize % self.tones.len()];
+                self.remaining += self.sps;
+                consumed += 1;
+            }
+            o[produced] = (self.map)(self.phase);
+            self.phase = (self.phase + self.inc) % (2.0 * PI);
+            self.remaining -= 1.0;
+            produced += 1;
+        }
+
+        sio.input(0).consume(consumed);
+        sio.output(0).produce(produced);
+
+        if sio.input(0).finished() && consumed == i.len() && self.remaining <= 0.0 {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Non-coherent demodulator, deciding for the tone with the highest energy in a window of one
+/// symbol.
+struct ToneDemod<T: Copy + Send + Into<Complex32> + 'static> {
+    /// Conjugated tones over one symbol.
+    refs: Vec<Vec<Complex32>>,
+    /// Last symbol of samples, the oldest at `pos`.
+    window: Vec<Complex32>,
+    pos: usize,
+    decision: usize,
+    clock: ClockRecovery,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: Copy + Send + Into<Complex32> + 'static> ToneDemod<T> {
+    /// Tone frequencies are normalized to the sample rate.
+    fn block(name: &str, tones: Vec<f32>, sps: f32, clock_gain: f32) -> Block {
+        let len = (sps.round() as usize).max(1);
+        let refs = tones
+            .iter()
+            .map(|f| {
+                (0..len)
+                    .map(|n| Complex32::from_polar(1.0, -2.0 * PI * f * n as f32))
+                    .collect()
+            })
+            .collect();
+
+        Block::new(
+            BlockMetaBuilder::new(name).build(),
+            StreamIoBuilder::new()
+                .add_input::<T>("in")
+                .add_output::<u8>("out")
+                .build(),
+            MessageIoBuilder::<Self>::new().build(),
+            ToneDemod::<T> {
+                refs,
+                window: vec![Complex32::new(0.0, 0.0); len],
+                pos: 0,
+                decision: 0,
+                clock: ClockRecovery::new(sps, clock_gain),
+                _type: std::marker::PhantomData,
+            },
+        )
+    }
+
+    fn detect(&self) -> usize {
+        let (newer, older) = self.window.split_at(self.pos);
+        let energy = |r: &Vec<Complex32>| {
+            older
+                .iter()
+                .chain(newer.iter())
+                .zip(r.iter())
+                .map(|(x, r)| x * r)
+                .sum::<Complex32>()
+                .norm_sqr()
+        };
+        self.refs
+            .iter()
+            .map(energy)
+            .enumerate()
+            .fold(
+                (0, -1.0),
+                |best, (k, e)| if e > best.1 { (k, e) } else { best },
+            )
+            .0
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: Copy + Send + Into<Complex32> + 'static> Kernel for ToneDemod<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<T>();
+        let o = sio.output(0).slice::<u8>();
+
+        let mut consumed = 0;
+        let mut produced = 0;
+        for x in i.iter() {
+            if produced == o.len() {
+                break;
+            }
+
+            self.window[self.pos] = (*x).into();
+            self.pos = (self.pos + 1) % self.window.len();
+
+            let decision = self.detect();
+            if self.clock.tick(decision != self.decision) {
+                o[produced] = decision as u8;
+                produced += 1;
+            }
+            self.decision = decision;
+            consumed += 1;
+        }
+
+        sio.input(0).consume(consumed);
+        sio.output(0).produce(produced);
+
+        if sio.input(0).finished() && consumed == i.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// M-FSK modulator.
+///
+/// Sends one of `levels` equally spaced tones per symbol with continuous phase. Symbol `0` is
+/// sent at `-deviation`, symbol `levels - 1` at `deviation` (normalized to the sample rate), i.e.,
+/// for 2-FSK, the input bit selects the upper tone. Samples per symbol may be fractional. For
+/// non-coherent demodulation, the tone spacing should be at least the symbol rate.
+///
+/// # Inputs
+///
+/// `in`: Symbols in `0..levels` (u8)
+///
+/// # Outputs
+///
+/// `out`: Modulated signal (Complex32)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::FskMod;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // 4-FSK at 1200 baud, 48 kHz sample rate, tones at +-600 Hz and +-1800 Hz
+/// let fsk = fg.add_block(FskMod::new(4, 40.0, 1800.0 / 48000.0));
+/// ```
+pub struct FskMod;
+
+impl FskMod {
+    pub fn new(levels: usize, sps: f32, deviation: f32) -> Block {
+        ToneMod::<Complex32>::block("FskMod", fsk_tones(levels, deviation), sps, |p| {
+            Complex32::from_polar(1.0, p)
+        })
+    }
+}
+
+/// Non-coherent M-FSK demodulator.
+///
+/// Counterpart of the [FskMod] block. For each sample, the energies of the tones in a window of
+/// one symbol are compared. The symbol clock is recovered from changes of the strongest tone,
+/// using a clock recovery gain (default 0.1) that trades tracking speed for jitter.
+///
+/// # Inputs
+///
+/// `in`: Modulated signal (Complex32)
+///
+/// # Outputs
+///
+/// `out`: Symbols in `0..levels` (u8)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::FskDemodBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let demod = fg.add_block(FskDemodBuilder::new(4, 40.0, 1800.0 / 48000.0).build());
+/// ```
+pub struct FskDemodBuilder {
+    levels: usize,
+    sps: f32,
+    deviation: f32,
+    clock_gain: f32,
+}
+
+impl FskDemodBuilder {
+    pub fn new(levels: usize, sps: f32, deviation: f32) -> FskDemodBuilder {
+        FskDemodBuilder {
+            levels,
+            sps,
+            deviation,
+            clock_gain: 0.1,
+        }
+    }
+
+    /// Gain of the clock recovery in `(0, 0.5]`.
+    #[must_use]
+    pub fn clock_gain(mut self, gain: f32) -> FskDemodBuilder {
+        self.clock_gain = gain;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        ToneDemod::<Complex32>::block(
+            "FskDemod",
+            fsk_tones(self.levels, self.deviation),
+            self.sps,
+            self.clock_gain,
+        )
+    }
+}
+
+/// AFSK modulator.
+///
+/// Sends bits as audio tones with continuous phase, the mark tone for ones and the space tone for
+/// zeros. The default parameters are those of the Bell 202 modem (1200 baud, mark at 1200 Hz,
+/// space at 2200 Hz), as used, e.g., for APRS. NRZI coding and HDLC framing are not applied.
+///
+/// # Inputs
+///
+/// `in`: Bits (u8)
+///
+/// # Outputs
+///
+/// `out`: Audio (f32)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::AfskModBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let afsk = fg.add_block(AfskModBuilder::new(48000.0).build());
+/// ```
+pub struct AfskModBuilder {
+    sample_rate: f32,
+    baud: f32,
+    mark: f32,
+    space: f32,
+}
+
+impl AfskModBuilder {
+    pub fn new(sample_rate: f32) -> AfskModBuilder {
+        AfskModBuilder {
+            sample_rate,
+            baud: 1200.0,
+            mark: 1200.0,
+            space: 2200.0,
+        }
+    }
+
+    /// Symbol rate in baud.
+    #[must_use]
+    pub fn baud(mut self, baud: f32) -> AfskModBuilder {
+        self.baud = baud;
+        self
+    }
+
+    /// Frequency of the mark (one) tone in Hz.
+    #[must_use]
+    pub fn mark(mut self, mark: f32) -> AfskModBuilder {
+        self.mark = mark;
+        self
+    }
+
+    /// Frequency of the space (zero) tone in Hz.
+    #[must_use]
+    pub fn space(mut self, space: f32) -> AfskModBuilder {
+        self.space = space;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        ToneMod::<f32>::block(
+            "AfskMod",
+            vec![self.space / self.sample_rate, self.mark / self.sample_rate],
+            self.sample_rate / self.baud,
+            |p| p.sin(),
+        )
+    }
+}
+
+/// Non-coherent AFSK demodulator.
+///
+/// Counterpart of the [AfskModBuilder], with the same defaults (Bell 202). Outputs one for the mark
+/// and zero for the space tone; NRZI decoding is left to downstream blocks. The clock recovery
+/// gain (default 0.1) trades tracking speed for jitter.
+///
+/// # Inputs
+///
+/// `in`: Audio (f32)
+///
+/// # Outputs
+///
+/// `out`: Bits (u8)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::AfskDemodBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let demod = fg.add_block(AfskDemodBuilder::new(48000.0).build());
+/// ```
+pub struct AfskDemodBuilder {
+    sample_rate: f32,
+    baud: f32,
+    mark: f32,
+    space: f32,
+    clock_gain: f32,
+}
+
+impl AfskDemodBuilder {
+    pub fn new(sample_rate: f32) -> AfskDemodBuilder {
+        AfskDemodBuilder {
+            sample_rate,
+            baud: 1200.0,
+            mark: 1200.0,
+            space: 2200.0,
+            clock_gain: 0.1,
+        }
+    }
+
+    /// Symbol rate in baud.
+    #[must_use]
+    pub fn baud(mut self, baud: f32) -> AfskDemodBuilder {
+        self.baud = baud;
+        self
+    }
+
+    /// Frequency of the mark (one) tone in Hz.
+    #[must_use]
+    pub fn mark(mut self, mark: f32) -> AfskDemodBuilder {
+        self.mark = mark;
+        self
+    }
+
+    /// Frequency of the space (zero) tone in Hz.
+    #[must_use]
+    pub fn space(mut self, space: f32) -> AfskDemodBuilder {
+        self.space = space;
+        self
+    }
+
+    /// Gain of the clock recovery in `(0, 0.5]`.
+    #[must_use]
+    pub fn clock_gain(mut self, gain: f32) -> AfskDemodBuilder {
+        self.clock_gain = gain;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        ToneDemod::<f32>::block(
+            "AfskDemod",
+            vec![self.space / self.sample_rate, self.mark / self.sample_rate],
+            self.sample_rate / self.baud,
+            self.clock_gain,
+        )
+    }
+}
@@ -0,0 +1,352 @@
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+
+use crate::anyhow::Result;
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Length of the Gaussian filter in symbols.
+const GAUSSIAN_SPAN: usize = 4;
+
+/// Frequency pulse of one symbol, i.e., a Gaussian filter with bandwidth-time product `bt`,
+/// convolved with a rectangular pulse of one symbol. The pulse is normalized, such that a
+/// sequence of equal symbols results in a frequency of one.
+pub(crate) fn gaussian_pulse(sps: usize, bt: f32) -> Vec<f32> {
+    let sigma = (2.0f32.ln()).sqrt() / (2.0 * PI * bt);
+    let n = GAUSSIAN_SPAN * sps + 1;
+    let center = (n - 1) as f32 / 2.0;
+    let gaussian: Vec<f32> = (0..n)
+        .map(|i| {
+            let t = (i as f32 - center) / sps as f32;
+            (-t * t / (2.0 * sigma * sigma)).exp()
+        })
+        .collect();
+    let sum: f32 = gaussian.iter().sum();
+
+    let mut pulse = vec![0.0; n + sps - 1];
+    for (i, g) in gaussian.iter().enumerate() {
+        for p in pulse[i..i + sps].iter_mut() {
+            *p += g / sum;
+        }
+    }
+    pulse
+}
+
+/// Symbol clock recovery from symbol transitions.
+///
+/// A phase accumulator advances by one symbol every `sps` samples. Symbol transitions (e.g., zero
+/// crossings of the input) are expected halfway between symbols; the phase is pulled towards
+/// this point by `gain` times the observed error.
+pub(crate) struct ClockRecovery {
+    step: f32,
+    gain: f32,
+    phase: f32,
+    last: f32,
+}
+
+impl ClockRecovery {
+    pub(crate) fn new(sps: f32, gain: f32) -> ClockRecovery {
+        assert!(sps >= 2.0, "at least two samples per symbol required");
+        assert!(
+            gain > 0.0 && gain <= 0.5,
+            "clock recovery gain must be in (0, 0.5]"
+        );
+        ClockRecovery {
+            step: 1.0 / sps,
+            gain,
+            phase: 0.0,
+            last: 0.0,
+        }
+    }
+
+    /// Advance by one sample, returning true if it is the center of a symbol.
+    pub(crate) fn tick(&mut self, transition: bool) -> bool {
+        let mut center = false;
+        self.phase += self.step;
+        if self.phase >= 1.0 {
+            self.phase -= 1.0;
+            center = true;
+        }
+        if transition {
+            self.phase += self.gain * (0.5 - self.phase);
+        }
+        center
+    }
+
+    /// Feed a sample, returning it if it is the center of a symbol.
+    pub(crate) fn process(&mut self, x: f32) -> Option<f32> {
+        let transition = (x >= 0.0) != (self.last >= 0.0);
+        self.last = x;
+        if self.tick(transition) {
+            Some(x)
+        } else {
+            None
+        }
+    }
+}
+
+/// GFSK modulator.
+pub struct GfskMod {
+    sps: usize,
+    pulse: Vec<f32>,
+    symbols: VecDeque<f32>,
+    sensitivity: f32,
+    phase: f32,
+}
+
+impl GfskMod {
+    pub fn new(sps: usize, bt: f32, index: f32) -> Block {
+        assert!(sps > 0, "samples per symbol must be positive");
+        assert!(bt > 0.0, "bandwidth-time product must be positive");
+
+        let pulse = gaussian_pulse(sps, bt);
+        let n_symbols = (pulse.len() + sps - 1) / sps;
+
+        Block::new(
+            BlockMetaBuilder::new("GfskMod").build(),
+            StreamIoBuilder::new()
+                .add_input::<u8>("in")
+                .add_output::<Complex32>("out")
+                .build(),
+            MessageIoBuilder::<GfskMod>::new().build(),
+            GfskMod {
+                sps,
+                pulse,
+                symbols: VecDeque::from(vec![0.0; n_symbols]),
+                sensitivity: PI * index / sps as f32,
+                phase: 0.0,
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for GfskMod {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<u8>();
+        let o = sio.output(0).slice::<Complex32>();
+
+        let m = std::cmp::min(i.len(), o.len() / self.sps);
+        for (b, y) in i[..m].iter().zip(o.chunks_exact_mut(self.sps)) {
+            self.symbols.pop_back();
+            self.symbols
+                .push_front(if *b & 1 == 1 { 1.0 } else { -1.0 });
+
+            for (j, y) in y.iter_mut().enumerate() {
+                let f: f32 = self
+                    .symbols
+                    .iter()
+                    .zip(self.pulse.iter().skip(j).step_by(self.sps))
+                    .map(|(s, p)| s * p)
+                    .sum();
+                self.phase = (self.phase + self.sensitivity * f) % (2.0 * PI);
+                *y = Complex32::from_polar(1.0, self.phase);
+            }
+        }
+
+        sio.input(0).consume(m);
+        sio.output(0).produce(m * self.sps);
+
+        if sio.input(0).finished() && m == i.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// GFSK modulator.
+///
+/// Maps bits (LSB of the input bytes) to `-1` and `1`, shapes them with a Gaussian filter with
+/// bandwidth-time product `bt` (default 0.5), and frequency modulates them with modulation index
+/// `index` (default 0.5), outputting `sps` samples per bit. The defaults result in GMSK, as used,
+/// e.g., for 9600-baud G3RUH packet radio; GSM uses a BT of 0.3.
+///
+/// # Inputs
+///
+/// `in`: Bits (u8)
+///
+/// # Outputs
+///
+/// `out`: Modulated signal (Complex32)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::GfskModBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let gmsk = fg.add_block(GfskModBuilder::new(8).bt(0.3).build());
+/// ```
+pub struct GfskModBuilder {
+    sps: usize,
+    bt: f32,
+    index: f32,
+}
+
+impl GfskModBuilder {
+    pub fn new(sps: usize) -> GfskModBuilder {
+        GfskModBuilder {
+            sps,
+            bt: 0.5,
+            index: 0.5,
+        }
+    }
+
+    /// Bandwidth-time product of the Gaussian filter.
+    #[must_use]
+    pub fn bt(mut self, bt: f32) -> GfskModBuilder {
+        self.bt = bt;
+        self
+    }
+
+    /// Modulation index, i.e., the frequency deviation relative to the symbol rate.
+    #[must_use]
+    pub fn index(mut self, index: f32) -> GfskModBuilder {
+        self.index = index;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        GfskMod::new(self.sps, self.bt, self.index)
+    }
+}
+
+/// GFSK demodulator.
+pub struct GfskDemod {
+    last: Complex32,
+    average: VecDeque<f32>,
+    sum: f32,
+    clock: ClockRecovery,
+}
+
+impl GfskDemod {
+    pub fn new(sps: f32, clock_gain: f32) -> Block {
+        let len = (sps.round() as usize).max(1);
+
+        Block::new(
+            BlockMetaBuilder::new("GfskDemod").build(),
+            StreamIoBuilder::new()
+                .add_input::<Complex32>("in")
+                .add_output::<u8>("out")
+                .build(),
+            MessageIoBuilder::<GfskDemod>::new().build(),
+            GfskDemod {
+                last: Complex32::new(0.0, 0.0),
+                average: VecDeque::from(vec![0.0; len]),
+                sum: 0.0,
+                clock: ClockRecovery::new(sps, clock_gain),
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for GfskDemod {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex32>();
+        let o = sio.output(0).slice::<u8>();
+
+        let mut consumed = 0;
+        let mut produced = 0;
+        for x in i.iter() {
+            if produced == o.len() {
+                break;
+            }
+
+            let f = (x * self.last.conj()).arg();
+            self.last = *x;
+
+            // integrate over one symbol
+            self.sum += f - self.average.pop_front().unwrap();
+            self.average.push_back(f);
+
+            if let Some(s) = self.clock.process(self.sum) {
+                o[produced] = u8::from(s > 0.0);
+                produced += 1;
+            }
+            consumed += 1;
+        }
+
+        sio.input(0).consume(consumed);
+        sio.output(0).produce(produced);
+
+        if sio.input(0).finished() && consumed == i.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// GFSK demodulator.
+///
+/// Non-coherent demodulator for GFSK and GMSK signals with `sps` samples per symbol. The
+/// instantaneous frequency is integrated over one symbol, the symbol clock is recovered from zero
+/// crossings, and the sign of the symbols is output as bits. The gain of the clock recovery
+/// (default 0.1) trades tracking speed for jitter.
+///
+/// # Inputs
+///
+/// `in`: Modulated signal (Complex32)
+///
+/// # Outputs
+///
+/// `out`: Bits (u8)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::GfskDemodBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let demod = fg.add_block(GfskDemodBuilder::new(8.0).clock_gain(0.05).build());
+/// ```
+pub struct GfskDemodBuilder {
+    sps: f32,
+    clock_gain: f32,
+}
+
+impl GfskDemodBuilder {
+    pub fn new(sps: f32) -> GfskDemodBuilder {
+        GfskDemodBuilder {
+            sps,
+            clock_gain: 0.1,
+        }
+    }
+
+    /// Gain of the clock recovery in `(0, 0.5]`.
+    #[must_use]
+    pub fn clock_gain(mut self, gain: f32) -> GfskDemodBuilder {
+        self.clock_gain = gain;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        GfskDemod::new(self.sps, self.clock_gain)
+    }
+}
@@ -0,0 +1,72 @@
+//! Bindings of the used subset of libhackrf.
+//!
+//! The library is linked by the build script, statically if `HACKRF_STATIC` is set.
+use std::os::raw::{c_char, c_double, c_int, c_void};
+
+#[repr(C)]
+pub struct hackrf_device {
+    _private: [u8; 0],
+}
+
+#[repr(C)]
+pub struct hackrf_transfer {
+    pub device: *mut hackrf_device,
+    pub buffer: *mut u8,
+    pub buffer_length: c_int,
+    pub valid_length: c_int,
+    pub rx_ctx: *mut c_void,
+    pub tx_ctx: *mut c_void,
+}
+
+pub type hackrf_sample_block_cb_fn = unsafe extern "C" fn(transfer: *mut hackrf_transfer) -> c_int;
+
+pub const HACKRF_TRUE: c_int = 1;
+pub const SWEEP_STYLE_INTERLEAVED: c_int = 1;
+
+extern "C" {
+    pub fn hackrf_init() -> c_int;
+    pub fn hackrf_exit() -> c_int;
+    pub fn hackrf_open(device: *mut *mut hackrf_device) -> c_int;
+    pub fn hackrf_open_by_serial(serial: *const c_char, device: *mut *mut hackrf_device) -> c_int;
+    pub fn hackrf_close(device: *mut hackrf_device) -> c_int;
+    pub fn hackrf_error_name(errcode: c_int) -> *const c_char;
+    pub fn hackrf_start_rx(
+        device: *mut hackrf_device,
+        callback: hackrf_sample_block_cb_fn,
+        rx_ctx: *mut c_void,
+    ) -> c_int;
+    pub fn hackrf_stop_rx(device: *mut hackrf_device) -> c_int;
+    pub fn hackrf_start_tx(
+        device: *mut hackrf_device,
+        callback: hackrf_sample_block_cb_fn,
+        tx_ctx: *mut c_void,
+    ) -> c_int;
+    pub fn hackrf_stop_tx(device: *mut hackrf_device) -> c_int;
+    pub fn hackrf_is_streaming(device: *mut hackrf_device) -> c_int;
+    pub fn hackrf_set_freq(device: *mut hackrf_device, freq_hz: u64) -> c_int;
+    pub fn hackrf_set_sample_rate(device: *mut hackrf_device, freq_hz: c_double) -> c_int;
+    pub fn hackrf_compute_baseband_filter_bw(bandwidth_hz: u32) -> u32;
+    pub fn hackrf_set_baseband_filter_bandwidth(
+        device: *mut hackrf_device,
+        bandwidth_hz: u32,
+    ) -> c_int;
+    pub fn hackrf_set_amp_enable(device: *mut hackrf_device, value: u8) -> c_int;
+    pub fn hackrf_set_antenna_enable(device: *mut hackrf_device, value: u8) -> c_int;
+    pub fn hackrf_set_lna_gain(device: *mut hackrf_device, value: u32) -> c_int;
+    pub fn hackrf_set_vga_gain(device: *mut hackrf_device, value: u32) -> c_int;
+    pub fn hackrf_set_txvga_gain(device: *mut hackrf_device, value: u32) -> c_int;
+    pub fn hackrf_init_sweep(
+        device: *mut hackrf_device,
+        frequency_list: *const u16,
+        num_ranges: c_int,
+        num_bytes: u32,
+        step_width: u32,
+        offset: u32,
+        style: c_int,
+    ) -> c_int;
+    pub fn hackrf_start_rx_sweep(
+        device: *mut hackrf_device,
+        callback: hackrf_sample_block_cb_fn,
+        rx_ctx: *mut c_void,
+    ) -> c_int;
+}
@@ -0,0 +1,134 @@
+//! ## HackRF Blocks (requires `hackrf` feature)
+//!
+//! Native [HackRF](https://greatscottgadgets.com/hackrf/) blocks, using libhackrf directly. In
+//! addition to streaming at a fixed frequency, the source supports the sweep mode of the
+//! firmware, which retunes the device in hardware to scan wide frequency ranges. Set
+//! `HACKRF_STATIC` at build time to link libhackrf and libusb statically.
+use std::ffi::{CStr, CString};
+
+use crate::anyhow::{bail, Result};
+use crate::runtime::Pmt;
+
+#[allow(non_camel_case_types)]
+mod ffi;
+
+mod sink;
+pub use self::sink::{HackRfSink, HackRfSinkBuilder};
+mod source;
+pub use self::source::{HackRfSource, HackRfSourceBuilder, HackRfSweep};
+
+/// Configuration, shared by [HackRfSourceBuilder] and [HackRfSinkBuilder].
+#[derive(Debug, Clone)]
+struct HackRfConfig {
+    serial: Option<String>,
+    frequency: f64,
+    sample_rate: f64,
+    bandwidth: Option<f64>,
+    amp: bool,
+    antenna_power: bool,
+}
+
+impl Default for HackRfConfig {
+    fn default() -> Self {
+        HackRfConfig {
+            serial: None,
+            frequency: 100e6,
+            sample_rate: 8e6,
+            bandwidth: None,
+            amp: false,
+            antenna_power: false,
+        }
+    }
+}
+
+fn pmt_to_f64(p: &Pmt) -> Option<f64> {
+    match p {
+        Pmt::F32(v) => Some(*v as f64),
+        Pmt::F64(v) => Some(*v),
+        Pmt::U32(v) => Some(*v as f64),
+        Pmt::U64(v) => Some(*v as f64),
+        _ => None,
+    }
+}
+
+fn check(ret: i32, what: &str) -> Result<()> {
+    if ret < 0 {
+        let name = unsafe { CStr::from_ptr(ffi::hackrf_error_name(ret)) };
+        bail!("HackRF: {} failed ({})", what, name.to_string_lossy());
+    }
+    Ok(())
+}
+
+/// Open HackRF device.
+struct HackRfDevice(*mut ffi::hackrf_device);
+
+// libhackrf allows to configure the device, while samples are transferred in another thread.
+unsafe impl Send for HackRfDevice {}
+
+impl HackRfDevice {
+    fn open(config: &HackRfConfig) -> Result<HackRfDevice> {
+        check(unsafe { ffi::hackrf_init() }, "initializing libhackrf")?;
+        let mut dev = std::ptr::null_mut();
+        match &config.serial {
+            Some(s) => {
+                let s = CString::new(s.as_str())?;
+                check(
+                    unsafe { ffi::hackrf_open_by_serial(s.as_ptr(), &mut dev) },
+                    "opening the device",
+                )?;
+            }
+            None => check(unsafe { ffi::hackrf_open(&mut dev) }, "opening the device")?,
+        }
+        let dev = HackRfDevice(dev);
+
+        dev.set_sample_rate(config.sample_rate, config.bandwidth)?;
+        dev.set_frequency(config.frequency)?;
+        check(
+            unsafe { ffi::hackrf_set_amp_enable(dev.0, u8::from(config.amp)) },
+            "setting the amplifier",
+        )?;
+        check(
+            unsafe { ffi::hackrf_set_antenna_enable(dev.0, u8::from(config.antenna_power)) },
+            "setting the antenna power",
+        )?;
+        Ok(dev)
+    }
+
+    fn set_frequency(&self, frequency: f64) -> Result<()> {
+        check(
+            unsafe { ffi::hackrf_set_freq(self.0, frequency as u64) },
+            "setting the frequency",
+        )
+    }
+
+    /// Set the sample rate and the baseband filter, defaulting to 75% of the sample rate.
+    fn set_sample_rate(&self, sample_rate: f64, bandwidth: Option<f64>) -> Result<()> {
+        check(
+            unsafe { ffi::hackrf_set_sample_rate(self.0, sample_rate) },
+            "setting the sample rate",
+        )?;
+        let bw = bandwidth.unwrap_or(sample_rate * 0.75) as u32;
+        check(
+            unsafe {
+                ffi::hackrf_set_baseband_filter_bandwidth(
+                    self.0,
+                    ffi::hackrf_compute_baseband_filter_bw(bw),
+                )
+            },
+            "setting the baseband filter",
+        )
+    }
+
+    fn streaming(&self) -> bool {
+        unsafe { ffi::hackrf_is_streaming(self.0) == ffi::HACKRF_TRUE }
+    }
+}
+
+impl Drop for HackRfDevice {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::hackrf_close(self.0);
+            ffi::hackrf_exit();
+        }
+    }
+}
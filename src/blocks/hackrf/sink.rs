@@ -0,0 +1,379 @@
+use async_io::Timer;
+use futures::channel::mpsc;
+use futures::FutureExt;
+use futures::SinkExt;
+use futures::StreamExt;
+use std::os::raw::{c_int, c_void};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::anyhow::{Context, Result};
+use crate::blocks::hackrf::{check, ffi, pmt_to_f64, HackRfConfig, HackRfDevice};
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Number of chunks that are queued for the device.
+const QUEUE_SIZE: usize = 64;
+/// Samples per chunk.
+const CHUNK: usize = 8192;
+/// Time to wait for queued samples to be transmitted, when the input is finished.
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Context of the TX callback.
+struct TxContext {
+    rx: mpsc::Receiver<Vec<u8>>,
+    /// Chunk that was only partially copied to the last transfer.
+    current: Vec<u8>,
+    pos: usize,
+    underruns: Arc<AtomicU64>,
+}
+
+unsafe extern "C" fn tx_callback(transfer: *mut ffi::hackrf_transfer) -> c_int {
+    let transfer = &mut *transfer;
+    let ctx = &mut *(transfer.tx_ctx as *mut TxContext);
+    let buf = std::slice::from_raw_parts_mut(transfer.buffer, transfer.buffer_length as usize);
+
+    let mut filled = 0;
+    while filled < buf.len() {
+        if ctx.pos == ctx.current.len() {
+            match ctx.rx.next().now_or_never() {
+                Some(Some(v)) => {
+                    ctx.current = v;
+                    ctx.pos = 0;
+                }
+                Some(None) => {
+                    // input finished: send the rest and stop
+                    if filled == 0 {
+                        return -1;
+                    }
+                    break;
+                }
+                None => {
+                    ctx.underruns.fetch_add(1, Ordering::Relaxed);
+                    break;
+                }
+            }
+        }
+        let n = std::cmp::min(buf.len() - filled, ctx.current.len() - ctx.pos);
+        buf[filled..filled + n].copy_from_slice(&ctx.current[ctx.pos..ctx.pos + n]);
+        filled += n;
+        ctx.pos += n;
+    }
+    buf[filled..].fill(0);
+    transfer.valid_length = transfer.buffer_length;
+    0
+}
+
+/// Transmit samples with a HackRF.
+pub struct HackRfSink {
+    config: HackRfConfig,
+    vga_gain: u32,
+    dev: Option<HackRfDevice>,
+    ctx: Option<Box<TxContext>>,
+    tx: Option<mpsc::Sender<Vec<u8>>>,
+    underruns: Arc<AtomicU64>,
+    reported: u64,
+}
+
+impl HackRfSink {
+    /// Number of transfers that were padded with zeros, since the flowgraph did not keep up.
+    pub fn underruns(&self) -> u64 {
+        self.underruns.load(Ordering::Relaxed)
+    }
+
+    fn set_gain(&mut self, gain: u32) -> Result<()> {
+        if let Some(dev) = &self.dev {
+            check(
+                unsafe { ffi::hackrf_set_txvga_gain(dev.0, gain) },
+                "setting the TX VGA gain",
+            )?;
+        }
+        self.vga_gain = gain;
+        Ok(())
+    }
+
+    fn create(builder: HackRfSinkBuilder) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("HackRfSink").build(),
+            StreamIoBuilder::new().add_input::<Complex32>("in").build(),
+            MessageIoBuilder::new()
+                .add_input(
+                    "freq",
+                    |block: &mut HackRfSink,
+                     _mio: &mut MessageIo<HackRfSink>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            match pmt_to_f64(&p) {
+                                Some(f) => {
+                                    block.config.frequency = f;
+                                    if let Some(dev) = &block.dev {
+                                        dev.set_frequency(f)?;
+                                    }
+                                }
+                                None => warn!("HackRfSink/freq: received wrong PMT type. {:?}", p),
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .add_input(
+                    "gain",
+                    |block: &mut HackRfSink,
+                     _mio: &mut MessageIo<HackRfSink>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            match pmt_to_f64(&p) {
+                                Some(g) => block.set_gain(g.clamp(0.0, 47.0) as u32)?,
+                                None => warn!("HackRfSink/gain: received wrong PMT type. {:?}", p),
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .add_input(
+                    "sample_rate",
+                    |block: &mut HackRfSink,
+                     _mio: &mut MessageIo<HackRfSink>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            match pmt_to_f64(&p) {
+                                Some(s) => {
+                                    block.config.sample_rate = s;
+                                    if let Some(dev) = &block.dev {
+                                        dev.set_sample_rate(s, block.config.bandwidth)?;
+                                    }
+                                }
+                                None => warn!(
+                                    "HackRfSink/sample_rate: received wrong PMT type. {:?}",
+                                    p
+                                ),
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .add_output("underrun")
+                .build(),
+            HackRfSink {
+                config: builder.config,
+                vga_gain: builder.vga_gain,
+                dev: None,
+                ctx: None,
+                tx: None,
+                underruns: Arc::new(AtomicU64::new(0)),
+                reported: 0,
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for HackRfSink {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let underruns = self.underruns();
+        if underruns > self.reported {
+            self.reported = underruns;
+            mio.post(0, Pmt::U64(underruns)).await;
+        }
+
+        let input = sio.input(0).slice::<Complex32>();
+        let n = std::cmp::min(input.len(), CHUNK);
+        if n > 0 {
+            let chunk: Vec<u8> = input[..n]
+                .iter()
+                .flat_map(|c| {
+                    [
+                        (c.re * 127.0).clamp(-128.0, 127.0) as i8 as u8,
+                        (c.im * 127.0).clamp(-128.0, 127.0) as i8 as u8,
+                    ]
+                })
+                .collect();
+            self.tx.as_mut().context("not started")?.send(chunk).await?;
+            sio.input(0).consume(n);
+            io.call_again = true;
+        } else if sio.input(0).finished() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        self.dev = Some(HackRfDevice::open(&self.config)?);
+        self.set_gain(self.vga_gain)?;
+
+        let (tx, rx) = mpsc::channel(QUEUE_SIZE);
+        let mut ctx = Box::new(TxContext {
+            rx,
+            current: Vec::new(),
+            pos: 0,
+            underruns: self.underruns.clone(),
+        });
+        let ctx_ptr = ctx.as_mut() as *mut TxContext as *mut c_void;
+        let dev = self.dev.as_ref().unwrap();
+        check(
+            unsafe { ffi::hackrf_start_tx(dev.0, tx_callback, ctx_ptr) },
+            "starting TX",
+        )?;
+        self.ctx = Some(ctx);
+        self.tx = Some(tx);
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        // close the queue and wait until the device has sent the queued samples
+        self.tx = None;
+        if let Some(dev) = self.dev.take() {
+            let start = Instant::now();
+            while dev.streaming() && start.elapsed() < DRAIN_TIMEOUT {
+                Timer::after(Duration::from_millis(10)).await;
+            }
+            unsafe {
+                ffi::hackrf_stop_tx(dev.0);
+            }
+        }
+        self.ctx = None;
+        Ok(())
+    }
+}
+
+/// Transmit samples with a HackRF, using libhackrf directly.
+///
+/// Samples are queued and transferred to the device in a separate thread. If the flowgraph does
+/// not keep up, the device transmits zeros, which is reported on the `underrun` output. Samples
+/// are expected to be in `[-1, 1]`. When the input is finished, the queued samples are sent,
+/// before the device is stopped.
+///
+/// # Inputs
+///
+/// `in`: Samples to transmit
+///
+/// **Message**: `freq`: Center frequency in Hz
+///
+/// **Message**: `gain`: TX VGA gain in dB (0-47)
+///
+/// **Message**: `sample_rate`: Sample rate
+///
+/// # Outputs
+///
+/// **Message**: `underrun`: Total number of transfers padded with zeros
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::hackrf::HackRfSinkBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let snk = fg.add_block(
+///     HackRfSinkBuilder::new()
+///         .frequency(433.92e6)
+///         .sample_rate(2e6)
+///         .vga_gain(20)
+///         .build(),
+/// );
+/// ```
+pub struct HackRfSinkBuilder {
+    config: HackRfConfig,
+    vga_gain: u32,
+}
+
+impl HackRfSinkBuilder {
+    pub fn new() -> HackRfSinkBuilder {
+        HackRfSinkBuilder {
+            config: HackRfConfig::default(),
+            vga_gain: 0,
+        }
+    }
+
+    /// Select the device by its serial number.
+    #[must_use]
+    pub fn serial(mut self, serial: impl Into<String>) -> HackRfSinkBuilder {
+        self.config.serial = Some(serial.into());
+        self
+    }
+
+    /// Center frequency in Hz.
+    #[must_use]
+    pub fn frequency(mut self, frequency: f64) -> HackRfSinkBuilder {
+        self.config.frequency = frequency;
+        self
+    }
+
+    #[must_use]
+    pub fn sample_rate(mut self, sample_rate: f64) -> HackRfSinkBuilder {
+        self.config.sample_rate = sample_rate;
+        self
+    }
+
+    /// Baseband filter bandwidth in Hz. Defaults to 75% of the sample rate.
+    #[must_use]
+    pub fn bandwidth(mut self, bandwidth: f64) -> HackRfSinkBuilder {
+        self.config.bandwidth = Some(bandwidth);
+        self
+    }
+
+    /// Enable the RF amplifier (about 11 dB).
+    #[must_use]
+    pub fn amp(mut self, amp: bool) -> HackRfSinkBuilder {
+        self.config.amp = amp;
+        self
+    }
+
+    /// Power an external amplifier through the antenna port.
+    #[must_use]
+    pub fn antenna_power(mut self, antenna_power: bool) -> HackRfSinkBuilder {
+        self.config.antenna_power = antenna_power;
+        self
+    }
+
+    /// TX VGA gain in dB, 0-47.
+    #[must_use]
+    pub fn vga_gain(mut self, gain: u32) -> HackRfSinkBuilder {
+        self.vga_gain = gain;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        HackRfSink::create(self)
+    }
+}
+
+impl Default for HackRfSinkBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,513 @@
+use futures::channel::mpsc;
+use futures::FutureExt;
+use futures::StreamExt;
+use std::os::raw::{c_int, c_void};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crate::anyhow::{bail, Context, Result};
+use crate::blocks::hackrf::{check, ffi, pmt_to_f64, HackRfConfig, HackRfDevice};
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::Tag;
+use crate::runtime::WorkIo;
+
+/// Number of USB transfers that are queued, before the device overruns.
+const QUEUE_SIZE: usize = 16;
+/// Size of the blocks of a sweep, each starting with a header.
+const SWEEP_BLOCK: usize = 16384;
+/// Bytes at the start of a sweep block, holding the 10-byte header.
+const SWEEP_HEADER: usize = 16;
+
+/// Frequency ranges and tuning of a [HackRfSource](HackRfSourceBuilder) in sweep mode.
+///
+/// The firmware tunes to every `step` in the ranges and, in interleaved style, captures one
+/// block at `frequency + offset` and one at `frequency + step / 2 + offset` (with the defaults,
+/// 20 MHz steps and 7.5 MHz offset, covering the whole range with the center of the band). The
+/// sample rate of the source should equal the step width.
+#[derive(Debug, Clone)]
+pub struct HackRfSweep {
+    ranges: Vec<(u16, u16)>,
+    step: u32,
+    offset: u32,
+}
+
+impl HackRfSweep {
+    /// Sweep from `start` to `stop` MHz.
+    pub fn new(start: u16, stop: u16) -> HackRfSweep {
+        HackRfSweep {
+            ranges: vec![(start, stop)],
+            step: 20_000_000,
+            offset: 7_500_000,
+        }
+    }
+
+    /// Add another range from `start` to `stop` MHz (at most 10 in total).
+    #[must_use]
+    pub fn range(mut self, start: u16, stop: u16) -> HackRfSweep {
+        self.ranges.push((start, stop));
+        self
+    }
+
+    /// Tuning step in Hz.
+    #[must_use]
+    pub fn step(mut self, step: u32) -> HackRfSweep {
+        self.step = step;
+        self
+    }
+
+    /// Offset of the captured band from the tuned frequency in Hz.
+    #[must_use]
+    pub fn offset(mut self, offset: u32) -> HackRfSweep {
+        self.offset = offset;
+        self
+    }
+
+    /// Frequency list for the firmware, with ranges extended to whole steps.
+    fn frequency_list(&self) -> Vec<u16> {
+        let step_mhz = std::cmp::max(1, self.step / 1_000_000) as u16;
+        self.ranges
+            .iter()
+            .flat_map(|(start, stop)| {
+                let steps = (stop.saturating_sub(*start) + step_mhz - 1) / step_mhz;
+                [*start, start + std::cmp::max(1, steps) * step_mhz]
+            })
+            .collect()
+    }
+}
+
+/// Context of the RX callback.
+struct RxContext {
+    tx: mpsc::Sender<Vec<u8>>,
+    overruns: Arc<AtomicU64>,
+}
+
+unsafe extern "C" fn rx_callback(transfer: *mut ffi::hackrf_transfer) -> c_int {
+    let transfer = &*transfer;
+    let ctx = &mut *(transfer.rx_ctx as *mut RxContext);
+    let data = std::slice::from_raw_parts(transfer.buffer, transfer.valid_length as usize);
+    if ctx.tx.try_send(data.to_vec()).is_err() {
+        ctx.overruns.fetch_add(1, Ordering::Relaxed);
+    }
+    0
+}
+
+/// Receive samples from a HackRF.
+pub struct HackRfSource {
+    config: HackRfConfig,
+    lna_gain: u32,
+    vga_gain: u32,
+    sweep: Option<HackRfSweep>,
+    dev: Option<HackRfDevice>,
+    ctx: Option<Box<RxContext>>,
+    rx: Option<mpsc::Receiver<Vec<u8>>>,
+    buff: Option<(Vec<u8>, usize)>,
+    overruns: Arc<AtomicU64>,
+    reported: u64,
+}
+
+impl HackRfSource {
+    /// Number of USB transfers that were dropped, since the flowgraph did not keep up.
+    pub fn overruns(&self) -> u64 {
+        self.overruns.load(Ordering::Relaxed)
+    }
+
+    /// Set the gain in dB, using the LNA in 8 dB steps first and the VGA for the rest.
+    fn set_gain(&mut self, gain: f64) -> Result<()> {
+        let gain = gain.max(0.0) as u32;
+        let lna = std::cmp::min(40, gain / 8 * 8);
+        let vga = std::cmp::min(62, (gain - lna) / 2 * 2);
+        self.set_gains(lna, vga)
+    }
+
+    fn set_gains(&mut self, lna: u32, vga: u32) -> Result<()> {
+        if let Some(dev) = &self.dev {
+            check(
+                unsafe { ffi::hackrf_set_lna_gain(dev.0, lna) },
+                "setting the LNA gain",
+            )?;
+            check(
+                unsafe { ffi::hackrf_set_vga_gain(dev.0, vga) },
+                "setting the VGA gain",
+            )?;
+        }
+        self.lna_gain = lna;
+        self.vga_gain = vga;
+        Ok(())
+    }
+
+    fn create(builder: HackRfSourceBuilder) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("HackRfSource").build(),
+            StreamIoBuilder::new()
+                .add_output::<Complex32>("out")
+                .build(),
+            MessageIoBuilder::new()
+                .add_input(
+                    "freq",
+                    |block: &mut HackRfSource,
+                     _mio: &mut MessageIo<HackRfSource>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            match pmt_to_f64(&p) {
+                                Some(f) => {
+                                    block.config.frequency = f;
+                                    if let Some(dev) = &block.dev {
+                                        if block.sweep.is_none() {
+                                            dev.set_frequency(f)?;
+                                        }
+                                    }
+                                }
+                                None => {
+                                    warn!("HackRfSource/freq: received wrong PMT type. {:?}", p)
+                                }
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .add_input(
+                    "gain",
+                    |block: &mut HackRfSource,
+                     _mio: &mut MessageIo<HackRfSource>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            match pmt_to_f64(&p) {
+                                Some(g) => block.set_gain(g)?,
+                                None => {
+                                    warn!("HackRfSource/gain: received wrong PMT type. {:?}", p)
+                                }
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .add_input(
+                    "sample_rate",
+                    |block: &mut HackRfSource,
+                     _mio: &mut MessageIo<HackRfSource>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            match pmt_to_f64(&p) {
+                                Some(s) => {
+                                    block.config.sample_rate = s;
+                                    if let Some(dev) = &block.dev {
+                                        dev.set_sample_rate(s, block.config.bandwidth)?;
+                                    }
+                                }
+                                None => warn!(
+                                    "HackRfSource/sample_rate: received wrong PMT type. {:?}",
+                                    p
+                                ),
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .add_output("overrun")
+                .build(),
+            HackRfSource {
+                config: builder.config,
+                lna_gain: builder.lna_gain,
+                vga_gain: builder.vga_gain,
+                sweep: builder.sweep,
+                dev: None,
+                ctx: None,
+                rx: None,
+                buff: None,
+                overruns: Arc::new(AtomicU64::new(0)),
+                reported: 0,
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for HackRfSource {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let overruns = self.overruns();
+        if overruns > self.reported {
+            warn!(
+                "HackRfSource: overrun, dropped {} transfers",
+                overruns - self.reported
+            );
+            self.reported = overruns;
+            mio.post(0, Pmt::U64(overruns)).await;
+        }
+
+        let out = sio.output(0).slice::<Complex32>();
+        if out.is_empty() {
+            return Ok(());
+        }
+
+        let (v, mut offset) = match self.buff.take() {
+            Some(b) => b,
+            None => match self.rx.as_mut().context("not started")?.next().await {
+                Some(v) => (v, 0),
+                None => bail!("HackRfSource: device stopped streaming"),
+            },
+        };
+
+        let mut end = v.len();
+        if self.sweep.is_some() {
+            if offset % SWEEP_BLOCK == 0 {
+                let h = &v[offset..];
+                if h.len() >= 10 && h[0] == 0x7f && h[1] == 0x7f {
+                    let freq = u64::from_le_bytes(h[2..10].try_into().unwrap());
+                    sio.output(0)
+                        .add_tag(0, Tag::NamedUsize("sweep_freq".to_string(), freq as usize));
+                }
+                offset += SWEEP_HEADER;
+            }
+            // stop at the end of the block, so that the next one is tagged
+            end = std::cmp::min(end, (offset / SWEEP_BLOCK + 1) * SWEEP_BLOCK);
+        }
+
+        let n = std::cmp::min((end.saturating_sub(offset)) / 2, out.len());
+        for (o, iq) in out
+            .iter_mut()
+            .zip(v[offset..offset + 2 * n].chunks_exact(2))
+        {
+            *o = Complex32::new(iq[0] as i8 as f32 / 128.0, iq[1] as i8 as f32 / 128.0);
+        }
+        offset += 2 * n;
+        sio.output(0).produce(n);
+        if offset < v.len() {
+            self.buff = Some((v, offset));
+        }
+        io.call_again = true;
+
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let dev = HackRfDevice::open(&self.config)?;
+        let (tx, rx) = mpsc::channel(QUEUE_SIZE);
+        let mut ctx = Box::new(RxContext {
+            tx,
+            overruns: self.overruns.clone(),
+        });
+        let ctx_ptr = ctx.as_mut() as *mut RxContext as *mut c_void;
+
+        match &self.sweep {
+            Some(sweep) => {
+                let list = sweep.frequency_list();
+                check(
+                    unsafe {
+                        ffi::hackrf_init_sweep(
+                            dev.0,
+                            list.as_ptr(),
+                            (list.len() / 2) as c_int,
+                            SWEEP_BLOCK as u32,
+                            sweep.step,
+                            sweep.offset,
+                            ffi::SWEEP_STYLE_INTERLEAVED,
+                        )
+                    },
+                    "initializing the sweep",
+                )?;
+                self.dev = Some(dev);
+                self.set_gains(self.lna_gain, self.vga_gain)?;
+                let dev = self.dev.as_ref().unwrap();
+                check(
+                    unsafe { ffi::hackrf_start_rx_sweep(dev.0, rx_callback, ctx_ptr) },
+                    "starting the sweep",
+                )?;
+            }
+            None => {
+                self.dev = Some(dev);
+                self.set_gains(self.lna_gain, self.vga_gain)?;
+                let dev = self.dev.as_ref().unwrap();
+                check(
+                    unsafe { ffi::hackrf_start_rx(dev.0, rx_callback, ctx_ptr) },
+                    "starting RX",
+                )?;
+            }
+        }
+        self.ctx = Some(ctx);
+        self.rx = Some(rx);
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        if let Some(dev) = self.dev.take() {
+            if dev.streaming() {
+                unsafe {
+                    ffi::hackrf_stop_rx(dev.0);
+                }
+            }
+        }
+        self.ctx = None;
+        Ok(())
+    }
+}
+
+/// Receive samples from a HackRF, using libhackrf directly.
+///
+/// Samples are transferred in a separate thread. If the flowgraph does not keep up, transfers
+/// are dropped, which is reported on the `overrun` output.
+///
+/// With [sweep](Self::sweep), the firmware continuously retunes the device over the given
+/// [HackRfSweep] ranges. The output is a sequence of segments of 8184 samples, each tagged at
+/// its first sample with [Tag::NamedUsize] `sweep_freq`, the frequency in Hz it was captured
+/// at. The first samples of a segment may still be affected by the retuning.
+///
+/// # Inputs
+///
+/// **Message**: `freq`: Center frequency in Hz (ignored in sweep mode)
+///
+/// **Message**: `gain`: Total gain in dB, split into LNA and VGA gain
+///
+/// **Message**: `sample_rate`: Sample rate
+///
+/// # Outputs
+///
+/// `out`: Received samples
+///
+/// **Message**: `overrun`: Total number of dropped transfers
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::hackrf::{HackRfSourceBuilder, HackRfSweep};
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // stream at a fixed frequency
+/// let src = fg.add_block(
+///     HackRfSourceBuilder::new()
+///         .frequency(915e6)
+///         .sample_rate(10e6)
+///         .lna_gain(16)
+///         .vga_gain(20)
+///         .build(),
+/// );
+///
+/// // sweep from 2.4 to 2.5 GHz
+/// let sweep = fg.add_block(
+///     HackRfSourceBuilder::new()
+///         .sample_rate(20e6)
+///         .sweep(HackRfSweep::new(2400, 2500))
+///         .build(),
+/// );
+/// ```
+pub struct HackRfSourceBuilder {
+    config: HackRfConfig,
+    lna_gain: u32,
+    vga_gain: u32,
+    sweep: Option<HackRfSweep>,
+}
+
+impl HackRfSourceBuilder {
+    pub fn new() -> HackRfSourceBuilder {
+        HackRfSourceBuilder {
+            config: HackRfConfig::default(),
+            lna_gain: 16,
+            vga_gain: 16,
+            sweep: None,
+        }
+    }
+
+    /// Select the device by its serial number.
+    #[must_use]
+    pub fn serial(mut self, serial: impl Into<String>) -> HackRfSourceBuilder {
+        self.config.serial = Some(serial.into());
+        self
+    }
+
+    /// Center frequency in Hz.
+    #[must_use]
+    pub fn frequency(mut self, frequency: f64) -> HackRfSourceBuilder {
+        self.config.frequency = frequency;
+        self
+    }
+
+    #[must_use]
+    pub fn sample_rate(mut self, sample_rate: f64) -> HackRfSourceBuilder {
+        self.config.sample_rate = sample_rate;
+        self
+    }
+
+    /// Baseband filter bandwidth in Hz. Defaults to 75% of the sample rate.
+    #[must_use]
+    pub fn bandwidth(mut self, bandwidth: f64) -> HackRfSourceBuilder {
+        self.config.bandwidth = Some(bandwidth);
+        self
+    }
+
+    /// Enable the RF amplifier (about 11 dB).
+    #[must_use]
+    pub fn amp(mut self, amp: bool) -> HackRfSourceBuilder {
+        self.config.amp = amp;
+        self
+    }
+
+    /// Power an active antenna or LNA through the antenna port.
+    #[must_use]
+    pub fn antenna_power(mut self, antenna_power: bool) -> HackRfSourceBuilder {
+        self.config.antenna_power = antenna_power;
+        self
+    }
+
+    /// LNA (IF) gain in dB, 0-40 in 8 dB steps.
+    #[must_use]
+    pub fn lna_gain(mut self, gain: u32) -> HackRfSourceBuilder {
+        self.lna_gain = gain;
+        self
+    }
+
+    /// VGA (baseband) gain in dB, 0-62 in 2 dB steps.
+    #[must_use]
+    pub fn vga_gain(mut self, gain: u32) -> HackRfSourceBuilder {
+        self.vga_gain = gain;
+        self
+    }
+
+    /// Use the sweep mode of the firmware.
+    #[must_use]
+    pub fn sweep(mut self, sweep: HackRfSweep) -> HackRfSourceBuilder {
+        self.sweep = Some(sweep);
+        self
+    }
+
+    pub fn build(self) -> Block {
+        HackRfSource::create(self)
+    }
+}
+
+impl Default for HackRfSourceBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
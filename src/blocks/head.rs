@@ -83,6 +83,10 @@ impl<T: Send + 'static> Kernel for Head<T> {
             sio.output(0).produce(m);
         }
 
+        if sio.input(0).finished() && m == i.len() / item_size {
+            io.finished = true;
+        }
+
         Ok(())
     }
 }
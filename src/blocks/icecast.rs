@@ -0,0 +1,572 @@
+use std::io::ErrorKind;
+use std::io::Read;
+use std::io::Write;
+use std::net::TcpListener;
+use std::net::TcpStream;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{sync_channel, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[cfg(feature = "lame")]
+use crate::anyhow::anyhow;
+use crate::anyhow::{bail, Context, Result};
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Encoded chunks, queued per listener, before slow listeners miss chunks.
+const QUEUE: usize = 64;
+
+type Chunk = Arc<Vec<u8>>;
+/// Queues of the connected listeners.
+type Listeners = Arc<Mutex<Vec<SyncSender<Chunk>>>>;
+
+/// Audio encoder for the [IcecastSink].
+///
+/// The sink comes with a [WavEncoder] and, with the `lame` feature, an `Mp3Encoder`. Other
+/// formats, like Ogg, can be streamed by implementing this trait, e.g., as a wrapper around an
+/// encoder library.
+pub trait AudioEncoder: Send {
+    /// MIME type of the stream, e.g., `audio/mpeg`.
+    fn content_type(&self) -> &str;
+    /// Start of the stream, sent to each listener before the encoded audio.
+    fn header(&self) -> Vec<u8>;
+    /// Encode interleaved samples, appending to `out`.
+    fn encode(&mut self, samples: &[f32], out: &mut Vec<u8>);
+    /// End of the stream, appending audio that is still buffered in the encoder to `out`.
+    fn finish(&mut self, _out: &mut Vec<u8>) {}
+}
+
+/// Uncompressed 16-bit WAV stream of unknown length.
+pub struct WavEncoder {
+    sample_rate: u32,
+    channels: u16,
+}
+
+impl WavEncoder {
+    pub fn new(sample_rate: u32, channels: u16) -> WavEncoder {
+        WavEncoder {
+            sample_rate,
+            channels,
+        }
+    }
+}
+
+impl AudioEncoder for WavEncoder {
+    fn content_type(&self) -> &str {
+        "audio/wav"
+    }
+
+    fn header(&self) -> Vec<u8> {
+        let block_align = self.channels * 2;
+        let mut h = Vec::with_capacity(44);
+        h.extend_from_slice(b"RIFF");
+        // streams have no length, use the maximum
+        h.extend_from_slice(&u32::MAX.to_le_bytes());
+        h.extend_from_slice(b"WAVEfmt ");
+        h.extend_from_slice(&16u32.to_le_bytes());
+        h.extend_from_slice(&1u16.to_le_bytes());
+        h.extend_from_slice(&self.channels.to_le_bytes());
+        h.extend_from_slice(&self.sample_rate.to_le_bytes());
+        h.extend_from_slice(&(self.sample_rate * block_align as u32).to_le_bytes());
+        h.extend_from_slice(&block_align.to_le_bytes());
+        h.extend_from_slice(&16u16.to_le_bytes());
+        h.extend_from_slice(b"data");
+        h.extend_from_slice(&(u32::MAX - 36).to_le_bytes());
+        h
+    }
+
+    fn encode(&mut self, samples: &[f32], out: &mut Vec<u8>) {
+        out.reserve(samples.len() * 2);
+        for s in samples {
+            let v = (s * 32768.0).round().clamp(-32768.0, 32767.0) as i16;
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+    }
+}
+
+/// MP3 stream with constant bitrate, encoded with [LAME](https://lame.sourceforge.io).
+#[cfg(feature = "lame")]
+#[cfg_attr(docsrs, doc(cfg(feature = "lame")))]
+pub struct Mp3Encoder {
+    encoder: mp3lame_encoder::Encoder,
+    channels: usize,
+}
+
+#[cfg(feature = "lame")]
+impl Mp3Encoder {
+    /// Create an encoder for one or two channels with the given bitrate in kbit/s, e.g., 128.
+    pub fn new(sample_rate: u32, channels: u16, bitrate: u16) -> Result<Mp3Encoder> {
+        use mp3lame_encoder::Bitrate;
+
+        let brate = match bitrate {
+            8 => Bitrate::Kbps8,
+            16 => Bitrate::Kbps16,
+            24 => Bitrate::Kbps24,
+            32 => Bitrate::Kbps32,
+            40 => Bitrate::Kbps40,
+            48 => Bitrate::Kbps48,
+            64 => Bitrate::Kbps64,
+            80 => Bitrate::Kbps80,
+            96 => Bitrate::Kbps96,
+            112 => Bitrate::Kbps112,
+            128 => Bitrate::Kbps128,
+            160 => Bitrate::Kbps160,
+            192 => Bitrate::Kbps192,
+            224 => Bitrate::Kbps224,
+            256 => Bitrate::Kbps256,
+            320 => Bitrate::Kbps320,
+            _ => bail!("Mp3Encoder: bitrate {} kbit/s not supported", bitrate),
+        };
+        if !(1..=2).contains(&channels) {
+            bail!("Mp3Encoder: {} channels not supported", channels);
+        }
+
+        let mut builder = mp3lame_encoder::Builder::new().context("Mp3Encoder: out of memory")?;
+        let invalid = |e| anyhow!("Mp3Encoder: invalid configuration ({})", e);
+        builder.set_sample_rate(sample_rate).map_err(invalid)?;
+        builder.set_num_channels(channels as u8).map_err(invalid)?;
+        builder.set_brate(brate).map_err(invalid)?;
+        builder
+            .set_quality(mp3lame_encoder::Quality::Good)
+            .map_err(invalid)?;
+        let encoder = builder
+            .build()
+            .map_err(|e| anyhow!("Mp3Encoder: initializing LAME failed ({})", e))?;
+
+        Ok(Mp3Encoder {
+            encoder,
+            channels: channels as usize,
+        })
+    }
+}
+
+#[cfg(feature = "lame")]
+impl AudioEncoder for Mp3Encoder {
+    fn content_type(&self) -> &str {
+        "audio/mpeg"
+    }
+
+    fn header(&self) -> Vec<u8> {
+        // MP3 frames are self-contained, listeners can start at any frame
+        Vec::new()
+    }
+
+    fn encode(&mut self, samples: &[f32], out: &mut Vec<u8>) {
+        use mp3lame_encoder::{InterleavedPcm, MonoPcm};
+
+        out.reserve(mp3lame_encoder::max_required_buffer_size(
+            samples.len() / self.channels,
+        ));
+        let r = if self.channels == 2 {
+            self.encoder
+                .encode(InterleavedPcm(samples), out.spare_capacity_mut())
+        } else {
+            self.encoder
+                .encode(MonoPcm(samples), out.spare_capacity_mut())
+        };
+        match r {
+            // LAME initialized the first n bytes of the spare capacity
+            Ok(n) => unsafe { out.set_len(out.len() + n) },
+            Err(e) => warn!("Mp3Encoder: encoding failed ({})", e),
+        }
+    }
+
+    fn finish(&mut self, out: &mut Vec<u8>) {
+        out.reserve(mp3lame_encoder::max_required_buffer_size(0));
+        match self
+            .encoder
+            .flush::<mp3lame_encoder::FlushNoGap>(out.spare_capacity_mut())
+        {
+            // LAME initialized the first n bytes of the spare capacity
+            Ok(n) => unsafe { out.set_len(out.len() + n) },
+            Err(e) => warn!("Mp3Encoder: flushing failed ({})", e),
+        }
+    }
+}
+
+/// Where the stream goes.
+enum Mode {
+    Serve(String),
+    Push {
+        server: String,
+        mount: String,
+        password: String,
+    },
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut s = String::new();
+    for c in data.chunks(3) {
+        let b = [c[0], *c.get(1).unwrap_or(&0), *c.get(2).unwrap_or(&0)];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= c.len() {
+                s.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                s.push('=');
+            }
+        }
+    }
+    s
+}
+
+/// Read an HTTP header, i.e., up to the empty line.
+fn read_header(s: &mut TcpStream) -> Result<String> {
+    let mut buf = Vec::new();
+    let mut b = [0u8; 1];
+    while !buf.ends_with(b"\r\n\r\n") {
+        if s.read(&mut b)? == 0 {
+            bail!("connection closed");
+        }
+        buf.push(b[0]);
+        if buf.len() > 8192 {
+            bail!("header too long");
+        }
+    }
+    Ok(String::from_utf8_lossy(&buf).to_string())
+}
+
+/// Write queued chunks to the stream, until the queue is closed or the peer is gone.
+fn spawn_writer(mut s: TcpStream) -> SyncSender<Chunk> {
+    let (tx, rx) = sync_channel::<Chunk>(QUEUE);
+    std::thread::spawn(move || {
+        for chunk in rx {
+            if s.write_all(&chunk).is_err() {
+                break;
+            }
+        }
+    });
+    tx
+}
+
+/// Accept listeners, until stopped.
+fn serve(listener: TcpListener, head: Vec<u8>, clients: Listeners, stop: Arc<AtomicBool>) {
+    while !stop.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok((mut s, addr)) => {
+                let head = head.clone();
+                let clients = clients.clone();
+                std::thread::spawn(move || {
+                    let _ = s.set_nonblocking(false);
+                    let _ = s.set_read_timeout(Some(Duration::from_secs(5)));
+                    match read_header(&mut s) {
+                        Ok(r) if r.starts_with("GET ") => {
+                            debug!("IcecastSink: listener {} connected", addr);
+                            let tx = spawn_writer(s);
+                            // queue the header first, so that the listener does not miss audio
+                            let _ = tx.send(Arc::new(head));
+                            clients.lock().unwrap().push(tx);
+                        }
+                        Ok(_) => {
+                            let _ = s.write_all(b"HTTP/1.0 405 Method Not Allowed\r\n\r\n");
+                        }
+                        Err(e) => debug!("IcecastSink: invalid request from {} ({})", addr, e),
+                    }
+                });
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                std::thread::sleep(Duration::from_millis(50));
+            }
+            Err(e) => {
+                warn!("IcecastSink: accepting listener failed ({})", e);
+                std::thread::sleep(Duration::from_millis(50));
+            }
+        }
+    }
+}
+
+/// Stream audio to web radio listeners.
+pub struct IcecastSink {
+    mode: Mode,
+    channels: usize,
+    name: String,
+    description: String,
+    encoder: Box<dyn AudioEncoder>,
+    clients: Listeners,
+    server: Option<SyncSender<Chunk>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl IcecastSink {
+    fn create(builder: IcecastSinkBuilder) -> Block {
+        let encoder = builder
+            .encoder
+            .unwrap_or_else(|| Box::new(WavEncoder::new(builder.sample_rate, builder.channels)));
+        Block::new(
+            BlockMetaBuilder::new("IcecastSink").blocking().build(),
+            StreamIoBuilder::new().add_input::<f32>("in").build(),
+            MessageIoBuilder::new().build(),
+            IcecastSink {
+                mode: builder.mode,
+                channels: builder.channels as usize,
+                name: builder.name,
+                description: builder.description,
+                encoder,
+                clients: Arc::new(Mutex::new(Vec::new())),
+                server: None,
+                stop: Arc::new(AtomicBool::new(false)),
+            },
+        )
+    }
+
+    /// Send encoded audio to the server and the listeners.
+    fn send(&self, out: Vec<u8>) -> Result<()> {
+        if out.is_empty() {
+            return Ok(());
+        }
+        let chunk = Arc::new(out);
+        if let Some(s) = self.server.as_ref() {
+            if s.send(chunk.clone()).is_err() {
+                bail!("IcecastSink: connection to server lost");
+            }
+        }
+        self.clients
+            .lock()
+            .unwrap()
+            .retain(|c| match c.try_send(chunk.clone()) {
+                Ok(()) => true,
+                Err(TrySendError::Full(_)) => {
+                    debug!("IcecastSink: listener too slow, dropping audio");
+                    true
+                }
+                Err(TrySendError::Disconnected(_)) => {
+                    debug!("IcecastSink: listener disconnected");
+                    false
+                }
+            });
+        Ok(())
+    }
+
+    fn push(server: &str, request: String) -> Result<TcpStream> {
+        let mut s = TcpStream::connect(server)
+            .with_context(|| format!("IcecastSink: connecting to {server} failed"))?;
+        s.set_read_timeout(Some(Duration::from_secs(5)))?;
+        s.write_all(request.as_bytes())?;
+        let response = read_header(&mut s)?;
+        let status = response.lines().next().unwrap_or_default();
+        if status.split_whitespace().nth(1) != Some("200") {
+            bail!("IcecastSink: server rejected the stream ({})", status);
+        }
+        Ok(s)
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for IcecastSink {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<f32>();
+        let n = i.len() / self.channels * self.channels;
+
+        if n > 0 {
+            let mut out = Vec::new();
+            self.encoder.encode(&i[..n], &mut out);
+            sio.input(0).consume(n);
+            self.send(out)?;
+        }
+
+        if sio.input(0).finished() && i.len() - n < self.channels {
+            let mut out = Vec::new();
+            self.encoder.finish(&mut out);
+            self.send(out)?;
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        match &self.mode {
+            Mode::Serve(addr) => {
+                let listener = TcpListener::bind(addr)?;
+                listener.set_nonblocking(true)?;
+                let mut head = format!(
+                    "HTTP/1.0 200 OK\r\nContent-Type: {}\r\nCache-Control: no-cache\r\nicy-name: {}\r\nicy-description: {}\r\n\r\n",
+                    self.encoder.content_type(),
+                    self.name,
+                    self.description
+                )
+                .into_bytes();
+                head.extend_from_slice(&self.encoder.header());
+                let clients = self.clients.clone();
+                let stop = self.stop.clone();
+                std::thread::spawn(move || serve(listener, head, clients, stop));
+            }
+            Mode::Push {
+                server,
+                mount,
+                password,
+            } => {
+                let request = format!(
+                    "PUT {} HTTP/1.1\r\nHost: {}\r\nAuthorization: Basic {}\r\nUser-Agent: FutureSDR\r\nContent-Type: {}\r\nIce-Public: 0\r\nIce-Name: {}\r\nIce-Description: {}\r\n\r\n",
+                    mount,
+                    server,
+                    base64(format!("source:{password}").as_bytes()),
+                    self.encoder.content_type(),
+                    self.name,
+                    self.description
+                );
+                let s = Self::push(server, request)?;
+                let tx = spawn_writer(s);
+                tx.send(Arc::new(self.encoder.header()))?;
+                self.server = Some(tx);
+            }
+        }
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        // writers send the queued audio and close their connections
+        self.stop.store(true, Ordering::Relaxed);
+        self.clients.lock().unwrap().clear();
+        self.server = None;
+        Ok(())
+    }
+}
+
+/// Stream audio as a web radio, serving listeners directly or pushing to an Icecast server.
+///
+/// The sink encodes (interleaved) audio samples and either
+/// - [serves](IcecastSinkBuilder::serve) them over HTTP, i.e., any media player or browser can
+///   connect to `http://<addr>/`, or
+/// - [pushes](IcecastSinkBuilder::push) them as source to an [Icecast](https://icecast.org)
+///   server, which distributes the stream to its listeners.
+///
+/// By default, audio is streamed as uncompressed 16-bit [WAV](WavEncoder). For compressed
+/// streams, set an [encoder](IcecastSinkBuilder::encoder), e.g., the `Mp3Encoder` of the `lame`
+/// feature or any other implementation of [AudioEncoder].
+///
+/// Listeners receive the stream from the moment they connect. Audio for listeners that cannot
+/// keep up is dropped. The connection to an Icecast server, in turn, applies backpressure and
+/// the sink fails, if it is lost.
+///
+/// # Inputs
+///
+/// `in`: Audio samples (`f32`), interleaved for multiple channels
+///
+/// # Outputs
+///
+/// No outputs.
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::IcecastSinkBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // listen at http://localhost:8000/
+/// let snk = fg.add_block(
+///     IcecastSinkBuilder::new(48_000, 1)
+///         .serve("0.0.0.0:8000")
+///         .name("FM 100.0")
+///         .build(),
+/// );
+///
+/// // or push to an Icecast server
+/// let snk = fg.add_block(
+///     IcecastSinkBuilder::new(48_000, 1)
+///         .push("icecast.example.com:8000", "/fm", "hackme")
+///         .build(),
+/// );
+/// ```
+#[cfg_attr(docsrs, doc(cfg(not(target_arch = "wasm32"))))]
+pub struct IcecastSinkBuilder {
+    sample_rate: u32,
+    channels: u16,
+    mode: Mode,
+    name: String,
+    description: String,
+    encoder: Option<Box<dyn AudioEncoder>>,
+}
+
+impl IcecastSinkBuilder {
+    pub fn new(sample_rate: u32, channels: u16) -> IcecastSinkBuilder {
+        assert!(channels > 0, "at least one channel required");
+        IcecastSinkBuilder {
+            sample_rate,
+            channels,
+            mode: Mode::Serve("0.0.0.0:8000".to_string()),
+            name: "FutureSDR".to_string(),
+            description: String::new(),
+            encoder: None,
+        }
+    }
+
+    /// Serve listeners at the given address (default `0.0.0.0:8000`).
+    #[must_use]
+    pub fn serve<S: Into<String>>(mut self, addr: S) -> IcecastSinkBuilder {
+        self.mode = Mode::Serve(addr.into());
+        self
+    }
+
+    /// Push the stream to the mount point of an Icecast server, authenticating as `source`.
+    #[must_use]
+    pub fn push<S: Into<String>, M: Into<String>, P: Into<String>>(
+        mut self,
+        server: S,
+        mount: M,
+        password: P,
+    ) -> IcecastSinkBuilder {
+        let mut mount = mount.into();
+        if !mount.starts_with('/') {
+            mount.insert(0, '/');
+        }
+        self.mode = Mode::Push {
+            server: server.into(),
+            mount,
+            password: password.into(),
+        };
+        self
+    }
+
+    /// Name of the station.
+    #[must_use]
+    pub fn name<S: Into<String>>(mut self, name: S) -> IcecastSinkBuilder {
+        self.name = name.into();
+        self
+    }
+
+    /// Description of the station.
+    #[must_use]
+    pub fn description<S: Into<String>>(mut self, description: S) -> IcecastSinkBuilder {
+        self.description = description.into();
+        self
+    }
+
+    /// Encoder for the stream, replacing the default [WavEncoder].
+    #[must_use]
+    pub fn encoder(mut self, encoder: Box<dyn AudioEncoder>) -> IcecastSinkBuilder {
+        self.encoder = Some(encoder);
+        self
+    }
+
+    pub fn build(self) -> Block {
+        IcecastSink::create(self)
+    }
+}
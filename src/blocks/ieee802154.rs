@@ -0,0 +1,516 @@
+use futures::FutureExt;
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+
+use crate::anyhow::Result;
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Chip sequence of symbol 0, first chip in the most significant bit.
+const CHIPS_0: u32 = 0b1101_1001_1100_0011_0101_0010_0010_1110;
+/// Start-of-frame delimiter.
+const SFD: u8 = 0xa7;
+/// Maximum PSDU length.
+const MAX_PSDU_LEN: usize = 127;
+
+/// Chip sequences of the symbols as seen by a frequency discriminator, i.e., after the O-QPSK
+/// signal is demodulated as MSK. The first and last chip depend on the neighboring symbols.
+const MSK_CHIPS: [u32; 16] = [
+    1618456172, 1309113062, 1826650030, 1724778362, 778887287, 2061946375, 2007919840, 125494990,
+    529027475, 838370585, 320833617, 422705285, 1368596360, 85537272, 139563807, 2021988657,
+];
+const MSK_MASK: u32 = 0x7fff_fffe;
+
+/// Chip sequence of a symbol. Symbols 1 to 7 are cyclic shifts of symbol 0 by four chips,
+/// symbols 8 to 15 invert the odd chips of symbols 0 to 7.
+fn chips(symbol: u8) -> u32 {
+    let c = CHIPS_0.rotate_right(4 * (symbol as u32 & 0x7));
+    if symbol & 0x8 != 0 {
+        c ^ 0x5555_5555
+    } else {
+        c
+    }
+}
+
+/// IEEE 802.15.4 O-QPSK modulator.
+///
+/// Modulates PSDUs for the 2.4 GHz O-QPSK PHY with 2 Mchip/s:
+/// - the synchronization header (four zero bytes and the start-of-frame delimiter 0xA7) and the
+///   PHY header with the PSDU length are prepended,
+/// - each byte is spread, low nibble first, to two sequences of 32 chips,
+/// - even chips are sent on the in-phase and odd chips on the quadrature component with half-sine
+///   pulse shaping, offset by one chip.
+///
+/// The PSDU is expected to include the FCS, which can be appended with a
+/// [CrcAppend](crate::blocks::CrcAppend) with [Crc::crc16_kermit](crate::blocks::Crc::crc16_kermit).
+/// The output has `samples_per_chip` samples per chip, i.e., the sample rate is 4 MHz for two
+/// samples per chip. Samples are only produced while frames are sent, i.e., the output is a
+/// stream of bursts.
+///
+/// # Outputs
+///
+/// `out`: Baseband samples (Complex32)
+///
+/// **Message**: `in`: PSDUs with up to 127 bytes ([Pmt::Blob])
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::Ieee802154Mod;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let modulator = fg.add_block(Ieee802154Mod::new(2));
+/// ```
+pub struct Ieee802154Mod {
+    shape: Vec<f32>,
+    frames: VecDeque<Vec<Complex32>>,
+    /// Samples of the first queued frame that were already streamed.
+    pos: usize,
+}
+
+impl Ieee802154Mod {
+    pub fn new(samples_per_chip: usize) -> Block {
+        assert!(samples_per_chip > 0, "samples per chip has to be positive");
+        let shape = (0..2 * samples_per_chip)
+            .map(|k| (PI * k as f32 / (2 * samples_per_chip) as f32).sin())
+            .collect();
+
+        Block::new(
+            BlockMetaBuilder::new("Ieee802154Mod").build(),
+            StreamIoBuilder::new()
+                .add_output::<Complex32>("out")
+                .build(),
+            MessageIoBuilder::<Self>::new()
+                .add_input(
+                    "in",
+                    |block: &mut Ieee802154Mod,
+                     _mio: &mut MessageIo<Ieee802154Mod>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            match p {
+                                Pmt::Blob(v) if v.len() <= MAX_PSDU_LEN => {
+                                    let frame = block.modulate(&v);
+                                    block.frames.push_back(frame);
+                                }
+                                Pmt::Blob(v) => {
+                                    warn!("Ieee802154Mod: PSDU too long ({} bytes)", v.len())
+                                }
+                                _ => warn!("Ieee802154Mod: received wrong PMT type. {:?}", p),
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .build(),
+            Ieee802154Mod {
+                shape,
+                frames: VecDeque::new(),
+                pos: 0,
+            },
+        )
+    }
+
+    fn modulate(&self, psdu: &[u8]) -> Vec<Complex32> {
+        let mut ppdu = vec![0, 0, 0, 0, SFD, psdu.len() as u8];
+        ppdu.extend_from_slice(psdu);
+
+        // each pulse spans two chips
+        let spc = self.shape.len() / 2;
+        let n_pulses = ppdu.len() * 2 * 16;
+        let mut frame = vec![Complex32::new(0.0, 0.0); (2 * n_pulses + 1) * spc];
+        let nibbles = ppdu.iter().flat_map(|b| [b & 0x0f, b >> 4]);
+        for (i, nibble) in nibbles.enumerate() {
+            let c = chips(nibble);
+            for k in 0..16 {
+                let p = (i * 16 + k) * 2 * spc;
+                let re = if c >> (31 - 2 * k) & 1 == 1 {
+                    1.0
+                } else {
+                    -1.0
+                };
+                let im = if c >> (30 - 2 * k) & 1 == 1 {
+                    1.0
+                } else {
+                    -1.0
+                };
+                for (j, s) in self.shape.iter().enumerate() {
+                    frame[p + j].re += re * s;
+                    frame[p + spc + j].im += im * s;
+                }
+            }
+        }
+        frame
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for Ieee802154Mod {
+    async fn work(
+        &mut self,
+        _io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let o = sio.output(0).slice::<Complex32>();
+
+        let mut n = 0;
+        while n < o.len() {
+            let frame = match self.frames.front() {
+                Some(frame) => frame,
+                None => break,
+            };
+            let m = std::cmp::min(frame.len() - self.pos, o.len() - n);
+            o[n..n + m].copy_from_slice(&frame[self.pos..self.pos + m]);
+            n += m;
+            self.pos += m;
+            if self.pos == frame.len() {
+                self.frames.pop_front();
+                self.pos = 0;
+            }
+        }
+        sio.output(0).produce(n);
+
+        Ok(())
+    }
+}
+
+/// Maximum number of discriminator samples, buffered for clock recovery.
+const MAX_BUFFER: usize = 1 << 16;
+/// Averaging factor of the frequency offset estimate.
+const OFFSET_ALPHA: f32 = 0.00016;
+/// Gains and maximum relative deviation of the clock recovery.
+const GAIN_OMEGA: f32 = 0.000225;
+const GAIN_MU: f32 = 0.03;
+const OMEGA_LIMIT: f32 = 0.0002;
+
+/// IEEE 802.15.4 O-QPSK demodulator.
+///
+/// Demodulates the O-QPSK signal as MSK, i.e., with a frequency discriminator on the samples,
+/// averaged over one chip. The frequency offset is removed with a slow average of the discriminator output and the chips are sampled
+/// with a Mueller and Müller clock recovery. The soft chips are the input of the
+/// [Ieee802154FrameSync].
+///
+/// # Inputs
+///
+/// `in`: Baseband samples (Complex32) with `samples_per_chip` samples per chip
+///
+/// # Outputs
+///
+/// `out`: Soft chips (f32)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::Ieee802154Demod;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // 4 MHz sample rate
+/// let demod = fg.add_block(Ieee802154Demod::new(2));
+/// ```
+pub struct Ieee802154Demod {
+    /// Samples of the last chip, averaged to suppress out-of-band noise.
+    history: VecDeque<Complex32>,
+    last: Complex32,
+    offset: f32,
+    /// Discriminator output, waiting for clock recovery.
+    buf: Vec<f32>,
+    omega: f32,
+    omega_mid: f32,
+    mu: f32,
+    last_chip: f32,
+    look_ahead: usize,
+}
+
+impl Ieee802154Demod {
+    pub fn new(samples_per_chip: usize) -> Block {
+        assert!(samples_per_chip > 0, "samples per chip has to be positive");
+        let omega = samples_per_chip as f32;
+
+        Block::new(
+            BlockMetaBuilder::new("Ieee802154Demod").build(),
+            StreamIoBuilder::new()
+                .add_input::<Complex32>("in")
+                .add_output::<f32>("out")
+                .build(),
+            MessageIoBuilder::<Self>::new().build(),
+            Ieee802154Demod {
+                history: VecDeque::from(vec![Complex32::new(0.0, 0.0); samples_per_chip]),
+                last: Complex32::new(0.0, 0.0),
+                offset: 0.0,
+                buf: Vec::new(),
+                omega,
+                omega_mid: omega,
+                mu: 0.5,
+                last_chip: 0.0,
+                look_ahead: (omega * (1.0 + OMEGA_LIMIT) + GAIN_MU).ceil() as usize,
+            },
+        )
+    }
+}
+
+fn slice(x: f32) -> f32 {
+    if x > 0.0 {
+        1.0
+    } else {
+        -1.0
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for Ieee802154Demod {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex32>();
+        let o = sio.output(0).slice::<f32>();
+
+        let n = std::cmp::min(i.len(), MAX_BUFFER.saturating_sub(self.buf.len()));
+        for x in i[..n].iter() {
+            self.history.pop_front();
+            self.history.push_back(*x);
+            let x: Complex32 = self.history.iter().sum();
+            let phase = (self.last.conj() * x).arg();
+            self.last = x;
+            self.offset = (1.0 - OFFSET_ALPHA) * self.offset + OFFSET_ALPHA * phase;
+            self.buf.push(phase - self.offset);
+        }
+        sio.input(0).consume(n);
+
+        let limit = self.omega_mid * OMEGA_LIMIT;
+        let mut ii = 0;
+        let mut oo = 0;
+        while ii + self.look_ahead < self.buf.len() && oo < o.len() {
+            let chip = self.buf[ii] + self.mu * (self.buf[ii + 1] - self.buf[ii]);
+            o[oo] = chip;
+            let error = slice(self.last_chip) * chip - slice(chip) * self.last_chip;
+            self.last_chip = chip;
+
+            self.omega += GAIN_OMEGA * error;
+            self.omega = self.omega_mid + (self.omega - self.omega_mid).clamp(-limit, limit);
+            self.mu += self.omega + GAIN_MU * error;
+
+            ii += self.mu.floor() as usize;
+            self.mu -= self.mu.floor();
+            oo += 1;
+        }
+        self.buf.drain(..std::cmp::min(ii, self.buf.len()));
+        sio.output(0).produce(oo);
+
+        if sio.input(0).finished() && n == i.len() {
+            if self.buf.len() <= self.look_ahead {
+                io.finished = true;
+            } else {
+                io.call_again = true;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+enum State {
+    /// Search for a preamble symbol.
+    Search,
+    /// Preamble symbols, followed by the first symbol of the SFD.
+    Preamble,
+    /// Second symbol of the SFD.
+    Sfd,
+    /// PHY header with the PSDU length.
+    Header { low: Option<u8> },
+    /// PSDU.
+    Psdu {
+        len: usize,
+        data: Vec<u8>,
+        low: Option<u8>,
+    },
+}
+
+/// IEEE 802.15.4 O-QPSK frame synchronization.
+///
+/// Searches the soft chips of an [Ieee802154Demod] for the preamble and the start-of-frame
+/// delimiter, despreads the PHY header and the PSDU, and posts the PSDU. Symbols are despread to
+/// the chip sequence with the fewest chip errors; a frame is dropped if a symbol has more than
+/// `max_chip_errors` chip errors. The FCS is not checked, which can be done with a
+/// [CrcCheck](crate::blocks::CrcCheck) with [Crc::crc16_kermit](crate::blocks::Crc::crc16_kermit).
+///
+/// # Inputs
+///
+/// `in`: Soft chips (f32)
+///
+/// # Outputs
+///
+/// **Message**: `out`: PSDUs, including the FCS ([Pmt::Blob])
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::Ieee802154Demod;
+/// use futuresdr::blocks::Ieee802154FrameSync;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let demod = fg.add_block(Ieee802154Demod::new(2));
+/// let sync = fg.add_block(Ieee802154FrameSync::new(5));
+/// fg.connect_stream(demod, "out", sync, "in").unwrap();
+/// ```
+pub struct Ieee802154FrameSync {
+    max_chip_errors: u32,
+    shift_reg: u32,
+    chip_count: u32,
+    state: State,
+}
+
+impl Ieee802154FrameSync {
+    pub fn new(max_chip_errors: u32) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("Ieee802154FrameSync").build(),
+            StreamIoBuilder::new().add_input::<f32>("in").build(),
+            MessageIoBuilder::<Self>::new().add_output("out").build(),
+            Ieee802154FrameSync {
+                max_chip_errors,
+                shift_reg: 0,
+                chip_count: 0,
+                state: State::Search,
+            },
+        )
+    }
+
+    fn errors(&self, symbol: usize) -> u32 {
+        ((self.shift_reg ^ MSK_CHIPS[symbol]) & MSK_MASK).count_ones()
+    }
+
+    fn matches(&self, symbol: usize) -> bool {
+        self.errors(symbol) <= self.max_chip_errors
+    }
+
+    fn despread(&self) -> Option<u8> {
+        (0..16)
+            .min_by_key(|s| self.errors(*s))
+            .filter(|s| self.matches(*s))
+            .map(|s| s as u8)
+    }
+
+    fn push(&mut self, chip: f32, frames: &mut Vec<Pmt>) {
+        self.shift_reg = self.shift_reg << 1 | (chip > 0.0) as u32;
+        self.chip_count = (self.chip_count + 1) % 32;
+
+        if let State::Search = self.state {
+            if self.matches(0) {
+                self.state = State::Preamble;
+                self.chip_count = 0;
+            }
+            return;
+        }
+        if self.chip_count != 0 {
+            return;
+        }
+
+        let state = std::mem::replace(&mut self.state, State::Search);
+        self.state = match state {
+            State::Search => State::Search,
+            State::Preamble => {
+                if self.matches((SFD & 0x0f) as usize) {
+                    State::Sfd
+                } else if self.matches(0) {
+                    State::Preamble
+                } else {
+                    State::Search
+                }
+            }
+            State::Sfd => {
+                if self.matches((SFD >> 4) as usize) {
+                    State::Header { low: None }
+                } else {
+                    State::Search
+                }
+            }
+            State::Header { low } => match (self.despread(), low) {
+                (Some(s), None) => State::Header { low: Some(s) },
+                (Some(s), Some(l)) => {
+                    let len = (s << 4 | l) as usize;
+                    if len > 0 && len <= MAX_PSDU_LEN {
+                        State::Psdu {
+                            len,
+                            data: Vec::with_capacity(len),
+                            low: None,
+                        }
+                    } else {
+                        State::Search
+                    }
+                }
+                (None, _) => State::Search,
+            },
+            State::Psdu { len, mut data, low } => match (self.despread(), low) {
+                (Some(s), None) => State::Psdu {
+                    len,
+                    data,
+                    low: Some(s),
+                },
+                (Some(s), Some(l)) => {
+                    data.push(s << 4 | l);
+                    if data.len() == len {
+                        frames.push(Pmt::Blob(data));
+                        State::Search
+                    } else {
+                        State::Psdu {
+                            len,
+                            data,
+                            low: None,
+                        }
+                    }
+                }
+                (None, _) => State::Search,
+            },
+        };
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for Ieee802154FrameSync {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<f32>();
+
+        let mut frames = Vec::new();
+        for x in i.iter() {
+            self.push(*x, &mut frames);
+        }
+        sio.input(0).consume(i.len());
+
+        if sio.input(0).finished() {
+            io.finished = true;
+        }
+
+        for f in frames {
+            mio.post(0, f).await;
+        }
+
+        Ok(())
+    }
+}
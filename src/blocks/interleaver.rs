@@ -0,0 +1,322 @@
+use std::collections::VecDeque;
+
+use crate::anyhow::Result;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Fixed permutation of blocks of items, where output `k` is input `perm[k]`.
+struct Permutation {
+    perm: Vec<usize>,
+}
+
+impl Permutation {
+    /// Write row-wise, read column-wise.
+    fn interleaver(rows: usize, cols: usize) -> Permutation {
+        assert!(rows > 0 && cols > 0, "matrix dimensions must be positive");
+        let mut perm = Vec::with_capacity(rows * cols);
+        for c in 0..cols {
+            for r in 0..rows {
+                perm.push(r * cols + c);
+            }
+        }
+        Permutation { perm }
+    }
+
+    fn process<T: Copy + Send + 'static>(&self, sio: &mut StreamIo, io: &mut WorkIo) {
+        let i = sio.input(0).slice::<T>();
+        let o = sio.output(0).slice::<T>();
+
+        let n = self.perm.len();
+        let blocks = std::cmp::min(i.len(), o.len()) / n;
+        for (x, y) in i.chunks_exact(n).zip(o.chunks_exact_mut(n)).take(blocks) {
+            for (y, p) in y.iter_mut().zip(self.perm.iter()) {
+                *y = x[*p];
+            }
+        }
+
+        sio.input(0).consume(blocks * n);
+        sio.output(0).produce(blocks * n);
+
+        // incomplete blocks at the end of the stream are dropped
+        if sio.input(0).finished() && i.len() - blocks * n < n {
+            io.finished = true;
+        }
+    }
+}
+
+/// Delay lines of increasing length, served by a commutator.
+struct Branches<T> {
+    branches: Vec<VecDeque<T>>,
+    pos: usize,
+}
+
+impl<T: Copy + Default + Send + 'static> Branches<T> {
+    fn new(delays: impl Iterator<Item = usize>) -> Branches<T> {
+        let branches: Vec<VecDeque<T>> = delays
+            .map(|d| std::iter::repeat(T::default()).take(d).collect())
+            .collect();
+        assert!(!branches.is_empty(), "number of branches must be positive");
+        Branches { branches, pos: 0 }
+    }
+
+    fn process(&mut self, sio: &mut StreamIo, io: &mut WorkIo) {
+        let i = sio.input(0).slice::<T>();
+        let o = sio.output(0).slice::<T>();
+
+        let m = std::cmp::min(i.len(), o.len());
+        for (x, y) in i[..m].iter().zip(o.iter_mut()) {
+            let b = &mut self.branches[self.pos];
+            b.push_back(*x);
+            *y = b.pop_front().unwrap();
+            self.pos = (self.pos + 1) % self.branches.len();
+        }
+
+        sio.input(0).consume(m);
+        sio.output(0).produce(m);
+
+        if sio.input(0).finished() && m == i.len() {
+            io.finished = true;
+        }
+    }
+}
+
+/// Block interleaver.
+///
+/// Writes blocks of `rows * cols` items row-wise into a matrix and reads them column-wise. An
+/// incomplete block at the end of the stream is dropped.
+///
+/// # Inputs
+///
+/// `in`: Input (e.g., bits or bytes as u8)
+///
+/// # Outputs
+///
+/// `out`: Interleaved output
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::BlockInterleaver;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let interleaver = fg.add_block(BlockInterleaver::<u8>::new(16, 12));
+/// ```
+pub struct BlockInterleaver<T: Copy + Send + 'static> {
+    permutation: Permutation,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: Copy + Send + 'static> BlockInterleaver<T> {
+    pub fn new(rows: usize, cols: usize) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("BlockInterleaver").build(),
+            StreamIoBuilder::new()
+                .add_input::<T>("in")
+                .add_output::<T>("out")
+                .build(),
+            MessageIoBuilder::<Self>::new().build(),
+            BlockInterleaver::<T> {
+                permutation: Permutation::interleaver(rows, cols),
+                _type: std::marker::PhantomData,
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: Copy + Send + 'static> Kernel for BlockInterleaver<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        self.permutation.process::<T>(sio, io);
+        Ok(())
+    }
+}
+
+/// Block deinterleaver.
+///
+/// Inverse of the [BlockInterleaver] with the same dimensions, i.e., writes blocks of
+/// `rows * cols` items column-wise into a matrix and reads them row-wise.
+///
+/// # Inputs
+///
+/// `in`: Interleaved input
+///
+/// # Outputs
+///
+/// `out`: Output
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::BlockDeinterleaver;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let deinterleaver = fg.add_block(BlockDeinterleaver::<u8>::new(16, 12));
+/// ```
+pub struct BlockDeinterleaver<T: Copy + Send + 'static> {
+    permutation: Permutation,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: Copy + Send + 'static> BlockDeinterleaver<T> {
+    pub fn new(rows: usize, cols: usize) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("BlockDeinterleaver").build(),
+            StreamIoBuilder::new()
+                .add_input::<T>("in")
+                .add_output::<T>("out")
+                .build(),
+            MessageIoBuilder::<Self>::new().build(),
+            BlockDeinterleaver::<T> {
+                permutation: Permutation::interleaver(cols, rows),
+                _type: std::marker::PhantomData,
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: Copy + Send + 'static> Kernel for BlockDeinterleaver<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        self.permutation.process::<T>(sio, io);
+        Ok(())
+    }
+}
+
+/// Convolutional (Forney) interleaver.
+///
+/// Items are distributed cyclically over `branches` branches, where branch `j` delays by
+/// `j * delay` items (e.g., 12 branches with a delay of 17 for DVB-S). Delay lines are
+/// initialized with `T::default()`.
+///
+/// # Inputs
+///
+/// `in`: Input (e.g., bits or bytes as u8)
+///
+/// # Outputs
+///
+/// `out`: Interleaved output
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::ConvolutionalInterleaver;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let interleaver = fg.add_block(ConvolutionalInterleaver::<u8>::new(12, 17));
+/// ```
+pub struct ConvolutionalInterleaver<T: Copy + Default + Send + 'static> {
+    branches: Branches<T>,
+}
+
+impl<T: Copy + Default + Send + 'static> ConvolutionalInterleaver<T> {
+    pub fn new(branches: usize, delay: usize) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("ConvolutionalInterleaver").build(),
+            StreamIoBuilder::new()
+                .add_input::<T>("in")
+                .add_output::<T>("out")
+                .build(),
+            MessageIoBuilder::<Self>::new().build(),
+            ConvolutionalInterleaver::<T> {
+                branches: Branches::new((0..branches).map(|j| j * delay)),
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: Copy + Default + Send + 'static> Kernel for ConvolutionalInterleaver<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        self.branches.process(sio, io);
+        Ok(())
+    }
+}
+
+/// Convolutional (Forney) deinterleaver.
+///
+/// Inverse of the [ConvolutionalInterleaver] with the same parameters, i.e., branch `j` delays by
+/// `(branches - 1 - j) * delay` items. Together, interleaver and deinterleaver delay the stream by
+/// `branches * (branches - 1) * delay` items.
+///
+/// # Inputs
+///
+/// `in`: Interleaved input
+///
+/// # Outputs
+///
+/// `out`: Output
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::ConvolutionalDeinterleaver;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let deinterleaver = fg.add_block(ConvolutionalDeinterleaver::<u8>::new(12, 17));
+/// ```
+pub struct ConvolutionalDeinterleaver<T: Copy + Default + Send + 'static> {
+    branches: Branches<T>,
+}
+
+impl<T: Copy + Default + Send + 'static> ConvolutionalDeinterleaver<T> {
+    pub fn new(branches: usize, delay: usize) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("ConvolutionalDeinterleaver").build(),
+            StreamIoBuilder::new()
+                .add_input::<T>("in")
+                .add_output::<T>("out")
+                .build(),
+            MessageIoBuilder::<Self>::new().build(),
+            ConvolutionalDeinterleaver::<T> {
+                branches: Branches::new((0..branches).rev().map(|j| j * delay)),
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: Copy + Default + Send + 'static> Kernel for ConvolutionalDeinterleaver<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        self.branches.process(sio, io);
+        Ok(())
+    }
+}
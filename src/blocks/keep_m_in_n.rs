@@ -0,0 +1,210 @@
+use crate::anyhow::Result;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::Tag;
+use crate::runtime::WorkIo;
+
+/// Keep `m` samples out of every `n` samples.
+pub struct KeepMInN<T: Copy + Send + 'static> {
+    m: usize,
+    n: usize,
+    offset: usize,
+    align_tag: Option<String>,
+    pos: usize,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: Copy + Send + 'static> KeepMInN<T> {
+    pub fn new(m: usize, n: usize, offset: usize, align_tag: Option<&str>) -> Block {
+        Self::with_name("KeepMInN", m, n, offset, align_tag)
+    }
+
+    fn with_name(name: &str, m: usize, n: usize, offset: usize, align_tag: Option<&str>) -> Block {
+        assert!(m > 0, "m must be positive");
+        assert!(offset + m <= n, "offset + m must not exceed n");
+
+        Block::new(
+            BlockMetaBuilder::new(name).build(),
+            StreamIoBuilder::new()
+                .add_input::<T>("in")
+                .add_output::<T>("out")
+                .build(),
+            MessageIoBuilder::<Self>::new().build(),
+            KeepMInN::<T> {
+                m,
+                n,
+                offset,
+                align_tag: align_tag.map(|s| s.to_string()),
+                pos: 0,
+                _type: std::marker::PhantomData,
+            },
+        )
+    }
+}
+
+fn tag_name(tag: &Tag) -> Option<&str> {
+    match tag {
+        Tag::String(s) | Tag::NamedUsize(s, _) | Tag::NamedF32(s, _) | Tag::NamedAny(s, _) => {
+            Some(s)
+        }
+        _ => None,
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: Copy + Send + 'static> Kernel for KeepMInN<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<T>();
+        let o = sio.output(0).slice::<T>();
+        let tags = sio.input(0).tags().clone();
+
+        let mut consumed = 0;
+        let mut produced = 0;
+        for (k, x) in i.iter().enumerate() {
+            if produced == o.len() {
+                break;
+            }
+
+            if let Some(ref name) = self.align_tag {
+                if tags
+                    .iter()
+                    .any(|t| t.index == k && tag_name(&t.tag) == Some(name.as_str()))
+                {
+                    self.pos = self.offset;
+                }
+            }
+
+            if self.pos >= self.offset && self.pos < self.offset + self.m {
+                for t in tags.iter().filter(|t| t.index == k) {
+                    sio.output(0).add_tag(produced, t.tag.clone());
+                }
+                o[produced] = *x;
+                produced += 1;
+            }
+            self.pos = (self.pos + 1) % self.n;
+            consumed += 1;
+        }
+
+        sio.input(0).consume(consumed);
+        sio.output(0).produce(produced);
+
+        if sio.input(0).finished() && consumed == i.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Keep `m` samples out of every `n` samples.
+///
+/// Of every `n` samples, the samples `offset` to `offset + m - 1` are kept (default: offset 0).
+/// Optionally, the window is aligned to tags with a given name (i.e., [Tag::String] or named
+/// tags), such that a tagged sample is the first sample that is kept. Tags of kept samples are
+/// forwarded.
+///
+/// # Inputs
+///
+/// `in`: Input
+///
+/// # Outputs
+///
+/// `out`: Kept samples
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::KeepMInNBuilder;
+/// use futuresdr::num_complex::Complex32;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // keep the 64 samples of each OFDM symbol after the cyclic prefix
+/// let keep = fg.add_block(
+///     KeepMInNBuilder::<Complex32>::new(64, 80)
+///         .offset(16)
+///         .align_tag("frame_start")
+///         .build(),
+/// );
+/// ```
+pub struct KeepMInNBuilder<T: Copy + Send + 'static> {
+    m: usize,
+    n: usize,
+    offset: usize,
+    align_tag: Option<String>,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: Copy + Send + 'static> KeepMInNBuilder<T> {
+    pub fn new(m: usize, n: usize) -> KeepMInNBuilder<T> {
+        KeepMInNBuilder {
+            m,
+            n,
+            offset: 0,
+            align_tag: None,
+            _type: std::marker::PhantomData,
+        }
+    }
+
+    /// Index of the first kept sample in each block of `n` samples.
+    #[must_use]
+    pub fn offset(mut self, offset: usize) -> KeepMInNBuilder<T> {
+        self.offset = offset;
+        self
+    }
+
+    /// Align the window to tags with the given name.
+    #[must_use]
+    pub fn align_tag(mut self, name: &str) -> KeepMInNBuilder<T> {
+        self.align_tag = Some(name.to_string());
+        self
+    }
+
+    pub fn build(self) -> Block {
+        KeepMInN::<T>::new(self.m, self.n, self.offset, self.align_tag.as_deref())
+    }
+}
+
+/// Keep one sample out of every `n` samples.
+///
+/// Decimates without filtering, keeping the first sample of every `n` samples.
+///
+/// # Inputs
+///
+/// `in`: Input
+///
+/// # Outputs
+///
+/// `out`: Kept samples
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::KeepOneInN;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let keep = fg.add_block(KeepOneInN::<f32>::new(100));
+/// ```
+pub struct KeepOneInN<T: Copy + Send + 'static> {
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: Copy + Send + 'static> KeepOneInN<T> {
+    pub fn new(n: usize) -> Block {
+        KeepMInN::<T>::with_name("KeepOneInN", 1, n, 0, None)
+    }
+}
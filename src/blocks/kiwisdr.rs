@@ -0,0 +1,456 @@
+use async_io::{Async, Timer};
+use async_tungstenite::tungstenite;
+use async_tungstenite::tungstenite::{Message, WebSocket};
+use futures::FutureExt;
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::anyhow::{anyhow, bail, Context, Result};
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+const KEEPALIVE: Duration = Duration::from_secs(5);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Demodulation mode of a [KiwiSdrSource](KiwiSdrSourceBuilder).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KiwiSdrMode {
+    /// Complex baseband samples.
+    Iq,
+    /// AM audio.
+    Am,
+    /// Lower sideband audio.
+    Lsb,
+    /// Upper sideband audio.
+    Usb,
+    /// CW audio.
+    Cw,
+    /// Narrowband FM audio.
+    Nbfm,
+}
+
+impl KiwiSdrMode {
+    fn name(&self) -> &'static str {
+        match self {
+            KiwiSdrMode::Iq => "iq",
+            KiwiSdrMode::Am => "am",
+            KiwiSdrMode::Lsb => "lsb",
+            KiwiSdrMode::Usb => "usb",
+            KiwiSdrMode::Cw => "cw",
+            KiwiSdrMode::Nbfm => "nbfm",
+        }
+    }
+
+    /// Default passband in Hz, relative to the carrier.
+    fn passband(&self) -> (i32, i32) {
+        match self {
+            KiwiSdrMode::Iq | KiwiSdrMode::Am | KiwiSdrMode::Nbfm => (-5000, 5000),
+            KiwiSdrMode::Lsb => (-2700, -300),
+            KiwiSdrMode::Usb => (300, 2700),
+            KiwiSdrMode::Cw => (300, 700),
+        }
+    }
+}
+
+/// Receive audio or IQ samples from a remote [KiwiSDR](http://kiwisdr.com/).
+pub struct KiwiSdrSource {
+    address: String,
+    password: String,
+    frequency: f64,
+    mode: KiwiSdrMode,
+    passband: (i32, i32),
+    gain: Option<u32>,
+    ws: Option<WebSocket<TcpStream>>,
+    socket: Option<Arc<Async<TcpStream>>>,
+    sample_rate: Option<f64>,
+    last_keepalive: Instant,
+    /// Received samples (interleaved I/Q in IQ mode) that did not fit into the output buffer.
+    samples: Vec<f32>,
+    pos: usize,
+}
+
+impl KiwiSdrSource {
+    fn create(
+        address: String,
+        password: String,
+        frequency: f64,
+        mode: KiwiSdrMode,
+        passband: (i32, i32),
+        gain: Option<u32>,
+    ) -> Block {
+        let stream_io = if mode == KiwiSdrMode::Iq {
+            StreamIoBuilder::new().add_output::<Complex32>("out")
+        } else {
+            StreamIoBuilder::new().add_output::<f32>("out")
+        };
+
+        Block::new(
+            BlockMetaBuilder::new("KiwiSdrSource").build(),
+            stream_io.build(),
+            MessageIoBuilder::new()
+                .add_input(
+                    "freq",
+                    |block: &mut KiwiSdrSource,
+                     _mio: &mut MessageIo<KiwiSdrSource>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            let f = match &p {
+                                Pmt::F32(v) => Some(*v as f64),
+                                Pmt::F64(v) => Some(*v),
+                                Pmt::U32(v) => Some(*v as f64),
+                                Pmt::U64(v) => Some(*v as f64),
+                                _ => None,
+                            };
+                            if let Some(f) = f {
+                                block.frequency = f;
+                                if block.sample_rate.is_some() {
+                                    block.send(block.tune_command())?;
+                                }
+                            } else {
+                                warn!("KiwiSdrSource/freq: received wrong PMT type. {:?}", p);
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .add_output("info")
+                .build(),
+            KiwiSdrSource {
+                address,
+                password,
+                frequency,
+                mode,
+                passband,
+                gain,
+                ws: None,
+                socket: None,
+                sample_rate: None,
+                last_keepalive: Instant::now(),
+                samples: Vec::new(),
+                pos: 0,
+            },
+        )
+    }
+
+    fn tune_command(&self) -> String {
+        format!(
+            "SET mod={} low_cut={} high_cut={} freq={:.3}",
+            self.mode.name(),
+            self.passband.0,
+            self.passband.1,
+            self.frequency / 1e3
+        )
+    }
+
+    /// Queue a command. It is sent, once the socket is writable.
+    fn send(&mut self, command: String) -> Result<()> {
+        let ws = self.ws.as_mut().context("not connected")?;
+        match ws.write_message(Message::Text(command)) {
+            Ok(()) => Ok(()),
+            Err(tungstenite::Error::Io(e)) if e.kind() == ErrorKind::WouldBlock => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Handle a status message of the server.
+    async fn handle_msg(&mut self, msg: &str, mio: &mut MessageIo<Self>) -> Result<()> {
+        for param in msg.split(' ') {
+            let (key, value) = param.split_once('=').unwrap_or((param, ""));
+            match key {
+                "too_busy" => bail!("KiwiSdrSource: all receiver channels are in use"),
+                "badp" if value != "0" => bail!("KiwiSdrSource: wrong password"),
+                "down" => bail!("KiwiSdrSource: server is down"),
+                "audio_rate" => {
+                    self.send(format!("SET AR OK in={} out=48000", value))?;
+                }
+                "sample_rate" => {
+                    let rate: f64 = value.parse()?;
+                    info!("KiwiSdrSource: sample rate {}", rate);
+                    self.sample_rate = Some(rate);
+
+                    self.send(self.tune_command())?;
+                    match self.gain {
+                        Some(g) => self.send(format!(
+                            "SET agc=0 hang=0 thresh=-100 slope=6 decay=1000 manGain={}",
+                            g
+                        ))?,
+                        None => self.send(
+                            "SET agc=1 hang=0 thresh=-100 slope=6 decay=1000 manGain=50"
+                                .to_string(),
+                        )?,
+                    }
+                    self.send("SET compression=0".to_string())?;
+                    self.send("SET ident_user=FutureSDR".to_string())?;
+
+                    let mut m = HashMap::new();
+                    m.insert("sample_rate".to_string(), Pmt::F64(rate));
+                    m.insert("frequency".to_string(), Pmt::F64(self.frequency));
+                    mio.post(0, Pmt::MapStrPmt(m)).await;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Decode the samples of an audio message.
+    fn handle_snd(&mut self, data: &[u8]) {
+        // flags (1), sequence number (4), s-meter (2)
+        if data.len() < 7 {
+            return;
+        }
+        let mut data = &data[7..];
+        if self.mode == KiwiSdrMode::Iq {
+            // GPS timestamp
+            if data.len() < 10 {
+                return;
+            }
+            data = &data[10..];
+        }
+        self.samples = data
+            .chunks_exact(2)
+            .map(|c| i16::from_be_bytes([c[0], c[1]]) as f32 / 32768.0)
+            .collect();
+        self.pos = 0;
+    }
+
+    /// Write pending samples to the output.
+    ///
+    /// Returns `true`, if all samples are written.
+    fn output(&mut self, sio: &mut StreamIo) -> bool {
+        if self.mode == KiwiSdrMode::Iq {
+            let o = sio.output(0).slice::<Complex32>();
+            let n = std::cmp::min(o.len(), (self.samples.len() - self.pos) / 2);
+            for (i, c) in self.samples[self.pos..self.pos + 2 * n]
+                .chunks_exact(2)
+                .enumerate()
+            {
+                o[i] = Complex32::new(c[0], c[1]);
+            }
+            sio.output(0).produce(n);
+            self.pos += 2 * n;
+            self.samples.len() - self.pos < 2
+        } else {
+            let o = sio.output(0).slice::<f32>();
+            let n = std::cmp::min(o.len(), self.samples.len() - self.pos);
+            o[..n].copy_from_slice(&self.samples[self.pos..self.pos + n]);
+            sio.output(0).produce(n);
+            self.pos += n;
+            self.pos == self.samples.len()
+        }
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for KiwiSdrSource {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        if !self.output(sio) {
+            return Ok(());
+        }
+
+        if self.last_keepalive.elapsed() >= KEEPALIVE {
+            self.send("SET keepalive".to_string())?;
+            self.last_keepalive = Instant::now();
+        }
+
+        let ws = self.ws.as_mut().context("not connected")?;
+        match ws.write_pending() {
+            Ok(()) => {}
+            Err(tungstenite::Error::Io(e)) if e.kind() == ErrorKind::WouldBlock => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        match ws.read_message() {
+            Ok(Message::Binary(data)) => {
+                if data.starts_with(b"SND") {
+                    self.handle_snd(&data[3..]);
+                } else if data.starts_with(b"MSG ") {
+                    let msg = String::from_utf8_lossy(&data[4..]).to_string();
+                    self.handle_msg(&msg, mio).await?;
+                }
+                io.call_again = true;
+            }
+            Ok(Message::Text(msg)) => {
+                if let Some(msg) = msg.strip_prefix("MSG ") {
+                    self.handle_msg(msg, mio).await?;
+                }
+                io.call_again = true;
+            }
+            Ok(Message::Close(_))
+            | Err(tungstenite::Error::ConnectionClosed)
+            | Err(tungstenite::Error::AlreadyClosed) => {
+                info!("KiwiSdrSource: server closed the connection");
+                io.finished = true;
+            }
+            Ok(_) => io.call_again = true,
+            Err(tungstenite::Error::Io(e)) if e.kind() == ErrorKind::WouldBlock => {
+                // wait for data or the next keepalive
+                let socket = self.socket.as_ref().context("not connected")?.clone();
+                let timeout = KEEPALIVE.saturating_sub(self.last_keepalive.elapsed());
+                io.block_on(async move {
+                    futures_lite::future::or(
+                        async move {
+                            let _ = socket.readable().await;
+                        },
+                        async move {
+                            Timer::after(timeout).await;
+                        },
+                    )
+                    .await;
+                });
+            }
+            Err(e) => bail!("KiwiSdrSource: websocket error {}", e),
+        }
+
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let addr = self
+            .address
+            .to_socket_addrs()?
+            .next()
+            .with_context(|| format!("cannot resolve {}", self.address))?;
+        let stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)?;
+        stream.set_read_timeout(Some(CONNECT_TIMEOUT))?;
+
+        let ts = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let url = format!("ws://{}/{}/SND", self.address, ts);
+        let (ws, _) = tungstenite::client(url.as_str(), stream.try_clone()?)
+            .map_err(|e| anyhow!("KiwiSdrSource: websocket handshake failed {}", e))?;
+        info!("KiwiSdrSource: connected to {}", self.address);
+
+        stream.set_read_timeout(None)?;
+        self.socket = Some(Arc::new(Async::new(stream)?));
+        self.ws = Some(ws);
+        self.send(format!("SET auth t=kiwi p={}", self.password))?;
+        self.last_keepalive = Instant::now();
+        Ok(())
+    }
+}
+
+/// Receive audio or IQ samples from a remote [KiwiSDR](http://kiwisdr.com/).
+///
+/// Connects to the audio WebSocket of the receiver and outputs the uncompressed samples, scaled
+/// to `[-1, 1)`. In [KiwiSdrMode::Iq], the output is [Complex32]; in all other modes, the
+/// receiver demodulates the signal and the output is `f32` audio. The sample rate is determined
+/// by the receiver (typically 12 kHz) and posted, together with the frequency, as
+/// [Pmt::MapStrPmt] on the `info` output, once it is known. Without a manual gain, the AGC of the
+/// receiver is enabled.
+///
+/// # Inputs
+///
+/// **Message**: `freq`: Carrier frequency in Hz
+///
+/// # Outputs
+///
+/// `out`: Received samples
+///
+/// **Message**: `info`: Sample rate and frequency
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::{KiwiSdrMode, KiwiSdrSourceBuilder};
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let src = fg.add_block(
+///     KiwiSdrSourceBuilder::new("kiwisdr.example.com:8073")
+///         .frequency(7_074_000.0)
+///         .mode(KiwiSdrMode::Usb)
+///         .build(),
+/// );
+/// ```
+pub struct KiwiSdrSourceBuilder {
+    address: String,
+    password: String,
+    frequency: f64,
+    mode: KiwiSdrMode,
+    passband: Option<(i32, i32)>,
+    gain: Option<u32>,
+}
+
+impl KiwiSdrSourceBuilder {
+    pub fn new(address: impl Into<String>) -> KiwiSdrSourceBuilder {
+        KiwiSdrSourceBuilder {
+            address: address.into(),
+            password: String::new(),
+            frequency: 10_000_000.0,
+            mode: KiwiSdrMode::Iq,
+            passband: None,
+            gain: None,
+        }
+    }
+
+    /// Carrier frequency in Hz.
+    #[must_use]
+    pub fn frequency(mut self, frequency: f64) -> KiwiSdrSourceBuilder {
+        self.frequency = frequency;
+        self
+    }
+
+    #[must_use]
+    pub fn mode(mut self, mode: KiwiSdrMode) -> KiwiSdrSourceBuilder {
+        self.mode = mode;
+        self
+    }
+
+    /// Passband in Hz, relative to the carrier. Defaults to a typical passband of the mode.
+    #[must_use]
+    pub fn passband(mut self, low: i32, high: i32) -> KiwiSdrSourceBuilder {
+        self.passband = Some((low, high));
+        self
+    }
+
+    /// Manual gain in dB, disabling the AGC.
+    #[must_use]
+    pub fn gain(mut self, gain: u32) -> KiwiSdrSourceBuilder {
+        self.gain = Some(gain);
+        self
+    }
+
+    /// Password of the receiver, if it is protected.
+    #[must_use]
+    pub fn password(mut self, password: impl Into<String>) -> KiwiSdrSourceBuilder {
+        self.password = password.into();
+        self
+    }
+
+    pub fn build(self) -> Block {
+        KiwiSdrSource::create(
+            self.address,
+            self.password,
+            self.frequency,
+            self.mode,
+            self.passband.unwrap_or_else(|| self.mode.passband()),
+            self.gain,
+        )
+    }
+}
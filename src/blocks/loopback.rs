@@ -0,0 +1,237 @@
+use rand::rngs::StdRng;
+
+use crate::anyhow::Result;
+use crate::blocks::channel_model::complex_gaussian;
+use crate::blocks::channel_model::rng;
+use crate::blocks::ChannelSource;
+use crate::futures::channel::mpsc;
+use crate::futures::channel::mpsc::Sender;
+use crate::futures::SinkExt;
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Transmit end of a [Loopback](LoopbackBuilder) channel.
+pub struct LoopbackSink {
+    sender: Option<Sender<Box<[Complex32]>>>,
+    delay: usize,
+    step: f64,
+    mu: f64,
+    prev: Option<Complex32>,
+    noise_std: Option<f32>,
+    rng: StdRng,
+}
+
+impl LoopbackSink {
+    fn new(
+        sender: Sender<Box<[Complex32]>>,
+        delay: usize,
+        ratio: f64,
+        snr_db: Option<f32>,
+        seed: Option<u64>,
+    ) -> Block {
+        assert!(ratio > 0.0, "resampling ratio has to be positive");
+
+        Block::new(
+            BlockMetaBuilder::new("LoopbackSink").build(),
+            StreamIoBuilder::new().add_input::<Complex32>("in").build(),
+            MessageIoBuilder::new().build(),
+            LoopbackSink {
+                sender: Some(sender),
+                delay,
+                step: 1.0 / ratio,
+                mu: 0.0,
+                prev: None,
+                noise_std: snr_db.map(|s| 10.0f32.powf(-s / 20.0)),
+                rng: rng(seed),
+            },
+        )
+    }
+
+    /// Resample with linear interpolation.
+    fn resample(&mut self, input: &[Complex32], out: &mut Vec<Complex32>) {
+        for x in input.iter() {
+            if let Some(p) = self.prev {
+                while self.mu < 1.0 {
+                    out.push(p + (x - p) * self.mu as f32);
+                    self.mu += self.step;
+                }
+                self.mu -= 1.0;
+            }
+            self.prev = Some(*x);
+        }
+    }
+
+    async fn send(&mut self, mut samples: Vec<Complex32>) {
+        if samples.is_empty() {
+            return;
+        }
+        if let Some(std) = self.noise_std {
+            for s in samples.iter_mut() {
+                *s += complex_gaussian(&mut self.rng) * std;
+            }
+        }
+        if let Some(sender) = self.sender.as_mut() {
+            if sender.send(samples.into_boxed_slice()).await.is_err() {
+                debug!("receive end of loopback channel was closed");
+                self.sender = None;
+            }
+        }
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for LoopbackSink {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex32>();
+        let n = i.len();
+
+        let mut out = vec![Complex32::new(0.0, 0.0); std::mem::take(&mut self.delay)];
+        if self.step == 1.0 {
+            out.extend_from_slice(i);
+        } else {
+            self.resample(i, &mut out);
+        }
+        sio.input(0).consume(n);
+
+        let finished = sio.input(0).finished();
+        if finished && self.mu.abs() < 1e-9 {
+            // the last input sample is also an output sample
+            if let Some(p) = self.prev.take() {
+                out.push(p);
+            }
+        }
+
+        self.send(out).await;
+
+        if finished {
+            // closes the channel
+            self.sender = None;
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// In-memory channel to connect a transmitter and a receiver without hardware.
+///
+/// Returns a pair of blocks: a sink that takes the transmitted samples and a
+/// [ChannelSource](crate::blocks::ChannelSource) that outputs the received samples. The blocks
+/// can be part of the same flowgraph or of different flowgraphs. The channel applies, in this
+/// order:
+/// - resampling with linear interpolation, yielding `ratio` output samples per input sample
+///   (e.g., `1.0 + 1e-4` for a receiver that runs 100 ppm fast),
+/// - a delay of the given number of (zero) samples,
+/// - AWGN with the given SNR in dB, relative to unit signal power.
+///
+/// By default, the channel is ideal, i.e., it passes the samples unmodified. The source finishes,
+/// once the sink finished and all samples are received.
+///
+/// # Inputs (sink)
+///
+/// `in`: Transmitted samples (Complex32)
+///
+/// # Outputs (source)
+///
+/// `out`: Received samples (Complex32)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::LoopbackBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let (tx, rx) = LoopbackBuilder::new()
+///     .delay(100)
+///     .snr(20.0)
+///     .resample(1.0 + 1e-4)
+///     .seed(42)
+///     .build();
+/// let tx = fg.add_block(tx);
+/// let rx = fg.add_block(rx);
+/// ```
+pub struct LoopbackBuilder {
+    delay: usize,
+    ratio: f64,
+    snr_db: Option<f32>,
+    seed: Option<u64>,
+    capacity: usize,
+}
+
+impl LoopbackBuilder {
+    pub fn new() -> LoopbackBuilder {
+        LoopbackBuilder {
+            delay: 0,
+            ratio: 1.0,
+            snr_db: None,
+            seed: None,
+            capacity: 16,
+        }
+    }
+
+    /// Delay in samples.
+    #[must_use]
+    pub fn delay(mut self, delay: usize) -> LoopbackBuilder {
+        self.delay = delay;
+        self
+    }
+
+    /// Add AWGN with the given SNR in dB.
+    #[must_use]
+    pub fn snr(mut self, snr_db: f32) -> LoopbackBuilder {
+        self.snr_db = Some(snr_db);
+        self
+    }
+
+    /// Number of output samples per input sample.
+    #[must_use]
+    pub fn resample(mut self, ratio: f64) -> LoopbackBuilder {
+        self.ratio = ratio;
+        self
+    }
+
+    /// Seed the noise generator for reproducible results.
+    #[must_use]
+    pub fn seed(mut self, seed: u64) -> LoopbackBuilder {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Number of chunks of samples that can be in flight, before the sink blocks (default: 16).
+    #[must_use]
+    pub fn capacity(mut self, capacity: usize) -> LoopbackBuilder {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Build the sink (transmit end) and source (receive end) of the channel.
+    pub fn build(self) -> (Block, Block) {
+        let (tx, rx) = mpsc::channel(self.capacity);
+        (
+            LoopbackSink::new(tx, self.delay, self.ratio, self.snr_db, self.seed),
+            ChannelSource::<Complex32>::new(rx),
+        )
+    }
+}
+
+impl Default for LoopbackBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,824 @@
+use futures::FutureExt;
+use rustfft::num_complex::Complex32;
+use rustfft::{Fft, FftPlanner};
+use std::collections::VecDeque;
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+use crate::anyhow::Result;
+use crate::blocks::Crc;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Symbol duration above which low data rate optimization is enabled.
+const LDRO_SYMBOL_DURATION: f64 = 16e-3;
+/// Number of consecutive preamble symbols to detect a frame.
+const DETECT_SYMBOLS: usize = 4;
+/// Minimum ratio of the energy in the bin of a preamble symbol to the average energy per bin.
+const DETECT_RATIO: f32 = 8.0;
+/// Maximum number of preamble symbols after detection, before the search is restarted.
+const MAX_PREAMBLE_LEN: usize = 64;
+/// Symbols of the header block.
+const HEADER_SYMBOLS: usize = 8;
+/// Nibbles of the explicit header.
+const HEADER_NIBBLES: usize = 5;
+
+/// LoRa parameters, shared by transmitter and receiver.
+#[derive(Debug, Clone, Copy)]
+struct Params {
+    sf: usize,
+    /// Code rate 4/(4 + cr).
+    cr: usize,
+    crc: bool,
+    sync_word: u8,
+    ldro: bool,
+    /// Samples per chip.
+    os: usize,
+}
+
+impl Params {
+    fn new(sf: usize, bandwidth: f64, sample_rate: f64) -> Params {
+        assert!(
+            (7..=12).contains(&sf),
+            "spreading factor has to be between 7 and 12"
+        );
+        let os = (sample_rate / bandwidth).round() as usize;
+        assert!(
+            os >= 1 && (os as f64 * bandwidth - sample_rate).abs() < 1e-6 * sample_rate,
+            "sample rate has to be an integer multiple of the bandwidth"
+        );
+        Params {
+            sf,
+            cr: 1,
+            crc: true,
+            sync_word: 0x12,
+            ldro: (1 << sf) as f64 / bandwidth > LDRO_SYMBOL_DURATION,
+            os,
+        }
+    }
+
+    /// Chips per symbol.
+    fn n(&self) -> usize {
+        1 << self.sf
+    }
+
+    /// Rows of the interleaver, i.e., bits per symbol, of the payload blocks.
+    fn rows(&self) -> usize {
+        if self.ldro {
+            self.sf - 2
+        } else {
+            self.sf
+        }
+    }
+
+    /// Symbols of the two sync word upchirps.
+    fn sync_symbols(&self) -> [usize; 2] {
+        [
+            8 * (self.sync_word >> 4) as usize,
+            8 * (self.sync_word & 0x0f) as usize,
+        ]
+    }
+}
+
+fn set_sync_word(params: &mut Params, sync_word: u8) {
+    assert!(
+        sync_word >> 4 != 0 && sync_word & 0x0f != 0,
+        "both nibbles of the sync word have to be nonzero"
+    );
+    params.sync_word = sync_word;
+}
+
+/// Whitening sequence of the payload.
+fn whitening() -> impl Iterator<Item = u8> {
+    std::iter::successors(Some(0xffu8), |w| {
+        Some(w << 1 | ((w >> 7) ^ (w >> 5) ^ (w >> 4) ^ (w >> 3)) & 1)
+    })
+}
+
+/// Five-bit checksum of the first three header nibbles.
+fn header_checksum(n: &[u8]) -> u8 {
+    let b = |i: usize, k: usize| (n[i] >> k) & 1;
+    let c4 = b(0, 3) ^ b(0, 2) ^ b(0, 1) ^ b(0, 0);
+    let c3 = b(0, 3) ^ b(1, 3) ^ b(1, 2) ^ b(1, 1) ^ b(2, 0);
+    let c2 = b(0, 2) ^ b(1, 3) ^ b(1, 0) ^ b(2, 3) ^ b(2, 1);
+    let c1 = b(0, 1) ^ b(1, 2) ^ b(1, 0) ^ b(2, 2) ^ b(2, 1) ^ b(2, 0);
+    let c0 = b(0, 0) ^ b(1, 1) ^ b(2, 3) ^ b(2, 2) ^ b(2, 1) ^ b(2, 0);
+    c4 << 4 | c3 << 3 | c2 << 2 | c1 << 1 | c0
+}
+
+/// CRC of the payload, i.e., CRC-16/XMODEM of all but the last two bytes, xored with the last
+/// two bytes.
+fn payload_crc(payload: &[u8]) -> u16 {
+    let n = payload.len().saturating_sub(2);
+    let crc = Crc::new(16, 0x1021, 0x0000, false, false, 0x0000).checksum(&payload[..n]) as u16;
+    crc ^ payload[n..]
+        .iter()
+        .fold(0u16, |acc, b| acc << 8 | *b as u16)
+}
+
+/// Hamming encode a nibble to a codeword of `4 + cr` bits.
+fn hamming_encode(nibble: u8, cr: usize) -> u8 {
+    let d = |i: usize| (nibble >> i) & 1;
+    if cr == 1 {
+        d(0) << 4 | d(1) << 3 | d(2) << 2 | d(3) << 1 | (d(0) ^ d(1) ^ d(2) ^ d(3))
+    } else {
+        let p0 = d(0) ^ d(1) ^ d(2);
+        let p1 = d(1) ^ d(2) ^ d(3);
+        let p2 = d(0) ^ d(1) ^ d(3);
+        let p3 = d(0) ^ d(2) ^ d(3);
+        (d(0) << 7 | d(1) << 6 | d(2) << 5 | d(3) << 4 | p0 << 3 | p1 << 2 | p2 << 1 | p3)
+            >> (4 - cr)
+    }
+}
+
+/// Decode a codeword to the nibble with the closest codeword.
+fn hamming_decode(cw: u8, cr: usize) -> u8 {
+    (0..16)
+        .min_by_key(|n| (hamming_encode(*n, cr) ^ cw).count_ones())
+        .unwrap()
+}
+
+/// Row of the codeword that holds bit `j` of symbol `i` in the diagonal interleaver.
+fn row(i: usize, j: usize, rows: usize) -> usize {
+    (i + 2 * rows - j - 1) % rows
+}
+
+/// Diagonally interleave `rows` codewords of `cw_len` bits to `cw_len` symbols of `rows` bits.
+fn interleave(codewords: &[u8], cw_len: usize, rows: usize) -> Vec<usize> {
+    (0..cw_len)
+        .map(|i| {
+            (0..rows).fold(0, |acc, j| {
+                acc << 1 | ((codewords[row(i, j, rows)] >> (cw_len - 1 - i)) & 1) as usize
+            })
+        })
+        .collect()
+}
+
+fn deinterleave(symbols: &[usize], cw_len: usize, rows: usize) -> Vec<u8> {
+    let mut codewords = vec![0u8; rows];
+    for (i, s) in symbols.iter().enumerate() {
+        for j in 0..rows {
+            let bit = ((s >> (rows - 1 - j)) & 1) as u8;
+            codewords[row(i, j, rows)] |= bit << (cw_len - 1 - i);
+        }
+    }
+    codewords
+}
+
+/// Map a value to the symbol of a chirp. Symbols of reduced rate blocks only use every fourth
+/// bin.
+fn map(value: usize, reduced: bool, n: usize) -> usize {
+    let mut v = value;
+    let mut g = value >> 1;
+    while g > 0 {
+        v ^= g;
+        g >>= 1;
+    }
+    if reduced {
+        v <<= 2;
+    }
+    (v + 1) % n
+}
+
+/// Map a received bin back to a value.
+fn demap(bin: usize, reduced: bool, n: usize) -> usize {
+    let v = if reduced {
+        ((bin + n + 1) / 4) % (n / 4)
+    } else {
+        (bin + n - 1) % n
+    };
+    v ^ (v >> 1)
+}
+
+/// Encode a payload to the symbols of a frame, following the preamble and sync word.
+fn encode(p: &Params, payload: &[u8]) -> Vec<usize> {
+    let len = payload.len() as u8;
+    let mut nibbles = vec![len >> 4, len & 0x0f, (p.cr as u8) << 1 | p.crc as u8];
+    let c = header_checksum(&nibbles);
+    nibbles.push(c >> 4);
+    nibbles.push(c & 0x0f);
+    for (b, w) in payload.iter().zip(whitening()) {
+        nibbles.push((b ^ w) & 0x0f);
+        nibbles.push((b ^ w) >> 4);
+    }
+    if p.crc {
+        let crc = payload_crc(payload);
+        nibbles.extend((0..4).map(|i| ((crc >> (4 * i)) & 0x0f) as u8));
+    }
+
+    let mut symbols = Vec::new();
+    let mut rest = &nibbles[..];
+    let mut header = true;
+    while !rest.is_empty() {
+        // the header block is sent with code rate 4/8 and reduced rate
+        let (rows, cr, reduced) = if header {
+            (p.sf - 2, 4, true)
+        } else {
+            (p.rows(), p.cr, p.ldro)
+        };
+        let m = std::cmp::min(rows, rest.len());
+        let mut codewords: Vec<u8> = rest[..m].iter().map(|x| hamming_encode(*x, cr)).collect();
+        codewords.resize(rows, 0);
+        rest = &rest[m..];
+        symbols.extend(
+            interleave(&codewords, 4 + cr, rows)
+                .into_iter()
+                .map(|v| map(v, reduced, p.n())),
+        );
+        header = false;
+    }
+    symbols
+}
+
+/// Chirp of `symbol` with `os` samples per chip, sweeping the band upwards or downwards.
+fn chirp(n: usize, os: usize, symbol: usize, up: bool) -> Vec<Complex32> {
+    let mut phase = 0.0f64;
+    (0..n * os)
+        .map(|i| {
+            let t = i as f64 / os as f64;
+            let f = ((symbol as f64 + t) % n as f64) / n as f64 - 0.5;
+            let f = if up { f } else { -f };
+            let s = Complex32::from_polar(1.0, phase as f32);
+            phase = (phase + 2.0 * PI * f / os as f64) % (2.0 * PI);
+            s
+        })
+        .collect()
+}
+
+/// LoRa transmitter.
+pub struct LoraTx {
+    params: Params,
+    preamble_len: usize,
+    frames: VecDeque<Vec<Complex32>>,
+    /// Samples of the first queued frame that were already streamed.
+    pos: usize,
+}
+
+impl LoraTx {
+    pub fn new(
+        spreading_factor: usize,
+        bandwidth: f64,
+        sample_rate: f64,
+        code_rate: usize,
+        crc: bool,
+        sync_word: u8,
+        preamble_len: usize,
+    ) -> Block {
+        assert!(
+            (1..=4).contains(&code_rate),
+            "code rate has to be between 1 (4/5) and 4 (4/8)"
+        );
+        assert!(preamble_len >= 6, "preamble has to be at least 6 symbols");
+        let mut params = Params::new(spreading_factor, bandwidth, sample_rate);
+        params.cr = code_rate;
+        params.crc = crc;
+        set_sync_word(&mut params, sync_word);
+
+        Block::new(
+            BlockMetaBuilder::new("LoraTx").build(),
+            StreamIoBuilder::new()
+                .add_output::<Complex32>("out")
+                .build(),
+            MessageIoBuilder::<Self>::new()
+                .add_input(
+                    "in",
+                    |block: &mut LoraTx,
+                     _mio: &mut MessageIo<LoraTx>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            match p {
+                                Pmt::Blob(v) if (1..=255).contains(&v.len()) => {
+                                    let frame = block.modulate(&v);
+                                    block.frames.push_back(frame);
+                                }
+                                Pmt::Blob(v) => {
+                                    warn!("LoraTx: invalid payload length {}", v.len())
+                                }
+                                _ => warn!("LoraTx: received wrong PMT type. {:?}", p),
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .build(),
+            LoraTx {
+                params,
+                preamble_len,
+                frames: VecDeque::new(),
+                pos: 0,
+            },
+        )
+    }
+
+    fn modulate(&self, payload: &[u8]) -> Vec<Complex32> {
+        let p = &self.params;
+        let (n, os) = (p.n(), p.os);
+
+        let mut frame = Vec::new();
+        for _ in 0..self.preamble_len {
+            frame.extend(chirp(n, os, 0, true));
+        }
+        for s in p.sync_symbols() {
+            frame.extend(chirp(n, os, s, true));
+        }
+        let down = chirp(n, os, 0, false);
+        frame.extend_from_slice(&down);
+        frame.extend_from_slice(&down);
+        frame.extend_from_slice(&down[..n * os / 4]);
+        for s in encode(p, payload) {
+            frame.extend(chirp(n, os, s, true));
+        }
+        frame
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for LoraTx {
+    async fn work(
+        &mut self,
+        _io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let o = sio.output(0).slice::<Complex32>();
+
+        let mut n = 0;
+        while n < o.len() {
+            let frame = match self.frames.front() {
+                Some(frame) => frame,
+                None => break,
+            };
+            let m = std::cmp::min(frame.len() - self.pos, o.len() - n);
+            o[n..n + m].copy_from_slice(&frame[self.pos..self.pos + m]);
+            n += m;
+            self.pos += m;
+            if self.pos == frame.len() {
+                self.frames.pop_front();
+                self.pos = 0;
+            }
+        }
+        sio.output(0).produce(n);
+
+        Ok(())
+    }
+}
+
+/// LoRa transmitter.
+///
+/// Chirp spread spectrum modulator for LoRa frames with explicit header:
+/// - payloads are whitened, a CRC-16 is appended (default), and the header with payload length,
+///   code rate, and CRC flag is prepended,
+/// - nibbles are Hamming encoded with code rate 4/5 (default) to 4/8, where the header block
+///   always uses 4/8,
+/// - codewords are diagonally interleaved and Gray mapped to symbols of `spreading_factor` bits,
+///   or two bits less for the header block and with low data rate optimization, which is enabled
+///   for symbols longer than 16 ms,
+/// - the frame starts with a preamble of upchirps (default 8), two sync word upchirps (default
+///   sync word 0x12, i.e., private networks), and 2.25 downchirps.
+///
+/// The sample rate has to be an integer multiple of the bandwidth. Samples are only produced
+/// while frames are sent, i.e., the output is a stream of bursts.
+///
+/// # Outputs
+///
+/// `out`: Baseband samples (Complex32)
+///
+/// **Message**: `in`: Payloads with 1 to 255 bytes ([Pmt::Blob])
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::LoraTxBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // SF7, 125 kHz bandwidth, sampled at 1 MHz
+/// let tx = fg.add_block(LoraTxBuilder::new(7, 125e3, 1e6).code_rate(4).build());
+/// ```
+pub struct LoraTxBuilder {
+    spreading_factor: usize,
+    bandwidth: f64,
+    sample_rate: f64,
+    code_rate: usize,
+    crc: bool,
+    sync_word: u8,
+    preamble_len: usize,
+}
+
+impl LoraTxBuilder {
+    pub fn new(spreading_factor: usize, bandwidth: f64, sample_rate: f64) -> LoraTxBuilder {
+        LoraTxBuilder {
+            spreading_factor,
+            bandwidth,
+            sample_rate,
+            code_rate: 1,
+            crc: true,
+            sync_word: 0x12,
+            preamble_len: 8,
+        }
+    }
+
+    /// Code rate 4/(4 + `code_rate`), between 1 (4/5) and 4 (4/8).
+    #[must_use]
+    pub fn code_rate(mut self, code_rate: usize) -> LoraTxBuilder {
+        self.code_rate = code_rate;
+        self
+    }
+
+    /// Enable or disable the payload CRC.
+    #[must_use]
+    pub fn crc(mut self, crc: bool) -> LoraTxBuilder {
+        self.crc = crc;
+        self
+    }
+
+    /// Sync word, e.g., 0x34 for LoRaWAN.
+    #[must_use]
+    pub fn sync_word(mut self, sync_word: u8) -> LoraTxBuilder {
+        self.sync_word = sync_word;
+        self
+    }
+
+    /// Number of preamble upchirps.
+    #[must_use]
+    pub fn preamble_len(mut self, preamble_len: usize) -> LoraTxBuilder {
+        self.preamble_len = preamble_len;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        LoraTx::new(
+            self.spreading_factor,
+            self.bandwidth,
+            self.sample_rate,
+            self.code_rate,
+            self.crc,
+            self.sync_word,
+            self.preamble_len,
+        )
+    }
+}
+
+enum State {
+    /// Search for consecutive preamble symbols in the same bin.
+    Detect { bin: usize, count: usize },
+    /// Aligned to the preamble, waiting for the first sync word symbol.
+    Sync { count: usize },
+    /// Check the second sync word symbol and estimate the frequency offset from the downchirps.
+    Sync2,
+    /// Decode the header block, given the frequency offset in bins.
+    Header { cfo: usize },
+    /// Decode the payload blocks.
+    Payload {
+        cfo: usize,
+        len: usize,
+        cr: usize,
+        crc: bool,
+        /// Payload nibbles of the header block.
+        nibbles: Vec<u8>,
+    },
+}
+
+/// LoRa receiver.
+pub struct LoraRx {
+    params: Params,
+    fft: Arc<dyn Fft<f32>>,
+    upchirp: Vec<Complex32>,
+    scratch: Vec<Complex32>,
+    /// Sum of the samples of the current chip.
+    acc: Complex32,
+    acc_n: usize,
+    /// Received chips.
+    buf: Vec<Complex32>,
+    pos: usize,
+    state: State,
+}
+
+impl LoraRx {
+    pub fn new(spreading_factor: usize, bandwidth: f64, sample_rate: f64, sync_word: u8) -> Block {
+        let mut params = Params::new(spreading_factor, bandwidth, sample_rate);
+        set_sync_word(&mut params, sync_word);
+        let n = params.n();
+
+        Block::new(
+            BlockMetaBuilder::new("LoraRx").build(),
+            StreamIoBuilder::new().add_input::<Complex32>("in").build(),
+            MessageIoBuilder::<Self>::new().add_output("out").build(),
+            LoraRx {
+                params,
+                fft: FftPlanner::new().plan_fft_forward(n),
+                upchirp: chirp(n, 1, 0, true),
+                scratch: vec![Complex32::new(0.0, 0.0); n],
+                acc: Complex32::new(0.0, 0.0),
+                acc_n: 0,
+                buf: Vec::new(),
+                pos: 0,
+                state: State::Detect { bin: 0, count: 0 },
+            },
+        )
+    }
+
+    /// Dechirp the symbol at `start` and return the bin with the most energy and its ratio to the
+    /// average energy per bin. Upchirps are dechirped with a downchirp and vice versa.
+    fn bin(&mut self, start: usize, up: bool) -> (usize, f32) {
+        for (i, s) in self.scratch.iter_mut().enumerate() {
+            let c = self.upchirp[i];
+            *s = self.buf[start + i] * if up { c.conj() } else { c };
+        }
+        self.fft.process(&mut self.scratch);
+        let n = self.scratch.len();
+        let (bin, peak) = self
+            .scratch
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (i, s.norm_sqr()))
+            .fold((0, -1.0), |m, x| if x.1 > m.1 { x } else { m });
+        let mean = self.scratch.iter().map(|s| s.norm_sqr()).sum::<f32>() / n as f32;
+        (bin, if mean > 0.0 { peak / mean } else { 0.0 })
+    }
+
+    /// Values of `symbols` symbols at the current position, corrected for the frequency offset.
+    fn values(&mut self, symbols: usize, cfo: usize, reduced: bool) -> Vec<usize> {
+        let n = self.params.n();
+        (0..symbols)
+            .map(|i| {
+                let (bin, _) = self.bin(self.pos + i * n, true);
+                demap((bin + n - cfo) % n, reduced, n)
+            })
+            .collect()
+    }
+
+    /// Number of chips, starting at the current position, that are required for the next step.
+    fn required(&self) -> usize {
+        let n = self.params.n();
+        match &self.state {
+            State::Detect { .. } | State::Sync { .. } => n,
+            State::Sync2 => 2 * n,
+            State::Header { .. } => HEADER_SYMBOLS * n,
+            State::Payload {
+                len,
+                cr,
+                crc,
+                nibbles,
+                ..
+            } => self.payload_symbols(*len, *cr, *crc, nibbles.len()) * n,
+        }
+    }
+
+    /// Number of symbols of the payload blocks, given the payload nibbles of the header block.
+    fn payload_symbols(&self, len: usize, cr: usize, crc: bool, received: usize) -> usize {
+        let missing = (2 * len + 4 * crc as usize).saturating_sub(received);
+        let rows = self.params.rows();
+        (missing + rows - 1) / rows * (4 + cr)
+    }
+
+    fn step(&mut self, frames: &mut Vec<Pmt>) {
+        let n = self.params.n();
+        let near = |a: usize, b: usize| {
+            let d = (a + n - b) % n;
+            d <= 1 || d == n - 1
+        };
+        let signed = |b: usize| {
+            if b > n / 2 {
+                b as isize - n as isize
+            } else {
+                b as isize
+            }
+        };
+
+        let state = std::mem::replace(&mut self.state, State::Detect { bin: 0, count: 0 });
+        self.state = match state {
+            State::Detect { bin, count } => {
+                let (b, ratio) = self.bin(self.pos, true);
+                self.pos += n;
+                let count = if ratio < DETECT_RATIO {
+                    0
+                } else if near(b, bin) {
+                    count + 1
+                } else {
+                    1
+                };
+                if count >= DETECT_SYMBOLS {
+                    // align the symbol boundaries, assuming there is no frequency offset
+                    self.pos -= b;
+                    State::Sync { count: 0 }
+                } else {
+                    State::Detect { bin: b, count }
+                }
+            }
+            State::Sync { count } => {
+                let (b, _) = self.bin(self.pos, true);
+                self.pos += n;
+                if near(b, 0) && count < MAX_PREAMBLE_LEN {
+                    // refine the alignment with a complete preamble symbol
+                    self.pos = (self.pos as isize - signed(b)) as usize;
+                    State::Sync { count: count + 1 }
+                } else if near(b, self.params.sync_symbols()[0]) {
+                    State::Sync2
+                } else {
+                    State::Detect { bin: b, count: 1 }
+                }
+            }
+            State::Sync2 => {
+                let (b, _) = self.bin(self.pos, true);
+                self.pos += n;
+                if near(b, self.params.sync_symbols()[1]) {
+                    // with aligned upchirps, the timing error cancels the frequency offset, which
+                    // doubles the offset of the downchirps
+                    let cfo = signed(self.bin(self.pos, false).0) / 2;
+                    self.pos = (self.pos as isize + 2 * n as isize + n as isize / 4 + cfo) as usize;
+                    State::Header {
+                        cfo: (cfo + n as isize) as usize % n,
+                    }
+                } else {
+                    State::Detect { bin: b, count: 1 }
+                }
+            }
+            State::Header { cfo } => {
+                let rows = self.params.sf - 2;
+                let values = self.values(HEADER_SYMBOLS, cfo, true);
+                self.pos += HEADER_SYMBOLS * n;
+                let nibbles: Vec<u8> = deinterleave(&values, 8, rows)
+                    .into_iter()
+                    .map(|cw| hamming_decode(cw, 4))
+                    .collect();
+                let len = (nibbles[0] << 4 | nibbles[1]) as usize;
+                let cr = (nibbles[2] >> 1) as usize;
+                let crc = nibbles[2] & 1 == 1;
+                let checksum = nibbles[3] << 4 | nibbles[4];
+                if header_checksum(&nibbles) == checksum && (1..=4).contains(&cr) && len > 0 {
+                    State::Payload {
+                        cfo,
+                        len,
+                        cr,
+                        crc,
+                        nibbles: nibbles[HEADER_NIBBLES..].to_vec(),
+                    }
+                } else {
+                    debug!("LoraRx: invalid header");
+                    State::Detect { bin: 0, count: 0 }
+                }
+            }
+            State::Payload {
+                cfo,
+                len,
+                cr,
+                crc,
+                mut nibbles,
+            } => {
+                let rows = self.params.rows();
+                let symbols = self.payload_symbols(len, cr, crc, nibbles.len());
+                let values = self.values(symbols, cfo, self.params.ldro);
+                self.pos += symbols * n;
+                for block in values.chunks(4 + cr) {
+                    nibbles.extend(
+                        deinterleave(block, 4 + cr, rows)
+                            .into_iter()
+                            .map(|cw| hamming_decode(cw, cr)),
+                    );
+                }
+
+                let payload: Vec<u8> = nibbles[..2 * len]
+                    .chunks(2)
+                    .zip(whitening())
+                    .map(|(c, w)| (c[0] | c[1] << 4) ^ w)
+                    .collect();
+                let valid = !crc || {
+                    let c = payload_crc(&payload);
+                    (0..4).all(|i| nibbles[2 * len + i] == ((c >> (4 * i)) & 0x0f) as u8)
+                };
+                if valid {
+                    frames.push(Pmt::Blob(payload));
+                } else {
+                    debug!("LoraRx: wrong CRC");
+                }
+                State::Detect { bin: 0, count: 0 }
+            }
+        };
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for LoraRx {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex32>();
+
+        // integrate and dump the samples of each chip
+        let os = self.params.os;
+        for x in i.iter() {
+            self.acc += x;
+            self.acc_n += 1;
+            if self.acc_n == os {
+                self.buf.push(self.acc / os as f32);
+                self.acc = Complex32::new(0.0, 0.0);
+                self.acc_n = 0;
+            }
+        }
+        sio.input(0).consume(i.len());
+
+        let mut frames = Vec::new();
+        while self.buf.len() >= self.pos + self.required() {
+            self.step(&mut frames);
+        }
+        let m = std::cmp::min(self.pos, self.buf.len());
+        self.buf.drain(..m);
+        self.pos -= m;
+
+        if sio.input(0).finished() {
+            io.finished = true;
+        }
+
+        for f in frames {
+            mio.post(0, f).await;
+        }
+
+        Ok(())
+    }
+}
+
+/// LoRa receiver.
+///
+/// Chirp spread spectrum demodulator for LoRa frames with explicit header, the counterpart of
+/// the [LoraTxBuilder]:
+/// - the samples of each chip are averaged, i.e., the sample rate has to be an integer multiple
+///   of the bandwidth,
+/// - frames are detected by consecutive preamble upchirps and the sync word (default 0x12),
+/// - symbol timing and integer frequency offsets of up to a quarter of the bandwidth are
+///   estimated from the upchirps of the preamble and the downchirps,
+/// - symbols are dechirped and demodulated with an FFT, Gray demapped, deinterleaved, and Hamming
+///   decoded, correcting single bit errors for code rates 4/7 and 4/8,
+/// - the header is checked and configures payload length, code rate, and CRC,
+/// - the payload is dewhitened and posted if the CRC (if present) is correct.
+///
+/// Low data rate optimization is enabled for symbols longer than 16 ms.
+///
+/// # Inputs
+///
+/// `in`: Baseband samples (Complex32)
+///
+/// # Outputs
+///
+/// **Message**: `out`: Payloads ([Pmt::Blob])
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::LoraRxBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // SF7, 125 kHz bandwidth, sampled at 1 MHz
+/// let rx = fg.add_block(LoraRxBuilder::new(7, 125e3, 1e6).build());
+/// ```
+pub struct LoraRxBuilder {
+    spreading_factor: usize,
+    bandwidth: f64,
+    sample_rate: f64,
+    sync_word: u8,
+}
+
+impl LoraRxBuilder {
+    pub fn new(spreading_factor: usize, bandwidth: f64, sample_rate: f64) -> LoraRxBuilder {
+        LoraRxBuilder {
+            spreading_factor,
+            bandwidth,
+            sample_rate,
+            sync_word: 0x12,
+        }
+    }
+
+    /// Sync word, e.g., 0x34 for LoRaWAN.
+    #[must_use]
+    pub fn sync_word(mut self, sync_word: u8) -> LoraRxBuilder {
+        self.sync_word = sync_word;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        LoraRx::new(
+            self.spreading_factor,
+            self.bandwidth,
+            self.sample_rate,
+            self.sync_word,
+        )
+    }
+}
@@ -0,0 +1,88 @@
+use futures::FutureExt;
+
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIoBuilder;
+
+/// Forward messages while the gate is open.
+///
+/// The gate is opened by a non-zero and closed by a zero [Pmt::U32] or [Pmt::U64] on the `gate`
+/// port, which returns the current state (`1` for open). Messages received while the gate is
+/// closed are dropped.
+///
+/// # Inputs
+///
+/// **Message**: `in`: Messages
+///
+/// **Message**: `gate`: Open or close the gate
+///
+/// # Outputs
+///
+/// **Message**: `out`: Messages received while the gate is open
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::MessageGate;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let gate = fg.add_block(MessageGate::new(false));
+/// ```
+pub struct MessageGate {
+    open: bool,
+}
+
+impl MessageGate {
+    pub fn new(open: bool) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("MessageGate").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::<Self>::new()
+                .add_output("out")
+                .add_input(
+                    "in",
+                    |block: &mut MessageGate,
+                     mio: &mut MessageIo<MessageGate>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            if block.open {
+                                mio.post(0, p).await;
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .add_input(
+                    "gate",
+                    |block: &mut MessageGate,
+                     _mio: &mut MessageIo<MessageGate>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            match p {
+                                Pmt::U32(v) => block.open = v != 0,
+                                Pmt::U64(v) => block.open = v != 0,
+                                _ => warn!("MessageGate: received wrong PMT type. {:?}", p),
+                            }
+                            Ok(Pmt::U32(block.open as u32))
+                        }
+                        .boxed()
+                    },
+                )
+                .build(),
+            MessageGate { open },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for MessageGate {}
@@ -20,7 +20,7 @@ impl MessageSink {
             BlockMetaBuilder::new("MessageSink").build(),
             StreamIoBuilder::new().build(),
             MessageIoBuilder::new()
-                .add_input("in", Self::in_port)
+                .add_batch_input("in", Self::in_port)
                 .build(),
             MessageSink { n_received: 0 },
         )
@@ -31,9 +31,9 @@ impl MessageSink {
         &mut self,
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
-        _p: Pmt,
+        p: Vec<Pmt>,
     ) -> Result<Pmt> {
-        self.n_received += 1;
+        self.n_received += p.len() as u64;
         Ok(Pmt::U64(self.n_received))
     }
 
@@ -0,0 +1,382 @@
+use async_io::Timer;
+use std::fs::File;
+use std::os::unix::io::AsRawFd;
+use std::time::Duration;
+use std::time::Instant;
+
+use crate::anyhow::{bail, Context, Result};
+use crate::blocks::file_source::Decoder;
+use crate::blocks::FileFormat;
+use crate::blocks::FileSample;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Size of the part of the file that is mapped at a time.
+const WINDOW: usize = 1 << 28;
+
+/// Read-only mapping of a part of a file.
+struct Mapping {
+    ptr: *mut libc::c_void,
+    len: usize,
+    /// Position of the mapping in the file.
+    start: u64,
+}
+
+// the mapping is only used by the block that owns it
+unsafe impl Send for Mapping {}
+
+impl Mapping {
+    fn new(file: &File, start: u64, len: usize) -> Result<Mapping> {
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ,
+                libc::MAP_PRIVATE,
+                file.as_raw_fd(),
+                start as libc::off_t,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            bail!(
+                "MmapSource: mapping {} bytes at {} failed ({})",
+                len,
+                start,
+                std::io::Error::last_os_error()
+            );
+        }
+        // only a hint, the mapping works without it
+        unsafe {
+            libc::madvise(ptr, len, libc::MADV_SEQUENTIAL);
+        }
+        Ok(Mapping { ptr, len, start })
+    }
+
+    fn end(&self) -> u64 {
+        self.start + self.len as u64
+    }
+
+    fn bytes(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.ptr as *const u8, self.len) }
+    }
+}
+
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.ptr, self.len);
+        }
+    }
+}
+
+/// Read samples from a memory-mapped file.
+pub struct MmapSource<T: Send + 'static> {
+    file_name: String,
+    file: Option<File>,
+    map: Option<Mapping>,
+    format: Option<Decoder<T>>,
+    offset: u64,
+    length: Option<u64>,
+    repeat: bool,
+    rate: Option<f64>,
+    chunk: usize,
+    /// Byte range of the file that is played back.
+    start: u64,
+    end: u64,
+    /// Position of the next sample in the file.
+    pos: u64,
+    window: usize,
+    t_init: Instant,
+    n_items: u64,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: Send + 'static> MmapSource<T> {
+    fn create(builder: MmapSourceBuilder<T>) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("MmapSource").blocking().build(),
+            StreamIoBuilder::new().add_output::<T>("out").build(),
+            MessageIoBuilder::new().build(),
+            MmapSource::<T> {
+                file_name: builder.file_name,
+                file: None,
+                map: None,
+                format: builder.format,
+                offset: builder.offset,
+                length: builder.length,
+                repeat: builder.repeat,
+                rate: builder.rate,
+                chunk: builder.chunk,
+                start: 0,
+                end: 0,
+                pos: 0,
+                window: WINDOW,
+                t_init: Instant::now(),
+                n_items: 0,
+                _type: std::marker::PhantomData,
+            },
+        )
+    }
+
+    fn item_size(&self) -> usize {
+        match self.format {
+            Some((f, _)) => f.item_size(),
+            None => std::mem::size_of::<T>(),
+        }
+    }
+
+    /// Map the window of the file, starting at the page of the current position.
+    fn remap(&mut self) -> Result<()> {
+        self.map = None;
+        let page = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as u64;
+        let start = self.pos / page * page;
+        let len = std::cmp::min(self.window as u64, self.end - start) as usize;
+        self.map = Some(Mapping::new(self.file.as_ref().unwrap(), start, len)?);
+        Ok(())
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: Send + 'static> Kernel for MmapSource<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let item_size = self.item_size();
+        let chunk_bytes = (self.chunk * item_size) as u64;
+
+        if self.end - self.pos < chunk_bytes {
+            // only complete chunks are used, a trailing partial chunk is dropped
+            if self.repeat && self.end - self.start >= chunk_bytes {
+                self.pos = self.start;
+            } else {
+                io.finished = true;
+                return Ok(());
+            }
+        }
+
+        let out = sio.output(0).slice_unchecked::<u8>();
+        let t_size = std::mem::size_of::<T>();
+        let mut n = std::cmp::min(
+            (out.len() / t_size) as u64,
+            (self.end - self.pos) / item_size as u64,
+        );
+
+        if let Some(rate) = self.rate {
+            let allowed = (self.t_init.elapsed().as_secs_f64() * rate) as u64;
+            let allowed = allowed.saturating_sub(self.n_items);
+            if allowed < self.chunk as u64 {
+                let next = (self.n_items + self.chunk as u64) as f64 / rate;
+                let wait = Duration::from_secs_f64(next)
+                    .saturating_sub(self.t_init.elapsed())
+                    .max(Duration::from_micros(100));
+                io.block_on(async move {
+                    Timer::after(wait).await;
+                });
+                return Ok(());
+            }
+            n = std::cmp::min(n, allowed);
+        }
+
+        let n = n / self.chunk as u64 * self.chunk as u64;
+        if n == 0 {
+            return Ok(());
+        }
+
+        let covered = self
+            .map
+            .as_ref()
+            .map(|m| m.start <= self.pos && self.pos + chunk_bytes <= m.end())
+            .unwrap_or(false);
+        if !covered {
+            self.remap()?;
+        }
+        let map = self.map.as_ref().unwrap();
+        let n = std::cmp::min(n, (map.end() - self.pos) / item_size as u64);
+        let n = (n / self.chunk as u64 * self.chunk as u64) as usize;
+
+        let i = (self.pos - map.start) as usize;
+        let bytes = &map.bytes()[i..i + n * item_size];
+        match self.format {
+            Some((format, convert)) => {
+                let o = sio.output(0).slice::<T>();
+                for (b, y) in bytes.chunks_exact(item_size).zip(o.iter_mut()) {
+                    *y = convert(format.decode(b));
+                }
+            }
+            None => out[..bytes.len()].copy_from_slice(bytes),
+        }
+
+        self.pos += (n * item_size) as u64;
+        self.n_items += n as u64;
+        sio.output(0).produce(n);
+        io.call_again = true;
+
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let file = File::open(&self.file_name)
+            .with_context(|| format!("MmapSource: cannot open {}", self.file_name))?;
+        let len = file.metadata()?.len();
+        let item_size = self.item_size() as u64;
+
+        self.start = std::cmp::min(self.offset * item_size, len);
+        let items = (len - self.start) / item_size;
+        let items = self.length.map_or(items, |l| std::cmp::min(l, items));
+        self.end = self.start + items * item_size;
+        self.pos = self.start;
+        // a window has to hold at least one chunk, starting anywhere in a page
+        self.window = std::cmp::max(WINDOW, 2 * self.chunk * item_size as usize + (1 << 16));
+        self.file = Some(file);
+        self.map = None;
+        self.t_init = Instant::now();
+        self.n_items = 0;
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        self.map = None;
+        self.file = None;
+        Ok(())
+    }
+}
+
+/// Read samples from a memory-mapped file.
+///
+/// Instead of copying the file through read calls, the source maps a window of the file into
+/// memory and lets the kernel page it in, which makes it suitable for captures of hundreds of
+/// gigabytes. Like the [FileSourceBuilder](crate::blocks::FileSourceBuilder), it reads samples
+/// in the native format or converts them from a [FileFormat], can skip the first `offset`
+/// samples, read at most `length` samples, and loop over this part of the file.
+///
+/// With a [rate](MmapSourceBuilder::rate), samples are paced to the given sample rate, e.g., to
+/// replay a capture in real time. With a [chunk](MmapSourceBuilder::chunk) size, samples are
+/// only produced in multiples of the chunk, e.g., to keep FFT frames aligned; a trailing partial
+/// chunk is dropped. The output buffer has to hold at least one chunk.
+///
+/// # Inputs
+///
+/// No inputs.
+///
+/// # Outputs
+///
+/// `out`: Output samples
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::FileFormat;
+/// use futuresdr::blocks::MmapSourceBuilder;
+/// use futuresdr::num_complex::Complex32;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // replay a 20 Msps capture in real time, in frames of 2048 samples
+/// let source = fg.add_block(
+///     MmapSourceBuilder::<Complex32>::new("capture.ci16")
+///         .format(FileFormat::Ci16)
+///         .rate(20e6)
+///         .chunk(2048)
+///         .build(),
+/// );
+/// ```
+#[cfg_attr(docsrs, doc(cfg(unix)))]
+pub struct MmapSourceBuilder<T: Send + 'static> {
+    file_name: String,
+    format: Option<Decoder<T>>,
+    offset: u64,
+    length: Option<u64>,
+    repeat: bool,
+    rate: Option<f64>,
+    chunk: usize,
+}
+
+impl<T: Send + 'static> MmapSourceBuilder<T> {
+    pub fn new<S: Into<String>>(file_name: S) -> MmapSourceBuilder<T> {
+        MmapSourceBuilder {
+            file_name: file_name.into(),
+            format: None,
+            offset: 0,
+            length: None,
+            repeat: false,
+            rate: None,
+            chunk: 1,
+        }
+    }
+
+    /// Start over after the end of the file or the given length.
+    #[must_use]
+    pub fn repeat(mut self, repeat: bool) -> MmapSourceBuilder<T> {
+        self.repeat = repeat;
+        self
+    }
+
+    /// Number of samples to skip at the start of the file.
+    #[must_use]
+    pub fn offset(mut self, offset: u64) -> MmapSourceBuilder<T> {
+        self.offset = offset;
+        self
+    }
+
+    /// Maximum number of samples to read (per pass, if repeated).
+    #[must_use]
+    pub fn length(mut self, length: u64) -> MmapSourceBuilder<T> {
+        self.length = Some(length);
+        self
+    }
+
+    /// Limit the output to the given sample rate.
+    #[must_use]
+    pub fn rate(mut self, rate: f64) -> MmapSourceBuilder<T> {
+        assert!(rate > 0.0, "rate has to be positive");
+        self.rate = Some(rate);
+        self
+    }
+
+    /// Only produce multiples of `chunk` samples.
+    #[must_use]
+    pub fn chunk(mut self, chunk: usize) -> MmapSourceBuilder<T> {
+        assert!(chunk > 0, "chunk has to be positive");
+        self.chunk = chunk;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        MmapSource::<T>::create(self)
+    }
+}
+
+impl<T: FileSample> MmapSourceBuilder<T> {
+    /// Sample format of the file.
+    #[must_use]
+    pub fn format(mut self, format: FileFormat) -> MmapSourceBuilder<T> {
+        assert!(
+            T::COMPLEX || !format.is_complex(),
+            "complex formats require a complex stream"
+        );
+        self.format = Some((format, T::from_complex));
+        self
+    }
+}
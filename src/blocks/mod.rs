@@ -15,31 +15,130 @@
 //! ## DSP blocks
 //! | Block | Usage | WebAssembly? |
 //! |---|---|---|
+//! | [Afc](AfcBuilder) | Automatic frequency correction, digitally or by retuning the device. | ✅ |
+//! | [AfskDemod](AfskDemodBuilder) | Non-coherent AFSK demodulator (default: Bell 202). | ✅ |
+//! | [AfskMod](AfskModBuilder) | AFSK modulator (default: Bell 202). | ✅ |
+//! | [AmDemod] | AM envelope demodulator. | ✅ |
+//! | [BlockDeinterleaver] | Block deinterleaver. | ✅ |
+//! | [BlockInterleaver] | Block interleaver with configurable matrix dimensions. | ✅ |
+//! | [BurstToPdu](BurstToPduBuilder) | Cut tagged or gated bursts out of a stream and post them as PDUs. | ✅ |
+//! | [ChannelModel](ChannelModelBuilder) | Channel model with AWGN, frequency offset, timing drift, and multipath. | ✅ |
+//! | [ConstellationDemapper](ConstellationDemapperBuilder) | Hard or soft decisions for a [Constellation], reporting the EVM. | ✅ |
+//! | [ConstellationMapper] | Map symbols to points of a [Constellation]. | ✅ |
+//! | [ConvolutionalDeinterleaver] | Convolutional (Forney) deinterleaver. | ✅ |
+//! | [ConvolutionalInterleaver] | Convolutional (Forney) interleaver. | ✅ |
+//! | [CorrelateAccessCode](CorrelateAccessCodeBuilder) | Tag access codes in a bit stream. | ✅ |
+//! | [CorrelateSync](CorrelateSyncBuilder) | Tag preambles with timing, phase, and amplitude estimates. | ✅ |
+//! | [CostasLoop] | Carrier recovery for BPSK, QPSK, and 8PSK symbols. | ✅ |
+//! | [CtcssSquelch] | Mute audio without CTCSS tone. | ✅ |
+//! | [Deemphasis] | FM de-emphasis filter. | ✅ |
+//! | [DopplerCorrection](DopplerCorrectionBuilder) | Remove a time-varying Doppler shift, optionally retuning a Soapy device. | ✅ |
+//! | [FadingChannel](FadingChannelBuilder) | Flat Rayleigh or Rician fading channel. | ✅ |
 //! | [Fft](Fft) | Compute an FFT. | ✅ |
-//! | [Fir](FirBuilder) | FIR filter and resampler. | ✅ |
+//! | [Fir](FirBuilder) | FIR filter, decimator, and resampler. | ✅ |
+//! | [Fs4Shift] | Shift the spectrum by a quarter of the sample rate. | ✅ |
+//! | [FskDemod](FskDemodBuilder) | Non-coherent M-FSK demodulator with clock recovery. | ✅ |
+//! | [FskMod] | M-FSK modulator. | ✅ |
+//! | [GfskDemod](GfskDemodBuilder) | Non-coherent GFSK/GMSK demodulator with clock recovery. | ✅ |
+//! | [GfskMod](GfskModBuilder) | GFSK/GMSK modulator. | ✅ |
+//! | [Ieee802154Demod] | IEEE 802.15.4 O-QPSK demodulator, from baseband samples to soft chips. | ✅ |
+//! | [Ieee802154Mod] | IEEE 802.15.4 O-QPSK modulator, from PSDUs to baseband samples. | ✅ |
 //! | [Iir](IirBuilder) | IIR filter. | ✅ |
+//! | [LoraRx](LoraRxBuilder) | LoRa receiver, from baseband samples to payloads. | ✅ |
+//! | [LoraTx](LoraTxBuilder) | LoRa transmitter, from payloads to baseband samples. | ✅ |
+//! | [ModeSDemod] | Demodulate Mode S messages, e.g., ADS-B, at 2 Msps. | ✅ |
+//! | [MovingAverage] | Moving average with optional decimation. | ✅ |
+//! | [NbfmRx](NbfmRxBuilder) | Narrowband FM demodulator with optional CTCSS squelch. | ✅ |
+//! | [NbfmTx](NbfmTxBuilder) | Narrowband FM modulator with optional CTCSS tone. | ✅ |
+//! | [PeakDetector](PeakDetectorBuilder) | Tag peaks above a threshold. | ✅ |
+//! | [PfbArbResampler] | Polyphase arbitrary-rate resampler. | ✅ |
+//! | [PowerSquelch](PowerSquelchBuilder) | Power squelch with hysteresis, hold time, and burst tags. | ✅ |
+//! | [Preemphasis] | FM pre-emphasis filter. | ✅ |
+//! | [PskRx](PskRxBuilder) | BPSK/QPSK receiver, from baseband samples to bits. | ✅ |
+//! | [PskTx](PskTxBuilder) | BPSK/QPSK transmitter, from bits to pulse-shaped baseband samples. | ✅ |
+//! | [QuadratureDemod] | Quadrature (frequency) demodulator. | ✅ |
+//! | [Rotator] | Shift the spectrum by a frequency that can be set at runtime. | ✅ |
+//! | [RrcFilter](RrcFilterBuilder) | Root-raised-cosine pulse shaping and matched filter. | ✅ |
+//! | [SinglePoleIir] | Single-pole IIR averaging filter. | ✅ |
+//! | [SsbDemod] | SSB demodulator (Weaver method). | ✅ |
+//! | [SymbolSync](SymbolSyncBuilder) | Polyphase filter bank symbol synchronizer. | ✅ |
+//! | [WbfmRx] | Broadcast FM receiver, from baseband samples to audio. | ✅ |
+//! | [Welch](WelchBuilder) | Estimate the averaged log-power spectrum. | ✅ |
 //!
 //! ## Misc
 //! | Block | Usage | WebAssembly? |
 //! |---|---|---|
+//! | [Add] | Add samples of several streams. | ✅ |
+//! | [AddConst] | Add a constant to each sample. | ✅ |
+//! | [AssertSink](AssertSinkBuilder) | Assert that received samples match expected samples, with tolerance. | ✅ |
+//! | [ComplexToArg] | Argument of complex samples. | ✅ |
+//! | [ComplexToImag] | Imaginary part of complex samples. | ✅ |
+//! | [ComplexToMag] | Magnitude of complex samples. | ✅ |
+//! | [ComplexToMagSquared] | Squared magnitude of complex samples. | ✅ |
+//! | [ComplexToReal] | Real part of complex samples. | ✅ |
 //! | [ConsoleSink] | Log stream data with [log::info!]. | ✅ |
+//! | [Delay] | Delays the stream by a given number of samples. | ✅ |
+//! | [Deinterleave] | Combine interleaved real and imaginary parts to complex samples. | ✅ |
+//! | [FloatToInt] | Convert `f32` samples to integers with scaling and saturation. | ✅ |
 //! | [Head] | Copies only a given number of samples and stops. | ✅ |
+//! | [IntToFloat] | Convert integer samples to `f32` with scaling. | ✅ |
+//! | [Interleave] | Interleave real and imaginary parts of complex samples. | ✅ |
+//! | [KeepMInN](KeepMInNBuilder) | Keep `m` samples out of every `n` samples, optionally aligned to tags. | ✅ |
+//! | [KeepOneInN] | Keep one sample out of every `n` samples. | ✅ |
+//! | [Loopback](LoopbackBuilder) | In-memory channel with delay, AWGN, and resampling between a sink and a source. | ✅ |
+//! | [MockSoapySink](MockSoapySinkBuilder) | Soapy sink for tests that records samples and settings in a [soapy::MockSoapyDevice]. | ❌ |
+//! | [MockSoapySource](MockSoapySourceBuilder) | Soapy source for tests that outputs samples queued in a [soapy::MockSoapyDevice]. | ❌ |
+//! | [Multiply] | Multiply samples of several streams. | ✅ |
+//! | [MultiplyConst] | Multiply each sample with a constant. | ✅ |
 //! | [NullSink] | Drops samples. | ✅ |
 //! | [NullSource] | Generates a stream of zeros. | ✅ |
+//! | [PackBits] | Pack bits to bytes. | ✅ |
+//! | [RateProbe] | Measure the throughput of a stream. | ❌ |
+//! | [RepackBits] | Repack symbols of `k` bits into symbols of `l` bits. | ✅ |
 //! | [Selector] | Forward the input stream with a given index to the output stream with a given index. | ✅ |
+//! | [SignalProbe] | Most recent value and average power of a stream. | ✅ |
+//! | [SkipHead] | Drops a given number of samples and copies the rest. | ✅ |
+//! | [StatisticsSink](StatisticsSinkBuilder) | Running statistics and histogram of a stream. | ✅ |
+//! | [StreamDemux] | Demultiplex a stream into chunks for several outputs. | ✅ |
+//! | [StreamMux] | Multiplex chunks of several input streams. | ✅ |
+//! | [StreamToVector] | Chunk a stream into vectors of `N` samples. | ✅ |
+//! | [Subtract] | Subtract samples of further streams from the first stream. | ✅ |
 //! | [TagDebug] | Drop samples, printing tags. | ✅ |
 //! | [Throttle] | Limit sample rate. | ❌ |
+//! | [UnpackBits] | Unpack bytes to bits. | ✅ |
 //! | [VectorSink] | Store received samples in vector. | ✅ |
-//! | [VectorSource] | Stream samples from vector. | ✅ |
+//! | [VectorSinkWithTags] | Store received samples and their tags in vectors. | ✅ |
+//! | [VectorSource](VectorSourceBuilder) | Stream samples from vector, optionally repeated and tagged. | ✅ |
+//! | [VectorToStream] | Flatten vectors of `N` samples into a stream. | ✅ |
 //!
 //! ## Message Passing
 //! | Block | Usage | WebAssembly? |
 //! |---|---|---|
+//! | [AdsbDecoder] | Decode identification, position, and velocity of ADS-B messages. | ✅ |
+//! | [AprsParser] | Parse APRS position reports from AX.25 frames. | ✅ |
+//! | [Ax25Decoder] | Decode HDLC-framed AX.25 frames from a bit stream. | ✅ |
+//! | [Ax25Encoder](Ax25EncoderBuilder) | HDLC-frame AX.25 frames into NRZI-encoded bits. | ✅ |
+//! | [CrcAppend] | Append a CRC to PDUs. | ✅ |
+//! | [CrcCheck] | Check and remove the CRC of PDUs. | ✅ |
+//! | [Framer](FramerBuilder) | Build bit frames with preamble, sync word, length field, CRC, and whitening from payload PDUs. | ✅ |
+//! | [Ieee802154FrameSync] | Synchronize to IEEE 802.15.4 frames and despread PSDUs from soft chips. | ✅ |
 //! | [MessageBurst] | Output a given number of messages in one burst and terminate. | ✅ |
 //! | [MessageCopy] | Forward messages. | ✅ |
+//! | [MessageGate] | Forward messages while the gate is open. | ✅ |
 //! | [MessagePipe] | Push received messages into a channel. | ✅ |
 //! | [MessageSink] | Black hole for messages. | ✅ |
 //! | [MessageSource](MessageSourceBuilder) | Output the same message periodically. | ✅ |
+//! | [PduDebug] | Print PDUs. | ✅ |
+//! | [PduLengthFilter] | Forward PDUs with a length in a given range. | ✅ |
+//! | [PduToStream] | Stream the samples of PDUs. | ✅ |
+//! | [PduToTaggedStream] | Stream the samples of PDUs, tagging the first sample with the PDU length. | ✅ |
+//! | [PocsagDecoder] | Decode POCSAG pager messages from a bit stream. | ✅ |
+//! | [ReedSolomonDecoder] | Reed-Solomon decode PDUs. | ✅ |
+//! | [ReedSolomonEncoder] | Reed-Solomon encode PDUs. | ✅ |
+//! | [StreamToPdu] | Chunk a stream into PDUs of a fixed length. | ✅ |
+//! | [TaggedStreamToPdu] | Collect length-tagged packets of a stream into PDUs. | ✅ |
+//! | [TelemetrySink] | Report received messages as [telemetry](crate::runtime::telemetry) gauge. | ❌ |
+//! | [WaterfallSink](WaterfallSinkBuilder) | Store power spectra and serve them as compressed waterfall frames. | ✅ |
 //!
 //! ## Performance Evaluation
 //! | Block | Usage | WebAssembly? | Feature |
@@ -53,14 +152,34 @@
 //! | Block | Usage | WebAssembly? |
 //! |---|---|---|
 //! | [BlobToUdp] | Push [Blobs](crate::runtime::Pmt::Blob) into a UDP socket. | ❌ |
+//! | [ChannelSink] | Push samples from a stream connection into a channel. | ✅ |
 //! | [ChannelSource] | Push samples through a channel into a stream connection. | ✅ |
-//! | [FileSink] | Write samples to a file. | ❌ |
-//! | [FileSource] | Read samples from a file. | ❌ |
-//! | [TcpSource] | Reads samples from a TCP socket. | ❌ |
-//! | [TcpSink] | Push samples into a TCP socket. | ❌ |
-//! | [WebsocketSink] | Push samples in a WebSocket. | ❌ |
+//! | [DirectFileSink](DirectFileSinkBuilder) | Write samples to a file with `O_DIRECT` and a deep queue of aligned buffers (Linux only). | ❌ |
+//! | [FileSink](FileSinkBuilder) | Write samples to a file. | ❌ |
+//! | [FileSource](FileSourceBuilder) | Read samples from a file. | ❌ |
+//! | [IcecastSink](IcecastSinkBuilder) | Stream audio as web radio, serving listeners or pushing to an [Icecast](https://icecast.org) server. | ❌ |
+//! | [MmapSource](MmapSourceBuilder) | Read samples from a memory-mapped file, optionally paced and in aligned chunks. | ❌ |
+//! | [NetworkSource](NetworkSourceBuilder) | Receive frames from a TCP or UDP socket as PDUs, split by a pluggable [NetworkFramer]. | ❌ |
+//! | [PcapSink](PcapSinkBuilder) | Write PDUs into a pcapng or pcap file for protocol analysis with Wireshark. | ❌ |
+//! | [PipeSink](PipeSinkBuilder) | Write raw samples to a named pipe or stdout. | ❌ |
+//! | [PipeSource](PipeSourceBuilder) | Read raw samples from a named pipe or stdin. | ❌ |
+//! | [RotatingFileSink](RotatingFileSinkBuilder) | Record into timestamped files, rotated by duration or size, within a disk quota. | ❌ |
+//! | [ShmSink](ShmSinkBuilder) | Write samples into a shared-memory ring buffer for another process on the host (Unix only). | ❌ |
+//! | [ShmSource](ShmSourceBuilder) | Read samples from a shared-memory ring buffer, written by a [ShmSink](ShmSinkBuilder) (Unix only). | ❌ |
+//! | [SigmfSink](SigmfSinkBuilder) | Record samples, metadata, and tags in the [SigMF](https://sigmf.org) format. | ❌ |
+//! | [SigmfSource] | Play back a [SigMF](https://sigmf.org) recording, restoring metadata and tags. | ❌ |
+//! | [TcpSource](TcpSourceBuilder) | Reads samples from a TCP socket, as server or client. | ❌ |
+//! | [TcpSink](TcpSinkBuilder) | Push samples into a TCP socket, as server or client. | ❌ |
+//! | [Vita49Sink](Vita49SinkBuilder) | Send samples and context in [VITA 49.2](https://www.vita.com)/[DIFI](https://dificonsortium.org) UDP packets. | ❌ |
+//! | [Vita49Source](Vita49SourceBuilder) | Receive VITA 49.2/DIFI UDP packets, restoring timestamps and context as tags. | ❌ |
+//! | [WebsocketSink] | Push samples in a WebSocket (requires `web` feature). | ❌ |
+//! | [WebsocketStreamSink](WebsocketStreamSinkBuilder) | Stream framed samples to browsers through a WebSocket of the control port (requires `web` feature). | ❌ |
 //! | [zeromq::PubSink] | Push samples into [ZeroMQ](https://zeromq.org/) socket. | ❌ |
 //! | [zeromq::SubSource] | Read samples from [ZeroMQ](https://zeromq.org/) socket. | ❌ |
+//! | [zeromq::PushSink] | Push samples into a PUSH [ZeroMQ](https://zeromq.org/) socket. | ❌ |
+//! | [zeromq::PullSource] | Read samples from a PULL [ZeroMQ](https://zeromq.org/) socket. | ❌ |
+//! | [zeromq::MsgSink](zeromq::MsgSinkBuilder) | Send messages through a [ZeroMQ](https://zeromq.org/) socket. | ❌ |
+//! | [zeromq::MsgSource](zeromq::MsgSourceBuilder) | Receive messages from a [ZeroMQ](https://zeromq.org/) socket. | ❌ |
 //!
 //! ## SDR Hardware (requires `soapy` feature)
 //! | Block | Usage | WebAssembly? |
@@ -68,11 +187,41 @@
 //! | [SoapySink](SoapySinkBuilder) | Transmit samples with a Soapy SDR device. | ❌ |
 //! | [SoapySource](SoapySourceBuilder) | Receive samples from a Soapy SDR device. | ❌ |
 //!
+//! ## ADALM-Pluto (requires `pluto` feature)
+//! | Block | Usage | WebAssembly? |
+//! |---|---|---|
+//! | [PlutoSink](pluto::PlutoSinkBuilder) | Transmit samples with an ADALM-Pluto, using libiio directly. | ❌ |
+//! | [PlutoSource](pluto::PlutoSourceBuilder) | Receive samples with an ADALM-Pluto, using libiio directly. | ❌ |
+//!
+//! ## Digital RF (requires `digital_rf` feature)
+//! | Block | Usage | WebAssembly? |
+//! |---|---|---|
+//! | [DigitalRfSink](digital_rf::DigitalRfSinkBuilder) | Record samples and metadata in the HDF5-based Digital RF format, with gaps from time tags. | ❌ |
+//!
+//! ## HackRF (requires `hackrf` feature)
+//! | Block | Usage | WebAssembly? |
+//! |---|---|---|
+//! | [HackRfSink](hackrf::HackRfSinkBuilder) | Transmit samples with a HackRF, using libhackrf directly. | ❌ |
+//! | [HackRfSource](hackrf::HackRfSourceBuilder) | Receive samples from a HackRF, optionally sweeping wide frequency ranges. | ❌ |
+//!
+//! ## RTL-SDR (requires `rtlsdr` feature)
+//! | Block | Usage | WebAssembly? |
+//! |---|---|---|
+//! | [RtlSdrSource](rtlsdr::RtlSdrSourceBuilder) | Receive samples from an RTL-SDR, using librtlsdr directly (WebUSB in the browser). | ✅ |
+//!
+//! ## Network Receivers
+//! | Block | Usage | WebAssembly? |
+//! |---|---|---|
+//! | [KiwiSdrSource](KiwiSdrSourceBuilder) | Receive audio or IQ samples from a remote [KiwiSDR](http://kiwisdr.com/) (requires `web` feature). | ❌ |
+//! | [SpyServerSource](SpyServerSourceBuilder) | Receive samples from a remote [SpyServer](https://airspy.com/download/). | ❌ |
+//!
 //! ## Hardware Acceleration
 //! | Block | Usage | WebAssembly? | Feature |
 //! |---|---|---|---|
 //! | [Vulkan] | Interface GPU w/ Vulkan. | ❌ | `vulkan` |
 //! | [Wgpu] | Interface GPU w/ native API. | ✅ | `wgpu` |
+//! | [WgpuFft](WgpuFftBuilder) | Batched FFT on the GPU with wgpu. | ✅ | `wgpu` |
+//! | [WgpuFir](WgpuFirBuilder) | FIR filter and decimator on the GPU with wgpu. | ✅ | `wgpu` |
 //! | [Zynq] | Interface Zynq FPGA w/ AXI DMA (async mode). | ❌ | `zynq` |
 //! | [ZynqSync] | Interface Zynq FPGA w/ AXI DMA (sync mode). | ❌ | `zynq` |
 //!
@@ -86,17 +235,29 @@
 //! ## Signal Sources
 //! | Block | Usage | WebAssembly? |
 //! |---|---|---|
-//! | [SignalSource](SignalSourceBuilder) | Create signals (sin, cos, square). | ✅ |
+//! | [SignalSource](SignalSourceBuilder) | Create signals (sin, cos, square, sawtooth). | ✅ |
 //!
 //! ## Audio (requires `audio` feature)
 //! | Block | Usage | WebAssembly? |
 //! |---|---|---|
-//! | [AudioSink](audio::AudioSink) | Audio sink. | ❌ |
-//! | [AudioSource](audio::AudioSource) | Audio source. | ❌ |
+//! | [AudioSink](audio::AudioSinkBuilder) | Play samples on a soundcard, reporting underruns. | ✅ |
+//! | [AudioSource](audio::AudioSourceBuilder) | Record samples from a soundcard, reporting overruns. | ✅ |
 //! | [FileSource](audio::FileSource) | Read an audio file and output its samples. | ❌ |
 //! | [WavSink](audio::WavSink) | Writes samples to a WAV file | ❌ |
 //!
 
+mod adsb;
+pub use adsb::{AdsbDecoder, ModeSDemod};
+
+mod afc;
+pub use afc::{Afc, AfcBuilder, AfcEstimator};
+
+mod am_demod;
+pub use am_demod::AmDemod;
+
+mod aprs;
+pub use aprs::AprsParser;
+
 mod apply;
 pub use apply::Apply;
 
@@ -106,6 +267,15 @@ pub use applynm::ApplyNM;
 mod applyintoiter;
 pub use applyintoiter::ApplyIntoIter;
 
+mod arithmetic;
+pub use arithmetic::{Add, AddConst, Arithmetic, Multiply, MultiplyConst, Subtract};
+
+mod assert_sink;
+pub use assert_sink::{ApproxEq, AssertSink, AssertSinkBuilder};
+
+mod ax25;
+pub use ax25::{Ax25Decoder, Ax25Encoder, Ax25EncoderBuilder, Ax25Frame};
+
 pub mod audio;
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -113,9 +283,27 @@ mod blob_to_udp;
 #[cfg(not(target_arch = "wasm32"))]
 pub use blob_to_udp::BlobToUdp;
 
+mod burst_to_pdu;
+pub use burst_to_pdu::{BurstDelimiter, BurstToPdu, BurstToPduBuilder};
+
+mod channel_model;
+pub use channel_model::{ChannelModel, ChannelModelBuilder};
+
 mod combine;
 pub use combine::Combine;
 
+mod complex;
+pub use complex::{
+    ComplexToArg, ComplexToImag, ComplexToMag, ComplexToMagSquared, ComplexToReal, Deinterleave,
+    Interleave,
+};
+
+mod constellation;
+pub use constellation::Constellation;
+pub use constellation::ConstellationDemapper;
+pub use constellation::ConstellationDemapperBuilder;
+pub use constellation::ConstellationMapper;
+
 mod console_sink;
 pub use console_sink::ConsoleSink;
 
@@ -124,12 +312,42 @@ pub use copy::Copy;
 mod copy_rand;
 pub use copy_rand::{CopyRand, CopyRandBuilder};
 
+mod correlate_access_code;
+pub use correlate_access_code::{CorrelateAccessCode, CorrelateAccessCodeBuilder};
+mod correlate_sync;
+pub use correlate_sync::{CorrelateSync, CorrelateSyncBuilder};
+
+mod costas_loop;
+pub use costas_loop::CostasLoop;
+
+mod crc;
+pub use crc::{Crc, CrcAppend, CrcCheck};
+
+mod delay;
+pub use delay::Delay;
+#[cfg(feature = "digital_rf")]
+pub mod digital_rf;
+#[cfg(target_os = "linux")]
+mod direct_file_sink;
+#[cfg(target_os = "linux")]
+pub use direct_file_sink::{DirectFileSink, DirectFileSinkBuilder};
+mod doppler;
+pub use doppler::{DopplerCorrection, DopplerCorrectionBuilder};
+
+mod emphasis;
+pub use emphasis::{Deemphasis, Preemphasis};
+
+mod fading_channel;
+pub use fading_channel::{FadingChannel, FadingChannelBuilder};
+
 mod filter;
 pub use filter::Filter;
 
 mod fir;
 pub use fir::Fir;
 pub use fir::FirBuilder;
+pub use fir::RrcFilterBuilder;
+pub use fir::RrcMode;
 
 mod fft;
 pub use fft::Fft;
@@ -138,21 +356,63 @@ pub use fft::FftDirection;
 #[cfg(not(target_arch = "wasm32"))]
 mod file_sink;
 #[cfg(not(target_arch = "wasm32"))]
-pub use file_sink::FileSink;
+pub use file_sink::{FileSink, FileSinkBuilder};
 
 #[cfg(not(target_arch = "wasm32"))]
 mod file_source;
 #[cfg(not(target_arch = "wasm32"))]
-pub use file_source::FileSource;
+pub use file_source::{FileFormat, FileSample, FileSource, FileSourceBuilder};
 
 mod finite_source;
 pub use finite_source::FiniteSource;
+#[cfg(feature = "hackrf")]
+pub mod hackrf;
+
 mod head;
 pub use head::Head;
 
+#[cfg(not(target_arch = "wasm32"))]
+mod icecast;
+#[cfg(all(feature = "lame", not(target_arch = "wasm32")))]
+pub use icecast::Mp3Encoder;
+#[cfg(not(target_arch = "wasm32"))]
+pub use icecast::{AudioEncoder, IcecastSink, IcecastSinkBuilder, WavEncoder};
+
+mod interleaver;
+pub use interleaver::{
+    BlockDeinterleaver, BlockInterleaver, ConvolutionalDeinterleaver, ConvolutionalInterleaver,
+};
+
+mod framer;
+pub use framer::{Framer, FramerBuilder, Whitening};
+
+mod fsk;
+pub use fsk::{AfskDemodBuilder, AfskModBuilder, FskDemodBuilder, FskMod};
+
+mod gfsk;
+pub use gfsk::{GfskDemod, GfskDemodBuilder, GfskMod, GfskModBuilder};
+
+mod ieee802154;
+pub use ieee802154::{Ieee802154Demod, Ieee802154FrameSync, Ieee802154Mod};
+
 mod iir;
 pub use iir::{Iir, IirBuilder};
 
+#[cfg(all(feature = "web", not(target_arch = "wasm32")))]
+mod kiwisdr;
+#[cfg(all(feature = "web", not(target_arch = "wasm32")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "web")))]
+pub use kiwisdr::{KiwiSdrMode, KiwiSdrSource, KiwiSdrSourceBuilder};
+
+mod keep_m_in_n;
+pub use keep_m_in_n::{KeepMInN, KeepMInNBuilder, KeepOneInN};
+
+mod loopback;
+pub use loopback::{LoopbackBuilder, LoopbackSink};
+
+mod lora;
+pub use lora::{LoraRx, LoraRxBuilder, LoraTx, LoraTxBuilder};
+
 #[cfg(feature = "lttng")]
 pub mod lttng;
 
@@ -160,6 +420,8 @@ mod message_burst;
 pub use message_burst::{MessageBurst, MessageBurstBuilder};
 mod message_copy;
 pub use message_copy::MessageCopy;
+mod message_gate;
+pub use message_gate::MessageGate;
 mod message_pipe;
 pub use message_pipe::MessagePipe;
 mod message_sink;
@@ -170,20 +432,119 @@ mod message_source;
 #[cfg(not(target_arch = "wasm32"))]
 pub use message_source::{MessageSource, MessageSourceBuilder};
 
+#[cfg(unix)]
+mod mmap_source;
+#[cfg(unix)]
+pub use mmap_source::{MmapSource, MmapSourceBuilder};
+
+mod moving_average;
+pub use moving_average::MovingAverage;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod pcap_sink;
+#[cfg(not(target_arch = "wasm32"))]
+pub use pcap_sink::{LinkType, PcapFormat, PcapSink, PcapSinkBuilder};
+
+mod pdu_debug;
+pub use pdu_debug::PduDebug;
+
+mod pdu_length_filter;
+pub use pdu_length_filter::PduLengthFilter;
+
+mod peak_detector;
+pub use peak_detector::{PeakDetector, PeakDetectorBuilder};
+
+mod pfb_arb_resampler;
+pub use pfb_arb_resampler::PfbArbResampler;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod pipe;
+#[cfg(not(target_arch = "wasm32"))]
+pub use pipe::{PipeSink, PipeSinkBuilder, PipeSource, PipeSourceBuilder};
+
+#[cfg(feature = "pluto")]
+pub mod pluto;
+
+mod pocsag;
+pub use pocsag::PocsagDecoder;
+
+mod probe;
+#[cfg(not(target_arch = "wasm32"))]
+pub use probe::RateProbe;
+pub use probe::{ProbeSample, SignalProbe};
+
+mod psk;
+pub use psk::{PskRx, PskRxBuilder, PskTx, PskTxBuilder};
+
+mod nbfm;
+pub use nbfm::{NbfmRx, NbfmRxBuilder, NbfmTx, NbfmTxBuilder};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod network_source;
+#[cfg(not(target_arch = "wasm32"))]
+pub use network_source::{
+    DelimiterFramer, FixedSizeFramer, Framed, LengthPrefixedFramer, NetworkFramer, NetworkSource,
+    NetworkSourceBuilder,
+};
+
 mod null_sink;
 pub use null_sink::NullSink;
 mod null_source;
 pub use null_source::NullSource;
 
-#[cfg(feature = "soapy")]
+#[cfg(not(target_arch = "wasm32"))]
 pub mod soapy;
+#[cfg(not(target_arch = "wasm32"))]
+pub use soapy::{MockSoapySink, MockSoapySinkBuilder, MockSoapySource, MockSoapySourceBuilder};
 #[cfg(feature = "soapy")]
 pub use soapy::{SoapySink, SoapySinkBuilder, SoapySource, SoapySourceBuilder};
 
+mod quadrature_demod;
+pub use quadrature_demod::QuadratureDemod;
+
+mod reed_solomon;
+pub use reed_solomon::{ReedSolomon, ReedSolomonDecoder, ReedSolomonEncoder};
+
+mod repack_bits;
+pub use repack_bits::{BitOrder, PackBits, RepackBits, UnpackBits};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod rotating_file_sink;
+#[cfg(not(target_arch = "wasm32"))]
+pub use rotating_file_sink::{RotatingFileSink, RotatingFileSinkBuilder};
+
+mod rotator;
+pub use rotator::{Fs4Shift, Rotator};
+
+#[cfg(all(feature = "rtlsdr", not(target_arch = "wasm32")))]
+pub mod rtlsdr;
+#[cfg(all(feature = "rtlsdr", target_arch = "wasm32"))]
+#[path = "rtlsdr_wasm/mod.rs"]
+pub mod rtlsdr;
+
 mod selector;
 pub use selector::DropPolicy as SelectorDropPolicy;
 pub use selector::Selector;
 
+#[cfg(unix)]
+mod shm;
+#[cfg(unix)]
+pub use shm::{ShmSink, ShmSinkBuilder, ShmSource, ShmSourceBuilder};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod sigmf;
+#[cfg(not(target_arch = "wasm32"))]
+pub use sigmf::{SigmfSink, SigmfSinkBuilder, SigmfSource};
+
+mod single_pole_iir;
+pub use single_pole_iir::SinglePoleIir;
+
+mod squelch;
+pub use squelch::{CtcssSquelch, PowerSquelch, PowerSquelchBuilder};
+
+mod skip_head;
+pub use skip_head::SkipHead;
+
 pub mod signal_source;
 pub use signal_source::FixedPointPhase;
 pub use signal_source::SignalSourceBuilder;
@@ -192,34 +553,73 @@ pub mod sink;
 pub use sink::Sink;
 mod source;
 pub use source::Source;
+#[cfg(not(target_arch = "wasm32"))]
+mod spyserver;
+#[cfg(not(target_arch = "wasm32"))]
+pub use spyserver::{SpyServerFormat, SpyServerSource, SpyServerSourceBuilder};
+
 mod split;
 pub use split::Split;
 
+mod ssb_demod;
+pub use ssb_demod::{Sideband, SsbDemod};
+mod statistics_sink;
+pub use statistics_sink::{StatisticsSink, StatisticsSinkBuilder};
+
+mod stream_mux;
+pub use stream_mux::{StreamDemux, StreamMux};
+mod stream_to_vector;
+pub use stream_to_vector::{PduSample, PduToStream, StreamToPdu, StreamToVector, VectorToStream};
+
+mod symbol_sync;
+pub use symbol_sync::{SymbolSync, SymbolSyncBuilder, TimingErrorDetector};
+
 mod tag_debug;
 pub use tag_debug::TagDebug;
 
+mod tagged_stream;
+pub use tagged_stream::{PduToTaggedStream, TaggedStreamToPdu};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod telemetry_sink;
+#[cfg(not(target_arch = "wasm32"))]
+pub use telemetry_sink::TelemetrySink;
+
 #[cfg(not(target_arch = "wasm32"))]
 mod tcp_sink;
 #[cfg(not(target_arch = "wasm32"))]
-pub use tcp_sink::TcpSink;
+pub use tcp_sink::{TcpSink, TcpSinkBuilder};
 
 #[cfg(not(target_arch = "wasm32"))]
 mod tcp_source;
 #[cfg(not(target_arch = "wasm32"))]
-pub use tcp_source::TcpSource;
+pub use tcp_source::{TcpMode, TcpSource, TcpSourceBuilder};
 
 #[cfg(not(target_arch = "wasm32"))]
 mod throttle;
 #[cfg(not(target_arch = "wasm32"))]
 pub use throttle::Throttle;
 
+mod channel_sink;
+pub use channel_sink::ChannelSink;
+
 mod channel_source;
 pub use channel_source::ChannelSource;
 
+mod type_convert;
+pub use type_convert::{FloatToInt, IntSample, IntToFloat};
+
 mod vector_sink;
-pub use vector_sink::{VectorSink, VectorSinkBuilder};
+#[cfg(not(target_arch = "wasm32"))]
+pub use vector_sink::run_and_collect;
+pub use vector_sink::{VectorSink, VectorSinkBuilder, VectorSinkWithTags};
 mod vector_source;
-pub use vector_source::VectorSource;
+pub use vector_source::{VectorSource, VectorSourceBuilder};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod vita49;
+#[cfg(not(target_arch = "wasm32"))]
+pub use vita49::{Vita49Sink, Vita49SinkBuilder, Vita49Source, Vita49SourceBuilder};
 
 #[cfg(feature = "vulkan")]
 mod vulkan;
@@ -235,15 +635,38 @@ mod wasm_freq;
 #[cfg(target_arch = "wasm32")]
 pub use wasm_freq::WasmFreq;
 
-#[cfg(not(target_arch = "wasm32"))]
+mod waterfall_sink;
+pub use waterfall_sink::{WaterfallFrame, WaterfallSink, WaterfallSinkBuilder};
+
+#[cfg(all(feature = "web", not(target_arch = "wasm32")))]
 mod websocket_sink;
-#[cfg(not(target_arch = "wasm32"))]
+#[cfg(all(feature = "web", not(target_arch = "wasm32")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "web")))]
 pub use websocket_sink::{WebsocketSink, WebsocketSinkBuilder, WebsocketSinkMode};
+#[cfg(all(feature = "web", not(target_arch = "wasm32")))]
+mod websocket_stream_sink;
+#[cfg(all(feature = "web", not(target_arch = "wasm32")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "web")))]
+pub use websocket_stream_sink::{WebsocketStreamSink, WebsocketStreamSinkBuilder};
+
+mod wbfm_rx;
+pub use wbfm_rx::WbfmRx;
+
+mod welch;
+pub use welch::{Welch, WelchBuilder};
 
 #[cfg(feature = "wgpu")]
 mod wgpu;
 #[cfg(feature = "wgpu")]
 pub use self::wgpu::Wgpu;
+#[cfg(feature = "wgpu")]
+mod wgpu_fft;
+#[cfg(feature = "wgpu")]
+pub use wgpu_fft::{WgpuFft, WgpuFftBuilder};
+#[cfg(feature = "wgpu")]
+mod wgpu_fir;
+#[cfg(feature = "wgpu")]
+pub use wgpu_fir::{WgpuFir, WgpuFirBuilder};
 
 #[cfg(feature = "zeromq")]
 pub mod zeromq;
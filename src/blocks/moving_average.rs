@@ -0,0 +1,126 @@
+use std::ops::{Add, Mul, Sub};
+
+use crate::anyhow::Result;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Moving average over a given number of samples.
+///
+/// Outputs the mean of the last `length` input samples, starting with a history of zeros.
+/// Optionally, only every `decimation`-th average is output. The running sum is recomputed once
+/// per `length` samples to avoid accumulating rounding errors.
+///
+/// # Inputs
+///
+/// `in`: Input
+///
+/// # Outputs
+///
+/// `out`: Average
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::MovingAverage;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let avg = fg.add_block(MovingAverage::<f32>::new(64));
+/// // average of 64 samples, output once per 16 samples
+/// let avg_decim = fg.add_block(MovingAverage::<f32>::with_decimation(64, 16));
+/// ```
+pub struct MovingAverage<T> {
+    history: Vec<T>,
+    index: usize,
+    sum: T,
+    scale: f32,
+    decimation: usize,
+    count: usize,
+}
+
+impl<T> MovingAverage<T>
+where
+    T: Copy + Default + Send + Add<Output = T> + Sub<Output = T> + Mul<f32, Output = T> + 'static,
+{
+    pub fn new(length: usize) -> Block {
+        Self::with_decimation(length, 1)
+    }
+
+    pub fn with_decimation(length: usize, decimation: usize) -> Block {
+        assert!(length > 0, "length must be positive");
+        assert!(decimation > 0, "decimation must be positive");
+
+        Block::new(
+            BlockMetaBuilder::new("MovingAverage").build(),
+            StreamIoBuilder::new()
+                .add_input::<T>("in")
+                .add_output::<T>("out")
+                .build(),
+            MessageIoBuilder::new().build(),
+            MovingAverage {
+                history: vec![T::default(); length],
+                index: 0,
+                sum: T::default(),
+                scale: 1.0 / length as f32,
+                decimation,
+                count: 0,
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T> Kernel for MovingAverage<T>
+where
+    T: Copy + Default + Send + Add<Output = T> + Sub<Output = T> + Mul<f32, Output = T> + 'static,
+{
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<T>();
+        let o = sio.output(0).slice::<T>();
+
+        let mut consumed = 0;
+        let mut produced = 0;
+        while consumed < i.len() && produced < o.len() {
+            let x = i[consumed];
+            consumed += 1;
+
+            self.sum = self.sum + x - self.history[self.index];
+            self.history[self.index] = x;
+            self.index += 1;
+            if self.index == self.history.len() {
+                self.index = 0;
+                self.sum = self.history.iter().fold(T::default(), |acc, h| acc + *h);
+            }
+
+            self.count += 1;
+            if self.count == self.decimation {
+                self.count = 0;
+                o[produced] = self.sum * self.scale;
+                produced += 1;
+            }
+        }
+
+        sio.input(0).consume(consumed);
+        sio.output(0).produce(produced);
+
+        if sio.input(0).finished() && consumed == i.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,307 @@
+use futuredsp::firdes;
+use futuredsp::UnaryKernel;
+use std::f32::consts::PI;
+
+use crate::anyhow::Result;
+use crate::blocks::squelch::Ctcss;
+use crate::kernels::FirKernel;
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Maximum number of samples, buffered between filter stages.
+const BUFFER_SIZE: usize = 1 << 14;
+/// Amplitude of the CTCSS tone, relative to the deviation.
+const CTCSS_LEVEL: f32 = 0.15;
+
+/// Narrowband FM modulator.
+pub struct NbfmTx {
+    sensitivity: f32,
+    phase: f32,
+    ctcss: Option<(f32, f32)>,
+}
+
+impl NbfmTx {
+    pub fn new(sample_rate: f32, deviation: f32, ctcss: Option<f32>) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("NbfmTx").build(),
+            StreamIoBuilder::new()
+                .add_input::<f32>("in")
+                .add_output::<Complex32>("out")
+                .build(),
+            MessageIoBuilder::<NbfmTx>::new().build(),
+            NbfmTx {
+                sensitivity: 2.0 * PI * deviation / sample_rate,
+                phase: 0.0,
+                ctcss: ctcss.map(|f| (2.0 * PI * f / sample_rate, 0.0)),
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for NbfmTx {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<f32>();
+        let o = sio.output(0).slice::<Complex32>();
+
+        let m = std::cmp::min(i.len(), o.len());
+        for (x, y) in i[..m].iter().zip(o.iter_mut()) {
+            let mut v = *x;
+            if let Some((inc, ref mut phase)) = self.ctcss {
+                v += CTCSS_LEVEL * phase.sin();
+                *phase = (*phase + inc) % (2.0 * PI);
+            }
+            self.phase = (self.phase + self.sensitivity * v) % (2.0 * PI);
+            *y = Complex32::from_polar(1.0, self.phase);
+        }
+
+        sio.input(0).consume(m);
+        sio.output(0).produce(m);
+
+        if sio.input(0).finished() && m == i.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Narrowband FM modulator.
+///
+/// Frequency modulates the audio input with the given maximum deviation (default 2.5 kHz), i.e.,
+/// an input of amplitude one results in the maximum deviation. Input and output use the same
+/// sample rate. Optionally, a sub-audible CTCSS tone is added with 15% of the deviation.
+///
+/// # Inputs
+///
+/// `in`: Audio (f32)
+///
+/// # Outputs
+///
+/// `out`: Modulated signal (Complex32)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::NbfmTxBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let tx = fg.add_block(NbfmTxBuilder::new(48000.0).deviation(5e3).ctcss(88.5).build());
+/// ```
+pub struct NbfmTxBuilder {
+    sample_rate: f32,
+    deviation: f32,
+    ctcss: Option<f32>,
+}
+
+impl NbfmTxBuilder {
+    pub fn new(sample_rate: f32) -> NbfmTxBuilder {
+        NbfmTxBuilder {
+            sample_rate,
+            deviation: 2.5e3,
+            ctcss: None,
+        }
+    }
+
+    /// Maximum frequency deviation in Hz.
+    #[must_use]
+    pub fn deviation(mut self, deviation: f32) -> NbfmTxBuilder {
+        self.deviation = deviation;
+        self
+    }
+
+    /// Add a CTCSS tone with the given frequency in Hz.
+    #[must_use]
+    pub fn ctcss(mut self, frequency: f32) -> NbfmTxBuilder {
+        self.ctcss = Some(frequency);
+        self
+    }
+
+    pub fn build(self) -> Block {
+        NbfmTx::new(self.sample_rate, self.deviation, self.ctcss)
+    }
+}
+
+/// Narrowband FM demodulator.
+pub struct NbfmRx {
+    channel: FirKernel<Complex32>,
+    audio_filter: FirKernel<f32>,
+    gain: f32,
+    last: Complex32,
+    ctcss: Option<Ctcss>,
+    filtered: Vec<Complex32>,
+    audio: Vec<f32>,
+}
+
+impl NbfmRx {
+    pub fn new(sample_rate: f32, deviation: f32, bandwidth: f32, ctcss: Option<f32>) -> Block {
+        assert!(
+            bandwidth < 0.6 * sample_rate,
+            "channel bandwidth has to be smaller than 60% of the sample rate"
+        );
+        assert!(sample_rate >= 8e3, "sample rate has to be at least 8 kHz");
+
+        let fs = sample_rate as f64;
+        let channel_taps = firdes::kaiser::lowpass::<f32>(
+            bandwidth as f64 / 2.0 / fs,
+            bandwidth as f64 / 4.0 / fs,
+            0.001,
+        );
+        // remove the CTCSS tone with a high-pass
+        let audio_taps = match ctcss {
+            Some(_) => firdes::kaiser::bandpass::<f32>(400.0 / fs, 3000.0 / fs, 140.0 / fs, 0.001),
+            None => firdes::kaiser::lowpass::<f32>(3000.0 / fs, 500.0 / fs, 0.001),
+        };
+
+        Block::new(
+            BlockMetaBuilder::new("NbfmRx").build(),
+            StreamIoBuilder::new()
+                .add_input::<Complex32>("in")
+                .add_output::<f32>("out")
+                .build(),
+            MessageIoBuilder::<NbfmRx>::new().build(),
+            NbfmRx {
+                channel: FirKernel::new(channel_taps),
+                audio_filter: FirKernel::new(audio_taps),
+                gain: sample_rate / (2.0 * PI * deviation),
+                last: Complex32::new(0.0, 0.0),
+                ctcss: ctcss.map(|f| Ctcss::new(f, sample_rate)),
+                filtered: vec![Complex32::new(0.0, 0.0); BUFFER_SIZE],
+                audio: Vec::with_capacity(BUFFER_SIZE),
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for NbfmRx {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex32>();
+
+        // channel filter and demodulate into the audio buffer
+        let space = BUFFER_SIZE - self.audio.len();
+        let (consumed, demodulated, status) = self.channel.work(i, &mut self.filtered[..space]);
+        for x in self.filtered[..demodulated].iter() {
+            let mut f = self.gain * (x * self.last.conj()).arg();
+            self.last = *x;
+            if let Some(ref mut ctcss) = self.ctcss {
+                ctcss.push(f);
+                if !ctcss.detected {
+                    f = 0.0;
+                }
+            }
+            self.audio.push(f);
+        }
+        sio.input(0).consume(consumed);
+
+        // audio filter
+        let o = sio.output(0).slice::<f32>();
+        let (used, produced, audio_status) = self.audio_filter.work(&self.audio, o);
+        self.audio.drain(..used);
+        sio.output(0).produce(produced);
+
+        if sio.input(0).finished()
+            && status.produced_all_samples()
+            && audio_status.produced_all_samples()
+        {
+            io.finished = true;
+        } else if demodulated > 0 && produced > 0 {
+            io.call_again = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Narrowband FM demodulator.
+///
+/// Filters the channel with the given bandwidth (default 12.5 kHz), demodulates it, scaled such
+/// that the maximum deviation (default 2.5 kHz) results in an amplitude of one, and low-pass
+/// filters the audio to 3 kHz. Input and output use the same sample rate, which should be low
+/// (e.g., 48 kHz), since the filters get longer with higher rates.
+///
+/// If a CTCSS frequency is set, the audio is muted, while the tone is not detected, and the
+/// sub-audible band is removed.
+///
+/// # Inputs
+///
+/// `in`: Baseband samples (Complex32)
+///
+/// # Outputs
+///
+/// `out`: Audio (f32)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::NbfmRxBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let rx = fg.add_block(NbfmRxBuilder::new(48000.0).ctcss(88.5).build());
+/// ```
+pub struct NbfmRxBuilder {
+    sample_rate: f32,
+    deviation: f32,
+    bandwidth: f32,
+    ctcss: Option<f32>,
+}
+
+impl NbfmRxBuilder {
+    pub fn new(sample_rate: f32) -> NbfmRxBuilder {
+        NbfmRxBuilder {
+            sample_rate,
+            deviation: 2.5e3,
+            bandwidth: 12.5e3,
+            ctcss: None,
+        }
+    }
+
+    /// Maximum frequency deviation in Hz.
+    #[must_use]
+    pub fn deviation(mut self, deviation: f32) -> NbfmRxBuilder {
+        self.deviation = deviation;
+        self
+    }
+
+    /// Channel bandwidth in Hz.
+    #[must_use]
+    pub fn bandwidth(mut self, bandwidth: f32) -> NbfmRxBuilder {
+        self.bandwidth = bandwidth;
+        self
+    }
+
+    /// Only output audio while a CTCSS tone with the given frequency in Hz is received.
+    #[must_use]
+    pub fn ctcss(mut self, frequency: f32) -> NbfmRxBuilder {
+        self.ctcss = Some(frequency);
+        self
+    }
+
+    pub fn build(self) -> Block {
+        NbfmRx::new(self.sample_rate, self.deviation, self.bandwidth, self.ctcss)
+    }
+}
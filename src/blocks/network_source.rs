@@ -0,0 +1,439 @@
+use async_io::Async;
+use std::io::{ErrorKind, Read};
+use std::net::{TcpStream, UdpSocket};
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::anyhow::{Context, Result};
+use crate::blocks::tcp_source::TcpConnection;
+use crate::blocks::TcpMode;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Result of a [NetworkFramer].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Framed {
+    /// A frame with the given payload, consuming the first `consumed` (> 0) bytes of the buffer.
+    Frame {
+        payload: Range<usize>,
+        consumed: usize,
+    },
+    /// More bytes are required.
+    Incomplete,
+    /// Discard the first bytes of the buffer, e.g., to resynchronize after invalid data.
+    Skip(usize),
+}
+
+/// Split a byte stream into frames for the [NetworkSource].
+pub trait NetworkFramer: Send {
+    /// Look for a frame at the start of the buffer.
+    fn frame(&mut self, buf: &[u8]) -> Framed;
+}
+
+/// Frames with a length field in front of the payload.
+#[derive(Debug, Clone)]
+pub struct LengthPrefixedFramer {
+    width: usize,
+    little_endian: bool,
+    includes_header: bool,
+    max_len: usize,
+}
+
+impl LengthPrefixedFramer {
+    /// Length field of `width` bytes (1, 2, 4, or 8), big endian.
+    pub fn new(width: usize) -> LengthPrefixedFramer {
+        assert!(
+            matches!(width, 1 | 2 | 4 | 8),
+            "length field has to be 1, 2, 4, or 8 bytes"
+        );
+        LengthPrefixedFramer {
+            width,
+            little_endian: false,
+            includes_header: false,
+            max_len: 65536,
+        }
+    }
+
+    /// Length field in little endian.
+    #[must_use]
+    pub fn little_endian(mut self, little_endian: bool) -> LengthPrefixedFramer {
+        self.little_endian = little_endian;
+        self
+    }
+
+    /// The length includes the length field itself.
+    #[must_use]
+    pub fn includes_header(mut self, includes_header: bool) -> LengthPrefixedFramer {
+        self.includes_header = includes_header;
+        self
+    }
+
+    /// Maximum payload length. Longer frames are considered invalid (default 65536).
+    #[must_use]
+    pub fn max_len(mut self, max_len: usize) -> LengthPrefixedFramer {
+        self.max_len = max_len;
+        self
+    }
+}
+
+impl NetworkFramer for LengthPrefixedFramer {
+    fn frame(&mut self, buf: &[u8]) -> Framed {
+        if buf.len() < self.width {
+            return Framed::Incomplete;
+        }
+        let mut len = 0u64;
+        for i in 0..self.width {
+            let b = if self.little_endian {
+                buf[self.width - 1 - i]
+            } else {
+                buf[i]
+            };
+            len = len << 8 | b as u64;
+        }
+        let len = if self.includes_header {
+            match (len as usize).checked_sub(self.width) {
+                Some(l) => l,
+                None => return Framed::Skip(1),
+            }
+        } else {
+            len as usize
+        };
+        if len > self.max_len {
+            // most likely out of sync
+            return Framed::Skip(1);
+        }
+        if buf.len() < self.width + len {
+            return Framed::Incomplete;
+        }
+        Framed::Frame {
+            payload: self.width..self.width + len,
+            consumed: self.width + len,
+        }
+    }
+}
+
+/// Frames terminated by a delimiter, e.g., lines of text.
+#[derive(Debug, Clone)]
+pub struct DelimiterFramer {
+    delimiter: Vec<u8>,
+    max_len: usize,
+}
+
+impl DelimiterFramer {
+    pub fn new<D: Into<Vec<u8>>>(delimiter: D) -> DelimiterFramer {
+        let delimiter = delimiter.into();
+        assert!(!delimiter.is_empty(), "delimiter must not be empty");
+        DelimiterFramer {
+            delimiter,
+            max_len: 65536,
+        }
+    }
+
+    /// Maximum frame length. Longer data without delimiter is discarded (default 65536).
+    #[must_use]
+    pub fn max_len(mut self, max_len: usize) -> DelimiterFramer {
+        self.max_len = max_len;
+        self
+    }
+}
+
+impl NetworkFramer for DelimiterFramer {
+    fn frame(&mut self, buf: &[u8]) -> Framed {
+        match buf
+            .windows(self.delimiter.len())
+            .position(|w| w == self.delimiter)
+        {
+            Some(i) if i <= self.max_len => Framed::Frame {
+                payload: 0..i,
+                consumed: i + self.delimiter.len(),
+            },
+            Some(i) => Framed::Skip(i + self.delimiter.len()),
+            // keep a partial delimiter at the end
+            None if buf.len() >= self.max_len + self.delimiter.len() => {
+                Framed::Skip(buf.len() + 1 - self.delimiter.len())
+            }
+            None => Framed::Incomplete,
+        }
+    }
+}
+
+/// Frames of a fixed size.
+#[derive(Debug, Clone)]
+pub struct FixedSizeFramer {
+    size: usize,
+}
+
+impl FixedSizeFramer {
+    pub fn new(size: usize) -> FixedSizeFramer {
+        assert!(size > 0, "frame size has to be positive");
+        FixedSizeFramer { size }
+    }
+}
+
+impl NetworkFramer for FixedSizeFramer {
+    fn frame(&mut self, buf: &[u8]) -> Framed {
+        if buf.len() < self.size {
+            Framed::Incomplete
+        } else {
+            Framed::Frame {
+                payload: 0..self.size,
+                consumed: self.size,
+            }
+        }
+    }
+}
+
+/// Transport of a [NetworkSource].
+enum Transport {
+    Tcp(TcpConnection),
+    Udp(String),
+}
+
+/// Receive frames from a TCP or UDP socket as PDUs.
+pub struct NetworkSource {
+    transport: Transport,
+    framer: Box<dyn NetworkFramer>,
+    tcp: Option<Arc<Async<TcpStream>>>,
+    udp: Option<Arc<Async<UdpSocket>>>,
+    /// Received bytes, not yet framed.
+    buf: Vec<u8>,
+}
+
+impl NetworkSource {
+    fn create(transport: Transport, framer: Box<dyn NetworkFramer>) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("NetworkSource").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new().add_output("out").build(),
+            NetworkSource {
+                transport,
+                framer,
+                tcp: None,
+                udp: None,
+                buf: Vec::new(),
+            },
+        )
+    }
+
+    /// Split the buffer into frames, returning the number of consumed bytes.
+    async fn deframe(
+        framer: &mut dyn NetworkFramer,
+        buf: &[u8],
+        mio: &mut MessageIo<NetworkSource>,
+    ) -> usize {
+        let mut pos = 0;
+        loop {
+            match framer.frame(&buf[pos..]) {
+                Framed::Frame { consumed: 0, .. } => {
+                    warn!("NetworkSource: framer did not consume any bytes");
+                    return pos;
+                }
+                Framed::Frame { payload, consumed } => {
+                    let frame = buf[pos + payload.start..pos + payload.end].to_vec();
+                    mio.post(0, Pmt::Blob(frame)).await;
+                    pos += consumed;
+                }
+                Framed::Skip(n) => {
+                    debug!("NetworkSource: discarding {} bytes", n);
+                    pos += n;
+                }
+                Framed::Incomplete => return pos,
+            }
+        }
+    }
+
+    fn closed(&mut self, io: &mut WorkIo) {
+        self.tcp = None;
+        self.buf.clear();
+        match &self.transport {
+            Transport::Tcp(c) if c.reconnect() => io.call_again = true,
+            _ => io.finished = true,
+        }
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for NetworkSource {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        _sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let mut chunk = [0u8; 65536];
+
+        match &mut self.transport {
+            Transport::Tcp(connection) => {
+                if self.tcp.is_none() {
+                    match connection.connect(io).await? {
+                        Some(s) => self.tcp = Some(s),
+                        None => return Ok(()),
+                    }
+                }
+                let socket = self.tcp.as_ref().context("no socket")?;
+                match socket.get_ref().read(&mut chunk) {
+                    Ok(0) => {
+                        debug!("NetworkSource: connection closed");
+                        self.closed(io);
+                    }
+                    Ok(n) => {
+                        self.buf.extend_from_slice(&chunk[..n]);
+                        let consumed = Self::deframe(self.framer.as_mut(), &self.buf, mio).await;
+                        self.buf.drain(..consumed);
+                        io.call_again = true;
+                    }
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                        let s = socket.clone();
+                        io.block_on(async move {
+                            let _ = s.readable().await;
+                        });
+                    }
+                    Err(e) if e.kind() == ErrorKind::Interrupted => io.call_again = true,
+                    Err(e) => {
+                        debug!("NetworkSource: socket error {}", e);
+                        self.closed(io);
+                    }
+                }
+            }
+            Transport::Udp(_) => {
+                let socket = self.udp.as_ref().context("no socket")?;
+                match socket.get_ref().recv(&mut chunk) {
+                    Ok(n) => {
+                        // frames do not span datagrams
+                        let consumed = Self::deframe(self.framer.as_mut(), &chunk[..n], mio).await;
+                        if consumed < n {
+                            debug!("NetworkSource: discarding {} bytes", n - consumed);
+                        }
+                        io.call_again = true;
+                    }
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                        let s = socket.clone();
+                        io.block_on(async move {
+                            let _ = s.readable().await;
+                        });
+                    }
+                    Err(e) if e.kind() == ErrorKind::Interrupted => io.call_again = true,
+                    Err(e) => return Err(e.into()),
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        match &mut self.transport {
+            Transport::Tcp(c) => c.init(),
+            Transport::Udp(bind) => {
+                self.udp = Some(Arc::new(Async::new(UdpSocket::bind(bind.as_str())?)?));
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Receive frames from a TCP or UDP socket as PDUs.
+///
+/// The source splits the received bytes into frames with a [NetworkFramer] and posts the
+/// payload of each frame as [Blob](Pmt::Blob). This allows ingesting ad-hoc telemetry feeds
+/// with, e.g., a [LengthPrefixedFramer], a [DelimiterFramer] for line-based protocols, or a
+/// [FixedSizeFramer]. Custom protocols only need to implement the [NetworkFramer] trait.
+///
+/// With TCP, the source listens for a connection or connects to a peer, like the
+/// [TcpSource](crate::blocks::TcpSourceBuilder). Without
+/// [reconnect](NetworkSourceBuilder::reconnect), it finishes when the connection is closed.
+/// With UDP, frames are extracted from each datagram and do not span datagrams.
+///
+/// # Inputs
+///
+/// No inputs.
+///
+/// # Outputs
+///
+/// **Message**: `out`: Frame payloads as [Blob](Pmt::Blob)
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::{DelimiterFramer, LengthPrefixedFramer, NetworkSourceBuilder, TcpMode};
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // newline-delimited telemetry over TCP
+/// let lines = fg.add_block(
+///     NetworkSourceBuilder::tcp(
+///         TcpMode::Client("127.0.0.1:4000".to_string()),
+///         DelimiterFramer::new("\n"),
+///     )
+///     .build(),
+/// );
+///
+/// // datagrams with a 16-bit length prefix
+/// let frames = fg.add_block(
+///     NetworkSourceBuilder::udp("0.0.0.0:5000", LengthPrefixedFramer::new(2)).build(),
+/// );
+/// ```
+#[cfg_attr(docsrs, doc(cfg(not(target_arch = "wasm32"))))]
+pub struct NetworkSourceBuilder {
+    mode: Option<TcpMode>,
+    bind: String,
+    reconnect: Option<Duration>,
+    framer: Box<dyn NetworkFramer>,
+}
+
+impl NetworkSourceBuilder {
+    /// Receive from a TCP connection.
+    pub fn tcp<F: NetworkFramer + 'static>(mode: TcpMode, framer: F) -> NetworkSourceBuilder {
+        NetworkSourceBuilder {
+            mode: Some(mode),
+            bind: String::new(),
+            reconnect: None,
+            framer: Box::new(framer),
+        }
+    }
+
+    /// Receive datagrams on a UDP socket, bound to the given address.
+    pub fn udp<S: Into<String>, F: NetworkFramer + 'static>(
+        bind: S,
+        framer: F,
+    ) -> NetworkSourceBuilder {
+        NetworkSourceBuilder {
+            mode: None,
+            bind: bind.into(),
+            reconnect: None,
+            framer: Box::new(framer),
+        }
+    }
+
+    /// Continue with a new TCP connection, retrying failed connection attempts after
+    /// `interval`.
+    #[must_use]
+    pub fn reconnect(mut self, interval: Duration) -> NetworkSourceBuilder {
+        self.reconnect = Some(interval);
+        self
+    }
+
+    pub fn build(self) -> Block {
+        let transport = match self.mode {
+            Some(mode) => Transport::Tcp(TcpConnection::new(mode, self.reconnect)),
+            None => Transport::Udp(self.bind),
+        };
+        NetworkSource::create(transport, self.framer)
+    }
+}
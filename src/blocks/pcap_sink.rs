@@ -0,0 +1,333 @@
+use async_fs::File;
+use futures::io::AsyncWriteExt;
+use futures::FutureExt;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use crate::anyhow::{bail, Result};
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+
+/// Maximum length of a captured frame.
+const SNAPLEN: u32 = 65535;
+
+/// Link-layer header type of the frames, telling Wireshark how to dissect them.
+///
+/// See the [list of link types](https://www.tcpdump.org/linktypes.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkType {
+    /// Ethernet frames (`LINKTYPE_ETHERNET`).
+    Ethernet,
+    /// AX.25 frames without FCS, as posted by the
+    /// [Ax25Decoder](crate::blocks::Ax25Decoder) (`LINKTYPE_AX25`).
+    Ax25,
+    /// AX.25 frames with a one-byte KISS header (`LINKTYPE_AX25_KISS`).
+    Ax25Kiss,
+    /// IEEE 802.15.4 frames with FCS, as posted by the
+    /// [Ieee802154FrameSync](crate::blocks::Ieee802154FrameSync)
+    /// (`LINKTYPE_IEEE802_15_4_WITHFCS`).
+    Ieee802154,
+    /// IEEE 802.15.4 frames without FCS (`LINKTYPE_IEEE802_15_4_NOFCS`).
+    Ieee802154NoFcs,
+    /// Raw IPv4 or IPv6 packets (`LINKTYPE_RAW`).
+    Raw,
+    /// Private link type 0 to 15 (`LINKTYPE_USER0` to `LINKTYPE_USER15`), e.g., for protocols
+    /// without a link type, dissected by a Wireshark plugin.
+    User(u8),
+    /// Other link type, given by its number.
+    Other(u16),
+}
+
+impl LinkType {
+    /// Number of the link type.
+    pub fn value(&self) -> u16 {
+        match self {
+            LinkType::Ethernet => 1,
+            LinkType::Ax25 => 3,
+            LinkType::Ax25Kiss => 202,
+            LinkType::Ieee802154 => 195,
+            LinkType::Ieee802154NoFcs => 230,
+            LinkType::Raw => 101,
+            LinkType::User(n) => {
+                assert!(*n < 16, "user link types are 0 to 15");
+                147 + *n as u16
+            }
+            LinkType::Other(n) => *n,
+        }
+    }
+}
+
+/// Capture file format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PcapFormat {
+    /// Classic pcap with nanosecond timestamps.
+    Pcap,
+    /// pcapng, which also records the application and interface name.
+    PcapNg,
+}
+
+/// Append a pcapng block with the given type and body.
+fn pcapng_block(buf: &mut Vec<u8>, block_type: u32, body: &[u8]) {
+    let len = 12 + body.len() as u32;
+    buf.extend_from_slice(&block_type.to_le_bytes());
+    buf.extend_from_slice(&len.to_le_bytes());
+    buf.extend_from_slice(body);
+    buf.extend_from_slice(&len.to_le_bytes());
+}
+
+/// Append a pcapng option, padded to 32 bits.
+fn pcapng_option(buf: &mut Vec<u8>, code: u16, value: &[u8]) {
+    buf.extend_from_slice(&code.to_le_bytes());
+    buf.extend_from_slice(&(value.len() as u16).to_le_bytes());
+    buf.extend_from_slice(value);
+    pad(buf);
+}
+
+fn pad(buf: &mut Vec<u8>) {
+    buf.resize((buf.len() + 3) / 4 * 4, 0);
+}
+
+/// Write PDUs into a pcap or pcapng file.
+pub struct PcapSink {
+    path: String,
+    format: PcapFormat,
+    link_type: LinkType,
+    interface: String,
+    file: Option<File>,
+    buf: Vec<u8>,
+}
+
+impl PcapSink {
+    fn create(builder: PcapSinkBuilder) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("PcapSink").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_input(
+                    "in",
+                    |block: &mut PcapSink,
+                     _mio: &mut MessageIo<PcapSink>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            match p {
+                                Pmt::Blob(v) => {
+                                    let t = SystemTime::now().duration_since(UNIX_EPOCH)?;
+                                    block.write(&v, t.as_nanos() as u64).await?;
+                                }
+                                Pmt::MapStrPmt(mut m) => {
+                                    let t = match (m.remove("seconds"), m.remove("picoseconds")) {
+                                        (Some(Pmt::U64(s)), Some(Pmt::U64(ps))) => {
+                                            s * 1_000_000_000 + ps / 1000
+                                        }
+                                        (Some(Pmt::U64(s)), _) => s * 1_000_000_000,
+                                        _ => {
+                                            SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos()
+                                                as u64
+                                        }
+                                    };
+                                    match m.remove("data") {
+                                        Some(Pmt::Blob(v)) => block.write(&v, t).await?,
+                                        _ => warn!("PcapSink: map without data entry"),
+                                    }
+                                }
+                                _ => warn!("PcapSink: received wrong PMT type. {:?}", p),
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .build(),
+            PcapSink {
+                path: builder.path,
+                format: builder.format,
+                link_type: builder.link_type,
+                interface: builder.interface,
+                file: None,
+                buf: Vec::new(),
+            },
+        )
+    }
+
+    /// Write a frame, received at the given nanoseconds since the epoch.
+    async fn write(&mut self, frame: &[u8], nanos: u64) -> Result<()> {
+        let captured = std::cmp::min(frame.len(), SNAPLEN as usize);
+        self.buf.clear();
+        match self.format {
+            PcapFormat::Pcap => {
+                self.buf
+                    .extend_from_slice(&((nanos / 1_000_000_000) as u32).to_le_bytes());
+                self.buf
+                    .extend_from_slice(&((nanos % 1_000_000_000) as u32).to_le_bytes());
+                self.buf.extend_from_slice(&(captured as u32).to_le_bytes());
+                self.buf
+                    .extend_from_slice(&(frame.len() as u32).to_le_bytes());
+                self.buf.extend_from_slice(&frame[..captured]);
+            }
+            PcapFormat::PcapNg => {
+                // enhanced packet block
+                let mut body = Vec::with_capacity(20 + captured + 3);
+                body.extend_from_slice(&0u32.to_le_bytes());
+                body.extend_from_slice(&((nanos >> 32) as u32).to_le_bytes());
+                body.extend_from_slice(&(nanos as u32).to_le_bytes());
+                body.extend_from_slice(&(captured as u32).to_le_bytes());
+                body.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+                body.extend_from_slice(&frame[..captured]);
+                pad(&mut body);
+                pcapng_block(&mut self.buf, 6, &body);
+            }
+        }
+
+        let file = self.file.as_mut().unwrap();
+        file.write_all(&self.buf).await?;
+        // flush every frame, so that the capture can be followed live
+        file.flush().await?;
+        Ok(())
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for PcapSink {
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let mut header = Vec::new();
+        match self.format {
+            PcapFormat::Pcap => {
+                // magic of nanosecond timestamps, version 2.4
+                header.extend_from_slice(&0xa1b2_3c4du32.to_le_bytes());
+                header.extend_from_slice(&2u16.to_le_bytes());
+                header.extend_from_slice(&4u16.to_le_bytes());
+                header.extend_from_slice(&[0; 8]);
+                header.extend_from_slice(&SNAPLEN.to_le_bytes());
+                header.extend_from_slice(&(self.link_type.value() as u32).to_le_bytes());
+            }
+            PcapFormat::PcapNg => {
+                // section header block, version 1.0, unspecified length
+                let mut body = Vec::new();
+                body.extend_from_slice(&0x1a2b_3c4du32.to_le_bytes());
+                body.extend_from_slice(&1u16.to_le_bytes());
+                body.extend_from_slice(&0u16.to_le_bytes());
+                body.extend_from_slice(&(-1i64).to_le_bytes());
+                pcapng_option(&mut body, 4, b"FutureSDR");
+                pcapng_option(&mut body, 0, &[]);
+                pcapng_block(&mut header, 0x0a0d_0d0a, &body);
+
+                // interface description block with nanosecond timestamps
+                let mut body = Vec::new();
+                body.extend_from_slice(&self.link_type.value().to_le_bytes());
+                body.extend_from_slice(&0u16.to_le_bytes());
+                body.extend_from_slice(&SNAPLEN.to_le_bytes());
+                pcapng_option(&mut body, 2, self.interface.as_bytes());
+                pcapng_option(&mut body, 9, &[9]);
+                pcapng_option(&mut body, 0, &[]);
+                pcapng_block(&mut header, 1, &body);
+            }
+        }
+
+        let mut file = match File::create(&self.path).await {
+            Ok(f) => f,
+            Err(e) => bail!("PcapSink: cannot create {}: {}", self.path, e),
+        };
+        file.write_all(&header).await?;
+        file.flush().await?;
+        self.file = Some(file);
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        if let Some(mut f) = self.file.take() {
+            f.close().await?;
+        }
+        Ok(())
+    }
+}
+
+/// Write PDUs into a pcapng or pcap file for protocol analysis with
+/// [Wireshark](https://www.wireshark.org).
+///
+/// Each PDU becomes a packet of the given [LinkType], which tells Wireshark how to dissect it,
+/// e.g., [LinkType::Ieee802154] for the frames of the
+/// [Ieee802154FrameSync](crate::blocks::Ieee802154FrameSync) or [LinkType::Ax25] for the
+/// frames of the [Ax25Decoder](crate::blocks::Ax25Decoder). Packets are timestamped with
+/// nanosecond resolution, either with the time of reception or with the `seconds` and
+/// `picoseconds` entries of a map.
+///
+/// The file is flushed after every packet. With a named pipe as path, Wireshark can display
+/// frames live, e.g., with `wireshark -k -i /tmp/fifo`.
+///
+/// # Inputs
+///
+/// **Message**: `in`: Frames ([Pmt::Blob]) or maps ([Pmt::MapStrPmt]) with the frame in `data`
+/// ([Pmt::Blob]) and the time since the epoch in `seconds` and `picoseconds` ([Pmt::U64])
+///
+/// # Outputs
+///
+/// No outputs.
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::Ieee802154FrameSync;
+/// use futuresdr::blocks::LinkType;
+/// use futuresdr::blocks::PcapSinkBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let sync = fg.add_block(Ieee802154FrameSync::new(2));
+/// let pcap = fg.add_block(PcapSinkBuilder::new("zigbee.pcapng", LinkType::Ieee802154).build());
+/// fg.connect_message(sync, "out", pcap, "in").unwrap();
+/// ```
+#[cfg_attr(docsrs, doc(cfg(not(target_arch = "wasm32"))))]
+pub struct PcapSinkBuilder {
+    path: String,
+    format: PcapFormat,
+    link_type: LinkType,
+    interface: String,
+}
+
+impl PcapSinkBuilder {
+    pub fn new<S: Into<String>>(path: S, link_type: LinkType) -> PcapSinkBuilder {
+        PcapSinkBuilder {
+            path: path.into(),
+            format: PcapFormat::PcapNg,
+            link_type,
+            interface: "futuresdr".to_string(),
+        }
+    }
+
+    /// File format (default: pcapng).
+    #[must_use]
+    pub fn format(mut self, format: PcapFormat) -> PcapSinkBuilder {
+        self.format = format;
+        self
+    }
+
+    /// Name of the interface, recorded in pcapng files (default: `futuresdr`).
+    #[must_use]
+    pub fn interface<S: Into<String>>(mut self, name: S) -> PcapSinkBuilder {
+        self.interface = name.into();
+        self
+    }
+
+    pub fn build(self) -> Block {
+        PcapSink::create(self)
+    }
+}
@@ -0,0 +1,91 @@
+use futures::FutureExt;
+
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIoBuilder;
+
+/// Print PDUs.
+///
+/// Console output is prefixed with the `name` to help differentiate the output from multiple PDU
+/// debug blocks. [Pmt::Blob] PDUs are printed as hex bytes.
+///
+/// # Inputs
+///
+/// **Message**: `in`: PDUs to print
+///
+/// # Outputs
+///
+/// No outputs
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::PduDebug;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let debug = fg.add_block(PduDebug::new("rx"));
+/// ```
+pub struct PduDebug {
+    name: String,
+    n_received: u64,
+}
+
+impl PduDebug {
+    pub fn new(name: impl Into<String>) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("PduDebug").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::<Self>::new()
+                .add_input(
+                    "in",
+                    |block: &mut PduDebug,
+                     _mio: &mut MessageIo<PduDebug>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            match &p {
+                                Pmt::Blob(v) => {
+                                    let hex: Vec<String> =
+                                        v.iter().map(|b| format!("{b:02x}")).collect();
+                                    println!(
+                                        "PduDebug {}: #{} ({} bytes) -- {}",
+                                        &block.name,
+                                        block.n_received,
+                                        v.len(),
+                                        hex.join(" ")
+                                    );
+                                }
+                                p => println!(
+                                    "PduDebug {}: #{} -- {:?}",
+                                    &block.name, block.n_received, p
+                                ),
+                            }
+                            block.n_received += 1;
+                            Ok(Pmt::U64(block.n_received))
+                        }
+                        .boxed()
+                    },
+                )
+                .build(),
+            PduDebug {
+                name: name.into(),
+                n_received: 0,
+            },
+        )
+    }
+
+    /// Number of received PDUs.
+    pub fn received(&self) -> u64 {
+        self.n_received
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for PduDebug {}
@@ -0,0 +1,81 @@
+use futures::FutureExt;
+
+use crate::blocks::PduSample;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIoBuilder;
+
+/// Forward PDUs with a length in a given range.
+///
+/// PDUs with at least `min` and at most `max` samples are forwarded, all others, including PDUs
+/// of the wrong type, are dropped.
+///
+/// # Inputs
+///
+/// **Message**: `in`: PDUs (see [PduSample])
+///
+/// # Outputs
+///
+/// **Message**: `out`: PDUs with a valid length
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::PduLengthFilter;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let filter = fg.add_block(PduLengthFilter::<u8>::new(16, 1500));
+/// ```
+pub struct PduLengthFilter<T: PduSample> {
+    min: usize,
+    max: usize,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: PduSample> PduLengthFilter<T> {
+    pub fn new(min: usize, max: usize) -> Block {
+        assert!(min <= max, "minimum length must not exceed maximum length");
+
+        Block::new(
+            BlockMetaBuilder::new("PduLengthFilter").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::<Self>::new()
+                .add_output("out")
+                .add_input(
+                    "in",
+                    |block: &mut PduLengthFilter<T>,
+                     mio: &mut MessageIo<PduLengthFilter<T>>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            match T::from_pmt(&p) {
+                                Some(v) if (block.min..=block.max).contains(&v.len()) => {
+                                    mio.post(0, p).await;
+                                }
+                                Some(_) => {}
+                                None => warn!("PduLengthFilter: received wrong PMT type. {:?}", p),
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .build(),
+            PduLengthFilter::<T> {
+                min,
+                max,
+                _type: std::marker::PhantomData,
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: PduSample> Kernel for PduLengthFilter<T> {}
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+
+use crate::anyhow::Result;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::Tag;
+use crate::runtime::WorkIo;
+
+/// Detect peaks above a threshold.
+pub struct PeakDetector {
+    threshold: f32,
+    look_ahead: usize,
+    tag_crossings: bool,
+    n_received: u64,
+}
+
+impl PeakDetector {
+    pub fn new(threshold: f32, look_ahead: usize, tag_crossings: bool) -> Block {
+        assert!(look_ahead > 0, "look ahead must be positive");
+
+        Block::new(
+            BlockMetaBuilder::new("PeakDetector").build(),
+            StreamIoBuilder::new()
+                .add_input::<f32>("in")
+                .add_output::<f32>("out")
+                .build(),
+            MessageIoBuilder::<PeakDetector>::new()
+                .add_output("peaks")
+                .build(),
+            PeakDetector {
+                threshold,
+                look_ahead,
+                tag_crossings,
+                n_received: 0,
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for PeakDetector {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<f32>();
+        let o = sio.output(0).slice::<f32>();
+
+        let n = std::cmp::min(i.len(), o.len());
+        let finished = sio.input(0).finished() && n == i.len();
+
+        let mut peaks = Vec::new();
+        let mut k = 0;
+        while k < n {
+            if i[k] <= self.threshold {
+                k += 1;
+                continue;
+            }
+
+            // region above the threshold, limited to look ahead samples
+            let start = k;
+            let limit = std::cmp::min(n, start + self.look_ahead);
+            let mut end = start;
+            while end < limit && i[end] > self.threshold {
+                end += 1;
+            }
+            if end == n && end - start < self.look_ahead && !finished {
+                // wait for the end of the region
+                break;
+            }
+
+            let (peak, value) =
+                i[start..end]
+                    .iter()
+                    .enumerate()
+                    .fold((start, f32::MIN), |(p, m), (j, x)| {
+                        if *x > m {
+                            (start + j, *x)
+                        } else {
+                            (p, m)
+                        }
+                    });
+
+            if self.tag_crossings {
+                sio.output(0)
+                    .add_tag(start, Tag::NamedF32("threshold".to_string(), i[start]));
+            }
+            sio.output(0)
+                .add_tag(peak, Tag::NamedF32("peak".to_string(), value));
+            peaks.push((self.n_received + peak as u64, value));
+            k = end;
+        }
+
+        o[..k].copy_from_slice(&i[..k]);
+        sio.input(0).consume(k);
+        sio.output(0).produce(k);
+        self.n_received += k as u64;
+
+        for (offset, value) in peaks {
+            let m = HashMap::from([
+                ("offset".to_string(), Pmt::U64(offset)),
+                ("value".to_string(), Pmt::F32(value)),
+            ]);
+            mio.post(0, Pmt::MapStrPmt(m)).await;
+        }
+
+        if finished && k == n {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Detect peaks above a threshold.
+///
+/// Searches each region, where the input exceeds the `threshold`, for its maximum. Regions longer
+/// than `look_ahead` samples (default 1000) are split. The input is copied to the output, tagging
+/// the peak with [Tag::NamedF32] `peak` and its value and, optionally, the first sample above the
+/// threshold with [Tag::NamedF32] `threshold`.
+///
+/// In addition, each peak is posted as [Pmt::MapStrPmt] with the absolute sample `offset`
+/// ([Pmt::U64]) and the `value` ([Pmt::F32]).
+///
+/// # Inputs
+///
+/// `in`: Input (f32), e.g., correlation magnitude or power
+///
+/// # Outputs
+///
+/// `out`: Tagged input (f32)
+///
+/// **Message**: `peaks`: Detected peaks
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::PeakDetectorBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let peaks = fg.add_block(
+///     PeakDetectorBuilder::new(0.5)
+///         .look_ahead(64)
+///         .tag_crossings()
+///         .build(),
+/// );
+/// ```
+pub struct PeakDetectorBuilder {
+    threshold: f32,
+    look_ahead: usize,
+    tag_crossings: bool,
+}
+
+impl PeakDetectorBuilder {
+    pub fn new(threshold: f32) -> PeakDetectorBuilder {
+        PeakDetectorBuilder {
+            threshold,
+            look_ahead: 1000,
+            tag_crossings: false,
+        }
+    }
+
+    /// Maximum number of samples searched for a peak.
+    #[must_use]
+    pub fn look_ahead(mut self, look_ahead: usize) -> PeakDetectorBuilder {
+        self.look_ahead = look_ahead;
+        self
+    }
+
+    /// Also tag the threshold crossings.
+    #[must_use]
+    pub fn tag_crossings(mut self) -> PeakDetectorBuilder {
+        self.tag_crossings = true;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        PeakDetector::new(self.threshold, self.look_ahead, self.tag_crossings)
+    }
+}
@@ -0,0 +1,237 @@
+use futures::FutureExt;
+use std::ops::{Add, Mul, Sub};
+
+use crate::anyhow::{bail, Result};
+use crate::firdes;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+const DEFAULT_FILTERS: usize = 32;
+
+/// Polyphase arbitrary-rate resampler.
+///
+/// Resamples the input stream by an arbitrary (non-rational) factor `rate` (output rate / input
+/// rate), using a bank of `nfilts` polyphase filters with linear interpolation between adjacent
+/// filters.
+///
+/// If no taps are given, the prototype filter is designed for the rate and redesigned when the
+/// rate is changed.
+///
+/// # Inputs
+///
+/// `in`: Input
+///
+/// `rate`: Message to set the resampling rate (`F32` or `F64`). Returns the current rate.
+///
+/// # Outputs
+///
+/// `out`: Resampled output
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::PfbArbResampler;
+/// use futuresdr::num_complex::Complex32;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // 2.4 Msps -> 1.92 Msps
+/// let resampler = fg.add_block(PfbArbResampler::<Complex32>::new(1.92e6 / 2.4e6));
+/// ```
+pub struct PfbArbResampler<T> {
+    rate: f64,
+    nfilts: usize,
+    taps: Vec<Vec<f32>>,
+    custom_taps: bool,
+    int_rate: usize,
+    flt_rate: f64,
+    last_filter: usize,
+    acc: f64,
+    _p: std::marker::PhantomData<T>,
+}
+
+impl<T> PfbArbResampler<T>
+where
+    T: Copy + Send + Default + Add<Output = T> + Sub<Output = T> + Mul<f32, Output = T> + 'static,
+{
+    /// Create a resampler, designing a suitable low-pass prototype filter.
+    pub fn new(rate: f64) -> Block {
+        Self::create(
+            rate,
+            DEFAULT_FILTERS,
+            Self::design(rate, DEFAULT_FILTERS),
+            false,
+        )
+    }
+
+    /// Create a resampler with a bank of `nfilts` filters, using the given prototype filter
+    /// `taps`, designed for a sample rate of `nfilts` times the input rate.
+    pub fn with_taps(rate: f64, nfilts: usize, taps: Vec<f32>) -> Block {
+        Self::create(rate, nfilts, taps, true)
+    }
+
+    fn create(rate: f64, nfilts: usize, taps: Vec<f32>, custom_taps: bool) -> Block {
+        assert!(rate > 0.0, "rate must be positive");
+        assert!(nfilts > 0, "number of filters must be positive");
+
+        let mut r = PfbArbResampler {
+            rate,
+            nfilts,
+            taps: Vec::new(),
+            custom_taps,
+            int_rate: 0,
+            flt_rate: 0.0,
+            last_filter: 0,
+            acc: 0.0,
+            _p: std::marker::PhantomData,
+        };
+        r.set_taps(&taps);
+        r.set_rate(rate);
+
+        Block::new(
+            BlockMetaBuilder::new("PfbArbResampler").build(),
+            StreamIoBuilder::new()
+                .add_input::<T>("in")
+                .add_output::<T>("out")
+                .build(),
+            MessageIoBuilder::<Self>::new()
+                .add_input(
+                    "rate",
+                    |block: &mut Self,
+                     _mio: &mut MessageIo<Self>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            match p {
+                                Pmt::F32(r) => block.update_rate(r as f64)?,
+                                Pmt::F64(r) => block.update_rate(r)?,
+                                Pmt::Null => {}
+                                _ => bail!("rate has to be F32 or F64"),
+                            }
+                            Ok(Pmt::F64(block.rate))
+                        }
+                        .boxed()
+                    },
+                )
+                .build(),
+            r,
+        )
+    }
+
+    /// Design the prototype filter for the given rate, like GNU Radio does.
+    fn design(rate: f64, nfilts: usize) -> Vec<f32> {
+        let halfband = 0.5 * rate.min(1.0);
+        let bw = 0.4 * halfband / nfilts as f64;
+        let tb = 0.4 * halfband / nfilts as f64;
+        firdes::kaiser::lowpass::<f32>(bw, tb, 0.0001)
+            .into_iter()
+            .map(|t| t * nfilts as f32)
+            .collect()
+    }
+
+    fn update_rate(&mut self, rate: f64) -> Result<()> {
+        if rate <= 0.0 || !rate.is_finite() {
+            bail!("invalid rate {}", rate);
+        }
+        if !self.custom_taps {
+            let taps = Self::design(rate, self.nfilts);
+            self.set_taps(&taps);
+        }
+        self.set_rate(rate);
+        Ok(())
+    }
+
+    fn set_rate(&mut self, rate: f64) {
+        let step = self.nfilts as f64 / rate;
+        self.rate = rate;
+        self.int_rate = step.floor() as usize;
+        self.flt_rate = step - step.floor();
+    }
+
+    /// Split the prototype filter into the polyphase filter bank.
+    fn set_taps(&mut self, taps: &[f32]) {
+        let n = (taps.len() + self.nfilts - 1) / self.nfilts;
+        self.taps = (0..self.nfilts)
+            .map(|k| {
+                (0..n)
+                    .map(|t| taps.get(k + t * self.nfilts).copied().unwrap_or(0.0))
+                    .collect()
+            })
+            .collect();
+    }
+
+    /// Apply filter `k` with newest input sample `i[len - 1]`.
+    fn filter(&self, k: usize, i: &[T]) -> T {
+        let taps = &self.taps[k];
+        let n = taps.len();
+        let mut sum = T::default();
+        for (t, tap) in taps.iter().enumerate() {
+            sum = sum + i[n - 1 - t] * *tap;
+        }
+        sum
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T> Kernel for PfbArbResampler<T>
+where
+    T: Copy + Send + Default + Add<Output = T> + Sub<Output = T> + Mul<f32, Output = T> + 'static,
+{
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<T>();
+        let o = sio.output(0).slice::<T>();
+        let n = self.taps[0].len();
+
+        let mut consumed = 0;
+        let mut produced = 0;
+        loop {
+            while self.last_filter >= self.nfilts && consumed < i.len() {
+                self.last_filter -= self.nfilts;
+                consumed += 1;
+            }
+            // the filter following the last one is the first one, applied to the next sample
+            if self.last_filter >= self.nfilts || consumed + n + 1 > i.len() {
+                if sio.input(0).finished() {
+                    io.finished = true;
+                }
+                break;
+            }
+            if produced == o.len() {
+                break;
+            }
+
+            let o0 = self.filter(self.last_filter, &i[consumed..]);
+            let o1 = if self.last_filter + 1 < self.nfilts {
+                self.filter(self.last_filter + 1, &i[consumed..])
+            } else {
+                self.filter(0, &i[consumed + 1..])
+            };
+            o[produced] = o0 + (o1 - o0) * self.acc as f32;
+            produced += 1;
+
+            self.acc += self.flt_rate;
+            self.last_filter += self.int_rate + self.acc.floor() as usize;
+            self.acc = self.acc.fract();
+        }
+
+        sio.input(0).consume(consumed);
+        sio.output(0).produce(produced);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,453 @@
+use std::io::ErrorKind;
+use std::io::Read;
+use std::io::Write;
+
+use crate::anyhow::{bail, Result};
+use crate::blocks::file_sink::Encoder;
+use crate::blocks::file_source::Decoder;
+use crate::blocks::FileFormat;
+use crate::blocks::FileSample;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// End of a pipe, i.e., a named pipe (or other file) or stdin/stdout.
+#[derive(Debug, Clone)]
+enum Endpoint {
+    Stdio,
+    Path { path: String, create: bool },
+}
+
+impl Endpoint {
+    /// Create the named pipe, if it does not exist.
+    #[cfg(unix)]
+    fn mkfifo(path: &str) -> Result<()> {
+        let c = std::ffi::CString::new(path)?;
+        if unsafe { libc::mkfifo(c.as_ptr(), 0o600) } < 0 {
+            let e = std::io::Error::last_os_error();
+            if e.kind() != ErrorKind::AlreadyExists {
+                bail!("creating named pipe {} failed ({})", path, e);
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    fn mkfifo(path: &str) -> Result<()> {
+        bail!("creating named pipe {} is only supported on Unix", path);
+    }
+
+    /// Open for reading. Blocks for a named pipe, until a writer opens it.
+    fn reader(&self) -> Result<Box<dyn Read + Send>> {
+        match self {
+            Endpoint::Stdio => Ok(Box::new(std::io::stdin())),
+            Endpoint::Path { path, create } => {
+                if *create {
+                    Self::mkfifo(path)?;
+                }
+                Ok(Box::new(std::fs::File::open(path)?))
+            }
+        }
+    }
+
+    /// Open for writing. Blocks for a named pipe, until a reader opens it.
+    fn writer(&self) -> Result<Box<dyn Write + Send>> {
+        match self {
+            Endpoint::Stdio => Ok(Box::new(std::io::stdout())),
+            Endpoint::Path { path, create } => {
+                if *create {
+                    Self::mkfifo(path)?;
+                }
+                Ok(Box::new(
+                    std::fs::OpenOptions::new().write(true).open(path)?,
+                ))
+            }
+        }
+    }
+}
+
+/// Read samples from a named pipe or stdin.
+pub struct PipeSource<T: Send + 'static> {
+    endpoint: Endpoint,
+    reader: Option<Box<dyn Read + Send>>,
+    format: Option<Decoder<T>>,
+    /// Bytes read, but not yet produced.
+    buf: Vec<u8>,
+}
+
+impl<T: Send + 'static> PipeSource<T> {
+    fn create(endpoint: Endpoint, format: Option<Decoder<T>>) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("PipeSource").blocking().build(),
+            StreamIoBuilder::new().add_output::<T>("out").build(),
+            MessageIoBuilder::new().build(),
+            PipeSource::<T> {
+                endpoint,
+                reader: None,
+                format,
+                buf: Vec::new(),
+            },
+        )
+    }
+
+    fn item_size(&self) -> usize {
+        match self.format {
+            Some((f, _)) => f.item_size(),
+            None => std::mem::size_of::<T>(),
+        }
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: Send + 'static> Kernel for PipeSource<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let out = sio.output(0).slice_unchecked::<u8>();
+        let t_size = std::mem::size_of::<T>();
+        let item_size = self.item_size();
+        let n_items = out.len() / t_size;
+
+        if n_items == 0 {
+            return Ok(());
+        }
+
+        if self.buf.len() < item_size {
+            let len = self.buf.len();
+            self.buf.resize(n_items * item_size, 0);
+            let read = loop {
+                match self.reader.as_mut().unwrap().read(&mut self.buf[len..]) {
+                    Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                    r => break r,
+                }
+            };
+            match read {
+                Ok(0) => {
+                    // writer closed the pipe, a trailing partial sample is dropped
+                    self.buf.clear();
+                    io.finished = true;
+                    return Ok(());
+                }
+                Ok(n) => self.buf.truncate(len + n),
+                Err(e) => {
+                    self.buf.truncate(len);
+                    return Err(e.into());
+                }
+            }
+        }
+
+        let items = std::cmp::min(self.buf.len() / item_size, n_items);
+        match self.format {
+            Some((format, convert)) => {
+                let o = sio.output(0).slice::<T>();
+                for (b, y) in self
+                    .buf
+                    .chunks_exact(item_size)
+                    .take(items)
+                    .zip(o.iter_mut())
+                {
+                    *y = convert(format.decode(b));
+                }
+            }
+            None => out[..items * t_size].copy_from_slice(&self.buf[..items * t_size]),
+        }
+        self.buf.drain(..items * item_size);
+        sio.output(0).produce(items);
+
+        // more samples might be buffered already
+        io.call_again = true;
+
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        self.reader = Some(self.endpoint.reader()?);
+        Ok(())
+    }
+}
+
+/// Read raw samples from a named pipe (FIFO) or stdin.
+///
+/// This allows to feed FutureSDR from classic Unix pipelines, e.g.,
+/// `rtl_sdr - | my_app` or `csdr ... | my_app`. Without a [format](PipeSourceBuilder::format),
+/// samples are read in the native format of the stream type. With a format, samples are
+/// converted to the stream type, like with the [FileSourceBuilder](crate::blocks::FileSourceBuilder).
+///
+/// Opening a named pipe blocks until a writer opens it; with [create](PipeSourceBuilder::create),
+/// the pipe is created, if it does not exist. The source terminates, once all writers have
+/// closed the pipe.
+///
+/// # Inputs
+///
+/// No inputs.
+///
+/// # Outputs
+///
+/// `out`: Samples
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::FileFormat;
+/// use futuresdr::blocks::PipeSourceBuilder;
+/// use futuresdr::num_complex::Complex32;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // rtl_sdr -f 100e6 - | my_app
+/// let src = fg.add_block(
+///     PipeSourceBuilder::<Complex32>::stdin()
+///         .format(FileFormat::Cu8)
+///         .build(),
+/// );
+/// ```
+#[cfg_attr(docsrs, doc(cfg(not(target_arch = "wasm32"))))]
+pub struct PipeSourceBuilder<T: Send + 'static> {
+    endpoint: Endpoint,
+    format: Option<Decoder<T>>,
+}
+
+impl<T: Send + 'static> PipeSourceBuilder<T> {
+    /// Read from a named pipe.
+    pub fn new<S: Into<String>>(path: S) -> PipeSourceBuilder<T> {
+        PipeSourceBuilder {
+            endpoint: Endpoint::Path {
+                path: path.into(),
+                create: false,
+            },
+            format: None,
+        }
+    }
+
+    /// Read from stdin.
+    pub fn stdin() -> PipeSourceBuilder<T> {
+        PipeSourceBuilder {
+            endpoint: Endpoint::Stdio,
+            format: None,
+        }
+    }
+
+    /// Create the named pipe, if it does not exist (Unix only).
+    #[must_use]
+    pub fn create(mut self, create: bool) -> PipeSourceBuilder<T> {
+        if let Endpoint::Path { create: c, .. } = &mut self.endpoint {
+            *c = create;
+        }
+        self
+    }
+
+    pub fn build(self) -> Block {
+        PipeSource::<T>::create(self.endpoint, self.format)
+    }
+}
+
+impl<T: FileSample> PipeSourceBuilder<T> {
+    /// Sample format in the pipe.
+    #[must_use]
+    pub fn format(mut self, format: FileFormat) -> PipeSourceBuilder<T> {
+        assert!(
+            T::COMPLEX || !format.is_complex(),
+            "complex formats require a complex stream"
+        );
+        self.format = Some((format, T::from_complex));
+        self
+    }
+}
+
+/// Write samples to a named pipe or stdout.
+pub struct PipeSink<T: Send + 'static> {
+    endpoint: Endpoint,
+    writer: Option<Box<dyn Write + Send>>,
+    format: Option<Encoder<T>>,
+    buf: Vec<u8>,
+}
+
+impl<T: Send + 'static> PipeSink<T> {
+    fn create(endpoint: Endpoint, format: Option<Encoder<T>>) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("PipeSink").blocking().build(),
+            StreamIoBuilder::new().add_input::<T>("in").build(),
+            MessageIoBuilder::new().build(),
+            PipeSink::<T> {
+                endpoint,
+                writer: None,
+                format,
+                buf: Vec::new(),
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: Send + 'static> Kernel for PipeSink<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<T>();
+        let n = i.len();
+
+        if n > 0 {
+            let bytes = match self.format {
+                Some((format, convert)) => {
+                    self.buf.resize(n * format.item_size(), 0);
+                    for (x, b) in i.iter().zip(self.buf.chunks_exact_mut(format.item_size())) {
+                        format.encode(convert(x), b);
+                    }
+                    &self.buf[..]
+                }
+                None => sio.input(0).slice_unchecked::<u8>(),
+            };
+
+            let writer = self.writer.as_mut().unwrap();
+            match writer.write_all(bytes).and_then(|_| writer.flush()) {
+                Ok(()) => {}
+                Err(e) if e.kind() == ErrorKind::BrokenPipe => {
+                    // the reader has gone away, e.g., the next tool in the pipeline terminated
+                    info!("PipeSink: reader closed the pipe");
+                    io.finished = true;
+                    return Ok(());
+                }
+                Err(e) => return Err(e.into()),
+            }
+            sio.input(0).consume(n);
+        }
+
+        if sio.input(0).finished() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        self.writer = Some(self.endpoint.writer()?);
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        // close the pipe, so that the reader sees the end of the stream
+        self.writer = None;
+        Ok(())
+    }
+}
+
+/// Write raw samples to a named pipe (FIFO) or stdout.
+///
+/// This allows to feed classic Unix tools from FutureSDR, e.g., `my_app | dump1090 --ifile -`
+/// or `my_app | csdr ...`. Without a [format](PipeSinkBuilder::format), samples are written in
+/// the native format of the stream type. With a format, samples are converted, like with the
+/// [FileSinkBuilder](crate::blocks::FileSinkBuilder). Every chunk is flushed immediately.
+///
+/// Opening a named pipe blocks until a reader opens it; with [create](PipeSinkBuilder::create),
+/// the pipe is created, if it does not exist. The sink terminates, when the input is done or
+/// the reader closes the pipe.
+///
+/// When writing to stdout, log messages should go to stderr, which is the default.
+///
+/// # Inputs
+///
+/// `in`: Samples
+///
+/// # Outputs
+///
+/// No outputs.
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::FileFormat;
+/// use futuresdr::blocks::PipeSinkBuilder;
+/// use futuresdr::num_complex::Complex32;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // my_app | dump1090 --ifile - --iformat UC8
+/// let snk = fg.add_block(
+///     PipeSinkBuilder::<Complex32>::stdout()
+///         .format(FileFormat::Cu8)
+///         .build(),
+/// );
+/// ```
+#[cfg_attr(docsrs, doc(cfg(not(target_arch = "wasm32"))))]
+pub struct PipeSinkBuilder<T: Send + 'static> {
+    endpoint: Endpoint,
+    format: Option<Encoder<T>>,
+}
+
+impl<T: Send + 'static> PipeSinkBuilder<T> {
+    /// Write to a named pipe.
+    pub fn new<S: Into<String>>(path: S) -> PipeSinkBuilder<T> {
+        PipeSinkBuilder {
+            endpoint: Endpoint::Path {
+                path: path.into(),
+                create: false,
+            },
+            format: None,
+        }
+    }
+
+    /// Write to stdout.
+    pub fn stdout() -> PipeSinkBuilder<T> {
+        PipeSinkBuilder {
+            endpoint: Endpoint::Stdio,
+            format: None,
+        }
+    }
+
+    /// Create the named pipe, if it does not exist (Unix only).
+    #[must_use]
+    pub fn create(mut self, create: bool) -> PipeSinkBuilder<T> {
+        if let Endpoint::Path { create: c, .. } = &mut self.endpoint {
+            *c = create;
+        }
+        self
+    }
+
+    pub fn build(self) -> Block {
+        PipeSink::<T>::create(self.endpoint, self.format)
+    }
+}
+
+impl<T: FileSample> PipeSinkBuilder<T> {
+    /// Sample format in the pipe.
+    #[must_use]
+    pub fn format(mut self, format: FileFormat) -> PipeSinkBuilder<T> {
+        assert!(
+            T::COMPLEX || !format.is_complex(),
+            "complex formats require a complex stream"
+        );
+        self.format = Some((format, T::to_complex));
+        self
+    }
+}
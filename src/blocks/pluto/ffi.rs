@@ -0,0 +1,64 @@
+//! Bindings of the used subset of libiio.
+//!
+//! The library is linked by the build script, statically if `IIO_STATIC` is set.
+use std::os::raw::{c_char, c_double, c_int, c_longlong, c_uint, c_void};
+
+#[repr(C)]
+pub struct iio_context {
+    _private: [u8; 0],
+}
+
+#[repr(C)]
+pub struct iio_device {
+    _private: [u8; 0],
+}
+
+#[repr(C)]
+pub struct iio_channel {
+    _private: [u8; 0],
+}
+
+#[repr(C)]
+pub struct iio_buffer {
+    _private: [u8; 0],
+}
+
+extern "C" {
+    pub fn iio_create_context_from_uri(uri: *const c_char) -> *mut iio_context;
+    pub fn iio_context_destroy(ctx: *mut iio_context);
+    pub fn iio_context_find_device(ctx: *const iio_context, name: *const c_char)
+        -> *mut iio_device;
+    pub fn iio_device_find_channel(
+        dev: *const iio_device,
+        name: *const c_char,
+        output: bool,
+    ) -> *mut iio_channel;
+    pub fn iio_device_set_kernel_buffers_count(dev: *const iio_device, nb_buffers: c_uint)
+        -> c_int;
+    pub fn iio_device_create_buffer(
+        dev: *const iio_device,
+        samples_count: usize,
+        cyclic: bool,
+    ) -> *mut iio_buffer;
+    pub fn iio_channel_attr_write(
+        chn: *const iio_channel,
+        attr: *const c_char,
+        src: *const c_char,
+    ) -> isize;
+    pub fn iio_channel_attr_write_longlong(
+        chn: *const iio_channel,
+        attr: *const c_char,
+        val: c_longlong,
+    ) -> c_int;
+    pub fn iio_channel_attr_write_double(
+        chn: *const iio_channel,
+        attr: *const c_char,
+        val: c_double,
+    ) -> c_int;
+    pub fn iio_channel_enable(chn: *mut iio_channel);
+    pub fn iio_buffer_destroy(buf: *mut iio_buffer);
+    pub fn iio_buffer_refill(buf: *mut iio_buffer) -> isize;
+    pub fn iio_buffer_push(buf: *mut iio_buffer) -> isize;
+    pub fn iio_buffer_start(buf: *const iio_buffer) -> *mut c_void;
+    pub fn iio_buffer_end(buf: *const iio_buffer) -> *mut c_void;
+}
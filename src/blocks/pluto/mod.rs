@@ -0,0 +1,281 @@
+//! ## ADALM-Pluto Blocks (requires `pluto` feature)
+//!
+//! [ADALM-Pluto](https://wiki.analog.com/university/tools/pluto) blocks, using
+//! [libiio](https://wiki.analog.com/resources/tools-software/linux-software/libiio) directly to
+//! configure the AD936x and stream samples. Set `IIO_STATIC` at build time to link libiio
+//! statically.
+use std::ffi::CString;
+use std::marker::PhantomData;
+
+use crate::anyhow::{bail, Result};
+use crate::runtime::Pmt;
+
+#[allow(non_camel_case_types)]
+mod ffi;
+
+mod sink;
+pub use self::sink::{PlutoSink, PlutoSinkBuilder};
+mod source;
+pub use self::source::{PlutoSource, PlutoSourceBuilder};
+
+/// Configuration, shared by [PlutoSourceBuilder] and [PlutoSinkBuilder].
+#[derive(Debug, Clone)]
+struct PlutoConfig {
+    uri: String,
+    frequency: f64,
+    sample_rate: f64,
+    bandwidth: Option<f64>,
+    gain: Option<f64>,
+    rf_port: Option<String>,
+    buffer_size: usize,
+    kernel_buffers: Option<u32>,
+}
+
+impl Default for PlutoConfig {
+    fn default() -> Self {
+        PlutoConfig {
+            uri: "ip:192.168.2.1".to_string(),
+            frequency: 2.4e9,
+            sample_rate: 2e6,
+            bandwidth: None,
+            gain: None,
+            rf_port: None,
+            buffer_size: 32768,
+            kernel_buffers: None,
+        }
+    }
+}
+
+fn pmt_to_f64(p: &Pmt) -> Option<f64> {
+    match p {
+        Pmt::F32(v) => Some(*v as f64),
+        Pmt::F64(v) => Some(*v),
+        Pmt::U32(v) => Some(*v as f64),
+        Pmt::U64(v) => Some(*v as f64),
+        _ => None,
+    }
+}
+
+fn cstr(s: &str) -> CString {
+    CString::new(s).unwrap()
+}
+
+/// AD936x transceiver and streaming device of one direction.
+struct PlutoDevice {
+    tx: bool,
+    ctx: *mut ffi::iio_context,
+    /// Channel of the transceiver, configuring sample rate, bandwidth, gain, and RF port.
+    phy: *mut ffi::iio_channel,
+    lo: *mut ffi::iio_channel,
+    stream: *mut ffi::iio_device,
+    buf: *mut ffi::iio_buffer,
+}
+
+// the device is only used by the block that owns it
+unsafe impl Send for PlutoDevice {}
+
+impl PlutoDevice {
+    fn open(config: &PlutoConfig, tx: bool) -> Result<PlutoDevice> {
+        let uri = cstr(&config.uri);
+        let ctx = unsafe { ffi::iio_create_context_from_uri(uri.as_ptr()) };
+        if ctx.is_null() {
+            bail!("Pluto: cannot create context for {}", config.uri);
+        }
+        let mut dev = PlutoDevice {
+            tx,
+            ctx,
+            phy: std::ptr::null_mut(),
+            lo: std::ptr::null_mut(),
+            stream: std::ptr::null_mut(),
+            buf: std::ptr::null_mut(),
+        };
+
+        unsafe {
+            let phy = ffi::iio_context_find_device(ctx, cstr("ad9361-phy").as_ptr());
+            let stream_name = if tx {
+                "cf-ad9361-dds-core-lpc"
+            } else {
+                "cf-ad9361-lpc"
+            };
+            dev.stream = ffi::iio_context_find_device(ctx, cstr(stream_name).as_ptr());
+            if phy.is_null() || dev.stream.is_null() {
+                bail!("Pluto: AD936x not found at {}", config.uri);
+            }
+            dev.phy = ffi::iio_device_find_channel(phy, cstr("voltage0").as_ptr(), tx);
+            let lo = if tx { "altvoltage1" } else { "altvoltage0" };
+            dev.lo = ffi::iio_device_find_channel(phy, cstr(lo).as_ptr(), true);
+            if dev.phy.is_null() || dev.lo.is_null() {
+                bail!("Pluto: AD936x channels not found");
+            }
+
+            for name in ["voltage0", "voltage1"] {
+                let c = ffi::iio_device_find_channel(dev.stream, cstr(name).as_ptr(), tx);
+                if c.is_null() {
+                    bail!("Pluto: streaming channel {} not found", name);
+                }
+                ffi::iio_channel_enable(c);
+            }
+        }
+
+        if let Some(port) = &config.rf_port {
+            dev.write_str(dev.phy, "rf_port_select", port)?;
+        }
+        dev.set_sample_rate(config.sample_rate)?;
+        dev.set_bandwidth(config.bandwidth.unwrap_or(config.sample_rate))?;
+        dev.set_frequency(config.frequency)?;
+        dev.set_gain(config.gain)?;
+
+        if let Some(n) = config.kernel_buffers {
+            let ret = unsafe { ffi::iio_device_set_kernel_buffers_count(dev.stream, n) };
+            if ret < 0 {
+                bail!("Pluto: setting kernel buffers count failed ({})", ret);
+            }
+        }
+        dev.buf = unsafe { ffi::iio_device_create_buffer(dev.stream, config.buffer_size, false) };
+        if dev.buf.is_null() {
+            bail!("Pluto: cannot create buffer");
+        }
+
+        Ok(dev)
+    }
+
+    fn write_str(&self, chn: *mut ffi::iio_channel, attr: &str, value: &str) -> Result<()> {
+        let ret =
+            unsafe { ffi::iio_channel_attr_write(chn, cstr(attr).as_ptr(), cstr(value).as_ptr()) };
+        if ret < 0 {
+            bail!("Pluto: writing {}={} failed ({})", attr, value, ret);
+        }
+        Ok(())
+    }
+
+    fn write_i64(&self, chn: *mut ffi::iio_channel, attr: &str, value: i64) -> Result<()> {
+        let ret = unsafe { ffi::iio_channel_attr_write_longlong(chn, cstr(attr).as_ptr(), value) };
+        if ret < 0 {
+            bail!("Pluto: writing {}={} failed ({})", attr, value, ret);
+        }
+        Ok(())
+    }
+
+    fn set_frequency(&self, frequency: f64) -> Result<()> {
+        self.write_i64(self.lo, "frequency", frequency as i64)
+    }
+
+    fn set_sample_rate(&self, sample_rate: f64) -> Result<()> {
+        self.write_i64(self.phy, "sampling_frequency", sample_rate as i64)
+    }
+
+    fn set_bandwidth(&self, bandwidth: f64) -> Result<()> {
+        self.write_i64(self.phy, "rf_bandwidth", bandwidth as i64)
+    }
+
+    /// Set the gain (RX) or the negative attenuation (TX) in dB.
+    ///
+    /// Without a gain, the RX AGC is enabled and the TX attenuation is set to 10 dB.
+    fn set_gain(&self, gain: Option<f64>) -> Result<()> {
+        if !self.tx {
+            let mode = if gain.is_some() {
+                "manual"
+            } else {
+                "slow_attack"
+            };
+            self.write_str(self.phy, "gain_control_mode", mode)?;
+            if gain.is_none() {
+                return Ok(());
+            }
+        }
+        let gain = gain.unwrap_or(-10.0);
+        let ret = unsafe {
+            ffi::iio_channel_attr_write_double(self.phy, cstr("hardwaregain").as_ptr(), gain)
+        };
+        if ret < 0 {
+            bail!("Pluto: setting gain {} failed ({})", gain, ret);
+        }
+        Ok(())
+    }
+
+    /// Interleaved I/Q samples of the buffer.
+    fn samples(&mut self) -> &mut [i16] {
+        unsafe {
+            let start = ffi::iio_buffer_start(self.buf) as *mut i16;
+            let end = ffi::iio_buffer_end(self.buf) as *mut i16;
+            std::slice::from_raw_parts_mut(start, end.offset_from(start) as usize)
+        }
+    }
+}
+
+impl Drop for PlutoDevice {
+    fn drop(&mut self) {
+        unsafe {
+            if !self.buf.is_null() {
+                ffi::iio_buffer_destroy(self.buf);
+            }
+            ffi::iio_context_destroy(self.ctx);
+        }
+    }
+}
+
+/// A generic builder that is used for both [PlutoSource] and [PlutoSink].
+pub struct PlutoBuilder<T> {
+    config: PlutoConfig,
+    _phantom: PhantomData<T>,
+}
+
+impl<T> PlutoBuilder<T> {
+    /// [libiio URI](https://analogdevicesinc.github.io/libiio/master/libiio/index.html) of the
+    /// device, e.g., `ip:192.168.2.1` (default) or `usb:1.2.5`.
+    #[must_use]
+    pub fn uri(mut self, uri: impl Into<String>) -> PlutoBuilder<T> {
+        self.config.uri = uri.into();
+        self
+    }
+
+    /// LO frequency in Hz.
+    #[must_use]
+    pub fn frequency(mut self, frequency: f64) -> PlutoBuilder<T> {
+        self.config.frequency = frequency;
+        self
+    }
+
+    #[must_use]
+    pub fn sample_rate(mut self, sample_rate: f64) -> PlutoBuilder<T> {
+        self.config.sample_rate = sample_rate;
+        self
+    }
+
+    /// RF bandwidth in Hz. Defaults to the sample rate.
+    #[must_use]
+    pub fn bandwidth(mut self, bandwidth: f64) -> PlutoBuilder<T> {
+        self.config.bandwidth = Some(bandwidth);
+        self
+    }
+
+    /// RX gain or TX gain (i.e., negative attenuation) in dB.
+    ///
+    /// Defaults to the slow-attack AGC for RX and -10 dB for TX.
+    #[must_use]
+    pub fn gain(mut self, gain: f64) -> PlutoBuilder<T> {
+        self.config.gain = Some(gain);
+        self
+    }
+
+    /// RF port, e.g., `A_BALANCED` for RX or `A` for TX.
+    #[must_use]
+    pub fn rf_port(mut self, port: impl Into<String>) -> PlutoBuilder<T> {
+        self.config.rf_port = Some(port.into());
+        self
+    }
+
+    /// Size of the buffers, exchanged with the device, in samples.
+    #[must_use]
+    pub fn buffer_size(mut self, samples: usize) -> PlutoBuilder<T> {
+        self.config.buffer_size = samples;
+        self
+    }
+
+    /// Number of buffers, queued in the kernel.
+    #[must_use]
+    pub fn kernel_buffers(mut self, n: u32) -> PlutoBuilder<T> {
+        self.config.kernel_buffers = Some(n);
+        self
+    }
+}
@@ -0,0 +1,240 @@
+use futures::FutureExt;
+use std::marker::PhantomData;
+
+use crate::anyhow::{bail, Context, Result};
+use crate::blocks::pluto::{ffi, pmt_to_f64, PlutoBuilder, PlutoConfig, PlutoDevice};
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Full scale of the 12-bit DAC, whose samples are MSB-aligned.
+const SCALE: f32 = 2047.0 * 16.0;
+
+/// Transmit samples with an ADALM-Pluto.
+pub struct PlutoSink {
+    config: PlutoConfig,
+    dev: Option<PlutoDevice>,
+    /// Position of the next I/Q value in the buffer.
+    pos: usize,
+}
+
+impl PlutoSink {
+    fn new(config: PlutoConfig) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("PlutoSink").blocking().build(),
+            StreamIoBuilder::new().add_input::<Complex32>("in").build(),
+            MessageIoBuilder::new()
+                .add_input(
+                    "freq",
+                    |block: &mut PlutoSink,
+                     _mio: &mut MessageIo<PlutoSink>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            match pmt_to_f64(&p) {
+                                Some(f) => {
+                                    block.config.frequency = f;
+                                    if let Some(dev) = &block.dev {
+                                        dev.set_frequency(f)?;
+                                    }
+                                }
+                                None => warn!("PlutoSink/freq: received wrong PMT type. {:?}", p),
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .add_input(
+                    "gain",
+                    |block: &mut PlutoSink,
+                     _mio: &mut MessageIo<PlutoSink>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            let gain = match (&p, pmt_to_f64(&p)) {
+                                (_, Some(g)) => Some(Some(g)),
+                                (Pmt::Null, _) => Some(None),
+                                _ => None,
+                            };
+                            match gain {
+                                Some(g) => {
+                                    block.config.gain = g;
+                                    if let Some(dev) = &block.dev {
+                                        dev.set_gain(g)?;
+                                    }
+                                }
+                                None => warn!("PlutoSink/gain: received wrong PMT type. {:?}", p),
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .add_input(
+                    "sample_rate",
+                    |block: &mut PlutoSink,
+                     _mio: &mut MessageIo<PlutoSink>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            match pmt_to_f64(&p) {
+                                Some(s) => {
+                                    block.config.sample_rate = s;
+                                    if let Some(dev) = &block.dev {
+                                        dev.set_sample_rate(s)?;
+                                        if block.config.bandwidth.is_none() {
+                                            dev.set_bandwidth(s)?;
+                                        }
+                                    }
+                                }
+                                None => {
+                                    warn!("PlutoSink/sample_rate: received wrong PMT type. {:?}", p)
+                                }
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .build(),
+            PlutoSink {
+                config,
+                dev: None,
+                pos: 0,
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for PlutoSink {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let input = sio.input(0).slice::<Complex32>();
+        let finished = sio.input(0).finished();
+        let dev = self.dev.as_mut().context("no device")?;
+        let samples = dev.samples();
+
+        let n = std::cmp::min(input.len(), (samples.len() - self.pos) / 2);
+        for (iq, i) in samples[self.pos..self.pos + 2 * n]
+            .chunks_exact_mut(2)
+            .zip(input.iter())
+        {
+            iq[0] = (i.re * SCALE) as i16;
+            iq[1] = (i.im * SCALE) as i16;
+        }
+        self.pos += 2 * n;
+        sio.input(0).consume(n);
+
+        let last = finished && n == input.len();
+        if self.pos > 0 && (self.pos == samples.len() || last) {
+            // the buffer is always sent completely, so pad the last one with zeros
+            samples[self.pos..].fill(0);
+            let ret = unsafe { ffi::iio_buffer_push(dev.buf) };
+            if ret < 0 {
+                bail!("PlutoSink: pushing buffer failed ({})", ret);
+            }
+            self.pos = 0;
+        }
+
+        if last {
+            io.finished = true;
+        } else if n > 0 {
+            io.call_again = true;
+        }
+
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        self.dev = Some(PlutoDevice::open(&self.config, true)?);
+        info!("PlutoSink: opened {}", self.config.uri);
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        self.dev = None;
+        Ok(())
+    }
+}
+
+/// Build a [PlutoSink].
+///
+/// The sink collects [buffer_size](PlutoBuilder::buffer_size) samples and pushes them to the
+/// device, blocking until there is space in the kernel buffers. Samples are expected to be in
+/// `[-1, 1)`. When the input is finished, the last buffer is padded with zeros.
+///
+/// # Inputs
+///
+/// `in`: Samples to transmit
+///
+/// **Message**: `freq`: LO frequency in Hz
+///
+/// **Message**: `gain`: Gain (i.e., negative attenuation) in dB
+///
+/// **Message**: `sample_rate`: Sample rate
+///
+/// # Outputs
+///
+/// No outputs.
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::pluto::PlutoSinkBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let snk = fg.add_block(
+///     PlutoSinkBuilder::new()
+///         .frequency(433.92e6)
+///         .sample_rate(4e6)
+///         .gain(-20.0)
+///         .build(),
+/// );
+/// ```
+pub type PlutoSinkBuilder = PlutoBuilder<PlutoSink>;
+
+impl PlutoBuilder<PlutoSink> {
+    pub fn new() -> Self {
+        Self {
+            config: PlutoConfig::default(),
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn build(self) -> Block {
+        PlutoSink::new(self.config)
+    }
+}
+
+impl Default for PlutoBuilder<PlutoSink> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,240 @@
+use futures::FutureExt;
+use std::marker::PhantomData;
+
+use crate::anyhow::{bail, Context, Result};
+use crate::blocks::pluto::{ffi, pmt_to_f64, PlutoBuilder, PlutoConfig, PlutoDevice};
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Full scale of the 12-bit ADC.
+const SCALE: f32 = 2048.0;
+
+/// Receive samples with an ADALM-Pluto.
+pub struct PlutoSource {
+    config: PlutoConfig,
+    dev: Option<PlutoDevice>,
+    /// Position of the next I/Q value in the buffer.
+    pos: usize,
+    /// Number of I/Q values in the buffer.
+    len: usize,
+}
+
+impl PlutoSource {
+    fn new(config: PlutoConfig) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("PlutoSource").blocking().build(),
+            StreamIoBuilder::new()
+                .add_output::<Complex32>("out")
+                .build(),
+            MessageIoBuilder::new()
+                .add_input(
+                    "freq",
+                    |block: &mut PlutoSource,
+                     _mio: &mut MessageIo<PlutoSource>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            match pmt_to_f64(&p) {
+                                Some(f) => {
+                                    block.config.frequency = f;
+                                    if let Some(dev) = &block.dev {
+                                        dev.set_frequency(f)?;
+                                    }
+                                }
+                                None => warn!("PlutoSource/freq: received wrong PMT type. {:?}", p),
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .add_input(
+                    "gain",
+                    |block: &mut PlutoSource,
+                     _mio: &mut MessageIo<PlutoSource>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            let gain = match (&p, pmt_to_f64(&p)) {
+                                (_, Some(g)) => Some(Some(g)),
+                                (Pmt::Null, _) => Some(None),
+                                _ => None,
+                            };
+                            match gain {
+                                Some(g) => {
+                                    block.config.gain = g;
+                                    if let Some(dev) = &block.dev {
+                                        dev.set_gain(g)?;
+                                    }
+                                }
+                                None => warn!("PlutoSource/gain: received wrong PMT type. {:?}", p),
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .add_input(
+                    "sample_rate",
+                    |block: &mut PlutoSource,
+                     _mio: &mut MessageIo<PlutoSource>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            match pmt_to_f64(&p) {
+                                Some(s) => {
+                                    block.config.sample_rate = s;
+                                    if let Some(dev) = &block.dev {
+                                        dev.set_sample_rate(s)?;
+                                        if block.config.bandwidth.is_none() {
+                                            dev.set_bandwidth(s)?;
+                                        }
+                                    }
+                                }
+                                None => warn!(
+                                    "PlutoSource/sample_rate: received wrong PMT type. {:?}",
+                                    p
+                                ),
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .build(),
+            PlutoSource {
+                config,
+                dev: None,
+                pos: 0,
+                len: 0,
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for PlutoSource {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let out = sio.output(0).slice::<Complex32>();
+        if out.is_empty() {
+            return Ok(());
+        }
+        let dev = self.dev.as_mut().context("no device")?;
+
+        if self.pos == self.len {
+            let ret = unsafe { ffi::iio_buffer_refill(dev.buf) };
+            if ret < 0 {
+                bail!("PlutoSource: refilling buffer failed ({})", ret);
+            }
+            self.len = dev.samples().len();
+            self.pos = 0;
+        }
+
+        let samples = dev.samples();
+        let n = std::cmp::min(out.len(), (self.len - self.pos) / 2);
+        for (o, iq) in out
+            .iter_mut()
+            .zip(samples[self.pos..self.pos + 2 * n].chunks_exact(2))
+        {
+            *o = Complex32::new(iq[0] as f32 / SCALE, iq[1] as f32 / SCALE);
+        }
+        self.pos += 2 * n;
+        sio.output(0).produce(n);
+        io.call_again = true;
+
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        self.dev = Some(PlutoDevice::open(&self.config, false)?);
+        info!("PlutoSource: opened {}", self.config.uri);
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        self.dev = None;
+        Ok(())
+    }
+}
+
+/// Build a [PlutoSource].
+///
+/// The source refills a buffer of [buffer_size](PlutoBuilder::buffer_size) samples at a time,
+/// blocking until the device has filled it. Samples are scaled to `[-1, 1)`.
+///
+/// # Inputs
+///
+/// **Message**: `freq`: LO frequency in Hz
+///
+/// **Message**: `gain`: Gain in dB, [Pmt::Null] for AGC
+///
+/// **Message**: `sample_rate`: Sample rate
+///
+/// # Outputs
+///
+/// `out`: Received samples
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::pluto::PlutoSourceBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let src = fg.add_block(
+///     PlutoSourceBuilder::new()
+///         .uri("usb:")
+///         .frequency(433.92e6)
+///         .sample_rate(4e6)
+///         .gain(40.0)
+///         .rf_port("A_BALANCED")
+///         .kernel_buffers(8)
+///         .build(),
+/// );
+/// ```
+pub type PlutoSourceBuilder = PlutoBuilder<PlutoSource>;
+
+impl PlutoBuilder<PlutoSource> {
+    pub fn new() -> Self {
+        Self {
+            config: PlutoConfig::default(),
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn build(self) -> Block {
+        PlutoSource::new(self.config)
+    }
+}
+
+impl Default for PlutoBuilder<PlutoSource> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
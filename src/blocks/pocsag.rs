@@ -0,0 +1,260 @@
+use std::collections::HashMap;
+
+use crate::anyhow::Result;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+const SYNC: u32 = 0x7cd2_15d8;
+const IDLE: u32 = 0x7a89_c197;
+/// Generator polynomial of the BCH(31, 21) code.
+const BCH_POLY: u32 = 0x769;
+/// Codewords per batch.
+const BATCH_LEN: usize = 16;
+/// Maximum number of bit errors in a sync codeword.
+const SYNC_ERRORS: u32 = 2;
+
+/// Check the BCH(31, 21) code and the even parity of a codeword.
+fn codeword_valid(cw: u32) -> bool {
+    let mut reg = cw >> 1;
+    for i in (10..31).rev() {
+        if reg & (1 << i) != 0 {
+            reg ^= BCH_POLY << (i - 10);
+        }
+    }
+    reg == 0 && cw.count_ones() % 2 == 0
+}
+
+/// Correct up to one bit error of a codeword.
+fn correct(cw: u32) -> Option<u32> {
+    if codeword_valid(cw) {
+        return Some(cw);
+    }
+    (0..32).map(|i| cw ^ (1 << i)).find(|c| codeword_valid(*c))
+}
+
+/// Message in reception.
+struct Message {
+    address: u32,
+    function: u32,
+    bits: Vec<u8>,
+}
+
+impl Message {
+    fn decode(self) -> Pmt {
+        let mut m = HashMap::new();
+        m.insert("address".to_string(), Pmt::U64(self.address as u64));
+        m.insert("function".to_string(), Pmt::U32(self.function));
+
+        let (kind, text) = if self.bits.is_empty() {
+            ("tone", String::new())
+        } else if self.function == 0 {
+            const DIGITS: &[u8] = b"0123456789*U -)(";
+            let text: String = self
+                .bits
+                .chunks_exact(4)
+                .map(|c| DIGITS[c.iter().rev().fold(0, |acc, b| (acc << 1) | *b as usize)] as char)
+                .collect();
+            ("numeric", text.trim_end().to_string())
+        } else {
+            let text: String = self
+                .bits
+                .chunks_exact(7)
+                .map(|c| c.iter().rev().fold(0, |acc, b| (acc << 1) | b) as char)
+                .collect();
+            (
+                "alphanumeric",
+                text.trim_end_matches(|c: char| c.is_ascii_control())
+                    .to_string(),
+            )
+        };
+        m.insert("type".to_string(), Pmt::String(kind.to_string()));
+        m.insert("message".to_string(), Pmt::String(text));
+        Pmt::MapStrPmt(m)
+    }
+}
+
+enum State {
+    /// Search for a sync codeword.
+    Hunt,
+    /// Receive the codewords of a batch.
+    Batch(usize),
+    /// Check for the sync codeword of the next batch.
+    Sync,
+}
+
+/// Decode POCSAG pager messages.
+///
+/// Searches a bit stream for POCSAG sync codewords (with up to two bit errors) and decodes the
+/// following batches. Inverted bit streams are detected automatically. Codewords with a single
+/// bit error are corrected; a codeword that cannot be corrected ends the current message.
+///
+/// The decoder is independent of the bit rate, i.e., it can be used for POCSAG with 512, 1200,
+/// and 2400 baud by setting the bit rate of the demodulator, e.g., an
+/// [FskDemod](crate::blocks::FskDemodBuilder) with two levels. FLEX is not supported.
+///
+/// Messages are posted as [Pmt::MapStrPmt] with the entries
+/// - `address`: 21-bit address of the pager ([Pmt::U64]),
+/// - `function`: function bits 0 to 3 ([Pmt::U32]),
+/// - `type`: `tone` for messages without content, otherwise `numeric` for function 0 and
+///   `alphanumeric` for the others ([Pmt::String]),
+/// - `message`: decoded message ([Pmt::String]).
+///
+/// # Inputs
+///
+/// `in`: Bits (u8)
+///
+/// # Outputs
+///
+/// **Message**: `out`: Pager messages ([Pmt::MapStrPmt])
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::FskDemodBuilder;
+/// use futuresdr::blocks::PocsagDecoder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // POCSAG 1200 at 48 kHz with 4.5 kHz deviation
+/// let demod = fg.add_block(FskDemodBuilder::new(2, 40.0, 0.09375).build());
+/// let pocsag = fg.add_block(PocsagDecoder::new());
+/// fg.connect_stream(demod, "out", pocsag, "in").unwrap();
+/// ```
+pub struct PocsagDecoder {
+    state: State,
+    inverted: bool,
+    reg: u32,
+    n_bits: usize,
+    message: Option<Message>,
+}
+
+impl PocsagDecoder {
+    pub fn new() -> Block {
+        Block::new(
+            BlockMetaBuilder::new("PocsagDecoder").build(),
+            StreamIoBuilder::new().add_input::<u8>("in").build(),
+            MessageIoBuilder::<PocsagDecoder>::new()
+                .add_output("out")
+                .build(),
+            PocsagDecoder {
+                state: State::Hunt,
+                inverted: false,
+                reg: 0,
+                n_bits: 0,
+                message: None,
+            },
+        )
+    }
+
+    fn flush(&mut self, msgs: &mut Vec<Pmt>) {
+        if let Some(m) = self.message.take() {
+            msgs.push(m.decode());
+        }
+    }
+
+    fn codeword(&mut self, index: usize, cw: u32, msgs: &mut Vec<Pmt>) {
+        match correct(cw) {
+            Some(IDLE) | None => self.flush(msgs),
+            Some(cw) if cw & 0x8000_0000 == 0 => {
+                self.flush(msgs);
+                self.message = Some(Message {
+                    address: ((cw >> 13) << 3) | (index / 2) as u32,
+                    function: (cw >> 11) & 0x3,
+                    bits: Vec::new(),
+                });
+            }
+            Some(cw) => {
+                if let Some(ref mut m) = self.message {
+                    m.bits.extend((11..31).rev().map(|i| ((cw >> i) & 1) as u8));
+                }
+            }
+        }
+    }
+
+    fn push(&mut self, bit: u8, msgs: &mut Vec<Pmt>) {
+        self.reg = (self.reg << 1) | (bit & 1) as u32;
+        self.n_bits += 1;
+
+        match self.state {
+            State::Hunt => {
+                if self.n_bits < 32 {
+                    return;
+                }
+                if (self.reg ^ SYNC).count_ones() <= SYNC_ERRORS {
+                    self.inverted = false;
+                } else if (!self.reg ^ SYNC).count_ones() <= SYNC_ERRORS {
+                    self.inverted = true;
+                } else {
+                    return;
+                }
+                self.state = State::Batch(0);
+                self.n_bits = 0;
+            }
+            State::Batch(n) => {
+                if self.n_bits < 32 {
+                    return;
+                }
+                let cw = if self.inverted { !self.reg } else { self.reg };
+                self.codeword(n, cw, msgs);
+                self.state = if n + 1 == BATCH_LEN {
+                    State::Sync
+                } else {
+                    State::Batch(n + 1)
+                };
+                self.n_bits = 0;
+            }
+            State::Sync => {
+                if self.n_bits < 32 {
+                    return;
+                }
+                let cw = if self.inverted { !self.reg } else { self.reg };
+                if (cw ^ SYNC).count_ones() <= SYNC_ERRORS {
+                    self.state = State::Batch(0);
+                    self.n_bits = 0;
+                } else {
+                    self.flush(msgs);
+                    self.state = State::Hunt;
+                }
+            }
+        }
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for PocsagDecoder {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<u8>();
+
+        let mut msgs = Vec::new();
+        for x in i.iter() {
+            self.push(*x, &mut msgs);
+        }
+        sio.input(0).consume(i.len());
+
+        if sio.input(0).finished() {
+            self.flush(&mut msgs);
+            io.finished = true;
+        }
+
+        for m in msgs {
+            mio.post(0, m).await;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,280 @@
+use futures::FutureExt;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Duration, Instant};
+
+use crate::anyhow::Result;
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Sample type of a [SignalProbe].
+pub trait ProbeSample: Copy + Send + 'static {
+    /// Convert a sample to a message.
+    fn to_pmt(&self) -> Pmt;
+    /// Power of the sample.
+    fn power(&self) -> f32;
+}
+
+impl ProbeSample for f32 {
+    fn to_pmt(&self) -> Pmt {
+        Pmt::F32(*self)
+    }
+    fn power(&self) -> f32 {
+        self * self
+    }
+}
+
+/// Complex samples are converted to [Pmt::VecF32] with the real and imaginary part.
+impl ProbeSample for Complex32 {
+    fn to_pmt(&self) -> Pmt {
+        Pmt::VecF32(vec![self.re, self.im])
+    }
+    fn power(&self) -> f32 {
+        self.norm_sqr()
+    }
+}
+
+/// Most recent value and average power of a stream.
+///
+/// Drops the samples, keeping the last one and the power, averaged with a single-pole IIR
+/// filter with gain `alpha`. Both can be queried through message ports, e.g., with a
+/// [callback](crate::runtime::FlowgraphHandle::callback), or from the kernel after the flowgraph
+/// terminated.
+///
+/// # Inputs
+///
+/// `in`: Input (f32 or Complex32)
+///
+/// **Message**: `value`: Return the most recent sample ([Pmt::F32] for f32 and [Pmt::VecF32]
+/// with real and imaginary part for Complex32) or [Pmt::Null], if there was no sample yet
+///
+/// **Message**: `power`: Return the average power in dB ([Pmt::F32]) or [Pmt::Null], if there was
+/// no sample yet
+///
+/// # Outputs
+///
+/// No outputs.
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::SignalProbe;
+/// use futuresdr::num_complex::Complex32;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let probe = fg.add_block(SignalProbe::<Complex32>::new(0.001));
+/// ```
+pub struct SignalProbe<T: ProbeSample> {
+    alpha: f32,
+    value: Option<T>,
+    power: f32,
+}
+
+impl<T: ProbeSample> SignalProbe<T> {
+    pub fn new(alpha: f32) -> Block {
+        assert!(alpha > 0.0 && alpha <= 1.0, "alpha has to be in (0, 1]");
+
+        Block::new(
+            BlockMetaBuilder::new("SignalProbe").build(),
+            StreamIoBuilder::new().add_input::<T>("in").build(),
+            MessageIoBuilder::<Self>::new()
+                .add_input(
+                    "value",
+                    |block: &mut Self,
+                     _mio: &mut MessageIo<Self>,
+                     _meta: &mut BlockMeta,
+                     _p: Pmt| {
+                        async move { Ok(block.value.map_or(Pmt::Null, |v| v.to_pmt())) }.boxed()
+                    },
+                )
+                .add_input(
+                    "power",
+                    |block: &mut Self,
+                     _mio: &mut MessageIo<Self>,
+                     _meta: &mut BlockMeta,
+                     _p: Pmt| {
+                        async move { Ok(block.power_db().map_or(Pmt::Null, Pmt::F32)) }.boxed()
+                    },
+                )
+                .build(),
+            SignalProbe {
+                alpha,
+                value: None,
+                power: 0.0,
+            },
+        )
+    }
+
+    /// Most recent sample.
+    pub fn value(&self) -> Option<T> {
+        self.value
+    }
+
+    /// Average power in dB.
+    pub fn power_db(&self) -> Option<f32> {
+        self.value.map(|_| 10.0 * self.power.log10())
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: ProbeSample> Kernel for SignalProbe<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<T>();
+
+        for x in i.iter() {
+            self.power = match self.value {
+                Some(_) => self.power + self.alpha * (x.power() - self.power),
+                None => x.power(),
+            };
+            self.value = Some(*x);
+        }
+        sio.input(0).consume(i.len());
+
+        if sio.input(0).finished() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Measure the throughput of a stream.
+///
+/// Drops the samples, estimating the rate in samples per second after every `interval`. The
+/// estimates are smoothed with a single-pole IIR filter with gain `alpha` and posted.
+///
+/// # Inputs
+///
+/// `in`: Input
+///
+/// **Message**: `rate`: Return the smoothed rate in samples per second ([Pmt::F64]) or
+/// [Pmt::Null], if there was no estimate yet
+///
+/// # Outputs
+///
+/// **Message**: `out`: Smoothed rate in samples per second ([Pmt::F64]) after every interval
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::RateProbe;
+/// use futuresdr::num_complex::Complex32;
+/// use futuresdr::runtime::Flowgraph;
+/// use std::time::Duration;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let probe = fg.add_block(RateProbe::<Complex32>::new(Duration::from_millis(500), 0.5));
+/// ```
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg_attr(docsrs, doc(cfg(not(target_arch = "wasm32"))))]
+pub struct RateProbe<T: Send + 'static> {
+    interval: Duration,
+    alpha: f64,
+    rate: Option<f64>,
+    items: u64,
+    last: Option<Instant>,
+    n_since: u64,
+    _type: std::marker::PhantomData<T>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<T: Send + 'static> RateProbe<T> {
+    pub fn new(interval: Duration, alpha: f64) -> Block {
+        assert!(alpha > 0.0 && alpha <= 1.0, "alpha has to be in (0, 1]");
+
+        Block::new(
+            BlockMetaBuilder::new("RateProbe").build(),
+            StreamIoBuilder::new().add_input::<T>("in").build(),
+            MessageIoBuilder::<Self>::new()
+                .add_input(
+                    "rate",
+                    |block: &mut Self,
+                     _mio: &mut MessageIo<Self>,
+                     _meta: &mut BlockMeta,
+                     _p: Pmt| {
+                        async move { Ok(block.rate.map_or(Pmt::Null, Pmt::F64)) }.boxed()
+                    },
+                )
+                .add_output("out")
+                .build(),
+            RateProbe::<T> {
+                interval,
+                alpha,
+                rate: None,
+                items: 0,
+                last: None,
+                n_since: 0,
+                _type: std::marker::PhantomData,
+            },
+        )
+    }
+
+    /// Smoothed rate in samples per second.
+    pub fn rate(&self) -> Option<f64> {
+        self.rate
+    }
+
+    /// Total number of received samples.
+    pub fn items(&self) -> u64 {
+        self.items
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[doc(hidden)]
+#[async_trait]
+impl<T: Send + 'static> Kernel for RateProbe<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let n = sio.input(0).slice_unchecked::<u8>().len() / std::mem::size_of::<T>();
+        sio.input(0).consume(n);
+        self.items += n as u64;
+
+        let now = Instant::now();
+        match self.last {
+            None => self.last = Some(now),
+            Some(last) => {
+                self.n_since += n as u64;
+                let elapsed = now.duration_since(last);
+                if elapsed >= self.interval {
+                    let estimate = self.n_since as f64 / elapsed.as_secs_f64();
+                    let rate = match self.rate {
+                        Some(r) => r + self.alpha * (estimate - r),
+                        None => estimate,
+                    };
+                    self.rate = Some(rate);
+                    self.last = Some(now);
+                    self.n_since = 0;
+                    mio.post(0, Pmt::F64(rate)).await;
+                }
+            }
+        }
+
+        if sio.input(0).finished() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
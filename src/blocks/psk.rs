@@ -0,0 +1,474 @@
+use futuredsp::fir::PolyphaseResamplingFirKernel;
+use futuredsp::firdes;
+use futuredsp::UnaryKernel;
+use std::f32::consts::PI;
+
+use crate::anyhow::Result;
+use crate::blocks::costas_loop::Costas;
+use crate::blocks::symbol_sync::interpolation_taps;
+use crate::blocks::symbol_sync::SymbolTiming;
+use crate::blocks::Constellation;
+use crate::blocks::TimingErrorDetector;
+use crate::kernels::FirKernel;
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Maximum number of samples, buffered between stages.
+const BUFFER_SIZE: usize = 1 << 14;
+/// Number of filters of the symbol synchronizer.
+const NFILTS: usize = 32;
+
+/// Differential coding of PSK symbols, encoding symbols as phase changes.
+struct Differential {
+    /// Position of each symbol on the circle.
+    position: Vec<usize>,
+    /// Symbol at each position.
+    symbol: Vec<u8>,
+    last: usize,
+}
+
+impl Differential {
+    fn new(constellation: &Constellation) -> Differential {
+        let points = constellation.points();
+        let step = 2.0 * PI / points.len() as f32;
+        let position: Vec<usize> = points
+            .iter()
+            .map(|p| {
+                let k = ((p.arg() - points[0].arg()) / step).round() as isize;
+                k.rem_euclid(points.len() as isize) as usize
+            })
+            .collect();
+        let mut symbol = vec![0; points.len()];
+        for (s, p) in position.iter().enumerate() {
+            symbol[*p] = s as u8;
+        }
+
+        Differential {
+            position,
+            symbol,
+            last: 0,
+        }
+    }
+
+    fn encode(&mut self, s: u8) -> u8 {
+        self.last = (self.last + self.position[s as usize]) % self.position.len();
+        self.symbol[self.last]
+    }
+
+    fn decode(&mut self, s: u8) -> u8 {
+        let n = self.position.len();
+        let p = self.position[s as usize];
+        let d = (p + n - self.last) % n;
+        self.last = p;
+        self.symbol[d]
+    }
+}
+
+/// Root-raised-cosine taps, padded to a multiple of `sps`.
+fn rrc_taps(sps: usize, rolloff: f32, span: usize) -> Vec<f32> {
+    let mut taps = firdes::root_raised_cosine::<f32>(span, sps, rolloff as f64);
+    let padding = (sps - taps.len() % sps) % sps;
+    taps.resize(taps.len() + padding, 0.0);
+    taps
+}
+
+/// PSK transmitter.
+pub struct PskTx {
+    constellation: Constellation,
+    differential: Option<Differential>,
+    filter: PolyphaseResamplingFirKernel<Complex32, Complex32, Vec<f32>, f32>,
+    symbols: Vec<Complex32>,
+    /// Number of zero symbols to flush the filter.
+    flush: usize,
+    flushed: bool,
+}
+
+impl PskTx {
+    pub fn new(
+        constellation: Constellation,
+        sps: usize,
+        rolloff: f32,
+        span: usize,
+        differential: bool,
+    ) -> Block {
+        assert!(sps >= 2, "at least two samples per symbol required");
+
+        let taps = rrc_taps(sps, rolloff, span);
+        let flush = taps.len() / sps;
+
+        Block::new(
+            BlockMetaBuilder::new("PskTx").build(),
+            StreamIoBuilder::new()
+                .add_input::<u8>("in")
+                .add_output::<Complex32>("out")
+                .build(),
+            MessageIoBuilder::<PskTx>::new().build(),
+            PskTx {
+                differential: differential.then(|| Differential::new(&constellation)),
+                constellation,
+                filter: PolyphaseResamplingFirKernel::new(sps, 1, taps),
+                // start with the complete impulse response of the first symbol
+                symbols: vec![Complex32::new(0.0, 0.0); flush - 1],
+                flush,
+                flushed: false,
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for PskTx {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<u8>();
+        let bps = self.constellation.bits_per_symbol();
+
+        // bits to symbols, most significant bit first
+        let n = std::cmp::min(i.len() / bps, BUFFER_SIZE - self.symbols.len());
+        for bits in i.chunks_exact(bps).take(n) {
+            let mut s = bits.iter().fold(0, |acc, b| (acc << 1) | (b & 1));
+            if let Some(ref mut d) = self.differential {
+                s = d.encode(s);
+            }
+            self.symbols.push(self.constellation.map(s));
+        }
+        sio.input(0).consume(n * bps);
+
+        if sio.input(0).finished() && i.len() - n * bps < bps && !self.flushed {
+            self.symbols
+                .extend(std::iter::repeat(Complex32::new(0.0, 0.0)).take(self.flush));
+            self.flushed = true;
+        }
+
+        // pulse shaping
+        let o = sio.output(0).slice::<Complex32>();
+        let (used, produced, status) = self.filter.work(&self.symbols, o);
+        self.symbols.drain(..used);
+        sio.output(0).produce(produced);
+
+        if self.flushed && status.produced_all_samples() {
+            io.finished = true;
+        } else if n > 0 && produced > 0 {
+            io.call_again = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// PSK transmitter.
+///
+/// Reference transmit chain for BPSK and QPSK:
+/// - bits (one per byte) are grouped to symbols, most significant bit first,
+/// - symbols are differentially encoded (default), which resolves the phase ambiguity of the
+///   receiver,
+/// - symbols are mapped to [Constellation::bpsk] or [Constellation::qpsk],
+/// - symbols are pulse shaped with a root-raised-cosine filter with roll-off factor (default 0.35)
+///   and span in symbols (default 8), outputting `sps` samples per symbol.
+///
+/// The filter is flushed, when the input finishes.
+///
+/// # Inputs
+///
+/// `in`: Bits (u8)
+///
+/// # Outputs
+///
+/// `out`: Baseband samples (Complex32)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::PskTxBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let tx = fg.add_block(PskTxBuilder::qpsk(4).rolloff(0.25).build());
+/// ```
+pub struct PskTxBuilder {
+    constellation: Constellation,
+    sps: usize,
+    rolloff: f32,
+    span: usize,
+    differential: bool,
+}
+
+impl PskTxBuilder {
+    pub fn bpsk(sps: usize) -> PskTxBuilder {
+        Self::new(Constellation::bpsk(), sps)
+    }
+
+    pub fn qpsk(sps: usize) -> PskTxBuilder {
+        Self::new(Constellation::qpsk(), sps)
+    }
+
+    fn new(constellation: Constellation, sps: usize) -> PskTxBuilder {
+        PskTxBuilder {
+            constellation,
+            sps,
+            rolloff: 0.35,
+            span: 8,
+            differential: true,
+        }
+    }
+
+    /// Roll-off factor of the pulse shaping filter.
+    #[must_use]
+    pub fn rolloff(mut self, rolloff: f32) -> PskTxBuilder {
+        self.rolloff = rolloff;
+        self
+    }
+
+    /// Length of the pulse shaping filter in symbols.
+    #[must_use]
+    pub fn span(mut self, span: usize) -> PskTxBuilder {
+        self.span = span;
+        self
+    }
+
+    /// Enable or disable differential encoding.
+    #[must_use]
+    pub fn differential(mut self, differential: bool) -> PskTxBuilder {
+        self.differential = differential;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        PskTx::new(
+            self.constellation,
+            self.sps,
+            self.rolloff,
+            self.span,
+            self.differential,
+        )
+    }
+}
+
+/// PSK receiver.
+pub struct PskRx {
+    constellation: Constellation,
+    differential: Option<Differential>,
+    filter: FirKernel<Complex32>,
+    timing: SymbolTiming,
+    costas: Costas,
+    scratch: Vec<Complex32>,
+    filtered: Vec<Complex32>,
+    symbols: Vec<Complex32>,
+}
+
+impl PskRx {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        constellation: Constellation,
+        sps: usize,
+        rolloff: f32,
+        span: usize,
+        differential: bool,
+        timing_bw: f32,
+        carrier_bw: f32,
+    ) -> Block {
+        assert!(sps >= 2, "at least two samples per symbol required");
+
+        let order = constellation.points().len();
+
+        Block::new(
+            BlockMetaBuilder::new("PskRx").build(),
+            StreamIoBuilder::new()
+                .add_input::<Complex32>("in")
+                .add_output::<u8>("out")
+                .build(),
+            MessageIoBuilder::<PskRx>::new().build(),
+            PskRx {
+                differential: differential.then(|| Differential::new(&constellation)),
+                constellation,
+                filter: FirKernel::new(rrc_taps(sps, rolloff, span)),
+                timing: SymbolTiming::new(
+                    sps as f32,
+                    timing_bw,
+                    1.0,
+                    1.0,
+                    0.015,
+                    TimingErrorDetector::MaximumLikelihood,
+                    NFILTS,
+                    interpolation_taps(NFILTS),
+                ),
+                costas: Costas::new(order, carrier_bw),
+                scratch: vec![Complex32::new(0.0, 0.0); BUFFER_SIZE],
+                filtered: Vec::with_capacity(BUFFER_SIZE),
+                symbols: vec![Complex32::new(0.0, 0.0); BUFFER_SIZE],
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for PskRx {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex32>();
+        let bps = self.constellation.bits_per_symbol();
+
+        // matched filter
+        let space = BUFFER_SIZE - self.filtered.len();
+        let (consumed, filtered, status) = self.filter.work(i, &mut self.scratch[..space]);
+        self.filtered.extend_from_slice(&self.scratch[..filtered]);
+        sio.input(0).consume(consumed);
+
+        // symbol timing
+        let o = sio.output(0).slice::<u8>();
+        let max_symbols = std::cmp::min(o.len() / bps, BUFFER_SIZE);
+        let (used, n) = self
+            .timing
+            .work(&self.filtered, &mut self.symbols[..max_symbols]);
+        self.filtered.drain(..used);
+
+        // carrier recovery, decisions, and symbols to bits
+        for (x, bits) in self.symbols[..n].iter().zip(o.chunks_exact_mut(bps)) {
+            let mut s = self.constellation.decide(self.costas.process(*x));
+            if let Some(ref mut d) = self.differential {
+                s = d.decode(s);
+            }
+            for (k, b) in bits.iter_mut().enumerate() {
+                *b = (s >> (bps - 1 - k)) & 1;
+            }
+        }
+        sio.output(0).produce(n * bps);
+
+        if sio.input(0).finished() && status.produced_all_samples() && n < max_symbols {
+            io.finished = true;
+        } else if filtered > 0 && n > 0 {
+            io.call_again = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// PSK receiver.
+///
+/// Reference receive chain for BPSK and QPSK, the counterpart of the [PskTxBuilder]:
+/// - root-raised-cosine matched filter with roll-off factor (default 0.35) and span in symbols
+///   (default 8) for `sps` samples per symbol,
+/// - polyphase filter bank symbol synchronizer (see [SymbolSync](crate::blocks::SymbolSyncBuilder))
+///   with normalized loop bandwidth (default 0.01),
+/// - [CostasLoop](crate::blocks::CostasLoop) with normalized loop bandwidth (default 0.02),
+/// - hard decisions and differential decoding (default),
+/// - symbols to bits (one per byte), most significant bit first.
+///
+/// The input should be roughly normalized to unit amplitude.
+///
+/// # Inputs
+///
+/// `in`: Baseband samples (Complex32)
+///
+/// # Outputs
+///
+/// `out`: Bits (u8)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::PskRxBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let rx = fg.add_block(PskRxBuilder::qpsk(4).rolloff(0.25).build());
+/// ```
+pub struct PskRxBuilder {
+    constellation: Constellation,
+    sps: usize,
+    rolloff: f32,
+    span: usize,
+    differential: bool,
+    timing_bw: f32,
+    carrier_bw: f32,
+}
+
+impl PskRxBuilder {
+    pub fn bpsk(sps: usize) -> PskRxBuilder {
+        Self::new(Constellation::bpsk(), sps)
+    }
+
+    pub fn qpsk(sps: usize) -> PskRxBuilder {
+        Self::new(Constellation::qpsk(), sps)
+    }
+
+    fn new(constellation: Constellation, sps: usize) -> PskRxBuilder {
+        PskRxBuilder {
+            constellation,
+            sps,
+            rolloff: 0.35,
+            span: 8,
+            differential: true,
+            timing_bw: 0.01,
+            carrier_bw: 0.02,
+        }
+    }
+
+    /// Roll-off factor of the matched filter.
+    #[must_use]
+    pub fn rolloff(mut self, rolloff: f32) -> PskRxBuilder {
+        self.rolloff = rolloff;
+        self
+    }
+
+    /// Length of the matched filter in symbols.
+    #[must_use]
+    pub fn span(mut self, span: usize) -> PskRxBuilder {
+        self.span = span;
+        self
+    }
+
+    /// Enable or disable differential decoding.
+    #[must_use]
+    pub fn differential(mut self, differential: bool) -> PskRxBuilder {
+        self.differential = differential;
+        self
+    }
+
+    /// Loop bandwidth of the symbol synchronizer.
+    #[must_use]
+    pub fn timing_bw(mut self, bw: f32) -> PskRxBuilder {
+        self.timing_bw = bw;
+        self
+    }
+
+    /// Loop bandwidth of the Costas loop.
+    #[must_use]
+    pub fn carrier_bw(mut self, bw: f32) -> PskRxBuilder {
+        self.carrier_bw = bw;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        PskRx::new(
+            self.constellation,
+            self.sps,
+            self.rolloff,
+            self.span,
+            self.differential,
+            self.timing_bw,
+            self.carrier_bw,
+        )
+    }
+}
@@ -0,0 +1,89 @@
+use crate::anyhow::Result;
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Quadrature (frequency) demodulator.
+///
+/// Outputs `gain * arg(x[n] * conj(x[n-1]))`, i.e., the phase difference of consecutive
+/// samples. To demodulate an FM signal with maximum deviation `dev` at sample rate `fs` to `[-1,
+/// 1]`, set the gain to `fs / (2 * PI * dev)`.
+///
+/// # Inputs
+///
+/// `in`: Input (Complex32)
+///
+/// # Outputs
+///
+/// `out`: Demodulated output (f32)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::QuadratureDemod;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // 250 kHz sample rate, 75 kHz deviation
+/// let demod = fg.add_block(QuadratureDemod::new(
+///     250e3 / (2.0 * std::f32::consts::PI * 75e3),
+/// ));
+/// ```
+pub struct QuadratureDemod {
+    gain: f32,
+    last: Complex32,
+}
+
+impl QuadratureDemod {
+    pub fn new(gain: f32) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("QuadratureDemod").build(),
+            StreamIoBuilder::new()
+                .add_input::<Complex32>("in")
+                .add_output::<f32>("out")
+                .build(),
+            MessageIoBuilder::<QuadratureDemod>::new().build(),
+            QuadratureDemod {
+                gain,
+                last: Complex32::new(0.0, 0.0),
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for QuadratureDemod {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex32>();
+        let o = sio.output(0).slice::<f32>();
+
+        let m = std::cmp::min(i.len(), o.len());
+        for (x, y) in i[..m].iter().zip(o.iter_mut()) {
+            *y = self.gain * (x * self.last.conj()).arg();
+            self.last = *x;
+        }
+
+        sio.input(0).consume(m);
+        sio.output(0).produce(m);
+
+        if sio.input(0).finished() && m == i.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
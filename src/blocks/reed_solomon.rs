@@ -0,0 +1,520 @@
+use futures::FutureExt;
+
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIoBuilder;
+
+const NN: usize = 255;
+const A0: usize = NN;
+
+/// Conventional to Berlekamp dual basis representation, as used by CCSDS.
+const TAL: [u8; 8] = [0x8d, 0xef, 0xec, 0x86, 0xfa, 0x99, 0xaf, 0x7b];
+
+/// Reed-Solomon code over GF(2^8).
+///
+/// Codewords are shortened to the length of the data, i.e., `k` data bytes are encoded into a
+/// codeword of `k + nroots` bytes, with `k <= 255 - nroots`. Optionally, codewords are byte-wise
+/// interleaved and represented in the Berlekamp dual basis.
+#[derive(Debug, Clone)]
+pub struct ReedSolomon {
+    alpha_to: [u8; 256],
+    index_of: [usize; 256],
+    genpoly: Vec<usize>,
+    fcr: usize,
+    prim: usize,
+    iprim: usize,
+    nroots: usize,
+    interleave: usize,
+    dual_basis: Option<([u8; 256], [u8; 256])>,
+}
+
+impl ReedSolomon {
+    /// Create a code with the given field generator polynomial, first consecutive root (in index
+    /// form), primitive element to generate the roots (in index form), and number of roots.
+    pub fn new(gfpoly: u32, fcr: usize, prim: usize, nroots: usize) -> ReedSolomon {
+        assert!(
+            (0x100..0x200).contains(&gfpoly),
+            "field polynomial has to be of degree 8"
+        );
+        assert!(prim > 0 && prim < NN, "prim has to be in [1, 255)");
+        assert!(nroots > 0 && nroots < NN, "nroots has to be in [1, 255)");
+
+        let mut alpha_to = [0u8; 256];
+        let mut index_of = [0usize; 256];
+        index_of[0] = A0;
+        let mut sr = 1u32;
+        for (i, a) in alpha_to.iter_mut().take(NN).enumerate() {
+            index_of[sr as usize] = i;
+            *a = sr as u8;
+            sr <<= 1;
+            if sr & 0x100 != 0 {
+                sr ^= gfpoly;
+            }
+        }
+        assert_eq!(sr, 1, "field polynomial is not primitive");
+
+        let iprim = (1..NN)
+            .find(|i| (i * prim) % NN == 1)
+            .expect("prim has to be coprime to 255");
+
+        let mut rs = ReedSolomon {
+            alpha_to,
+            index_of,
+            genpoly: Vec::new(),
+            fcr,
+            prim,
+            iprim,
+            nroots,
+            interleave: 1,
+            dual_basis: None,
+        };
+
+        // generator polynomial, from the lowest to the highest coefficient in index form
+        let mut genpoly = vec![0u8; nroots + 1];
+        genpoly[0] = 1;
+        let mut root = fcr * prim;
+        for i in 0..nroots {
+            genpoly[i + 1] = 1;
+            for j in (1..=i).rev() {
+                genpoly[j] = if genpoly[j] != 0 {
+                    genpoly[j - 1] ^ rs.alpha(rs.index_of[genpoly[j] as usize] + root)
+                } else {
+                    genpoly[j - 1]
+                };
+            }
+            genpoly[0] = rs.alpha(rs.index_of[genpoly[0] as usize] + root);
+            root += prim;
+        }
+        rs.genpoly = genpoly.iter().map(|g| rs.index_of[*g as usize]).collect();
+        rs
+    }
+
+    /// CCSDS RS(255,223) code with dual basis representation.
+    pub fn ccsds() -> ReedSolomon {
+        Self::new(0x187, 112, 11, 32).dual_basis(true)
+    }
+
+    /// DVB RS(204,188) code, i.e., RS(255,239) shortened to 188 data bytes.
+    pub fn dvb() -> ReedSolomon {
+        Self::new(0x11d, 0, 1, 16)
+    }
+
+    /// Interleave `depth` codewords byte-wise.
+    #[must_use]
+    pub fn interleave(mut self, depth: usize) -> ReedSolomon {
+        assert!(depth > 0, "interleaving depth must be positive");
+        self.interleave = depth;
+        self
+    }
+
+    /// Represent symbols in the Berlekamp dual basis.
+    #[must_use]
+    pub fn dual_basis(mut self, dual_basis: bool) -> ReedSolomon {
+        self.dual_basis = if dual_basis {
+            let mut to_dual = [0u8; 256];
+            let mut from_dual = [0u8; 256];
+            for i in 0..256 {
+                for (k, t) in TAL.iter().rev().enumerate() {
+                    if i & (1 << k) != 0 {
+                        to_dual[i] ^= t;
+                    }
+                }
+                from_dual[to_dual[i] as usize] = i as u8;
+            }
+            Some((to_dual, from_dual))
+        } else {
+            None
+        };
+        self
+    }
+
+    /// Number of parity bytes per codeword.
+    pub fn nroots(&self) -> usize {
+        self.nroots
+    }
+
+    /// Maximum number of data bytes per codeword.
+    pub fn max_data_len(&self) -> usize {
+        NN - self.nroots
+    }
+
+    fn alpha(&self, i: usize) -> u8 {
+        self.alpha_to[i % NN]
+    }
+
+    /// Encode `data`, returning data followed by parity.
+    ///
+    /// Returns `None` if the data length is no multiple of the interleaving depth or the
+    /// codewords are too long.
+    pub fn encode(&self, data: &[u8]) -> Option<Vec<u8>> {
+        let depth = self.interleave;
+        if data.len() % depth != 0 || data.len() / depth > self.max_data_len() {
+            return None;
+        }
+
+        let mut out = data.to_vec();
+        out.resize(data.len() + depth * self.nroots, 0);
+        for c in 0..depth {
+            let word: Vec<u8> = data
+                .iter()
+                .skip(c)
+                .step_by(depth)
+                .map(|x| self.conventional(*x))
+                .collect();
+            let parity = self.encode_word(&word);
+            for (i, p) in parity.into_iter().enumerate() {
+                out[data.len() + i * depth + c] = self.dual(p);
+            }
+        }
+        Some(out)
+    }
+
+    /// Decode `codeword` in place and strip the parity.
+    ///
+    /// Returns the number of corrected bytes or `None` if the codeword could not be corrected.
+    pub fn decode(&self, codeword: &mut Vec<u8>) -> Option<usize> {
+        let depth = self.interleave;
+        if codeword.len() % depth != 0
+            || codeword.len() / depth <= self.nroots
+            || codeword.len() / depth > NN
+        {
+            return None;
+        }
+
+        let mut corrected = 0;
+        for c in 0..depth {
+            let mut word: Vec<u8> = codeword
+                .iter()
+                .skip(c)
+                .step_by(depth)
+                .map(|x| self.conventional(*x))
+                .collect();
+            corrected += self.decode_word(&mut word)?;
+            for (i, x) in word.into_iter().enumerate() {
+                codeword[i * depth + c] = self.dual(x);
+            }
+        }
+        codeword.truncate(codeword.len() - depth * self.nroots);
+        Some(corrected)
+    }
+
+    fn dual(&self, x: u8) -> u8 {
+        match self.dual_basis {
+            Some((ref to_dual, _)) => to_dual[x as usize],
+            None => x,
+        }
+    }
+
+    fn conventional(&self, x: u8) -> u8 {
+        match self.dual_basis {
+            Some((_, ref from_dual)) => from_dual[x as usize],
+            None => x,
+        }
+    }
+
+    fn encode_word(&self, data: &[u8]) -> Vec<u8> {
+        let nroots = self.nroots;
+        let mut parity = vec![0u8; nroots];
+        for x in data {
+            let feedback = self.index_of[(x ^ parity[0]) as usize];
+            if feedback != A0 {
+                for (j, p) in parity.iter_mut().enumerate().skip(1) {
+                    *p ^= self.alpha(feedback + self.genpoly[nroots - j]);
+                }
+            }
+            parity.rotate_left(1);
+            parity[nroots - 1] = if feedback != A0 {
+                self.alpha(feedback + self.genpoly[0])
+            } else {
+                0
+            };
+        }
+        parity
+    }
+
+    fn decode_word(&self, data: &mut [u8]) -> Option<usize> {
+        let nroots = self.nroots;
+        let pad = NN - data.len();
+
+        // syndromes in index form
+        let mut s = vec![data[0]; nroots];
+        for x in &data[1..] {
+            for (i, s) in s.iter_mut().enumerate() {
+                *s = if *s == 0 {
+                    *x
+                } else {
+                    x ^ self.alpha(self.index_of[*s as usize] + (self.fcr + i) * self.prim)
+                };
+            }
+        }
+        if s.iter().all(|s| *s == 0) {
+            return Some(0);
+        }
+        let s: Vec<usize> = s.iter().map(|s| self.index_of[*s as usize]).collect();
+
+        // Berlekamp-Massey for the error locator polynomial
+        let mut lambda = vec![0u8; nroots + 1];
+        lambda[0] = 1;
+        let mut b: Vec<usize> = lambda.iter().map(|l| self.index_of[*l as usize]).collect();
+        let mut el = 0;
+        for r in 1..=nroots {
+            let mut discr = 0u8;
+            for i in 0..r {
+                if lambda[i] != 0 && s[r - i - 1] != A0 {
+                    discr ^= self.alpha(self.index_of[lambda[i] as usize] + s[r - i - 1]);
+                }
+            }
+            let discr = self.index_of[discr as usize];
+
+            if discr == A0 {
+                b.rotate_right(1);
+                b[0] = A0;
+            } else {
+                let mut t = vec![0u8; nroots + 1];
+                t[0] = lambda[0];
+                for i in 0..nroots {
+                    t[i + 1] = if b[i] != A0 {
+                        lambda[i + 1] ^ self.alpha(discr + b[i])
+                    } else {
+                        lambda[i + 1]
+                    };
+                }
+                if 2 * el < r {
+                    el = r - el;
+                    for (b, l) in b.iter_mut().zip(lambda.iter()) {
+                        *b = if *l == 0 {
+                            A0
+                        } else {
+                            (self.index_of[*l as usize] + NN - discr) % NN
+                        };
+                    }
+                } else {
+                    b.rotate_right(1);
+                    b[0] = A0;
+                }
+                lambda = t;
+            }
+        }
+
+        let lambda: Vec<usize> = lambda.iter().map(|l| self.index_of[*l as usize]).collect();
+        let deg_lambda = lambda.iter().rposition(|l| *l != A0).unwrap_or(0);
+        if deg_lambda == 0 {
+            return None;
+        }
+
+        // Chien search for the roots of the error locator polynomial
+        let mut reg = lambda.clone();
+        let mut roots = Vec::with_capacity(deg_lambda);
+        let mut locs = Vec::with_capacity(deg_lambda);
+        let mut k = self.iprim - 1;
+        for i in 1..=NN {
+            let mut q = 1u8;
+            for j in (1..=deg_lambda).rev() {
+                if reg[j] != A0 {
+                    reg[j] = (reg[j] + j) % NN;
+                    q ^= self.alpha_to[reg[j]];
+                }
+            }
+            if q == 0 {
+                roots.push(i);
+                locs.push(k);
+                if roots.len() == deg_lambda {
+                    break;
+                }
+            }
+            k = (k + self.iprim) % NN;
+        }
+        if roots.len() != deg_lambda {
+            return None;
+        }
+
+        // error evaluator polynomial in index form
+        let deg_omega = deg_lambda - 1;
+        let omega: Vec<usize> = (0..=deg_omega)
+            .map(|i| {
+                let mut tmp = 0u8;
+                for j in 0..=i {
+                    if s[i - j] != A0 && lambda[j] != A0 {
+                        tmp ^= self.alpha(s[i - j] + lambda[j]);
+                    }
+                }
+                self.index_of[tmp as usize]
+            })
+            .collect();
+
+        // Forney algorithm for the error values
+        let mut errors = Vec::with_capacity(roots.len());
+        for (root, loc) in roots.iter().zip(locs.iter()) {
+            let mut num1 = 0u8;
+            for (i, o) in omega.iter().enumerate() {
+                if *o != A0 {
+                    num1 ^= self.alpha(o + i * root);
+                }
+            }
+            let num2 = self.alpha_to
+                [((*root as i64 * (self.fcr as i64 - 1)).rem_euclid(NN as i64)) as usize];
+            let mut den = 0u8;
+            let mut i = std::cmp::min(deg_lambda, nroots - 1) & !1;
+            loop {
+                if lambda[i + 1] != A0 {
+                    den ^= self.alpha(lambda[i + 1] + i * root);
+                }
+                if i < 2 {
+                    break;
+                }
+                i -= 2;
+            }
+            if den == 0 || *loc < pad {
+                return None;
+            }
+            if num1 != 0 {
+                let e = self.alpha(
+                    self.index_of[num1 as usize] + self.index_of[num2 as usize] + NN
+                        - self.index_of[den as usize],
+                );
+                errors.push((loc - pad, e));
+            }
+        }
+
+        for (i, e) in errors.iter() {
+            data[*i] ^= e;
+        }
+        Some(errors.len())
+    }
+}
+
+/// Reed-Solomon encode PDUs.
+///
+/// # Inputs
+///
+/// **Message**: `in`: Data ([Pmt::Blob])
+///
+/// # Outputs
+///
+/// **Message**: `out`: Data followed by parity ([Pmt::Blob])
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::ReedSolomon;
+/// use futuresdr::blocks::ReedSolomonEncoder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let enc = fg.add_block(ReedSolomonEncoder::new(ReedSolomon::ccsds().interleave(4)));
+/// ```
+pub struct ReedSolomonEncoder {
+    rs: ReedSolomon,
+}
+
+impl ReedSolomonEncoder {
+    pub fn new(rs: ReedSolomon) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("ReedSolomonEncoder").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_input(
+                    "in",
+                    |block: &mut ReedSolomonEncoder,
+                     mio: &mut MessageIo<ReedSolomonEncoder>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            if let Pmt::Blob(v) = p {
+                                if let Some(c) = block.rs.encode(&v) {
+                                    mio.post(0, Pmt::Blob(c)).await;
+                                } else {
+                                    warn!("ReedSolomonEncoder: invalid data length {}", v.len());
+                                }
+                            } else {
+                                warn!("ReedSolomonEncoder: received wrong PMT type. {:?}", p);
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .add_output("out")
+                .build(),
+            ReedSolomonEncoder { rs },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for ReedSolomonEncoder {}
+
+/// Reed-Solomon decode PDUs.
+///
+/// Corrected codewords are forwarded without parity. Codewords that cannot be corrected are
+/// dropped or, if the `failed` port is connected, forwarded there unchanged.
+///
+/// # Inputs
+///
+/// **Message**: `in`: Codewords ([Pmt::Blob])
+///
+/// # Outputs
+///
+/// **Message**: `out`: Corrected data ([Pmt::Blob])
+///
+/// **Message**: `failed`: Uncorrectable codewords ([Pmt::Blob])
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::ReedSolomon;
+/// use futuresdr::blocks::ReedSolomonDecoder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let dec = fg.add_block(ReedSolomonDecoder::new(ReedSolomon::dvb()));
+/// ```
+pub struct ReedSolomonDecoder {
+    rs: ReedSolomon,
+}
+
+impl ReedSolomonDecoder {
+    pub fn new(rs: ReedSolomon) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("ReedSolomonDecoder").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_input(
+                    "in",
+                    |block: &mut ReedSolomonDecoder,
+                     mio: &mut MessageIo<ReedSolomonDecoder>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            if let Pmt::Blob(v) = p {
+                                let mut data = v.clone();
+                                if block.rs.decode(&mut data).is_some() {
+                                    mio.post(0, Pmt::Blob(data)).await;
+                                } else {
+                                    mio.post(1, Pmt::Blob(v)).await;
+                                }
+                            } else {
+                                warn!("ReedSolomonDecoder: received wrong PMT type. {:?}", p);
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .add_output("out")
+                .add_output("failed")
+                .build(),
+            ReedSolomonDecoder { rs },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for ReedSolomonDecoder {}
@@ -0,0 +1,198 @@
+use crate::anyhow::Result;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Order of bits within a byte or symbol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitOrder {
+    /// Most significant bit first.
+    MsbFirst,
+    /// Least significant bit first.
+    LsbFirst,
+}
+
+/// Repack symbols of `k` bits into symbols of `l` bits.
+///
+/// Input and output symbols are stored in the least significant bits of a byte. Remaining bits
+/// at the end of the stream that do not fill an output symbol are dropped.
+///
+/// # Inputs
+///
+/// `in`: Symbols with `k` bits (u8)
+///
+/// # Outputs
+///
+/// `out`: Symbols with `l` bits (u8)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::BitOrder;
+/// use futuresdr::blocks::RepackBits;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // bytes to QPSK symbols
+/// let repack = fg.add_block(RepackBits::new(8, 2, BitOrder::MsbFirst));
+/// ```
+pub struct RepackBits {
+    k: usize,
+    l: usize,
+    order: BitOrder,
+    reg: u32,
+    n: usize,
+}
+
+impl RepackBits {
+    pub fn new(k: usize, l: usize, order: BitOrder) -> Block {
+        Self::with_name("RepackBits", k, l, order)
+    }
+
+    fn with_name(name: &str, k: usize, l: usize, order: BitOrder) -> Block {
+        assert!((1..=8).contains(&k), "k has to be in [1, 8]");
+        assert!((1..=8).contains(&l), "l has to be in [1, 8]");
+
+        Block::new(
+            BlockMetaBuilder::new(name).build(),
+            StreamIoBuilder::new()
+                .add_input::<u8>("in")
+                .add_output::<u8>("out")
+                .build(),
+            MessageIoBuilder::<Self>::new().build(),
+            RepackBits {
+                k,
+                l,
+                order,
+                reg: 0,
+                n: 0,
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for RepackBits {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<u8>();
+        let o = sio.output(0).slice::<u8>();
+
+        let mask_k = (1u32 << self.k) - 1;
+        let mask_l = (1u32 << self.l) - 1;
+
+        let mut consumed = 0;
+        let mut produced = 0;
+        for x in i.iter() {
+            if o.len() - produced < (self.n + self.k) / self.l {
+                break;
+            }
+
+            let x = *x as u32 & mask_k;
+            match self.order {
+                BitOrder::MsbFirst => {
+                    self.reg = (self.reg << self.k) | x;
+                    self.n += self.k;
+                    while self.n >= self.l {
+                        self.n -= self.l;
+                        o[produced] = ((self.reg >> self.n) & mask_l) as u8;
+                        produced += 1;
+                    }
+                    self.reg &= (1 << self.n) - 1;
+                }
+                BitOrder::LsbFirst => {
+                    self.reg |= x << self.n;
+                    self.n += self.k;
+                    while self.n >= self.l {
+                        o[produced] = (self.reg & mask_l) as u8;
+                        produced += 1;
+                        self.reg >>= self.l;
+                        self.n -= self.l;
+                    }
+                }
+            }
+            consumed += 1;
+        }
+
+        sio.input(0).consume(consumed);
+        sio.output(0).produce(produced);
+
+        if sio.input(0).finished() && consumed == i.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Unpack bytes to bits.
+///
+/// Outputs one bit per byte, i.e., a [RepackBits] block with `k = 8` and `l = 1`.
+///
+/// # Inputs
+///
+/// `in`: Bytes (u8)
+///
+/// # Outputs
+///
+/// `out`: Bits (u8)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::BitOrder;
+/// use futuresdr::blocks::UnpackBits;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let unpack = fg.add_block(UnpackBits::new(BitOrder::MsbFirst));
+/// ```
+pub struct UnpackBits;
+
+impl UnpackBits {
+    pub fn new(order: BitOrder) -> Block {
+        RepackBits::with_name("UnpackBits", 8, 1, order)
+    }
+}
+
+/// Pack bits to bytes.
+///
+/// Takes one bit per byte, i.e., a [RepackBits] block with `k = 1` and `l = 8`.
+///
+/// # Inputs
+///
+/// `in`: Bits (u8)
+///
+/// # Outputs
+///
+/// `out`: Bytes (u8)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::BitOrder;
+/// use futuresdr::blocks::PackBits;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let pack = fg.add_block(PackBits::new(BitOrder::LsbFirst));
+/// ```
+pub struct PackBits;
+
+impl PackBits {
+    pub fn new(order: BitOrder) -> Block {
+        RepackBits::with_name("PackBits", 1, 8, order)
+    }
+}
@@ -0,0 +1,503 @@
+use futures::io::AsyncWriteExt;
+use futures::FutureExt;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use crate::anyhow::Result;
+use crate::blocks::sigmf::{datatype, SIGMF_VERSION};
+use crate::blocks::FileFormat;
+use crate::blocks::FileSample;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Calendar date and time in UTC as (year, month, day, hour, minute, second, microsecond).
+pub(super) fn utc(t: SystemTime) -> (i64, u32, u32, u32, u32, u32, u32) {
+    let d = t.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = d.as_secs();
+    let (days, rem) = ((secs / 86400) as i64, secs % 86400);
+
+    // civil from days, http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    (
+        year,
+        month,
+        day,
+        (rem / 3600) as u32,
+        (rem / 60 % 60) as u32,
+        (rem % 60) as u32,
+        d.subsec_micros(),
+    )
+}
+
+/// File extension of a format.
+fn extension(format: FileFormat) -> &'static str {
+    match format {
+        FileFormat::Cf32 => "cf32",
+        FileFormat::Ci16 => "ci16",
+        FileFormat::Ci8 => "ci8",
+        FileFormat::Cu8 => "cu8",
+        FileFormat::F32 => "f32",
+    }
+}
+
+/// Record samples into a series of files, rotated by duration or size.
+pub struct RotatingFileSink<T: FileSample> {
+    directory: PathBuf,
+    prefix: String,
+    format: FileFormat,
+    sample_rate: Option<f64>,
+    frequency: Option<f64>,
+    /// Maximum number of samples per file.
+    max_items: Option<u64>,
+    quota: Option<u64>,
+    sigmf: bool,
+    file: Option<async_fs::File>,
+    path: PathBuf,
+    /// Time of the first sample of the file.
+    file_start: SystemTime,
+    file_items: u64,
+    /// Time of the first sample of the recording.
+    start: SystemTime,
+    n: u64,
+    /// Whether the file should be closed before the next sample, e.g., after a retune.
+    rotate: bool,
+    buf: Vec<u8>,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: FileSample> RotatingFileSink<T> {
+    fn create(builder: RotatingFileSinkBuilder<T>) -> Block {
+        let item_size = builder.format.item_size() as u64;
+        let by_size = builder.max_size.map(|s| std::cmp::max(1, s / item_size));
+        let by_duration = builder.max_duration.map(|d| {
+            let rate = builder
+                .sample_rate
+                .expect("rotating by duration requires the sample rate");
+            std::cmp::max(1, (d.as_secs_f64() * rate) as u64)
+        });
+        let max_items = match (by_size, by_duration) {
+            (Some(a), Some(b)) => Some(std::cmp::min(a, b)),
+            (a, b) => a.or(b),
+        };
+
+        Block::new(
+            BlockMetaBuilder::new("RotatingFileSink").build(),
+            StreamIoBuilder::new().add_input::<T>("in").build(),
+            MessageIoBuilder::new()
+                .add_input(
+                    "freq",
+                    |block: &mut RotatingFileSink<T>,
+                     _mio: &mut MessageIo<RotatingFileSink<T>>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            let freq = match &p {
+                                Pmt::F64(f) => Some(*f),
+                                Pmt::F32(f) => Some(*f as f64),
+                                Pmt::U32(f) => Some(*f as f64),
+                                Pmt::U64(f) => Some(*f as f64),
+                                _ => None,
+                            };
+                            match freq {
+                                Some(f) if block.frequency != Some(f) => {
+                                    block.frequency = Some(f);
+                                    block.rotate = true;
+                                }
+                                Some(_) => {}
+                                None => {
+                                    warn!("RotatingFileSink/freq: received wrong PMT type. {:?}", p)
+                                }
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .add_output("file")
+                .build(),
+            RotatingFileSink::<T> {
+                directory: builder.directory,
+                prefix: builder.prefix,
+                format: builder.format,
+                sample_rate: builder.sample_rate,
+                frequency: builder.frequency,
+                max_items,
+                quota: builder.quota,
+                sigmf: builder.sigmf,
+                file: None,
+                path: PathBuf::new(),
+                file_start: UNIX_EPOCH,
+                file_items: 0,
+                start: UNIX_EPOCH,
+                n: 0,
+                rotate: false,
+                buf: Vec::new(),
+                _type: std::marker::PhantomData,
+            },
+        )
+    }
+
+    /// Time of the next sample, derived from the sample count, if the sample rate is known.
+    fn now(&self) -> SystemTime {
+        match self.sample_rate {
+            Some(r) => self.start + Duration::from_secs_f64(self.n as f64 / r),
+            None => SystemTime::now(),
+        }
+    }
+
+    /// Path of the data file without extension.
+    fn stem(&self, t: SystemTime) -> PathBuf {
+        let (y, mo, d, h, mi, s, us) = utc(t);
+        let mut name = format!(
+            "{}_{:04}{:02}{:02}T{:02}{:02}{:02}.{:06}Z",
+            self.prefix, y, mo, d, h, mi, s, us
+        );
+        if let Some(f) = self.frequency {
+            name.push_str(&format!("_{}Hz", f.round() as u64));
+        }
+        self.directory.join(name)
+    }
+
+    async fn open(&mut self) -> Result<()> {
+        self.file_start = self.now();
+        let stem = self.stem(self.file_start);
+        let ext = if self.sigmf {
+            "sigmf-data"
+        } else {
+            extension(self.format)
+        };
+        // the stem contains a dot, so the extension is appended and not set
+        self.path = PathBuf::from(format!("{}.{}", stem.display(), ext));
+        self.file = Some(async_fs::File::create(&self.path).await?);
+        self.file_items = 0;
+        Ok(())
+    }
+
+    fn metadata(&self) -> serde_json::Value {
+        let (y, mo, d, h, mi, s, us) = utc(self.file_start);
+        let mut global = serde_json::Map::new();
+        global.insert("core:datatype".to_string(), json!(datatype(self.format)));
+        global.insert("core:version".to_string(), json!(SIGMF_VERSION));
+        global.insert("core:recorder".to_string(), json!("FutureSDR"));
+        if let Some(r) = self.sample_rate {
+            global.insert("core:sample_rate".to_string(), json!(r));
+        }
+        let mut capture = serde_json::Map::new();
+        capture.insert("core:sample_start".to_string(), json!(0));
+        capture.insert(
+            "core:datetime".to_string(),
+            json!(format!(
+                "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:06}Z",
+                y, mo, d, h, mi, s, us
+            )),
+        );
+        if let Some(f) = self.frequency {
+            capture.insert("core:frequency".to_string(), json!(f));
+        }
+        json!({
+            "global": global,
+            "captures": [capture],
+            "annotations": [],
+        })
+    }
+
+    /// Finish the current file, announce it, and prune old files. Empty files are removed.
+    async fn close(&mut self, mio: &mut MessageIo<Self>) -> Result<()> {
+        let file = match self.file.take() {
+            Some(f) => f,
+            None => return Ok(()),
+        };
+        file.sync_all().await?;
+        drop(file);
+
+        if self.file_items == 0 {
+            async_fs::remove_file(&self.path).await?;
+            return Ok(());
+        }
+        if self.sigmf {
+            let meta = serde_json::to_string_pretty(&self.metadata())?;
+            async_fs::write(self.path.with_extension("sigmf-meta"), meta).await?;
+        }
+        mio.post(0, Pmt::String(self.path.display().to_string()))
+            .await;
+        self.prune()
+    }
+
+    /// Remove the oldest files of the recording, until the total size is within the quota.
+    fn prune(&self) -> Result<()> {
+        let quota = match self.quota {
+            Some(q) => q,
+            None => return Ok(()),
+        };
+        let prefix = format!("{}_", self.prefix);
+        // data and metadata files of a recording, keyed by their common stem, which starts with
+        // the timestamp, so they are sorted chronologically
+        let mut recordings: BTreeMap<String, (u64, Vec<PathBuf>)> = BTreeMap::new();
+        for entry in std::fs::read_dir(&self.directory)? {
+            let entry = entry?;
+            let path = entry.path();
+            let stem = match path.file_stem() {
+                Some(s) => s.to_string_lossy().to_string(),
+                None => continue,
+            };
+            if stem.starts_with(&prefix) && entry.file_type()?.is_file() {
+                let r = recordings.entry(stem).or_default();
+                r.0 += entry.metadata()?.len();
+                r.1.push(path);
+            }
+        }
+        let mut total: u64 = recordings.values().map(|(s, _)| s).sum();
+        for (stem, (size, paths)) in recordings {
+            if total <= quota {
+                break;
+            }
+            info!("RotatingFileSink: removing {} to stay within quota", stem);
+            for p in paths {
+                std::fs::remove_file(p)?;
+            }
+            total -= size;
+        }
+        Ok(())
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: FileSample> Kernel for RotatingFileSink<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        if self.rotate {
+            self.rotate = false;
+            self.close(mio).await?;
+        }
+
+        let available = sio.input(0).slice::<T>().len();
+        if available > 0 {
+            if self.file.is_none() {
+                self.open().await?;
+            }
+            let room = self.max_items.map_or(u64::MAX, |m| m - self.file_items);
+            let n = std::cmp::min(available as u64, room) as usize;
+
+            {
+                let i = sio.input(0).slice::<T>();
+                let s = self.format.item_size();
+                self.buf.resize(n * s, 0);
+                for (x, b) in i[..n].iter().zip(self.buf.chunks_exact_mut(s)) {
+                    self.format.encode(x.to_complex(), b);
+                }
+            }
+            self.file.as_mut().unwrap().write_all(&self.buf).await?;
+
+            self.file_items += n as u64;
+            self.n += n as u64;
+            sio.input(0).consume(n);
+
+            if Some(self.file_items) == self.max_items {
+                self.close(mio).await?;
+                io.call_again = true;
+            }
+            if n < available {
+                return Ok(());
+            }
+        }
+
+        if sio.input(0).finished() {
+            self.close(mio).await?;
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        async_fs::create_dir_all(&self.directory).await?;
+        self.start = SystemTime::now();
+        self.n = 0;
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        self.close(mio).await
+    }
+}
+
+/// Record samples into a series of files, rotated by duration or size, for unattended capture.
+///
+/// Files are named `<prefix>_<UTC timestamp of the first sample>[_<frequency>Hz].<format>`,
+/// e.g., `capture_20240131T235959.000000Z_433920000Hz.ci16`, in the given directory. If the
+/// sample rate is set, timestamps are derived from the sample count, i.e., they stay consistent
+/// over long recordings. With [sigmf](RotatingFileSinkBuilder::sigmf), each file is written as
+/// a [SigMF](https://sigmf.org) pair, i.e., `.sigmf-data` and `.sigmf-meta` with datatype,
+/// sample rate, frequency, and datetime.
+///
+/// A new file is started after [max_duration](RotatingFileSinkBuilder::max_duration) or
+/// [max_size](RotatingFileSinkBuilder::max_size), whatever comes first, and when the frequency
+/// changes. With a [quota](RotatingFileSinkBuilder::quota), the oldest files with the prefix
+/// are removed after each file, until the files fit into the quota.
+///
+/// # Inputs
+///
+/// `in`: Input (f32 or Complex32)
+///
+/// **Message**: `freq`: Center frequency in Hz, starting a new file
+///
+/// # Outputs
+///
+/// **Message**: `file`: Path of each completed file ([Pmt::String])
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::FileFormat;
+/// use futuresdr::blocks::RotatingFileSinkBuilder;
+/// use futuresdr::num_complex::Complex32;
+/// use futuresdr::runtime::Flowgraph;
+/// use std::time::Duration;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // one SigMF recording per minute, keeping at most 100 GB
+/// let sink = fg.add_block(
+///     RotatingFileSinkBuilder::<Complex32>::new("/data/captures")
+///         .prefix("ism")
+///         .format(FileFormat::Ci16)
+///         .sample_rate(2e6)
+///         .frequency(433.92e6)
+///         .max_duration(Duration::from_secs(60))
+///         .quota(100_000_000_000)
+///         .sigmf(true)
+///         .build(),
+/// );
+/// ```
+pub struct RotatingFileSinkBuilder<T: FileSample> {
+    directory: PathBuf,
+    prefix: String,
+    format: FileFormat,
+    sample_rate: Option<f64>,
+    frequency: Option<f64>,
+    max_duration: Option<Duration>,
+    max_size: Option<u64>,
+    quota: Option<u64>,
+    sigmf: bool,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: FileSample> RotatingFileSinkBuilder<T> {
+    pub fn new<P: Into<PathBuf>>(directory: P) -> RotatingFileSinkBuilder<T> {
+        RotatingFileSinkBuilder {
+            directory: directory.into(),
+            prefix: "capture".to_string(),
+            format: if T::COMPLEX {
+                FileFormat::Cf32
+            } else {
+                FileFormat::F32
+            },
+            sample_rate: None,
+            frequency: None,
+            max_duration: None,
+            max_size: None,
+            quota: None,
+            sigmf: false,
+            _type: std::marker::PhantomData,
+        }
+    }
+
+    /// Start of the file names (default `capture`).
+    #[must_use]
+    pub fn prefix<S: Into<String>>(mut self, prefix: S) -> RotatingFileSinkBuilder<T> {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Sample format of the files.
+    #[must_use]
+    pub fn format(mut self, format: FileFormat) -> RotatingFileSinkBuilder<T> {
+        assert!(
+            !T::COMPLEX || format.is_complex(),
+            "complex streams require a complex format"
+        );
+        self.format = format;
+        self
+    }
+
+    #[must_use]
+    pub fn sample_rate(mut self, sample_rate: f64) -> RotatingFileSinkBuilder<T> {
+        self.sample_rate = Some(sample_rate);
+        self
+    }
+
+    /// Center frequency in Hz.
+    #[must_use]
+    pub fn frequency(mut self, frequency: f64) -> RotatingFileSinkBuilder<T> {
+        self.frequency = Some(frequency);
+        self
+    }
+
+    /// Maximum duration of a file. Requires the sample rate.
+    #[must_use]
+    pub fn max_duration(mut self, duration: Duration) -> RotatingFileSinkBuilder<T> {
+        self.max_duration = Some(duration);
+        self
+    }
+
+    /// Maximum size of a data file in bytes.
+    #[must_use]
+    pub fn max_size(mut self, bytes: u64) -> RotatingFileSinkBuilder<T> {
+        self.max_size = Some(bytes);
+        self
+    }
+
+    /// Maximum total size of the files with the prefix in bytes.
+    #[must_use]
+    pub fn quota(mut self, bytes: u64) -> RotatingFileSinkBuilder<T> {
+        self.quota = Some(bytes);
+        self
+    }
+
+    /// Write SigMF data and metadata files.
+    #[must_use]
+    pub fn sigmf(mut self, sigmf: bool) -> RotatingFileSinkBuilder<T> {
+        self.sigmf = sigmf;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        RotatingFileSink::<T>::create(self)
+    }
+}
@@ -0,0 +1,244 @@
+use futures::FutureExt;
+use std::f64::consts::PI;
+
+use crate::anyhow::Result;
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Number of samples after which the phasor is normalized to unit magnitude.
+const NORMALIZE_INTERVAL: usize = 512;
+
+/// Shift the spectrum by a quarter of the sample rate.
+///
+/// Multiplies the samples with `j^n` (or `(-j)^n`), which only swaps and negates the real and
+/// imaginary parts and is, therefore, exact and cheap. This is typically used for offset tuning,
+/// i.e., to tune the device `fs/4` away from the signal of interest and move the signal back to
+/// DC, avoiding the DC spike of the device.
+///
+/// # Inputs
+///
+/// `in`: Input (Complex32)
+///
+/// # Outputs
+///
+/// `out`: Shifted samples (Complex32)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::Fs4Shift;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // shift up by fs/4
+/// let shift = fg.add_block(Fs4Shift::new(true));
+/// ```
+pub struct Fs4Shift {
+    up: bool,
+    n: usize,
+}
+
+impl Fs4Shift {
+    /// Shift up by `fs/4`, if `up` is true, or down by `fs/4` otherwise.
+    pub fn new(up: bool) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("Fs4Shift").in_place().build(),
+            StreamIoBuilder::new()
+                .add_input::<Complex32>("in")
+                .add_output::<Complex32>("out")
+                .build(),
+            MessageIoBuilder::<Self>::new().build(),
+            Fs4Shift { up, n: 0 },
+        )
+    }
+}
+
+impl Fs4Shift {
+    fn shift(&mut self, x: Complex32) -> Complex32 {
+        // multiply with j^n, mirroring the index for a shift down
+        let n = if self.up { self.n } else { (4 - self.n) % 4 };
+        self.n = (self.n + 1) % 4;
+        match n {
+            0 => x,
+            1 => Complex32::new(-x.im, x.re),
+            2 => -x,
+            _ => Complex32::new(x.im, -x.re),
+        }
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for Fs4Shift {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let (m, n) = if let Some(s) = sio.in_place_slice::<Complex32>() {
+            for x in s.iter_mut() {
+                *x = self.shift(*x);
+            }
+            (s.len(), s.len())
+        } else {
+            let i = sio.input(0).slice::<Complex32>();
+            let o = sio.output(0).slice::<Complex32>();
+            for (x, y) in i.iter().zip(o.iter_mut()) {
+                *y = self.shift(*x);
+            }
+            (std::cmp::min(i.len(), o.len()), i.len())
+        };
+
+        sio.input(0).consume(m);
+        sio.output(0).produce(m);
+
+        if sio.input(0).finished() && m == n {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Shift the spectrum by an arbitrary frequency.
+///
+/// Multiplies the samples with a complex exponential, generated by rotating a phasor with a
+/// constant phase increment. The phasor is normalized periodically to avoid drifting amplitude.
+/// The frequency can be changed at runtime without phase discontinuities.
+///
+/// # Inputs
+///
+/// `in`: Input (Complex32)
+///
+/// **Message**: `freq`: Set the frequency in Hz ([Pmt::F32] or [Pmt::F64]), or query it
+/// ([Pmt::Null]). Returns the current frequency ([Pmt::F64]).
+///
+/// # Outputs
+///
+/// `out`: Shifted samples (Complex32)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::Rotator;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // shift down by 1.5 kHz at a sample rate of 48 kHz
+/// let rotator = fg.add_block(Rotator::new(48e3, -1.5e3));
+/// ```
+pub struct Rotator {
+    sample_rate: f64,
+    frequency: f64,
+    phasor: Complex32,
+    increment: Complex32,
+    n: usize,
+}
+
+impl Rotator {
+    /// Create a rotator, shifting by `frequency` Hz at the given sample rate.
+    pub fn new(sample_rate: f64, frequency: f64) -> Block {
+        assert!(sample_rate > 0.0, "sample rate must be positive");
+
+        let mut r = Rotator {
+            sample_rate,
+            frequency: 0.0,
+            phasor: Complex32::new(1.0, 0.0),
+            increment: Complex32::new(1.0, 0.0),
+            n: 0,
+        };
+        r.set_frequency(frequency);
+
+        Block::new(
+            BlockMetaBuilder::new("Rotator").in_place().build(),
+            StreamIoBuilder::new()
+                .add_input::<Complex32>("in")
+                .add_output::<Complex32>("out")
+                .build(),
+            MessageIoBuilder::<Self>::new()
+                .add_input(
+                    "freq",
+                    |block: &mut Self,
+                     _mio: &mut MessageIo<Self>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            match p {
+                                Pmt::F32(f) => block.set_frequency(f as f64),
+                                Pmt::F64(f) => block.set_frequency(f),
+                                Pmt::Null => {}
+                                _ => warn!("Rotator: received wrong PMT type. {:?}", p),
+                            }
+                            Ok(Pmt::F64(block.frequency))
+                        }
+                        .boxed()
+                    },
+                )
+                .build(),
+            r,
+        )
+    }
+
+    fn set_frequency(&mut self, frequency: f64) {
+        self.frequency = frequency;
+        let inc = 2.0 * PI * frequency / self.sample_rate;
+        self.increment = Complex32::new(inc.cos() as f32, inc.sin() as f32);
+    }
+
+    fn rotate(&mut self, x: Complex32) -> Complex32 {
+        let y = x * self.phasor;
+        self.phasor *= self.increment;
+        self.n += 1;
+        if self.n == NORMALIZE_INTERVAL {
+            self.n = 0;
+            self.phasor /= self.phasor.norm();
+        }
+        y
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for Rotator {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let (m, n) = if let Some(s) = sio.in_place_slice::<Complex32>() {
+            for x in s.iter_mut() {
+                *x = self.rotate(*x);
+            }
+            (s.len(), s.len())
+        } else {
+            let i = sio.input(0).slice::<Complex32>();
+            let o = sio.output(0).slice::<Complex32>();
+            for (x, y) in i.iter().zip(o.iter_mut()) {
+                *y = self.rotate(*x);
+            }
+            (std::cmp::min(i.len(), o.len()), i.len())
+        };
+
+        sio.input(0).consume(m);
+        sio.output(0).produce(m);
+
+        if sio.input(0).finished() && m == n {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,44 @@
+//! Bindings of the used subset of librtlsdr.
+//!
+//! The library is linked by the build script, statically if `RTLSDR_STATIC` is set.
+use std::os::raw::{c_char, c_int, c_uchar, c_void};
+
+#[repr(C)]
+pub struct rtlsdr_dev {
+    _private: [u8; 0],
+}
+
+pub type rtlsdr_read_async_cb_t =
+    unsafe extern "C" fn(buf: *mut c_uchar, len: u32, ctx: *mut c_void);
+
+extern "C" {
+    pub fn rtlsdr_get_device_count() -> u32;
+    pub fn rtlsdr_get_device_name(index: u32) -> *const c_char;
+    pub fn rtlsdr_get_device_usb_strings(
+        index: u32,
+        manufact: *mut c_char,
+        product: *mut c_char,
+        serial: *mut c_char,
+    ) -> c_int;
+    pub fn rtlsdr_get_index_by_serial(serial: *const c_char) -> c_int;
+    pub fn rtlsdr_open(dev: *mut *mut rtlsdr_dev, index: u32) -> c_int;
+    pub fn rtlsdr_close(dev: *mut rtlsdr_dev) -> c_int;
+    pub fn rtlsdr_set_center_freq(dev: *mut rtlsdr_dev, freq: u32) -> c_int;
+    pub fn rtlsdr_set_freq_correction(dev: *mut rtlsdr_dev, ppm: c_int) -> c_int;
+    pub fn rtlsdr_get_tuner_gains(dev: *mut rtlsdr_dev, gains: *mut c_int) -> c_int;
+    pub fn rtlsdr_set_tuner_gain(dev: *mut rtlsdr_dev, gain: c_int) -> c_int;
+    pub fn rtlsdr_set_tuner_gain_mode(dev: *mut rtlsdr_dev, manual: c_int) -> c_int;
+    pub fn rtlsdr_set_sample_rate(dev: *mut rtlsdr_dev, rate: u32) -> c_int;
+    pub fn rtlsdr_set_agc_mode(dev: *mut rtlsdr_dev, on: c_int) -> c_int;
+    pub fn rtlsdr_set_direct_sampling(dev: *mut rtlsdr_dev, on: c_int) -> c_int;
+    pub fn rtlsdr_set_bias_tee(dev: *mut rtlsdr_dev, on: c_int) -> c_int;
+    pub fn rtlsdr_reset_buffer(dev: *mut rtlsdr_dev) -> c_int;
+    pub fn rtlsdr_read_async(
+        dev: *mut rtlsdr_dev,
+        cb: rtlsdr_read_async_cb_t,
+        ctx: *mut c_void,
+        buf_num: u32,
+        buf_len: u32,
+    ) -> c_int;
+    pub fn rtlsdr_cancel_async(dev: *mut rtlsdr_dev) -> c_int;
+}
@@ -0,0 +1,602 @@
+//! ## RTL-SDR Blocks (requires `rtlsdr` feature)
+//!
+//! Native [librtlsdr](https://osmocom.org/projects/rtl-sdr/wiki) blocks, which do not need
+//! SoapySDR. Set `RTLSDR_STATIC` at build time to link librtlsdr and libusb statically.
+use futures::channel::mpsc;
+use futures::FutureExt;
+use futures::StreamExt;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_uchar, c_void};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+use crate::anyhow::{bail, Context, Result};
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+#[allow(non_camel_case_types)]
+mod ffi;
+
+/// Number of USB buffers that are queued, before the device overruns.
+const QUEUE_SIZE: usize = 16;
+
+/// Direct sampling mode of the RTL2832U, used to receive HF without an upconverter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RtlSdrDirectSampling {
+    /// Use the tuner.
+    Off,
+    /// Sample the I branch.
+    I,
+    /// Sample the Q branch.
+    Q,
+}
+
+/// Device handle, shared with the thread that reads the samples.
+#[derive(Clone, Copy)]
+struct Device(*mut ffi::rtlsdr_dev);
+
+// librtlsdr allows to configure the device, while samples are read in another thread.
+unsafe impl Send for Device {}
+
+/// Context of the callback of the read thread.
+struct ReadContext {
+    tx: mpsc::Sender<Vec<u8>>,
+    overruns: Arc<AtomicU64>,
+}
+
+unsafe extern "C" fn read_callback(buf: *mut c_uchar, len: u32, ctx: *mut c_void) {
+    let ctx = &mut *(ctx as *mut ReadContext);
+    let data = std::slice::from_raw_parts(buf, len as usize).to_vec();
+    if ctx.tx.try_send(data).is_err() {
+        ctx.overruns.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+fn check(ret: i32, what: &str) -> Result<()> {
+    if ret < 0 {
+        bail!("RtlSdrSource: {} failed ({})", what, ret);
+    }
+    Ok(())
+}
+
+fn pmt_to_f64(p: &Pmt) -> Option<f64> {
+    match p {
+        Pmt::F32(v) => Some(*v as f64),
+        Pmt::F64(v) => Some(*v),
+        Pmt::U32(v) => Some(*v as f64),
+        Pmt::U64(v) => Some(*v as f64),
+        _ => None,
+    }
+}
+
+/// Receive samples from an RTL-SDR.
+pub struct RtlSdrSource {
+    index: Option<u32>,
+    serial: Option<String>,
+    frequency: f64,
+    sample_rate: f64,
+    gain: Option<f64>,
+    ppm: i32,
+    bias_tee: bool,
+    direct_sampling: RtlSdrDirectSampling,
+    rtl_agc: bool,
+    buffers: u32,
+    buffer_len: u32,
+    dev: Option<Device>,
+    thread: Option<JoinHandle<i32>>,
+    rx: Option<mpsc::Receiver<Vec<u8>>>,
+    buff: Option<(Vec<u8>, usize)>,
+    overruns: Arc<AtomicU64>,
+    reported: u64,
+}
+
+impl RtlSdrSource {
+    /// Names of the connected devices.
+    pub fn devices() -> Vec<String> {
+        unsafe {
+            (0..ffi::rtlsdr_get_device_count())
+                .map(|i| {
+                    let name = CStr::from_ptr(ffi::rtlsdr_get_device_name(i))
+                        .to_string_lossy()
+                        .to_string();
+                    let mut manufact = [0 as c_char; 256];
+                    let mut product = [0 as c_char; 256];
+                    let mut serial = [0 as c_char; 256];
+                    if ffi::rtlsdr_get_device_usb_strings(
+                        i,
+                        manufact.as_mut_ptr(),
+                        product.as_mut_ptr(),
+                        serial.as_mut_ptr(),
+                    ) == 0
+                    {
+                        let serial = CStr::from_ptr(serial.as_ptr()).to_string_lossy();
+                        format!("{} (serial {})", name, serial)
+                    } else {
+                        name
+                    }
+                })
+                .collect()
+        }
+    }
+
+    /// Number of USB buffers that were dropped, since the flowgraph did not keep up.
+    pub fn overruns(&self) -> u64 {
+        self.overruns.load(Ordering::Relaxed)
+    }
+
+    fn dev(&self) -> Result<*mut ffi::rtlsdr_dev> {
+        Ok(self.dev.context("device not open")?.0)
+    }
+
+    fn set_frequency(&mut self, frequency: f64) -> Result<()> {
+        check(
+            unsafe { ffi::rtlsdr_set_center_freq(self.dev()?, frequency as u32) },
+            "setting the frequency",
+        )?;
+        self.frequency = frequency;
+        Ok(())
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) -> Result<()> {
+        check(
+            unsafe { ffi::rtlsdr_set_sample_rate(self.dev()?, sample_rate as u32) },
+            "setting the sample rate",
+        )?;
+        self.sample_rate = sample_rate;
+        Ok(())
+    }
+
+    /// Set the gain in dB, choosing the closest gain supported by the tuner, or enable AGC.
+    fn set_gain(&mut self, gain: Option<f64>) -> Result<()> {
+        let dev = self.dev()?;
+        match gain {
+            Some(g) => unsafe {
+                check(
+                    ffi::rtlsdr_set_tuner_gain_mode(dev, 1),
+                    "setting the gain mode",
+                )?;
+                let n = ffi::rtlsdr_get_tuner_gains(dev, std::ptr::null_mut());
+                check(n, "getting the gains")?;
+                let mut gains = vec![0; n as usize];
+                ffi::rtlsdr_get_tuner_gains(dev, gains.as_mut_ptr());
+                // gains are in tenths of dB
+                let target = (g * 10.0) as i32;
+                let closest = gains
+                    .iter()
+                    .min_by_key(|x| (*x - target).abs())
+                    .copied()
+                    .unwrap_or(target);
+                check(ffi::rtlsdr_set_tuner_gain(dev, closest), "setting the gain")?;
+            },
+            None => check(
+                unsafe { ffi::rtlsdr_set_tuner_gain_mode(dev, 0) },
+                "setting the gain mode",
+            )?,
+        }
+        self.gain = gain;
+        Ok(())
+    }
+
+    fn status(&self) -> Pmt {
+        let mut m = std::collections::HashMap::new();
+        m.insert("frequency".to_string(), Pmt::F64(self.frequency));
+        m.insert("sample_rate".to_string(), Pmt::F64(self.sample_rate));
+        m.insert(
+            "gain".to_string(),
+            self.gain.map(Pmt::F64).unwrap_or(Pmt::Null),
+        );
+        m.insert("overruns".to_string(), Pmt::U64(self.overruns()));
+        Pmt::MapStrPmt(m)
+    }
+
+    fn create(builder: RtlSdrSourceBuilder) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("RtlSdrSource").build(),
+            StreamIoBuilder::new()
+                .add_output::<Complex32>("out")
+                .build(),
+            MessageIoBuilder::new()
+                .add_input(
+                    "freq",
+                    |block: &mut RtlSdrSource,
+                     _mio: &mut MessageIo<RtlSdrSource>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            match pmt_to_f64(&p) {
+                                Some(f) => block.set_frequency(f)?,
+                                None => {
+                                    warn!("RtlSdrSource/freq: received wrong PMT type. {:?}", p)
+                                }
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .add_input(
+                    "gain",
+                    |block: &mut RtlSdrSource,
+                     _mio: &mut MessageIo<RtlSdrSource>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            match (&p, pmt_to_f64(&p)) {
+                                (_, Some(g)) => block.set_gain(Some(g))?,
+                                (Pmt::Null, _) => block.set_gain(None)?,
+                                _ => warn!("RtlSdrSource/gain: received wrong PMT type. {:?}", p),
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .add_input(
+                    "sample_rate",
+                    |block: &mut RtlSdrSource,
+                     _mio: &mut MessageIo<RtlSdrSource>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            match pmt_to_f64(&p) {
+                                Some(s) => block.set_sample_rate(s)?,
+                                None => warn!(
+                                    "RtlSdrSource/sample_rate: received wrong PMT type. {:?}",
+                                    p
+                                ),
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .add_input(
+                    "status",
+                    |block: &mut RtlSdrSource,
+                     _mio: &mut MessageIo<RtlSdrSource>,
+                     _meta: &mut BlockMeta,
+                     _p: Pmt| { async move { Ok(block.status()) }.boxed() },
+                )
+                .add_output("overrun")
+                .build(),
+            RtlSdrSource {
+                index: builder.index,
+                serial: builder.serial,
+                frequency: builder.frequency,
+                sample_rate: builder.sample_rate,
+                gain: builder.gain,
+                ppm: builder.ppm,
+                bias_tee: builder.bias_tee,
+                direct_sampling: builder.direct_sampling,
+                rtl_agc: builder.rtl_agc,
+                buffers: builder.buffers,
+                buffer_len: builder.buffer_len,
+                dev: None,
+                thread: None,
+                rx: None,
+                buff: None,
+                overruns: Arc::new(AtomicU64::new(0)),
+                reported: 0,
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for RtlSdrSource {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let overruns = self.overruns();
+        if overruns > self.reported {
+            warn!(
+                "RtlSdrSource: overrun, dropped {} buffers",
+                overruns - self.reported
+            );
+            self.reported = overruns;
+            mio.post(0, Pmt::U64(overruns)).await;
+        }
+
+        let out = sio.output(0).slice::<Complex32>();
+        if out.is_empty() {
+            return Ok(());
+        }
+
+        if let Some((v, mut offset)) = self.buff.take() {
+            let n = std::cmp::min((v.len() - offset) / 2, out.len());
+            for (o, iq) in out
+                .iter_mut()
+                .zip(v[offset..offset + 2 * n].chunks_exact(2))
+            {
+                *o = Complex32::new(
+                    (iq[0] as f32 - 127.5) / 128.0,
+                    (iq[1] as f32 - 127.5) / 128.0,
+                );
+            }
+            offset += 2 * n;
+            sio.output(0).produce(n);
+            if v.len() - offset >= 2 {
+                self.buff = Some((v, offset));
+            } else {
+                io.call_again = true;
+            }
+        } else if let Some(v) = self.rx.as_mut().context("not started")?.next().await {
+            self.buff = Some((v, 0));
+            io.call_again = true;
+        } else {
+            bail!("RtlSdrSource: device stopped streaming");
+        }
+
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let index = match &self.serial {
+            Some(s) => {
+                let c = CString::new(s.as_str())?;
+                let i = unsafe { ffi::rtlsdr_get_index_by_serial(c.as_ptr()) };
+                check(i, "finding the device")?;
+                i as u32
+            }
+            None => self.index.unwrap_or(0),
+        };
+
+        let mut dev = std::ptr::null_mut();
+        check(
+            unsafe { ffi::rtlsdr_open(&mut dev, index) },
+            "opening the device",
+        )?;
+        self.dev = Some(Device(dev));
+        info!("RtlSdrSource: opened device {}", index);
+
+        unsafe {
+            let ds = match self.direct_sampling {
+                RtlSdrDirectSampling::Off => 0,
+                RtlSdrDirectSampling::I => 1,
+                RtlSdrDirectSampling::Q => 2,
+            };
+            check(
+                ffi::rtlsdr_set_direct_sampling(dev, ds),
+                "setting direct sampling",
+            )?;
+            if self.ppm != 0 {
+                check(
+                    ffi::rtlsdr_set_freq_correction(dev, self.ppm),
+                    "setting the frequency correction",
+                )?;
+            }
+            check(
+                ffi::rtlsdr_set_agc_mode(dev, i32::from(self.rtl_agc)),
+                "setting the RTL AGC",
+            )?;
+            check(
+                ffi::rtlsdr_set_bias_tee(dev, i32::from(self.bias_tee)),
+                "setting the bias tee",
+            )?;
+        }
+        self.set_sample_rate(self.sample_rate)?;
+        self.set_frequency(self.frequency)?;
+        self.set_gain(self.gain)?;
+        check(
+            unsafe { ffi::rtlsdr_reset_buffer(dev) },
+            "resetting the buffer",
+        )?;
+
+        let (tx, rx) = mpsc::channel(QUEUE_SIZE);
+        self.rx = Some(rx);
+        let ctx = Box::new(ReadContext {
+            tx,
+            overruns: self.overruns.clone(),
+        });
+        let device = Device(dev);
+        let buffers = self.buffers;
+        let buffer_len = self.buffer_len;
+        self.thread = Some(std::thread::spawn(move || {
+            let device = device;
+            let ctx = Box::into_raw(ctx);
+            let ret = unsafe {
+                ffi::rtlsdr_read_async(
+                    device.0,
+                    read_callback,
+                    ctx as *mut c_void,
+                    buffers,
+                    buffer_len,
+                )
+            };
+            drop(unsafe { Box::from_raw(ctx) });
+            ret
+        }));
+
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let dev = self.dev()?;
+        unsafe {
+            ffi::rtlsdr_cancel_async(dev);
+        }
+        if let Some(t) = self.thread.take() {
+            let _ = t.join();
+        }
+        unsafe {
+            ffi::rtlsdr_close(dev);
+        }
+        self.dev = None;
+        Ok(())
+    }
+}
+
+/// Receive samples from an RTL-SDR, using librtlsdr directly.
+///
+/// The samples are read asynchronously in a separate thread. If the flowgraph does not keep
+/// up, USB buffers are dropped, which is reported on the `overrun` output. Without a gain, the
+/// AGC of the tuner is enabled; otherwise, the closest gain supported by the tuner is used.
+///
+/// # Inputs
+///
+/// **Message**: `freq`: Center frequency in Hz
+///
+/// **Message**: `gain`: Gain in dB, [Pmt::Null] for AGC
+///
+/// **Message**: `sample_rate`: Sample rate
+///
+/// **Message**: `status`: Returns a [Pmt::MapStrPmt] with the current settings and overruns
+///
+/// # Outputs
+///
+/// `out`: Received samples
+///
+/// **Message**: `overrun`: Total number of dropped buffers
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::rtlsdr::RtlSdrSourceBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let src = fg.add_block(
+///     RtlSdrSourceBuilder::new()
+///         .frequency(100e6)
+///         .sample_rate(2.4e6)
+///         .gain(30.0)
+///         .ppm(-2)
+///         .build(),
+/// );
+/// ```
+pub struct RtlSdrSourceBuilder {
+    index: Option<u32>,
+    serial: Option<String>,
+    frequency: f64,
+    sample_rate: f64,
+    gain: Option<f64>,
+    ppm: i32,
+    bias_tee: bool,
+    direct_sampling: RtlSdrDirectSampling,
+    rtl_agc: bool,
+    buffers: u32,
+    buffer_len: u32,
+}
+
+impl RtlSdrSourceBuilder {
+    pub fn new() -> RtlSdrSourceBuilder {
+        RtlSdrSourceBuilder {
+            index: None,
+            serial: None,
+            frequency: 100e6,
+            sample_rate: 2.048e6,
+            gain: None,
+            ppm: 0,
+            bias_tee: false,
+            direct_sampling: RtlSdrDirectSampling::Off,
+            rtl_agc: false,
+            buffers: 15,
+            buffer_len: 16 * 16384,
+        }
+    }
+
+    /// Index of the device. Defaults to the first device.
+    #[must_use]
+    pub fn index(mut self, index: u32) -> RtlSdrSourceBuilder {
+        self.index = Some(index);
+        self
+    }
+
+    /// Select the device by the serial number of its EEPROM.
+    #[must_use]
+    pub fn serial(mut self, serial: impl Into<String>) -> RtlSdrSourceBuilder {
+        self.serial = Some(serial.into());
+        self
+    }
+
+    /// Center frequency in Hz.
+    #[must_use]
+    pub fn frequency(mut self, frequency: f64) -> RtlSdrSourceBuilder {
+        self.frequency = frequency;
+        self
+    }
+
+    #[must_use]
+    pub fn sample_rate(mut self, sample_rate: f64) -> RtlSdrSourceBuilder {
+        self.sample_rate = sample_rate;
+        self
+    }
+
+    /// Tuner gain in dB. Defaults to the AGC of the tuner.
+    #[must_use]
+    pub fn gain(mut self, gain: f64) -> RtlSdrSourceBuilder {
+        self.gain = Some(gain);
+        self
+    }
+
+    /// Frequency correction of the oscillator in ppm.
+    #[must_use]
+    pub fn ppm(mut self, ppm: i32) -> RtlSdrSourceBuilder {
+        self.ppm = ppm;
+        self
+    }
+
+    /// Power an active antenna or LNA through the antenna port.
+    #[must_use]
+    pub fn bias_tee(mut self, bias_tee: bool) -> RtlSdrSourceBuilder {
+        self.bias_tee = bias_tee;
+        self
+    }
+
+    #[must_use]
+    pub fn direct_sampling(mut self, mode: RtlSdrDirectSampling) -> RtlSdrSourceBuilder {
+        self.direct_sampling = mode;
+        self
+    }
+
+    /// Enable the digital AGC of the RTL2832U.
+    #[must_use]
+    pub fn rtl_agc(mut self, rtl_agc: bool) -> RtlSdrSourceBuilder {
+        self.rtl_agc = rtl_agc;
+        self
+    }
+
+    /// Number and length in bytes of the USB buffers. The length has to be a multiple of 512.
+    #[must_use]
+    pub fn buffers(mut self, n: u32, len: u32) -> RtlSdrSourceBuilder {
+        assert_eq!(len % 512, 0, "buffer length has to be a multiple of 512");
+        self.buffers = n;
+        self.buffer_len = len;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        RtlSdrSource::create(self)
+    }
+}
+
+impl Default for RtlSdrSourceBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
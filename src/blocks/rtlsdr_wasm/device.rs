@@ -0,0 +1,449 @@
+//! RTL2832U demodulator, accessed through WebUSB.
+//!
+//! Register access and initialization follow librtlsdr.
+use futures::Future;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::UsbControlTransferParameters;
+use web_sys::UsbDevice;
+use web_sys::UsbInTransferResult;
+use web_sys::UsbOutTransferResult;
+use web_sys::UsbRecipient;
+use web_sys::UsbRequestType;
+use web_sys::UsbTransferStatus;
+
+use super::r82xx;
+use super::r82xx::R82xx;
+use super::RtlSdrSourceBuilder;
+use crate::anyhow::{anyhow, bail, Result};
+
+/// Vendor and product IDs of RTL2832U dongles.
+pub const DEVICE_IDS: [(u16, u16); 2] = [(0x0bda, 0x2838), (0x0bda, 0x2832)];
+
+const XTAL_FREQ: f64 = 28.8e6;
+const BULK_ENDPOINT: u8 = 1;
+
+// register blocks
+const USBB: u16 = 1;
+const SYSB: u16 = 2;
+const IICB: u16 = 6;
+
+// USB registers
+const USB_SYSCTL: u16 = 0x2000;
+const USB_EPA_CTL: u16 = 0x2148;
+const USB_EPA_MAXPKT: u16 = 0x2158;
+
+// system registers
+const DEMOD_CTL: u16 = 0x3000;
+const GPO: u16 = 0x3001;
+const GPOE: u16 = 0x3003;
+const GPD: u16 = 0x3004;
+const DEMOD_CTL_1: u16 = 0x300b;
+
+/// Coefficients of the decimation filter (8 and 12 bit signed).
+const FIR: [i32; 16] = [
+    -54, -36, -41, -40, -32, -14, 14, 53, 101, 156, 215, 273, 327, 372, 404, 421,
+];
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = setTimeout)]
+    fn set_timeout(closure: &js_sys::Function, millis: i32) -> i32;
+}
+
+pub fn js_err(e: JsValue) -> crate::anyhow::Error {
+    anyhow!("{:?}", e)
+}
+
+/// Await a promise.
+pub async fn wait<T: JsCast>(promise: impl Into<JsValue>) -> Result<T> {
+    let v: JsValue = JsFuture::from(js_sys::Promise::unchecked_from_js(promise.into()))
+        .await
+        .map_err(js_err)?;
+    Ok(v.unchecked_into())
+}
+
+pub async fn sleep(millis: i32) {
+    let p = js_sys::Promise::new(&mut |resolve, _| {
+        set_timeout(&resolve, millis);
+    });
+    let _ = JsFuture::from(p).await;
+}
+
+fn transfer_data(r: &UsbInTransferResult) -> Result<Vec<u8>> {
+    if r.status() != UsbTransferStatus::Ok {
+        bail!("USB transfer failed ({:?})", r.status());
+    }
+    let v = r
+        .data()
+        .ok_or_else(|| anyhow!("USB transfer without data"))?;
+    let buffer = js_sys::Uint8Array::new(&v.buffer());
+    let offset = v.byte_offset() as u32;
+    Ok(buffer
+        .subarray(offset, offset + v.byte_length() as u32)
+        .to_vec())
+}
+
+/// Demodulator registers, accessed through vendor control transfers.
+pub struct Rtl2832 {
+    usb: UsbDevice,
+    ppm: i32,
+}
+
+impl Rtl2832 {
+    fn setup(index: u16, value: u16) -> UsbControlTransferParameters {
+        UsbControlTransferParameters::new(
+            index,
+            UsbRecipient::Device,
+            0,
+            UsbRequestType::Vendor,
+            value,
+        )
+    }
+
+    async fn write_array(&self, block: u16, addr: u16, data: &[u8]) -> Result<()> {
+        let setup = Self::setup((block << 8) | 0x10, addr);
+        let p = self
+            .usb
+            .control_transfer_out_with_u8_array(&setup, &js_sys::Uint8Array::from(data))
+            .map_err(js_err)?;
+        let r: UsbOutTransferResult = wait(p).await?;
+        if r.status() != UsbTransferStatus::Ok {
+            bail!("USB control transfer failed ({:?})", r.status());
+        }
+        Ok(())
+    }
+
+    async fn read_array(&self, block: u16, addr: u16, len: u16) -> Result<Vec<u8>> {
+        let setup = Self::setup(block << 8, addr);
+        let r: UsbInTransferResult = wait(self.usb.control_transfer_in(&setup, len)).await?;
+        transfer_data(&r)
+    }
+
+    fn encode(val: u16, len: usize) -> Vec<u8> {
+        if len == 1 {
+            vec![val as u8]
+        } else {
+            vec![(val >> 8) as u8, val as u8]
+        }
+    }
+
+    async fn write_reg(&self, block: u16, addr: u16, val: u16, len: usize) -> Result<()> {
+        self.write_array(block, addr, &Self::encode(val, len)).await
+    }
+
+    async fn read_reg(&self, block: u16, addr: u16, len: u16) -> Result<u16> {
+        let d = self.read_array(block, addr, len).await?;
+        Ok(d.first().copied().unwrap_or(0) as u16 | (d.get(1).copied().unwrap_or(0) as u16) << 8)
+    }
+
+    pub async fn demod_write_reg(&self, page: u8, addr: u16, val: u16, len: usize) -> Result<()> {
+        let setup = Self::setup(0x10 | page as u16, (addr << 8) | 0x20);
+        let p = self
+            .usb
+            .control_transfer_out_with_u8_array(
+                &setup,
+                &js_sys::Uint8Array::from(&Self::encode(val, len)[..]),
+            )
+            .map_err(js_err)?;
+        let r: UsbOutTransferResult = wait(p).await?;
+        if r.status() != UsbTransferStatus::Ok {
+            bail!("USB control transfer failed ({:?})", r.status());
+        }
+        // dummy read, as librtlsdr does
+        self.demod_read_reg(0x0a, 0x01).await?;
+        Ok(())
+    }
+
+    async fn demod_read_reg(&self, page: u8, addr: u16) -> Result<u8> {
+        let setup = Self::setup(page as u16, (addr << 8) | 0x20);
+        let r: UsbInTransferResult = wait(self.usb.control_transfer_in(&setup, 1)).await?;
+        Ok(transfer_data(&r)?.first().copied().unwrap_or(0))
+    }
+
+    pub async fn i2c_write(&self, i2c_addr: u8, data: &[u8]) -> Result<()> {
+        self.write_array(IICB, i2c_addr as u16, data).await
+    }
+
+    pub async fn i2c_read(&self, i2c_addr: u8, len: u16) -> Result<Vec<u8>> {
+        self.read_array(IICB, i2c_addr as u16, len).await
+    }
+
+    async fn i2c_read_reg(&self, i2c_addr: u8, reg: u8) -> Result<u8> {
+        self.i2c_write(i2c_addr, &[reg]).await?;
+        Ok(self
+            .i2c_read(i2c_addr, 1)
+            .await?
+            .first()
+            .copied()
+            .unwrap_or(0))
+    }
+
+    pub async fn set_i2c_repeater(&self, on: bool) -> Result<()> {
+        self.demod_write_reg(1, 0x01, if on { 0x18 } else { 0x10 }, 1)
+            .await
+    }
+
+    fn xtal(&self) -> f64 {
+        XTAL_FREQ * (1.0 + self.ppm as f64 / 1e6)
+    }
+
+    async fn set_fir(&self) -> Result<()> {
+        let mut fir = [0u8; 20];
+        for i in 0..8 {
+            fir[i] = FIR[i] as u8;
+        }
+        for i in (0..8).step_by(2) {
+            let val0 = FIR[8 + i];
+            let val1 = FIR[8 + i + 1];
+            fir[8 + i * 3 / 2] = (val0 >> 4) as u8;
+            fir[8 + i * 3 / 2 + 1] = ((val0 << 4) | ((val1 >> 8) & 0x0f)) as u8;
+            fir[8 + i * 3 / 2 + 2] = val1 as u8;
+        }
+        for (i, v) in fir.iter().enumerate() {
+            self.demod_write_reg(1, 0x1c + i as u16, *v as u16, 1)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn init_baseband(&self) -> Result<()> {
+        // initialize USB
+        self.write_reg(USBB, USB_SYSCTL, 0x09, 1).await?;
+        self.write_reg(USBB, USB_EPA_MAXPKT, 0x0002, 2).await?;
+        self.write_reg(USBB, USB_EPA_CTL, 0x1002, 2).await?;
+
+        // power on demodulator
+        self.write_reg(SYSB, DEMOD_CTL_1, 0x22, 1).await?;
+        self.write_reg(SYSB, DEMOD_CTL, 0xe8, 1).await?;
+
+        // reset demodulator
+        self.demod_write_reg(1, 0x01, 0x14, 1).await?;
+        self.demod_write_reg(1, 0x01, 0x10, 1).await?;
+
+        // disable spectrum inversion and adjacent channel rejection
+        self.demod_write_reg(1, 0x15, 0x00, 1).await?;
+        self.demod_write_reg(1, 0x16, 0x0000, 2).await?;
+
+        // clear DDC shift and IF frequency registers
+        for i in 0..6 {
+            self.demod_write_reg(1, 0x16 + i, 0x00, 1).await?;
+        }
+
+        self.set_fir().await?;
+
+        // enable SDR mode, disable DAGC
+        self.demod_write_reg(0, 0x19, 0x05, 1).await?;
+
+        // init FSM state-holding register
+        self.demod_write_reg(1, 0x93, 0xf0, 1).await?;
+        self.demod_write_reg(1, 0x94, 0x0f, 1).await?;
+
+        // disable AGC, RF and IF AGC loop, and PID filter
+        self.demod_write_reg(1, 0x11, 0x00, 1).await?;
+        self.demod_write_reg(1, 0x04, 0x00, 1).await?;
+        self.demod_write_reg(0, 0x61, 0x60, 1).await?;
+
+        // default ADC_I/ADC_Q datapath
+        self.demod_write_reg(0, 0x06, 0x80, 1).await?;
+
+        // enable zero-IF mode, DC cancellation, and IQ estimation/compensation
+        self.demod_write_reg(1, 0xb1, 0x1b, 1).await?;
+
+        // disable 4.096 MHz clock output on pin TP_CK0
+        self.demod_write_reg(0, 0x0d, 0x83, 1).await
+    }
+
+    async fn set_if_freq(&self, freq: u32) -> Result<()> {
+        let if_freq = -((freq as f64 * (1u64 << 22) as f64) / self.xtal()) as i32;
+        self.demod_write_reg(1, 0x19, ((if_freq >> 16) & 0x3f) as u16, 1)
+            .await?;
+        self.demod_write_reg(1, 0x1a, ((if_freq >> 8) & 0xff) as u16, 1)
+            .await?;
+        self.demod_write_reg(1, 0x1b, (if_freq & 0xff) as u16, 1)
+            .await
+    }
+
+    async fn set_sample_freq_correction(&self) -> Result<()> {
+        let offs = (-(self.ppm as f64) * (1u64 << 24) as f64 / 1e6) as i16;
+        self.demod_write_reg(1, 0x3f, (offs & 0xff) as u16, 1)
+            .await?;
+        self.demod_write_reg(1, 0x3e, ((offs >> 8) & 0x3f) as u16, 1)
+            .await
+    }
+
+    /// Set the sample rate and return the actual rate.
+    async fn set_sample_rate(&self, rate: f64) -> Result<f64> {
+        if rate <= 225e3 || rate > 3.2e6 || (rate > 300e3 && rate <= 900e3) {
+            bail!("invalid sample rate {}", rate);
+        }
+
+        let scale = (1u64 << 22) as f64 * self.xtal();
+        let ratio = (scale / rate) as u32 & 0x0fff_fffc;
+        let real_ratio = ratio | ((ratio & 0x0800_0000) << 1);
+
+        self.demod_write_reg(1, 0x9f, (ratio >> 16) as u16, 2)
+            .await?;
+        self.demod_write_reg(1, 0xa1, (ratio & 0xffff) as u16, 2)
+            .await?;
+        self.set_sample_freq_correction().await?;
+
+        // reset demodulator
+        self.demod_write_reg(1, 0x01, 0x14, 1).await?;
+        self.demod_write_reg(1, 0x01, 0x10, 1).await?;
+
+        Ok(scale / real_ratio as f64)
+    }
+
+    async fn set_gpio(&self, gpio: u8, on: bool) -> Result<()> {
+        let bit = 1 << gpio;
+        // configure as output
+        let r = self.read_reg(SYSB, GPD, 1).await?;
+        self.write_reg(SYSB, GPD, r & !bit, 1).await?;
+        let r = self.read_reg(SYSB, GPOE, 1).await?;
+        self.write_reg(SYSB, GPOE, r | bit, 1).await?;
+
+        let r = self.read_reg(SYSB, GPO, 1).await?;
+        let r = if on { r | bit } else { r & !bit };
+        self.write_reg(SYSB, GPO, r, 1).await
+    }
+
+    async fn reset_buffer(&self) -> Result<()> {
+        self.write_reg(USBB, USB_EPA_CTL, 0x1002, 2).await?;
+        self.write_reg(USBB, USB_EPA_CTL, 0x0000, 2).await
+    }
+
+    /// Start a bulk transfer of samples.
+    pub fn read(&self, len: u32) -> impl Future<Output = Result<Vec<u8>>> {
+        let p = self.usb.transfer_in(BULK_ENDPOINT, len);
+        async move {
+            let r: UsbInTransferResult = wait(p).await?;
+            transfer_data(&r)
+        }
+    }
+}
+
+/// RTL-SDR with an R820T or R828D tuner.
+pub struct Device {
+    rtl: Rtl2832,
+    tuner: R82xx,
+    pub sample_rate: f64,
+}
+
+impl Device {
+    /// Find the device, among the devices the user granted access to.
+    async fn find(index: Option<u32>, serial: Option<&str>) -> Result<UsbDevice> {
+        let devices: js_sys::Array = wait(super::usb()?.get_devices()).await?;
+        let mut devices = devices
+            .iter()
+            .map(|d| d.unchecked_into::<UsbDevice>())
+            .filter(|d| DEVICE_IDS.contains(&(d.vendor_id(), d.product_id())));
+
+        let dev = match serial {
+            Some(s) => devices.find(|d| d.serial_number().as_deref() == Some(s)),
+            None => devices.nth(index.unwrap_or(0) as usize),
+        };
+        dev.ok_or_else(|| anyhow!("RTL-SDR not found (request access with request_device())"))
+    }
+
+    pub async fn open(config: &RtlSdrSourceBuilder) -> Result<Device> {
+        let usb = Self::find(config.index, config.serial.as_deref()).await?;
+        wait::<JsValue>(usb.open()).await?;
+        if usb.configuration().is_none() {
+            wait::<JsValue>(usb.select_configuration(1)).await?;
+        }
+        wait::<JsValue>(usb.claim_interface(0)).await?;
+
+        let rtl = Rtl2832 {
+            usb,
+            ppm: config.ppm,
+        };
+        rtl.init_baseband().await?;
+
+        rtl.set_i2c_repeater(true).await?;
+        let chip = if rtl
+            .i2c_read_reg(r82xx::R820T_I2C_ADDR, r82xx::CHECK_ADDR)
+            .await?
+            == r82xx::CHECK_VAL
+        {
+            r82xx::Chip::R820T
+        } else if rtl
+            .i2c_read_reg(r82xx::R828D_I2C_ADDR, r82xx::CHECK_ADDR)
+            .await?
+            == r82xx::CHECK_VAL
+        {
+            r82xx::Chip::R828D
+        } else {
+            bail!("no supported tuner found (R820T, R820T2, R828D)");
+        };
+        info!("RtlSdrSource: found {:?} tuner", chip);
+
+        // disable zero-IF mode, only enable in-phase ADC input
+        rtl.demod_write_reg(1, 0xb1, 0x1a, 1).await?;
+        rtl.demod_write_reg(0, 0x08, 0x4d, 1).await?;
+        rtl.set_if_freq(r82xx::IF_FREQ).await?;
+        // enable spectrum inversion
+        rtl.demod_write_reg(1, 0x15, 0x01, 1).await?;
+
+        let mut tuner = R82xx::new(chip, config.ppm);
+        tuner.init(&rtl).await?;
+        rtl.set_i2c_repeater(false).await?;
+
+        rtl.demod_write_reg(0, 0x19, if config.rtl_agc { 0x25 } else { 0x05 }, 1)
+            .await?;
+        rtl.set_gpio(0, config.bias_tee).await?;
+
+        let mut dev = Device {
+            rtl,
+            tuner,
+            sample_rate: 0.0,
+        };
+        dev.set_sample_rate(config.sample_rate).await?;
+        dev.set_frequency(config.frequency).await?;
+        dev.set_gain(config.gain).await?;
+        dev.rtl.reset_buffer().await?;
+
+        Ok(dev)
+    }
+
+    pub async fn set_sample_rate(&mut self, rate: f64) -> Result<()> {
+        self.sample_rate = self.rtl.set_sample_rate(rate).await?;
+        Ok(())
+    }
+
+    pub async fn set_frequency(&mut self, frequency: f64) -> Result<()> {
+        self.rtl.set_i2c_repeater(true).await?;
+        let ret = self.tuner.set_freq(&self.rtl, frequency as u32).await;
+        self.rtl.set_i2c_repeater(false).await?;
+        ret
+    }
+
+    /// Set the gain in dB, choosing the closest gain supported by the tuner, or enable AGC.
+    pub async fn set_gain(&mut self, gain: Option<f64>) -> Result<()> {
+        self.rtl.set_i2c_repeater(true).await?;
+        let ret = match gain {
+            Some(g) => {
+                // gains are in tenths of dB
+                let target = (g * 10.0) as i32;
+                let closest = r82xx::GAINS
+                    .iter()
+                    .min_by_key(|x| (*x - target).abs())
+                    .copied()
+                    .unwrap_or(target);
+                self.tuner.set_gain(&self.rtl, true, closest).await
+            }
+            None => self.tuner.set_gain(&self.rtl, false, 0).await,
+        };
+        self.rtl.set_i2c_repeater(false).await?;
+        ret
+    }
+
+    pub fn read(&self, len: u32) -> impl Future<Output = Result<Vec<u8>>> {
+        self.rtl.read(len)
+    }
+
+    pub async fn close(self) {
+        let _ = wait::<JsValue>(self.rtl.usb.close()).await;
+    }
+}
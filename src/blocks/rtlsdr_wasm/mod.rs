@@ -0,0 +1,521 @@
+//! ## RTL-SDR Blocks (requires `rtlsdr` feature)
+//!
+//! In the browser, the RTL-SDR is driven through [WebUSB](https://wicg.github.io/webusb/),
+//! using a port of the RTL2832U and R820T/R828D drivers of librtlsdr. WebUSB is an unstable
+//! API of `web-sys`, i.e., build with `RUSTFLAGS=--cfg=web_sys_unstable_apis`.
+//!
+//! Browsers only grant access to a device after the user selected it. Call
+//! [request_device] (exported to JavaScript as `request_rtlsdr`) from a user gesture, e.g.,
+//! a button click, before the flowgraph is started. On Linux, the `dvb_usb_rtl28xxu` kernel
+//! module has to be unloaded or blacklisted.
+use futures::channel::mpsc;
+use futures::channel::oneshot;
+use futures::stream::FuturesOrdered;
+use futures::FutureExt;
+use futures::SinkExt;
+use futures::StreamExt;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use crate::anyhow::{anyhow, bail, Context, Result};
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+mod device;
+mod r82xx;
+
+use device::wait;
+use device::Device;
+
+/// Number of USB buffers that are queued for the block.
+const QUEUE_SIZE: usize = 16;
+
+fn usb() -> Result<web_sys::Usb> {
+    let global = js_sys::global();
+    if let Some(w) = global.dyn_ref::<web_sys::Window>() {
+        Ok(w.navigator().usb())
+    } else if let Some(w) = global.dyn_ref::<web_sys::WorkerGlobalScope>() {
+        Ok(w.navigator().usb())
+    } else {
+        bail!("WebUSB not available")
+    }
+}
+
+/// Ask the user to select an RTL-SDR, granting the page access to it.
+///
+/// Has to be called from a user gesture. Returns the name of the device.
+pub async fn request_device() -> Result<String> {
+    let filters: js_sys::Array = device::DEVICE_IDS
+        .iter()
+        .map(|(vendor, product)| {
+            let f = web_sys::UsbDeviceFilter::new();
+            f.set_vendor_id(*vendor);
+            f.set_product_id(*product);
+            JsValue::from(f)
+        })
+        .collect();
+    let options = web_sys::UsbDeviceRequestOptions::new(&filters);
+    let dev: web_sys::UsbDevice = wait(usb()?.request_device(&options)).await?;
+    Ok(dev.product_name().unwrap_or_else(|| "RTL-SDR".to_string()))
+}
+
+#[doc(hidden)]
+#[wasm_bindgen]
+pub async fn request_rtlsdr() -> Result<String, JsValue> {
+    request_device()
+        .await
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Settings, changed by the device task.
+enum Command {
+    Frequency(f64, oneshot::Sender<Result<()>>),
+    SampleRate(f64, oneshot::Sender<Result<()>>),
+    Gain(Option<f64>, oneshot::Sender<Result<()>>),
+}
+
+/// Open the device and stream samples, until the block drops its end of the channels.
+///
+/// JavaScript objects cannot be shared between threads, so the device lives in a local task.
+async fn run(
+    config: RtlSdrSourceBuilder,
+    mut commands: mpsc::UnboundedReceiver<Command>,
+    mut tx: mpsc::Sender<Vec<u8>>,
+    ready: oneshot::Sender<Result<()>>,
+) {
+    let mut dev = match Device::open(&config).await {
+        Ok(dev) => {
+            let _ = ready.send(Ok(()));
+            dev
+        }
+        Err(e) => {
+            let _ = ready.send(Err(e));
+            return;
+        }
+    };
+
+    let mut transfers = FuturesOrdered::new();
+    for _ in 0..config.buffers {
+        transfers.push_back(dev.read(config.buffer_len));
+    }
+
+    loop {
+        futures::select! {
+            c = commands.next() => match c {
+                Some(Command::Frequency(f, r)) => {
+                    let _ = r.send(dev.set_frequency(f).await);
+                }
+                Some(Command::SampleRate(s, r)) => {
+                    let _ = r.send(dev.set_sample_rate(s).await);
+                }
+                Some(Command::Gain(g, r)) => {
+                    let _ = r.send(dev.set_gain(g).await);
+                }
+                None => break,
+            },
+            d = transfers.next() => match d {
+                Some(Ok(data)) => {
+                    transfers.push_back(dev.read(config.buffer_len));
+                    if tx.send(data).await.is_err() {
+                        break;
+                    }
+                }
+                Some(Err(e)) => {
+                    warn!("RtlSdrSource: USB transfer failed ({})", e);
+                    break;
+                }
+                None => break,
+            },
+        }
+    }
+
+    dev.close().await;
+}
+
+fn pmt_to_f64(p: &Pmt) -> Option<f64> {
+    match p {
+        Pmt::F32(v) => Some(*v as f64),
+        Pmt::F64(v) => Some(*v),
+        Pmt::U32(v) => Some(*v as f64),
+        Pmt::U64(v) => Some(*v as f64),
+        _ => None,
+    }
+}
+
+/// Receive samples from an RTL-SDR.
+pub struct RtlSdrSource {
+    config: RtlSdrSourceBuilder,
+    commands: Option<mpsc::UnboundedSender<Command>>,
+    rx: Option<mpsc::Receiver<Vec<u8>>>,
+    buff: Option<(Vec<u8>, usize)>,
+}
+
+impl RtlSdrSource {
+    /// Names of the devices that the page has access to.
+    pub async fn devices() -> Result<Vec<String>> {
+        let devices: js_sys::Array = wait(usb()?.get_devices()).await?;
+        Ok(devices
+            .iter()
+            .map(|d| d.unchecked_into::<web_sys::UsbDevice>())
+            .filter(|d| device::DEVICE_IDS.contains(&(d.vendor_id(), d.product_id())))
+            .map(|d| {
+                let name = d.product_name().unwrap_or_else(|| "RTL-SDR".to_string());
+                match d.serial_number() {
+                    Some(s) => format!("{} (serial {})", name, s),
+                    None => name,
+                }
+            })
+            .collect())
+    }
+
+    async fn command(
+        &mut self,
+        c: impl FnOnce(oneshot::Sender<Result<()>>) -> Command,
+    ) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.commands
+            .as_ref()
+            .context("device not open")?
+            .unbounded_send(c(tx))
+            .map_err(|_| anyhow!("device task stopped"))?;
+        rx.await.map_err(|_| anyhow!("device task stopped"))?
+    }
+
+    async fn set_frequency(&mut self, frequency: f64) -> Result<()> {
+        self.command(|r| Command::Frequency(frequency, r)).await?;
+        self.config.frequency = frequency;
+        Ok(())
+    }
+
+    async fn set_sample_rate(&mut self, sample_rate: f64) -> Result<()> {
+        self.command(|r| Command::SampleRate(sample_rate, r))
+            .await?;
+        self.config.sample_rate = sample_rate;
+        Ok(())
+    }
+
+    /// Set the gain in dB, choosing the closest gain supported by the tuner, or enable AGC.
+    async fn set_gain(&mut self, gain: Option<f64>) -> Result<()> {
+        self.command(|r| Command::Gain(gain, r)).await?;
+        self.config.gain = gain;
+        Ok(())
+    }
+
+    fn status(&self) -> Pmt {
+        let mut m = std::collections::HashMap::new();
+        m.insert("frequency".to_string(), Pmt::F64(self.config.frequency));
+        m.insert("sample_rate".to_string(), Pmt::F64(self.config.sample_rate));
+        m.insert(
+            "gain".to_string(),
+            self.config.gain.map(Pmt::F64).unwrap_or(Pmt::Null),
+        );
+        Pmt::MapStrPmt(m)
+    }
+
+    fn create(builder: RtlSdrSourceBuilder) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("RtlSdrSource").build(),
+            StreamIoBuilder::new()
+                .add_output::<Complex32>("out")
+                .build(),
+            MessageIoBuilder::new()
+                .add_input(
+                    "freq",
+                    |block: &mut RtlSdrSource,
+                     _mio: &mut MessageIo<RtlSdrSource>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            match pmt_to_f64(&p) {
+                                Some(f) => block.set_frequency(f).await?,
+                                None => {
+                                    warn!("RtlSdrSource/freq: received wrong PMT type. {:?}", p)
+                                }
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .add_input(
+                    "gain",
+                    |block: &mut RtlSdrSource,
+                     _mio: &mut MessageIo<RtlSdrSource>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            match (&p, pmt_to_f64(&p)) {
+                                (_, Some(g)) => block.set_gain(Some(g)).await?,
+                                (Pmt::Null, _) => block.set_gain(None).await?,
+                                _ => warn!("RtlSdrSource/gain: received wrong PMT type. {:?}", p),
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .add_input(
+                    "sample_rate",
+                    |block: &mut RtlSdrSource,
+                     _mio: &mut MessageIo<RtlSdrSource>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            match pmt_to_f64(&p) {
+                                Some(s) => block.set_sample_rate(s).await?,
+                                None => warn!(
+                                    "RtlSdrSource/sample_rate: received wrong PMT type. {:?}",
+                                    p
+                                ),
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .add_input(
+                    "status",
+                    |block: &mut RtlSdrSource,
+                     _mio: &mut MessageIo<RtlSdrSource>,
+                     _meta: &mut BlockMeta,
+                     _p: Pmt| { async move { Ok(block.status()) }.boxed() },
+                )
+                .build(),
+            RtlSdrSource {
+                config: builder,
+                commands: None,
+                rx: None,
+                buff: None,
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for RtlSdrSource {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let out = sio.output(0).slice::<Complex32>();
+        if out.is_empty() {
+            return Ok(());
+        }
+
+        if let Some((v, mut offset)) = self.buff.take() {
+            let n = std::cmp::min((v.len() - offset) / 2, out.len());
+            for (o, iq) in out
+                .iter_mut()
+                .zip(v[offset..offset + 2 * n].chunks_exact(2))
+            {
+                *o = Complex32::new(
+                    (iq[0] as f32 - 127.5) / 128.0,
+                    (iq[1] as f32 - 127.5) / 128.0,
+                );
+            }
+            offset += 2 * n;
+            sio.output(0).produce(n);
+            if v.len() - offset >= 2 {
+                self.buff = Some((v, offset));
+            } else {
+                io.call_again = true;
+            }
+        } else if let Some(v) = self.rx.as_mut().context("not started")?.next().await {
+            self.buff = Some((v, 0));
+            io.call_again = true;
+        } else {
+            bail!("RtlSdrSource: device stopped streaming");
+        }
+
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let (tx, rx) = mpsc::channel(QUEUE_SIZE);
+        let (commands_tx, commands_rx) = mpsc::unbounded();
+        let (ready_tx, ready_rx) = oneshot::channel();
+
+        wasm_bindgen_futures::spawn_local(run(self.config.clone(), commands_rx, tx, ready_tx));
+        ready_rx
+            .await
+            .map_err(|_| anyhow!("RtlSdrSource: device task stopped"))??;
+        info!("RtlSdrSource: opened device");
+
+        self.rx = Some(rx);
+        self.commands = Some(commands_tx);
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        // the device task closes the device, when the channels are dropped
+        self.commands = None;
+        self.rx = None;
+        Ok(())
+    }
+}
+
+/// Receive samples from an RTL-SDR in the browser, using WebUSB.
+///
+/// The page has to be granted access to the device with [request_device] first. Bulk
+/// transfers are queued in a local task and passed to the block. Without a gain, the AGC of
+/// the tuner is enabled; otherwise, the closest gain supported by the tuner is used. Only
+/// R820T, R820T2, and R828D tuners are supported.
+///
+/// # Inputs
+///
+/// **Message**: `freq`: Center frequency in Hz
+///
+/// **Message**: `gain`: Gain in dB, [Pmt::Null] for AGC
+///
+/// **Message**: `sample_rate`: Sample rate
+///
+/// **Message**: `status`: Returns a [Pmt::MapStrPmt] with the current settings
+///
+/// # Outputs
+///
+/// `out`: Received samples
+///
+/// # Usage
+/// ```ignore
+/// use futuresdr::blocks::rtlsdr::RtlSdrSourceBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let src = fg.add_block(
+///     RtlSdrSourceBuilder::new()
+///         .frequency(100e6)
+///         .sample_rate(2.4e6)
+///         .gain(30.0)
+///         .build(),
+/// );
+/// ```
+#[derive(Clone)]
+pub struct RtlSdrSourceBuilder {
+    index: Option<u32>,
+    serial: Option<String>,
+    frequency: f64,
+    sample_rate: f64,
+    gain: Option<f64>,
+    ppm: i32,
+    bias_tee: bool,
+    rtl_agc: bool,
+    buffers: u32,
+    buffer_len: u32,
+}
+
+impl RtlSdrSourceBuilder {
+    pub fn new() -> RtlSdrSourceBuilder {
+        RtlSdrSourceBuilder {
+            index: None,
+            serial: None,
+            frequency: 100e6,
+            sample_rate: 2.048e6,
+            gain: None,
+            ppm: 0,
+            bias_tee: false,
+            rtl_agc: false,
+            buffers: 8,
+            buffer_len: 16 * 16384,
+        }
+    }
+
+    /// Index of the device, among the devices the page has access to. Defaults to the first
+    /// device.
+    #[must_use]
+    pub fn index(mut self, index: u32) -> RtlSdrSourceBuilder {
+        self.index = Some(index);
+        self
+    }
+
+    /// Select the device by the serial number of its EEPROM.
+    #[must_use]
+    pub fn serial(mut self, serial: impl Into<String>) -> RtlSdrSourceBuilder {
+        self.serial = Some(serial.into());
+        self
+    }
+
+    /// Center frequency in Hz.
+    #[must_use]
+    pub fn frequency(mut self, frequency: f64) -> RtlSdrSourceBuilder {
+        self.frequency = frequency;
+        self
+    }
+
+    #[must_use]
+    pub fn sample_rate(mut self, sample_rate: f64) -> RtlSdrSourceBuilder {
+        self.sample_rate = sample_rate;
+        self
+    }
+
+    /// Tuner gain in dB. Defaults to the AGC of the tuner.
+    #[must_use]
+    pub fn gain(mut self, gain: f64) -> RtlSdrSourceBuilder {
+        self.gain = Some(gain);
+        self
+    }
+
+    /// Frequency correction of the oscillator in ppm.
+    #[must_use]
+    pub fn ppm(mut self, ppm: i32) -> RtlSdrSourceBuilder {
+        self.ppm = ppm;
+        self
+    }
+
+    /// Power an active antenna or LNA through the antenna port.
+    #[must_use]
+    pub fn bias_tee(mut self, bias_tee: bool) -> RtlSdrSourceBuilder {
+        self.bias_tee = bias_tee;
+        self
+    }
+
+    /// Enable the digital AGC of the RTL2832U.
+    #[must_use]
+    pub fn rtl_agc(mut self, rtl_agc: bool) -> RtlSdrSourceBuilder {
+        self.rtl_agc = rtl_agc;
+        self
+    }
+
+    /// Number of queued bulk transfers and their length in bytes. The length has to be a
+    /// multiple of 512.
+    #[must_use]
+    pub fn buffers(mut self, n: u32, len: u32) -> RtlSdrSourceBuilder {
+        assert_eq!(len % 512, 0, "buffer length has to be a multiple of 512");
+        self.buffers = n;
+        self.buffer_len = len;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        RtlSdrSource::create(self)
+    }
+}
+
+impl Default for RtlSdrSourceBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
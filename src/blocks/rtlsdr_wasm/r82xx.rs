@@ -0,0 +1,417 @@
+//! Rafael Micro R820T, R820T2, and R828D tuners.
+//!
+//! Port of the tuner driver of librtlsdr, configured for SDR use (DVB-T standard, 6 MHz
+//! bandwidth, 3.57 MHz IF).
+use super::device::sleep;
+use super::device::Rtl2832;
+use crate::anyhow::{bail, Result};
+
+pub const R820T_I2C_ADDR: u8 = 0x34;
+pub const R828D_I2C_ADDR: u8 = 0x74;
+pub const CHECK_ADDR: u8 = 0x00;
+pub const CHECK_VAL: u8 = 0x69;
+
+/// Intermediate frequency in Hz.
+pub const IF_FREQ: u32 = 3_570_000;
+
+/// Supported gains in tenths of dB.
+pub const GAINS: [i32; 29] = [
+    0, 9, 14, 27, 37, 77, 87, 125, 144, 157, 166, 197, 207, 229, 254, 280, 297, 328, 338, 364, 372,
+    386, 402, 421, 434, 439, 445, 480, 496,
+];
+
+const REG_SHADOW_START: usize = 5;
+const NUM_REGS: usize = 30;
+const MAX_I2C_MSG_LEN: usize = 8;
+
+const INIT_ARRAY: [u8; 27] = [
+    0x83, 0x32, 0x75, // 05 to 07
+    0xc0, 0x40, 0xd6, 0x6c, // 08 to 0b
+    0xf5, 0x63, 0x75, 0x68, // 0c to 0f
+    0x6c, 0x83, 0x80, 0x00, // 10 to 13
+    0x0f, 0x00, 0xc0, 0x30, // 14 to 17
+    0x48, 0xcc, 0x60, 0x00, // 18 to 1b
+    0x54, 0xae, 0x4a, 0xc0, // 1c to 1f
+];
+
+const LNA_GAIN_STEPS: [i32; 16] = [0, 9, 13, 40, 38, 13, 31, 22, 26, 31, 26, 14, 19, 5, 35, 13];
+const MIXER_GAIN_STEPS: [i32; 16] = [0, 5, 10, 10, 19, 9, 10, 25, 17, 10, 8, 16, 13, 6, 3, -8];
+
+/// Tracking filter and RF mux settings, depending on the LO frequency.
+struct FreqRange {
+    /// Start of the range in MHz.
+    freq: u32,
+    open_d: u8,
+    rf_mux_ploy: u8,
+    tf_c: u8,
+}
+
+const fn range(freq: u32, open_d: u8, rf_mux_ploy: u8, tf_c: u8) -> FreqRange {
+    FreqRange {
+        freq,
+        open_d,
+        rf_mux_ploy,
+        tf_c,
+    }
+}
+
+const FREQ_RANGES: [FreqRange; 21] = [
+    range(0, 0x08, 0x02, 0xdf),
+    range(50, 0x08, 0x02, 0xbe),
+    range(55, 0x08, 0x02, 0x8b),
+    range(60, 0x08, 0x02, 0x7b),
+    range(65, 0x08, 0x02, 0x69),
+    range(70, 0x08, 0x02, 0x58),
+    range(75, 0x00, 0x02, 0x44),
+    range(80, 0x00, 0x02, 0x44),
+    range(90, 0x00, 0x02, 0x34),
+    range(100, 0x00, 0x02, 0x34),
+    range(110, 0x00, 0x02, 0x24),
+    range(120, 0x00, 0x02, 0x24),
+    range(140, 0x00, 0x02, 0x14),
+    range(180, 0x00, 0x02, 0x13),
+    range(220, 0x00, 0x02, 0x13),
+    range(250, 0x00, 0x02, 0x11),
+    range(280, 0x00, 0x02, 0x00),
+    range(310, 0x00, 0x41, 0x00),
+    range(450, 0x00, 0x41, 0x00),
+    range(588, 0x00, 0x40, 0x00),
+    range(650, 0x00, 0x40, 0x00),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Chip {
+    R820T,
+    R828D,
+}
+
+fn bitrev(b: u8) -> u8 {
+    const LUT: [u8; 16] = [
+        0x0, 0x8, 0x4, 0xc, 0x2, 0xa, 0x6, 0xe, 0x1, 0x9, 0x5, 0xd, 0x3, 0xb, 0x7, 0xf,
+    ];
+    (LUT[(b & 0xf) as usize] << 4) | LUT[(b >> 4) as usize]
+}
+
+/// Tuner state, including a shadow copy of its registers.
+pub struct R82xx {
+    chip: Chip,
+    i2c_addr: u8,
+    xtal: u32,
+    regs: [u8; NUM_REGS],
+    input: u8,
+}
+
+impl R82xx {
+    pub fn new(chip: Chip, ppm: i32) -> R82xx {
+        let (i2c_addr, xtal) = match chip {
+            Chip::R820T => (R820T_I2C_ADDR, 28_800_000.0),
+            Chip::R828D => (R828D_I2C_ADDR, 16_000_000.0),
+        };
+        R82xx {
+            chip,
+            i2c_addr,
+            xtal: (xtal * (1.0 + ppm as f64 / 1e6)) as u32,
+            regs: [0; NUM_REGS],
+            input: 0,
+        }
+    }
+
+    async fn write(&mut self, rtl: &Rtl2832, reg: u8, val: &[u8]) -> Result<()> {
+        let r = reg as usize - REG_SHADOW_START;
+        let n = val.len().min(NUM_REGS - r);
+        self.regs[r..r + n].copy_from_slice(&val[..n]);
+
+        for (i, chunk) in val.chunks(MAX_I2C_MSG_LEN - 1).enumerate() {
+            let mut buf = Vec::with_capacity(chunk.len() + 1);
+            buf.push(reg + (i * (MAX_I2C_MSG_LEN - 1)) as u8);
+            buf.extend_from_slice(chunk);
+            rtl.i2c_write(self.i2c_addr, &buf).await?;
+        }
+        Ok(())
+    }
+
+    async fn write_reg(&mut self, rtl: &Rtl2832, reg: u8, val: u8) -> Result<()> {
+        self.write(rtl, reg, &[val]).await
+    }
+
+    async fn write_reg_mask(&mut self, rtl: &Rtl2832, reg: u8, val: u8, mask: u8) -> Result<()> {
+        let rc = self.regs[reg as usize - REG_SHADOW_START];
+        self.write_reg(rtl, reg, (rc & !mask) | (val & mask)).await
+    }
+
+    /// Read registers, starting at register 0.
+    async fn read(&self, rtl: &Rtl2832, len: u16) -> Result<Vec<u8>> {
+        rtl.i2c_write(self.i2c_addr, &[0x00]).await?;
+        let data = rtl.i2c_read(self.i2c_addr, len).await?;
+        Ok(data.into_iter().map(bitrev).collect())
+    }
+
+    async fn set_mux(&mut self, rtl: &Rtl2832, freq: u32) -> Result<()> {
+        let mhz = freq / 1_000_000;
+        let range = FREQ_RANGES
+            .iter()
+            .rev()
+            .find(|r| mhz >= r.freq)
+            .unwrap_or(&FREQ_RANGES[0]);
+
+        self.write_reg_mask(rtl, 0x17, range.open_d, 0x08).await?;
+        self.write_reg_mask(rtl, 0x1a, range.rf_mux_ploy, 0xc3)
+            .await?;
+        self.write_reg(rtl, 0x1b, range.tf_c).await?;
+        // crystal capacitance 0 pF, high drive
+        self.write_reg_mask(rtl, 0x10, 0x00, 0x0b).await?;
+        self.write_reg_mask(rtl, 0x08, 0x00, 0x3f).await?;
+        self.write_reg_mask(rtl, 0x09, 0x00, 0x3f).await
+    }
+
+    async fn set_pll(&mut self, rtl: &Rtl2832, freq: u32) -> Result<()> {
+        let freq_khz = (freq + 500) / 1000;
+        let pll_ref = self.xtal;
+        let pll_ref_khz = (pll_ref + 500) / 1000;
+        let vco_power_ref = if self.chip == Chip::R828D { 1 } else { 2 };
+
+        // refdiv2 off, PLL auto tune clock 128 kHz, VCO current 100
+        self.write_reg_mask(rtl, 0x10, 0x00, 0x10).await?;
+        self.write_reg_mask(rtl, 0x1a, 0x00, 0x0c).await?;
+        self.write_reg_mask(rtl, 0x12, 0x80, 0xe0).await?;
+
+        // mixer divider to keep the VCO in range
+        let mut mix_div: u32 = 2;
+        let mut div_num: u8 = 0;
+        while mix_div <= 64 {
+            let vco = freq_khz * mix_div;
+            if (1_770_000..3_540_000).contains(&vco) {
+                let mut div_buf = mix_div;
+                while div_buf > 2 {
+                    div_buf >>= 1;
+                    div_num += 1;
+                }
+                break;
+            }
+            mix_div <<= 1;
+        }
+
+        let data = self.read(rtl, 5).await?;
+        let vco_fine_tune = (data[4] & 0x30) >> 4;
+        if vco_fine_tune > vco_power_ref {
+            div_num = div_num.wrapping_sub(1);
+        } else if vco_fine_tune < vco_power_ref {
+            div_num = div_num.wrapping_add(1);
+        }
+        self.write_reg_mask(rtl, 0x10, div_num << 5, 0xe0).await?;
+
+        let vco_freq = freq as u64 * mix_div as u64;
+        let nint = vco_freq / (2 * pll_ref as u64);
+        let mut vco_fra = ((vco_freq - 2 * pll_ref as u64 * nint) / 1000) as u32;
+
+        if nint > (128 / vco_power_ref as u64) - 1 {
+            bail!("R82xx: no valid PLL values for {} Hz", freq);
+        }
+
+        let ni = (nint - 13) / 4;
+        let si = nint - 4 * ni - 13;
+        self.write_reg(rtl, 0x14, (ni + (si << 6)) as u8).await?;
+
+        // pw_sdm
+        let val = if vco_fra == 0 { 0x08 } else { 0x00 };
+        self.write_reg_mask(rtl, 0x12, val, 0x08).await?;
+
+        // sdm calculator
+        let mut n_sdm: u32 = 2;
+        let mut sdm: u32 = 0;
+        while vco_fra > 1 {
+            if vco_fra > 2 * pll_ref_khz / n_sdm {
+                sdm += 32768 / (n_sdm / 2);
+                vco_fra -= 2 * pll_ref_khz / n_sdm;
+                if n_sdm >= 0x8000 {
+                    break;
+                }
+            }
+            n_sdm <<= 1;
+        }
+        self.write_reg(rtl, 0x16, (sdm >> 8) as u8).await?;
+        self.write_reg(rtl, 0x15, sdm as u8).await?;
+
+        let mut locked = false;
+        for i in 0..2 {
+            sleep(10).await;
+            let data = self.read(rtl, 3).await?;
+            if data[2] & 0x40 != 0 {
+                locked = true;
+                break;
+            }
+            if i == 0 {
+                // increase VCO current
+                self.write_reg_mask(rtl, 0x12, 0x60, 0xe0).await?;
+            }
+        }
+        if !locked {
+            bail!("R82xx: PLL not locked for {} Hz", freq);
+        }
+
+        // PLL auto tune clock 8 kHz
+        self.write_reg_mask(rtl, 0x1a, 0x08, 0x08).await
+    }
+
+    async fn sysfreq_sel(&mut self, rtl: &Rtl2832) -> Result<()> {
+        // DVB-T 6 MHz
+        let mixer_top = 0x24;
+        let lna_top = 0xe5;
+        let cp_cur = 0x38;
+        let div_buf_cur = 0x30;
+        let lna_vth_l = 0x53;
+        let mixer_vth_l = 0x75;
+        let air_cable1_in = 0x00;
+        let cable2_in = 0x00;
+        let lna_discharge = 14;
+        let filter_cur = 0x40;
+
+        self.write_reg_mask(rtl, 0x1d, lna_top, 0xc7).await?;
+        self.write_reg_mask(rtl, 0x1c, mixer_top, 0xf8).await?;
+        self.write_reg(rtl, 0x0d, lna_vth_l).await?;
+        self.write_reg(rtl, 0x0e, mixer_vth_l).await?;
+
+        self.input = air_cable1_in;
+        self.write_reg_mask(rtl, 0x05, air_cable1_in, 0x60).await?;
+        self.write_reg_mask(rtl, 0x06, cable2_in, 0x08).await?;
+        self.write_reg_mask(rtl, 0x11, cp_cur, 0x38).await?;
+        self.write_reg_mask(rtl, 0x17, div_buf_cur, 0x30).await?;
+        self.write_reg_mask(rtl, 0x0a, filter_cur, 0x60).await?;
+
+        // LNA top, normal mode
+        self.write_reg_mask(rtl, 0x1d, 0x00, 0x38).await?;
+        self.write_reg_mask(rtl, 0x1c, 0x00, 0x04).await?;
+        self.write_reg_mask(rtl, 0x06, 0x00, 0x40).await?;
+        self.write_reg_mask(rtl, 0x1a, 0x30, 0x30).await?;
+        sleep(250).await;
+
+        self.write_reg_mask(rtl, 0x1d, 0x18, 0x38).await?;
+        self.write_reg_mask(rtl, 0x1c, mixer_top, 0x04).await?;
+        self.write_reg_mask(rtl, 0x1e, lna_discharge, 0x1f).await?;
+        self.write_reg_mask(rtl, 0x1a, 0x20, 0x30).await
+    }
+
+    async fn set_tv_standard(&mut self, rtl: &Rtl2832) -> Result<()> {
+        let filt_cal_lo = 56_000_000;
+        let filt_gain = 0x10;
+        let img_r = 0x00;
+        let filt_q = 0x10;
+        let hp_cor = 0x6b;
+        let ext_enable = 0x60;
+        let loop_through = 0x01;
+        let lt_att = 0x00;
+        let flt_ext_widest = 0x00;
+        let polyfil_cur = 0x60;
+
+        self.regs[..INIT_ARRAY.len()].copy_from_slice(&INIT_ARRAY);
+
+        // init flag and xtal check result
+        self.write_reg_mask(rtl, 0x0c, 0x00, 0x0f).await?;
+        // version
+        self.write_reg_mask(rtl, 0x13, 49, 0x3f).await?;
+        // LT gain test
+        self.write_reg_mask(rtl, 0x1d, 0x00, 0x38).await?;
+        sleep(1).await;
+
+        let mut fil_cal_code = 0;
+        for _ in 0..2 {
+            // set filter cap, enable filter calibration
+            self.write_reg_mask(rtl, 0x0b, hp_cor, 0x60).await?;
+            self.write_reg_mask(rtl, 0x0f, 0x04, 0x04).await?;
+            // xtal cap 0 pF for PLL
+            self.write_reg_mask(rtl, 0x10, 0x00, 0x03).await?;
+
+            self.set_pll(rtl, filt_cal_lo).await?;
+
+            // start trigger
+            self.write_reg_mask(rtl, 0x0b, 0x10, 0x10).await?;
+            sleep(1).await;
+            // stop trigger
+            self.write_reg_mask(rtl, 0x0b, 0x00, 0x10).await?;
+            // disable filter calibration
+            self.write_reg_mask(rtl, 0x0f, 0x00, 0x04).await?;
+
+            let data = self.read(rtl, 5).await?;
+            fil_cal_code = data[4] & 0x0f;
+            if fil_cal_code != 0 && fil_cal_code != 0x0f {
+                break;
+            }
+        }
+        if fil_cal_code == 0x0f {
+            fil_cal_code = 0;
+        }
+
+        self.write_reg_mask(rtl, 0x0a, filt_q | fil_cal_code, 0x1f)
+            .await?;
+        // BW, filter gain and HP corner
+        self.write_reg_mask(rtl, 0x0b, hp_cor, 0xef).await?;
+        // image rejection
+        self.write_reg_mask(rtl, 0x07, img_r, 0x80).await?;
+        self.write_reg_mask(rtl, 0x06, filt_gain, 0x30).await?;
+        self.write_reg_mask(rtl, 0x1e, ext_enable, 0x60).await?;
+        self.write_reg_mask(rtl, 0x05, loop_through, 0x80).await?;
+        self.write_reg_mask(rtl, 0x1f, lt_att, 0x80).await?;
+        self.write_reg_mask(rtl, 0x0f, flt_ext_widest, 0x80).await?;
+        self.write_reg_mask(rtl, 0x19, polyfil_cur, 0x60).await
+    }
+
+    pub async fn init(&mut self, rtl: &Rtl2832) -> Result<()> {
+        self.write(rtl, REG_SHADOW_START as u8, &INIT_ARRAY).await?;
+        self.set_tv_standard(rtl).await?;
+        self.sysfreq_sel(rtl).await
+    }
+
+    pub async fn set_freq(&mut self, rtl: &Rtl2832, freq: u32) -> Result<()> {
+        let lo_freq = freq + IF_FREQ;
+        self.set_mux(rtl, lo_freq).await?;
+        self.set_pll(rtl, lo_freq).await?;
+
+        // the R828D has separate inputs for VHF and UHF
+        if self.chip == Chip::R828D {
+            let input = if freq > 345_000_000 { 0x00 } else { 0x60 };
+            if input != self.input {
+                self.input = input;
+                self.write_reg_mask(rtl, 0x05, input, 0x60).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Set a manual gain in tenths of dB or enable the AGC of the tuner.
+    pub async fn set_gain(&mut self, rtl: &Rtl2832, manual: bool, gain: i32) -> Result<()> {
+        if manual {
+            // LNA auto off
+            self.write_reg_mask(rtl, 0x05, 0x10, 0x10).await?;
+            // mixer auto off
+            self.write_reg_mask(rtl, 0x07, 0x00, 0x10).await?;
+            // set fixed VGA gain
+            self.write_reg_mask(rtl, 0x0c, 0x08, 0x9f).await?;
+
+            let mut total = 0;
+            let mut lna_index = 0;
+            let mut mix_index = 0;
+            for _ in 0..15 {
+                if total >= gain {
+                    break;
+                }
+                lna_index += 1;
+                total += LNA_GAIN_STEPS[lna_index];
+                if total >= gain {
+                    break;
+                }
+                mix_index += 1;
+                total += MIXER_GAIN_STEPS[mix_index];
+            }
+
+            self.write_reg_mask(rtl, 0x05, lna_index as u8, 0x0f)
+                .await?;
+            self.write_reg_mask(rtl, 0x07, mix_index as u8, 0x0f).await
+        } else {
+            // LNA and mixer auto on
+            self.write_reg_mask(rtl, 0x05, 0x00, 0x10).await?;
+            self.write_reg_mask(rtl, 0x07, 0x10, 0x10).await?;
+            // fixed VGA gain of 26.5 dB
+            self.write_reg_mask(rtl, 0x0c, 0x0b, 0x9f).await
+        }
+    }
+}
@@ -106,7 +106,7 @@ where
                             match p {
                                 Pmt::U32(v) => block.input_index = (v as usize) % N,
                                 Pmt::U64(v) => block.input_index = (v as usize) % N,
-                                _ => todo!(),
+                                _ => warn!("Selector: received wrong PMT type. {:?}", p),
                             }
                             Ok(Pmt::U32(block.input_index as u32))
                         }
@@ -123,7 +123,7 @@ where
                             match p {
                                 Pmt::U32(v) => block.output_index = (v as usize) % M,
                                 Pmt::U64(v) => block.output_index = (v as usize) % M,
-                                _ => todo!(),
+                                _ => warn!("Selector: received wrong PMT type. {:?}", p),
                             }
                             Ok(Pmt::U32(block.output_index as u32))
                         }
@@ -175,7 +175,7 @@ where
             let nb_drop = if self.drop_policy == DropPolicy::SameRate {
                 m / item_size // Drop at the same rate as the selected one
             } else {
-                std::usize::MAX // Drops all other inputs
+                usize::MAX // Drops all other inputs
             };
             for i in 0..N {
                 if i != self.input_index {
@@ -0,0 +1,537 @@
+use async_io::Timer;
+use std::ffi::CString;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::time::Duration;
+use std::time::Instant;
+
+use crate::anyhow::{bail, Result};
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Marks an initialized segment ("FSDRSHM1").
+const MAGIC: u64 = 0x4653_4452_5348_4d31;
+/// The ring starts after the first page.
+const DATA_OFFSET: usize = 4096;
+
+/// Header at the start of the segment, shared between writer and reader.
+#[repr(C)]
+struct Header {
+    magic: AtomicU64,
+    item_size: AtomicU64,
+    /// Size of the ring in bytes.
+    capacity: AtomicU64,
+    /// Total bytes written.
+    write: AtomicU64,
+    /// Total bytes read.
+    read: AtomicU64,
+    /// Set by the writer, once it has written all samples.
+    closed: AtomicU32,
+}
+
+/// Shared-memory segment, mapped into the process.
+struct Segment {
+    name: CString,
+    ptr: *mut u8,
+    len: usize,
+}
+
+// the segment is only used by the block that owns it, synchronized through the header atomics
+unsafe impl Send for Segment {}
+
+impl Segment {
+    fn name(name: &str) -> Result<CString> {
+        let name = if name.starts_with('/') {
+            name.to_string()
+        } else {
+            format!("/{name}")
+        };
+        Ok(CString::new(name)?)
+    }
+
+    fn map(name: CString, fd: i32, len: usize) -> Result<Segment> {
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                fd,
+                0,
+            )
+        };
+        unsafe {
+            libc::close(fd);
+        }
+        if ptr == libc::MAP_FAILED {
+            bail!(
+                "mapping segment failed ({})",
+                std::io::Error::last_os_error()
+            );
+        }
+        Ok(Segment {
+            name,
+            ptr: ptr as *mut u8,
+            len,
+        })
+    }
+
+    /// Create a segment for a ring of `capacity` bytes, replacing an existing one.
+    fn create(name: &str, item_size: usize, capacity: usize) -> Result<Segment> {
+        let name = Self::name(name)?;
+        let len = DATA_OFFSET + capacity;
+        unsafe {
+            libc::shm_unlink(name.as_ptr());
+        }
+        let fd = unsafe {
+            libc::shm_open(
+                name.as_ptr(),
+                libc::O_CREAT | libc::O_EXCL | libc::O_RDWR,
+                0o600 as libc::mode_t,
+            )
+        };
+        if fd < 0 {
+            bail!(
+                "creating segment {:?} failed ({})",
+                name,
+                std::io::Error::last_os_error()
+            );
+        }
+        if unsafe { libc::ftruncate(fd, len as libc::off_t) } < 0 {
+            let e = std::io::Error::last_os_error();
+            unsafe {
+                libc::close(fd);
+                libc::shm_unlink(name.as_ptr());
+            }
+            bail!("resizing segment {:?} failed ({})", name, e);
+        }
+        let segment = Self::map(name, fd, len)?;
+        let h = segment.header();
+        h.item_size.store(item_size as u64, Ordering::Relaxed);
+        h.capacity.store(capacity as u64, Ordering::Relaxed);
+        h.write.store(0, Ordering::Relaxed);
+        h.read.store(0, Ordering::Relaxed);
+        h.closed.store(0, Ordering::Relaxed);
+        h.magic.store(MAGIC, Ordering::Release);
+        Ok(segment)
+    }
+
+    /// Open an initialized segment. Returns `None`, if it does not exist (yet).
+    fn open(name: &str) -> Result<Option<Segment>> {
+        let name = Self::name(name)?;
+        let fd = unsafe { libc::shm_open(name.as_ptr(), libc::O_RDWR, 0 as libc::mode_t) };
+        if fd < 0 {
+            return Ok(None);
+        }
+        let mut stat: libc::stat = unsafe { std::mem::zeroed() };
+        if unsafe { libc::fstat(fd, &mut stat) } < 0 || (stat.st_size as usize) < DATA_OFFSET {
+            // not resized by the writer yet
+            unsafe {
+                libc::close(fd);
+            }
+            return Ok(None);
+        }
+        let segment = Self::map(name, fd, stat.st_size as usize)?;
+        if segment.header().magic.load(Ordering::Acquire) != MAGIC {
+            return Ok(None);
+        }
+        Ok(Some(segment))
+    }
+
+    fn header(&self) -> &Header {
+        unsafe { &*(self.ptr as *const Header) }
+    }
+
+    fn ring(&self) -> *mut u8 {
+        unsafe { self.ptr.add(DATA_OFFSET) }
+    }
+
+    fn capacity(&self) -> usize {
+        self.header().capacity.load(Ordering::Relaxed) as usize
+    }
+
+    /// Copy bytes into the ring at the given position, wrapping around.
+    fn copy_in(&self, pos: u64, data: &[u8]) {
+        let cap = self.capacity();
+        let start = (pos % cap as u64) as usize;
+        let first = std::cmp::min(data.len(), cap - start);
+        unsafe {
+            std::ptr::copy_nonoverlapping(data.as_ptr(), self.ring().add(start), first);
+            std::ptr::copy_nonoverlapping(data[first..].as_ptr(), self.ring(), data.len() - first);
+        }
+    }
+
+    /// Copy bytes out of the ring at the given position, wrapping around.
+    fn copy_out(&self, pos: u64, data: &mut [u8]) {
+        let cap = self.capacity();
+        let start = (pos % cap as u64) as usize;
+        let first = std::cmp::min(data.len(), cap - start);
+        unsafe {
+            std::ptr::copy_nonoverlapping(self.ring().add(start), data.as_mut_ptr(), first);
+            std::ptr::copy_nonoverlapping(
+                self.ring(),
+                data[first..].as_mut_ptr(),
+                data.len() - first,
+            );
+        }
+    }
+
+    fn unlink(&self) {
+        unsafe {
+            libc::shm_unlink(self.name.as_ptr());
+        }
+    }
+}
+
+impl Drop for Segment {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.ptr as *mut libc::c_void, self.len);
+        }
+    }
+}
+
+/// Write samples into a shared-memory ring buffer.
+pub struct ShmSink<T: Send + 'static> {
+    name: String,
+    capacity: usize,
+    poll: Duration,
+    segment: Option<Segment>,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: Send + 'static> ShmSink<T> {
+    fn create(builder: ShmSinkBuilder<T>) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("ShmSink").build(),
+            StreamIoBuilder::new().add_input::<T>("in").build(),
+            MessageIoBuilder::new().build(),
+            ShmSink::<T> {
+                name: builder.name,
+                capacity: builder.capacity,
+                poll: builder.poll,
+                segment: None,
+                _type: std::marker::PhantomData,
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: Send + 'static> Kernel for ShmSink<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice_unchecked::<u8>();
+        let item_size = std::mem::size_of::<T>();
+        let segment = self.segment.as_ref().unwrap();
+        let h = segment.header();
+
+        let write = h.write.load(Ordering::Relaxed);
+        let free = segment.capacity() as u64 - (write - h.read.load(Ordering::Acquire));
+        let n = std::cmp::min(i.len(), free as usize) / item_size;
+
+        if n > 0 {
+            segment.copy_in(write, &i[..n * item_size]);
+            h.write
+                .store(write + (n * item_size) as u64, Ordering::Release);
+            sio.input(0).consume(n);
+        }
+
+        if sio.input(0).finished() && n * item_size == i.len() {
+            io.finished = true;
+        } else if n * item_size < i.len() {
+            // the ring is full, wait for the reader
+            let poll = self.poll;
+            io.block_on(async move {
+                Timer::after(poll).await;
+            });
+        }
+
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let item_size = std::mem::size_of::<T>();
+        self.segment = Some(Segment::create(
+            &self.name,
+            item_size,
+            self.capacity * item_size,
+        )?);
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        if let Some(s) = self.segment.take() {
+            s.header().closed.store(1, Ordering::Release);
+            // a reader, that has mapped the segment, can still read the remaining samples
+            s.unlink();
+        }
+        Ok(())
+    }
+}
+
+/// Write samples into a shared-memory ring buffer, to be read by a [ShmSource] in another
+/// process on the same host.
+///
+/// The sink creates a POSIX shared-memory segment with the given name (e.g., in `/dev/shm` on
+/// Linux), holding a header and a ring buffer of [capacity](ShmSinkBuilder::capacity) samples.
+/// Writer and reader exchange their positions through atomics in the header, i.e., without
+/// locks or system calls, and copy samples directly into and out of the ring. If the ring is
+/// full, the sink waits for the reader, polling with the given interval.
+///
+/// The segment is created in `init`, replacing an existing segment with the same name, and
+/// removed once the sink terminates. A reader that has already opened it, reads the remaining
+/// samples before it terminates.
+///
+/// # Inputs
+///
+/// `in`: Samples
+///
+/// # Outputs
+///
+/// No outputs.
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::ShmSinkBuilder;
+/// use futuresdr::num_complex::Complex32;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let sink = fg.add_block(
+///     ShmSinkBuilder::<Complex32>::new("capture")
+///         .capacity(1 << 24)
+///         .build(),
+/// );
+/// ```
+#[cfg_attr(docsrs, doc(cfg(unix)))]
+pub struct ShmSinkBuilder<T: Send + 'static> {
+    name: String,
+    capacity: usize,
+    poll: Duration,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: Send + 'static> ShmSinkBuilder<T> {
+    pub fn new<S: Into<String>>(name: S) -> ShmSinkBuilder<T> {
+        ShmSinkBuilder {
+            name: name.into(),
+            capacity: 1 << 22,
+            poll: Duration::from_millis(1),
+            _type: std::marker::PhantomData,
+        }
+    }
+
+    /// Size of the ring buffer in samples.
+    #[must_use]
+    pub fn capacity(mut self, samples: usize) -> ShmSinkBuilder<T> {
+        assert!(samples > 0, "capacity has to be positive");
+        self.capacity = samples;
+        self
+    }
+
+    /// Interval to check for space in the ring, if it is full.
+    #[must_use]
+    pub fn poll(mut self, interval: Duration) -> ShmSinkBuilder<T> {
+        self.poll = interval;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        ShmSink::<T>::create(self)
+    }
+}
+
+/// Read samples from a shared-memory ring buffer.
+pub struct ShmSource<T: Send + 'static> {
+    name: String,
+    poll: Duration,
+    timeout: Option<Duration>,
+    segment: Option<Segment>,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: Send + 'static> ShmSource<T> {
+    fn create(builder: ShmSourceBuilder<T>) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("ShmSource").build(),
+            StreamIoBuilder::new().add_output::<T>("out").build(),
+            MessageIoBuilder::new().build(),
+            ShmSource::<T> {
+                name: builder.name,
+                poll: builder.poll,
+                timeout: builder.timeout,
+                segment: None,
+                _type: std::marker::PhantomData,
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: Send + 'static> Kernel for ShmSource<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let o = sio.output(0).slice_unchecked::<u8>();
+        let item_size = std::mem::size_of::<T>();
+        let segment = self.segment.as_ref().unwrap();
+        let h = segment.header();
+
+        // check before reading, so that no samples are missed after the writer closed
+        let closed = h.closed.load(Ordering::Acquire) != 0;
+        let read = h.read.load(Ordering::Relaxed);
+        let available = h.write.load(Ordering::Acquire) - read;
+        let n = std::cmp::min(o.len() as u64, available) as usize / item_size;
+
+        if n > 0 {
+            segment.copy_out(read, &mut o[..n * item_size]);
+            h.read
+                .store(read + (n * item_size) as u64, Ordering::Release);
+            sio.output(0).produce(n);
+        }
+
+        if closed && available as usize == n * item_size {
+            io.finished = true;
+        } else if available == 0 {
+            let poll = self.poll;
+            io.block_on(async move {
+                Timer::after(poll).await;
+            });
+        }
+
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let start = Instant::now();
+        let segment = loop {
+            if let Some(s) = Segment::open(&self.name)? {
+                break s;
+            }
+            if matches!(self.timeout, Some(t) if start.elapsed() > t) {
+                bail!("ShmSource: segment {} not found", self.name);
+            }
+            Timer::after(Duration::from_millis(10)).await;
+        };
+
+        let item_size = segment.header().item_size.load(Ordering::Relaxed) as usize;
+        if item_size != std::mem::size_of::<T>() {
+            bail!(
+                "ShmSource: segment {} holds items of {} bytes, expected {}",
+                self.name,
+                item_size,
+                std::mem::size_of::<T>()
+            );
+        }
+        self.segment = Some(segment);
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        self.segment = None;
+        Ok(())
+    }
+}
+
+/// Read samples from a shared-memory ring buffer, written by a [ShmSink] in another process on
+/// the same host.
+///
+/// The source waits in `init` until the segment with the given name was created by the sink,
+/// at most for the [timeout](ShmSourceBuilder::timeout), if set. It checks that the item size
+/// matches the stream type, copies samples directly out of the ring, and polls with the given
+/// interval, if the ring is empty. It terminates, once the sink has terminated and all samples
+/// are read.
+///
+/// # Inputs
+///
+/// No inputs.
+///
+/// # Outputs
+///
+/// `out`: Samples
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::ShmSourceBuilder;
+/// use futuresdr::num_complex::Complex32;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let src = fg.add_block(ShmSourceBuilder::<Complex32>::new("capture").build());
+/// ```
+#[cfg_attr(docsrs, doc(cfg(unix)))]
+pub struct ShmSourceBuilder<T: Send + 'static> {
+    name: String,
+    poll: Duration,
+    timeout: Option<Duration>,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: Send + 'static> ShmSourceBuilder<T> {
+    pub fn new<S: Into<String>>(name: S) -> ShmSourceBuilder<T> {
+        ShmSourceBuilder {
+            name: name.into(),
+            poll: Duration::from_millis(1),
+            timeout: None,
+            _type: std::marker::PhantomData,
+        }
+    }
+
+    /// Interval to check for new samples, if the ring is empty.
+    #[must_use]
+    pub fn poll(mut self, interval: Duration) -> ShmSourceBuilder<T> {
+        self.poll = interval;
+        self
+    }
+
+    /// Maximum time to wait for the segment to be created.
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> ShmSourceBuilder<T> {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn build(self) -> Block {
+        ShmSource::<T>::create(self)
+    }
+}
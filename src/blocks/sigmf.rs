@@ -0,0 +1,553 @@
+use futures::io::AsyncWriteExt;
+use futures::AsyncReadExt;
+use futures::FutureExt;
+use serde_json::json;
+use serde_json::Map;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::anyhow::{anyhow, bail, Result};
+use crate::blocks::FileFormat;
+use crate::blocks::FileSample;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::Tag;
+use crate::runtime::WorkIo;
+
+pub(super) const SIGMF_VERSION: &str = "1.0.0";
+
+/// Paths of the data and metadata file of a recording, with or without SigMF extension.
+fn paths(base: &str) -> (PathBuf, PathBuf) {
+    let base = [".sigmf-data", ".sigmf-meta", ".sigmf"]
+        .iter()
+        .find_map(|e| base.strip_suffix(e))
+        .unwrap_or(base);
+    (
+        PathBuf::from(format!("{base}.sigmf-data")),
+        PathBuf::from(format!("{base}.sigmf-meta")),
+    )
+}
+
+pub(super) fn datatype(format: FileFormat) -> &'static str {
+    match format {
+        FileFormat::Cf32 => "cf32_le",
+        FileFormat::Ci16 => "ci16_le",
+        FileFormat::Ci8 => "ci8",
+        FileFormat::Cu8 => "cu8",
+        FileFormat::F32 => "rf32_le",
+    }
+}
+
+fn parse_datatype(datatype: &str) -> Option<FileFormat> {
+    match datatype {
+        "cf32_le" => Some(FileFormat::Cf32),
+        "ci16_le" => Some(FileFormat::Ci16),
+        "ci8" => Some(FileFormat::Ci8),
+        "cu8" => Some(FileFormat::Cu8),
+        "rf32_le" => Some(FileFormat::F32),
+        _ => None,
+    }
+}
+
+/// Record samples in the [SigMF](https://sigmf.org) format.
+pub struct SigmfSink<T: FileSample> {
+    data_path: PathBuf,
+    meta_path: PathBuf,
+    file: Option<async_fs::File>,
+    format: FileFormat,
+    global: Map<String, Value>,
+    /// Capture segments as (first sample, frequency).
+    captures: Vec<(u64, Option<f64>)>,
+    annotations: Vec<Value>,
+    n: u64,
+    buf: Vec<u8>,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: FileSample> SigmfSink<T> {
+    fn create(
+        base: &str,
+        format: FileFormat,
+        global: Map<String, Value>,
+        frequency: Option<f64>,
+    ) -> Block {
+        assert!(
+            !T::COMPLEX || format.is_complex(),
+            "complex streams require a complex format"
+        );
+        let (data_path, meta_path) = paths(base);
+
+        Block::new(
+            BlockMetaBuilder::new("SigmfSink").build(),
+            StreamIoBuilder::new().add_input::<T>("in").build(),
+            MessageIoBuilder::new()
+                .add_input(
+                    "freq",
+                    |block: &mut SigmfSink<T>,
+                     _mio: &mut MessageIo<SigmfSink<T>>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            let freq = match &p {
+                                Pmt::F64(f) => Some(*f),
+                                Pmt::F32(f) => Some(*f as f64),
+                                Pmt::MapStrPmt(m) => match m.get("freq") {
+                                    Some(Pmt::F64(f)) => Some(*f),
+                                    Some(Pmt::F32(f)) => Some(*f as f64),
+                                    _ => None,
+                                },
+                                _ => None,
+                            };
+                            match freq {
+                                Some(f) => block.capture(f),
+                                None => warn!("SigmfSink: received wrong PMT type. {:?}", p),
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .build(),
+            SigmfSink::<T> {
+                data_path,
+                meta_path,
+                file: None,
+                format,
+                global,
+                captures: vec![(0, frequency)],
+                annotations: Vec::new(),
+                n: 0,
+                buf: Vec::new(),
+                _type: std::marker::PhantomData,
+            },
+        )
+    }
+
+    /// Start a new capture segment with the given frequency at the next sample.
+    fn capture(&mut self, frequency: f64) {
+        match self.captures.last_mut() {
+            Some((start, f)) if *start == self.n => *f = Some(frequency),
+            _ => self.captures.push((self.n, Some(frequency))),
+        }
+    }
+
+    fn annotate(&mut self, index: u64, tag: &Tag) {
+        let (label, comment) = match tag {
+            Tag::String(s) => (s.clone(), None),
+            Tag::NamedF32(s, v) => (s.clone(), Some(v.to_string())),
+            Tag::NamedUsize(s, v) => (s.clone(), Some(v.to_string())),
+            Tag::Id(id) => (id.to_string(), None),
+            _ => return,
+        };
+        let mut a = Map::new();
+        a.insert("core:sample_start".to_string(), json!(index));
+        a.insert("core:label".to_string(), json!(label));
+        if let Some(c) = comment {
+            a.insert("core:comment".to_string(), json!(c));
+        }
+        self.annotations.push(Value::Object(a));
+    }
+
+    fn metadata(&self) -> Value {
+        let mut global = self.global.clone();
+        global.insert("core:datatype".to_string(), json!(datatype(self.format)));
+        global.insert("core:version".to_string(), json!(SIGMF_VERSION));
+        global.insert("core:recorder".to_string(), json!("FutureSDR"));
+
+        let captures: Vec<Value> = self
+            .captures
+            .iter()
+            .map(|(start, freq)| {
+                let mut c = Map::new();
+                c.insert("core:sample_start".to_string(), json!(start));
+                if let Some(f) = freq {
+                    c.insert("core:frequency".to_string(), json!(f));
+                }
+                Value::Object(c)
+            })
+            .collect();
+
+        json!({
+            "global": global,
+            "captures": captures,
+            "annotations": self.annotations,
+        })
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: FileSample> Kernel for SigmfSink<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let n = {
+            let i = sio.input(0).slice::<T>();
+            let s = self.format.item_size();
+            self.buf.resize(i.len() * s, 0);
+            for (x, b) in i.iter().zip(self.buf.chunks_exact_mut(s)) {
+                self.format.encode(x.to_complex(), b);
+            }
+            i.len()
+        };
+
+        let mut tags: Vec<(usize, Tag)> = sio
+            .input(0)
+            .tags()
+            .iter()
+            .filter(|t| t.index < n)
+            .map(|t| (t.index, t.tag.clone()))
+            .collect();
+        tags.sort_by_key(|(index, _)| *index);
+        for (index, tag) in tags.iter() {
+            self.annotate(self.n + *index as u64, tag);
+        }
+
+        if n > 0 {
+            self.file.as_mut().unwrap().write_all(&self.buf).await?;
+        }
+
+        self.n += n as u64;
+        sio.input(0).consume(n);
+
+        if sio.input(0).finished() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        self.file = Some(async_fs::File::create(&self.data_path).await?);
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        self.file.as_mut().unwrap().sync_all().await?;
+        let meta = serde_json::to_string_pretty(&self.metadata())?;
+        async_fs::write(&self.meta_path, meta).await?;
+        Ok(())
+    }
+}
+
+/// Record samples in the [SigMF](https://sigmf.org) format.
+///
+/// Writes the samples to `<base>.sigmf-data` and, once the flowgraph terminates, the metadata to
+/// `<base>.sigmf-meta`. Samples are written as `cf32_le` for complex and `rf32_le` for real
+/// streams, unless another [format](SigmfSinkBuilder::format) is set.
+///
+/// Stream tags are recorded as annotations: [Tag::String] and [Tag::Id] as label and
+/// [Tag::NamedF32] and [Tag::NamedUsize] as label with the value as comment. Frequency changes
+/// start a new capture segment, e.g., by connecting the `cmd` output of a
+/// [DopplerCorrection](crate::blocks::DopplerCorrectionBuilder).
+///
+/// # Inputs
+///
+/// `in`: Input (f32 or Complex32)
+///
+/// **Message**: `freq`: Center frequency in Hz ([Pmt::F64] or [Pmt::F32] or [Pmt::MapStrPmt]
+/// with a `freq` entry), starting a new capture segment
+///
+/// # Outputs
+///
+/// No outputs.
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::SigmfSinkBuilder;
+/// use futuresdr::num_complex::Complex32;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let sink = fg.add_block(
+///     SigmfSinkBuilder::<Complex32>::new("capture")
+///         .sample_rate(1e6)
+///         .frequency(433.92e6)
+///         .description("remote control")
+///         .build(),
+/// );
+/// ```
+pub struct SigmfSinkBuilder<T: FileSample> {
+    base: String,
+    format: FileFormat,
+    global: Map<String, Value>,
+    frequency: Option<f64>,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: FileSample> SigmfSinkBuilder<T> {
+    pub fn new<S: Into<String>>(base: S) -> SigmfSinkBuilder<T> {
+        SigmfSinkBuilder {
+            base: base.into(),
+            format: if T::COMPLEX {
+                FileFormat::Cf32
+            } else {
+                FileFormat::F32
+            },
+            global: Map::new(),
+            frequency: None,
+            _type: std::marker::PhantomData,
+        }
+    }
+
+    /// Sample format of the data file.
+    #[must_use]
+    pub fn format(mut self, format: FileFormat) -> SigmfSinkBuilder<T> {
+        self.format = format;
+        self
+    }
+
+    #[must_use]
+    pub fn sample_rate(mut self, sample_rate: f64) -> SigmfSinkBuilder<T> {
+        self.global
+            .insert("core:sample_rate".to_string(), json!(sample_rate));
+        self
+    }
+
+    /// Center frequency in Hz of the first capture segment.
+    #[must_use]
+    pub fn frequency(mut self, frequency: f64) -> SigmfSinkBuilder<T> {
+        self.frequency = Some(frequency);
+        self
+    }
+
+    #[must_use]
+    pub fn description<S: Into<String>>(mut self, description: S) -> SigmfSinkBuilder<T> {
+        self.global
+            .insert("core:description".to_string(), json!(description.into()));
+        self
+    }
+
+    #[must_use]
+    pub fn author<S: Into<String>>(mut self, author: S) -> SigmfSinkBuilder<T> {
+        self.global
+            .insert("core:author".to_string(), json!(author.into()));
+        self
+    }
+
+    pub fn build(self) -> Block {
+        SigmfSink::<T>::create(&self.base, self.format, self.global, self.frequency)
+    }
+}
+
+/// Play back a [SigMF](https://sigmf.org) recording.
+///
+/// Reads the samples from `<base>.sigmf-data`, converting them from the datatype of the
+/// recording (`cf32_le`, `ci16_le`, `ci8`, `cu8`, or `rf32_le`) to the stream type. Complex
+/// recordings can only be played back into complex streams.
+///
+/// The global metadata is posted before the first sample and the metadata of each capture
+/// segment, when its first sample is produced. Annotations are restored as tags at their first
+/// sample, i.e., as [Tag::NamedF32], if the comment is a number, or as [Tag::String] otherwise.
+///
+/// # Inputs
+///
+/// No inputs.
+///
+/// # Outputs
+///
+/// `out`: Output (f32 or Complex32)
+///
+/// **Message**: `meta`: Global metadata ([Pmt::MapStrPmt] with `datatype`, `sample_rate`,
+/// `description`, and `author`, as far as they are present), followed by the capture segments
+/// ([Pmt::MapStrPmt] with `sample_start` and, if present, `frequency`)
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::SigmfSource;
+/// use futuresdr::num_complex::Complex32;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let source = fg.add_block(SigmfSource::<Complex32>::new("capture"));
+/// ```
+pub struct SigmfSource<T: FileSample> {
+    data_path: PathBuf,
+    meta_path: PathBuf,
+    file: Option<async_fs::File>,
+    format: FileFormat,
+    /// Messages with their first sample, in reverse order.
+    messages: Vec<(u64, Pmt)>,
+    /// Tags with their first sample, in reverse order.
+    tags: Vec<(u64, Tag)>,
+    n: u64,
+    buf: Vec<u8>,
+    /// Bytes of an incomplete sample at the start of the buffer.
+    pending: usize,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: FileSample> SigmfSource<T> {
+    pub fn new<S: Into<String>>(base: S) -> Block {
+        let (data_path, meta_path) = paths(&base.into());
+
+        Block::new(
+            BlockMetaBuilder::new("SigmfSource").build(),
+            StreamIoBuilder::new().add_output::<T>("out").build(),
+            MessageIoBuilder::new().add_output("meta").build(),
+            SigmfSource::<T> {
+                data_path,
+                meta_path,
+                file: None,
+                format: FileFormat::Cf32,
+                messages: Vec::new(),
+                tags: Vec::new(),
+                n: 0,
+                buf: Vec::new(),
+                pending: 0,
+                _type: std::marker::PhantomData,
+            },
+        )
+    }
+
+    fn parse(&mut self, meta: &Value) -> Result<()> {
+        let global = meta
+            .get("global")
+            .and_then(Value::as_object)
+            .ok_or_else(|| anyhow!("SigmfSource: no global metadata"))?;
+        let dt = global
+            .get("core:datatype")
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow!("SigmfSource: no datatype"))?;
+        self.format = match parse_datatype(dt) {
+            Some(f) => f,
+            None => bail!("SigmfSource: unsupported datatype {}", dt),
+        };
+        if self.format.is_complex() && !T::COMPLEX {
+            bail!("SigmfSource: complex recording requires a complex stream");
+        }
+
+        let mut g = HashMap::new();
+        g.insert("datatype".to_string(), Pmt::String(dt.to_string()));
+        if let Some(r) = global.get("core:sample_rate").and_then(Value::as_f64) {
+            g.insert("sample_rate".to_string(), Pmt::F64(r));
+        }
+        for key in ["description", "author"] {
+            if let Some(s) = global.get(&format!("core:{key}")).and_then(Value::as_str) {
+                g.insert(key.to_string(), Pmt::String(s.to_string()));
+            }
+        }
+        self.messages.push((0, Pmt::MapStrPmt(g)));
+
+        let start = |v: &Value| v.get("core:sample_start").and_then(Value::as_u64);
+        let empty = Vec::new();
+        let list = |key: &str| meta.get(key).and_then(Value::as_array).unwrap_or(&empty);
+
+        for c in list("captures") {
+            if let Some(s) = start(c) {
+                let mut m = HashMap::new();
+                m.insert("sample_start".to_string(), Pmt::U64(s));
+                if let Some(f) = c.get("core:frequency").and_then(Value::as_f64) {
+                    m.insert("frequency".to_string(), Pmt::F64(f));
+                }
+                self.messages.push((s, Pmt::MapStrPmt(m)));
+            }
+        }
+        for a in list("annotations") {
+            let label = a.get("core:label").and_then(Value::as_str);
+            let comment = a.get("core:comment").and_then(Value::as_str);
+            let tag = match (label, comment.and_then(|c| c.parse::<f32>().ok())) {
+                (Some(l), Some(v)) => Tag::NamedF32(l.to_string(), v),
+                (Some(l), None) => Tag::String(l.to_string()),
+                _ => continue,
+            };
+            if let Some(s) = start(a) {
+                self.tags.push((s, tag));
+            }
+        }
+
+        // stable sort keeps the global metadata before a capture at the first sample
+        self.messages.sort_by_key(|(s, _)| *s);
+        self.messages.reverse();
+        self.tags.sort_by_key(|(s, _)| *s);
+        self.tags.reverse();
+        Ok(())
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: FileSample> Kernel for SigmfSource<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let o = sio.output(0).slice::<T>();
+        let s = self.format.item_size();
+
+        self.buf.resize(self.pending + o.len() * s, 0);
+        let read = self
+            .file
+            .as_mut()
+            .unwrap()
+            .read(&mut self.buf[self.pending..])
+            .await?;
+        let available = self.pending + read;
+        let items = available / s;
+
+        for (b, y) in self.buf.chunks_exact(s).take(items).zip(o.iter_mut()) {
+            *y = T::from_complex(self.format.decode(b));
+        }
+        self.buf.copy_within(items * s..available, 0);
+        self.pending = available - items * s;
+
+        let end = self.n + items as u64;
+        while matches!(self.messages.last(), Some((start, _)) if *start < end || read == 0) {
+            let (_, m) = self.messages.pop().unwrap();
+            mio.post(0, m).await;
+        }
+        while matches!(self.tags.last(), Some((start, _)) if *start < end) {
+            let (start, tag) = self.tags.pop().unwrap();
+            sio.output(0).add_tag((start - self.n) as usize, tag);
+        }
+
+        self.n = end;
+        sio.output(0).produce(items);
+
+        if read == 0 && !o.is_empty() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let meta = async_fs::read_to_string(&self.meta_path).await?;
+        let meta: Value = serde_json::from_str(&meta)?;
+        self.parse(&meta)?;
+        self.file = Some(async_fs::File::open(&self.data_path).await?);
+        Ok(())
+    }
+}
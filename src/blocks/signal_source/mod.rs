@@ -1,4 +1,6 @@
-use crate::anyhow::Result;
+use futures::FutureExt;
+
+use crate::anyhow::{bail, Result};
 use crate::num_complex::Complex32;
 use crate::runtime::Block;
 use crate::runtime::BlockMeta;
@@ -6,6 +8,7 @@ use crate::runtime::BlockMetaBuilder;
 use crate::runtime::Kernel;
 use crate::runtime::MessageIo;
 use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
 use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
 use crate::runtime::WorkIo;
@@ -16,12 +19,27 @@ pub use fxpt_phase::FixedPointPhase;
 mod fxpt_nco;
 pub use fxpt_nco::NCO;
 
+/// Signal source.
+///
+/// Usually created with the [SignalSourceBuilder](crate::blocks::SignalSourceBuilder).
+///
+/// # Inputs
+///
+/// `freq`: Message to set the frequency in Hz (`F32` or `F64`). Returns the current frequency.
+///
+/// `amplitude`: Message to set the amplitude (`F32` or `F64`).
+///
+/// # Outputs
+///
+/// `out`: Signal
 pub struct SignalSource<F, A>
 where
     F: FnMut(FixedPointPhase) -> A + Send + 'static,
     A: Send + 'static,
 {
     nco: NCO,
+    sample_rate: f32,
+    frequency: f32,
     phase_to_amplitude: F,
     amplitude: A,
     offset: A,
@@ -30,21 +48,76 @@ where
 impl<F, A> SignalSource<F, A>
 where
     F: FnMut(FixedPointPhase) -> A + Send + 'static,
-    A: Copy + Send + 'static + std::ops::Mul<Output = A> + std::ops::Add<Output = A>,
+    A: Copy + Send + 'static + From<f32> + std::ops::Mul<Output = A> + std::ops::Add<Output = A>,
 {
-    pub fn new(phase_to_amplitude: F, nco: NCO, amplitude: A, offset: A) -> Block {
+    pub fn new(
+        phase_to_amplitude: F,
+        frequency: f32,
+        sample_rate: f32,
+        initial_phase: f32,
+        amplitude: A,
+        offset: A,
+    ) -> Block {
+        let nco = NCO::new(
+            initial_phase,
+            2.0 * core::f32::consts::PI * frequency / sample_rate,
+        );
         Block::new(
             BlockMetaBuilder::new("SignalSource").build(),
             StreamIoBuilder::new().add_output::<A>("out").build(),
-            MessageIoBuilder::<Self>::new().build(),
+            MessageIoBuilder::<Self>::new()
+                .add_input(
+                    "freq",
+                    |block: &mut Self,
+                     _mio: &mut MessageIo<Self>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            match p {
+                                Pmt::F32(f) => block.set_frequency(f),
+                                Pmt::F64(f) => block.set_frequency(f as f32),
+                                Pmt::Null => {}
+                                _ => bail!("frequency has to be F32 or F64"),
+                            }
+                            Ok(Pmt::F32(block.frequency))
+                        }
+                        .boxed()
+                    },
+                )
+                .add_input(
+                    "amplitude",
+                    |block: &mut Self,
+                     _mio: &mut MessageIo<Self>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            match p {
+                                Pmt::F32(a) => block.amplitude = A::from(a),
+                                Pmt::F64(a) => block.amplitude = A::from(a as f32),
+                                _ => bail!("amplitude has to be F32 or F64"),
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .build(),
             SignalSource {
                 nco,
+                sample_rate,
+                frequency,
                 phase_to_amplitude,
                 amplitude,
                 offset,
             },
         )
     }
+
+    fn set_frequency(&mut self, frequency: f32) {
+        self.frequency = frequency;
+        self.nco
+            .set_freq(2.0 * core::f32::consts::PI * frequency / self.sample_rate);
+    }
 }
 
 #[doc(hidden)]
@@ -52,7 +125,7 @@ where
 impl<F, A> Kernel for SignalSource<F, A>
 where
     F: FnMut(FixedPointPhase) -> A + Send + 'static,
-    A: Copy + Send + 'static + std::ops::Mul<Output = A> + std::ops::Add<Output = A>,
+    A: Copy + Send + 'static + From<f32> + std::ops::Mul<Output = A> + std::ops::Add<Output = A>,
 {
     async fn work(
         &mut self,
@@ -81,6 +154,7 @@ enum WaveForm {
     Sin,
     Cos,
     Square,
+    Sawtooth,
 }
 
 pub struct SignalSourceBuilder<A> {
@@ -143,21 +217,32 @@ impl SignalSourceBuilder<f32> {
         }
     }
 
+    pub fn sawtooth(frequency: f32, sample_rate: f32) -> SignalSourceBuilder<f32> {
+        SignalSourceBuilder {
+            offset: 0.0,
+            amplitude: 1.0,
+            sample_rate,
+            frequency,
+            initial_phase: 0.0,
+            wave_form: WaveForm::Sawtooth,
+        }
+    }
+
     pub fn build(self) -> Block {
-        let nco = NCO::new(
-            self.initial_phase,
-            2.0 * core::f32::consts::PI * self.frequency / self.sample_rate,
-        );
         match self.wave_form {
             WaveForm::Cos => SignalSource::new(
                 |phase: FixedPointPhase| phase.cos(),
-                nco,
+                self.frequency,
+                self.sample_rate,
+                self.initial_phase,
                 self.amplitude,
                 self.offset,
             ),
             WaveForm::Sin => SignalSource::new(
                 |phase: FixedPointPhase| phase.sin(),
-                nco,
+                self.frequency,
+                self.sample_rate,
+                self.initial_phase,
                 self.amplitude,
                 self.offset,
             ),
@@ -169,7 +254,17 @@ impl SignalSourceBuilder<f32> {
                         0.0
                     }
                 },
-                nco,
+                self.frequency,
+                self.sample_rate,
+                self.initial_phase,
+                self.amplitude,
+                self.offset,
+            ),
+            WaveForm::Sawtooth => SignalSource::new(
+                sawtooth,
+                self.frequency,
+                self.sample_rate,
+                self.initial_phase,
                 self.amplitude,
                 self.offset,
             ),
@@ -211,15 +306,24 @@ impl SignalSourceBuilder<Complex32> {
         }
     }
 
+    pub fn sawtooth(frequency: f32, sample_rate: f32) -> SignalSourceBuilder<Complex32> {
+        SignalSourceBuilder {
+            offset: Complex32::new(0.0, 0.0),
+            amplitude: Complex32::new(1.0, 0.0),
+            sample_rate,
+            frequency,
+            initial_phase: 0.0,
+            wave_form: WaveForm::Sawtooth,
+        }
+    }
+
     pub fn build(self) -> Block {
-        let nco = NCO::new(
-            self.initial_phase,
-            2.0 * core::f32::consts::PI * self.frequency / self.sample_rate,
-        );
         match self.wave_form {
             WaveForm::Cos | WaveForm::Sin => SignalSource::new(
                 |phase: FixedPointPhase| Complex32::new(phase.cos(), phase.sin()),
-                nco,
+                self.frequency,
+                self.sample_rate,
+                self.initial_phase,
                 self.amplitude,
                 self.offset,
             ),
@@ -234,10 +338,28 @@ impl SignalSourceBuilder<Complex32> {
                         _ => unreachable!(),
                     }
                 },
-                nco,
+                self.frequency,
+                self.sample_rate,
+                self.initial_phase,
+                self.amplitude,
+                self.offset,
+            ),
+            WaveForm::Sawtooth => SignalSource::new(
+                |phase: FixedPointPhase| {
+                    let v = sawtooth(phase);
+                    Complex32::new(v, v)
+                },
+                self.frequency,
+                self.sample_rate,
+                self.initial_phase,
                 self.amplitude,
                 self.offset,
             ),
         }
     }
 }
+
+/// Ramp from 0 to 1 over one period, starting at phase -pi.
+fn sawtooth(phase: FixedPointPhase) -> f32 {
+    (phase.value as f32 / 4294967296.0) + 0.5
+}
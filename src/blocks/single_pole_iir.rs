@@ -0,0 +1,95 @@
+use std::ops::{Add, Mul, Sub};
+
+use crate::anyhow::Result;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Single-pole IIR averaging filter.
+///
+/// Computes `y[n] = y[n-1] + alpha * (x[n] - y[n-1])`, starting with zero, i.e., an exponential
+/// average with a time constant of roughly `1 / alpha` samples. Typically used to smooth power
+/// estimates.
+///
+/// # Inputs
+///
+/// `in`: Input
+///
+/// # Outputs
+///
+/// `out`: Average
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::SinglePoleIir;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let avg = fg.add_block(SinglePoleIir::<f32>::new(0.01));
+/// ```
+pub struct SinglePoleIir<T> {
+    alpha: f32,
+    y: T,
+}
+
+impl<T> SinglePoleIir<T>
+where
+    T: Copy + Default + Send + Add<Output = T> + Sub<Output = T> + Mul<f32, Output = T> + 'static,
+{
+    pub fn new(alpha: f32) -> Block {
+        assert!(alpha > 0.0 && alpha <= 1.0, "alpha has to be in (0, 1]");
+
+        Block::new(
+            BlockMetaBuilder::new("SinglePoleIir").build(),
+            StreamIoBuilder::new()
+                .add_input::<T>("in")
+                .add_output::<T>("out")
+                .build(),
+            MessageIoBuilder::new().build(),
+            SinglePoleIir {
+                alpha,
+                y: T::default(),
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T> Kernel for SinglePoleIir<T>
+where
+    T: Copy + Default + Send + Add<Output = T> + Sub<Output = T> + Mul<f32, Output = T> + 'static,
+{
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<T>();
+        let o = sio.output(0).slice::<T>();
+
+        let m = std::cmp::min(i.len(), o.len());
+        for (x, y) in i[..m].iter().zip(o.iter_mut()) {
+            self.y = self.y + (*x - self.y) * self.alpha;
+            *y = self.y;
+        }
+
+        sio.input(0).consume(m);
+        sio.output(0).produce(m);
+
+        if sio.input(0).finished() && m == i.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,99 @@
+use std::cmp;
+use std::ptr;
+
+use crate::anyhow::Result;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Drops a given number of samples and copies the rest.
+///
+/// # Inputs
+///
+/// `in`: Input
+///
+/// # Outputs
+///
+/// `out`: Output
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::SkipHead;
+/// use futuresdr::runtime::Flowgraph;
+/// use num_complex::Complex;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let skip_head = fg.add_block(SkipHead::<Complex<f32>>::new(1_000));
+/// ```
+pub struct SkipHead<T: Send + 'static> {
+    n_items: u64,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: Send + 'static> SkipHead<T> {
+    pub fn new(n_items: u64) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("SkipHead").build(),
+            StreamIoBuilder::new()
+                .add_input::<T>("in")
+                .add_output::<T>("out")
+                .build(),
+            MessageIoBuilder::new().build(),
+            SkipHead::<T> {
+                n_items,
+                _type: std::marker::PhantomData,
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: Send + 'static> Kernel for SkipHead<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice_unchecked::<u8>();
+        let item_size = std::mem::size_of::<T>();
+        let n_input = i.len() / item_size;
+
+        if self.n_items > 0 {
+            let m = cmp::min(self.n_items as usize, n_input);
+            self.n_items -= m as u64;
+            sio.input(0).consume(m);
+            if sio.input(0).finished() && m == n_input {
+                io.finished = true;
+            } else if self.n_items == 0 {
+                io.call_again = true;
+            }
+            return Ok(());
+        }
+
+        let o = sio.output(0).slice_unchecked::<u8>();
+        let m = cmp::min(n_input, o.len() / item_size);
+        if m > 0 {
+            unsafe {
+                ptr::copy_nonoverlapping(i.as_ptr(), o.as_mut_ptr(), m * item_size);
+            }
+            sio.input(0).consume(m);
+            sio.output(0).produce(m);
+        }
+
+        if sio.input(0).finished() && m == n_input {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
@@ -1,8 +1,13 @@
 use crate::anyhow::{bail, Result};
+use futuresdr_pmt::HandlerDescription;
+use futuresdr_pmt::ParameterDescription;
 use futuresdr_pmt::Pmt;
+use futuresdr_pmt::PmtKind;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
+use crate::blocks::soapy::MockSoapyDevice;
+
 // TODO: Conversions should be supported by the Pmt library directly
 pub fn pmt_to_f64(pmt: &Pmt) -> Result<f64> {
     let v = match pmt {
@@ -10,6 +15,8 @@ pub fn pmt_to_f64(pmt: &Pmt) -> Result<f64> {
         Pmt::F32(v) => *v as f64,
         Pmt::U32(v) => *v as f64,
         Pmt::U64(v) => *v as f64,
+        Pmt::I32(v) => *v as f64,
+        Pmt::I64(v) => *v as f64,
         _ => bail!("can't convert PMT to f64"),
     };
     Ok(v)
@@ -21,17 +28,69 @@ pub fn pmt_to_usize(pmt: &Pmt) -> Result<usize> {
         Pmt::F32(v) => *v as usize,
         Pmt::U32(v) => *v as usize,
         Pmt::U64(v) => *v as usize,
+        Pmt::I32(v) => usize::try_from(*v)?,
+        Pmt::I64(v) => usize::try_from(*v)?,
         _ => bail!("can't convert PMT to usize"),
     };
     Ok(v)
 }
 
+/// Kinds of PMTs that can be converted to numbers.
+const NUMERIC_KINDS: [PmtKind; 6] = [
+    PmtKind::F64,
+    PmtKind::F32,
+    PmtKind::U32,
+    PmtKind::U64,
+    PmtKind::I32,
+    PmtKind::I64,
+];
+
+/// Describe a message handler that sets a numeric value in `unit`.
+pub(super) fn describe_numeric(unit: &str, description: &str) -> HandlerDescription {
+    HandlerDescription::new()
+        .kinds(&NUMERIC_KINDS)
+        .unit(unit)
+        .description(description)
+}
+
+/// Describe the `cmd` message handler, which accepts a [`SoapyConfig`] as map.
+pub(super) fn describe_cmd() -> HandlerDescription {
+    HandlerDescription::new()
+        .parameter(ParameterDescription::new("chan", PmtKind::U32).description("Channel"))
+        .parameter(ParameterDescription::new("antenna", PmtKind::String).description("Antenna"))
+        .parameter(
+            ParameterDescription::new("bandwidth", PmtKind::F64)
+                .unit("Hz")
+                .description("Bandwidth"),
+        )
+        .parameter(
+            ParameterDescription::new("freq", PmtKind::F64)
+                .unit("Hz")
+                .description("Center frequency"),
+        )
+        .parameter(
+            ParameterDescription::new("gain", PmtKind::F64)
+                .unit("dB")
+                .description("Gain"),
+        )
+        .parameter(
+            ParameterDescription::new("rate", PmtKind::F64)
+                .unit("Hz")
+                .description("Sample rate"),
+        )
+        .description("Apply a configuration")
+}
+
 /// Soapy device specifier options
 #[derive(Clone, Serialize, Deserialize)]
 pub enum SoapyDevSpec {
     Filter(String),
+    #[cfg(feature = "soapy")]
     #[serde(skip)]
     Dev(soapysdr::Device),
+    /// In-memory device for tests, only supported by the mock blocks.
+    #[serde(skip)]
+    Mock(MockSoapyDevice),
 }
 
 impl Default for SoapyDevSpec {
@@ -44,6 +103,7 @@ impl fmt::Debug for SoapyDevSpec {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             SoapyDevSpec::Filter(s) => write!(f, "Filter({}", s),
+            #[cfg(feature = "soapy")]
             SoapyDevSpec::Dev(d) => {
                 write!(
                     f,
@@ -51,6 +111,7 @@ impl fmt::Debug for SoapyDevSpec {
                     d.hardware_key().unwrap_or_else(|_| "?".to_owned())
                 )
             }
+            SoapyDevSpec::Mock(_) => write!(f, "Mock"),
         }
     }
 }
@@ -161,6 +222,11 @@ impl TryFrom<Pmt> for SoapyConfig {
             }
             Pmt::MapStrPmt(m) => {
                 let mut cfg = Self::default();
+                // The channel has to come first to apply to all other settings, independent of
+                // the iteration order of the map.
+                if let Some(p) = m.get("chan") {
+                    cfg.push(SCI::Channels(Some(vec![pmt_to_usize(p)?])));
+                }
                 for (n, v) in m.iter() {
                     match (n.as_str(), v) {
                         ("antenna", Pmt::String(v)) => {
@@ -169,9 +235,7 @@ impl TryFrom<Pmt> for SoapyConfig {
                         ("bandwidth", p) => {
                             cfg.push(SCI::Bandwidth(pmt_to_f64(p)?));
                         }
-                        ("chan", p) => {
-                            cfg.push(SCI::Channels(Some(vec![pmt_to_usize(p)?])));
-                        }
+                        ("chan", _) => {}
                         ("freq", p) => {
                             cfg.push(SCI::Freq(pmt_to_f64(p)?));
                         }
@@ -192,6 +256,65 @@ impl TryFrom<Pmt> for SoapyConfig {
     }
 }
 
+/// Apply a [`SoapyConfig`], calling `set` for each setting with the direction
+/// ([`SoapyDirection::Rx`] or [`SoapyDirection::Tx`]) and the channel it applies to.
+///
+/// Settings apply to the direction(s) of `default_dir` and to all `chans`, until this is changed
+/// by `Direction` and `Channels` items. Devices share this function to interpret configurations
+/// in the same way.
+pub(super) fn apply_config<F>(
+    cfg: &SoapyConfig,
+    chans: &[usize],
+    default_dir: &SoapyDirection,
+    mut set: F,
+) -> Result<()>
+where
+    F: FnMut(&SoapyDirection, usize, &SoapyConfigItem) -> Result<()>,
+{
+    use SoapyConfigItem as SCI;
+
+    // The channels to which configuration items will apply.
+    // This defaults to all device channels, but can be modified
+    // with the "Channel" configuration item.
+    let mut active_chans = chans.to_vec();
+
+    let update_dir_fn = |d: &SoapyDirection| -> Vec<SoapyDirection> {
+        match (d.is_rx(default_dir), d.is_tx(default_dir)) {
+            (false, true) => vec![SoapyDirection::Tx],
+            (true, false) => vec![SoapyDirection::Rx],
+            (true, true) => vec![SoapyDirection::Rx, SoapyDirection::Tx],
+            _ => vec![],
+        }
+    };
+
+    let mut dir_flags = update_dir_fn(default_dir);
+
+    debug!("initial dir:{:?} chans:{:?})", dir_flags, active_chans);
+
+    for ci in &cfg.0 {
+        match ci {
+            SCI::Channels(None) => {
+                //All configured channels
+                active_chans = chans.to_vec();
+            }
+            SCI::Channels(Some(c)) => {
+                active_chans = c.clone();
+            }
+            SCI::Direction(d) => {
+                dir_flags = update_dir_fn(d);
+            }
+            _ => {
+                for d in dir_flags.iter() {
+                    for c in active_chans.iter() {
+                        set(d, *c, ci)?;
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
 /// Encapsulate all [`SoapyDevice`] Initialization settings.
 ///
 /// This include initialization only configuration items, as well
@@ -0,0 +1,662 @@
+use std::cmp;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use std::time::Instant;
+
+use async_io::Timer;
+
+use crate::anyhow::{bail, Context, Result};
+use crate::blocks::soapy::config;
+use crate::blocks::soapy::SoapyConfig;
+use crate::blocks::soapy::SoapyConfigItem;
+use crate::blocks::soapy::SoapyDevBuilder;
+use crate::blocks::soapy::SoapyDevSpec;
+use crate::blocks::soapy::SoapyDirection;
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamInput;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::StreamOutput;
+use crate::runtime::WorkIo;
+
+/// Settings of a channel of a [MockSoapyDevice].
+///
+/// Settings that were never applied are `None`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MockChannelSettings {
+    pub antenna: Option<String>,
+    pub bandwidth: Option<f64>,
+    pub frequency: Option<f64>,
+    pub gain: Option<f64>,
+    pub sample_rate: Option<f64>,
+}
+
+#[derive(Default)]
+struct MockState {
+    rx: HashMap<usize, MockChannelSettings>,
+    tx: HashMap<usize, MockChannelSettings>,
+    rx_queue: HashMap<usize, VecDeque<Complex32>>,
+    transmitted: HashMap<usize, Vec<Complex32>>,
+    rx_calls: Vec<usize>,
+    tx_calls: Vec<usize>,
+}
+
+/// In-memory stand-in for a Soapy device.
+///
+/// Stores the settings per direction and channel, queues samples that are output by a
+/// [MockSoapySource], and records samples that are transmitted by a [MockSoapySink].
+/// Configurations are interpreted like by the [SoapySource](crate::blocks::SoapySource) and
+/// [SoapySink](crate::blocks::SoapySink), so tests can check config handling and multi-channel
+/// wiring without hardware.
+///
+/// Clones share the same state. Pass a clone to the blocks with [`SoapyDevSpec::Mock`] to inspect
+/// the device, while the flowgraph is running.
+#[derive(Clone)]
+pub struct MockSoapyDevice {
+    channels: usize,
+    mtu: usize,
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockSoapyDevice {
+    /// Create a device with the given number of channels per direction.
+    pub fn new(channels: usize) -> MockSoapyDevice {
+        MockSoapyDevice {
+            channels,
+            mtu: 1,
+            state: Arc::new(Mutex::new(MockState::default())),
+        }
+    }
+
+    /// Set the MTU of the streams in items.
+    ///
+    /// Like the [SoapySource](crate::blocks::SoapySource) and
+    /// [SoapySink](crate::blocks::SoapySink), the mock blocks read and write whole MTUs, if
+    /// possible. By default, the MTU is one item.
+    #[must_use]
+    pub fn with_mtu(mut self, mtu: usize) -> MockSoapyDevice {
+        assert!(mtu > 0, "MTU has to be positive");
+        self.mtu = mtu;
+        self
+    }
+
+    /// Number of channels per direction.
+    pub fn num_channels(&self) -> usize {
+        self.channels
+    }
+
+    /// MTU of the streams in items.
+    pub fn mtu(&self) -> usize {
+        self.mtu
+    }
+
+    /// Number of items of each read ([`SoapyDirection::Rx`]) or write ([`SoapyDirection::Tx`])
+    /// of a stream, in the order of the calls.
+    pub fn stream_calls(&self, dir: &SoapyDirection) -> Vec<usize> {
+        let state = self.state.lock().unwrap();
+        if dir.is_tx(&SoapyDirection::None) {
+            state.tx_calls.clone()
+        } else {
+            state.rx_calls.clone()
+        }
+    }
+
+    /// Settings of a channel in the given direction ([`SoapyDirection::Rx`] or
+    /// [`SoapyDirection::Tx`]).
+    pub fn settings(&self, dir: &SoapyDirection, chan: usize) -> MockChannelSettings {
+        let state = self.state.lock().unwrap();
+        let map = if dir.is_tx(&SoapyDirection::None) {
+            &state.tx
+        } else {
+            &state.rx
+        };
+        map.get(&chan).cloned().unwrap_or_default()
+    }
+
+    /// Queue samples to be received on a channel.
+    ///
+    /// Once the queue is empty, the channel receives zeros.
+    pub fn push_rx(&self, chan: usize, samples: &[Complex32]) {
+        self.state
+            .lock()
+            .unwrap()
+            .rx_queue
+            .entry(chan)
+            .or_default()
+            .extend(samples.iter());
+    }
+
+    /// Samples transmitted on a channel.
+    pub fn transmitted(&self, chan: usize) -> Vec<Complex32> {
+        self.state
+            .lock()
+            .unwrap()
+            .transmitted
+            .get(&chan)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn check_channels(&self, chans: &[usize]) -> Result<()> {
+        if let Some(c) = chans.iter().find(|c| **c >= self.channels) {
+            bail!(
+                "invalid channel {} (device has {} channels)",
+                c,
+                self.channels
+            );
+        }
+        Ok(())
+    }
+
+    fn set(&self, dir: &SoapyDirection, chan: usize, ci: &SoapyConfigItem) -> Result<()> {
+        use SoapyConfigItem as SCI;
+
+        self.check_channels(&[chan])?;
+        let mut state = self.state.lock().unwrap();
+        let map = if dir.is_tx(&SoapyDirection::None) {
+            &mut state.tx
+        } else {
+            &mut state.rx
+        };
+        let s = map.entry(chan).or_default();
+        debug!("mock.set({:?},{},{:?})", dir, chan, ci);
+        match ci {
+            SCI::Antenna(a) => s.antenna = Some(a.clone()),
+            SCI::Bandwidth(bw) => s.bandwidth = Some(*bw),
+            SCI::Freq(f) => s.frequency = Some(*f),
+            SCI::Gain(g) => s.gain = Some(*g),
+            SCI::SampleRate(r) => s.sample_rate = Some(*r),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Read from the channels of a stream into the output buffers.
+    fn receive(&self, chans: &[usize], outs: &mut [StreamOutput], n: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.rx_calls.push(n);
+        for (o, c) in outs.iter_mut().zip(chans.iter()) {
+            let queue = state.rx_queue.entry(*c).or_default();
+            for x in o.slice::<Complex32>()[0..n].iter_mut() {
+                *x = queue
+                    .pop_front()
+                    .unwrap_or_else(|| Complex32::new(0.0, 0.0));
+            }
+        }
+    }
+
+    /// Write from the input buffers to the channels of a stream.
+    fn transmit(&self, chans: &[usize], ins: &mut [StreamInput], n: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.tx_calls.push(n);
+        for (i, c) in ins.iter_mut().zip(chans.iter()) {
+            state
+                .transmitted
+                .entry(*c)
+                .or_default()
+                .extend_from_slice(&i.slice::<Complex32>()[0..n]);
+        }
+    }
+}
+
+/// State shared by the mock source and sink.
+struct MockCore {
+    dev: Option<MockSoapyDevice>,
+    init_cfg: config::SoapyInitConfig,
+    chans: Vec<usize>,
+    rate: Option<f64>,
+    t_start: Instant,
+    n_items: usize,
+}
+
+impl MockCore {
+    fn new(init_cfg: config::SoapyInitConfig) -> MockCore {
+        let mut chans = init_cfg.chans.clone();
+        if chans.is_empty() {
+            chans.push(0);
+        }
+        MockCore {
+            dev: None,
+            init_cfg,
+            chans,
+            rate: None,
+            t_start: Instant::now(),
+            n_items: 0,
+        }
+    }
+
+    fn dev(&self) -> Result<&MockSoapyDevice> {
+        self.dev.as_ref().context("no dev")
+    }
+
+    fn apply_config(&self, cfg: &SoapyConfig, default_dir: &SoapyDirection) -> Result<()> {
+        let dev = self.dev()?;
+        config::apply_config(cfg, &self.chans, default_dir, |d, c, ci| dev.set(d, c, ci))
+    }
+
+    fn cmd(&self, pmt: Pmt, default_dir: &SoapyDirection) -> Result<Pmt> {
+        self.apply_config(&SoapyConfig::try_from(pmt)?, default_dir)?;
+        Ok(Pmt::Null)
+    }
+
+    // For backwards compatibility, can only set the first stream channel
+    fn set_first(&self, ci: SoapyConfigItem, default_dir: &SoapyDirection) -> Result<Pmt> {
+        let cfg = SoapyConfig(vec![
+            SoapyConfigItem::Channels(Some(vec![self.chans[0]])),
+            ci,
+        ]);
+        self.apply_config(&cfg, default_dir)?;
+        Ok(Pmt::Null)
+    }
+
+    fn init(&mut self, default_dir: &SoapyDirection) -> Result<()> {
+        let dev = match &self.init_cfg.dev {
+            SoapyDevSpec::Mock(d) => d.clone(),
+            SoapyDevSpec::Filter(_) => {
+                MockSoapyDevice::new(self.chans.iter().max().map_or(1, |c| c + 1))
+            }
+            #[cfg(feature = "soapy")]
+            SoapyDevSpec::Dev(_) => bail!("Soapy devices are not supported by the mock blocks"),
+        };
+        dev.check_channels(&self.chans)?;
+        self.dev = Some(dev);
+
+        if let Err(e) = self.apply_config(&self.init_cfg.config, default_dir) {
+            warn!("MockSoapy apply_init_config error: {}", e);
+        }
+        self.rate = None;
+        Ok(())
+    }
+
+    /// Number of items that can be processed now to run at the sample rate of the first channel.
+    ///
+    /// Without a sample rate, the device runs as fast as possible.
+    fn budget(&mut self, dir: &SoapyDirection) -> Result<usize> {
+        let rate = self.dev()?.settings(dir, self.chans[0]).sample_rate;
+        if rate != self.rate {
+            self.rate = rate;
+            self.t_start = Instant::now();
+            self.n_items = 0;
+        }
+        Ok(match rate {
+            Some(r) => {
+                let target = (self.t_start.elapsed().as_secs_f64() * r) as usize;
+                target.saturating_sub(self.n_items)
+            }
+            None => usize::MAX,
+        })
+    }
+}
+
+/// Mock [SoapySource](crate::blocks::SoapySource) for tests.
+pub struct MockSoapySource {
+    core: MockCore,
+}
+
+impl MockSoapySource {
+    fn new(init_cfg: config::SoapyInitConfig) -> Block {
+        let core = MockCore::new(init_cfg);
+
+        let mut siob = StreamIoBuilder::new();
+        for i in 0..core.chans.len() {
+            if i == 0 {
+                siob = siob.add_output::<Complex32>("out");
+            } else {
+                siob = siob.add_output::<Complex32>(&format!("out{}", i + 1));
+            }
+        }
+
+        Block::new(
+            BlockMetaBuilder::new("MockSoapySource").build(),
+            siob.build(),
+            MessageIoBuilder::new()
+                .add_input("freq", Self::on_freq_port)
+                .add_input("gain", Self::on_gain_port)
+                .add_input("sample_rate", Self::on_sample_rate_port)
+                .add_input("cmd", Self::on_cmd_port)
+                .describe_input("freq", config::describe_numeric("Hz", "Center frequency"))
+                .describe_input("gain", config::describe_numeric("dB", "Gain"))
+                .describe_input("sample_rate", config::describe_numeric("Hz", "Sample rate"))
+                .describe_input("cmd", config::describe_cmd())
+                .build(),
+            MockSoapySource { core },
+        )
+    }
+
+    #[message_handler]
+    fn on_cmd_port(
+        &mut self,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        self.core.cmd(p, &SoapyDirection::Rx)
+    }
+
+    #[message_handler]
+    fn on_freq_port(
+        &mut self,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        let freq = config::pmt_to_f64(&p)?;
+        self.core
+            .set_first(SoapyConfigItem::Freq(freq), &SoapyDirection::Rx)
+    }
+
+    #[message_handler]
+    fn on_gain_port(
+        &mut self,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        let gain = config::pmt_to_f64(&p)?;
+        self.core
+            .set_first(SoapyConfigItem::Gain(gain), &SoapyDirection::Rx)
+    }
+
+    #[message_handler]
+    fn on_sample_rate_port(
+        &mut self,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        let rate = config::pmt_to_f64(&p)?;
+        self.core
+            .set_first(SoapyConfigItem::SampleRate(rate), &SoapyDirection::Rx)
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for MockSoapySource {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let outs = sio.outputs_mut();
+        let space = outs
+            .iter_mut()
+            .map(|o| o.slice::<Complex32>().len())
+            .min()
+            .unwrap_or(0);
+        if space == 0 {
+            return Ok(());
+        }
+
+        let n = cmp::min(space, self.core.budget(&SoapyDirection::Rx)?);
+        let dev = self.core.dev()?;
+        let n = super::whole_mtus(n, dev.mtu);
+        if n == 0 {
+            io.block_on(async {
+                Timer::after(Duration::from_millis(1)).await;
+            });
+            return Ok(());
+        }
+
+        dev.receive(&self.core.chans, outs, n);
+        for o in outs.iter_mut() {
+            o.produce(n);
+        }
+        self.core.n_items += n;
+        io.call_again = true;
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        self.core.init(&SoapyDirection::Rx)
+    }
+}
+
+/// Build a [MockSoapySource].
+///
+/// Uses the same [`SoapyDevBuilder`] as the [SoapySourceBuilder](crate::blocks::SoapySourceBuilder).
+/// The device is given with [`SoapyDevSpec::Mock`]. Otherwise, a new [MockSoapyDevice] with
+/// enough channels is created.
+///
+/// # Inputs
+///
+/// - **Message** `freq`, `gain`, `sample_rate`: set the parameter of the first channel.
+/// - **Message** `cmd`: a [`Pmt`] representing a configuration update. See: [`SoapyConfig`].
+///
+/// # Outputs
+///
+/// `out`, `out2`, ...: Samples queued with [`MockSoapyDevice::push_rx`], followed by zeros. If a
+/// sample rate is set, the source produces samples at this rate.
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::soapy::MockSoapyDevice;
+/// use futuresdr::blocks::soapy::SoapyDevSpec;
+/// use futuresdr::blocks::MockSoapySourceBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+/// let dev = MockSoapyDevice::new(2);
+///
+/// let source = fg.add_block(
+///     MockSoapySourceBuilder::new()
+///         .device(SoapyDevSpec::Mock(dev.clone()))
+///         .dev_channels(vec![0, 1])
+///         .sample_rate(1e6)
+///         .freq(100e6)
+///         .build()
+/// );
+/// ```
+pub type MockSoapySourceBuilder = SoapyDevBuilder<MockSoapySource>;
+
+impl SoapyDevBuilder<MockSoapySource> {
+    pub fn new() -> Self {
+        Self {
+            init_cfg: config::SoapyInitConfig::default(),
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn build(mut self) -> Block {
+        self.fixup();
+        MockSoapySource::new(self.init_cfg)
+    }
+}
+
+impl Default for SoapyDevBuilder<MockSoapySource> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Mock [SoapySink](crate::blocks::SoapySink) for tests.
+pub struct MockSoapySink {
+    core: MockCore,
+}
+
+impl MockSoapySink {
+    fn new(init_cfg: config::SoapyInitConfig) -> Block {
+        let core = MockCore::new(init_cfg);
+
+        let mut siob = StreamIoBuilder::new();
+        for i in 0..core.chans.len() {
+            if i == 0 {
+                siob = siob.add_input::<Complex32>("in");
+            } else {
+                siob = siob.add_input::<Complex32>(&format!("in{}", i + 1));
+            }
+        }
+
+        Block::new(
+            BlockMetaBuilder::new("MockSoapySink").build(),
+            siob.build(),
+            MessageIoBuilder::new()
+                .add_input("freq", Self::on_freq_port)
+                .add_input("gain", Self::on_gain_port)
+                .add_input("cmd", Self::on_cmd_port)
+                .describe_input("freq", config::describe_numeric("Hz", "Center frequency"))
+                .describe_input("gain", config::describe_numeric("dB", "Gain"))
+                .describe_input("cmd", config::describe_cmd())
+                .build(),
+            MockSoapySink { core },
+        )
+    }
+
+    #[message_handler]
+    fn on_cmd_port(
+        &mut self,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        self.core.cmd(p, &SoapyDirection::Tx)
+    }
+
+    #[message_handler]
+    fn on_freq_port(
+        &mut self,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        let freq = config::pmt_to_f64(&p)?;
+        self.core
+            .set_first(SoapyConfigItem::Freq(freq), &SoapyDirection::Tx)
+    }
+
+    #[message_handler]
+    fn on_gain_port(
+        &mut self,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        let gain = config::pmt_to_f64(&p)?;
+        self.core
+            .set_first(SoapyConfigItem::Gain(gain), &SoapyDirection::Tx)
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for MockSoapySink {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let ins = sio.inputs_mut();
+        let finished = ins.iter().any(|i| i.finished());
+        let available = ins
+            .iter_mut()
+            .map(|i| i.slice::<Complex32>().len())
+            .min()
+            .unwrap_or(0);
+
+        let n = cmp::min(available, self.core.budget(&SoapyDirection::Tx)?);
+        let dev = self.core.dev()?;
+        let n = super::whole_mtus(n, dev.mtu);
+        if n > 0 {
+            dev.transmit(&self.core.chans, ins, n);
+            for i in ins.iter_mut() {
+                i.consume(n);
+            }
+            self.core.n_items += n;
+        }
+
+        if n == available {
+            if finished {
+                io.finished = true;
+            }
+        } else if n == 0 {
+            io.block_on(async {
+                Timer::after(Duration::from_millis(1)).await;
+            });
+        } else {
+            io.call_again = true;
+        }
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        self.core.init(&SoapyDirection::Tx)
+    }
+}
+
+/// Build a [MockSoapySink].
+///
+/// Uses the same [`SoapyDevBuilder`] as the [SoapySinkBuilder](crate::blocks::SoapySinkBuilder).
+/// The device is given with [`SoapyDevSpec::Mock`]. Otherwise, a new [MockSoapyDevice] with
+/// enough channels is created.
+///
+/// # Inputs
+///
+/// `in`, `in2`, ...: Samples to transmit, recorded in [`MockSoapyDevice::transmitted`]. If a
+/// sample rate is set, the sink consumes samples at this rate.
+///
+/// - **Message** `freq`, `gain`: set the parameter of the first channel.
+/// - **Message** `cmd`: a [`Pmt`] representing a configuration update. See: [`SoapyConfig`].
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::soapy::MockSoapyDevice;
+/// use futuresdr::blocks::soapy::SoapyDevSpec;
+/// use futuresdr::blocks::MockSoapySinkBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+/// let dev = MockSoapyDevice::new(1);
+///
+/// let sink = fg.add_block(
+///     MockSoapySinkBuilder::new()
+///         .device(SoapyDevSpec::Mock(dev.clone()))
+///         .freq(100e6)
+///         .gain(10.0)
+///         .build()
+/// );
+/// ```
+pub type MockSoapySinkBuilder = SoapyDevBuilder<MockSoapySink>;
+
+impl SoapyDevBuilder<MockSoapySink> {
+    pub fn new() -> Self {
+        Self {
+            init_cfg: config::SoapyInitConfig::default(),
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn build(mut self) -> Block {
+        self.fixup();
+        MockSoapySink::new(self.init_cfg)
+    }
+}
+
+impl Default for SoapyDevBuilder<MockSoapySink> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,31 +1,48 @@
+//! Soapy SDR devices and an in-memory mock device for tests.
+//!
+//! The mock blocks and the configuration types are available without the `soapy` feature.
+#[cfg(feature = "soapy")]
 use crate::{
     anyhow::{bail, Context, Result},
     runtime::Pmt,
 };
+#[cfg(feature = "soapy")]
 use soapysdr::Direction::{Rx, Tx};
-use std::{
-    marker::PhantomData,
-    sync::{Arc, Mutex},
-};
+use std::marker::PhantomData;
+#[cfg(feature = "soapy")]
+use std::sync::{Arc, Mutex};
 
 mod config;
+mod mock;
+#[cfg(feature = "soapy")]
 mod sink;
+#[cfg(feature = "soapy")]
 mod source;
 
 pub use self::config::{SoapyConfig, SoapyConfigItem, SoapyDevSpec, SoapyDirection};
+pub use self::mock::{
+    MockChannelSettings, MockSoapyDevice, MockSoapySink, MockSoapySinkBuilder, MockSoapySource,
+    MockSoapySourceBuilder,
+};
+#[cfg(feature = "soapy")]
 pub use self::sink::{SoapySink, SoapySinkBuilder};
+#[cfg(feature = "soapy")]
 pub use self::source::{SoapySource, SoapySourceBuilder};
 
+#[cfg(feature = "soapy")]
 static SOAPY_INIT: async_lock::Mutex<()> = async_lock::Mutex::new(());
 
+#[cfg(feature = "soapy")]
 pub struct SoapyDevice<T> {
     dev: Option<soapysdr::Device>,
     init_cfg: Arc<Mutex<config::SoapyInitConfig>>,
     chans: Vec<usize>,
     stream: Option<T>,
+    mtu: usize,
 }
 
 // Note: there is additional impl in [`Self::command`]
+#[cfg(feature = "soapy")]
 impl<T> SoapyDevice<T> {
     /// The handler for messages on the "cmd" port.
     ///
@@ -101,77 +118,34 @@ impl<T> SoapyDevice<T> {
             Some(d) => d,
         };
 
-        // The channels to which configuration items will apply.
-        // This defaults to all device channels, but can be modified
-        // with the "Channel" configuration item.
-        let mut chans = self.chans.clone();
-
-        let update_dir_fn = |d: &SoapyDirection| -> Vec<soapysdr::Direction> {
-            match (d.is_rx(default_dir), d.is_tx(default_dir)) {
-                (false, true) => vec![Tx],
-                (true, false) => vec![Rx],
-                (true, true) => vec![Rx, Tx],
-                _ => vec![],
-            }
-        };
-
-        let mut dir_flags = update_dir_fn(default_dir);
-
-        debug!("initial dir:{:?} chans:{:?})", dir_flags, chans);
-
-        for ci in &cfg.0 {
+        config::apply_config(cfg, &self.chans, default_dir, |d, c, ci| {
+            let d = match d {
+                SoapyDirection::Tx => Tx,
+                _ => Rx,
+            };
             match ci {
                 SCI::Antenna(a) => {
-                    for d in dir_flags.iter() {
-                        for c in chans.iter() {
-                            dev.set_antenna(*d, *c, a.as_bytes())?;
-                        }
-                    }
+                    dev.set_antenna(d, c, a.as_bytes())?;
                 }
                 SCI::Bandwidth(bw) => {
-                    for d in dir_flags.iter() {
-                        for c in chans.iter() {
-                            dev.set_bandwidth(*d, *c, *bw)?;
-                        }
-                    }
-                }
-                SCI::Channels(None) => {
-                    //All configured channels
-                    chans = self.chans.clone();
-                }
-                SCI::Channels(Some(c)) => {
-                    chans = c.clone();
-                }
-                SCI::Direction(d) => {
-                    dir_flags = update_dir_fn(d);
+                    dev.set_bandwidth(d, c, *bw)?;
                 }
                 SCI::Freq(freq) => {
-                    for d in dir_flags.iter() {
-                        for c in chans.iter() {
-                            debug!("dev.set_frequency({:?},{},{})", *d, *c, *freq);
-                            dev.set_frequency(*d, *c, *freq, ())?;
-                        }
-                    }
+                    debug!("dev.set_frequency({:?},{},{})", d, c, *freq);
+                    dev.set_frequency(d, c, *freq, ())?;
                 }
                 SCI::Gain(gain) => {
-                    for d in dir_flags.iter() {
-                        for c in chans.iter() {
-                            debug!("dev.set_gain({:?},{},{})", *d, *c, *gain);
-                            dev.set_gain(*d, *c, *gain)?;
-                        }
-                    }
+                    debug!("dev.set_gain({:?},{},{})", d, c, *gain);
+                    dev.set_gain(d, c, *gain)?;
                 }
                 SCI::SampleRate(rate) => {
-                    for d in dir_flags.iter() {
-                        for c in chans.iter() {
-                            debug!("dev.set_sample_rate({:?},{},{})", *d, *c, *rate);
-                            dev.set_sample_rate(*d, *c, *rate)?;
-                        }
-                    }
+                    debug!("dev.set_sample_rate({:?},{},{})", d, c, *rate);
+                    dev.set_sample_rate(d, c, *rate)?;
                 }
+                _ => {}
             }
-        }
-        Ok(())
+            Ok(())
+        })
     }
 
     fn apply_init_config(&mut self, default_dir: &SoapyDirection) -> Result<()> {
@@ -193,6 +167,9 @@ impl<T> SoapyDevice<T> {
                     }
                 };
             }
+            SoapyDevSpec::Mock(_) => {
+                bail!("mock devices are only supported by the mock blocks");
+            }
         };
         self.chans = cfg.chans.clone();
         self.apply_config(&cfg.config, default_dir)?;
@@ -202,6 +179,17 @@ impl<T> SoapyDevice<T> {
 
 // unsafe impl<T> Sync for SoapyDevice<T> {}
 
+/// Number of items to read or write in one call into the driver.
+///
+/// Whole MTUs, if at least one MTU of the `n` items is available, or all `n` items otherwise.
+fn whole_mtus(n: usize, mtu: usize) -> usize {
+    if n >= mtu {
+        n / mtu * mtu
+    } else {
+        n
+    }
+}
+
 pub struct SoapyDevBuilder<T> {
     init_cfg: config::SoapyInitConfig,
     _phantom: PhantomData<T>,
@@ -0,0 +1,507 @@
+use async_io::Async;
+use futures::FutureExt;
+use std::collections::HashMap;
+use std::io::{ErrorKind, Read, Write};
+use std::net::TcpStream;
+use std::sync::Arc;
+
+use crate::anyhow::{bail, Context, Result};
+use crate::blocks::tcp_source::TcpConnection;
+use crate::blocks::TcpMode;
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+const PROTOCOL_VERSION: u32 = (2 << 24) | 1700;
+const HEADER_LEN: usize = 20;
+const MAX_BODY_LEN: usize = 1 << 22;
+
+const CMD_HELLO: u32 = 0;
+const CMD_SET_SETTING: u32 = 2;
+
+const SETTING_STREAMING_MODE: u32 = 0;
+const SETTING_STREAMING_ENABLED: u32 = 1;
+const SETTING_GAIN: u32 = 2;
+const SETTING_IQ_FORMAT: u32 = 100;
+const SETTING_IQ_FREQUENCY: u32 = 101;
+const SETTING_IQ_DECIMATION: u32 = 102;
+
+const STREAM_MODE_IQ_ONLY: u32 = 1;
+
+const MSG_DEVICE_INFO: u32 = 0;
+const MSG_CLIENT_SYNC: u32 = 1;
+const MSG_UINT8_IQ: u32 = 100;
+const MSG_INT16_IQ: u32 = 101;
+const MSG_FLOAT_IQ: u32 = 103;
+
+/// Sample format of the IQ stream, requested from a [SpyServerSource](SpyServerSourceBuilder).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpyServerFormat {
+    /// Unsigned 8-bit samples, requiring the least bandwidth.
+    U8,
+    /// Signed 16-bit samples.
+    I16,
+    /// 32-bit float samples.
+    F32,
+}
+
+impl SpyServerFormat {
+    fn id(&self) -> u32 {
+        match self {
+            SpyServerFormat::U8 => 1,
+            SpyServerFormat::I16 => 2,
+            SpyServerFormat::F32 => 4,
+        }
+    }
+}
+
+/// Device information, sent by the server after the hello.
+#[derive(Debug, Clone, Copy, Default)]
+struct DeviceInfo {
+    max_sample_rate: u32,
+    decimation_stages: u32,
+    max_gain_index: u32,
+    min_frequency: u32,
+    max_frequency: u32,
+    min_decimation: u32,
+}
+
+/// Receive samples from a remote [SpyServer](https://airspy.com/download/).
+pub struct SpyServerSource {
+    connection: TcpConnection,
+    socket: Option<Arc<Async<TcpStream>>>,
+    frequency: u32,
+    sample_rate: f64,
+    gain: Option<u32>,
+    format: SpyServerFormat,
+    device: Option<DeviceInfo>,
+    can_control: bool,
+    streaming: bool,
+    /// Received bytes that do not form a complete message yet.
+    rx: Vec<u8>,
+    /// Samples of the last IQ message that did not fit into the output buffer.
+    samples: Vec<Complex32>,
+    pos: usize,
+}
+
+impl SpyServerSource {
+    fn create(
+        address: String,
+        frequency: u32,
+        sample_rate: f64,
+        gain: Option<u32>,
+        format: SpyServerFormat,
+    ) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("SpyServerSource").build(),
+            StreamIoBuilder::new()
+                .add_output::<Complex32>("out")
+                .build(),
+            MessageIoBuilder::new()
+                .add_input(
+                    "freq",
+                    |block: &mut SpyServerSource,
+                     _mio: &mut MessageIo<SpyServerSource>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            let f = match &p {
+                                Pmt::F32(v) => Some(*v as u32),
+                                Pmt::F64(v) => Some(*v as u32),
+                                Pmt::U32(v) => Some(*v),
+                                Pmt::U64(v) => Some(*v as u32),
+                                _ => None,
+                            };
+                            if let Some(f) = f {
+                                block.frequency = f;
+                                if block.streaming {
+                                    block.set_setting(SETTING_IQ_FREQUENCY, f)?;
+                                }
+                            } else {
+                                warn!("SpyServerSource/freq: received wrong PMT type. {:?}", p);
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .add_input(
+                    "gain",
+                    |block: &mut SpyServerSource,
+                     _mio: &mut MessageIo<SpyServerSource>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            let g = match &p {
+                                Pmt::F32(v) => Some(*v as u32),
+                                Pmt::F64(v) => Some(*v as u32),
+                                Pmt::U32(v) => Some(*v),
+                                Pmt::U64(v) => Some(*v as u32),
+                                _ => None,
+                            };
+                            if let Some(g) = g {
+                                block.gain = Some(g);
+                                if block.streaming && block.can_control {
+                                    block.set_setting(SETTING_GAIN, g)?;
+                                }
+                            } else {
+                                warn!("SpyServerSource/gain: received wrong PMT type. {:?}", p);
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .add_output("info")
+                .build(),
+            SpyServerSource {
+                connection: TcpConnection::new(TcpMode::Client(address), None),
+                socket: None,
+                frequency,
+                sample_rate,
+                gain,
+                format,
+                device: None,
+                can_control: false,
+                streaming: false,
+                rx: Vec::new(),
+                samples: Vec::new(),
+                pos: 0,
+            },
+        )
+    }
+
+    fn command(&self, command: u32, body: &[u8]) -> Result<()> {
+        let mut msg = Vec::with_capacity(8 + body.len());
+        msg.extend_from_slice(&command.to_le_bytes());
+        msg.extend_from_slice(&(body.len() as u32).to_le_bytes());
+        msg.extend_from_slice(body);
+
+        // commands are small and only sent occasionally, so just wait for the socket
+        let mut socket = self.socket.as_ref().context("not connected")?.get_ref();
+        let mut written = 0;
+        while written < msg.len() {
+            match socket.write(&msg[written..]) {
+                Ok(n) => written += n,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => std::thread::yield_now(),
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(())
+    }
+
+    fn set_setting(&self, setting: u32, value: u32) -> Result<()> {
+        let mut body = [0; 8];
+        body[..4].copy_from_slice(&setting.to_le_bytes());
+        body[4..].copy_from_slice(&value.to_le_bytes());
+        self.command(CMD_SET_SETTING, &body)
+    }
+
+    /// Decimation stage, whose sample rate is closest to the requested one.
+    fn decimation(&self, device: &DeviceInfo) -> u32 {
+        (device.min_decimation..device.decimation_stages.max(device.min_decimation + 1))
+            .min_by(|a, b| {
+                let ra = (device.max_sample_rate >> a) as f64;
+                let rb = (device.max_sample_rate >> b) as f64;
+                (ra - self.sample_rate)
+                    .abs()
+                    .partial_cmp(&(rb - self.sample_rate).abs())
+                    .unwrap()
+            })
+            .unwrap_or(device.min_decimation)
+    }
+
+    async fn start(&mut self, mio: &mut MessageIo<Self>) -> Result<()> {
+        let device = self.device.context("no device info")?;
+        let decimation = self.decimation(&device);
+        let sample_rate = device.max_sample_rate >> decimation;
+        info!(
+            "SpyServerSource: streaming at {} Hz, {} S/s",
+            self.frequency, sample_rate
+        );
+
+        self.set_setting(SETTING_STREAMING_MODE, STREAM_MODE_IQ_ONLY)?;
+        self.set_setting(SETTING_IQ_FORMAT, self.format.id())?;
+        self.set_setting(SETTING_IQ_FREQUENCY, self.frequency)?;
+        self.set_setting(SETTING_IQ_DECIMATION, decimation)?;
+        if let Some(g) = self.gain {
+            if self.can_control {
+                self.set_setting(SETTING_GAIN, g.min(device.max_gain_index))?;
+            } else {
+                warn!("SpyServerSource: server does not allow to set the gain");
+            }
+        }
+        self.set_setting(SETTING_STREAMING_ENABLED, 1)?;
+        self.streaming = true;
+
+        let mut m = HashMap::new();
+        m.insert("sample_rate".to_string(), Pmt::F64(sample_rate as f64));
+        m.insert("frequency".to_string(), Pmt::U32(self.frequency));
+        m.insert("min_frequency".to_string(), Pmt::U32(device.min_frequency));
+        m.insert("max_frequency".to_string(), Pmt::U32(device.max_frequency));
+        m.insert("max_gain".to_string(), Pmt::U32(device.max_gain_index));
+        m.insert(
+            "can_control".to_string(),
+            Pmt::U32(u32::from(self.can_control)),
+        );
+        mio.post(0, Pmt::MapStrPmt(m)).await;
+        Ok(())
+    }
+
+    fn u32_at(data: &[u8], i: usize) -> u32 {
+        u32::from_le_bytes(data[i * 4..i * 4 + 4].try_into().unwrap())
+    }
+
+    /// Handle the next complete message in the receive buffer.
+    ///
+    /// Returns `false`, if there is no complete message.
+    async fn handle_message(&mut self, mio: &mut MessageIo<Self>) -> Result<bool> {
+        if self.rx.len() < HEADER_LEN {
+            return Ok(false);
+        }
+        let msg_type = Self::u32_at(&self.rx, 1) & 0xffff;
+        let len = Self::u32_at(&self.rx, 4) as usize;
+        if len > MAX_BODY_LEN {
+            bail!("SpyServerSource: invalid message length {}", len);
+        }
+        if self.rx.len() < HEADER_LEN + len {
+            return Ok(false);
+        }
+
+        let body = &self.rx[HEADER_LEN..HEADER_LEN + len];
+        match msg_type {
+            MSG_DEVICE_INFO if len >= 48 => {
+                self.device = Some(DeviceInfo {
+                    max_sample_rate: Self::u32_at(body, 2),
+                    decimation_stages: Self::u32_at(body, 4),
+                    max_gain_index: Self::u32_at(body, 6),
+                    min_frequency: Self::u32_at(body, 7),
+                    max_frequency: Self::u32_at(body, 8),
+                    min_decimation: Self::u32_at(body, 10),
+                });
+            }
+            MSG_CLIENT_SYNC if len >= 4 => {
+                self.can_control = Self::u32_at(body, 0) != 0;
+                if !self.streaming {
+                    self.rx.drain(..HEADER_LEN + len);
+                    self.start(mio).await?;
+                    return Ok(true);
+                }
+            }
+            MSG_UINT8_IQ => {
+                self.samples = body
+                    .chunks_exact(2)
+                    .map(|c| {
+                        Complex32::new((c[0] as f32 - 127.5) / 128.0, (c[1] as f32 - 127.5) / 128.0)
+                    })
+                    .collect();
+                self.pos = 0;
+            }
+            MSG_INT16_IQ => {
+                self.samples = body
+                    .chunks_exact(4)
+                    .map(|c| {
+                        Complex32::new(
+                            i16::from_le_bytes([c[0], c[1]]) as f32 / 32768.0,
+                            i16::from_le_bytes([c[2], c[3]]) as f32 / 32768.0,
+                        )
+                    })
+                    .collect();
+                self.pos = 0;
+            }
+            MSG_FLOAT_IQ => {
+                self.samples = body
+                    .chunks_exact(8)
+                    .map(|c| {
+                        Complex32::new(
+                            f32::from_le_bytes(c[..4].try_into().unwrap()),
+                            f32::from_le_bytes(c[4..].try_into().unwrap()),
+                        )
+                    })
+                    .collect();
+                self.pos = 0;
+            }
+            t => debug!("SpyServerSource: ignoring message type {}", t),
+        }
+        self.rx.drain(..HEADER_LEN + len);
+        Ok(true)
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for SpyServerSource {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        if self.socket.is_none() {
+            match self.connection.connect(io).await? {
+                Some(s) => {
+                    self.socket = Some(s);
+                    let mut hello = PROTOCOL_VERSION.to_le_bytes().to_vec();
+                    hello.extend_from_slice(b"FutureSDR");
+                    self.command(CMD_HELLO, &hello)?;
+                }
+                None => return Ok(()),
+            }
+        }
+
+        // output pending samples, before handling the next message
+        if self.pos < self.samples.len() {
+            let o = sio.output(0).slice::<Complex32>();
+            let n = std::cmp::min(o.len(), self.samples.len() - self.pos);
+            o[..n].copy_from_slice(&self.samples[self.pos..self.pos + n]);
+            sio.output(0).produce(n);
+            self.pos += n;
+            if self.pos < self.samples.len() {
+                return Ok(());
+            }
+        }
+
+        if self.handle_message(mio).await? {
+            io.call_again = true;
+            return Ok(());
+        }
+
+        let socket = self.socket.as_ref().context("no socket")?;
+        let mut buf = [0u8; 1 << 16];
+        match socket.get_ref().read(&mut buf) {
+            Ok(0) => {
+                info!("SpyServerSource: server closed the connection");
+                io.finished = true;
+            }
+            Ok(n) => {
+                self.rx.extend_from_slice(&buf[..n]);
+                io.call_again = true;
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                let s = socket.clone();
+                io.block_on(async move {
+                    let _ = s.readable().await;
+                });
+            }
+            Err(e) if e.kind() == ErrorKind::Interrupted => io.call_again = true,
+            Err(e) => bail!("SpyServerSource: socket error {}", e),
+        }
+
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        self.connection.init()
+    }
+}
+
+/// Receive samples from a remote [SpyServer](https://airspy.com/download/).
+///
+/// Connects to the server, configures the IQ stream, and outputs the received samples, scaled
+/// to `[-1, 1)`. The server only offers sample rates that are the maximum sample rate of the
+/// device, decimated by a power of two; the closest one to the requested rate is selected.
+/// Frequency and gain (as gain index) can be changed at runtime, the latter only if the server
+/// allows to control the device.
+///
+/// After the stream is configured, the `info` output posts a [Pmt::MapStrPmt] with the actual
+/// `sample_rate`, the `frequency`, `min_frequency` and `max_frequency`, `max_gain`, and
+/// `can_control`.
+///
+/// # Inputs
+///
+/// **Message**: `freq`: Center frequency in Hz
+///
+/// **Message**: `gain`: Gain index
+///
+/// # Outputs
+///
+/// `out`: Received samples
+///
+/// **Message**: `info`: Stream and device information
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::{SpyServerFormat, SpyServerSourceBuilder};
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let src = fg.add_block(
+///     SpyServerSourceBuilder::new("127.0.0.1:5555")
+///         .frequency(100_000_000)
+///         .sample_rate(600e3)
+///         .format(SpyServerFormat::I16)
+///         .build(),
+/// );
+/// ```
+pub struct SpyServerSourceBuilder {
+    address: String,
+    frequency: u32,
+    sample_rate: f64,
+    gain: Option<u32>,
+    format: SpyServerFormat,
+}
+
+impl SpyServerSourceBuilder {
+    pub fn new(address: impl Into<String>) -> SpyServerSourceBuilder {
+        SpyServerSourceBuilder {
+            address: address.into(),
+            frequency: 100_000_000,
+            sample_rate: 0.0,
+            gain: None,
+            format: SpyServerFormat::I16,
+        }
+    }
+
+    /// Center frequency in Hz.
+    #[must_use]
+    pub fn frequency(mut self, frequency: u32) -> SpyServerSourceBuilder {
+        self.frequency = frequency;
+        self
+    }
+
+    /// Requested sample rate. Defaults to the lowest rate offered by the server.
+    #[must_use]
+    pub fn sample_rate(mut self, sample_rate: f64) -> SpyServerSourceBuilder {
+        self.sample_rate = sample_rate;
+        self
+    }
+
+    /// Gain index, if the server allows to control the device.
+    #[must_use]
+    pub fn gain(mut self, gain: u32) -> SpyServerSourceBuilder {
+        self.gain = Some(gain);
+        self
+    }
+
+    #[must_use]
+    pub fn format(mut self, format: SpyServerFormat) -> SpyServerSourceBuilder {
+        self.format = format;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        SpyServerSource::create(
+            self.address,
+            self.frequency,
+            self.sample_rate,
+            self.gain,
+            self.format,
+        )
+    }
+}
@@ -0,0 +1,353 @@
+use std::f32::consts::PI;
+
+use crate::anyhow::Result;
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::Tag;
+use crate::runtime::WorkIo;
+
+/// Minimum share of the CTCSS tone in the signal power to open the squelch.
+const CTCSS_THRESHOLD: f32 = 0.01;
+
+/// Goertzel detector for a CTCSS tone, deciding once per 100 ms.
+pub(crate) struct Ctcss {
+    coeff: f32,
+    window: usize,
+    n: usize,
+    s1: f32,
+    s2: f32,
+    power: f32,
+    pub(crate) detected: bool,
+}
+
+impl Ctcss {
+    pub(crate) fn new(frequency: f32, sample_rate: f32) -> Ctcss {
+        Ctcss {
+            coeff: 2.0 * (2.0 * PI * frequency / sample_rate).cos(),
+            window: (sample_rate / 10.0) as usize,
+            n: 0,
+            s1: 0.0,
+            s2: 0.0,
+            power: 0.0,
+            detected: false,
+        }
+    }
+
+    pub(crate) fn push(&mut self, x: f32) {
+        let s = x + self.coeff * self.s1 - self.s2;
+        self.s2 = self.s1;
+        self.s1 = s;
+        self.power += x * x;
+        self.n += 1;
+
+        if self.n == self.window {
+            let tone = self.s1 * self.s1 + self.s2 * self.s2 - self.coeff * self.s1 * self.s2;
+            let tone = 2.0 * tone / (self.n * self.n) as f32;
+            let power = self.power / self.n as f32;
+            self.detected = power > 0.0 && tone / power > CTCSS_THRESHOLD;
+            self.n = 0;
+            self.s1 = 0.0;
+            self.s2 = 0.0;
+            self.power = 0.0;
+        }
+    }
+}
+
+/// Power squelch.
+pub struct PowerSquelch {
+    open_level: f32,
+    close_level: f32,
+    hold: usize,
+    alpha: f32,
+    gate: bool,
+    tag: bool,
+    power: f32,
+    open: bool,
+    below: usize,
+}
+
+impl PowerSquelch {
+    pub fn new(
+        threshold: f32,
+        hysteresis: f32,
+        hold: usize,
+        alpha: f32,
+        gate: bool,
+        tag: bool,
+    ) -> Block {
+        assert!(hysteresis >= 0.0, "hysteresis must not be negative");
+        assert!(alpha > 0.0 && alpha <= 1.0, "alpha has to be in (0, 1]");
+
+        Block::new(
+            BlockMetaBuilder::new("PowerSquelch").build(),
+            StreamIoBuilder::new()
+                .add_input::<Complex32>("in")
+                .add_output::<Complex32>("out")
+                .build(),
+            MessageIoBuilder::<PowerSquelch>::new().build(),
+            PowerSquelch {
+                open_level: 10.0f32.powf(threshold / 10.0),
+                close_level: 10.0f32.powf((threshold - hysteresis) / 10.0),
+                hold,
+                alpha,
+                gate,
+                tag,
+                power: 0.0,
+                open: false,
+                below: 0,
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for PowerSquelch {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex32>();
+        let o = sio.output(0).slice::<Complex32>();
+
+        let mut consumed = 0;
+        let mut produced = 0;
+        while consumed < i.len() && produced < o.len() {
+            let x = i[consumed];
+            consumed += 1;
+            self.power += self.alpha * (x.norm_sqr() - self.power);
+
+            let mut tag = None;
+            let pass = if self.open {
+                if self.power < self.close_level {
+                    self.below += 1;
+                } else {
+                    self.below = 0;
+                }
+                if self.below > self.hold {
+                    self.open = false;
+                    tag = Some("squelch_eob");
+                }
+                true
+            } else if self.power > self.open_level {
+                self.open = true;
+                self.below = 0;
+                tag = Some("squelch_sob");
+                true
+            } else {
+                false
+            };
+
+            if pass {
+                if self.tag {
+                    if let Some(t) = tag {
+                        sio.output(0).add_tag(produced, Tag::String(t.to_string()));
+                    }
+                }
+                o[produced] = x;
+                produced += 1;
+            } else if !self.gate {
+                o[produced] = Complex32::new(0.0, 0.0);
+                produced += 1;
+            }
+        }
+
+        sio.input(0).consume(consumed);
+        sio.output(0).produce(produced);
+
+        if sio.input(0).finished() && consumed == i.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Power squelch.
+///
+/// Passes the input, while its power, smoothed with a single-pole IIR filter with gain `alpha`
+/// (default 0.001), is above a threshold. The squelch opens, once the power exceeds the
+/// `threshold` (in dB), and closes, once the power stayed below `threshold - hysteresis`
+/// (default hysteresis 3 dB) for more than `hold` samples (default 0).
+///
+/// While closed, zeros are output or, if gated, samples are dropped. Optionally, the first and
+/// last sample of each burst are tagged with [Tag::String] `squelch_sob` and `squelch_eob`.
+///
+/// # Inputs
+///
+/// `in`: Input (Complex32)
+///
+/// # Outputs
+///
+/// `out`: Squelched output (Complex32)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::PowerSquelchBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let squelch = fg.add_block(
+///     PowerSquelchBuilder::new(-40.0)
+///         .hold(1000)
+///         .gate()
+///         .tag()
+///         .build(),
+/// );
+/// ```
+pub struct PowerSquelchBuilder {
+    threshold: f32,
+    hysteresis: f32,
+    hold: usize,
+    alpha: f32,
+    gate: bool,
+    tag: bool,
+}
+
+impl PowerSquelchBuilder {
+    pub fn new(threshold: f32) -> PowerSquelchBuilder {
+        PowerSquelchBuilder {
+            threshold,
+            hysteresis: 3.0,
+            hold: 0,
+            alpha: 0.001,
+            gate: false,
+            tag: false,
+        }
+    }
+
+    /// Difference in dB between the open and close level.
+    #[must_use]
+    pub fn hysteresis(mut self, hysteresis: f32) -> PowerSquelchBuilder {
+        self.hysteresis = hysteresis;
+        self
+    }
+
+    /// Number of samples below the close level that are still passed.
+    #[must_use]
+    pub fn hold(mut self, hold: usize) -> PowerSquelchBuilder {
+        self.hold = hold;
+        self
+    }
+
+    /// Gain of the power estimator.
+    #[must_use]
+    pub fn alpha(mut self, alpha: f32) -> PowerSquelchBuilder {
+        self.alpha = alpha;
+        self
+    }
+
+    /// Drop samples while closed instead of outputting zeros.
+    #[must_use]
+    pub fn gate(mut self) -> PowerSquelchBuilder {
+        self.gate = true;
+        self
+    }
+
+    /// Tag start and end of bursts.
+    #[must_use]
+    pub fn tag(mut self) -> PowerSquelchBuilder {
+        self.tag = true;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        PowerSquelch::new(
+            self.threshold,
+            self.hysteresis,
+            self.hold,
+            self.alpha,
+            self.gate,
+            self.tag,
+        )
+    }
+}
+
+/// CTCSS squelch.
+///
+/// Mutes the audio input, while the CTCSS tone with the given frequency is not detected. The
+/// detector decides every 100 ms, if the tone has at least 1% of the signal power. The tone is
+/// not removed from the output.
+///
+/// # Inputs
+///
+/// `in`: Audio (f32)
+///
+/// # Outputs
+///
+/// `out`: Squelched audio (f32)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::CtcssSquelch;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let squelch = fg.add_block(CtcssSquelch::new(48000.0, 88.5));
+/// ```
+pub struct CtcssSquelch {
+    ctcss: Ctcss,
+}
+
+impl CtcssSquelch {
+    pub fn new(sample_rate: f32, frequency: f32) -> Block {
+        assert!(
+            frequency > 0.0 && frequency < sample_rate / 2.0,
+            "tone frequency has to be below the Nyquist frequency"
+        );
+
+        Block::new(
+            BlockMetaBuilder::new("CtcssSquelch").build(),
+            StreamIoBuilder::new()
+                .add_input::<f32>("in")
+                .add_output::<f32>("out")
+                .build(),
+            MessageIoBuilder::<CtcssSquelch>::new().build(),
+            CtcssSquelch {
+                ctcss: Ctcss::new(frequency, sample_rate),
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for CtcssSquelch {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<f32>();
+        let o = sio.output(0).slice::<f32>();
+
+        let m = std::cmp::min(i.len(), o.len());
+        for (x, y) in i[..m].iter().zip(o.iter_mut()) {
+            self.ctcss.push(*x);
+            *y = if self.ctcss.detected { *x } else { 0.0 };
+        }
+
+        sio.input(0).consume(m);
+        sio.output(0).produce(m);
+
+        if sio.input(0).finished() && m == i.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
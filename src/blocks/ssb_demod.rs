@@ -0,0 +1,147 @@
+use futuredsp::firdes;
+use futuredsp::UnaryKernel;
+use std::f32::consts::PI;
+
+use crate::anyhow::Result;
+use crate::kernels::FirKernel;
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Maximum number of mixed samples, buffered for the filter.
+const BUFFER_SIZE: usize = 1 << 14;
+
+/// Sideband of an SSB signal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sideband {
+    /// Upper sideband
+    Usb,
+    /// Lower sideband
+    Lsb,
+}
+
+/// SSB demodulator (Weaver method).
+///
+/// Shifts the center of the sideband to DC, low-pass filters it to half the audio bandwidth
+/// (default 3 kHz), and shifts it back, taking the real part. The input has to be centered at the
+/// (suppressed) carrier. Input and output use the same sample rate.
+///
+/// # Inputs
+///
+/// `in`: Baseband samples (Complex32)
+///
+/// # Outputs
+///
+/// `out`: Audio (f32)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::Sideband;
+/// use futuresdr::blocks::SsbDemod;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let demod = fg.add_block(SsbDemod::new(48000.0, Sideband::Lsb));
+/// ```
+pub struct SsbDemod {
+    filter: FirKernel<Complex32>,
+    shift: Complex32,
+    osc_in: Complex32,
+    osc_out: Complex32,
+    mixed: Vec<Complex32>,
+    filtered: Vec<Complex32>,
+}
+
+impl SsbDemod {
+    pub fn new(sample_rate: f32, sideband: Sideband) -> Block {
+        Self::with_bandwidth(sample_rate, sideband, 3000.0)
+    }
+
+    /// Create a demodulator for the given audio bandwidth in Hz.
+    pub fn with_bandwidth(sample_rate: f32, sideband: Sideband, bandwidth: f32) -> Block {
+        assert!(
+            bandwidth < 0.6 * sample_rate,
+            "bandwidth has to be smaller than 60% of the sample rate"
+        );
+
+        let fs = sample_rate as f64;
+        let bw = bandwidth as f64;
+        let taps = firdes::kaiser::lowpass::<f32>(bw / 2.0 / fs, bw / 6.0 / fs, 0.001);
+        let n_taps = taps.len();
+
+        let f = match sideband {
+            Sideband::Usb => bandwidth / 2.0,
+            Sideband::Lsb => -bandwidth / 2.0,
+        };
+        let shift = Complex32::from_polar(1.0, 2.0 * PI * f / sample_rate);
+
+        Block::new(
+            BlockMetaBuilder::new("SsbDemod").build(),
+            StreamIoBuilder::new()
+                .add_input::<Complex32>("in")
+                .add_output::<f32>("out")
+                .build(),
+            MessageIoBuilder::<SsbDemod>::new().build(),
+            SsbDemod {
+                filter: FirKernel::new(taps),
+                shift,
+                osc_in: Complex32::new(1.0, 0.0),
+                // filter output k ends with input sample k + n_taps - 1
+                osc_out: shift.powi(n_taps as i32 - 1),
+                mixed: Vec::with_capacity(BUFFER_SIZE),
+                filtered: vec![Complex32::new(0.0, 0.0); BUFFER_SIZE],
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for SsbDemod {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex32>();
+
+        // shift center of sideband to DC
+        let m = std::cmp::min(i.len(), BUFFER_SIZE - self.mixed.len());
+        for x in i[..m].iter() {
+            self.mixed.push(x * self.osc_in.conj());
+            self.osc_in *= self.shift;
+        }
+        self.osc_in /= self.osc_in.norm();
+        sio.input(0).consume(m);
+
+        // filter and shift back
+        let o = sio.output(0).slice::<f32>();
+        let n = std::cmp::min(o.len(), BUFFER_SIZE);
+        let (used, produced, status) = self.filter.work(&self.mixed, &mut self.filtered[..n]);
+        for (x, y) in self.filtered[..produced].iter().zip(o.iter_mut()) {
+            *y = (x * self.osc_out).re;
+            self.osc_out *= self.shift;
+        }
+        self.osc_out /= self.osc_out.norm();
+        self.mixed.drain(..used);
+        sio.output(0).produce(produced);
+
+        if sio.input(0).finished() && m == i.len() && status.produced_all_samples() {
+            io.finished = true;
+        } else if m > 0 && produced > 0 {
+            io.call_again = true;
+        }
+
+        Ok(())
+    }
+}
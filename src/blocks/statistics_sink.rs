@@ -0,0 +1,329 @@
+use futures::FutureExt;
+use std::collections::HashMap;
+
+use crate::anyhow::Result;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Histogram with equally-spaced bins.
+struct Histogram {
+    min: f32,
+    max: f32,
+    bins: Vec<u64>,
+    underflow: u64,
+    overflow: u64,
+}
+
+impl Histogram {
+    fn push(&mut self, x: f32) {
+        if x < self.min {
+            self.underflow += 1;
+        } else if x >= self.max {
+            self.overflow += 1;
+        } else {
+            let n = self.bins.len();
+            let i = ((x - self.min) / (self.max - self.min) * n as f32) as usize;
+            self.bins[i.min(n - 1)] += 1;
+        }
+    }
+}
+
+/// Running statistics of a stream.
+pub struct StatisticsSink {
+    count: u64,
+    min: f32,
+    max: f32,
+    sum: f64,
+    sum_sq: f64,
+    histogram: Option<Histogram>,
+    report_every: Option<u64>,
+    reset_on_report: bool,
+    n: u64,
+}
+
+impl StatisticsSink {
+    pub fn new(
+        histogram: Option<(f32, f32, usize)>,
+        report_every: Option<u64>,
+        reset_on_report: bool,
+    ) -> Block {
+        let histogram = histogram.map(|(min, max, bins)| {
+            assert!(
+                min < max,
+                "histogram minimum has to be smaller than maximum"
+            );
+            assert!(bins > 0, "number of bins must be positive");
+            Histogram {
+                min,
+                max,
+                bins: vec![0; bins],
+                underflow: 0,
+                overflow: 0,
+            }
+        });
+        if let Some(n) = report_every {
+            assert!(n > 0, "report interval must be positive");
+        }
+
+        Block::new(
+            BlockMetaBuilder::new("StatisticsSink").build(),
+            StreamIoBuilder::new().add_input::<f32>("in").build(),
+            MessageIoBuilder::new()
+                .add_input(
+                    "report",
+                    |block: &mut StatisticsSink,
+                     mio: &mut MessageIo<StatisticsSink>,
+                     _meta: &mut BlockMeta,
+                     _p: Pmt| {
+                        async move {
+                            let report = block.report();
+                            mio.post(0, report.clone()).await;
+                            Ok(report)
+                        }
+                        .boxed()
+                    },
+                )
+                .add_input(
+                    "reset",
+                    |block: &mut StatisticsSink,
+                     _mio: &mut MessageIo<StatisticsSink>,
+                     _meta: &mut BlockMeta,
+                     _p: Pmt| {
+                        async move {
+                            block.reset();
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .add_output("out")
+                .build(),
+            StatisticsSink {
+                count: 0,
+                min: f32::INFINITY,
+                max: f32::NEG_INFINITY,
+                sum: 0.0,
+                sum_sq: 0.0,
+                histogram,
+                report_every,
+                reset_on_report,
+                n: 0,
+            },
+        )
+    }
+
+    /// Number of samples since the last reset.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn min(&self) -> f32 {
+        self.min
+    }
+
+    pub fn max(&self) -> f32 {
+        self.max
+    }
+
+    pub fn mean(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.sum / self.count as f64
+        }
+    }
+
+    pub fn rms(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            (self.sum_sq / self.count as f64).sqrt()
+        }
+    }
+
+    /// Counts of the histogram bins, if configured.
+    pub fn histogram(&self) -> Option<&[u64]> {
+        self.histogram.as_ref().map(|h| h.bins.as_slice())
+    }
+
+    fn push(&mut self, x: f32) {
+        self.count += 1;
+        self.min = self.min.min(x);
+        self.max = self.max.max(x);
+        self.sum += x as f64;
+        self.sum_sq += x as f64 * x as f64;
+        if let Some(ref mut h) = self.histogram {
+            h.push(x);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.count = 0;
+        self.min = f32::INFINITY;
+        self.max = f32::NEG_INFINITY;
+        self.sum = 0.0;
+        self.sum_sq = 0.0;
+        if let Some(ref mut h) = self.histogram {
+            h.bins.iter_mut().for_each(|b| *b = 0);
+            h.underflow = 0;
+            h.overflow = 0;
+        }
+    }
+
+    /// Current statistics, resetting them if configured.
+    fn report(&mut self) -> Pmt {
+        let mut m = HashMap::new();
+        m.insert("count".to_string(), Pmt::U64(self.count));
+        m.insert("min".to_string(), Pmt::F32(self.min));
+        m.insert("max".to_string(), Pmt::F32(self.max));
+        m.insert("mean".to_string(), Pmt::F64(self.mean()));
+        m.insert("rms".to_string(), Pmt::F64(self.rms()));
+        if let Some(ref h) = self.histogram {
+            m.insert("histogram".to_string(), Pmt::VecU64(h.bins.clone()));
+            m.insert("underflow".to_string(), Pmt::U64(h.underflow));
+            m.insert("overflow".to_string(), Pmt::U64(h.overflow));
+        }
+        if self.reset_on_report {
+            self.reset();
+        }
+        Pmt::MapStrPmt(m)
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for StatisticsSink {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<f32>();
+
+        let mut reports = Vec::new();
+        for x in i.iter() {
+            self.push(*x);
+            if let Some(n) = self.report_every {
+                self.n += 1;
+                if self.n == n {
+                    self.n = 0;
+                    reports.push(self.report());
+                }
+            }
+        }
+        sio.input(0).consume(i.len());
+
+        for r in reports {
+            mio.post(0, r).await;
+        }
+
+        if sio.input(0).finished() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Running statistics of a stream.
+///
+/// Tracks the number of samples, minimum, maximum, mean, and RMS value of the input and,
+/// optionally, a histogram. Statistics are posted on request and, if configured with
+/// [report_every](StatisticsSinkBuilder::report_every), after every given number of samples.
+/// They are accumulated since the start or the last reset, unless
+/// [reset_on_report](StatisticsSinkBuilder::reset_on_report) is set.
+///
+/// After the flowgraph terminated, the statistics can also be queried from the kernel, e.g.,
+/// with [mean](StatisticsSink::mean).
+///
+/// Reports are [Pmt::MapStrPmt] with the entries
+/// - `count`: number of samples ([Pmt::U64]),
+/// - `min`, `max`: minimum and maximum, infinite without samples ([Pmt::F32]),
+/// - `mean`, `rms`: mean and RMS value, zero without samples ([Pmt::F64]),
+/// - `histogram`: counts of the histogram bins, if configured ([Pmt::VecU64]),
+/// - `underflow`, `overflow`: counts of samples below and above the histogram range, if
+///   configured ([Pmt::U64]).
+///
+/// # Inputs
+///
+/// `in`: Input (f32)
+///
+/// **Message**: `report`: Post the current statistics, which are also returned to the caller
+///
+/// **Message**: `reset`: Reset the statistics
+///
+/// # Outputs
+///
+/// **Message**: `out`: Statistics ([Pmt::MapStrPmt])
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::StatisticsSinkBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let stats = fg.add_block(
+///     StatisticsSinkBuilder::new()
+///         .histogram(-1.0, 1.0, 20)
+///         .report_every(48000)
+///         .reset_on_report()
+///         .build(),
+/// );
+/// ```
+pub struct StatisticsSinkBuilder {
+    histogram: Option<(f32, f32, usize)>,
+    report_every: Option<u64>,
+    reset_on_report: bool,
+}
+
+impl StatisticsSinkBuilder {
+    pub fn new() -> StatisticsSinkBuilder {
+        StatisticsSinkBuilder {
+            histogram: None,
+            report_every: None,
+            reset_on_report: false,
+        }
+    }
+
+    /// Histogram with `bins` equally-spaced bins from `min` to `max`.
+    #[must_use]
+    pub fn histogram(mut self, min: f32, max: f32, bins: usize) -> StatisticsSinkBuilder {
+        self.histogram = Some((min, max, bins));
+        self
+    }
+
+    /// Post the statistics after every `n` samples.
+    #[must_use]
+    pub fn report_every(mut self, n: u64) -> StatisticsSinkBuilder {
+        self.report_every = Some(n);
+        self
+    }
+
+    /// Reset the statistics after each report.
+    #[must_use]
+    pub fn reset_on_report(mut self) -> StatisticsSinkBuilder {
+        self.reset_on_report = true;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        StatisticsSink::new(self.histogram, self.report_every, self.reset_on_report)
+    }
+}
+
+impl Default for StatisticsSinkBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,214 @@
+use crate::anyhow::Result;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Multiplex streams, taking chunks of samples from the inputs in turn.
+///
+/// Takes `lengths[0]` samples from `in0`, `lengths[1]` samples from `in1`, and so on, before
+/// starting over with `in0`. The block finishes, when it waits for samples of a finished input.
+///
+/// # Inputs
+///
+/// `in0`, `in1`, ...: Inputs, one per chunk length
+///
+/// # Outputs
+///
+/// `out`: Multiplexed output
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::StreamMux;
+/// use futuresdr::num_complex::Complex32;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // preamble followed by payload
+/// let mux = fg.add_block(StreamMux::<Complex32>::new(vec![64, 1024]));
+/// ```
+pub struct StreamMux<T: Copy + Send + 'static> {
+    lengths: Vec<usize>,
+    current: usize,
+    remaining: usize,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: Copy + Send + 'static> StreamMux<T> {
+    pub fn new(lengths: Vec<usize>) -> Block {
+        assert!(!lengths.is_empty(), "at least one input required");
+        assert!(
+            lengths.iter().all(|l| *l > 0),
+            "chunk lengths must be positive"
+        );
+
+        let mut sio = StreamIoBuilder::new();
+        for i in 0..lengths.len() {
+            sio = sio.add_input::<T>(format!("in{i}").as_str());
+        }
+
+        Block::new(
+            BlockMetaBuilder::new(format!("StreamMux<{}>", lengths.len())).build(),
+            sio.add_output::<T>("out").build(),
+            MessageIoBuilder::<Self>::new().build(),
+            StreamMux::<T> {
+                remaining: lengths[0],
+                lengths,
+                current: 0,
+                _type: std::marker::PhantomData,
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: Copy + Send + 'static> Kernel for StreamMux<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let o = sio.output(0).slice::<T>();
+        let mut consumed = vec![0; self.lengths.len()];
+        let mut produced = 0;
+
+        loop {
+            let i = &sio.input(self.current).slice::<T>()[consumed[self.current]..];
+
+            let n = std::cmp::min(self.remaining, std::cmp::min(i.len(), o.len() - produced));
+            o[produced..produced + n].copy_from_slice(&i[..n]);
+            consumed[self.current] += n;
+            produced += n;
+            self.remaining -= n;
+
+            if self.remaining > 0 {
+                if sio.input(self.current).finished() && n == i.len() {
+                    io.finished = true;
+                }
+                break;
+            }
+
+            self.current = (self.current + 1) % self.lengths.len();
+            self.remaining = self.lengths[self.current];
+        }
+
+        for (k, n) in consumed.into_iter().enumerate() {
+            if n > 0 {
+                sio.input(k).consume(n);
+            }
+        }
+        sio.output(0).produce(produced);
+
+        Ok(())
+    }
+}
+
+/// Demultiplex a stream, forwarding chunks of samples to the outputs in turn.
+///
+/// Forwards `lengths[0]` samples to `out0`, `lengths[1]` samples to `out1`, and so on, before
+/// starting over with `out0`.
+///
+/// # Inputs
+///
+/// `in`: Multiplexed input
+///
+/// # Outputs
+///
+/// `out0`, `out1`, ...: Outputs, one per chunk length
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::StreamDemux;
+/// use futuresdr::num_complex::Complex32;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // separate preamble and payload
+/// let demux = fg.add_block(StreamDemux::<Complex32>::new(vec![64, 1024]));
+/// ```
+pub struct StreamDemux<T: Copy + Send + 'static> {
+    lengths: Vec<usize>,
+    current: usize,
+    remaining: usize,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: Copy + Send + 'static> StreamDemux<T> {
+    pub fn new(lengths: Vec<usize>) -> Block {
+        assert!(!lengths.is_empty(), "at least one output required");
+        assert!(
+            lengths.iter().all(|l| *l > 0),
+            "chunk lengths must be positive"
+        );
+
+        let mut sio = StreamIoBuilder::new().add_input::<T>("in");
+        for i in 0..lengths.len() {
+            sio = sio.add_output::<T>(format!("out{i}").as_str());
+        }
+
+        Block::new(
+            BlockMetaBuilder::new(format!("StreamDemux<{}>", lengths.len())).build(),
+            sio.build(),
+            MessageIoBuilder::<Self>::new().build(),
+            StreamDemux::<T> {
+                remaining: lengths[0],
+                lengths,
+                current: 0,
+                _type: std::marker::PhantomData,
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: Copy + Send + 'static> Kernel for StreamDemux<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<T>();
+        let mut consumed = 0;
+        let mut produced = vec![0; self.lengths.len()];
+
+        loop {
+            let o = &mut sio.output(self.current).slice::<T>()[produced[self.current]..];
+
+            let n = std::cmp::min(self.remaining, std::cmp::min(i.len() - consumed, o.len()));
+            o[..n].copy_from_slice(&i[consumed..consumed + n]);
+            consumed += n;
+            produced[self.current] += n;
+            self.remaining -= n;
+
+            if self.remaining > 0 {
+                if sio.input(0).finished() && consumed == i.len() {
+                    io.finished = true;
+                }
+                break;
+            }
+
+            self.current = (self.current + 1) % self.lengths.len();
+            self.remaining = self.lengths[self.current];
+        }
+
+        sio.input(0).consume(consumed);
+        for (k, n) in produced.into_iter().enumerate() {
+            sio.output(k).produce(n);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,380 @@
+use std::collections::VecDeque;
+
+use futures::FutureExt;
+
+use crate::anyhow::Result;
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Sample type that can be carried in PDUs.
+pub trait PduSample: Copy + Send + 'static {
+    /// Convert samples to a PDU.
+    fn to_pmt(samples: &[Self]) -> Pmt;
+    /// Convert a PDU to samples, returning [None] for PDUs of the wrong type.
+    fn from_pmt(p: &Pmt) -> Option<Vec<Self>>;
+}
+
+/// Bytes are carried as [Pmt::Blob].
+impl PduSample for u8 {
+    fn to_pmt(samples: &[Self]) -> Pmt {
+        Pmt::Blob(samples.to_vec())
+    }
+    fn from_pmt(p: &Pmt) -> Option<Vec<Self>> {
+        match p {
+            Pmt::Blob(v) => Some(v.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Samples are carried as [Pmt::VecF32].
+impl PduSample for f32 {
+    fn to_pmt(samples: &[Self]) -> Pmt {
+        Pmt::VecF32(samples.to_vec())
+    }
+    fn from_pmt(p: &Pmt) -> Option<Vec<Self>> {
+        match p {
+            Pmt::VecF32(v) => Some(v.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Samples are carried as [Pmt::VecU64].
+impl PduSample for u64 {
+    fn to_pmt(samples: &[Self]) -> Pmt {
+        Pmt::VecU64(samples.to_vec())
+    }
+    fn from_pmt(p: &Pmt) -> Option<Vec<Self>> {
+        match p {
+            Pmt::VecU64(v) => Some(v.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Samples are carried as [Pmt::Blob] with interleaved real and imaginary parts as
+/// little-endian `f32`, like the PDUs of [BurstToPdu](crate::blocks::BurstToPdu).
+impl PduSample for Complex32 {
+    fn to_pmt(samples: &[Self]) -> Pmt {
+        let mut blob = Vec::with_capacity(samples.len() * 8);
+        for x in samples {
+            blob.extend_from_slice(&x.re.to_le_bytes());
+            blob.extend_from_slice(&x.im.to_le_bytes());
+        }
+        Pmt::Blob(blob)
+    }
+    fn from_pmt(p: &Pmt) -> Option<Vec<Self>> {
+        match p {
+            Pmt::Blob(v) if v.len() % 8 == 0 => Some(
+                v.chunks_exact(8)
+                    .map(|c| {
+                        Complex32::new(
+                            f32::from_le_bytes(c[0..4].try_into().unwrap()),
+                            f32::from_le_bytes(c[4..8].try_into().unwrap()),
+                        )
+                    })
+                    .collect(),
+            ),
+            _ => None,
+        }
+    }
+}
+
+/// Chunk a stream into vectors of `N` samples.
+///
+/// An incomplete vector at the end of the stream is dropped.
+///
+/// # Inputs
+///
+/// `in`: Input
+///
+/// # Outputs
+///
+/// `out`: Vectors (`[T; N]`)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::StreamToVector;
+/// use futuresdr::num_complex::Complex32;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let s2v = fg.add_block(StreamToVector::<Complex32, 64>::new());
+/// ```
+pub struct StreamToVector<T: Copy + Send + 'static, const N: usize> {
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: Copy + Send + 'static, const N: usize> StreamToVector<T, N> {
+    pub fn new() -> Block {
+        assert!(N > 0, "vector length must be positive");
+
+        Block::new(
+            BlockMetaBuilder::new(format!("StreamToVector<{N}>")).build(),
+            StreamIoBuilder::new()
+                .add_input::<T>("in")
+                .add_output::<[T; N]>("out")
+                .build(),
+            MessageIoBuilder::<Self>::new().build(),
+            StreamToVector::<T, N> {
+                _type: std::marker::PhantomData,
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: Copy + Send + 'static, const N: usize> Kernel for StreamToVector<T, N> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<T>();
+        let o = sio.output(0).slice::<[T; N]>();
+
+        let m = std::cmp::min(i.len() / N, o.len());
+        for (x, y) in i.chunks_exact(N).zip(o[..m].iter_mut()) {
+            y.copy_from_slice(x);
+        }
+
+        sio.input(0).consume(m * N);
+        sio.output(0).produce(m);
+
+        if sio.input(0).finished() && i.len() - m * N < N {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Flatten vectors of `N` samples into a stream.
+///
+/// # Inputs
+///
+/// `in`: Vectors (`[T; N]`)
+///
+/// # Outputs
+///
+/// `out`: Output
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::VectorToStream;
+/// use futuresdr::num_complex::Complex32;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let v2s = fg.add_block(VectorToStream::<Complex32, 64>::new());
+/// ```
+pub struct VectorToStream<T: Copy + Send + 'static, const N: usize> {
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: Copy + Send + 'static, const N: usize> VectorToStream<T, N> {
+    pub fn new() -> Block {
+        assert!(N > 0, "vector length must be positive");
+
+        Block::new(
+            BlockMetaBuilder::new(format!("VectorToStream<{N}>")).build(),
+            StreamIoBuilder::new()
+                .add_input::<[T; N]>("in")
+                .add_output::<T>("out")
+                .build(),
+            MessageIoBuilder::<Self>::new().build(),
+            VectorToStream::<T, N> {
+                _type: std::marker::PhantomData,
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: Copy + Send + 'static, const N: usize> Kernel for VectorToStream<T, N> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<[T; N]>();
+        let o = sio.output(0).slice::<T>();
+
+        let m = std::cmp::min(i.len(), o.len() / N);
+        for (x, y) in i[..m].iter().zip(o.chunks_exact_mut(N)) {
+            y.copy_from_slice(x);
+        }
+
+        sio.input(0).consume(m);
+        sio.output(0).produce(m * N);
+
+        if sio.input(0).finished() && m == i.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Chunk a stream into PDUs of `len` samples.
+///
+/// An incomplete PDU at the end of the stream is dropped.
+///
+/// # Inputs
+///
+/// `in`: Input (u8, f32, u64, or Complex32)
+///
+/// **Message**: `out`: PDUs (see [PduSample])
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::StreamToPdu;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let frames = fg.add_block(StreamToPdu::<u8>::new(188));
+/// ```
+pub struct StreamToPdu<T: PduSample> {
+    len: usize,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: PduSample> StreamToPdu<T> {
+    pub fn new(len: usize) -> Block {
+        assert!(len > 0, "PDU length must be positive");
+
+        Block::new(
+            BlockMetaBuilder::new("StreamToPdu").build(),
+            StreamIoBuilder::new().add_input::<T>("in").build(),
+            MessageIoBuilder::<Self>::new().add_output("out").build(),
+            StreamToPdu::<T> {
+                len,
+                _type: std::marker::PhantomData,
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: PduSample> Kernel for StreamToPdu<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<T>();
+
+        let pdus: Vec<Pmt> = i.chunks_exact(self.len).map(T::to_pmt).collect();
+        sio.input(0).consume(pdus.len() * self.len);
+
+        if sio.input(0).finished() {
+            io.finished = true;
+        }
+
+        for pdu in pdus {
+            mio.post(0, pdu).await;
+        }
+
+        Ok(())
+    }
+}
+
+/// Stream the samples of PDUs.
+///
+/// PDUs of the wrong type are dropped. Samples that are still queued when the block is
+/// terminated are dropped.
+///
+/// # Outputs
+///
+/// `out`: Output (u8, f32, u64, or Complex32)
+///
+/// **Message**: `in`: PDUs (see [PduSample])
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::PduToStream;
+/// use futuresdr::num_complex::Complex32;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let samples = fg.add_block(PduToStream::<Complex32>::new());
+/// ```
+pub struct PduToStream<T: PduSample> {
+    queue: VecDeque<T>,
+}
+
+impl<T: PduSample> PduToStream<T> {
+    pub fn new() -> Block {
+        Block::new(
+            BlockMetaBuilder::new("PduToStream").build(),
+            StreamIoBuilder::new().add_output::<T>("out").build(),
+            MessageIoBuilder::<Self>::new()
+                .add_input(
+                    "in",
+                    |block: &mut PduToStream<T>,
+                     _mio: &mut MessageIo<PduToStream<T>>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            if let Some(v) = T::from_pmt(&p) {
+                                block.queue.extend(v);
+                            } else {
+                                warn!("PduToStream: received wrong PMT type. {:?}", p);
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .build(),
+            PduToStream::<T> {
+                queue: VecDeque::new(),
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: PduSample> Kernel for PduToStream<T> {
+    async fn work(
+        &mut self,
+        _io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let o = sio.output(0).slice::<T>();
+
+        let m = std::cmp::min(self.queue.len(), o.len());
+        for (x, y) in self.queue.drain(..m).zip(o.iter_mut()) {
+            *y = x;
+        }
+        sio.output(0).produce(m);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,389 @@
+use futuredsp::firdes;
+
+use crate::anyhow::Result;
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Timing error detector of the [SymbolSync] block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimingErrorDetector {
+    /// Gardner detector, using an additional sample between symbols.
+    Gardner,
+    /// Maximum likelihood detector, using the derivative of the filtered signal.
+    MaximumLikelihood,
+}
+
+/// Polyphase filter bank, evaluating a prototype filter (and its derivative) at fractional
+/// sample positions.
+struct FilterBank {
+    nfilts: usize,
+    /// First input offset (relative to the integer sample position) and taps of each arm.
+    arms: Vec<(isize, Vec<f32>)>,
+    diff_arms: Vec<(isize, Vec<f32>)>,
+    /// Number of samples required before and after the integer sample position.
+    history: usize,
+    lookahead: usize,
+}
+
+impl FilterBank {
+    fn new(nfilts: usize, taps: &[f32]) -> FilterBank {
+        // derivative per input sample
+        let diff: Vec<f32> = (0..taps.len())
+            .map(|j| {
+                let next = taps.get(j + 1).copied().unwrap_or(0.0);
+                let prev = if j > 0 { taps[j - 1] } else { 0.0 };
+                (next - prev) / 2.0 * nfilts as f32
+            })
+            .collect();
+
+        let arms = Self::arms(nfilts, taps);
+        let diff_arms = Self::arms(nfilts, &diff);
+        let history = arms
+            .iter()
+            .map(|(s, t)| (s + t.len() as isize - 1).max(0) as usize)
+            .max()
+            .unwrap_or(0);
+        let lookahead = arms
+            .iter()
+            .map(|(s, _)| (-s).max(0) as usize)
+            .max()
+            .unwrap_or(0);
+
+        FilterBank {
+            nfilts,
+            arms,
+            diff_arms,
+            history,
+            lookahead,
+        }
+    }
+
+    /// Arm `k` interpolates at position `n + k / nfilts` as `sum_s x[n - s] * h[c + s * nfilts + k]`,
+    /// with `c` the center of the prototype filter.
+    fn arms(nfilts: usize, taps: &[f32]) -> Vec<(isize, Vec<f32>)> {
+        let n = nfilts as isize;
+        let c = (taps.len() as isize - 1) / 2;
+        (0..n)
+            .map(|k| {
+                let s_min =
+                    (-c - k).div_euclid(n) + if (-c - k).rem_euclid(n) == 0 { 0 } else { 1 };
+                let arm = (s_min..)
+                    .map(|s| c + s * n + k)
+                    .take_while(|j| *j < taps.len() as isize)
+                    .map(|j| taps[j as usize])
+                    .collect();
+                (s_min, arm)
+            })
+            .collect()
+    }
+
+    fn eval(arms: &[(isize, Vec<f32>)], nfilts: usize, x: &[Complex32], pos: f64) -> Complex32 {
+        let mut n = pos.floor() as isize;
+        let mut k = ((pos - pos.floor()) * nfilts as f64).round() as usize;
+        if k == nfilts {
+            k = 0;
+            n += 1;
+        }
+        let (s_min, taps) = &arms[k];
+        let mut sum = Complex32::new(0.0, 0.0);
+        for (i, t) in taps.iter().enumerate() {
+            sum += x[(n - s_min - i as isize) as usize] * t;
+        }
+        sum
+    }
+
+    fn filter(&self, x: &[Complex32], pos: f64) -> Complex32 {
+        Self::eval(&self.arms, self.nfilts, x, pos)
+    }
+
+    fn diff(&self, x: &[Complex32], pos: f64) -> Complex32 {
+        Self::eval(&self.diff_arms, self.nfilts, x, pos)
+    }
+}
+
+/// Symbol timing recovery of the [SymbolSync] block.
+pub(crate) struct SymbolTiming {
+    bank: FilterBank,
+    ted: TimingErrorDetector,
+    sps: f64,
+    max_deviation: f64,
+    alpha: f64,
+    beta: f64,
+    period: f64,
+    pos: f64,
+    last: Complex32,
+}
+
+impl SymbolTiming {
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        sps: f32,
+        loop_bw: f32,
+        damping: f32,
+        ted_gain: f32,
+        max_deviation: f32,
+        ted: TimingErrorDetector,
+        nfilts: usize,
+        taps: Vec<f32>,
+    ) -> SymbolTiming {
+        assert!(sps >= 2.0, "at least two samples per symbol required");
+        assert!(nfilts > 0, "number of filters must be positive");
+
+        // PI loop filter gains, like GNU Radio's clock tracking loop
+        let (bw, zeta) = (loop_bw as f64, damping as f64);
+        let theta = bw / (zeta + 1.0 / (4.0 * zeta));
+        let denom = 1.0 + 2.0 * zeta * theta + theta * theta;
+        let alpha = 4.0 * zeta * theta / denom / ted_gain as f64;
+        let beta = 4.0 * theta * theta / denom / ted_gain as f64;
+
+        let bank = FilterBank::new(nfilts, &taps);
+        let sps = sps as f64;
+        let pos = bank.history as f64 + sps;
+
+        SymbolTiming {
+            bank,
+            ted,
+            sps,
+            max_deviation: max_deviation as f64,
+            alpha,
+            beta,
+            period: sps,
+            pos,
+            last: Complex32::new(0.0, 0.0),
+        }
+    }
+
+    /// Interpolate symbols from the input, returning the number of consumed and produced items.
+    pub(crate) fn work(&mut self, i: &[Complex32], o: &mut [Complex32]) -> (usize, usize) {
+        let mut produced = 0;
+        while produced < o.len() && (self.pos.ceil() as usize + self.bank.lookahead) < i.len() {
+            let y = self.bank.filter(i, self.pos);
+            let e = match self.ted {
+                TimingErrorDetector::MaximumLikelihood => {
+                    let d = self.bank.diff(i, self.pos);
+                    y.re * d.re + y.im * d.im
+                }
+                TimingErrorDetector::Gardner => {
+                    let mid = self.bank.filter(i, self.pos - self.period / 2.0);
+                    let d = self.last - y;
+                    d.re * mid.re + d.im * mid.im
+                }
+            } as f64;
+            let e = e.clamp(-1.0, 1.0);
+
+            o[produced] = y;
+            produced += 1;
+            self.last = y;
+
+            self.period = (self.period + self.beta * e).clamp(
+                self.sps * (1.0 - self.max_deviation),
+                self.sps * (1.0 + self.max_deviation),
+            );
+            self.pos += self.period + self.alpha * e;
+        }
+
+        // keep enough history for the filter and the Gardner detector
+        let keep = self.bank.history as f64 + self.sps;
+        let consumed = (self.pos - keep).floor().max(0.0) as usize;
+        self.pos -= consumed as f64;
+
+        (consumed, produced)
+    }
+}
+
+/// Interpolating prototype filter for a bank of `nfilts` filters.
+pub(crate) fn interpolation_taps(nfilts: usize) -> Vec<f32> {
+    firdes::kaiser::lowpass::<f32>(0.35 / nfilts as f64, 0.15 / nfilts as f64, 0.0001)
+        .into_iter()
+        .map(|t| t * nfilts as f32)
+        .collect()
+}
+
+/// Polyphase filter bank symbol synchronizer.
+pub struct SymbolSync {
+    timing: SymbolTiming,
+}
+
+impl SymbolSync {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        sps: f32,
+        loop_bw: f32,
+        damping: f32,
+        ted_gain: f32,
+        max_deviation: f32,
+        ted: TimingErrorDetector,
+        nfilts: usize,
+        taps: Vec<f32>,
+    ) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("SymbolSync").build(),
+            StreamIoBuilder::new()
+                .add_input::<Complex32>("in")
+                .add_output::<Complex32>("out")
+                .build(),
+            MessageIoBuilder::<SymbolSync>::new().build(),
+            SymbolSync {
+                timing: SymbolTiming::new(
+                    sps,
+                    loop_bw,
+                    damping,
+                    ted_gain,
+                    max_deviation,
+                    ted,
+                    nfilts,
+                    taps,
+                ),
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for SymbolSync {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex32>();
+        let o = sio.output(0).slice::<Complex32>();
+
+        let (consumed, produced) = self.timing.work(i, o);
+
+        sio.input(0).consume(consumed);
+        sio.output(0).produce(produced);
+
+        if sio.input(0).finished() && produced < o.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Polyphase filter bank symbol synchronizer.
+///
+/// Recovers the symbol timing and outputs one sample per symbol. The input is interpolated with a
+/// bank of `nfilts` filters (default 32). The timing error is computed with a
+/// [TimingErrorDetector] (default: maximum likelihood) and tracked with a second-order loop with
+/// normalized bandwidth `loop_bw` and damping factor (default 1.0). The symbol period is allowed to
+/// deviate by `max_deviation` (default 1.5%) from the nominal samples per symbol.
+///
+/// By default, the prototype filter only interpolates. To also apply a matched filter, set its
+/// taps, designed for a sample rate of `nfilts` times the input rate.
+///
+/// # Inputs
+///
+/// `in`: Input (Complex32)
+///
+/// # Outputs
+///
+/// `out`: Symbols (Complex32)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::SymbolSyncBuilder;
+/// use futuresdr::blocks::TimingErrorDetector;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let sync = fg.add_block(
+///     SymbolSyncBuilder::new(4.0, 0.01)
+///         .ted(TimingErrorDetector::Gardner)
+///         .build(),
+/// );
+/// ```
+pub struct SymbolSyncBuilder {
+    sps: f32,
+    loop_bw: f32,
+    damping: f32,
+    ted_gain: f32,
+    max_deviation: f32,
+    ted: TimingErrorDetector,
+    nfilts: usize,
+    taps: Option<Vec<f32>>,
+}
+
+impl SymbolSyncBuilder {
+    pub fn new(sps: f32, loop_bw: f32) -> SymbolSyncBuilder {
+        SymbolSyncBuilder {
+            sps,
+            loop_bw,
+            damping: 1.0,
+            ted_gain: 1.0,
+            max_deviation: 0.015,
+            ted: TimingErrorDetector::MaximumLikelihood,
+            nfilts: 32,
+            taps: None,
+        }
+    }
+
+    /// Damping factor of the loop.
+    #[must_use]
+    pub fn damping(mut self, damping: f32) -> SymbolSyncBuilder {
+        self.damping = damping;
+        self
+    }
+
+    /// Expected gain of the timing error detector, used to normalize the loop gains.
+    #[must_use]
+    pub fn ted_gain(mut self, ted_gain: f32) -> SymbolSyncBuilder {
+        self.ted_gain = ted_gain;
+        self
+    }
+
+    /// Maximum relative deviation of the symbol period.
+    #[must_use]
+    pub fn max_deviation(mut self, max_deviation: f32) -> SymbolSyncBuilder {
+        self.max_deviation = max_deviation;
+        self
+    }
+
+    #[must_use]
+    pub fn ted(mut self, ted: TimingErrorDetector) -> SymbolSyncBuilder {
+        self.ted = ted;
+        self
+    }
+
+    /// Number of filters in the filter bank.
+    #[must_use]
+    pub fn nfilts(mut self, nfilts: usize) -> SymbolSyncBuilder {
+        self.nfilts = nfilts;
+        self
+    }
+
+    /// Prototype filter, designed for a sample rate of `nfilts` times the input rate.
+    #[must_use]
+    pub fn taps(mut self, taps: Vec<f32>) -> SymbolSyncBuilder {
+        self.taps = Some(taps);
+        self
+    }
+
+    pub fn build(self) -> Block {
+        let nfilts = self.nfilts;
+        let taps = self.taps.unwrap_or_else(|| interpolation_taps(nfilts));
+        SymbolSync::new(
+            self.sps,
+            self.loop_bw,
+            self.damping,
+            self.ted_gain,
+            self.max_deviation,
+            self.ted,
+            nfilts,
+            taps,
+        )
+    }
+}
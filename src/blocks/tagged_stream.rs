@@ -0,0 +1,226 @@
+use std::collections::VecDeque;
+
+use futures::FutureExt;
+
+use crate::anyhow::Result;
+use crate::blocks::PduSample;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::Tag;
+use crate::runtime::WorkIo;
+
+/// Stream the samples of PDUs, tagging the first sample of each PDU with its length.
+///
+/// The first sample of each PDU is tagged with [Tag::NamedUsize] `len_tag` and the number of
+/// samples of the PDU. PDUs of the wrong type and empty PDUs are dropped. Samples that are
+/// still queued when the block is terminated are dropped.
+///
+/// # Outputs
+///
+/// `out`: Tagged stream (u8, f32, u64, or Complex32)
+///
+/// **Message**: `in`: PDUs (see [PduSample])
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::PduToTaggedStream;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let packets = fg.add_block(PduToTaggedStream::<u8>::new("packet_len"));
+/// ```
+pub struct PduToTaggedStream<T: PduSample> {
+    len_tag: String,
+    queue: VecDeque<Vec<T>>,
+    /// Samples of the first queued PDU that were already streamed.
+    pos: usize,
+}
+
+impl<T: PduSample> PduToTaggedStream<T> {
+    pub fn new(len_tag: &str) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("PduToTaggedStream").build(),
+            StreamIoBuilder::new().add_output::<T>("out").build(),
+            MessageIoBuilder::<Self>::new()
+                .add_input(
+                    "in",
+                    |block: &mut PduToTaggedStream<T>,
+                     _mio: &mut MessageIo<PduToTaggedStream<T>>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            match T::from_pmt(&p) {
+                                Some(v) if !v.is_empty() => block.queue.push_back(v),
+                                Some(_) => {}
+                                None => {
+                                    warn!("PduToTaggedStream: received wrong PMT type. {:?}", p)
+                                }
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .build(),
+            PduToTaggedStream::<T> {
+                len_tag: len_tag.to_string(),
+                queue: VecDeque::new(),
+                pos: 0,
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: PduSample> Kernel for PduToTaggedStream<T> {
+    async fn work(
+        &mut self,
+        _io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let o = sio.output(0).slice::<T>();
+
+        let mut n = 0;
+        while n < o.len() {
+            let pdu = match self.queue.front() {
+                Some(pdu) => pdu,
+                None => break,
+            };
+            if self.pos == 0 {
+                sio.output(0)
+                    .add_tag(n, Tag::NamedUsize(self.len_tag.clone(), pdu.len()));
+            }
+            let m = std::cmp::min(pdu.len() - self.pos, o.len() - n);
+            o[n..n + m].copy_from_slice(&pdu[self.pos..self.pos + m]);
+            n += m;
+            self.pos += m;
+            if self.pos == pdu.len() {
+                self.queue.pop_front();
+                self.pos = 0;
+            }
+        }
+        sio.output(0).produce(n);
+
+        Ok(())
+    }
+}
+
+/// Collect tagged packets of a stream into PDUs.
+///
+/// A packet starts at a sample tagged with [Tag::NamedUsize] `len_tag` and spans the number of
+/// samples given by the tag, as added by the [PduToTaggedStream] block. Samples outside of
+/// packets are dropped. A packet that is interrupted by the start of the next packet or by the
+/// end of the stream is dropped.
+///
+/// # Inputs
+///
+/// `in`: Tagged stream (u8, f32, u64, or Complex32)
+///
+/// # Outputs
+///
+/// **Message**: `out`: PDUs (see [PduSample])
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::TaggedStreamToPdu;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let packets = fg.add_block(TaggedStreamToPdu::<u8>::new("packet_len"));
+/// ```
+pub struct TaggedStreamToPdu<T: PduSample> {
+    len_tag: String,
+    packet: Vec<T>,
+    /// Samples missing to complete the current packet.
+    remaining: usize,
+}
+
+impl<T: PduSample> TaggedStreamToPdu<T> {
+    pub fn new(len_tag: &str) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("TaggedStreamToPdu").build(),
+            StreamIoBuilder::new().add_input::<T>("in").build(),
+            MessageIoBuilder::<Self>::new().add_output("out").build(),
+            TaggedStreamToPdu::<T> {
+                len_tag: len_tag.to_string(),
+                packet: Vec::new(),
+                remaining: 0,
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: PduSample> Kernel for TaggedStreamToPdu<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<T>();
+        let n = i.len();
+
+        let starts: Vec<(usize, usize)> = sio
+            .input(0)
+            .tags()
+            .iter()
+            .filter_map(|t| match &t.tag {
+                Tag::NamedUsize(s, len) if t.index < n && *s == self.len_tag => {
+                    Some((t.index, *len))
+                }
+                _ => None,
+            })
+            .collect();
+
+        let mut pdus = Vec::new();
+        let mut k = 0;
+        for (index, len) in starts.into_iter().chain(std::iter::once((n, 0))) {
+            let m = std::cmp::min(self.remaining, index - k);
+            if m > 0 {
+                self.packet.extend_from_slice(&i[k..k + m]);
+                self.remaining -= m;
+                if self.remaining == 0 {
+                    pdus.push(T::to_pmt(&self.packet));
+                    self.packet.clear();
+                }
+            }
+
+            if index == n {
+                break;
+            }
+            if self.remaining > 0 {
+                warn!("TaggedStreamToPdu: packet interrupted by the next packet, dropping");
+                self.packet.clear();
+            }
+            self.remaining = len;
+            k = index;
+        }
+
+        sio.input(0).consume(n);
+
+        if sio.input(0).finished() {
+            io.finished = true;
+        }
+
+        for pdu in pdus {
+            mio.post(0, pdu).await;
+        }
+
+        Ok(())
+    }
+}
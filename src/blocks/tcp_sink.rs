@@ -1,7 +1,12 @@
-use async_net::{TcpListener, TcpStream};
-use futures::AsyncWriteExt;
+use async_io::Async;
+use std::io::{ErrorKind, Write};
+use std::net::{Shutdown, TcpStream};
+use std::sync::Arc;
+use std::time::Duration;
 
 use crate::anyhow::{bail, Context, Result};
+use crate::blocks::tcp_source::TcpConnection;
+use crate::blocks::TcpMode;
 use crate::runtime::Block;
 use crate::runtime::BlockMeta;
 use crate::runtime::BlockMetaBuilder;
@@ -13,22 +18,32 @@ use crate::runtime::StreamIoBuilder;
 use crate::runtime::WorkIo;
 
 /// Push samples into a TCP socket.
-pub struct TcpSink {
-    port: u32,
-    listener: Option<TcpListener>,
-    socket: Option<TcpStream>,
+pub struct TcpSink<T: Send + 'static> {
+    connection: TcpConnection,
+    socket: Option<Arc<Async<TcpStream>>>,
+    /// Bytes of the first sample that were already sent.
+    partial: usize,
+    _type: std::marker::PhantomData<T>,
 }
 
-impl TcpSink {
+impl TcpSink<u8> {
+    /// Accept a connection on `127.0.0.1:<port>` and write bytes to it.
     pub fn new(port: u32) -> Block {
+        TcpSinkBuilder::<u8>::new(TcpMode::Server(format!("127.0.0.1:{port}"))).build()
+    }
+}
+
+impl<T: Send + 'static> TcpSink<T> {
+    fn create(mode: TcpMode, reconnect: Option<Duration>) -> Block {
         Block::new(
             BlockMetaBuilder::new("TcpSink").build(),
-            StreamIoBuilder::new().add_input::<u8>("in").build(),
+            StreamIoBuilder::new().add_input::<T>("in").build(),
             MessageIoBuilder::new().build(),
-            TcpSink {
-                port,
-                listener: None,
+            TcpSink::<T> {
+                connection: TcpConnection::new(mode, reconnect),
                 socket: None,
+                partial: 0,
+                _type: std::marker::PhantomData,
             },
         )
     }
@@ -36,7 +51,7 @@ impl TcpSink {
 
 #[doc(hidden)]
 #[async_trait]
-impl Kernel for TcpSink {
+impl<T: Send + 'static> Kernel for TcpSink<T> {
     async fn work(
         &mut self,
         io: &mut WorkIo,
@@ -45,36 +60,48 @@ impl Kernel for TcpSink {
         _meta: &mut BlockMeta,
     ) -> Result<()> {
         if self.socket.is_none() {
-            let (socket, _) = self
-                .listener
-                .as_mut()
-                .context("no listener")?
-                .accept()
-                .await?;
-            self.socket = Some(socket);
-            debug!("tcp sink accepted connection");
+            match self.connection.connect(io).await? {
+                Some(s) => self.socket = Some(s),
+                None => return Ok(()),
+            }
         }
+        let socket = self.socket.as_ref().context("no socket")?;
 
-        let i = sio.input(0).slice::<u8>();
-
-        match self
-            .socket
-            .as_mut()
-            .context("no socket")?
-            .write_all(i)
-            .await
-        {
-            Ok(()) => {}
-            Err(_) => bail!("tcp sink socket error"),
-        }
+        let i = sio.input(0).slice_unchecked::<u8>();
+        let item_size = std::mem::size_of::<T>();
+        let len = i.len() / item_size * item_size;
 
-        if sio.input(0).finished() {
+        if self.partial < len {
+            match socket.get_ref().write(&i[self.partial..len]) {
+                Ok(n) => {
+                    debug!("tcp sink wrote bytes {}", n);
+                    let sent = self.partial + n;
+                    sio.input(0).consume(sent / item_size);
+                    self.partial = sent % item_size;
+                    io.call_again = true;
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                    let s = socket.clone();
+                    io.block_on(async move {
+                        let _ = s.writable().await;
+                    });
+                }
+                Err(e) if e.kind() == ErrorKind::Interrupted => io.call_again = true,
+                Err(e) if self.connection.reconnect() => {
+                    // send the samples again on the next connection
+                    debug!("tcp sink socket error {}", e);
+                    self.socket = None;
+                    self.partial = 0;
+                    io.call_again = true;
+                }
+                Err(e) => bail!("tcp sink socket error {}", e),
+            }
+        } else if sio.input(0).finished() {
+            // signal the end of the stream to the peer
+            let _ = socket.get_ref().shutdown(Shutdown::Write);
             io.finished = true;
         }
 
-        debug!("tcp sink wrote bytes {}", i.len());
-        sio.input(0).consume(i.len());
-
         Ok(())
     }
 
@@ -84,7 +111,67 @@ impl Kernel for TcpSink {
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
-        self.listener = Some(TcpListener::bind(format!("127.0.0.1:{}", self.port)).await?);
-        Ok(())
+        self.connection.init()
+    }
+}
+
+/// Push samples into a TCP socket.
+///
+/// Either listens for a connection ([TcpMode::Server]) or connects to a peer
+/// ([TcpMode::Client]) and sends the samples in native byte order. Without
+/// [reconnect](TcpSinkBuilder::reconnect), a broken connection is an error. Otherwise, the sink
+/// accepts or establishes a new connection, retrying failed connection attempts after the given
+/// interval, and sends the samples that were not transmitted completely to the new peer.
+///
+/// Samples are only consumed once they are written to the socket, i.e., the flowgraph is slowed
+/// down by TCP flow control, if the peer does not keep up, and while there is no connection.
+/// When the input is finished, the connection is shut down.
+///
+/// # Inputs
+///
+/// `in`: Samples to send
+///
+/// # Outputs
+///
+/// No outputs.
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::{TcpMode, TcpSinkBuilder};
+/// use futuresdr::runtime::Flowgraph;
+/// use std::time::Duration;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let snk = fg.add_block(
+///     TcpSinkBuilder::<f32>::new(TcpMode::Server("0.0.0.0:1234".to_string()))
+///         .reconnect(Duration::from_secs(1))
+///         .build(),
+/// );
+/// ```
+pub struct TcpSinkBuilder<T: Send + 'static> {
+    mode: TcpMode,
+    reconnect: Option<Duration>,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: Send + 'static> TcpSinkBuilder<T> {
+    pub fn new(mode: TcpMode) -> TcpSinkBuilder<T> {
+        TcpSinkBuilder {
+            mode,
+            reconnect: None,
+            _type: std::marker::PhantomData,
+        }
+    }
+
+    /// Continue with a new connection, retrying failed connection attempts after `interval`.
+    #[must_use]
+    pub fn reconnect(mut self, interval: Duration) -> TcpSinkBuilder<T> {
+        self.reconnect = Some(interval);
+        self
+    }
+
+    pub fn build(self) -> Block {
+        TcpSink::<T>::create(self.mode, self.reconnect)
     }
 }
@@ -1,5 +1,8 @@
-use async_net::{TcpListener, TcpStream};
-use futures::AsyncReadExt;
+use async_io::{Async, Timer};
+use std::io::{ErrorKind, Read};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::time::Duration;
 
 use crate::anyhow::{Context, Result};
 use crate::runtime::Block;
@@ -12,23 +15,130 @@ use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
 use crate::runtime::WorkIo;
 
-/// Push samples into a TCP socket.
-pub struct TcpSource {
-    port: u32,
-    listener: Option<TcpListener>,
-    socket: Option<TcpStream>,
+/// Role of a [TcpSource](TcpSourceBuilder) or [TcpSink](crate::blocks::TcpSinkBuilder) in the
+/// connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TcpMode {
+    /// Listen on the given address and accept connections.
+    Server(String),
+    /// Connect to the given address.
+    Client(String),
 }
 
-impl TcpSource {
+/// Connection of the TCP blocks, accepting or establishing connections.
+///
+/// All operations return immediately and let the block wait for the socket with
+/// [WorkIo::block_on], so that the flowgraph can be terminated while waiting for a peer.
+pub(super) struct TcpConnection {
+    mode: TcpMode,
+    reconnect: Option<Duration>,
+    listener: Option<Arc<Async<TcpListener>>>,
+}
+
+impl TcpConnection {
+    pub fn new(mode: TcpMode, reconnect: Option<Duration>) -> TcpConnection {
+        TcpConnection {
+            mode,
+            reconnect,
+            listener: None,
+        }
+    }
+
+    /// Whether the block continues with a new connection, after the current one was closed.
+    pub fn reconnect(&self) -> bool {
+        self.reconnect.is_some()
+    }
+
+    fn resolve(addr: &str) -> Result<SocketAddr> {
+        addr.to_socket_addrs()?
+            .next()
+            .with_context(|| format!("cannot resolve {addr}"))
+    }
+
+    pub fn init(&mut self) -> Result<()> {
+        if let TcpMode::Server(addr) = &self.mode {
+            let addr = Self::resolve(addr)?;
+            self.listener = Some(Arc::new(Async::<TcpListener>::bind(addr)?));
+        }
+        Ok(())
+    }
+
+    /// Accept or establish a connection.
+    ///
+    /// Returns `None`, if there is no connection yet, after scheduling a wake-up for the next
+    /// attempt. Failed connection attempts are retried, if reconnecting is enabled.
+    pub async fn connect(&mut self, io: &mut WorkIo) -> Result<Option<Arc<Async<TcpStream>>>> {
+        match &self.mode {
+            TcpMode::Server(_) => {
+                let listener = self.listener.as_ref().context("no listener")?;
+                match listener.get_ref().accept() {
+                    Ok((socket, peer)) => {
+                        debug!("tcp accepted connection from {}", peer);
+                        Ok(Some(Arc::new(Async::new(socket)?)))
+                    }
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                        let l = listener.clone();
+                        io.block_on(async move {
+                            let _ = l.readable().await;
+                        });
+                        Ok(None)
+                    }
+                    Err(e) => Err(e.into()),
+                }
+            }
+            TcpMode::Client(addr) => {
+                let e = match Self::resolve(addr) {
+                    Ok(a) => match Async::<TcpStream>::connect(a).await {
+                        Ok(socket) => {
+                            debug!("tcp connected to {}", addr);
+                            return Ok(Some(Arc::new(socket)));
+                        }
+                        Err(e) => e.into(),
+                    },
+                    Err(e) => e,
+                };
+                match self.reconnect {
+                    Some(interval) => {
+                        debug!("tcp connecting to {} failed ({}), retrying", addr, e);
+                        io.block_on(async move {
+                            Timer::after(interval).await;
+                        });
+                        Ok(None)
+                    }
+                    None => Err(e),
+                }
+            }
+        }
+    }
+}
+
+/// Read samples from a TCP socket.
+pub struct TcpSource<T: Send + 'static> {
+    connection: TcpConnection,
+    socket: Option<Arc<Async<TcpStream>>>,
+    /// Bytes of an incomplete sample.
+    carry: Vec<u8>,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl TcpSource<u8> {
+    /// Accept a connection on `127.0.0.1:<port>` and read bytes until it is closed.
     pub fn new(port: u32) -> Block {
+        TcpSourceBuilder::<u8>::new(TcpMode::Server(format!("127.0.0.1:{port}"))).build()
+    }
+}
+
+impl<T: Send + 'static> TcpSource<T> {
+    fn create(mode: TcpMode, reconnect: Option<Duration>) -> Block {
         Block::new(
             BlockMetaBuilder::new("TcpSource").build(),
-            StreamIoBuilder::new().add_output::<u8>("out").build(),
+            StreamIoBuilder::new().add_output::<T>("out").build(),
             MessageIoBuilder::new().build(),
-            TcpSource {
-                port,
-                listener: None,
+            TcpSource::<T> {
+                connection: TcpConnection::new(mode, reconnect),
                 socket: None,
+                carry: Vec::new(),
+                _type: std::marker::PhantomData,
             },
         )
     }
@@ -36,7 +146,7 @@ impl TcpSource {
 
 #[doc(hidden)]
 #[async_trait]
-impl Kernel for TcpSource {
+impl<T: Send + 'static> Kernel for TcpSource<T> {
     async fn work(
         &mut self,
         io: &mut WorkIo,
@@ -45,35 +155,59 @@ impl Kernel for TcpSource {
         _meta: &mut BlockMeta,
     ) -> Result<()> {
         if self.socket.is_none() {
-            let (socket, _) = self
-                .listener
-                .as_mut()
-                .context("no listener")?
-                .accept()
-                .await?;
-            self.socket = Some(socket);
-            debug!("tcp source accepted connection");
+            match self.connection.connect(io).await? {
+                Some(s) => self.socket = Some(s),
+                None => return Ok(()),
+            }
         }
+        let socket = self.socket.as_ref().context("no socket")?;
 
-        let out = sio.output(0).slice::<u8>();
-        if out.is_empty() {
+        let item_size = std::mem::size_of::<T>();
+        let out = sio.output(0).slice_unchecked::<u8>();
+        if out.len() < item_size {
             return Ok(());
         }
 
-        match self
-            .socket
-            .as_mut()
-            .context("no socket")?
-            .read_exact(out)
-            .await
-        {
-            Ok(_) => {
-                debug!("tcp source read bytes {}", out.len());
-                sio.output(0).produce(out.len());
-            }
-            Err(_) => {
+        let n = self.carry.len();
+        out[..n].copy_from_slice(&self.carry);
+
+        match socket.get_ref().read(&mut out[n..]) {
+            Ok(0) => {
                 debug!("tcp source socket closed");
-                io.finished = true;
+                self.socket = None;
+                self.carry.clear();
+                if self.connection.reconnect() {
+                    // samples are not split across connections
+                    io.call_again = true;
+                } else {
+                    io.finished = true;
+                }
+            }
+            Ok(read) => {
+                debug!("tcp source read bytes {}", read);
+                let items = (n + read) / item_size;
+                self.carry.clear();
+                self.carry
+                    .extend_from_slice(&out[items * item_size..n + read]);
+                sio.output(0).produce(items);
+                io.call_again = true;
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                let s = socket.clone();
+                io.block_on(async move {
+                    let _ = s.readable().await;
+                });
+            }
+            Err(e) if e.kind() == ErrorKind::Interrupted => io.call_again = true,
+            Err(e) => {
+                debug!("tcp source socket error {}", e);
+                self.socket = None;
+                self.carry.clear();
+                if self.connection.reconnect() {
+                    io.call_again = true;
+                } else {
+                    io.finished = true;
+                }
             }
         }
 
@@ -86,7 +220,67 @@ impl Kernel for TcpSource {
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
-        self.listener = Some(TcpListener::bind(format!("127.0.0.1:{}", self.port)).await?);
-        Ok(())
+        self.connection.init()
+    }
+}
+
+/// Read samples from a TCP socket.
+///
+/// Either listens for a connection ([TcpMode::Server]) or connects to a peer
+/// ([TcpMode::Client]) and outputs the received bytes as samples in native byte order. Without
+/// [reconnect](TcpSourceBuilder::reconnect), the source finishes when the connection is closed.
+/// Otherwise, it accepts or establishes a new connection, retrying failed connection attempts
+/// after the given interval.
+///
+/// The source only reads from the socket, when there is space in the output buffer, i.e., the
+/// peer is slowed down by TCP flow control, if the flowgraph does not keep up.
+///
+/// # Inputs
+///
+/// No inputs.
+///
+/// # Outputs
+///
+/// `out`: Received samples
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::{TcpMode, TcpSourceBuilder};
+/// use futuresdr::num_complex::Complex32;
+/// use futuresdr::runtime::Flowgraph;
+/// use std::time::Duration;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let src = fg.add_block(
+///     TcpSourceBuilder::<Complex32>::new(TcpMode::Client("127.0.0.1:1234".to_string()))
+///         .reconnect(Duration::from_secs(1))
+///         .build(),
+/// );
+/// ```
+pub struct TcpSourceBuilder<T: Send + 'static> {
+    mode: TcpMode,
+    reconnect: Option<Duration>,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: Send + 'static> TcpSourceBuilder<T> {
+    pub fn new(mode: TcpMode) -> TcpSourceBuilder<T> {
+        TcpSourceBuilder {
+            mode,
+            reconnect: None,
+            _type: std::marker::PhantomData,
+        }
+    }
+
+    /// Continue with a new connection, retrying failed connection attempts after `interval`.
+    #[must_use]
+    pub fn reconnect(mut self, interval: Duration) -> TcpSourceBuilder<T> {
+        self.reconnect = Some(interval);
+        self
+    }
+
+    pub fn build(self) -> Block {
+        TcpSource::<T>::create(self.mode, self.reconnect)
     }
 }
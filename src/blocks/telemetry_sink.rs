@@ -0,0 +1,68 @@
+use crate::anyhow::Result;
+use crate::runtime::telemetry;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+
+/// Report received messages as [telemetry](crate::runtime::telemetry) gauge.
+///
+/// # Inputs
+///
+/// `in`: Message, used as current value of the gauge
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::TelemetrySink;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let sink = fg.add_block(TelemetrySink::new("snr"));
+/// ```
+pub struct TelemetrySink {
+    name: String,
+}
+
+impl TelemetrySink {
+    pub fn new(name: impl Into<String>) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("TelemetrySink").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_input("in", Self::in_port)
+                .build(),
+            TelemetrySink { name: name.into() },
+        )
+    }
+
+    #[message_handler]
+    async fn in_port(
+        &mut self,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        telemetry::gauge(self.name.clone(), p);
+        Ok(Pmt::Null)
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for TelemetrySink {
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        telemetry::remove_gauge(&self.name);
+        Ok(())
+    }
+}
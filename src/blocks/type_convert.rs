@@ -0,0 +1,243 @@
+use crate::anyhow::Result;
+use crate::kernels;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Integer sample type of raw sample formats.
+pub trait IntSample: Copy + Send + 'static {
+    /// Value corresponding to `0.0`.
+    const OFFSET: f32;
+    /// Distance from the offset corresponding to `1.0`.
+    const FULL_SCALE: f32;
+
+    fn to_f32(self) -> f32;
+    /// Round to the nearest value, saturating at the limits of the type.
+    fn from_f32(x: f32) -> Self;
+
+    /// Convert a slice, `out[k] = (x[k] - offset) * scale`.
+    fn slice_to_f32(x: &[Self], scale: f32, offset: f32, out: &mut [f32]) {
+        for (x, y) in x.iter().zip(out.iter_mut()) {
+            *y = (x.to_f32() - offset) * scale;
+        }
+    }
+    /// Convert a slice, `out[k] = from_f32(x[k] * scale + offset)`.
+    fn slice_from_f32(x: &[f32], scale: f32, offset: f32, out: &mut [Self]) {
+        for (x, y) in x.iter().zip(out.iter_mut()) {
+            *y = Self::from_f32(x * scale + offset);
+        }
+    }
+}
+
+impl IntSample for u8 {
+    const OFFSET: f32 = 127.5;
+    const FULL_SCALE: f32 = 127.5;
+
+    fn to_f32(self) -> f32 {
+        self as f32
+    }
+    fn from_f32(x: f32) -> Self {
+        x.round() as u8
+    }
+    fn slice_to_f32(x: &[Self], scale: f32, offset: f32, out: &mut [f32]) {
+        kernels::u8_to_f32(x, scale, offset, out);
+    }
+}
+
+impl IntSample for i8 {
+    const OFFSET: f32 = 0.0;
+    const FULL_SCALE: f32 = 128.0;
+
+    fn to_f32(self) -> f32 {
+        self as f32
+    }
+    fn from_f32(x: f32) -> Self {
+        x.round() as i8
+    }
+}
+
+impl IntSample for i16 {
+    const OFFSET: f32 = 0.0;
+    const FULL_SCALE: f32 = 32768.0;
+
+    fn to_f32(self) -> f32 {
+        self as f32
+    }
+    fn from_f32(x: f32) -> Self {
+        x.round() as i16
+    }
+    fn slice_to_f32(x: &[Self], scale: f32, offset: f32, out: &mut [f32]) {
+        kernels::i16_to_f32(x, scale, offset, out);
+    }
+    fn slice_from_f32(x: &[f32], scale: f32, offset: f32, out: &mut [Self]) {
+        kernels::f32_to_i16(x, scale, offset, out);
+    }
+}
+
+/// Convert integer samples to `f32`.
+///
+/// Computes `(x - offset) * scale`. With [IntToFloat::normalized], the full range of the type
+/// is mapped to `[-1, 1]`, e.g., `(x - 127.5) / 127.5` for `u8` samples of RTL-SDRs and `x / 128`
+/// for `i8` samples of HackRFs.
+///
+/// # Inputs
+///
+/// `in`: Input (u8, i8, or i16)
+///
+/// # Outputs
+///
+/// `out`: Output (f32)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::Deinterleave;
+/// use futuresdr::blocks::IntToFloat;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // raw rtl_tcp samples to complex samples
+/// let conv = fg.add_block(IntToFloat::<u8>::normalized());
+/// let iq = fg.add_block(Deinterleave::new());
+/// fg.connect_stream(conv, "out", iq, "in").unwrap();
+/// ```
+pub struct IntToFloat<T: IntSample> {
+    scale: f32,
+    offset: f32,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: IntSample> IntToFloat<T> {
+    pub fn new(scale: f32, offset: f32) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("IntToFloat").build(),
+            StreamIoBuilder::new()
+                .add_input::<T>("in")
+                .add_output::<f32>("out")
+                .build(),
+            MessageIoBuilder::<Self>::new().build(),
+            IntToFloat::<T> {
+                scale,
+                offset,
+                _type: std::marker::PhantomData,
+            },
+        )
+    }
+
+    /// Map the full range of the type to `[-1, 1]`.
+    pub fn normalized() -> Block {
+        Self::new(1.0 / T::FULL_SCALE, T::OFFSET)
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: IntSample> Kernel for IntToFloat<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<T>();
+        let o = sio.output(0).slice::<f32>();
+
+        let m = std::cmp::min(i.len(), o.len());
+        T::slice_to_f32(&i[..m], self.scale, self.offset, &mut o[..m]);
+
+        sio.input(0).consume(m);
+        sio.output(0).produce(m);
+
+        if sio.input(0).finished() && m == i.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Convert `f32` samples to integers.
+///
+/// Computes `x * scale + offset`, rounded to the nearest integer and saturated at the limits of
+/// the type. [FloatToInt::normalized] is the inverse of [IntToFloat::normalized].
+///
+/// # Inputs
+///
+/// `in`: Input (f32)
+///
+/// # Outputs
+///
+/// `out`: Output (u8, i8, or i16)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::FloatToInt;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // compact capture file format
+/// let conv = fg.add_block(FloatToInt::<i16>::normalized());
+/// ```
+pub struct FloatToInt<T: IntSample> {
+    scale: f32,
+    offset: f32,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: IntSample> FloatToInt<T> {
+    pub fn new(scale: f32, offset: f32) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("FloatToInt").build(),
+            StreamIoBuilder::new()
+                .add_input::<f32>("in")
+                .add_output::<T>("out")
+                .build(),
+            MessageIoBuilder::<Self>::new().build(),
+            FloatToInt::<T> {
+                scale,
+                offset,
+                _type: std::marker::PhantomData,
+            },
+        )
+    }
+
+    /// Map `[-1, 1]` to the full range of the type.
+    pub fn normalized() -> Block {
+        Self::new(T::FULL_SCALE, T::OFFSET)
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: IntSample> Kernel for FloatToInt<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<f32>();
+        let o = sio.output(0).slice::<T>();
+
+        let m = std::cmp::min(i.len(), o.len());
+        T::slice_from_f32(&i[..m], self.scale, self.offset, &mut o[..m]);
+
+        sio.input(0).consume(m);
+        sio.output(0).produce(m);
+
+        if sio.input(0).finished() && m == i.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
@@ -1,12 +1,19 @@
 use std::marker::PhantomData;
 
 use crate::anyhow::Result;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::blocks::VectorSource;
 use crate::runtime::Block;
 use crate::runtime::BlockMeta;
 use crate::runtime::BlockMetaBuilder;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::runtime::Flowgraph;
+use crate::runtime::ItemTag;
 use crate::runtime::Kernel;
 use crate::runtime::MessageIo;
 use crate::runtime::MessageIoBuilder;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::runtime::Runtime;
 use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
 use crate::runtime::WorkIo;
@@ -87,3 +94,121 @@ impl<T: Clone + std::fmt::Debug + Send + Sync + 'static> Default for VectorSinkB
         Self::new()
     }
 }
+
+/// Store received samples and their tags in vectors.
+///
+/// Tags are stored with the absolute index of the item they are attached to.
+///
+/// # Inputs
+///
+/// `in`: Samples
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::VectorSinkWithTags;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let snk = fg.add_block(VectorSinkWithTags::<f32>::new(1024));
+/// ```
+pub struct VectorSinkWithTags<T> {
+    items: Vec<T>,
+    tags: Vec<ItemTag>,
+}
+
+impl<T: Clone + std::fmt::Debug + Send + Sync + 'static> VectorSinkWithTags<T> {
+    pub fn new(capacity: usize) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("VectorSinkWithTags").build(),
+            StreamIoBuilder::new().add_input::<T>("in").build(),
+            MessageIoBuilder::<Self>::new().build(),
+            VectorSinkWithTags {
+                items: Vec::<T>::with_capacity(capacity),
+                tags: Vec::new(),
+            },
+        )
+    }
+
+    pub fn items(&self) -> &Vec<T> {
+        &self.items
+    }
+
+    /// Received tags, with the absolute index of their item.
+    pub fn tags(&self) -> &Vec<ItemTag> {
+        &self.tags
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: Clone + std::fmt::Debug + Send + Sync + 'static> Kernel for VectorSinkWithTags<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<T>();
+
+        let offset = self.items.len();
+        for t in sio.input(0).tags().iter().filter(|t| t.index < i.len()) {
+            self.tags.push(ItemTag {
+                index: offset + t.index,
+                tag: t.tag.clone(),
+            });
+        }
+        self.items.extend_from_slice(i);
+
+        sio.input(0).consume(i.len());
+
+        if sio.input(0).finished() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Run a block on the given samples and return its output.
+///
+/// Builds a flowgraph, streaming `items` from a [VectorSource](crate::blocks::VectorSource)
+/// into the `in` port of the block and collecting the samples of its `out` port in a
+/// [VectorSink]. Runs the flowgraph to completion, i.e., the block has to terminate when its
+/// input is finished.
+///
+/// # Usage
+/// ```
+/// use futuresdr::anyhow::Result;
+/// use futuresdr::blocks::run_and_collect;
+/// use futuresdr::blocks::Apply;
+///
+/// # fn main() -> Result<()> {
+/// let out: Vec<f32> = run_and_collect(vec![1.0f32, 2.0, 3.0], Apply::new(|x: &f32| x * 2.0))?;
+/// assert_eq!(out, vec![2.0, 4.0, 6.0]);
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(not(target_arch = "wasm32"))]
+pub fn run_and_collect<I, O>(items: Vec<I>, block: Block) -> Result<Vec<O>>
+where
+    I: Send + 'static,
+    O: Clone + std::fmt::Debug + Send + Sync + 'static,
+{
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(VectorSource::<I>::new(items));
+    let block = fg.add_block(block);
+    let snk = fg.add_block(VectorSinkBuilder::<O>::new().build());
+
+    fg.connect_stream(src, "out", block, "in")?;
+    fg.connect_stream(block, "out", snk, "in")?;
+
+    let fg = Runtime::new().run(fg)?;
+
+    Ok(fg
+        .kernel::<VectorSink<O>>(snk)
+        .map(|s| s.items().clone())
+        .unwrap_or_default())
+}
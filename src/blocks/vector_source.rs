@@ -5,27 +5,26 @@ use crate::anyhow::Result;
 use crate::runtime::Block;
 use crate::runtime::BlockMeta;
 use crate::runtime::BlockMetaBuilder;
+use crate::runtime::ItemTag;
 use crate::runtime::Kernel;
 use crate::runtime::MessageIo;
 use crate::runtime::MessageIoBuilder;
 use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
+use crate::runtime::Tag;
 use crate::runtime::WorkIo;
 
 /// Stream samples from vector.
 pub struct VectorSource<T> {
     items: Vec<T>,
+    tags: Vec<ItemTag>,
     n_copied: usize,
+    repeat: Option<usize>,
 }
 
 impl<T: Send + 'static> VectorSource<T> {
     pub fn new(items: Vec<T>) -> Block {
-        Block::new(
-            BlockMetaBuilder::new("VectorSource").build(),
-            StreamIoBuilder::new().add_output::<T>("out").build(),
-            MessageIoBuilder::new().build(),
-            VectorSource { items, n_copied: 0 },
-        )
+        VectorSourceBuilder::new(items).build()
     }
 }
 
@@ -39,26 +38,138 @@ impl<T: Send + 'static> Kernel for VectorSource<T> {
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
+        if self.items.is_empty() || self.repeat == Some(0) {
+            io.finished = true;
+            return Ok(());
+        }
+
         let out = sio.output(0).slice::<T>();
+        let mut produced = 0;
 
-        let n = cmp::min(out.len(), self.items.len() - self.n_copied);
+        while produced < out.len() && self.repeat != Some(0) {
+            let n = cmp::min(out.len() - produced, self.items.len() - self.n_copied);
 
-        if n > 0 {
             unsafe {
                 let src_ptr = self.items.as_ptr().add(self.n_copied);
-                let dst_ptr = out.as_mut_ptr();
+                let dst_ptr = out.as_mut_ptr().add(produced);
                 ptr::copy_nonoverlapping(src_ptr, dst_ptr, n)
             };
 
+            for t in self.tags.iter() {
+                if t.index >= self.n_copied && t.index < self.n_copied + n {
+                    sio.output(0)
+                        .add_tag(produced + t.index - self.n_copied, t.tag.clone());
+                }
+            }
+
             self.n_copied += n;
+            produced += n;
 
             if self.n_copied == self.items.len() {
-                io.finished = true;
+                self.n_copied = 0;
+                if let Some(r) = self.repeat.as_mut() {
+                    *r -= 1;
+                }
             }
+        }
+
+        if self.repeat == Some(0) {
+            io.finished = true;
+        }
 
-            sio.output(0).produce(n);
+        if produced > 0 {
+            sio.output(0).produce(produced);
         }
 
         Ok(())
     }
 }
+
+/// Build a [VectorSource].
+///
+/// Outputs the items once, unless configured to repeat them. Tags are attached to items, using
+/// their index in the vector, and are output with every repetition.
+///
+/// # Outputs
+///
+/// `out`: Items of the vector
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::VectorSourceBuilder;
+/// use futuresdr::runtime::Flowgraph;
+/// use futuresdr::runtime::Tag;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let src = fg.add_block(
+///     VectorSourceBuilder::new(vec![1u32, 2, 3, 4])
+///         .repeat(10)
+///         .tag(0, Tag::String("start".to_string()))
+///         .build(),
+/// );
+/// ```
+pub struct VectorSourceBuilder<T> {
+    items: Vec<T>,
+    tags: Vec<ItemTag>,
+    repeat: Option<usize>,
+}
+
+impl<T: Send + 'static> VectorSourceBuilder<T> {
+    pub fn new(items: Vec<T>) -> VectorSourceBuilder<T> {
+        VectorSourceBuilder {
+            items,
+            tags: Vec::new(),
+            repeat: Some(1),
+        }
+    }
+
+    /// Output the items the given number of times.
+    #[must_use]
+    pub fn repeat(mut self, times: usize) -> VectorSourceBuilder<T> {
+        self.repeat = Some(times);
+        self
+    }
+
+    /// Output the items over and over again, without terminating.
+    #[must_use]
+    pub fn repeat_forever(mut self) -> VectorSourceBuilder<T> {
+        self.repeat = None;
+        self
+    }
+
+    /// Attach a tag to the item at the given index.
+    #[must_use]
+    pub fn tag(mut self, index: usize, tag: Tag) -> VectorSourceBuilder<T> {
+        assert!(index < self.items.len(), "tag index out of range");
+        self.tags.push(ItemTag { index, tag });
+        self
+    }
+
+    /// Attach tags to items, using their index in the vector.
+    #[must_use]
+    pub fn tags(mut self, tags: Vec<ItemTag>) -> VectorSourceBuilder<T> {
+        assert!(
+            tags.iter().all(|t| t.index < self.items.len()),
+            "tag index out of range"
+        );
+        self.tags.extend(tags);
+        self
+    }
+
+    pub fn build(mut self) -> Block {
+        self.tags.sort_by_key(|t| t.index);
+
+        Block::new(
+            BlockMetaBuilder::new("VectorSource").build(),
+            StreamIoBuilder::new().add_output::<T>("out").build(),
+            MessageIoBuilder::new().build(),
+            VectorSource {
+                items: self.items,
+                tags: self.tags,
+                n_copied: 0,
+                repeat: self.repeat,
+            },
+        )
+    }
+}
@@ -0,0 +1,760 @@
+use async_io::Async;
+use async_net::UdpSocket;
+use futures::FutureExt;
+use std::collections::HashMap;
+use std::net::ToSocketAddrs;
+use std::sync::Arc;
+use std::time::Instant;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use crate::anyhow::{anyhow, bail, Context, Result};
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::Tag;
+use crate::runtime::WorkIo;
+
+/// Organizationally unique identifier of DIFI in the class ID.
+const DIFI_OUI: u32 = 0x6a_621e;
+/// Signal data packet with stream ID.
+const TYPE_DATA: u32 = 1;
+const TYPE_CONTEXT: u32 = 4;
+/// Integer timestamps in UTC.
+const TSI_UTC: u32 = 1;
+/// Fractional timestamps in picoseconds.
+const TSF_REAL_TIME: u32 = 2;
+
+const CIF0_CHANGE: u32 = 1 << 31;
+const CIF0_BANDWIDTH: u32 = 1 << 29;
+const CIF0_RF_FREQ: u32 = 1 << 27;
+const CIF0_SAMPLE_RATE: u32 = 1 << 21;
+const CIF0_PAYLOAD_FORMAT: u32 = 1 << 15;
+/// Size in words of the CIF0 fields from bit 30 down to bit 15, which are all fixed.
+const CIF0_SIZES: [(u32, usize); 16] = [
+    (30, 1),
+    (29, 2),
+    (28, 2),
+    (27, 2),
+    (26, 2),
+    (25, 2),
+    (24, 1),
+    (23, 1),
+    (22, 1),
+    (21, 2),
+    (20, 2),
+    (19, 1),
+    (18, 1),
+    (17, 2),
+    (16, 1),
+    (15, 2),
+];
+
+/// Context fields that are encoded and decoded.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Vita49Context {
+    frequency: Option<f64>,
+    sample_rate: Option<f64>,
+    bandwidth: Option<f64>,
+}
+
+impl Vita49Context {
+    /// Update the fields from a tag or message map.
+    fn update(&mut self, m: &HashMap<String, Pmt>) -> bool {
+        let old = *self;
+        let value = |key: &str| match m.get(key) {
+            Some(Pmt::F64(v)) => Some(*v),
+            Some(Pmt::F32(v)) => Some(*v as f64),
+            Some(Pmt::U32(v)) => Some(*v as f64),
+            Some(Pmt::U64(v)) => Some(*v as f64),
+            _ => None,
+        };
+        if let Some(v) = value("freq") {
+            self.frequency = Some(v);
+        }
+        if let Some(v) = value("sample_rate") {
+            self.sample_rate = Some(v);
+        }
+        if let Some(v) = value("bandwidth") {
+            self.bandwidth = Some(v);
+        }
+        old != *self
+    }
+
+    fn to_pmt(self) -> Pmt {
+        let mut m = HashMap::new();
+        for (key, v) in [
+            ("freq", self.frequency),
+            ("sample_rate", self.sample_rate),
+            ("bandwidth", self.bandwidth),
+        ] {
+            if let Some(v) = v {
+                m.insert(key.to_string(), Pmt::F64(v));
+            }
+        }
+        Pmt::MapStrPmt(m)
+    }
+}
+
+/// 64-bit fixed point with 20 fractional bits, as used for frequencies.
+fn fixed(v: f64, out: &mut Vec<u32>) {
+    let v = (v * (1u64 << 20) as f64).round() as i64 as u64;
+    out.push((v >> 32) as u32);
+    out.push(v as u32);
+}
+
+fn parse_fixed(w: &[u32]) -> f64 {
+    (((w[0] as u64) << 32) | w[1] as u64) as i64 as f64 / (1u64 << 20) as f64
+}
+
+fn time_tag(seconds: u64, picoseconds: Option<u64>) -> Tag {
+    let mut m = HashMap::from([("seconds".to_string(), Pmt::U64(seconds))]);
+    if let Some(ps) = picoseconds {
+        m.insert("picoseconds".to_string(), Pmt::U64(ps));
+    }
+    Tag::Data(Pmt::MapStrPmt(m))
+}
+
+/// Encapsulate samples in [VITA 49.2](https://www.vita.com)/[DIFI](https://dificonsortium.org)
+/// packets and send them over UDP.
+pub struct Vita49Sink {
+    remote: String,
+    socket: Option<UdpSocket>,
+    stream_id: u32,
+    samples_per_packet: usize,
+    context: Vita49Context,
+    context_changed: bool,
+    last_context: Option<Instant>,
+    data_count: u32,
+    context_count: u32,
+    /// Time of a sample as (seconds, picoseconds, sample index).
+    time_ref: (u64, u64, u64),
+    n: u64,
+    buf: Vec<u8>,
+}
+
+impl Vita49Sink {
+    fn create(builder: Vita49SinkBuilder) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("Vita49Sink").build(),
+            StreamIoBuilder::new().add_input::<Complex32>("in").build(),
+            MessageIoBuilder::new()
+                .add_input(
+                    "context",
+                    |block: &mut Vita49Sink,
+                     _mio: &mut MessageIo<Vita49Sink>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            match &p {
+                                Pmt::MapStrPmt(m) => {
+                                    block.context_changed |= block.context.update(m);
+                                }
+                                Pmt::F64(_) | Pmt::F32(_) | Pmt::U32(_) | Pmt::U64(_) => {
+                                    let m = HashMap::from([("freq".to_string(), p.clone())]);
+                                    block.context_changed |= block.context.update(&m);
+                                }
+                                _ => warn!("Vita49Sink/context: received wrong PMT type. {:?}", p),
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .build(),
+            Vita49Sink {
+                remote: builder.remote,
+                socket: None,
+                stream_id: builder.stream_id,
+                samples_per_packet: builder.samples_per_packet,
+                context: builder.context,
+                context_changed: true,
+                last_context: None,
+                data_count: 0,
+                context_count: 0,
+                time_ref: (0, 0, 0),
+                n: 0,
+                buf: Vec::new(),
+            },
+        )
+    }
+
+    /// Timestamp of the sample with the given index.
+    fn timestamp(&self, index: u64) -> (u64, u64) {
+        let (s, ps, n) = self.time_ref;
+        let t = (s as i128) * 1_000_000_000_000 + ps as i128;
+        let t = match self.context.sample_rate {
+            Some(r) => t + ((index as f64 - n as f64) / r * 1e12) as i128,
+            None => {
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default();
+                now.as_nanos() as i128 * 1000
+            }
+        };
+        let t = t.max(0);
+        (
+            (t / 1_000_000_000_000) as u64,
+            (t % 1_000_000_000_000) as u64,
+        )
+    }
+
+    /// Header, stream ID, class ID, and timestamp of a packet.
+    fn prefix(&self, packet_type: u32, count: u32, index: u64, out: &mut Vec<u32>) {
+        let (s, ps) = self.timestamp(index);
+        out.push(
+            packet_type << 28 | 1 << 27 | TSI_UTC << 22 | TSF_REAL_TIME << 20 | (count & 0xf) << 16,
+        );
+        out.push(self.stream_id);
+        out.push(DIFI_OUI);
+        out.push(u32::from(packet_type == TYPE_CONTEXT));
+        out.push(s as u32);
+        out.push((ps >> 32) as u32);
+        out.push(ps as u32);
+    }
+
+    async fn send(&mut self, mut words: Vec<u32>) -> Result<()> {
+        words[0] |= words.len() as u32;
+        self.buf.clear();
+        for w in words {
+            self.buf.extend_from_slice(&w.to_be_bytes());
+        }
+        let socket = self.socket.as_ref().unwrap();
+        socket.send(&self.buf).await?;
+        Ok(())
+    }
+
+    async fn send_context(&mut self, index: u64) -> Result<()> {
+        let mut w = Vec::new();
+        self.prefix(TYPE_CONTEXT, self.context_count, index, &mut w);
+        self.context_count += 1;
+
+        let c = self.context;
+        let mut cif0 = CIF0_PAYLOAD_FORMAT;
+        if self.context_changed {
+            cif0 |= CIF0_CHANGE;
+        }
+        let mut fields = Vec::new();
+        if let Some(bw) = c.bandwidth {
+            cif0 |= CIF0_BANDWIDTH;
+            fixed(bw, &mut fields);
+        }
+        if let Some(f) = c.frequency {
+            cif0 |= CIF0_RF_FREQ;
+            fixed(f, &mut fields);
+        }
+        if let Some(r) = c.sample_rate {
+            cif0 |= CIF0_SAMPLE_RATE;
+            fixed(r, &mut fields);
+        }
+        // complex cartesian, signed fixed point, 16 bit items in 16 bit fields
+        fields.push(1 << 29 | 15 << 6 | 15);
+        fields.push(0);
+
+        w.push(cif0);
+        w.extend(fields);
+        self.send(w).await?;
+
+        self.context_changed = false;
+        self.last_context = Some(Instant::now());
+        Ok(())
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for Vita49Sink {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let available = sio.input(0).slice::<Complex32>().len();
+        let finished = sio.input(0).finished();
+        let n = if available >= self.samples_per_packet {
+            self.samples_per_packet
+        } else if finished {
+            available
+        } else {
+            0
+        };
+
+        if n > 0 {
+            // tags in the packet apply to the whole packet
+            for t in sio.input(0).tags().iter().filter(|t| t.index < n) {
+                if let Tag::Data(Pmt::MapStrPmt(m)) = &t.tag {
+                    if let Some(Pmt::U64(s)) = m.get("seconds") {
+                        let ps = match m.get("picoseconds") {
+                            Some(Pmt::U64(ps)) => *ps,
+                            _ => 0,
+                        };
+                        self.time_ref = (*s, ps, self.n + t.index as u64);
+                    }
+                    self.context_changed |= self.context.update(m);
+                }
+            }
+
+            let due = self
+                .last_context
+                .map_or(true, |t| t.elapsed().as_secs_f64() >= 1.0);
+            if self.context_changed || due {
+                self.send_context(self.n).await?;
+            }
+
+            let mut w = Vec::with_capacity(7 + n);
+            self.prefix(TYPE_DATA, self.data_count, self.n, &mut w);
+            self.data_count += 1;
+            let q = |v: f32| (v * 32768.0).round().clamp(-32768.0, 32767.0) as i16 as u16 as u32;
+            w.extend(
+                sio.input(0).slice::<Complex32>()[..n]
+                    .iter()
+                    .map(|x| q(x.re) << 16 | q(x.im)),
+            );
+            self.send(w).await?;
+
+            self.n += n as u64;
+            sio.input(0).consume(n);
+            io.call_again = true;
+        }
+
+        if finished && available == n {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let remote = self
+            .remote
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| anyhow!("Vita49Sink: cannot resolve {}", self.remote))?;
+        let local = if remote.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+        let socket = UdpSocket::bind(local).await?;
+        socket.connect(remote).await?;
+        self.socket = Some(socket);
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        self.time_ref = (now.as_secs(), now.subsec_nanos() as u64 * 1000, 0);
+        self.n = 0;
+        self.context_changed = true;
+        self.last_context = None;
+        Ok(())
+    }
+}
+
+/// Encapsulate samples in [VITA 49.2](https://www.vita.com)/[DIFI](https://dificonsortium.org)
+/// packets and send them over UDP.
+///
+/// Samples are sent in signal data packets with stream ID, DIFI class ID, UTC timestamps in
+/// picoseconds, and 16-bit complex samples, big endian. Context packets with the RF frequency,
+/// sample rate, bandwidth (as far as known), and payload format precede the first data packet
+/// and are repeated every second and after changes.
+///
+/// Timestamps start at the system time and advance with the sample rate. Tags of the form
+/// `Tag::Data(Pmt::MapStrPmt)` with `seconds` and `picoseconds` (as produced by the
+/// [Vita49Source](Vita49SourceBuilder)) set the time of the tagged sample, while `freq`,
+/// `sample_rate`, and `bandwidth` entries update the context.
+///
+/// # Inputs
+///
+/// `in`: Samples
+///
+/// **Message**: `context`: [Pmt::MapStrPmt] with `freq`, `sample_rate`, and/or `bandwidth`,
+/// or a number as frequency in Hz
+///
+/// # Outputs
+///
+/// No outputs.
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::Vita49SinkBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let sink = fg.add_block(
+///     Vita49SinkBuilder::new("192.168.1.10:50000")
+///         .stream_id(1)
+///         .frequency(2.4e9)
+///         .sample_rate(20e6)
+///         .build(),
+/// );
+/// ```
+pub struct Vita49SinkBuilder {
+    remote: String,
+    stream_id: u32,
+    samples_per_packet: usize,
+    context: Vita49Context,
+}
+
+impl Vita49SinkBuilder {
+    pub fn new<S: Into<String>>(remote: S) -> Vita49SinkBuilder {
+        Vita49SinkBuilder {
+            remote: remote.into(),
+            stream_id: 0,
+            samples_per_packet: 360,
+            context: Vita49Context::default(),
+        }
+    }
+
+    #[must_use]
+    pub fn stream_id(mut self, stream_id: u32) -> Vita49SinkBuilder {
+        self.stream_id = stream_id;
+        self
+    }
+
+    /// Samples per data packet. The default of 360 fits packets into an Ethernet MTU of 1500.
+    #[must_use]
+    pub fn samples_per_packet(mut self, samples: usize) -> Vita49SinkBuilder {
+        assert!(samples > 0 && samples < 65528, "invalid packet size");
+        self.samples_per_packet = samples;
+        self
+    }
+
+    /// RF reference frequency in Hz.
+    #[must_use]
+    pub fn frequency(mut self, frequency: f64) -> Vita49SinkBuilder {
+        self.context.frequency = Some(frequency);
+        self
+    }
+
+    /// Sample rate, required to derive the timestamps from the sample count.
+    #[must_use]
+    pub fn sample_rate(mut self, sample_rate: f64) -> Vita49SinkBuilder {
+        self.context.sample_rate = Some(sample_rate);
+        self
+    }
+
+    #[must_use]
+    pub fn bandwidth(mut self, bandwidth: f64) -> Vita49SinkBuilder {
+        self.context.bandwidth = Some(bandwidth);
+        self
+    }
+
+    pub fn build(self) -> Block {
+        Vita49Sink::create(self)
+    }
+}
+
+/// Receive [VITA 49.2](https://www.vita.com)/[DIFI](https://dificonsortium.org) packets over
+/// UDP.
+pub struct Vita49Source {
+    bind: String,
+    socket: Option<Arc<Async<std::net::UdpSocket>>>,
+    stream_id: Option<u32>,
+    sample_bits: u32,
+    context: Vita49Context,
+    context_tag: bool,
+    count: Option<u32>,
+    buf: Vec<u8>,
+    /// Samples of the last packet, not yet produced.
+    pending: Vec<Complex32>,
+    pos: usize,
+    pending_tags: Vec<Tag>,
+}
+
+impl Vita49Source {
+    fn create(builder: Vita49SourceBuilder) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("Vita49Source").build(),
+            StreamIoBuilder::new()
+                .add_output::<Complex32>("out")
+                .build(),
+            MessageIoBuilder::new().add_output("context").build(),
+            Vita49Source {
+                bind: builder.bind,
+                socket: None,
+                stream_id: builder.stream_id,
+                sample_bits: builder.sample_bits,
+                context: Vita49Context::default(),
+                context_tag: false,
+                count: None,
+                buf: vec![0; 65536],
+                pending: Vec::new(),
+                pos: 0,
+                pending_tags: Vec::new(),
+            },
+        )
+    }
+
+    /// Decode a packet, queueing its samples and tags.
+    async fn parse(&mut self, len: usize, mio: &mut MessageIo<Self>) -> Result<()> {
+        let words: Vec<u32> = self.buf[..len]
+            .chunks_exact(4)
+            .map(|w| u32::from_be_bytes([w[0], w[1], w[2], w[3]]))
+            .collect();
+        let h = match words.first() {
+            Some(h) => *h,
+            None => return Ok(()),
+        };
+        let size = (h & 0xffff) as usize;
+        if size == 0 || size > words.len() {
+            bail!("invalid packet size {} of {} words", size, words.len());
+        }
+        let words = &words[..size];
+        let packet_type = h >> 28;
+
+        let mut i = 1;
+        let sid = if matches!(packet_type, 1 | 3 | 4 | 5) {
+            i += 1;
+            words.get(1).copied()
+        } else {
+            None
+        };
+        if self.stream_id.is_some() && sid != self.stream_id {
+            return Ok(());
+        }
+        if h & 1 << 27 != 0 {
+            i += 2;
+        }
+        let seconds = if (h >> 22) & 3 != 0 {
+            i += 1;
+            words.get(i - 1).map(|s| *s as u64)
+        } else {
+            None
+        };
+        let fractional = if (h >> 20) & 3 != 0 {
+            i += 2;
+            words
+                .get(i - 2..i)
+                .map(|w| ((w[0] as u64) << 32) | w[1] as u64)
+        } else {
+            None
+        };
+        let picoseconds = fractional.filter(|_| (h >> 20) & 3 == TSF_REAL_TIME);
+        if i > size {
+            bail!("truncated packet");
+        }
+
+        match packet_type {
+            0 | 1 => {
+                let end = if h & 1 << 26 != 0 { size - 1 } else { size };
+                let payload = &words[i..std::cmp::max(i, end)];
+
+                let count = (h >> 16) & 0xf;
+                let lost = match self.count {
+                    Some(c) => (c + 1) & 0xf != count,
+                    None => true,
+                };
+                if lost && self.count.is_some() {
+                    warn!("Vita49Source: lost packets");
+                }
+                self.count = Some(count);
+
+                self.pending.clear();
+                self.pos = 0;
+                if lost {
+                    if let Some(s) = seconds {
+                        self.pending_tags.push(time_tag(s, picoseconds));
+                    }
+                }
+                if self.context_tag {
+                    self.context_tag = false;
+                    self.pending_tags.push(Tag::Data(self.context.to_pmt()));
+                }
+                match self.sample_bits {
+                    16 => self.pending.extend(payload.iter().map(|w| {
+                        Complex32::new(
+                            (*w >> 16) as i16 as f32 / 32768.0,
+                            *w as i16 as f32 / 32768.0,
+                        )
+                    })),
+                    8 => self.pending.extend(payload.iter().flat_map(|w| {
+                        let b = w.to_be_bytes();
+                        [
+                            Complex32::new(b[0] as i8 as f32 / 128.0, b[1] as i8 as f32 / 128.0),
+                            Complex32::new(b[2] as i8 as f32 / 128.0, b[3] as i8 as f32 / 128.0),
+                        ]
+                    })),
+                    b => bail!("unsupported sample size of {} bits", b),
+                }
+            }
+            4 => {
+                let w = &words[i..];
+                let cif0 = *w.first().context("missing CIF0")?;
+                // CIF1 to CIF7 follow CIF0 and precede the fields
+                let mut j = 1 + (cif0 & 0xfe).count_ones() as usize;
+                let mut context = self.context;
+                for (bit, len) in CIF0_SIZES {
+                    if cif0 & 1 << bit == 0 {
+                        continue;
+                    }
+                    let f = w.get(j..j + len).context("truncated context packet")?;
+                    match 1 << bit {
+                        CIF0_BANDWIDTH => context.bandwidth = Some(parse_fixed(f)),
+                        CIF0_RF_FREQ => context.frequency = Some(parse_fixed(f)),
+                        CIF0_SAMPLE_RATE => context.sample_rate = Some(parse_fixed(f)),
+                        CIF0_PAYLOAD_FORMAT => self.sample_bits = (f[0] & 0x3f) + 1,
+                        _ => {}
+                    }
+                    j += len;
+                }
+                if context != self.context {
+                    self.context = context;
+                    self.context_tag = true;
+                    mio.post(0, context.to_pmt()).await;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for Vita49Source {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let out = sio.output(0).slice::<Complex32>();
+        let mut produced = 0;
+
+        loop {
+            if self.pos == self.pending.len() {
+                let socket = self.socket.as_ref().unwrap();
+                match socket.get_ref().recv(&mut self.buf) {
+                    Ok(len) => {
+                        if let Err(e) = self.parse(len, mio).await {
+                            warn!("Vita49Source: dropping packet ({})", e);
+                        }
+                        continue;
+                    }
+                    Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                        let socket = socket.clone();
+                        io.block_on(async move {
+                            let _ = socket.readable().await;
+                        });
+                        break;
+                    }
+                    Err(e) => return Err(e.into()),
+                }
+            }
+
+            let n = std::cmp::min(out.len() - produced, self.pending.len() - self.pos);
+            if n == 0 {
+                break;
+            }
+            if self.pos == 0 {
+                for t in self.pending_tags.drain(..) {
+                    sio.output(0).add_tag(produced, t);
+                }
+            }
+            out[produced..produced + n].copy_from_slice(&self.pending[self.pos..self.pos + n]);
+            self.pos += n;
+            produced += n;
+        }
+
+        sio.output(0).produce(produced);
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let socket = std::net::UdpSocket::bind(&self.bind)
+            .with_context(|| format!("Vita49Source: cannot bind {}", self.bind))?;
+        self.socket = Some(Arc::new(Async::new(socket)?));
+        Ok(())
+    }
+}
+
+/// Receive [VITA 49.2](https://www.vita.com)/[DIFI](https://dificonsortium.org) packets over
+/// UDP.
+///
+/// Signal data packets with 8 or 16-bit complex samples are decoded to the output, optionally
+/// only those with the given stream ID. The sample size is taken from the payload format of
+/// context packets and defaults to 16 bits. Other packet types are ignored.
+///
+/// Timestamps and context are restored as tags of the form `Tag::Data(Pmt::MapStrPmt)`: the
+/// integer `seconds` and, for real-time timestamps, `picoseconds` at the first sample and after
+/// lost packets, and `freq`, `sample_rate`, and `bandwidth` at the first sample after a change
+/// of the context.
+///
+/// # Inputs
+///
+/// No inputs.
+///
+/// # Outputs
+///
+/// `out`: Samples
+///
+/// **Message**: `context`: [Pmt::MapStrPmt] with `freq`, `sample_rate`, and `bandwidth`, as far
+/// as present, after each change
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::Vita49SourceBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let src = fg.add_block(Vita49SourceBuilder::new("0.0.0.0:50000").stream_id(1).build());
+/// ```
+pub struct Vita49SourceBuilder {
+    bind: String,
+    stream_id: Option<u32>,
+    sample_bits: u32,
+}
+
+impl Vita49SourceBuilder {
+    pub fn new<S: Into<String>>(bind: S) -> Vita49SourceBuilder {
+        Vita49SourceBuilder {
+            bind: bind.into(),
+            stream_id: None,
+            sample_bits: 16,
+        }
+    }
+
+    /// Only decode packets of the stream.
+    #[must_use]
+    pub fn stream_id(mut self, stream_id: u32) -> Vita49SourceBuilder {
+        self.stream_id = Some(stream_id);
+        self
+    }
+
+    /// Sample size in bits (8 or 16), until a context packet specifies it.
+    #[must_use]
+    pub fn sample_bits(mut self, bits: u32) -> Vita49SourceBuilder {
+        assert!(
+            bits == 8 || bits == 16,
+            "only 8 and 16-bit samples are supported"
+        );
+        self.sample_bits = bits;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        Vita49Source::create(self)
+    }
+}
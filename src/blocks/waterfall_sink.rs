@@ -0,0 +1,315 @@
+use futures::FutureExt;
+use std::collections::VecDeque;
+
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIoBuilder;
+
+/// Length of the frame header in bytes.
+const HEADER_LEN: usize = 24;
+
+/// Compress bytes with run-length encoding (PackBits).
+///
+/// A header byte `h < 128` is followed by `h + 1` literal bytes, a header byte `h > 128` by one
+/// byte that is repeated `257 - h` times.
+fn pack_bits(data: &[u8], out: &mut Vec<u8>) {
+    let mut i = 0;
+    while i < data.len() {
+        let run = data[i..]
+            .iter()
+            .take(128)
+            .take_while(|b| **b == data[i])
+            .count();
+        if run > 1 {
+            out.push((257 - run) as u8);
+            out.push(data[i]);
+            i += run;
+        } else {
+            // literals up to the next run of at least three bytes
+            let mut n = 1;
+            while n < 128 && i + n < data.len() {
+                let rest = &data[i + n..];
+                if rest.len() >= 3 && rest[0] == rest[1] && rest[1] == rest[2] {
+                    break;
+                }
+                n += 1;
+            }
+            out.push((n - 1) as u8);
+            out.extend_from_slice(&data[i..i + n]);
+            i += n;
+        }
+    }
+}
+
+fn unpack_bits(mut data: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    while let Some((&h, rest)) = data.split_first() {
+        match h {
+            0..=127 => {
+                let n = h as usize + 1;
+                out.extend_from_slice(rest.get(..n)?);
+                data = &rest[n..];
+            }
+            128 => data = rest,
+            _ => {
+                out.extend(std::iter::repeat(*rest.first()?).take(257 - h as usize));
+                data = &rest[1..];
+            }
+        }
+    }
+    Some(out)
+}
+
+/// Lines of a waterfall, as served by the [WaterfallSink].
+///
+/// Frames are encoded as a little-endian header with the index of the first line (u64), the
+/// number of lines (u32), the line width (u32), and the power range in dB (two f32), followed by
+/// the lines, quantized to one byte per bin and compressed with PackBits run-length encoding.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WaterfallFrame {
+    /// Index of the first line, counting all lines received by the sink.
+    pub first: u64,
+    /// Width of the lines.
+    pub width: usize,
+    /// Power that corresponds to zero in dB.
+    pub min: f32,
+    /// Power that corresponds to 255 in dB.
+    pub max: f32,
+    /// Quantized lines, oldest first.
+    pub lines: Vec<Vec<u8>>,
+}
+
+impl WaterfallFrame {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut v = Vec::with_capacity(HEADER_LEN);
+        v.extend_from_slice(&self.first.to_le_bytes());
+        v.extend_from_slice(&(self.lines.len() as u32).to_le_bytes());
+        v.extend_from_slice(&(self.width as u32).to_le_bytes());
+        v.extend_from_slice(&self.min.to_le_bytes());
+        v.extend_from_slice(&self.max.to_le_bytes());
+        let data: Vec<u8> = self.lines.iter().flatten().copied().collect();
+        pack_bits(&data, &mut v);
+        v
+    }
+
+    /// Decode a frame, returning `None` if it is malformed.
+    pub fn decode(data: &[u8]) -> Option<WaterfallFrame> {
+        if data.len() < HEADER_LEN {
+            return None;
+        }
+        let first = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let n_lines = u32::from_le_bytes(data[8..12].try_into().unwrap()) as usize;
+        let width = u32::from_le_bytes(data[12..16].try_into().unwrap()) as usize;
+        let min = f32::from_le_bytes(data[16..20].try_into().unwrap());
+        let max = f32::from_le_bytes(data[20..24].try_into().unwrap());
+
+        let lines = unpack_bits(&data[HEADER_LEN..])?;
+        if lines.len() != n_lines * width {
+            return None;
+        }
+        let lines = if width == 0 {
+            vec![Vec::new(); n_lines]
+        } else {
+            lines.chunks(width).map(|l| l.to_vec()).collect()
+        };
+
+        Some(WaterfallFrame {
+            first,
+            width,
+            min,
+            max,
+            lines,
+        })
+    }
+}
+
+/// Ring of waterfall lines, served as compressed frames.
+pub struct WaterfallSink {
+    width: usize,
+    n_lines: usize,
+    min: f32,
+    max: f32,
+    lines: VecDeque<Vec<u8>>,
+    /// Index of the next line.
+    next: u64,
+}
+
+impl WaterfallSink {
+    pub fn new(width: usize, n_lines: usize, min: f32, max: f32) -> Block {
+        assert!(width > 0, "width must be positive");
+        assert!(n_lines > 0, "number of lines must be positive");
+        assert!(min < max, "minimum has to be smaller than maximum");
+
+        Block::new(
+            BlockMetaBuilder::new("WaterfallSink").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_input(
+                    "in",
+                    |block: &mut WaterfallSink,
+                     _mio: &mut MessageIo<WaterfallSink>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            match p {
+                                Pmt::VecF32(ref v) if v.len() == block.width => block.push(v),
+                                _ => warn!("WaterfallSink: received wrong PMT type. {:?}", p),
+                            }
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .add_input(
+                    "request",
+                    |block: &mut WaterfallSink,
+                     _mio: &mut MessageIo<WaterfallSink>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            match p {
+                                Pmt::Null => Ok(block.frame(0)),
+                                Pmt::U64(since) => Ok(block.frame(since)),
+                                Pmt::U32(since) => Ok(block.frame(since as u64)),
+                                _ => {
+                                    warn!("WaterfallSink: received wrong PMT type. {:?}", p);
+                                    Ok(Pmt::Null)
+                                }
+                            }
+                        }
+                        .boxed()
+                    },
+                )
+                .build(),
+            WaterfallSink {
+                width,
+                n_lines,
+                min,
+                max,
+                lines: VecDeque::with_capacity(n_lines),
+                next: 0,
+            },
+        )
+    }
+
+    fn push(&mut self, line: &[f32]) {
+        let scale = 255.0 / (self.max - self.min);
+        let line = line
+            .iter()
+            .map(|x| ((x - self.min) * scale).round().clamp(0.0, 255.0) as u8)
+            .collect();
+        if self.lines.len() == self.n_lines {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line);
+        self.next += 1;
+    }
+
+    /// Frame with the lines starting at index `since`, as far as they are still stored.
+    fn frame(&self, since: u64) -> Pmt {
+        let oldest = self.next - self.lines.len() as u64;
+        let first = since.clamp(oldest, self.next);
+        let lines = self
+            .lines
+            .iter()
+            .skip((first - oldest) as usize)
+            .cloned()
+            .collect();
+        Pmt::Blob(
+            WaterfallFrame {
+                first,
+                width: self.width,
+                min: self.min,
+                max: self.max,
+                lines,
+            }
+            .encode(),
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for WaterfallSink {}
+
+/// Ring of waterfall lines, served as compressed frames.
+///
+/// Stores the last `lines` (default 256) power spectra, e.g., from a [Welch](crate::blocks::Welch)
+/// block. Each spectrum is quantized to one byte per bin, mapping the power range from `min` to
+/// `max` (default -120 dB to 0 dB) to 0 to 255.
+///
+/// Requests return the stored lines as [Pmt::Blob] with a [WaterfallFrame]. A request with
+/// [Pmt::U64] only returns lines with an index of at least the given index, i.e., clients can
+/// poll for new lines by requesting the index after the last line they received
+/// ([first](WaterfallFrame::first) + number of lines). Requests are meant to be sent through
+/// the control port, where the frame is returned to the caller.
+///
+/// # Inputs
+///
+/// **Message**: `in`: Power spectrum in dB ([Pmt::VecF32]) with `width` bins
+///
+/// **Message**: `request`: Return the lines since the given index ([Pmt::U64]) or all lines
+/// ([Pmt::Null])
+///
+/// # Outputs
+///
+/// No outputs.
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::WaterfallSinkBuilder;
+/// use futuresdr::blocks::WelchBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let welch = fg.add_block(WelchBuilder::new(1024).build());
+/// let waterfall = fg.add_block(
+///     WaterfallSinkBuilder::new(1024)
+///         .lines(512)
+///         .range(-100.0, -20.0)
+///         .build(),
+/// );
+/// fg.connect_message(welch, "out", waterfall, "in").unwrap();
+/// ```
+pub struct WaterfallSinkBuilder {
+    width: usize,
+    lines: usize,
+    min: f32,
+    max: f32,
+}
+
+impl WaterfallSinkBuilder {
+    pub fn new(width: usize) -> WaterfallSinkBuilder {
+        WaterfallSinkBuilder {
+            width,
+            lines: 256,
+            min: -120.0,
+            max: 0.0,
+        }
+    }
+
+    /// Number of stored lines.
+    #[must_use]
+    pub fn lines(mut self, lines: usize) -> WaterfallSinkBuilder {
+        self.lines = lines;
+        self
+    }
+
+    /// Power range in dB that is mapped to the quantized values.
+    #[must_use]
+    pub fn range(mut self, min: f32, max: f32) -> WaterfallSinkBuilder {
+        self.min = min;
+        self.max = max;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        WaterfallSink::new(self.width, self.lines, self.min, self.max)
+    }
+}
@@ -0,0 +1,161 @@
+use futuredsp::fir::PolyphaseResamplingFirKernel;
+use futuredsp::firdes;
+use futuredsp::UnaryKernel;
+
+use crate::anyhow::Result;
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Minimum sample rate for demodulation.
+const QUAD_RATE: u32 = 200_000;
+/// Maximum frequency deviation of broadcast FM.
+const DEVIATION: f32 = 75e3;
+/// Maximum number of demodulated samples, buffered for the audio resampler.
+const BUFFER_SIZE: usize = 1 << 15;
+
+/// Broadcast (wideband) FM receiver.
+///
+/// Combines the receive chain from baseband samples to audio in one block:
+/// - low-pass channel filter, decimating to a demodulation rate of at least 200 kHz,
+/// - quadrature demodulator, scaled to a deviation of 75 kHz,
+/// - de-emphasis filter (default time constant 75 us, use 50 us in Europe),
+/// - low-pass audio filter and rational resampler to the audio rate.
+///
+/// The sample rate and audio rate have to be given in Hz.
+///
+/// # Inputs
+///
+/// `in`: Baseband samples (Complex32), centered at the station
+///
+/// # Outputs
+///
+/// `out`: Mono audio (f32)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::WbfmRx;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let rx = fg.add_block(WbfmRx::new(2_400_000, 48_000));
+/// ```
+pub struct WbfmRx {
+    channel: PolyphaseResamplingFirKernel<Complex32, Complex32, Vec<f32>, f32>,
+    audio_filter: PolyphaseResamplingFirKernel<f32, f32, Vec<f32>, f32>,
+    gain: f32,
+    last: Complex32,
+    alpha: f32,
+    deemph: f32,
+    quad: Vec<Complex32>,
+    audio: Vec<f32>,
+}
+
+impl WbfmRx {
+    pub fn new(sample_rate: u32, audio_rate: u32) -> Block {
+        Self::with_tau(sample_rate, audio_rate, 75e-6)
+    }
+
+    /// Create a receiver with de-emphasis time constant `tau` in seconds.
+    pub fn with_tau(sample_rate: u32, audio_rate: u32, tau: f32) -> Block {
+        assert!(sample_rate > 0, "sample rate must be positive");
+        assert!(
+            audio_rate > 0 && audio_rate < sample_rate,
+            "audio rate must be positive and smaller than the sample rate"
+        );
+
+        // largest integer decimation that keeps the demodulation rate above QUAD_RATE
+        let decim = (1..=(sample_rate / QUAD_RATE).max(1))
+            .rev()
+            .find(|d| sample_rate % d == 0)
+            .unwrap();
+        let quad_rate = sample_rate / decim;
+        let channel_taps = firdes::kaiser::lowpass::<f32>(
+            (80e3 / sample_rate as f64).min(0.3),
+            (40e3 / sample_rate as f64).min(0.15),
+            0.001,
+        );
+
+        let gcd = num_integer::gcd(quad_rate, audio_rate);
+        let interp = (audio_rate / gcd) as usize;
+        let audio_decim = (quad_rate / gcd) as usize;
+        let cutoff = (15e3_f64).min(0.4 * audio_rate as f64);
+        let transition = (0.5 * audio_rate as f64 - cutoff).min(4e3);
+        let filter_rate = quad_rate as f64 * interp as f64;
+        let mut audio_taps: Vec<f32> =
+            firdes::kaiser::lowpass::<f32>(cutoff / filter_rate, transition / filter_rate, 0.001)
+                .into_iter()
+                .map(|t| t * interp as f32)
+                .collect();
+        audio_taps.resize((audio_taps.len() + interp - 1) / interp * interp, 0.0);
+
+        Block::new(
+            BlockMetaBuilder::new("WbfmRx").build(),
+            StreamIoBuilder::new()
+                .add_input::<Complex32>("in")
+                .add_output::<f32>("out")
+                .build(),
+            MessageIoBuilder::<WbfmRx>::new().build(),
+            WbfmRx {
+                channel: PolyphaseResamplingFirKernel::new(1, decim as usize, channel_taps),
+                audio_filter: PolyphaseResamplingFirKernel::new(interp, audio_decim, audio_taps),
+                gain: quad_rate as f32 / (2.0 * std::f32::consts::PI * DEVIATION),
+                last: Complex32::new(0.0, 0.0),
+                alpha: 1.0 - (-1.0 / (quad_rate as f32 * tau)).exp(),
+                deemph: 0.0,
+                quad: vec![Complex32::new(0.0, 0.0); BUFFER_SIZE],
+                audio: Vec::with_capacity(BUFFER_SIZE),
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for WbfmRx {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex32>();
+
+        // channel filter, demodulate, and de-emphasize into the audio buffer
+        let space = BUFFER_SIZE - self.audio.len();
+        let (consumed, demodulated, status) = self.channel.work(i, &mut self.quad[..space]);
+        for x in self.quad[..demodulated].iter() {
+            let f = self.gain * (x * self.last.conj()).arg();
+            self.last = *x;
+            self.deemph += self.alpha * (f - self.deemph);
+            self.audio.push(self.deemph);
+        }
+        sio.input(0).consume(consumed);
+
+        // resample to audio rate
+        let o = sio.output(0).slice::<f32>();
+        let (used, produced, audio_status) = self.audio_filter.work(&self.audio, o);
+        self.audio.drain(..used);
+        sio.output(0).produce(produced);
+
+        if sio.input(0).finished()
+            && status.produced_all_samples()
+            && audio_status.produced_all_samples()
+        {
+            io.finished = true;
+        } else if demodulated > 0 && produced > 0 {
+            io.call_again = true;
+        }
+
+        Ok(())
+    }
+}
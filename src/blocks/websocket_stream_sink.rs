@@ -0,0 +1,217 @@
+use crate::anyhow::{bail, Result};
+use crate::blocks::FileSample;
+use crate::runtime::stream_publish;
+use crate::runtime::stream_register;
+use crate::runtime::stream_remove;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Size of the frame header in bytes.
+const HEADER: usize = 16;
+
+/// Stream samples to browsers through a WebSocket of the control port.
+pub struct WebsocketStreamSink<T: FileSample> {
+    name: String,
+    decimation: usize,
+    frame_size: usize,
+    sample_rate: f64,
+    /// Input samples to skip, before the next one is kept.
+    skip: usize,
+    sequence: u32,
+    frame: Vec<u8>,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: FileSample> WebsocketStreamSink<T> {
+    fn create(builder: WebsocketStreamSinkBuilder<T>) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("WebsocketStreamSink").build(),
+            StreamIoBuilder::new().add_input::<T>("in").build(),
+            MessageIoBuilder::new().build(),
+            WebsocketStreamSink::<T> {
+                name: builder.name,
+                decimation: builder.decimation,
+                frame_size: builder.frame_size,
+                sample_rate: builder.sample_rate,
+                skip: 0,
+                sequence: 0,
+                frame: Vec::new(),
+                _type: std::marker::PhantomData,
+            },
+        )
+    }
+
+    fn start_frame(&mut self) {
+        self.frame.clear();
+        self.frame.extend_from_slice(&self.sequence.to_le_bytes());
+        self.frame.push(T::COMPLEX as u8);
+        self.frame.extend_from_slice(&[0; 3]);
+        self.frame
+            .extend_from_slice(&(self.sample_rate / self.decimation as f64).to_le_bytes());
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: FileSample> Kernel for WebsocketStreamSink<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<T>();
+        let item_size = if T::COMPLEX { 8 } else { 4 };
+
+        let mut n = self.skip;
+        while n < i.len() {
+            if self.frame.is_empty() {
+                self.start_frame();
+            }
+            let x = i[n].to_complex();
+            self.frame.extend_from_slice(&x.re.to_le_bytes());
+            if T::COMPLEX {
+                self.frame.extend_from_slice(&x.im.to_le_bytes());
+            }
+            if self.frame.len() == HEADER + self.frame_size * item_size {
+                // clients that cannot keep up miss frames, visible as gaps in the sequence
+                stream_publish(&self.name, std::mem::take(&mut self.frame));
+                self.sequence = self.sequence.wrapping_add(1);
+            }
+            n += self.decimation;
+        }
+        self.skip = n - i.len();
+
+        let len = i.len();
+        sio.input(0).consume(len);
+        if sio.input(0).finished() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        if !stream_register(&self.name) {
+            bail!("WebsocketStreamSink: stream {} already exists", self.name);
+        }
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        stream_remove(&self.name);
+        Ok(())
+    }
+}
+
+/// Stream samples to browsers through a WebSocket of the control port.
+///
+/// The sink registers a stream with the given name on the web server of the control port,
+/// which is available as WebSocket at `/api/stream/<name>/`, and sends frames of
+/// [frame_size](WebsocketStreamSinkBuilder::frame_size) samples to all connected clients. This
+/// is the transport for in-browser displays, like waterfalls, or audio players.
+///
+/// Before framing, the sink keeps every [decimation](WebsocketStreamSinkBuilder::decimation)-th
+/// sample, i.e., the input should be filtered accordingly. The sink never applies backpressure:
+/// without clients, samples are discarded and clients that cannot keep up miss frames.
+///
+/// Each binary message consists of a 16-byte header, followed by the samples as little endian
+/// `f32`, with real and imaginary parts interleaved for complex samples:
+///
+/// | Bytes | Content |
+/// |---|---|
+/// | 0..4 | Sequence number (`u32`), to detect missed frames |
+/// | 4 | Sample type, `0` for `f32`, `1` for `Complex32` |
+/// | 5..8 | Reserved |
+/// | 8..16 | Sample rate after decimation (`f64`), `0` if not set |
+///
+/// # Inputs
+///
+/// `in`: Samples (`f32` or `Complex32`)
+///
+/// # Outputs
+///
+/// No outputs.
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::WebsocketStreamSinkBuilder;
+/// use futuresdr::num_complex::Complex32;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // available at ws://<ctrlport_bind>/api/stream/iq/
+/// let snk = fg.add_block(
+///     WebsocketStreamSinkBuilder::<Complex32>::new("iq")
+///         .sample_rate(2e6)
+///         .decimation(10)
+///         .frame_size(2048)
+///         .build(),
+/// );
+/// ```
+#[cfg_attr(docsrs, doc(cfg(not(target_arch = "wasm32"))))]
+pub struct WebsocketStreamSinkBuilder<T: FileSample> {
+    name: String,
+    decimation: usize,
+    frame_size: usize,
+    sample_rate: f64,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: FileSample> WebsocketStreamSinkBuilder<T> {
+    pub fn new<S: Into<String>>(name: S) -> WebsocketStreamSinkBuilder<T> {
+        WebsocketStreamSinkBuilder {
+            name: name.into(),
+            decimation: 1,
+            frame_size: 2048,
+            sample_rate: 0.0,
+            _type: std::marker::PhantomData,
+        }
+    }
+
+    /// Keep only every n-th sample.
+    #[must_use]
+    pub fn decimation(mut self, n: usize) -> WebsocketStreamSinkBuilder<T> {
+        assert!(n > 0, "decimation has to be positive");
+        self.decimation = n;
+        self
+    }
+
+    /// Number of samples per frame.
+    #[must_use]
+    pub fn frame_size(mut self, n: usize) -> WebsocketStreamSinkBuilder<T> {
+        assert!(n > 0, "frame size has to be positive");
+        self.frame_size = n;
+        self
+    }
+
+    /// Sample rate of the input, announced in the frame header (divided by the decimation).
+    #[must_use]
+    pub fn sample_rate(mut self, rate: f64) -> WebsocketStreamSinkBuilder<T> {
+        self.sample_rate = rate;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        WebsocketStreamSink::<T>::create(self)
+    }
+}
@@ -0,0 +1,206 @@
+use rustfft::num_complex::Complex32;
+use rustfft::{self, FftPlanner};
+use std::sync::Arc;
+
+use crate::anyhow::Result;
+use crate::futuredsp::windows;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Estimate the log-power spectrum with Welch's method.
+pub struct Welch {
+    fft_size: usize,
+    hop: usize,
+    averages: usize,
+    window: Vec<f32>,
+    plan: Arc<dyn rustfft::Fft<f32>>,
+    buf: Vec<Complex32>,
+    scratch: Vec<Complex32>,
+    acc: Vec<f32>,
+    n_acc: usize,
+}
+
+impl Welch {
+    pub fn new(fft_size: usize, overlap: usize, averages: usize, window: Vec<f32>) -> Block {
+        assert!(fft_size > 0, "FFT size must be positive");
+        assert!(
+            overlap < fft_size,
+            "overlap has to be smaller than the FFT size"
+        );
+        assert!(averages > 0, "number of averages must be positive");
+        assert_eq!(
+            window.len(),
+            fft_size,
+            "window length has to match FFT size"
+        );
+
+        // scale, such that a full-scale tone in the center of a bin has 0 dB
+        let gain: f32 = window.iter().sum();
+        let window = window.iter().map(|w| w / gain).collect();
+
+        let mut planner = FftPlanner::<f32>::new();
+        let plan = planner.plan_fft_forward(fft_size);
+        let scratch = vec![Complex32::new(0.0, 0.0); plan.get_inplace_scratch_len()];
+
+        Block::new(
+            BlockMetaBuilder::new("Welch").build(),
+            StreamIoBuilder::new().add_input::<Complex32>("in").build(),
+            MessageIoBuilder::<Welch>::new().add_output("out").build(),
+            Welch {
+                fft_size,
+                hop: fft_size - overlap,
+                averages,
+                window,
+                plan,
+                buf: vec![Complex32::new(0.0, 0.0); fft_size],
+                scratch,
+                acc: vec![0.0; fft_size],
+                n_acc: 0,
+            },
+        )
+    }
+
+    fn frame(&mut self) -> Vec<f32> {
+        let n = self.fft_size;
+        let scale = 1.0 / self.n_acc as f32;
+        let frame = (0..n)
+            .map(|k| 10.0 * (self.acc[(k + n / 2) % n] * scale).log10())
+            .collect();
+        self.acc.iter_mut().for_each(|a| *a = 0.0);
+        self.n_acc = 0;
+        frame
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for Welch {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex32>();
+
+        let mut consumed = 0;
+        while consumed + self.fft_size <= i.len() {
+            for (b, (x, w)) in self
+                .buf
+                .iter_mut()
+                .zip(i[consumed..].iter().zip(self.window.iter()))
+            {
+                *b = x * w;
+            }
+            self.plan
+                .process_with_scratch(&mut self.buf, &mut self.scratch);
+            for (a, b) in self.acc.iter_mut().zip(self.buf.iter()) {
+                *a += b.norm_sqr();
+            }
+            self.n_acc += 1;
+            consumed += self.hop;
+
+            if self.n_acc == self.averages {
+                let frame = self.frame();
+                mio.post(0, Pmt::VecF32(frame)).await;
+            }
+        }
+
+        sio.input(0).consume(consumed);
+
+        if sio.input(0).finished() && i.len() - consumed < self.fft_size {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Estimate the log-power spectrum with Welch's method.
+///
+/// Splits the input into segments of `fft_size` samples, overlapping by `overlap` samples, and
+/// averages the power spectra of `averages` windowed segments. Each average is posted as
+/// [Pmt::VecF32] with `fft_size` power values in dB, ordered from negative to positive
+/// frequencies (i.e., DC in the center). The spectrum is scaled, such that a complex tone with
+/// amplitude one, centered in a bin, has 0 dB.
+///
+/// # Inputs
+///
+/// `in`: Input samples (Complex32)
+///
+/// # Outputs
+///
+/// **Message**: `out`: Log-power spectra
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::WelchBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // 50% overlap, average 10 segments
+/// let welch = fg.add_block(
+///     WelchBuilder::new(1024)
+///         .overlap(512)
+///         .averages(10)
+///         .build()
+/// );
+/// ```
+pub struct WelchBuilder {
+    fft_size: usize,
+    overlap: usize,
+    averages: usize,
+    window: Option<Vec<f32>>,
+}
+
+impl WelchBuilder {
+    pub fn new(fft_size: usize) -> WelchBuilder {
+        WelchBuilder {
+            fft_size,
+            overlap: fft_size / 2,
+            averages: 1,
+            window: None,
+        }
+    }
+
+    /// Number of samples shared by consecutive segments (default: half the FFT size).
+    #[must_use]
+    pub fn overlap(mut self, overlap: usize) -> WelchBuilder {
+        self.overlap = overlap;
+        self
+    }
+
+    /// Number of segments averaged per output spectrum (default: 1).
+    #[must_use]
+    pub fn averages(mut self, averages: usize) -> WelchBuilder {
+        self.averages = averages;
+        self
+    }
+
+    /// Window applied to each segment (default: Hann).
+    #[must_use]
+    pub fn window(mut self, window: Vec<f32>) -> WelchBuilder {
+        self.window = Some(window);
+        self
+    }
+
+    pub fn build(self) -> Block {
+        let window = self.window.unwrap_or_else(|| {
+            windows::hann(self.fft_size, true)
+                .into_iter()
+                .map(|w| w as f32)
+                .collect()
+        });
+        Welch::new(self.fft_size, self.overlap, self.averages, window)
+    }
+}
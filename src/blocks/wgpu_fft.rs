@@ -0,0 +1,416 @@
+use ::wgpu::BindGroup;
+use ::wgpu::BindGroupDescriptor;
+use ::wgpu::BindGroupEntry;
+use ::wgpu::Buffer;
+use ::wgpu::BufferDescriptor;
+use ::wgpu::BufferUsages;
+use ::wgpu::CommandEncoderDescriptor;
+use ::wgpu::ComputePassDescriptor;
+use ::wgpu::ComputePipeline;
+use ::wgpu::ComputePipelineDescriptor;
+use ::wgpu::Maintain;
+use ::wgpu::MapMode;
+use ::wgpu::ShaderModuleDescriptor;
+use ::wgpu::ShaderSource;
+use std::borrow::Cow;
+use std::sync::Arc;
+
+use crate::anyhow::{bail, Result};
+use crate::num_complex::Complex32;
+use crate::runtime::buffer::wgpu;
+use crate::runtime::buffer::wgpu::StagingPool;
+use crate::runtime::buffer::BufferReaderCustom;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Size of a complex sample in bytes.
+const ITEM_SIZE: usize = 8;
+/// Size of the uniform parameters in bytes.
+const PARAMS_SIZE: u64 = 32;
+/// Maximum number of samples per buffer, limited by the number of workgroups per dispatch.
+const MAX_BUFFER_ITEMS: usize = 1 << 22;
+
+/// GPU resources, created in `init`.
+struct State {
+    pipeline: ComputePipeline,
+    ping: Buffer,
+    pong: Buffer,
+    /// Parameters of each pass.
+    params: Vec<Buffer>,
+    bind_groups: Vec<BindGroup>,
+}
+
+/// Batched FFT on the GPU.
+pub struct WgpuFft {
+    broker: Arc<wgpu::Broker>,
+    len: usize,
+    inverse: bool,
+    normalize: bool,
+    buffer_items: usize,
+    n_input_buffers: usize,
+    n_output_buffers: usize,
+    staging: StagingPool,
+    state: Option<State>,
+    output_buffers: Vec<Buffer>,
+}
+
+impl WgpuFft {
+    fn create(builder: WgpuFftBuilder) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("WgpuFft").build(),
+            StreamIoBuilder::new()
+                .add_input::<Complex32>("in")
+                .add_output::<Complex32>("out")
+                .build(),
+            MessageIoBuilder::<WgpuFft>::new().build(),
+            WgpuFft {
+                broker: builder.broker,
+                len: builder.len,
+                inverse: builder.inverse,
+                normalize: builder.normalize,
+                buffer_items: builder.buffer_items,
+                n_input_buffers: builder.n_input_buffers,
+                n_output_buffers: builder.n_output_buffers,
+                staging: StagingPool::new((builder.buffer_items * ITEM_SIZE) as u64),
+                state: None,
+                output_buffers: Vec::new(),
+            },
+        )
+    }
+
+    /// Parameters of pass `stage` for `count` samples.
+    fn params(&self, stage: usize, count: usize) -> Vec<u8> {
+        let stages = self.len.trailing_zeros() as usize;
+        let scale = if self.normalize && stage == stages - 1 {
+            1.0 / self.len as f32
+        } else {
+            1.0
+        };
+        let sign: f32 = if self.inverse { 1.0 } else { -1.0 };
+
+        let mut p = Vec::with_capacity(PARAMS_SIZE as usize);
+        p.extend_from_slice(&(self.len as u32).to_le_bytes());
+        p.extend_from_slice(&(1u32 << stage).to_le_bytes());
+        p.extend_from_slice(&(count as u32).to_le_bytes());
+        p.extend_from_slice(&sign.to_le_bytes());
+        p.extend_from_slice(&scale.to_le_bytes());
+        p.resize(PARAMS_SIZE as usize, 0);
+        p
+    }
+}
+
+#[inline]
+fn o(sio: &mut StreamIo, id: usize) -> &mut wgpu::WriterD2H {
+    sio.output(id).try_as::<wgpu::WriterD2H>().unwrap()
+}
+
+#[inline]
+fn i(sio: &mut StreamIo, id: usize) -> &mut wgpu::ReaderH2D {
+    sio.input(id).try_as::<wgpu::ReaderH2D>().unwrap()
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for WgpuFft {
+    async fn init(
+        &mut self,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let device = &self.broker.device;
+        let bytes = (self.buffer_items * ITEM_SIZE) as u64;
+
+        for _ in 0..self.n_output_buffers {
+            self.output_buffers
+                .push(device.create_buffer(&BufferDescriptor {
+                    label: None,
+                    size: bytes,
+                    usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                }));
+        }
+        for _ in 0..self.n_input_buffers {
+            i(sio, 0).submit(wgpu::InputBufferEmpty {
+                buffer: vec![0; bytes as usize].into_boxed_slice(),
+            });
+        }
+
+        let storage = || {
+            device.create_buffer(&BufferDescriptor {
+                label: None,
+                size: bytes,
+                usage: BufferUsages::STORAGE | BufferUsages::COPY_SRC | BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            })
+        };
+        let ping = storage();
+        let pong = storage();
+
+        let module = device.create_shader_module(ShaderModuleDescriptor {
+            label: None,
+            source: ShaderSource::Wgsl(Cow::Borrowed(include_str!("wgpu_fft.wgsl"))),
+        });
+        let pipeline = device.create_compute_pipeline(&ComputePipelineDescriptor {
+            label: None,
+            layout: None,
+            module: &module,
+            entry_point: "main",
+        });
+        let layout = pipeline.get_bind_group_layout(0);
+
+        // passes alternate between the two storage buffers
+        let mut params = Vec::new();
+        let mut bind_groups = Vec::new();
+        for stage in 0..self.len.trailing_zeros() as usize {
+            let (src, dst) = if stage % 2 == 0 {
+                (&ping, &pong)
+            } else {
+                (&pong, &ping)
+            };
+            let p = device.create_buffer(&BufferDescriptor {
+                label: None,
+                size: PARAMS_SIZE,
+                usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+            bind_groups.push(device.create_bind_group(&BindGroupDescriptor {
+                label: None,
+                layout: &layout,
+                entries: &[
+                    BindGroupEntry {
+                        binding: 0,
+                        resource: src.as_entire_binding(),
+                    },
+                    BindGroupEntry {
+                        binding: 1,
+                        resource: dst.as_entire_binding(),
+                    },
+                    BindGroupEntry {
+                        binding: 2,
+                        resource: p.as_entire_binding(),
+                    },
+                ],
+            }));
+            params.push(p);
+        }
+
+        self.state = Some(State {
+            pipeline,
+            ping,
+            pong,
+            params,
+            bind_groups,
+        });
+
+        Ok(())
+    }
+
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        for m in o(sio, 0).buffers().into_iter() {
+            self.output_buffers.push(m.buffer);
+        }
+
+        let mut drained = false;
+        while !self.output_buffers.is_empty() {
+            let m = match i(sio, 0).get_buffer() {
+                Some(m) => m,
+                None => {
+                    drained = true;
+                    break;
+                }
+            };
+
+            let items = m.used_bytes / ITEM_SIZE;
+            let count = items / self.len * self.len;
+            if count < items {
+                warn!(
+                    "WgpuFft: dropping {} samples of an incomplete FFT",
+                    items - count
+                );
+            }
+            if count == 0 {
+                i(sio, 0).submit(wgpu::InputBufferEmpty { buffer: m.buffer });
+                continue;
+            }
+            let bytes = (count * ITEM_SIZE) as u64;
+
+            let state = self.state.as_ref().unwrap();
+            for (stage, p) in state.params.iter().enumerate() {
+                self.broker
+                    .queue
+                    .write_buffer(p, 0, &self.params(stage, count));
+            }
+
+            let output = self.output_buffers.pop().unwrap();
+            let mut encoder = self
+                .broker
+                .device
+                .create_command_encoder(&CommandEncoderDescriptor { label: None });
+            self.staging.upload(
+                &self.broker.device,
+                &mut encoder,
+                &m.buffer[0..bytes as usize],
+                &state.ping,
+                0,
+            );
+            {
+                let mut cpass = encoder.begin_compute_pass(&ComputePassDescriptor { label: None });
+                cpass.set_pipeline(&state.pipeline);
+                // one butterfly per invocation, 64 invocations per workgroup
+                let workgroups = ((count / 2 + 63) / 64) as u32;
+                for bind_group in state.bind_groups.iter() {
+                    cpass.set_bind_group(0, bind_group, &[]);
+                    cpass.dispatch_workgroups(workgroups, 1, 1);
+                }
+            }
+            let result = if state.bind_groups.len() % 2 == 0 {
+                &state.ping
+            } else {
+                &state.pong
+            };
+            encoder.copy_buffer_to_buffer(result, 0, &output, 0, bytes);
+            self.broker.queue.submit(Some(encoder.finish()));
+            self.staging.recall();
+
+            let (tx, rx) = futures::channel::oneshot::channel();
+            output.slice(0..bytes).map_async(MapMode::Read, move |r| {
+                let _ = tx.send(r);
+            });
+            self.broker.device.poll(Maintain::Wait);
+
+            if let Ok(Ok(())) = rx.await {
+                o(sio, 0).submit(wgpu::OutputBufferFull {
+                    buffer: output,
+                    used_bytes: bytes as usize,
+                });
+            } else {
+                bail!("WgpuFft: failed to map result buffer");
+            }
+            i(sio, 0).submit(wgpu::InputBufferEmpty { buffer: m.buffer });
+        }
+
+        if drained && i(sio, 0).finished() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Batched FFT on the GPU.
+///
+/// Computes FFTs of `len` samples, a power of two, with a radix-2 Stockham algorithm in a
+/// [wgpu](https://wgpu.rs) compute shader. All FFTs of a stream buffer are computed in one
+/// submission, which makes the block suitable for wideband captures, e.g., channelizing tens of
+/// MHz, that exceed the throughput of a CPU. Like the [Fft](crate::blocks::Fft) block,
+/// inverse transforms are not normalized by default.
+///
+/// Samples are uploaded through a [StagingPool] of mapped staging buffers and results are
+/// read back through the mapped [D2H](wgpu::D2H) buffers. Stream buffers hold
+/// [buffer_items](WgpuFftBuilder::buffer_items) samples, a multiple of `len`. Remaining
+/// samples of an incomplete FFT at the end of the stream are dropped.
+///
+/// # Inputs
+///
+/// `in`: Input samples (Complex32), connected with a [H2D](wgpu::H2D) buffer
+///
+/// # Outputs
+///
+/// `out`: FFT results (Complex32), connected with a [D2H](wgpu::D2H) buffer
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::WgpuFftBuilder;
+/// use futuresdr::runtime::buffer::wgpu;
+/// use futuresdr::runtime::Flowgraph;
+/// use std::sync::Arc;
+///
+/// # async fn f() -> futuresdr::anyhow::Result<()> {
+/// let mut fg = Flowgraph::new();
+///
+/// let broker = Arc::new(wgpu::Broker::new().await);
+/// let fft = fg.add_block(WgpuFftBuilder::new(broker, 1024).buffer_items(1 << 20).build());
+/// # Ok(())
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "wgpu")))]
+pub struct WgpuFftBuilder {
+    broker: Arc<wgpu::Broker>,
+    len: usize,
+    inverse: bool,
+    normalize: bool,
+    buffer_items: usize,
+    n_input_buffers: usize,
+    n_output_buffers: usize,
+}
+
+impl WgpuFftBuilder {
+    /// FFT of size `len` on the GPU of the broker, which can be shared with other blocks.
+    pub fn new(broker: Arc<wgpu::Broker>, len: usize) -> WgpuFftBuilder {
+        assert!(
+            len >= 2 && len.is_power_of_two(),
+            "FFT size has to be a power of two"
+        );
+        WgpuFftBuilder {
+            broker,
+            len,
+            inverse: false,
+            normalize: false,
+            buffer_items: std::cmp::max(len, 1 << 18),
+            n_input_buffers: 3,
+            n_output_buffers: 4,
+        }
+    }
+
+    /// Compute inverse FFTs.
+    #[must_use]
+    pub fn inverse(mut self) -> WgpuFftBuilder {
+        self.inverse = true;
+        self
+    }
+
+    /// Scale the results by `1 / len`.
+    #[must_use]
+    pub fn normalize(mut self) -> WgpuFftBuilder {
+        self.normalize = true;
+        self
+    }
+
+    /// Samples per stream buffer, a multiple of the FFT size (default: 2^18 or the FFT size).
+    #[must_use]
+    pub fn buffer_items(mut self, n: usize) -> WgpuFftBuilder {
+        assert!(
+            n > 0 && n % self.len == 0,
+            "buffer has to hold a multiple of the FFT size"
+        );
+        assert!(n <= MAX_BUFFER_ITEMS, "buffer too large");
+        self.buffer_items = n;
+        self
+    }
+
+    /// Number of input and output stream buffers (default: 3 and 4).
+    #[must_use]
+    pub fn buffers(mut self, input: usize, output: usize) -> WgpuFftBuilder {
+        self.n_input_buffers = input;
+        self.n_output_buffers = output;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        WgpuFft::create(self)
+    }
+}
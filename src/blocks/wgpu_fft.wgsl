@@ -0,0 +1,40 @@
+// One radix-2 pass of a batched Stockham FFT, i.e., one butterfly per invocation.
+struct Params {
+    // FFT size
+    n: u32,
+    // size of the sub-transforms of this pass
+    p: u32,
+    // number of samples
+    count: u32,
+    // -1 for forward, 1 for inverse transforms
+    sign: f32,
+    // scale of the outputs
+    scale: f32,
+}
+
+@group(0) @binding(0) var<storage, read> src: array<vec2<f32>>;
+@group(0) @binding(1) var<storage, read_write> dst: array<vec2<f32>>;
+@group(0) @binding(2) var<uniform> params: Params;
+
+@compute
+@workgroup_size(64)
+fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let i = global_id.x;
+    if (i >= params.count / 2u) {
+        return;
+    }
+    let half = params.n / 2u;
+    let base = i / half * params.n;
+    let j = i % half;
+    let k = j % params.p;
+
+    let a = src[base + j];
+    let b = src[base + j + half];
+    let angle = params.sign * 3.14159265358979 * f32(k) / f32(params.p);
+    let w = vec2<f32>(cos(angle), sin(angle));
+    let bw = vec2<f32>(b.x * w.x - b.y * w.y, b.x * w.y + b.y * w.x);
+
+    let out = base + 2u * j - k;
+    dst[out] = (a + bw) * params.scale;
+    dst[out + params.p] = (a - bw) * params.scale;
+}
@@ -0,0 +1,417 @@
+use ::wgpu::BindGroup;
+use ::wgpu::BindGroupDescriptor;
+use ::wgpu::BindGroupEntry;
+use ::wgpu::Buffer;
+use ::wgpu::BufferDescriptor;
+use ::wgpu::BufferUsages;
+use ::wgpu::CommandEncoderDescriptor;
+use ::wgpu::ComputePassDescriptor;
+use ::wgpu::ComputePipeline;
+use ::wgpu::ComputePipelineDescriptor;
+use ::wgpu::Maintain;
+use ::wgpu::MapMode;
+use ::wgpu::ShaderModuleDescriptor;
+use ::wgpu::ShaderSource;
+use std::borrow::Cow;
+use std::sync::Arc;
+
+use crate::anyhow::{bail, Result};
+use crate::num_complex::Complex32;
+use crate::runtime::buffer::wgpu;
+use crate::runtime::buffer::wgpu::StagingPool;
+use crate::runtime::buffer::BufferReaderCustom;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Size of a complex sample in bytes.
+const ITEM_SIZE: usize = 8;
+/// Size of the uniform parameters in bytes.
+const PARAMS_SIZE: u64 = 16;
+/// Maximum number of samples per buffer, limited by the number of workgroups per dispatch.
+const MAX_BUFFER_ITEMS: usize = 1 << 22;
+
+/// GPU resources, created in `init`.
+struct State {
+    pipeline: ComputePipeline,
+    input: Buffer,
+    result: Buffer,
+    params: Buffer,
+    bind_group: BindGroup,
+    // keep the taps alive, while they are bound
+    _taps: Buffer,
+}
+
+/// FIR filter and decimator on the GPU.
+pub struct WgpuFir {
+    broker: Arc<wgpu::Broker>,
+    taps: Vec<f32>,
+    decimation: usize,
+    buffer_items: usize,
+    n_input_buffers: usize,
+    n_output_buffers: usize,
+    staging: StagingPool,
+    state: Option<State>,
+    output_buffers: Vec<Buffer>,
+    /// Last `taps - 1` input samples (as bytes), needed for the next outputs.
+    history: Vec<u8>,
+    /// Index of the first input of the next output, relative to the history.
+    phase: usize,
+}
+
+impl WgpuFir {
+    fn create(builder: WgpuFirBuilder) -> Block {
+        let history = builder.taps.len() - 1;
+        Block::new(
+            BlockMetaBuilder::new("WgpuFir").build(),
+            StreamIoBuilder::new()
+                .add_input::<Complex32>("in")
+                .add_output::<Complex32>("out")
+                .build(),
+            MessageIoBuilder::<WgpuFir>::new().build(),
+            WgpuFir {
+                broker: builder.broker,
+                taps: builder.taps,
+                decimation: builder.decimation,
+                buffer_items: builder.buffer_items,
+                n_input_buffers: builder.n_input_buffers,
+                n_output_buffers: builder.n_output_buffers,
+                staging: StagingPool::new(((builder.buffer_items + history) * ITEM_SIZE) as u64),
+                state: None,
+                output_buffers: Vec::new(),
+                history: Vec::with_capacity(history * ITEM_SIZE),
+                phase: 0,
+            },
+        )
+    }
+
+    /// Keep the last `taps - 1` samples of history and new input and move the phase accordingly.
+    fn update_history(&mut self, data: &[u8], outputs: usize) {
+        let n_taps = self.taps.len();
+        let hist = self.history.len() / ITEM_SIZE;
+        let n = data.len() / ITEM_SIZE;
+        let available = hist + n;
+        let keep = std::cmp::min(n_taps - 1, available);
+
+        if n >= keep {
+            self.history.clear();
+            self.history
+                .extend_from_slice(&data[(n - keep) * ITEM_SIZE..]);
+        } else {
+            self.history.drain(..(available - keep) * ITEM_SIZE);
+            self.history.extend_from_slice(data);
+        }
+        self.phase = self.phase + outputs * self.decimation - (available - keep);
+    }
+}
+
+#[inline]
+fn o(sio: &mut StreamIo, id: usize) -> &mut wgpu::WriterD2H {
+    sio.output(id).try_as::<wgpu::WriterD2H>().unwrap()
+}
+
+#[inline]
+fn i(sio: &mut StreamIo, id: usize) -> &mut wgpu::ReaderH2D {
+    sio.input(id).try_as::<wgpu::ReaderH2D>().unwrap()
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for WgpuFir {
+    async fn init(
+        &mut self,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let device = &self.broker.device;
+        let bytes = (self.buffer_items * ITEM_SIZE) as u64;
+
+        for _ in 0..self.n_output_buffers {
+            self.output_buffers
+                .push(device.create_buffer(&BufferDescriptor {
+                    label: None,
+                    size: bytes,
+                    usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                }));
+        }
+        for _ in 0..self.n_input_buffers {
+            i(sio, 0).submit(wgpu::InputBufferEmpty {
+                buffer: vec![0; bytes as usize].into_boxed_slice(),
+            });
+        }
+
+        let input = device.create_buffer(&BufferDescriptor {
+            label: None,
+            size: ((self.buffer_items + self.taps.len() - 1) * ITEM_SIZE) as u64,
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let result = device.create_buffer(&BufferDescriptor {
+            label: None,
+            size: bytes,
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let params = device.create_buffer(&BufferDescriptor {
+            label: None,
+            size: PARAMS_SIZE,
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let taps = device.create_buffer(&BufferDescriptor {
+            label: None,
+            size: (self.taps.len() * 4) as u64,
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let reversed: Vec<u8> = self
+            .taps
+            .iter()
+            .rev()
+            .flat_map(|t| t.to_le_bytes())
+            .collect();
+        self.broker.queue.write_buffer(&taps, 0, &reversed);
+
+        let module = device.create_shader_module(ShaderModuleDescriptor {
+            label: None,
+            source: ShaderSource::Wgsl(Cow::Borrowed(include_str!("wgpu_fir.wgsl"))),
+        });
+        let pipeline = device.create_compute_pipeline(&ComputePipelineDescriptor {
+            label: None,
+            layout: None,
+            module: &module,
+            entry_point: "main",
+        });
+        let bind_group = device.create_bind_group(&BindGroupDescriptor {
+            label: None,
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: input.as_entire_binding(),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: taps.as_entire_binding(),
+                },
+                BindGroupEntry {
+                    binding: 2,
+                    resource: result.as_entire_binding(),
+                },
+                BindGroupEntry {
+                    binding: 3,
+                    resource: params.as_entire_binding(),
+                },
+            ],
+        });
+
+        self.state = Some(State {
+            pipeline,
+            input,
+            result,
+            params,
+            bind_group,
+            _taps: taps,
+        });
+
+        Ok(())
+    }
+
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        for m in o(sio, 0).buffers().into_iter() {
+            self.output_buffers.push(m.buffer);
+        }
+
+        let mut drained = false;
+        while !self.output_buffers.is_empty() {
+            let m = match i(sio, 0).get_buffer() {
+                Some(m) => m,
+                None => {
+                    drained = true;
+                    break;
+                }
+            };
+            let data = &m.buffer[0..m.used_bytes];
+
+            let n_taps = self.taps.len();
+            let available = self.history.len() / ITEM_SIZE + m.used_bytes / ITEM_SIZE;
+            let count = if available >= n_taps && self.phase <= available - n_taps {
+                (available - n_taps - self.phase) / self.decimation + 1
+            } else {
+                0
+            };
+
+            if count > 0 {
+                let state = self.state.as_ref().unwrap();
+                let bytes = (count * ITEM_SIZE) as u64;
+
+                let mut params = Vec::with_capacity(PARAMS_SIZE as usize);
+                for p in [n_taps, self.decimation, self.phase, count] {
+                    params.extend_from_slice(&(p as u32).to_le_bytes());
+                }
+                self.broker.queue.write_buffer(&state.params, 0, &params);
+
+                let output = self.output_buffers.pop().unwrap();
+                let mut encoder = self
+                    .broker
+                    .device
+                    .create_command_encoder(&CommandEncoderDescriptor { label: None });
+                self.staging.upload(
+                    &self.broker.device,
+                    &mut encoder,
+                    &self.history,
+                    &state.input,
+                    0,
+                );
+                self.staging.upload(
+                    &self.broker.device,
+                    &mut encoder,
+                    data,
+                    &state.input,
+                    self.history.len() as u64,
+                );
+                {
+                    let mut cpass =
+                        encoder.begin_compute_pass(&ComputePassDescriptor { label: None });
+                    cpass.set_pipeline(&state.pipeline);
+                    cpass.set_bind_group(0, &state.bind_group, &[]);
+                    // one output per invocation, 64 invocations per workgroup
+                    cpass.dispatch_workgroups(((count + 63) / 64) as u32, 1, 1);
+                }
+                encoder.copy_buffer_to_buffer(&state.result, 0, &output, 0, bytes);
+                self.broker.queue.submit(Some(encoder.finish()));
+                self.staging.recall();
+
+                let (tx, rx) = futures::channel::oneshot::channel();
+                output.slice(0..bytes).map_async(MapMode::Read, move |r| {
+                    let _ = tx.send(r);
+                });
+                self.broker.device.poll(Maintain::Wait);
+
+                if let Ok(Ok(())) = rx.await {
+                    o(sio, 0).submit(wgpu::OutputBufferFull {
+                        buffer: output,
+                        used_bytes: bytes as usize,
+                    });
+                } else {
+                    bail!("WgpuFir: failed to map result buffer");
+                }
+            }
+
+            self.update_history(&m.buffer[0..m.used_bytes], count);
+            i(sio, 0).submit(wgpu::InputBufferEmpty { buffer: m.buffer });
+        }
+
+        if drained && i(sio, 0).finished() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// FIR filter and decimator on the GPU.
+///
+/// Filters complex samples with real taps in a [wgpu](https://wgpu.rs) compute shader,
+/// keeping only every [decimation](WgpuFirBuilder::decimation)-th output. Together with the
+/// [WgpuFft](crate::blocks::WgpuFftBuilder), it offloads the channelization of wideband
+/// captures that exceed the throughput of a CPU.
+///
+/// Like the [Fir](crate::blocks::FirBuilder) block, output `k` is computed from the inputs `k`
+/// to `k + taps - 1`, i.e., there is no output for the first `taps - 1` samples. The last
+/// `taps - 1` samples of a stream buffer are kept as history for the next one.
+///
+/// Samples are uploaded through a [StagingPool] of mapped staging buffers and results are
+/// read back through the mapped [D2H](wgpu::D2H) buffers.
+///
+/// # Inputs
+///
+/// `in`: Input samples (Complex32), connected with a [H2D](wgpu::H2D) buffer
+///
+/// # Outputs
+///
+/// `out`: Filtered samples (Complex32), connected with a [D2H](wgpu::D2H) buffer
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::WgpuFirBuilder;
+/// use futuresdr::futuredsp::firdes;
+/// use futuresdr::runtime::buffer::wgpu;
+/// use futuresdr::runtime::Flowgraph;
+/// use std::sync::Arc;
+///
+/// # async fn f() -> futuresdr::anyhow::Result<()> {
+/// let mut fg = Flowgraph::new();
+///
+/// let broker = Arc::new(wgpu::Broker::new().await);
+/// let taps = firdes::kaiser::lowpass::<f32>(0.01, 0.005, 0.001);
+/// let fir = fg.add_block(WgpuFirBuilder::new(broker, taps).decimation(40).build());
+/// # Ok(())
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "wgpu")))]
+pub struct WgpuFirBuilder {
+    broker: Arc<wgpu::Broker>,
+    taps: Vec<f32>,
+    decimation: usize,
+    buffer_items: usize,
+    n_input_buffers: usize,
+    n_output_buffers: usize,
+}
+
+impl WgpuFirBuilder {
+    /// FIR filter on the GPU of the broker, which can be shared with other blocks.
+    pub fn new(broker: Arc<wgpu::Broker>, taps: Vec<f32>) -> WgpuFirBuilder {
+        assert!(!taps.is_empty(), "FIR filter needs at least one tap");
+        WgpuFirBuilder {
+            broker,
+            taps,
+            decimation: 1,
+            buffer_items: 1 << 18,
+            n_input_buffers: 3,
+            n_output_buffers: 4,
+        }
+    }
+
+    /// Keep only every n-th output.
+    #[must_use]
+    pub fn decimation(mut self, n: usize) -> WgpuFirBuilder {
+        assert!(n > 0, "decimation has to be positive");
+        self.decimation = n;
+        self
+    }
+
+    /// Samples per stream buffer (default: 2^18).
+    #[must_use]
+    pub fn buffer_items(mut self, n: usize) -> WgpuFirBuilder {
+        assert!(n > 0 && n <= MAX_BUFFER_ITEMS, "invalid buffer size");
+        self.buffer_items = n;
+        self
+    }
+
+    /// Number of input and output stream buffers (default: 3 and 4).
+    #[must_use]
+    pub fn buffers(mut self, input: usize, output: usize) -> WgpuFirBuilder {
+        self.n_input_buffers = input;
+        self.n_output_buffers = output;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        WgpuFir::create(self)
+    }
+}
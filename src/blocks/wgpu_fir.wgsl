@@ -0,0 +1,31 @@
+// FIR filter with real taps on complex samples, i.e., one output per invocation.
+struct Params {
+    // number of taps
+    taps: u32,
+    decimation: u32,
+    // input of the first output
+    phase: u32,
+    // number of outputs
+    count: u32,
+}
+
+@group(0) @binding(0) var<storage, read> x: array<vec2<f32>>;
+// taps in reverse order
+@group(0) @binding(1) var<storage, read> h: array<f32>;
+@group(0) @binding(2) var<storage, read_write> y: array<vec2<f32>>;
+@group(0) @binding(3) var<uniform> params: Params;
+
+@compute
+@workgroup_size(64)
+fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let i = global_id.x;
+    if (i >= params.count) {
+        return;
+    }
+    let start = params.phase + i * params.decimation;
+    var acc = vec2<f32>(0.0, 0.0);
+    for (var t = 0u; t < params.taps; t = t + 1u) {
+        acc = acc + x[start + t] * h[t];
+    }
+    y[i] = acc;
+}
@@ -0,0 +1,399 @@
+//! Wire format of GNU Radio PMTs and gr-zeromq tag headers.
+use std::collections::HashMap;
+
+use crate::anyhow::{bail, Context, Result};
+use crate::runtime::Pmt;
+use crate::runtime::Tag;
+
+const PST_TRUE: u8 = 0x00;
+const PST_FALSE: u8 = 0x01;
+const PST_SYMBOL: u8 = 0x02;
+const PST_INT32: u8 = 0x03;
+const PST_DOUBLE: u8 = 0x04;
+const PST_COMPLEX: u8 = 0x05;
+const PST_NULL: u8 = 0x06;
+const PST_PAIR: u8 = 0x07;
+const PST_VECTOR: u8 = 0x08;
+const PST_DICT: u8 = 0x09;
+const PST_UNIFORM_VECTOR: u8 = 0x0a;
+const PST_UINT64: u8 = 0x0b;
+const PST_TUPLE: u8 = 0x0c;
+const PST_INT64: u8 = 0x0d;
+
+const UVI_U8: u8 = 0x00;
+const UVI_S8: u8 = 0x01;
+const UVI_U16: u8 = 0x02;
+const UVI_S16: u8 = 0x03;
+const UVI_U32: u8 = 0x04;
+const UVI_S32: u8 = 0x05;
+const UVI_U64: u8 = 0x06;
+const UVI_S64: u8 = 0x07;
+const UVI_F32: u8 = 0x08;
+const UVI_F64: u8 = 0x09;
+const UVI_C32: u8 = 0x0a;
+const UVI_C64: u8 = 0x0b;
+
+const HEADER_MAGIC: u16 = 0x5ff0;
+const HEADER_VERSION: u8 = 0x01;
+
+/// Serialize a message like `pmt::serialize_str`.
+///
+/// Strings become symbols, unsigned integers `uint64`, signed integers `int64`, floats `double`,
+/// [Pmt::MapStrPmt] a dictionary with symbol keys, [Pmt::VecPmt] a vector, and the vector types
+/// uniform vectors.
+pub fn serialize(p: &Pmt, out: &mut Vec<u8>) -> Result<()> {
+    match p {
+        Pmt::Null => out.push(PST_NULL),
+        Pmt::String(s) => symbol(s, out)?,
+        Pmt::U32(v) => uint64(*v as u64, out),
+        Pmt::U64(v) => uint64(*v, out),
+        Pmt::I32(v) => int64(*v as i64, out),
+        Pmt::I64(v) => int64(*v, out),
+        Pmt::F32(v) => double(*v as f64, out),
+        Pmt::F64(v) => double(*v, out),
+        Pmt::Blob(v) => {
+            uniform_header(UVI_U8, v.len(), out);
+            out.extend_from_slice(v);
+        }
+        Pmt::VecF32(v) => {
+            uniform_header(UVI_F32, v.len(), out);
+            for x in v {
+                out.extend_from_slice(&(*x as f64).to_be_bytes());
+            }
+        }
+        Pmt::VecU64(v) => {
+            uniform_header(UVI_U64, v.len(), out);
+            for x in v {
+                out.extend_from_slice(&x.to_be_bytes());
+            }
+        }
+        Pmt::VecPmt(v) => {
+            out.push(PST_VECTOR);
+            out.extend_from_slice(&(v.len() as u32).to_be_bytes());
+            for x in v {
+                serialize(x, out)?;
+            }
+        }
+        Pmt::MapStrPmt(m) => {
+            // dictionaries are lists of (key . value) pairs
+            let mut keys: Vec<&String> = m.keys().collect();
+            keys.sort();
+            for k in keys {
+                out.push(PST_PAIR);
+                out.push(PST_PAIR);
+                symbol(k, out)?;
+                serialize(&m[k], out)?;
+            }
+            out.push(PST_NULL);
+        }
+        _ => bail!("cannot serialize {:?}", p),
+    }
+    Ok(())
+}
+
+fn symbol(s: &str, out: &mut Vec<u8>) -> Result<()> {
+    let len = u16::try_from(s.len()).context("symbol too long")?;
+    out.push(PST_SYMBOL);
+    out.extend_from_slice(&len.to_be_bytes());
+    out.extend_from_slice(s.as_bytes());
+    Ok(())
+}
+
+fn uint64(v: u64, out: &mut Vec<u8>) {
+    out.push(PST_UINT64);
+    out.extend_from_slice(&v.to_be_bytes());
+}
+
+fn int64(v: i64, out: &mut Vec<u8>) {
+    out.push(PST_INT64);
+    out.extend_from_slice(&v.to_be_bytes());
+}
+
+fn double(v: f64, out: &mut Vec<u8>) {
+    out.push(PST_DOUBLE);
+    out.extend_from_slice(&v.to_be_bytes());
+}
+
+fn uniform_header(kind: u8, len: usize, out: &mut Vec<u8>) {
+    out.push(PST_UNIFORM_VECTOR);
+    out.push(kind);
+    out.extend_from_slice(&(len as u32).to_be_bytes());
+    // one byte of padding
+    out.push(1);
+    out.push(0);
+}
+
+/// GNU Radio PMT, as far as it is needed to map it to a [Pmt].
+#[derive(Debug, Clone, PartialEq)]
+enum GrPmt {
+    Bool(bool),
+    Symbol(String),
+    Int(i64),
+    Uint(u64),
+    Double(f64),
+    Complex(f64, f64),
+    Null,
+    Pair(Box<GrPmt>, Box<GrPmt>),
+    Vector(Vec<GrPmt>),
+    Blob(Vec<u8>),
+    Reals(Vec<f64>),
+    Unsigned(Vec<u64>),
+    Signed(Vec<i64>),
+}
+
+impl GrPmt {
+    fn into_pmt(self) -> Pmt {
+        match self {
+            GrPmt::Bool(b) => Pmt::U32(b as u32),
+            GrPmt::Symbol(s) => Pmt::String(s),
+            GrPmt::Int(v) => int(v),
+            GrPmt::Uint(v) => Pmt::U64(v),
+            GrPmt::Double(v) => Pmt::F64(v),
+            GrPmt::Complex(re, im) => Pmt::VecF32(vec![re as f32, im as f32]),
+            GrPmt::Null => Pmt::Null,
+            GrPmt::Pair(car, cdr) => match GrPmt::dict(&car, &cdr) {
+                Some(m) => Pmt::MapStrPmt(m),
+                None => Pmt::VecPmt(vec![car.into_pmt(), cdr.into_pmt()]),
+            },
+            GrPmt::Vector(v) => Pmt::VecPmt(v.into_iter().map(GrPmt::into_pmt).collect()),
+            GrPmt::Blob(v) => Pmt::Blob(v),
+            GrPmt::Reals(v) => Pmt::VecF32(v.into_iter().map(|x| x as f32).collect()),
+            GrPmt::Unsigned(v) => Pmt::VecU64(v),
+            GrPmt::Signed(v) => Pmt::VecPmt(v.into_iter().map(int).collect()),
+        }
+    }
+
+    /// Convert a list of (symbol . value) pairs, given as first pair and rest, to a map.
+    fn dict<'a>(mut car: &'a GrPmt, mut cdr: &'a GrPmt) -> Option<HashMap<String, Pmt>> {
+        let mut m = HashMap::new();
+        loop {
+            match car {
+                GrPmt::Pair(k, v) => match k.as_ref() {
+                    GrPmt::Symbol(k) => m.insert(k.clone(), v.as_ref().clone().into_pmt()),
+                    _ => return None,
+                },
+                _ => return None,
+            };
+            match cdr {
+                GrPmt::Null => return Some(m),
+                GrPmt::Pair(a, d) => {
+                    car = a;
+                    cdr = d;
+                }
+                _ => return None,
+            }
+        }
+    }
+}
+
+/// Negative integers do not fit into the unsigned integer variants and become floats.
+fn int(v: i64) -> Pmt {
+    if v >= 0 {
+        Pmt::U64(v as u64)
+    } else {
+        Pmt::I64(v)
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        if self.data.len() < n {
+            bail!("truncated PMT");
+        }
+        let (a, b) = self.data.split_at(n);
+        self.data = b;
+        Ok(a)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        Ok(u16::from_be_bytes(self.take(2)?.try_into()?))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into()?))
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_be_bytes(self.take(8)?.try_into()?))
+    }
+
+    fn f64(&mut self) -> Result<f64> {
+        Ok(f64::from_bits(self.u64()?))
+    }
+
+    fn pmt(&mut self) -> Result<GrPmt> {
+        Ok(match self.u8()? {
+            PST_TRUE => GrPmt::Bool(true),
+            PST_FALSE => GrPmt::Bool(false),
+            PST_SYMBOL => {
+                let len = self.u16()? as usize;
+                GrPmt::Symbol(String::from_utf8_lossy(self.take(len)?).into_owned())
+            }
+            PST_INT32 => GrPmt::Int(self.u32()? as i32 as i64),
+            PST_INT64 => GrPmt::Int(self.u64()? as i64),
+            PST_UINT64 => GrPmt::Uint(self.u64()?),
+            PST_DOUBLE => GrPmt::Double(self.f64()?),
+            PST_COMPLEX => GrPmt::Complex(self.f64()?, self.f64()?),
+            PST_NULL => GrPmt::Null,
+            PST_PAIR | PST_DICT => GrPmt::Pair(Box::new(self.pmt()?), Box::new(self.pmt()?)),
+            PST_VECTOR | PST_TUPLE => {
+                let len = self.u32()? as usize;
+                let mut v = Vec::new();
+                for _ in 0..len {
+                    v.push(self.pmt()?);
+                }
+                GrPmt::Vector(v)
+            }
+            PST_UNIFORM_VECTOR => {
+                let kind = self.u8()?;
+                let len = self.u32()? as usize;
+                let npad = self.u8()? as usize;
+                self.take(npad)?;
+                match kind {
+                    UVI_U8 => GrPmt::Blob(self.take(len)?.to_vec()),
+                    UVI_S8 => {
+                        GrPmt::Signed(self.take(len)?.iter().map(|x| *x as i8 as i64).collect())
+                    }
+                    UVI_U16 => GrPmt::Unsigned(self.elements(len, |r| Ok(r.u16()? as u64))?),
+                    UVI_S16 => GrPmt::Signed(self.elements(len, |r| Ok(r.u16()? as i16 as i64))?),
+                    UVI_U32 => GrPmt::Unsigned(self.elements(len, |r| Ok(r.u32()? as u64))?),
+                    UVI_S32 => GrPmt::Signed(self.elements(len, |r| Ok(r.u32()? as i32 as i64))?),
+                    UVI_U64 => GrPmt::Unsigned(self.elements(len, Reader::u64)?),
+                    UVI_S64 => GrPmt::Signed(self.elements(len, |r| Ok(r.u64()? as i64))?),
+                    UVI_F32 | UVI_F64 => GrPmt::Reals(self.elements(len, Reader::f64)?),
+                    // complex vectors are interleaved
+                    UVI_C32 | UVI_C64 => GrPmt::Reals(self.elements(2 * len, Reader::f64)?),
+                    _ => bail!("unknown uniform vector type {}", kind),
+                }
+            }
+            t => bail!("unknown PMT type {}", t),
+        })
+    }
+
+    fn elements<T>(
+        &mut self,
+        len: usize,
+        f: impl Fn(&mut Reader<'a>) -> Result<T>,
+    ) -> Result<Vec<T>> {
+        let mut v = Vec::with_capacity(std::cmp::min(len, self.data.len()));
+        for _ in 0..len {
+            v.push(f(self)?);
+        }
+        Ok(v)
+    }
+}
+
+/// Deserialize a message like `pmt::deserialize_str`.
+///
+/// Booleans become [Pmt::U32] (0 or 1), negative integers [Pmt::I64], dictionaries
+/// [Pmt::MapStrPmt], other pairs [Pmt::VecPmt] with two elements, and complex numbers and
+/// vectors interleaved [Pmt::VecF32].
+pub fn deserialize(data: &[u8]) -> Result<Pmt> {
+    let mut r = Reader { data };
+    Ok(r.pmt()?.into_pmt())
+}
+
+/// Tag as GNU Radio key and value.
+fn tag_to_gr(tag: &Tag, out: &mut Vec<u8>) -> Result<()> {
+    match tag {
+        Tag::Id(id) => {
+            symbol("id", out)?;
+            uint64(*id, out);
+        }
+        Tag::String(s) => {
+            symbol(s, out)?;
+            out.push(PST_TRUE);
+        }
+        Tag::Data(p) => {
+            symbol("data", out)?;
+            serialize(p, out)?;
+        }
+        Tag::NamedUsize(s, v) => {
+            symbol(s, out)?;
+            uint64(*v as u64, out);
+        }
+        Tag::NamedF32(s, v) => {
+            symbol(s, out)?;
+            double(*v as f64, out);
+        }
+        _ => bail!("cannot serialize {:?}", tag),
+    }
+    Ok(())
+}
+
+fn tag_from_gr(key: GrPmt, value: GrPmt) -> Result<Tag> {
+    let key = match key {
+        GrPmt::Symbol(s) => s,
+        k => bail!("tag key is not a symbol {:?}", k),
+    };
+    Ok(match value {
+        GrPmt::Bool(true) => Tag::String(key),
+        GrPmt::Double(v) => Tag::NamedF32(key, v as f32),
+        GrPmt::Uint(v) => Tag::NamedUsize(key, v as usize),
+        GrPmt::Int(v) if v >= 0 => Tag::NamedUsize(key, v as usize),
+        v => Tag::Data(Pmt::MapStrPmt(HashMap::from([(key, v.into_pmt())]))),
+    })
+}
+
+/// Header of gr-zeromq messages with tags, preceding the samples.
+///
+/// `offset` is the index of the first sample of the message, tags are given with absolute
+/// indices. Tags that cannot be serialized are skipped.
+pub fn tag_header(offset: u64, tags: &[(u64, Tag)], out: &mut Vec<u8>) {
+    let mut encoded = Vec::new();
+    let mut n = 0u64;
+    for (index, tag) in tags {
+        let start = encoded.len();
+        encoded.extend_from_slice(&index.to_le_bytes());
+        if tag_to_gr(tag, &mut encoded).is_ok() {
+            // srcid
+            encoded.push(PST_FALSE);
+            n += 1;
+        } else {
+            encoded.truncate(start);
+        }
+    }
+
+    out.extend_from_slice(&HEADER_MAGIC.to_le_bytes());
+    out.push(HEADER_VERSION);
+    out.extend_from_slice(&offset.to_le_bytes());
+    out.extend_from_slice(&n.to_le_bytes());
+    out.extend_from_slice(&encoded);
+}
+
+/// Parse the header of a gr-zeromq message with tags.
+///
+/// Returns the index of the first sample, the tags with their absolute index, and the length of
+/// the header.
+pub fn parse_tag_header(data: &[u8]) -> Result<(u64, Vec<(u64, Tag)>, usize)> {
+    if data.len() < 19 {
+        bail!("truncated tag header");
+    }
+    if u16::from_le_bytes(data[0..2].try_into()?) != HEADER_MAGIC || data[2] != HEADER_VERSION {
+        bail!("invalid tag header");
+    }
+    let offset = u64::from_le_bytes(data[3..11].try_into()?);
+    let n = u64::from_le_bytes(data[11..19].try_into()?);
+
+    let mut r = Reader { data: &data[19..] };
+    let mut tags = Vec::new();
+    for _ in 0..n {
+        let index = u64::from_le_bytes(r.take(8)?.try_into()?);
+        let key = r.pmt()?;
+        let value = r.pmt()?;
+        let _srcid = r.pmt()?;
+        match tag_from_gr(key, value) {
+            Ok(t) => tags.push((index, t)),
+            Err(e) => warn!("skipping tag: {}", e),
+        }
+    }
+    Ok((offset, tags, data.len() - r.data.len()))
+}
@@ -1,6 +1,24 @@
 //! ## [ZeroMQ](https://zeromq.org/) Blocks
+//!
+//! The blocks are wire-compatible with GNU Radio's gr-zeromq. Stream blocks optionally pass
+//! tags in gr-zeromq tag headers, message blocks exchange serialized GNU Radio PMTs.
+mod gr_pmt;
+mod stream;
+
+mod msg_sink;
+pub use msg_sink::{MsgSink, MsgSinkBuilder};
+
+mod msg_source;
+pub use msg_source::{MsgSource, MsgSourceBuilder};
+
 mod pub_sink;
 pub use pub_sink::{PubSink, PubSinkBuilder};
 
+mod pull_source;
+pub use pull_source::{PullSource, PullSourceBuilder};
+
+mod push_sink;
+pub use push_sink::{PushSink, PushSinkBuilder};
+
 mod sub_source;
 pub use sub_source::{SubSource, SubSourceBuilder};
@@ -0,0 +1,158 @@
+use crate::anyhow::Result;
+use crate::blocks::zeromq::gr_pmt;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+
+/// Send messages through a [ZeroMQ](https://zeromq.org/) socket.
+pub struct MsgSink {
+    address: String,
+    socket_type: zmq::SocketType,
+    socket: Option<zmq::Socket>,
+    buf: Vec<u8>,
+    n_dropped: u64,
+}
+
+impl MsgSink {
+    fn create(address: String, socket_type: zmq::SocketType) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("MsgSink").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_input("in", Self::in_port)
+                .build(),
+            MsgSink {
+                address,
+                socket_type,
+                socket: None,
+                buf: Vec::new(),
+                n_dropped: 0,
+            },
+        )
+    }
+
+    #[message_handler]
+    async fn in_port(
+        &mut self,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        self.buf.clear();
+        if let Err(e) = gr_pmt::serialize(&p, &mut self.buf) {
+            warn!("MsgSink: cannot serialize PMT ({}). {:?}", e, p);
+            return Ok(Pmt::Null);
+        }
+
+        match self
+            .socket
+            .as_ref()
+            .unwrap()
+            .send(&self.buf[..], zmq::DONTWAIT)
+        {
+            Ok(()) => Ok(Pmt::Null),
+            Err(zmq::Error::EAGAIN) => {
+                self.n_dropped += 1;
+                warn!("MsgSink: no peer ready, dropped message");
+                Ok(Pmt::Null)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for MsgSink {
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let context = zmq::Context::new();
+        let socket = context.socket(self.socket_type)?;
+        info!("MsgSink Binding to {:?}", self.address);
+        socket.bind(&self.address)?;
+        self.socket = Some(socket);
+
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        debug!("MsgSink dropped {} messages", self.n_dropped);
+        Ok(())
+    }
+}
+
+/// Send messages through a [ZeroMQ](https://zeromq.org/) socket.
+///
+/// Compatible with the PUB and PUSH message sinks of GNU Radio's gr-zeromq, i.e., each message
+/// is sent as a serialized GNU Radio PMT. Messages are sent without blocking. If the socket
+/// cannot take a message, e.g., since there is no peer connected to a PUSH socket, the message
+/// is dropped.
+///
+/// # Inputs
+///
+/// **Message**: `in`: Messages to send
+///
+/// # Outputs
+///
+/// No outputs.
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::zeromq::MsgSinkBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let snk = fg.add_block(MsgSinkBuilder::new().address("tcp://*:5556").push().build());
+/// ```
+pub struct MsgSinkBuilder {
+    address: String,
+    socket_type: zmq::SocketType,
+}
+
+impl MsgSinkBuilder {
+    pub fn new() -> MsgSinkBuilder {
+        MsgSinkBuilder {
+            address: "tcp://*:5555".into(),
+            socket_type: zmq::PUB,
+        }
+    }
+
+    #[must_use]
+    pub fn address(mut self, address: &str) -> MsgSinkBuilder {
+        self.address = address.to_string();
+        self
+    }
+
+    /// Use a PUSH instead of a PUB socket.
+    #[must_use]
+    pub fn push(mut self) -> MsgSinkBuilder {
+        self.socket_type = zmq::PUSH;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        MsgSink::create(self.address, self.socket_type)
+    }
+}
+
+impl Default for MsgSinkBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
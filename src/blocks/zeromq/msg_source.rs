@@ -0,0 +1,145 @@
+use crate::anyhow::Result;
+use crate::blocks::zeromq::gr_pmt;
+use crate::blocks::zeromq::stream::RECV_TIMEOUT;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Receive messages from a [ZeroMQ](https://zeromq.org/) socket.
+pub struct MsgSource {
+    address: String,
+    socket_type: zmq::SocketType,
+    socket: Option<zmq::Socket>,
+}
+
+impl MsgSource {
+    fn create(address: String, socket_type: zmq::SocketType) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("MsgSource").blocking().build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new().add_output("out").build(),
+            MsgSource {
+                address,
+                socket_type,
+                socket: None,
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for MsgSource {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        _sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        io.call_again = true;
+
+        let message = match self.socket.as_ref().unwrap().recv_bytes(0) {
+            Ok(m) => m,
+            Err(zmq::Error::EAGAIN) => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+
+        match gr_pmt::deserialize(&message) {
+            Ok(p) => mio.post(0, p).await,
+            Err(e) => warn!("MsgSource: cannot deserialize PMT ({}), dropped message", e),
+        }
+
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let context = zmq::Context::new();
+        let socket = context.socket(self.socket_type)?;
+        info!("MsgSource Connecting to {:?}", self.address);
+        socket.connect(&self.address)?;
+        if self.socket_type == zmq::SUB {
+            socket.set_subscribe(b"")?;
+        }
+        socket.set_rcvtimeo(RECV_TIMEOUT)?;
+        self.socket = Some(socket);
+
+        Ok(())
+    }
+}
+
+/// Receive messages from a [ZeroMQ](https://zeromq.org/) socket.
+///
+/// Compatible with the SUB and PULL message sources of GNU Radio's gr-zeromq, i.e., each
+/// message is expected to be a serialized GNU Radio PMT. Messages that cannot be deserialized
+/// are dropped with a warning.
+///
+/// # Inputs
+///
+/// No inputs.
+///
+/// # Outputs
+///
+/// **Message**: `out`: Received messages
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::zeromq::MsgSourceBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let src = fg.add_block(
+///     MsgSourceBuilder::new()
+///         .address("tcp://127.0.0.1:5556")
+///         .pull()
+///         .build(),
+/// );
+/// ```
+pub struct MsgSourceBuilder {
+    address: String,
+    socket_type: zmq::SocketType,
+}
+
+impl MsgSourceBuilder {
+    pub fn new() -> MsgSourceBuilder {
+        MsgSourceBuilder {
+            address: "tcp://127.0.0.1:5555".into(),
+            socket_type: zmq::SUB,
+        }
+    }
+
+    #[must_use]
+    pub fn address(mut self, address: &str) -> MsgSourceBuilder {
+        self.address = address.to_string();
+        self
+    }
+
+    /// Use a PULL instead of a SUB socket.
+    #[must_use]
+    pub fn pull(mut self) -> MsgSourceBuilder {
+        self.socket_type = zmq::PULL;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        MsgSource::create(self.address, self.socket_type)
+    }
+}
+
+impl Default for MsgSourceBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,4 +1,5 @@
 use crate::anyhow::Result;
+use crate::blocks::zeromq::stream::Sender;
 use crate::runtime::Block;
 use crate::runtime::BlockMeta;
 use crate::runtime::BlockMetaBuilder;
@@ -15,19 +16,25 @@ pub struct PubSink<T: Send + 'static> {
     publisher: Option<zmq::Socket>,
     _type: std::marker::PhantomData<T>,
     min_item: usize,
+    sender: Sender,
 }
 
 impl<T: Send + 'static> PubSink<T> {
     pub fn new(address: impl Into<String>, min_item: usize) -> Block {
+        Self::create(address.into(), min_item, false)
+    }
+
+    fn create(address: String, min_item: usize, pass_tags: bool) -> Block {
         Block::new(
             BlockMetaBuilder::new("PubSink").blocking().build(),
             StreamIoBuilder::new().add_input::<T>("in").build(),
             MessageIoBuilder::new().build(),
             PubSink {
-                address: address.into(),
+                address,
                 publisher: None,
                 _type: std::marker::PhantomData::<T>,
                 min_item,
+                sender: Sender::new(pass_tags),
             },
         )
     }
@@ -47,8 +54,12 @@ impl<T: Send + 'static> Kernel for PubSink<T> {
 
         let n = i.len();
         if n > 0 && n > self.min_item {
-            let i = sio.input(0).slice_unchecked::<u8>();
-            self.publisher.as_mut().unwrap().send(i, 0).unwrap();
+            self.sender.send(
+                self.publisher.as_ref().unwrap(),
+                sio,
+                n,
+                std::mem::size_of::<T>(),
+            )?;
             sio.input(0).consume(n);
         }
 
@@ -76,11 +87,15 @@ impl<T: Send + 'static> Kernel for PubSink<T> {
 }
 
 /// Build a ZeroMQ [PubSink].
+///
+/// Compatible with the PUB sink of GNU Radio's gr-zeromq. If [pass_tags](Self::pass_tags) is
+/// set, the samples of each message are preceded by a gr-zeromq tag header.
 pub struct PubSinkBuilder<T: Send + 'static> {
     address: String,
     _type: std::marker::PhantomData<T>,
     /// Minimum number of items per send
     min_item: usize,
+    pass_tags: bool,
 }
 
 impl<T: Send + 'static> PubSinkBuilder<T> {
@@ -89,6 +104,7 @@ impl<T: Send + 'static> PubSinkBuilder<T> {
             address: "tcp://*:5555".into(),
             _type: std::marker::PhantomData,
             min_item: 1,
+            pass_tags: false,
         }
     }
 
@@ -103,8 +119,15 @@ impl<T: Send + 'static> PubSinkBuilder<T> {
         self
     }
 
+    /// Send stream tags in gr-zeromq tag headers.
+    #[must_use]
+    pub fn pass_tags(mut self) -> PubSinkBuilder<T> {
+        self.pass_tags = true;
+        self
+    }
+
     pub fn build(self) -> Block {
-        PubSink::<T>::new(self.address, self.min_item)
+        PubSink::<T>::create(self.address, self.min_item, self.pass_tags)
     }
 }
 
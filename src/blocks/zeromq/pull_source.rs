@@ -0,0 +1,119 @@
+use crate::anyhow::Result;
+use crate::blocks::zeromq::stream::{Receiver, RECV_TIMEOUT};
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Read samples from a PULL [ZeroMQ](https://zeromq.org/) socket.
+pub struct PullSource<T: Send + 'static> {
+    address: String,
+    receiver: Option<zmq::Socket>,
+    _type: std::marker::PhantomData<T>,
+    stream: Receiver,
+}
+
+impl<T: Send + 'static> PullSource<T> {
+    pub fn new(address: impl Into<String>) -> Block {
+        Self::create(address.into(), false)
+    }
+
+    fn create(address: String, pass_tags: bool) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("PullSource").blocking().build(),
+            StreamIoBuilder::new().add_output::<T>("out").build(),
+            MessageIoBuilder::new().build(),
+            PullSource {
+                address,
+                receiver: None,
+                _type: std::marker::PhantomData::<T>,
+                stream: Receiver::new(pass_tags),
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: Send + 'static> Kernel for PullSource<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        self.stream.receive(
+            self.receiver.as_ref().unwrap(),
+            io,
+            sio,
+            std::mem::size_of::<T>(),
+        )
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        debug!("PullSource Init");
+
+        let context = zmq::Context::new();
+        let receiver = context.socket(zmq::PULL).unwrap();
+        info!("PullSource Connecting to {:?}", self.address);
+        receiver.connect(&self.address)?;
+        receiver.set_rcvtimeo(RECV_TIMEOUT)?;
+        self.receiver = Some(receiver);
+        Ok(())
+    }
+}
+
+/// Build a ZeroMQ [PullSource].
+///
+/// Compatible with the PULL source of GNU Radio's gr-zeromq. If [pass_tags](Self::pass_tags) is
+/// set, each message has to start with a gr-zeromq tag header, whose tags are added to the
+/// stream.
+pub struct PullSourceBuilder<T: Send + 'static> {
+    address: String,
+    _type: std::marker::PhantomData<T>,
+    pass_tags: bool,
+}
+
+impl<T: Send + 'static> PullSourceBuilder<T> {
+    pub fn new() -> PullSourceBuilder<T> {
+        PullSourceBuilder {
+            address: "tcp://*:5555".into(),
+            _type: std::marker::PhantomData,
+            pass_tags: false,
+        }
+    }
+
+    #[must_use]
+    pub fn address(mut self, address: &str) -> PullSourceBuilder<T> {
+        self.address = address.to_string();
+        self
+    }
+
+    /// Receive stream tags in gr-zeromq tag headers.
+    #[must_use]
+    pub fn pass_tags(mut self) -> PullSourceBuilder<T> {
+        self.pass_tags = true;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        PullSource::<T>::create(self.address, self.pass_tags)
+    }
+}
+
+impl<T: Send + 'static> Default for PullSourceBuilder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
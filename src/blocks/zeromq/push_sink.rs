@@ -0,0 +1,138 @@
+use crate::anyhow::Result;
+use crate::blocks::zeromq::stream::Sender;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Push samples into a PUSH [ZeroMQ](https://zeromq.org/) socket.
+pub struct PushSink<T: Send + 'static> {
+    address: String,
+    socket: Option<zmq::Socket>,
+    _type: std::marker::PhantomData<T>,
+    min_item: usize,
+    sender: Sender,
+}
+
+impl<T: Send + 'static> PushSink<T> {
+    pub fn new(address: impl Into<String>, min_item: usize) -> Block {
+        Self::create(address.into(), min_item, false)
+    }
+
+    fn create(address: String, min_item: usize, pass_tags: bool) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("PushSink").blocking().build(),
+            StreamIoBuilder::new().add_input::<T>("in").build(),
+            MessageIoBuilder::new().build(),
+            PushSink {
+                address,
+                socket: None,
+                _type: std::marker::PhantomData::<T>,
+                min_item,
+                sender: Sender::new(pass_tags),
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: Send + 'static> Kernel for PushSink<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<T>();
+
+        let n = i.len();
+        if n > 0 && n > self.min_item {
+            self.sender.send(
+                self.socket.as_ref().unwrap(),
+                sio,
+                n,
+                std::mem::size_of::<T>(),
+            )?;
+            sio.input(0).consume(n);
+        }
+
+        if sio.input(0).finished() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let context = zmq::Context::new();
+        let socket = context.socket(zmq::PUSH)?;
+        info!("PushSink Binding to {:?}", self.address);
+        socket.bind(&self.address)?;
+        self.socket = Some(socket);
+
+        Ok(())
+    }
+}
+
+/// Build a ZeroMQ [PushSink].
+///
+/// Compatible with the PUSH sink of GNU Radio's gr-zeromq. If [pass_tags](Self::pass_tags) is
+/// set, the samples of each message are preceded by a gr-zeromq tag header.
+pub struct PushSinkBuilder<T: Send + 'static> {
+    address: String,
+    _type: std::marker::PhantomData<T>,
+    /// Minimum number of items per send
+    min_item: usize,
+    pass_tags: bool,
+}
+
+impl<T: Send + 'static> PushSinkBuilder<T> {
+    pub fn new() -> PushSinkBuilder<T> {
+        PushSinkBuilder {
+            address: "tcp://*:5555".into(),
+            _type: std::marker::PhantomData,
+            min_item: 1,
+            pass_tags: false,
+        }
+    }
+
+    #[must_use]
+    pub fn address(mut self, address: &str) -> PushSinkBuilder<T> {
+        self.address = address.to_string();
+        self
+    }
+
+    pub fn min_item_per_send(mut self, min_item: usize) -> PushSinkBuilder<T> {
+        self.min_item = min_item;
+        self
+    }
+
+    /// Send stream tags in gr-zeromq tag headers.
+    #[must_use]
+    pub fn pass_tags(mut self) -> PushSinkBuilder<T> {
+        self.pass_tags = true;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        PushSink::<T>::create(self.address, self.min_item, self.pass_tags)
+    }
+}
+
+impl<T: Send + 'static> Default for PushSinkBuilder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
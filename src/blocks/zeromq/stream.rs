@@ -0,0 +1,134 @@
+//! Stream transport shared by the ZeroMQ sinks and sources.
+use crate::anyhow::Result;
+use crate::blocks::zeromq::gr_pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::Tag;
+use crate::runtime::WorkIo;
+
+/// Receive timeout in ms of the sources, after which they check for termination.
+pub const RECV_TIMEOUT: i32 = 100;
+
+/// Send samples, optionally preceded by a gr-zeromq tag header.
+pub struct Sender {
+    pass_tags: bool,
+    /// Index of the next sample.
+    n: u64,
+    buf: Vec<u8>,
+}
+
+impl Sender {
+    pub fn new(pass_tags: bool) -> Sender {
+        Sender {
+            pass_tags,
+            n: 0,
+            buf: Vec::new(),
+        }
+    }
+
+    /// Send the first `items` samples of the input in one message.
+    pub fn send(
+        &mut self,
+        socket: &zmq::Socket,
+        sio: &mut StreamIo,
+        items: usize,
+        item_size: usize,
+    ) -> Result<()> {
+        let data = &sio.input(0).slice_unchecked::<u8>()[..items * item_size];
+        if self.pass_tags {
+            let tags: Vec<(u64, Tag)> = sio
+                .input(0)
+                .tags()
+                .iter()
+                .filter(|t| t.index < items)
+                .map(|t| (self.n + t.index as u64, t.tag.clone()))
+                .collect();
+            self.buf.clear();
+            gr_pmt::tag_header(self.n, &tags, &mut self.buf);
+            self.buf.extend_from_slice(data);
+            socket.send(&self.buf[..], 0)?;
+        } else {
+            socket.send(data, 0)?;
+        }
+        self.n += items as u64;
+        Ok(())
+    }
+}
+
+/// Receive samples, optionally preceded by a gr-zeromq tag header.
+pub struct Receiver {
+    pass_tags: bool,
+    message: Vec<u8>,
+    /// Position of the next sample in the message.
+    pos: usize,
+    /// Index of the next sample, according to the sender.
+    n: u64,
+    /// Tags of the message with their absolute index.
+    tags: Vec<(u64, Tag)>,
+}
+
+impl Receiver {
+    pub fn new(pass_tags: bool) -> Receiver {
+        Receiver {
+            pass_tags,
+            message: Vec::new(),
+            pos: 0,
+            n: 0,
+            tags: Vec::new(),
+        }
+    }
+
+    /// Output the samples of the current message, receiving a new one, if it is consumed.
+    ///
+    /// Messages are split, if they do not fit into the output buffer. Incomplete samples at the
+    /// end of a message are dropped.
+    pub fn receive(
+        &mut self,
+        socket: &zmq::Socket,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        item_size: usize,
+    ) -> Result<()> {
+        if self.message.len() - self.pos < item_size {
+            let message = match socket.recv_bytes(0) {
+                Ok(m) => m,
+                Err(zmq::Error::EAGAIN) => {
+                    io.call_again = true;
+                    return Ok(());
+                }
+                Err(e) => return Err(e.into()),
+            };
+            if self.pass_tags {
+                let (offset, tags, len) = gr_pmt::parse_tag_header(&message)?;
+                self.n = offset;
+                self.tags = tags;
+                self.pos = len;
+            } else {
+                self.pos = 0;
+            }
+            self.message = message;
+        }
+
+        let o = sio.output(0).slice_unchecked::<u8>();
+        let items = std::cmp::min(o.len(), self.message.len() - self.pos) / item_size;
+        let bytes = items * item_size;
+        o[..bytes].copy_from_slice(&self.message[self.pos..self.pos + bytes]);
+
+        let end = self.n + items as u64;
+        for (index, tag) in self.tags.iter() {
+            if *index >= self.n && *index < end {
+                sio.output(0)
+                    .add_tag((*index - self.n) as usize, tag.clone());
+            }
+        }
+        self.tags.retain(|(index, _)| *index >= end);
+
+        self.pos += bytes;
+        self.n = end;
+        sio.output(0).produce(items);
+        if items > 0 {
+            io.call_again = true;
+        }
+
+        Ok(())
+    }
+}
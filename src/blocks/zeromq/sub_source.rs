@@ -1,4 +1,5 @@
 use crate::anyhow::Result;
+use crate::blocks::zeromq::stream::{Receiver, RECV_TIMEOUT};
 use crate::runtime::Block;
 use crate::runtime::BlockMeta;
 use crate::runtime::BlockMetaBuilder;
@@ -14,18 +15,24 @@ pub struct SubSource<T: Send + 'static> {
     address: String,
     receiver: Option<zmq::Socket>,
     _type: std::marker::PhantomData<T>,
+    stream: Receiver,
 }
 
 impl<T: Send + 'static> SubSource<T> {
     pub fn new(address: impl Into<String>) -> Block {
+        Self::create(address.into(), false)
+    }
+
+    fn create(address: String, pass_tags: bool) -> Block {
         Block::new(
             BlockMetaBuilder::new("SubSource").blocking().build(),
             StreamIoBuilder::new().add_output::<T>("out").build(),
             MessageIoBuilder::new().build(),
             SubSource {
-                address: address.into(),
+                address,
                 receiver: None,
                 _type: std::marker::PhantomData::<T>,
+                stream: Receiver::new(pass_tags),
             },
         )
     }
@@ -36,19 +43,17 @@ impl<T: Send + 'static> SubSource<T> {
 impl<T: Send + 'static> Kernel for SubSource<T> {
     async fn work(
         &mut self,
-        _io: &mut WorkIo,
+        io: &mut WorkIo,
         sio: &mut StreamIo,
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
-        let o = sio.output(0).slice_unchecked::<u8>();
-        let n_bytes = self.receiver.as_mut().unwrap().recv_into(o, 0)?;
-        debug_assert_eq!(o.len() % std::mem::size_of::<T>(), 0);
-        let n = n_bytes / std::mem::size_of::<T>();
-        debug!("SubSource received {}", n);
-        sio.output(0).produce(n);
-
-        Ok(())
+        self.stream.receive(
+            self.receiver.as_ref().unwrap(),
+            io,
+            sio,
+            std::mem::size_of::<T>(),
+        )
     }
 
     async fn init(
@@ -64,15 +69,21 @@ impl<T: Send + 'static> Kernel for SubSource<T> {
         info!("SubSource Connecting to {:?}", self.address);
         receiver.connect(&self.address)?;
         receiver.set_subscribe(b"")?;
+        receiver.set_rcvtimeo(RECV_TIMEOUT)?;
         self.receiver = Some(receiver);
         Ok(())
     }
 }
 
 /// Build a ZeroMQ [SubSource].
+///
+/// Compatible with the SUB source of GNU Radio's gr-zeromq. If [pass_tags](Self::pass_tags) is
+/// set, each message has to start with a gr-zeromq tag header, whose tags are added to the
+/// stream.
 pub struct SubSourceBuilder<T: Send + 'static> {
     address: String,
     _type: std::marker::PhantomData<T>,
+    pass_tags: bool,
 }
 
 impl<T: Send + 'static> SubSourceBuilder<T> {
@@ -80,6 +91,7 @@ impl<T: Send + 'static> SubSourceBuilder<T> {
         SubSourceBuilder {
             address: "tcp://*:5555".into(),
             _type: std::marker::PhantomData,
+            pass_tags: false,
         }
     }
 
@@ -89,8 +101,15 @@ impl<T: Send + 'static> SubSourceBuilder<T> {
         self
     }
 
+    /// Receive stream tags in gr-zeromq tag headers.
+    #[must_use]
+    pub fn pass_tags(mut self) -> SubSourceBuilder<T> {
+        self.pass_tags = true;
+        self
+    }
+
     pub fn build(self) -> Block {
-        SubSource::<T>::new(self.address)
+        SubSource::<T>::create(self.address, self.pass_tags)
     }
 }
 
@@ -0,0 +1,62 @@
+use futuredsp::{ComputationStatus, UnaryKernel};
+use num_complex::Complex32;
+use std::cmp::Ordering;
+
+/// Non-resampling FIR filter with `f32` taps for `f32` or `Complex32` samples, using the
+/// vectorized [dot products](super::dot).
+///
+/// It is a drop-in replacement for
+/// [NonResamplingFirKernel](futuredsp::fir::NonResamplingFirKernel), i.e., output `k` is
+/// computed from the inputs `k` to `k + taps.len() - 1` and only the inputs that are no longer
+/// needed are consumed.
+pub struct FirKernel<T> {
+    /// Taps in reverse order, i.e., in the order of the input samples.
+    reversed: Vec<f32>,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T> FirKernel<T> {
+    pub fn new(taps: Vec<f32>) -> FirKernel<T> {
+        assert!(!taps.is_empty(), "FIR filter needs at least one tap");
+        let mut reversed = taps;
+        reversed.reverse();
+        FirKernel {
+            reversed,
+            _type: std::marker::PhantomData,
+        }
+    }
+
+    fn run<I>(
+        &self,
+        i: &[I],
+        o: &mut [I],
+        dot: unsafe fn(&[I], &[f32]) -> I,
+    ) -> (usize, usize, ComputationStatus) {
+        let available = (i.len() + 1).saturating_sub(self.reversed.len());
+        let (n, status) = match available.cmp(&o.len()) {
+            Ordering::Greater => (o.len(), ComputationStatus::InsufficientOutput),
+            Ordering::Equal => (available, ComputationStatus::BothSufficient),
+            Ordering::Less => (available, ComputationStatus::InsufficientInput),
+        };
+
+        let len = self.reversed.len();
+        for (k, y) in o[..n].iter_mut().enumerate() {
+            // the dot product of the selected kernels, see `dispatch!`
+            *y = unsafe { dot(&i[k..k + len], &self.reversed) };
+        }
+
+        (n, n, status)
+    }
+}
+
+impl UnaryKernel<f32, f32> for FirKernel<f32> {
+    fn work(&self, i: &[f32], o: &mut [f32]) -> (usize, usize, ComputationStatus) {
+        self.run(i, o, super::KERNELS.dot)
+    }
+}
+
+impl UnaryKernel<Complex32, Complex32> for FirKernel<Complex32> {
+    fn work(&self, i: &[Complex32], o: &mut [Complex32]) -> (usize, usize, ComputationStatus) {
+        self.run(i, o, super::KERNELS.dot_complex)
+    }
+}
@@ -0,0 +1,142 @@
+//! ## Vectorized DSP Kernels
+//!
+//! Explicitly vectorized implementations of the inner loops of common blocks, like complex
+//! multiplication, FIR dot products, magnitudes, and sample type conversion. They are used by
+//! the [Fir](crate::blocks::FirBuilder), the [complex](crate::blocks::ComplexToMag) and
+//! [conversion](crate::blocks::IntToFloat) blocks, and can be used in custom blocks.
+//!
+//! The implementation is selected at runtime:
+//! - on x86-64 with AVX2 and FMA, AVX2 intrinsics are used,
+//! - on AArch64, NEON intrinsics are used,
+//! - otherwise, the kernels fall back to scalar loops.
+//!
+//! Results of the vectorized dot products differ from the scalar loops by rounding, since
+//! the sums are accumulated in a different order.
+//!
+//! All kernels process the common length of their input and output slices.
+use num_complex::Complex32;
+use once_cell::sync::Lazy;
+
+mod fir;
+pub use fir::FirKernel;
+
+#[cfg(target_arch = "aarch64")]
+mod neon;
+mod scalar;
+#[cfg(target_arch = "x86_64")]
+mod x86;
+
+/// Implementations of the kernels, selected once for this machine.
+struct Kernels {
+    vectorized: bool,
+    multiply: unsafe fn(&[Complex32], &[Complex32], &mut [Complex32]),
+    dot: unsafe fn(&[f32], &[f32]) -> f32,
+    dot_complex: unsafe fn(&[Complex32], &[f32]) -> Complex32,
+    magnitude: unsafe fn(&[Complex32], &mut [f32]),
+    magnitude_squared: unsafe fn(&[Complex32], &mut [f32]),
+    i16_to_f32: unsafe fn(&[i16], f32, f32, &mut [f32]),
+    u8_to_f32: unsafe fn(&[u8], f32, f32, &mut [f32]),
+    f32_to_i16: unsafe fn(&[f32], f32, f32, &mut [i16]),
+}
+
+macro_rules! kernels {
+    ($m:ident, $vectorized:expr) => {
+        Kernels {
+            vectorized: $vectorized,
+            multiply: $m::multiply,
+            dot: $m::dot,
+            dot_complex: $m::dot_complex,
+            magnitude: $m::magnitude,
+            magnitude_squared: $m::magnitude_squared,
+            i16_to_f32: $m::i16_to_f32,
+            u8_to_f32: $m::u8_to_f32,
+            f32_to_i16: $m::f32_to_i16,
+        }
+    };
+}
+
+/// The vectorized implementations, if available, or the scalar loops.
+static KERNELS: Lazy<Kernels> = Lazy::new(|| {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx2") && is_x86_feature_detected!("fma") {
+            return kernels!(x86, true);
+        }
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        return kernels!(neon, true);
+    }
+    #[cfg(not(target_arch = "aarch64"))]
+    kernels!(scalar, false)
+});
+
+/// Call the implementation, selected for this machine.
+macro_rules! dispatch {
+    ($f:ident($($arg:expr),*)) => {
+        // the required CPU features were checked, when the implementation was selected
+        unsafe { (KERNELS.$f)($($arg),*) }
+    };
+}
+
+/// Whether vectorized kernels are available on this machine.
+pub fn vectorized() -> bool {
+    KERNELS.vectorized
+}
+
+/// Element-wise product of complex samples, `out[k] = a[k] * b[k]`.
+pub fn multiply(a: &[Complex32], b: &[Complex32], out: &mut [Complex32]) {
+    let n = a.len().min(b.len()).min(out.len());
+    let (a, b, out) = (&a[..n], &b[..n], &mut out[..n]);
+    dispatch!(multiply(a, b, out))
+}
+
+/// Dot product of real samples, `a[0] * b[0] + a[1] * b[1] + ...`.
+pub fn dot(a: &[f32], b: &[f32]) -> f32 {
+    let n = a.len().min(b.len());
+    let (a, b) = (&a[..n], &b[..n]);
+    dispatch!(dot(a, b))
+}
+
+/// Dot product of complex samples with real taps, `x[0] * taps[0] + x[1] * taps[1] + ...`.
+pub fn dot_complex(x: &[Complex32], taps: &[f32]) -> Complex32 {
+    let n = x.len().min(taps.len());
+    let (x, taps) = (&x[..n], &taps[..n]);
+    dispatch!(dot_complex(x, taps))
+}
+
+/// Magnitude of complex samples, `out[k] = |x[k]|`.
+pub fn magnitude(x: &[Complex32], out: &mut [f32]) {
+    let n = x.len().min(out.len());
+    let (x, out) = (&x[..n], &mut out[..n]);
+    dispatch!(magnitude(x, out))
+}
+
+/// Squared magnitude of complex samples, `out[k] = |x[k]|^2`.
+pub fn magnitude_squared(x: &[Complex32], out: &mut [f32]) {
+    let n = x.len().min(out.len());
+    let (x, out) = (&x[..n], &mut out[..n]);
+    dispatch!(magnitude_squared(x, out))
+}
+
+/// Convert 16-bit integers to floats, `out[k] = (x[k] - offset) * scale`.
+pub fn i16_to_f32(x: &[i16], scale: f32, offset: f32, out: &mut [f32]) {
+    let n = x.len().min(out.len());
+    let (x, out) = (&x[..n], &mut out[..n]);
+    dispatch!(i16_to_f32(x, scale, offset, out))
+}
+
+/// Convert unsigned bytes to floats, `out[k] = (x[k] - offset) * scale`.
+pub fn u8_to_f32(x: &[u8], scale: f32, offset: f32, out: &mut [f32]) {
+    let n = x.len().min(out.len());
+    let (x, out) = (&x[..n], &mut out[..n]);
+    dispatch!(u8_to_f32(x, scale, offset, out))
+}
+
+/// Convert floats to 16-bit integers, `out[k] = round(x[k] * scale + offset)`, rounding half
+/// away from zero and saturating at the limits of `i16`.
+pub fn f32_to_i16(x: &[f32], scale: f32, offset: f32, out: &mut [i16]) {
+    let n = x.len().min(out.len());
+    let (x, out) = (&x[..n], &mut out[..n]);
+    dispatch!(f32_to_i16(x, scale, offset, out))
+}
@@ -0,0 +1,89 @@
+//! NEON kernels, always available on AArch64.
+use num_complex::Complex32;
+use std::arch::aarch64::*;
+
+use super::scalar;
+
+pub unsafe fn multiply(a: &[Complex32], b: &[Complex32], out: &mut [Complex32]) {
+    let n = a.len() / 4 * 4;
+    let pa = a.as_ptr() as *const f32;
+    let pb = b.as_ptr() as *const f32;
+    let po = out.as_mut_ptr() as *mut f32;
+    for k in (0..2 * n).step_by(8) {
+        // deinterleave into real and imaginary parts
+        let x = vld2q_f32(pa.add(k));
+        let y = vld2q_f32(pb.add(k));
+        let re = vfmsq_f32(vmulq_f32(x.0, y.0), x.1, y.1);
+        let im = vfmaq_f32(vmulq_f32(x.0, y.1), x.1, y.0);
+        vst2q_f32(po.add(k), float32x4x2_t(re, im));
+    }
+    scalar::multiply(&a[n..], &b[n..], &mut out[n..]);
+}
+
+pub unsafe fn dot(a: &[f32], b: &[f32]) -> f32 {
+    let n = a.len() / 8 * 8;
+    let pa = a.as_ptr();
+    let pb = b.as_ptr();
+    let mut acc0 = vdupq_n_f32(0.0);
+    let mut acc1 = vdupq_n_f32(0.0);
+    for k in (0..n).step_by(8) {
+        acc0 = vfmaq_f32(acc0, vld1q_f32(pa.add(k)), vld1q_f32(pb.add(k)));
+        acc1 = vfmaq_f32(acc1, vld1q_f32(pa.add(k + 4)), vld1q_f32(pb.add(k + 4)));
+    }
+    vaddvq_f32(vaddq_f32(acc0, acc1)) + scalar::dot(&a[n..], &b[n..])
+}
+
+pub unsafe fn dot_complex(x: &[Complex32], taps: &[f32]) -> Complex32 {
+    let n = x.len() / 4 * 4;
+    let px = x.as_ptr() as *const f32;
+    let pt = taps.as_ptr();
+    let mut re = vdupq_n_f32(0.0);
+    let mut im = vdupq_n_f32(0.0);
+    for k in (0..n).step_by(4) {
+        let v = vld2q_f32(px.add(2 * k));
+        let t = vld1q_f32(pt.add(k));
+        re = vfmaq_f32(re, v.0, t);
+        im = vfmaq_f32(im, v.1, t);
+    }
+    Complex32::new(vaddvq_f32(re), vaddvq_f32(im)) + scalar::dot_complex(&x[n..], &taps[n..])
+}
+
+pub unsafe fn magnitude(x: &[Complex32], out: &mut [f32]) {
+    let n = x.len() / 4 * 4;
+    let px = x.as_ptr() as *const f32;
+    let po = out.as_mut_ptr();
+    for k in (0..n).step_by(4) {
+        let v = vld2q_f32(px.add(2 * k));
+        let m = vaddq_f32(vmulq_f32(v.0, v.0), vmulq_f32(v.1, v.1));
+        vst1q_f32(po.add(k), vsqrtq_f32(m));
+    }
+    scalar::magnitude(&x[n..], &mut out[n..]);
+}
+
+pub unsafe fn magnitude_squared(x: &[Complex32], out: &mut [f32]) {
+    let n = x.len() / 4 * 4;
+    let px = x.as_ptr() as *const f32;
+    let po = out.as_mut_ptr();
+    for k in (0..n).step_by(4) {
+        let v = vld2q_f32(px.add(2 * k));
+        vst1q_f32(
+            po.add(k),
+            vaddq_f32(vmulq_f32(v.0, v.0), vmulq_f32(v.1, v.1)),
+        );
+    }
+    scalar::magnitude_squared(&x[n..], &mut out[n..]);
+}
+
+// The conversions auto-vectorize well on AArch64.
+
+pub unsafe fn i16_to_f32(x: &[i16], scale: f32, offset: f32, out: &mut [f32]) {
+    scalar::i16_to_f32(x, scale, offset, out);
+}
+
+pub unsafe fn u8_to_f32(x: &[u8], scale: f32, offset: f32, out: &mut [f32]) {
+    scalar::u8_to_f32(x, scale, offset, out);
+}
+
+pub unsafe fn f32_to_i16(x: &[f32], scale: f32, offset: f32, out: &mut [i16]) {
+    scalar::f32_to_i16(x, scale, offset, out);
+}
@@ -0,0 +1,48 @@
+//! Scalar fallbacks, also used for the remainders of the vectorized kernels.
+use num_complex::Complex32;
+
+pub fn multiply(a: &[Complex32], b: &[Complex32], out: &mut [Complex32]) {
+    for ((a, b), y) in a.iter().zip(b.iter()).zip(out.iter_mut()) {
+        *y = a * b;
+    }
+}
+
+pub fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b.iter()).map(|(a, b)| a * b).sum()
+}
+
+pub fn dot_complex(x: &[Complex32], taps: &[f32]) -> Complex32 {
+    x.iter()
+        .zip(taps.iter())
+        .fold(Complex32::new(0.0, 0.0), |acc, (x, t)| acc + x * t)
+}
+
+pub fn magnitude(x: &[Complex32], out: &mut [f32]) {
+    for (x, y) in x.iter().zip(out.iter_mut()) {
+        *y = x.norm_sqr().sqrt();
+    }
+}
+
+pub fn magnitude_squared(x: &[Complex32], out: &mut [f32]) {
+    for (x, y) in x.iter().zip(out.iter_mut()) {
+        *y = x.norm_sqr();
+    }
+}
+
+pub fn i16_to_f32(x: &[i16], scale: f32, offset: f32, out: &mut [f32]) {
+    for (x, y) in x.iter().zip(out.iter_mut()) {
+        *y = (*x as f32 - offset) * scale;
+    }
+}
+
+pub fn u8_to_f32(x: &[u8], scale: f32, offset: f32, out: &mut [f32]) {
+    for (x, y) in x.iter().zip(out.iter_mut()) {
+        *y = (*x as f32 - offset) * scale;
+    }
+}
+
+pub fn f32_to_i16(x: &[f32], scale: f32, offset: f32, out: &mut [i16]) {
+    for (x, y) in x.iter().zip(out.iter_mut()) {
+        *y = (x * scale + offset).round() as i16;
+    }
+}
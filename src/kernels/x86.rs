@@ -0,0 +1,167 @@
+//! AVX2 and FMA kernels. Callers have to make sure that both are available.
+use num_complex::Complex32;
+use std::arch::x86_64::*;
+
+use super::scalar;
+
+/// Horizontal sum of all lanes.
+#[target_feature(enable = "avx2,fma")]
+unsafe fn sum(v: __m256) -> f32 {
+    let s = _mm_add_ps(_mm256_castps256_ps128(v), _mm256_extractf128_ps(v, 1));
+    let s = _mm_add_ps(s, _mm_movehl_ps(s, s));
+    let s = _mm_add_ss(s, _mm_shuffle_ps(s, s, 0b01));
+    _mm_cvtss_f32(s)
+}
+
+#[target_feature(enable = "avx2,fma")]
+pub unsafe fn multiply(a: &[Complex32], b: &[Complex32], out: &mut [Complex32]) {
+    let n = a.len() / 4 * 4;
+    let pa = a.as_ptr() as *const f32;
+    let pb = b.as_ptr() as *const f32;
+    let po = out.as_mut_ptr() as *mut f32;
+    for k in (0..2 * n).step_by(8) {
+        let x = _mm256_loadu_ps(pa.add(k));
+        let y = _mm256_loadu_ps(pb.add(k));
+        let y_re = _mm256_moveldup_ps(y);
+        let y_im = _mm256_movehdup_ps(y);
+        // swap real and imaginary parts
+        let x_swap = _mm256_permute_ps(x, 0b1011_0001);
+        // even lanes: re * re - im * im, odd lanes: im * re + re * im
+        let r = _mm256_fmaddsub_ps(x, y_re, _mm256_mul_ps(x_swap, y_im));
+        _mm256_storeu_ps(po.add(k), r);
+    }
+    scalar::multiply(&a[n..], &b[n..], &mut out[n..]);
+}
+
+#[target_feature(enable = "avx2,fma")]
+pub unsafe fn dot(a: &[f32], b: &[f32]) -> f32 {
+    let n = a.len() / 16 * 16;
+    let pa = a.as_ptr();
+    let pb = b.as_ptr();
+    let mut acc0 = _mm256_setzero_ps();
+    let mut acc1 = _mm256_setzero_ps();
+    for k in (0..n).step_by(16) {
+        acc0 = _mm256_fmadd_ps(_mm256_loadu_ps(pa.add(k)), _mm256_loadu_ps(pb.add(k)), acc0);
+        acc1 = _mm256_fmadd_ps(
+            _mm256_loadu_ps(pa.add(k + 8)),
+            _mm256_loadu_ps(pb.add(k + 8)),
+            acc1,
+        );
+    }
+    sum(_mm256_add_ps(acc0, acc1)) + scalar::dot(&a[n..], &b[n..])
+}
+
+#[target_feature(enable = "avx2,fma")]
+pub unsafe fn dot_complex(x: &[Complex32], taps: &[f32]) -> Complex32 {
+    let n = x.len() / 8 * 8;
+    let px = x.as_ptr() as *const f32;
+    let pt = taps.as_ptr();
+    // duplicate each tap for the real and imaginary part
+    let dup = _mm256_setr_epi32(0, 0, 1, 1, 2, 2, 3, 3);
+    let mut acc0 = _mm256_setzero_ps();
+    let mut acc1 = _mm256_setzero_ps();
+    for k in (0..n).step_by(8) {
+        let t0 = _mm256_permutevar8x32_ps(_mm256_castps128_ps256(_mm_loadu_ps(pt.add(k))), dup);
+        let t1 = _mm256_permutevar8x32_ps(_mm256_castps128_ps256(_mm_loadu_ps(pt.add(k + 4))), dup);
+        acc0 = _mm256_fmadd_ps(_mm256_loadu_ps(px.add(2 * k)), t0, acc0);
+        acc1 = _mm256_fmadd_ps(_mm256_loadu_ps(px.add(2 * k + 8)), t1, acc1);
+    }
+    let mut lanes = [0.0f32; 8];
+    _mm256_storeu_ps(lanes.as_mut_ptr(), _mm256_add_ps(acc0, acc1));
+    let acc = Complex32::new(
+        lanes[0] + lanes[2] + lanes[4] + lanes[6],
+        lanes[1] + lanes[3] + lanes[5] + lanes[7],
+    );
+    acc + scalar::dot_complex(&x[n..], &taps[n..])
+}
+
+/// Squared magnitudes of eight complex samples.
+#[target_feature(enable = "avx2,fma")]
+unsafe fn norm_sqr8(p: *const f32) -> __m256 {
+    let a = _mm256_loadu_ps(p);
+    let b = _mm256_loadu_ps(p.add(8));
+    // samples 0, 1, 4, 5, 2, 3, 6, 7
+    let s = _mm256_hadd_ps(_mm256_mul_ps(a, a), _mm256_mul_ps(b, b));
+    _mm256_castpd_ps(_mm256_permute4x64_pd(_mm256_castps_pd(s), 0b1101_1000))
+}
+
+#[target_feature(enable = "avx2,fma")]
+pub unsafe fn magnitude(x: &[Complex32], out: &mut [f32]) {
+    let n = x.len() / 8 * 8;
+    let px = x.as_ptr() as *const f32;
+    let po = out.as_mut_ptr();
+    for k in (0..n).step_by(8) {
+        _mm256_storeu_ps(po.add(k), _mm256_sqrt_ps(norm_sqr8(px.add(2 * k))));
+    }
+    scalar::magnitude(&x[n..], &mut out[n..]);
+}
+
+#[target_feature(enable = "avx2,fma")]
+pub unsafe fn magnitude_squared(x: &[Complex32], out: &mut [f32]) {
+    let n = x.len() / 8 * 8;
+    let px = x.as_ptr() as *const f32;
+    let po = out.as_mut_ptr();
+    for k in (0..n).step_by(8) {
+        _mm256_storeu_ps(po.add(k), norm_sqr8(px.add(2 * k)));
+    }
+    scalar::magnitude_squared(&x[n..], &mut out[n..]);
+}
+
+#[target_feature(enable = "avx2,fma")]
+pub unsafe fn i16_to_f32(x: &[i16], scale: f32, offset: f32, out: &mut [f32]) {
+    let n = x.len() / 8 * 8;
+    let px = x.as_ptr();
+    let po = out.as_mut_ptr();
+    let s = _mm256_set1_ps(scale);
+    let o = _mm256_set1_ps(offset);
+    for k in (0..n).step_by(8) {
+        let v = _mm256_cvtepi16_epi32(_mm_loadu_si128(px.add(k) as *const __m128i));
+        let v = _mm256_mul_ps(_mm256_sub_ps(_mm256_cvtepi32_ps(v), o), s);
+        _mm256_storeu_ps(po.add(k), v);
+    }
+    scalar::i16_to_f32(&x[n..], scale, offset, &mut out[n..]);
+}
+
+#[target_feature(enable = "avx2,fma")]
+pub unsafe fn u8_to_f32(x: &[u8], scale: f32, offset: f32, out: &mut [f32]) {
+    let n = x.len() / 8 * 8;
+    let px = x.as_ptr();
+    let po = out.as_mut_ptr();
+    let s = _mm256_set1_ps(scale);
+    let o = _mm256_set1_ps(offset);
+    for k in (0..n).step_by(8) {
+        let v = _mm256_cvtepu8_epi32(_mm_loadl_epi64(px.add(k) as *const __m128i));
+        let v = _mm256_mul_ps(_mm256_sub_ps(_mm256_cvtepi32_ps(v), o), s);
+        _mm256_storeu_ps(po.add(k), v);
+    }
+    scalar::u8_to_f32(&x[n..], scale, offset, &mut out[n..]);
+}
+
+#[target_feature(enable = "avx2,fma")]
+pub unsafe fn f32_to_i16(x: &[f32], scale: f32, offset: f32, out: &mut [i16]) {
+    let n = x.len() / 8 * 8;
+    let px = x.as_ptr();
+    let po = out.as_mut_ptr();
+    let s = _mm256_set1_ps(scale);
+    let o = _mm256_set1_ps(offset);
+    let half = _mm256_set1_ps(0.5);
+    let one = _mm256_set1_ps(1.0);
+    let sign = _mm256_set1_ps(-0.0);
+    let min = _mm256_set1_ps(i16::MIN as f32);
+    let max = _mm256_set1_ps(i16::MAX as f32);
+    for k in (0..n).step_by(8) {
+        let v = _mm256_mul_ps(_mm256_loadu_ps(px.add(k)), s);
+        let v = _mm256_add_ps(v, o);
+        // round half away from zero, like f32::round
+        let t = _mm256_round_ps(v, _MM_FROUND_TO_ZERO | _MM_FROUND_NO_EXC);
+        let frac = _mm256_andnot_ps(sign, _mm256_sub_ps(v, t));
+        let up = _mm256_cmp_ps(frac, half, _CMP_GE_OQ);
+        let step = _mm256_or_ps(_mm256_and_ps(v, sign), one);
+        let r = _mm256_add_ps(t, _mm256_and_ps(up, step));
+        let r = _mm256_min_ps(_mm256_max_ps(r, min), max);
+        let r = _mm256_cvtps_epi32(r);
+        let r = _mm_packs_epi32(_mm256_castsi256_si128(r), _mm256_extracti128_si256(r, 1));
+        _mm_storeu_si128(po.add(k) as *mut __m128i, r);
+    }
+    scalar::f32_to_i16(&x[n..], scale, offset, &mut out[n..]);
+}
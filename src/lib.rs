@@ -34,8 +34,34 @@
 //!     Ok(())
 //! }
 //! ```
+//!
+//! ## Minimal Runtime
+//! For resource-constrained targets, like ARM Cortex-A gateways, FutureSDR can be built without
+//! its default features:
+//! ```toml
+//! futuresdr = { version = "0.0.27", default-features = false }
+//! ```
+//! This disables the `web` feature, i.e., the control port with its REST API and web server
+//! (axum, hyper, tokio) and the WebSocket-based blocks
+//! ([WebsocketSink](blocks::WebsocketSink), [WebsocketStreamSink](blocks::WebsocketStreamSink),
+//! [KiwiSdrSource](blocks::KiwiSdrSource)). Flowgraphs run on the smol-based
+//! [SmolScheduler](runtime::scheduler::SmolScheduler); optional features, like `soapy`, are
+//! disabled anyway. [FlowgraphHandle](runtime::FlowgraphHandle)s are still available to control
+//! flowgraphs from within the application.
+//!
+//! The polymorphic types ([Pmt](runtime::Pmt)) are defined in the `futuresdr-pmt` crate, which
+//! is `no_std`-compatible (requiring only `alloc`), when its default `std` feature is disabled.
+//!
+//! To compare the footprint with and without the default features, `perf/footprint.sh` builds
+//! and runs a `NullSource > Head > NullSink` flowgraph with one worker thread
+//! (`examples/footprint.rs`). It reports the number of dependencies, the size of the stripped
+//! release binary, and the peak RSS.
+//!
+//! Memory usage grows mainly with the number of blocks and the size of the stream buffers,
+//! which can be reduced through the `buffer_size` [config](runtime::config) option.
 
 pub mod blocks;
+pub mod kernels;
 pub mod runtime;
 
 // re-exports
@@ -46,6 +72,8 @@ pub use async_io;
 pub use async_net;
 #[macro_use]
 pub extern crate async_trait;
+pub use futuredsp;
+pub use futuredsp::firdes;
 pub use futures;
 pub use futures_lite;
 #[macro_use]
@@ -59,5 +87,7 @@ pub mod macros {
 }
 pub use num_complex;
 pub use num_integer;
+#[cfg(feature = "proptest")]
+pub use proptest;
 #[cfg(feature = "soapy")]
 pub use soapysdr;
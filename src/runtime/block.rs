@@ -6,6 +6,7 @@ use std::result;
 
 use crate::anyhow::Result;
 use crate::runtime::BlockMeta;
+use crate::runtime::HandlerDescription;
 use crate::runtime::HandlerError;
 use crate::runtime::MessageIo;
 use crate::runtime::MessageOutput;
@@ -84,6 +85,8 @@ pub trait BlockT: Send + Any {
 
     // ##### STREAM IO
     fn commit(&mut self);
+    fn flush(&mut self);
+    fn stream_ready(&mut self) -> bool;
     #[allow(clippy::type_complexity)]
     fn set_tag_propagation(
         &mut self,
@@ -103,6 +106,7 @@ pub trait BlockT: Send + Any {
     // ##### MESSAGE IO
     fn message_input_name_to_id(&self, name: &str) -> Option<usize>;
     fn message_input_names(&self) -> Vec<String>;
+    fn message_input_descriptions(&self) -> Vec<HandlerDescription>;
     fn message_outputs(&self) -> &Vec<MessageOutput>;
     fn message_outputs_mut(&mut self) -> &mut Vec<MessageOutput>;
     fn message_output(&self, id: usize) -> &MessageOutput;
@@ -110,6 +114,12 @@ pub trait BlockT: Send + Any {
     fn message_output_name_to_id(&self, name: &str) -> Option<usize>;
 
     async fn call_handler(&mut self, id: PortId, p: Pmt) -> result::Result<Pmt, HandlerError>;
+    fn batch_input(&self, id: &PortId) -> Option<usize>;
+    async fn call_batch_handler(
+        &mut self,
+        id: usize,
+        p: Vec<Pmt>,
+    ) -> result::Result<Pmt, HandlerError>;
 }
 
 pub struct TypedBlock<T> {
@@ -145,6 +155,9 @@ impl<T: Kernel + Send + 'static> BlockT for TypedBlock<T> {
 
     // ##### KERNEL
     async fn work(&mut self, io: &mut WorkIo) -> Result<()> {
+        if self.meta.is_in_place() {
+            self.sio.forward_in_place();
+        }
         self.kernel
             .work(io, &mut self.sio, &mut self.mio, &mut self.meta)
             .await
@@ -152,7 +165,8 @@ impl<T: Kernel + Send + 'static> BlockT for TypedBlock<T> {
     async fn init(&mut self) -> Result<()> {
         self.kernel
             .init(&mut self.sio, &mut self.mio, &mut self.meta)
-            .await
+            .await?;
+        self.sio.check_buffers()
     }
     async fn deinit(&mut self) -> Result<()> {
         self.kernel
@@ -164,6 +178,12 @@ impl<T: Kernel + Send + 'static> BlockT for TypedBlock<T> {
     fn commit(&mut self) {
         self.sio.commmit();
     }
+    fn flush(&mut self) {
+        self.sio.flush();
+    }
+    fn stream_ready(&mut self) -> bool {
+        self.sio.ready()
+    }
     fn set_tag_propagation(
         &mut self,
         f: Box<dyn FnMut(&mut [StreamInput], &mut [StreamOutput]) + Send + 'static>,
@@ -208,6 +228,9 @@ impl<T: Kernel + Send + 'static> BlockT for TypedBlock<T> {
     fn message_input_names(&self) -> Vec<String> {
         self.mio.input_names()
     }
+    fn message_input_descriptions(&self) -> Vec<HandlerDescription> {
+        self.mio.input_descriptions()
+    }
     fn message_outputs(&self) -> &Vec<MessageOutput> {
         self.mio.outputs()
     }
@@ -251,6 +274,30 @@ impl<T: Kernel + Send + 'static> BlockT for TypedBlock<T> {
         let f = (h)(&mut self.kernel, &mut self.mio, &mut self.meta, p);
         f.await.or(Err(HandlerError::HandlerError))
     }
+    fn batch_input(&self, id: &PortId) -> Option<usize> {
+        let id = match id {
+            PortId::Index(i) => *i,
+            PortId::Name(n) => self.mio.input_name_to_id(n)?,
+        };
+        if id < self.mio.inputs().len() && self.mio.input(id).is_batch() {
+            Some(id)
+        } else {
+            None
+        }
+    }
+    async fn call_batch_handler(
+        &mut self,
+        id: usize,
+        p: Vec<Pmt>,
+    ) -> result::Result<Pmt, HandlerError> {
+        let h = self
+            .mio
+            .input(id)
+            .get_batch_handler()
+            .ok_or(HandlerError::InvalidHandler)?;
+        let f = (h)(&mut self.kernel, &mut self.mio, &mut self.meta, p);
+        f.await.or(Err(HandlerError::HandlerError))
+    }
 }
 
 #[derive(Debug)]
@@ -314,6 +361,12 @@ impl Block {
     pub fn commit(&mut self) {
         self.0.commit();
     }
+    pub fn flush(&mut self) {
+        self.0.flush();
+    }
+    pub fn stream_ready(&mut self) -> bool {
+        self.0.stream_ready()
+    }
     #[allow(clippy::type_complexity)]
     pub fn set_tag_propagation(
         &mut self,
@@ -359,6 +412,9 @@ impl Block {
     pub fn message_input_names(&self) -> Vec<String> {
         self.0.message_input_names()
     }
+    pub fn message_input_descriptions(&self) -> Vec<HandlerDescription> {
+        self.0.message_input_descriptions()
+    }
     pub fn message_outputs(&self) -> &Vec<MessageOutput> {
         self.0.message_outputs()
     }
@@ -377,6 +433,17 @@ impl Block {
     pub async fn call_handler(&mut self, id: PortId, p: Pmt) -> result::Result<Pmt, HandlerError> {
         self.0.call_handler(id, p).await
     }
+    /// Id of the input port, if it has a batch handler.
+    pub fn batch_input(&self, id: &PortId) -> Option<usize> {
+        self.0.batch_input(id)
+    }
+    pub async fn call_batch_handler(
+        &mut self,
+        id: usize,
+        p: Vec<Pmt>,
+    ) -> result::Result<Pmt, HandlerError> {
+        self.0.call_batch_handler(id, p).await
+    }
 }
 
 impl<T: Kernel + Send + 'static> fmt::Debug for TypedBlock<T> {
@@ -2,14 +2,16 @@ pub struct BlockMeta {
     type_name: String,
     instance_name: Option<String>,
     blocking: bool,
+    in_place: bool,
 }
 
 impl BlockMeta {
-    fn new(type_name: String, blocking: bool) -> BlockMeta {
+    fn new(type_name: String, blocking: bool, in_place: bool) -> BlockMeta {
         BlockMeta {
             type_name,
             instance_name: None,
             blocking,
+            in_place,
         }
     }
 
@@ -25,6 +27,10 @@ impl BlockMeta {
         self.blocking
     }
 
+    pub fn is_in_place(&self) -> bool {
+        self.in_place
+    }
+
     pub fn set_instance_name(&mut self, name: impl Into<String>) {
         self.instance_name = Some(name.into());
     }
@@ -33,6 +39,7 @@ impl BlockMeta {
 pub struct BlockMetaBuilder {
     name: String,
     blocking: bool,
+    in_place: bool,
 }
 
 impl BlockMetaBuilder {
@@ -40,6 +47,7 @@ impl BlockMetaBuilder {
         BlockMetaBuilder {
             name: name.into(),
             blocking: false,
+            in_place: false,
         }
     }
 
@@ -49,6 +57,19 @@ impl BlockMetaBuilder {
         self
     }
 
+    /// Advertise that the block transforms its single input 1:1 into its single output.
+    ///
+    /// If the input and output are connected with [Slab](crate::runtime::buffer::slab::Slab)
+    /// buffers and have the same item size, the runtime hands the upstream buffer to the
+    /// block's output, i.e., input and output slices refer to the same memory. The kernel has
+    /// to consume and produce the same number of items and read each item before writing the
+    /// output at the same index.
+    #[must_use]
+    pub fn in_place(mut self) -> Self {
+        self.in_place = true;
+        self
+    }
+
     #[must_use]
     pub fn name(mut self, name: &str) -> Self {
         self.name = name.to_string();
@@ -56,6 +77,6 @@ impl BlockMetaBuilder {
     }
 
     pub fn build(self) -> BlockMeta {
-        BlockMeta::new(self.name, self.blocking)
+        BlockMeta::new(self.name, self.blocking, self.in_place)
     }
 }
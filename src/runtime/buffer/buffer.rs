@@ -1,8 +1,9 @@
-use futures::channel::mpsc::Sender;
+use crate::runtime::channel::Sender;
 use std::any::Any;
 use std::fmt::Debug;
 use std::usize;
 
+use crate::anyhow::Result;
 use crate::runtime::BlockMessage;
 use crate::runtime::ItemTag;
 
@@ -29,6 +30,20 @@ pub trait BufferWriterHost: Send + Any + Debug {
 
     fn bytes(&mut self) -> (*mut u8, usize);
 
+    /// Check that the writer can make progress, if it only produces multiples of
+    /// `item_multiple` items.
+    ///
+    /// Called after `init()`, before the flowgraph runs, so buffers may adapt to the
+    /// requirement. Buffers that are drained eventually accept all multiples.
+    fn set_item_multiple(&mut self, _item_multiple: usize) -> Result<()> {
+        Ok(())
+    }
+
+    /// Notify readers about produced items, whose notification was deferred.
+    ///
+    /// Called by the runtime, before the block waits for new events.
+    fn flush(&mut self) {}
+
     async fn notify_finished(&mut self);
 
     fn finish(&mut self);
@@ -92,6 +107,19 @@ impl BufferWriter {
         }
     }
 
+    pub fn set_item_multiple(&mut self, item_multiple: usize) -> Result<()> {
+        match self {
+            BufferWriter::Host(w) => w.set_item_multiple(item_multiple),
+            BufferWriter::Custom(_) => Ok(()),
+        }
+    }
+
+    pub fn flush(&mut self) {
+        if let BufferWriter::Host(w) = self {
+            w.flush();
+        }
+    }
+
     pub async fn notify_finished(&mut self) {
         match self {
             BufferWriter::Host(w) => w.notify_finished().await,
@@ -122,6 +150,15 @@ pub trait BufferReaderHost: Send + Any + Debug {
 
     fn consume(&mut self, amount: usize);
 
+    /// Check that the reader can make progress, if it waits for at least `min_items` items and
+    /// only consumes multiples of `item_multiple` items.
+    ///
+    /// Called after `init()`, before the flowgraph runs, so buffers may adapt to the
+    /// requirements. Buffers that cannot tell accept all requirements.
+    fn check_min_items(&mut self, _min_items: usize, _item_multiple: usize) -> Result<()> {
+        Ok(())
+    }
+
     async fn notify_finished(&mut self);
 
     fn finish(&mut self);
@@ -161,6 +198,13 @@ impl BufferReader {
         }
     }
 
+    pub fn check_min_items(&mut self, min_items: usize, item_multiple: usize) -> Result<()> {
+        match self {
+            BufferReader::Host(w) => w.check_min_items(min_items, item_multiple),
+            BufferReader::Custom(_) => Ok(()),
+        }
+    }
+
     pub fn try_as<W: 'static>(&mut self) -> Option<&mut W> {
         match self {
             BufferReader::Host(w) => w.as_any().downcast_mut::<W>(),
@@ -1,9 +1,27 @@
-use futures::channel::mpsc::Sender;
+//! Circular Buffers
+//!
+//! Circular buffers hand out contiguous slices, even if they wrap around the end of the
+//! buffer. By default, this is achieved with a [double mapping](Mapping::DoubleMapped), i.e.,
+//! the buffer memory is mapped twice, back to back, and the MMU takes care of the
+//! wrap-around. If the platform does not support it, buffers fall back to a
+//! [copy](Mapping::Copy) of the memory, which is updated when items are produced.
+//!
+//! The mapping is set per buffer with [Circular::with_mapping] or for all buffers with the
+//! `circular_mapping` config option (`auto`, `double_mapped`, or `copy`).
+use crate::runtime::channel::Sender;
 use futures::prelude::*;
+use once_cell::sync::Lazy;
+use slab::Slab;
+use std::alloc::Layout;
 use std::any::Any;
 use std::fmt;
-use vmcircbuffer::generic;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::Mutex;
 
+use crate::anyhow::{bail, Result};
+use crate::runtime::buffer::double_mapped;
+use crate::runtime::buffer::double_mapped::DoubleMapped;
 use crate::runtime::buffer::BufferBuilder;
 use crate::runtime::buffer::BufferReader;
 use crate::runtime::buffer::BufferReaderHost;
@@ -13,50 +31,49 @@ use crate::runtime::config;
 use crate::runtime::BlockMessage;
 use crate::runtime::ItemTag;
 
-// everything is measured in items, e.g., offsets, capacity, space available
-
-struct MyNotifier {
-    sender: Sender<BlockMessage>,
+/// How a [Circular] buffer handles the wrap-around.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Mapping {
+    /// Double mapping, if it is supported, copy otherwise.
+    Auto,
+    /// Map the memory twice, back to back.
+    DoubleMapped,
+    /// Mirror produced items in a second copy of the buffer.
+    Copy,
 }
 
-impl generic::Notifier for MyNotifier {
-    fn arm(&mut self) {}
+impl FromStr for Mapping {
+    type Err = String;
 
-    fn notify(&mut self) {
-        let _ = self.sender.try_send(BlockMessage::Notify);
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "auto" => Ok(Mapping::Auto),
+            "double_mapped" | "double-mapped" => Ok(Mapping::DoubleMapped),
+            "copy" => Ok(Mapping::Copy),
+            _ => Err(format!("unknown circular buffer mapping {s}")),
+        }
     }
 }
 
-struct MyMetadata {
-    tags: Vec<ItemTag>,
-}
-
-impl generic::Metadata for MyMetadata {
-    type Item = ItemTag;
-
-    fn new() -> Self {
-        MyMetadata { tags: Vec::new() }
-    }
-    fn add(&mut self, offset: usize, mut tags: Vec<Self::Item>) {
-        for t in tags.iter_mut() {
-            t.index += offset;
-        }
-        self.tags.append(&mut tags);
-    }
-    fn get(&self) -> Vec<Self::Item> {
-        self.tags.clone()
-    }
-    fn consume(&mut self, items: usize) {
-        self.tags.retain(|x| x.index >= items);
-        for t in self.tags.iter_mut() {
-            t.index -= items;
+static DOUBLE_MAPPING: Lazy<bool> =
+    Lazy::new(|| match DoubleMapped::new(double_mapped::granularity()) {
+        Ok(_) => true,
+        Err(e) => {
+            warn!("double-mapped buffers not supported ({e}), falling back to copy");
+            false
         }
-    }
+    });
+
+/// Whether the platform supports double-mapped buffers.
+pub fn double_mapping_supported() -> bool {
+    *DOUBLE_MAPPING
 }
 
 #[derive(Clone, Debug, PartialEq, Hash)]
 pub struct Circular {
     min_bytes: usize,
+    notify_bytes: usize,
+    mapping: Mapping,
 }
 
 impl Eq for Circular {}
@@ -65,10 +82,39 @@ impl Circular {
     pub fn new() -> Circular {
         Circular {
             min_bytes: config::config().buffer_size,
+            notify_bytes: config::config().notify_threshold,
+            mapping: config::get_or_default("circular_mapping", Mapping::Auto),
         }
     }
     pub fn with_size(min_bytes: usize) -> Circular {
-        Circular { min_bytes }
+        Circular {
+            min_bytes,
+            ..Circular::new()
+        }
+    }
+
+    /// Coalesce wakeups of the readers, until at least `bytes` were produced.
+    ///
+    /// Produced items, which did not reach the threshold, are announced once the writing block
+    /// waits for new events or finishes. Blocks that keep setting `call_again`, like a source
+    /// that polls a device for a few samples at a time, do not wait, so their readers only see
+    /// the items once the threshold is reached. Keep the threshold at 0 for edges, where this
+    /// latency matters. The default is set through the `notify_threshold` config option (0,
+    /// i.e., wake up on every produce).
+    #[must_use]
+    pub fn with_notify_threshold(mut self, bytes: usize) -> Circular {
+        self.notify_bytes = bytes;
+        self
+    }
+
+    /// Set how the buffer handles the wrap-around.
+    ///
+    /// [Mapping::DoubleMapped] also falls back to [Mapping::Copy] if the mapping fails, but
+    /// logs a warning.
+    #[must_use]
+    pub fn with_mapping(mut self, mapping: Mapping) -> Circular {
+        self.mapping = mapping;
+        self
     }
 }
 
@@ -85,22 +131,117 @@ impl BufferBuilder for Circular {
         writer_inbox: Sender<BlockMessage>,
         writer_output_id: usize,
     ) -> BufferWriter {
-        BufferWriter::Host(Box::new(Writer::new(
+        let mut w = Writer::with_mapping(
             item_size,
             self.min_bytes,
+            self.mapping,
             writer_inbox,
             writer_output_id,
-        )))
+        );
+        w.notify_bytes = self.notify_bytes;
+        BufferWriter::Host(Box::new(w))
     }
 }
 
+enum Memory {
+    DoubleMapped(DoubleMapped),
+    Copy(*mut u8, Layout),
+}
+
+/// Buffer memory of `2 * size` bytes, where the second half mirrors the first one.
+struct Ring {
+    memory: Memory,
+    size: usize,
+}
+
+unsafe impl Send for Ring {}
+unsafe impl Sync for Ring {}
+
+impl Ring {
+    fn new(size: usize, mapping: Mapping) -> Ring {
+        if mapping == Mapping::DoubleMapped
+            || (mapping == Mapping::Auto && double_mapping_supported())
+        {
+            match DoubleMapped::new(size) {
+                Ok(m) => {
+                    return Ring {
+                        memory: Memory::DoubleMapped(m),
+                        size,
+                    }
+                }
+                Err(e) => warn!("failed to double-map buffer ({e}), falling back to copy"),
+            }
+        }
+
+        let layout = Layout::from_size_align(2 * size, double_mapped::granularity()).unwrap();
+        let ptr = unsafe { std::alloc::alloc_zeroed(layout) };
+        if ptr.is_null() {
+            std::alloc::handle_alloc_error(layout);
+        }
+        Ring {
+            memory: Memory::Copy(ptr, layout),
+            size,
+        }
+    }
+
+    fn mapping(&self) -> Mapping {
+        match self.memory {
+            Memory::DoubleMapped(_) => Mapping::DoubleMapped,
+            Memory::Copy(..) => Mapping::Copy,
+        }
+    }
+
+    fn ptr(&self) -> *mut u8 {
+        match self.memory {
+            Memory::DoubleMapped(ref m) => m.as_ptr(),
+            Memory::Copy(p, _) => p,
+        }
+    }
+
+    /// Mirror `n` bytes, written at `offset`, to the other half of the buffer.
+    fn commit(&self, offset: usize, n: usize) {
+        if let Memory::Copy(p, _) = self.memory {
+            debug_assert!(offset < self.size && n <= self.size);
+            unsafe {
+                let first = n.min(self.size - offset);
+                std::ptr::copy_nonoverlapping(p.add(offset), p.add(offset + self.size), first);
+                std::ptr::copy_nonoverlapping(p.add(self.size), p, n - first);
+            }
+        }
+    }
+}
+
+impl Drop for Ring {
+    fn drop(&mut self) {
+        if let Memory::Copy(p, layout) = self.memory {
+            unsafe { std::alloc::dealloc(p, layout) };
+        }
+    }
+}
+
+// everything is measured in bytes, e.g., offsets, capacity, space available
+
+struct ReaderState {
+    read: u64,
+    // tag indices are relative to `read`
+    tags: Vec<ItemTag>,
+}
+
+struct State {
+    written: u64,
+    readers: Slab<ReaderState>,
+}
+
 pub struct Writer {
-    writer: generic::Writer<u8, MyNotifier, MyMetadata>,
+    ring: Arc<Ring>,
+    state: Arc<Mutex<State>>,
     readers: Vec<(Sender<BlockMessage>, usize)>,
     item_size: usize,
     inbox: Sender<BlockMessage>,
     output_id: usize,
     finished: bool,
+    notify_bytes: usize,
+    pending_bytes: usize,
 }
 
 impl Writer {
@@ -110,20 +251,60 @@ impl Writer {
         inbox: Sender<BlockMessage>,
         output_id: usize,
     ) -> Writer {
-        let page_size = vmcircbuffer::double_mapped_buffer::pagesize();
-        let mut buffer_size = page_size;
+        Self::with_mapping(
+            item_size,
+            min_bytes,
+            config::get_or_default("circular_mapping", Mapping::Auto),
+            inbox,
+            output_id,
+        )
+    }
+
+    pub fn with_mapping(
+        item_size: usize,
+        min_bytes: usize,
+        mapping: Mapping,
+        inbox: Sender<BlockMessage>,
+        output_id: usize,
+    ) -> Writer {
+        let granularity = double_mapped::granularity();
+        let mut buffer_size = granularity;
 
         while (buffer_size < min_bytes) || (buffer_size % item_size != 0) {
-            buffer_size += page_size;
+            buffer_size += granularity;
         }
 
+        let ring = Ring::new(buffer_size, mapping);
+        #[cfg(target_os = "linux")]
+        crate::runtime::realtime::prefault(unsafe {
+            std::slice::from_raw_parts_mut(ring.ptr(), 2 * buffer_size)
+        });
+
         Writer {
-            writer: generic::Circular::with_capacity(buffer_size).unwrap(),
+            ring: Arc::new(ring),
+            state: Arc::new(Mutex::new(State {
+                written: 0,
+                readers: Slab::new(),
+            })),
             readers: Vec::new(),
             item_size,
             inbox,
             output_id,
             finished: false,
+            notify_bytes: 0,
+            pending_bytes: 0,
+        }
+    }
+
+    /// How the buffer handles the wrap-around, i.e., [Mapping::DoubleMapped] or
+    /// [Mapping::Copy].
+    pub fn mapping(&self) -> Mapping {
+        self.ring.mapping()
+    }
+
+    fn notify_readers(&mut self) {
+        for (inbox, _) in self.readers.iter_mut() {
+            let _ = inbox.try_send(BlockMessage::Notify);
         }
     }
 }
@@ -133,6 +314,7 @@ impl fmt::Debug for Writer {
         f.debug_struct("circular::Writer")
             .field("item_size", &self.item_size)
             .field("output_id", &self.output_id)
+            .field("mapping", &self.mapping())
             .field("finished", &self.finished)
             .finish()
     }
@@ -141,20 +323,21 @@ impl fmt::Debug for Writer {
 #[async_trait]
 impl BufferWriterHost for Writer {
     fn add_reader(&mut self, inbox: Sender<BlockMessage>, input_id: usize) -> BufferReader {
-        let writer_notifier = MyNotifier {
-            sender: self.inbox.clone(),
-        };
-
-        let reader_notifier = MyNotifier {
-            sender: inbox.clone(),
+        let id = {
+            let mut state = self.state.lock().unwrap();
+            let read = state.written;
+            state.readers.insert(ReaderState {
+                read,
+                tags: Vec::new(),
+            })
         };
 
-        let reader = self.writer.add_reader(reader_notifier, writer_notifier);
-
         self.readers.push((inbox, input_id));
 
         BufferReader::Host(Box::new(Reader {
-            reader,
+            id,
+            ring: self.ring.clone(),
+            state: self.state.clone(),
             item_size: self.item_size,
             finished: false,
             writer_inbox: self.inbox.clone(),
@@ -166,16 +349,53 @@ impl BufferWriterHost for Writer {
         self
     }
 
-    fn produce(&mut self, items: usize, mut tags: Vec<ItemTag>) {
-        for t in tags.iter_mut() {
-            t.index *= self.item_size;
+    fn produce(&mut self, items: usize, tags: Vec<ItemTag>) {
+        if items == 0 {
+            return;
+        }
+        let bytes = items * self.item_size;
+
+        {
+            let mut state = self.state.lock().unwrap();
+            let written = state.written;
+            // mirror, before readers can see the items
+            self.ring
+                .commit((written % self.ring.size as u64) as usize, bytes);
+            for (_, r) in state.readers.iter_mut() {
+                let offset = (written - r.read) as usize;
+                r.tags.extend(tags.iter().map(|t| ItemTag {
+                    index: t.index * self.item_size + offset,
+                    tag: t.tag.clone(),
+                }));
+            }
+            state.written += bytes as u64;
+        }
+
+        self.pending_bytes += bytes;
+        if self.pending_bytes >= self.notify_bytes {
+            self.pending_bytes = 0;
+            self.notify_readers();
+        }
+    }
+
+    fn flush(&mut self) {
+        if self.pending_bytes > 0 {
+            self.pending_bytes = 0;
+            self.notify_readers();
         }
-        self.writer.produce(items * self.item_size, tags);
     }
 
     fn bytes(&mut self) -> (*mut u8, usize) {
-        let s = self.writer.slice(false);
-        (s.as_mut_ptr(), s.len())
+        let state = self.state.lock().unwrap();
+        let min_read = state
+            .readers
+            .iter()
+            .map(|(_, r)| r.read)
+            .min()
+            .unwrap_or(state.written);
+        let space = self.ring.size - (state.written - min_read) as usize;
+        let offset = (state.written % self.ring.size as u64) as usize;
+        (unsafe { self.ring.ptr().add(offset) }, space)
     }
 
     async fn notify_finished(&mut self) {
@@ -183,6 +403,7 @@ impl BufferWriterHost for Writer {
             return;
         }
 
+        self.pending_bytes = 0;
         for i in self.readers.iter_mut() {
             let _ =
                 i.0.send(BlockMessage::StreamInputDone { input_id: i.1 })
@@ -199,12 +420,10 @@ impl BufferWriterHost for Writer {
     }
 }
 
-#[allow(clippy::non_send_fields_in_send_ty)]
-unsafe impl Send for Writer {}
-unsafe impl Sync for Writer {}
-
 pub struct Reader {
-    reader: generic::Reader<u8, MyNotifier, MyMetadata>,
+    id: usize,
+    ring: Arc<Ring>,
+    state: Arc<Mutex<State>>,
     item_size: usize,
     finished: bool,
     writer_inbox: Sender<BlockMessage>,
@@ -218,18 +437,49 @@ impl BufferReaderHost for Reader {
     }
 
     fn bytes(&mut self) -> (*const u8, usize, Vec<ItemTag>) {
-        if let Some((s, mut tags)) = self.reader.slice(false) {
-            for t in tags.iter_mut() {
-                t.index /= self.item_size;
+        let state = self.state.lock().unwrap();
+        let r = &state.readers[self.id];
+        let available = (state.written - r.read) as usize;
+        let offset = (r.read % self.ring.size as u64) as usize;
+        let tags = r
+            .tags
+            .iter()
+            .map(|t| ItemTag {
+                index: t.index / self.item_size,
+                tag: t.tag.clone(),
+            })
+            .collect();
+        (unsafe { self.ring.ptr().add(offset) }, available, tags)
+    }
+
+    fn consume(&mut self, amount: usize) {
+        if amount == 0 {
+            return;
+        }
+        let bytes = amount * self.item_size;
+        {
+            let mut state = self.state.lock().unwrap();
+            let r = &mut state.readers[self.id];
+            r.read += bytes as u64;
+            r.tags.retain(|t| t.index >= bytes);
+            for t in r.tags.iter_mut() {
+                t.index -= bytes;
             }
-            (s.as_ptr(), s.len(), tags)
-        } else {
-            (std::ptr::null(), 0, Vec::new())
         }
+        let _ = self.writer_inbox.try_send(BlockMessage::Notify);
     }
 
-    fn consume(&mut self, amount: usize) {
-        self.reader.consume(amount * self.item_size);
+    fn check_min_items(&mut self, min_items: usize, item_multiple: usize) -> Result<()> {
+        let capacity = self.ring.size / self.item_size;
+        let min = min_items.max(item_multiple);
+        if capacity < min {
+            bail!(
+                "circular buffer holds {} items, but the reader requires {}",
+                capacity,
+                min
+            );
+        }
+        Ok(())
     }
 
     async fn notify_finished(&mut self) {
@@ -255,6 +505,14 @@ impl BufferReaderHost for Reader {
     }
 }
 
+impl Drop for Reader {
+    fn drop(&mut self) {
+        // do not hold the writer back
+        self.state.lock().unwrap().readers.remove(self.id);
+        let _ = self.writer_inbox.try_send(BlockMessage::Notify);
+    }
+}
+
 impl fmt::Debug for Reader {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("circular::Reader")
@@ -264,6 +522,3 @@ impl fmt::Debug for Reader {
             .finish()
     }
 }
-
-unsafe impl Send for Reader {}
-unsafe impl Sync for Reader {}
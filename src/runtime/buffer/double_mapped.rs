@@ -0,0 +1,349 @@
+//! Double-Mapped Memory
+//!
+//! The same physical memory is mapped twice, back to back, into the address space. Reads and
+//! writes that run over the end of the first mapping continue at the start of the buffer,
+//! i.e., the MMU handles the wrap-around of a circular buffer.
+//!
+//! - Linux and Android: anonymous file from `memfd_create`, mapped twice with `mmap`.
+//! - macOS and iOS: `vm_remap` of the first half of a Mach VM allocation onto the second half.
+//! - Windows: a pagefile-backed section, mapped into a split placeholder with `MapViewOfFile3`
+//!   (Windows 10, version 1803 or later).
+//! - Other Unix systems: POSIX shared memory object, mapped twice with `mmap`.
+use std::io;
+
+/// Memory region of `2 * size` bytes, where the second half mirrors the first one.
+pub(crate) struct DoubleMapped {
+    ptr: *mut u8,
+    size: usize,
+}
+
+unsafe impl Send for DoubleMapped {}
+unsafe impl Sync for DoubleMapped {}
+
+impl DoubleMapped {
+    /// Map `size` bytes twice. The size has to be a multiple of the [granularity].
+    pub(crate) fn new(size: usize) -> io::Result<DoubleMapped> {
+        if size == 0 || size % granularity() != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "size has to be a multiple of the allocation granularity",
+            ));
+        }
+        let ptr = unsafe { imp::map(size)? };
+        Ok(DoubleMapped { ptr, size })
+    }
+
+    /// Start of the first mapping.
+    pub(crate) fn as_ptr(&self) -> *mut u8 {
+        self.ptr
+    }
+}
+
+impl Drop for DoubleMapped {
+    fn drop(&mut self) {
+        unsafe { imp::unmap(self.ptr, self.size) };
+    }
+}
+
+/// Granularity of the size of double-mapped regions.
+pub(crate) fn granularity() -> usize {
+    imp::granularity()
+}
+
+#[cfg(unix)]
+mod imp {
+    use std::io;
+
+    pub fn granularity() -> usize {
+        unsafe { libc::sysconf(libc::_SC_PAGESIZE) }.max(1) as usize
+    }
+
+    /// Map the file twice into a reserved region of `2 * size` bytes.
+    #[cfg(not(any(target_os = "macos", target_os = "ios")))]
+    unsafe fn map_fd(fd: i32, size: usize) -> io::Result<*mut u8> {
+        if libc::ftruncate(fd, size as libc::off_t) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let base = libc::mmap(
+            std::ptr::null_mut(),
+            2 * size,
+            libc::PROT_NONE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        );
+        if base == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+
+        for i in 0..2 {
+            let addr = libc::mmap(
+                (base as *mut u8).add(i * size) as *mut libc::c_void,
+                size,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED | libc::MAP_FIXED,
+                fd,
+                0,
+            );
+            if addr == libc::MAP_FAILED {
+                let e = io::Error::last_os_error();
+                libc::munmap(base, 2 * size);
+                return Err(e);
+            }
+        }
+
+        Ok(base as *mut u8)
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub unsafe fn map(size: usize) -> io::Result<*mut u8> {
+        let name = b"futuresdr-circular\0";
+        let fd = libc::syscall(libc::SYS_memfd_create, name.as_ptr(), libc::MFD_CLOEXEC) as i32;
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let ret = map_fd(fd, size);
+        libc::close(fd);
+        ret
+    }
+
+    #[cfg(not(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "macos",
+        target_os = "ios"
+    )))]
+    pub unsafe fn map(size: usize) -> io::Result<*mut u8> {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let name = std::ffi::CString::new(format!(
+            "/futuresdr-{}-{}",
+            libc::getpid(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ))
+        .unwrap();
+        let fd = libc::shm_open(
+            name.as_ptr(),
+            libc::O_RDWR | libc::O_CREAT | libc::O_EXCL,
+            0o600,
+        );
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        libc::shm_unlink(name.as_ptr());
+        let ret = map_fd(fd, size);
+        libc::close(fd);
+        ret
+    }
+
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    #[allow(non_camel_case_types, non_upper_case_globals)]
+    mod mach {
+        pub type kern_return_t = libc::c_int;
+        pub type mach_port_t = libc::c_uint;
+        pub type vm_address_t = usize;
+        pub type vm_size_t = usize;
+        pub type vm_prot_t = libc::c_int;
+        pub type vm_inherit_t = libc::c_uint;
+
+        pub const KERN_SUCCESS: kern_return_t = 0;
+        pub const VM_FLAGS_FIXED: libc::c_int = 0x0000;
+        pub const VM_FLAGS_ANYWHERE: libc::c_int = 0x0001;
+        pub const VM_FLAGS_OVERWRITE: libc::c_int = 0x4000;
+        pub const VM_INHERIT_NONE: vm_inherit_t = 2;
+
+        extern "C" {
+            pub static mach_task_self_: mach_port_t;
+
+            pub fn vm_allocate(
+                target_task: mach_port_t,
+                address: *mut vm_address_t,
+                size: vm_size_t,
+                flags: libc::c_int,
+            ) -> kern_return_t;
+
+            pub fn vm_deallocate(
+                target_task: mach_port_t,
+                address: vm_address_t,
+                size: vm_size_t,
+            ) -> kern_return_t;
+
+            pub fn vm_remap(
+                target_task: mach_port_t,
+                target_address: *mut vm_address_t,
+                size: vm_size_t,
+                mask: vm_address_t,
+                flags: libc::c_int,
+                src_task: mach_port_t,
+                src_address: vm_address_t,
+                copy: libc::c_int,
+                cur_protection: *mut vm_prot_t,
+                max_protection: *mut vm_prot_t,
+                inheritance: vm_inherit_t,
+            ) -> kern_return_t;
+        }
+    }
+
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    pub unsafe fn map(size: usize) -> io::Result<*mut u8> {
+        use mach::*;
+
+        let task = mach_task_self_;
+        let mut base: vm_address_t = 0;
+        if vm_allocate(task, &mut base, 2 * size, VM_FLAGS_ANYWHERE) != KERN_SUCCESS {
+            return Err(io::Error::new(io::ErrorKind::Other, "vm_allocate failed"));
+        }
+
+        let mut mirror = base + size;
+        let mut cur = 0;
+        let mut max = 0;
+        let ret = vm_remap(
+            task,
+            &mut mirror,
+            size,
+            0,
+            VM_FLAGS_FIXED | VM_FLAGS_OVERWRITE,
+            task,
+            base,
+            0,
+            &mut cur,
+            &mut max,
+            VM_INHERIT_NONE,
+        );
+        if ret != KERN_SUCCESS || mirror != base + size {
+            vm_deallocate(task, base, 2 * size);
+            return Err(io::Error::new(io::ErrorKind::Other, "vm_remap failed"));
+        }
+
+        Ok(base as *mut u8)
+    }
+
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    pub unsafe fn unmap(ptr: *mut u8, size: usize) {
+        mach::vm_deallocate(mach::mach_task_self_, ptr as usize, 2 * size);
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "ios")))]
+    pub unsafe fn unmap(ptr: *mut u8, size: usize) {
+        libc::munmap(ptr as *mut libc::c_void, 2 * size);
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use std::io;
+    use windows_sys::Win32::Foundation::CloseHandle;
+    use windows_sys::Win32::Foundation::INVALID_HANDLE_VALUE;
+    use windows_sys::Win32::System::Memory::CreateFileMappingW;
+    use windows_sys::Win32::System::Memory::MapViewOfFile3;
+    use windows_sys::Win32::System::Memory::UnmapViewOfFile;
+    use windows_sys::Win32::System::Memory::VirtualAlloc2;
+    use windows_sys::Win32::System::Memory::VirtualFree;
+    use windows_sys::Win32::System::Memory::MEM_PRESERVE_PLACEHOLDER;
+    use windows_sys::Win32::System::Memory::MEM_RELEASE;
+    use windows_sys::Win32::System::Memory::MEM_REPLACE_PLACEHOLDER;
+    use windows_sys::Win32::System::Memory::MEM_RESERVE;
+    use windows_sys::Win32::System::Memory::MEM_RESERVE_PLACEHOLDER;
+    use windows_sys::Win32::System::Memory::PAGE_NOACCESS;
+    use windows_sys::Win32::System::Memory::PAGE_READWRITE;
+    use windows_sys::Win32::System::SystemInformation::GetSystemInfo;
+    use windows_sys::Win32::System::Threading::GetCurrentProcess;
+
+    pub fn granularity() -> usize {
+        unsafe {
+            let mut info = std::mem::zeroed();
+            GetSystemInfo(&mut info);
+            info.dwAllocationGranularity as usize
+        }
+    }
+
+    pub unsafe fn map(size: usize) -> io::Result<*mut u8> {
+        let process = GetCurrentProcess();
+
+        // reserve a placeholder for both views and split it in two
+        let base = VirtualAlloc2(
+            process,
+            std::ptr::null(),
+            2 * size,
+            MEM_RESERVE | MEM_RESERVE_PLACEHOLDER,
+            PAGE_NOACCESS,
+            std::ptr::null_mut(),
+            0,
+        );
+        if base.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        VirtualFree(base, size, MEM_RELEASE | MEM_PRESERVE_PLACEHOLDER);
+
+        let section = CreateFileMappingW(
+            INVALID_HANDLE_VALUE,
+            std::ptr::null(),
+            PAGE_READWRITE,
+            (size as u64 >> 32) as u32,
+            size as u32,
+            std::ptr::null(),
+        );
+        if section == 0 {
+            let e = io::Error::last_os_error();
+            VirtualFree(base, 0, MEM_RELEASE);
+            VirtualFree((base as *mut u8).add(size) as _, 0, MEM_RELEASE);
+            return Err(e);
+        }
+
+        let mut views = [0; 2];
+        for (i, view) in views.iter_mut().enumerate() {
+            *view = MapViewOfFile3(
+                section,
+                process,
+                (base as *mut u8).add(i * size) as _,
+                0,
+                size,
+                MEM_REPLACE_PLACEHOLDER,
+                PAGE_READWRITE,
+                std::ptr::null_mut(),
+                0,
+            );
+        }
+        // the views keep the section alive
+        CloseHandle(section);
+
+        if views.iter().any(|v| *v == 0) {
+            let e = io::Error::last_os_error();
+            for (i, view) in views.iter().enumerate() {
+                if *view == 0 {
+                    VirtualFree((base as *mut u8).add(i * size) as _, 0, MEM_RELEASE);
+                } else {
+                    UnmapViewOfFile(*view);
+                }
+            }
+            return Err(e);
+        }
+
+        Ok(base as *mut u8)
+    }
+
+    pub unsafe fn unmap(ptr: *mut u8, size: usize) {
+        UnmapViewOfFile(ptr as _);
+        UnmapViewOfFile(ptr.add(size) as _);
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod imp {
+    use std::io;
+
+    pub fn granularity() -> usize {
+        4096
+    }
+
+    pub unsafe fn map(_size: usize) -> io::Result<*mut u8> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "double mapping is not supported on this platform",
+        ))
+    }
+
+    pub unsafe fn unmap(_ptr: *mut u8, _size: usize) {}
+}
@@ -0,0 +1,187 @@
+//! Memory for Host Stream Buffers
+//!
+//! Stream buffers that manage their own memory, like the [Slab](super::slab::Slab) buffer,
+//! allocate it through an [Allocator]. Besides the alignment of the buffers, it selects
+//! whether they are backed by 2 MB huge pages, which reduces TLB misses in flowgraphs with
+//! very high sample rates.
+//!
+//! The allocator can be set per edge, e.g., with
+//! [Slab::with_allocator](super::slab::Slab::with_allocator), or globally through the
+//! `buffer_alignment` and `buffer_huge_pages` config options.
+//! Double-mapped [Circular](super::circular::Circular) buffers are always page-aligned and
+//! backed by regular pages.
+use std::alloc::Layout;
+use std::ops::Deref;
+use std::ops::DerefMut;
+use std::ptr::NonNull;
+
+use crate::runtime::config;
+
+/// Size of a huge page.
+pub const HUGE_PAGE_SIZE: usize = 2 * 1024 * 1024;
+
+/// Memory allocation strategy for host stream buffers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Allocator {
+    /// Heap memory with the given alignment in bytes, which has to be a power of two.
+    Aligned(usize),
+    /// Memory backed by 2 MB huge pages.
+    ///
+    /// On Linux, buffers are mapped from the huge page pool (`vm.nr_hugepages`). If the pool
+    /// is exhausted, they fall back to 2 MB-aligned heap memory, advised to use transparent
+    /// huge pages. On other platforms, buffers are only aligned to 2 MB.
+    HugePages,
+}
+
+impl Allocator {
+    /// Allocator configured with the `buffer_alignment` and `buffer_huge_pages` options.
+    pub fn from_config() -> Allocator {
+        let c = config::config();
+        if c.buffer_huge_pages {
+            Allocator::HugePages
+        } else {
+            Allocator::Aligned(c.buffer_alignment)
+        }
+    }
+
+    /// Allocate a zeroed buffer of `len` bytes.
+    pub fn allocate(&self, len: usize) -> HostBuffer {
+        #[allow(unused_mut)]
+        let mut b = match self {
+            Allocator::Aligned(align) => HostBuffer::heap(len, *align),
+            Allocator::HugePages => HostBuffer::huge_pages(len),
+        };
+        #[cfg(target_os = "linux")]
+        crate::runtime::realtime::prefault(&mut b);
+        b
+    }
+}
+
+impl Default for Allocator {
+    fn default() -> Self {
+        Self::from_config()
+    }
+}
+
+#[derive(Debug)]
+enum Backing {
+    Heap(Layout),
+    #[cfg(target_os = "linux")]
+    Mapped(usize),
+}
+
+/// Zeroed host memory, allocated by an [Allocator].
+#[derive(Debug)]
+pub struct HostBuffer {
+    ptr: NonNull<u8>,
+    len: usize,
+    backing: Backing,
+}
+
+// The buffer exclusively owns its memory.
+unsafe impl Send for HostBuffer {}
+unsafe impl Sync for HostBuffer {}
+
+impl HostBuffer {
+    fn heap(len: usize, align: usize) -> HostBuffer {
+        let layout = Layout::from_size_align(len.max(1), align).expect("invalid buffer alignment");
+        let ptr = unsafe { std::alloc::alloc_zeroed(layout) };
+        let ptr = NonNull::new(ptr).unwrap_or_else(|| std::alloc::handle_alloc_error(layout));
+        HostBuffer {
+            ptr,
+            len,
+            backing: Backing::Heap(layout),
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    fn huge_pages(len: usize) -> HostBuffer {
+        let size = (len.max(1) + HUGE_PAGE_SIZE - 1) / HUGE_PAGE_SIZE * HUGE_PAGE_SIZE;
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                size,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS | libc::MAP_HUGETLB,
+                -1,
+                0,
+            )
+        };
+        if ptr != libc::MAP_FAILED {
+            return HostBuffer {
+                ptr: NonNull::new(ptr as *mut u8).unwrap(),
+                len,
+                backing: Backing::Mapped(size),
+            };
+        }
+
+        debug!("no huge pages available, falling back to transparent huge pages");
+        let mut b = HostBuffer::heap(size, HUGE_PAGE_SIZE);
+        unsafe {
+            libc::madvise(
+                b.ptr.as_ptr() as *mut libc::c_void,
+                size,
+                libc::MADV_HUGEPAGE,
+            );
+        }
+        b.len = len;
+        b
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn huge_pages(len: usize) -> HostBuffer {
+        HostBuffer::heap(len, HUGE_PAGE_SIZE)
+    }
+
+    /// Pointer to the start of the buffer.
+    ///
+    /// Like for [Vec], this does not create an intermediate reference, so pointers returned by
+    /// [as_ptr](Self::as_ptr) and [as_mut_ptr](Self::as_mut_ptr) stay valid next to each other.
+    /// In-place blocks rely on this, when the buffer is forwarded from the reader to the writer.
+    pub fn as_ptr(&self) -> *const u8 {
+        self.ptr.as_ptr()
+    }
+
+    /// Mutable pointer to the start of the buffer.
+    pub fn as_mut_ptr(&mut self) -> *mut u8 {
+        self.ptr.as_ptr()
+    }
+
+    /// Whether the buffer is mapped from the huge page pool.
+    pub fn is_huge_page_mapped(&self) -> bool {
+        #[cfg(target_os = "linux")]
+        {
+            matches!(self.backing, Backing::Mapped(_))
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            false
+        }
+    }
+}
+
+impl Deref for HostBuffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl DerefMut for HostBuffer {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl Drop for HostBuffer {
+    fn drop(&mut self) {
+        match self.backing {
+            Backing::Heap(layout) => unsafe { std::alloc::dealloc(self.ptr.as_ptr(), layout) },
+            #[cfg(target_os = "linux")]
+            Backing::Mapped(size) => unsafe {
+                libc::munmap(self.ptr.as_ptr() as *mut libc::c_void, size);
+            },
+        }
+    }
+}
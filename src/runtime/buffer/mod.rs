@@ -11,6 +11,11 @@ pub use buffer::BufferWriterHost;
 
 #[cfg(not(target_arch = "wasm32"))]
 pub mod circular;
+#[cfg(not(target_arch = "wasm32"))]
+mod double_mapped;
+
+// ==================== MEMORY =======================
+pub mod memory;
 
 // ===================== SLAB ========================
 pub mod slab;
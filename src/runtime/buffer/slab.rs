@@ -1,9 +1,12 @@
-use futures::channel::mpsc::Sender;
+use crate::runtime::channel::Sender;
 use futures::prelude::*;
 use std::any::Any;
 use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
 
+use crate::anyhow::{Context, Result};
+use crate::runtime::buffer::memory::Allocator;
+use crate::runtime::buffer::memory::HostBuffer;
 use crate::runtime::buffer::BufferBuilder;
 use crate::runtime::buffer::BufferReader;
 use crate::runtime::buffer::BufferReaderHost;
@@ -18,6 +21,7 @@ pub struct Slab {
     min_bytes: usize,
     n_buffer: usize,
     reserved_items: usize,
+    allocator: Allocator,
 }
 
 impl Eq for Slab {}
@@ -28,6 +32,7 @@ impl Slab {
             min_bytes: config::config().buffer_size,
             n_buffer: 2,
             reserved_items: config::config().slab_reserved,
+            allocator: Allocator::from_config(),
         }
     }
 
@@ -36,6 +41,7 @@ impl Slab {
             min_bytes,
             n_buffer: 2,
             reserved_items: config::config().slab_reserved,
+            allocator: Allocator::from_config(),
         }
     }
 
@@ -44,6 +50,7 @@ impl Slab {
             min_bytes: config::config().buffer_size,
             n_buffer,
             reserved_items: config::config().slab_reserved,
+            allocator: Allocator::from_config(),
         }
     }
 
@@ -52,8 +59,16 @@ impl Slab {
             min_bytes,
             n_buffer,
             reserved_items,
+            allocator: Allocator::from_config(),
         }
     }
+
+    /// Allocate the buffers with the given [Allocator], e.g., to use huge pages on this edge.
+    #[must_use]
+    pub fn with_allocator(mut self, allocator: Allocator) -> Slab {
+        self.allocator = allocator;
+        self
+    }
 }
 
 impl Default for Slab {
@@ -74,6 +89,7 @@ impl BufferBuilder for Slab {
             self.min_bytes,
             self.n_buffer,
             self.reserved_items,
+            self.allocator,
             writer_inbox,
             writer_output_id,
         )
@@ -82,12 +98,12 @@ impl BufferBuilder for Slab {
 
 #[derive(Debug)]
 struct BufferEmpty {
-    buffer: Box<[u8]>,
+    buffer: HostBuffer,
 }
 
 #[derive(Debug)]
 struct BufferFull {
-    buffer: Box<[u8]>,
+    buffer: HostBuffer,
     items: usize,
     tags: Vec<ItemTag>,
 }
@@ -96,7 +112,7 @@ struct BufferFull {
 
 #[derive(Debug)]
 struct CurrentBuffer {
-    buffer: Box<[u8]>,
+    buffer: HostBuffer,
     offset: usize,
     capacity: usize,
     tags: Vec<ItemTag>,
@@ -107,7 +123,9 @@ pub struct Writer {
     current: Option<CurrentBuffer>,
     state: Arc<Mutex<State>>,
     item_size: usize,
+    capacity: usize,
     reserved_items: usize,
+    item_multiple: usize,
     reader_inbox: Option<Sender<BlockMessage>>,
     reader_input_id: Option<usize>,
     writer_inbox: Sender<BlockMessage>,
@@ -119,6 +137,55 @@ pub struct Writer {
 struct State {
     writer_input: VecDeque<BufferEmpty>,
     reader_input: VecDeque<BufferFull>,
+    /// Reserved items, which might grow to satisfy the requirements of the reader and writer.
+    reserved_items: usize,
+    min_items: usize,
+    reader_multiple: usize,
+    writer_multiple: usize,
+}
+
+impl State {
+    /// Reserve enough items for the requirements of the reader and writer.
+    ///
+    /// Both check their requirements after `init()`, before anything is written, so reader and
+    /// writer pick up the new value with their first buffer.
+    fn reserve(&mut self, capacity: usize) -> Result<()> {
+        let min = self.min_items.max(self.reader_multiple);
+        // items of a full buffer, the writer only fills multiples
+        let items = |reserved: usize| {
+            capacity.saturating_sub(reserved) / self.writer_multiple * self.writer_multiple
+        };
+        // Items that are left, when the reader cannot continue, are copied to the reserved
+        // items of the next buffer. If the reader consumes multiples, the remainder cycles
+        // through the multiples of gcd(items, item_multiple).
+        let left = |items: usize| {
+            if self.min_items <= self.reader_multiple {
+                self.reader_multiple - gcd(items, self.reader_multiple)
+            } else {
+                min - 1
+            }
+        };
+
+        let reserved = (self.reserved_items..=capacity)
+            .find(|r| {
+                let n = items(*r);
+                n >= min && n >= self.writer_multiple && left(n) <= *r
+            })
+            .with_context(|| {
+                format!(
+                    "slab buffers hold {} items, which cannot satisfy a reader requiring {} items in multiples of {} and a writer producing multiples of {}",
+                    capacity, min, self.reader_multiple, self.writer_multiple
+                )
+            })?;
+        if reserved != self.reserved_items {
+            debug!(
+                "slab buffers reserve {} instead of {} items",
+                reserved, self.reserved_items
+            );
+            self.reserved_items = reserved;
+        }
+        Ok(())
+    }
 }
 
 impl Writer {
@@ -127,6 +194,7 @@ impl Writer {
         min_bytes: usize,
         n_buffer: usize,
         reserved_items: usize,
+        allocator: Allocator,
         writer_inbox: Sender<BlockMessage>,
         writer_output_id: usize,
     ) -> BufferWriter {
@@ -138,7 +206,7 @@ impl Writer {
         let mut writer_input = VecDeque::new();
         for _ in 0..n_buffer {
             writer_input.push_back(BufferEmpty {
-                buffer: vec![0; buffer_size].into_boxed_slice(),
+                buffer: allocator.allocate(buffer_size),
             });
         }
 
@@ -147,9 +215,15 @@ impl Writer {
             state: Arc::new(Mutex::new(State {
                 writer_input,
                 reader_input: VecDeque::new(),
+                reserved_items,
+                min_items: 0,
+                reader_multiple: 1,
+                writer_multiple: 1,
             })),
             item_size,
+            capacity: buffer_size / item_size,
             reserved_items,
+            item_multiple: 1,
             reader_inbox: None,
             reader_input_id: None,
             writer_inbox,
@@ -174,8 +248,10 @@ impl BufferWriterHost for Writer {
 
         BufferReader::Host(Box::new(Reader {
             current: None,
+            borrowed: None,
             state: self.state.clone(),
             item_size: self.item_size,
+            capacity: self.capacity,
             reader_inbox,
             reserved_items: self.reserved_items,
             writer_inbox: self.writer_inbox.clone(),
@@ -188,11 +264,22 @@ impl BufferWriterHost for Writer {
         self
     }
 
+    fn set_item_multiple(&mut self, item_multiple: usize) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.writer_multiple = item_multiple;
+        self.item_multiple = item_multiple;
+        state.reserve(self.capacity)
+    }
+
     fn bytes(&mut self) -> (*mut u8, usize) {
         if self.current.is_none() {
             let mut state = self.state.lock().unwrap();
+            self.reserved_items = state.reserved_items;
             if let Some(b) = state.writer_input.pop_front() {
-                let capacity = b.buffer.len() / self.item_size;
+                // full buffers are handed downstream, so only expose multiples
+                let items = b.buffer.len() / self.item_size - self.reserved_items;
+                let capacity =
+                    self.reserved_items + items / self.item_multiple * self.item_multiple;
                 self.current = Some(CurrentBuffer {
                     buffer: b.buffer,
                     offset: self.reserved_items,
@@ -208,7 +295,7 @@ impl BufferWriterHost for Writer {
 
         unsafe {
             (
-                (c.buffer.as_mut_ptr() as *mut u8).add(c.offset * self.item_size),
+                c.buffer.as_mut_ptr().add(c.offset * self.item_size),
                 (c.capacity - c.offset) * self.item_size,
             )
         }
@@ -291,11 +378,22 @@ impl BufferWriterHost for Writer {
 
 unsafe impl Send for Writer {}
 
+/// Buffer of the upstream edge, forwarded to the writer of an in-place block.
+#[derive(Debug)]
+struct Borrowed {
+    ptr: *const u8,
+    offset: usize,
+    capacity: usize,
+    tags: Vec<ItemTag>,
+}
+
 #[derive(Debug)]
 pub struct Reader {
     current: Option<CurrentBuffer>,
+    borrowed: Option<Borrowed>,
     state: Arc<Mutex<State>>,
     item_size: usize,
+    capacity: usize,
     reserved_items: usize,
     reader_inbox: Sender<BlockMessage>,
     writer_inbox: Sender<BlockMessage>,
@@ -310,6 +408,16 @@ impl BufferReaderHost for Reader {
     }
 
     fn bytes(&mut self) -> (*const u8, usize, Vec<ItemTag>) {
+        if let Some(b) = self.borrowed.as_ref() {
+            return unsafe {
+                (
+                    b.ptr.add(b.offset * self.item_size),
+                    (b.capacity - b.offset) * self.item_size,
+                    b.tags.clone(),
+                )
+            };
+        }
+
         if let Some(cur) = self.current.as_mut() {
             let left = cur.capacity - cur.offset;
             debug_assert!(left > 0);
@@ -346,6 +454,7 @@ impl BufferReaderHost for Reader {
             }
         } else {
             let mut state = self.state.lock().unwrap();
+            self.reserved_items = state.reserved_items;
             if let Some(b) = state.reader_input.pop_front() {
                 let capacity = b.items + self.reserved_items;
                 self.current = Some(CurrentBuffer {
@@ -363,7 +472,7 @@ impl BufferReaderHost for Reader {
 
         unsafe {
             (
-                (c.buffer.as_ptr() as *const u8).add(c.offset * self.item_size),
+                c.buffer.as_ptr().add(c.offset * self.item_size),
                 (c.capacity - c.offset) * self.item_size,
                 c.tags.clone(),
             )
@@ -373,6 +482,18 @@ impl BufferReaderHost for Reader {
     fn consume(&mut self, amount: usize) {
         debug_assert!(amount > 0);
 
+        if let Some(b) = self.borrowed.as_mut() {
+            debug_assert!(amount <= b.capacity - b.offset);
+            b.offset += amount;
+            if b.offset == b.capacity {
+                self.borrowed = None;
+                if !self.state.lock().unwrap().reader_input.is_empty() {
+                    let _ = self.reader_inbox.try_send(BlockMessage::Notify);
+                }
+            }
+            return;
+        }
+
         let c = self.current.as_mut().unwrap();
         debug_assert!(amount <= c.capacity - c.offset);
         c.offset += amount;
@@ -399,6 +520,13 @@ impl BufferReaderHost for Reader {
         }
     }
 
+    fn check_min_items(&mut self, min_items: usize, item_multiple: usize) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.min_items = min_items;
+        state.reader_multiple = item_multiple;
+        state.reserve(self.capacity)
+    }
+
     async fn notify_finished(&mut self) {
         if self.finished {
             return;
@@ -422,3 +550,70 @@ impl BufferReaderHost for Reader {
 }
 
 unsafe impl Send for Reader {}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Forward the next full buffer of `reader` to `writer` of an in-place block.
+///
+/// The buffer is swapped with an empty buffer of the writer, which is returned to the upstream
+/// edge. The reader only keeps a view of the buffer, which is handed downstream by the writer,
+/// once the block produced all items. Returns `false`, if no buffers are available or the
+/// buffers of the edges are not compatible.
+pub(crate) fn forward_in_place(reader: &mut Reader, writer: &mut Writer) -> bool {
+    if reader.current.is_some()
+        || reader.borrowed.is_some()
+        || writer.current.is_some()
+        || reader.item_size != writer.item_size
+        || Arc::ptr_eq(&reader.state, &writer.state)
+    {
+        return false;
+    }
+
+    let mut upstream = reader.state.lock().unwrap();
+    let mut downstream = writer.state.lock().unwrap();
+    reader.reserved_items = upstream.reserved_items;
+    writer.reserved_items = downstream.reserved_items;
+    if reader.reserved_items != writer.reserved_items
+        || upstream.writer_multiple != downstream.writer_multiple
+    {
+        return false;
+    }
+
+    let len = match upstream.reader_input.front() {
+        Some(b) => b.buffer.len(),
+        None => return false,
+    };
+    let empty = match downstream
+        .writer_input
+        .iter()
+        .position(|b| b.buffer.len() == len)
+    {
+        Some(i) => downstream.writer_input.remove(i).unwrap(),
+        None => return false,
+    };
+    let full = upstream.reader_input.pop_front().unwrap();
+    upstream.writer_input.push_back(empty);
+    let _ = reader.writer_inbox.try_send(BlockMessage::Notify);
+
+    let capacity = full.items + reader.reserved_items;
+    reader.borrowed = Some(Borrowed {
+        ptr: full.buffer.as_ptr(),
+        offset: reader.reserved_items,
+        capacity,
+        tags: full.tags,
+    });
+    writer.current = Some(CurrentBuffer {
+        buffer: full.buffer,
+        offset: writer.reserved_items,
+        capacity,
+        tags: Vec::new(),
+    });
+
+    true
+}
@@ -1,4 +1,4 @@
-use futures::channel::mpsc::Sender;
+use crate::runtime::channel::Sender;
 use futures::prelude::*;
 use std::any::Any;
 use std::sync::{Arc, Mutex};
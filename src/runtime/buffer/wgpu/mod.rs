@@ -6,6 +6,8 @@ mod h2d;
 pub use h2d::ReaderH2D;
 pub use h2d::WriterH2D;
 pub use h2d::H2D;
+mod staging;
+pub use staging::StagingPool;
 
 use wgpu::{Adapter, Buffer, Device, Queue};
 
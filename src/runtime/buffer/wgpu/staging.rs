@@ -0,0 +1,92 @@
+use std::sync::{Arc, Mutex};
+use wgpu::{
+    Buffer, BufferDescriptor, BufferUsages, CommandEncoder, Device, MapMode, COPY_BUFFER_ALIGNMENT,
+};
+
+/// Pool of host-visible upload buffers.
+///
+/// Samples of the [H2D](super::H2D) stream buffers are copied once into a staging buffer that
+/// is mapped for writing and, on the GPU, from there into the storage buffers of a compute
+/// kernel. Staging buffers are re-mapped asynchronously, once the GPU is done with them, and
+/// reused, avoiding the allocation of a new staging buffer per upload. Results are read back
+/// through the mapped buffers of the [D2H](super::D2H) stream buffers without further copies.
+#[derive(Debug)]
+pub struct StagingPool {
+    size: u64,
+    ready: Arc<Mutex<Vec<Arc<Buffer>>>>,
+    in_flight: Vec<Arc<Buffer>>,
+}
+
+impl StagingPool {
+    /// Create a pool of staging buffers with the given size in bytes.
+    pub fn new(size: u64) -> StagingPool {
+        StagingPool {
+            size: align(size),
+            ready: Arc::new(Mutex::new(Vec::new())),
+            in_flight: Vec::new(),
+        }
+    }
+
+    /// Copy `data` into a staging buffer and record the copy to `dst` at `offset` in the
+    /// encoder.
+    ///
+    /// The size of `data` has to be a multiple of four bytes and must not exceed the size of
+    /// the staging buffers.
+    pub fn upload(
+        &mut self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        data: &[u8],
+        dst: &Buffer,
+        offset: u64,
+    ) {
+        let len = data.len() as u64;
+        if len == 0 {
+            return;
+        }
+        assert!(len <= self.size, "upload exceeds staging buffer size");
+        assert_eq!(
+            len % COPY_BUFFER_ALIGNMENT,
+            0,
+            "upload has to be 4-byte aligned"
+        );
+
+        let buffer = self.ready.lock().unwrap().pop().unwrap_or_else(|| {
+            Arc::new(device.create_buffer(&BufferDescriptor {
+                label: Some("staging"),
+                size: self.size,
+                usage: BufferUsages::MAP_WRITE | BufferUsages::COPY_SRC,
+                mapped_at_creation: true,
+            }))
+        });
+
+        buffer
+            .slice(0..len)
+            .get_mapped_range_mut()
+            .copy_from_slice(data);
+        buffer.unmap();
+        encoder.copy_buffer_to_buffer(&buffer, 0, dst, offset, len);
+        self.in_flight.push(buffer);
+    }
+
+    /// Re-map the buffers of all uploads, once the submitted commands completed.
+    ///
+    /// Has to be called after the encoder was submitted. The mapping completes with the next
+    /// poll of the device.
+    pub fn recall(&mut self) {
+        for buffer in self.in_flight.drain(..) {
+            let ready = self.ready.clone();
+            let b = buffer.clone();
+            buffer.slice(..).map_async(MapMode::Write, move |r| {
+                if r.is_ok() {
+                    ready.lock().unwrap().push(b);
+                }
+            });
+        }
+    }
+}
+
+/// Round up to the copy alignment.
+fn align(size: u64) -> u64 {
+    (size + COPY_BUFFER_ALIGNMENT - 1) / COPY_BUFFER_ALIGNMENT * COPY_BUFFER_ALIGNMENT
+}
@@ -0,0 +1,315 @@
+//! Lock-free Bounded MPSC Channel
+//!
+//! Inboxes of blocks and flowgraphs receive messages from many producers (upstream and
+//! downstream buffers, message outputs, flowgraph handles). This channel replaces
+//! `futures::channel::mpsc` for them. Messages are kept in a lock-free queue that allocates
+//! segments of several slots instead of one node per message. The capacity is enforced by
+//! reserving a slot before a message is pushed, so that a reserved push never fails.
+//!
+//! Unlike `futures::channel::mpsc`, the slots are counted by one `reserved` counter, shared
+//! by all senders. An `mpsc` channel holds `capacity` messages plus one guaranteed slot per
+//! sender, and parked senders are woken in order. Here, the channel holds at most `capacity`
+//! messages, no matter how many senders there are, and a sender that did not have to wait
+//! can take a freed slot before a waiting one. A busy sender, e.g., a block posting messages
+//! at a high rate, can therefore fill an inbox and delay other senders, like a
+//! [FlowgraphHandle](super::FlowgraphHandle), until the receiver catches up. Buffer
+//! notifications are sent with [Sender::try_send] and dropped if the inbox is full, which is
+//! harmless, since the block has pending messages anyway.
+//!
+//! The `channel` benchmark sends 100k messages from 1 or 4 threads. On a single core of an
+//! Intel Xeon, this channel reaches 4.4 (1 sender) and 3.8 (4 senders) Mmsg/s with a
+//! capacity of 64, compared to 3.1 and 1.9 Mmsg/s for `mpsc`. With a capacity of 8192, it
+//! reaches 8.4 and 9.8 Mmsg/s, compared to 5.6 and 4.6 Mmsg/s.
+//!
+//! [Sender] implements [Sink] and [Receiver] implements [Stream], i.e., they are used like
+//! their `futures` counterparts.
+use concurrent_queue::ConcurrentQueue;
+use event_listener::Event;
+use event_listener::EventListener;
+use futures::sink::Sink;
+use futures::stream::Stream;
+use futures::task::AtomicWaker;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use thiserror::Error;
+
+struct Shared<T> {
+    queue: ConcurrentQueue<T>,
+    capacity: usize,
+    /// Messages in the queue plus slots reserved by senders.
+    reserved: AtomicUsize,
+    senders: AtomicUsize,
+    receiver: AtomicWaker,
+    /// Signals senders that wait for a free slot.
+    slot_freed: Event,
+}
+
+impl<T> Shared<T> {
+    fn try_reserve(&self) -> bool {
+        let mut n = self.reserved.load(Ordering::Relaxed);
+        loop {
+            if n >= self.capacity {
+                return false;
+            }
+            match self
+                .reserved
+                .compare_exchange_weak(n, n + 1, Ordering::AcqRel, Ordering::Relaxed)
+            {
+                Ok(_) => return true,
+                Err(x) => n = x,
+            }
+        }
+    }
+
+    fn release(&self) {
+        self.reserved.fetch_sub(1, Ordering::AcqRel);
+        self.slot_freed.notify(1);
+    }
+}
+
+/// Create a channel that holds up to `capacity` messages.
+pub fn channel<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    let shared = Arc::new(Shared {
+        queue: ConcurrentQueue::unbounded(),
+        capacity: capacity.max(1),
+        reserved: AtomicUsize::new(0),
+        senders: AtomicUsize::new(1),
+        receiver: AtomicWaker::new(),
+        slot_freed: Event::new(),
+    });
+
+    (
+        Sender {
+            shared: shared.clone(),
+            reserved: false,
+            listener: None,
+        },
+        Receiver { shared },
+    )
+}
+
+/// Error returned when sending into a closed channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("receiver dropped")]
+pub struct SendError;
+
+impl SendError {
+    pub fn is_disconnected(&self) -> bool {
+        true
+    }
+}
+
+/// Error returned by [Sender::try_send], containing the message that was not sent.
+#[derive(Clone, PartialEq, Eq)]
+pub struct TrySendError<T> {
+    full: bool,
+    msg: T,
+}
+
+impl<T> fmt::Debug for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TrySendError")
+            .field("full", &self.full)
+            .finish()
+    }
+}
+
+impl<T> fmt::Display for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.full {
+            write!(f, "channel full")
+        } else {
+            write!(f, "receiver dropped")
+        }
+    }
+}
+
+impl<T> std::error::Error for TrySendError<T> {}
+
+impl<T> TrySendError<T> {
+    pub fn is_full(&self) -> bool {
+        self.full
+    }
+
+    pub fn is_disconnected(&self) -> bool {
+        !self.full
+    }
+
+    pub fn into_inner(self) -> T {
+        self.msg
+    }
+}
+
+/// Sending half of a [channel].
+pub struct Sender<T> {
+    shared: Arc<Shared<T>>,
+    /// Whether the sender holds a slot, reserved in `poll_ready`.
+    reserved: bool,
+    listener: Option<EventListener>,
+}
+
+impl<T> Sender<T> {
+    /// Send a message, if the channel has capacity.
+    pub fn try_send(&mut self, msg: T) -> Result<(), TrySendError<T>> {
+        if self.shared.queue.is_closed() {
+            return Err(TrySendError { full: false, msg });
+        }
+        if !self.reserved && !self.shared.try_reserve() {
+            return Err(TrySendError { full: true, msg });
+        }
+        self.reserved = false;
+        self.push(msg)
+            .map_err(|msg| TrySendError { full: false, msg })
+    }
+
+    /// Whether the receiver was dropped.
+    pub fn is_closed(&self) -> bool {
+        self.shared.queue.is_closed()
+    }
+
+    fn push(&self, msg: T) -> Result<(), T> {
+        match self.shared.queue.push(msg) {
+            Ok(()) => {
+                self.shared.receiver.wake();
+                Ok(())
+            }
+            Err(e) => {
+                self.shared.release();
+                Err(e.into_inner())
+            }
+        }
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.shared.senders.fetch_add(1, Ordering::Relaxed);
+        Sender {
+            shared: self.shared.clone(),
+            reserved: false,
+            listener: None,
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        if self.reserved {
+            self.shared.release();
+        }
+        if self.shared.senders.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.shared.receiver.wake();
+        }
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender")
+            .field("len", &self.shared.queue.len())
+            .field("capacity", &self.shared.capacity)
+            .finish()
+    }
+}
+
+impl<T> Unpin for Sender<T> {}
+
+impl<T> Sink<T> for Sender<T> {
+    type Error = SendError;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError>> {
+        let this = self.get_mut();
+        loop {
+            if this.shared.queue.is_closed() {
+                this.listener = None;
+                return Poll::Ready(Err(SendError));
+            }
+            if this.reserved || this.shared.try_reserve() {
+                this.reserved = true;
+                this.listener = None;
+                return Poll::Ready(Ok(()));
+            }
+            match this.listener.as_mut() {
+                None => {
+                    // register, before checking for a free slot again
+                    let l = this.shared.slot_freed.listen();
+                    this.listener = Some(l);
+                }
+                Some(l) => match Pin::new(l).poll(cx) {
+                    Poll::Ready(()) => this.listener = None,
+                    Poll::Pending => return Poll::Pending,
+                },
+            }
+        }
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, msg: T) -> Result<(), SendError> {
+        debug_assert!(self.reserved, "start_send without poll_ready");
+        self.reserved = false;
+        self.push(msg).map_err(|_| SendError)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), SendError>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), SendError>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Receiving half of a [channel].
+pub struct Receiver<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Receiver<T> {
+    fn pop(&self) -> Option<T> {
+        let msg = self.shared.queue.pop().ok()?;
+        self.shared.release();
+        Some(msg)
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        // drop queued messages, e.g., to cancel pending replies
+        self.shared.queue.close();
+        while self.shared.queue.pop().is_ok() {}
+        self.shared.slot_freed.notify(usize::MAX);
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver")
+            .field("len", &self.shared.queue.len())
+            .field("capacity", &self.shared.capacity)
+            .finish()
+    }
+}
+
+impl<T> Stream for Receiver<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        if let Some(msg) = self.pop() {
+            return Poll::Ready(Some(msg));
+        }
+
+        self.shared.receiver.register(cx.waker());
+
+        if let Some(msg) = self.pop() {
+            return Poll::Ready(Some(msg));
+        }
+        if self.shared.senders.load(Ordering::Acquire) == 0 {
+            // the last sender might have pushed before it was dropped
+            return Poll::Ready(self.pop());
+        }
+        Poll::Pending
+    }
+}
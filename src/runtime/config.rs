@@ -60,6 +60,15 @@ static CONFIG: Lazy<Config> = Lazy::new(|| {
                 "buffer_size" => {
                     c.buffer_size = config_parse::<usize>(v);
                 }
+                "buffer_alignment" => {
+                    c.buffer_alignment = config_parse::<usize>(v);
+                }
+                "buffer_huge_pages" => {
+                    c.buffer_huge_pages = config_parse::<bool>(v);
+                }
+                "notify_threshold" => {
+                    c.notify_threshold = config_parse::<usize>(v);
+                }
                 "log_level" => {
                     c.log_level = config_parse::<LevelFilter>(v);
                 }
@@ -91,6 +100,9 @@ pub struct Config {
     pub queue_size: usize,
     pub buffer_size: usize,
     pub slab_reserved: usize,
+    pub buffer_alignment: usize,
+    pub buffer_huge_pages: bool,
+    pub notify_threshold: usize,
     pub log_level: LevelFilter,
     pub ctrlport_enable: bool,
     pub ctrlport_bind: Option<SocketAddr>,
@@ -105,6 +117,10 @@ impl Config {
             println!("ctrlport enabled but socket not set");
             return false;
         }
+        if !self.buffer_alignment.is_power_of_two() {
+            println!("buffer alignment has to be a power of two");
+            return false;
+        }
         true
     }
 }
@@ -116,6 +132,9 @@ impl Default for Config {
             queue_size: 8192,
             buffer_size: 32768,
             slab_reserved: 128,
+            buffer_alignment: 64,
+            buffer_huge_pages: false,
+            notify_threshold: 0,
             log_level: LevelFilter::Debug,
             ctrlport_enable: true,
             ctrlport_bind: "127.0.0.1:1337".parse::<SocketAddr>().ok(),
@@ -130,6 +149,9 @@ impl Default for Config {
             queue_size: 8192,
             buffer_size: 32768,
             slab_reserved: 128,
+            buffer_alignment: 64,
+            buffer_huge_pages: false,
+            notify_threshold: 0,
             log_level: LevelFilter::Info,
             ctrlport_enable: false,
             ctrlport_bind: None,
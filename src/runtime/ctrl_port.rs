@@ -1,24 +1,56 @@
 //! Remote Control through REST API
-use axum::extract::{Extension, Path};
-use axum::http::{StatusCode, Uri};
-use axum::response::Redirect;
-use axum::routing::{any, get, get_service};
+use async_io::Timer;
+use async_tungstenite::tungstenite::handshake::derive_accept_key;
+use async_tungstenite::tungstenite::protocol::Role;
+use async_tungstenite::tungstenite::Message;
+use async_tungstenite::WebSocketStream;
+use axum::body::Body;
+use axum::extract::{Extension, Form, Path};
+use axum::http::{header, Request, StatusCode, Uri};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{Html, Redirect};
+use axum::routing::{any, any_service, get, get_service, post};
 use axum::Json;
 use axum::Router;
+use futures::channel::mpsc;
+use futures::future;
+use futures::future::BoxFuture;
+use futures::stream::{self, Stream, StreamExt};
+use futures::SinkExt;
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use serde_json::json;
 use slab::Slab;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::net::SocketAddr;
 use std::path;
+use std::path::PathBuf;
+use std::str::FromStr;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::thread::JoinHandle;
+use std::time::Duration;
+use tokio_util::compat::TokioAsyncReadCompatExt;
 use tower_http::add_extension::AddExtensionLayer;
 use tower_http::cors::CorsLayer;
-use tower_http::services::ServeDir;
+use tower_http::services::{ServeDir, ServeFile};
+use utoipa::{OpenApi, ToSchema};
 
+use crate::anyhow::Result;
 use crate::runtime::config;
+use crate::runtime::thrift_port;
 use crate::runtime::BlockDescription;
+use crate::runtime::BlockState;
+use crate::runtime::CallbackError;
 use crate::runtime::FlowgraphDescription;
+use crate::runtime::FlowgraphEvent;
 use crate::runtime::FlowgraphHandle;
+use crate::runtime::FlowgraphStats;
+use crate::runtime::HandlerDescription;
+use crate::runtime::ParameterDescription;
 use crate::runtime::Pmt;
+use crate::runtime::PmtKind;
 use crate::runtime::PortId;
 
 macro_rules! relative {
@@ -31,18 +63,41 @@ macro_rules! relative {
     };
 }
 
-async fn flowgraphs(
-    Extension(flowgraphs): Extension<Arc<Mutex<Slab<FlowgraphHandle>>>>,
-) -> Json<usize> {
+#[utoipa::path(
+    get,
+    path = "/api/fg/",
+    responses((status = 200, description = "Number of flowgraphs", body = usize))
+)]
+async fn flowgraphs(Extension(flowgraphs): Extension<Arc<Mutex<Flowgraphs>>>) -> Json<usize> {
     let l = flowgraphs.lock().unwrap().len();
     Json::from(l)
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/names/",
+    responses((status = 200, description = "Ids of named flowgraphs", body = HashMap<String, usize>))
+)]
+async fn names(
+    Extension(flowgraphs): Extension<Arc<Mutex<Flowgraphs>>>,
+) -> Json<HashMap<String, usize>> {
+    Json::from(flowgraphs.lock().unwrap().names())
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/fg/{fg}/",
+    params(("fg" = String, Path, description = "Flowgraph id or name")),
+    responses(
+        (status = 200, description = "Flowgraph description", body = FlowgraphDescription),
+        (status = 400, description = "Invalid flowgraph"),
+    )
+)]
 async fn flowgraph_description(
-    Path(fg): Path<usize>,
-    Extension(flowgraphs): Extension<Arc<Mutex<Slab<FlowgraphHandle>>>>,
+    Path(fg): Path<String>,
+    Extension(flowgraphs): Extension<Arc<Mutex<Flowgraphs>>>,
 ) -> Result<Json<FlowgraphDescription>, StatusCode> {
-    let fg = flowgraphs.lock().unwrap().get(fg).cloned();
+    let fg = flowgraphs.lock().unwrap().get(&fg);
     if let Some(mut fg) = fg {
         if let Ok(d) = fg.description().await {
             return Ok(Json::from(d));
@@ -51,11 +106,20 @@ async fn flowgraph_description(
     Err(StatusCode::BAD_REQUEST)
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/fg/{fg}/block/{blk}/",
+    params(("fg" = String, Path, description = "Flowgraph id or name"), ("blk" = usize, Path, description = "Block id")),
+    responses(
+        (status = 200, description = "Block description", body = BlockDescription),
+        (status = 400, description = "Invalid flowgraph or block"),
+    )
+)]
 async fn block_description(
-    Path((fg, blk)): Path<(usize, usize)>,
-    Extension(flowgraphs): Extension<Arc<Mutex<Slab<FlowgraphHandle>>>>,
+    Path((fg, blk)): Path<(String, usize)>,
+    Extension(flowgraphs): Extension<Arc<Mutex<Flowgraphs>>>,
 ) -> Result<Json<BlockDescription>, StatusCode> {
-    let fg = flowgraphs.lock().unwrap().get(fg).cloned();
+    let fg = flowgraphs.lock().unwrap().get(&fg);
     if let Some(mut fg) = fg {
         if let Ok(d) = fg.block_description(blk).await {
             return Ok(Json::from(d));
@@ -65,73 +129,823 @@ async fn block_description(
     Err(StatusCode::BAD_REQUEST)
 }
 
+/// Status code for a failed call, letting clients tell unknown blocks from failing handlers.
+fn callback_status(e: CallbackError) -> StatusCode {
+    match e {
+        CallbackError::InvalidBlock => StatusCode::NOT_FOUND,
+        _ => StatusCode::BAD_REQUEST,
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/fg/{fg}/block/{blk}/call/{handler}/",
+    params(("fg" = String, Path, description = "Flowgraph id or name"), ("blk" = usize, Path, description = "Block id"), ("handler" = String, Path, description = "Message handler id or name")),
+    responses(
+        (status = 200, description = "Return value of the handler, called with Pmt::Null", body = Pmt),
+        (status = 400, description = "Invalid handler or error in handler"),
+        (status = 404, description = "Invalid flowgraph or block"),
+    )
+)]
 async fn handler_id(
-    Path((fg, blk, handler)): Path<(usize, usize, String)>,
-    Extension(flowgraphs): Extension<Arc<Mutex<Slab<FlowgraphHandle>>>>,
+    Path((fg, blk, handler)): Path<(String, usize, String)>,
+    Extension(flowgraphs): Extension<Arc<Mutex<Flowgraphs>>>,
 ) -> Result<Json<Pmt>, StatusCode> {
-    let fg = flowgraphs.lock().unwrap().get(fg).cloned();
+    let fg = flowgraphs.lock().unwrap().get(&fg);
     let handler = match handler.parse::<usize>() {
         Ok(i) => PortId::Index(i),
         Err(_) => PortId::Name(handler),
     };
-    if let Some(mut fg) = fg {
-        if let Ok(ret) = fg.callback(blk, handler, Pmt::Null).await {
-            return Ok(Json::from(ret));
-        }
-    }
-
-    Err(StatusCode::BAD_REQUEST)
+    let mut fg = fg.ok_or(StatusCode::NOT_FOUND)?;
+    fg.callback(blk, handler, Pmt::Null)
+        .await
+        .map(Json::from)
+        .map_err(callback_status)
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/fg/{fg}/block/{blk}/call/{handler}/",
+    params(("fg" = String, Path, description = "Flowgraph id or name"), ("blk" = usize, Path, description = "Block id"), ("handler" = String, Path, description = "Message handler id or name")),
+    request_body = Pmt,
+    responses(
+        (status = 200, description = "Return value of the handler", body = Pmt),
+        (status = 400, description = "Invalid handler or error in handler"),
+        (status = 404, description = "Invalid flowgraph or block"),
+    )
+)]
 async fn handler_id_post(
-    Path((fg, blk, handler)): Path<(usize, usize, String)>,
+    Path((fg, blk, handler)): Path<(String, usize, String)>,
     Json(pmt): Json<Pmt>,
-    Extension(flowgraphs): Extension<Arc<Mutex<Slab<FlowgraphHandle>>>>,
+    Extension(flowgraphs): Extension<Arc<Mutex<Flowgraphs>>>,
 ) -> Result<Json<Pmt>, StatusCode> {
-    let fg = flowgraphs.lock().unwrap().get(fg).cloned();
+    let fg = flowgraphs.lock().unwrap().get(&fg);
     let handler = match handler.parse::<usize>() {
         Ok(i) => PortId::Index(i),
         Err(_) => PortId::Name(handler),
     };
+    let mut fg = fg.ok_or(StatusCode::NOT_FOUND)?;
+    fg.callback(blk, handler, pmt)
+        .await
+        .map(Json::from)
+        .map_err(callback_status)
+}
+
+/// Kinds of PMTs that can be entered in forms, offered for handlers without description.
+const FORM_KINDS: [PmtKind; 8] = [
+    PmtKind::F64,
+    PmtKind::F32,
+    PmtKind::U64,
+    PmtKind::U32,
+    PmtKind::I64,
+    PmtKind::I32,
+    PmtKind::String,
+    PmtKind::Null,
+];
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Description of the `i`-th message handler, empty if the block does not provide one.
+fn handler_description(d: &BlockDescription, i: usize) -> HandlerDescription {
+    d.message_input_descriptions
+        .get(i)
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Kinds of PMTs, a form offers for a handler.
+fn form_kinds(h: &HandlerDescription) -> Vec<PmtKind> {
+    if h.kinds.is_empty() {
+        FORM_KINDS.to_vec()
+    } else {
+        h.kinds
+            .iter()
+            .filter(|k| FORM_KINDS.contains(k))
+            .cloned()
+            .collect()
+    }
+}
+
+fn label(name: &str, unit: &Option<String>) -> String {
+    match unit {
+        Some(u) => format!("{} [{}]", escape(name), escape(u)),
+        None => escape(name),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/fg/{fg}/block/{blk}/form/",
+    params(("fg" = String, Path, description = "Flowgraph id or name"), ("blk" = usize, Path, description = "Block id")),
+    responses(
+        (status = 200, description = "HTML form to call the message handlers of the block", content_type = "text/html"),
+        (status = 400, description = "Invalid flowgraph or block"),
+    )
+)]
+async fn block_form(
+    Path((fg, blk)): Path<(String, usize)>,
+    Extension(flowgraphs): Extension<Arc<Mutex<Flowgraphs>>>,
+) -> Result<Html<String>, StatusCode> {
+    let fg = flowgraphs.lock().unwrap().get(&fg);
+    let mut fg = fg.ok_or(StatusCode::BAD_REQUEST)?;
+    let d = fg
+        .block_description(blk)
+        .await
+        .or(Err(StatusCode::BAD_REQUEST))?;
+
+    let title = format!("{} ({})", escape(&d.instance_name), escape(&d.type_name));
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n</head>\n<body>\n<h1>{title}</h1>\n"
+    );
+    if d.message_inputs.is_empty() {
+        html.push_str("<p>Block has no message handlers.</p>\n");
+    }
+    for (i, h) in d.message_inputs.iter().enumerate() {
+        let desc = handler_description(&d, i);
+        let h = escape(h);
+        let _ = write!(
+            html,
+            "<form method=\"post\" action=\"{h}/\">\n<fieldset>\n<legend>{h}</legend>\n"
+        );
+        if let Some(t) = &desc.description {
+            let _ = writeln!(html, "<p>{}</p>", escape(t));
+        }
+        if desc.parameters.is_empty() {
+            let kinds = form_kinds(&desc);
+            if kinds.is_empty() {
+                html.push_str(
+                    "<p>Handler cannot be called through a form.</p>\n</fieldset>\n</form>\n",
+                );
+                continue;
+            }
+            html.push_str("<select name=\"kind\">\n");
+            for k in kinds.iter() {
+                let _ = writeln!(html, "<option>{k}</option>");
+            }
+            let _ = writeln!(
+                html,
+                "</select>\n<label>{} <input type=\"text\" name=\"value\"></label>",
+                label("value", &desc.unit)
+            );
+        } else {
+            let _ = writeln!(
+                html,
+                "<input type=\"hidden\" name=\"kind\" value=\"{}\">",
+                PmtKind::MapStrPmt
+            );
+            for p in desc.parameters.iter() {
+                let _ = write!(html, "<label");
+                if let Some(t) = &p.description {
+                    let _ = write!(html, " title=\"{}\"", escape(t));
+                }
+                let _ = writeln!(
+                    html,
+                    ">{} <input type=\"text\" name=\"{}\"></label>",
+                    label(&p.name, &p.unit),
+                    escape(&p.name)
+                );
+            }
+        }
+        html.push_str("<button type=\"submit\">Call</button>\n</fieldset>\n</form>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+
+    Ok(Html(html))
+}
+
+/// Form data to call a message handler.
+///
+/// Handlers that accept a map of parameters take one field per parameter instead of `value`.
+/// Empty fields are left out of the map.
+#[derive(Deserialize, ToSchema)]
+struct HandlerForm {
+    kind: String,
+    #[serde(default)]
+    value: String,
+    #[serde(flatten)]
+    parameters: HashMap<String, String>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/fg/{fg}/block/{blk}/form/{handler}/",
+    params(("fg" = String, Path, description = "Flowgraph id or name"), ("blk" = usize, Path, description = "Block id"), ("handler" = String, Path, description = "Message handler id or name")),
+    request_body(content = HandlerForm, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Return value of the handler", body = Pmt),
+        (status = 400, description = "Invalid flowgraph, block, handler, or value"),
+    )
+)]
+async fn block_form_post(
+    Path((fg, blk, handler)): Path<(String, usize, String)>,
+    Form(form): Form<HandlerForm>,
+    Extension(flowgraphs): Extension<Arc<Mutex<Flowgraphs>>>,
+) -> Result<Json<Pmt>, StatusCode> {
+    let kind = PmtKind::from_str(&form.kind).or(Err(StatusCode::BAD_REQUEST))?;
+
+    let d = {
+        let fg = flowgraphs.lock().unwrap().get(&fg);
+        let mut fg = fg.ok_or(StatusCode::BAD_REQUEST)?;
+        fg.block_description(blk)
+            .await
+            .or(Err(StatusCode::BAD_REQUEST))?
+    };
+    let i = match handler.parse::<usize>() {
+        Ok(i) if i < d.message_inputs.len() => i,
+        _ => d
+            .message_inputs
+            .iter()
+            .position(|n| *n == handler)
+            .ok_or(StatusCode::BAD_REQUEST)?,
+    };
+    let desc = handler_description(&d, i);
+    if !desc.kinds.is_empty() && !desc.kinds.contains(&kind) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let pmt = match kind {
+        PmtKind::Null => Pmt::Null,
+        PmtKind::MapStrPmt if !desc.parameters.is_empty() => {
+            let mut map = HashMap::new();
+            for p in desc.parameters.iter() {
+                if let Some(v) = form.parameters.get(&p.name) {
+                    if v.trim().is_empty() {
+                        continue;
+                    }
+                    let v = Pmt::from_string(v.trim(), &p.kind).ok_or(StatusCode::BAD_REQUEST)?;
+                    map.insert(p.name.clone(), v);
+                }
+            }
+            Pmt::MapStrPmt(map)
+        }
+        k => Pmt::from_string(form.value.trim(), &k).ok_or(StatusCode::BAD_REQUEST)?,
+    };
+    handler_id_post(
+        Path((fg, blk, i.to_string())),
+        Json(pmt),
+        Extension(flowgraphs),
+    )
+    .await
+}
+
+/// JSON schema of a PMT of the given kind, following its serde representation.
+fn kind_schema(k: &PmtKind, h: Option<&HandlerDescription>) -> serde_json::Value {
+    let value = match k {
+        PmtKind::Null => return json!({ "const": "Null" }),
+        PmtKind::String => json!({ "type": "string" }),
+        PmtKind::U32 | PmtKind::U64 => json!({ "type": "integer", "minimum": 0 }),
+        PmtKind::I32 | PmtKind::I64 => json!({ "type": "integer" }),
+        PmtKind::F32 | PmtKind::F64 => json!({ "type": "number" }),
+        PmtKind::VecF32 => json!({ "type": "array", "items": { "type": "number" } }),
+        PmtKind::VecU64 | PmtKind::Blob => {
+            json!({ "type": "array", "items": { "type": "integer", "minimum": 0 } })
+        }
+        PmtKind::MapStrPmt if h.map_or(false, |h| !h.parameters.is_empty()) => {
+            let properties: serde_json::Map<String, serde_json::Value> = h
+                .unwrap()
+                .parameters
+                .iter()
+                .map(|p| {
+                    let mut s = kind_schema(&p.kind, None);
+                    if let Some(t) = describe(&p.description, &p.unit) {
+                        s["description"] = json!(t);
+                    }
+                    (p.name.clone(), s)
+                })
+                .collect();
+            json!({
+                "type": "object",
+                "properties": properties,
+                "additionalProperties": false,
+            })
+        }
+        _ => json!({}),
+    };
+    json!({
+        "type": "object",
+        "properties": { k.to_string(): value },
+        "required": [k.to_string()],
+        "additionalProperties": false,
+    })
+}
+
+fn describe(description: &Option<String>, unit: &Option<String>) -> Option<String> {
+    match (description, unit) {
+        (Some(d), Some(u)) => Some(format!("{d} [{u}]")),
+        (Some(d), None) => Some(d.clone()),
+        (None, Some(u)) => Some(format!("[{u}]")),
+        (None, None) => None,
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/fg/{fg}/block/{blk}/schema/",
+    params(("fg" = String, Path, description = "Flowgraph id or name"), ("blk" = usize, Path, description = "Block id")),
+    responses(
+        (status = 200, description = "JSON schema of the message handlers of the block", body = Object),
+        (status = 400, description = "Invalid flowgraph or block"),
+    )
+)]
+async fn block_schema(
+    Path((fg, blk)): Path<(String, usize)>,
+    Extension(flowgraphs): Extension<Arc<Mutex<Flowgraphs>>>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let fg = flowgraphs.lock().unwrap().get(&fg);
+    let mut fg = fg.ok_or(StatusCode::BAD_REQUEST)?;
+    let d = fg
+        .block_description(blk)
+        .await
+        .or(Err(StatusCode::BAD_REQUEST))?;
+
+    let properties: serde_json::Map<String, serde_json::Value> = d
+        .message_inputs
+        .iter()
+        .enumerate()
+        .map(|(i, h)| {
+            let desc = handler_description(&d, i);
+            let kinds = if desc.kinds.is_empty() {
+                FORM_KINDS.to_vec()
+            } else {
+                desc.kinds.clone()
+            };
+            let variants: Vec<serde_json::Value> =
+                kinds.iter().map(|k| kind_schema(k, Some(&desc))).collect();
+            let mut s = json!({ "oneOf": variants });
+            if let Some(t) = describe(&desc.description, &desc.unit) {
+                s["description"] = json!(t);
+            }
+            (h.clone(), s)
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": d.instance_name,
+        "description": d.type_name,
+        "type": "object",
+        "properties": properties,
+    })))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/fg/{fg}/stats/",
+    params(("fg" = String, Path, description = "Flowgraph id or name")),
+    responses(
+        (status = 200, description = "State of all blocks", body = FlowgraphStats),
+        (status = 400, description = "Invalid flowgraph"),
+    )
+)]
+async fn stats(
+    Path(fg): Path<String>,
+    Extension(flowgraphs): Extension<Arc<Mutex<Flowgraphs>>>,
+) -> Result<Json<FlowgraphStats>, StatusCode> {
+    let fg = flowgraphs.lock().unwrap().get(&fg);
     if let Some(mut fg) = fg {
-        if let Ok(ret) = fg.callback(blk, handler, pmt).await {
-            return Ok(Json::from(ret));
+        if let Ok(s) = fg.stats().await {
+            return Ok(Json::from(s));
         }
     }
+    Err(StatusCode::BAD_REQUEST)
+}
 
+#[utoipa::path(
+    post,
+    path = "/api/fg/{fg}/terminate/",
+    params(("fg" = String, Path, description = "Flowgraph id or name")),
+    responses(
+        (status = 200, description = "Flowgraph is terminating", body = Pmt),
+        (status = 400, description = "Invalid flowgraph"),
+    )
+)]
+async fn terminate(
+    Path(fg): Path<String>,
+    Extension(flowgraphs): Extension<Arc<Mutex<Flowgraphs>>>,
+) -> Result<Json<Pmt>, StatusCode> {
+    let fg = flowgraphs.lock().unwrap().get(&fg);
+    if let Some(mut fg) = fg {
+        if fg.terminate().await.is_ok() {
+            return Ok(Json::from(Pmt::Null));
+        }
+    }
     Err(StatusCode::BAD_REQUEST)
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/fg/{fg}/start/",
+    params(("fg" = String, Path, description = "Flowgraph name")),
+    responses(
+        (status = 200, description = "Flowgraph was started", body = usize),
+        (status = 400, description = "No flowgraph registered with this name"),
+        (status = 409, description = "Flowgraph is already running"),
+        (status = 500, description = "Building the flowgraph failed"),
+    )
+)]
+async fn start(
+    Path(fg): Path<String>,
+    Extension(flowgraphs): Extension<Arc<Mutex<Flowgraphs>>>,
+) -> Result<Json<usize>, StatusCode> {
+    let launcher = {
+        let mut f = flowgraphs.lock().unwrap();
+        if f.get(&fg).is_some() {
+            return Err(StatusCode::CONFLICT);
+        }
+        f.launchers.get(&fg).cloned()
+    };
+    let launcher = launcher.ok_or(StatusCode::BAD_REQUEST)?;
+    if let Err(e) = launcher().await {
+        warn!("cannot start flowgraph {}: {:?}", fg, e);
+        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+    let id = flowgraphs.lock().unwrap().names().get(&fg).copied();
+    id.map(Json::from).ok_or(StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/fg/{fg}/events/",
+    params(("fg" = String, Path, description = "Flowgraph id or name")),
+    responses(
+        (status = 200, description = "Server-sent events stream of runtime events", body = FlowgraphEvent, content_type = "text/event-stream"),
+        (status = 400, description = "Invalid flowgraph"),
+    )
+)]
+async fn events(
+    Path(fg): Path<String>,
+    Extension(flowgraphs): Extension<Arc<Mutex<Flowgraphs>>>,
+) -> Result<Sse<impl Stream<Item = Result<Event, axum::Error>>>, StatusCode> {
+    let fg = flowgraphs.lock().unwrap().get(&fg);
+    let mut fg = fg.ok_or(StatusCode::BAD_REQUEST)?;
+    let events = fg.subscribe().await.or(Err(StatusCode::BAD_REQUEST))?;
+
+    let interval = config::get_or_default("ctrlport_stats_interval", 1000u64);
+    let stats = Timer::interval(Duration::from_millis(interval))
+        .then(move |_| {
+            let mut fg = fg.clone();
+            async move { fg.stats().await }
+        })
+        .take_while(|s| future::ready(s.is_ok()))
+        .filter_map(|s| future::ready(s.ok().map(FlowgraphEvent::Stats)));
+
+    let stream = stream::select(events, stats)
+        .map(|e| Event::default().json_data(e).map_err(axum::Error::new));
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+/// Frames of a binary stream, queued per client, before slow clients miss frames.
+const STREAM_QUEUE: usize = 16;
+
+/// Frame queues of the WebSocket clients of a stream.
+type StreamClients = Vec<mpsc::Sender<Arc<Vec<u8>>>>;
+
+/// Binary streams, published by blocks, with their clients.
+static STREAMS: Lazy<Mutex<HashMap<String, StreamClients>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Register a binary stream, served as WebSocket at `/api/stream/<name>/`.
+///
+/// Returns false, if the name is already in use.
+pub(crate) fn stream_register(name: &str) -> bool {
+    let mut s = STREAMS.lock().unwrap();
+    if s.contains_key(name) {
+        return false;
+    }
+    s.insert(name.to_string(), Vec::new());
+    true
+}
+
+/// Send a frame to all clients of a stream. Clients that cannot keep up miss the frame.
+///
+/// Returns the number of connected clients.
+pub(crate) fn stream_publish(name: &str, frame: Vec<u8>) -> usize {
+    let mut s = STREAMS.lock().unwrap();
+    let clients = match s.get_mut(name) {
+        Some(c) => c,
+        None => return 0,
+    };
+    let frame = Arc::new(frame);
+    let mut i = 0;
+    while i < clients.len() {
+        match clients[i].try_send(frame.clone()) {
+            Err(e) if e.is_disconnected() => {
+                clients.swap_remove(i);
+            }
+            _ => i += 1,
+        }
+    }
+    clients.len()
+}
+
+/// Remove a stream, closing the connections of its clients.
+pub(crate) fn stream_remove(name: &str) {
+    STREAMS.lock().unwrap().remove(name);
+}
+
+async fn stream(Path(name): Path<String>, mut req: Request<Body>) -> axum::response::Response {
+    use axum::response::IntoResponse;
+
+    let key = match req.headers().get(header::SEC_WEBSOCKET_KEY) {
+        Some(k) => derive_accept_key(k.as_bytes()),
+        None => return (StatusCode::BAD_REQUEST, "WebSocket upgrade required").into_response(),
+    };
+
+    let (tx, mut rx) = mpsc::channel::<Arc<Vec<u8>>>(STREAM_QUEUE);
+    match STREAMS.lock().unwrap().get_mut(&name) {
+        Some(clients) => clients.push(tx),
+        None => return StatusCode::NOT_FOUND.into_response(),
+    }
+
+    let upgrade = hyper::upgrade::on(&mut req);
+    tokio::spawn(async move {
+        let upgraded = match upgrade.await {
+            Ok(u) => u,
+            Err(e) => {
+                debug!("stream {}: upgrade failed ({})", name, e);
+                return;
+            }
+        };
+        let mut ws = WebSocketStream::from_raw_socket(upgraded.compat(), Role::Server, None).await;
+        while let Some(frame) = rx.next().await {
+            if ws.send(Message::Binary(frame.to_vec())).await.is_err() {
+                debug!("stream {}: client disconnected", name);
+                return;
+            }
+        }
+        let _ = ws.close(None).await;
+    });
+
+    (
+        StatusCode::SWITCHING_PROTOCOLS,
+        [
+            (header::CONNECTION, "upgrade".to_string()),
+            (header::UPGRADE, "websocket".to_string()),
+            (header::SEC_WEBSOCKET_ACCEPT, key),
+        ],
+    )
+        .into_response()
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        flowgraphs,
+        names,
+        flowgraph_description,
+        block_description,
+        handler_id,
+        handler_id_post,
+        block_form,
+        block_form_post,
+        block_schema,
+        stats,
+        terminate,
+        start,
+        events,
+    ),
+    components(schemas(
+        BlockDescription,
+        BlockState,
+        FlowgraphDescription,
+        FlowgraphEvent,
+        FlowgraphStats,
+        HandlerDescription,
+        HandlerForm,
+        ParameterDescription,
+        Pmt,
+        PmtKind,
+    ))
+)]
+struct ApiDoc;
+
+const SWAGGER_UI: &str = r##"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>FutureSDR API</title>
+<link rel="stylesheet" href="swagger-ui.css">
+</head>
+<body>
+<div id="swagger-ui"></div>
+<script src="swagger-ui-bundle.js"></script>
+<script>
+window.onload = () => {
+    window.ui = SwaggerUIBundle({ url: "openapi.json", dom_id: "#swagger-ui" });
+};
+</script>
+</body>
+</html>
+"##;
+
+/// Assets of the Swagger UI, vendored to work offline.
+const SWAGGER_UI_CSS: &str = include_str!("swagger-ui/swagger-ui.css");
+const SWAGGER_UI_JS: &str = include_str!("swagger-ui/swagger-ui-bundle.js");
+
+/// Configuration of the web server of the control port.
+///
+/// Besides custom routes, applications can mount static asset directories, override the index
+/// page, and serve everything under a path prefix (e.g., when running behind a reverse proxy).
+/// The prefix and index page can also be set with the `ctrlport_prefix` and `frontend_index`
+/// config options.
+///
+/// ```no_run
+/// use futuresdr::runtime::ControlPortConfig;
+/// use futuresdr::runtime::Runtime;
+///
+/// let config = ControlPortConfig::new()
+///     .assets("/static", "my-app/static")
+///     .index_file("my-app/index.html")
+///     .prefix("/sdr");
+/// let rt = Runtime::with_control_port(config);
+/// ```
+#[derive(Default)]
+pub struct ControlPortConfig {
+    routes: Option<Router>,
+    assets: Vec<(String, PathBuf)>,
+    index: Option<Index>,
+    prefix: Option<String>,
+}
+
+enum Index {
+    Html(String),
+    File(PathBuf),
+}
+
+impl ControlPortConfig {
+    pub fn new() -> ControlPortConfig {
+        ControlPortConfig::default()
+    }
+
+    /// Add custom routes.
+    ///
+    /// Custom routes take precedence over the index page and the frontend, i.e., a custom route
+    /// for `/` replaces the index page.
+    pub fn routes(mut self, routes: Router) -> ControlPortConfig {
+        self.routes = Some(routes);
+        self
+    }
+
+    /// Serve the static files in `dir` under `path`.
+    pub fn assets(mut self, path: impl AsRef<str>, dir: impl Into<PathBuf>) -> ControlPortConfig {
+        self.assets.push((normalize(path.as_ref()), dir.into()));
+        self
+    }
+
+    /// Serve the given HTML as index page.
+    pub fn index_html(mut self, html: impl Into<String>) -> ControlPortConfig {
+        self.index = Some(Index::Html(html.into()));
+        self
+    }
+
+    /// Serve the given file as index page.
+    pub fn index_file(mut self, file: impl Into<PathBuf>) -> ControlPortConfig {
+        self.index = Some(Index::File(file.into()));
+        self
+    }
+
+    /// Serve all routes under the given path prefix (e.g., `/sdr`).
+    pub fn prefix(mut self, prefix: impl AsRef<str>) -> ControlPortConfig {
+        self.prefix = Some(normalize(prefix.as_ref()));
+        self
+    }
+}
+
+/// Normalize a path to start with and not end with a `/` (empty for the root).
+fn normalize(p: &str) -> String {
+    let p = p.trim_matches('/');
+    if p.is_empty() {
+        String::new()
+    } else {
+        format!("/{}", p)
+    }
+}
+
+async fn io_error(error: std::io::Error) -> (StatusCode, String) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        format!("Unhandled internal error: {error}"),
+    )
+}
+
+/// Builds and starts a flowgraph, registered with
+/// [Runtime::add_named](crate::runtime::Runtime::add_named).
+pub(crate) type Launcher = Arc<dyn Fn() -> BoxFuture<'static, Result<()>> + Send + Sync>;
+
+/// Flowgraphs of the control port, addressed by id or name.
+///
+/// Terminated flowgraphs are removed lazily, i.e., when the flowgraphs are accessed.
+#[derive(Default)]
+pub(crate) struct Flowgraphs {
+    handles: Slab<FlowgraphHandle>,
+    names: HashMap<String, usize>,
+    launchers: HashMap<String, Launcher>,
+}
+
+impl Flowgraphs {
+    /// Add a flowgraph. A name that is already in use is reassigned to the new flowgraph.
+    ///
+    /// Numeric names are ignored, since they would be ambiguous with ids.
+    pub(crate) fn insert(&mut self, name: Option<String>, handle: FlowgraphHandle) -> usize {
+        self.prune();
+        let id = self.handles.insert(handle);
+        if let Some(name) = name {
+            if name.parse::<usize>().is_ok() {
+                warn!("ignoring numeric flowgraph name {}", name);
+            } else {
+                self.names.insert(name, id);
+            }
+        }
+        id
+    }
+
+    /// Get a flowgraph by id or name.
+    pub(crate) fn get(&mut self, fg: &str) -> Option<FlowgraphHandle> {
+        self.prune();
+        let id = match fg.parse::<usize>() {
+            Ok(id) => id,
+            Err(_) => *self.names.get(fg)?,
+        };
+        self.handles.get(id).cloned()
+    }
+
+    pub(crate) fn iter(&mut self) -> slab::Iter<'_, FlowgraphHandle> {
+        self.prune();
+        self.handles.iter()
+    }
+
+    fn names(&mut self) -> HashMap<String, usize> {
+        self.prune();
+        self.names.clone()
+    }
+
+    fn len(&mut self) -> usize {
+        self.prune();
+        self.handles.len()
+    }
+
+    /// Remove terminated flowgraphs and their names.
+    fn prune(&mut self) {
+        self.handles.retain(|_, h| !h.is_terminated());
+        let handles = &self.handles;
+        self.names.retain(|_, id| handles.contains(*id));
+    }
+}
+
 pub struct ControlPort {
-    flowgraphs: Arc<Mutex<Slab<FlowgraphHandle>>>,
+    flowgraphs: Arc<Mutex<Flowgraphs>>,
     thread: Option<JoinHandle<()>>,
 }
 
 impl ControlPort {
     pub fn new() -> Self {
-        let mut cp = ControlPort {
-            flowgraphs: Arc::new(Mutex::new(Slab::new())),
-            thread: None,
-        };
-        cp.start(None);
-        cp
+        Self::with_config(ControlPortConfig::new())
     }
 
     pub fn with_routes(routes: Router) -> Self {
+        Self::with_config(ControlPortConfig::new().routes(routes))
+    }
+
+    pub fn with_config(config: ControlPortConfig) -> Self {
         let mut cp = ControlPort {
-            flowgraphs: Arc::new(Mutex::new(Slab::new())),
+            flowgraphs: Arc::new(Mutex::new(Flowgraphs::default())),
             thread: None,
         };
-        cp.start(Some(routes));
+        cp.start(config);
         cp
     }
 
     pub fn add_flowgraph(&self, handle: FlowgraphHandle) -> usize {
         let mut v = self.flowgraphs.lock().unwrap();
-        v.insert(handle)
+        v.insert(None, handle)
+    }
+
+    /// Add a flowgraph, addressable by its name. A name that is already in use is reassigned.
+    pub fn add_named_flowgraph(&self, name: String, handle: FlowgraphHandle) -> usize {
+        let mut v = self.flowgraphs.lock().unwrap();
+        v.insert(Some(name), handle)
+    }
+
+    /// Register a flowgraph that is started through `POST /api/fg/{name}/start/`.
+    pub(crate) fn add_launcher(&self, name: String, launcher: Launcher) {
+        let mut v = self.flowgraphs.lock().unwrap();
+        v.launchers.insert(name, launcher);
+    }
+
+    pub(crate) fn flowgraphs(&self) -> Arc<Mutex<Flowgraphs>> {
+        self.flowgraphs.clone()
     }
 
-    fn start(&mut self, custom_routes: Option<Router>) {
+    fn start(&mut self, cp_config: ControlPortConfig) {
+        if let Some(addr) = config::get::<SocketAddr>("ctrlport_thrift_bind") {
+            thrift_port::start(addr, self.flowgraphs.clone());
+        }
+
         if !config::config().ctrlport_enable {
             return;
         }
@@ -140,46 +954,7 @@ impl ControlPort {
             return;
         }
 
-        let mut app = Router::new()
-            .route("/api/fg/", get(flowgraphs))
-            .route("/api/fg/:fg/", get(flowgraph_description))
-            .route("/api/fg/:fg/block/:blk/", get(block_description))
-            .route(
-                "/api/fg/:fg/block/:blk/call/:handler/",
-                get(handler_id).post(handler_id_post),
-            )
-            .route(
-                "/api/block/*foo",
-                any(|uri: Uri| async move {
-                    let u = uri.to_string().split_off(11);
-                    Redirect::permanent(&format!("/api/fg/0/block/{}", u))
-                }),
-            )
-            .layer(AddExtensionLayer::new(self.flowgraphs.clone()))
-            .layer(CorsLayer::permissive());
-
-        if let Some(c) = custom_routes {
-            app = app.nest("/", c);
-        }
-
-        let frontend = if let Some(ref p) = config::config().frontend_path {
-            Some(ServeDir::new(p))
-        } else if path::Path::new(relative!("frontend/dist")).is_dir() {
-            Some(ServeDir::new(relative!("frontend/dist")))
-        } else {
-            None
-        };
-
-        if let Some(service) = frontend {
-            app = app.fallback(get_service(service).handle_error(
-                |error: std::io::Error| async move {
-                    (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        format!("Unhandled internal error: {error}"),
-                    )
-                },
-            ));
-        }
+        let app = router(self.flowgraphs.clone(), cp_config);
 
         let handle = std::thread::spawn(move || {
             let runtime = tokio::runtime::Builder::new_current_thread()
@@ -202,8 +977,506 @@ impl ControlPort {
     }
 }
 
+/// Routes of the control port.
+pub(crate) fn router(registry: Arc<Mutex<Flowgraphs>>, cp_config: ControlPortConfig) -> Router {
+    let prefix = cp_config
+        .prefix
+        .or_else(|| config::get::<String>("ctrlport_prefix").map(|p| normalize(&p)))
+        .unwrap_or_default();
+    let doc_prefix = prefix.clone();
+    let block_prefix = prefix.clone();
+
+    let mut app = Router::new()
+        .route("/api/fg/", get(flowgraphs))
+        .route("/api/names/", get(names))
+        .route("/api/fg/:fg/", get(flowgraph_description))
+        .route("/api/doc/", get(|| async { Html(SWAGGER_UI) }))
+        .route(
+            "/api/doc",
+            get(move || {
+                let p = doc_prefix.clone();
+                async move { Redirect::permanent(&format!("{}/api/doc/", p)) }
+            }),
+        )
+        .route(
+            "/api/doc/openapi.json",
+            get(|| async { Json(ApiDoc::openapi()) }),
+        )
+        .route(
+            "/api/doc/swagger-ui.css",
+            get(|| async { ([(header::CONTENT_TYPE, "text/css")], SWAGGER_UI_CSS) }),
+        )
+        .route(
+            "/api/doc/swagger-ui-bundle.js",
+            get(|| async { ([(header::CONTENT_TYPE, "text/javascript")], SWAGGER_UI_JS) }),
+        )
+        .route("/api/fg/:fg/events/", get(events))
+        .route("/api/stream/:name/", get(stream))
+        .route("/api/fg/:fg/stats/", get(stats))
+        .route("/api/fg/:fg/terminate/", post(terminate))
+        .route("/api/fg/:fg/start/", post(start))
+        .route("/api/fg/:fg/block/:blk/", get(block_description))
+        .route("/api/fg/:fg/block/:blk/form/", get(block_form))
+        .route(
+            "/api/fg/:fg/block/:blk/form/:handler/",
+            post(block_form_post),
+        )
+        .route("/api/fg/:fg/block/:blk/schema/", get(block_schema))
+        .route(
+            "/api/fg/:fg/block/:blk/call/:handler/",
+            get(handler_id).post(handler_id_post),
+        )
+        .route(
+            "/api/block/*foo",
+            any(move |uri: Uri| {
+                let p = block_prefix.clone();
+                async move {
+                    let u = uri.to_string().split_off(11);
+                    Redirect::permanent(&format!("{}/api/fg/0/block/{}", p, u))
+                }
+            }),
+        )
+        .layer(AddExtensionLayer::new(registry))
+        .layer(CorsLayer::permissive());
+
+    if let Some(c) = cp_config.routes {
+        app = app.nest("/", c);
+    }
+
+    for (path, dir) in cp_config.assets {
+        app = app.nest(
+            &path,
+            get_service(ServeDir::new(dir)).handle_error(io_error),
+        );
+    }
+
+    // The index page and the frontend are served by the fallback, so that custom routes take
+    // precedence and may replace the index page without conflicting routes.
+    let mut fallback = Router::new();
+    let index = cp_config
+        .index
+        .or_else(|| config::get::<String>("frontend_index").map(|f| Index::File(PathBuf::from(f))));
+    match index {
+        Some(Index::Html(html)) => {
+            fallback = fallback.route("/", get(move || future::ready(Html(html.clone()))));
+        }
+        Some(Index::File(file)) => {
+            fallback = fallback.route(
+                "/",
+                get_service(ServeFile::new(file)).handle_error(io_error),
+            );
+        }
+        None => {}
+    }
+
+    let frontend = if let Some(ref p) = config::config().frontend_path {
+        Some(ServeDir::new(p))
+    } else if path::Path::new(relative!("frontend/dist")).is_dir() {
+        Some(ServeDir::new(relative!("frontend/dist")))
+    } else {
+        None
+    };
+
+    if let Some(service) = frontend {
+        fallback = fallback.fallback(get_service(service).handle_error(io_error));
+    }
+    app = app.fallback(fallback);
+
+    if !prefix.is_empty() {
+        app = Router::new().nest(&prefix, any_service(app));
+    }
+
+    app
+}
+
 impl Default for ControlPort {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use axum::body::Bytes;
+    use tower::ServiceExt;
+
+    use crate::async_io::block_on;
+    use crate::blocks::soapy::{MockSoapyDevice, SoapyDevSpec, SoapyDirection};
+    use crate::blocks::{MessageCopy, MockSoapySourceBuilder, NullSink};
+    use crate::num_complex::Complex32;
+    use crate::runtime::channel::{channel, Receiver};
+    use crate::runtime::Flowgraph;
+    use crate::runtime::FlowgraphMessage;
+    use crate::runtime::Runtime;
+
+    /// Send a request to the router, returning status and body.
+    pub(crate) async fn request(
+        app: &Router,
+        method: &str,
+        uri: &str,
+        body: Option<&str>,
+    ) -> (StatusCode, Bytes) {
+        send(app, method, uri, "application/json", body).await
+    }
+
+    async fn post_form(app: &Router, uri: &str, body: &str) -> StatusCode {
+        send(
+            app,
+            "POST",
+            uri,
+            "application/x-www-form-urlencoded",
+            Some(body),
+        )
+        .await
+        .0
+    }
+
+    async fn send(
+        app: &Router,
+        method: &str,
+        uri: &str,
+        content_type: &str,
+        body: Option<&str>,
+    ) -> (StatusCode, Bytes) {
+        let req = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::CONTENT_TYPE, content_type)
+            .body(Body::from(body.unwrap_or_default().to_string()))
+            .unwrap();
+        let res = app.clone().oneshot(req).await.unwrap();
+        let status = res.status();
+        let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+        (status, body)
+    }
+
+    /// Serve the router on an ephemeral port of localhost.
+    pub(crate) fn serve(app: Router) -> SocketAddr {
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap();
+            runtime.block_on(async move {
+                let s = axum::Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0)))
+                    .serve(app.into_make_service());
+                tx.send(s.local_addr()).unwrap();
+                s.await.unwrap();
+            });
+        });
+        rx.recv().unwrap()
+    }
+
+    fn handle() -> (FlowgraphHandle, Receiver<FlowgraphMessage>) {
+        let (tx, rx) = channel(1);
+        (FlowgraphHandle::new(tx), rx)
+    }
+
+    #[test]
+    fn resolve_flowgraphs() {
+        let mut f = Flowgraphs::default();
+        let (a, _ra) = handle();
+        let (b, _rb) = handle();
+        let a = f.insert(None, a);
+        let b = f.insert(Some("adsb".to_string()), b);
+        assert!(f.get(&a.to_string()).is_some());
+        assert!(f.get(&b.to_string()).is_some());
+        assert!(f.get("adsb").is_some());
+        assert!(f.get("ais").is_none());
+        assert!(f.get("42").is_none());
+        assert_eq!(f.names(), HashMap::from([("adsb".to_string(), b)]));
+
+        // numeric names would shadow ids
+        let (c, _rc) = handle();
+        let c = f.insert(Some(a.to_string()), c);
+        assert_eq!(f.names(), HashMap::from([("adsb".to_string(), b)]));
+        assert!(f.get(&c.to_string()).is_some());
+        assert_eq!(f.len(), 3);
+    }
+
+    #[test]
+    fn reassign_name() {
+        let mut f = Flowgraphs::default();
+        let (a, _ra) = handle();
+        let (b, rb) = handle();
+        let a = f.insert(Some("adsb".to_string()), a);
+        let b = f.insert(Some("adsb".to_string()), b);
+        assert_eq!(f.names(), HashMap::from([("adsb".to_string(), b)]));
+        assert!(f.get(&a.to_string()).is_some());
+
+        // the name is not passed back to the old flowgraph
+        drop(rb);
+        assert!(f.get("adsb").is_none());
+        assert!(f.names().is_empty());
+        assert!(f.get(&a.to_string()).is_some());
+    }
+
+    #[test]
+    fn remove_terminated() {
+        let mut f = Flowgraphs::default();
+        let (a, ra) = handle();
+        let (b, _rb) = handle();
+        let a = f.insert(Some("adsb".to_string()), a);
+        let b = f.insert(Some("ais".to_string()), b);
+        assert_eq!(f.len(), 2);
+
+        drop(ra);
+        assert_eq!(f.len(), 1);
+        assert!(f.get(&a.to_string()).is_none());
+        assert!(f.get("adsb").is_none());
+        assert_eq!(f.names(), HashMap::from([("ais".to_string(), b)]));
+        assert_eq!(f.iter().count(), 1);
+    }
+
+    #[test]
+    fn prefix_assets_index() {
+        let dir =
+            std::env::temp_dir().join(format!("futuresdr-test-{}-assets", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("app.js"), "let x = 1;").unwrap();
+
+        let config = ControlPortConfig::new()
+            .assets("/static/", dir.clone())
+            .index_html("<h1>SDR</h1>")
+            .prefix("sdr/");
+        let app = router(Arc::new(Mutex::new(Flowgraphs::default())), config);
+
+        // static files are served with tokio
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async move {
+            let (status, body) = request(&app, "GET", "/sdr/api/fg/", None).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(&body[..], b"0");
+            let (status, _) = request(&app, "GET", "/api/fg/", None).await;
+            assert_eq!(status, StatusCode::NOT_FOUND);
+
+            let (status, body) = request(&app, "GET", "/sdr/static/app.js", None).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(&body[..], b"let x = 1;");
+            let (status, _) = request(&app, "GET", "/sdr/static/lib.js", None).await;
+            assert_eq!(status, StatusCode::NOT_FOUND);
+
+            let (status, body) = request(&app, "GET", "/sdr/", None).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(&body[..], b"<h1>SDR</h1>");
+        });
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn custom_routes_replace_index() {
+        let routes = Router::new()
+            .route("/", get(|| async { "custom" }))
+            .route("/hello/", get(|| async { "hello" }));
+        let config = ControlPortConfig::new()
+            .routes(routes)
+            .index_html("<h1>SDR</h1>");
+        let app = router(Arc::new(Mutex::new(Flowgraphs::default())), config);
+
+        block_on(async move {
+            let (status, body) = request(&app, "GET", "/", None).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(&body[..], b"custom");
+            let (_, body) = request(&app, "GET", "/hello/", None).await;
+            assert_eq!(&body[..], b"hello");
+            let (status, _) = request(&app, "GET", "/api/fg/", None).await;
+            assert_eq!(status, StatusCode::OK);
+        });
+    }
+
+    #[test]
+    fn described_handlers() {
+        let dev = MockSoapyDevice::new(1);
+        let mut fg = Flowgraph::new();
+        let src = fg.add_block(
+            MockSoapySourceBuilder::new()
+                .device(SoapyDevSpec::Mock(dev.clone()))
+                .dev_channels(vec![0])
+                .build(),
+        );
+        let snk = fg.add_block(NullSink::<Complex32>::new());
+        fg.connect_stream(src, "out", snk, "in").unwrap();
+        let copy = fg.add_block(MessageCopy::new());
+
+        let rt = Runtime::new();
+        block_on(async move {
+            let (task, mut handle) = rt.start(fg).await;
+            let registry = Arc::new(Mutex::new(Flowgraphs::default()));
+            let fg = registry.lock().unwrap().insert(None, handle.clone());
+            let app = router(registry, ControlPortConfig::new());
+            let blk = format!("/api/fg/{fg}/block/{src}");
+
+            let (status, body) = request(&app, "GET", &format!("{blk}/form/"), None).await;
+            assert_eq!(status, StatusCode::OK);
+            let html = String::from_utf8(body.to_vec()).unwrap();
+            assert!(html.contains("<legend>freq</legend>"));
+            assert!(html.contains("value [Hz]"));
+            assert!(html.contains("<option>F64</option>"));
+            assert!(!html.contains("<option>String</option>"));
+            assert!(html.contains("<input type=\"hidden\" name=\"kind\" value=\"MapStrPmt\">"));
+            assert!(html.contains("gain [dB] <input type=\"text\" name=\"gain\">"));
+
+            let (status, body) = request(&app, "GET", &format!("{blk}/schema/"), None).await;
+            assert_eq!(status, StatusCode::OK);
+            let schema: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            let freq = &schema["properties"]["freq"];
+            assert_eq!(freq["description"], "Center frequency [Hz]");
+            assert_eq!(freq["oneOf"].as_array().unwrap().len(), 6);
+            assert_eq!(freq["oneOf"][0]["required"], json!(["F64"]));
+            let cmd = &schema["properties"]["cmd"]["oneOf"][0]["properties"]["MapStrPmt"];
+            assert_eq!(cmd["properties"]["gain"]["description"], "Gain [dB]");
+            assert_eq!(
+                cmd["properties"]["antenna"]["properties"]["String"]["type"],
+                "string"
+            );
+
+            // handlers without description fall back to the generic kinds
+            let (_, body) = request(
+                &app,
+                "GET",
+                &format!("/api/fg/{fg}/block/{copy}/schema/"),
+                None,
+            )
+            .await;
+            let schema: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(
+                schema["properties"]["in"]["oneOf"]
+                    .as_array()
+                    .unwrap()
+                    .len(),
+                FORM_KINDS.len()
+            );
+            let (_, body) = request(
+                &app,
+                "GET",
+                &format!("/api/fg/{fg}/block/{copy}/form/"),
+                None,
+            )
+            .await;
+            assert!(String::from_utf8(body.to_vec())
+                .unwrap()
+                .contains("<option>String</option>"));
+
+            let status =
+                post_form(&app, &format!("{blk}/form/freq/"), "kind=F64&value=100e6").await;
+            assert_eq!(status, StatusCode::OK);
+            let status = post_form(
+                &app,
+                &format!("{blk}/form/cmd/"),
+                "kind=MapStrPmt&chan=0&gain=12&antenna=",
+            )
+            .await;
+            assert_eq!(status, StatusCode::OK);
+            let status =
+                post_form(&app, &format!("{blk}/form/freq/"), "kind=String&value=high").await;
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            let status = post_form(
+                &app,
+                &format!("{blk}/form/cmd/"),
+                "kind=MapStrPmt&gain=high",
+            )
+            .await;
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            let status = post_form(&app, &format!("{blk}/form/foo/"), "kind=Null").await;
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+
+            let s = dev.settings(&SoapyDirection::Rx, 0);
+            assert_eq!(s.frequency, Some(100e6));
+            assert_eq!(s.gain, Some(12.0));
+            assert_eq!(s.antenna, None);
+
+            handle.terminate().await.unwrap();
+            task.await.unwrap();
+        });
+    }
+
+    /// Collect the `$ref`s of a JSON document.
+    fn refs<'a>(v: &'a serde_json::Value, out: &mut Vec<&'a str>) {
+        match v {
+            serde_json::Value::Object(m) => {
+                if let Some(serde_json::Value::String(r)) = m.get("$ref") {
+                    out.push(r);
+                }
+                m.values().for_each(|v| refs(v, out));
+            }
+            serde_json::Value::Array(a) => a.iter().for_each(|v| refs(v, out)),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn openapi_doc() {
+        let app = router(
+            Arc::new(Mutex::new(Flowgraphs::default())),
+            ControlPortConfig::new(),
+        );
+
+        block_on(async move {
+            let (status, body) = request(&app, "GET", "/api/doc/openapi.json", None).await;
+            assert_eq!(status, StatusCode::OK);
+            let doc: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert!(doc["openapi"].as_str().unwrap().starts_with("3."));
+            assert!(serde_json::from_value::<utoipa::openapi::OpenApi>(doc.clone()).is_ok());
+
+            let paths = doc["paths"].as_object().unwrap();
+            let expected = [
+                ("/api/fg/", "get"),
+                ("/api/names/", "get"),
+                ("/api/fg/{fg}/", "get"),
+                ("/api/fg/{fg}/events/", "get"),
+                ("/api/fg/{fg}/stats/", "get"),
+                ("/api/fg/{fg}/terminate/", "post"),
+                ("/api/fg/{fg}/start/", "post"),
+                ("/api/fg/{fg}/block/{blk}/", "get"),
+                ("/api/fg/{fg}/block/{blk}/form/", "get"),
+                ("/api/fg/{fg}/block/{blk}/form/{handler}/", "post"),
+                ("/api/fg/{fg}/block/{blk}/schema/", "get"),
+                ("/api/fg/{fg}/block/{blk}/call/{handler}/", "get"),
+                ("/api/fg/{fg}/block/{blk}/call/{handler}/", "post"),
+            ];
+            for (path, method) in expected {
+                let op = &paths[path][method];
+                assert!(op.is_object(), "{method} {path} missing");
+                assert!(op["responses"]["200"].is_object(), "{method} {path}");
+                // each path parameter is documented
+                for p in path.split('/').filter(|p| p.starts_with('{')) {
+                    let name = p.trim_matches(|c| c == '{' || c == '}');
+                    assert!(
+                        op["parameters"]
+                            .as_array()
+                            .map_or(false, |a| a.iter().any(|x| x["name"] == name)),
+                        "{method} {path}: parameter {name}"
+                    );
+                }
+            }
+            let operations: usize = paths.values().map(|p| p.as_object().unwrap().len()).sum();
+            assert_eq!(operations, expected.len());
+
+            // references resolve to the schemas of the components
+            let mut r = Vec::new();
+            refs(&doc, &mut r);
+            assert!(r.contains(&"#/components/schemas/HandlerDescription"));
+            for r in r {
+                let name = r.strip_prefix("#/components/schemas/").unwrap();
+                assert!(doc["components"]["schemas"][name].is_object(), "{r}");
+            }
+
+            // the UI is served without network access
+            let (_, body) = request(&app, "GET", "/api/doc/", None).await;
+            let html = String::from_utf8(body.to_vec()).unwrap();
+            assert!(!html.contains("https://"));
+            for asset in ["swagger-ui.css", "swagger-ui-bundle.js"] {
+                assert!(html.contains(asset));
+                let (status, body) = request(&app, "GET", &format!("/api/doc/{asset}"), None).await;
+                assert_eq!(status, StatusCode::OK);
+                assert!(body.len() > 10_000);
+            }
+        });
+    }
+}
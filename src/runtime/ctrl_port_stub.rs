@@ -10,6 +10,10 @@ impl ControlPort {
     pub fn add_flowgraph(&self, _handle: FlowgraphHandle) -> usize {
         0
     }
+
+    pub fn add_named_flowgraph(&self, _name: String, _handle: FlowgraphHandle) -> usize {
+        0
+    }
 }
 
 impl Default for ControlPort {
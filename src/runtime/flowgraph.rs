@@ -1,8 +1,11 @@
-use futures::channel::mpsc::Sender;
+use futures::channel::mpsc;
 use futures::channel::oneshot;
 use futures::SinkExt;
 use futuresdr_pmt::BlockDescription;
 use futuresdr_pmt::FlowgraphDescription;
+use futuresdr_pmt::FlowgraphEvent;
+use futuresdr_pmt::FlowgraphStats;
+use serde::{Deserialize, Serialize};
 use std::cmp::{Eq, PartialEq};
 use std::fmt::Debug;
 use std::hash::Hash;
@@ -15,6 +18,10 @@ use crate::runtime::buffer::circular::Circular;
 use crate::runtime::buffer::slab::Slab;
 use crate::runtime::buffer::BufferBuilder;
 use crate::runtime::buffer::BufferWriter;
+use crate::runtime::channel::Sender;
+use crate::runtime::config;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::runtime::session::{recordable, Recording, SessionEvent};
 use crate::runtime::Block;
 use crate::runtime::BlockDescriptionError;
 use crate::runtime::BlockMessage;
@@ -113,7 +120,7 @@ impl Default for Flowgraph {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum PortId {
     Index(usize),
     Name(String),
@@ -140,11 +147,29 @@ impl From<String> for PortId {
 #[derive(Clone)]
 pub struct FlowgraphHandle {
     inbox: Sender<FlowgraphMessage>,
+    #[cfg(not(target_arch = "wasm32"))]
+    recording: Option<Recording>,
 }
 
 impl FlowgraphHandle {
     pub(crate) fn new(inbox: Sender<FlowgraphMessage>) -> FlowgraphHandle {
-        FlowgraphHandle { inbox }
+        FlowgraphHandle {
+            inbox,
+            #[cfg(not(target_arch = "wasm32"))]
+            recording: None,
+        }
+    }
+
+    /// Whether the flowgraph terminated.
+    #[cfg(all(feature = "web", not(target_arch = "wasm32")))]
+    pub(crate) fn is_terminated(&self) -> bool {
+        self.inbox.is_closed()
+    }
+
+    /// Record the interactions of this handle and its clones.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn set_recording(&mut self, recording: Recording) {
+        self.recording = Some(recording);
     }
 
     pub async fn call(
@@ -153,17 +178,40 @@ impl FlowgraphHandle {
         port_id: impl Into<PortId>,
         data: Pmt,
     ) -> result::Result<(), CallbackError> {
+        let port_id = port_id.into();
+        #[cfg(not(target_arch = "wasm32"))]
+        let record = self
+            .recording
+            .clone()
+            .map(|r| (r.time(), port_id.clone(), recordable(&data), r));
+
         let (tx, rx) = oneshot::channel::<result::Result<(), CallbackError>>();
-        self.inbox
+        let res = match self
+            .inbox
             .send(FlowgraphMessage::BlockCall {
                 block_id,
-                port_id: port_id.into(),
+                port_id,
                 data,
                 tx,
             })
             .await
-            .map_err(|_| CallbackError::InvalidBlock)?;
-        rx.await.map_err(|_| CallbackError::HandlerError)?
+        {
+            Ok(_) => rx.await.unwrap_or(Err(CallbackError::HandlerError)),
+            Err(_) => Err(CallbackError::InvalidBlock),
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some((time, port_id, data, r)) = record {
+            r.record(SessionEvent::Call {
+                flowgraph: r.flowgraph(),
+                time,
+                block_id,
+                port_id,
+                data,
+                result: res.as_ref().map(|_| ()).map_err(|e| e.to_string()),
+            });
+        }
+        res
     }
 
     pub async fn callback(
@@ -172,20 +220,44 @@ impl FlowgraphHandle {
         port_id: impl Into<PortId>,
         data: Pmt,
     ) -> result::Result<Pmt, CallbackError> {
+        let port_id = port_id.into();
+        #[cfg(not(target_arch = "wasm32"))]
+        let record = self
+            .recording
+            .clone()
+            .map(|r| (r.time(), port_id.clone(), recordable(&data), r));
+
         let (tx, rx) = oneshot::channel::<result::Result<Pmt, CallbackError>>();
-        self.inbox
+        let res = match self
+            .inbox
             .send(FlowgraphMessage::BlockCallback {
                 block_id,
-                port_id: port_id.into(),
+                port_id,
                 data,
                 tx,
             })
             .await
-            .map_err(|_| CallbackError::InvalidBlock)?;
-        rx.await.map_err(|_| CallbackError::HandlerError)?
+        {
+            Ok(_) => rx.await.unwrap_or(Err(CallbackError::HandlerError)),
+            Err(_) => Err(CallbackError::InvalidBlock),
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some((time, port_id, data, r)) = record {
+            r.record(SessionEvent::Callback {
+                flowgraph: r.flowgraph(),
+                time,
+                block_id,
+                port_id,
+                data,
+                result: res.as_ref().map(recordable).map_err(|e| e.to_string()),
+            });
+        }
+        res
     }
 
     pub async fn description(&mut self) -> Result<FlowgraphDescription> {
+        self.record_query(|| "description".to_string());
         let (tx, rx) = oneshot::channel::<FlowgraphDescription>();
         self.inbox
             .send(FlowgraphMessage::FlowgraphDescription { tx })
@@ -195,6 +267,7 @@ impl FlowgraphHandle {
     }
 
     pub async fn block_description(&mut self, block_id: usize) -> Result<BlockDescription> {
+        self.record_query(|| format!("block_description {block_id}"));
         let (tx, rx) =
             oneshot::channel::<result::Result<BlockDescription, BlockDescriptionError>>();
         self.inbox
@@ -204,10 +277,51 @@ impl FlowgraphHandle {
         Ok(d)
     }
 
+    /// Subscribe to runtime events (block state changes, termination).
+    ///
+    /// The stream ends when the flowgraph terminates. Events are dropped for
+    /// subscribers that do not keep up.
+    pub async fn subscribe(&mut self) -> Result<mpsc::Receiver<FlowgraphEvent>> {
+        self.record_query(|| "subscribe".to_string());
+        let (tx, rx) = mpsc::channel::<FlowgraphEvent>(config::config().queue_size);
+        self.inbox.send(FlowgraphMessage::Subscribe { tx }).await?;
+        Ok(rx)
+    }
+
+    /// Get a snapshot of the state of all blocks.
+    pub async fn stats(&mut self) -> Result<FlowgraphStats> {
+        self.record_query(|| "stats".to_string());
+        let (tx, rx) = oneshot::channel::<FlowgraphStats>();
+        self.inbox.send(FlowgraphMessage::Stats { tx }).await?;
+        let s = rx.await?;
+        Ok(s)
+    }
+
     pub async fn terminate(&mut self) -> Result<()> {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(r) = self.recording.as_ref() {
+            r.record(SessionEvent::Terminate {
+                flowgraph: r.flowgraph(),
+                time: r.time(),
+            });
+        }
         self.inbox.send(FlowgraphMessage::Terminate).await?;
         Ok(())
     }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn record_query(&self, query: impl FnOnce() -> String) {
+        if let Some(r) = self.recording.as_ref() {
+            r.record(SessionEvent::Query {
+                flowgraph: r.flowgraph(),
+                time: r.time(),
+                query: query(),
+            });
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn record_query(&self, _query: impl FnOnce() -> String) {}
 }
 
 #[derive(Debug, PartialEq, Hash)]
@@ -1,5 +1,5 @@
 //! Message/Event/RPC-based Ports
-use futures::channel::mpsc::Sender;
+use crate::runtime::channel::Sender;
 use futures::prelude::*;
 use std::future::Future;
 use std::pin::Pin;
@@ -8,6 +8,7 @@ use std::sync::Arc;
 use crate::anyhow::Result;
 use crate::runtime::BlockMessage;
 use crate::runtime::BlockMeta;
+use crate::runtime::HandlerDescription;
 use crate::runtime::Pmt;
 use crate::runtime::PortId;
 
@@ -24,6 +25,21 @@ pub struct MessageInput<T: ?Sized> {
             + Send
             + Sync,
     >,
+    #[allow(clippy::type_complexity)]
+    batch_handler: Option<
+        Arc<
+            dyn for<'a> Fn(
+                    &'a mut T,
+                    &'a mut MessageIo<T>,
+                    &'a mut BlockMeta,
+                    Vec<Pmt>,
+                )
+                    -> Pin<Box<dyn Future<Output = Result<Pmt>> + Send + 'a>>
+                + Send
+                + Sync,
+        >,
+    >,
+    description: HandlerDescription,
 }
 
 impl<T: Send + ?Sized> MessageInput<T> {
@@ -45,6 +61,39 @@ impl<T: Send + ?Sized> MessageInput<T> {
         MessageInput {
             name: name.to_string(),
             handler,
+            batch_handler: None,
+            description: HandlerDescription::default(),
+        }
+    }
+
+    /// Input with a handler that receives all queued messages at once.
+    ///
+    /// Messages that are called individually, e.g., through the control port, are passed as
+    /// a batch of one.
+    #[allow(clippy::type_complexity)]
+    pub fn new_batch(
+        name: &str,
+        batch_handler: Arc<
+            dyn for<'a> Fn(
+                    &'a mut T,
+                    &'a mut MessageIo<T>,
+                    &'a mut BlockMeta,
+                    Vec<Pmt>,
+                )
+                    -> Pin<Box<dyn Future<Output = Result<Pmt>> + Send + 'a>>
+                + Send
+                + Sync,
+        >,
+    ) -> MessageInput<T>
+    where
+        T: 'static,
+    {
+        let h = batch_handler.clone();
+        MessageInput {
+            name: name.to_string(),
+            handler: Arc::new(move |k, mio, meta, p| (h)(k, mio, meta, vec![p])),
+            batch_handler: Some(batch_handler),
+            description: HandlerDescription::default(),
         }
     }
 
@@ -64,9 +113,42 @@ impl<T: Send + ?Sized> MessageInput<T> {
         self.handler.clone()
     }
 
+    #[allow(clippy::type_complexity)]
+    pub fn get_batch_handler(
+        &self,
+    ) -> Option<
+        Arc<
+            dyn for<'a> Fn(
+                    &'a mut T,
+                    &'a mut MessageIo<T>,
+                    &'a mut BlockMeta,
+                    Vec<Pmt>,
+                )
+                    -> Pin<Box<dyn Future<Output = Result<Pmt>> + Send + 'a>>
+                + Send
+                + Sync,
+        >,
+    > {
+        self.batch_handler.clone()
+    }
+
+    /// Whether the handler receives batches of messages.
+    pub fn is_batch(&self) -> bool {
+        self.batch_handler.is_some()
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
+
+    /// Description of the values, the handler accepts.
+    pub fn description(&self) -> &HandlerDescription {
+        &self.description
+    }
+
+    pub fn set_description(&mut self, description: HandlerDescription) {
+        self.description = description;
+    }
 }
 
 #[derive(Debug)]
@@ -140,6 +222,13 @@ impl<T: Send + ?Sized> MessageIo<T> {
         self.inputs.iter().map(|x| x.name().to_string()).collect()
     }
 
+    pub fn input_descriptions(&self) -> Vec<HandlerDescription> {
+        self.inputs
+            .iter()
+            .map(|x| x.description().clone())
+            .collect()
+    }
+
     pub fn outputs(&self) -> &Vec<MessageOutput> {
         &self.outputs
     }
@@ -174,7 +263,7 @@ pub struct MessageIoBuilder<T> {
     outputs: Vec<MessageOutput>,
 }
 
-impl<T: Send> MessageIoBuilder<T> {
+impl<T: Send + 'static> MessageIoBuilder<T> {
     pub fn new() -> MessageIoBuilder<T> {
         MessageIoBuilder {
             inputs: Vec::new(),
@@ -200,6 +289,47 @@ impl<T: Send> MessageIoBuilder<T> {
         self
     }
 
+    /// Add an input, whose handler receives all messages that are queued for the port in one
+    /// invocation, avoiding the overhead of one handler call per message for high message
+    /// rates. The return value of the handler is passed to all callers.
+    #[must_use]
+    pub fn add_batch_input(
+        mut self,
+        name: &str,
+        c: impl for<'a> Fn(
+                &'a mut T,
+                &'a mut MessageIo<T>,
+                &'a mut BlockMeta,
+                Vec<Pmt>,
+            ) -> Pin<Box<dyn Future<Output = Result<Pmt>> + Send + 'a>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> MessageIoBuilder<T> {
+        self.inputs.push(MessageInput::new_batch(name, Arc::new(c)));
+        self
+    }
+
+    /// Describe the values, the input `name` accepts, e.g., to generate forms and schemas in
+    /// the control port.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no input `name` was added before.
+    #[must_use]
+    pub fn describe_input(
+        mut self,
+        name: &str,
+        description: HandlerDescription,
+    ) -> MessageIoBuilder<T> {
+        self.inputs
+            .iter_mut()
+            .find(|i| i.name() == name)
+            .unwrap_or_else(|| panic!("no message input {name}"))
+            .set_description(description);
+        self
+    }
+
     #[must_use]
     pub fn add_output(mut self, name: &str) -> MessageIoBuilder<T> {
         self.outputs.push(MessageOutput::new(name));
@@ -211,7 +341,7 @@ impl<T: Send> MessageIoBuilder<T> {
     }
 }
 
-impl<T: Send> Default for MessageIoBuilder<T> {
+impl<T: Send + 'static> Default for MessageIoBuilder<T> {
     fn default() -> Self {
         Self::new()
     }
@@ -1,9 +1,15 @@
-use futures::channel::mpsc::Sender;
+use crate::runtime::channel::Sender;
 use std::any::Any;
+use std::cmp;
 use std::fmt::Debug;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use crate::anyhow::{anyhow, Result};
 
 use crate::runtime::buffer::BufferReaderHost;
 use crate::runtime::buffer::BufferWriterHost;
+use crate::runtime::testing::Schedule;
 use crate::runtime::Block;
 use crate::runtime::BlockMessage;
 use crate::runtime::BufferReader;
@@ -11,43 +17,66 @@ use crate::runtime::BufferWriter;
 use crate::runtime::ItemTag;
 use crate::runtime::WorkIo;
 
+/// Output space in items, while draining a kernel after the schedule.
+const DRAIN_SPACE: usize = 65536;
+/// Maximum number of `work()` calls, before the next step of a schedule.
+const MAX_CALLS: usize = 1024;
+
+/// Items of a port that are visible to the kernel and items that were processed.
+#[derive(Debug, Default)]
+struct MockPort {
+    limit: AtomicUsize,
+    done: AtomicUsize,
+}
+
 pub struct Mocker {
     block: Block,
+    inputs: Vec<(usize, Arc<MockPort>)>,
+    outputs: Vec<Arc<MockPort>>,
 }
 
 impl Mocker {
     pub fn new(block: Block) -> Self {
-        Mocker { block }
+        Mocker {
+            block,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+        }
     }
 
     pub fn input<T>(&mut self, id: usize, data: Vec<T>)
     where
         T: Debug + Send + 'static,
     {
-        self.block
-            .stream_input_mut(id)
-            .set_reader(BufferReader::Host(Box::new(MockReader::new(
-                data,
-                Vec::new(),
-            ))));
+        self.input_with_tags(id, data, Vec::new());
     }
 
     pub fn input_with_tags<T>(&mut self, id: usize, data: Vec<T>, tags: Vec<ItemTag>)
     where
         T: Debug + Send + 'static,
     {
+        let port = Arc::new(MockPort::default());
+        port.limit.store(data.len(), Ordering::Relaxed);
+        self.inputs.push((data.len(), port.clone()));
         self.block
             .stream_input_mut(id)
-            .set_reader(BufferReader::Host(Box::new(MockReader::new(data, tags))));
+            .set_reader(BufferReader::Host(Box::new(MockReader::new(
+                data, tags, port,
+            ))));
     }
 
     pub fn init_output<T>(&mut self, id: usize, size: usize)
     where
         T: Debug + Send + 'static,
     {
+        let port = Arc::new(MockPort::default());
+        port.limit.store(usize::MAX, Ordering::Relaxed);
+        self.outputs.push(port.clone());
         self.block
             .stream_output_mut(id)
-            .init(BufferWriter::Host(Box::new(MockWriter::<T>::new(size))));
+            .init(BufferWriter::Host(Box::new(MockWriter::<T>::new(
+                size, port,
+            ))));
     }
 
     pub fn output<T>(&mut self, id: usize) -> Vec<T>
@@ -84,6 +113,96 @@ impl Mocker {
             }
         }
     }
+
+    /// Run the kernel, following a [Schedule].
+    ///
+    /// Initializes the kernel and, in every step of the schedule, makes more input available,
+    /// limits the space of the outputs, and calls a message handler. Afterwards, all input is
+    /// made available and the kernel runs, until it finishes or stops making progress. Outputs
+    /// grow as needed.
+    ///
+    /// Returns an error, if the kernel or a message handler fails.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn run_schedule(&mut self, schedule: &Schedule) -> Result<()> {
+        crate::async_io::block_on(self.run_schedule_async(schedule))
+    }
+
+    pub async fn run_schedule_async(&mut self, schedule: &Schedule) -> Result<()> {
+        for (_, p) in self.inputs.iter() {
+            p.limit.store(0, Ordering::Relaxed);
+        }
+        self.block.init().await?;
+
+        let mut finished = false;
+        for step in schedule.steps() {
+            for (len, p) in self.inputs.iter() {
+                let limit = p.limit.load(Ordering::Relaxed).saturating_add(step.items);
+                p.limit.store(cmp::min(limit, *len), Ordering::Relaxed);
+            }
+            for p in self.outputs.iter() {
+                p.limit
+                    .store(step.space.clamp(1, DRAIN_SPACE), Ordering::Relaxed);
+            }
+            if let Some((port, pmt)) = &step.message {
+                self.block
+                    .call_handler(port.clone(), pmt.clone())
+                    .await
+                    .map_err(|e| anyhow!("mocker: message handler {:?} failed: {}", port, e))?;
+            }
+            if self.work(MAX_CALLS).await? {
+                finished = true;
+                break;
+            }
+        }
+
+        for (len, p) in self.inputs.iter() {
+            p.limit.store(*len, Ordering::Relaxed);
+        }
+        for p in self.outputs.iter() {
+            p.limit.store(DRAIN_SPACE, Ordering::Relaxed);
+        }
+        while !finished {
+            let before = self.progress();
+            finished = self.work(MAX_CALLS).await?;
+            if self.progress() == before {
+                break;
+            }
+        }
+
+        self.block.deinit().await
+    }
+
+    /// Call `work()` until the kernel finishes, does not ask to be called again, or after `n`
+    /// calls. Returns whether the kernel finished.
+    async fn work(&mut self, n: usize) -> Result<bool> {
+        let mut io = WorkIo {
+            call_again: false,
+            finished: false,
+            block_on: None,
+        };
+
+        for _ in 0..n {
+            self.block.work(&mut io).await?;
+            self.block.commit();
+            if io.finished {
+                return Ok(true);
+            }
+            if !io.call_again {
+                break;
+            }
+            io.call_again = false;
+        }
+        Ok(false)
+    }
+
+    fn progress(&self) -> Vec<usize> {
+        self.inputs
+            .iter()
+            .map(|(_, p)| p)
+            .chain(self.outputs.iter())
+            .map(|p| p.done.load(Ordering::Relaxed))
+            .collect()
+    }
 }
 
 #[derive(Debug)]
@@ -91,16 +210,22 @@ struct MockReader<T: Debug + Send + 'static> {
     data: Vec<T>,
     index: usize,
     tags: Vec<ItemTag>,
+    port: Arc<MockPort>,
 }
 
 impl<T: Debug + Send + 'static> MockReader<T> {
-    pub fn new(data: Vec<T>, tags: Vec<ItemTag>) -> Self {
+    pub fn new(data: Vec<T>, tags: Vec<ItemTag>, port: Arc<MockPort>) -> Self {
         MockReader {
             data,
             index: 0,
             tags,
+            port,
         }
     }
+
+    fn available(&self) -> usize {
+        cmp::min(self.port.limit.load(Ordering::Relaxed), self.data.len()) - self.index
+    }
 }
 
 #[async_trait]
@@ -109,16 +234,18 @@ impl<T: Debug + Send + 'static> BufferReaderHost for MockReader<T> {
         self
     }
     fn bytes(&mut self) -> (*const u8, usize, Vec<ItemTag>) {
+        let n = self.available();
         unsafe {
             (
                 self.data.as_ptr().add(self.index) as *const u8,
-                (self.data.len() - self.index) * std::mem::size_of::<T>(),
-                self.tags.clone(),
+                n * std::mem::size_of::<T>(),
+                self.tags.iter().filter(|t| t.index < n).cloned().collect(),
             )
         }
     }
     fn consume(&mut self, amount: usize) {
         self.index += amount;
+        self.port.done.store(self.index, Ordering::Relaxed);
         self.tags.retain(|x| x.index >= amount);
 
         for t in self.tags.iter_mut() {
@@ -128,19 +255,21 @@ impl<T: Debug + Send + 'static> BufferReaderHost for MockReader<T> {
     async fn notify_finished(&mut self) {}
     fn finish(&mut self) {}
     fn finished(&self) -> bool {
-        true
+        self.port.limit.load(Ordering::Relaxed) >= self.data.len()
     }
 }
 
 #[derive(Debug)]
 struct MockWriter<T: Debug + Send + 'static> {
     data: Vec<T>,
+    port: Arc<MockPort>,
 }
 
 impl<T: Debug + Send + 'static> MockWriter<T> {
-    pub fn new(size: usize) -> Self {
+    pub fn new(size: usize, port: Arc<MockPort>) -> Self {
         MockWriter::<T> {
             data: Vec::with_capacity(size),
+            port,
         }
     }
 
@@ -166,13 +295,19 @@ impl<T: Debug + Send + 'static> BufferWriterHost for MockWriter<T> {
         unsafe {
             self.data.set_len(self.data.len() + amount);
         }
+        self.port.done.fetch_add(amount, Ordering::Relaxed);
     }
 
     fn bytes(&mut self) -> (*mut u8, usize) {
+        let limit = self.port.limit.load(Ordering::Relaxed);
+        if limit != usize::MAX {
+            self.data.reserve(limit);
+        }
+        let n = cmp::min(self.data.capacity() - self.data.len(), limit);
         unsafe {
             (
                 self.data.as_mut_ptr().add(self.data.len()) as *mut u8,
-                (self.data.capacity() - self.data.len()) * std::mem::size_of::<T>(),
+                n * std::mem::size_of::<T>(),
             )
         }
     }
@@ -7,14 +7,22 @@ use thiserror::Error;
 mod block;
 mod block_meta;
 pub mod buffer;
+pub mod channel;
 pub mod config;
 
-#[cfg(not(target_arch = "wasm32"))]
+#[cfg(all(feature = "web", not(target_arch = "wasm32")))]
 mod ctrl_port;
-#[cfg(target_arch = "wasm32")]
-#[path = "ctrl_port_wasm.rs"]
+#[cfg(any(not(feature = "web"), target_arch = "wasm32"))]
+#[path = "ctrl_port_stub.rs"]
 mod ctrl_port;
 use crate::runtime::ctrl_port::ControlPort;
+#[cfg(all(feature = "web", not(target_arch = "wasm32")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "web")))]
+pub use ctrl_port::ControlPortConfig;
+#[cfg(all(feature = "web", not(target_arch = "wasm32")))]
+pub(crate) use ctrl_port::Launcher;
+#[cfg(all(feature = "web", not(target_arch = "wasm32")))]
+pub(crate) use ctrl_port::{stream_publish, stream_register, stream_remove};
 
 #[cfg(all(not(target_arch = "wasm32"), not(target_os = "android")))]
 mod logging;
@@ -28,11 +36,27 @@ mod logging;
 mod flowgraph;
 pub mod message_io;
 mod mocker;
+#[cfg(all(feature = "rayon", not(target_arch = "wasm32")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
+pub mod parallel;
+pub mod pdu_pool;
+#[cfg(target_os = "linux")]
+#[cfg_attr(docsrs, doc(cfg(target_os = "linux")))]
+pub mod realtime;
+#[cfg(not(target_arch = "wasm32"))]
+mod remote;
 #[allow(clippy::module_inception)]
 mod runtime;
 pub mod scheduler;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod session;
 pub mod stream_io;
 mod tag;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod telemetry;
+pub mod testing;
+#[cfg(all(feature = "web", not(target_arch = "wasm32")))]
+mod thrift_port;
 mod topology;
 
 pub use block::Block;
@@ -44,11 +68,14 @@ pub use flowgraph::Flowgraph;
 pub use flowgraph::FlowgraphHandle;
 pub use flowgraph::PortId;
 pub use futuresdr_pmt::Pmt;
+pub use futuresdr_pmt::PmtKind;
 pub use message_io::MessageInput;
 pub use message_io::MessageIo;
 pub use message_io::MessageIoBuilder;
 pub use message_io::MessageOutput;
 pub use mocker::Mocker;
+#[cfg(not(target_arch = "wasm32"))]
+pub use remote::RemoteFlowgraphHandle;
 pub(crate) use runtime::run_block;
 pub use runtime::Runtime;
 pub use stream_io::StreamInput;
@@ -60,7 +87,12 @@ pub use tag::Tag;
 pub use topology::Topology;
 
 pub use futuresdr_pmt::BlockDescription;
+pub use futuresdr_pmt::BlockState;
 pub use futuresdr_pmt::FlowgraphDescription;
+pub use futuresdr_pmt::FlowgraphEvent;
+pub use futuresdr_pmt::FlowgraphStats;
+pub use futuresdr_pmt::HandlerDescription;
+pub use futuresdr_pmt::ParameterDescription;
 
 use buffer::BufferReader;
 use buffer::BufferWriter;
@@ -100,6 +132,12 @@ pub enum FlowgraphMessage {
         block_id: usize,
         tx: oneshot::Sender<result::Result<BlockDescription, BlockDescriptionError>>,
     },
+    Subscribe {
+        tx: mpsc::Sender<FlowgraphEvent>,
+    },
+    Stats {
+        tx: oneshot::Sender<FlowgraphStats>,
+    },
 }
 
 #[derive(Debug)]
@@ -127,7 +165,7 @@ pub enum BlockMessage {
     MessageOutputConnect {
         src_port: usize,
         dst_port: usize,
-        dst_inbox: mpsc::Sender<BlockMessage>,
+        dst_inbox: channel::Sender<BlockMessage>,
     },
     Call {
         port_id: PortId,
@@ -0,0 +1,109 @@
+//! Data-Parallel Work on a Shared Compute Pool
+//!
+//! Kernels with heavy, independent work per item or batch (e.g., batches of FFTs or
+//! independent channels of a filter bank) can split their stream buffers across a shared
+//! [rayon](https://docs.rs/rayon) thread pool.
+//!
+//! The pool is shared by all blocks of all flowgraphs. Its size is set with the
+//! `compute_threads` config option and defaults to the number of CPUs. While the pool processes
+//! the work, the block only awaits the result, i.e., the executor thread does not block or spin
+//! and can run other blocks. As a result, there are never more busy threads than executor
+//! threads plus compute threads, independent of the number of blocks that use the pool.
+//!
+//! ```no_run
+//! use futuresdr::runtime::parallel;
+//!
+//! # async fn f(i: &'static mut [f32], o: &'static mut [f32]) {
+//! parallel::zip_chunks(i, o, 4096, |i, o| {
+//!     for (x, y) in i.iter().zip(o.iter_mut()) {
+//!         *y = x.sqrt();
+//!     }
+//! })
+//! .await;
+//! # }
+//! ```
+use futures::channel::oneshot;
+use once_cell::sync::Lazy;
+use rayon::prelude::*;
+use rayon::ThreadPool;
+use rayon::ThreadPoolBuilder;
+use std::panic;
+use std::panic::AssertUnwindSafe;
+
+use crate::runtime::config;
+
+static POOL: Lazy<ThreadPool> = Lazy::new(|| {
+    let threads = config::get_or_default("compute_threads", num_cpus::get());
+    ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .thread_name(|i| format!("futuresdr-compute-{i}"))
+        .build()
+        .expect("failed to create compute pool")
+});
+
+/// The shared compute pool.
+pub fn pool() -> &'static ThreadPool {
+    &POOL
+}
+
+/// Number of threads of the compute pool.
+pub fn threads() -> usize {
+    POOL.current_num_threads()
+}
+
+/// Run a closure on the compute pool and await its result.
+///
+/// Panics of the closure are propagated to the caller.
+pub async fn spawn<F, R>(f: F) -> R
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    let (tx, rx) = oneshot::channel();
+    POOL.spawn(move || {
+        let _ = tx.send(panic::catch_unwind(AssertUnwindSafe(f)));
+    });
+    match rx.await.expect("compute pool dropped task") {
+        Ok(r) => r,
+        Err(e) => panic::resume_unwind(e),
+    }
+}
+
+/// Process corresponding chunks of an input and an output slice in parallel.
+///
+/// Both slices are split into chunks of `chunk` items. The last chunks might be shorter and, if
+/// the slices differ in length, surplus chunks of the longer slice are not processed.
+pub async fn zip_chunks<A, B, F>(
+    input: &'static mut [A],
+    output: &'static mut [B],
+    chunk: usize,
+    f: F,
+) where
+    A: Send + Sync,
+    B: Send,
+    F: Fn(&mut [A], &mut [B]) + Send + Sync + 'static,
+{
+    assert!(chunk > 0, "chunk size has to be positive");
+    spawn(move || {
+        input
+            .par_chunks_mut(chunk)
+            .zip(output.par_chunks_mut(chunk))
+            .for_each(|(i, o)| f(i, o));
+    })
+    .await
+}
+
+/// Process chunks of a slice in parallel.
+///
+/// The slice is split into chunks of `chunk` items, the last chunk might be shorter.
+pub async fn chunks<A, F>(data: &'static mut [A], chunk: usize, f: F)
+where
+    A: Send,
+    F: Fn(&mut [A]) + Send + Sync + 'static,
+{
+    assert!(chunk > 0, "chunk size has to be positive");
+    spawn(move || {
+        data.par_chunks_mut(chunk).for_each(f);
+    })
+    .await
+}
@@ -0,0 +1,211 @@
+//! Memory Pool for PDU Payloads
+//!
+//! Packet-processing flowgraphs create and drop thousands of [Pmt::Blob] PDUs per second.
+//! To take pressure off the allocator, the payloads can be taken from a global pool of
+//! reusable byte buffers.
+//!
+//! A [PooledBlob] is a `Vec<u8>` that returns its memory to the pool when it is dropped.
+//! Converted to a [Pmt::Blob], the buffer leaves the pool. Blocks at the end of a PDU chain
+//! can give it back with [recycle], once they are done with the payload.
+//!
+//! Buffers are kept in power-of-two size classes. The pool is configured with the
+//! `pdu_pool_max_size` (largest buffer that is kept, default 64 KiB) and `pdu_pool_buffers`
+//! (buffers kept per size class, default 1024) config options. Larger buffers are allocated
+//! and freed as usual.
+//!
+//! ```
+//! use futuresdr::runtime::pdu_pool;
+//! use futuresdr::runtime::pdu_pool::PooledBlob;
+//! use futuresdr::runtime::Pmt;
+//!
+//! let mut blob = PooledBlob::with_capacity(1500);
+//! blob.extend_from_slice(b"payload");
+//! let pdu: Pmt = blob.into();
+//!
+//! // ... at the end of the chain
+//! pdu_pool::recycle(pdu);
+//! ```
+use concurrent_queue::ConcurrentQueue;
+use once_cell::sync::Lazy;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::runtime::config;
+use crate::runtime::Pmt;
+
+/// Smallest size class.
+const MIN_SIZE: usize = 64;
+
+struct Pool {
+    /// Free buffers, one queue per size class, starting at [MIN_SIZE].
+    classes: Vec<ConcurrentQueue<Vec<u8>>>,
+    max_size: usize,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+impl Pool {
+    fn new(max_size: usize, buffers: usize) -> Pool {
+        let max_size = max_size.max(MIN_SIZE).next_power_of_two();
+        let n_classes = (max_size / MIN_SIZE).trailing_zeros() as usize + 1;
+        Pool {
+            classes: (0..n_classes)
+                .map(|_| ConcurrentQueue::bounded(buffers.max(1)))
+                .collect(),
+            max_size,
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        }
+    }
+
+    fn get(&self, capacity: usize) -> Vec<u8> {
+        if capacity > self.max_size {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            return Vec::with_capacity(capacity);
+        }
+
+        let size = capacity.max(MIN_SIZE).next_power_of_two();
+        let class = (size / MIN_SIZE).trailing_zeros() as usize;
+        match self.classes[class].pop() {
+            Ok(v) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                v
+            }
+            Err(_) => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                Vec::with_capacity(size)
+            }
+        }
+    }
+
+    fn put(&self, mut v: Vec<u8>) {
+        let capacity = v.capacity();
+        if capacity < MIN_SIZE || capacity > self.max_size {
+            return;
+        }
+
+        // the largest class that the buffer can serve
+        let size = 1 << (usize::BITS - 1 - capacity.leading_zeros());
+        let class = (size / MIN_SIZE).trailing_zeros() as usize;
+        v.clear();
+        // drop the buffer, if the class is full
+        let _ = self.classes[class].push(v);
+    }
+}
+
+static POOL: Lazy<Pool> = Lazy::new(|| {
+    Pool::new(
+        config::get_or_default("pdu_pool_max_size", 64 * 1024),
+        config::get_or_default("pdu_pool_buffers", 1024),
+    )
+});
+
+/// Byte buffer from the PDU pool, which returns its memory to the pool when dropped.
+///
+/// The buffer dereferences to a `Vec<u8>`. It can be converted into a [Pmt::Blob] and
+/// from a `Vec<u8>`, e.g., to recycle the payload of a received PDU.
+#[derive(Default, PartialEq, Eq)]
+pub struct PooledBlob {
+    data: Vec<u8>,
+}
+
+impl PooledBlob {
+    /// Take an empty buffer with at least the given capacity from the pool.
+    pub fn with_capacity(capacity: usize) -> PooledBlob {
+        PooledBlob {
+            data: POOL.get(capacity),
+        }
+    }
+
+    /// Take a buffer from the pool and copy the slice into it.
+    pub fn from_slice(s: &[u8]) -> PooledBlob {
+        let mut b = PooledBlob::with_capacity(s.len());
+        b.data.extend_from_slice(s);
+        b
+    }
+
+    /// Take the buffer out of the pool.
+    pub fn into_vec(mut self) -> Vec<u8> {
+        std::mem::take(&mut self.data)
+    }
+}
+
+impl Drop for PooledBlob {
+    fn drop(&mut self) {
+        POOL.put(std::mem::take(&mut self.data));
+    }
+}
+
+impl Clone for PooledBlob {
+    fn clone(&self) -> Self {
+        PooledBlob::from_slice(&self.data)
+    }
+}
+
+impl fmt::Debug for PooledBlob {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PooledBlob")
+            .field("len", &self.data.len())
+            .field("capacity", &self.data.capacity())
+            .finish()
+    }
+}
+
+impl Deref for PooledBlob {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Vec<u8> {
+        &self.data
+    }
+}
+
+impl DerefMut for PooledBlob {
+    fn deref_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.data
+    }
+}
+
+impl From<Vec<u8>> for PooledBlob {
+    fn from(data: Vec<u8>) -> Self {
+        PooledBlob { data }
+    }
+}
+
+impl From<PooledBlob> for Vec<u8> {
+    fn from(b: PooledBlob) -> Self {
+        b.into_vec()
+    }
+}
+
+impl From<PooledBlob> for Pmt {
+    fn from(b: PooledBlob) -> Self {
+        Pmt::Blob(b.into_vec())
+    }
+}
+
+/// Return the payload of a [Pmt::Blob] to the pool.
+///
+/// Other PMTs are dropped.
+pub fn recycle(p: Pmt) {
+    if let Pmt::Blob(v) = p {
+        POOL.put(v);
+    }
+}
+
+/// Statistics of the pool.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PoolStats {
+    /// Buffers that were served from the pool.
+    pub hits: usize,
+    /// Buffers that had to be allocated.
+    pub misses: usize,
+}
+
+/// Statistics of the pool since the start of the process.
+pub fn stats() -> PoolStats {
+    PoolStats {
+        hits: POOL.hits.load(Ordering::Relaxed),
+        misses: POOL.misses.load(Ordering::Relaxed),
+    }
+}
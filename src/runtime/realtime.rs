@@ -0,0 +1,167 @@
+//! Realtime Scheduling for Low-Latency Applications
+//!
+//! Transceivers with tight latency budgets (e.g., TDD protocols that have to answer within a
+//! few hundred microseconds) are prone to glitches caused by the OS: streaming threads get
+//! preempted by other processes and page faults stall the first access to buffer memory.
+//! A [RealtimeConfig], set with [Runtime::realtime](crate::runtime::Runtime::realtime),
+//! addresses these issues on Linux by
+//!
+//! - running the threads that execute blocks with `SCHED_FIFO` priority,
+//! - locking all current and future memory of the process in RAM (`mlockall`), and
+//! - prefaulting stream buffers, i.e., touching every page when the buffer is created.
+//!
+//! The settings are process-wide and apply to all flowgraphs that are started afterwards.
+//! Threads are promoted the first time they run a block; this includes the threads of the
+//! blocking thread pool, which keep their priority after the block terminated.
+//!
+//! Realtime priorities and memory locking require `CAP_SYS_NICE` and `CAP_IPC_LOCK` or
+//! corresponding limits (`rtprio` and `memlock` in `/etc/security/limits.conf`). If they
+//! are not available, the runtime logs a warning and continues without.
+//!
+//! ```no_run
+//! use futuresdr::runtime::realtime::RealtimeConfig;
+//! use futuresdr::runtime::Runtime;
+//!
+//! let rt = Runtime::new().realtime(
+//!     RealtimeConfig::new()
+//!         .priority(50)
+//!         .lock_memory(true)
+//!         .prefault_buffers(true),
+//! );
+//! ```
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+
+static PRIORITY: AtomicI32 = AtomicI32::new(0);
+static PREFAULT: AtomicBool = AtomicBool::new(false);
+static LOCKED: AtomicBool = AtomicBool::new(false);
+static PRIORITY_WARNED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    static THREAD_PRIORITY: Cell<i32> = const { Cell::new(0) };
+}
+
+/// Realtime settings of a [Runtime](crate::runtime::Runtime).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RealtimeConfig {
+    priority: Option<i32>,
+    lock_memory: bool,
+    prefault_buffers: bool,
+}
+
+impl RealtimeConfig {
+    pub fn new() -> RealtimeConfig {
+        RealtimeConfig::default()
+    }
+
+    /// Run blocks on threads with the given `SCHED_FIFO` priority (1-99).
+    #[must_use]
+    pub fn priority(mut self, priority: u8) -> RealtimeConfig {
+        assert!(
+            (1..=99).contains(&priority),
+            "SCHED_FIFO priority has to be in 1..=99"
+        );
+        self.priority = Some(priority as i32);
+        self
+    }
+
+    /// Lock all current and future memory of the process in RAM.
+    #[must_use]
+    pub fn lock_memory(mut self, lock: bool) -> RealtimeConfig {
+        self.lock_memory = lock;
+        self
+    }
+
+    /// Touch all pages of stream buffers when they are created.
+    #[must_use]
+    pub fn prefault_buffers(mut self, prefault: bool) -> RealtimeConfig {
+        self.prefault_buffers = prefault;
+        self
+    }
+
+    pub(crate) fn apply(&self) {
+        if self.lock_memory {
+            lock_memory();
+        }
+        PREFAULT.store(self.prefault_buffers, Ordering::Relaxed);
+        PRIORITY.store(self.priority.unwrap_or(0), Ordering::Relaxed);
+    }
+}
+
+fn lock_memory() {
+    if LOCKED.load(Ordering::Relaxed) {
+        return;
+    }
+
+    // With MCL_FUTURE, allocations that exceed the memlock limit fail, which would abort the
+    // process. Only lock memory if there is no limit.
+    let mut limit = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    let unlimited = unsafe {
+        libc::getrlimit(libc::RLIMIT_MEMLOCK, &mut limit) == 0
+            && (limit.rlim_cur == libc::RLIM_INFINITY || libc::geteuid() == 0)
+    };
+    if !unlimited {
+        warn!(
+            "not locking memory, memlock limit is {} bytes (set it to unlimited)",
+            limit.rlim_cur
+        );
+        return;
+    }
+
+    if unsafe { libc::mlockall(libc::MCL_CURRENT | libc::MCL_FUTURE) } == 0 {
+        LOCKED.store(true, Ordering::Relaxed);
+        debug!("locked process memory");
+    } else {
+        warn!("failed to lock memory: {}", std::io::Error::last_os_error());
+    }
+}
+
+/// Whether the memory of the process is locked.
+pub fn memory_locked() -> bool {
+    LOCKED.load(Ordering::Relaxed)
+}
+
+/// Set the configured realtime priority for the current thread, if it is not set already.
+pub(crate) fn promote_current_thread() {
+    let priority = PRIORITY.load(Ordering::Relaxed);
+    if priority == 0 || THREAD_PRIORITY.with(|p| p.get()) == priority {
+        return;
+    }
+    THREAD_PRIORITY.with(|p| p.set(priority));
+
+    let param = libc::sched_param {
+        sched_priority: priority,
+    };
+    let ret =
+        unsafe { libc::pthread_setschedparam(libc::pthread_self(), libc::SCHED_FIFO, &param) };
+    if ret != 0 {
+        if !PRIORITY_WARNED.swap(true, Ordering::Relaxed) {
+            warn!(
+                "failed to set SCHED_FIFO priority {}: {}",
+                priority,
+                std::io::Error::from_raw_os_error(ret)
+            );
+        }
+    } else {
+        debug!("running thread with SCHED_FIFO priority {}", priority);
+    }
+}
+
+/// Prefault the buffer, if configured.
+pub(crate) fn prefault(buffer: &mut [u8]) {
+    if !PREFAULT.load(Ordering::Relaxed) {
+        return;
+    }
+
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) }.max(1) as usize;
+    for i in (0..buffer.len()).step_by(page_size) {
+        // write the current value, so that contents are kept and the write is not elided
+        unsafe {
+            let p = buffer.as_mut_ptr().add(i);
+            std::ptr::write_volatile(p, std::ptr::read_volatile(p));
+        }
+    }
+}
@@ -0,0 +1,360 @@
+//! Remote Control of a Flowgraph through the REST API
+use async_net::TcpStream;
+use futures::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use futures::stream::{self, BoxStream, StreamExt};
+use serde::de::DeserializeOwned;
+use std::result;
+
+use crate::anyhow::{bail, Context, Result};
+use crate::runtime::BlockDescription;
+use crate::runtime::CallbackError;
+use crate::runtime::FlowgraphDescription;
+use crate::runtime::FlowgraphEvent;
+use crate::runtime::FlowgraphStats;
+use crate::runtime::Pmt;
+use crate::runtime::PortId;
+
+/// Handle to a [Flowgraph](crate::runtime::Flowgraph) running in a remote [Runtime](crate::runtime::Runtime).
+///
+/// Provides the same interface as [FlowgraphHandle](crate::runtime::FlowgraphHandle) but talks
+/// to the control port of the remote runtime.
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::async_io::block_on;
+/// use futuresdr::runtime::Pmt;
+/// use futuresdr::runtime::RemoteFlowgraphHandle;
+///
+/// block_on(async {
+///     let mut handle = RemoteFlowgraphHandle::new("127.0.0.1:1337", 0);
+///     let desc = handle.description().await.unwrap();
+///     println!("{:?}", desc);
+///     handle.call(0, "freq", Pmt::F64(100e6)).await.unwrap();
+/// });
+/// ```
+#[derive(Debug, Clone)]
+pub struct RemoteFlowgraphHandle {
+    addr: String,
+    fg: String,
+}
+
+impl RemoteFlowgraphHandle {
+    /// Create a handle for flowgraph `fg` (id or name) of the control port at `url` (e.g.,
+    /// `127.0.0.1:1337`).
+    pub fn new(url: impl AsRef<str>, fg: impl ToString) -> RemoteFlowgraphHandle {
+        let addr = url.as_ref();
+        let addr = addr.strip_prefix("http://").unwrap_or(addr);
+        RemoteFlowgraphHandle {
+            addr: addr.trim_end_matches('/').to_string(),
+            fg: fg.to_string(),
+        }
+    }
+
+    pub async fn call(
+        &mut self,
+        block_id: usize,
+        port_id: impl Into<PortId>,
+        data: Pmt,
+    ) -> result::Result<(), CallbackError> {
+        self.callback(block_id, port_id, data).await.map(|_| ())
+    }
+
+    pub async fn callback(
+        &mut self,
+        block_id: usize,
+        port_id: impl Into<PortId>,
+        data: Pmt,
+    ) -> result::Result<Pmt, CallbackError> {
+        let handler = match port_id.into() {
+            PortId::Index(i) => i.to_string(),
+            PortId::Name(n) => n,
+        };
+        let body = serde_json::to_string(&data).or(Err(CallbackError::RuntimeError))?;
+        let path = format!("/api/fg/{}/block/{}/call/{}/", self.fg, block_id, handler);
+
+        let res = self
+            .request("POST", &path, Some(body))
+            .await
+            .or(Err(CallbackError::RuntimeError))?;
+        match res.status {
+            200 => {
+                let body = res.body().await.or(Err(CallbackError::RuntimeError))?;
+                serde_json::from_slice(&body).or(Err(CallbackError::RuntimeError))
+            }
+            404 => Err(CallbackError::InvalidBlock),
+            _ => Err(CallbackError::HandlerError),
+        }
+    }
+
+    pub async fn description(&mut self) -> Result<FlowgraphDescription> {
+        self.get(&format!("/api/fg/{}/", self.fg)).await
+    }
+
+    pub async fn block_description(&mut self, block_id: usize) -> Result<BlockDescription> {
+        self.get(&format!("/api/fg/{}/block/{}/", self.fg, block_id))
+            .await
+    }
+
+    /// Subscribe to runtime events (block state changes, termination).
+    ///
+    /// The stream ends when the flowgraph terminates or the connection is closed.
+    pub async fn subscribe(&mut self) -> Result<BoxStream<'static, FlowgraphEvent>> {
+        let res = self
+            .request("GET", &format!("/api/fg/{}/events/", self.fg), None)
+            .await?;
+        if res.status != 200 {
+            bail!("remote returned status {}", res.status);
+        }
+
+        Ok(
+            stream::unfold((res, Vec::new()), |(mut res, mut buf)| async move {
+                loop {
+                    // decode complete lines only, as chunks might split UTF-8 characters
+                    if let Some(i) = buf.iter().position(|b| *b == b'\n') {
+                        let line: Vec<u8> = buf.drain(..=i).collect();
+                        let line = String::from_utf8_lossy(&line);
+                        if let Some(data) = line.trim_end().strip_prefix("data:") {
+                            match serde_json::from_str::<FlowgraphEvent>(data.trim_start()) {
+                                Ok(FlowgraphEvent::Stats(_)) => {}
+                                Ok(e) => return Some((e, (res, buf))),
+                                Err(e) => warn!("remote sent invalid event ({:?})", e),
+                            }
+                        }
+                        continue;
+                    }
+
+                    match res.chunk().await {
+                        Ok(Some(mut c)) => buf.append(&mut c),
+                        _ => return None,
+                    }
+                }
+            })
+            .boxed(),
+        )
+    }
+
+    /// Get a snapshot of the state of all blocks.
+    pub async fn stats(&mut self) -> Result<FlowgraphStats> {
+        self.get(&format!("/api/fg/{}/stats/", self.fg)).await
+    }
+
+    pub async fn terminate(&mut self) -> Result<()> {
+        let reader = self
+            .send("POST", &format!("/api/fg/{}/terminate/", self.fg), None)
+            .await?;
+        // the remote runtime might exit before answering
+        if let Ok(res) = response(reader).await {
+            if res.status != 200 {
+                bail!("remote returned status {}", res.status);
+            }
+        }
+        Ok(())
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        let res = self.request("GET", path, None).await?;
+        if res.status != 200 {
+            bail!("remote returned status {}", res.status);
+        }
+        let body = res.body().await?;
+        Ok(serde_json::from_slice(&body)?)
+    }
+
+    async fn request(&self, method: &str, path: &str, body: Option<String>) -> Result<Response> {
+        let reader = self.send(method, path, body).await?;
+        response(reader).await
+    }
+
+    async fn send(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<String>,
+    ) -> Result<BufReader<TcpStream>> {
+        send(&self.addr, method, path, "application/json", body).await
+    }
+}
+
+/// Send an HTTP/1.1 request to `addr` (`host:port`), returning the connection to read the response.
+pub(crate) async fn send(
+    addr: &str,
+    method: &str,
+    path: &str,
+    content_type: &str,
+    body: Option<String>,
+) -> Result<BufReader<TcpStream>> {
+    let mut stream = TcpStream::connect(addr)
+        .await
+        .with_context(|| format!("cannot connect to {}", addr))?;
+
+    let mut req = format!(
+        "{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\nAccept: */*\r\n",
+        method, path, addr
+    );
+    let body = body.unwrap_or_default();
+    if method == "POST" {
+        req.push_str(&format!("Content-Type: {}\r\n", content_type));
+        req.push_str(&format!("Content-Length: {}\r\n", body.len()));
+    }
+    req.push_str("\r\n");
+    req.push_str(&body);
+    stream.write_all(req.as_bytes()).await?;
+
+    Ok(BufReader::new(stream))
+}
+
+/// Parse status line and headers of an HTTP/1.1 response.
+pub(crate) async fn response(mut reader: BufReader<TcpStream>) -> Result<Response> {
+    let mut line = String::new();
+    reader.read_line(&mut line).await?;
+    let status = line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse::<u16>().ok())
+        .context("invalid HTTP status line")?;
+
+    let mut chunked = false;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).await? == 0 || line.trim().is_empty() {
+            break;
+        }
+        let l = line.to_lowercase();
+        if l.starts_with("transfer-encoding:") && l.contains("chunked") {
+            chunked = true;
+        }
+    }
+
+    Ok(Response {
+        status,
+        chunked,
+        reader,
+    })
+}
+
+pub(crate) struct Response {
+    pub(crate) status: u16,
+    chunked: bool,
+    reader: BufReader<TcpStream>,
+}
+
+impl Response {
+    pub(crate) async fn body(mut self) -> Result<Vec<u8>> {
+        let mut body = Vec::new();
+        while let Some(mut c) = self.chunk().await? {
+            body.append(&mut c);
+        }
+        Ok(body)
+    }
+
+    async fn chunk(&mut self) -> Result<Option<Vec<u8>>> {
+        if !self.chunked {
+            let mut buf = vec![0; 4096];
+            let n = self.reader.read(&mut buf).await?;
+            if n == 0 {
+                return Ok(None);
+            }
+            buf.truncate(n);
+            return Ok(Some(buf));
+        }
+
+        let mut line = String::new();
+        self.reader.read_line(&mut line).await?;
+        let size = line.trim().split(';').next().unwrap_or_default();
+        let size = usize::from_str_radix(size, 16).context("invalid chunk size")?;
+        if size == 0 {
+            return Ok(None);
+        }
+        let mut buf = vec![0; size + 2];
+        self.reader.read_exact(&mut buf).await?;
+        buf.truncate(size);
+        Ok(Some(buf))
+    }
+}
+
+#[cfg(all(test, feature = "web"))]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    use crate::async_io::block_on;
+    use crate::blocks::soapy::{MockSoapyDevice, SoapyDevSpec, SoapyDirection};
+    use crate::blocks::{MockSoapySourceBuilder, NullSink};
+    use crate::num_complex::Complex32;
+    use crate::runtime::ctrl_port::tests::serve;
+    use crate::runtime::ctrl_port::{router, Flowgraphs};
+    use crate::runtime::ControlPortConfig;
+    use crate::runtime::Flowgraph;
+    use crate::runtime::Runtime;
+
+    #[test]
+    fn remote_handle() {
+        let dev = MockSoapyDevice::new(1);
+        let mut fg = Flowgraph::new();
+        let src = fg.add_block(
+            MockSoapySourceBuilder::new()
+                .device(SoapyDevSpec::Mock(dev.clone()))
+                .dev_channels(vec![0])
+                .build(),
+        );
+        let snk = fg.add_block(NullSink::<Complex32>::new());
+        fg.connect_stream(src, "out", snk, "in").unwrap();
+
+        let rt = Runtime::new();
+        block_on(async move {
+            let (task, handle) = rt.start(fg).await;
+            let registry = Arc::new(Mutex::new(Flowgraphs::default()));
+            registry
+                .lock()
+                .unwrap()
+                .insert(Some("rx".to_string()), handle);
+            let addr = serve(router(registry, ControlPortConfig::new()));
+            let mut remote = RemoteFlowgraphHandle::new(format!("http://{addr}/"), "rx");
+
+            let d = remote.description().await.unwrap();
+            assert_eq!(d.blocks.len(), 2);
+            assert_eq!(d.stream_edges, vec![(src, 0, snk, 0)]);
+            let b = remote.block_description(src).await.unwrap();
+            assert_eq!(b.type_name, "MockSoapySource");
+            assert!(b.message_inputs.contains(&"freq".to_string()));
+            assert!(remote.block_description(42).await.is_err());
+
+            let mut events = remote.subscribe().await.unwrap();
+
+            remote.call(src, "freq", Pmt::F64(100e6)).await.unwrap();
+            let ret = remote.callback(src, 1, Pmt::U32(12)).await.unwrap();
+            assert_eq!(ret, Pmt::Null);
+            let s = dev.settings(&SoapyDirection::Rx, 0);
+            assert_eq!(s.frequency, Some(100e6));
+            assert_eq!(s.gain, Some(12.0));
+
+            assert!(matches!(
+                remote.call(42, "freq", Pmt::Null).await,
+                Err(CallbackError::InvalidBlock)
+            ));
+            assert!(matches!(
+                RemoteFlowgraphHandle::new(addr.to_string(), "tx")
+                    .call(src, "freq", Pmt::Null)
+                    .await,
+                Err(CallbackError::InvalidBlock)
+            ));
+
+            // handler errors terminate the flowgraph
+            assert!(matches!(
+                remote
+                    .call(src, "freq", Pmt::String("high".to_string()))
+                    .await,
+                Err(CallbackError::HandlerError)
+            ));
+            let mut terminated = false;
+            while let Some(e) = events.next().await {
+                if let FlowgraphEvent::Terminated { error } = e {
+                    assert!(error);
+                    terminated = true;
+                }
+            }
+            assert!(terminated);
+            assert!(task.await.is_err());
+        });
+    }
+}
@@ -2,36 +2,59 @@
 use async_io::block_on;
 #[cfg(not(target_arch = "wasm32"))]
 use async_task::Task;
-#[cfg(not(target_arch = "wasm32"))]
+#[cfg(all(feature = "web", not(target_arch = "wasm32")))]
 use axum::Router;
-use futures::channel::mpsc::{channel, Receiver, Sender};
+use futures::channel::mpsc;
 use futures::channel::oneshot;
 use futures::future::join_all;
 use futures::future::Either;
 use futures::prelude::*;
 use futures::FutureExt;
+use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
 use std::result;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::Arc;
 #[cfg(target_arch = "wasm32")]
 type Task<T> = crate::runtime::scheduler::wasm::TaskHandle<T>;
 
 use crate::anyhow::{bail, Context, Result};
+use crate::runtime::channel::{channel, Receiver, Sender};
 use crate::runtime::config;
+#[cfg(target_os = "linux")]
+use crate::runtime::realtime;
+#[cfg(target_os = "linux")]
+use crate::runtime::realtime::RealtimeConfig;
 use crate::runtime::scheduler::Scheduler;
 #[cfg(not(target_arch = "wasm32"))]
 use crate::runtime::scheduler::SmolScheduler;
 #[cfg(target_arch = "wasm32")]
 use crate::runtime::scheduler::WasmScheduler;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::runtime::session;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::runtime::session::{Recording, SessionRecorder};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::runtime::telemetry;
 use crate::runtime::Block;
 use crate::runtime::BlockDescription;
 use crate::runtime::BlockDescriptionError;
 use crate::runtime::BlockMessage;
+use crate::runtime::BlockState;
 use crate::runtime::CallbackError;
 use crate::runtime::ControlPort;
+#[cfg(all(feature = "web", not(target_arch = "wasm32")))]
+use crate::runtime::ControlPortConfig;
 use crate::runtime::Flowgraph;
 use crate::runtime::FlowgraphDescription;
+use crate::runtime::FlowgraphEvent;
 use crate::runtime::FlowgraphHandle;
 use crate::runtime::FlowgraphMessage;
+use crate::runtime::FlowgraphStats;
 use crate::runtime::HandlerError;
+#[cfg(all(feature = "web", not(target_arch = "wasm32")))]
+use crate::runtime::Launcher;
 use crate::runtime::Pmt;
 use crate::runtime::WorkIo;
 
@@ -41,6 +64,8 @@ use crate::runtime::WorkIo;
 pub struct Runtime<S> {
     scheduler: S,
     control_port: ControlPort,
+    #[cfg(not(target_arch = "wasm32"))]
+    session: Option<Arc<SessionRecorder>>,
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -50,13 +75,28 @@ impl Runtime<SmolScheduler> {
         Runtime {
             scheduler: SmolScheduler::default(),
             control_port: ControlPort::new(),
+            session: session::from_config(),
         }
     }
 
+    #[cfg(all(feature = "web", not(target_arch = "wasm32")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "web")))]
     pub fn with_custom_routes(routes: Router) -> Self {
         Runtime {
             scheduler: SmolScheduler::default(),
             control_port: ControlPort::with_routes(routes),
+            session: session::from_config(),
+        }
+    }
+
+    /// Constructs a new [Runtime], configuring the web server of the control port.
+    #[cfg(all(feature = "web", not(target_arch = "wasm32")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "web")))]
+    pub fn with_control_port(config: ControlPortConfig) -> Self {
+        Runtime {
+            scheduler: SmolScheduler::default(),
+            control_port: ControlPort::with_config(config),
+            session: session::from_config(),
         }
     }
 }
@@ -91,17 +131,54 @@ impl<S: Scheduler> Runtime<S> {
         Runtime {
             scheduler,
             control_port: ControlPort::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            session: session::from_config(),
         }
     }
 
-    #[cfg(not(target_arch = "wasm32"))]
+    #[cfg(all(feature = "web", not(target_arch = "wasm32")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "web")))]
     pub fn with_config(scheduler: S, routes: Router) -> Runtime<S> {
         Runtime {
             scheduler,
             control_port: ControlPort::with_routes(routes),
+            session: session::from_config(),
+        }
+    }
+
+    /// Create a [Runtime] with a given [Scheduler], configuring the web server of the control port.
+    #[cfg(all(feature = "web", not(target_arch = "wasm32")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "web")))]
+    pub fn with_scheduler_and_control_port(scheduler: S, config: ControlPortConfig) -> Runtime<S> {
+        Runtime {
+            scheduler,
+            control_port: ControlPort::with_config(config),
+            session: session::from_config(),
         }
     }
 
+    /// Record the control sessions of all flowgraphs started by this [Runtime] to a file.
+    ///
+    /// Overrides the `record_session` config option. See [session](crate::runtime::session) for
+    /// details.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn record_session(mut self, path: impl AsRef<Path>) -> Result<Runtime<S>> {
+        self.session = Some(Arc::new(SessionRecorder::create(path)?));
+        Ok(self)
+    }
+
+    /// Apply realtime settings for low-latency applications.
+    ///
+    /// The settings are process-wide and apply to all flowgraphs started afterwards. See
+    /// [realtime](crate::runtime::realtime) for details.
+    #[cfg(target_os = "linux")]
+    #[cfg_attr(docsrs, doc(cfg(target_os = "linux")))]
+    #[must_use]
+    pub fn realtime(self, config: RealtimeConfig) -> Runtime<S> {
+        config.apply();
+        self
+    }
+
     pub fn spawn<T: Send + 'static>(
         &self,
         future: impl Future<Output = T> + Send + 'static,
@@ -141,22 +218,102 @@ impl<S: Scheduler> Runtime<S> {
     }
 
     pub async fn start(&self, fg: Flowgraph) -> (Task<Result<Flowgraph>>, FlowgraphHandle) {
-        let queue_size = config::config().queue_size;
-        let (fg_inbox, fg_inbox_rx) = channel::<FlowgraphMessage>(queue_size);
+        self.start_flowgraph(None, fg).await
+    }
 
-        let (tx, rx) = oneshot::channel::<()>();
-        let task = self.scheduler.spawn(run_flowgraph(
-            fg,
+    /// Start a [Flowgraph] that can be addressed by its name through the control port.
+    ///
+    /// Several flowgraphs can run side by side in one [Runtime]. Starting a flowgraph with a
+    /// name that is already in use (e.g., to restart it) reassigns the name to the new flowgraph.
+    /// Terminated flowgraphs are removed from the control port.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name is numeric, since it would be ambiguous with flowgraph ids.
+    pub async fn start_named(
+        &self,
+        name: impl Into<String>,
+        fg: Flowgraph,
+    ) -> (Task<Result<Flowgraph>>, FlowgraphHandle) {
+        let name = name.into();
+        assert!(
+            name.parse::<usize>().is_err(),
+            "flowgraph name {} is numeric",
+            name
+        );
+        self.start_flowgraph(Some(name), fg).await
+    }
+
+    /// Register a named [Flowgraph] that is started through the control port.
+    ///
+    /// `POST /api/fg/{name}/start/` calls `build` and starts the returned flowgraph, unless a
+    /// flowgraph with this name is running. Since `build` is called for every start, the
+    /// flowgraph can be restarted after it was terminated (`POST /api/fg/{name}/terminate/`).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name is numeric, since it would be ambiguous with flowgraph ids.
+    #[cfg(all(feature = "web", not(target_arch = "wasm32")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "web")))]
+    pub fn add_named(
+        &self,
+        name: impl Into<String>,
+        build: impl Fn() -> Result<Flowgraph> + Send + Sync + 'static,
+    ) where
+        S: Sync,
+    {
+        let name = name.into();
+        assert!(
+            name.parse::<usize>().is_err(),
+            "flowgraph name {} is numeric",
+            name
+        );
+        let scheduler = self.scheduler.clone();
+        let session = self.session.clone();
+        let flowgraphs = self.control_port.flowgraphs();
+        let build = Arc::new(build);
+        let launcher_name = name.clone();
+        let launcher: Launcher = Arc::new(move || {
+            let scheduler = scheduler.clone();
+            let session = session.clone();
+            let flowgraphs = flowgraphs.clone();
+            let build = build.clone();
+            let name = launcher_name.clone();
+            async move {
+                let fg = build()?;
+                let (task, _) = start_flowgraph(
+                    scheduler,
+                    session,
+                    move |name, handle| flowgraphs.lock().unwrap().insert(name, handle),
+                    Some(name),
+                    fg,
+                )
+                .await;
+                task.detach();
+                Ok(())
+            }
+            .boxed()
+        });
+        self.control_port.add_launcher(name, launcher);
+    }
+
+    async fn start_flowgraph(
+        &self,
+        name: Option<String>,
+        fg: Flowgraph,
+    ) -> (Task<Result<Flowgraph>>, FlowgraphHandle) {
+        start_flowgraph(
             self.scheduler.clone(),
-            fg_inbox.clone(),
-            fg_inbox_rx,
-            tx,
-        ));
-        rx.await
-            .expect("run_flowgraph did not signal startup completed");
-        let handle = FlowgraphHandle::new(fg_inbox);
-        self.control_port.add_flowgraph(handle.clone());
-        (task, handle)
+            #[cfg(not(target_arch = "wasm32"))]
+            self.session.clone(),
+            |name, handle| match name {
+                Some(name) => self.control_port.add_named_flowgraph(name, handle),
+                None => self.control_port.add_flowgraph(handle),
+            },
+            name,
+            fg,
+        )
+        .await
     }
 
     /// Main method that kicks-off the running of a [Flowgraph].
@@ -172,6 +329,45 @@ impl<S: Scheduler> Runtime<S> {
     }
 }
 
+/// Spawn a [Flowgraph] and register its handle with the session recorder, the control port (through
+/// `register`, returning the id), and telemetry.
+async fn start_flowgraph<S: Scheduler>(
+    scheduler: S,
+    #[cfg(not(target_arch = "wasm32"))] session: Option<Arc<SessionRecorder>>,
+    register: impl FnOnce(Option<String>, FlowgraphHandle) -> usize,
+    name: Option<String>,
+    fg: Flowgraph,
+) -> (Task<Result<Flowgraph>>, FlowgraphHandle) {
+    let queue_size = config::config().queue_size;
+    let (fg_inbox, fg_inbox_rx) = channel::<FlowgraphMessage>(queue_size);
+
+    let (tx, rx) = oneshot::channel::<()>();
+    let task = scheduler.spawn(run_flowgraph(
+        fg,
+        scheduler.clone(),
+        fg_inbox.clone(),
+        fg_inbox_rx,
+        tx,
+    ));
+    rx.await
+        .expect("run_flowgraph did not signal startup completed");
+    #[allow(unused_mut)]
+    let mut handle = FlowgraphHandle::new(fg_inbox);
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(recorder) = session {
+        match Recording::start(recorder, name.clone(), &mut handle).await {
+            Ok(r) => handle.set_recording(r),
+            Err(e) => warn!("cannot record session: {:?}", e),
+        }
+    }
+    let id = register(name.clone(), handle.clone());
+    #[cfg(not(target_arch = "wasm32"))]
+    telemetry::add_flowgraph(name.unwrap_or_else(|| id.to_string()), handle.clone());
+    #[cfg(target_arch = "wasm32")]
+    let _ = id;
+    (task, handle)
+}
+
 async fn run_flowgraph<S: Scheduler>(
     mut fg: Flowgraph,
     scheduler: S,
@@ -237,12 +433,15 @@ async fn run_flowgraph<S: Scheduler>(
     debug!("init blocks");
     // init blocks
     let mut active_blocks = 0u32;
-    for (_, opt) in inboxes.iter_mut() {
+    let mut states = HashMap::new();
+    for (id, opt) in inboxes.iter_mut() {
         if let Some(ref mut chan) = opt {
             chan.send(BlockMessage::Initialize).await.unwrap();
+            states.insert(id, BlockState::Running);
             active_blocks += 1;
         }
     }
+    let mut subscribers: Vec<mpsc::Sender<FlowgraphEvent>> = Vec::new();
 
     debug!("wait for blocks init");
     // wait until all blocks are initialized
@@ -260,6 +459,7 @@ async fn run_flowgraph<S: Scheduler>(
             FlowgraphMessage::BlockError { block_id, block } => {
                 *topology.blocks.get_mut(block_id).unwrap() = Some(block);
                 inboxes[block_id] = None;
+                states.insert(block_id, BlockState::Error);
                 i -= 1;
                 active_blocks -= 1;
                 block_error = true;
@@ -316,7 +516,7 @@ async fn run_flowgraph<S: Scheduler>(
                 tx,
             } => {
                 let (block_tx, block_rx) = oneshot::channel::<result::Result<(), HandlerError>>();
-                if let Some(inbox) = inboxes[block_id].as_mut() {
+                if let Some(Some(inbox)) = inboxes.get_mut(block_id) {
                     match inbox
                         .send(BlockMessage::Call {
                             port_id,
@@ -356,7 +556,7 @@ async fn run_flowgraph<S: Scheduler>(
                 tx,
             } => {
                 let (block_tx, block_rx) = oneshot::channel::<result::Result<Pmt, HandlerError>>();
-                if let Some(inbox) = inboxes[block_id].as_mut() {
+                if let Some(Some(inbox)) = inboxes.get_mut(block_id) {
                     match inbox
                         .send(BlockMessage::Callback {
                             port_id,
@@ -385,20 +585,44 @@ async fn run_flowgraph<S: Scheduler>(
                             let _ = tx.send(Err(CallbackError::InvalidBlock));
                         }
                     }
+                } else {
+                    let _ = tx.send(Err(CallbackError::InvalidBlock));
                 }
             }
             FlowgraphMessage::BlockDone { block_id, block } => {
                 *topology.blocks.get_mut(block_id).unwrap() = Some(block);
                 inboxes[block_id] = None;
                 active_blocks -= 1;
+                states.insert(block_id, BlockState::Done);
+                publish(
+                    &mut subscribers,
+                    FlowgraphEvent::BlockState {
+                        block_id,
+                        state: BlockState::Done,
+                    },
+                );
             }
             FlowgraphMessage::BlockError { block_id, block } => {
                 *topology.blocks.get_mut(block_id).unwrap() = Some(block);
                 inboxes[block_id] = None;
                 block_error = true;
                 active_blocks -= 1;
+                states.insert(block_id, BlockState::Error);
+                publish(
+                    &mut subscribers,
+                    FlowgraphEvent::BlockState {
+                        block_id,
+                        state: BlockState::Error,
+                    },
+                );
                 let _ = main_channel.send(FlowgraphMessage::Terminate).await;
             }
+            FlowgraphMessage::Subscribe { tx } => {
+                subscribers.push(tx);
+            }
+            FlowgraphMessage::Stats { tx } => {
+                let _ = tx.send(stats(&states));
+            }
             FlowgraphMessage::BlockDescription { block_id, tx } => {
                 match inboxes.get_mut(block_id) {
                     Some(Some(ref mut b)) => {
@@ -467,6 +691,11 @@ async fn run_flowgraph<S: Scheduler>(
         }
     }
 
+    publish(
+        &mut subscribers,
+        FlowgraphEvent::Terminated { error: block_error },
+    );
+
     fg.topology = Some(topology);
     if block_error {
         bail!("flowgraph error");
@@ -475,6 +704,28 @@ async fn run_flowgraph<S: Scheduler>(
     Ok(fg)
 }
 
+fn publish(subscribers: &mut Vec<mpsc::Sender<FlowgraphEvent>>, event: FlowgraphEvent) {
+    subscribers.retain(|s| !s.is_closed());
+    for s in subscribers.iter_mut() {
+        if s.try_send(event.clone()).is_err() {
+            debug!("event subscriber lagging, dropping event");
+        }
+    }
+}
+
+fn stats(states: &HashMap<usize, BlockState>) -> FlowgraphStats {
+    let mut blocks: Vec<(usize, BlockState)> = states.iter().map(|(k, v)| (*k, *v)).collect();
+    blocks.sort_by_key(|x| x.0);
+    let count = |s| blocks.iter().filter(|x| x.1 == s).count();
+
+    FlowgraphStats {
+        running: count(BlockState::Running),
+        done: count(BlockState::Done),
+        error: count(BlockState::Error),
+        blocks,
+    }
+}
+
 pub(crate) async fn run_block(
     block: Block,
     block_id: usize,
@@ -549,6 +800,9 @@ async fn run_block_internal(
 
     // main loop
     loop {
+        #[cfg(target_os = "linux")]
+        realtime::promote_current_thread();
+
         // ================== non blocking
         loop {
             match inbox.next().now_or_never() {
@@ -565,6 +819,7 @@ async fn run_block_internal(
                         .map(|x| x.name().to_string())
                         .collect();
                     let message_inputs: Vec<String> = block.message_input_names();
+                    let message_input_descriptions = block.message_input_descriptions();
                     let message_outputs: Vec<String> = block
                         .message_outputs()
                         .iter()
@@ -578,6 +833,7 @@ async fn run_block_internal(
                         stream_inputs,
                         stream_outputs,
                         message_inputs,
+                        message_input_descriptions,
                         message_outputs,
                         blocking: block.is_blocking(),
                     };
@@ -590,14 +846,33 @@ async fn run_block_internal(
                     work_io.finished = true;
                 }
                 Some(Some(BlockMessage::Call { port_id, data, tx })) => {
-                    match block.call_handler(port_id, data).await {
+                    let (res, txs) = if let Some(id) = block.batch_input(&port_id) {
+                        // collect all calls to the port that are queued
+                        let mut batch = vec![data];
+                        let mut txs = vec![tx];
+                        while let Some(Some(BlockMessage::Call { data, tx, .. })) = inbox
+                            .as_mut()
+                            .next_if(|m| {
+                                matches!(m, BlockMessage::Call { port_id, .. }
+                                    if block.batch_input(port_id) == Some(id))
+                            })
+                            .now_or_never()
+                        {
+                            batch.push(data);
+                            txs.push(tx);
+                        }
+                        (block.call_batch_handler(id, batch).await, txs)
+                    } else {
+                        (block.call_handler(port_id, data).await, vec![tx])
+                    };
+                    match res {
                         Ok(_) => {
-                            if let Some(tx) = tx {
+                            for tx in txs.into_iter().flatten() {
                                 let _ = tx.send(Ok(()));
                             }
                         }
                         Err(HandlerError::InvalidHandler) => {
-                            if let Some(tx) = tx {
+                            for tx in txs.into_iter().flatten() {
                                 let _ = tx.send(Err(HandlerError::InvalidHandler));
                             }
                         }
@@ -607,7 +882,7 @@ async fn run_block_internal(
                                 block.instance_name().unwrap(),
                                 HandlerError::HandlerError
                             );
-                            if let Some(tx) = tx {
+                            for tx in txs.into_iter().flatten() {
                                 let _ = tx.send(Err(HandlerError::HandlerError));
                             }
                             main_inbox
@@ -692,6 +967,8 @@ async fn run_block_internal(
 
         // ================== blocking
         if !work_io.call_again {
+            // wake up downstream blocks, before going to sleep
+            block.flush();
             if let Some(f) = work_io.block_on.take() {
                 let p = inbox.as_mut().peek();
 
@@ -712,6 +989,10 @@ async fn run_block_internal(
 
         // ================== work
         work_io.call_again = false;
+        if !block.stream_ready() {
+            // wait for more input
+            continue;
+        }
         if let Err(e) = block.work(&mut work_io).await {
             error!(
                 "{}: Error in work(). Terminating. ({:?})",
@@ -730,3 +1011,68 @@ async fn run_block_internal(
 
     Ok(())
 }
+
+#[cfg(all(test, feature = "web", not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use async_io::Timer;
+    use axum::http::StatusCode;
+    use std::time::Duration;
+
+    use crate::blocks::NullSink;
+    use crate::blocks::NullSource;
+    use crate::runtime::ctrl_port::router;
+    use crate::runtime::ctrl_port::tests::request;
+
+    fn build() -> Result<Flowgraph> {
+        let mut fg = Flowgraph::new();
+        let src = fg.add_block(NullSource::<u8>::new());
+        let snk = fg.add_block(NullSink::<u8>::new());
+        fg.connect_stream(src, "out", snk, "in")?;
+        Ok(fg)
+    }
+
+    #[test]
+    fn start_named_through_control_port() {
+        let rt = Runtime::new();
+        rt.add_named("adsb", build);
+        let app = router(rt.control_port.flowgraphs(), ControlPortConfig::new());
+
+        block_on(async move {
+            let (status, body) = request(&app, "POST", "/api/fg/adsb/start/", None).await;
+            assert_eq!(status, StatusCode::OK);
+            let id: usize = serde_json::from_slice(&body).unwrap();
+            let (status, _) = request(&app, "GET", &format!("/api/fg/{}/", id), None).await;
+            assert_eq!(status, StatusCode::OK);
+
+            let (status, _) = request(&app, "POST", "/api/fg/adsb/start/", None).await;
+            assert_eq!(status, StatusCode::CONFLICT);
+            let (status, _) = request(&app, "POST", "/api/fg/ais/start/", None).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+
+            // stop and restart
+            let (status, _) = request(&app, "POST", "/api/fg/adsb/terminate/", None).await;
+            assert_eq!(status, StatusCode::OK);
+            for _ in 0..500 {
+                let (_, body) = request(&app, "GET", "/api/names/", None).await;
+                if &body[..] == b"{}" {
+                    break;
+                }
+                Timer::after(Duration::from_millis(10)).await;
+            }
+            let (_, body) = request(&app, "GET", "/api/fg/", None).await;
+            assert_eq!(&body[..], b"0");
+
+            let (status, _) = request(&app, "POST", "/api/fg/adsb/start/", None).await;
+            assert_eq!(status, StatusCode::OK);
+            let (status, _) = request(&app, "POST", "/api/fg/adsb/terminate/", None).await;
+            assert_eq!(status, StatusCode::OK);
+        });
+    }
+
+    #[test]
+    #[should_panic]
+    fn numeric_name() {
+        Runtime::new().add_named("1", build);
+    }
+}
@@ -4,7 +4,6 @@ use async_task::Runnable;
 use async_task::Task;
 use concurrent_queue::ConcurrentQueue;
 use core_affinity;
-use futures::channel::mpsc::{channel, Sender};
 use futures::channel::oneshot;
 use futures_lite::future::{self, Future, FutureExt};
 use slab::Slab;
@@ -16,6 +15,7 @@ use std::sync::{Arc, Mutex, RwLock};
 use std::task::{Poll, Waker};
 use std::thread;
 
+use crate::runtime::channel::{channel, Sender};
 use crate::runtime::config;
 use crate::runtime::run_block;
 use crate::runtime::scheduler::Scheduler;
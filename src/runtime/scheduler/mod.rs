@@ -4,6 +4,13 @@ mod flow;
 #[cfg(feature = "flow_scheduler")]
 pub use crate::runtime::scheduler::flow::FlowScheduler;
 
+#[cfg(feature = "profiling_scheduler")]
+mod profiling;
+#[cfg(feature = "profiling_scheduler")]
+pub use crate::runtime::scheduler::profiling::BlockProfile;
+#[cfg(feature = "profiling_scheduler")]
+pub use crate::runtime::scheduler::profiling::ProfilingScheduler;
+
 #[cfg(not(target_arch = "wasm32"))]
 mod smol;
 #[cfg(not(target_arch = "wasm32"))]
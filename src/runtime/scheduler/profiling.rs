@@ -0,0 +1,438 @@
+use async_task::Runnable;
+use async_task::Task;
+use concurrent_queue::ConcurrentQueue;
+use futures::future::poll_fn;
+use futures::future::Future;
+use once_cell::sync::OnceCell;
+use slab::Slab;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::runtime::channel::{channel, Sender};
+use crate::runtime::config;
+use crate::runtime::run_block;
+use crate::runtime::scheduler::Scheduler;
+use crate::runtime::BlockMessage;
+use crate::runtime::FlowgraphMessage;
+use crate::runtime::Topology;
+
+/// Scheduler that profiles blocks and migrates them between workers to balance the load.
+///
+/// Each worker thread is pinned to a core and runs the blocks that are currently assigned to
+/// it. The scheduler measures the time each block spends in its `work()` and message handlers.
+/// Periodically, it orders the blocks along the stream connections of the flowgraph (i.e.,
+/// from sources to sinks) and splits this order into contiguous segments of equal load, one per
+/// worker. This balances the load, while producers and consumers end up on the same or
+/// neighboring workers, i.e., cores. A block moves to its new worker the next time it is woken
+/// up.
+///
+/// Blocking blocks run on their own threads and are not profiled.
+///
+/// ```no_run
+/// use futuresdr::runtime::scheduler::ProfilingScheduler;
+/// use futuresdr::runtime::Runtime;
+/// use std::time::Duration;
+///
+/// let rt = Runtime::with_scheduler(ProfilingScheduler::new(4, Duration::from_millis(200)));
+/// ```
+#[derive(Clone, Debug)]
+pub struct ProfilingScheduler {
+    inner: Arc<ProfilingSchedulerInner>,
+}
+
+struct ProfilingSchedulerInner {
+    state: Arc<State>,
+    workers: Vec<thread::JoinHandle<()>>,
+    balancer: Option<thread::JoinHandle<()>>,
+}
+
+impl fmt::Debug for ProfilingSchedulerInner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProfilingSchedulerInner")
+            .field("workers", &self.workers.len())
+            .finish()
+    }
+}
+
+impl Drop for ProfilingSchedulerInner {
+    fn drop(&mut self) {
+        self.state.shutdown.store(true, Ordering::SeqCst);
+        if let Some(b) = self.balancer.take() {
+            b.thread().unpark();
+            b.join().unwrap();
+        }
+        for w in self.workers.drain(..) {
+            w.thread().unpark();
+            w.join().unwrap();
+        }
+        // dropping runnables might wake other tasks, which pushes them to the queues again
+        loop {
+            let mut empty = true;
+            for q in self.state.queues.iter() {
+                while let Ok(r) = q.pop() {
+                    drop(r);
+                    empty = false;
+                }
+            }
+            if empty {
+                break;
+            }
+        }
+    }
+}
+
+/// Load of a block, measured by the [ProfilingScheduler].
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlockProfile {
+    /// Instance name of the block.
+    pub name: String,
+    /// Time the block spent running.
+    pub busy: Duration,
+    /// Worker, the block is currently assigned to.
+    pub worker: usize,
+}
+
+/// Required reduction of the maximum worker load to migrate blocks.
+const MIGRATION_GAIN: f64 = 0.9;
+
+struct Profile {
+    name: String,
+    rank: usize,
+    busy: Arc<AtomicU64>,
+    worker: Arc<AtomicUsize>,
+    last: u64,
+    load: f64,
+}
+
+struct State {
+    queues: Vec<ConcurrentQueue<Runnable>>,
+    threads: OnceCell<Vec<thread::Thread>>,
+    shutdown: AtomicBool,
+    blocks: Mutex<Slab<Profile>>,
+    next_rank: AtomicUsize,
+    next_worker: AtomicUsize,
+}
+
+impl State {
+    fn schedule(&self, worker: usize, runnable: Runnable) {
+        self.queues[worker].push(runnable).unwrap();
+        if let Some(t) = self.threads.get() {
+            t[worker].unpark();
+        }
+    }
+
+    fn run(&self, worker: usize) {
+        loop {
+            if let Ok(r) = self.queues[worker].pop() {
+                r.run();
+                continue;
+            }
+            if self.shutdown.load(Ordering::SeqCst) {
+                break;
+            }
+            thread::park();
+        }
+    }
+
+    fn balance(&self) {
+        let n_workers = self.queues.len();
+        let mut blocks = self.blocks.lock().unwrap();
+
+        for (_, p) in blocks.iter_mut() {
+            let busy = p.busy.load(Ordering::Relaxed);
+            p.load = 0.5 * p.load + 0.5 * (busy - p.last) as f64;
+            p.last = busy;
+        }
+
+        let total: f64 = blocks.iter().map(|(_, p)| p.load).sum();
+        if total <= 0.0 {
+            return;
+        }
+
+        let mut order: Vec<&Profile> = blocks.iter().map(|(_, p)| p).collect();
+        order.sort_by_key(|p| p.rank);
+
+        let target = total / n_workers as f64;
+        let mut acc = 0.0;
+        let assignment: Vec<usize> = order
+            .iter()
+            .map(|p| {
+                let worker = (((acc + p.load / 2.0) / target) as usize).min(n_workers - 1);
+                acc += p.load;
+                worker
+            })
+            .collect();
+
+        // only migrate if it reduces the load of the busiest worker noticeably, to avoid
+        // blocks bouncing between workers due to measurement noise
+        let max_load = |workers: &mut dyn Iterator<Item = usize>| {
+            let mut loads = vec![0.0; n_workers];
+            for (w, p) in workers.zip(order.iter()) {
+                loads[w] += p.load;
+            }
+            loads.into_iter().fold(0.0, f64::max)
+        };
+        let current = max_load(&mut order.iter().map(|p| p.worker.load(Ordering::Relaxed)));
+        let balanced = max_load(&mut assignment.iter().cloned());
+        if balanced > MIGRATION_GAIN * current {
+            return;
+        }
+
+        for (p, worker) in order.iter().zip(assignment) {
+            if p.worker.swap(worker, Ordering::Relaxed) != worker {
+                debug!("migrating {} to worker {}", p.name, worker);
+            }
+        }
+    }
+}
+
+impl ProfilingScheduler {
+    /// Create a scheduler with `n_workers` threads that rebalances blocks every `interval`.
+    pub fn new(n_workers: usize, interval: Duration) -> ProfilingScheduler {
+        assert!(n_workers > 0, "scheduler needs at least one worker");
+
+        let state = Arc::new(State {
+            queues: (0..n_workers)
+                .map(|_| ConcurrentQueue::unbounded())
+                .collect(),
+            threads: OnceCell::new(),
+            shutdown: AtomicBool::new(false),
+            blocks: Mutex::new(Slab::new()),
+            next_rank: AtomicUsize::new(0),
+            next_worker: AtomicUsize::new(0),
+        });
+
+        let core_ids = core_affinity::get_core_ids().unwrap_or_default();
+        let mut workers = Vec::new();
+        for i in 0..n_workers {
+            let s = state.clone();
+            let core = core_ids.get(i % core_ids.len().max(1)).cloned();
+            let handle = thread::Builder::new()
+                .name(format!("profiling-{i}"))
+                .spawn(move || {
+                    if let Some(c) = core {
+                        debug!("starting worker {} on core id {}", i, c.id);
+                        core_affinity::set_for_current(c);
+                    }
+                    s.run(i);
+                })
+                .expect("failed to spawn worker thread");
+            workers.push(handle);
+        }
+        let _ = state
+            .threads
+            .set(workers.iter().map(|w| w.thread().clone()).collect());
+
+        let s = state.clone();
+        let balancer = thread::Builder::new()
+            .name("profiling-balancer".to_string())
+            .spawn(move || loop {
+                let deadline = Instant::now() + interval;
+                while !s.shutdown.load(Ordering::SeqCst) {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        break;
+                    }
+                    thread::park_timeout(deadline - now);
+                }
+                if s.shutdown.load(Ordering::SeqCst) {
+                    break;
+                }
+                s.balance();
+            })
+            .expect("failed to spawn balancer thread");
+
+        ProfilingScheduler {
+            inner: Arc::new(ProfilingSchedulerInner {
+                state,
+                workers,
+                balancer: Some(balancer),
+            }),
+        }
+    }
+
+    /// Profiles of all running blocks, ordered from sources to sinks.
+    pub fn profile(&self) -> Vec<BlockProfile> {
+        let blocks = self.inner.state.blocks.lock().unwrap();
+        let mut profiles: Vec<(usize, BlockProfile)> = blocks
+            .iter()
+            .map(|(_, p)| {
+                (
+                    p.rank,
+                    BlockProfile {
+                        name: p.name.clone(),
+                        busy: Duration::from_nanos(p.busy.load(Ordering::Relaxed)),
+                        worker: p.worker.load(Ordering::Relaxed),
+                    },
+                )
+            })
+            .collect();
+        profiles.sort_by_key(|p| p.0);
+        profiles.into_iter().map(|p| p.1).collect()
+    }
+
+    fn spawn_on<T: Send + 'static>(
+        &self,
+        future: impl Future<Output = T> + Send + 'static,
+        worker: Arc<AtomicUsize>,
+    ) -> Task<T> {
+        let state = self.inner.state.clone();
+        let (runnable, task) = async_task::spawn(future, move |r| {
+            state.schedule(worker.load(Ordering::Relaxed), r)
+        });
+        runnable.schedule();
+        task
+    }
+
+    fn spawn_profiled<T: Send + 'static>(
+        &self,
+        future: impl Future<Output = T> + Send + 'static,
+        name: String,
+        rank: usize,
+        worker: usize,
+    ) -> Task<T> {
+        let busy = Arc::new(AtomicU64::new(0));
+        let worker = Arc::new(AtomicUsize::new(worker));
+        let key = self.inner.state.blocks.lock().unwrap().insert(Profile {
+            name,
+            rank,
+            busy: busy.clone(),
+            worker: worker.clone(),
+            last: 0,
+            load: 0.0,
+        });
+
+        let state = self.inner.state.clone();
+        let guard = CallOnDrop(move || drop(state.blocks.lock().unwrap().remove(key)));
+        let mut future = Box::pin(future);
+        let future = poll_fn(move |cx| {
+            let _ = &guard;
+            let start = Instant::now();
+            let ret = future.as_mut().poll(cx);
+            busy.fetch_add(start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+            ret
+        });
+
+        self.spawn_on(future, worker)
+    }
+
+    /// Order blocks from sources to sinks, following the stream connections.
+    fn order(topology: &Topology) -> Vec<usize> {
+        let mut edges: Vec<(usize, usize)> = topology
+            .stream_edges
+            .iter()
+            .flat_map(|((src, _, _), dsts)| dsts.iter().map(move |(dst, _)| (*src, *dst)))
+            .collect();
+        edges.sort_unstable();
+        edges.dedup();
+
+        let ids: Vec<usize> = topology.blocks.iter().map(|(i, _)| i).collect();
+        let mut visited = vec![false; ids.iter().max().map(|m| m + 1).unwrap_or(0)];
+        let mut order = Vec::new();
+
+        let sources = ids
+            .iter()
+            .filter(|i| !edges.iter().any(|(_, dst)| dst == *i))
+            .chain(ids.iter());
+        for s in sources {
+            let mut stack = vec![*s];
+            while let Some(b) = stack.pop() {
+                if visited[b] {
+                    continue;
+                }
+                visited[b] = true;
+                order.push(b);
+                stack.extend(
+                    edges
+                        .iter()
+                        .rev()
+                        .filter(|(src, _)| *src == b)
+                        .map(|(_, dst)| *dst),
+                );
+            }
+        }
+
+        order
+    }
+}
+
+impl Scheduler for ProfilingScheduler {
+    fn run_topology(
+        &self,
+        topology: &mut Topology,
+        main_channel: &Sender<FlowgraphMessage>,
+    ) -> Slab<Option<Sender<BlockMessage>>> {
+        let mut inboxes = Slab::new();
+        let max = topology.blocks.iter().map(|(i, _)| i).max().unwrap_or(0);
+        for _ in 0..=max {
+            inboxes.insert(None);
+        }
+        let queue_size = config::config().queue_size;
+
+        let order = ProfilingScheduler::order(topology);
+        let n_blocks = order.len();
+        let n_workers = self.inner.workers.len();
+        let first_rank = self
+            .inner
+            .state
+            .next_rank
+            .fetch_add(n_blocks, Ordering::Relaxed);
+
+        // spawn block executors, initially distributing them evenly
+        for (i, id) in order.into_iter().enumerate() {
+            let block = topology.blocks[id].take().unwrap();
+
+            let (sender, receiver) = channel::<BlockMessage>(queue_size);
+            inboxes[id] = Some(sender);
+
+            if block.is_blocking() {
+                self.spawn_blocking(run_block(block, id, main_channel.clone(), receiver))
+                    .detach();
+            } else {
+                let name = block.instance_name().unwrap_or_default().to_string();
+                self.spawn_profiled(
+                    run_block(block, id, main_channel.clone(), receiver),
+                    name,
+                    first_rank + i,
+                    i * n_workers / n_blocks,
+                )
+                .detach();
+            }
+        }
+
+        inboxes
+    }
+
+    fn spawn<T: Send + 'static>(
+        &self,
+        future: impl Future<Output = T> + Send + 'static,
+    ) -> Task<T> {
+        let n_workers = self.inner.workers.len();
+        let worker = self.inner.state.next_worker.fetch_add(1, Ordering::Relaxed) % n_workers;
+        self.spawn_on(future, Arc::new(AtomicUsize::new(worker)))
+    }
+
+    fn spawn_blocking<T: Send + 'static>(
+        &self,
+        future: impl Future<Output = T> + Send + 'static,
+    ) -> Task<T> {
+        self.spawn(blocking::unblock(|| async_io::block_on(future)))
+    }
+}
+
+impl Default for ProfilingScheduler {
+    fn default() -> Self {
+        Self::new(num_cpus::get(), Duration::from_millis(500))
+    }
+}
+
+struct CallOnDrop<F: Fn()>(F);
+
+impl<F: Fn()> Drop for CallOnDrop<F> {
+    fn drop(&mut self) {
+        (self.0)();
+    }
+}
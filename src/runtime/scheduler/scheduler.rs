@@ -1,4 +1,4 @@
-use futures::channel::mpsc::Sender;
+use crate::runtime::channel::Sender;
 use futures::future::Future;
 use slab::Slab;
 
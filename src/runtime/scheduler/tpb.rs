@@ -1,5 +1,4 @@
 use async_executor::{Executor, Task};
-use futures::channel::mpsc::{channel, Sender};
 use futures::channel::oneshot;
 use futures::future::Future;
 use once_cell::sync::Lazy;
@@ -8,6 +7,7 @@ use std::fmt;
 use std::sync::{Arc, Mutex};
 use std::thread;
 
+use crate::runtime::channel::{channel, Sender};
 use crate::runtime::config;
 use crate::runtime::run_block;
 use crate::runtime::scheduler::Scheduler;
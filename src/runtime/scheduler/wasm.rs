@@ -1,4 +1,3 @@
-use futures::channel::mpsc::{channel, Sender};
 use futures::channel::oneshot;
 use futures::future::Future;
 use futures::task::Context;
@@ -7,6 +6,7 @@ use futures_lite::FutureExt;
 use slab::Slab;
 use std::pin::Pin;
 
+use crate::runtime::channel::{channel, Sender};
 use crate::runtime::config;
 use crate::runtime::run_block;
 use crate::runtime::scheduler::Scheduler;
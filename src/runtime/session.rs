@@ -0,0 +1,475 @@
+//! Record and Replay Control Sessions
+//!
+//! Records all interactions with the [FlowgraphHandle]s of a running flowgraph (calls and
+//! callbacks with their [Pmt]s and results, queries, and termination) together with their
+//! timing. This includes interactions through the control port, which uses a
+//! [FlowgraphHandle] internally. The recorded session can be replayed against a rebuilt
+//! flowgraph to reproduce control-plane issues, like mis-applied device configurations, on a
+//! developer machine.
+//!
+//! Recording is enabled by setting `record_session` in the config (or the
+//! `FUTURESDR_RECORD_SESSION` environment variable) to the path of the session file, which then
+//! records all flowgraphs of the process. Alternatively, it is enabled for a single
+//! [Runtime](crate::runtime::Runtime) with
+//! [record_session](crate::runtime::Runtime::record_session). Sessions are stored as JSON lines,
+//! one [SessionEvent] per line, and the file is flushed after every event.
+//!
+//! ```no_run
+//! use futuresdr::anyhow::Result;
+//! use futuresdr::async_io::block_on;
+//! use futuresdr::runtime::session::SessionReplay;
+//! use futuresdr::runtime::Flowgraph;
+//! use futuresdr::runtime::Runtime;
+//!
+//! # fn build_flowgraph() -> Result<Flowgraph> { Ok(Flowgraph::new()) }
+//! # fn main() -> Result<()> {
+//! let replay = SessionReplay::load("session.jsonl")?.speed(10.0);
+//!
+//! let rt = Runtime::new();
+//! let (task, mut handle) = block_on(rt.start(build_flowgraph()?));
+//! let report = block_on(replay.replay(&mut handle))?;
+//! block_on(task)?;
+//! assert!(report.is_ok(), "{}", report);
+//! # Ok(())
+//! # }
+//! ```
+use async_io::Timer;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::anyhow::{bail, Context, Result};
+use crate::runtime::config;
+use crate::runtime::FlowgraphDescription;
+use crate::runtime::FlowgraphHandle;
+use crate::runtime::Pmt;
+use crate::runtime::PortId;
+
+static CONFIG_RECORDER: Lazy<Option<Arc<SessionRecorder>>> = Lazy::new(|| {
+    let path = config::get::<String>("record_session")?;
+    match SessionRecorder::create(&path) {
+        Ok(r) => Some(Arc::new(r)),
+        Err(e) => {
+            warn!("cannot record session: {:?}", e);
+            None
+        }
+    }
+});
+
+/// Recorder of the `record_session` config option, shared by all runtimes of the process.
+pub(crate) fn from_config() -> Option<Arc<SessionRecorder>> {
+    CONFIG_RECORDER.clone()
+}
+
+/// Event of a recorded session.
+///
+/// `time` is the time in seconds since the recording started. [Pmt]s that cannot be
+/// serialized (i.e., [Pmt::Any]) are recorded as `None` and skipped during replay.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum SessionEvent {
+    /// A flowgraph was started.
+    Start {
+        flowgraph: usize,
+        time: f64,
+        name: Option<String>,
+        description: FlowgraphDescription,
+    },
+    /// [FlowgraphHandle::call]
+    Call {
+        flowgraph: usize,
+        time: f64,
+        block_id: usize,
+        port_id: PortId,
+        data: Option<Pmt>,
+        result: Result<(), String>,
+    },
+    /// [FlowgraphHandle::callback]
+    Callback {
+        flowgraph: usize,
+        time: f64,
+        block_id: usize,
+        port_id: PortId,
+        data: Option<Pmt>,
+        result: Result<Option<Pmt>, String>,
+    },
+    /// Query of the flowgraph that does not change its state (e.g., `description` or `stats`).
+    Query {
+        flowgraph: usize,
+        time: f64,
+        query: String,
+    },
+    /// [FlowgraphHandle::terminate]
+    Terminate { flowgraph: usize, time: f64 },
+}
+
+impl SessionEvent {
+    /// Id of the flowgraph within the session.
+    pub fn flowgraph(&self) -> usize {
+        match self {
+            SessionEvent::Start { flowgraph, .. }
+            | SessionEvent::Call { flowgraph, .. }
+            | SessionEvent::Callback { flowgraph, .. }
+            | SessionEvent::Query { flowgraph, .. }
+            | SessionEvent::Terminate { flowgraph, .. } => *flowgraph,
+        }
+    }
+
+    /// Time in seconds since the recording started.
+    pub fn time(&self) -> f64 {
+        match self {
+            SessionEvent::Start { time, .. }
+            | SessionEvent::Call { time, .. }
+            | SessionEvent::Callback { time, .. }
+            | SessionEvent::Query { time, .. }
+            | SessionEvent::Terminate { time, .. } => *time,
+        }
+    }
+}
+
+/// [Pmt] as it is recorded, i.e., `None` if it cannot be serialized.
+pub(crate) fn recordable(p: &Pmt) -> Option<Pmt> {
+    fn check(p: &Pmt) -> bool {
+        match p {
+            Pmt::Any(_) => false,
+            Pmt::VecPmt(v) => v.iter().all(check),
+            Pmt::MapStrPmt(m) => m.values().all(check),
+            _ => true,
+        }
+    }
+    if check(p) {
+        Some(p.clone())
+    } else {
+        None
+    }
+}
+
+/// Writes session events to a file.
+pub(crate) struct SessionRecorder {
+    file: Mutex<BufWriter<File>>,
+    start: Instant,
+    flowgraphs: AtomicUsize,
+}
+
+impl SessionRecorder {
+    pub(crate) fn create(path: impl AsRef<Path>) -> Result<SessionRecorder> {
+        let path = path.as_ref();
+        let file = File::create(path)
+            .with_context(|| format!("cannot create session file {}", path.display()))?;
+        info!("recording session to {}", path.display());
+        Ok(SessionRecorder {
+            file: Mutex::new(BufWriter::new(file)),
+            start: Instant::now(),
+            flowgraphs: AtomicUsize::new(0),
+        })
+    }
+
+    fn time(&self) -> f64 {
+        self.start.elapsed().as_secs_f64()
+    }
+
+    fn write(&self, event: &SessionEvent) {
+        let mut file = self.file.lock().unwrap();
+        let res = serde_json::to_writer(&mut *file, event)
+            .map_err(std::io::Error::from)
+            .and_then(|_| file.write_all(b"\n"))
+            .and_then(|_| file.flush());
+        if let Err(e) = res {
+            warn!("cannot record session event: {:?}", e);
+        }
+    }
+}
+
+/// Recording of a single flowgraph, attached to its [FlowgraphHandle]s.
+#[derive(Clone)]
+pub(crate) struct Recording {
+    recorder: Arc<SessionRecorder>,
+    flowgraph: usize,
+}
+
+impl Recording {
+    /// Register a started flowgraph with the recorder.
+    pub(crate) async fn start(
+        recorder: Arc<SessionRecorder>,
+        name: Option<String>,
+        handle: &mut FlowgraphHandle,
+    ) -> Result<Recording> {
+        let description = handle.description().await?;
+        let flowgraph = recorder.flowgraphs.fetch_add(1, Ordering::SeqCst);
+        recorder.write(&SessionEvent::Start {
+            flowgraph,
+            time: recorder.time(),
+            name,
+            description,
+        });
+        Ok(Recording {
+            recorder,
+            flowgraph,
+        })
+    }
+
+    pub(crate) fn flowgraph(&self) -> usize {
+        self.flowgraph
+    }
+
+    pub(crate) fn time(&self) -> f64 {
+        self.recorder.time()
+    }
+
+    pub(crate) fn record(&self, event: SessionEvent) {
+        self.recorder.write(&event);
+    }
+}
+
+/// Replay a recorded session against a [FlowgraphHandle].
+///
+/// The events are replayed with their original timing (scaled by [speed](Self::speed)) and the
+/// results are compared to the recorded ones.
+#[derive(Debug, Clone)]
+pub struct SessionReplay {
+    events: Vec<SessionEvent>,
+    flowgraph: usize,
+    speed: f64,
+}
+
+impl SessionReplay {
+    /// Create a replay from session events.
+    pub fn new(events: Vec<SessionEvent>) -> SessionReplay {
+        SessionReplay {
+            events,
+            flowgraph: 0,
+            speed: 1.0,
+        }
+    }
+
+    /// Load a recorded session file.
+    pub fn load(path: impl AsRef<Path>) -> Result<SessionReplay> {
+        let path = path.as_ref();
+        let file = File::open(path)
+            .with_context(|| format!("cannot open session file {}", path.display()))?;
+
+        let mut events = Vec::new();
+        for (i, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            events.push(
+                serde_json::from_str(&line)
+                    .with_context(|| format!("{}:{}: invalid event", path.display(), i + 1))?,
+            );
+        }
+        Ok(SessionReplay::new(events))
+    }
+
+    /// Events of the session.
+    pub fn events(&self) -> &[SessionEvent] {
+        &self.events
+    }
+
+    /// Flowgraph of the session to replay (default: `0`, i.e., the first one started).
+    #[must_use]
+    pub fn flowgraph(mut self, flowgraph: usize) -> SessionReplay {
+        self.flowgraph = flowgraph;
+        self
+    }
+
+    /// Speed-up relative to the recorded timing (default: `1.0`).
+    ///
+    /// Use `f64::INFINITY` to replay the events back-to-back.
+    #[must_use]
+    pub fn speed(mut self, speed: f64) -> SessionReplay {
+        assert!(speed > 0.0, "speed has to be positive");
+        self.speed = speed;
+        self
+    }
+
+    /// Replay the session.
+    ///
+    /// Fails, if the session does not contain the flowgraph or if the blocks of the flowgraph
+    /// behind the handle do not match the recorded ones. Deviating results are reported in the
+    /// [ReplayReport].
+    pub async fn replay(&self, handle: &mut FlowgraphHandle) -> Result<ReplayReport> {
+        let mut events = self
+            .events
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.flowgraph() == self.flowgraph);
+
+        let t0 = match events.next() {
+            Some((
+                _,
+                SessionEvent::Start {
+                    time, description, ..
+                },
+            )) => {
+                check_description(description, &handle.description().await?)?;
+                *time
+            }
+            _ => bail!("session does not contain flowgraph {}", self.flowgraph),
+        };
+
+        let start = Instant::now();
+        let mut report = ReplayReport::default();
+        for (i, event) in events {
+            let offset = ((event.time() - t0) / self.speed).max(0.0);
+            if offset.is_finite() {
+                Timer::at(start + Duration::from_secs_f64(offset)).await;
+            }
+
+            match event {
+                SessionEvent::Call {
+                    block_id,
+                    port_id,
+                    data: Some(data),
+                    result,
+                    ..
+                } => {
+                    let actual = handle
+                        .call(*block_id, port_id.clone(), data.clone())
+                        .await
+                        .map_err(|e| e.to_string());
+                    report.replayed += 1;
+                    if &actual != result {
+                        report.mismatches.push(ReplayMismatch {
+                            event: i,
+                            action: format!("call block {} port {:?}", block_id, port_id),
+                            expected: format!("{:?}", result),
+                            actual: format!("{:?}", actual),
+                        });
+                    }
+                }
+                SessionEvent::Callback {
+                    block_id,
+                    port_id,
+                    data: Some(data),
+                    result,
+                    ..
+                } => {
+                    let actual = handle
+                        .callback(*block_id, port_id.clone(), data.clone())
+                        .await
+                        .map(|p| recordable(&p))
+                        .map_err(|e| e.to_string());
+                    report.replayed += 1;
+                    if !same_result(result, &actual) {
+                        report.mismatches.push(ReplayMismatch {
+                            event: i,
+                            action: format!("callback block {} port {:?}", block_id, port_id),
+                            expected: format!("{:?}", result),
+                            actual: format!("{:?}", actual),
+                        });
+                    }
+                }
+                SessionEvent::Call { .. } | SessionEvent::Callback { .. } => {
+                    warn!("skipping event {} with unrecorded data", i);
+                    report.skipped += 1;
+                }
+                SessionEvent::Terminate { .. } => {
+                    if handle.terminate().await.is_err() {
+                        debug!("flowgraph already terminated");
+                    }
+                    report.replayed += 1;
+                }
+                SessionEvent::Query { .. } => {}
+                SessionEvent::Start { .. } => {
+                    bail!("event {}: flowgraph {} started twice", i, self.flowgraph)
+                }
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+fn check_description(recorded: &FlowgraphDescription, actual: &FlowgraphDescription) -> Result<()> {
+    for b in recorded.blocks.iter() {
+        match actual.blocks.iter().find(|a| a.id == b.id) {
+            Some(a) if a.type_name == b.type_name => {}
+            Some(a) => bail!(
+                "block {}: recorded {}, flowgraph has {}",
+                b.id,
+                b.type_name,
+                a.type_name
+            ),
+            None => bail!("block {} ({}) does not exist", b.id, b.type_name),
+        }
+    }
+    Ok(())
+}
+
+fn same_result(
+    recorded: &Result<Option<Pmt>, String>,
+    actual: &Result<Option<Pmt>, String>,
+) -> bool {
+    match (recorded, actual) {
+        (Ok(None), Ok(_)) => true,
+        // compare serialized Pmts, since PartialEq does not compare maps and vectors of Pmts
+        (Ok(Some(r)), Ok(Some(a))) => serde_json::to_value(r).ok() == serde_json::to_value(a).ok(),
+        (Err(r), Err(a)) => r == a,
+        _ => false,
+    }
+}
+
+/// Result of a [SessionReplay].
+#[derive(Debug, Clone, Default)]
+pub struct ReplayReport {
+    /// Number of replayed events.
+    pub replayed: usize,
+    /// Number of events that were skipped, since their data was not recorded.
+    pub skipped: usize,
+    /// Events with results that differ from the recorded ones.
+    pub mismatches: Vec<ReplayMismatch>,
+}
+
+impl ReplayReport {
+    /// Check if all results matched the recorded ones.
+    pub fn is_ok(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+impl fmt::Display for ReplayReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "replayed {} events, skipped {}, {} mismatches",
+            self.replayed,
+            self.skipped,
+            self.mismatches.len()
+        )?;
+        for m in self.mismatches.iter() {
+            write!(f, "\n  {}", m)?;
+        }
+        Ok(())
+    }
+}
+
+/// Event of a [SessionReplay] with a result that differs from the recorded one.
+#[derive(Debug, Clone)]
+pub struct ReplayMismatch {
+    /// Index of the event in the session.
+    pub event: usize,
+    /// Replayed action.
+    pub action: String,
+    /// Recorded result.
+    pub expected: String,
+    /// Result of the replay.
+    pub actual: String,
+}
+
+impl fmt::Display for ReplayMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "event {}, {}: expected {}, got {}",
+            self.event, self.action, self.expected, self.actual
+        )
+    }
+}
@@ -1,11 +1,13 @@
 //! Stream-based Ports
-use futures::channel::mpsc::Sender;
+use crate::runtime::channel::Sender;
 use std::any::Any;
 use std::any::TypeId;
 use std::fmt;
 use std::mem;
 use std::slice;
 
+use crate::anyhow::{Context, Result};
+use crate::runtime::buffer::slab;
 use crate::runtime::buffer::BufferReader;
 use crate::runtime::buffer::BufferWriter;
 use crate::runtime::tag::default_tag_propagation;
@@ -29,6 +31,8 @@ pub struct StreamInput {
     reader: Option<BufferReader>,
     current: Option<CurrentInput>,
     tags: Vec<ItemTag>,
+    min_items: usize,
+    multiple: usize,
 }
 
 unsafe impl Send for StreamInput {}
@@ -42,6 +46,8 @@ impl StreamInput {
             reader: None,
             current: None,
             tags: Vec::new(),
+            min_items: 0,
+            multiple: 1,
         }
     }
 
@@ -61,12 +67,70 @@ impl StreamInput {
         self.reader.as_mut().unwrap().try_as::<T>()
     }
 
+    /// Only call `work()` once at least `n` items are available or the input is finished.
+    ///
+    /// By default, `work()` is called whenever the upstream block produced items. The buffer of
+    /// the input has to be large enough to hold `n` items. This is checked after `init()`, failing
+    /// the block instead of stalling the flowgraph, so the requirement should be set before.
+    pub fn set_min_items(&mut self, n: usize) {
+        self.min_items = n;
+    }
+
+    pub fn min_items(&self) -> usize {
+        self.min_items
+    }
+
+    /// Only expose a multiple of `n` items through [slice](Self::slice).
+    ///
+    /// This also defers `work()` until at least `n` items are available. Remaining items,
+    /// less than `n`, are not exposed, when the input is finished. Like for
+    /// [set_min_items](Self::set_min_items), the buffer is checked after `init()`. Slab buffers
+    /// reserve more items, if needed to carry over remainders, so any multiple that fits into a
+    /// slab works with the default configuration.
+    pub fn set_item_multiple(&mut self, n: usize) {
+        assert!(n > 0, "item multiple has to be positive");
+        self.multiple = n;
+    }
+
+    pub fn item_multiple(&self) -> usize {
+        self.multiple
+    }
+
+    /// Check that the buffer can satisfy the minimum item count and item multiple.
+    pub(crate) fn check_buffer(&mut self) -> Result<()> {
+        match self.reader.as_mut() {
+            Some(r) => r
+                .check_min_items(self.min_items, self.multiple)
+                .with_context(|| format!("stream input {}", self.name)),
+            None => Ok(()),
+        }
+    }
+
+    /// Whether enough items are available to call `work()`.
+    pub(crate) fn ready(&mut self) -> bool {
+        let min = if self.multiple > 1 {
+            self.min_items.max(self.multiple)
+        } else {
+            self.min_items
+        };
+        if min <= 1 || self.reader.is_none() || self.finished() {
+            return true;
+        }
+
+        // querying the buffer might dequeue the last items of a finished input
+        let ready = self.slice_unchecked::<u8>().len() / self.item_size >= min || self.finished();
+        if !ready {
+            // query the buffer again before the next call
+            self.current = None;
+        }
+        ready
+    }
+
     pub fn consume(&mut self, amount: usize) {
         debug_assert!(self.current.is_some());
         debug_assert!(
-            amount
-                <= self.current.as_mut().unwrap().len
-                    - self.current.as_mut().unwrap().index * self.item_size
+            amount * self.item_size
+                <= self.current.as_ref().unwrap().len - self.current.as_ref().unwrap().index
         );
 
         self.current.as_mut().unwrap().index += amount * self.item_size;
@@ -80,6 +144,15 @@ impl StreamInput {
     }
 
     pub fn slice_unchecked<T>(&mut self) -> &'static [T] {
+        let (ptr, len) = self.bytes();
+        if ptr.is_null() {
+            return &[];
+        }
+        unsafe { slice::from_raw_parts(ptr as *const T, len / mem::size_of::<T>()) }
+    }
+
+    /// Start and length in bytes of the items exposed through [slice](Self::slice).
+    fn bytes(&mut self) -> (*const u8, usize) {
         if self.current.is_none() {
             let (ptr, len, tags) = self.reader.as_mut().unwrap().bytes();
             self.tags = tags;
@@ -93,7 +166,8 @@ impl StreamInput {
         }
 
         let c = self.current.as_ref().unwrap();
-        unsafe { slice::from_raw_parts(c.ptr as *const T, c.len / mem::size_of::<T>()) }
+        let chunk = self.item_size * self.multiple;
+        (c.ptr, c.len / chunk * chunk)
     }
 
     /// Returns a mutable slice to the input buffer.
@@ -162,6 +236,7 @@ pub struct StreamOutput {
     writer: Option<BufferWriter>,
     tags: Vec<ItemTag>,
     offset: usize,
+    multiple: usize,
 }
 
 impl StreamOutput {
@@ -173,6 +248,7 @@ impl StreamOutput {
             writer: None,
             tags: Vec::new(),
             offset: 0,
+            multiple: 1,
         }
     }
 
@@ -193,6 +269,30 @@ impl StreamOutput {
         self.writer = Some(writer);
     }
 
+    /// Only produce multiples of `n` items.
+    ///
+    /// Slab buffers are handed downstream once they are full, so they only expose space for
+    /// multiples of `n` items. Other buffers are drained eventually and ignore this. Like for
+    /// [StreamInput::set_item_multiple], the buffer is checked after `init()`.
+    pub fn set_item_multiple(&mut self, n: usize) {
+        assert!(n > 0, "item multiple has to be positive");
+        self.multiple = n;
+    }
+
+    pub fn item_multiple(&self) -> usize {
+        self.multiple
+    }
+
+    /// Check that the buffer can satisfy the item multiple.
+    pub(crate) fn check_buffer(&mut self) -> Result<()> {
+        match self.writer.as_mut() {
+            Some(w) if self.multiple > 1 => w
+                .set_item_multiple(self.multiple)
+                .with_context(|| format!("stream output {}", self.name)),
+            _ => Ok(()),
+        }
+    }
+
     pub fn add_tag(&mut self, index: usize, tag: Tag) {
         self.tags.push(ItemTag {
             index: index + self.offset,
@@ -230,14 +330,23 @@ impl StreamOutput {
     }
 
     pub fn slice_unchecked<T>(&mut self) -> &'static mut [T] {
-        let (ptr, len) = self.writer.as_mut().unwrap().bytes();
+        let (ptr, len) = self.bytes();
+        if ptr.is_null() {
+            return &mut [];
+        }
+
+        unsafe { slice::from_raw_parts_mut(ptr.cast::<T>(), len / mem::size_of::<T>()) }
+    }
 
-        unsafe {
-            slice::from_raw_parts_mut(
-                ptr.cast::<T>().add(self.offset),
-                (len / mem::size_of::<T>()) - self.offset,
-            )
+    /// Start and length in bytes of the space exposed through [slice](Self::slice).
+    fn bytes(&mut self) -> (*mut u8, usize) {
+        let (ptr, len) = self.writer.as_mut().unwrap().bytes();
+        if ptr.is_null() {
+            return (ptr, 0);
         }
+
+        let offset = self.offset * self.item_size;
+        unsafe { (ptr.add(offset), len - offset) }
     }
 
     fn commit(&mut self) {
@@ -257,6 +366,10 @@ impl StreamOutput {
         self.offset
     }
 
+    pub fn flush(&mut self) {
+        self.writer.as_mut().unwrap().flush();
+    }
+
     pub async fn notify_finished(&mut self) {
         self.writer.as_mut().unwrap().notify_finished().await;
     }
@@ -369,6 +482,73 @@ impl StreamIo {
             .map(|(i, _)| i)
     }
 
+    /// Whether all inputs have enough items to call `work()`.
+    pub(crate) fn ready(&mut self) -> bool {
+        self.inputs.iter_mut().all(|i| i.ready())
+    }
+
+    /// Check that the buffers of all inputs and outputs can satisfy their minimum item count and
+    /// item multiple, which would otherwise deadlock.
+    pub(crate) fn check_buffers(&mut self) -> Result<()> {
+        self.inputs.iter_mut().try_for_each(|i| i.check_buffer())?;
+        self.outputs.iter_mut().try_for_each(|o| o.check_buffer())
+    }
+
+    /// Hand the next upstream buffer to the output of a 1:1 block, if possible.
+    ///
+    /// Only supported for a single input and output with [Slab](slab::Slab) buffers of the same item size.
+    pub(crate) fn forward_in_place(&mut self) -> bool {
+        if self.inputs.len() != 1 || self.outputs.len() != 1 {
+            return false;
+        }
+        let input = &mut self.inputs[0];
+        let output = &mut self.outputs[0];
+        if input.item_size != output.item_size || input.current.is_some() || output.offset != 0 {
+            return false;
+        }
+
+        match (
+            input.try_as::<slab::Reader>(),
+            output.try_as::<slab::Writer>(),
+        ) {
+            (Some(r), Some(w)) => slab::forward_in_place(r, w),
+            _ => false,
+        }
+    }
+
+    /// Items of the input of an in-place block, if its buffer was forwarded to the output.
+    ///
+    /// Input and output then share the same memory, so [StreamInput::slice] and
+    /// [StreamOutput::slice] would alias. Instead, the block reads and overwrites the items
+    /// through the returned slice and consumes and produces the same number of items. The output
+    /// has the same item size but may have a different type than `T`.
+    ///
+    /// Returns `None`, if the buffers are not shared, e.g., for other buffer types or if no
+    /// downstream buffer was free.
+    pub fn in_place_slice<T>(&mut self) -> Option<&'static mut [T]> {
+        if self.inputs.len() != 1 || self.outputs.len() != 1 {
+            return None;
+        }
+        let input = &mut self.inputs[0];
+        assert_eq!(input.type_id, TypeId::of::<T>());
+        if input.item_size != self.outputs[0].item_size {
+            return None;
+        }
+
+        let (i_ptr, i_len) = input.bytes();
+        if i_ptr.is_null() {
+            return None;
+        }
+        let index = input.current.as_ref().unwrap().index;
+        let (o_ptr, o_len) = self.outputs[0].bytes();
+        if !std::ptr::eq(unsafe { i_ptr.add(index) }, o_ptr) {
+            return None;
+        }
+
+        let len = i_len.saturating_sub(index).min(o_len) / mem::size_of::<T>();
+        Some(unsafe { slice::from_raw_parts_mut(o_ptr.cast::<T>(), len) })
+    }
+
     pub fn commmit(&mut self) {
         (self.tag_propagation)(&mut self.inputs, &mut self.outputs);
         for i in self.inputs_mut() {
@@ -379,6 +559,13 @@ impl StreamIo {
         }
     }
 
+    /// Send deferred notifications of all outputs.
+    pub fn flush(&mut self) {
+        for o in self.outputs_mut() {
+            o.flush();
+        }
+    }
+
     #[allow(clippy::type_complexity)]
     pub fn set_tag_propagation(
         &mut self,
@@ -450,4 +637,68 @@ mod tests {
         assert_eq!(o.name(), "foo");
         assert_eq!(o.item_size(), 4);
     }
+
+    // Also run under Miri in CI, which checks that the forwarded buffer is never accessed through
+    // aliasing references.
+    #[test]
+    fn in_place() {
+        use crate::runtime::buffer::memory::Allocator;
+        use crate::runtime::buffer::slab::Slab;
+        use crate::runtime::buffer::BufferBuilder;
+        use crate::runtime::channel::channel;
+
+        let (tx, _rx) = channel::<BlockMessage>(16);
+        let slab = Slab::with_config(64, 2, 4).with_allocator(Allocator::Aligned(64));
+        let mut upstream = slab.build(4, tx.clone(), 0);
+        let mut sio = StreamIoBuilder::new()
+            .add_input::<f32>("in")
+            .add_output::<u32>("out")
+            .build();
+        sio.input(0).set_reader(upstream.add_reader(tx.clone(), 0));
+        sio.output(0).init(slab.build(4, tx.clone(), 0));
+        let mut downstream = sio.output(0).add_reader(tx, 0);
+
+        // not forwarded yet
+        assert!(sio.in_place_slice::<f32>().is_none());
+        sio.commmit();
+
+        let (ptr, len) = upstream.bytes();
+        assert_eq!(len, 12 * 4);
+        let items = unsafe { slice::from_raw_parts_mut(ptr as *mut f32, 12) };
+        for (i, x) in items.iter_mut().enumerate() {
+            *x = i as f32;
+        }
+        upstream.produce(12, Vec::new());
+
+        assert!(sio.forward_in_place());
+        let s = sio.in_place_slice::<f32>().unwrap();
+        assert_eq!(s.len(), 12);
+        let p = s.as_mut_ptr();
+        for k in 0..5 {
+            unsafe {
+                let r = (*p.add(k) * 2.0).to_bits();
+                p.add(k).cast::<u32>().write(r);
+            }
+        }
+        sio.input(0).consume(5);
+        sio.output(0).produce(5);
+
+        // input and output stay in sync
+        let s = sio.in_place_slice::<f32>().unwrap();
+        assert_eq!(s.len(), 7);
+        for x in s.iter_mut() {
+            // same bits as the u32 output
+            *x *= 2.0;
+        }
+        sio.input(0).consume(7);
+        sio.output(0).produce(7);
+        sio.commmit();
+
+        let (ptr, len, _) = downstream.bytes();
+        assert_eq!(len, 12 * 4);
+        let out = unsafe { slice::from_raw_parts(ptr as *const u32, 12) };
+        for (i, x) in out.iter().enumerate() {
+            assert_eq!(f32::from_bits(*x), i as f32 * 2.0);
+        }
+    }
 }
@@ -0,0 +1,244 @@
+//! Telemetry
+//!
+//! Periodically pushes runtime stats and user-registered gauges to a time series database (e.g.,
+//! InfluxDB or VictoriaMetrics) using the [line
+//! protocol](https://docs.influxdata.com/influxdb/v1.8/write_protocols/line_protocol_reference/).
+//!
+//! Telemetry is enabled by setting `telemetry_url` in the config (or the `FUTURESDR_TELEMETRY_URL`
+//! environment variable), e.g., `http://127.0.0.1:8086/write?db=futuresdr`. The interval (in ms)
+//! is set with `telemetry_interval` (default 10000) and additional tags, attached to all
+//! measurements, with `telemetry_tags` (e.g., `host=rx1,site=roof`).
+//!
+//! For every flowgraph, the `futuresdr_flowgraph` measurement reports the number of running,
+//! finished, and failed blocks. Gauges are reported with their name as measurement. Scalar gauges
+//! have a single `value` field, [Pmt::MapStrPmt] gauges map entries to fields. Integers are
+//! written as signed 64-bit fields, i.e., large [Pmt::U64] values are clamped, and non-finite
+//! floats are skipped.
+//!
+//! ```no_run
+//! use futuresdr::runtime::telemetry;
+//! use futuresdr::runtime::Pmt;
+//!
+//! telemetry::gauge("snr", Pmt::F32(12.3));
+//! ```
+use async_io::block_on;
+use async_io::Timer;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use crate::anyhow::{bail, Result};
+use crate::runtime::config;
+use crate::runtime::remote;
+use crate::runtime::FlowgraphHandle;
+use crate::runtime::FlowgraphStats;
+use crate::runtime::Pmt;
+
+static GAUGES: Lazy<Mutex<HashMap<String, Pmt>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static FLOWGRAPHS: Lazy<Mutex<Vec<(String, FlowgraphHandle)>>> =
+    Lazy::new(|| Mutex::new(Vec::new()));
+static STARTED: Lazy<bool> = Lazy::new(start);
+
+/// Set the value of a gauge, reported with the next telemetry update.
+pub fn gauge(name: impl Into<String>, value: Pmt) {
+    Lazy::force(&STARTED);
+    GAUGES.lock().unwrap().insert(name.into(), value);
+}
+
+/// Remove a gauge.
+pub fn remove_gauge(name: &str) {
+    GAUGES.lock().unwrap().remove(name);
+}
+
+pub(crate) fn add_flowgraph(name: String, handle: FlowgraphHandle) {
+    // without the telemetry thread, the handle would never be removed
+    if !*STARTED {
+        return;
+    }
+    FLOWGRAPHS.lock().unwrap().push((name, handle));
+}
+
+/// Start the telemetry thread, if telemetry is enabled.
+fn start() -> bool {
+    let url = match config::get::<String>("telemetry_url") {
+        Some(url) => url,
+        None => return false,
+    };
+    let url = url.strip_prefix("http://").unwrap_or(&url);
+    let (addr, path) = match url.find('/') {
+        Some(i) => (url[..i].to_string(), url[i..].to_string()),
+        None => (url.to_string(), "/".to_string()),
+    };
+    let interval = Duration::from_millis(config::get_or_default("telemetry_interval", 10000u64));
+    let tags = config::get::<String>("telemetry_tags")
+        .map(|t| format!(",{}", t.trim_matches(',')))
+        .unwrap_or_default();
+
+    std::thread::Builder::new()
+        .name("telemetry".to_string())
+        .spawn(move || {
+            block_on(async move {
+                loop {
+                    Timer::after(interval).await;
+                    let lines = collect(&tags).await;
+                    if lines.is_empty() {
+                        continue;
+                    }
+                    if let Err(e) = push(&addr, &path, lines).await {
+                        warn!("telemetry: push to {} failed ({:?})", addr, e);
+                    }
+                }
+            })
+        })
+        .unwrap();
+    true
+}
+
+async fn collect(tags: &str) -> String {
+    let ts = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let mut lines = String::new();
+
+    let flowgraphs = FLOWGRAPHS.lock().unwrap().clone();
+    let mut terminated = Vec::new();
+    for (i, (name, mut handle)) in flowgraphs.into_iter().enumerate() {
+        match handle.stats().await {
+            Ok(stats) => lines.push_str(&flowgraph_line(&name, &stats, tags, ts)),
+            Err(_) => terminated.push(i),
+        }
+    }
+    // flowgraphs are only removed here, i.e., indices are stable
+    let mut i = 0;
+    FLOWGRAPHS.lock().unwrap().retain(|_| {
+        i += 1;
+        !terminated.contains(&(i - 1))
+    });
+
+    for (name, value) in GAUGES.lock().unwrap().iter() {
+        if let Some(l) = gauge_line(name, value, tags, ts) {
+            lines.push_str(&l);
+        }
+    }
+
+    lines
+}
+
+async fn push(addr: &str, path: &str, lines: String) -> Result<()> {
+    let reader = remote::send(addr, "POST", path, "text/plain", Some(lines)).await?;
+    let res = remote::response(reader).await?;
+    if !(200..300).contains(&res.status) {
+        bail!("server returned status {}", res.status);
+    }
+    Ok(())
+}
+
+fn flowgraph_line(name: &str, stats: &FlowgraphStats, tags: &str, ts: u128) -> String {
+    format!(
+        "futuresdr_flowgraph,fg={}{} running={}i,done={}i,error={}i {}\n",
+        escape(name),
+        tags,
+        stats.running,
+        stats.done,
+        stats.error,
+        ts
+    )
+}
+
+fn gauge_line(name: &str, value: &Pmt, tags: &str, ts: u128) -> Option<String> {
+    let fields = match value {
+        Pmt::MapStrPmt(m) => {
+            let mut fields: Vec<String> = m
+                .iter()
+                .filter_map(|(k, v)| field(v).map(|v| format!("{}={}", escape(k), v)))
+                .collect();
+            fields.sort();
+            fields.join(",")
+        }
+        v => field(v).map(|v| format!("value={}", v))?,
+    };
+    if fields.is_empty() {
+        debug!("telemetry: gauge {} has no numeric or string value", name);
+        return None;
+    }
+    Some(format!("{}{} {} {}\n", escape(name), tags, fields, ts))
+}
+
+fn field(p: &Pmt) -> Option<String> {
+    match p {
+        Pmt::U32(v) => Some(format!("{}i", v)),
+        // unsigned integers are not supported by InfluxDB 1.x
+        Pmt::U64(v) => Some(format!("{}i", i64::try_from(*v).unwrap_or(i64::MAX))),
+        Pmt::I32(v) => Some(format!("{}i", v)),
+        Pmt::I64(v) => Some(format!("{}i", v)),
+        Pmt::F32(v) if v.is_finite() => Some(format!("{:?}", v)),
+        Pmt::F64(v) if v.is_finite() => Some(format!("{:?}", v)),
+        Pmt::String(s) => Some(format!(
+            "\"{}\"",
+            s.replace('\\', "\\\\").replace('"', "\\\"")
+        )),
+        _ => None,
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace(',', "\\,")
+        .replace(' ', "\\ ")
+        .replace('=', "\\=")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_protocol() {
+        assert_eq!(
+            gauge_line("snr", &Pmt::F32(12.5), ",host=rx1", 42).unwrap(),
+            "snr,host=rx1 value=12.5 42\n"
+        );
+        assert_eq!(
+            gauge_line("my gauge", &Pmt::U64(3), "", 1).unwrap(),
+            "my\\ gauge value=3i 1\n"
+        );
+        let mut m = HashMap::new();
+        m.insert("snr".to_string(), Pmt::F64(1.0));
+        m.insert("mode".to_string(), Pmt::String("a\"b".to_string()));
+        m.insert("blob".to_string(), Pmt::Blob(vec![1]));
+        assert_eq!(
+            gauge_line("rx", &Pmt::MapStrPmt(m), "", 1).unwrap(),
+            "rx mode=\"a\\\"b\",snr=1.0 1\n"
+        );
+        assert!(gauge_line("x", &Pmt::Null, "", 1).is_none());
+    }
+
+    #[test]
+    fn out_of_range() {
+        assert_eq!(
+            gauge_line("x", &Pmt::U64(u64::MAX), "", 1).unwrap(),
+            format!("x value={}i 1\n", i64::MAX)
+        );
+        assert!(gauge_line("x", &Pmt::F32(f32::NAN), "", 1).is_none());
+        assert!(gauge_line("x", &Pmt::F64(f64::INFINITY), "", 1).is_none());
+        let mut m = HashMap::new();
+        m.insert("snr".to_string(), Pmt::F64(f64::NAN));
+        m.insert("gain".to_string(), Pmt::F64(3.0));
+        assert_eq!(
+            gauge_line("rx", &Pmt::MapStrPmt(m), "", 1).unwrap(),
+            "rx gain=3.0 1\n"
+        );
+    }
+
+    #[test]
+    fn disabled() {
+        // the test environment does not set `telemetry_url`
+        assert!(!*STARTED);
+        let (tx, _rx) = crate::runtime::channel::channel(1);
+        add_flowgraph("fg".to_string(), FlowgraphHandle::new(tx));
+        assert!(FLOWGRAPHS.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,205 @@
+//! Test Support
+//!
+//! Utilities to check that kernels do not depend on how their input is split into chunks, how
+//! much output space they get, or when messages arrive. A [Schedule] defines these conditions
+//! and [Mocker::run_schedule] drives a kernel accordingly. [check_chunking] compares the output
+//! of a kernel that runs with a schedule to the output of a kernel that gets all input at once.
+//!
+//! With the `proptest` feature, [strategy] generates random schedules for property-based tests.
+//!
+//! The [golden] module compares the output of blocks to stored golden files.
+//!
+//! ```
+//! use futuresdr::blocks::Apply;
+//! use futuresdr::runtime::testing::check_chunking;
+//! use futuresdr::runtime::testing::Schedule;
+//!
+//! let schedule = Schedule::chunks(&[1, 7, 0, 100]);
+//! check_chunking::<u32, u32>(
+//!     || Apply::new(|x: &u32| x + 1),
+//!     (0..1000).collect(),
+//!     &schedule,
+//!     0.0,
+//! )
+//! .unwrap();
+//! ```
+#[cfg(not(target_arch = "wasm32"))]
+use std::fmt::Debug;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod golden;
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::anyhow::{bail, Result};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::blocks::ApproxEq;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::runtime::Block;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::runtime::Mocker;
+use crate::runtime::Pmt;
+use crate::runtime::PortId;
+
+/// Step of a [Schedule].
+#[derive(Debug, Clone)]
+pub struct Step {
+    /// Number of items that become available on each input.
+    pub items: usize,
+    /// Output space in items, i.e., the maximum number of items that can be produced per
+    /// `work()` call (at most 65536).
+    pub space: usize,
+    /// Message that is passed to a handler before calling `work()`.
+    pub message: Option<(PortId, Pmt)>,
+}
+
+/// Sequence of input chunks, output space, and messages to drive a kernel with a [Mocker].
+#[derive(Debug, Clone, Default)]
+pub struct Schedule {
+    steps: Vec<Step>,
+}
+
+impl Schedule {
+    pub fn new() -> Schedule {
+        Schedule::default()
+    }
+
+    /// Split the input in chunks of the given sizes, without limiting the output space.
+    pub fn chunks(chunks: &[usize]) -> Schedule {
+        chunks
+            .iter()
+            .fold(Schedule::new(), |s, c| s.step(*c, usize::MAX))
+    }
+
+    /// Make `items` more items available and limit the output to `space` items.
+    #[must_use]
+    pub fn step(mut self, items: usize, space: usize) -> Schedule {
+        self.steps.push(Step {
+            items,
+            space,
+            message: None,
+        });
+        self
+    }
+
+    /// Pass a message to the handler, before the next `work()` call.
+    #[must_use]
+    pub fn message(mut self, port: impl Into<PortId>, p: Pmt) -> Schedule {
+        self.steps.push(Step {
+            items: 0,
+            space: usize::MAX,
+            message: Some((port.into(), p)),
+        });
+        self
+    }
+
+    pub fn steps(&self) -> &Vec<Step> {
+        &self.steps
+    }
+}
+
+impl From<Vec<Step>> for Schedule {
+    fn from(steps: Vec<Step>) -> Self {
+        Schedule { steps }
+    }
+}
+
+/// Run a kernel with one input and one output, following the schedule, and return its output.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn run_schedule<I, O>(block: Block, input: Vec<I>, schedule: &Schedule) -> Result<Vec<O>>
+where
+    I: Debug + Send + 'static,
+    O: Debug + Send + 'static,
+{
+    let mut mocker = Mocker::new(block);
+    mocker.input(0, input);
+    mocker.init_output::<O>(0, 0);
+    mocker.run_schedule(schedule)?;
+    Ok(mocker.output::<O>(0))
+}
+
+/// Check that the output of a kernel does not depend on the schedule.
+///
+/// Runs a kernel, created with `block`, once with all input available at once and once
+/// following the schedule. Returns an error with the index of the first sample that deviates
+/// by more than `tolerance`, or if the number of output samples differs.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn check_chunking<I, O>(
+    block: impl Fn() -> Block,
+    input: Vec<I>,
+    schedule: &Schedule,
+    tolerance: f64,
+) -> Result<()>
+where
+    I: Clone + Debug + Send + 'static,
+    O: ApproxEq + Send + 'static,
+{
+    let expected = run_schedule::<I, O>(block(), input.clone(), &Schedule::new())?;
+    let output = run_schedule::<I, O>(block(), input, schedule)?;
+
+    for (i, (o, e)) in output.iter().zip(expected.iter()).enumerate() {
+        if !o.approx_eq(e, tolerance) {
+            bail!(
+                "sample {} is {:?}, expected {:?} (tolerance {})",
+                i,
+                o,
+                e,
+                tolerance
+            );
+        }
+    }
+    if output.len() != expected.len() {
+        bail!("got {} samples, expected {}", output.len(), expected.len());
+    }
+
+    Ok(())
+}
+
+/// Strategy for random schedules with chunks and output space of at most `max_chunk` items.
+#[cfg(feature = "proptest")]
+#[cfg_attr(docsrs, doc(cfg(feature = "proptest")))]
+pub fn strategy(max_chunk: usize) -> impl proptest::strategy::Strategy<Value = Schedule> {
+    strategy_with_messages(max_chunk, Vec::new())
+}
+
+/// Strategy for random schedules that pass the given messages at random points.
+///
+/// The messages are passed in order, interleaved with chunks and output space of at most
+/// `max_chunk` items.
+#[cfg(feature = "proptest")]
+#[cfg_attr(docsrs, doc(cfg(feature = "proptest")))]
+pub fn strategy_with_messages(
+    max_chunk: usize,
+    messages: Vec<(PortId, Pmt)>,
+) -> impl proptest::strategy::Strategy<Value = Schedule> {
+    use proptest::prelude::*;
+
+    let max_chunk = max_chunk.max(1);
+    let n_messages = messages.len();
+    (
+        prop::collection::vec((0..=max_chunk, 1..=max_chunk), 1..64),
+        prop::collection::vec(any::<prop::sample::Index>(), n_messages),
+    )
+        .prop_map(move |(chunks, mut positions)| {
+            let mut steps: Vec<Step> = chunks
+                .into_iter()
+                .map(|(items, space)| Step {
+                    items,
+                    space,
+                    message: None,
+                })
+                .collect();
+            let n_steps = steps.len();
+            positions.sort_by_key(|p| p.index(n_steps));
+            for (m, p) in messages.iter().zip(positions.iter()).rev() {
+                steps.insert(
+                    p.index(n_steps),
+                    Step {
+                        items: 0,
+                        space: usize::MAX,
+                        message: Some(m.clone()),
+                    },
+                );
+            }
+            Schedule::from(steps)
+        })
+}
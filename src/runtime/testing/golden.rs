@@ -0,0 +1,247 @@
+//! Golden-File Regression Tests
+//!
+//! Run a block or a chain of blocks on a recorded input capture and compare the output to a
+//! stored, known-good golden file. This validates refactorings of filters or demodulators
+//! against previous results.
+//!
+//! Captures and golden files are raw samples in native byte order, i.e., the format of the
+//! [FileSource](crate::blocks::FileSource) and [FileSink](crate::blocks::FileSink).
+//!
+//! If the output deviates, the test fails with a summary of the deviations and the output is
+//! written next to the golden file with the extension `.actual` for inspection. If the change is
+//! intended, re-baseline the golden files by running the tests with the `FUTURESDR_BLESS`
+//! environment variable set:
+//! ```sh
+//! FUTURESDR_BLESS=1 cargo test --test my_golden_tests
+//! ```
+//!
+//! ```no_run
+//! use futuresdr::blocks::FirBuilder;
+//! use futuresdr::blocks::QuadratureDemod;
+//! use futuresdr::num_complex::Complex32;
+//! use futuresdr::runtime::testing::golden::GoldenTest;
+//!
+//! GoldenTest::new("tests/data/capture.cf32", "tests/golden/demod.f32")
+//!     .tolerance(1e-5)
+//!     .run::<Complex32, f32>(|fg| {
+//!         let fir = fg.add_block(FirBuilder::new::<Complex32, Complex32, f32, _>(vec![0.5, 0.5]));
+//!         let demod = fg.add_block(QuadratureDemod::new(1.0));
+//!         fg.connect_stream(fir, "out", demod, "in")?;
+//!         Ok((fir, demod))
+//!     })
+//!     .unwrap();
+//! ```
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::anyhow::{bail, Context, Result};
+use crate::blocks::ApproxEq;
+use crate::blocks::VectorSink;
+use crate::blocks::VectorSinkBuilder;
+use crate::blocks::VectorSource;
+use crate::runtime::Block;
+use crate::runtime::Flowgraph;
+use crate::runtime::Runtime;
+
+/// Environment variable to re-baseline golden files instead of comparing against them.
+pub const BLESS_ENV: &str = "FUTURESDR_BLESS";
+
+/// Check if golden files should be re-baselined, i.e., if [BLESS_ENV] is set to a value other
+/// than `0`.
+pub fn bless() -> bool {
+    std::env::var(BLESS_ENV).map_or(false, |v| !v.is_empty() && v != "0")
+}
+
+/// Read raw samples from a file.
+pub fn read_samples<T: Copy>(path: impl AsRef<Path>) -> Result<Vec<T>> {
+    let path = path.as_ref();
+    let bytes = fs::read(path).with_context(|| format!("cannot read {}", path.display()))?;
+    let item_size = std::mem::size_of::<T>();
+    if bytes.len() % item_size != 0 {
+        bail!(
+            "{}: size ({} bytes) is not a multiple of the item size ({} bytes)",
+            path.display(),
+            bytes.len(),
+            item_size
+        );
+    }
+
+    let n = bytes.len() / item_size;
+    let mut v = Vec::<T>::with_capacity(n);
+    unsafe {
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), v.as_mut_ptr() as *mut u8, bytes.len());
+        v.set_len(n);
+    }
+    Ok(v)
+}
+
+/// Write raw samples to a file, creating parent directories as needed.
+pub fn write_samples<T: Copy>(path: impl AsRef<Path>, samples: &[T]) -> Result<()> {
+    let path = path.as_ref();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("cannot create {}", dir.display()))?;
+    }
+    let bytes = unsafe {
+        std::slice::from_raw_parts(
+            samples.as_ptr() as *const u8,
+            std::mem::size_of_val(samples),
+        )
+    };
+    fs::write(path, bytes).with_context(|| format!("cannot write {}", path.display()))
+}
+
+/// Compare samples to a golden file or, if [bless] is true, re-baseline the golden file.
+pub fn check_golden<T: ApproxEq + Copy>(
+    path: impl AsRef<Path>,
+    output: &[T],
+    tolerance: f64,
+) -> Result<()> {
+    compare_or_bless(path.as_ref(), output, tolerance, bless())
+}
+
+fn compare_or_bless<T: ApproxEq + Copy>(
+    path: &Path,
+    output: &[T],
+    tolerance: f64,
+    bless: bool,
+) -> Result<()> {
+    if bless {
+        info!(
+            "re-baselining {} ({} samples)",
+            path.display(),
+            output.len()
+        );
+        return write_samples(path, output);
+    }
+
+    if !path.exists() {
+        bail!(
+            "golden file {} does not exist, run with {}=1 to create it",
+            path.display(),
+            BLESS_ENV
+        );
+    }
+    let expected = read_samples::<T>(path)?;
+
+    let mut mismatches = 0;
+    let mut first = None;
+    let mut max_error = 0.0f64;
+    for (i, (o, e)) in output.iter().zip(expected.iter()).enumerate() {
+        if !o.approx_eq(e, tolerance) {
+            mismatches += 1;
+            first.get_or_insert(i);
+        }
+        max_error = max_error.max(o.distance(e));
+    }
+
+    if mismatches == 0 && output.len() == expected.len() {
+        return Ok(());
+    }
+
+    let mut actual = path.as_os_str().to_owned();
+    actual.push(".actual");
+    let actual = PathBuf::from(actual);
+    write_samples(&actual, output)?;
+
+    let mut msg = format!("golden file {}:", path.display());
+    if output.len() != expected.len() {
+        msg += &format!(
+            " got {} samples, expected {};",
+            output.len(),
+            expected.len()
+        );
+    }
+    if let Some(i) = first {
+        msg += &format!(
+            " {} samples differ by more than {} (first at index {}: {:?}, expected {:?}; max error {});",
+            mismatches, tolerance, i, output[i], expected[i], max_error
+        );
+    }
+    bail!(
+        "{} output written to {}, run with {}=1 to re-baseline",
+        msg,
+        actual.display(),
+        BLESS_ENV
+    )
+}
+
+/// Golden-file test of a block or a chain of blocks.
+///
+/// Feeds the samples of the input capture into the `in` port of the first block and compares
+/// the samples of the `out` port of the last block to the golden file.
+#[derive(Debug, Clone)]
+pub struct GoldenTest {
+    input: PathBuf,
+    golden: PathBuf,
+    tolerance: f64,
+    bless: bool,
+}
+
+impl GoldenTest {
+    pub fn new(input: impl AsRef<Path>, golden: impl AsRef<Path>) -> GoldenTest {
+        GoldenTest {
+            input: input.as_ref().to_path_buf(),
+            golden: golden.as_ref().to_path_buf(),
+            tolerance: 0.0,
+            bless: bless(),
+        }
+    }
+
+    /// Absolute tolerance per sample (default: `0`).
+    #[must_use]
+    pub fn tolerance(mut self, tolerance: f64) -> GoldenTest {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Re-baseline the golden file instead of comparing against it.
+    ///
+    /// Defaults to [bless], i.e., to the [BLESS_ENV] environment variable.
+    #[must_use]
+    pub fn bless(mut self, bless: bool) -> GoldenTest {
+        self.bless = bless;
+        self
+    }
+
+    /// Run a single block.
+    pub fn run_block<I, O>(&self, block: Block) -> Result<()>
+    where
+        I: Copy + Send + 'static,
+        O: ApproxEq + Copy + Send + Sync + 'static,
+    {
+        self.run::<I, O>(|fg| {
+            let id = fg.add_block(block);
+            Ok((id, id))
+        })
+    }
+
+    /// Run a chain of blocks.
+    ///
+    /// `chain` adds and connects the blocks and returns the ids of the first and the last block.
+    pub fn run<I, O>(
+        &self,
+        chain: impl FnOnce(&mut Flowgraph) -> Result<(usize, usize)>,
+    ) -> Result<()>
+    where
+        I: Copy + Send + 'static,
+        O: ApproxEq + Copy + Send + Sync + 'static,
+    {
+        let input = read_samples::<I>(&self.input)?;
+
+        let mut fg = Flowgraph::new();
+        let src = fg.add_block(VectorSource::<I>::new(input));
+        let (first, last) = chain(&mut fg)?;
+        let snk = fg.add_block(VectorSinkBuilder::<O>::new().build());
+        fg.connect_stream(src, "out", first, "in")?;
+        fg.connect_stream(last, "out", snk, "in")?;
+
+        let fg = Runtime::new().run(fg)?;
+        let output = fg
+            .kernel::<VectorSink<O>>(snk)
+            .context("no vector sink")?
+            .items();
+
+        compare_or_bless(&self.golden, output, self.tolerance, self.bless)
+    }
+}
@@ -0,0 +1,786 @@
+//! GNU Radio ControlPort-compatible Thrift endpoint
+//!
+//! Implements the `ControlPort` service of GNU Radio's `gnuradio.thrift` (binary protocol,
+//! buffered transport) on top of the flowgraphs of the [ControlPort], allowing existing GNU Radio
+//! tools (e.g., `gr-ctrlport-monitor`) to inspect and control FutureSDR flowgraphs.
+//!
+//! The endpoint is enabled by setting `ctrlport_thrift_bind` in the config (e.g.,
+//! `127.0.0.1:9090`).
+//!
+//! Knobs are named `<alias>::<knob>`, where the alias is the instance name of the block. For
+//! flowgraphs other than the first one, aliases are prefixed with `fg<id>_`.
+//! - every block has a read-only `state` knob (`Running`, `Done`, or `Error`),
+//! - every message handler of a block is a write-only knob; setting it calls the handler,
+//! - the `flowgraph` alias reports the number of `running`, `done`, and `error` blocks.
+//!
+//! [ControlPort]: crate::runtime::ctrl_port::ControlPort
+use async_executor::Executor;
+use async_io::block_on;
+use async_net::{TcpListener, TcpStream};
+use futures::future::BoxFuture;
+use futures::io::{AsyncReadExt, AsyncWriteExt, BufReader};
+use futures::FutureExt;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use crate::anyhow::{anyhow, bail, Context, Result};
+use crate::runtime::ctrl_port;
+use crate::runtime::BlockState;
+use crate::runtime::FlowgraphHandle;
+use crate::runtime::Pmt;
+
+type Flowgraphs = Arc<Mutex<ctrl_port::Flowgraphs>>;
+
+const T_STOP: u8 = 0;
+const T_BOOL: u8 = 2;
+const T_BYTE: u8 = 3;
+const T_DOUBLE: u8 = 4;
+const T_I16: u8 = 6;
+const T_I32: u8 = 8;
+const T_I64: u8 = 10;
+const T_STRING: u8 = 11;
+const T_STRUCT: u8 = 12;
+const T_MAP: u8 = 13;
+const T_SET: u8 = 14;
+const T_LIST: u8 = 15;
+
+const MSG_CALL: i32 = 1;
+const MSG_REPLY: i32 = 2;
+const MSG_EXCEPTION: i32 = 3;
+const MSG_ONEWAY: i32 = 4;
+const VERSION_1: u32 = 0x8001_0000;
+
+const MAX_LEN: usize = 16 * 1024 * 1024;
+
+// gnuradio.thrift BaseTypes
+const BASE_BOOL: i32 = 0;
+const BASE_BYTE: i32 = 1;
+const BASE_SHORT: i32 = 2;
+const BASE_INT: i32 = 3;
+const BASE_LONG: i32 = 4;
+const BASE_DOUBLE: i32 = 5;
+const BASE_STRING: i32 = 6;
+const BASE_F32VECTOR: i32 = 8;
+const BASE_F64VECTOR: i32 = 9;
+const BASE_S64VECTOR: i32 = 10;
+const BASE_S8VECTOR: i32 = 13;
+
+// gnuradio.thrift KnobType
+const KNOB_DOUBLE: i32 = 4;
+const KNOB_STRING: i32 = 5;
+const KNOB_LONG: i32 = 6;
+
+// gnuradio.thrift display options
+const DISPNULL: i32 = 0x0000;
+const DISPTIME: i32 = 0x0001;
+const DISPOPTSTRIP: i32 = 0x0800;
+
+/// Thrift value in the binary protocol.
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Bool(bool),
+    Byte(i8),
+    Double(f64),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    Binary(Vec<u8>),
+    Struct(Vec<(i16, Value)>),
+    Map(u8, u8, Vec<(Value, Value)>),
+    List(u8, Vec<Value>),
+}
+
+impl Value {
+    fn ty(&self) -> u8 {
+        match self {
+            Value::Bool(_) => T_BOOL,
+            Value::Byte(_) => T_BYTE,
+            Value::Double(_) => T_DOUBLE,
+            Value::I16(_) => T_I16,
+            Value::I32(_) => T_I32,
+            Value::I64(_) => T_I64,
+            Value::Binary(_) => T_STRING,
+            Value::Struct(_) => T_STRUCT,
+            Value::Map(..) => T_MAP,
+            Value::List(..) => T_LIST,
+        }
+    }
+
+    fn string(s: impl Into<String>) -> Value {
+        Value::Binary(s.into().into_bytes())
+    }
+
+    fn field(&self, id: i16) -> Option<&Value> {
+        match self {
+            Value::Struct(f) => f.iter().find(|(i, _)| *i == id).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    fn as_string(&self) -> Option<String> {
+        match self {
+            Value::Binary(b) => String::from_utf8(b.clone()).ok(),
+            _ => None,
+        }
+    }
+
+    fn write(&self, out: &mut Vec<u8>) {
+        match self {
+            Value::Bool(v) => out.push(*v as u8),
+            Value::Byte(v) => out.push(*v as u8),
+            Value::Double(v) => out.extend_from_slice(&v.to_be_bytes()),
+            Value::I16(v) => out.extend_from_slice(&v.to_be_bytes()),
+            Value::I32(v) => out.extend_from_slice(&v.to_be_bytes()),
+            Value::I64(v) => out.extend_from_slice(&v.to_be_bytes()),
+            Value::Binary(v) => {
+                out.extend_from_slice(&(v.len() as i32).to_be_bytes());
+                out.extend_from_slice(v);
+            }
+            Value::Struct(fields) => {
+                for (id, v) in fields {
+                    out.push(v.ty());
+                    out.extend_from_slice(&id.to_be_bytes());
+                    v.write(out);
+                }
+                out.push(T_STOP);
+            }
+            Value::Map(k, v, entries) => {
+                out.push(*k);
+                out.push(*v);
+                out.extend_from_slice(&(entries.len() as i32).to_be_bytes());
+                for (k, v) in entries {
+                    k.write(out);
+                    v.write(out);
+                }
+            }
+            Value::List(t, items) => {
+                out.push(*t);
+                out.extend_from_slice(&(items.len() as i32).to_be_bytes());
+                for i in items {
+                    i.write(out);
+                }
+            }
+        }
+    }
+}
+
+async fn read_u8(r: &mut BufReader<TcpStream>) -> Result<u8> {
+    let mut b = [0; 1];
+    r.read_exact(&mut b).await?;
+    Ok(b[0])
+}
+
+async fn read_i16(r: &mut BufReader<TcpStream>) -> Result<i16> {
+    let mut b = [0; 2];
+    r.read_exact(&mut b).await?;
+    Ok(i16::from_be_bytes(b))
+}
+
+async fn read_i32(r: &mut BufReader<TcpStream>) -> Result<i32> {
+    let mut b = [0; 4];
+    r.read_exact(&mut b).await?;
+    Ok(i32::from_be_bytes(b))
+}
+
+async fn read_i64(r: &mut BufReader<TcpStream>) -> Result<i64> {
+    let mut b = [0; 8];
+    r.read_exact(&mut b).await?;
+    Ok(i64::from_be_bytes(b))
+}
+
+async fn read_len(r: &mut BufReader<TcpStream>) -> Result<usize> {
+    let l = read_i32(r).await?;
+    if l < 0 || l as usize > MAX_LEN {
+        bail!("invalid length {}", l);
+    }
+    Ok(l as usize)
+}
+
+async fn read_binary(r: &mut BufReader<TcpStream>, len: usize) -> Result<Vec<u8>> {
+    let mut b = vec![0; len];
+    r.read_exact(&mut b).await?;
+    Ok(b)
+}
+
+fn read_value(r: &mut BufReader<TcpStream>, ty: u8) -> BoxFuture<'_, Result<Value>> {
+    async move {
+        Ok(match ty {
+            T_BOOL => Value::Bool(read_u8(r).await? != 0),
+            T_BYTE => Value::Byte(read_u8(r).await? as i8),
+            T_DOUBLE => Value::Double(f64::from_bits(read_i64(r).await? as u64)),
+            T_I16 => Value::I16(read_i16(r).await?),
+            T_I32 => Value::I32(read_i32(r).await?),
+            T_I64 => Value::I64(read_i64(r).await?),
+            T_STRING => {
+                let len = read_len(r).await?;
+                Value::Binary(read_binary(r, len).await?)
+            }
+            T_STRUCT => {
+                let mut fields = Vec::new();
+                loop {
+                    let ty = read_u8(r).await?;
+                    if ty == T_STOP {
+                        break;
+                    }
+                    let id = read_i16(r).await?;
+                    fields.push((id, read_value(r, ty).await?));
+                }
+                Value::Struct(fields)
+            }
+            T_MAP => {
+                let k = read_u8(r).await?;
+                let v = read_u8(r).await?;
+                let len = read_len(r).await?;
+                let mut entries = Vec::new();
+                for _ in 0..len {
+                    entries.push((read_value(r, k).await?, read_value(r, v).await?));
+                }
+                Value::Map(k, v, entries)
+            }
+            T_SET | T_LIST => {
+                let t = read_u8(r).await?;
+                let len = read_len(r).await?;
+                let mut items = Vec::new();
+                for _ in 0..len {
+                    items.push(read_value(r, t).await?);
+                }
+                Value::List(t, items)
+            }
+            t => bail!("invalid thrift type {}", t),
+        })
+    }
+    .boxed()
+}
+
+/// Read a message, returning name, type, sequence id, and arguments.
+async fn read_message(r: &mut BufReader<TcpStream>) -> Result<(String, i32, i32, Value)> {
+    let v = read_i32(r).await?;
+    let (name, ty) = if v < 0 {
+        if (v as u32) & 0xffff_0000 != VERSION_1 {
+            bail!("unsupported thrift protocol version");
+        }
+        let len = read_len(r).await?;
+        (read_binary(r, len).await?, v & 0xff)
+    } else {
+        let name = read_binary(r, v as usize).await?;
+        (name, read_u8(r).await? as i32)
+    };
+    let seq = read_i32(r).await?;
+    let args = read_value(r, T_STRUCT).await?;
+    Ok((String::from_utf8(name)?, ty, seq, args))
+}
+
+fn message(name: &str, ty: i32, seq: i32, body: Value) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(&((VERSION_1 | ty as u32) as i32).to_be_bytes());
+    Value::string(name).write(&mut out);
+    out.extend_from_slice(&seq.to_be_bytes());
+    body.write(&mut out);
+    out
+}
+
+pub(crate) fn start(addr: SocketAddr, flowgraphs: Flowgraphs) {
+    std::thread::spawn(move || {
+        let ex = Executor::new();
+        block_on(ex.run(async {
+            let listener = match TcpListener::bind(addr).await {
+                Ok(l) => l,
+                Err(e) => {
+                    warn!("Thrift ControlPort cannot bind to {} ({:?})", addr, e);
+                    return;
+                }
+            };
+            debug!("Thrift ControlPort listening on {}", addr);
+            loop {
+                match listener.accept().await {
+                    Ok((stream, _)) => {
+                        let fgs = flowgraphs.clone();
+                        ex.spawn(async move {
+                            if let Err(e) = serve(stream, fgs).await {
+                                debug!("Thrift ControlPort connection closed ({:?})", e);
+                            }
+                        })
+                        .detach();
+                    }
+                    Err(e) => warn!("Thrift ControlPort accept failed ({:?})", e),
+                }
+            }
+        }));
+    });
+}
+
+async fn serve(stream: TcpStream, flowgraphs: Flowgraphs) -> Result<()> {
+    let mut reader = BufReader::new(stream.clone());
+    let mut writer = stream;
+
+    loop {
+        let (name, ty, seq, args) = read_message(&mut reader).await?;
+        if ty != MSG_CALL && ty != MSG_ONEWAY {
+            bail!("unexpected message type {}", ty);
+        }
+
+        let res = match name.as_str() {
+            "setKnobs" => set_knobs(&flowgraphs, &args).await.map(|_| None),
+            "getKnobs" => get_knobs(&flowgraphs, &args, false).await.map(Some),
+            "getRe" => get_knobs(&flowgraphs, &args, true).await.map(Some),
+            "properties" => properties(&flowgraphs, &args).await.map(Some),
+            "postMessage" => post_message(&flowgraphs, &args).await.map(|_| None),
+            "shutdown" => shutdown(&flowgraphs).await.map(|_| None),
+            _ => {
+                let e = app_exception(format!("unknown method {}", name), 1);
+                writer
+                    .write_all(&message(&name, MSG_EXCEPTION, seq, e))
+                    .await?;
+                continue;
+            }
+        };
+
+        if ty == MSG_ONEWAY {
+            continue;
+        }
+
+        let reply = match res {
+            Ok(Some(v)) => message(&name, MSG_REPLY, seq, Value::Struct(vec![(0, v)])),
+            Ok(None) => message(&name, MSG_REPLY, seq, Value::Struct(vec![])),
+            Err(e) => message(
+                &name,
+                MSG_EXCEPTION,
+                seq,
+                app_exception(format!("{:#}", e), 6),
+            ),
+        };
+        writer.write_all(&reply).await?;
+    }
+}
+
+fn app_exception(msg: String, ty: i32) -> Value {
+    Value::Struct(vec![(1, Value::string(msg)), (2, Value::I32(ty))])
+}
+
+fn alias(fg: usize, name: &str) -> String {
+    if fg == 0 {
+        name.to_string()
+    } else {
+        format!("fg{}_{}", fg, name)
+    }
+}
+
+/// Read-only knobs of all flowgraphs.
+async fn knobs(flowgraphs: &Flowgraphs) -> Vec<(String, Pmt)> {
+    let handles: Vec<(usize, FlowgraphHandle)> = flowgraphs
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(i, h)| (i, h.clone()))
+        .collect();
+
+    let mut knobs = Vec::new();
+    for (fg, mut handle) in handles {
+        let (desc, stats) = match (handle.description().await, handle.stats().await) {
+            (Ok(d), Ok(s)) => (d, s),
+            _ => continue,
+        };
+        let a = alias(fg, "flowgraph");
+        knobs.push((format!("{}::running", a), Pmt::U64(stats.running as u64)));
+        knobs.push((format!("{}::done", a), Pmt::U64(stats.done as u64)));
+        knobs.push((format!("{}::error", a), Pmt::U64(stats.error as u64)));
+
+        let states: HashMap<usize, BlockState> = stats.blocks.into_iter().collect();
+        for b in desc.blocks {
+            let state = match states.get(&b.id) {
+                Some(s) => format!("{:?}", s),
+                None => continue,
+            };
+            knobs.push((
+                format!("{}::state", alias(fg, &b.instance_name)),
+                Pmt::String(state),
+            ));
+        }
+    }
+    knobs
+}
+
+/// Writable knobs (message handlers) of all flowgraphs with flowgraph and block id.
+async fn handlers(flowgraphs: &Flowgraphs) -> Vec<(String, usize, usize, String)> {
+    let handles: Vec<(usize, FlowgraphHandle)> = flowgraphs
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(i, h)| (i, h.clone()))
+        .collect();
+
+    let mut handlers = Vec::new();
+    for (fg, mut handle) in handles {
+        if let Ok(desc) = handle.description().await {
+            for b in desc.blocks {
+                for h in b.message_inputs {
+                    handlers.push((
+                        format!("{}::{}", alias(fg, &b.instance_name), h),
+                        fg,
+                        b.id,
+                        h,
+                    ));
+                }
+            }
+        }
+    }
+    handlers
+}
+
+fn knob_ids(args: &Value) -> Vec<String> {
+    match args.field(1) {
+        Some(Value::List(_, ids)) => ids.iter().filter_map(Value::as_string).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Check if `s` matches the regex `re`, supporting only literals and `.*` wildcards.
+fn matches(re: &str, s: &str) -> bool {
+    let re = re.trim_start_matches('^').trim_end_matches('$');
+    let parts: Vec<&str> = re.split(".*").collect();
+    if parts.len() == 1 {
+        return re == s;
+    }
+
+    let first = parts[0];
+    let last = parts[parts.len() - 1];
+    if s.len() < first.len() + last.len() || !s.starts_with(first) || !s.ends_with(last) {
+        return false;
+    }
+    let mut rest = &s[first.len()..s.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(p) => rest = &rest[p + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+fn selected(ids: &[String], name: &str, regex: bool) -> bool {
+    ids.is_empty()
+        || ids
+            .iter()
+            .any(|id| if regex { matches(id, name) } else { id == name })
+}
+
+async fn get_knobs(flowgraphs: &Flowgraphs, args: &Value, regex: bool) -> Result<Value> {
+    let ids = knob_ids(args);
+    let entries = knobs(flowgraphs)
+        .await
+        .into_iter()
+        .filter(|(n, _)| selected(&ids, n, regex))
+        .filter_map(|(n, p)| Some((Value::string(n), pmt_to_knob(&p)?)))
+        .collect();
+    Ok(Value::Map(T_STRING, T_STRUCT, entries))
+}
+
+async fn properties(flowgraphs: &Flowgraphs, args: &Value) -> Result<Value> {
+    let ids = knob_ids(args);
+    let mut entries = Vec::new();
+
+    for (n, p) in knobs(flowgraphs).await {
+        if !selected(&ids, &n, false) {
+            continue;
+        }
+        let (ty, display, default) = match p {
+            Pmt::String(_) => (KNOB_STRING, DISPNULL, Pmt::String(String::new())),
+            _ => (KNOB_LONG, DISPTIME | DISPOPTSTRIP, Pmt::U64(0)),
+        };
+        let desc = n.rsplit("::").next().unwrap_or_default().to_string();
+        entries.push((Value::string(n), knob_prop(ty, &desc, display, &default)));
+    }
+    for (n, _, _, h) in handlers(flowgraphs).await {
+        if !selected(&ids, &n, false) {
+            continue;
+        }
+        entries.push((
+            Value::string(n),
+            knob_prop(
+                KNOB_DOUBLE,
+                &format!("message handler {}", h),
+                DISPNULL,
+                &Pmt::F64(0.0),
+            ),
+        ));
+    }
+
+    Ok(Value::Map(T_STRING, T_STRUCT, entries))
+}
+
+fn knob_prop(ty: i32, description: &str, display: i32, default: &Pmt) -> Value {
+    let default = pmt_to_knob(default).unwrap();
+    Value::Struct(vec![
+        (1, Value::I32(ty)),
+        (2, Value::string("")),
+        (3, Value::string(description)),
+        (4, Value::I32(display)),
+        (5, default.clone()),
+        (6, default.clone()),
+        (7, default),
+    ])
+}
+
+async fn call(flowgraphs: &Flowgraphs, name: &str, p: Pmt) -> Result<()> {
+    let (_, fg, block, handler) = handlers(flowgraphs)
+        .await
+        .into_iter()
+        .find(|(n, ..)| n == name)
+        .with_context(|| format!("unknown knob {}", name))?;
+    let mut handle = flowgraphs
+        .lock()
+        .unwrap()
+        .get(&fg.to_string())
+        .context("flowgraph terminated")?;
+    handle
+        .call(block, handler, p)
+        .await
+        .map_err(|e| anyhow!("calling {} failed ({:?})", name, e))
+}
+
+async fn set_knobs(flowgraphs: &Flowgraphs, args: &Value) -> Result<()> {
+    let knobs = match args.field(1) {
+        Some(Value::Map(_, _, k)) => k,
+        _ => bail!("missing knobs"),
+    };
+    for (k, v) in knobs {
+        let name = k.as_string().context("invalid knob name")?;
+        let p = knob_to_pmt(v).with_context(|| format!("unsupported value for {}", name))?;
+        call(flowgraphs, &name, p).await?;
+    }
+    Ok(())
+}
+
+async fn post_message(flowgraphs: &Flowgraphs, args: &Value) -> Result<()> {
+    let alias = args
+        .field(1)
+        .and_then(Value::as_string)
+        .context("missing block alias")?;
+    let port = args
+        .field(2)
+        .and_then(Value::as_string)
+        .context("missing port")?;
+    let msg = match args.field(3) {
+        Some(Value::Binary(b)) => b,
+        _ => bail!("missing message"),
+    };
+    let mut data = msg.as_slice();
+    let p = deserialize_pmt(&mut data)?;
+    call(flowgraphs, &format!("{}::{}", alias, port), p).await
+}
+
+async fn shutdown(flowgraphs: &Flowgraphs) -> Result<()> {
+    let handles: Vec<FlowgraphHandle> = flowgraphs
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(_, h)| h.clone())
+        .collect();
+    for mut h in handles {
+        let _ = h.terminate().await;
+    }
+    Ok(())
+}
+
+fn knob(base: i32, v: Value) -> Value {
+    Value::Struct(vec![
+        (1, Value::I32(base)),
+        (2, Value::Struct(vec![(base as i16 + 1, v)])),
+    ])
+}
+
+fn pmt_to_knob(p: &Pmt) -> Option<Value> {
+    Some(match p {
+        Pmt::String(s) => knob(BASE_STRING, Value::string(s.clone())),
+        Pmt::U32(v) => knob(BASE_LONG, Value::I64(*v as i64)),
+        Pmt::U64(v) => knob(BASE_LONG, Value::I64(*v as i64)),
+        Pmt::I32(v) => knob(BASE_INT, Value::I32(*v)),
+        Pmt::I64(v) => knob(BASE_LONG, Value::I64(*v)),
+        Pmt::F32(v) => knob(BASE_DOUBLE, Value::Double(*v as f64)),
+        Pmt::F64(v) => knob(BASE_DOUBLE, Value::Double(*v)),
+        Pmt::VecF32(v) => knob(
+            BASE_F32VECTOR,
+            Value::List(
+                T_DOUBLE,
+                v.iter().map(|x| Value::Double(*x as f64)).collect(),
+            ),
+        ),
+        Pmt::VecU64(v) => knob(
+            BASE_S64VECTOR,
+            Value::List(T_I64, v.iter().map(|x| Value::I64(*x as i64)).collect()),
+        ),
+        Pmt::Blob(v) => knob(BASE_S8VECTOR, Value::Binary(v.clone())),
+        _ => return None,
+    })
+}
+
+/// [Pmt::U32] for non-negative integers, [Pmt::I32] otherwise.
+fn int32(i: i32) -> Pmt {
+    u32::try_from(i).map_or(Pmt::I32(i), Pmt::U32)
+}
+
+/// [Pmt::U64] for non-negative integers, [Pmt::I64] otherwise.
+fn int64(i: i64) -> Pmt {
+    u64::try_from(i).map_or(Pmt::I64(i), Pmt::U64)
+}
+
+fn knob_to_pmt(k: &Value) -> Option<Pmt> {
+    let v = match k.field(2)? {
+        Value::Struct(f) => &f.first()?.1,
+        _ => return None,
+    };
+    let base = match k.field(1) {
+        Some(Value::I32(b)) => *b,
+        _ => return None,
+    };
+    let unsigned = |i: i64| -> Option<u64> { u64::try_from(i).ok() };
+    Some(match (base, v) {
+        (BASE_BOOL, Value::Bool(b)) => Pmt::U32(*b as u32),
+        (BASE_BYTE, Value::Byte(i)) => int32(*i as i32),
+        (BASE_SHORT, Value::I16(i)) => int32(*i as i32),
+        (BASE_INT, Value::I32(i)) => int32(*i),
+        (BASE_LONG, Value::I64(i)) => int64(*i),
+        (BASE_DOUBLE, Value::Double(d)) => Pmt::F64(*d),
+        (BASE_STRING, Value::Binary(_)) => Pmt::String(v.as_string()?),
+        (BASE_F32VECTOR | BASE_F64VECTOR, Value::List(_, l)) => Pmt::VecF32(
+            l.iter()
+                .map(|x| match x {
+                    Value::Double(d) => Some(*d as f32),
+                    _ => None,
+                })
+                .collect::<Option<Vec<f32>>>()?,
+        ),
+        (BASE_S64VECTOR, Value::List(_, l)) => Pmt::VecU64(
+            l.iter()
+                .map(|x| match x {
+                    Value::I64(i) => unsigned(*i),
+                    _ => None,
+                })
+                .collect::<Option<Vec<u64>>>()?,
+        ),
+        (BASE_S8VECTOR, Value::Binary(b)) => Pmt::Blob(b.clone()),
+        _ => return None,
+    })
+}
+
+fn take<'a>(data: &mut &'a [u8], n: usize) -> Result<&'a [u8]> {
+    if data.len() < n {
+        bail!("truncated pmt");
+    }
+    let (a, b) = data.split_at(n);
+    *data = b;
+    Ok(a)
+}
+
+/// Deserialize a (subset of) GNU Radio serialized PMTs.
+fn deserialize_pmt(data: &mut &[u8]) -> Result<Pmt> {
+    let tag = take(data, 1)?[0];
+    Ok(match tag {
+        // true, false
+        0x00 => Pmt::U32(1),
+        0x01 => Pmt::U32(0),
+        // symbol
+        0x02 => {
+            let len = u16::from_be_bytes(take(data, 2)?.try_into()?) as usize;
+            Pmt::String(String::from_utf8(take(data, len)?.to_vec())?)
+        }
+        // int32
+        0x03 => int32(i32::from_be_bytes(take(data, 4)?.try_into()?)),
+        // double
+        0x04 => Pmt::F64(f64::from_be_bytes(take(data, 8)?.try_into()?)),
+        // null
+        0x06 => Pmt::Null,
+        // pair
+        0x07 => Pmt::VecPmt(vec![deserialize_pmt(data)?, deserialize_pmt(data)?]),
+        // vector, tuple
+        0x08 | 0x0c => {
+            let len = u32::from_be_bytes(take(data, 4)?.try_into()?);
+            let mut v = Vec::new();
+            for _ in 0..len {
+                v.push(deserialize_pmt(data)?);
+            }
+            Pmt::VecPmt(v)
+        }
+        // uniform vector
+        0x0a => {
+            let ty = take(data, 1)?[0];
+            let len = u32::from_be_bytes(take(data, 4)?.try_into()?) as usize;
+            let npad = take(data, 1)?[0] as usize;
+            take(data, npad)?;
+            match ty {
+                0x00 => Pmt::Blob(take(data, len)?.to_vec()),
+                0x06 => Pmt::VecU64(
+                    take(data, len.checked_mul(8).context("invalid length")?)?
+                        .chunks_exact(8)
+                        .map(|c| u64::from_be_bytes(c.try_into().unwrap()))
+                        .collect(),
+                ),
+                0x08 => Pmt::VecF32(
+                    take(data, len.checked_mul(4).context("invalid length")?)?
+                        .chunks_exact(4)
+                        .map(|c| f32::from_be_bytes(c.try_into().unwrap()))
+                        .collect(),
+                ),
+                t => bail!("unsupported uniform vector type {}", t),
+            }
+        }
+        // uint64
+        0x0b => Pmt::U64(u64::from_be_bytes(take(data, 8)?.try_into()?)),
+        // int64
+        0x0d => int64(i64::from_be_bytes(take(data, 8)?.try_into()?)),
+        t => bail!("unsupported pmt type {}", t),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn regex() {
+        assert!(matches(".*", "a::b"));
+        assert!(matches("Fir_0::.*", "Fir_0::state"));
+        assert!(matches(".*::state", "Fir_0::state"));
+        assert!(!matches(".*::state", "Fir_0::state2"));
+        assert!(matches("Fir_0::state", "Fir_0::state"));
+        assert!(!matches("Fir_0", "Fir_0::state"));
+        assert!(matches(".*_0::.*e", "Fir_0::state"));
+    }
+
+    #[test]
+    fn knob_roundtrip() {
+        for p in [
+            Pmt::U64(42),
+            Pmt::I32(-42),
+            Pmt::I64(-42),
+            Pmt::F64(1.5),
+            Pmt::String("foo".to_string()),
+            Pmt::VecF32(vec![1.0, 2.0]),
+            Pmt::Blob(vec![1, 2, 3]),
+        ] {
+            assert_eq!(knob_to_pmt(&pmt_to_knob(&p).unwrap()).unwrap(), p);
+        }
+    }
+
+    #[test]
+    fn gr_pmt() {
+        let mut d: &[u8] = &[0x04, 0x40, 0x59, 0, 0, 0, 0, 0, 0];
+        assert_eq!(deserialize_pmt(&mut d).unwrap(), Pmt::F64(100.0));
+        let mut d: &[u8] = &[0x02, 0x00, 0x03, b'f', b'o', b'o'];
+        assert_eq!(
+            deserialize_pmt(&mut d).unwrap(),
+            Pmt::String("foo".to_string())
+        );
+        let mut d: &[u8] = &[0x03, 0xff, 0xff, 0xff, 0xfe];
+        assert_eq!(deserialize_pmt(&mut d).unwrap(), Pmt::I32(-2));
+        let mut d: &[u8] = &[0x03, 0x00, 0x00, 0x00, 0x02];
+        assert_eq!(deserialize_pmt(&mut d).unwrap(), Pmt::U32(2));
+        let mut d: &[u8] = &[0x07, 0x06, 0x0a, 0x00, 0, 0, 0, 2, 0, 1, 2];
+        match deserialize_pmt(&mut d).unwrap() {
+            Pmt::VecPmt(v) => assert_eq!(v, vec![Pmt::Null, Pmt::Blob(vec![1, 2])]),
+            p => panic!("unexpected pmt {:?}", p),
+        }
+    }
+}
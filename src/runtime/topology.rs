@@ -1,4 +1,4 @@
-use futures::channel::mpsc::Sender;
+use crate::runtime::channel::Sender;
 use std::collections::HashMap;
 
 use crate::anyhow::{bail, Context, Result};
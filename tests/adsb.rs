@@ -0,0 +1,176 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::blocks::AdsbDecoder;
+use futuresdr::blocks::Crc;
+use futuresdr::blocks::MessagePipe;
+use futuresdr::blocks::ModeSDemod;
+use futuresdr::blocks::VectorSource;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::futures::StreamExt;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+const IDENTIFICATION: &str = "8D4840D6202CC371C32CE0576098";
+const POSITION_ODD: &str = "8D40621D58C386435CC412692AD6";
+const POSITION_EVEN: &str = "8D40621D58C382D690C8AC2863A7";
+const VELOCITY: &str = "8D485020994409940838175B284F";
+
+fn hex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn decode(msgs: &[&str]) -> Result<Vec<Pmt>> {
+    let mut fg = Flowgraph::new();
+    let (tx, rx) = mpsc::channel(10);
+
+    let decoder = fg.add_block(AdsbDecoder::new());
+    let pipe = fg.add_block(MessagePipe::new(tx));
+    fg.connect_message(decoder, "out", pipe, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+    block_on(async {
+        for m in msgs {
+            handle.call(decoder, "in", Pmt::Blob(hex(m))).await?;
+        }
+        handle.terminate().await
+    })?;
+    block_on(task)?;
+
+    Ok(block_on(rx.collect()))
+}
+
+fn get_f64(p: &Pmt, key: &str) -> f64 {
+    match p {
+        Pmt::MapStrPmt(m) => match m.get(key) {
+            Some(Pmt::F64(v)) => *v,
+            _ => panic!("missing {key}"),
+        },
+        _ => panic!("wrong PMT type"),
+    }
+}
+
+fn get_string(p: &Pmt, key: &str) -> String {
+    match p {
+        Pmt::MapStrPmt(m) => match m.get(key) {
+            Some(Pmt::String(v)) => v.clone(),
+            _ => panic!("missing {key}"),
+        },
+        _ => panic!("wrong PMT type"),
+    }
+}
+
+#[test]
+fn adsb_identification() -> Result<()> {
+    let r = decode(&[IDENTIFICATION])?;
+    assert_eq!(r.len(), 1);
+    assert_eq!(get_string(&r[0], "icao"), "4840D6");
+    assert_eq!(get_string(&r[0], "type"), "identification");
+    assert_eq!(get_string(&r[0], "callsign"), "KLM1023");
+    Ok(())
+}
+
+#[test]
+fn adsb_position() -> Result<()> {
+    let r = decode(&[POSITION_ODD, POSITION_EVEN])?;
+    assert_eq!(r.len(), 2);
+    for p in r.iter() {
+        assert_eq!(get_string(p, "icao"), "40621D");
+        assert_eq!(get_string(p, "type"), "position");
+        assert_eq!(get_f64(p, "altitude"), 38000.0);
+    }
+    // position requires an even and an odd message
+    if let Pmt::MapStrPmt(m) = &r[0] {
+        assert!(!m.contains_key("latitude"));
+    }
+    assert!((get_f64(&r[1], "latitude") - 52.25720).abs() < 1e-4);
+    assert!((get_f64(&r[1], "longitude") - 3.91937).abs() < 1e-4);
+    Ok(())
+}
+
+#[test]
+fn adsb_velocity() -> Result<()> {
+    let r = decode(&[VELOCITY])?;
+    assert_eq!(get_string(&r[0], "type"), "velocity");
+    assert!((get_f64(&r[0], "speed") - 159.20).abs() < 0.01);
+    assert!((get_f64(&r[0], "heading") - 182.88).abs() < 0.01);
+    assert_eq!(get_f64(&r[0], "vertical_rate"), -832.0);
+    Ok(())
+}
+
+/// Pulse position modulate messages at 2 Msps, with gaps and noise in between.
+fn modulate(msgs: &[Vec<u8>]) -> Vec<Complex32> {
+    let mut state = 0x1234_5678u32;
+    let mut noise = move || {
+        state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+        ((state >> 16) & 0x7fff) as f32 / 32768.0 * 0.1
+    };
+
+    let mut pulses = Vec::new();
+    for m in msgs {
+        pulses.extend(std::iter::repeat(0.0).take(300));
+        let mut preamble = [0.0f32; 16];
+        for k in [0, 2, 7, 9] {
+            preamble[k] = 1.0;
+        }
+        pulses.extend_from_slice(&preamble);
+        for b in m {
+            for i in (0..8).rev() {
+                if (b >> i) & 1 == 1 {
+                    pulses.extend_from_slice(&[1.0, 0.0]);
+                } else {
+                    pulses.extend_from_slice(&[0.0, 1.0]);
+                }
+            }
+        }
+    }
+    pulses.extend(std::iter::repeat(0.0).take(300));
+
+    pulses
+        .into_iter()
+        .enumerate()
+        .map(|(k, a)| Complex32::from_polar(0.5 * a + noise(), k as f32 * 0.3))
+        .collect()
+}
+
+#[test]
+fn mode_s_demod() -> Result<()> {
+    let mut msgs: Vec<Vec<u8>> = [IDENTIFICATION, POSITION_ODD, POSITION_EVEN, VELOCITY]
+        .iter()
+        .map(|m| hex(m))
+        .collect();
+    // short message with correct parity (DF 11 with zero interrogator code)
+    msgs.push(vec![0x5d, 0x48, 0x40, 0xd6, 0x00, 0x00, 0x00]);
+    let crc = Crc::new(24, 0xfff409, 0, false, false, 0).encode(&msgs[4][..4]);
+    msgs[4][4..].copy_from_slice(&crc);
+
+    // corrupted message is dropped
+    let mut corrupted = hex(VELOCITY);
+    corrupted[5] ^= 0x10;
+
+    let mut input = modulate(&msgs[..2]);
+    input.extend(modulate(&[corrupted]));
+    input.extend(modulate(&msgs[2..]));
+
+    let mut fg = Flowgraph::new();
+    let (tx, rx) = mpsc::channel(10);
+
+    let src = fg.add_block(VectorSource::<Complex32>::new(input));
+    let demod = fg.add_block(ModeSDemod::new());
+    let pipe = fg.add_block(MessagePipe::new(tx));
+    fg.connect_stream(src, "out", demod, "in")?;
+    fg.connect_message(demod, "out", pipe, "in")?;
+
+    Runtime::new().run(fg)?;
+
+    let received: Vec<Pmt> = block_on(rx.collect());
+    let expected: Vec<Pmt> = msgs.into_iter().map(Pmt::Blob).collect();
+    assert_eq!(received, expected);
+
+    Ok(())
+}
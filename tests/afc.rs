@@ -0,0 +1,91 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::AfcBuilder;
+use futuresdr::blocks::AfcEstimator;
+use futuresdr::blocks::MessagePipe;
+use futuresdr::blocks::NullSink;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::futures::StreamExt;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+fn tone(freq: f32, sample_rate: f32, n: usize) -> Vec<Complex32> {
+    (0..n)
+        .map(|i| {
+            Complex32::from_polar(
+                1.0,
+                2.0 * std::f32::consts::PI * freq / sample_rate * i as f32,
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn afc_correct() -> Result<()> {
+    for estimator in [AfcEstimator::PhaseIncrement, AfcEstimator::Fft(512)] {
+        let mut fg = Flowgraph::new();
+
+        let src = fg.add_block(VectorSource::<Complex32>::new(tone(1234.0, 48e3, 48000)));
+        let afc = fg.add_block(
+            AfcBuilder::new(48e3)
+                .estimator(estimator)
+                .window(2048)
+                .build(),
+        );
+        let snk = fg.add_block(VectorSinkBuilder::<Complex32>::new().build());
+
+        fg.connect_stream(src, "out", afc, "in")?;
+        fg.connect_stream(afc, "out", snk, "in")?;
+
+        fg = Runtime::new().run(fg)?;
+
+        let v = fg.kernel::<VectorSink<Complex32>>(snk).unwrap().items();
+        assert_eq!(v.len(), 48000);
+        // remaining offset in Hz at the end
+        let acc: Complex32 = v[40000..].windows(2).map(|w| w[1] * w[0].conj()).sum();
+        let residual = acc.arg() * 48e3 / (2.0 * std::f32::consts::PI);
+        assert!(residual.abs() < 5.0, "{:?}: {}", estimator, residual);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn afc_retune() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let (tx, rx) = mpsc::channel(100);
+
+    let src = fg.add_block(VectorSource::<Complex32>::new(tone(-2000.0, 48e3, 20000)));
+    let afc = fg.add_block(
+        AfcBuilder::new(48e3)
+            .estimator(AfcEstimator::Fft(1024))
+            .window(4096)
+            .retune(100e6)
+            .build(),
+    );
+    let snk = fg.add_block(NullSink::<Complex32>::new());
+    let pipe = fg.add_block(MessagePipe::new(tx));
+
+    fg.connect_stream(src, "out", afc, "in")?;
+    fg.connect_stream(afc, "out", snk, "in")?;
+    fg.connect_message(afc, "freq", pipe, "in")?;
+
+    Runtime::new().run(fg)?;
+
+    let freqs: Vec<Pmt> = futuresdr::async_io::block_on(rx.collect());
+    // the source is not retuned, so the offset is detected again after two settle windows
+    assert_eq!(freqs.len(), 2);
+    for (i, f) in freqs.into_iter().enumerate() {
+        let expected = 100e6 - 2000.0 * (i + 1) as f64;
+        match f {
+            Pmt::F64(f) => assert!((f - expected).abs() < 5.0, "{} != {}", f, expected),
+            _ => panic!("wrong message type"),
+        }
+    }
+
+    Ok(())
+}
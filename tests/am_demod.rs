@@ -0,0 +1,55 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::AmDemod;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+use std::f32::consts::PI;
+
+fn demod(block: Block) -> Result<Vec<f32>> {
+    // 50% modulation with a tone at 0.01 * fs, random carrier phase
+    let input: Vec<Complex32> = (0..20000)
+        .map(|i| {
+            let a = 1.0 + 0.5 * (2.0 * PI * 0.01 * i as f32).sin();
+            Complex32::from_polar(a, 0.3 * i as f32)
+        })
+        .collect();
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::<Complex32>::new(input));
+    let demod = fg.add_block(block);
+    let snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+
+    fg.connect_stream(src, "out", demod, "in")?;
+    fg.connect_stream(demod, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    Ok(fg.kernel::<VectorSink<f32>>(snk).unwrap().items().clone())
+}
+
+#[test]
+fn am_envelope() -> Result<()> {
+    let v = demod(AmDemod::with_dc_block(false))?;
+    assert_eq!(v.len(), 20000);
+    for (i, x) in v.iter().enumerate() {
+        let a = 1.0 + 0.5 * (2.0 * PI * 0.01 * i as f32).sin();
+        assert!((x - a).abs() < 1e-4);
+    }
+    Ok(())
+}
+
+#[test]
+fn am_dc_block() -> Result<()> {
+    let v = demod(AmDemod::new())?;
+    assert_eq!(v.len(), 20000);
+    // after settling, only the tone remains
+    for (i, x) in v.iter().enumerate().skip(15000) {
+        let a = 0.5 * (2.0 * PI * 0.01 * i as f32).sin();
+        assert!((x - a).abs() < 0.02);
+    }
+    Ok(())
+}
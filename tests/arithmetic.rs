@@ -0,0 +1,113 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::async_io::Timer;
+use futuresdr::blocks::Add;
+use futuresdr::blocks::AddConst;
+use futuresdr::blocks::Multiply;
+use futuresdr::blocks::MultiplyConst;
+use futuresdr::blocks::Subtract;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+fn run<T>(inputs: Vec<Vec<T>>, block: Block) -> Result<Vec<T>>
+where
+    T: Clone + std::fmt::Debug + Send + Sync + 'static,
+{
+    let mut fg = Flowgraph::new();
+
+    let block = fg.add_block(block);
+    let n = inputs.len();
+    for (i, input) in inputs.into_iter().enumerate() {
+        let src = fg.add_block(VectorSource::<T>::new(input));
+        let port = if n == 1 {
+            "in".to_string()
+        } else {
+            format!("in{i}")
+        };
+        fg.connect_stream(src, "out", block, port.as_str())?;
+    }
+    let snk = fg.add_block(VectorSinkBuilder::<T>::new().build());
+    fg.connect_stream(block, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    Ok(fg.kernel::<VectorSink<T>>(snk).unwrap().items().clone())
+}
+
+#[test]
+fn const_ops() -> Result<()> {
+    let input: Vec<f32> = (0..1000).map(|i| i as f32).collect();
+    let v = run(vec![input.clone()], AddConst::<f32>::new(1.5))?;
+    assert!(v.iter().zip(input.iter()).all(|(y, x)| *y == x + 1.5));
+
+    let v = run(vec![input.clone()], MultiplyConst::<f32>::new(-2.0))?;
+    assert!(v.iter().zip(input.iter()).all(|(y, x)| *y == x * -2.0));
+
+    let c = Complex32::new(0.0, 1.0);
+    let input: Vec<Complex32> = (0..1000).map(|i| Complex32::new(i as f32, 1.0)).collect();
+    let v = run(vec![input.clone()], MultiplyConst::<Complex32>::new(c))?;
+    assert!(v.iter().zip(input.iter()).all(|(y, x)| *y == x * c));
+
+    Ok(())
+}
+
+#[test]
+fn nary_ops() -> Result<()> {
+    let a: Vec<f32> = (0..1000).map(|i| i as f32).collect();
+    let b: Vec<f32> = (0..1200).map(|i| 2.0 * i as f32).collect();
+    let c: Vec<f32> = vec![3.0; 1000];
+
+    let v = run(vec![a.clone(), b.clone(), c.clone()], Add::<f32>::new(3))?;
+    assert_eq!(v.len(), 1000);
+    assert!((0..1000).all(|i| v[i] == a[i] + b[i] + c[i]));
+
+    let v = run(
+        vec![a.clone(), b.clone(), c.clone()],
+        Subtract::<f32>::new(3),
+    )?;
+    assert!((0..1000).all(|i| v[i] == a[i] - b[i] - c[i]));
+
+    let v = run(vec![a.clone(), b.clone()], Multiply::<f32>::new(2))?;
+    assert!((0..1000).all(|i| v[i] == a[i] * b[i]));
+
+    Ok(())
+}
+
+#[test]
+fn update_const() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(VectorSource::<Complex32>::new(vec![
+        Complex32::new(1.0, 0.0);
+        1 << 20
+    ]));
+    let mul = fg.add_block(MultiplyConst::<Complex32>::new(Complex32::new(1.0, 0.0)));
+    let snk = fg.add_block(VectorSinkBuilder::<Complex32>::new().build());
+    fg.connect_stream(src, "out", mul, "in")?;
+    fg.connect_stream(mul, "out", snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+    block_on(async {
+        Timer::after(std::time::Duration::from_millis(1)).await;
+        handle
+            .call(mul, "const", Pmt::VecF32(vec![0.0, 2.0]))
+            .await?;
+        handle.call(mul, "const", Pmt::Null).await
+    })?;
+    let fg = block_on(task)?;
+
+    let v = fg.kernel::<VectorSink<Complex32>>(snk).unwrap().items();
+    assert_eq!(v.len(), 1 << 20);
+    assert!(v
+        .iter()
+        .all(|x| *x == Complex32::new(1.0, 0.0) || *x == Complex32::new(0.0, 2.0)));
+
+    Ok(())
+}
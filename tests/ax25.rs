@@ -0,0 +1,193 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::blocks::AfskDemodBuilder;
+use futuresdr::blocks::AfskModBuilder;
+use futuresdr::blocks::AprsParser;
+use futuresdr::blocks::Ax25Decoder;
+use futuresdr::blocks::Ax25EncoderBuilder;
+use futuresdr::blocks::Ax25Frame;
+use futuresdr::blocks::MessagePipe;
+use futuresdr::blocks::PduToStream;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::futures::StreamExt;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+fn frame(info: &str) -> Ax25Frame {
+    Ax25Frame {
+        destination: "APRS".to_string(),
+        source: "N0CALL-9".to_string(),
+        path: vec!["WIDE1-1*".to_string(), "WIDE2-2".to_string()],
+        info: info.as_bytes().to_vec(),
+    }
+}
+
+fn parse(info: &str) -> Result<Vec<Pmt>> {
+    let mut fg = Flowgraph::new();
+    let (tx, rx) = mpsc::channel(10);
+
+    let aprs = fg.add_block(AprsParser::new());
+    let pipe = fg.add_block(MessagePipe::new(tx));
+    fg.connect_message(aprs, "out", pipe, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+    block_on(async {
+        handle
+            .call(aprs, "in", Pmt::Blob(frame(info).encode()))
+            .await?;
+        handle.terminate().await
+    })?;
+    block_on(task)?;
+
+    Ok(block_on(rx.collect()))
+}
+
+fn position(report: &Pmt) -> (f64, f64) {
+    match report {
+        Pmt::MapStrPmt(m) => match (&m["latitude"], &m["longitude"]) {
+            (Pmt::F64(lat), Pmt::F64(lon)) => (*lat, *lon),
+            _ => panic!("wrong position type"),
+        },
+        _ => panic!("wrong PMT type"),
+    }
+}
+
+#[test]
+fn ax25_frame() {
+    let f = frame(">test");
+    let bytes = f.encode();
+    assert_eq!(
+        bytes[..14],
+        [
+            b'A' << 1,
+            b'P' << 1,
+            b'R' << 1,
+            b'S' << 1,
+            b' ' << 1,
+            b' ' << 1,
+            0xe0,
+            b'N' << 1,
+            b'0' << 1,
+            b'C' << 1,
+            b'A' << 1,
+            b'L' << 1,
+            b'L' << 1,
+            0x72,
+        ]
+    );
+    assert_eq!(bytes[20], 0xe2);
+    assert_eq!(bytes[27], 0x65);
+    assert_eq!(bytes[28..30], [0x03, 0xf0]);
+    assert_eq!(Ax25Frame::decode(&bytes), Some(f));
+    assert_eq!(Ax25Frame::decode(&bytes[..10]), None);
+}
+
+#[test]
+fn aprs_uncompressed() -> Result<()> {
+    let reports = parse("!4903.50N/07201.75W-Test 001234")?;
+    assert_eq!(reports.len(), 1);
+    let (lat, lon) = position(&reports[0]);
+    assert!((lat - 49.058333).abs() < 1e-5);
+    assert!((lon + 72.029167).abs() < 1e-5);
+    if let Pmt::MapStrPmt(m) = &reports[0] {
+        assert_eq!(m["source"], Pmt::String("N0CALL-9".to_string()));
+        assert_eq!(m["symbol"], Pmt::String("/-".to_string()));
+        assert_eq!(m["comment"], Pmt::String("Test 001234".to_string()));
+        assert!(!m.contains_key("timestamp"));
+    }
+
+    let reports = parse("@092345z4903.50S/07201.75E>")?;
+    let (lat, lon) = position(&reports[0]);
+    assert!((lat + 49.058333).abs() < 1e-5);
+    assert!((lon - 72.029167).abs() < 1e-5);
+    if let Pmt::MapStrPmt(m) = &reports[0] {
+        assert_eq!(m["timestamp"], Pmt::String("092345z".to_string()));
+    }
+
+    // status reports are no position reports
+    assert!(parse(">on the air")?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn aprs_compressed() -> Result<()> {
+    let reports = parse("=/5L!!<*e7> sTComment")?;
+    let (lat, lon) = position(&reports[0]);
+    assert!((lat - 49.5).abs() < 1e-4);
+    assert!((lon + 72.75).abs() < 1e-4);
+    if let Pmt::MapStrPmt(m) = &reports[0] {
+        assert_eq!(m["symbol"], Pmt::String("/>".to_string()));
+        assert_eq!(m["comment"], Pmt::String("Comment".to_string()));
+    }
+    Ok(())
+}
+
+/// Encode frames and post the decoded frames, passing the bits through the given blocks.
+fn loopback(frames: &[Vec<u8>], afsk: bool) -> Result<Vec<Pmt>> {
+    let mut fg = Flowgraph::new();
+    let (tx, mut rx) = mpsc::channel(10);
+
+    let encoder = fg.add_block(Ax25EncoderBuilder::new().preamble(8).build());
+    let p2s = fg.add_block(PduToStream::<u8>::new());
+    let decoder = fg.add_block(Ax25Decoder::new());
+    let pipe = fg.add_block(MessagePipe::new(tx));
+    fg.connect_message(encoder, "out", p2s, "in")?;
+    if afsk {
+        let modulator = fg.add_block(AfskModBuilder::new(48000.0).build());
+        let demodulator = fg.add_block(AfskDemodBuilder::new(48000.0).build());
+        fg.connect_stream(p2s, "out", modulator, "in")?;
+        fg.connect_stream(modulator, "out", demodulator, "in")?;
+        fg.connect_stream(demodulator, "out", decoder, "in")?;
+    } else {
+        fg.connect_stream(p2s, "out", decoder, "in")?;
+    }
+    fg.connect_message(decoder, "out", pipe, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+    let received = block_on(async {
+        for f in frames {
+            handle.call(encoder, "in", Pmt::Blob(f.clone())).await?;
+        }
+        let mut received = Vec::new();
+        for _ in frames {
+            received.push(rx.next().await.unwrap());
+        }
+        handle.terminate().await?;
+        Ok::<_, futuresdr::anyhow::Error>(received)
+    })?;
+    block_on(task)?;
+
+    Ok(received)
+}
+
+#[test]
+fn hdlc_loopback() -> Result<()> {
+    let mut frames: Vec<Vec<u8>> = (0..5)
+        .map(|i| frame(&format!("!4903.50N/07201.75W-#{i}")).encode())
+        .collect();
+    // long runs of ones have to be stuffed
+    frames.push([frame("").encode(), vec![0xff; 20], vec![0x7e; 4]].concat());
+
+    let received = loopback(&frames, false)?;
+    for (r, f) in received.iter().zip(frames.iter()) {
+        assert_eq!(*r, Pmt::Blob(f.clone()));
+    }
+    Ok(())
+}
+
+#[test]
+fn aprs_afsk_loopback() -> Result<()> {
+    let frames: Vec<Vec<u8>> = (0..3)
+        .map(|i| frame(&format!("!4903.50N/07201.75W-#{i}")).encode())
+        .collect();
+
+    let received = loopback(&frames, true)?;
+    for (r, f) in received.iter().zip(frames.iter()) {
+        assert_eq!(*r, Pmt::Blob(f.clone()));
+    }
+    Ok(())
+}
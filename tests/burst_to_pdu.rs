@@ -0,0 +1,107 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::BurstToPduBuilder;
+use futuresdr::blocks::MessagePipe;
+use futuresdr::blocks::PowerSquelchBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::futures::StreamExt;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+fn samples(pdu: Pmt) -> Vec<Complex32> {
+    match pdu {
+        Pmt::Blob(b) => b
+            .chunks_exact(8)
+            .map(|c| {
+                Complex32::new(
+                    f32::from_le_bytes(c[0..4].try_into().unwrap()),
+                    f32::from_le_bytes(c[4..8].try_into().unwrap()),
+                )
+            })
+            .collect(),
+        _ => panic!("wrong message type"),
+    }
+}
+
+#[test]
+fn burst_to_pdu_tags() -> Result<()> {
+    // bursts of 300 and 500 samples, counting up in the imaginary part, zeros otherwise
+    let input: Vec<Complex32> = (0..2000)
+        .map(|i| {
+            if (200..500).contains(&i) || (1000..1500).contains(&i) {
+                Complex32::new(1.0, i as f32)
+            } else {
+                Complex32::new(0.0, 0.0)
+            }
+        })
+        .collect();
+
+    let mut fg = Flowgraph::new();
+    let (tx, rx) = mpsc::channel(10);
+
+    let src = fg.add_block(VectorSource::<Complex32>::new(input));
+    let squelch = fg.add_block(PowerSquelchBuilder::new(-3.0).alpha(1.0).tag().build());
+    let bursts = fg.add_block(BurstToPduBuilder::new().padding(10, 5).build());
+    let pipe = fg.add_block(MessagePipe::new(tx));
+
+    fg.connect_stream(src, "out", squelch, "in")?;
+    fg.connect_stream(squelch, "out", bursts, "in")?;
+    fg.connect_message(bursts, "out", pipe, "in")?;
+
+    Runtime::new().run(fg)?;
+
+    let pdus: Vec<Pmt> = futuresdr::async_io::block_on(rx.collect());
+    assert_eq!(pdus.len(), 2);
+    for (pdu, (start, end)) in pdus.into_iter().zip([(200, 500), (1000, 1500)]) {
+        let v = samples(pdu);
+        // squelch closes one sample late, the first sample below the threshold is zeroed
+        assert_eq!(v.len(), 10 + end + 1 - start + 5);
+        assert!(v[..10].iter().all(|x| x.norm() == 0.0));
+        assert_eq!(v[10], Complex32::new(1.0, start as f32));
+        assert_eq!(
+            v[10 + end - start - 1],
+            Complex32::new(1.0, (end - 1) as f32)
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn burst_to_pdu_gate() -> Result<()> {
+    let input: Vec<Complex32> = (0..1000).map(|i| Complex32::new(i as f32, 0.0)).collect();
+    let gate: Vec<f32> = (0..1000)
+        .map(|i| {
+            if (100..150).contains(&i) || (300..700).contains(&i) {
+                1.0
+            } else {
+                0.0
+            }
+        })
+        .collect();
+
+    let mut fg = Flowgraph::new();
+    let (tx, rx) = mpsc::channel(10);
+
+    let src = fg.add_block(VectorSource::<Complex32>::new(input));
+    let gate = fg.add_block(VectorSource::<f32>::new(gate));
+    let bursts = fg.add_block(BurstToPduBuilder::new().gate().max_len(300).build());
+    let pipe = fg.add_block(MessagePipe::new(tx));
+
+    fg.connect_stream(src, "out", bursts, "in")?;
+    fg.connect_stream(gate, "out", bursts, "gate")?;
+    fg.connect_message(bursts, "out", pipe, "in")?;
+
+    Runtime::new().run(fg)?;
+
+    let pdus: Vec<Vec<Complex32>> = futuresdr::async_io::block_on(rx.collect::<Vec<Pmt>>())
+        .into_iter()
+        .map(samples)
+        .collect();
+    let ranges: Vec<(f32, usize)> = pdus.iter().map(|v| (v[0].re, v.len())).collect();
+    assert_eq!(ranges, vec![(100.0, 50), (300.0, 300), (600.0, 100)]);
+
+    Ok(())
+}
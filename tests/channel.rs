@@ -0,0 +1,71 @@
+use futuresdr::async_io::block_on;
+use futuresdr::futures::SinkExt;
+use futuresdr::futures::StreamExt;
+use futuresdr::runtime::channel::channel;
+use std::thread;
+
+#[test]
+fn try_send_full() {
+    let (mut tx, mut rx) = channel::<u32>(2);
+    tx.try_send(1).unwrap();
+    tx.try_send(2).unwrap();
+    let e = tx.try_send(3).unwrap_err();
+    assert!(e.is_full());
+    assert_eq!(e.into_inner(), 3);
+
+    assert_eq!(block_on(rx.next()), Some(1));
+    tx.try_send(3).unwrap();
+    assert_eq!(block_on(rx.next()), Some(2));
+    assert_eq!(block_on(rx.next()), Some(3));
+}
+
+#[test]
+fn disconnect() {
+    let (tx, mut rx) = channel::<u32>(2);
+    let mut tx2 = tx.clone();
+    drop(tx);
+    block_on(tx2.send(1)).unwrap();
+    drop(tx2);
+    assert_eq!(block_on(rx.next()), Some(1));
+    assert_eq!(block_on(rx.next()), None);
+
+    let (mut tx, rx) = channel::<u32>(2);
+    drop(rx);
+    assert!(tx.is_closed());
+    assert!(block_on(tx.send(1)).is_err());
+    assert!(tx.try_send(1).unwrap_err().is_disconnected());
+}
+
+#[test]
+fn many_senders() {
+    let n_senders = 8;
+    let n_msgs = 10_000u64;
+    let (tx, mut rx) = channel::<u64>(16);
+
+    let handles: Vec<_> = (0..n_senders)
+        .map(|_| {
+            let mut tx = tx.clone();
+            thread::spawn(move || {
+                block_on(async {
+                    for i in 0..n_msgs {
+                        tx.send(i).await.unwrap();
+                    }
+                })
+            })
+        })
+        .collect();
+    drop(tx);
+
+    let sum = block_on(async {
+        let mut sum = 0;
+        while let Some(i) = rx.next().await {
+            sum += i;
+        }
+        sum
+    });
+
+    for h in handles {
+        h.join().unwrap();
+    }
+    assert_eq!(sum, n_senders * n_msgs * (n_msgs - 1) / 2);
+}
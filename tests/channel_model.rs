@@ -0,0 +1,103 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::ChannelModelBuilder;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+
+fn run(input: Vec<Complex32>, block: Block) -> Result<Vec<Complex32>> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(VectorSource::<Complex32>::new(input));
+    let block = fg.add_block(block);
+    let snk = fg.add_block(VectorSinkBuilder::<Complex32>::new().build());
+
+    fg.connect_stream(src, "out", block, "in")?;
+    fg.connect_stream(block, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    Ok(fg
+        .kernel::<VectorSink<Complex32>>(snk)
+        .unwrap()
+        .items()
+        .clone())
+}
+
+fn random_input(n: usize) -> Vec<Complex32> {
+    (0..n)
+        .map(|_| Complex32::new(rand::random::<f32>() - 0.5, rand::random::<f32>() - 0.5))
+        .collect()
+}
+
+#[test]
+fn channel_ideal() -> Result<()> {
+    let input = random_input(10_000);
+    let output = run(input.clone(), ChannelModelBuilder::new().build())?;
+    assert_eq!(output, input);
+    Ok(())
+}
+
+#[test]
+fn channel_noise() -> Result<()> {
+    let n = 100_000;
+    let input = vec![Complex32::new(0.0, 0.0); n];
+    let output = run(input, ChannelModelBuilder::new().snr(10.0).seed(1).build())?;
+
+    let power = output.iter().map(|x| x.norm_sqr()).sum::<f32>() / n as f32;
+    assert!((power - 0.1).abs() < 0.005, "noise power {}", power);
+
+    // seeded noise is reproducible
+    let again = run(
+        vec![Complex32::new(0.0, 0.0); n],
+        ChannelModelBuilder::new().snr(10.0).seed(1).build(),
+    )?;
+    assert_eq!(output, again);
+    Ok(())
+}
+
+#[test]
+fn channel_offsets_multipath() -> Result<()> {
+    let input = random_input(1000);
+    let taps = vec![Complex32::new(1.0, 0.0), Complex32::new(0.0, 0.5)];
+    let output = run(
+        input.clone(),
+        ChannelModelBuilder::new()
+            .frequency_offset(0.01)
+            .phase_offset(0.3)
+            .taps(taps.clone())
+            .build(),
+    )?;
+
+    for (n, y) in output.iter().enumerate().skip(1) {
+        let x = input[n] * taps[0] + input[n - 1] * taps[1];
+        let r = Complex32::from_polar(1.0, 0.3 + 2.0 * std::f32::consts::PI * 0.01 * n as f32);
+        assert!((y - x * r).norm() < 1e-3);
+    }
+    Ok(())
+}
+
+#[test]
+fn channel_timing_drift() -> Result<()> {
+    let n = 100_000;
+    // slowly varying signal, such that interpolation is accurate
+    let signal = |t: f64| Complex32::from_polar(1.0, (0.01 * t) as f32);
+    let input: Vec<Complex32> = (0..n).map(|t| signal(t as f64)).collect();
+    let drift = 1e-3;
+    let output = run(
+        input,
+        ChannelModelBuilder::new().timing_drift(drift).build(),
+    )?;
+
+    let expected = n as f64 / (1.0 + drift);
+    assert!((output.len() as f64 - expected).abs() < 3.0);
+    // output k is the input at time k * (1 + drift), delayed by two samples
+    for (k, y) in output.iter().enumerate().skip(10).step_by(97) {
+        let t = k as f64 * (1.0 + drift) - 2.0;
+        assert!((y - signal(t)).norm() < 1e-3);
+    }
+    Ok(())
+}
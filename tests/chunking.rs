@@ -0,0 +1,141 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_trait::async_trait;
+use futuresdr::blocks::Apply;
+use futuresdr::blocks::FirBuilder;
+use futuresdr::blocks::KeepOneInN;
+use futuresdr::blocks::MovingAverage;
+use futuresdr::num_complex::Complex32;
+use futuresdr::proptest::prelude::*;
+use futuresdr::runtime::testing;
+use futuresdr::runtime::testing::check_chunking;
+use futuresdr::runtime::testing::Schedule;
+use futuresdr::runtime::{
+    Block, BlockMeta, BlockMetaBuilder, Kernel, MessageIo, MessageIoBuilder, Pmt, StreamIo,
+    StreamIoBuilder, WorkIo,
+};
+
+/// Sum of the samples in each work() call, i.e., a kernel that depends on the chunking.
+struct ChunkSum;
+
+impl ChunkSum {
+    fn block() -> Block {
+        Block::new(
+            BlockMetaBuilder::new("ChunkSum").build(),
+            StreamIoBuilder::new()
+                .add_input::<u32>("in")
+                .add_output::<u32>("out")
+                .build(),
+            MessageIoBuilder::<Self>::new().build(),
+            ChunkSum,
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for ChunkSum {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<u32>();
+        let o = sio.output(0).slice::<u32>();
+        if !i.is_empty() && !o.is_empty() {
+            o[0] = i.iter().sum();
+            sio.input(0).consume(i.len());
+            sio.output(0).produce(1);
+        }
+        if sio.input(0).finished() && sio.input(0).slice::<u32>().is_empty() {
+            io.finished = true;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn chunking_detects_dependency() -> Result<()> {
+    let input: Vec<u32> = (0..100).collect();
+    check_chunking::<u32, u32>(ChunkSum::block, input.clone(), &Schedule::new(), 0.0)?;
+    assert!(
+        check_chunking::<u32, u32>(ChunkSum::block, input, &Schedule::chunks(&[10, 5]), 0.0)
+            .is_err()
+    );
+    Ok(())
+}
+
+#[test]
+fn chunking_output_space() -> Result<()> {
+    let schedule = Schedule::new()
+        .step(3, 1)
+        .step(0, 2)
+        .step(100, 1)
+        .step(7, 5);
+    check_chunking::<u32, u32>(
+        || KeepOneInN::<u32>::new(3),
+        (0..1000).collect(),
+        &schedule,
+        0.0,
+    )
+}
+
+#[test]
+fn schedule_message() -> Result<()> {
+    let schedule = Schedule::chunks(&[10]).message("foo", Pmt::Null);
+    let ret =
+        testing::run_schedule::<f32, f32>(MovingAverage::<f32>::new(4), vec![0.0; 100], &schedule);
+    assert!(ret.is_err());
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn apply(schedule in testing::strategy(64)) {
+        check_chunking::<u32, u32>(
+            || Apply::new(|x: &u32| x.wrapping_mul(3)),
+            (0..2000).collect(),
+            &schedule,
+            0.0,
+        ).unwrap();
+    }
+
+    #[test]
+    fn fir(schedule in testing::strategy(100)) {
+        let taps = [0.1f32, -0.4, 1.0, 0.3, 0.2];
+        let input: Vec<Complex32> = (0..2000)
+            .map(|i| Complex32::from_polar(1.0, i as f32 * 0.1))
+            .collect();
+        check_chunking::<Complex32, Complex32>(
+            || FirBuilder::new::<Complex32, Complex32, f32, _>(taps),
+            input.clone(),
+            &schedule,
+            1e-5,
+        ).unwrap();
+    }
+
+    #[test]
+    fn moving_average(schedule in testing::strategy(50)) {
+        let input: Vec<f32> = (0..2000).map(|i| (i % 17) as f32).collect();
+        check_chunking::<f32, f32>(
+            || MovingAverage::<f32>::with_decimation(8, 3),
+            input.clone(),
+            &schedule,
+            1e-3,
+        ).unwrap();
+    }
+
+    #[test]
+    fn messages_in_order(schedule in testing::strategy_with_messages(
+        10,
+        vec![("a".into(), Pmt::U32(0)), ("b".into(), Pmt::U32(1)), ("c".into(), Pmt::U32(2))],
+    )) {
+        let messages: Vec<_> = schedule
+            .steps()
+            .iter()
+            .filter_map(|s| s.message.as_ref())
+            .map(|(_, p)| p.clone())
+            .collect();
+        prop_assert_eq!(messages, vec![Pmt::U32(0), Pmt::U32(1), Pmt::U32(2)]);
+    }
+}
@@ -0,0 +1,224 @@
+use std::iter::repeat_with;
+use std::time::Duration;
+
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::Timer;
+use futuresdr::async_trait::async_trait;
+use futuresdr::blocks::CopyRand;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::runtime::buffer::circular;
+use futuresdr::runtime::buffer::circular::Circular;
+use futuresdr::runtime::buffer::circular::Mapping;
+use futuresdr::runtime::scheduler::SmolScheduler;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::BlockMeta;
+use futuresdr::runtime::BlockMetaBuilder;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Kernel;
+use futuresdr::runtime::MessageIo;
+use futuresdr::runtime::MessageIoBuilder;
+use futuresdr::runtime::Runtime;
+use futuresdr::runtime::StreamIo;
+use futuresdr::runtime::StreamIoBuilder;
+use futuresdr::runtime::WorkIo;
+
+fn coalesced(threshold: usize, single_thread: bool) -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let n_items = 1_000_000;
+    let orig: Vec<u32> = repeat_with(rand::random::<u32>).take(n_items).collect();
+
+    let src = fg.add_block(VectorSource::<u32>::new(orig.clone()));
+    let copy1 = fg.add_block(CopyRand::<u32>::new(64));
+    let copy2 = fg.add_block(CopyRand::<u32>::new(64));
+    let snk = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+
+    let buffer = Circular::new().with_notify_threshold(threshold);
+    fg.connect_stream_with_type(src, "out", copy1, "in", buffer.clone())?;
+    fg.connect_stream_with_type(copy1, "out", copy2, "in", buffer.clone())?;
+    fg.connect_stream_with_type(copy2, "out", snk, "in", buffer)?;
+
+    fg = if single_thread {
+        Runtime::with_scheduler(SmolScheduler::new(1, false)).run(fg)?
+    } else {
+        Runtime::new().run(fg)?
+    };
+
+    let snk = fg.kernel::<VectorSink<u32>>(snk).unwrap();
+    assert_eq!(snk.items(), &orig);
+
+    Ok(())
+}
+
+#[test]
+fn notify_threshold() -> Result<()> {
+    coalesced(1024, false)
+}
+
+#[test]
+fn notify_threshold_single_thread() -> Result<()> {
+    coalesced(1024, true)
+}
+
+#[test]
+fn notify_threshold_exceeds_buffer() -> Result<()> {
+    coalesced(1 << 30, false)
+}
+
+/// Polls for one item at a time and keeps setting `call_again`, like a slow device.
+struct SlowSource {
+    remaining: u32,
+}
+
+impl SlowSource {
+    fn build(n: u32) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("SlowSource").build(),
+            StreamIoBuilder::new().add_output::<u32>("out").build(),
+            MessageIoBuilder::new().build(),
+            SlowSource { remaining: n },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for SlowSource {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        Timer::after(Duration::from_millis(1)).await;
+        sio.output(0).slice::<u32>()[0] = self.remaining;
+        sio.output(0).produce(1);
+        self.remaining -= 1;
+        if self.remaining == 0 {
+            io.finished = true;
+        } else {
+            io.call_again = true;
+        }
+        Ok(())
+    }
+}
+
+/// Records the number of items, available in each call to `work()`.
+struct Recorder {
+    calls: Vec<usize>,
+}
+
+impl Recorder {
+    fn build() -> Block {
+        Block::new(
+            BlockMetaBuilder::new("Recorder").build(),
+            StreamIoBuilder::new().add_input::<u32>("in").build(),
+            MessageIoBuilder::new().build(),
+            Recorder { calls: Vec::new() },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for Recorder {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let finished = sio.input(0).finished();
+        let n = sio.input(0).slice::<u32>().len();
+        if n > 0 {
+            self.calls.push(n);
+            sio.input(0).consume(n);
+        }
+        if finished {
+            io.finished = true;
+        }
+        Ok(())
+    }
+}
+
+/// Items available in each wakeup of the reader of a slow source.
+fn slow_source(threshold: usize) -> Result<Vec<usize>> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(SlowSource::build(40));
+    let snk = fg.add_block(Recorder::build());
+    let buffer = Circular::new().with_notify_threshold(threshold);
+    fg.connect_stream_with_type(src, "out", snk, "in", buffer)?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<Recorder>(snk).unwrap();
+    assert_eq!(snk.calls.iter().sum::<usize>(), 40);
+    Ok(snk.calls.clone())
+}
+
+#[test]
+fn notify_threshold_slow_source() -> Result<()> {
+    // without a threshold, the reader sees every item, when it is produced
+    assert!(slow_source(0)?.len() >= 20);
+
+    // with a threshold of 10 items, the source keeps calling again and only announces the
+    // items, once the threshold is reached
+    let calls = slow_source(10 * 4)?;
+    assert!(calls.len() <= 4, "{calls:?}");
+    assert!(calls.iter().all(|n| *n >= 10), "{calls:?}");
+    Ok(())
+}
+
+fn fan_out(mapping: Mapping) -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let n_items = 1_000_000;
+    let orig: Vec<u32> = repeat_with(rand::random::<u32>).take(n_items).collect();
+
+    let src = fg.add_block(VectorSource::<u32>::new(orig.clone()));
+    let copy = fg.add_block(CopyRand::<u32>::new(1000));
+    let snk1 = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+    let snk2 = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+
+    // small buffers, which wrap around often and at odd offsets
+    let buffer = Circular::with_size(1).with_mapping(mapping);
+    fg.connect_stream_with_type(src, "out", copy, "in", buffer.clone())?;
+    fg.connect_stream_with_type(copy, "out", snk1, "in", buffer.clone())?;
+    fg.connect_stream_with_type(copy, "out", snk2, "in", buffer)?;
+
+    fg = Runtime::new().run(fg)?;
+
+    for snk in [snk1, snk2] {
+        let snk = fg.kernel::<VectorSink<u32>>(snk).unwrap();
+        assert_eq!(snk.items(), &orig);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn copy_mapping() -> Result<()> {
+    fan_out(Mapping::Copy)
+}
+
+#[test]
+fn double_mapping() -> Result<()> {
+    if !circular::double_mapping_supported() {
+        return Ok(());
+    }
+    fan_out(Mapping::DoubleMapped)
+}
+
+#[test]
+fn parse_mapping() {
+    assert_eq!("auto".parse::<Mapping>(), Ok(Mapping::Auto));
+    assert_eq!(
+        "double_mapped".parse::<Mapping>(),
+        Ok(Mapping::DoubleMapped)
+    );
+    assert_eq!("Copy".parse::<Mapping>(), Ok(Mapping::Copy));
+    assert!("ring".parse::<Mapping>().is_err());
+}
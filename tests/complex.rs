@@ -0,0 +1,48 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::run_and_collect;
+use futuresdr::blocks::ComplexToArg;
+use futuresdr::blocks::ComplexToImag;
+use futuresdr::blocks::ComplexToMagSquared;
+use futuresdr::blocks::ComplexToReal;
+use futuresdr::blocks::Deinterleave;
+use futuresdr::blocks::Interleave;
+use futuresdr::num_complex::Complex32;
+
+#[test]
+fn interleave() -> Result<()> {
+    let input: Vec<Complex32> = (0..10_000)
+        .map(|i| Complex32::new(i as f32, -(i as f32)))
+        .collect();
+
+    let iq: Vec<f32> = run_and_collect(input.clone(), Interleave::new())?;
+    assert_eq!(iq.len(), 20_000);
+    assert_eq!(iq[..4], [0.0, -0.0, 1.0, -1.0]);
+
+    let mut iq = iq;
+    iq.push(1.0);
+    let output: Vec<Complex32> = run_and_collect(iq, Deinterleave::new())?;
+    assert_eq!(output, input);
+
+    Ok(())
+}
+
+#[test]
+fn complex_to_float() -> Result<()> {
+    let input: Vec<Complex32> = (0..1000)
+        .map(|_| Complex32::new(rand::random::<f32>() - 0.5, rand::random::<f32>() - 0.5))
+        .collect();
+
+    let re: Vec<f32> = run_and_collect(input.clone(), ComplexToReal::new())?;
+    let im: Vec<f32> = run_and_collect(input.clone(), ComplexToImag::new())?;
+    let mag: Vec<f32> = run_and_collect(input.clone(), ComplexToMagSquared::new())?;
+    let arg: Vec<f32> = run_and_collect(input.clone(), ComplexToArg::new())?;
+
+    for (n, x) in input.iter().enumerate() {
+        assert_eq!(re[n], x.re);
+        assert_eq!(im[n], x.im);
+        assert_eq!(mag[n], x.norm_sqr());
+        assert_eq!(arg[n], x.arg());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::Constellation;
+use futuresdr::blocks::ConstellationDemapperBuilder;
+use futuresdr::blocks::ConstellationMapper;
+use futuresdr::blocks::MessagePipe;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::futures::StreamExt;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+#[test]
+fn constellation_gray_unit_energy() {
+    for c in [
+        Constellation::bpsk(),
+        Constellation::qpsk(),
+        Constellation::psk8(),
+        Constellation::qam16(),
+    ] {
+        let p = c.points();
+        assert_eq!(p.len(), 1 << c.bits_per_symbol());
+        let energy: f32 = p.iter().map(|x| x.norm_sqr()).sum::<f32>() / p.len() as f32;
+        assert!((energy - 1.0).abs() < 1e-5);
+
+        // nearest neighbors differ in a single bit
+        let dmin = (0..p.len())
+            .flat_map(|a| (0..p.len()).filter(move |b| *b != a).map(move |b| (a, b)))
+            .map(|(a, b)| (p[a] - p[b]).norm())
+            .fold(f32::INFINITY, f32::min);
+        for a in 0..p.len() {
+            for b in 0..p.len() {
+                if a != b && (p[a] - p[b]).norm() < dmin + 1e-4 {
+                    assert_eq!((a ^ b).count_ones(), 1);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn constellation_hard() -> Result<()> {
+    let c = Constellation::qam16();
+    let symbols: Vec<u8> = (0..1000).map(|i| ((i * 7) % 16) as u8).collect();
+
+    let mut fg = Flowgraph::new();
+    let (tx, rx) = mpsc::channel(100);
+
+    let src = fg.add_block(VectorSource::<u8>::new(symbols.clone()));
+    let mapper = fg.add_block(ConstellationMapper::new(c.clone()));
+    let demapper = fg.add_block(
+        ConstellationDemapperBuilder::new(c)
+            .evm_interval(100)
+            .build(),
+    );
+    let snk = fg.add_block(VectorSinkBuilder::<u8>::new().build());
+    let pipe = fg.add_block(MessagePipe::new(tx));
+
+    fg.connect_stream(src, "out", mapper, "in")?;
+    fg.connect_stream(mapper, "out", demapper, "in")?;
+    fg.connect_stream(demapper, "out", snk, "in")?;
+    fg.connect_message(demapper, "evm", pipe, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let v = fg.kernel::<VectorSink<u8>>(snk).unwrap().items().clone();
+    drop(fg);
+    assert_eq!(v, symbols);
+
+    let evms: Vec<Pmt> = futuresdr::async_io::block_on(rx.collect());
+    assert_eq!(evms.len(), 10);
+    for e in evms {
+        assert!(matches!(e, Pmt::F32(e) if e < 1e-6));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn constellation_soft_evm() -> Result<()> {
+    let c = Constellation::qpsk();
+    // symbols with a constant error of 0.1
+    let input: Vec<Complex32> = (0..400)
+        .map(|i| c.map((i % 4) as u8) + Complex32::new(0.0, 0.1))
+        .collect();
+
+    let mut fg = Flowgraph::new();
+    let (tx, rx) = mpsc::channel(100);
+
+    let src = fg.add_block(VectorSource::<Complex32>::new(input));
+    let demapper = fg.add_block(
+        ConstellationDemapperBuilder::new(c)
+            .soft()
+            .evm_interval(200)
+            .build(),
+    );
+    let snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+    let pipe = fg.add_block(MessagePipe::new(tx));
+
+    fg.connect_stream(src, "out", demapper, "in")?;
+    fg.connect_stream(demapper, "out", snk, "in")?;
+    fg.connect_message(demapper, "evm", pipe, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let v = fg.kernel::<VectorSink<f32>>(snk).unwrap().items().clone();
+    drop(fg);
+    assert_eq!(v.len(), 800);
+    for (i, llrs) in v.chunks(2).enumerate() {
+        let s = i % 4;
+        // most significant bit first
+        assert_eq!(llrs[0] > 0.0, s & 2 != 0);
+        assert_eq!(llrs[1] > 0.0, s & 1 != 0);
+    }
+
+    let evms: Vec<Pmt> = futuresdr::async_io::block_on(rx.collect());
+    assert_eq!(evms.len(), 2);
+    for e in evms {
+        assert!(matches!(e, Pmt::F32(e) if (e - 0.1).abs() < 1e-4));
+    }
+
+    Ok(())
+}
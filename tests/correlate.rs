@@ -0,0 +1,187 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_trait::async_trait;
+use futuresdr::blocks::CorrelateAccessCodeBuilder;
+use futuresdr::blocks::CorrelateSyncBuilder;
+use futuresdr::blocks::MessagePipe;
+use futuresdr::blocks::VectorSource;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::futures::StreamExt;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::{
+    Block, BlockMeta, BlockMetaBuilder, Flowgraph, Kernel, MessageIo, MessageIoBuilder, Pmt,
+    Runtime, StreamIo, StreamIoBuilder, Tag, WorkIo,
+};
+
+/// Store all tags with their absolute index.
+struct TagSink<T> {
+    n: usize,
+    tags: Vec<(usize, Tag)>,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: Send + 'static> TagSink<T> {
+    fn block() -> Block {
+        Block::new(
+            BlockMetaBuilder::new("TagSink").build(),
+            StreamIoBuilder::new().add_input::<T>("in").build(),
+            MessageIoBuilder::<Self>::new().build(),
+            TagSink::<T> {
+                n: 0,
+                tags: Vec::new(),
+                _type: std::marker::PhantomData,
+            },
+        )
+    }
+}
+
+#[async_trait]
+impl<T: Send + 'static> Kernel for TagSink<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<T>();
+        for t in sio.input(0).tags().iter().filter(|t| t.index < i.len()) {
+            self.tags.push((self.n + t.index, t.tag.clone()));
+        }
+        self.n += i.len();
+        sio.input(0).consume(i.len());
+
+        if sio.input(0).finished() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn correlate_access_code() -> Result<()> {
+    let code: Vec<u8> = (0..32).map(|_| rand::random::<u8>() & 1).collect();
+    let payload: Vec<u8> = (0..100).map(|_| rand::random::<u8>() & 1).collect();
+
+    // code with one bit error at 500, followed by the payload
+    let mut input = vec![0u8; 500];
+    input.extend_from_slice(&code);
+    input[510] ^= 1;
+    input.extend_from_slice(&payload);
+    input.extend_from_slice(&[0u8; 200]);
+
+    let mut fg = Flowgraph::new();
+    let (tx, rx) = mpsc::channel(10);
+
+    let src = fg.add_block(VectorSource::<u8>::new(input));
+    let sync = fg.add_block(
+        CorrelateAccessCodeBuilder::new(&code)
+            .threshold(2)
+            .frame_len(100)
+            .build(),
+    );
+    let snk = fg.add_block(TagSink::<u8>::block());
+    let pipe = fg.add_block(MessagePipe::new(tx));
+
+    fg.connect_stream(src, "out", sync, "in")?;
+    fg.connect_stream(sync, "out", snk, "in")?;
+    fg.connect_message(sync, "frames", pipe, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<TagSink<u8>>(snk).unwrap();
+    let tags: Vec<(usize, usize)> = snk
+        .tags
+        .iter()
+        .filter_map(|(i, t)| match t {
+            Tag::NamedUsize(n, e) if n == "access_code" => Some((*i, *e)),
+            _ => None,
+        })
+        .collect();
+    assert!(tags.contains(&(532, 1)));
+    drop(fg);
+
+    let frames: Vec<Pmt> = futuresdr::async_io::block_on(rx.collect());
+    assert!(frames.contains(&Pmt::Blob(payload)));
+
+    Ok(())
+}
+
+#[test]
+fn correlate_sync() -> Result<()> {
+    let barker = [1., 1., 1., 1., 1., -1., -1., 1., 1., -1., 1., -1., 1.];
+    let preamble: Vec<Complex32> = barker.iter().map(|x| Complex32::new(*x, 0.0)).collect();
+    // fixed pseudo-random symbols, since random noise might correlate with the preamble
+    let mut state = 0x2545_f491u32;
+    let mut bit = move || {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        if state & 1 == 1 {
+            1.0
+        } else {
+            -1.0
+        }
+    };
+    let mut qpsk = || Complex32::new(bit(), bit()) / 2.0f32.sqrt();
+
+    let mut frame = preamble.clone();
+    frame.extend((0..50).map(|_| qpsk()));
+
+    let rotation = Complex32::from_polar(0.5, 0.7);
+    let mut input: Vec<Complex32> = (0..300).map(|_| qpsk() * 0.1).collect();
+    input.extend(frame.iter().map(|x| x * rotation));
+    input.extend((0..300).map(|_| qpsk() * 0.1));
+
+    let mut fg = Flowgraph::new();
+    let (tx, rx) = mpsc::channel(10);
+
+    let src = fg.add_block(VectorSource::<Complex32>::new(input));
+    let sync = fg.add_block(
+        CorrelateSyncBuilder::new(preamble)
+            .threshold(0.8)
+            .frame_len(frame.len())
+            .build(),
+    );
+    let snk = fg.add_block(TagSink::<Complex32>::block());
+    let pipe = fg.add_block(MessagePipe::new(tx));
+
+    fg.connect_stream(src, "out", sync, "in")?;
+    fg.connect_stream(sync, "out", snk, "in")?;
+    fg.connect_message(sync, "frames", pipe, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<TagSink<Complex32>>(snk).unwrap();
+    let get = |name: &str| {
+        snk.tags.iter().find_map(|(i, t)| match t {
+            Tag::NamedF32(n, v) if n == name => Some((*i, *v)),
+            _ => None,
+        })
+    };
+    let (start, corr) = get("corr_start").unwrap();
+    assert_eq!(start, 300);
+    assert!(corr > 0.99);
+    assert!((get("phase_est").unwrap().1 - 0.7).abs() < 1e-3);
+    assert!((get("amp_est").unwrap().1 - 0.5).abs() < 1e-3);
+    assert!(get("time_est").unwrap().1.abs() < 0.1);
+    drop(fg);
+
+    let frames: Vec<Pmt> = futuresdr::async_io::block_on(rx.collect());
+    assert_eq!(frames.len(), 1);
+    match &frames[0] {
+        Pmt::Blob(b) => {
+            assert_eq!(b.len(), frame.len() * 8);
+            for (c, x) in b.chunks_exact(8).zip(frame.iter()) {
+                let y = Complex32::new(
+                    f32::from_le_bytes(c[0..4].try_into().unwrap()),
+                    f32::from_le_bytes(c[4..8].try_into().unwrap()),
+                );
+                assert!((y - x).norm() < 1e-3);
+            }
+        }
+        _ => panic!("wrong message type"),
+    }
+
+    Ok(())
+}
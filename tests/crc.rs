@@ -0,0 +1,71 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::blocks::Crc;
+use futuresdr::blocks::CrcAppend;
+use futuresdr::blocks::CrcCheck;
+use futuresdr::blocks::MessagePipe;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::futures::StreamExt;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+#[test]
+fn crc_check_values() {
+    let data = b"123456789";
+    assert_eq!(Crc::crc8().checksum(data), 0xf4);
+    assert_eq!(Crc::crc16_ccitt().checksum(data), 0x29b1);
+    assert_eq!(Crc::crc16_x25().checksum(data), 0x906e);
+    assert_eq!(Crc::crc16_kermit().checksum(data), 0x2189);
+    assert_eq!(Crc::crc32().checksum(data), 0xcbf43926);
+    // CRC-16/ARC
+    assert_eq!(
+        Crc::new(16, 0x8005, 0x0000, true, true, 0x0000).checksum(data),
+        0xbb3d
+    );
+
+    assert_eq!(Crc::crc16_ccitt().encode(data), vec![0x29, 0xb1]);
+    assert_eq!(Crc::crc32().encode(data), vec![0x26, 0x39, 0xf4, 0xcb]);
+}
+
+#[test]
+fn crc_append_check() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let (tx, rx) = mpsc::channel(10);
+    let (tx_failed, rx_failed) = mpsc::channel(10);
+
+    let append = fg.add_block(CrcAppend::new(Crc::crc32()));
+    let check = fg.add_block(CrcCheck::new(Crc::crc32()));
+    let pipe = fg.add_block(MessagePipe::new(tx));
+    let pipe_failed = fg.add_block(MessagePipe::new(tx_failed));
+
+    fg.connect_message(append, "out", check, "in")?;
+    fg.connect_message(check, "out", pipe, "in")?;
+    fg.connect_message(check, "failed", pipe_failed, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+
+    let frame: Vec<u8> = (0..100).map(|_| rand::random()).collect();
+    let mut corrupted = frame.clone();
+    corrupted.extend_from_slice(&Crc::crc32().encode(&frame));
+    corrupted[10] ^= 0x04;
+
+    block_on(async {
+        handle.call(append, "in", Pmt::Blob(frame.clone())).await?;
+        handle
+            .call(check, "in", Pmt::Blob(corrupted.clone()))
+            .await?;
+        // too short for the CRC
+        handle.call(check, "in", Pmt::Blob(vec![1, 2])).await?;
+        handle.terminate().await
+    })?;
+    drop(block_on(task)?);
+
+    let frames: Vec<Pmt> = block_on(rx.collect());
+    assert_eq!(frames, vec![Pmt::Blob(frame)]);
+    let failed: Vec<Pmt> = block_on(rx_failed.collect());
+    assert_eq!(failed, vec![Pmt::Blob(corrupted), Pmt::Blob(vec![1, 2])]);
+
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::Delay;
+use futuresdr::blocks::Head;
+use futuresdr::blocks::SkipHead;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+
+fn run(input: Vec<u32>, block: Block) -> Result<Vec<u32>> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(VectorSource::<u32>::new(input));
+    let block = fg.add_block(block);
+    let snk = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+
+    fg.connect_stream(src, "out", block, "in")?;
+    fg.connect_stream(block, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    Ok(fg.kernel::<VectorSink<u32>>(snk).unwrap().items().clone())
+}
+
+#[test]
+fn delay() -> Result<()> {
+    let v = run((1..=100_000).collect(), Delay::<u32>::new(10))?;
+    assert_eq!(v.len(), 100_010);
+    assert!(v[..10].iter().all(|x| *x == 0));
+    assert!(v[10..].iter().copied().eq(1..=100_000));
+    Ok(())
+}
+
+#[test]
+fn skip_head() -> Result<()> {
+    let v = run((0..100_000).collect(), SkipHead::<u32>::new(70_000))?;
+    assert!(v.iter().copied().eq(70_000..100_000));
+
+    let v = run((0..100).collect(), SkipHead::<u32>::new(1000))?;
+    assert!(v.is_empty());
+    Ok(())
+}
+
+#[test]
+fn head() -> Result<()> {
+    let v = run((0..100_000).collect(), Head::<u32>::new(1234))?;
+    assert!(v.iter().copied().eq(0..1234));
+
+    let v = run((0..100).collect(), Head::<u32>::new(1000))?;
+    assert!(v.iter().copied().eq(0..100));
+    Ok(())
+}
@@ -0,0 +1,246 @@
+//! Round trip through libhdf5, reading the recording back with the C API.
+use futuresdr::anyhow::{bail, Result};
+use futuresdr::blocks::digital_rf::DigitalRfSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+use std::ffi::CString;
+use std::os::raw::{c_char, c_int, c_uint, c_void};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+
+#[allow(non_camel_case_types)]
+type hid_t = i64;
+#[allow(non_camel_case_types)]
+type herr_t = c_int;
+#[allow(non_camel_case_types)]
+type hsize_t = u64;
+
+const H5P_DEFAULT: hid_t = 0;
+const H5F_ACC_RDONLY: c_uint = 0;
+const H5T_COMPOUND: c_int = 6;
+
+// the library is linked through futuresdr
+#[allow(non_upper_case_globals)]
+extern "C" {
+    fn H5open() -> herr_t;
+    fn H5Fopen(name: *const c_char, flags: c_uint, fapl: hid_t) -> hid_t;
+    fn H5Fclose(file_id: hid_t) -> herr_t;
+    fn H5Dopen2(loc_id: hid_t, name: *const c_char, dapl_id: hid_t) -> hid_t;
+    fn H5Dget_space(dset_id: hid_t) -> hid_t;
+    fn H5Dread(
+        dset_id: hid_t,
+        mem_type_id: hid_t,
+        mem_space_id: hid_t,
+        file_space_id: hid_t,
+        plist_id: hid_t,
+        buf: *mut c_void,
+    ) -> herr_t;
+    fn H5Dclose(dset_id: hid_t) -> herr_t;
+    fn H5Sget_simple_extent_dims(
+        space_id: hid_t,
+        dims: *mut hsize_t,
+        maxdims: *mut hsize_t,
+    ) -> c_int;
+    fn H5Sclose(space_id: hid_t) -> herr_t;
+    fn H5Aopen(obj_id: hid_t, attr_name: *const c_char, aapl_id: hid_t) -> hid_t;
+    fn H5Aread(attr_id: hid_t, type_id: hid_t, buf: *mut c_void) -> herr_t;
+    fn H5Aclose(attr_id: hid_t) -> herr_t;
+    fn H5Tcreate(class: c_int, size: usize) -> hid_t;
+    fn H5Tinsert(parent_id: hid_t, name: *const c_char, offset: usize, member_id: hid_t) -> herr_t;
+    fn H5Tclose(type_id: hid_t) -> herr_t;
+
+    static H5T_NATIVE_FLOAT_g: hid_t;
+    static H5T_NATIVE_DOUBLE_g: hid_t;
+    static H5T_NATIVE_INT_g: hid_t;
+    static H5T_NATIVE_LLONG_g: hid_t;
+    static H5T_NATIVE_ULLONG_g: hid_t;
+}
+
+/// HDF5 object, closed on drop.
+struct Object(hid_t, unsafe extern "C" fn(hid_t) -> herr_t);
+
+impl Object {
+    fn new(id: hid_t, close: unsafe extern "C" fn(hid_t) -> herr_t) -> Result<Object> {
+        if id < 0 {
+            bail!("HDF5 call failed");
+        }
+        Ok(Object(id, close))
+    }
+}
+
+impl Drop for Object {
+    fn drop(&mut self) {
+        unsafe {
+            (self.1)(self.0);
+        }
+    }
+}
+
+fn cstr(s: &str) -> CString {
+    CString::new(s).unwrap()
+}
+
+fn open(path: &Path) -> Result<Object> {
+    let name = cstr(&path.to_string_lossy());
+    Object::new(
+        unsafe { H5Fopen(name.as_ptr(), H5F_ACC_RDONLY, H5P_DEFAULT) },
+        H5Fclose,
+    )
+}
+
+fn dataset(loc: &Object, name: &str) -> Result<Object> {
+    let n = cstr(name);
+    Object::new(
+        unsafe { H5Dopen2(loc.0, n.as_ptr(), H5P_DEFAULT) },
+        H5Dclose,
+    )
+}
+
+/// Read a complete dataset of `cols` elements per row.
+fn read<T: Default + Clone>(d: &Object, dtype: hid_t, cols: u64) -> Result<Vec<T>> {
+    let space = Object::new(unsafe { H5Dget_space(d.0) }, H5Sclose)?;
+    let mut dims = [0; 2];
+    let rank =
+        unsafe { H5Sget_simple_extent_dims(space.0, dims.as_mut_ptr(), std::ptr::null_mut()) };
+    assert_eq!(rank, 2);
+    assert_eq!(dims[1], cols);
+    let mut v = vec![T::default(); (dims[0] * dims[1]) as usize];
+    let ret = unsafe {
+        H5Dread(
+            d.0,
+            dtype,
+            H5P_DEFAULT,
+            H5P_DEFAULT,
+            H5P_DEFAULT,
+            v.as_mut_ptr() as *mut c_void,
+        )
+    };
+    assert!(ret >= 0);
+    Ok(v)
+}
+
+/// Read a scalar attribute.
+fn attribute<T: Default>(loc: &Object, name: &str, dtype: hid_t) -> Result<T> {
+    let n = cstr(name);
+    let a = Object::new(unsafe { H5Aopen(loc.0, n.as_ptr(), H5P_DEFAULT) }, H5Aclose)?;
+    let mut v = T::default();
+    assert!(unsafe { H5Aread(a.0, dtype, &mut v as *mut T as *mut c_void) } >= 0);
+    Ok(v)
+}
+
+/// Files in the directory tree, whose names start with the prefix, sorted by path.
+fn files(dir: &Path, prefix: &str) -> Vec<PathBuf> {
+    let mut v = Vec::new();
+    for e in std::fs::read_dir(dir).unwrap() {
+        let p = e.unwrap().path();
+        if p.is_dir() {
+            v.extend(files(&p, prefix));
+        } else if p.file_name().unwrap().to_string_lossy().starts_with(prefix) {
+            v.push(p);
+        }
+    }
+    v.sort();
+    v
+}
+
+#[test]
+fn digital_rf_round_trip() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("futuresdr-test-{}-drf", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let start = 1_600_000_000;
+    let input: Vec<Complex32> = (0..2500)
+        .map(|i| Complex32::new(i as f32, -(i as f32) / 2.0))
+        .collect();
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::new(input.clone()));
+    let snk = fg.add_block(
+        DigitalRfSinkBuilder::<Complex32>::new(&dir, "ch0", 1000)
+            .start_time(UNIX_EPOCH + Duration::from_secs(start))
+            .uuid("test")
+            .metadata("center_frequencies", Pmt::F64(7.2e6))
+            .build(),
+    );
+    fg.connect_stream(src, "out", snk, "in")?;
+    Runtime::new().run(fg)?;
+
+    unsafe {
+        assert!(H5open() >= 0);
+    }
+    let channel = dir.join("ch0");
+    let complex = Object::new(unsafe { H5Tcreate(H5T_COMPOUND, 8) }, H5Tclose)?;
+    for (name, offset) in [("r", 0), ("i", 4)] {
+        let n = cstr(name);
+        assert!(unsafe { H5Tinsert(complex.0, n.as_ptr(), offset, H5T_NATIVE_FLOAT_g) } >= 0);
+    }
+
+    // one file per second, each starting a continuous block
+    let rf = files(&channel, "rf@");
+    let names: Vec<_> = rf
+        .iter()
+        .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            format!("rf@{}.000.h5", start),
+            format!("rf@{}.000.h5", start + 1),
+            format!("rf@{}.000.h5", start + 2),
+        ]
+    );
+
+    let mut samples = Vec::new();
+    for (i, path) in rf.iter().enumerate() {
+        let f = open(path)?;
+        let data = dataset(&f, "rf_data")?;
+        samples.extend(read::<Complex32>(&data, complex.0, 1)?);
+
+        let index = read::<i64>(
+            &dataset(&f, "rf_data_index")?,
+            unsafe { H5T_NATIVE_LLONG_g },
+            2,
+        )?;
+        assert_eq!(index, [(start as i64 + i as i64) * 1000, 0]);
+
+        let rate: u64 = attribute(&data, "sample_rate_numerator", unsafe {
+            H5T_NATIVE_ULLONG_g
+        })?;
+        assert_eq!(rate, 1000);
+        let is_complex: i32 = attribute(&data, "is_complex", unsafe { H5T_NATIVE_INT_g })?;
+        assert_eq!(is_complex, 1);
+        let sequence: i32 = attribute(&data, "sequence_num", unsafe { H5T_NATIVE_INT_g })?;
+        assert_eq!(sequence, i as i32);
+    }
+    assert_eq!(samples, input);
+
+    let props = open(&channel.join("drf_properties.h5"))?;
+    let cadence: i64 = attribute(&props, "file_cadence_millisecs", unsafe {
+        H5T_NATIVE_LLONG_g
+    })?;
+    assert_eq!(cadence, 1000);
+
+    // metadata at the first sample
+    let metadata = files(&channel.join("metadata"), "metadata@");
+    assert_eq!(metadata.len(), 1);
+    let f = open(&metadata[0])?;
+    let d = dataset(&f, &format!("{}/center_frequencies", start * 1000))?;
+    let mut freq = 0.0f64;
+    let ret = unsafe {
+        H5Dread(
+            d.0,
+            H5T_NATIVE_DOUBLE_g,
+            H5P_DEFAULT,
+            H5P_DEFAULT,
+            H5P_DEFAULT,
+            &mut freq as *mut f64 as *mut c_void,
+        )
+    };
+    assert!(ret >= 0);
+    assert_eq!(freq, 7.2e6);
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
@@ -0,0 +1,135 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::blocks::DopplerCorrectionBuilder;
+use futuresdr::blocks::MessagePipe;
+use futuresdr::blocks::NullSink;
+use futuresdr::blocks::Source;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::futures::StreamExt;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+use std::f64::consts::PI;
+
+/// Carrier with a Doppler shift that changes linearly from `f0` to `f1` Hz within one second.
+fn pass(f0: f64, f1: f64, sample_rate: f64, n: usize) -> Vec<Complex32> {
+    (0..n)
+        .map(|i| {
+            let t = i as f64 / sample_rate;
+            let phase = 2.0 * PI * (f0 * t + 0.5 * (f1 - f0) * t * t);
+            Complex32::from_polar(1.0, phase as f32)
+        })
+        .collect()
+}
+
+fn freq(p: Pmt) -> f64 {
+    match p {
+        Pmt::MapStrPmt(m) => match m.get("freq") {
+            Some(Pmt::F64(f)) => *f,
+            _ => panic!("no frequency in command"),
+        },
+        _ => panic!("wrong message type"),
+    }
+}
+
+#[test]
+fn doppler_schedule() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(VectorSource::<Complex32>::new(pass(
+        1000.0, -1000.0, 48e3, 48000,
+    )));
+    let doppler = fg.add_block(
+        DopplerCorrectionBuilder::new(48e3)
+            .start_time(100.0)
+            .schedule(&[(101.0, -1000.0), (100.0, 1000.0), (100.5, 0.0)])
+            .build(),
+    );
+    let snk = fg.add_block(VectorSinkBuilder::<Complex32>::new().build());
+
+    fg.connect_stream(src, "out", doppler, "in")?;
+    fg.connect_stream(doppler, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let v = fg.kernel::<VectorSink<Complex32>>(snk).unwrap().items();
+    assert_eq!(v.len(), 48000);
+    // phase is accumulated per sample, leaving a small error for the changing shift
+    for (i, x) in v.iter().enumerate() {
+        assert!((x - Complex32::new(1.0, 0.0)).norm() < 0.2, "{}: {}", i, x);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn doppler_retune_schedule() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let (tx, rx) = mpsc::channel(100);
+
+    let src = fg.add_block(VectorSource::<Complex32>::new(vec![
+        Complex32::new(1.0, 0.0);
+        72000
+    ]));
+    let doppler = fg.add_block(
+        DopplerCorrectionBuilder::new(48e3)
+            .schedule(&[(0.0, 0.0), (1.0, -3500.0)])
+            .retune(437e6, 1000.0)
+            .build(),
+    );
+    let snk = fg.add_block(NullSink::<Complex32>::new());
+    let pipe = fg.add_block(MessagePipe::new(tx));
+
+    fg.connect_stream(src, "out", doppler, "in")?;
+    fg.connect_stream(doppler, "out", snk, "in")?;
+    fg.connect_message(doppler, "cmd", pipe, "in")?;
+
+    Runtime::new().run(fg)?;
+
+    let freqs: Vec<f64> = block_on(rx.map(freq).collect());
+    assert_eq!(freqs.len(), 3);
+    for (i, f) in freqs.into_iter().enumerate() {
+        let expected = 437e6 - 1000.0 * (i + 1) as f64;
+        assert!((f - expected).abs() < 1.0, "{} != {}", f, expected);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn doppler_retune_message() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let (tx, mut rx) = mpsc::channel(10);
+
+    let src = fg.add_block(Source::new(|| Complex32::new(1.0, 0.0)));
+    let doppler = fg.add_block(
+        DopplerCorrectionBuilder::new(48e3)
+            .retune(100e6, 500.0)
+            .build(),
+    );
+    let snk = fg.add_block(NullSink::<Complex32>::new());
+    let pipe = fg.add_block(MessagePipe::new(tx));
+
+    fg.connect_stream(src, "out", doppler, "in")?;
+    fg.connect_stream(doppler, "out", snk, "in")?;
+    fg.connect_message(doppler, "cmd", pipe, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+    block_on(async {
+        handle.call(doppler, "doppler", Pmt::F64(1000.0)).await?;
+        assert_eq!(freq(rx.next().await.unwrap()), 100e6 + 1000.0);
+        // below the threshold
+        handle.call(doppler, "doppler", Pmt::F64(1200.0)).await?;
+        handle.call(doppler, "doppler", Pmt::F64(-2000.0)).await?;
+        assert_eq!(freq(rx.next().await.unwrap()), 100e6 - 2000.0);
+        handle.terminate().await
+    })?;
+    block_on(task)?;
+
+    Ok(())
+}
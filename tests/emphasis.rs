@@ -0,0 +1,56 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::Deemphasis;
+use futuresdr::blocks::Preemphasis;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+use std::f32::consts::PI;
+
+const SAMPLE_RATE: f32 = 48000.0;
+const TAU: f32 = 75e-6;
+
+/// Gain of the block for a tone with the given frequency.
+fn gain(block: Block, freq: f32) -> Result<f32> {
+    let input: Vec<f32> = (0..48000)
+        .map(|i| (2.0 * PI * freq * i as f32 / SAMPLE_RATE).sin())
+        .collect();
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::<f32>::new(input));
+    let block = fg.add_block(block);
+    let snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+
+    fg.connect_stream(src, "out", block, "in")?;
+    fg.connect_stream(block, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let v = fg.kernel::<VectorSink<f32>>(snk).unwrap().items();
+    assert_eq!(v.len(), 48000);
+    let v = &v[24000..];
+    let power = v.iter().map(|x| x * x).sum::<f32>() / v.len() as f32;
+    Ok((2.0 * power).sqrt())
+}
+
+#[test]
+fn deemphasis() -> Result<()> {
+    for freq in [100.0, 1000.0, 5000.0] {
+        let g = gain(Deemphasis::new(SAMPLE_RATE, TAU), freq)?;
+        let expected = 1.0 / (1.0 + (2.0 * PI * freq * TAU).powi(2)).sqrt();
+        assert!((g - expected).abs() < 0.05 * expected, "{} {}", g, expected);
+    }
+    Ok(())
+}
+
+#[test]
+fn preemphasis() -> Result<()> {
+    for freq in [100.0, 1000.0, 5000.0] {
+        let g = gain(Preemphasis::new(SAMPLE_RATE, TAU), freq)?;
+        let expected = (1.0 + (2.0 * PI * freq * TAU).powi(2)).sqrt();
+        assert!((g - expected).abs() < 0.05 * expected, "{} {}", g, expected);
+    }
+    Ok(())
+}
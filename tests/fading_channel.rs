@@ -0,0 +1,78 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::FadingChannelBuilder;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+
+fn run(n: usize, block: Block) -> Result<Vec<Complex32>> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(VectorSource::<Complex32>::new(vec![
+        Complex32::new(1.0, 0.0);
+        n
+    ]));
+    let block = fg.add_block(block);
+    let snk = fg.add_block(VectorSinkBuilder::<Complex32>::new().build());
+
+    fg.connect_stream(src, "out", block, "in")?;
+    fg.connect_stream(block, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    Ok(fg
+        .kernel::<VectorSink<Complex32>>(snk)
+        .unwrap()
+        .items()
+        .clone())
+}
+
+#[test]
+fn rayleigh() -> Result<()> {
+    let n = 100_000;
+    let mut power = 0.0;
+    let mut deep_fades = 0;
+    for seed in 0..20 {
+        let h = run(n, FadingChannelBuilder::new(0.01).seed(seed).build())?;
+        power += h.iter().map(|h| h.norm_sqr()).sum::<f32>() / n as f32;
+        // Rayleigh: P(|h|^2 < 0.1) = 1 - exp(-0.1)
+        deep_fades += h.iter().filter(|h| h.norm_sqr() < 0.1).count();
+    }
+    power /= 20.0;
+    let p_fade = deep_fades as f32 / (20 * n) as f32;
+
+    assert!((power - 1.0).abs() < 0.15, "power {}", power);
+    assert!((p_fade - 0.095).abs() < 0.03, "fade probability {}", p_fade);
+
+    // slow fading changes little from sample to sample
+    let h = run(1000, FadingChannelBuilder::new(1e-4).seed(1).build())?;
+    assert!(h.windows(2).all(|w| (w[1] - w[0]).norm() < 0.01));
+
+    // seeded fading is reproducible
+    assert_eq!(
+        h,
+        run(1000, FadingChannelBuilder::new(1e-4).seed(1).build())?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn rician() -> Result<()> {
+    let n = 100_000;
+    let h = run(
+        n,
+        FadingChannelBuilder::new(0.01)
+            .rician(100.0)
+            .seed(3)
+            .build(),
+    )?;
+    let power = h.iter().map(|h| h.norm_sqr()).sum::<f32>() / n as f32;
+    assert!((power - 1.0).abs() < 0.05, "power {}", power);
+    assert!(h.iter().all(|h| h.norm() > 0.6 && h.norm() < 1.4));
+
+    Ok(())
+}
@@ -0,0 +1,354 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::FileFormat;
+use futuresdr::blocks::FileSink;
+use futuresdr::blocks::FileSinkBuilder;
+use futuresdr::blocks::FileSource;
+use futuresdr::blocks::FileSourceBuilder;
+use futuresdr::blocks::Head;
+use futuresdr::blocks::MmapSourceBuilder;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+use std::path::PathBuf;
+
+fn temp_file(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("futuresdr-test-{}-{}", std::process::id(), name))
+}
+
+fn write<T: Clone + std::fmt::Debug + Send + Sync + 'static>(v: Vec<T>, snk: Block) -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::<T>::new(v));
+    let snk = fg.add_block(snk);
+    fg.connect_stream(src, "out", snk, "in")?;
+    Runtime::new().run(fg)?;
+    Ok(())
+}
+
+fn read<T: Clone + std::fmt::Debug + Send + Sync + 'static>(
+    src: Block,
+    head: Option<usize>,
+) -> Result<Vec<T>> {
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(src);
+    let snk = fg.add_block(VectorSinkBuilder::<T>::new().build());
+    match head {
+        Some(n) => {
+            let head = fg.add_block(Head::<T>::new(n as u64));
+            fg.connect_stream(src, "out", head, "in")?;
+            fg.connect_stream(head, "out", snk, "in")?;
+        }
+        None => fg.connect_stream(src, "out", snk, "in")?,
+    }
+    fg = Runtime::new().run(fg)?;
+    Ok(fg.kernel::<VectorSink<T>>(snk).unwrap().items().clone())
+}
+
+#[test]
+fn file_native() -> Result<()> {
+    let path = temp_file("native.cf32");
+    let v: Vec<Complex32> = (0..10000)
+        .map(|i| Complex32::new(i as f32, -(i as f32)))
+        .collect();
+
+    write(
+        v.clone(),
+        FileSink::<Complex32>::new(path.to_str().unwrap()),
+    )?;
+    assert_eq!(std::fs::metadata(&path)?.len(), 80000);
+    let r = read::<Complex32>(
+        FileSource::<Complex32>::new(path.to_str().unwrap(), false),
+        None,
+    )?;
+    assert_eq!(r, v);
+
+    let r = read::<Complex32>(
+        FileSource::<Complex32>::new(path.to_str().unwrap(), true),
+        Some(25000),
+    )?;
+    assert_eq!(r[..10000], v[..]);
+    assert_eq!(r[20000..], v[..5000]);
+
+    std::fs::remove_file(path)?;
+    Ok(())
+}
+
+#[test]
+fn file_formats() -> Result<()> {
+    let path = temp_file("formats.cu8");
+    std::fs::write(&path, [0u8, 255, 127, 128, 64, 200])?;
+    let r = read::<Complex32>(
+        FileSourceBuilder::<Complex32>::new(path.to_str().unwrap())
+            .format(FileFormat::Cu8)
+            .build(),
+        None,
+    )?;
+    let expected = [
+        Complex32::new(-127.5 / 128.0, 127.5 / 128.0),
+        Complex32::new(-0.5 / 128.0, 0.5 / 128.0),
+        Complex32::new(-63.5 / 128.0, 72.5 / 128.0),
+    ];
+    assert_eq!(r, expected);
+
+    let v: Vec<Complex32> = (0..1000)
+        .map(|i| Complex32::from_polar(0.9, i as f32 * 0.1))
+        .chain(std::iter::once(Complex32::new(2.0, -2.0)))
+        .collect();
+    for (format, resolution) in [
+        (FileFormat::Cf32, 0.0),
+        (FileFormat::Ci16, 1.0 / 32768.0),
+        (FileFormat::Ci8, 1.0 / 128.0),
+        (FileFormat::Cu8, 1.0 / 128.0),
+    ] {
+        let path = temp_file(&format!("formats.{:?}", format));
+        write(
+            v.clone(),
+            FileSinkBuilder::<Complex32>::new(path.to_str().unwrap())
+                .format(format)
+                .build(),
+        )?;
+        assert_eq!(
+            std::fs::metadata(&path)?.len(),
+            (v.len() * format.item_size()) as u64
+        );
+        let r = read::<Complex32>(
+            FileSourceBuilder::<Complex32>::new(path.to_str().unwrap())
+                .format(format)
+                .build(),
+            None,
+        )?;
+        assert_eq!(r.len(), v.len());
+        for (a, b) in r[..1000].iter().zip(v.iter()) {
+            assert!((a - b).norm() <= resolution, "{:?}: {} != {}", format, a, b);
+        }
+        // saturated
+        if format != FileFormat::Cf32 {
+            assert!((r[1000].re - 1.0).abs() <= resolution);
+            assert!((r[1000].im + 1.0).abs() <= resolution);
+        }
+        std::fs::remove_file(path)?;
+    }
+
+    std::fs::remove_file(path)?;
+    Ok(())
+}
+
+#[test]
+fn file_offset_length() -> Result<()> {
+    let path = temp_file("offset.f32");
+    let v: Vec<f32> = (0..100).map(|i| i as f32).collect();
+    write(
+        v,
+        FileSinkBuilder::<f32>::new(path.to_str().unwrap())
+            .format(FileFormat::F32)
+            .length(50)
+            .build(),
+    )?;
+    assert_eq!(std::fs::metadata(&path)?.len(), 200);
+
+    let r = read::<f32>(
+        FileSourceBuilder::<f32>::new(path.to_str().unwrap())
+            .format(FileFormat::F32)
+            .offset(10)
+            .length(5)
+            .repeat(true)
+            .build(),
+        Some(12),
+    )?;
+    assert_eq!(
+        r,
+        vec![10.0, 11.0, 12.0, 13.0, 14.0, 10.0, 11.0, 12.0, 13.0, 14.0, 10.0, 11.0]
+    );
+
+    // real samples into a complex stream
+    let r = read::<Complex32>(
+        FileSourceBuilder::<Complex32>::new(path.to_str().unwrap())
+            .format(FileFormat::F32)
+            .offset(48)
+            .build(),
+        None,
+    )?;
+    assert_eq!(
+        r,
+        vec![Complex32::new(48.0, 0.0), Complex32::new(49.0, 0.0)]
+    );
+
+    // native samples, starting beyond the end of the file
+    let r = read::<f32>(
+        FileSourceBuilder::<f32>::new(path.to_str().unwrap())
+            .offset(100)
+            .repeat(true)
+            .build(),
+        None,
+    )?;
+    assert!(r.is_empty());
+
+    std::fs::remove_file(path)?;
+    Ok(())
+}
+
+#[test]
+fn mmap_source() -> Result<()> {
+    let path = temp_file("mmap.ci16");
+    let v: Vec<Complex32> = (0..10000)
+        .map(|i| Complex32::new((i % 100) as f32 / 128.0, -((i % 50) as f32) / 128.0))
+        .collect();
+
+    write(
+        v.clone(),
+        FileSinkBuilder::<Complex32>::new(path.to_str().unwrap())
+            .format(FileFormat::Ci16)
+            .build(),
+    )?;
+    let r = read::<Complex32>(
+        MmapSourceBuilder::<Complex32>::new(path.to_str().unwrap())
+            .format(FileFormat::Ci16)
+            .build(),
+        None,
+    )?;
+    assert_eq!(r, v);
+
+    // trailing partial chunk is dropped
+    let r = read::<Complex32>(
+        MmapSourceBuilder::<Complex32>::new(path.to_str().unwrap())
+            .format(FileFormat::Ci16)
+            .offset(100)
+            .chunk(1024)
+            .build(),
+        None,
+    )?;
+    assert_eq!(r.len(), 9 * 1024);
+    assert_eq!(r[..], v[100..100 + 9 * 1024]);
+
+    let r = read::<Complex32>(
+        MmapSourceBuilder::<Complex32>::new(path.to_str().unwrap())
+            .format(FileFormat::Ci16)
+            .length(3000)
+            .repeat(true)
+            .build(),
+        Some(7000),
+    )?;
+    assert_eq!(r[..3000], v[..3000]);
+    assert_eq!(r[6000..], v[..1000]);
+
+    std::fs::remove_file(path)?;
+    Ok(())
+}
+
+#[test]
+fn mmap_source_rate() -> Result<()> {
+    let path = temp_file("mmap-rate.f32");
+    let v: Vec<f32> = (0..2000).map(|i| i as f32).collect();
+    write(v.clone(), FileSink::<f32>::new(path.to_str().unwrap()))?;
+
+    let start = std::time::Instant::now();
+    let r = read::<f32>(
+        MmapSourceBuilder::<f32>::new(path.to_str().unwrap())
+            .rate(10_000.0)
+            .build(),
+        None,
+    )?;
+    assert_eq!(r, v);
+    assert!(start.elapsed() >= std::time::Duration::from_millis(150));
+
+    std::fs::remove_file(path)?;
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn direct_file_sink() -> Result<()> {
+    use futuresdr::blocks::DirectFileSinkBuilder;
+
+    let path = temp_file("direct.ci16");
+    let v: Vec<Complex32> = (0..10001)
+        .map(|i| Complex32::new((i % 100) as f32 / 128.0, -((i % 50) as f32) / 128.0))
+        .collect();
+
+    // small buffers, so that the queue runs full and the last buffer is partial
+    write(
+        v.clone(),
+        DirectFileSinkBuilder::<Complex32>::new(path.to_str().unwrap())
+            .format(FileFormat::Ci16)
+            .buffer_size(4096)
+            .queue_depth(2)
+            .writers(2)
+            .build(),
+    )?;
+    assert_eq!(std::fs::metadata(&path)?.len(), 40004);
+    let r = read::<Complex32>(
+        FileSourceBuilder::<Complex32>::new(path.to_str().unwrap())
+            .format(FileFormat::Ci16)
+            .build(),
+        None,
+    )?;
+    assert_eq!(r, v);
+
+    write(
+        v.clone(),
+        DirectFileSinkBuilder::<Complex32>::new(path.to_str().unwrap())
+            .length(5000)
+            .build(),
+    )?;
+    let r = read::<Complex32>(
+        FileSource::<Complex32>::new(path.to_str().unwrap(), false),
+        None,
+    )?;
+    assert_eq!(r[..], v[..5000]);
+
+    std::fs::remove_file(path)?;
+    Ok(())
+}
+
+#[test]
+fn rotating_file_sink() -> Result<()> {
+    use futuresdr::blocks::RotatingFileSinkBuilder;
+
+    let dir = temp_file("rotating");
+    let v: Vec<Complex32> = (0..10000)
+        .map(|i| Complex32::new(i as f32, -(i as f32)))
+        .collect();
+
+    // 2000 samples per file, of which the last two fit into the quota
+    write(
+        v.clone(),
+        RotatingFileSinkBuilder::<Complex32>::new(&dir)
+            .prefix("test")
+            .sample_rate(1e6)
+            .frequency(100e6)
+            .max_size(16000)
+            .quota(40000)
+            .sigmf(true)
+            .build(),
+    )?;
+
+    let mut names: Vec<String> = std::fs::read_dir(&dir)?
+        .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    names.sort();
+    assert_eq!(names.len(), 4);
+    assert!(names
+        .iter()
+        .all(|n| n.starts_with("test_") && n.contains("_100000000Hz.sigmf-")));
+    assert!(names[0].ends_with(".sigmf-data") && names[1].ends_with(".sigmf-meta"));
+
+    let meta: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(dir.join(&names[1]))?)?;
+    assert_eq!(meta["global"]["core:datatype"], "cf32_le");
+    assert_eq!(meta["captures"][0]["core:frequency"], 100e6);
+
+    let mut r = Vec::new();
+    for n in names.iter().filter(|n| n.ends_with(".sigmf-data")) {
+        r.extend(read::<Complex32>(
+            FileSource::<Complex32>::new(dir.join(n).to_str().unwrap(), false),
+            None,
+        )?);
+    }
+    assert_eq!(r[..], v[6000..]);
+
+    std::fs::remove_dir_all(dir)?;
+    Ok(())
+}
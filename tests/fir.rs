@@ -1,8 +1,12 @@
 use futuresdr::anyhow::Result;
 use futuresdr::blocks::FirBuilder;
+use futuresdr::blocks::RrcFilterBuilder;
 use futuresdr::blocks::VectorSink;
 use futuresdr::blocks::VectorSinkBuilder;
 use futuresdr::blocks::VectorSource;
+use futuresdr::firdes;
+use futuresdr::futuredsp::windows;
+use futuresdr::num_complex::Complex32;
 use futuresdr::runtime::Flowgraph;
 use futuresdr::runtime::Runtime;
 
@@ -33,3 +37,104 @@ fn fir_f32() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn fir_decimating_f32() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let orig: Vec<f32> = (1..=9).map(|x| x as f32).collect();
+    let taps: [f32; 3] = [1.0, 1.0, 1.0];
+
+    let src = fg.add_block(VectorSource::<f32>::new(orig));
+    let fir = fg.add_block(FirBuilder::new_decimating::<f32, f32, f32, _>(2, taps));
+    let snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+
+    fg.connect_stream(src, "out", fir, "in")?;
+    fg.connect_stream(fir, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<VectorSink<f32>>(snk).unwrap();
+    let v = snk.items();
+
+    let res = vec![6.0f32, 12.0, 18.0];
+    assert_eq!(v.len(), res.len());
+    for (have, want) in v.iter().zip(res) {
+        assert!((have - want).abs() < f32::EPSILON);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn fir_lowpass_c32() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let n = 4096;
+    let orig: Vec<Complex32> = (0..n)
+        .map(|i| Complex32::from_polar(1.0, 2.0 * std::f32::consts::PI * 0.4 * i as f32))
+        .collect();
+    let taps = firdes::lowpass::<f32>(0.1, &windows::hamming(64, false));
+
+    let src = fg.add_block(VectorSource::<Complex32>::new(orig));
+    let fir = fg.add_block(FirBuilder::new::<Complex32, Complex32, f32, _>(taps));
+    let snk = fg.add_block(VectorSinkBuilder::<Complex32>::new().build());
+
+    fg.connect_stream(src, "out", fir, "in")?;
+    fg.connect_stream(fir, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<VectorSink<Complex32>>(snk).unwrap();
+    let v = snk.items();
+
+    assert_eq!(v.len(), n - 63);
+    // stop band
+    for x in v {
+        assert!(x.norm() < 0.01);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn rrc_pulse_shaping_and_matched_filter() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let sps = 4;
+    let span = 10;
+    let symbols: Vec<f32> = (0..200)
+        .map(|i| if (i * 7 + i / 3) % 5 < 2 { 1.0 } else { -1.0 })
+        .collect();
+
+    let src = fg.add_block(VectorSource::<f32>::new(symbols.clone()));
+    let tx = fg.add_block(
+        RrcFilterBuilder::new(sps)
+            .rolloff(0.35)
+            .span(span)
+            .interpolate()
+            .build::<f32>(),
+    );
+    let rx = fg.add_block(
+        RrcFilterBuilder::new(sps)
+            .rolloff(0.35)
+            .span(span)
+            .build::<f32>(),
+    );
+    let snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+
+    fg.connect_stream(src, "out", tx, "in")?;
+    fg.connect_stream(tx, "out", rx, "in")?;
+    fg.connect_stream(rx, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let v = fg.kernel::<VectorSink<f32>>(snk).unwrap().items();
+    let rx_symbols: Vec<f32> = v.iter().copied().step_by(sps).collect();
+    assert!(rx_symbols.len() > 150);
+    for (have, want) in rx_symbols.iter().zip(symbols.iter().skip(span)) {
+        assert!((have - want).abs() < 0.1, "have {have}, want {want}");
+    }
+
+    Ok(())
+}
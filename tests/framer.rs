@@ -0,0 +1,136 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::blocks::CorrelateAccessCodeBuilder;
+use futuresdr::blocks::Crc;
+use futuresdr::blocks::FramerBuilder;
+use futuresdr::blocks::MessagePipe;
+use futuresdr::blocks::NullSink;
+use futuresdr::blocks::PduToStream;
+use futuresdr::blocks::Whitening;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::futures::StreamExt;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+const SYNC: [u8; 16] = [1, 0, 1, 1, 0, 1, 0, 0, 1, 1, 1, 0, 0, 1, 0, 0];
+
+fn pack(bits: &[u8]) -> Vec<u8> {
+    bits.chunks(8)
+        .map(|c| c.iter().fold(0, |acc, b| (acc << 1) | b))
+        .collect()
+}
+
+#[test]
+fn pn9_sequence() {
+    let mut v = vec![0u8; 8];
+    Whitening::pn9().apply(&mut v);
+    assert_eq!(v, vec![0xff, 0xe1, 0x1d, 0x9a, 0xed, 0x85, 0x33, 0x24]);
+
+    let data: Vec<u8> = (0..100).collect();
+    let mut v = data.clone();
+    Whitening::pn9().apply(&mut v);
+    Whitening::pn9().apply(&mut v);
+    assert_eq!(v, data);
+}
+
+#[test]
+fn framer_layout() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let (tx, rx) = mpsc::channel(10);
+
+    let framer = fg.add_block(
+        FramerBuilder::new(&SYNC)
+            .preamble(&[1, 0, 1, 0])
+            .length_field(1)
+            .crc(Crc::crc16_ccitt())
+            .build(),
+    );
+    let pipe = fg.add_block(MessagePipe::new(tx));
+    fg.connect_message(framer, "out", pipe, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+    block_on(async {
+        handle
+            .call(framer, "in", Pmt::Blob(b"123456789".to_vec()))
+            .await?;
+        // too long for the length field
+        handle.call(framer, "in", Pmt::Blob(vec![0; 256])).await?;
+        handle.terminate().await
+    })?;
+    block_on(task)?;
+
+    let frames: Vec<Pmt> = block_on(rx.collect());
+    assert_eq!(frames.len(), 1);
+    let bits = match &frames[0] {
+        Pmt::Blob(b) => b.clone(),
+        _ => panic!("wrong PMT type"),
+    };
+    assert_eq!(bits.len(), 4 + 16 + 8 * (1 + 9 + 2));
+    assert_eq!(bits[..4], [1, 0, 1, 0]);
+    assert_eq!(bits[4..20], SYNC);
+    let mut expected = vec![9];
+    expected.extend_from_slice(b"123456789");
+    // CRC-16/CCITT-FALSE check value
+    expected.extend_from_slice(&[0x29, 0xb1]);
+    assert_eq!(pack(&bits[20..]), expected);
+
+    Ok(())
+}
+
+#[test]
+fn framer_correlate_loopback() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let (tx, mut rx) = mpsc::channel(10);
+
+    let crc = Crc::crc32();
+    let framer = fg.add_block(
+        FramerBuilder::new(&SYNC)
+            .crc(crc)
+            .whitening(Whitening::pn9())
+            .build(),
+    );
+    let p2s = fg.add_block(PduToStream::<u8>::new());
+    let sync = fg.add_block(
+        CorrelateAccessCodeBuilder::new(&SYNC)
+            .frame_len(8 * (2 + 20 + 4))
+            .build(),
+    );
+    let snk = fg.add_block(NullSink::<u8>::new());
+    let pipe = fg.add_block(MessagePipe::new(tx));
+    fg.connect_message(framer, "out", p2s, "in")?;
+    fg.connect_stream(p2s, "out", sync, "in")?;
+    fg.connect_stream(sync, "out", snk, "in")?;
+    fg.connect_message(sync, "frames", pipe, "in")?;
+
+    let payloads: Vec<Vec<u8>> = (0..5u8).map(|i| vec![i; 20]).collect();
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+    let received = block_on(async {
+        for p in payloads.iter() {
+            handle.call(framer, "in", Pmt::Blob(p.clone())).await?;
+        }
+        let mut received = Vec::new();
+        for _ in 0..payloads.len() {
+            received.push(rx.next().await.unwrap());
+        }
+        handle.terminate().await?;
+        Ok::<_, futuresdr::anyhow::Error>(received)
+    })?;
+    block_on(task)?;
+
+    for (frame, payload) in received.iter().zip(payloads.iter()) {
+        let mut bytes = match frame {
+            Pmt::Blob(b) => pack(b),
+            _ => panic!("wrong PMT type"),
+        };
+        Whitening::pn9().apply(&mut bytes);
+        assert_eq!(bytes[..2], [0, 20]);
+        assert_eq!(bytes[2..22], payload[..]);
+        assert_eq!(bytes[22..], crc.encode(payload)[..]);
+    }
+
+    Ok(())
+}
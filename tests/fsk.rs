@@ -0,0 +1,98 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::AfskDemodBuilder;
+use futuresdr::blocks::AfskModBuilder;
+use futuresdr::blocks::ChannelModelBuilder;
+use futuresdr::blocks::FskDemodBuilder;
+use futuresdr::blocks::FskMod;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+
+fn symbols(n: usize, levels: u8) -> Vec<u8> {
+    let mut state = 0x1234_5678u32;
+    (0..n)
+        .map(|_| {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            (state >> 16) as u8 % levels
+        })
+        .collect()
+}
+
+fn run(input: Vec<u8>, blocks: Vec<Block>) -> Result<Vec<u8>> {
+    let mut fg = Flowgraph::new();
+
+    let mut last = fg.add_block(VectorSource::<u8>::new(input));
+    for b in blocks {
+        let b = fg.add_block(b);
+        fg.connect_stream(last, "out", b, "in")?;
+        last = b;
+    }
+    let snk = fg.add_block(VectorSinkBuilder::<u8>::new().build());
+    fg.connect_stream(last, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    Ok(fg.kernel::<VectorSink<u8>>(snk).unwrap().items().clone())
+}
+
+/// After the clock recovery converged, the symbols are recovered with a fixed delay.
+fn assert_recovered(input: &[u8], output: &[u8]) {
+    let reference = &input[100..input.len() - 100];
+    assert!(
+        output.windows(reference.len()).any(|w| w == reference),
+        "symbols not recovered"
+    );
+}
+
+#[test]
+fn fsk2_loopback() -> Result<()> {
+    let input = symbols(2000, 2);
+    let output = run(
+        input.clone(),
+        vec![
+            FskMod::new(2, 8.0, 0.0625),
+            ChannelModelBuilder::new()
+                .snr(10.0)
+                .frequency_offset(0.005)
+                .seed(1)
+                .build(),
+            FskDemodBuilder::new(2, 8.0, 0.0625).build(),
+        ],
+    )?;
+    assert_recovered(&input, &output);
+    Ok(())
+}
+
+#[test]
+fn fsk4_loopback() -> Result<()> {
+    let input = symbols(2000, 4);
+    let output = run(
+        input.clone(),
+        vec![
+            FskMod::new(4, 16.0, 0.09375),
+            ChannelModelBuilder::new().snr(15.0).seed(2).build(),
+            FskDemodBuilder::new(4, 16.0, 0.09375).build(),
+        ],
+    )?;
+    assert_recovered(&input, &output);
+    Ok(())
+}
+
+#[test]
+fn afsk_bell202_loopback() -> Result<()> {
+    for sample_rate in [48000.0, 44100.0] {
+        let input = symbols(2000, 2);
+        let output = run(
+            input.clone(),
+            vec![
+                AfskModBuilder::new(sample_rate).build(),
+                AfskDemodBuilder::new(sample_rate).build(),
+            ],
+        )?;
+        assert_recovered(&input, &output);
+    }
+    Ok(())
+}
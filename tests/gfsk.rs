@@ -0,0 +1,83 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::ChannelModelBuilder;
+use futuresdr::blocks::GfskDemodBuilder;
+use futuresdr::blocks::GfskModBuilder;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+
+fn bits(n: usize) -> Vec<u8> {
+    let mut state = 0x1234_5678u32;
+    (0..n)
+        .map(|_| {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            (state >> 16) as u8 & 1
+        })
+        .collect()
+}
+
+#[test]
+fn gfsk_mod_deviation() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(VectorSource::<u8>::new(vec![1; 20]));
+    let gmsk = fg.add_block(GfskModBuilder::new(8).build());
+    let snk = fg.add_block(VectorSinkBuilder::<Complex32>::new().build());
+
+    fg.connect_stream(src, "out", gmsk, "in")?;
+    fg.connect_stream(gmsk, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let v = fg.kernel::<VectorSink<Complex32>>(snk).unwrap().items();
+    assert_eq!(v.len(), 160);
+    for x in v.iter() {
+        assert!((x.norm() - 1.0).abs() < 1e-4);
+    }
+    // a phase change of pi/2 per symbol for modulation index 0.5
+    for w in v[100..].windows(2) {
+        let f = (w[1] * w[0].conj()).arg();
+        assert!((f - std::f32::consts::PI / 16.0).abs() < 1e-3, "{f}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn gfsk_loopback() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let input = bits(2000);
+    let src = fg.add_block(VectorSource::<u8>::new(input.clone()));
+    let gmsk = fg.add_block(GfskModBuilder::new(8).build());
+    let channel = fg.add_block(
+        ChannelModelBuilder::new()
+            .snr(15.0)
+            .frequency_offset(0.002)
+            .phase_offset(1.0)
+            .seed(3)
+            .build(),
+    );
+    let demod = fg.add_block(GfskDemodBuilder::new(8.0).build());
+    let snk = fg.add_block(VectorSinkBuilder::<u8>::new().build());
+
+    fg.connect_stream(src, "out", gmsk, "in")?;
+    fg.connect_stream(gmsk, "out", channel, "in")?;
+    fg.connect_stream(channel, "out", demod, "in")?;
+    fg.connect_stream(demod, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let v = fg.kernel::<VectorSink<u8>>(snk).unwrap().items();
+    // after the clock recovery converged, the bits are recovered with a fixed delay
+    let reference = &input[100..1900];
+    assert!(
+        v.windows(reference.len()).any(|w| w == reference),
+        "bits not recovered"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::Apply;
+use futuresdr::blocks::FirBuilder;
+use futuresdr::blocks::Head;
+use futuresdr::blocks::QuadratureDemod;
+use futuresdr::futuredsp::firdes;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::testing::golden::*;
+use futuresdr::runtime::Flowgraph;
+use std::path::PathBuf;
+
+fn temp_file(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("futuresdr-test-{}-{}", std::process::id(), name))
+}
+
+fn data(path: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(path)
+}
+
+fn lowpass_demod(fg: &mut Flowgraph) -> Result<(usize, usize)> {
+    let taps = firdes::kaiser::lowpass::<f32>(0.2, 0.05, 0.01);
+    let fir = fg.add_block(FirBuilder::new::<Complex32, Complex32, f32, _>(taps));
+    let demod = fg.add_block(QuadratureDemod::new(1.0));
+    fg.connect_stream(fir, "out", demod, "in")?;
+    Ok((fir, demod))
+}
+
+#[test]
+fn lowpass_demod_golden() -> Result<()> {
+    GoldenTest::new(
+        data("examples/wlan/data/sync.cf32"),
+        data("tests/golden/sync_lowpass_demod.f32"),
+    )
+    .tolerance(1e-4)
+    .run::<Complex32, f32>(lowpass_demod)
+}
+
+#[test]
+fn samples_roundtrip() -> Result<()> {
+    let path = temp_file("golden-roundtrip.cf32");
+    let samples: Vec<Complex32> = (0..100).map(|i| Complex32::new(i as f32, 1.0)).collect();
+    write_samples(&path, &samples)?;
+    assert_eq!(read_samples::<Complex32>(&path)?, samples);
+    assert!(read_samples::<[u8; 3]>(&path).is_err());
+    std::fs::remove_file(path)?;
+    Ok(())
+}
+
+#[test]
+fn bless_and_compare() -> Result<()> {
+    let input = temp_file("golden-input.f32");
+    let golden = temp_file("golden-output.f32");
+    let actual = temp_file("golden-output.f32.actual");
+    write_samples(&input, &(0..1000).map(|i| i as f32).collect::<Vec<f32>>())?;
+
+    let test = GoldenTest::new(&input, &golden).tolerance(1e-3);
+
+    // missing golden file
+    let e = test
+        .clone()
+        .bless(false)
+        .run_block::<f32, f32>(Apply::new(|x: &f32| x * 2.0))
+        .unwrap_err();
+    assert!(format!("{}", e).contains(BLESS_ENV));
+
+    test.clone()
+        .bless(true)
+        .run_block::<f32, f32>(Apply::new(|x: &f32| x * 2.0))?;
+    test.clone()
+        .bless(false)
+        .run_block::<f32, f32>(Apply::new(|x: &f32| x * 2.0 + 1e-4))?;
+    assert!(!actual.exists());
+
+    // deviation
+    let e = test
+        .clone()
+        .bless(false)
+        .run_block::<f32, f32>(Apply::new(
+            |x: &f32| if *x == 500.0 { 0.0 } else { x * 2.0 },
+        ))
+        .unwrap_err();
+    let msg = format!("{}", e);
+    assert!(msg.contains("1 samples differ"), "{}", msg);
+    assert!(msg.contains("index 500"), "{}", msg);
+    assert_eq!(read_samples::<f32>(&actual)?[500], 0.0);
+
+    // different length
+    let e = test
+        .clone()
+        .bless(false)
+        .run_block::<f32, f32>(Head::<f32>::new(2))
+        .unwrap_err();
+    assert!(format!("{}", e).contains("got 2 samples, expected 1000"));
+
+    for f in [input, golden, actual] {
+        std::fs::remove_file(f)?;
+    }
+    Ok(())
+}
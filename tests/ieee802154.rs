@@ -0,0 +1,86 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::blocks::ChannelModelBuilder;
+use futuresdr::blocks::Crc;
+use futuresdr::blocks::Delay;
+use futuresdr::blocks::Ieee802154Demod;
+use futuresdr::blocks::Ieee802154FrameSync;
+use futuresdr::blocks::Ieee802154Mod;
+use futuresdr::blocks::MessagePipe;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::futures::StreamExt;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+fn psdu(len: usize, seed: u32) -> Vec<u8> {
+    let mut state = seed;
+    let mut v: Vec<u8> = (0..len - 2)
+        .map(|_| {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            (state >> 16) as u8
+        })
+        .collect();
+    v.extend(Crc::crc16_kermit().encode(&v));
+    v
+}
+
+fn loopback(samples_per_chip: usize, snr: f32, frequency_offset: f32) -> Result<()> {
+    let psdus = [psdu(10, 1), psdu(127, 2), psdu(3, 3), psdu(50, 4)];
+
+    let mut fg = Flowgraph::new();
+    let (tx, mut rx) = mpsc::channel(10);
+
+    let modulator = fg.add_block(Ieee802154Mod::new(samples_per_chip));
+    let delay = fg.add_block(Delay::<Complex32>::new(1000));
+    let channel = fg.add_block(
+        ChannelModelBuilder::new()
+            .snr(snr)
+            .frequency_offset(frequency_offset)
+            .seed(1)
+            .build(),
+    );
+    let demod = fg.add_block(Ieee802154Demod::new(samples_per_chip));
+    let sync = fg.add_block(Ieee802154FrameSync::new(5));
+    let pipe = fg.add_block(MessagePipe::new(tx));
+
+    fg.connect_stream(modulator, "out", delay, "in")?;
+    fg.connect_stream(delay, "out", channel, "in")?;
+    fg.connect_stream(channel, "out", demod, "in")?;
+    fg.connect_stream(demod, "out", sync, "in")?;
+    fg.connect_message(sync, "out", pipe, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+    block_on(async {
+        for p in psdus.iter() {
+            handle.call(modulator, "in", Pmt::Blob(p.clone())).await?;
+        }
+        // flush the clock recovery
+        handle.call(modulator, "in", Pmt::Blob(vec![0; 10])).await?;
+        for p in psdus.iter() {
+            let received = rx.next().await.unwrap();
+            assert_eq!(received, Pmt::Blob(p.clone()));
+            if let Pmt::Blob(v) = received {
+                assert_eq!(Crc::crc16_kermit().checksum(&v), 0);
+            }
+        }
+        handle.terminate().await
+    })?;
+    block_on(task)?;
+
+    Ok(())
+}
+
+#[test]
+fn ieee802154_loopback() -> Result<()> {
+    loopback(2, 100.0, 0.0)
+}
+
+#[test]
+fn ieee802154_channel() -> Result<()> {
+    // 40 kHz frequency offset at 4 MHz
+    loopback(2, 5.0, 0.01)?;
+    loopback(4, 10.0, -0.002)
+}
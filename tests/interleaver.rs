@@ -0,0 +1,73 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::BlockDeinterleaver;
+use futuresdr::blocks::BlockInterleaver;
+use futuresdr::blocks::ConvolutionalDeinterleaver;
+use futuresdr::blocks::ConvolutionalInterleaver;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+
+fn run(input: Vec<u8>, a: Block, b: Block) -> Result<(Vec<u8>, Vec<u8>)> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(VectorSource::<u8>::new(input));
+    let a = fg.add_block(a);
+    let b = fg.add_block(b);
+    let snk_a = fg.add_block(VectorSinkBuilder::<u8>::new().build());
+    let snk_b = fg.add_block(VectorSinkBuilder::<u8>::new().build());
+
+    fg.connect_stream(src, "out", a, "in")?;
+    fg.connect_stream(a, "out", snk_a, "in")?;
+    fg.connect_stream(a, "out", b, "in")?;
+    fg.connect_stream(b, "out", snk_b, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    Ok((
+        fg.kernel::<VectorSink<u8>>(snk_a).unwrap().items().clone(),
+        fg.kernel::<VectorSink<u8>>(snk_b).unwrap().items().clone(),
+    ))
+}
+
+#[test]
+fn block_interleaver() -> Result<()> {
+    let input: Vec<u8> = (0..=255).cycle().take(6 * 1000 + 5).collect();
+    let (interleaved, output) = run(
+        input.clone(),
+        BlockInterleaver::<u8>::new(2, 3),
+        BlockDeinterleaver::<u8>::new(2, 3),
+    )?;
+
+    assert_eq!(interleaved.len(), 6000);
+    assert_eq!(interleaved[..6], [0, 3, 1, 4, 2, 5]);
+    assert_eq!(output, input[..6000]);
+
+    Ok(())
+}
+
+#[test]
+fn convolutional_interleaver() -> Result<()> {
+    let (branches, delay) = (12, 17);
+    let input: Vec<u8> = (0..100_000).map(|_| rand::random::<u8>() | 1).collect();
+    let (interleaved, output) = run(
+        input.clone(),
+        ConvolutionalInterleaver::<u8>::new(branches, delay),
+        ConvolutionalDeinterleaver::<u8>::new(branches, delay),
+    )?;
+
+    assert_eq!(interleaved.len(), input.len());
+    // first branch has no delay
+    for k in (0..1000).step_by(branches) {
+        assert_eq!(interleaved[k], input[k]);
+    }
+    assert_eq!(interleaved[1], 0);
+
+    let d = branches * (branches - 1) * delay;
+    assert!(output[..d].iter().all(|x| *x == 0));
+    assert_eq!(output[d..], input[..input.len() - d]);
+
+    Ok(())
+}
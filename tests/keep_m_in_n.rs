@@ -0,0 +1,111 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_trait::async_trait;
+use futuresdr::blocks::KeepMInNBuilder;
+use futuresdr::blocks::KeepOneInN;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::runtime::{
+    Block, BlockMeta, BlockMetaBuilder, Flowgraph, Kernel, MessageIo, MessageIoBuilder, Runtime,
+    StreamIo, StreamIoBuilder, Tag, WorkIo,
+};
+
+/// Output a vector, adding string tags at the given indices.
+struct TaggedSource {
+    items: Vec<u32>,
+    tags: Vec<(usize, String)>,
+    n: usize,
+}
+
+impl TaggedSource {
+    fn block(items: Vec<u32>, tags: Vec<(usize, String)>) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("TaggedSource").build(),
+            StreamIoBuilder::new().add_output::<u32>("out").build(),
+            MessageIoBuilder::<Self>::new().build(),
+            TaggedSource { items, tags, n: 0 },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for TaggedSource {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let o = sio.output(0).slice::<u32>();
+        let m = std::cmp::min(o.len(), self.items.len() - self.n);
+        o[..m].copy_from_slice(&self.items[self.n..self.n + m]);
+        for (i, t) in self.tags.iter() {
+            if *i >= self.n && *i < self.n + m {
+                sio.output(0).add_tag(i - self.n, Tag::String(t.clone()));
+            }
+        }
+        self.n += m;
+        sio.output(0).produce(m);
+
+        if self.n == self.items.len() {
+            io.finished = true;
+        }
+        Ok(())
+    }
+}
+
+fn run(src: Block, block: Block) -> Result<Vec<u32>> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(src);
+    let block = fg.add_block(block);
+    let snk = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+
+    fg.connect_stream(src, "out", block, "in")?;
+    fg.connect_stream(block, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    Ok(fg.kernel::<VectorSink<u32>>(snk).unwrap().items().clone())
+}
+
+#[test]
+fn keep_one_in_n() -> Result<()> {
+    let v = run(
+        VectorSource::<u32>::new((0..10_001).collect()),
+        KeepOneInN::<u32>::new(10),
+    )?;
+    assert!(v.into_iter().eq((0..10_001).step_by(10)));
+    Ok(())
+}
+
+#[test]
+fn keep_m_in_n() -> Result<()> {
+    let v = run(
+        VectorSource::<u32>::new((0..10_000).collect()),
+        KeepMInNBuilder::<u32>::new(3, 10).offset(2).build(),
+    )?;
+    assert!(v
+        .into_iter()
+        .eq((0..10_000).filter(|x| (2..5).contains(&(x % 10)))));
+    Ok(())
+}
+
+#[test]
+fn keep_m_in_n_aligned() -> Result<()> {
+    let tags = vec![(7, "start".to_string()), (55, "start".to_string())];
+    let v = run(
+        TaggedSource::block((0..100).collect(), tags),
+        KeepMInNBuilder::<u32>::new(2, 10)
+            .offset(1)
+            .align_tag("start")
+            .build(),
+    )?;
+
+    let mut expected = vec![1, 2];
+    expected.extend([7, 8, 17, 18, 27, 28, 37, 38, 47, 48]);
+    expected.extend([55, 56, 65, 66, 75, 76, 85, 86, 95, 96]);
+    assert_eq!(v, expected);
+    Ok(())
+}
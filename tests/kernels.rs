@@ -0,0 +1,112 @@
+use futuresdr::futuredsp::fir::NonResamplingFirKernel;
+use futuresdr::futuredsp::UnaryKernel;
+use futuresdr::kernels;
+use futuresdr::kernels::FirKernel;
+use futuresdr::num_complex::Complex32;
+
+fn complex(n: usize) -> Vec<Complex32> {
+    (0..n)
+        .map(|_| Complex32::new(rand::random::<f32>() - 0.5, rand::random::<f32>() - 0.5))
+        .collect()
+}
+
+fn real(n: usize) -> Vec<f32> {
+    (0..n).map(|_| rand::random::<f32>() - 0.5).collect()
+}
+
+#[test]
+fn elementwise() {
+    // lengths with and without remainders
+    for n in [0, 1, 7, 8, 9, 31, 1000] {
+        let a = complex(n);
+        let b = complex(n);
+
+        let mut out = vec![Complex32::new(0.0, 0.0); n];
+        kernels::multiply(&a, &b, &mut out);
+        for k in 0..n {
+            assert!((out[k] - a[k] * b[k]).norm() < 1e-6);
+        }
+
+        let mut mag = vec![0.0; n];
+        kernels::magnitude_squared(&a, &mut mag);
+        for k in 0..n {
+            assert_eq!(mag[k], a[k].norm_sqr());
+        }
+        kernels::magnitude(&a, &mut mag);
+        for k in 0..n {
+            assert!((mag[k] - a[k].norm()).abs() < 1e-6);
+        }
+    }
+}
+
+#[test]
+fn dot_products() {
+    for n in [0, 1, 5, 16, 17, 100, 1023] {
+        let a = real(n);
+        let b = real(n);
+        let x = complex(n);
+
+        let expected: f32 = a.iter().zip(b.iter()).map(|(a, b)| a * b).sum();
+        assert!((kernels::dot(&a, &b) - expected).abs() < 1e-4);
+
+        let expected: Complex32 = x.iter().zip(a.iter()).map(|(x, t)| x * t).sum();
+        assert!((kernels::dot_complex(&x, &a) - expected).norm() < 1e-4);
+    }
+
+    // common length
+    assert_eq!(kernels::dot(&[1.0, 2.0, 3.0], &[4.0, 5.0]), 14.0);
+}
+
+#[test]
+fn conversion() {
+    let x: Vec<i16> = (0..1001).map(|_| rand::random()).collect();
+    let mut y = vec![0.0; x.len()];
+    kernels::i16_to_f32(&x, 1.0 / 32768.0, 0.0, &mut y);
+    for (x, y) in x.iter().zip(y.iter()) {
+        assert_eq!(*y, *x as f32 / 32768.0);
+    }
+
+    let x: Vec<u8> = (0..1001).map(|_| rand::random()).collect();
+    let mut y = vec![0.0; x.len()];
+    kernels::u8_to_f32(&x, 1.0 / 127.5, 127.5, &mut y);
+    for (x, y) in x.iter().zip(y.iter()) {
+        assert_eq!(*y, (*x as f32 - 127.5) * (1.0 / 127.5));
+    }
+
+    let x = [
+        0.0, 0.4, 0.5, -0.5, 1.5, 2.5, -2.5, -2.4, 1e6, -1e6, 32767.4, -32768.6, 100.49, -7.5, 3.0,
+        -3.0, 0.49999997,
+    ];
+    let mut y = vec![0; x.len()];
+    kernels::f32_to_i16(&x, 1.0, 0.0, &mut y);
+    let expected: Vec<i16> = x.iter().map(|x| x.round() as i16).collect();
+    assert_eq!(y, expected);
+}
+
+#[test]
+fn fir_matches_futuredsp() {
+    for taps in [1, 3, 16, 63] {
+        let t = real(taps);
+
+        let x = complex(500);
+        let mut o1 = vec![Complex32::new(0.0, 0.0); 400];
+        let mut o2 = o1.clone();
+        let r1 = FirKernel::<Complex32>::new(t.clone()).work(&x, &mut o1);
+        let r2 = NonResamplingFirKernel::<Complex32, Complex32, _, f32>::new(t.clone())
+            .work(&x, &mut o2);
+        assert_eq!(r1, r2);
+        for (a, b) in o1.iter().zip(o2.iter()) {
+            assert!((a - b).norm() < 1e-5);
+        }
+
+        let x = real(300);
+        let mut o1 = vec![0.0; 400];
+        let mut o2 = o1.clone();
+        let r1 = FirKernel::<f32>::new(t.clone()).work(&x, &mut o1);
+        let r2 = NonResamplingFirKernel::<f32, f32, _, f32>::new(t).work(&x, &mut o2);
+        assert_eq!(r1, r2);
+        for (a, b) in o1.iter().zip(o2.iter()) {
+            assert!((a - b).abs() < 1e-5);
+        }
+    }
+}
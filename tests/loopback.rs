@@ -0,0 +1,111 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::ChannelSink;
+use futuresdr::blocks::ChannelSource;
+use futuresdr::blocks::LoopbackBuilder;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+
+fn run_loopback(builder: LoopbackBuilder, input: Vec<Complex32>) -> Result<Vec<Complex32>> {
+    let mut fg = Flowgraph::new();
+    let (tx, rx) = builder.build();
+
+    let src = fg.add_block(VectorSource::<Complex32>::new(input));
+    let tx = fg.add_block(tx);
+    let rx = fg.add_block(rx);
+    let snk = fg.add_block(VectorSinkBuilder::<Complex32>::new().build());
+
+    fg.connect_stream(src, "out", tx, "in")?;
+    fg.connect_stream(rx, "out", snk, "in")?;
+
+    let fg = Runtime::new().run(fg)?;
+    Ok(fg
+        .kernel::<VectorSink<Complex32>>(snk)
+        .unwrap()
+        .items()
+        .clone())
+}
+
+fn ramp(n: usize) -> Vec<Complex32> {
+    (0..n)
+        .map(|i| Complex32::new(i as f32, -(i as f32)))
+        .collect()
+}
+
+#[test]
+fn channel_sink_source() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let (tx, rx) = mpsc::channel(4);
+
+    let orig: Vec<u32> = (0..100_000).collect();
+    let src = fg.add_block(VectorSource::<u32>::new(orig.clone()));
+    let cs = fg.add_block(ChannelSink::<u32>::new(tx));
+    let cr = fg.add_block(ChannelSource::<u32>::new(rx));
+    let snk = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+
+    fg.connect_stream(src, "out", cs, "in")?;
+    fg.connect_stream(cr, "out", snk, "in")?;
+
+    let fg = Runtime::new().run(fg)?;
+    let items = fg.kernel::<VectorSink<u32>>(snk).unwrap().items();
+    assert_eq!(items, &orig);
+    Ok(())
+}
+
+#[test]
+fn loopback_ideal() -> Result<()> {
+    let input = ramp(10_000);
+    let output = run_loopback(LoopbackBuilder::new(), input.clone())?;
+    assert_eq!(output, input);
+    Ok(())
+}
+
+#[test]
+fn loopback_delay() -> Result<()> {
+    let input = ramp(1000);
+    let output = run_loopback(LoopbackBuilder::new().delay(123), input.clone())?;
+    assert_eq!(output.len(), 1123);
+    assert!(output[..123].iter().all(|x| *x == Complex32::new(0.0, 0.0)));
+    assert_eq!(&output[123..], &input[..]);
+    Ok(())
+}
+
+#[test]
+fn loopback_noise() -> Result<()> {
+    let input = vec![Complex32::new(1.0, 0.0); 20_000];
+    let builder = || LoopbackBuilder::new().snr(10.0).seed(7);
+    let output = run_loopback(builder(), input.clone())?;
+    assert_eq!(output, run_loopback(builder(), input.clone())?);
+
+    let noise: f32 = output
+        .iter()
+        .zip(input.iter())
+        .map(|(o, i)| (o - i).norm_sqr())
+        .sum::<f32>()
+        / input.len() as f32;
+    assert!((noise - 0.1).abs() < 0.01, "noise power {}", noise);
+    Ok(())
+}
+
+#[test]
+fn loopback_resample() -> Result<()> {
+    let input = ramp(10_001);
+    let output = run_loopback(LoopbackBuilder::new().resample(2.0), input)?;
+    assert_eq!(output.len(), 20_001);
+    for (i, o) in output.iter().enumerate() {
+        let x = i as f32 / 2.0;
+        assert!((o - Complex32::new(x, -x)).norm() < 1e-3, "{} {}", i, o);
+    }
+
+    let output = run_loopback(LoopbackBuilder::new().resample(0.5), ramp(10_001))?;
+    assert_eq!(output.len(), 5001);
+    for (i, o) in output.iter().enumerate() {
+        let x = i as f32 * 2.0;
+        assert!((o - Complex32::new(x, -x)).norm() < 1e-3, "{} {}", i, o);
+    }
+    Ok(())
+}
@@ -0,0 +1,127 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::blocks::ChannelModelBuilder;
+use futuresdr::blocks::Delay;
+use futuresdr::blocks::LoraRxBuilder;
+use futuresdr::blocks::LoraTxBuilder;
+use futuresdr::blocks::MessagePipe;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::futures::StreamExt;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+fn payload(len: usize, seed: u32) -> Vec<u8> {
+    let mut state = seed;
+    (0..len)
+        .map(|_| {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            (state >> 16) as u8
+        })
+        .collect()
+}
+
+/// Send payloads through the transmitter, an optional channel, and the receiver.
+fn loopback(tx: Block, channel: Option<Block>, rx: Block, payloads: &[Vec<u8>]) -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let (sender, mut receiver) = mpsc::channel(10);
+
+    let tx = fg.add_block(tx);
+    // start in the middle of a symbol
+    let delay = fg.add_block(Delay::<Complex32>::new(1234));
+    let rx = fg.add_block(rx);
+    let pipe = fg.add_block(MessagePipe::new(sender));
+
+    fg.connect_stream(tx, "out", delay, "in")?;
+    match channel {
+        Some(channel) => {
+            let channel = fg.add_block(channel);
+            fg.connect_stream(delay, "out", channel, "in")?;
+            fg.connect_stream(channel, "out", rx, "in")?;
+        }
+        None => fg.connect_stream(delay, "out", rx, "in")?,
+    }
+    fg.connect_message(rx, "out", pipe, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+    block_on(async {
+        for p in payloads {
+            handle.call(tx, "in", Pmt::Blob(p.clone())).await?;
+        }
+        // last frame is only decoded once the next samples arrive
+        handle.call(tx, "in", Pmt::Blob(vec![0])).await?;
+        for p in payloads {
+            assert_eq!(receiver.next().await.unwrap(), Pmt::Blob(p.clone()));
+        }
+        handle.terminate().await
+    })?;
+    block_on(task)?;
+
+    Ok(())
+}
+
+#[test]
+fn lora_loopback() -> Result<()> {
+    let payloads = vec![
+        b"Hello FutureSDR!".to_vec(),
+        payload(1, 1),
+        payload(2, 2),
+        payload(255, 3),
+    ];
+
+    for cr in 1..=4 {
+        loopback(
+            LoraTxBuilder::new(7, 125e3, 125e3).code_rate(cr).build(),
+            None,
+            LoraRxBuilder::new(7, 125e3, 125e3).build(),
+            &payloads,
+        )?;
+    }
+
+    loopback(
+        LoraTxBuilder::new(9, 125e3, 125e3)
+            .crc(false)
+            .sync_word(0x34)
+            .preamble_len(12)
+            .build(),
+        None,
+        LoraRxBuilder::new(9, 125e3, 125e3).sync_word(0x34).build(),
+        &payloads,
+    )
+}
+
+#[test]
+fn lora_channel() -> Result<()> {
+    let payloads = vec![payload(20, 4), payload(64, 5)];
+
+    // frequency offset of 10.3 bins at 0 dB SNR
+    loopback(
+        LoraTxBuilder::new(7, 125e3, 125e3).code_rate(2).build(),
+        Some(
+            ChannelModelBuilder::new()
+                .snr(0.0)
+                .frequency_offset(10.3 / 128.0)
+                .seed(1)
+                .build(),
+        ),
+        LoraRxBuilder::new(7, 125e3, 125e3).build(),
+        &payloads,
+    )?;
+
+    // oversampling and low data rate optimization
+    loopback(
+        LoraTxBuilder::new(12, 125e3, 500e3).build(),
+        Some(
+            ChannelModelBuilder::new()
+                .snr(-5.0)
+                .frequency_offset(-5.2 / 4096.0 / 4.0)
+                .seed(2)
+                .build(),
+        ),
+        LoraRxBuilder::new(12, 125e3, 500e3).build(),
+        &payloads,
+    )
+}
@@ -0,0 +1,86 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::MessageBurst;
+use futuresdr::blocks::MessageSink;
+use futuresdr::macros::message_handler;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::BlockMeta;
+use futuresdr::runtime::BlockMetaBuilder;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Kernel;
+use futuresdr::runtime::MessageIo;
+use futuresdr::runtime::MessageIoBuilder;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+use futuresdr::runtime::StreamIoBuilder;
+
+struct BatchCounter {
+    calls: u64,
+    messages: u64,
+}
+
+impl BatchCounter {
+    fn build() -> Block {
+        Block::new(
+            BlockMetaBuilder::new("BatchCounter").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_batch_input("in", Self::handler)
+                .build(),
+            BatchCounter {
+                calls: 0,
+                messages: 0,
+            },
+        )
+    }
+
+    #[message_handler]
+    async fn handler(
+        &mut self,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Vec<Pmt>,
+    ) -> Result<Pmt> {
+        assert!(!p.is_empty());
+        assert!(p.iter().all(|p| matches!(p, Pmt::U32(1))));
+        self.calls += 1;
+        self.messages += p.len() as u64;
+        Ok(Pmt::Null)
+    }
+}
+
+impl Kernel for BatchCounter {}
+
+#[test]
+fn batch_handler() -> Result<()> {
+    let n = 100_000;
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(MessageBurst::new(Pmt::U32(1), n));
+    let cnt = fg.add_block(BatchCounter::build());
+    fg.connect_message(src, "out", cnt, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let cnt = fg.kernel::<BatchCounter>(cnt).unwrap();
+    assert_eq!(cnt.messages, n);
+    assert!(cnt.calls < n);
+
+    Ok(())
+}
+
+#[test]
+fn batch_sink() -> Result<()> {
+    let n = 10_000;
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(MessageBurst::new(Pmt::U32(1), n));
+    let snk = fg.add_block(MessageSink::new());
+    fg.connect_message(src, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<MessageSink>(snk).unwrap();
+    assert_eq!(snk.received(), n);
+
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::run_and_collect;
+use futuresdr::blocks::MovingAverage;
+use futuresdr::blocks::SinglePoleIir;
+use futuresdr::num_complex::Complex32;
+
+#[test]
+fn moving_average() -> Result<()> {
+    let input: Vec<f32> = (0..1000).map(|i| (i % 7) as f32).collect();
+    let v: Vec<f32> = run_and_collect(input.clone(), MovingAverage::<f32>::new(5))?;
+
+    assert_eq!(v.len(), input.len());
+    for (n, y) in v.iter().enumerate() {
+        let expected: f32 = input[n.saturating_sub(4)..=n].iter().sum::<f32>() / 5.0;
+        assert!((y - expected).abs() < 1e-5, "{}: {} != {}", n, y, expected);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn moving_average_decimation() -> Result<()> {
+    let input: Vec<Complex32> = (0..1000).map(|i| Complex32::new(i as f32, 1.0)).collect();
+    let v: Vec<Complex32> =
+        run_and_collect(input, MovingAverage::<Complex32>::with_decimation(4, 4))?;
+
+    assert_eq!(v.len(), 250);
+    for (n, y) in v.iter().enumerate() {
+        let expected = Complex32::new(4.0 * n as f32 + 1.5, 1.0);
+        assert!((y - expected).norm() < 1e-3, "{}: {} != {}", n, y, expected);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn single_pole_iir() -> Result<()> {
+    let alpha = 0.1;
+    let v: Vec<f32> = run_and_collect(vec![2.0f32; 200], SinglePoleIir::<f32>::new(alpha))?;
+
+    assert_eq!(v.len(), 200);
+    for (n, y) in v.iter().enumerate() {
+        let expected = 2.0 * (1.0 - (1.0 - alpha).powi(n as i32 + 1));
+        assert!((y - expected).abs() < 1e-4, "{}: {} != {}", n, y, expected);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::NbfmRxBuilder;
+use futuresdr::blocks::NbfmTxBuilder;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+use std::f32::consts::PI;
+
+const SAMPLE_RATE: f32 = 48000.0;
+
+fn loopback(tx: Block, rx: Block) -> Result<Vec<f32>> {
+    let input: Vec<f32> = (0..48000)
+        .map(|i| 0.5 * (2.0 * PI * 1000.0 * i as f32 / SAMPLE_RATE).sin())
+        .collect();
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::<f32>::new(input));
+    let tx = fg.add_block(tx);
+    let rx = fg.add_block(rx);
+    let snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+
+    fg.connect_stream(src, "out", tx, "in")?;
+    fg.connect_stream(tx, "out", rx, "in")?;
+    fg.connect_stream(rx, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    Ok(fg.kernel::<VectorSink<f32>>(snk).unwrap().items().clone())
+}
+
+/// Amplitude of the 1 kHz tone in the second half of the output.
+fn amplitude(v: &[f32]) -> f32 {
+    let v = &v[v.len() / 2..v.len() / 2 + 4800];
+    let (i, q) = v.iter().enumerate().fold((0.0, 0.0), |(i, q), (k, x)| {
+        let p = 2.0 * PI * 1000.0 * k as f32 / SAMPLE_RATE;
+        (i + x * p.cos(), q + x * p.sin())
+    });
+    2.0 * (i * i + q * q).sqrt() / v.len() as f32
+}
+
+#[test]
+fn nbfm_loopback() -> Result<()> {
+    let v = loopback(
+        NbfmTxBuilder::new(SAMPLE_RATE).deviation(5e3).build(),
+        NbfmRxBuilder::new(SAMPLE_RATE).deviation(5e3).build(),
+    )?;
+    assert!(v.len() > 40000);
+    assert!((amplitude(&v) - 0.5).abs() < 0.02);
+    Ok(())
+}
+
+#[test]
+fn nbfm_ctcss() -> Result<()> {
+    let v = loopback(
+        NbfmTxBuilder::new(SAMPLE_RATE).ctcss(88.5).build(),
+        NbfmRxBuilder::new(SAMPLE_RATE).ctcss(88.5).build(),
+    )?;
+    assert!((amplitude(&v) - 0.5).abs() < 0.02);
+    // tone is removed
+    let power = v[v.len() / 2..].iter().map(|x| x * x).sum::<f32>() / (v.len() / 2) as f32;
+    assert!((power - 0.125).abs() < 0.01);
+
+    // no tone or wrong tone: squelch closed
+    let v = loopback(
+        NbfmTxBuilder::new(SAMPLE_RATE).build(),
+        NbfmRxBuilder::new(SAMPLE_RATE).ctcss(88.5).build(),
+    )?;
+    assert!(v.iter().all(|x| *x == 0.0));
+
+    let v = loopback(
+        NbfmTxBuilder::new(SAMPLE_RATE).ctcss(100.0).build(),
+        NbfmRxBuilder::new(SAMPLE_RATE).ctcss(88.5).build(),
+    )?;
+    assert!(v.iter().all(|x| *x == 0.0));
+    Ok(())
+}
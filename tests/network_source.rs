@@ -0,0 +1,144 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::async_io::Timer;
+use futuresdr::blocks::{
+    DelimiterFramer, FixedSizeFramer, Framed, LengthPrefixedFramer, MessagePipe, NetworkFramer,
+    NetworkSourceBuilder, TcpMode,
+};
+use futuresdr::futures::channel::mpsc;
+use futuresdr::futures::future::{select, Either};
+use futuresdr::futures::StreamExt;
+use futuresdr::runtime::{Flowgraph, Pmt, Runtime};
+use std::io::Write;
+use std::net::{TcpListener, UdpSocket};
+use std::time::Duration;
+
+#[test]
+fn framers() {
+    let mut f = LengthPrefixedFramer::new(2);
+    assert_eq!(f.frame(&[0, 3, 1, 2]), Framed::Incomplete);
+    assert_eq!(
+        f.frame(&[0, 3, 1, 2, 3, 9]),
+        Framed::Frame {
+            payload: 2..5,
+            consumed: 5
+        }
+    );
+
+    let mut f = LengthPrefixedFramer::new(4)
+        .little_endian(true)
+        .includes_header(true)
+        .max_len(10);
+    assert_eq!(
+        f.frame(&[6, 0, 0, 0, 1, 2]),
+        Framed::Frame {
+            payload: 4..6,
+            consumed: 6
+        }
+    );
+    assert_eq!(f.frame(&[100, 0, 0, 0]), Framed::Skip(1));
+
+    let mut f = DelimiterFramer::new("\r\n").max_len(4);
+    assert_eq!(f.frame(b"ab\r"), Framed::Incomplete);
+    assert_eq!(
+        f.frame(b"ab\r\ncd"),
+        Framed::Frame {
+            payload: 0..2,
+            consumed: 4
+        }
+    );
+    assert_eq!(f.frame(b"abcdefg\r\n"), Framed::Skip(9));
+    assert_eq!(f.frame(b"abcdefg\r"), Framed::Skip(7));
+
+    let mut f = FixedSizeFramer::new(3);
+    assert_eq!(f.frame(&[1, 2]), Framed::Incomplete);
+    assert_eq!(
+        f.frame(&[1, 2, 3, 4]),
+        Framed::Frame {
+            payload: 0..3,
+            consumed: 3
+        }
+    );
+}
+
+#[test]
+fn network_source_tcp() -> Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let addr = listener.local_addr()?;
+
+    let server = std::thread::spawn(move || {
+        let (mut s, _) = listener.accept().unwrap();
+        // lines split across writes
+        s.write_all(b"hello\nwor").unwrap();
+        s.flush().unwrap();
+        std::thread::sleep(Duration::from_millis(50));
+        s.write_all(b"ld\n\nrest").unwrap();
+    });
+
+    let (tx, rx) = mpsc::channel(10);
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(
+        NetworkSourceBuilder::tcp(
+            TcpMode::Client(addr.to_string()),
+            DelimiterFramer::new("\n"),
+        )
+        .build(),
+    );
+    let pipe = fg.add_block(MessagePipe::new(tx));
+    fg.connect_message(src, "out", pipe, "in")?;
+    Runtime::new().run(fg)?;
+    server.join().unwrap();
+
+    let frames: Vec<Pmt> = block_on(rx.collect());
+    assert_eq!(
+        frames,
+        vec![
+            Pmt::Blob(b"hello".to_vec()),
+            Pmt::Blob(b"world".to_vec()),
+            Pmt::Blob(Vec::new()),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn network_source_udp() -> Result<()> {
+    let port = UdpSocket::bind("127.0.0.1:0")?.local_addr()?.port();
+
+    let (tx, mut rx) = mpsc::channel(10);
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(
+        NetworkSourceBuilder::udp(format!("127.0.0.1:{port}"), LengthPrefixedFramer::new(1))
+            .build(),
+    );
+    let pipe = fg.add_block(MessagePipe::new(tx));
+    fg.connect_message(src, "out", pipe, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+
+    let socket = UdpSocket::bind("127.0.0.1:0")?;
+    let frames = block_on(async {
+        let mut frames = Vec::new();
+        // the source might not be bound yet, resend until frames arrive
+        while frames.is_empty() {
+            socket
+                .send_to(&[2, 1, 2, 1, 3, 5, 0], ("127.0.0.1", port))
+                .unwrap();
+            let timeout = Timer::after(Duration::from_millis(50));
+            if let Either::Left((Some(f), _)) = select(rx.next(), Box::pin(timeout)).await {
+                frames.push(f);
+                frames.push(rx.next().await.unwrap());
+            }
+        }
+        frames
+    });
+    block_on(handle.terminate())?;
+    block_on(task)?;
+
+    // the incomplete frame at the end of the datagram is discarded
+    assert_eq!(frames, vec![Pmt::Blob(vec![1, 2]), Pmt::Blob(vec![3])]);
+
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::blocks::Fft;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::buffer::circular::Circular;
+use futuresdr::runtime::parallel;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+use rustfft::FftPlanner;
+
+#[test]
+fn zip_chunks() {
+    let i: &'static mut [u32] = Box::leak((0..100_000).collect::<Vec<u32>>().into_boxed_slice());
+    let o: &'static mut [u32] = Box::leak(vec![0; 100_000].into_boxed_slice());
+    let o_ptr = o.as_ptr();
+
+    block_on(parallel::zip_chunks(i, o, 1000, |i, o| {
+        for (x, y) in i.iter().zip(o.iter_mut()) {
+            *y = x * 2;
+        }
+    }));
+
+    let o = unsafe { std::slice::from_raw_parts(o_ptr, 100_000) };
+    for (k, v) in o.iter().enumerate() {
+        assert_eq!(*v, 2 * k as u32);
+    }
+}
+
+#[test]
+fn spawn_panics() {
+    let r = std::panic::catch_unwind(|| block_on(parallel::spawn(|| panic!("boom"))));
+    assert!(r.is_err());
+    assert_eq!(block_on(parallel::spawn(|| 42)), 42);
+}
+
+#[test]
+fn parallel_fft() -> Result<()> {
+    let len = 256;
+    let n_items = len * 2048;
+    let orig: Vec<Complex32> = (0..n_items)
+        .map(|_| Complex32::new(rand::random::<f32>(), rand::random::<f32>()))
+        .collect();
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::<Complex32>::new(orig.clone()));
+    let fft = fg.add_block(Fft::new(len));
+    let snk = fg.add_block(VectorSinkBuilder::<Complex32>::new().build());
+    // large buffers, so that the FFTs are split across the compute pool
+    fg.connect_stream_with_type(src, "out", fft, "in", Circular::with_size(1 << 21))?;
+    fg.connect_stream_with_type(fft, "out", snk, "in", Circular::with_size(1 << 21))?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<VectorSink<Complex32>>(snk).unwrap();
+    let v = snk.items();
+    assert_eq!(v.len(), n_items);
+
+    let mut expected = orig;
+    let plan = FftPlanner::<f32>::new().plan_fft_forward(len);
+    plan.process(&mut expected);
+    for (a, b) in v.iter().zip(expected) {
+        assert!((a - b).norm() < 1e-3);
+    }
+
+    Ok(())
+}
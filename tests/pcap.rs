@@ -0,0 +1,100 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::blocks::LinkType;
+use futuresdr::blocks::PcapFormat;
+use futuresdr::blocks::PcapSinkBuilder;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+use std::collections::HashMap;
+
+fn u16_at(b: &[u8], i: usize) -> u16 {
+    u16::from_le_bytes(b[i..i + 2].try_into().unwrap())
+}
+
+fn u32_at(b: &[u8], i: usize) -> u32 {
+    u32::from_le_bytes(b[i..i + 4].try_into().unwrap())
+}
+
+fn capture(path: &std::path::Path, format: PcapFormat, link_type: LinkType) -> Result<Vec<u8>> {
+    let mut fg = Flowgraph::new();
+    let pcap = fg.add_block(
+        PcapSinkBuilder::new(path.to_str().unwrap(), link_type)
+            .format(format)
+            .build(),
+    );
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+
+    let mut m = HashMap::new();
+    m.insert("data".to_string(), Pmt::Blob(vec![1, 2, 3, 4, 5]));
+    m.insert("seconds".to_string(), Pmt::U64(1_600_000_000));
+    m.insert("picoseconds".to_string(), Pmt::U64(250_000_000_000));
+    block_on(async {
+        handle.call(pcap, "in", Pmt::MapStrPmt(m)).await?;
+        handle.call(pcap, "in", Pmt::Blob(vec![9; 8])).await?;
+        handle.call(pcap, "in", Pmt::U32(1)).await?;
+        handle.terminate().await
+    })?;
+    drop(block_on(task)?);
+
+    Ok(std::fs::read(path)?)
+}
+
+#[test]
+fn pcapng_blocks() -> Result<()> {
+    let path = std::env::temp_dir().join(format!("fsdr-{}.pcapng", rand::random::<u32>()));
+    let b = capture(&path, PcapFormat::PcapNg, LinkType::Ieee802154)?;
+    std::fs::remove_file(&path)?;
+
+    // section header
+    assert_eq!(u32_at(&b, 0), 0x0a0d0d0a);
+    assert_eq!(u32_at(&b, 8), 0x1a2b3c4d);
+    let mut pos = u32_at(&b, 4) as usize;
+    assert_eq!(u32_at(&b, pos - 4) as usize, pos);
+
+    // interface description
+    assert_eq!(u32_at(&b, pos), 1);
+    assert_eq!(u16_at(&b, pos + 8), 195);
+    pos += u32_at(&b, pos + 4) as usize;
+
+    // enhanced packets
+    let mut packets = Vec::new();
+    while pos < b.len() {
+        assert_eq!(u32_at(&b, pos), 6);
+        let len = u32_at(&b, pos + 4) as usize;
+        assert_eq!(len % 4, 0);
+        assert_eq!(u32_at(&b, pos + len - 4) as usize, len);
+        let ts = (u32_at(&b, pos + 12) as u64) << 32 | u32_at(&b, pos + 16) as u64;
+        let n = u32_at(&b, pos + 20) as usize;
+        assert_eq!(u32_at(&b, pos + 24) as usize, n);
+        packets.push((ts, b[pos + 28..pos + 28 + n].to_vec()));
+        pos += len;
+    }
+    assert_eq!(pos, b.len());
+    assert_eq!(packets.len(), 2);
+    assert_eq!(packets[0], (1_600_000_000_250_000_000, vec![1, 2, 3, 4, 5]));
+    assert_eq!(packets[1].1, vec![9; 8]);
+    assert!(packets[1].0 > packets[0].0);
+
+    Ok(())
+}
+
+#[test]
+fn pcap_records() -> Result<()> {
+    let path = std::env::temp_dir().join(format!("fsdr-{}.pcap", rand::random::<u32>()));
+    let b = capture(&path, PcapFormat::Pcap, LinkType::Ax25)?;
+    std::fs::remove_file(&path)?;
+
+    assert_eq!(u32_at(&b, 0), 0xa1b23c4d);
+    assert_eq!(u32_at(&b, 20), 3);
+    assert_eq!(u32_at(&b, 24), 1_600_000_000);
+    assert_eq!(u32_at(&b, 28), 250_000_000);
+    assert_eq!(u32_at(&b, 32), 5);
+    assert_eq!(&b[40..45], &[1, 2, 3, 4, 5]);
+    assert_eq!(u32_at(&b, 53), 8);
+    assert_eq!(b.len(), 45 + 16 + 8);
+
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use futuresdr::runtime::pdu_pool;
+use futuresdr::runtime::pdu_pool::PooledBlob;
+use futuresdr::runtime::Pmt;
+
+#[test]
+fn reuse_on_drop() {
+    let mut b = PooledBlob::with_capacity(3000);
+    assert!(b.capacity() >= 3000);
+    b.extend_from_slice(&[1, 2, 3]);
+    let ptr = b.as_ptr();
+    drop(b);
+
+    let b = PooledBlob::with_capacity(2500);
+    assert!(b.is_empty());
+    assert_eq!(b.as_ptr(), ptr);
+    assert!(pdu_pool::stats().hits > 0);
+}
+
+#[test]
+fn recycle_pmt() {
+    let mut b = PooledBlob::with_capacity(10000);
+    b.extend_from_slice(b"payload");
+    let ptr = b.as_ptr();
+
+    let p: Pmt = b.into();
+    assert_eq!(p, Pmt::Blob(b"payload".to_vec()));
+    pdu_pool::recycle(p);
+
+    let b = PooledBlob::with_capacity(9000);
+    assert_eq!(b.as_ptr(), ptr);
+}
+
+#[test]
+fn clone() {
+    let b = PooledBlob::from_slice(&[42; 100]);
+    let c = b.clone();
+    assert_eq!(b, c);
+    assert_ne!(b.as_ptr(), c.as_ptr());
+    assert_eq!(c.into_vec(), vec![42; 100]);
+}
@@ -0,0 +1,133 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_trait::async_trait;
+use futuresdr::blocks::MessagePipe;
+use futuresdr::blocks::PeakDetectorBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::futures::StreamExt;
+use futuresdr::runtime::{
+    Block, BlockMeta, BlockMetaBuilder, Flowgraph, Kernel, MessageIo, MessageIoBuilder, Pmt,
+    Runtime, StreamIo, StreamIoBuilder, Tag, WorkIo,
+};
+
+/// Store samples and named tags with their absolute index.
+#[derive(Default)]
+struct TagSink {
+    items: Vec<f32>,
+    tags: Vec<(usize, String, f32)>,
+}
+
+impl TagSink {
+    fn into_block(self) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("TagSink").build(),
+            StreamIoBuilder::new().add_input::<f32>("in").build(),
+            MessageIoBuilder::<Self>::new().build(),
+            self,
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for TagSink {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<f32>();
+        for t in sio.input(0).tags().iter().filter(|t| t.index < i.len()) {
+            if let Tag::NamedF32(s, v) = &t.tag {
+                self.tags.push((self.items.len() + t.index, s.clone(), *v));
+            }
+        }
+        self.items.extend_from_slice(i);
+        sio.input(0).consume(i.len());
+
+        if sio.input(0).finished() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn peak_detector() -> Result<()> {
+    // triangular pulses, peaking at 100, 500, and at the last sample
+    let peaks = [(100, 2.0), (500, 3.0), (999, 1.5)];
+    let mut input = vec![0.0f32; 1000];
+    for (p, v) in peaks {
+        for d in 0..5usize {
+            let x = v * (1.0 - d as f32 / 5.0);
+            input[p - d] = x;
+            if p + d < input.len() {
+                input[p + d] = x;
+            }
+        }
+    }
+
+    let mut fg = Flowgraph::new();
+    let (tx, rx) = mpsc::channel(10);
+
+    let src = fg.add_block(VectorSource::<f32>::new(input.clone()));
+    let detector = fg.add_block(PeakDetectorBuilder::new(0.5).tag_crossings().build());
+    let snk = fg.add_block(TagSink::default().into_block());
+    let pipe = fg.add_block(MessagePipe::new(tx));
+
+    fg.connect_stream(src, "out", detector, "in")?;
+    fg.connect_stream(detector, "out", snk, "in")?;
+    fg.connect_message(detector, "peaks", pipe, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<TagSink>(snk).unwrap();
+    assert_eq!(snk.items, input);
+    let mut expected = Vec::new();
+    for (p, v) in peaks {
+        let start = (p - 5..p).find(|k| input[*k] > 0.5).unwrap();
+        expected.push((start, "threshold".to_string(), input[start]));
+        expected.push((p, "peak".to_string(), v));
+    }
+    assert_eq!(snk.tags, expected);
+    drop(fg);
+
+    let msgs: Vec<Pmt> = futuresdr::async_io::block_on(rx.collect());
+    assert_eq!(msgs.len(), 3);
+    for (m, (p, v)) in msgs.into_iter().zip(peaks) {
+        match m {
+            Pmt::MapStrPmt(m) => {
+                assert_eq!(m.get("offset"), Some(&Pmt::U64(p as u64)));
+                assert_eq!(m.get("value"), Some(&Pmt::F32(v)));
+            }
+            _ => panic!("wrong message type"),
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn peak_detector_look_ahead() -> Result<()> {
+    // long ramp above the threshold
+    let input: Vec<f32> = (0..100).map(|i| i as f32).collect();
+
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(VectorSource::<f32>::new(input));
+    let detector = fg.add_block(PeakDetectorBuilder::new(0.5).look_ahead(30).build());
+    let snk = fg.add_block(TagSink::default().into_block());
+
+    fg.connect_stream(src, "out", detector, "in")?;
+    fg.connect_stream(detector, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<TagSink>(snk).unwrap();
+    let peaks: Vec<usize> = snk.tags.iter().map(|t| t.0).collect();
+    assert_eq!(peaks, vec![30, 60, 90, 99]);
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::PfbArbResampler;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+
+fn resample(input: Vec<Complex32>, rate: f64) -> Result<Vec<Complex32>> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(VectorSource::<Complex32>::new(input));
+    let resampler = fg.add_block(PfbArbResampler::<Complex32>::new(rate));
+    let snk = fg.add_block(VectorSinkBuilder::<Complex32>::new().build());
+
+    fg.connect_stream(src, "out", resampler, "in")?;
+    fg.connect_stream(resampler, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    Ok(fg
+        .kernel::<VectorSink<Complex32>>(snk)
+        .unwrap()
+        .items()
+        .clone())
+}
+
+fn tone(n: usize, freq: f32) -> Vec<Complex32> {
+    (0..n)
+        .map(|i| Complex32::from_polar(1.0, 2.0 * std::f32::consts::PI * freq * i as f32))
+        .collect()
+}
+
+#[test]
+fn pfb_arb_resampler_c32() -> Result<()> {
+    for rate in [0.8, 1.25, 0.5123] {
+        let n = 20000;
+        let freq = 0.05;
+        let v = resample(tone(n, freq), rate)?;
+
+        let expected = n as f64 * rate;
+        assert!((v.len() as f64 - expected).abs() < 0.02 * expected);
+
+        // skip filter transient, check magnitude and frequency of the tone
+        let v = &v[200..v.len() - 200];
+        for x in v.iter() {
+            assert!((x.norm() - 1.0).abs() < 0.01);
+        }
+        let phase: f32 = v.windows(2).map(|w| (w[1] * w[0].conj()).arg()).sum();
+        let f = phase / (v.len() - 1) as f32 / (2.0 * std::f32::consts::PI);
+        assert!((f as f64 - freq as f64 / rate).abs() < 1e-4);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn pfb_arb_resampler_f32() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let n = 10000;
+    let src = fg.add_block(VectorSource::<f32>::new(vec![1.0; n]));
+    let resampler = fg.add_block(PfbArbResampler::<f32>::new(1.5));
+    let snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+
+    fg.connect_stream(src, "out", resampler, "in")?;
+    fg.connect_stream(resampler, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let v = fg.kernel::<VectorSink<f32>>(snk).unwrap().items();
+    assert!((v.len() as f64 - 1.5 * n as f64).abs() < 0.02 * 1.5 * n as f64);
+    for x in v[100..].iter() {
+        assert!((x - 1.0).abs() < 0.01);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,77 @@
+#![cfg(unix)]
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::FileFormat;
+use futuresdr::blocks::PipeSinkBuilder;
+use futuresdr::blocks::PipeSourceBuilder;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+
+fn round_trip(
+    name: &str,
+    format: Option<FileFormat>,
+    input: Vec<Complex32>,
+) -> Result<Vec<Complex32>> {
+    let path = std::env::temp_dir()
+        .join(format!("futuresdr-test-{}-{}", std::process::id(), name))
+        .to_str()
+        .unwrap()
+        .to_string();
+
+    let tx = {
+        let path = path.clone();
+        std::thread::spawn(move || -> Result<()> {
+            let mut fg = Flowgraph::new();
+            let src = fg.add_block(VectorSource::new(input));
+            let mut snk = PipeSinkBuilder::<Complex32>::new(path).create(true);
+            if let Some(f) = format {
+                snk = snk.format(f);
+            }
+            let snk = fg.add_block(snk.build());
+            fg.connect_stream(src, "out", snk, "in")?;
+            Runtime::new().run(fg)?;
+            Ok(())
+        })
+    };
+
+    let mut fg = Flowgraph::new();
+    let mut src = PipeSourceBuilder::<Complex32>::new(path.clone()).create(true);
+    if let Some(f) = format {
+        src = src.format(f);
+    }
+    let src = fg.add_block(src.build());
+    let snk = fg.add_block(VectorSinkBuilder::<Complex32>::new().build());
+    fg.connect_stream(src, "out", snk, "in")?;
+    fg = Runtime::new().run(fg)?;
+    tx.join().unwrap()?;
+    std::fs::remove_file(path)?;
+
+    let snk = fg.kernel::<VectorSink<Complex32>>(snk).unwrap();
+    Ok(snk.items().clone())
+}
+
+#[test]
+fn pipe_native() -> Result<()> {
+    let input: Vec<Complex32> = (0..100_000)
+        .map(|i| Complex32::new(i as f32, -(i as f32)))
+        .collect();
+    let output = round_trip("native.fifo", None, input.clone())?;
+    assert_eq!(output, input);
+    Ok(())
+}
+
+#[test]
+fn pipe_cu8() -> Result<()> {
+    let input: Vec<Complex32> = (0..10_000)
+        .map(|i| Complex32::new((i % 256) as f32 / 128.0 - 1.0, 0.5))
+        .collect();
+    let output = round_trip("cu8.fifo", Some(FileFormat::Cu8), input.clone())?;
+    assert_eq!(output.len(), input.len());
+    for (a, b) in output.iter().zip(input.iter()) {
+        assert!((a - b).norm() < 1.0 / 128.0);
+    }
+    Ok(())
+}
@@ -0,0 +1,171 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::blocks::ChannelModelBuilder;
+use futuresdr::blocks::FskDemodBuilder;
+use futuresdr::blocks::FskMod;
+use futuresdr::blocks::MessagePipe;
+use futuresdr::blocks::PocsagDecoder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::futures::StreamExt;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+const SYNC: u32 = 0x7cd2_15d8;
+const IDLE: u32 = 0x7a89_c197;
+
+/// BCH(31, 21) encode 21 data bits and add the even parity bit.
+fn codeword(data: u32) -> u32 {
+    let mut reg = data << 10;
+    for i in (10..31).rev() {
+        if reg & (1 << i) != 0 {
+            reg ^= 0x769 << (i - 10);
+        }
+    }
+    let cw = (data << 11) | (reg << 1);
+    cw | (cw.count_ones() & 1)
+}
+
+fn alpha(text: &str) -> Vec<u8> {
+    text.bytes()
+        .flat_map(|c| (0..7).map(move |i| (c >> i) & 1))
+        .collect()
+}
+
+/// Encode a numeric message, padded with spaces to full codewords.
+fn numeric(text: &str) -> Vec<u8> {
+    let mut text = text.to_string();
+    while text.len() % 5 != 0 {
+        text.push(' ');
+    }
+    text.bytes()
+        .flat_map(|c| {
+            let d = b"0123456789*U -)(".iter().position(|x| *x == c).unwrap() as u8;
+            (0..4).map(move |i| (d >> i) & 1)
+        })
+        .collect()
+}
+
+/// Encode messages, given as address, function, and content bits, into a POCSAG bit stream.
+fn encode(msgs: &[(u32, u32, Vec<u8>)]) -> Vec<u32> {
+    let mut cws = Vec::new();
+    for (address, function, bits) in msgs {
+        while cws.len() % 16 != 2 * (address & 7) as usize {
+            cws.push(IDLE);
+        }
+        cws.push(codeword(((address >> 3) << 2) | function));
+        for c in bits.chunks(20) {
+            let mut data = c.iter().fold(0, |acc, b| (acc << 1) | *b as u32);
+            data <<= 20 - c.len();
+            cws.push(codeword((1 << 20) | data));
+        }
+    }
+    cws.push(IDLE);
+    while cws.len() % 16 != 0 {
+        cws.push(IDLE);
+    }
+    cws
+}
+
+fn bits(cws: &[u32]) -> Vec<u8> {
+    let mut bits: Vec<u8> = (0..576).map(|i| ((i + 1) % 2) as u8).collect();
+    for (i, cw) in cws.iter().enumerate() {
+        if i % 16 == 0 {
+            bits.extend((0..32).rev().map(|k| ((SYNC >> k) & 1) as u8));
+        }
+        bits.extend((0..32).rev().map(|k| ((cw >> k) & 1) as u8));
+    }
+    bits
+}
+
+fn messages() -> Vec<(u32, u32, Vec<u8>)> {
+    vec![
+        (1_234_567, 3, alpha("Hello FutureSDR!\u{4}")),
+        (8, 0, numeric("0123 456-789")),
+        (2000, 1, Vec::new()),
+    ]
+}
+
+fn check(received: &[Pmt]) {
+    let expected = [
+        (1_234_567, 3, "alphanumeric", "Hello FutureSDR!"),
+        (8, 0, "numeric", "0123 456-789"),
+        (2000, 1, "tone", ""),
+    ];
+    assert_eq!(received.len(), expected.len());
+    for (r, (address, function, kind, message)) in received.iter().zip(expected.iter()) {
+        if let Pmt::MapStrPmt(m) = r {
+            assert_eq!(m["address"], Pmt::U64(*address));
+            assert_eq!(m["function"], Pmt::U32(*function));
+            assert_eq!(m["type"], Pmt::String(kind.to_string()));
+            assert_eq!(m["message"], Pmt::String(message.to_string()));
+        } else {
+            panic!("wrong PMT type");
+        }
+    }
+}
+
+fn run(input: Vec<u8>, blocks: Vec<Block>) -> Result<Vec<Pmt>> {
+    let mut fg = Flowgraph::new();
+    let (tx, rx) = mpsc::channel(10);
+
+    let mut last = fg.add_block(VectorSource::<u8>::new(input));
+    let mut port = "out";
+    for b in blocks {
+        let b = fg.add_block(b);
+        fg.connect_stream(last, port, b, "in")?;
+        last = b;
+        port = "out";
+    }
+    let pocsag = fg.add_block(PocsagDecoder::new());
+    let pipe = fg.add_block(MessagePipe::new(tx));
+    fg.connect_stream(last, port, pocsag, "in")?;
+    fg.connect_message(pocsag, "out", pipe, "in")?;
+
+    Runtime::new().run(fg)?;
+
+    Ok(block_on(rx.collect()))
+}
+
+#[test]
+fn pocsag_bits() -> Result<()> {
+    let mut cws = encode(&messages());
+    // single bit errors are corrected
+    cws[2] ^= 1 << 7;
+    cws[3] ^= 1 << 30;
+
+    let mut input = vec![0, 1, 1, 0, 1];
+    input.extend(bits(&cws));
+    check(&run(input.clone(), vec![])?);
+
+    // inverted polarity
+    let inverted = input.iter().map(|b| b ^ 1).collect();
+    check(&run(inverted, vec![])?);
+
+    Ok(())
+}
+
+#[test]
+fn pocsag_fsk_loopback() -> Result<()> {
+    let mut input = bits(&encode(&messages()));
+    input.extend(std::iter::repeat(0).take(100));
+
+    // POCSAG 1200 at 48 kHz with 4.5 kHz deviation
+    let received = run(
+        input,
+        vec![
+            FskMod::new(2, 40.0, 0.09375),
+            ChannelModelBuilder::new()
+                .snr(10.0)
+                .frequency_offset(0.005)
+                .seed(1)
+                .build(),
+            FskDemodBuilder::new(2, 40.0, 0.09375).build(),
+        ],
+    )?;
+    check(&received);
+
+    Ok(())
+}
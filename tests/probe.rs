@@ -0,0 +1,98 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::blocks::MessagePipe;
+use futuresdr::blocks::RateProbe;
+use futuresdr::blocks::SignalProbe;
+use futuresdr::blocks::Source;
+use futuresdr::blocks::VectorSource;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::futures::StreamExt;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+use std::time::Duration;
+
+#[test]
+fn signal_probe() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let v: Vec<Complex32> = (0..1000)
+        .map(|i| Complex32::new(if i % 2 == 0 { 2.0 } else { -2.0 }, 0.0))
+        .chain(std::iter::once(Complex32::new(0.5, -1.0)))
+        .collect();
+    let src = fg.add_block(VectorSource::<Complex32>::new(v));
+    let probe = fg.add_block(SignalProbe::<Complex32>::new(0.1));
+    fg.connect_stream(src, "out", probe, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let p = fg.kernel::<SignalProbe<Complex32>>(probe).unwrap();
+    assert_eq!(p.value(), Some(Complex32::new(0.5, -1.0)));
+    // 4 with a small dip from the last sample
+    let power = p.power_db().unwrap();
+    assert!(power < 6.03 && power > 5.5, "{}", power);
+
+    Ok(())
+}
+
+#[test]
+fn signal_probe_messages() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(Source::new(|| 0.1f32));
+    let probe = fg.add_block(SignalProbe::<f32>::new(1.0));
+    fg.connect_stream(src, "out", probe, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+    block_on(async {
+        let mut value = Pmt::Null;
+        while value == Pmt::Null {
+            value = handle.callback(probe, "value", Pmt::Null).await?;
+        }
+        assert_eq!(value, Pmt::F32(0.1));
+        match handle.callback(probe, "power", Pmt::Null).await? {
+            Pmt::F32(p) => assert!((p + 20.0).abs() < 1e-3, "{}", p),
+            p => panic!("wrong message type {:?}", p),
+        }
+        handle.terminate().await
+    })?;
+    block_on(task)?;
+
+    Ok(())
+}
+
+#[test]
+fn rate_probe() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let (tx, mut rx) = mpsc::channel(10);
+
+    let src = fg.add_block(Source::new(|| 1.0f32));
+    let probe = fg.add_block(RateProbe::<f32>::new(Duration::from_millis(50), 0.5));
+    let pipe = fg.add_block(MessagePipe::new(tx));
+    fg.connect_stream(src, "out", probe, "in")?;
+    fg.connect_message(probe, "out", pipe, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+    block_on(async {
+        let posted = match rx.next().await {
+            Some(Pmt::F64(r)) => r,
+            p => panic!("wrong message {:?}", p),
+        };
+        assert!(posted > 0.0);
+        match handle.callback(probe, "rate", Pmt::Null).await? {
+            Pmt::F64(r) => assert!(r > 0.0),
+            p => panic!("wrong message type {:?}", p),
+        }
+        handle.terminate().await
+    })?;
+    let fg = block_on(task)?;
+
+    let p = fg.kernel::<RateProbe<f32>>(probe).unwrap();
+    assert!(p.items() > 0);
+    assert!(p.rate().unwrap() > 0.0);
+
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::async_io::Timer;
+use futuresdr::blocks::Apply;
+use futuresdr::blocks::Head;
+use futuresdr::blocks::NullSource;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::runtime::scheduler::ProfilingScheduler;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+use std::time::Duration;
+
+#[test]
+fn flowgraph_profiling() -> Result<()> {
+    let n_items = 2_000_000;
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(NullSource::<u32>::new());
+    let head = fg.add_block(Head::<u32>::new(n_items));
+    fg.connect_stream(src, "out", head, "in")?;
+
+    let mut last = head;
+    for _ in 0..6 {
+        let apply = fg.add_block(Apply::new(|x: &u32| x.wrapping_add(1)));
+        fg.connect_stream(last, "out", apply, "in")?;
+        last = apply;
+    }
+    let snk = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+    fg.connect_stream(last, "out", snk, "in")?;
+
+    let scheduler = ProfilingScheduler::new(2, Duration::from_millis(10));
+    let rt = Runtime::with_scheduler(scheduler.clone());
+
+    let fg = block_on(async {
+        let (task, _) = rt.start(fg).await;
+        Timer::after(Duration::from_millis(50)).await;
+
+        let profile = scheduler.profile();
+        assert!(!profile.is_empty());
+        assert!(profile.iter().all(|p| p.worker < 2));
+        assert!(profile.iter().any(|p| p.busy > Duration::ZERO));
+
+        task.await
+    })?;
+
+    let snk = fg.kernel::<VectorSink<u32>>(snk).unwrap();
+    assert_eq!(snk.items().len(), n_items as usize);
+    assert!(snk.items().iter().all(|x| *x == 6));
+
+    // profiles are removed when blocks terminate
+    assert!(scheduler.profile().is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn profile_order() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    // add blocks in reverse order
+    let snk = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+    let apply = fg.add_block(Apply::new(|x: &u32| *x));
+    let head = fg.add_block(Head::<u32>::new(u64::MAX));
+    let src = fg.add_block(NullSource::<u32>::new());
+    fg.connect_stream(src, "out", head, "in")?;
+    fg.connect_stream(head, "out", apply, "in")?;
+    fg.connect_stream(apply, "out", snk, "in")?;
+
+    let scheduler = ProfilingScheduler::new(2, Duration::from_millis(10));
+    let rt = Runtime::with_scheduler(scheduler.clone());
+
+    block_on(async {
+        let (task, mut handle) = rt.start(fg).await;
+        let names: Vec<String> = scheduler.profile().into_iter().map(|p| p.name).collect();
+        assert_eq!(names.len(), 4);
+        assert!(names[0].starts_with("NullSource"));
+        assert!(names[1].starts_with("Head"));
+        assert!(names[2].starts_with("Apply"));
+        assert!(names[3].starts_with("VectorSink"));
+
+        handle.terminate().await?;
+        task.await?;
+        Ok(())
+    })
+}
@@ -0,0 +1,206 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::ChannelModelBuilder;
+use futuresdr::blocks::CostasLoop;
+use futuresdr::blocks::PskRxBuilder;
+use futuresdr::blocks::PskTxBuilder;
+use futuresdr::blocks::RrcFilterBuilder;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+
+fn bits(n: usize) -> Vec<u8> {
+    let mut state = 0x1234_5678u32;
+    (0..n)
+        .map(|_| {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            (state >> 16) as u8 & 1
+        })
+        .collect()
+}
+
+fn tx(input: Vec<u8>, tx: Block) -> Result<Vec<Complex32>> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(VectorSource::<u8>::new(input));
+    let tx = fg.add_block(tx);
+    let snk = fg.add_block(VectorSinkBuilder::<Complex32>::new().build());
+
+    fg.connect_stream(src, "out", tx, "in")?;
+    fg.connect_stream(tx, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    Ok(fg
+        .kernel::<VectorSink<Complex32>>(snk)
+        .unwrap()
+        .items()
+        .clone())
+}
+
+fn loopback(input: Vec<u8>, blocks: Vec<Block>) -> Result<Vec<u8>> {
+    let mut fg = Flowgraph::new();
+
+    let mut last = fg.add_block(VectorSource::<u8>::new(input));
+    for b in blocks {
+        let b = fg.add_block(b);
+        fg.connect_stream(last, "out", b, "in")?;
+        last = b;
+    }
+    let snk = fg.add_block(VectorSinkBuilder::<u8>::new().build());
+    fg.connect_stream(last, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    Ok(fg.kernel::<VectorSink<u8>>(snk).unwrap().items().clone())
+}
+
+/// After the loops converged, the bits are recovered with a fixed delay.
+fn assert_recovered(input: &[u8], output: &[u8]) {
+    let reference = &input[400..input.len() - 400];
+    assert!(
+        output.windows(reference.len()).any(|w| w == reference),
+        "bits not recovered"
+    );
+}
+
+#[test]
+fn psk_tx_impulse_response() -> Result<()> {
+    let output = tx(vec![1], PskTxBuilder::bpsk(4).differential(false).build())?;
+
+    // complete pulse, padded to a multiple of the samples per symbol
+    let taps = RrcFilterBuilder::new(4).rolloff(0.35).span(8).taps();
+    assert_eq!(output.len(), 36);
+    for (k, x) in output.iter().enumerate() {
+        let want = taps.get(k).copied().unwrap_or(0.0);
+        assert!((x.re - want).abs() < 1e-6 && x.im.abs() < 1e-6);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn psk_tx_qpsk_vector() -> Result<()> {
+    let sps = 2;
+    let input = vec![0, 0, 0, 1, 1, 1, 1, 0, 1, 1];
+    let output = tx(input, PskTxBuilder::qpsk(sps).differential(false).build())?;
+
+    // symbols 0, 1, 3, 2, 3 with bit 0 (LSB) on the in-phase component
+    let a = 1.0 / 2.0f32.sqrt();
+    let symbols = [
+        Complex32::new(-a, -a),
+        Complex32::new(a, -a),
+        Complex32::new(a, a),
+        Complex32::new(-a, a),
+        Complex32::new(a, a),
+    ];
+    let taps = RrcFilterBuilder::new(sps).taps();
+    assert_eq!(output.len(), (symbols.len() + 8) * sps);
+    for (k, x) in output.iter().enumerate() {
+        let want: Complex32 = symbols
+            .iter()
+            .enumerate()
+            .filter(|(s, _)| k >= s * sps && k - s * sps < taps.len())
+            .map(|(s, p)| p * taps[k - s * sps])
+            .sum();
+        assert!(
+            (x - want).norm() < 1e-5,
+            "sample {k}: have {x}, want {want}"
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn psk_loopback_ideal() -> Result<()> {
+    let input = bits(2000);
+    for differential in [false, true] {
+        let output = loopback(
+            input.clone(),
+            vec![
+                PskTxBuilder::qpsk(4).differential(differential).build(),
+                PskRxBuilder::qpsk(4).differential(differential).build(),
+            ],
+        )?;
+        assert_recovered(&input, &output);
+    }
+    Ok(())
+}
+
+#[test]
+fn bpsk_loopback() -> Result<()> {
+    let input = bits(4000);
+    let output = loopback(
+        input.clone(),
+        vec![
+            PskTxBuilder::bpsk(4).build(),
+            ChannelModelBuilder::new()
+                .snr(15.0)
+                .frequency_offset(0.0005)
+                .phase_offset(2.0)
+                .timing_drift(1e-4)
+                .seed(1)
+                .build(),
+            PskRxBuilder::bpsk(4).build(),
+        ],
+    )?;
+    assert_recovered(&input, &output);
+    Ok(())
+}
+
+#[test]
+fn qpsk_loopback() -> Result<()> {
+    let input = bits(4000);
+    let output = loopback(
+        input.clone(),
+        vec![
+            PskTxBuilder::qpsk(4).build(),
+            ChannelModelBuilder::new()
+                .snr(20.0)
+                .frequency_offset(0.0005)
+                .phase_offset(2.0)
+                .timing_drift(1e-4)
+                .seed(2)
+                .build(),
+            PskRxBuilder::qpsk(4).build(),
+        ],
+    )?;
+    assert_recovered(&input, &output);
+    Ok(())
+}
+
+#[test]
+fn costas_loop_qpsk() -> Result<()> {
+    let a = 1.0 / 2.0f32.sqrt();
+    let input: Vec<Complex32> = bits(4000)
+        .chunks(2)
+        .enumerate()
+        .map(|(k, b)| {
+            let x = Complex32::new(
+                if b[0] == 1 { a } else { -a },
+                if b[1] == 1 { a } else { -a },
+            );
+            x * Complex32::from_polar(1.0, 0.5 + 0.01 * k as f32)
+        })
+        .collect();
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::<Complex32>::new(input));
+    let costas = fg.add_block(CostasLoop::new(4, 0.05));
+    let snk = fg.add_block(VectorSinkBuilder::<Complex32>::new().build());
+    fg.connect_stream(src, "out", costas, "in")?;
+    fg.connect_stream(costas, "out", snk, "in")?;
+    fg = Runtime::new().run(fg)?;
+
+    let v = fg.kernel::<VectorSink<Complex32>>(snk).unwrap().items();
+    // locked to the constellation points (up to a multiple of 90 degrees)
+    for x in v[500..].iter() {
+        assert!((x.re.abs() - a).abs() < 0.05 && (x.im.abs() - a).abs() < 0.05);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::QuadratureDemod;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+
+#[test]
+fn quadrature_demod() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    // tone at 0.1 * fs for 1000 samples, then at -0.2 * fs
+    let mut phase = 0.0f32;
+    let input: Vec<Complex32> = (0..2000)
+        .map(|i| {
+            phase += if i < 1000 { 0.1 } else { -0.2 } * 2.0 * std::f32::consts::PI;
+            Complex32::from_polar(0.5, phase)
+        })
+        .collect();
+
+    let gain = 2.0;
+    let src = fg.add_block(VectorSource::<Complex32>::new(input));
+    let demod = fg.add_block(QuadratureDemod::new(gain));
+    let snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+
+    fg.connect_stream(src, "out", demod, "in")?;
+    fg.connect_stream(demod, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let v = fg.kernel::<VectorSink<f32>>(snk).unwrap().items();
+    assert_eq!(v.len(), 2000);
+    for (i, x) in v.iter().enumerate().skip(1) {
+        let f = if i < 1000 { 0.1 } else { -0.2 };
+        let expected = gain * f * 2.0 * std::f32::consts::PI;
+        assert!((x - expected).abs() < 1e-3, "{}: {} != {}", i, x, expected);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,46 @@
+#![cfg(target_os = "linux")]
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::Copy;
+use futuresdr::blocks::Head;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::runtime::buffer::circular::Circular;
+use futuresdr::runtime::realtime::RealtimeConfig;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+
+#[test]
+fn fg_realtime() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let orig: Vec<u32> = (0..100_000).collect();
+    let src = fg.add_block(VectorSource::<u32>::new(orig.clone()));
+    let head = fg.add_block(Head::<u32>::new(orig.len() as u64));
+    let copy = fg.add_block(Copy::<u32>::new());
+    let snk = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+
+    fg.connect_stream(src, "out", head, "in")?;
+    fg.connect_stream_with_type(head, "out", copy, "in", Circular::new())?;
+    fg.connect_stream(copy, "out", snk, "in")?;
+
+    // missing permissions only cause warnings
+    let rt = Runtime::new().realtime(
+        RealtimeConfig::new()
+            .priority(10)
+            .lock_memory(true)
+            .prefault_buffers(true),
+    );
+    fg = rt.run(fg)?;
+
+    let snk = fg.kernel::<VectorSink<u32>>(snk).unwrap();
+    assert_eq!(snk.items(), &orig);
+
+    Ok(())
+}
+
+#[test]
+#[should_panic]
+fn invalid_priority() {
+    let _ = RealtimeConfig::new().priority(100);
+}
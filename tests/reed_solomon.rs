@@ -0,0 +1,109 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::blocks::MessagePipe;
+use futuresdr::blocks::ReedSolomon;
+use futuresdr::blocks::ReedSolomonDecoder;
+use futuresdr::blocks::ReedSolomonEncoder;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::futures::StreamExt;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+fn corrupt(v: &mut [u8], n: usize) {
+    let mut pos: Vec<usize> = Vec::new();
+    while pos.len() < n {
+        let p = rand::random::<usize>() % v.len();
+        if !pos.contains(&p) {
+            pos.push(p);
+            v[p] ^= rand::random::<u8>() | 1;
+        }
+    }
+}
+
+#[test]
+fn rs_ccsds() {
+    let rs = ReedSolomon::ccsds();
+    let data: Vec<u8> = (0..223).map(|_| rand::random()).collect();
+    let codeword = rs.encode(&data).unwrap();
+    assert_eq!(codeword.len(), 255);
+    assert_eq!(codeword[..223], data[..]);
+
+    for n in [0, 1, 8, 16] {
+        let mut c = codeword.clone();
+        corrupt(&mut c, n);
+        assert_eq!(rs.decode(&mut c), Some(n));
+        assert_eq!(c, data);
+    }
+
+    let mut c = codeword;
+    corrupt(&mut c, 40);
+    assert_eq!(rs.decode(&mut c), None);
+}
+
+#[test]
+fn rs_dvb() {
+    let rs = ReedSolomon::dvb();
+    let data: Vec<u8> = (0..188).map(|_| rand::random()).collect();
+    let codeword = rs.encode(&data).unwrap();
+    assert_eq!(codeword.len(), 204);
+
+    let mut c = codeword.clone();
+    corrupt(&mut c, 8);
+    assert_eq!(rs.decode(&mut c), Some(8));
+    assert_eq!(c, data);
+
+    assert!(rs.encode(&[0; 240]).is_none());
+}
+
+#[test]
+fn rs_interleaved() {
+    let rs = ReedSolomon::ccsds().interleave(4);
+    let data: Vec<u8> = (0..4 * 223).map(|_| rand::random()).collect();
+    let mut codeword = rs.encode(&data).unwrap();
+    assert_eq!(codeword.len(), 4 * 255);
+
+    // burst error spanning all codewords
+    for x in codeword[100..160].iter_mut() {
+        *x ^= 0xff;
+    }
+    assert_eq!(rs.decode(&mut codeword), Some(60));
+    assert_eq!(codeword, data);
+}
+
+#[test]
+fn rs_blocks() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let (tx, rx) = mpsc::channel(10);
+    let (tx_failed, rx_failed) = mpsc::channel(10);
+
+    let enc = fg.add_block(ReedSolomonEncoder::new(ReedSolomon::dvb()));
+    let dec = fg.add_block(ReedSolomonDecoder::new(ReedSolomon::dvb()));
+    let pipe = fg.add_block(MessagePipe::new(tx));
+    let pipe_failed = fg.add_block(MessagePipe::new(tx_failed));
+
+    fg.connect_message(enc, "out", dec, "in")?;
+    fg.connect_message(dec, "out", pipe, "in")?;
+    fg.connect_message(dec, "failed", pipe_failed, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+
+    let data: Vec<u8> = (0..100).map(|_| rand::random()).collect();
+    let mut corrupted = ReedSolomon::dvb().encode(&data).unwrap();
+    corrupt(&mut corrupted, 30);
+
+    block_on(async {
+        handle.call(enc, "in", Pmt::Blob(data.clone())).await?;
+        handle.call(dec, "in", Pmt::Blob(corrupted.clone())).await?;
+        handle.terminate().await
+    })?;
+    drop(block_on(task)?);
+
+    let frames: Vec<Pmt> = block_on(rx.collect());
+    assert_eq!(frames, vec![Pmt::Blob(data)]);
+    let failed: Vec<Pmt> = block_on(rx_failed.collect());
+    assert_eq!(failed, vec![Pmt::Blob(corrupted)]);
+
+    Ok(())
+}
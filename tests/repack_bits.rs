@@ -0,0 +1,70 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::BitOrder;
+use futuresdr::blocks::PackBits;
+use futuresdr::blocks::RepackBits;
+use futuresdr::blocks::UnpackBits;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+
+fn run(input: Vec<u8>, block: Block) -> Result<Vec<u8>> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(VectorSource::<u8>::new(input));
+    let block = fg.add_block(block);
+    let snk = fg.add_block(VectorSinkBuilder::<u8>::new().build());
+
+    fg.connect_stream(src, "out", block, "in")?;
+    fg.connect_stream(block, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    Ok(fg.kernel::<VectorSink<u8>>(snk).unwrap().items().clone())
+}
+
+#[test]
+fn unpack_pack() -> Result<()> {
+    let bits = run(vec![0b1100_1010, 0x01], UnpackBits::new(BitOrder::MsbFirst))?;
+    assert_eq!(bits, [1, 1, 0, 0, 1, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 1]);
+
+    let bits = run(vec![0b1100_1010], UnpackBits::new(BitOrder::LsbFirst))?;
+    assert_eq!(bits, [0, 1, 0, 1, 0, 0, 1, 1]);
+
+    let bytes = run(
+        vec![0, 1, 0, 1, 0, 0, 1, 1, 1, 0],
+        PackBits::new(BitOrder::LsbFirst),
+    )?;
+    assert_eq!(bytes, [0b1100_1010]);
+
+    let input: Vec<u8> = (0..10_000).map(|_| rand::random()).collect();
+    for order in [BitOrder::MsbFirst, BitOrder::LsbFirst] {
+        let bits = run(input.clone(), UnpackBits::new(order))?;
+        assert_eq!(run(bits, PackBits::new(order))?, input);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn repack() -> Result<()> {
+    let symbols = run(
+        vec![0b1100_1010, 0b0111_0001],
+        RepackBits::new(8, 3, BitOrder::MsbFirst),
+    )?;
+    assert_eq!(symbols, [0b110, 0b010, 0b100, 0b111, 0b000]);
+
+    let symbols = run(vec![0b1100_1010], RepackBits::new(8, 2, BitOrder::LsbFirst))?;
+    assert_eq!(symbols, [0b10, 0b10, 0b00, 0b11]);
+
+    let input: Vec<u8> = (0..3000).map(|_| rand::random()).collect();
+    for order in [BitOrder::MsbFirst, BitOrder::LsbFirst] {
+        let symbols = run(input.clone(), RepackBits::new(8, 3, order))?;
+        assert!(symbols.iter().all(|s| *s < 8));
+        assert_eq!(run(symbols, RepackBits::new(3, 8, order))?, input);
+    }
+
+    Ok(())
+}
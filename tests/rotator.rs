@@ -0,0 +1,98 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::blocks::Fs4Shift;
+use futuresdr::blocks::NullSink;
+use futuresdr::blocks::Rotator;
+use futuresdr::blocks::Source;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+use std::f64::consts::PI;
+
+fn tone(freq: f64, sample_rate: f64, n: usize) -> Vec<Complex32> {
+    (0..n)
+        .map(|i| Complex32::from_polar(1.0, (2.0 * PI * freq / sample_rate * i as f64) as f32))
+        .collect()
+}
+
+fn run(block: Block, input: Vec<Complex32>) -> Result<Vec<Complex32>> {
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::<Complex32>::new(input));
+    let block = fg.add_block(block);
+    let snk = fg.add_block(VectorSinkBuilder::<Complex32>::new().build());
+    fg.connect_stream(src, "out", block, "in")?;
+    fg.connect_stream(block, "out", snk, "in")?;
+    fg = Runtime::new().run(fg)?;
+    Ok(fg
+        .kernel::<VectorSink<Complex32>>(snk)
+        .unwrap()
+        .items()
+        .clone())
+}
+
+#[test]
+fn fs4_shift() -> Result<()> {
+    let input = tone(1000.0, 48e3, 10000);
+
+    for (up, freq) in [(true, 12000.0), (false, -12000.0)] {
+        let v = run(Fs4Shift::new(up), input.clone())?;
+        let expected = tone(1000.0 + freq, 48e3, 10000);
+        assert_eq!(v.len(), expected.len());
+        for (a, b) in v.iter().zip(expected.iter()) {
+            assert!((a - b).norm() < 1e-2, "{} != {}", a, b);
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn rotator() -> Result<()> {
+    let v = run(Rotator::new(48e3, -1234.5), tone(1234.5, 48e3, 200000))?;
+    assert_eq!(v.len(), 200000);
+    for x in v.iter() {
+        assert!((x - Complex32::new(1.0, 0.0)).norm() < 1e-2, "{}", x);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn rotator_set_frequency() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(Source::new(|| Complex32::new(1.0, 0.0)));
+    let rotator = fg.add_block(Rotator::new(1e6, 1000.0));
+    let snk = fg.add_block(NullSink::<Complex32>::new());
+    fg.connect_stream(src, "out", rotator, "in")?;
+    fg.connect_stream(rotator, "out", snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+    block_on(async {
+        assert_eq!(
+            handle.callback(rotator, "freq", Pmt::Null).await?,
+            Pmt::F64(1000.0)
+        );
+        assert_eq!(
+            handle.callback(rotator, "freq", Pmt::F32(-250.0)).await?,
+            Pmt::F64(-250.0)
+        );
+        // wrong type is ignored
+        assert_eq!(
+            handle
+                .callback(rotator, "freq", Pmt::String("foo".to_string()))
+                .await?,
+            Pmt::F64(-250.0)
+        );
+        handle.terminate().await
+    })?;
+    block_on(task)?;
+
+    Ok(())
+}
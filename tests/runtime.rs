@@ -10,7 +10,10 @@ use futuresdr::blocks::Throttle;
 use futuresdr::blocks::VectorSink;
 use futuresdr::blocks::VectorSinkBuilder;
 use futuresdr::blocks::VectorSource;
+use futuresdr::futures::StreamExt;
+use futuresdr::runtime::BlockState;
 use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::FlowgraphEvent;
 use futuresdr::runtime::Runtime;
 
 #[test]
@@ -70,6 +73,50 @@ fn fg_terminate() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn fg_events() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let null_source = fg.add_block(NullSource::<f32>::new());
+    let throttle = fg.add_block(Throttle::<f32>::new(10.0));
+    let null_sink = fg.add_block(NullSink::<f32>::new());
+
+    fg.connect_stream(null_source, "out", throttle, "in")?;
+    fg.connect_stream(throttle, "out", null_sink, "in")?;
+
+    let rt = Runtime::new();
+    let (fg, mut handle) = block_on(rt.start(fg));
+    block_on(async move {
+        let mut events = handle.subscribe().await.unwrap();
+        let stats = handle.stats().await.unwrap();
+        assert_eq!(stats.running, 3);
+        assert_eq!(stats.done, 0);
+
+        handle.terminate().await.unwrap();
+        let _ = fg.await;
+
+        let mut done = 0;
+        let mut terminated = false;
+        while let Some(e) = events.next().await {
+            match e {
+                FlowgraphEvent::BlockState {
+                    state: BlockState::Done,
+                    ..
+                } => done += 1,
+                FlowgraphEvent::Terminated { error } => {
+                    assert!(!error);
+                    terminated = true;
+                }
+                _ => {}
+            }
+        }
+        assert_eq!(done, 3);
+        assert!(terminated);
+    });
+
+    Ok(())
+}
+
 #[test]
 fn fg_rand_vec() -> Result<()> {
     let mut fg = Flowgraph::new();
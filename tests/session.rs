@@ -0,0 +1,185 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::blocks::soapy::*;
+use futuresdr::blocks::NullSink;
+use futuresdr::blocks::NullSource;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::session::SessionEvent;
+use futuresdr::runtime::session::SessionReplay;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+const RX: SoapyDirection = SoapyDirection::Rx;
+
+fn session_file(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "futuresdr-session-{}-{}.jsonl",
+        name,
+        std::process::id()
+    ))
+}
+
+fn soapy_flowgraph(dev: &MockSoapyDevice) -> Result<(Flowgraph, usize)> {
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(
+        MockSoapySourceBuilder::new()
+            .device(SoapyDevSpec::Mock(dev.clone()))
+            .dev_channels(vec![0, 1])
+            .sample_rate(1e6)
+            .freq(100e6)
+            .build(),
+    );
+    let snk1 = fg.add_block(NullSink::<Complex32>::new());
+    let snk2 = fg.add_block(NullSink::<Complex32>::new());
+    fg.connect_stream(src, "out", snk1, "in")?;
+    fg.connect_stream(src, "out2", snk2, "in")?;
+    Ok((fg, src))
+}
+
+fn record(path: &PathBuf, dev: &MockSoapyDevice) -> Result<()> {
+    let (fg, src) = soapy_flowgraph(dev)?;
+    let rt = Runtime::new().record_session(path)?;
+    let (task, mut handle) = block_on(rt.start(fg));
+
+    block_on(async {
+        handle.callback(src, "freq", Pmt::F64(101e6)).await?;
+        handle.description().await?;
+        let cmd = Pmt::MapStrPmt(HashMap::from([
+            ("chan".to_owned(), Pmt::U32(1)),
+            ("freq".to_owned(), Pmt::F64(102e6)),
+            ("gain".to_owned(), Pmt::F32(2.0)),
+        ]));
+        handle.call(src, "cmd", cmd).await?;
+        assert!(handle.call(src, "foo", Pmt::Null).await.is_err());
+        handle.terminate().await?;
+        task.await?;
+        Result::<()>::Ok(())
+    })
+}
+
+#[test]
+fn record_session() -> Result<()> {
+    let path = session_file("record");
+    record(&path, &MockSoapyDevice::new(2))?;
+
+    let replay = SessionReplay::load(&path)?;
+    let events = replay.events();
+    assert_eq!(events.len(), 6);
+    assert!(matches!(
+        events[0],
+        SessionEvent::Start { flowgraph: 0, .. }
+    ));
+    assert!(matches!(
+        &events[1],
+        SessionEvent::Callback {
+            block_id: 0,
+            data: Some(Pmt::F64(f)),
+            result: Ok(Some(_)),
+            ..
+        } if *f == 101e6
+    ));
+    assert!(matches!(&events[2], SessionEvent::Query { query, .. } if query == "description"));
+    assert!(matches!(
+        &events[3],
+        SessionEvent::Call {
+            data: Some(Pmt::MapStrPmt(_)),
+            result: Ok(()),
+            ..
+        }
+    ));
+    assert!(matches!(
+        &events[4],
+        SessionEvent::Call { result: Err(_), .. }
+    ));
+    assert!(matches!(events[5], SessionEvent::Terminate { .. }));
+    assert!(events.windows(2).all(|w| w[0].time() <= w[1].time()));
+
+    std::fs::remove_file(path)?;
+    Ok(())
+}
+
+#[test]
+fn replay_session() -> Result<()> {
+    let path = session_file("replay");
+    let recorded = MockSoapyDevice::new(2);
+    record(&path, &recorded)?;
+
+    let dev = MockSoapyDevice::new(2);
+    let (fg, _) = soapy_flowgraph(&dev)?;
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+
+    let replay = SessionReplay::load(&path)?.speed(f64::INFINITY);
+    let report = block_on(replay.replay(&mut handle))?;
+    block_on(task)?;
+
+    assert!(report.is_ok(), "{}", report);
+    assert_eq!(report.replayed, 4);
+    assert_eq!(report.skipped, 0);
+    for chan in 0..2 {
+        assert_eq!(dev.settings(&RX, chan), recorded.settings(&RX, chan));
+    }
+    assert_eq!(dev.settings(&RX, 0).frequency, Some(101e6));
+    assert_eq!(dev.settings(&RX, 1).frequency, Some(102e6));
+    assert_eq!(dev.settings(&RX, 1).gain, Some(2.0));
+
+    std::fs::remove_file(path)?;
+    Ok(())
+}
+
+#[test]
+fn replay_mismatch() -> Result<()> {
+    let path = session_file("mismatch");
+    record(&path, &MockSoapyDevice::new(2))?;
+
+    // pretend that the call to the invalid port succeeded in the field
+    let mut events = SessionReplay::load(&path)?.events().to_vec();
+    if let SessionEvent::Call { result, .. } = &mut events[4] {
+        *result = Ok(());
+    }
+
+    let dev = MockSoapyDevice::new(2);
+    let (fg, _) = soapy_flowgraph(&dev)?;
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+
+    let replay = SessionReplay::new(events).speed(f64::INFINITY);
+    let report = block_on(replay.replay(&mut handle))?;
+    block_on(task)?;
+
+    assert!(!report.is_ok());
+    assert_eq!(report.mismatches.len(), 1);
+    assert_eq!(report.mismatches[0].event, 4);
+
+    std::fs::remove_file(path)?;
+    Ok(())
+}
+
+#[test]
+fn replay_other_flowgraph() -> Result<()> {
+    let path = session_file("other");
+    record(&path, &MockSoapyDevice::new(2))?;
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(NullSource::<Complex32>::new());
+    let snk = fg.add_block(NullSink::<Complex32>::new());
+    fg.connect_stream(src, "out", snk, "in")?;
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+
+    let replay = SessionReplay::load(&path)?;
+    assert!(block_on(replay.replay(&mut handle)).is_err());
+    assert!(block_on(replay.clone().flowgraph(1).replay(&mut handle)).is_err());
+
+    block_on(async {
+        handle.terminate().await?;
+        task.await?;
+        Result::<()>::Ok(())
+    })?;
+
+    std::fs::remove_file(path)?;
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::ShmSinkBuilder;
+use futuresdr::blocks::ShmSourceBuilder;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+use std::time::Duration;
+
+#[test]
+fn shm_round_trip() -> Result<()> {
+    let name = format!("futuresdr-test-{}", std::process::id());
+    let input: Vec<u32> = (0..100_000).collect();
+
+    let tx = {
+        let input = input.clone();
+        let name = name.clone();
+        std::thread::spawn(move || -> Result<()> {
+            let mut fg = Flowgraph::new();
+            let src = fg.add_block(VectorSource::new(input));
+            // small ring, to exercise wrap-around and back pressure
+            let snk = fg.add_block(ShmSinkBuilder::<u32>::new(name).capacity(1000).build());
+            fg.connect_stream(src, "out", snk, "in")?;
+            Runtime::new().run(fg)?;
+            Ok(())
+        })
+    };
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(
+        ShmSourceBuilder::<u32>::new(name)
+            .timeout(Duration::from_secs(10))
+            .build(),
+    );
+    let snk = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+    fg.connect_stream(src, "out", snk, "in")?;
+    fg = Runtime::new().run(fg)?;
+    tx.join().unwrap()?;
+
+    let snk = fg.kernel::<VectorSink<u32>>(snk).unwrap();
+    assert_eq!(snk.items(), &input);
+
+    Ok(())
+}
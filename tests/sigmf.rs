@@ -0,0 +1,264 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_trait::async_trait;
+use futuresdr::blocks::MessagePipe;
+use futuresdr::blocks::SigmfSinkBuilder;
+use futuresdr::blocks::SigmfSource;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::futures::StreamExt;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::{
+    Block, BlockMeta, BlockMetaBuilder, Flowgraph, Kernel, MessageIo, MessageIoBuilder, Pmt,
+    Runtime, StreamIo, StreamIoBuilder, Tag, WorkIo,
+};
+use std::path::PathBuf;
+
+fn temp_base(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("futuresdr-test-{}-{}", std::process::id(), name))
+}
+
+/// Output a vector of samples, tagged at the given indices.
+struct TagSource {
+    items: Vec<f32>,
+    tags: Vec<(usize, Tag)>,
+    n: usize,
+}
+
+impl TagSource {
+    fn block(items: Vec<f32>, tags: Vec<(usize, Tag)>) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("TagSource").build(),
+            StreamIoBuilder::new().add_output::<f32>("out").build(),
+            MessageIoBuilder::<Self>::new().build(),
+            TagSource { items, tags, n: 0 },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for TagSource {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let o = sio.output(0).slice::<f32>();
+        let m = std::cmp::min(o.len(), self.items.len() - self.n);
+        o[..m].copy_from_slice(&self.items[self.n..self.n + m]);
+        for (index, tag) in self.tags.iter() {
+            if *index >= self.n && *index < self.n + m {
+                sio.output(0).add_tag(index - self.n, tag.clone());
+            }
+        }
+        self.n += m;
+        sio.output(0).produce(m);
+
+        if self.n == self.items.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Store samples and string and named tags with their absolute index.
+#[derive(Default)]
+struct TagSink<T> {
+    items: Vec<T>,
+    tags: Vec<(usize, String, Option<f32>)>,
+}
+
+impl<T: Copy + Send + 'static> TagSink<T> {
+    fn into_block(self) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("TagSink").build(),
+            StreamIoBuilder::new().add_input::<T>("in").build(),
+            MessageIoBuilder::<Self>::new().build(),
+            self,
+        )
+    }
+}
+
+#[async_trait]
+impl<T: Copy + Send + 'static> Kernel for TagSink<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<T>();
+        for t in sio.input(0).tags().iter().filter(|t| t.index < i.len()) {
+            let index = self.items.len() + t.index;
+            match &t.tag {
+                Tag::String(s) => self.tags.push((index, s.clone(), None)),
+                Tag::NamedF32(s, v) => self.tags.push((index, s.clone(), Some(*v))),
+                _ => {}
+            }
+        }
+        self.items.extend_from_slice(i);
+        sio.input(0).consume(i.len());
+
+        if sio.input(0).finished() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn sigmf_round_trip() -> Result<()> {
+    let base = temp_base("round-trip");
+    let base = base.to_str().unwrap();
+    let input: Vec<f32> = (0..10000).map(|x| x as f32).collect();
+    let tags = vec![
+        (10, Tag::String("burst".to_string())),
+        (5000, Tag::NamedF32("snr".to_string(), 12.5)),
+        (9999, Tag::NamedUsize("len".to_string(), 3)),
+    ];
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(TagSource::block(input.clone(), tags));
+    let snk = fg.add_block(
+        SigmfSinkBuilder::<f32>::new(base)
+            .sample_rate(48e3)
+            .frequency(100e6)
+            .description("test")
+            .build(),
+    );
+    fg.connect_stream(src, "out", snk, "in")?;
+    Runtime::new().run(fg)?;
+
+    let meta = std::fs::read_to_string(format!("{base}.sigmf-meta"))?;
+    let meta: serde_json::Value = serde_json::from_str(&meta)?;
+    assert_eq!(meta["global"]["core:datatype"], "rf32_le");
+    assert_eq!(meta["global"]["core:version"], "1.0.0");
+    assert_eq!(meta["global"]["core:sample_rate"], 48e3);
+    assert_eq!(meta["global"]["core:description"], "test");
+    assert_eq!(meta["captures"][0]["core:sample_start"], 0);
+    assert_eq!(meta["captures"][0]["core:frequency"], 100e6);
+    let annotations = meta["annotations"].as_array().unwrap();
+    assert_eq!(annotations.len(), 3);
+    assert_eq!(annotations[0]["core:sample_start"], 10);
+    assert_eq!(annotations[0]["core:label"], "burst");
+    assert_eq!(annotations[1]["core:sample_start"], 5000);
+    assert_eq!(annotations[1]["core:comment"], "12.5");
+    assert_eq!(annotations[2]["core:label"], "len");
+
+    let data = std::fs::read(format!("{base}.sigmf-data"))?;
+    assert_eq!(data.len(), input.len() * 4);
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(SigmfSource::<f32>::new(base));
+    let snk = fg.add_block(TagSink::<f32>::default().into_block());
+    fg.connect_stream(src, "out", snk, "in")?;
+    let fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<TagSink<f32>>(snk).unwrap();
+    assert_eq!(snk.items, input);
+    assert_eq!(
+        snk.tags,
+        vec![
+            (10, "burst".to_string(), None),
+            (5000, "snr".to_string(), Some(12.5)),
+            (9999, "len".to_string(), Some(3.0)),
+        ]
+    );
+
+    std::fs::remove_file(format!("{base}.sigmf-meta"))?;
+    std::fs::remove_file(format!("{base}.sigmf-data"))?;
+    Ok(())
+}
+
+#[test]
+fn sigmf_source_metadata() -> Result<()> {
+    let base = temp_base("source");
+    let base = base.to_str().unwrap();
+
+    let data: Vec<u8> = (0..1000i16)
+        .flat_map(|x| [x, -x])
+        .flat_map(|x| x.to_le_bytes())
+        .collect();
+    std::fs::write(format!("{base}.sigmf-data"), data)?;
+    let meta = serde_json::json!({
+        "global": {
+            "core:datatype": "ci16_le",
+            "core:version": "1.0.0",
+            "core:sample_rate": 2e6,
+        },
+        "captures": [
+            { "core:sample_start": 0, "core:frequency": 915e6 },
+            { "core:sample_start": 600, "core:frequency": 916e6 },
+        ],
+        "annotations": [
+            { "core:sample_start": 700, "core:label": "packet", "core:comment": "crc ok" },
+        ],
+    });
+    std::fs::write(format!("{base}.sigmf-meta"), meta.to_string())?;
+
+    let mut fg = Flowgraph::new();
+    let (tx, rx) = mpsc::channel(10);
+    let src = fg.add_block(SigmfSource::<Complex32>::new(format!("{base}.sigmf-meta")));
+    let snk = fg.add_block(TagSink::<Complex32>::default().into_block());
+    let pipe = fg.add_block(MessagePipe::new(tx));
+    fg.connect_stream(src, "out", snk, "in")?;
+    fg.connect_message(src, "meta", pipe, "in")?;
+    let fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<TagSink<Complex32>>(snk).unwrap();
+    assert_eq!(snk.items.len(), 1000);
+    for (i, x) in snk.items.iter().enumerate() {
+        let v = i as f32 / 32768.0;
+        assert_eq!(*x, Complex32::new(v, -v));
+    }
+    assert_eq!(snk.tags, vec![(700, "packet".to_string(), None)]);
+
+    drop(fg);
+
+    let messages: Vec<Pmt> = futuresdr::async_io::block_on(rx.collect());
+    assert_eq!(messages.len(), 3);
+    match &messages[0] {
+        Pmt::MapStrPmt(m) => {
+            assert_eq!(m["datatype"], Pmt::String("ci16_le".to_string()));
+            assert_eq!(m["sample_rate"], Pmt::F64(2e6));
+        }
+        _ => panic!("wrong metadata {:?}", messages[0]),
+    }
+    match &messages[2] {
+        Pmt::MapStrPmt(m) => {
+            assert_eq!(m["sample_start"], Pmt::U64(600));
+            assert_eq!(m["frequency"], Pmt::F64(916e6));
+        }
+        _ => panic!("wrong capture {:?}", messages[2]),
+    }
+
+    std::fs::remove_file(format!("{base}.sigmf-meta"))?;
+    std::fs::remove_file(format!("{base}.sigmf-data"))?;
+    Ok(())
+}
+
+#[test]
+fn sigmf_source_rejects_complex_for_real() -> Result<()> {
+    let base = temp_base("reject");
+    let base = base.to_str().unwrap();
+    std::fs::write(format!("{base}.sigmf-data"), [0u8; 8])?;
+    let meta = serde_json::json!({
+        "global": { "core:datatype": "cf32_le", "core:version": "1.0.0" },
+        "captures": [],
+        "annotations": [],
+    });
+    std::fs::write(format!("{base}.sigmf-meta"), meta.to_string())?;
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(SigmfSource::<f32>::new(base));
+    let snk = fg.add_block(TagSink::<f32>::default().into_block());
+    fg.connect_stream(src, "out", snk, "in")?;
+    assert!(Runtime::new().run(fg).is_err());
+
+    std::fs::remove_file(format!("{base}.sigmf-meta"))?;
+    std::fs::remove_file(format!("{base}.sigmf-data"))?;
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::blocks::Head;
+use futuresdr::blocks::NullSink;
+use futuresdr::blocks::SignalSourceBuilder;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+#[test]
+fn sawtooth() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(
+        SignalSourceBuilder::<f32>::sawtooth(100.0, 1000.0)
+            .initial_phase(-std::f32::consts::PI)
+            .amplitude(2.0)
+            .offset(-1.0)
+            .build(),
+    );
+    let head = fg.add_block(Head::<f32>::new(20));
+    let snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+
+    fg.connect_stream(src, "out", head, "in")?;
+    fg.connect_stream(head, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let v = fg.kernel::<VectorSink<f32>>(snk).unwrap().items();
+    assert_eq!(v.len(), 20);
+    for (i, x) in v.iter().enumerate() {
+        let expected = (i % 10) as f32 / 5.0 - 1.0;
+        assert!((x - expected).abs() < 1e-4, "{} != {}", x, expected);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn retune() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(SignalSourceBuilder::<f32>::sin(440.0, 48000.0).build());
+    let snk = fg.add_block(NullSink::<f32>::new());
+    fg.connect_stream(src, "out", snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+
+    block_on(async move {
+        assert!(matches!(
+            handle.callback(src, "freq", Pmt::F64(1000.0)).await,
+            Ok(Pmt::F32(f)) if f == 1000.0
+        ));
+        assert!(matches!(
+            handle.callback(src, "freq", Pmt::Null).await,
+            Ok(Pmt::F32(f)) if f == 1000.0
+        ));
+        assert!(handle.call(src, "amplitude", Pmt::F32(0.5)).await.is_ok());
+        assert!(handle
+            .call(src, "amplitude", Pmt::String("foo".to_string()))
+            .await
+            .is_err());
+        handle.terminate().await.unwrap();
+        let _ = task.await;
+    });
+
+    Ok(())
+}
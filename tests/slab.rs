@@ -1,15 +1,21 @@
 use std::iter::repeat_with;
 
 use futuresdr::anyhow::Result;
+use futuresdr::blocks::Apply;
 use futuresdr::blocks::Copy;
+use futuresdr::blocks::Fft;
 use futuresdr::blocks::Head;
 use futuresdr::blocks::NullSource;
 use futuresdr::blocks::VectorSink;
 use futuresdr::blocks::VectorSinkBuilder;
 use futuresdr::blocks::VectorSource;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::buffer::memory::Allocator;
+use futuresdr::runtime::buffer::memory::HUGE_PAGE_SIZE;
 use futuresdr::runtime::buffer::slab::Slab;
 use futuresdr::runtime::Flowgraph;
 use futuresdr::runtime::Runtime;
+use rustfft::FftPlanner;
 
 #[test]
 fn flowgraph() -> Result<()> {
@@ -72,3 +78,111 @@ fn fg_rand_vec() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn fg_allocators() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let n_items = 1_000_000;
+    let orig: Vec<f32> = repeat_with(rand::random::<f32>).take(n_items).collect();
+
+    let src = VectorSource::<f32>::new(orig.clone());
+    let copy = Copy::<f32>::new();
+    let snk = VectorSinkBuilder::<f32>::new().build();
+
+    let src = fg.add_block(src);
+    let copy = fg.add_block(copy);
+    let snk = fg.add_block(snk);
+
+    let aligned = Slab::new().with_allocator(Allocator::Aligned(4096));
+    let huge = Slab::with_size(HUGE_PAGE_SIZE).with_allocator(Allocator::HugePages);
+    fg.connect_stream_with_type(src, "out", copy, "in", aligned)?;
+    fg.connect_stream_with_type(copy, "out", snk, "in", huge)?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<VectorSink<f32>>(snk).unwrap();
+    let v = snk.items();
+
+    assert_eq!(v, &orig);
+
+    Ok(())
+}
+
+#[test]
+fn allocator_alignment() {
+    let b = Allocator::Aligned(4096).allocate(10_000);
+    assert_eq!(b.len(), 10_000);
+    assert_eq!(b.as_ptr() as usize % 4096, 0);
+    assert!(b.iter().all(|x| *x == 0));
+
+    let b = Allocator::HugePages.allocate(100);
+    assert_eq!(b.len(), 100);
+    assert_eq!(b.as_ptr() as usize % HUGE_PAGE_SIZE, 0);
+}
+
+#[test]
+fn fg_in_place() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let n_items = 1_000_000;
+    let orig: Vec<f32> = repeat_with(rand::random::<f32>).take(n_items).collect();
+
+    let src = fg.add_block(VectorSource::<f32>::new(orig.clone()));
+    let double = fg.add_block(Apply::new(|x: &f32| x * 2.0));
+    let add = fg.add_block(Apply::new(|x: &f32| x + 1.0));
+    let to_bits = fg.add_block(Apply::new(|x: &f32| x.to_bits()));
+    let snk = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+
+    fg.connect_stream_with_type(src, "out", double, "in", Slab::new())?;
+    fg.connect_stream_with_type(double, "out", add, "in", Slab::new())?;
+    fg.connect_stream_with_type(add, "out", to_bits, "in", Slab::with_buffers(4))?;
+    // different buffer size falls back to copying
+    fg.connect_stream_with_type(to_bits, "out", snk, "in", Slab::with_size(4096))?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<VectorSink<u32>>(snk).unwrap();
+    let v = snk.items();
+
+    assert_eq!(v.len(), n_items);
+    for (o, i) in v.iter().zip(orig) {
+        assert_eq!(f32::from_bits(*o), i * 2.0 + 1.0);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn fg_fft() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    // frames of 2048 items do not align with the default slabs
+    let len = 2048;
+    let n_items = len * 50;
+    let orig: Vec<Complex32> = repeat_with(|| Complex32::new(rand::random(), rand::random()))
+        .take(n_items)
+        .collect();
+
+    let src = fg.add_block(VectorSource::<Complex32>::new(orig.clone()));
+    let fft = fg.add_block(Fft::new(len));
+    let snk = fg.add_block(VectorSinkBuilder::<Complex32>::new().build());
+
+    fg.connect_stream_with_type(src, "out", fft, "in", Slab::new())?;
+    fg.connect_stream_with_type(fft, "out", snk, "in", Slab::new())?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<VectorSink<Complex32>>(snk).unwrap();
+    let v = snk.items();
+    assert_eq!(v.len(), n_items);
+
+    let mut expected = orig;
+    let plan = FftPlanner::<f32>::new().plan_fft_forward(len);
+    plan.process(&mut expected);
+    for (a, b) in v.iter().zip(expected) {
+        assert!((a - b).norm() < 1e-2);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,397 @@
+//! Hardware-free analogues of the Soapy tests, using a [MockSoapyDevice].
+
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::blocks::soapy::*;
+use futuresdr::blocks::Head;
+use futuresdr::blocks::NullSink;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::macros::connect;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::buffer::circular::Circular;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+use std::collections::HashMap;
+use std::time::Duration;
+
+use SoapyConfigItem as SCI;
+
+const RX: SoapyDirection = SoapyDirection::Rx;
+const TX: SoapyDirection = SoapyDirection::Tx;
+
+#[test]
+fn builder_compat() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let src = MockSoapySourceBuilder::new()
+        .freq(100e6)
+        .sample_rate(3.2e6)
+        .gain(34.0)
+        .build();
+
+    let head = Head::<Complex32>::new(1024);
+    let snk = NullSink::<Complex32>::new();
+
+    connect!(fg, src > head > snk);
+
+    Runtime::new().run(fg)?;
+
+    Ok(())
+}
+
+#[test]
+fn builder_config() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let dev = MockSoapyDevice::new(1);
+
+    let ss = MockSoapySourceBuilder::new()
+        .device(SoapyDevSpec::Mock(dev.clone()))
+        .dev_channels(vec![0])
+        .sample_rate(1e6)
+        .freq(100e6)
+        .gain(1.0)
+        .antenna("RX2")
+        .build();
+
+    let head = Head::<Complex32>::new(1024);
+    let snk = NullSink::<Complex32>::new();
+    connect!(fg, ss > head > snk);
+
+    Runtime::new().run(fg)?;
+
+    let s = dev.settings(&RX, 0);
+    assert_eq!(s.sample_rate, Some(1e6));
+    assert_eq!(s.frequency, Some(100e6));
+    assert_eq!(s.gain, Some(1.0));
+    assert_eq!(s.antenna.as_deref(), Some("RX2"));
+    assert_eq!(dev.settings(&TX, 0), MockChannelSettings::default());
+    Ok(())
+}
+
+#[test]
+fn builder_multichan() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let dev = MockSoapyDevice::new(2);
+
+    let src = fg.add_block(
+        MockSoapySourceBuilder::new()
+            .device(SoapyDevSpec::Mock(dev.clone()))
+            .dev_channels(vec![0, 1])
+            .sample_rate(1e6)
+            .freq(90e6)
+            .cfg_channel(0)
+            .gain(2.0)
+            .cfg_channels(Some(vec![1]))
+            .gain(3.0)
+            .build(),
+    );
+    let head1 = fg.add_block(Head::<Complex32>::new(100));
+    let head2 = fg.add_block(Head::<Complex32>::new(100));
+    let snk1 = fg.add_block(NullSink::<Complex32>::new());
+    let snk2 = fg.add_block(NullSink::<Complex32>::new());
+
+    fg.connect_stream(src, "out", head1, "in")?;
+    fg.connect_stream(src, "out2", head2, "in")?;
+    fg.connect_stream(head1, "out", snk1, "in")?;
+    fg.connect_stream(head2, "out", snk2, "in")?;
+
+    Runtime::new().run(fg)?;
+
+    assert_eq!(dev.settings(&RX, 0).frequency, Some(90e6));
+    assert_eq!(dev.settings(&RX, 0).gain, Some(2.0));
+    assert_eq!(dev.settings(&RX, 1).frequency, Some(90e6));
+    assert_eq!(dev.settings(&RX, 1).gain, Some(3.0));
+    Ok(())
+}
+
+#[test]
+fn invalid_channel() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let dev = MockSoapyDevice::new(1);
+
+    let src = MockSoapySourceBuilder::new()
+        .device(SoapyDevSpec::Mock(dev))
+        .dev_channels(vec![0, 1])
+        .build();
+    let snk = NullSink::<Complex32>::new();
+    let snk2 = NullSink::<Complex32>::new();
+    connect!(fg, src > snk; src.out2 > snk2);
+
+    assert!(Runtime::new().run(fg).is_err());
+    Ok(())
+}
+
+#[test]
+fn config_freq_gain_ports() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let dev = MockSoapyDevice::new(1);
+
+    let ss = fg.add_block(
+        MockSoapySourceBuilder::new()
+            .device(SoapyDevSpec::Mock(dev.clone()))
+            .sample_rate(1e6)
+            .freq(100e6)
+            .gain(1.0)
+            .build(),
+    );
+    let snk = fg.add_block(NullSink::<Complex32>::new());
+    fg.connect_stream(ss, "out", snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut fg_handle) = block_on(rt.start(fg));
+
+    block_on(async {
+        fg_handle.callback(ss, "freq", Pmt::F64(102e6)).await?;
+        // use Pmt::U32 to test type conversion
+        fg_handle.callback(ss, "gain", Pmt::U32(2)).await?;
+        fg_handle.callback(ss, "sample_rate", Pmt::F32(2e6)).await?;
+        Result::<()>::Ok(())
+    })?;
+
+    assert_eq!(dev.settings(&RX, 0).frequency, Some(102e6));
+    assert_eq!(dev.settings(&RX, 0).gain, Some(2.0));
+    assert_eq!(dev.settings(&RX, 0).sample_rate, Some(2e6));
+
+    block_on(async {
+        fg_handle.terminate().await?;
+        task.await?;
+        Result::<()>::Ok(())
+    })
+}
+
+#[test]
+fn config_cmd_map() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let dev = MockSoapyDevice::new(2);
+
+    let ss = fg.add_block(
+        MockSoapySourceBuilder::new()
+            .device(SoapyDevSpec::Mock(dev.clone()))
+            .dev_channels(vec![0, 1])
+            .sample_rate(1e6)
+            .freq(100e6)
+            .gain(1.0)
+            .build(),
+    );
+    let snk1 = fg.add_block(NullSink::<Complex32>::new());
+    let snk2 = fg.add_block(NullSink::<Complex32>::new());
+    fg.connect_stream(ss, "out", snk1, "in")?;
+    fg.connect_stream(ss, "out2", snk2, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut fg_handle) = block_on(rt.start(fg));
+
+    // Like a GNU Radio Soapy block
+    block_on(async {
+        let pmt = Pmt::MapStrPmt(HashMap::from([
+            ("chan".to_owned(), Pmt::U32(1)),
+            ("freq".to_owned(), Pmt::F64(102e6)),
+            ("gain".to_owned(), Pmt::F32(2.0)),
+            ("antenna".to_owned(), Pmt::String("TX/RX".to_owned())),
+        ]));
+        fg_handle.callback(ss, "cmd", pmt).await
+    })?;
+
+    assert_eq!(dev.settings(&RX, 0).frequency, Some(100e6));
+    assert_eq!(dev.settings(&RX, 0).gain, Some(1.0));
+    assert_eq!(dev.settings(&RX, 1).frequency, Some(102e6));
+    assert_eq!(dev.settings(&RX, 1).gain, Some(2.0));
+    assert_eq!(dev.settings(&RX, 1).antenna.as_deref(), Some("TX/RX"));
+
+    // invalid commands fail the flowgraph
+    block_on(async {
+        assert!(fg_handle.callback(ss, "cmd", Pmt::U32(1)).await.is_err());
+        assert!(task.await.is_err());
+    });
+    Ok(())
+}
+
+#[test]
+fn config_cmd_any_multichan() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let dev = MockSoapyDevice::new(2);
+    let dev_spec = SoapyDevSpec::Mock(dev.clone());
+
+    let soapy_src = fg.add_block(
+        MockSoapySourceBuilder::new()
+            .device(dev_spec.clone())
+            .dev_channels(vec![0, 1])
+            .freq(95e6)
+            .sample_rate(1e6)
+            .gain(0.0)
+            .build(),
+    );
+    let soapy_snk = fg.add_block(
+        MockSoapySinkBuilder::new()
+            .device(dev_spec)
+            .dev_channels(vec![0, 1])
+            .freq(96e6)
+            .sample_rate(1e6)
+            .gain(0.0)
+            .build(),
+    );
+
+    let zero_src = fg.add_block(futuresdr::blocks::Source::new(|| Complex32::new(0.0, 0.0)));
+    let null_snk1 = fg.add_block(NullSink::<Complex32>::new());
+    let null_snk2 = fg.add_block(NullSink::<Complex32>::new());
+
+    fg.connect_stream(soapy_src, "out", null_snk1, "in")?;
+    fg.connect_stream(soapy_src, "out2", null_snk2, "in")?;
+    fg.connect_stream(zero_src, "out", soapy_snk, "in")?;
+    fg.connect_stream(zero_src, "out", soapy_snk, "in2")?;
+
+    let rt = Runtime::new();
+    let (task, mut fg_handle) = block_on(rt.start(fg));
+
+    block_on(async {
+        let pmt = SoapyConfig::new()
+            .push(SCI::Channels(None)) // All chans
+            .push(SCI::Direction(SoapyDirection::Both))
+            .push(SCI::Gain(1.0))
+            // Both chans still
+            .push(SCI::Direction(SoapyDirection::Rx))
+            .push(SCI::Freq(90e6))
+            .push(SCI::Direction(SoapyDirection::Tx))
+            .push(SCI::Freq(100e6))
+            //
+            .push(SCI::Channels(Some(vec![0]))) // Only chan 0
+            .push(SCI::Direction(SoapyDirection::Both))
+            .push(SCI::Gain(2.0))
+            //
+            .push(SCI::Channels(Some(vec![1]))) // Only chan 1
+            .push(SCI::Direction(SoapyDirection::Rx)) // Only Rx
+            .push(SCI::Gain(3.0))
+            .to_pmt();
+        fg_handle.callback(soapy_snk, "cmd", pmt).await
+    })?;
+
+    assert_eq!(dev.settings(&RX, 0).frequency, Some(90e6));
+    assert_eq!(dev.settings(&RX, 0).gain, Some(2.0));
+
+    assert_eq!(dev.settings(&RX, 1).frequency, Some(90e6));
+    assert_eq!(dev.settings(&RX, 1).gain, Some(3.0));
+
+    assert_eq!(dev.settings(&TX, 0).frequency, Some(100e6));
+    assert_eq!(dev.settings(&TX, 0).gain, Some(2.0));
+
+    assert_eq!(dev.settings(&TX, 1).frequency, Some(100e6));
+    assert_eq!(dev.settings(&TX, 1).gain, Some(1.0));
+
+    block_on(async {
+        fg_handle.terminate().await?;
+        task.await?;
+        Result::<()>::Ok(())
+    })
+}
+
+#[test]
+fn multichan_wiring() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let dev = MockSoapyDevice::new(2);
+    let dev_spec = SoapyDevSpec::Mock(dev.clone());
+
+    let ch0: Vec<Complex32> = (0..1000).map(|i| Complex32::new(i as f32, 0.0)).collect();
+    let ch1: Vec<Complex32> = (0..1000).map(|i| Complex32::new(0.0, i as f32)).collect();
+    dev.push_rx(0, &ch0);
+    dev.push_rx(1, &ch1);
+
+    let soapy_src = fg.add_block(
+        MockSoapySourceBuilder::new()
+            .device(dev_spec.clone())
+            .dev_channels(vec![0, 1])
+            .build(),
+    );
+    let head1 = fg.add_block(Head::<Complex32>::new(1000));
+    let head2 = fg.add_block(Head::<Complex32>::new(1000));
+    let vsnk1 = fg.add_block(VectorSinkBuilder::<Complex32>::new().build());
+    let vsnk2 = fg.add_block(VectorSinkBuilder::<Complex32>::new().build());
+    fg.connect_stream(soapy_src, "out", head1, "in")?;
+    fg.connect_stream(soapy_src, "out2", head2, "in")?;
+    fg.connect_stream(head1, "out", vsnk1, "in")?;
+    fg.connect_stream(head2, "out", vsnk2, "in")?;
+
+    // transmit on channel 1 only
+    let soapy_snk = fg.add_block(
+        MockSoapySinkBuilder::new()
+            .device(dev_spec)
+            .dev_channels(vec![1])
+            .sample_rate(1e6)
+            .build(),
+    );
+    let vsrc = fg.add_block(VectorSource::<Complex32>::new(ch0.clone()));
+    fg.connect_stream(vsrc, "out", soapy_snk, "in")?;
+
+    let fg = Runtime::new().run(fg)?;
+
+    assert_eq!(
+        fg.kernel::<VectorSink<Complex32>>(vsnk1).unwrap().items(),
+        &ch0
+    );
+    assert_eq!(
+        fg.kernel::<VectorSink<Complex32>>(vsnk2).unwrap().items(),
+        &ch1
+    );
+    assert!(dev.transmitted(0).is_empty());
+    assert_eq!(dev.transmitted(1), ch0);
+    assert_eq!(dev.settings(&TX, 1).sample_rate, Some(1e6));
+    Ok(())
+}
+
+#[test]
+fn source_whole_mtus() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let dev = MockSoapyDevice::new(1).with_mtu(1024);
+
+    let src = fg.add_block(
+        MockSoapySourceBuilder::new()
+            .device(SoapyDevSpec::Mock(dev.clone()))
+            .build(),
+    );
+    let snk = fg.add_block(NullSink::<Complex32>::new());
+    // 4096 items, i.e., four MTUs
+    fg.connect_stream_with_type(src, "out", snk, "in", Circular::with_size(32768))?;
+
+    let rt = Runtime::new();
+    let (task, mut fg_handle) = block_on(rt.start(fg));
+    while dev.stream_calls(&RX).len() < 100 {
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    block_on(async {
+        fg_handle.terminate().await?;
+        task.await?;
+        Result::<()>::Ok(())
+    })?;
+
+    let calls = dev.stream_calls(&RX);
+    assert!(calls.iter().all(|n| *n > 0 && n % 1024 == 0));
+    Ok(())
+}
+
+#[test]
+fn sink_whole_mtus() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let dev = MockSoapyDevice::new(1).with_mtu(1024);
+
+    // ends with a partial MTU
+    let input: Vec<Complex32> = (0..10 * 1024 + 300)
+        .map(|i| Complex32::new(i as f32, 0.0))
+        .collect();
+    let src = fg.add_block(VectorSource::<Complex32>::new(input.clone()));
+    let snk = fg.add_block(
+        MockSoapySinkBuilder::new()
+            .device(SoapyDevSpec::Mock(dev.clone()))
+            .build(),
+    );
+    fg.connect_stream_with_type(src, "out", snk, "in", Circular::with_size(32768))?;
+
+    Runtime::new().run(fg)?;
+
+    let calls = dev.stream_calls(&TX);
+    let (last, whole) = calls.split_last().unwrap();
+    assert!(whole.iter().all(|n| *n > 0 && n % 1024 == 0));
+    assert_eq!(*last, 300);
+    assert_eq!(dev.transmitted(0), input);
+    Ok(())
+}
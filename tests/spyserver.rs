@@ -0,0 +1,109 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::SpyServerFormat;
+use futuresdr::blocks::SpyServerSourceBuilder;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+
+fn message(s: &mut TcpStream, msg_type: u32, body: &[u8]) {
+    let mut m = Vec::new();
+    for v in [0x0200_06a4, msg_type, 0, 0, body.len() as u32] {
+        m.extend_from_slice(&u32::to_le_bytes(v));
+    }
+    m.extend_from_slice(body);
+    s.write_all(&m).unwrap();
+}
+
+fn u32s(v: &[u32]) -> Vec<u8> {
+    v.iter().flat_map(|x| x.to_le_bytes()).collect()
+}
+
+#[test]
+fn spyserver_iq() -> Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let addr = listener.local_addr()?.to_string();
+
+    let server = std::thread::spawn(move || {
+        let (mut s, _) = listener.accept().unwrap();
+
+        let mut header = [0u8; 8];
+        s.read_exact(&mut header).unwrap();
+        assert_eq!(u32::from_le_bytes(header[..4].try_into().unwrap()), 0);
+        let len = u32::from_le_bytes(header[4..].try_into().unwrap()) as usize;
+        let mut hello = vec![0u8; len];
+        s.read_exact(&mut hello).unwrap();
+
+        // 10 MS/s, 9 decimation stages
+        message(
+            &mut s,
+            0,
+            &u32s(&[
+                1,
+                1234,
+                10_000_000,
+                8_000_000,
+                9,
+                1,
+                15,
+                24_000_000,
+                1_700_000_000,
+                16,
+                0,
+                0,
+            ]),
+        );
+        message(&mut s, 1, &u32s(&[1, 5, 0, 0, 0, 0, 0, 0, 0]));
+
+        // streaming mode, format, frequency, decimation, enable
+        let mut settings = Vec::new();
+        for _ in 0..5 {
+            let mut cmd = [0u8; 16];
+            s.read_exact(&mut cmd).unwrap();
+            let v: Vec<u32> = cmd
+                .chunks_exact(4)
+                .map(|c| u32::from_le_bytes(c.try_into().unwrap()))
+                .collect();
+            assert_eq!(v[0], 2);
+            settings.push((v[2], v[3]));
+        }
+
+        for i in 0..10i16 {
+            let body: Vec<u8> = [i * 100, -i * 100]
+                .iter()
+                .flat_map(|x| x.to_le_bytes())
+                .collect();
+            message(&mut s, 101, &body);
+        }
+        settings
+    });
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(
+        SpyServerSourceBuilder::new(addr)
+            .frequency(145_000_000)
+            .sample_rate(600e3)
+            .format(SpyServerFormat::I16)
+            .build(),
+    );
+    let snk = fg.add_block(VectorSinkBuilder::<Complex32>::new().build());
+    fg.connect_stream(src, "out", snk, "in")?;
+
+    let fg = Runtime::new().run(fg)?;
+
+    let settings = server.join().unwrap();
+    assert_eq!(
+        settings,
+        vec![(0, 1), (100, 2), (101, 145_000_000), (102, 4), (1, 1)]
+    );
+
+    let snk = fg.kernel::<VectorSink<Complex32>>(snk).unwrap();
+    let expected: Vec<Complex32> = (0..10)
+        .map(|i| Complex32::new(i as f32 * 100.0 / 32768.0, -i as f32 * 100.0 / 32768.0))
+        .collect();
+    assert_eq!(snk.items(), &expected);
+    Ok(())
+}
@@ -0,0 +1,166 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_trait::async_trait;
+use futuresdr::blocks::CtcssSquelch;
+use futuresdr::blocks::PowerSquelchBuilder;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::{
+    Block, BlockMeta, BlockMetaBuilder, Flowgraph, Kernel, MessageIo, MessageIoBuilder, Runtime,
+    StreamIo, StreamIoBuilder, Tag, WorkIo,
+};
+
+/// Store samples and string tags with their absolute index.
+#[derive(Default)]
+struct TagSink {
+    items: Vec<Complex32>,
+    tags: Vec<(usize, String)>,
+}
+
+impl TagSink {
+    fn into_block(self) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("TagSink").build(),
+            StreamIoBuilder::new().add_input::<Complex32>("in").build(),
+            MessageIoBuilder::<Self>::new().build(),
+            self,
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for TagSink {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex32>();
+        for t in sio.input(0).tags().iter().filter(|t| t.index < i.len()) {
+            if let Tag::String(s) = &t.tag {
+                self.tags.push((self.items.len() + t.index, s.clone()));
+            }
+        }
+        self.items.extend_from_slice(i);
+        sio.input(0).consume(i.len());
+
+        if sio.input(0).finished() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+fn bursts() -> Vec<Complex32> {
+    // two bursts of 1000 samples with amplitude one
+    (0..5000)
+        .map(|i| {
+            if (1000..2000).contains(&i) || (3000..4000).contains(&i) {
+                Complex32::new(0.0, 1.0)
+            } else {
+                Complex32::new(0.001, 0.0)
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn power_squelch_gate_tag() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(VectorSource::<Complex32>::new(bursts()));
+    let squelch = fg.add_block(
+        PowerSquelchBuilder::new(-10.0)
+            .alpha(0.5)
+            .hold(10)
+            .gate()
+            .tag()
+            .build(),
+    );
+    let snk = fg.add_block(TagSink::default().into_block());
+
+    fg.connect_stream(src, "out", squelch, "in")?;
+    fg.connect_stream(squelch, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<TagSink>(snk).unwrap();
+    // each burst, followed by the hold time and the decay of the estimate
+    assert!(snk.items.len() >= 2020 && snk.items.len() < 2040);
+    let burst = snk.items.len() / 2;
+    assert_eq!(
+        snk.tags,
+        vec![
+            (0, "squelch_sob".to_string()),
+            (burst - 1, "squelch_eob".to_string()),
+            (burst, "squelch_sob".to_string()),
+            (2 * burst - 1, "squelch_eob".to_string()),
+        ]
+    );
+    assert!(snk.items[..1000].iter().all(|x| x.im == 1.0));
+
+    Ok(())
+}
+
+#[test]
+fn power_squelch_zeros() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(VectorSource::<Complex32>::new(bursts()));
+    let squelch = fg.add_block(PowerSquelchBuilder::new(-10.0).alpha(0.5).build());
+    let snk = fg.add_block(VectorSinkBuilder::<Complex32>::new().build());
+
+    fg.connect_stream(src, "out", squelch, "in")?;
+    fg.connect_stream(squelch, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let v = fg.kernel::<VectorSink<Complex32>>(snk).unwrap().items();
+    assert_eq!(v.len(), 5000);
+    let passed = v.iter().filter(|x| x.norm() > 0.0).count();
+    assert!((2000..2020).contains(&passed));
+    assert!(v[..1000].iter().all(|x| x.norm() == 0.0));
+    assert!(v[1000..2000].iter().all(|x| x.im == 1.0));
+
+    Ok(())
+}
+
+#[test]
+fn ctcss_squelch() -> Result<()> {
+    let fs = 8000.0;
+    let tone = 100.0;
+    // audio only for one second, then audio with tone
+    let input: Vec<f32> = (0..16000)
+        .map(|i| {
+            let t = i as f32 / fs;
+            let mut x = 0.5 * (2.0 * std::f32::consts::PI * 1000.0 * t).sin();
+            if i >= 8000 {
+                x += 0.15 * (2.0 * std::f32::consts::PI * tone * t).sin();
+            }
+            x
+        })
+        .collect();
+
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(VectorSource::<f32>::new(input.clone()));
+    let squelch = fg.add_block(CtcssSquelch::new(fs, tone));
+    let snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+
+    fg.connect_stream(src, "out", squelch, "in")?;
+    fg.connect_stream(squelch, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let v = fg.kernel::<VectorSink<f32>>(snk).unwrap().items();
+    assert_eq!(v.len(), input.len());
+    // muted without tone, open after the first detection window with tone
+    assert!(v[..8799].iter().all(|x| *x == 0.0));
+    assert_eq!(&v[8799..], &input[8799..]);
+
+    Ok(())
+}
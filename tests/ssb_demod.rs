@@ -0,0 +1,62 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::Sideband;
+use futuresdr::blocks::SsbDemod;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+use std::f32::consts::PI;
+
+const SAMPLE_RATE: f32 = 48000.0;
+
+/// Demodulate tones at 1 kHz above and 2 kHz below the carrier.
+fn demod(sideband: Sideband) -> Result<Vec<f32>> {
+    let input: Vec<Complex32> = (0..48000)
+        .map(|i| {
+            let t = i as f32 / SAMPLE_RATE;
+            Complex32::from_polar(0.5, 2.0 * PI * 1000.0 * t)
+                + Complex32::from_polar(0.25, -2.0 * PI * 2000.0 * t)
+        })
+        .collect();
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::<Complex32>::new(input));
+    let demod = fg.add_block(SsbDemod::new(SAMPLE_RATE, sideband));
+    let snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+
+    fg.connect_stream(src, "out", demod, "in")?;
+    fg.connect_stream(demod, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    Ok(fg.kernel::<VectorSink<f32>>(snk).unwrap().items().clone())
+}
+
+fn amplitude(v: &[f32], freq: f32) -> f32 {
+    let v = &v[v.len() / 2..v.len() / 2 + 4800];
+    let (i, q) = v.iter().enumerate().fold((0.0, 0.0), |(i, q), (k, x)| {
+        let p = 2.0 * PI * freq * k as f32 / SAMPLE_RATE;
+        (i + x * p.cos(), q + x * p.sin())
+    });
+    2.0 * (i * i + q * q).sqrt() / v.len() as f32
+}
+
+#[test]
+fn ssb_usb() -> Result<()> {
+    let v = demod(Sideband::Usb)?;
+    assert!(v.len() > 40000);
+    assert!((amplitude(&v, 1000.0) - 0.5).abs() < 0.02);
+    assert!(amplitude(&v, 2000.0) < 0.01);
+    Ok(())
+}
+
+#[test]
+fn ssb_lsb() -> Result<()> {
+    let v = demod(Sideband::Lsb)?;
+    assert!(v.len() > 40000);
+    assert!((amplitude(&v, 2000.0) - 0.25).abs() < 0.02);
+    assert!(amplitude(&v, 1000.0) < 0.01);
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::blocks::MessagePipe;
+use futuresdr::blocks::Source;
+use futuresdr::blocks::StatisticsSink;
+use futuresdr::blocks::StatisticsSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::futures::StreamExt;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+fn entry(p: &Pmt, key: &str) -> Pmt {
+    match p {
+        Pmt::MapStrPmt(m) => m.get(key).unwrap().clone(),
+        _ => panic!("wrong message type"),
+    }
+}
+
+#[test]
+fn statistics_sink() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let v: Vec<f32> = (0..1000).map(|i| (i % 10) as f32 - 4.5).collect();
+    let src = fg.add_block(VectorSource::<f32>::new(v));
+    let snk = fg.add_block(StatisticsSinkBuilder::new().histogram(-4.0, 4.0, 4).build());
+    fg.connect_stream(src, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let s = fg.kernel::<StatisticsSink>(snk).unwrap();
+    assert_eq!(s.count(), 1000);
+    assert_eq!(s.min(), -4.5);
+    assert_eq!(s.max(), 4.5);
+    assert!(s.mean().abs() < 1e-9);
+    // rms of -4.5..=4.5 in steps of one
+    assert!((s.rms() - 8.25f64.sqrt()).abs() < 1e-9);
+    assert_eq!(s.histogram().unwrap(), &[200, 200, 200, 200]);
+
+    Ok(())
+}
+
+#[test]
+fn statistics_sink_report() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let (tx, rx) = mpsc::channel(10);
+
+    let v: Vec<f32> = (0..300).map(|i| if i < 100 { 1.0 } else { -2.0 }).collect();
+    let src = fg.add_block(VectorSource::<f32>::new(v));
+    let snk = fg.add_block(
+        StatisticsSinkBuilder::new()
+            .histogram(-3.0, 0.0, 3)
+            .report_every(100)
+            .reset_on_report()
+            .build(),
+    );
+    let pipe = fg.add_block(MessagePipe::new(tx));
+    fg.connect_stream(src, "out", snk, "in")?;
+    fg.connect_message(snk, "out", pipe, "in")?;
+
+    Runtime::new().run(fg)?;
+
+    let reports: Vec<Pmt> = block_on(rx.collect());
+    assert_eq!(reports.len(), 3);
+    assert_eq!(entry(&reports[0], "mean"), Pmt::F64(1.0));
+    assert_eq!(entry(&reports[0], "overflow"), Pmt::U64(100));
+    assert_eq!(entry(&reports[0], "histogram"), Pmt::VecU64(vec![0, 0, 0]));
+    for r in &reports[1..] {
+        assert_eq!(entry(r, "count"), Pmt::U64(100));
+        assert_eq!(entry(r, "min"), Pmt::F32(-2.0));
+        assert_eq!(entry(r, "rms"), Pmt::F64(2.0));
+        assert_eq!(entry(r, "histogram"), Pmt::VecU64(vec![0, 100, 0]));
+        assert_eq!(entry(r, "overflow"), Pmt::U64(0));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn statistics_sink_request() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(Source::new(|| 0.5f32));
+    let snk = fg.add_block(StatisticsSinkBuilder::new().build());
+    fg.connect_stream(src, "out", snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+    block_on(async {
+        handle.call(snk, "reset", Pmt::Null).await?;
+        let r = handle.callback(snk, "report", Pmt::Null).await?;
+        assert_eq!(entry(&r, "mean"), Pmt::F64(0.5));
+        assert_eq!(entry(&r, "max"), Pmt::F32(0.5));
+        handle.terminate().await
+    })?;
+    block_on(task)?;
+
+    Ok(())
+}
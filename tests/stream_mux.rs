@@ -0,0 +1,59 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::StreamDemux;
+use futuresdr::blocks::StreamMux;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+
+#[test]
+fn stream_mux() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src0 = fg.add_block(VectorSource::<u32>::new(vec![0; 3000]));
+    let src1 = fg.add_block(VectorSource::<u32>::new((0..10_000).collect()));
+    let mux = fg.add_block(StreamMux::<u32>::new(vec![3, 10]));
+    let snk = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+
+    fg.connect_stream(src0, "out", mux, "in0")?;
+    fg.connect_stream(src1, "out", mux, "in1")?;
+    fg.connect_stream(mux, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let v = fg.kernel::<VectorSink<u32>>(snk).unwrap().items();
+    assert_eq!(v.len(), 13_000);
+    for (k, c) in v.chunks(13).enumerate() {
+        assert_eq!(c[..3], [0, 0, 0]);
+        assert!(c[3..]
+            .iter()
+            .copied()
+            .eq(k as u32 * 10..(k as u32 + 1) * 10));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn stream_demux() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(VectorSource::<u32>::new((0..10_005).collect()));
+    let demux = fg.add_block(StreamDemux::<u32>::new(vec![2, 3]));
+    let snk0 = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+    let snk1 = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+
+    fg.connect_stream(src, "out", demux, "in")?;
+    fg.connect_stream(demux, "out0", snk0, "in")?;
+    fg.connect_stream(demux, "out1", snk1, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let v0 = fg.kernel::<VectorSink<u32>>(snk0).unwrap().items();
+    let v1 = fg.kernel::<VectorSink<u32>>(snk1).unwrap().items();
+    assert!(v0.iter().copied().eq((0..10_005).filter(|x| x % 5 < 2)));
+    assert!(v1.iter().copied().eq((0..10_005).filter(|x| x % 5 >= 2)));
+
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::blocks::Head;
+use futuresdr::blocks::MessagePipe;
+use futuresdr::blocks::PduSample;
+use futuresdr::blocks::PduToStream;
+use futuresdr::blocks::StreamToPdu;
+use futuresdr::blocks::StreamToVector;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::blocks::VectorToStream;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::futures::StreamExt;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+#[test]
+fn stream_to_vector() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(VectorSource::<u32>::new((0..10_003).collect()));
+    let s2v = fg.add_block(StreamToVector::<u32, 4>::new());
+    let snk = fg.add_block(VectorSinkBuilder::<[u32; 4]>::new().build());
+
+    fg.connect_stream(src, "out", s2v, "in")?;
+    fg.connect_stream(s2v, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let v = fg.kernel::<VectorSink<[u32; 4]>>(snk).unwrap().items();
+    assert_eq!(v.len(), 2500);
+    for (k, x) in v.iter().enumerate() {
+        let k = 4 * k as u32;
+        assert_eq!(*x, [k, k + 1, k + 2, k + 3]);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn vector_to_stream() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let input: Vec<u32> = (0..10_000).collect();
+    let src = fg.add_block(VectorSource::<u32>::new(input.clone()));
+    let s2v = fg.add_block(StreamToVector::<u32, 16>::new());
+    let v2s = fg.add_block(VectorToStream::<u32, 16>::new());
+    let snk = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+
+    fg.connect_stream(src, "out", s2v, "in")?;
+    fg.connect_stream(s2v, "out", v2s, "in")?;
+    fg.connect_stream(v2s, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let v = fg.kernel::<VectorSink<u32>>(snk).unwrap().items();
+    assert_eq!(*v, input);
+
+    Ok(())
+}
+
+#[test]
+fn stream_to_pdu() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let (tx, rx) = mpsc::channel(100);
+
+    let input: Vec<Complex32> = (0..250).map(|i| Complex32::new(i as f32, -1.0)).collect();
+    let src = fg.add_block(VectorSource::<Complex32>::new(input.clone()));
+    let s2p = fg.add_block(StreamToPdu::<Complex32>::new(100));
+    let pipe = fg.add_block(MessagePipe::new(tx));
+
+    fg.connect_stream(src, "out", s2p, "in")?;
+    fg.connect_message(s2p, "out", pipe, "in")?;
+
+    Runtime::new().run(fg)?;
+
+    let pdus: Vec<Pmt> = block_on(rx.collect());
+    assert_eq!(pdus.len(), 2);
+    for (pdu, chunk) in pdus.iter().zip(input.chunks(100)) {
+        assert_eq!(Complex32::from_pmt(pdu).unwrap(), chunk);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn pdu_to_stream() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let p2s = fg.add_block(PduToStream::<f32>::new());
+    let head = fg.add_block(Head::<f32>::new(5));
+    let snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+
+    fg.connect_stream(p2s, "out", head, "in")?;
+    fg.connect_stream(head, "out", snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+    block_on(async {
+        handle.call(p2s, "in", Pmt::VecF32(vec![1.0, 2.0])).await?;
+        handle.call(p2s, "in", Pmt::U32(3)).await?;
+        handle
+            .call(p2s, "in", Pmt::VecF32(vec![3.0, 4.0, 5.0, 6.0]))
+            .await
+    })?;
+    let fg = block_on(task)?;
+
+    let v = fg.kernel::<VectorSink<f32>>(snk).unwrap().items();
+    assert_eq!(*v, vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+
+    Ok(())
+}
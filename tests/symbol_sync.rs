@@ -0,0 +1,86 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::SymbolSyncBuilder;
+use futuresdr::blocks::TimingErrorDetector;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+use std::f64::consts::PI;
+
+/// Raised cosine pulse with roll-off 0.5 and a symbol period of one.
+fn raised_cosine(t: f64) -> f64 {
+    let beta = 0.5;
+    if t.abs() < 1e-9 {
+        return 1.0;
+    }
+    if (t.abs() - 1.0 / (2.0 * beta)).abs() < 1e-9 {
+        return PI / 4.0 * (PI / (2.0 * beta)).sin() / (PI / (2.0 * beta));
+    }
+    (PI * t).sin() / (PI * t) * (PI * beta * t).cos() / (1.0 - (2.0 * beta * t).powi(2))
+}
+
+/// QPSK symbols, pulse shaped with the given samples per symbol and timing offset.
+fn signal(n_symbols: usize, sps: f64, offset: f64) -> (Vec<Complex32>, Vec<Complex32>) {
+    let mut state = 1u32;
+    let symbols: Vec<Complex32> = (0..n_symbols)
+        .map(|_| {
+            state = state.wrapping_mul(1103515245).wrapping_add(12345);
+            let b = (state >> 16) & 3;
+            Complex32::new(
+                if b & 1 == 0 { 1.0 } else { -1.0 },
+                if b & 2 == 0 { 1.0 } else { -1.0 },
+            )
+        })
+        .collect();
+
+    let n_samples = (n_symbols as f64 * sps) as usize;
+    let samples = (0..n_samples)
+        .map(|i| {
+            let t = i as f64 / sps - offset;
+            let k = t.round() as isize;
+            let mut v = Complex32::new(0.0, 0.0);
+            for s in (k - 8).max(0)..(k + 8).min(n_symbols as isize) {
+                v += symbols[s as usize] * raised_cosine(t - s as f64) as f32;
+            }
+            v
+        })
+        .collect();
+
+    (symbols, samples)
+}
+
+fn sync(ted: TimingErrorDetector) -> Result<()> {
+    // slight clock offset and fractional timing offset
+    let (_, input) = signal(4000, 4.002, 0.37);
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::<Complex32>::new(input));
+    let sync = fg.add_block(SymbolSyncBuilder::new(4.0, 0.02).ted(ted).build());
+    let snk = fg.add_block(VectorSinkBuilder::<Complex32>::new().build());
+
+    fg.connect_stream(src, "out", sync, "in")?;
+    fg.connect_stream(sync, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let v = fg.kernel::<VectorSink<Complex32>>(snk).unwrap().items();
+    assert!((v.len() as i64 - 4000).abs() < 20, "{}", v.len());
+    for x in v[1000..v.len() - 20].iter() {
+        assert!((x.re.abs() - 1.0).abs() < 0.15, "{}", x);
+        assert!((x.im.abs() - 1.0).abs() < 0.15, "{}", x);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn symbol_sync_ml() -> Result<()> {
+    sync(TimingErrorDetector::MaximumLikelihood)
+}
+
+#[test]
+fn symbol_sync_gardner() -> Result<()> {
+    sync(TimingErrorDetector::Gardner)
+}
@@ -0,0 +1,173 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::async_trait::async_trait;
+use futuresdr::blocks::MessageGate;
+use futuresdr::blocks::MessagePipe;
+use futuresdr::blocks::PduLengthFilter;
+use futuresdr::blocks::PduToTaggedStream;
+use futuresdr::blocks::TaggedStreamToPdu;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::futures::StreamExt;
+use futuresdr::runtime::{
+    Block, BlockMeta, BlockMetaBuilder, Flowgraph, Kernel, MessageIo, MessageIoBuilder, Pmt,
+    Runtime, StreamIo, StreamIoBuilder, Tag, WorkIo,
+};
+
+/// Output a vector, adding tags at the given indices.
+struct TaggedSource {
+    items: Vec<f32>,
+    tags: Vec<(usize, Tag)>,
+    n: usize,
+}
+
+impl TaggedSource {
+    fn block(items: Vec<f32>, tags: Vec<(usize, Tag)>) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("TaggedSource").build(),
+            StreamIoBuilder::new().add_output::<f32>("out").build(),
+            MessageIoBuilder::<Self>::new().build(),
+            TaggedSource { items, tags, n: 0 },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for TaggedSource {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let o = sio.output(0).slice::<f32>();
+        let m = std::cmp::min(o.len(), self.items.len() - self.n);
+        o[..m].copy_from_slice(&self.items[self.n..self.n + m]);
+        for (i, t) in self.tags.iter() {
+            if *i >= self.n && *i < self.n + m {
+                sio.output(0).add_tag(i - self.n, t.clone());
+            }
+        }
+        self.n += m;
+        sio.output(0).produce(m);
+
+        if self.n == self.items.len() {
+            io.finished = true;
+        }
+        Ok(())
+    }
+}
+
+fn len_tag(len: usize) -> Tag {
+    Tag::NamedUsize("packet_len".to_string(), len)
+}
+
+#[test]
+fn tagged_stream_to_pdu() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let (tx, rx) = mpsc::channel(100);
+
+    let src = fg.add_block(TaggedSource::block(
+        (0..20).map(|x| x as f32).collect(),
+        vec![
+            (2, len_tag(3)),
+            (6, Tag::NamedUsize("other".to_string(), 2)),
+            // interrupted by the next packet
+            (8, len_tag(4)),
+            (10, len_tag(2)),
+            // incomplete at the end of the stream
+            (18, len_tag(4)),
+        ],
+    ));
+    let s2p = fg.add_block(TaggedStreamToPdu::<f32>::new("packet_len"));
+    let pipe = fg.add_block(MessagePipe::new(tx));
+
+    fg.connect_stream(src, "out", s2p, "in")?;
+    fg.connect_message(s2p, "out", pipe, "in")?;
+
+    Runtime::new().run(fg)?;
+
+    let pdus: Vec<Pmt> = block_on(rx.collect());
+    assert_eq!(
+        pdus,
+        vec![
+            Pmt::VecF32(vec![2.0, 3.0, 4.0]),
+            Pmt::VecF32(vec![10.0, 11.0]),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn pdu_tagged_stream_loopback() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let (tx, mut rx) = mpsc::channel(100);
+
+    let pdus = [
+        Pmt::Blob(vec![1, 2, 3]),
+        Pmt::Blob(vec![4]),
+        Pmt::Blob((0..=255).collect()),
+        Pmt::Blob(vec![5, 6]),
+    ];
+
+    let p2s = fg.add_block(PduToTaggedStream::<u8>::new("packet_len"));
+    let s2p = fg.add_block(TaggedStreamToPdu::<u8>::new("packet_len"));
+    let filter = fg.add_block(PduLengthFilter::<u8>::new(2, 1000));
+    let pipe = fg.add_block(MessagePipe::new(tx));
+
+    fg.connect_stream(p2s, "out", s2p, "in")?;
+    fg.connect_message(s2p, "out", filter, "in")?;
+    fg.connect_message(filter, "out", pipe, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+    block_on(async {
+        handle.call(p2s, "in", Pmt::U32(1)).await?;
+        for p in pdus.iter() {
+            handle.call(p2s, "in", p.clone()).await?;
+        }
+        // the PDU with one byte is filtered
+        for i in [0, 2, 3] {
+            assert_eq!(rx.next().await.unwrap(), pdus[i]);
+        }
+        handle.terminate().await
+    })?;
+    block_on(task)?;
+
+    Ok(())
+}
+
+#[test]
+fn message_gate() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let (tx, rx) = mpsc::channel(100);
+
+    let gate = fg.add_block(MessageGate::new(false));
+    let pipe = fg.add_block(MessagePipe::new(tx));
+    fg.connect_message(gate, "out", pipe, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+    block_on(async {
+        handle.call(gate, "in", Pmt::U32(1)).await?;
+        assert_eq!(
+            handle.callback(gate, "gate", Pmt::U32(1)).await?,
+            Pmt::U32(1)
+        );
+        handle.call(gate, "in", Pmt::U32(2)).await?;
+        handle.call(gate, "in", Pmt::U32(3)).await?;
+        assert_eq!(
+            handle.callback(gate, "gate", Pmt::U64(0)).await?,
+            Pmt::U32(0)
+        );
+        handle.call(gate, "in", Pmt::U32(4)).await?;
+        handle.terminate().await
+    })?;
+    block_on(task)?;
+
+    let received: Vec<Pmt> = block_on(rx.collect());
+    assert_eq!(received, vec![Pmt::U32(2), Pmt::U32(3)]);
+
+    Ok(())
+}
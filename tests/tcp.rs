@@ -0,0 +1,97 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::Head;
+use futuresdr::blocks::TcpMode;
+use futuresdr::blocks::TcpSinkBuilder;
+use futuresdr::blocks::TcpSourceBuilder;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::time::Duration;
+
+fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+fn loopback(sink: TcpMode, source: TcpMode) -> Result<()> {
+    let input: Vec<u32> = (0..100_000).collect();
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::<u32>::new(input.clone()));
+    let tcp_snk = fg.add_block(
+        TcpSinkBuilder::<u32>::new(sink)
+            .reconnect(Duration::from_millis(10))
+            .build(),
+    );
+    let tcp_src = fg.add_block(
+        TcpSourceBuilder::<u32>::new(source)
+            .reconnect(Duration::from_millis(10))
+            .build(),
+    );
+    let head = fg.add_block(Head::<u32>::new(input.len() as u64));
+    let snk = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+    fg.connect_stream(src, "out", tcp_snk, "in")?;
+    fg.connect_stream(tcp_src, "out", head, "in")?;
+    fg.connect_stream(head, "out", snk, "in")?;
+
+    let fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<VectorSink<u32>>(snk).unwrap();
+    assert_eq!(snk.items(), &input);
+    Ok(())
+}
+
+#[test]
+fn tcp_server_sink_client_source() -> Result<()> {
+    let addr = format!("127.0.0.1:{}", free_port());
+    loopback(TcpMode::Server(addr.clone()), TcpMode::Client(addr))
+}
+
+#[test]
+fn tcp_client_sink_server_source() -> Result<()> {
+    let addr = format!("127.0.0.1:{}", free_port());
+    loopback(TcpMode::Client(addr.clone()), TcpMode::Server(addr))
+}
+
+#[test]
+fn tcp_source_reconnect() -> Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let addr = listener.local_addr()?.to_string();
+
+    // two connections, splitting a sample across them
+    let server = std::thread::spawn(move || {
+        let (mut s, _) = listener.accept().unwrap();
+        s.write_all(&[1, 0, 0, 0, 2, 0, 0, 0, 3, 0]).unwrap();
+        drop(s);
+        let (mut s, _) = listener.accept().unwrap();
+        s.write_all(&[4, 0, 0, 0, 5, 0, 0, 0]).unwrap();
+        // keep the connection open until the source is done
+        let _ = s.read(&mut [0u8; 1]);
+    });
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(
+        TcpSourceBuilder::<u32>::new(TcpMode::Client(addr))
+            .reconnect(Duration::from_millis(10))
+            .build(),
+    );
+    let head = fg.add_block(Head::<u32>::new(4));
+    let snk = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+    fg.connect_stream(src, "out", head, "in")?;
+    fg.connect_stream(head, "out", snk, "in")?;
+
+    let fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<VectorSink<u32>>(snk).unwrap();
+    assert_eq!(snk.items(), &vec![1, 2, 4, 5]);
+    drop(fg);
+    server.join().unwrap();
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::run_and_collect;
+use futuresdr::blocks::FloatToInt;
+use futuresdr::blocks::IntToFloat;
+
+#[test]
+fn int_to_float() -> Result<()> {
+    let v: Vec<f32> = run_and_collect(vec![0u8, 255, 127, 128], IntToFloat::<u8>::normalized())?;
+    assert_eq!(v, [-1.0, 1.0, -0.5 / 127.5, 0.5 / 127.5]);
+
+    let v: Vec<f32> = run_and_collect(vec![-128i8, 0, 64], IntToFloat::<i8>::normalized())?;
+    assert_eq!(v, [-1.0, 0.0, 0.5]);
+
+    let v: Vec<f32> = run_and_collect(vec![-32768i16, 16384], IntToFloat::<i16>::normalized())?;
+    assert_eq!(v, [-1.0, 0.5]);
+
+    let v: Vec<f32> = run_and_collect(vec![10i16, 20], IntToFloat::<i16>::new(0.5, 10.0))?;
+    assert_eq!(v, [0.0, 5.0]);
+
+    Ok(())
+}
+
+#[test]
+fn float_to_int() -> Result<()> {
+    let v: Vec<i16> = run_and_collect(
+        vec![-2.0f32, -1.0, 0.5, 1.0, 2.0],
+        FloatToInt::<i16>::normalized(),
+    )?;
+    assert_eq!(v, [-32768, -32768, 16384, 32767, 32767]);
+
+    let v: Vec<i8> = run_and_collect(vec![-1.0f32, 0.1, 1.0], FloatToInt::<i8>::normalized())?;
+    assert_eq!(v, [-128, 13, 127]);
+
+    let v: Vec<u8> = run_and_collect(vec![-2.0f32, 0.0, 2.0], FloatToInt::<u8>::normalized())?;
+    assert_eq!(v, [0, 128, 255]);
+
+    // round trip
+    let input: Vec<i8> = (-128..=127).collect();
+    let f: Vec<f32> = run_and_collect(input.clone(), IntToFloat::<i8>::normalized())?;
+    let v: Vec<i8> = run_and_collect(f, FloatToInt::<i8>::normalized())?;
+    assert_eq!(v, input);
+
+    let input: Vec<u8> = (0..=255).collect();
+    let f: Vec<f32> = run_and_collect(input.clone(), IntToFloat::<u8>::normalized())?;
+    let v: Vec<u8> = run_and_collect(f, FloatToInt::<u8>::normalized())?;
+    assert_eq!(v, input);
+
+    Ok(())
+}
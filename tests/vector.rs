@@ -0,0 +1,128 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::run_and_collect;
+use futuresdr::blocks::Apply;
+use futuresdr::blocks::AssertSink;
+use futuresdr::blocks::AssertSinkBuilder;
+use futuresdr::blocks::Head;
+use futuresdr::blocks::VectorSinkWithTags;
+use futuresdr::blocks::VectorSource;
+use futuresdr::blocks::VectorSourceBuilder;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+use futuresdr::runtime::Tag;
+
+#[test]
+fn vector_source_repeat_tags() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(
+        VectorSourceBuilder::new(vec![0u32, 1, 2, 3, 4])
+            .repeat(1000)
+            .tag(0, Tag::Id(0))
+            .tag(3, Tag::String("three".to_string()))
+            .build(),
+    );
+    let snk = fg.add_block(VectorSinkWithTags::<u32>::new(5000));
+
+    fg.connect_stream(src, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<VectorSinkWithTags<u32>>(snk).unwrap();
+    assert!(snk.items().iter().copied().eq((0..5000).map(|i| i % 5)));
+
+    let tags = snk.tags();
+    assert_eq!(tags.len(), 2000);
+    for (n, t) in tags.chunks(2).enumerate() {
+        assert_eq!(t[0].index, 5 * n);
+        assert!(matches!(t[0].tag, Tag::Id(0)));
+        assert_eq!(t[1].index, 5 * n + 3);
+        assert!(matches!(&t[1].tag, Tag::String(s) if s == "three"));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn vector_source_repeat_forever() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(
+        VectorSourceBuilder::new(vec![1u8, 2, 3])
+            .repeat_forever()
+            .build(),
+    );
+    let head = fg.add_block(Head::<u8>::new(100_000));
+    let snk = fg.add_block(AssertSink::new(
+        (0..100_000).map(|i| (i % 3 + 1) as u8).collect(),
+    ));
+
+    fg.connect_stream(src, "out", head, "in")?;
+    fg.connect_stream(head, "out", snk, "in")?;
+
+    Runtime::new().run(fg)?;
+
+    Ok(())
+}
+
+#[test]
+fn vector_source_empty() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::<u32>::new(Vec::new()));
+    let snk = fg.add_block(AssertSink::<u32>::new(Vec::new()));
+    fg.connect_stream(src, "out", snk, "in")?;
+    Runtime::new().run(fg)?;
+
+    Ok(())
+}
+
+#[test]
+fn assert_sink_tolerance() -> Result<()> {
+    let input: Vec<Complex32> = (0..1000).map(|i| Complex32::new(i as f32, 0.0)).collect();
+    let expected: Vec<Complex32> = input.iter().map(|x| x + 0.001).collect();
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::new(input.clone()));
+    let snk = fg.add_block(AssertSinkBuilder::new(expected).tolerance(0.01).build());
+    fg.connect_stream(src, "out", snk, "in")?;
+    fg = Runtime::new().run(fg)?;
+    assert_eq!(
+        fg.kernel::<AssertSink<Complex32>>(snk).unwrap().received(),
+        1000
+    );
+
+    let mut expected = input.clone();
+    expected[500].re += 0.1;
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::new(input));
+    let snk = fg.add_block(AssertSinkBuilder::new(expected).tolerance(0.01).build());
+    fg.connect_stream(src, "out", snk, "in")?;
+    assert!(Runtime::new().run(fg).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn assert_sink_length() -> Result<()> {
+    for n in [99, 101] {
+        let mut fg = Flowgraph::new();
+        let src = fg.add_block(VectorSource::<u32>::new((0..100).collect()));
+        let snk = fg.add_block(AssertSink::<u32>::new((0..n).collect()));
+        fg.connect_stream(src, "out", snk, "in")?;
+        assert!(Runtime::new().run(fg).is_err());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn run_and_collect_apply() -> Result<()> {
+    let v: Vec<f32> = run_and_collect(
+        (0..10_000).map(|i| i as f32).collect(),
+        Apply::new(|x: &f32| x * 2.0),
+    )?;
+    assert!(v.into_iter().eq((0..10_000).map(|i| 2.0 * i as f32)));
+
+    Ok(())
+}
@@ -0,0 +1,135 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_trait::async_trait;
+use futuresdr::blocks::VectorSource;
+use futuresdr::blocks::Vita49SinkBuilder;
+use futuresdr::blocks::Vita49SourceBuilder;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::{
+    Block, BlockMeta, BlockMetaBuilder, Flowgraph, Kernel, MessageIo, MessageIoBuilder, Pmt,
+    Runtime, StreamIo, StreamIoBuilder, Tag, WorkIo,
+};
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Store samples and data tags with their absolute index, until the limit is reached.
+struct TagSink {
+    limit: usize,
+    items: Vec<Complex32>,
+    tags: Vec<(usize, HashMap<String, Pmt>)>,
+}
+
+impl TagSink {
+    fn into_block(self) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("TagSink").build(),
+            StreamIoBuilder::new().add_input::<Complex32>("in").build(),
+            MessageIoBuilder::<Self>::new().build(),
+            self,
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for TagSink {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex32>();
+        for t in sio.input(0).tags().iter().filter(|t| t.index < i.len()) {
+            if let Tag::Data(Pmt::MapStrPmt(m)) = &t.tag {
+                self.tags.push((self.items.len() + t.index, m.clone()));
+            }
+        }
+        self.items.extend_from_slice(i);
+        sio.input(0).consume(i.len());
+
+        if sio.input(0).finished() || self.items.len() >= self.limit {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn vita49_round_trip() -> Result<()> {
+    let port = std::net::UdpSocket::bind("127.0.0.1:0")?
+        .local_addr()?
+        .port();
+    let input: Vec<Complex32> = (0..1000)
+        .map(|i| Complex32::new((i % 200) as f32 / 256.0, -((i % 100) as f32) / 128.0))
+        .collect();
+
+    let tx = {
+        let input = input.clone();
+        std::thread::spawn(move || -> Result<()> {
+            // give the receiver time to bind
+            std::thread::sleep(Duration::from_millis(500));
+            let mut fg = Flowgraph::new();
+            let src = fg.add_block(VectorSource::new(input));
+            let snk = fg.add_block(
+                Vita49SinkBuilder::new(format!("127.0.0.1:{port}"))
+                    .stream_id(7)
+                    .frequency(433.92e6)
+                    .sample_rate(1e6)
+                    .samples_per_packet(100)
+                    .build(),
+            );
+            fg.connect_stream(src, "out", snk, "in")?;
+            Runtime::new().run(fg)?;
+            Ok(())
+        })
+    };
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(
+        Vita49SourceBuilder::new(format!("127.0.0.1:{port}"))
+            .stream_id(7)
+            .build(),
+    );
+    let snk = fg.add_block(
+        TagSink {
+            limit: input.len(),
+            items: Vec::new(),
+            tags: Vec::new(),
+        }
+        .into_block(),
+    );
+    fg.connect_stream(src, "out", snk, "in")?;
+    fg = Runtime::new().run(fg)?;
+    tx.join().unwrap()?;
+
+    let snk = fg.kernel::<TagSink>(snk).unwrap();
+    assert_eq!(snk.items.len(), input.len());
+    for (a, b) in snk.items.iter().zip(input.iter()) {
+        assert!((a - b).norm() < 1e-4);
+    }
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let (index, time) = snk
+        .tags
+        .iter()
+        .find(|(_, m)| m.contains_key("seconds"))
+        .unwrap();
+    assert_eq!(*index, 0);
+    match time.get("seconds") {
+        Some(Pmt::U64(s)) => assert!(now - s < 10),
+        t => panic!("wrong time {t:?}"),
+    }
+    assert!(matches!(time.get("picoseconds"), Some(Pmt::U64(_))));
+
+    let (index, context) = snk
+        .tags
+        .iter()
+        .find(|(_, m)| m.contains_key("freq"))
+        .unwrap();
+    assert_eq!(*index, 0);
+    assert_eq!(context.get("freq"), Some(&Pmt::F64(433.92e6)));
+    assert_eq!(context.get("sample_rate"), Some(&Pmt::F64(1e6)));
+
+    Ok(())
+}
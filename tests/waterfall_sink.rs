@@ -0,0 +1,98 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::blocks::WaterfallFrame;
+use futuresdr::blocks::WaterfallSinkBuilder;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+fn frame(p: Pmt) -> WaterfallFrame {
+    match p {
+        Pmt::Blob(b) => WaterfallFrame::decode(&b).unwrap(),
+        _ => panic!("wrong message type"),
+    }
+}
+
+#[test]
+fn waterfall_frame() {
+    let mut state = 7u32;
+    let lines: Vec<Vec<u8>> = (0..20)
+        .map(|l| {
+            (0..300)
+                .map(|i| {
+                    state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                    // mix of runs and noise
+                    if (i / 50 + l) % 2 == 0 {
+                        10
+                    } else {
+                        (state >> 16) as u8
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    let f = WaterfallFrame {
+        first: 1234,
+        width: 300,
+        min: -90.0,
+        max: -10.0,
+        lines,
+    };
+    let encoded = f.encode();
+    assert!(encoded.len() < 20 * 300);
+    assert_eq!(WaterfallFrame::decode(&encoded), Some(f));
+    assert_eq!(WaterfallFrame::decode(&encoded[..10]), None);
+    assert_eq!(WaterfallFrame::decode(&encoded[..encoded.len() - 1]), None);
+}
+
+#[test]
+fn waterfall_sink() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let waterfall = fg.add_block(
+        WaterfallSinkBuilder::new(4)
+            .lines(3)
+            .range(-100.0, 0.0)
+            .build(),
+    );
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+    block_on(async {
+        let f = frame(handle.callback(waterfall, "request", Pmt::Null).await?);
+        assert_eq!(f.first, 0);
+        assert!(f.lines.is_empty());
+
+        for l in 0..5 {
+            let line = vec![-100.0, -40.0, 10.0, -12.0 * l as f32];
+            handle.call(waterfall, "in", Pmt::VecF32(line)).await?;
+        }
+        // wrong width is ignored
+        handle.call(waterfall, "in", Pmt::VecF32(vec![0.0])).await?;
+
+        let f = frame(handle.callback(waterfall, "request", Pmt::Null).await?);
+        assert_eq!(f.first, 2);
+        assert_eq!(f.width, 4);
+        assert_eq!((f.min, f.max), (-100.0, 0.0));
+        assert_eq!(
+            f.lines,
+            vec![
+                vec![0, 153, 255, 194],
+                vec![0, 153, 255, 163],
+                vec![0, 153, 255, 133]
+            ]
+        );
+
+        let f = frame(handle.callback(waterfall, "request", Pmt::U64(4)).await?);
+        assert_eq!(f.first, 4);
+        assert_eq!(f.lines, vec![vec![0, 153, 255, 133]]);
+
+        let f = frame(handle.callback(waterfall, "request", Pmt::U64(5)).await?);
+        assert_eq!(f.first, 5);
+        assert!(f.lines.is_empty());
+
+        handle.terminate().await
+    })?;
+    block_on(task)?;
+
+    Ok(())
+}
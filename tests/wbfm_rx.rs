@@ -0,0 +1,56 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::blocks::WbfmRx;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+use std::f32::consts::PI;
+
+#[test]
+fn wbfm_rx_tone() -> Result<()> {
+    let sample_rate = 2_400_000;
+    let audio_rate = 48_000;
+    let n = sample_rate as usize / 5;
+
+    // 1 kHz tone with 37.5 kHz deviation
+    let mut phase = 0.0f32;
+    let input: Vec<Complex32> = (0..n)
+        .map(|i| {
+            let m = (2.0 * PI * 1000.0 * i as f32 / sample_rate as f32).sin();
+            phase += 2.0 * PI * 37.5e3 * m / sample_rate as f32;
+            Complex32::from_polar(1.0, phase)
+        })
+        .collect();
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::<Complex32>::new(input));
+    let rx = fg.add_block(WbfmRx::new(sample_rate, audio_rate));
+    let snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+
+    fg.connect_stream(src, "out", rx, "in")?;
+    fg.connect_stream(rx, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let v = fg.kernel::<VectorSink<f32>>(snk).unwrap().items();
+    let expected = n * audio_rate as usize / sample_rate as usize;
+    assert!(v.len() > expected - 200 && v.len() <= expected);
+
+    // skip transient, evaluate 100 periods of the tone
+    let v = &v[1000..1000 + 4800];
+    let (i, q) = v.iter().enumerate().fold((0.0, 0.0), |(i, q), (k, x)| {
+        let p = 2.0 * PI * 1000.0 * k as f32 / audio_rate as f32;
+        (i + x * p.cos(), q + x * p.sin())
+    });
+    let amplitude = 2.0 * (i * i + q * q).sqrt() / v.len() as f32;
+    // 0.5 scaled by the 75 us de-emphasis at 1 kHz
+    let deemph = 1.0 / (1.0 + (2.0 * PI * 1000.0 * 75e-6f32).powi(2)).sqrt();
+    assert!((amplitude - 0.5 * deemph).abs() < 0.02, "{}", amplitude);
+
+    let power = v.iter().map(|x| x * x).sum::<f32>() / v.len() as f32;
+    assert!((power - amplitude * amplitude / 2.0).abs() < 0.01 * power);
+
+    Ok(())
+}
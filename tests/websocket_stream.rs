@@ -0,0 +1,68 @@
+use async_io::Async;
+use futuresdr::anyhow::Result;
+use futuresdr::async_io;
+use futuresdr::blocks::ChannelSource;
+use futuresdr::blocks::WebsocketStreamSinkBuilder;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::futures::StreamExt;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+#[test]
+fn websocket_stream() -> Result<()> {
+    let port = TcpListener::bind("127.0.0.1:0")?.local_addr()?.port();
+    std::env::set_var("FUTURESDR_CTRLPORT_ENABLE", "true");
+    std::env::set_var("FUTURESDR_CTRLPORT_BIND", format!("127.0.0.1:{port}"));
+
+    let input: Vec<Complex32> = (0..1000).map(|i| Complex32::new(i as f32, -1.0)).collect();
+    let (mut tx, rx) = mpsc::channel(10);
+    let fg_thread = std::thread::spawn(move || -> Result<()> {
+        let mut fg = Flowgraph::new();
+        let src = fg.add_block(ChannelSource::<Complex32>::new(rx));
+        let snk = fg.add_block(
+            WebsocketStreamSinkBuilder::<Complex32>::new("test")
+                .sample_rate(1000.0)
+                .decimation(2)
+                .frame_size(100)
+                .build(),
+        );
+        fg.connect_stream(src, "out", snk, "in")?;
+        Runtime::new().run(fg)?;
+        Ok(())
+    });
+
+    async_io::block_on(async move {
+        let url = format!("ws://127.0.0.1:{port}/api/stream/test/");
+        let mut ws = loop {
+            if let Ok(s) = Async::<TcpStream>::connect(([127, 0, 0, 1], port)).await {
+                if let Ok((ws, _)) = async_tungstenite::client_async(&url, s).await {
+                    break ws;
+                }
+            }
+            async_io::Timer::after(Duration::from_millis(20)).await;
+        };
+
+        tx.try_send(input.into_boxed_slice()).unwrap();
+        tx.close_channel();
+
+        for seq in 0..5u32 {
+            let frame = ws.next().await.unwrap().unwrap().into_data();
+            assert_eq!(frame.len(), 16 + 100 * 8);
+            assert_eq!(u32::from_le_bytes(frame[0..4].try_into().unwrap()), seq);
+            assert_eq!(frame[4], 1);
+            assert_eq!(f64::from_le_bytes(frame[8..16].try_into().unwrap()), 500.0);
+            for (k, x) in frame[16..].chunks_exact(8).enumerate() {
+                let re = f32::from_le_bytes(x[0..4].try_into().unwrap());
+                let im = f32::from_le_bytes(x[4..8].try_into().unwrap());
+                assert_eq!(re, ((seq as usize * 100 + k) * 2) as f32);
+                assert_eq!(im, -1.0);
+            }
+        }
+    });
+
+    fg_thread.join().unwrap()?;
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::MessagePipe;
+use futuresdr::blocks::VectorSource;
+use futuresdr::blocks::WelchBuilder;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::futures::StreamExt;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+#[test]
+fn welch_tone() -> Result<()> {
+    let fft_size = 64;
+    let n = 64 * 32;
+    let input: Vec<Complex32> = (0..n)
+        .map(|i| Complex32::from_polar(1.0, 2.0 * std::f32::consts::PI * 8.0 / 64.0 * i as f32))
+        .collect();
+
+    let mut fg = Flowgraph::new();
+    let (tx, rx) = mpsc::channel(100);
+
+    let src = fg.add_block(VectorSource::<Complex32>::new(input));
+    let welch = fg.add_block(WelchBuilder::new(fft_size).overlap(32).averages(4).build());
+    let pipe = fg.add_block(MessagePipe::new(tx));
+
+    fg.connect_stream(src, "out", welch, "in")?;
+    fg.connect_message(welch, "out", pipe, "in")?;
+
+    Runtime::new().run(fg)?;
+
+    let frames: Vec<Pmt> = futuresdr::async_io::block_on(rx.collect());
+    // (2048 - 64) / 32 + 1 = 63 segments
+    assert_eq!(frames.len(), 63 / 4);
+    for f in frames {
+        match f {
+            Pmt::VecF32(v) => {
+                assert_eq!(v.len(), fft_size);
+                let (max, _) =
+                    v.iter().enumerate().fold(
+                        (0, f32::MIN),
+                        |m, (i, x)| if *x > m.1 { (i, *x) } else { m },
+                    );
+                assert_eq!(max, fft_size / 2 + 8);
+                assert!(v[max].abs() < 0.01);
+                assert!(v[fft_size / 2 - 8] < -60.0);
+            }
+            _ => panic!("wrong pmt type"),
+        }
+    }
+
+    Ok(())
+}
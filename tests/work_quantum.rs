@@ -0,0 +1,192 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_trait::async_trait;
+use futuresdr::runtime::buffer::circular::Circular;
+use futuresdr::runtime::buffer::slab::Slab;
+use futuresdr::runtime::buffer::BufferBuilder;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::BlockMeta;
+use futuresdr::runtime::BlockMetaBuilder;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Kernel;
+use futuresdr::runtime::MessageIo;
+use futuresdr::runtime::MessageIoBuilder;
+use futuresdr::runtime::Runtime;
+use futuresdr::runtime::StreamIo;
+use futuresdr::runtime::StreamIoBuilder;
+use futuresdr::runtime::WorkIo;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+/// Produces a few items per call.
+struct Trickle {
+    remaining: usize,
+}
+
+impl Trickle {
+    fn build(n: usize) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("Trickle").build(),
+            StreamIoBuilder::new().add_output::<u32>("out").build(),
+            MessageIoBuilder::new().build(),
+            Trickle { remaining: n },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for Trickle {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let o = sio.output(0).slice::<u32>();
+        let n = o.len().min(self.remaining).min(10);
+        o[0..n].fill(1);
+        sio.output(0).produce(n);
+        self.remaining -= n;
+        if self.remaining == 0 {
+            io.finished = true;
+        } else {
+            io.call_again = true;
+        }
+        Ok(())
+    }
+}
+
+/// Records the number of items, available in each call to `work()`.
+struct Recorder {
+    min_items: usize,
+    multiple: usize,
+    calls: Vec<(usize, bool)>,
+    received: usize,
+}
+
+impl Recorder {
+    fn build(min_items: usize, multiple: usize) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("Recorder").build(),
+            StreamIoBuilder::new().add_input::<u32>("in").build(),
+            MessageIoBuilder::new().build(),
+            Recorder {
+                min_items,
+                multiple,
+                calls: Vec::new(),
+                received: 0,
+            },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for Recorder {
+    async fn init(
+        &mut self,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        sio.input(0).set_min_items(self.min_items);
+        sio.input(0).set_item_multiple(self.multiple);
+        Ok(())
+    }
+
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let finished = sio.input(0).finished();
+        let i = sio.input(0).slice::<u32>();
+        self.calls.push((i.len(), finished));
+        self.received += i.len();
+        sio.input(0).consume(i.len());
+        if finished {
+            io.finished = true;
+        }
+        Ok(())
+    }
+}
+
+fn run(n: usize, min_items: usize, multiple: usize) -> Result<(Vec<(usize, bool)>, usize)> {
+    run_with(n, min_items, multiple, Circular::new())
+}
+
+fn run_with<B: BufferBuilder + Debug + Eq + Hash>(
+    n: usize,
+    min_items: usize,
+    multiple: usize,
+    buffer: B,
+) -> Result<(Vec<(usize, bool)>, usize)> {
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(Trickle::build(n));
+    let snk = fg.add_block(Recorder::build(min_items, multiple));
+    fg.connect_stream_with_type(src, "out", snk, "in", buffer)?;
+    let fg = Runtime::new().run(fg)?;
+    let snk = fg.kernel::<Recorder>(snk).unwrap();
+    Ok((snk.calls.clone(), snk.received))
+}
+
+#[test]
+fn min_items() -> Result<()> {
+    let (calls, received) = run(100_000, 1000, 1)?;
+    assert_eq!(received, 100_000);
+    assert!(calls.iter().all(|(n, finished)| *n >= 1000 || *finished));
+    Ok(())
+}
+
+#[test]
+fn item_multiple() -> Result<()> {
+    let (calls, received) = run(100_010, 0, 64)?;
+    assert_eq!(received, 100_010 / 64 * 64);
+    assert!(calls.iter().all(|(n, _)| n % 64 == 0));
+    assert!(calls.iter().all(|(n, finished)| *n > 0 || *finished));
+    Ok(())
+}
+
+#[test]
+fn slab_item_multiple() -> Result<()> {
+    // slabs of 1000 items, 64 of them reserved, i.e., 936 new items per slab
+    let (calls, received) = run_with(100_010, 0, 64, Slab::with_config(4000, 2, 64))?;
+    assert_eq!(received, 100_010 / 64 * 64);
+    assert!(calls.iter().all(|(n, _)| n % 64 == 0));
+    Ok(())
+}
+
+#[test]
+fn slab_item_multiple_grows_reserve() -> Result<()> {
+    // remainders of up to 56 items do not fit into the 32 reserved items, so more are reserved
+    let (calls, received) = run_with(100_010, 0, 64, Slab::with_config(4000, 2, 32))?;
+    assert_eq!(received, 100_010 / 64 * 64);
+    assert!(calls.iter().all(|(n, _)| n % 64 == 0));
+    Ok(())
+}
+
+#[test]
+fn slab_item_multiple_too_large() {
+    assert!(run_with(10_000, 0, 4096, Slab::with_config(8000, 2, 0)).is_err());
+    assert!(run_with(10_000, 0, 64, Slab::with_config(4000, 2, 960)).is_err());
+}
+
+#[test]
+fn slab_min_items() -> Result<()> {
+    let (calls, received) = run_with(100_000, 500, 1, Slab::with_config(4000, 2, 500))?;
+    assert_eq!(received, 100_000);
+    assert!(calls.iter().all(|(n, finished)| *n >= 500 || *finished));
+
+    let (calls, received) = run_with(100_000, 400, 1, Slab::with_config(4000, 2, 100))?;
+    assert_eq!(received, 100_000);
+    assert!(calls.iter().all(|(n, finished)| *n >= 400 || *finished));
+
+    assert!(run_with(10_000, 600, 1, Slab::with_config(4000, 2, 100)).is_err());
+    Ok(())
+}
+
+#[test]
+fn circular_too_small() {
+    assert!(run_with(10_000, 0, 1 << 20, Circular::with_size(4096)).is_err());
+}